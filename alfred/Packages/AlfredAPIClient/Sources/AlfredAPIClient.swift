@@ -53,6 +53,41 @@ public final class AlfredAPIClient: Sendable {
         )
     }
 
+    public func listDevices() async throws -> ListDevicesResponse {
+        try await send(
+            method: "GET",
+            path: "/v1/devices",
+            body: Optional<EmptyBody>.none,
+            requiresAuth: true
+        )
+    }
+
+    public func deleteDevice(deviceID: String) async throws -> OkResponse {
+        guard let encodedDeviceID = deviceID.addingPercentEncoding(withAllowedCharacters: Self.pathComponentAllowedCharacters) else {
+            throw AlfredAPIClientError.invalidURL
+        }
+
+        return try await send(
+            method: "DELETE",
+            path: "/v1/devices/\(encodedDeviceID)",
+            body: Optional<EmptyBody>.none,
+            requiresAuth: true
+        )
+    }
+
+    public func enableDevice(deviceID: String) async throws -> OkResponse {
+        guard let encodedDeviceID = deviceID.addingPercentEncoding(withAllowedCharacters: Self.pathComponentAllowedCharacters) else {
+            throw AlfredAPIClientError.invalidURL
+        }
+
+        return try await send(
+            method: "POST",
+            path: "/v1/devices/\(encodedDeviceID)/enable",
+            body: Optional<EmptyBody>.none,
+            requiresAuth: true
+        )
+    }
+
     public func sendAPNSTestNotification(_ request: SendTestNotificationRequest) async throws -> SendTestNotificationResponse {
         try await send(
             method: "POST",
@@ -145,6 +180,60 @@ public final class AlfredAPIClient: Sendable {
         )
     }
 
+    public func exportAssistantSessions(_ request: AssistantSessionExportRequest) async throws -> AssistantSessionExportResponse {
+        try await send(
+            method: "POST",
+            path: "/v1/assistant/sessions/export",
+            body: request,
+            requiresAuth: true
+        )
+    }
+
+    /// Exports every assistant session and decrypts the archive on device. The server only
+    /// relays sealed state to the enclave, which encrypts the archive to a fresh client key.
+    public func exportAssistantSessionsEncrypted(
+        attestationConfig: AssistantAttestationVerificationConfig
+    ) async throws -> AssistantPlaintextSessionExport {
+        let challengeNonce = UUID().uuidString.replacingOccurrences(of: "-", with: "").lowercased()
+        let requestID = UUID().uuidString
+        let issuedAt = Int64(Date().timeIntervalSince1970)
+        let expiresAt = issuedAt + Int64(attestationConfig.challengeWindowSeconds)
+        let keyResponse = try await fetchAssistantAttestedKey(
+            AssistantAttestedKeyRequest(
+                challengeNonce: challengeNonce,
+                issuedAt: issuedAt,
+                expiresAt: expiresAt,
+                requestId: requestID
+            )
+        )
+
+        try AssistantEnvelopeCrypto.verifyAttestedKeyResponse(
+            keyResponse,
+            expectedChallengeNonce: challengeNonce,
+            expectedRequestID: requestID,
+            config: attestationConfig
+        )
+
+        let clientKey = AssistantEnvelopeCrypto.makeClientEphemeralKey()
+        let apiResponse = try await exportAssistantSessions(
+            AssistantSessionExportRequest(
+                requestId: requestID,
+                clientEphemeralPublicKey: clientKey.publicKey
+            )
+        )
+
+        guard apiResponse.envelope.requestId == requestID else {
+            throw AlfredAPIClientError.assistantDecryptionFailed(reason: "export request_id mismatch")
+        }
+
+        return try AssistantEnvelopeCrypto.decryptSessionExport(
+            envelope: apiResponse.envelope,
+            requestID: requestID,
+            clientEphemeralPrivateKey: clientKey.privateKey,
+            attestedKey: keyResponse
+        )
+    }
+
     public func startGoogleOAuth(_ request: StartGoogleConnectRequest) async throws -> StartGoogleConnectResponse {
         try await send(
             method: "POST",
@@ -163,6 +252,82 @@ public final class AlfredAPIClient: Sendable {
         )
     }
 
+    public func connectCaldav(_ request: ConnectCaldavRequest) async throws -> ConnectCaldavResponse {
+        try await send(
+            method: "POST",
+            path: "/v1/connectors/caldav",
+            body: request,
+            requiresAuth: true
+        )
+    }
+
+    public func connectCaldavEncrypted(
+        serverURL: String,
+        username: String,
+        appPassword: String,
+        attestationConfig: AssistantAttestationVerificationConfig
+    ) async throws -> ConnectCaldavResponse {
+        let encryptedEnvelope = try await encryptAutomationPromptEnvelope(
+            prompt: appPassword,
+            attestationConfig: attestationConfig
+        )
+
+        return try await connectCaldav(
+            ConnectCaldavRequest(
+                serverURL: serverURL,
+                username: username,
+                appPasswordEnvelope: encryptedEnvelope
+            )
+        )
+    }
+
+    public func connectImap(_ request: ConnectImapRequest) async throws -> ConnectImapResponse {
+        try await send(
+            method: "POST",
+            path: "/v1/connectors/imap",
+            body: request,
+            requiresAuth: true
+        )
+    }
+
+    public func connectImapEncrypted(
+        host: String,
+        port: Int? = nil,
+        username: String,
+        password: String,
+        attestationConfig: AssistantAttestationVerificationConfig
+    ) async throws -> ConnectImapResponse {
+        let encryptedEnvelope = try await encryptAutomationPromptEnvelope(
+            prompt: password,
+            attestationConfig: attestationConfig
+        )
+
+        return try await connectImap(
+            ConnectImapRequest(
+                host: host,
+                port: port,
+                username: username,
+                passwordEnvelope: encryptedEnvelope
+            )
+        )
+    }
+
+    public func upgradeConnectorScopes(
+        connectorID: String,
+        _ request: UpgradeConnectorScopesRequest
+    ) async throws -> UpgradeConnectorScopesResponse {
+        guard let encodedConnectorID = connectorID.addingPercentEncoding(withAllowedCharacters: Self.pathComponentAllowedCharacters) else {
+            throw AlfredAPIClientError.invalidURL
+        }
+
+        return try await send(
+            method: "POST",
+            path: "/v1/connectors/\(encodedConnectorID)/scopes/upgrade",
+            body: request,
+            requiresAuth: true
+        )
+    }
+
     public func revokeConnector(connectorID: String) async throws -> RevokeConnectorResponse {
         guard let encodedConnectorID = connectorID.addingPercentEncoding(withAllowedCharacters: Self.pathComponentAllowedCharacters) else {
             throw AlfredAPIClientError.invalidURL
@@ -176,6 +341,24 @@ public final class AlfredAPIClient: Sendable {
         )
     }
 
+    public func getPublicStatus() async throws -> PublicStatusResponse {
+        try await send(
+            method: "GET",
+            path: "/v1/public/status",
+            body: Optional<EmptyBody>.none,
+            requiresAuth: false
+        )
+    }
+
+    public func getStatus() async throws -> SystemStatusResponse {
+        try await send(
+            method: "GET",
+            path: "/v1/status",
+            body: Optional<EmptyBody>.none,
+            requiresAuth: true
+        )
+    }
+
     public func listConnectors() async throws -> ListConnectorsResponse {
         try await send(
             method: "GET",
@@ -185,10 +368,17 @@ public final class AlfredAPIClient: Sendable {
         )
     }
 
-    public func listAutomations(limit: Int? = nil) async throws -> ListAutomationsResponse {
-        var path = "/v1/automations"
+    public func listAutomations(limit: Int? = nil, includeArchived: Bool = false) async throws -> ListAutomationsResponse {
+        var queryItems: [String] = []
         if let limit {
-            path += "?limit=\(limit)"
+            queryItems.append("limit=\(limit)")
+        }
+        if includeArchived {
+            queryItems.append("include_archived=true")
+        }
+        var path = "/v1/automations"
+        if !queryItems.isEmpty {
+            path += "?" + queryItems.joined(separator: "&")
         }
 
         return try await send(
@@ -246,6 +436,7 @@ public final class AlfredAPIClient: Sendable {
         schedule: AutomationSchedule? = nil,
         prompt: String? = nil,
         status: AutomationStatus? = nil,
+        expectedVersion: Int64? = nil,
         attestationConfig: AssistantAttestationVerificationConfig
     ) async throws -> AutomationRuleSummary {
         let promptEnvelope: AssistantEncryptedRequestEnvelope?
@@ -269,7 +460,8 @@ public final class AlfredAPIClient: Sendable {
                 title: title,
                 schedule: schedule,
                 promptEnvelope: promptEnvelope,
-                status: status
+                status: status,
+                expectedVersion: expectedVersion
             )
         )
     }
@@ -283,6 +475,15 @@ public final class AlfredAPIClient: Sendable {
         )
     }
 
+    public func runAutomationNow(ruleID: UUID, requestID: String) async throws -> RunAutomationNowResponse {
+        try await send(
+            method: "POST",
+            path: "/v1/automations/\(ruleID.uuidString.lowercased())/run",
+            body: RunAutomationNowRequest(requestId: requestID),
+            requiresAuth: true
+        )
+    }
+
     public func triggerAutomationDebugRun(ruleID: UUID) async throws -> TriggerAutomationDebugRunResponse {
         try await send(
             method: "POST",
@@ -306,6 +507,26 @@ public final class AlfredAPIClient: Sendable {
         )
     }
 
+    public func uploadSupportDiagnostics(
+        _ request: UploadSupportDiagnosticsRequest
+    ) async throws -> UploadSupportDiagnosticsResponse {
+        try await send(
+            method: "POST",
+            path: "/v1/support/diagnostics",
+            body: request,
+            requiresAuth: true
+        )
+    }
+
+    public func deleteSupportDiagnostics(diagnosticID: UUID) async throws -> OkResponse {
+        try await send(
+            method: "DELETE",
+            path: "/v1/support/diagnostics/\(diagnosticID.uuidString.lowercased())",
+            body: Optional<EmptyBody>.none,
+            requiresAuth: true
+        )
+    }
+
     public func requestDeleteAll() async throws -> DeleteAllResponse {
         try await send(
             method: "POST",
@@ -315,6 +536,28 @@ public final class AlfredAPIClient: Sendable {
         )
     }
 
+    public func requestPrivacyExport() async throws -> PrivacyExportResponse {
+        try await send(
+            method: "POST",
+            path: "/v1/privacy/export",
+            body: Optional<EmptyBody>.none,
+            requiresAuth: true
+        )
+    }
+
+    public func getPrivacyExportStatus(requestID: String) async throws -> PrivacyExportStatusResponse {
+        guard let encodedRequestID = requestID.addingPercentEncoding(withAllowedCharacters: Self.pathComponentAllowedCharacters) else {
+            throw AlfredAPIClientError.invalidURL
+        }
+
+        return try await send(
+            method: "GET",
+            path: "/v1/privacy/export/\(encodedRequestID)",
+            body: Optional<EmptyBody>.none,
+            requiresAuth: true
+        )
+    }
+
     private func send<T: Decodable, U: Encodable>(
         method: String,
         path: String,
@@ -179,6 +179,60 @@ enum AssistantEnvelopeCrypto {
         clientEphemeralPrivateKey: Data,
         attestedKey: AssistantAttestedKeyResponse
     ) throws -> AssistantPlaintextQueryResponse {
+        let plaintext = try openEnvelope(
+            envelope: envelope,
+            requestID: requestID,
+            clientEphemeralPrivateKey: clientEphemeralPrivateKey,
+            attestedKey: attestedKey,
+            direction: "response"
+        )
+
+        do {
+            return try JSONDecoder().decode(AssistantPlaintextQueryResponse.self, from: plaintext)
+        } catch {
+            throw AlfredAPIClientError.assistantDecryptionFailed(reason: "response payload decode failed")
+        }
+    }
+
+    /// Fresh X25519 key for a response-only exchange such as a session export: base64 public
+    /// key for the request, raw private key for `decryptSessionExport`.
+    static func makeClientEphemeralKey() -> (publicKey: String, privateKey: Data) {
+        let privateKey = Curve25519.KeyAgreement.PrivateKey()
+        return (privateKey.publicKey.rawRepresentation.base64EncodedString(), privateKey.rawRepresentation)
+    }
+
+    /// Session exports use their own key direction, so an export envelope can never be
+    /// opened as a query response for the same request ID.
+    static func decryptSessionExport(
+        envelope: AssistantEncryptedResponseEnvelope,
+        requestID: String,
+        clientEphemeralPrivateKey: Data,
+        attestedKey: AssistantAttestedKeyResponse
+    ) throws -> AssistantPlaintextSessionExport {
+        let plaintext = try openEnvelope(
+            envelope: envelope,
+            requestID: requestID,
+            clientEphemeralPrivateKey: clientEphemeralPrivateKey,
+            attestedKey: attestedKey,
+            direction: "export"
+        )
+
+        let decoder = JSONDecoder()
+        decoder.dateDecodingStrategy = .iso8601
+        do {
+            return try decoder.decode(AssistantPlaintextSessionExport.self, from: plaintext)
+        } catch {
+            throw AlfredAPIClientError.assistantDecryptionFailed(reason: "export payload decode failed")
+        }
+    }
+
+    private static func openEnvelope(
+        envelope: AssistantEncryptedResponseEnvelope,
+        requestID: String,
+        clientEphemeralPrivateKey: Data,
+        attestedKey: AssistantAttestedKeyResponse,
+        direction: String
+    ) throws -> Data {
         guard envelope.version == versionV1 else {
             throw AlfredAPIClientError.assistantDecryptionFailed(reason: "unsupported envelope version")
         }
@@ -226,7 +280,7 @@ enum AssistantEnvelopeCrypto {
         let symmetricKey = deriveDirectionalSymmetricKey(
             sharedSecret: sharedSecret,
             requestID: requestID,
-            direction: "response"
+            direction: direction
         )
 
         let nonce: ChaChaPoly.Nonce
@@ -252,9 +306,8 @@ enum AssistantEnvelopeCrypto {
             throw AlfredAPIClientError.assistantDecryptionFailed(reason: "sealed box parse failed")
         }
 
-        let plaintext: Data
         do {
-            plaintext = try ChaChaPoly.open(
+            return try ChaChaPoly.open(
                 sealedBox,
                 using: symmetricKey,
                 authenticating: Data(requestID.utf8)
@@ -262,12 +315,6 @@ enum AssistantEnvelopeCrypto {
         } catch {
             throw AlfredAPIClientError.assistantDecryptionFailed(reason: "response decryption failed")
         }
-
-        do {
-            return try JSONDecoder().decode(AssistantPlaintextQueryResponse.self, from: plaintext)
-        } catch {
-            throw AlfredAPIClientError.assistantDecryptionFailed(reason: "response payload decode failed")
-        }
     }
 
     private static func deriveDirectionalSymmetricKey(
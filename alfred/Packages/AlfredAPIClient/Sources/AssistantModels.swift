@@ -56,11 +56,36 @@ public struct AssistantEncryptedResponseEnvelope: Codable, Sendable {
 public struct AssistantQueryResponse: Codable, Sendable {
     public let sessionId: UUID
     public let envelope: AssistantEncryptedResponseEnvelope
+    public let degradedReasons: [AssistantDegradedReason]
 
     enum CodingKeys: String, CodingKey {
         case sessionId = "session_id"
         case envelope
+        case degradedReasons = "degraded_reasons"
     }
+
+    public init(
+        sessionId: UUID,
+        envelope: AssistantEncryptedResponseEnvelope,
+        degradedReasons: [AssistantDegradedReason] = []
+    ) {
+        self.sessionId = sessionId
+        self.envelope = envelope
+        self.degradedReasons = degradedReasons
+    }
+
+    public init(from decoder: Decoder) throws {
+        let container = try decoder.container(keyedBy: CodingKeys.self)
+        sessionId = try container.decode(UUID.self, forKey: .sessionId)
+        envelope = try container.decode(AssistantEncryptedResponseEnvelope.self, forKey: .envelope)
+        degradedReasons =
+            try container.decodeIfPresent([AssistantDegradedReason].self, forKey: .degradedReasons) ?? []
+    }
+}
+
+public enum AssistantDegradedReason: String, Codable, Sendable, Equatable {
+    case plannerFallback = "planner_fallback"
+    case responseFallback = "response_fallback"
 }
 
 public struct AssistantPlaintextQueryRequest: Codable, Sendable {
@@ -84,6 +109,8 @@ public enum AssistantQueryCapability: String, Codable, Sendable, Equatable {
     case emailLookup = "email_lookup"
     case generalChat = "general_chat"
     case mixed = "mixed"
+    case focusTime = "focus_time"
+    case emailCleanup = "email_cleanup"
 }
 
 public enum AssistantResponsePartType: String, Codable, Sendable, Equatable {
@@ -96,24 +123,58 @@ public struct AssistantResponsePart: Codable, Sendable, Equatable {
     public let text: String?
     public let capability: AssistantQueryCapability?
     public let payload: AssistantStructuredPayload?
+    public let focusBlocks: [AssistantFocusBlockProposal]?
+    public let senderFilters: [AssistantSenderFilterSuggestion]?
 
     enum CodingKeys: String, CodingKey {
         case type
         case text
         case capability
         case payload
+        case focusBlocks = "focus_blocks"
+        case senderFilters = "sender_filters"
     }
 
     public init(
         type: AssistantResponsePartType,
         text: String? = nil,
         capability: AssistantQueryCapability? = nil,
-        payload: AssistantStructuredPayload? = nil
+        payload: AssistantStructuredPayload? = nil,
+        focusBlocks: [AssistantFocusBlockProposal]? = nil,
+        senderFilters: [AssistantSenderFilterSuggestion]? = nil
     ) {
         self.type = type
         self.text = text
         self.capability = capability
         self.payload = payload
+        self.focusBlocks = focusBlocks
+        self.senderFilters = senderFilters
+    }
+}
+
+public struct AssistantFocusBlockProposal: Codable, Sendable, Equatable {
+    public let title: String
+    public let startAt: String
+    public let endAt: String
+    public let durationMinutes: Int
+
+    enum CodingKeys: String, CodingKey {
+        case title
+        case startAt = "start_at"
+        case endAt = "end_at"
+        case durationMinutes = "duration_minutes"
+    }
+}
+
+public struct AssistantSenderFilterSuggestion: Codable, Sendable, Equatable {
+    public let sender: String
+    public let unreadCount: Int
+    public let categories: [String]
+
+    enum CodingKeys: String, CodingKey {
+        case sender
+        case unreadCount = "unread_count"
+        case categories
     }
 }
 
@@ -262,7 +323,9 @@ public struct AssistantPlaintextQueryResponse: Codable, Sendable {
                     type: .toolSummary,
                     text: part.text,
                     capability: capability,
-                    payload: part.payload
+                    payload: part.payload,
+                    focusBlocks: part.focusBlocks,
+                    senderFilters: part.senderFilters
                 )
             }
             return part
@@ -355,3 +418,96 @@ public struct AssistantAttestedKeyResponse: Codable, Sendable {
         case attestation
     }
 }
+
+public struct AssistantSessionExportRequest: Codable, Sendable {
+    public let requestId: String
+    public let clientEphemeralPublicKey: String
+
+    enum CodingKeys: String, CodingKey {
+        case requestId = "request_id"
+        case clientEphemeralPublicKey = "client_ephemeral_public_key"
+    }
+
+    public init(requestId: String, clientEphemeralPublicKey: String) {
+        self.requestId = requestId
+        self.clientEphemeralPublicKey = clientEphemeralPublicKey
+    }
+}
+
+public struct AssistantSessionSummary: Codable, Sendable, Equatable {
+    public let sessionId: UUID
+    public let createdAt: Date
+    public let updatedAt: Date
+    public let expiresAt: Date
+
+    enum CodingKeys: String, CodingKey {
+        case sessionId = "session_id"
+        case createdAt = "created_at"
+        case updatedAt = "updated_at"
+        case expiresAt = "expires_at"
+    }
+}
+
+public struct AssistantSessionExportResponse: Codable, Sendable {
+    public let requestId: String
+    public let exportedAt: Date
+    public let sessions: [AssistantSessionSummary]
+    public let skippedSessionIds: [UUID]
+    public let envelope: AssistantEncryptedResponseEnvelope
+
+    enum CodingKeys: String, CodingKey {
+        case requestId = "request_id"
+        case exportedAt = "exported_at"
+        case sessions
+        case skippedSessionIds = "skipped_session_ids"
+        case envelope
+    }
+}
+
+public struct AssistantSessionTurn: Codable, Sendable, Equatable {
+    public let userQuerySnippet: String
+    public let assistantSummarySnippet: String
+    public let capability: AssistantQueryCapability
+    public let createdAt: Date
+
+    enum CodingKeys: String, CodingKey {
+        case userQuerySnippet = "user_query_snippet"
+        case assistantSummarySnippet = "assistant_summary_snippet"
+        case capability
+        case createdAt = "created_at"
+    }
+}
+
+public struct AssistantSessionMemory: Codable, Sendable, Equatable {
+    public let version: String
+    public let turns: [AssistantSessionTurn]
+}
+
+public struct AssistantPlaintextExportedSession: Codable, Sendable, Equatable {
+    public let sessionId: UUID
+    public let createdAt: Date
+    public let updatedAt: Date
+    public let lastCapability: AssistantQueryCapability
+    public let memory: AssistantSessionMemory
+
+    enum CodingKeys: String, CodingKey {
+        case sessionId = "session_id"
+        case createdAt = "created_at"
+        case updatedAt = "updated_at"
+        case lastCapability = "last_capability"
+        case memory
+    }
+}
+
+/// Decrypted contents of an assistant session export.
+public struct AssistantPlaintextSessionExport: Codable, Sendable, Equatable {
+    public let formatVersion: Int
+    public let exportedAt: Date
+    public let sessions: [AssistantPlaintextExportedSession]
+
+    enum CodingKeys: String, CodingKey {
+        case formatVersion = "format_version"
+        case exportedAt = "exported_at"
+        case sessions
+    }
+}
@@ -9,6 +9,7 @@ public struct RegisterDeviceRequest: Codable, Sendable {
     public let deviceId: String
     public let apnsToken: String
     public let environment: APNSEnvironment
+    public let appBundleId: String?
     public let notificationKeyAlgorithm: String?
     public let notificationPublicKey: String?
 
@@ -16,6 +17,7 @@ public struct RegisterDeviceRequest: Codable, Sendable {
         case deviceId = "device_id"
         case apnsToken = "apns_token"
         case environment
+        case appBundleId = "app_bundle_id"
         case notificationKeyAlgorithm = "notification_key_algorithm"
         case notificationPublicKey = "notification_public_key"
     }
@@ -24,17 +26,47 @@ public struct RegisterDeviceRequest: Codable, Sendable {
         deviceId: String,
         apnsToken: String,
         environment: APNSEnvironment,
+        appBundleId: String? = nil,
         notificationKeyAlgorithm: String? = nil,
         notificationPublicKey: String? = nil
     ) {
         self.deviceId = deviceId
         self.apnsToken = apnsToken
         self.environment = environment
+        self.appBundleId = appBundleId
         self.notificationKeyAlgorithm = notificationKeyAlgorithm
         self.notificationPublicKey = notificationPublicKey
     }
 }
 
+public struct DeviceSummary: Codable, Sendable {
+    public let deviceId: String
+    public let environment: APNSEnvironment
+    public let appBundleId: String?
+    public let createdAt: Date
+    public let updatedAt: Date
+    public let lastDeliveryAt: Date?
+    public let lastSeenAt: Date
+    public let disabledAt: Date?
+    public let disabledReason: String?
+
+    enum CodingKeys: String, CodingKey {
+        case deviceId = "device_id"
+        case environment
+        case appBundleId = "app_bundle_id"
+        case createdAt = "created_at"
+        case updatedAt = "updated_at"
+        case lastDeliveryAt = "last_delivery_at"
+        case lastSeenAt = "last_seen_at"
+        case disabledAt = "disabled_at"
+        case disabledReason = "disabled_reason"
+    }
+}
+
+public struct ListDevicesResponse: Codable, Sendable {
+    public let items: [DeviceSummary]
+}
+
 public struct SendTestNotificationRequest: Codable, Sendable {
     public let title: String?
     public let body: String?
@@ -77,6 +109,33 @@ public struct StartGoogleConnectResponse: Codable, Sendable {
     }
 }
 
+public struct UpgradeConnectorScopesRequest: Codable, Sendable {
+    public let redirectURI: String
+    public let scopes: [String]
+
+    enum CodingKeys: String, CodingKey {
+        case redirectURI = "redirect_uri"
+        case scopes
+    }
+
+    public init(redirectURI: String, scopes: [String]) {
+        self.redirectURI = redirectURI
+        self.scopes = scopes
+    }
+}
+
+public struct UpgradeConnectorScopesResponse: Codable, Sendable {
+    public let authURL: String
+    public let state: String
+    public let requestedScopes: [String]
+
+    enum CodingKeys: String, CodingKey {
+        case authURL = "auth_url"
+        case state
+        case requestedScopes = "requested_scopes"
+    }
+}
+
 public struct CompleteGoogleConnectRequest: Codable, Sendable {
     public let code: String?
     public let state: String
@@ -115,6 +174,65 @@ public struct CompleteGoogleConnectResponse: Codable, Sendable {
     }
 }
 
+public struct ConnectCaldavRequest: Codable, Sendable {
+    public let serverURL: String
+    public let username: String
+    public let appPasswordEnvelope: AssistantEncryptedRequestEnvelope
+
+    enum CodingKeys: String, CodingKey {
+        case serverURL = "server_url"
+        case username
+        case appPasswordEnvelope = "app_password_envelope"
+    }
+
+    public init(serverURL: String, username: String, appPasswordEnvelope: AssistantEncryptedRequestEnvelope) {
+        self.serverURL = serverURL
+        self.username = username
+        self.appPasswordEnvelope = appPasswordEnvelope
+    }
+}
+
+public struct ConnectCaldavResponse: Codable, Sendable {
+    public let connectorId: String
+    public let status: ConnectorStatus
+
+    enum CodingKeys: String, CodingKey {
+        case connectorId = "connector_id"
+        case status
+    }
+}
+
+public struct ConnectImapRequest: Codable, Sendable {
+    public let host: String
+    public let port: Int?
+    public let username: String
+    public let passwordEnvelope: AssistantEncryptedRequestEnvelope
+
+    enum CodingKeys: String, CodingKey {
+        case host
+        case port
+        case username
+        case passwordEnvelope = "password_envelope"
+    }
+
+    public init(host: String, port: Int? = nil, username: String, passwordEnvelope: AssistantEncryptedRequestEnvelope) {
+        self.host = host
+        self.port = port
+        self.username = username
+        self.passwordEnvelope = passwordEnvelope
+    }
+}
+
+public struct ConnectImapResponse: Codable, Sendable {
+    public let connectorId: String
+    public let status: ConnectorStatus
+
+    enum CodingKeys: String, CodingKey {
+        case connectorId = "connector_id"
+        case status
+    }
+}
+
 public struct RevokeConnectorResponse: Codable, Sendable {
     public let status: ConnectorStatus
 }
@@ -123,11 +241,19 @@ public struct ConnectorSummary: Codable, Sendable {
     public let connectorId: String
     public let provider: String
     public let status: ConnectorStatus
+    public let scopes: [String]
+    public let tokenRotatedAt: Date
+    public let createdAt: Date
+    public let healthScore: Int
 
     enum CodingKeys: String, CodingKey {
         case connectorId = "connector_id"
         case provider
         case status
+        case scopes
+        case tokenRotatedAt = "token_rotated_at"
+        case createdAt = "created_at"
+        case healthScore = "health_score"
     }
 }
 
@@ -178,6 +304,7 @@ public struct AutomationSchedule: Codable, Sendable {
 public enum AutomationStatus: String, Codable, Sendable {
     case active = "ACTIVE"
     case paused = "PAUSED"
+    case archived = "ARCHIVED"
 }
 
 public struct UpdateAutomationRequest: Codable, Sendable {
@@ -185,24 +312,28 @@ public struct UpdateAutomationRequest: Codable, Sendable {
     public let schedule: AutomationSchedule?
     public let promptEnvelope: AssistantEncryptedRequestEnvelope?
     public let status: AutomationStatus?
+    public let expectedVersion: Int64?
 
     enum CodingKeys: String, CodingKey {
         case title
         case schedule
         case promptEnvelope = "prompt_envelope"
         case status
+        case expectedVersion = "expected_version"
     }
 
     public init(
         title: String? = nil,
         schedule: AutomationSchedule? = nil,
         promptEnvelope: AssistantEncryptedRequestEnvelope? = nil,
-        status: AutomationStatus? = nil
+        status: AutomationStatus? = nil,
+        expectedVersion: Int64? = nil
     ) {
         self.title = title
         self.schedule = schedule
         self.promptEnvelope = promptEnvelope
         self.status = status
+        self.expectedVersion = expectedVersion
     }
 }
 
@@ -221,6 +352,7 @@ public struct AutomationRuleSummary: Codable, Sendable {
     public let nextRunAt: Date
     public let lastRunAt: Date?
     public let promptSha256: String
+    public let version: Int64
     public let createdAt: Date
     public let updatedAt: Date
 
@@ -232,6 +364,7 @@ public struct AutomationRuleSummary: Codable, Sendable {
         case nextRunAt = "next_run_at"
         case lastRunAt = "last_run_at"
         case promptSha256 = "prompt_sha256"
+        case version
         case createdAt = "created_at"
         case updatedAt = "updated_at"
     }
@@ -239,6 +372,12 @@ public struct AutomationRuleSummary: Codable, Sendable {
 
 public struct ListAutomationsResponse: Codable, Sendable {
     public let items: [AutomationRuleSummary]
+    public let nextCursor: String?
+
+    enum CodingKeys: String, CodingKey {
+        case items
+        case nextCursor = "next_cursor"
+    }
 }
 
 public struct TriggerAutomationDebugRunResponse: Codable, Sendable {
@@ -251,6 +390,32 @@ public struct TriggerAutomationDebugRunResponse: Codable, Sendable {
     }
 }
 
+public struct RunAutomationNowRequest: Codable, Sendable {
+    public let requestId: String
+
+    public init(requestId: String) {
+        self.requestId = requestId
+    }
+
+    enum CodingKeys: String, CodingKey {
+        case requestId = "request_id"
+    }
+}
+
+public struct RunAutomationNowResponse: Codable, Sendable {
+    public let queuedJobId: String
+    public let status: String
+    public let replayed: Bool
+    public let runsRemainingToday: Int
+
+    enum CodingKeys: String, CodingKey {
+        case queuedJobId = "queued_job_id"
+        case status
+        case replayed
+        case runsRemainingToday = "runs_remaining_today"
+    }
+}
+
 public struct AuditEvent: Codable, Sendable {
     public let id: String
     public let timestamp: Date
@@ -279,6 +444,43 @@ public struct ListAuditEventsResponse: Codable, Sendable {
     }
 }
 
+public struct UploadSupportDiagnosticsRequest: Codable, Sendable {
+    public let ticketReference: String?
+    public let algorithm: String
+    public let keyId: String
+    public let ciphertext: String
+
+    enum CodingKeys: String, CodingKey {
+        case ticketReference = "ticket_reference"
+        case algorithm
+        case keyId = "key_id"
+        case ciphertext
+    }
+
+    public init(ticketReference: String? = nil, algorithm: String, keyId: String, ciphertext: String) {
+        self.ticketReference = ticketReference
+        self.algorithm = algorithm
+        self.keyId = keyId
+        self.ciphertext = ciphertext
+    }
+}
+
+public struct UploadSupportDiagnosticsResponse: Codable, Sendable {
+    public let diagnosticId: String
+    public let ticketReference: String
+    public let sizeBytes: Int
+    public let createdAt: Date
+    public let expiresAt: Date
+
+    enum CodingKeys: String, CodingKey {
+        case diagnosticId = "diagnostic_id"
+        case ticketReference = "ticket_reference"
+        case sizeBytes = "size_bytes"
+        case createdAt = "created_at"
+        case expiresAt = "expires_at"
+    }
+}
+
 public struct DeleteAllResponse: Codable, Sendable {
     public let requestId: String
     public let status: String
@@ -289,10 +491,145 @@ public struct DeleteAllResponse: Codable, Sendable {
     }
 }
 
+public struct PrivacyExportResponse: Codable, Sendable {
+    public let requestId: String
+    public let status: String
+
+    enum CodingKeys: String, CodingKey {
+        case requestId = "request_id"
+        case status
+    }
+}
+
+public struct PrivacyExportStatusResponse: Codable, Sendable {
+    public let requestId: String
+    public let status: String
+    public let createdAt: Date
+    public let startedAt: Date?
+    public let completedAt: Date?
+    public let failedAt: Date?
+    public let downloadedAt: Date?
+    public let expiresAt: Date?
+    public let archiveSizeBytes: Int?
+    public let downloadUrl: String?
+    public let downloadToken: String?
+    public let downloadUrlExpiresAt: Date?
+
+    enum CodingKeys: String, CodingKey {
+        case requestId = "request_id"
+        case status
+        case createdAt = "created_at"
+        case startedAt = "started_at"
+        case completedAt = "completed_at"
+        case failedAt = "failed_at"
+        case downloadedAt = "downloaded_at"
+        case expiresAt = "expires_at"
+        case archiveSizeBytes = "archive_size_bytes"
+        case downloadUrl = "download_url"
+        case downloadToken = "download_token"
+        case downloadUrlExpiresAt = "download_url_expires_at"
+    }
+}
+
 public struct OkResponse: Codable, Sendable {
     public let ok: Bool
 }
 
+public enum ComponentStatus: String, Codable, Sendable, Equatable {
+    case operational
+    case degraded
+    case unavailable
+}
+
+public enum StatusComponent: String, Codable, Sendable, Equatable {
+    case database
+    case enclave
+    case assistant
+}
+
+public struct ComponentStatusEntry: Codable, Sendable {
+    public let component: StatusComponent
+    public let status: ComponentStatus
+    public let reason: String?
+}
+
+public enum AvailabilityComponent: String, Codable, Sendable, Equatable {
+    case pushDelivery = "push_delivery"
+    case assistant
+    case connectors
+    case automations
+}
+
+public struct DailyAvailability: Codable, Sendable {
+    public let date: String
+    public let attempts: Int
+    public let availability: Double?
+}
+
+public struct PublicComponentStatus: Codable, Sendable {
+    public let component: AvailabilityComponent
+    public let status: ComponentStatus
+    public let message: String?
+    public let availability24h: Double?
+    public let history: [DailyAvailability]
+
+    enum CodingKeys: String, CodingKey {
+        case component
+        case status
+        case message
+        case availability24h = "availability_24h"
+        case history
+    }
+}
+
+public struct PublicSloStatus: Codable, Sendable {
+    public let objectivePercent: Int
+    public let targetSeconds: Int
+    public let windowMinutes: Int
+    public let compliance: Double?
+    public let breached: Bool
+    public let breachedSince: Date?
+    public let evaluatedAt: Date
+
+    enum CodingKeys: String, CodingKey {
+        case objectivePercent = "objective_percent"
+        case targetSeconds = "target_seconds"
+        case windowMinutes = "window_minutes"
+        case compliance
+        case breached
+        case breachedSince = "breached_since"
+        case evaluatedAt = "evaluated_at"
+    }
+}
+
+public struct PublicStatusResponse: Codable, Sendable {
+    public let status: ComponentStatus
+    public let components: [PublicComponentStatus]
+    public let pushDeliverySlo: PublicSloStatus?
+    public let generatedAt: Date
+
+    enum CodingKeys: String, CodingKey {
+        case status
+        case components
+        case pushDeliverySlo = "push_delivery_slo"
+        case generatedAt = "generated_at"
+    }
+}
+
+public struct SystemStatusResponse: Codable, Sendable {
+    public let status: ComponentStatus
+    public let components: [ComponentStatusEntry]
+    public let windowMinutes: Int
+    public let checkedAt: Date
+
+    enum CodingKeys: String, CodingKey {
+        case status
+        case components
+        case windowMinutes = "window_minutes"
+        case checkedAt = "checked_at"
+    }
+}
+
 public struct APIErrorEnvelope: Codable, Sendable {
     public let error: APIErrorBody
 }
@@ -302,14 +639,21 @@ public struct APIErrorBody: Codable, Sendable {
     public let message: String
 }
 
-public enum StringOrNumberOrBool: Codable, Sendable {
+public indirect enum StringOrNumberOrBool: Codable, Sendable {
     case string(String)
     case int(Int)
     case double(Double)
     case bool(Bool)
+    case array([StringOrNumberOrBool])
+    case object([String: StringOrNumberOrBool])
+    case null
 
     public init(from decoder: Decoder) throws {
         let container = try decoder.singleValueContainer()
+        if container.decodeNil() {
+            self = .null
+            return
+        }
         if let value = try? container.decode(String.self) {
             self = .string(value)
             return
@@ -326,6 +670,14 @@ public enum StringOrNumberOrBool: Codable, Sendable {
             self = .bool(value)
             return
         }
+        if let value = try? container.decode([StringOrNumberOrBool].self) {
+            self = .array(value)
+            return
+        }
+        if let value = try? container.decode([String: StringOrNumberOrBool].self) {
+            self = .object(value)
+            return
+        }
         throw DecodingError.typeMismatch(
             StringOrNumberOrBool.self,
             DecodingError.Context(codingPath: decoder.codingPath, debugDescription: "Unsupported metadata value")
@@ -343,6 +695,12 @@ public enum StringOrNumberOrBool: Codable, Sendable {
             try container.encode(value)
         case .bool(let value):
             try container.encode(value)
+        case .array(let value):
+            try container.encode(value)
+        case .object(let value):
+            try container.encode(value)
+        case .null:
+            try container.encodeNil()
         }
     }
 }
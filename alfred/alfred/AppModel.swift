@@ -522,6 +522,7 @@ final class AppModel: ObservableObject {
                     deviceId: registrationMaterial.deviceID,
                     apnsToken: token,
                     environment: environment,
+                    appBundleId: Bundle.main.bundleIdentifier,
                     notificationKeyAlgorithm: registrationMaterial.algorithm,
                     notificationPublicKey: registrationMaterial.publicKey
                 )
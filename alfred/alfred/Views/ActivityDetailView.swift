@@ -74,6 +74,15 @@ extension StringOrNumberOrBool {
             return String(format: "%.2f", value)
         case .bool(let value):
             return value ? "true" : "false"
+        case .array(let values):
+            return values.map(\.displayValue).joined(separator: ", ")
+        case .object(let values):
+            return values
+                .sorted(by: { $0.key < $1.key })
+                .map { "\($0.key): \($0.value.displayValue)" }
+                .joined(separator: ", ")
+        case .null:
+            return "—"
         }
     }
 }
@@ -277,6 +277,10 @@ private struct AssistantToolSummaryCard: View {
             return "Chat"
         case .mixed:
             return "Mixed"
+        case .focusTime:
+            return "Focus Time"
+        case .emailCleanup:
+            return "Inbox Cleanup"
         }
     }
 }
@@ -68,6 +68,7 @@ final class AutomationAPIModelCodableTests: XCTestCase {
               "next_run_at": "2026-02-21T12:00:00Z",
               "last_run_at": null,
               "prompt_sha256": "abc123",
+              "version": 1,
               "created_at": "2026-02-21T10:00:00Z",
               "updated_at": "2026-02-21T11:00:00Z"
             }
@@ -67,6 +67,7 @@ final class AutomationRuleStoreTests: XCTestCase {
           "next_run_at": "2026-02-21T13:00:00Z",
           "last_run_at": null,
           "prompt_sha256": "\(String(repeating: "a", count: 64))",
+          "version": 1,
           "created_at": "\(nowISO)",
           "updated_at": "\(nowISO)"
         }
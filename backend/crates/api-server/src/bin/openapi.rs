@@ -0,0 +1,63 @@
+//! Prints the OpenAPI document generated from the api-server handlers, or checks it against
+//! the checked-in contract:
+//!
+//! ```text
+//! cargo run -p api-server --bin openapi -- print
+//! cargo run -p api-server --bin openapi -- check ../api/openapi.yaml
+//! ```
+
+use api_server::http::{contract_drift, openapi_document};
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+    match args.as_slice() {
+        ["print"] => print_document(),
+        ["check", path] => check_contract(path),
+        _ => {
+            print_usage();
+            std::process::exit(2);
+        }
+    }
+}
+
+fn print_document() {
+    match serde_json::to_string_pretty(&openapi_document()) {
+        Ok(document) => println!("{document}"),
+        Err(err) => {
+            eprintln!("failed to render openapi document: {err}");
+            std::process::exit(2);
+        }
+    }
+}
+
+fn check_contract(path: &str) {
+    let checked_in = match std::fs::read_to_string(path)
+        .map_err(|err| err.to_string())
+        .and_then(|contents| serde_yaml::from_str(&contents).map_err(|err| err.to_string()))
+    {
+        Ok(checked_in) => checked_in,
+        Err(err) => {
+            eprintln!("failed to read {path}: {err}");
+            std::process::exit(2);
+        }
+    };
+
+    let drift = contract_drift(&openapi_document(), &checked_in);
+    if drift.is_empty() {
+        println!("{path} matches the api-server handlers");
+        return;
+    }
+
+    eprintln!("{path} drifted from the api-server handlers:");
+    for entry in &drift {
+        eprintln!("  - {entry}");
+    }
+    std::process::exit(1);
+}
+
+fn print_usage() {
+    eprintln!("Usage:");
+    eprintln!("  openapi print");
+    eprintln!("  openapi check <path-to-openapi.yaml>");
+}
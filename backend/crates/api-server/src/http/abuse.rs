@@ -0,0 +1,212 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Client behaviours that burn enclave LLM budget without a plausible user need.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(super) enum AbuseSignal {
+    AutomationCreateSpike,
+    MaxSizePromptEnvelope,
+    PauseResumeLoop,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct AbuseSignalPolicy {
+    threshold: usize,
+    window_seconds: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct AbuseEscalation {
+    pub(super) signal: AbuseSignal,
+    pub(super) observed: usize,
+    pub(super) blocked_for_seconds: u64,
+}
+
+/// How long a subject stays blocked from automation mutations once a signal trips.
+const ESCALATION_SECONDS: u64 = 15 * 60;
+
+impl AbuseSignal {
+    pub(super) fn key_name(self) -> &'static str {
+        match self {
+            Self::AutomationCreateSpike => "automation_create_spike",
+            Self::MaxSizePromptEnvelope => "max_size_prompt_envelope",
+            Self::PauseResumeLoop => "pause_resume_loop",
+        }
+    }
+
+    fn policy(self) -> AbuseSignalPolicy {
+        match self {
+            Self::AutomationCreateSpike => AbuseSignalPolicy {
+                threshold: 10,
+                window_seconds: 600,
+            },
+            Self::MaxSizePromptEnvelope => AbuseSignalPolicy {
+                threshold: 5,
+                window_seconds: 3600,
+            },
+            Self::PauseResumeLoop => AbuseSignalPolicy {
+                threshold: 6,
+                window_seconds: 600,
+            },
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub(super) struct AbuseTracker {
+    state: Arc<Mutex<AbuseState>>,
+}
+
+#[derive(Default)]
+struct AbuseState {
+    signals: HashMap<(AbuseSignal, String), VecDeque<Instant>>,
+    escalations: HashMap<String, Instant>,
+}
+
+impl AbuseTracker {
+    /// Records one occurrence and returns an escalation when the signal crosses its
+    /// threshold for a subject that is not already escalated.
+    pub(super) fn record_at(
+        &self,
+        signal: AbuseSignal,
+        subject: &str,
+        now: Instant,
+    ) -> Option<AbuseEscalation> {
+        let policy = signal.policy();
+        let cutoff = now
+            .checked_sub(Duration::from_secs(policy.window_seconds))
+            .unwrap_or(now);
+        let mut state = self
+            .state
+            .lock()
+            .expect("abuse tracker mutex should not be poisoned");
+
+        let events = state
+            .signals
+            .entry((signal, subject.to_string()))
+            .or_default();
+        while events.front().is_some_and(|seen| *seen <= cutoff) {
+            events.pop_front();
+        }
+        events.push_back(now);
+        let observed = events.len();
+        if observed < policy.threshold {
+            return None;
+        }
+        events.clear();
+
+        if state
+            .escalations
+            .get(subject)
+            .is_some_and(|until| *until > now)
+        {
+            return None;
+        }
+        state.escalations.insert(
+            subject.to_string(),
+            now + Duration::from_secs(ESCALATION_SECONDS),
+        );
+
+        Some(AbuseEscalation {
+            signal,
+            observed,
+            blocked_for_seconds: ESCALATION_SECONDS,
+        })
+    }
+
+    /// Seconds left on an active escalation for the subject, if any.
+    pub(super) fn blocked_for_at(&self, subject: &str, now: Instant) -> Option<u64> {
+        let state = self
+            .state
+            .lock()
+            .expect("abuse tracker mutex should not be poisoned");
+        state
+            .escalations
+            .get(subject)
+            .filter(|until| **until > now)
+            .map(|until| until.saturating_duration_since(now).as_secs().max(1))
+    }
+
+    pub(super) fn prune(&self, now: Instant, max_window: Duration) {
+        let cutoff = now.checked_sub(max_window).unwrap_or(now);
+        let mut state = self
+            .state
+            .lock()
+            .expect("abuse tracker prune mutex should not be poisoned");
+        state.signals.retain(|_, events| {
+            while events.front().is_some_and(|seen| *seen <= cutoff) {
+                events.pop_front();
+            }
+            !events.is_empty()
+        });
+        state.escalations.retain(|_, until| *until > now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escalates_once_threshold_is_crossed_within_window() {
+        let tracker = AbuseTracker::default();
+        let start = Instant::now();
+
+        for _ in 0..9 {
+            assert_eq!(
+                tracker.record_at(AbuseSignal::AutomationCreateSpike, "user:a", start),
+                None
+            );
+        }
+        let escalation = tracker
+            .record_at(AbuseSignal::AutomationCreateSpike, "user:a", start)
+            .expect("tenth creation should escalate");
+        assert_eq!(escalation.observed, 10);
+        assert_eq!(
+            tracker.blocked_for_at("user:a", start),
+            Some(ESCALATION_SECONDS)
+        );
+        assert_eq!(tracker.blocked_for_at("user:b", start), None);
+
+        let after_block = start + Duration::from_secs(ESCALATION_SECONDS + 1);
+        assert_eq!(tracker.blocked_for_at("user:a", after_block), None);
+    }
+
+    #[test]
+    fn events_outside_window_do_not_count() {
+        let tracker = AbuseTracker::default();
+        let start = Instant::now();
+
+        for _ in 0..5 {
+            tracker.record_at(AbuseSignal::PauseResumeLoop, "user:a", start);
+        }
+        let later = start + Duration::from_secs(601);
+        assert_eq!(
+            tracker.record_at(AbuseSignal::PauseResumeLoop, "user:a", later),
+            None
+        );
+        assert_eq!(tracker.blocked_for_at("user:a", later), None);
+    }
+
+    #[test]
+    fn prune_drops_expired_signals_and_escalations() {
+        let tracker = AbuseTracker::default();
+        let start = Instant::now();
+        for _ in 0..5 {
+            tracker.record_at(AbuseSignal::MaxSizePromptEnvelope, "user:a", start);
+        }
+
+        tracker.prune(
+            start + Duration::from_secs(ESCALATION_SECONDS + 3601),
+            Duration::from_secs(3600),
+        );
+
+        let state = tracker
+            .state
+            .lock()
+            .expect("test mutex should not be poisoned");
+        assert!(state.signals.is_empty());
+        assert!(state.escalations.is_empty());
+    }
+}
@@ -0,0 +1,357 @@
+use axum::extract::{Request, State};
+use axum::http::header;
+use axum::middleware::Next;
+use axum::response::Response;
+use shared::config::AdminClerkOrgConfig;
+use shared::enclave::constant_time_eq;
+use shared::events::DomainEvent;
+use shared::models::{ApiErrorCode, AuditMetadata};
+use tracing::warn;
+use uuid::Uuid;
+
+use super::AppState;
+use super::clerk_identity::{ClerkIdentityError, ClerkOrganization, verify_identity_token};
+use super::errors::{
+    error_response, event_publish_error_response, store_error_response, unauthorized_response,
+};
+
+mod audit_chain;
+mod connectors;
+mod feature_flags;
+mod jobs;
+mod organizations;
+mod rate_limits;
+
+pub(super) use audit_chain::{VERIFY_AUDIT_CHAIN, verify_audit_chain};
+pub(super) use connectors::{
+    FORCE_CONNECTOR_HEALTH_CHECK, LIST_CONNECTOR_HEALTH, force_connector_health_check,
+    list_connector_health,
+};
+pub(super) use feature_flags::{
+    CLEAR_FEATURE_FLAG, LIST_FEATURE_FLAGS, SET_FEATURE_FLAG, clear_feature_flag,
+    list_feature_flags, set_feature_flag,
+};
+pub(super) use jobs::{
+    GET_JOB_QUEUE_DEPTH, LIST_DEAD_LETTER_JOBS, REPLAY_DEAD_LETTER_JOB, get_job_queue_depth,
+    list_dead_letter_jobs, replay_dead_letter_job,
+};
+pub(super) use organizations::{
+    LIST_ORGANIZATION_MEMBERS, SET_ORGANIZATION_PLAN, list_organization_members,
+    set_organization_plan,
+};
+pub(super) use rate_limits::{GET_RATE_LIMIT_STATE, get_rate_limit_state};
+
+/// The credential behind an admin request. Every per-user admin route records it on the
+/// audit event it writes to the target user's trail.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum AdminPrincipal {
+    ServiceToken,
+    ClerkOrgMember {
+        org_id: String,
+        subject: String,
+    },
+    /// Admin of a customer organization. Limited to read-only routes about members of
+    /// `org_id`.
+    OrgAdmin {
+        org_id: String,
+        subject: String,
+    },
+}
+
+impl AdminPrincipal {
+    fn audit_label(&self) -> String {
+        match self {
+            Self::ServiceToken => "service_token".to_string(),
+            Self::ClerkOrgMember { org_id, subject } => format!("clerk:{org_id}:{subject}"),
+            Self::OrgAdmin { org_id, subject } => format!("org_admin:{org_id}:{subject}"),
+        }
+    }
+
+    /// The organization the principal is confined to; operators see every user.
+    fn organization_scope(&self) -> Option<&str> {
+        match self {
+            Self::OrgAdmin { org_id, .. } => Some(org_id),
+            Self::ServiceToken | Self::ClerkOrgMember { .. } => None,
+        }
+    }
+}
+
+/// Guards `/admin/v1` routes. Accepts the operator service token, a Clerk session token whose
+/// active organization and role match `ADMIN_CLERK_ORG_ID`/`ADMIN_CLERK_ORG_ROLE`, or, with
+/// `ORG_ADMIN_ROLE` set, a session holding that role in any organization, which is scoped to
+/// that organization. Routes reject every request when none is configured.
+pub(super) async fn admin_auth_middleware(
+    State(state): State<AppState>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    if state.admin_api_token.is_none()
+        && state.admin_clerk_org.is_none()
+        && state.org_admin_role.is_none()
+    {
+        warn!("admin request rejected because no admin credential is configured");
+        return unauthorized_response();
+    }
+
+    let token = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+        .unwrap_or_default();
+
+    if token.is_empty() {
+        warn!("admin request rejected: missing bearer token");
+        return unauthorized_response();
+    }
+
+    let principal = match authenticate_admin(&state, token).await {
+        Ok(principal) => principal,
+        Err(response) => return response,
+    };
+
+    req.extensions_mut().insert(principal);
+    next.run(req).await
+}
+
+async fn authenticate_admin(state: &AppState, token: &str) -> Result<AdminPrincipal, Response> {
+    if let Some(expected) = state.admin_api_token.as_deref()
+        && constant_time_eq(token, expected)
+    {
+        return Ok(AdminPrincipal::ServiceToken);
+    }
+
+    if state.admin_clerk_org.is_none() && state.org_admin_role.is_none() {
+        warn!("admin request rejected: invalid service token");
+        return Err(unauthorized_response());
+    }
+
+    let identity = match verify_identity_token(
+        &state.http_client,
+        &state.clerk_jwks_cache,
+        &state.clerk_jwks_url,
+        &state.clerk_secret_key,
+        &state.clerk_issuer,
+        &state.clerk_audience,
+        token,
+    )
+    .await
+    {
+        Ok(identity) => identity,
+        Err(ClerkIdentityError::InvalidToken { code, message }) => {
+            warn!("admin request rejected: code={code}, message={message}");
+            return Err(unauthorized_response());
+        }
+        Err(ClerkIdentityError::UpstreamUnavailable { code, message }) => {
+            warn!(
+                "admin clerk auth upstream unavailable: code={}, message={message}",
+                code.as_str()
+            );
+            return Err(error_response(code, message));
+        }
+    };
+
+    let principal = session_principal(
+        identity.subject,
+        identity.organization.as_ref(),
+        state.admin_clerk_org.as_ref(),
+        state.org_admin_role.as_deref(),
+    );
+    principal.ok_or_else(|| {
+        warn!("admin request rejected: clerk session lacks an admin organization role");
+        unauthorized_response()
+    })
+}
+
+/// Operator organization membership wins, so an operator who is also a customer org admin
+/// keeps operator access.
+fn session_principal(
+    subject: String,
+    organization: Option<&ClerkOrganization>,
+    admin_org: Option<&AdminClerkOrgConfig>,
+    org_admin_role: Option<&str>,
+) -> Option<AdminPrincipal> {
+    if let Some(admin_org) = admin_org
+        && is_admin_org_member(organization, admin_org)
+    {
+        return Some(AdminPrincipal::ClerkOrgMember {
+            org_id: admin_org.org_id.clone(),
+            subject,
+        });
+    }
+
+    let organization = organization?;
+    (Some(organization.role.as_str()) == org_admin_role).then(|| AdminPrincipal::OrgAdmin {
+        org_id: organization.id.clone(),
+        subject,
+    })
+}
+
+fn is_admin_org_member(
+    organization: Option<&ClerkOrganization>,
+    admin_org: &AdminClerkOrgConfig,
+) -> bool {
+    organization.is_some_and(|organization| {
+        organization.id == admin_org.org_id && organization.role == admin_org.role
+    })
+}
+
+/// Admin routes address users by id; unknown ids are a 404 rather than an empty view, and
+/// there is no trail to attribute the access to. Users outside an org admin's organization are
+/// reported the same way, so their existence does not leak across tenants.
+async fn require_known_user(
+    state: &AppState,
+    principal: &AdminPrincipal,
+    user_id: Uuid,
+) -> Result<(), Response> {
+    let known = match principal.organization_scope() {
+        Some(org_id) => state.store.user_in_organization(user_id, org_id).await,
+        None => state.store.user_exists(user_id).await,
+    };
+    match known {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(error_response(ApiErrorCode::UserNotFound, "User not found")),
+        Err(err) => Err(store_error_response(err)),
+    }
+}
+
+/// Routes that change state or span every tenant are for operators only; org admins get the
+/// returned rejection.
+fn reject_org_admin(principal: &AdminPrincipal) -> Option<Response> {
+    principal
+        .organization_scope()
+        .map(|_| admin_scope_forbidden_response())
+}
+
+fn admin_scope_forbidden_response() -> Response {
+    error_response(
+        ApiErrorCode::AdminScopeForbidden,
+        "Organization admins cannot use this admin route",
+    )
+}
+
+async fn record_admin_action(
+    state: &AppState,
+    principal: &AdminPrincipal,
+    user_id: Uuid,
+    action: &'static str,
+    details: AuditMetadata,
+) -> Result<(), Response> {
+    state
+        .events
+        .publish(
+            user_id,
+            DomainEvent::AdminActionPerformed {
+                principal: principal.audit_label(),
+                action,
+                details,
+            },
+        )
+        .await
+        .map_err(event_publish_error_response)
+}
+
+#[cfg(test)]
+mod tests {
+    use shared::config::AdminClerkOrgConfig;
+
+    use super::{AdminPrincipal, ClerkOrganization, is_admin_org_member, session_principal};
+
+    #[test]
+    fn clerk_admins_need_the_configured_org_and_role() {
+        let admin_org = AdminClerkOrgConfig {
+            org_id: "org_ops".to_string(),
+            role: "org:admin".to_string(),
+        };
+        let membership = |id: &str, role: &str| ClerkOrganization {
+            id: id.to_string(),
+            role: role.to_string(),
+        };
+
+        assert!(is_admin_org_member(
+            Some(&membership("org_ops", "org:admin")),
+            &admin_org
+        ));
+        assert!(!is_admin_org_member(
+            Some(&membership("org_ops", "org:member")),
+            &admin_org
+        ));
+        assert!(!is_admin_org_member(
+            Some(&membership("org_other", "org:admin")),
+            &admin_org
+        ));
+        assert!(!is_admin_org_member(None, &admin_org));
+    }
+
+    #[test]
+    fn org_admin_role_scopes_sessions_to_their_own_organization() {
+        let admin_org = AdminClerkOrgConfig {
+            org_id: "org_ops".to_string(),
+            role: "org:admin".to_string(),
+        };
+        let membership = |id: &str, role: &str| ClerkOrganization {
+            id: id.to_string(),
+            role: role.to_string(),
+        };
+        let principal = |organization: Option<&ClerkOrganization>, org_admin_role| {
+            session_principal(
+                "user_123".to_string(),
+                organization,
+                Some(&admin_org),
+                org_admin_role,
+            )
+        };
+
+        assert_eq!(
+            principal(
+                Some(&membership("org_acme", "org:admin")),
+                Some("org:admin")
+            ),
+            Some(AdminPrincipal::OrgAdmin {
+                org_id: "org_acme".to_string(),
+                subject: "user_123".to_string(),
+            })
+        );
+        assert_eq!(
+            principal(Some(&membership("org_ops", "org:admin")), Some("org:admin")),
+            Some(AdminPrincipal::ClerkOrgMember {
+                org_id: "org_ops".to_string(),
+                subject: "user_123".to_string(),
+            })
+        );
+        assert_eq!(
+            principal(
+                Some(&membership("org_acme", "org:member")),
+                Some("org:admin")
+            ),
+            None
+        );
+        assert_eq!(
+            principal(Some(&membership("org_acme", "org:admin")), None),
+            None
+        );
+        assert_eq!(principal(None, Some("org:admin")), None);
+    }
+
+    #[test]
+    fn principals_have_stable_audit_labels() {
+        assert_eq!(AdminPrincipal::ServiceToken.audit_label(), "service_token");
+        assert_eq!(
+            AdminPrincipal::ClerkOrgMember {
+                org_id: "org_ops".to_string(),
+                subject: "user_123".to_string(),
+            }
+            .audit_label(),
+            "clerk:org_ops:user_123"
+        );
+        assert_eq!(
+            AdminPrincipal::OrgAdmin {
+                org_id: "org_acme".to_string(),
+                subject: "user_123".to_string(),
+            }
+            .audit_label(),
+            "org_admin:org_acme:user_123"
+        );
+    }
+}
@@ -0,0 +1,53 @@
+use axum::Json;
+use axum::extract::{Extension, Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use shared::models::{AuditChainVerification, AuditMetadata};
+use tracing::info;
+use uuid::Uuid;
+
+use super::super::AppState;
+use super::super::errors::store_error_response;
+use super::super::openapi::ApiOperation;
+use super::{AdminPrincipal, record_admin_action, require_known_user};
+
+pub(crate) const VERIFY_AUDIT_CHAIN: ApiOperation = ApiOperation::get(
+    "/admin/v1/users/{user_id}/audit-chain",
+    "verifyAuditChain",
+    "Admin",
+    "Verify a user's audit hash chain",
+)
+.admin()
+.response::<AuditChainVerification>();
+
+pub(crate) async fn verify_audit_chain(
+    State(state): State<AppState>,
+    Extension(principal): Extension<AdminPrincipal>,
+    Path(user_id): Path<Uuid>,
+) -> Response {
+    if let Err(response) = require_known_user(&state, &principal, user_id).await {
+        return response;
+    }
+
+    let verification = match state.store.verify_audit_chain(user_id).await {
+        Ok(verification) => verification,
+        Err(err) => return store_error_response(err),
+    };
+    info!(
+        user_id = %user_id,
+        valid = verification.valid,
+        verified_events = verification.verified_events,
+        "audit chain verified"
+    );
+
+    // Recorded after verifying so the access never shows up in the result it produced.
+    let mut details = AuditMetadata::new();
+    details.insert("valid".to_string(), verification.valid.into());
+    if let Err(response) =
+        record_admin_action(&state, &principal, user_id, "verify_audit_chain", details).await
+    {
+        return response;
+    }
+
+    (StatusCode::OK, Json(verification)).into_response()
+}
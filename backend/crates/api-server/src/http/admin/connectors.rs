@@ -0,0 +1,220 @@
+use axum::Json;
+use axum::extract::{Extension, Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use shared::enclave::{ConnectorSecretRequest, EnclaveRpcError};
+use shared::models::{
+    AdminConnectorHealth, ApiErrorCode, AuditMetadata, ConnectorHealthCheckResponse,
+    ConnectorStatus, ListAdminConnectorHealthResponse,
+};
+use shared::repos::{ConnectorHealthRecord, StoreError};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use super::super::errors::{decrypt_not_authorized_response, error_response, store_error_response};
+use super::super::openapi::ApiOperation;
+use super::super::{AppState, build_enclave_client};
+use super::{AdminPrincipal, record_admin_action, reject_org_admin, require_known_user};
+
+pub(crate) const LIST_CONNECTOR_HEALTH: ApiOperation = ApiOperation::get(
+    "/admin/v1/users/{user_id}/connectors/health",
+    "listAdminConnectorHealth",
+    "Admin",
+    "List a user's connector health",
+)
+.admin()
+.response::<ListAdminConnectorHealthResponse>();
+
+pub(crate) const FORCE_CONNECTOR_HEALTH_CHECK: ApiOperation = ApiOperation::post(
+    "/admin/v1/users/{user_id}/connectors/{connector_id}/health-check",
+    "forceAdminConnectorHealthCheck",
+    "Admin",
+    "Probe a connector and refresh its health",
+)
+.admin()
+.response::<ConnectorHealthCheckResponse>();
+
+pub(crate) async fn list_connector_health(
+    State(state): State<AppState>,
+    Extension(principal): Extension<AdminPrincipal>,
+    Path(user_id): Path<Uuid>,
+) -> Response {
+    if let Err(response) = require_known_user(&state, &principal, user_id).await {
+        return response;
+    }
+
+    let records = match state.store.list_connector_health(user_id).await {
+        Ok(records) => records,
+        Err(err) => return store_error_response(err),
+    };
+    let items = match records
+        .into_iter()
+        .map(admin_connector_health)
+        .collect::<Result<Vec<_>, _>>()
+    {
+        Ok(items) => items,
+        Err(err) => return store_error_response(err),
+    };
+
+    if let Err(response) = record_admin_action(
+        &state,
+        &principal,
+        user_id,
+        "view_connector_health",
+        AuditMetadata::new(),
+    )
+    .await
+    {
+        return response;
+    }
+
+    (
+        StatusCode::OK,
+        Json(ListAdminConnectorHealthResponse { items }),
+    )
+        .into_response()
+}
+
+/// Exchanges the connector's refresh token through the enclave, which folds the outcome into
+/// the connector's health exactly like a worker-initiated call would. The access token is
+/// discarded. Provider-side failures are the check's result, not an error of this request.
+pub(crate) async fn force_connector_health_check(
+    State(state): State<AppState>,
+    Extension(principal): Extension<AdminPrincipal>,
+    Path((user_id, connector_id)): Path<(Uuid, Uuid)>,
+) -> Response {
+    if let Some(response) = reject_org_admin(&principal) {
+        return response;
+    }
+
+    let connector = match find_connector_health(&state, user_id, connector_id).await {
+        Ok(connector) => connector,
+        Err(response) => return response,
+    };
+    if connector.status != "ACTIVE" {
+        return error_response(
+            ApiErrorCode::ConnectorNotActive,
+            "Only active connectors can be health checked",
+        );
+    }
+    // CalDAV and IMAP connectors are only exercised by their sync calls; there is no cheap
+    // credential probe to force.
+    if connector.provider != "google" {
+        return error_response(
+            ApiErrorCode::UnsupportedProvider,
+            "Health checks are only supported for Google connectors",
+        );
+    }
+
+    let enclave_client = build_enclave_client(&state);
+    let probe_error_code = match enclave_client
+        .exchange_google_access_token(ConnectorSecretRequest {
+            user_id,
+            connector_id,
+        })
+        .await
+    {
+        Ok(_) => None,
+        Err(EnclaveRpcError::DecryptNotAuthorized { .. }) => {
+            return decrypt_not_authorized_response();
+        }
+        Err(
+            err @ (EnclaveRpcError::RpcUnauthorized { .. }
+            | EnclaveRpcError::RpcContractRejected { .. }
+            | EnclaveRpcError::RpcTransportUnavailable { .. }
+            | EnclaveRpcError::RpcResponseInvalid { .. }),
+        ) => {
+            warn!(connector_id = %connector_id, "connector health check rpc failed: {err}");
+            return error_response(
+                ApiErrorCode::EnclaveRpcFailed,
+                "Secure enclave RPC request failed",
+            );
+        }
+        Err(err) => Some(err.code()),
+    };
+    info!(
+        user_id = %user_id,
+        connector_id = %connector_id,
+        probe_error_code = probe_error_code.unwrap_or("none"),
+        "connector health check forced"
+    );
+
+    let connector = match find_connector_health(&state, user_id, connector_id).await {
+        Ok(connector) => connector,
+        Err(response) => return response,
+    };
+    let connector = match admin_connector_health(connector) {
+        Ok(connector) => connector,
+        Err(err) => return store_error_response(err),
+    };
+
+    let mut details = AuditMetadata::new();
+    details.insert("connector_id".to_string(), connector_id.to_string().into());
+    details.insert("health_score".to_string(), connector.health_score.into());
+    if let Some(code) = probe_error_code {
+        details.insert("probe_error_code".to_string(), code.into());
+    }
+    if let Err(response) = record_admin_action(
+        &state,
+        &principal,
+        user_id,
+        "force_connector_health_check",
+        details,
+    )
+    .await
+    {
+        return response;
+    }
+
+    (
+        StatusCode::OK,
+        Json(ConnectorHealthCheckResponse {
+            probe_succeeded: probe_error_code.is_none(),
+            probe_error_code: probe_error_code.map(str::to_string),
+            connector,
+        }),
+    )
+        .into_response()
+}
+
+async fn find_connector_health(
+    state: &AppState,
+    user_id: Uuid,
+    connector_id: Uuid,
+) -> Result<ConnectorHealthRecord, Response> {
+    let records = state
+        .store
+        .list_connector_health(user_id)
+        .await
+        .map_err(store_error_response)?;
+    records
+        .into_iter()
+        .find(|record| record.connector_id == connector_id)
+        .ok_or_else(|| error_response(ApiErrorCode::ConnectorNotFound, "Connector not found"))
+}
+
+fn admin_connector_health(
+    record: ConnectorHealthRecord,
+) -> Result<AdminConnectorHealth, StoreError> {
+    let status = match record.status.as_str() {
+        "ACTIVE" => ConnectorStatus::Active,
+        "REVOKED" => ConnectorStatus::Revoked,
+        value => {
+            return Err(StoreError::InvalidData(format!(
+                "unknown connector status persisted: {value}"
+            )));
+        }
+    };
+
+    Ok(AdminConnectorHealth {
+        connector_id: record.connector_id.to_string(),
+        provider: record.provider,
+        status,
+        health_score: record.health_score,
+        refresh_failure_count: record.refresh_failure_count,
+        provider_unauthorized_count: record.provider_unauthorized_count,
+        missing_scopes: record.missing_scopes,
+        health_updated_at: record.health_updated_at,
+        reauth_nudged_at: record.reauth_nudged_at,
+    })
+}
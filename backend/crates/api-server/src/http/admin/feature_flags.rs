@@ -0,0 +1,156 @@
+use axum::Json;
+use axum::extract::{Extension, Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use shared::feature_flags::{
+    FeatureFlag, FeatureFlagRule, FeatureFlagState, FeatureFlagStoreError,
+};
+use shared::models::{AdminFeatureFlag, ApiErrorCode, ListAdminFeatureFlagsResponse};
+use tracing::{info, warn};
+
+use super::super::AppState;
+use super::super::errors::error_response;
+use super::super::openapi::ApiOperation;
+use super::super::request_body::ApiJson;
+use super::{AdminPrincipal, reject_org_admin};
+
+pub(crate) const LIST_FEATURE_FLAGS: ApiOperation = ApiOperation::get(
+    "/admin/v1/feature-flags",
+    "listAdminFeatureFlags",
+    "Admin",
+    "List feature flags with their defaults and overrides",
+)
+.admin()
+.response::<ListAdminFeatureFlagsResponse>();
+
+pub(crate) const SET_FEATURE_FLAG: ApiOperation = ApiOperation::put(
+    "/admin/v1/feature-flags/{flag}",
+    "setAdminFeatureFlag",
+    "Admin",
+    "Override a feature flag's rule",
+)
+.admin()
+.request::<FeatureFlagRule>()
+.response::<AdminFeatureFlag>();
+
+pub(crate) const CLEAR_FEATURE_FLAG: ApiOperation = ApiOperation::delete(
+    "/admin/v1/feature-flags/{flag}",
+    "clearAdminFeatureFlag",
+    "Admin",
+    "Clear a feature flag override",
+)
+.admin()
+.response::<AdminFeatureFlag>();
+
+pub(crate) async fn list_feature_flags(
+    State(state): State<AppState>,
+    Extension(principal): Extension<AdminPrincipal>,
+) -> Response {
+    if let Some(response) = reject_org_admin(&principal) {
+        return response;
+    }
+
+    match state.feature_flags.states().await {
+        Ok(states) => (
+            StatusCode::OK,
+            Json(ListAdminFeatureFlagsResponse {
+                items: states.into_iter().map(admin_feature_flag).collect(),
+            }),
+        )
+            .into_response(),
+        Err(err) => feature_flag_store_error_response(err),
+    }
+}
+
+/// Flags are global, so unlike per-user admin routes there is no trail to audit into; flips
+/// are logged with the operator instead.
+pub(crate) async fn set_feature_flag(
+    State(state): State<AppState>,
+    Extension(principal): Extension<AdminPrincipal>,
+    Path(flag): Path<String>,
+    ApiJson(rule): ApiJson<FeatureFlagRule>,
+) -> Response {
+    if let Some(response) = reject_org_admin(&principal) {
+        return response;
+    }
+    let Some(flag) = FeatureFlag::parse(&flag) else {
+        return unknown_feature_flag_response();
+    };
+    if let Err(message) = rule.validate() {
+        return error_response(ApiErrorCode::InvalidFeatureFlagRule, &message);
+    }
+
+    if let Err(err) = state.feature_flags.set_override(flag, &rule).await {
+        return feature_flag_store_error_response(err);
+    }
+    info!(
+        principal = principal.audit_label(),
+        flag = flag.as_str(),
+        enabled = rule.enabled,
+        rollout_percent = rule.rollout_percent,
+        user_ids = rule.user_ids.len(),
+        "feature flag override set"
+    );
+
+    feature_flag_response(&state, flag).await
+}
+
+pub(crate) async fn clear_feature_flag(
+    State(state): State<AppState>,
+    Extension(principal): Extension<AdminPrincipal>,
+    Path(flag): Path<String>,
+) -> Response {
+    if let Some(response) = reject_org_admin(&principal) {
+        return response;
+    }
+    let Some(flag) = FeatureFlag::parse(&flag) else {
+        return unknown_feature_flag_response();
+    };
+
+    if let Err(err) = state.feature_flags.clear_override(flag).await {
+        return feature_flag_store_error_response(err);
+    }
+    info!(
+        principal = principal.audit_label(),
+        flag = flag.as_str(),
+        "feature flag override cleared"
+    );
+
+    feature_flag_response(&state, flag).await
+}
+
+fn unknown_feature_flag_response() -> Response {
+    error_response(
+        ApiErrorCode::FeatureFlagNotFound,
+        "Feature flag is not defined",
+    )
+}
+
+async fn feature_flag_response(state: &AppState, flag: FeatureFlag) -> Response {
+    let states = match state.feature_flags.states().await {
+        Ok(states) => states,
+        Err(err) => return feature_flag_store_error_response(err),
+    };
+
+    match states.into_iter().find(|state| state.flag == flag) {
+        Some(flag_state) => (StatusCode::OK, Json(admin_feature_flag(flag_state))).into_response(),
+        None => unknown_feature_flag_response(),
+    }
+}
+
+fn admin_feature_flag(state: FeatureFlagState) -> AdminFeatureFlag {
+    AdminFeatureFlag {
+        flag: state.flag,
+        rule: state.effective_rule().clone(),
+        overridden: state.override_rule.is_some(),
+        default_rule: state.default_rule,
+    }
+}
+
+fn feature_flag_store_error_response(err: FeatureFlagStoreError) -> Response {
+    warn!("feature flag store request failed: {err}");
+    error_response(
+        ApiErrorCode::FeatureFlagStoreUnavailable,
+        "Feature flag overrides are unavailable",
+    )
+}
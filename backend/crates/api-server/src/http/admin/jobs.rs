@@ -0,0 +1,200 @@
+use axum::Json;
+use axum::extract::{Extension, Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use chrono::Utc;
+use shared::models::{
+    AdminDeadLetterJob, AdminJobQueueDepth, ApiErrorCode, AuditMetadata,
+    ListAdminDeadLetterJobsResponse, ReplayDeadLetterJobResponse,
+};
+use tracing::info;
+use uuid::Uuid;
+
+use super::super::AppState;
+use super::super::errors::{error_response, store_error_response};
+use super::super::openapi::ApiOperation;
+use super::{AdminPrincipal, record_admin_action, reject_org_admin, require_known_user};
+
+/// Dead letters are inspected one incident at a time, so only the newest ones are listed.
+const DEAD_LETTER_LIST_LIMIT: i64 = 100;
+
+pub(crate) const GET_JOB_QUEUE_DEPTH: ApiOperation = ApiOperation::get(
+    "/admin/v1/users/{user_id}/jobs",
+    "getAdminJobQueueDepth",
+    "Admin",
+    "Get a user's job queue depth",
+)
+.admin()
+.response::<AdminJobQueueDepth>();
+
+pub(crate) const LIST_DEAD_LETTER_JOBS: ApiOperation = ApiOperation::get(
+    "/admin/v1/users/{user_id}/dead-letter-jobs",
+    "listAdminDeadLetterJobs",
+    "Admin",
+    "List a user's dead-lettered jobs",
+)
+.admin()
+.response::<ListAdminDeadLetterJobsResponse>();
+
+pub(crate) const REPLAY_DEAD_LETTER_JOB: ApiOperation = ApiOperation::post(
+    "/admin/v1/users/{user_id}/dead-letter-jobs/{dead_letter_id}/replay",
+    "replayAdminDeadLetterJob",
+    "Admin",
+    "Queue a dead-lettered job again",
+)
+.admin()
+.response::<ReplayDeadLetterJobResponse>();
+
+pub(crate) async fn get_job_queue_depth(
+    State(state): State<AppState>,
+    Extension(principal): Extension<AdminPrincipal>,
+    Path(user_id): Path<Uuid>,
+) -> Response {
+    if let Err(response) = require_known_user(&state, &principal, user_id).await {
+        return response;
+    }
+
+    let depth = match state.store.job_queue_depth(user_id, Utc::now()).await {
+        Ok(depth) => depth,
+        Err(err) => return store_error_response(err),
+    };
+
+    if let Err(response) = record_admin_action(
+        &state,
+        &principal,
+        user_id,
+        "view_job_queue",
+        AuditMetadata::new(),
+    )
+    .await
+    {
+        return response;
+    }
+
+    (
+        StatusCode::OK,
+        Json(AdminJobQueueDepth {
+            user_id: user_id.to_string(),
+            pending: depth.pending,
+            due: depth.due,
+            running: depth.running,
+            dead_lettered: depth.dead_lettered,
+            oldest_due_at: depth.oldest_due_at,
+        }),
+    )
+        .into_response()
+}
+
+pub(crate) async fn list_dead_letter_jobs(
+    State(state): State<AppState>,
+    Extension(principal): Extension<AdminPrincipal>,
+    Path(user_id): Path<Uuid>,
+) -> Response {
+    if let Err(response) = require_known_user(&state, &principal, user_id).await {
+        return response;
+    }
+
+    let records = match state
+        .store
+        .list_dead_letter_jobs(user_id, DEAD_LETTER_LIST_LIMIT)
+        .await
+    {
+        Ok(records) => records,
+        Err(err) => return store_error_response(err),
+    };
+
+    if let Err(response) = record_admin_action(
+        &state,
+        &principal,
+        user_id,
+        "view_dead_letter_jobs",
+        AuditMetadata::new(),
+    )
+    .await
+    {
+        return response;
+    }
+
+    let items = records
+        .into_iter()
+        .map(|record| AdminDeadLetterJob {
+            dead_letter_id: record.id.to_string(),
+            job_id: record.job_id.to_string(),
+            job_type: record.job_type.as_str().to_string(),
+            attempts: record.attempts,
+            reason_code: record.reason_code,
+            reason_message: record.reason_message,
+            failed_at: record.failed_at,
+            replayed_at: record.replayed_at,
+            replay_job_id: record.replay_job_id.map(|job_id| job_id.to_string()),
+        })
+        .collect();
+
+    (
+        StatusCode::OK,
+        Json(ListAdminDeadLetterJobsResponse { items }),
+    )
+        .into_response()
+}
+
+pub(crate) async fn replay_dead_letter_job(
+    State(state): State<AppState>,
+    Extension(principal): Extension<AdminPrincipal>,
+    Path((user_id, dead_letter_id)): Path<(Uuid, Uuid)>,
+) -> Response {
+    if let Some(response) = reject_org_admin(&principal) {
+        return response;
+    }
+
+    let replay = match state
+        .store
+        .replay_dead_letter_job(user_id, dead_letter_id, Utc::now())
+        .await
+    {
+        Ok(Some(replay)) => replay,
+        Ok(None) => {
+            return error_response(
+                ApiErrorCode::DeadLetterJobNotFound,
+                "Dead-lettered job not found",
+            );
+        }
+        Err(err) => return store_error_response(err),
+    };
+    info!(
+        user_id = %user_id,
+        dead_letter_id = %dead_letter_id,
+        job_id = %replay.job_id,
+        newly_queued = replay.newly_queued,
+        "dead-lettered job replayed"
+    );
+
+    let mut details = AuditMetadata::new();
+    details.insert(
+        "dead_letter_id".to_string(),
+        dead_letter_id.to_string().into(),
+    );
+    details.insert("job_id".to_string(), replay.job_id.to_string().into());
+    details.insert("newly_queued".to_string(), replay.newly_queued.into());
+    if let Err(response) = record_admin_action(
+        &state,
+        &principal,
+        user_id,
+        "replay_dead_letter_job",
+        details,
+    )
+    .await
+    {
+        return response;
+    }
+
+    (
+        StatusCode::OK,
+        Json(ReplayDeadLetterJobResponse {
+            dead_letter_id: dead_letter_id.to_string(),
+            job_id: replay.job_id.to_string(),
+            replayed_at: replay.replayed_at,
+            newly_queued: replay.newly_queued,
+        }),
+    )
+        .into_response()
+}
@@ -0,0 +1,125 @@
+use axum::Json;
+use axum::extract::{Extension, Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use shared::models::{
+    AdminOrganizationMember, AdminOrganizationPlan, ListAdminOrganizationMembersResponse,
+    SetOrganizationPlanRequest,
+};
+use tracing::info;
+
+use super::super::AppState;
+use super::super::errors::store_error_response;
+use super::super::openapi::ApiOperation;
+use super::super::request_body::ApiJson;
+use super::{AdminPrincipal, admin_scope_forbidden_response, reject_org_admin};
+
+/// Organizations are customer teams, so one page covers them; the oldest members come first.
+const ORGANIZATION_MEMBER_LIST_LIMIT: i64 = 500;
+
+pub(crate) const LIST_ORGANIZATION_MEMBERS: ApiOperation = ApiOperation::get(
+    "/admin/v1/organizations/{org_id}/members",
+    "listAdminOrganizationMembers",
+    "Admin",
+    "List an organization's members with their connector and automation counts",
+)
+.admin()
+.response::<ListAdminOrganizationMembersResponse>();
+
+pub(crate) const SET_ORGANIZATION_PLAN: ApiOperation = ApiOperation::put(
+    "/admin/v1/organizations/{org_id}/plan",
+    "setAdminOrganizationPlan",
+    "Admin",
+    "Set the plan granted to an organization's members",
+)
+.admin()
+.request::<SetOrganizationPlanRequest>()
+.response::<AdminOrganizationPlan>();
+
+/// Org admins may list their own organization. Like feature flags, the listing spans users, so
+/// it is logged with the principal rather than audited on each member's trail.
+pub(crate) async fn list_organization_members(
+    State(state): State<AppState>,
+    Extension(principal): Extension<AdminPrincipal>,
+    Path(org_id): Path<String>,
+) -> Response {
+    if principal
+        .organization_scope()
+        .is_some_and(|scope| scope != org_id)
+    {
+        return admin_scope_forbidden_response();
+    }
+
+    let plan = match state.store.get_organization_plan(&org_id).await {
+        Ok(plan) => plan,
+        Err(err) => return store_error_response(err),
+    };
+    let members = match state
+        .store
+        .list_organization_members(&org_id, ORGANIZATION_MEMBER_LIST_LIMIT)
+        .await
+    {
+        Ok(members) => members,
+        Err(err) => return store_error_response(err),
+    };
+    info!(
+        principal = principal.audit_label(),
+        org_id = %org_id,
+        members = members.len(),
+        "organization members listed"
+    );
+
+    let items = members
+        .into_iter()
+        .map(|member| AdminOrganizationMember {
+            user_id: member.user_id.to_string(),
+            created_at: member.created_at,
+            connector_count: member.connector_count,
+            automation_count: member.automation_count,
+        })
+        .collect();
+
+    (
+        StatusCode::OK,
+        Json(ListAdminOrganizationMembersResponse {
+            org_id,
+            plan,
+            items,
+        }),
+    )
+        .into_response()
+}
+
+pub(crate) async fn set_organization_plan(
+    State(state): State<AppState>,
+    Extension(principal): Extension<AdminPrincipal>,
+    Path(org_id): Path<String>,
+    ApiJson(request): ApiJson<SetOrganizationPlanRequest>,
+) -> Response {
+    if let Some(response) = reject_org_admin(&principal) {
+        return response;
+    }
+
+    if let Err(err) = state
+        .store
+        .set_organization_plan(&org_id, request.plan)
+        .await
+    {
+        return store_error_response(err);
+    }
+    info!(
+        principal = principal.audit_label(),
+        org_id = %org_id,
+        plan = request.plan.as_str(),
+        "organization plan set"
+    );
+
+    (
+        StatusCode::OK,
+        Json(AdminOrganizationPlan {
+            org_id,
+            plan: request.plan,
+        }),
+    )
+        .into_response()
+}
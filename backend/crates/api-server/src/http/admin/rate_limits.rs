@@ -0,0 +1,56 @@
+use axum::Json;
+use axum::extract::{Extension, Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use shared::models::{AdminRateLimitState, AuditMetadata};
+use uuid::Uuid;
+
+use super::super::AppState;
+use super::super::openapi::ApiOperation;
+use super::{AdminPrincipal, record_admin_action, require_known_user};
+
+pub(crate) const GET_RATE_LIMIT_STATE: ApiOperation = ApiOperation::get(
+    "/admin/v1/users/{user_id}/rate-limits",
+    "getAdminRateLimitState",
+    "Admin",
+    "Get a user's rate limit windows",
+)
+.admin()
+.response::<AdminRateLimitState>();
+
+/// Reads every route class window without recording a request. Windows come from Redis when
+/// it is attached; abuse escalations are tracked per instance and only this one is reported.
+pub(crate) async fn get_rate_limit_state(
+    State(state): State<AppState>,
+    Extension(principal): Extension<AdminPrincipal>,
+    Path(user_id): Path<Uuid>,
+) -> Response {
+    if let Err(response) = require_known_user(&state, &principal, user_id).await {
+        return response;
+    }
+
+    let items = state.rate_limiter.user_windows(user_id).await;
+    let abuse_blocked_for_seconds = state.rate_limiter.abuse_blocked_for(user_id);
+
+    if let Err(response) = record_admin_action(
+        &state,
+        &principal,
+        user_id,
+        "view_rate_limits",
+        AuditMetadata::new(),
+    )
+    .await
+    {
+        return response;
+    }
+
+    (
+        StatusCode::OK,
+        Json(AdminRateLimitState {
+            user_id: user_id.to_string(),
+            abuse_blocked_for_seconds,
+            items,
+        }),
+    )
+        .into_response()
+}
@@ -0,0 +1,436 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::extract::{Request, State};
+use axum::http::StatusCode;
+use axum::middleware::Next;
+use axum::response::Response;
+use shared::events::DomainEvent;
+use tracing::warn;
+use uuid::Uuid;
+
+use super::errors::too_many_requests_response;
+use super::rate_limit::{ip_subject, remote_ip, user_subject};
+use super::rate_limit_redis::RedisRateLimitStore;
+use super::{AppState, AuthUser};
+
+/// Failures that point at credential stuffing, a tampered client, or a replayed request.
+/// Handlers attach one to their response with [`flag_anomaly`]; any other `401` counts as an
+/// [`AnomalySignal::AuthFailure`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(super) enum AnomalySignal {
+    AuthFailure,
+    AttestationFailure,
+    ReplayDetected,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct AnomalySignalPolicy {
+    threshold: usize,
+    window_seconds: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct SecurityAnomaly {
+    pub(super) signal: AnomalySignal,
+    pub(super) observed: usize,
+    pub(super) window_seconds: u64,
+}
+
+const DEFAULT_COOLDOWN_SECONDS: u64 = 15 * 60;
+
+impl AnomalySignal {
+    pub(super) fn key_name(self) -> &'static str {
+        match self {
+            Self::AuthFailure => "auth_failure",
+            Self::AttestationFailure => "attestation_failure",
+            Self::ReplayDetected => "replay_detected",
+        }
+    }
+
+    fn policy(self) -> AnomalySignalPolicy {
+        match self {
+            Self::AuthFailure => AnomalySignalPolicy {
+                threshold: 20,
+                window_seconds: 60,
+            },
+            Self::AttestationFailure => AnomalySignalPolicy {
+                threshold: 5,
+                window_seconds: 600,
+            },
+            Self::ReplayDetected => AnomalySignalPolicy {
+                threshold: 3,
+                window_seconds: 600,
+            },
+        }
+    }
+}
+
+/// Marks `response` as evidence of `signal` for [`security_anomaly_middleware`].
+pub(super) fn flag_anomaly(mut response: Response, signal: AnomalySignal) -> Response {
+    response.extensions_mut().insert(signal);
+    response
+}
+
+/// Sliding-window counters per signal and subject, plus the cooldown a subject enters once a
+/// signal trips. Cooldowns always deduplicate alerts; they only block or tighten limits when
+/// enforcement is on.
+#[derive(Clone)]
+pub(super) struct AnomalyTracker {
+    state: Arc<Mutex<AnomalyState>>,
+    enforce: bool,
+    cooldown_seconds: u64,
+}
+
+#[derive(Default)]
+struct AnomalyState {
+    signals: HashMap<(AnomalySignal, String), VecDeque<Instant>>,
+    cooldowns: HashMap<String, Instant>,
+}
+
+impl Default for AnomalyTracker {
+    fn default() -> Self {
+        Self {
+            state: Arc::default(),
+            enforce: false,
+            cooldown_seconds: DEFAULT_COOLDOWN_SECONDS,
+        }
+    }
+}
+
+impl AnomalyTracker {
+    pub(super) fn with_enforcement(mut self, enforce: bool, cooldown_seconds: u64) -> Self {
+        self.enforce = enforce;
+        self.cooldown_seconds = cooldown_seconds;
+        self
+    }
+
+    /// Cooldown applied to a subject that trips a signal, when enforcement is on.
+    pub(super) fn enforced_for_seconds(&self) -> Option<u64> {
+        self.enforce.then_some(self.cooldown_seconds)
+    }
+
+    /// Counts the occurrence in Redis when attached so every instance sees the same window,
+    /// falling back to this instance's counters when Redis fails.
+    pub(super) async fn record(
+        &self,
+        redis: Option<&RedisRateLimitStore>,
+        signal: AnomalySignal,
+        subject: &str,
+    ) -> Option<SecurityAnomaly> {
+        if let Some(redis) = redis {
+            match self.record_in_redis(redis, signal, subject).await {
+                Ok(anomaly) => return anomaly,
+                Err(err) => warn!(
+                    signal = signal.key_name(),
+                    "redis anomaly counter failed, using in-process counters: {err}"
+                ),
+            }
+        }
+
+        self.record_at(signal, subject, Instant::now())
+    }
+
+    /// Seconds left on the subject's cooldown while enforcement is on.
+    pub(super) async fn enforced_for(
+        &self,
+        redis: Option<&RedisRateLimitStore>,
+        subject: &str,
+    ) -> Option<u64> {
+        if !self.enforce {
+            return None;
+        }
+        if let Some(redis) = redis {
+            match redis.anomaly_cooldown(subject).await {
+                Ok(remaining) => return remaining,
+                Err(err) => warn!("redis anomaly cooldown lookup failed, using in-process: {err}"),
+            }
+        }
+
+        self.cooldown_at(subject, Instant::now())
+    }
+
+    async fn record_in_redis(
+        &self,
+        redis: &RedisRateLimitStore,
+        signal: AnomalySignal,
+        subject: &str,
+    ) -> redis::RedisResult<Option<SecurityAnomaly>> {
+        let policy = signal.policy();
+        let observed = redis
+            .record_security_signal(signal.key_name(), subject, policy.window_seconds)
+            .await?;
+        if observed < policy.threshold {
+            return Ok(None);
+        }
+        let started = redis
+            .start_anomaly_cooldown(subject, self.cooldown_seconds)
+            .await?;
+
+        Ok(started.then_some(SecurityAnomaly {
+            signal,
+            observed,
+            window_seconds: policy.window_seconds,
+        }))
+    }
+
+    /// Records one occurrence and returns an anomaly when the signal crosses its threshold for
+    /// a subject that is not already cooling down.
+    fn record_at(
+        &self,
+        signal: AnomalySignal,
+        subject: &str,
+        now: Instant,
+    ) -> Option<SecurityAnomaly> {
+        let policy = signal.policy();
+        let cutoff = now
+            .checked_sub(Duration::from_secs(policy.window_seconds))
+            .unwrap_or(now);
+        let mut state = self
+            .state
+            .lock()
+            .expect("anomaly tracker mutex should not be poisoned");
+
+        let events = state
+            .signals
+            .entry((signal, subject.to_string()))
+            .or_default();
+        while events.front().is_some_and(|seen| *seen <= cutoff) {
+            events.pop_front();
+        }
+        events.push_back(now);
+        let observed = events.len();
+        if observed < policy.threshold {
+            return None;
+        }
+        events.clear();
+
+        if state
+            .cooldowns
+            .get(subject)
+            .is_some_and(|until| *until > now)
+        {
+            return None;
+        }
+        state.cooldowns.insert(
+            subject.to_string(),
+            now + Duration::from_secs(self.cooldown_seconds),
+        );
+
+        Some(SecurityAnomaly {
+            signal,
+            observed,
+            window_seconds: policy.window_seconds,
+        })
+    }
+
+    fn cooldown_at(&self, subject: &str, now: Instant) -> Option<u64> {
+        let state = self
+            .state
+            .lock()
+            .expect("anomaly tracker mutex should not be poisoned");
+        state
+            .cooldowns
+            .get(subject)
+            .filter(|until| **until > now)
+            .map(|until| until.saturating_duration_since(now).as_secs().max(1))
+    }
+
+    pub(super) fn prune(&self, now: Instant, max_window: Duration) {
+        let cutoff = now.checked_sub(max_window).unwrap_or(now);
+        let mut state = self
+            .state
+            .lock()
+            .expect("anomaly tracker prune mutex should not be poisoned");
+        state.signals.retain(|_, events| {
+            while events.front().is_some_and(|seen| *seen <= cutoff) {
+                events.pop_front();
+            }
+            !events.is_empty()
+        });
+        state.cooldowns.retain(|_, until| *until > now);
+    }
+}
+
+/// Turns away IPs in an enforced cooldown, then counts anomaly signals on the way out
+/// against the client IP and, for authenticated requests, the account.
+pub(super) async fn security_anomaly_middleware(
+    State(state): State<AppState>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let ip = remote_ip(&req, &state.trusted_proxy_ips).map(ip_subject);
+    if let Some(subject) = ip.as_deref()
+        && let Some(retry_after_seconds) = state.rate_limiter.anomaly_enforced_for(subject).await
+    {
+        warn!(
+            retry_after_seconds,
+            "request denied by security anomaly cooldown"
+        );
+        state
+            .metrics
+            .record_rate_limit_rejection("security_anomaly", "anomaly_block");
+        return too_many_requests_response(retry_after_seconds);
+    }
+
+    let response = next.run(req).await;
+    let Some(signal) = response
+        .extensions()
+        .get::<AnomalySignal>()
+        .copied()
+        .or_else(|| {
+            (response.status() == StatusCode::UNAUTHORIZED).then_some(AnomalySignal::AuthFailure)
+        })
+    else {
+        return response;
+    };
+
+    let user_id = response
+        .extensions()
+        .get::<AuthUser>()
+        .map(|user| user.user_id);
+    let subjects = [
+        ip.map(|subject| ("ip", subject)),
+        user_id.map(|user_id| ("user", user_subject(user_id))),
+    ];
+    for (subject_kind, subject) in subjects.into_iter().flatten() {
+        if let Some(anomaly) = state
+            .rate_limiter
+            .record_security_signal(signal, &subject)
+            .await
+        {
+            report_anomaly(&state, anomaly, subject_kind, user_id).await;
+        }
+    }
+    response
+}
+
+/// Alerts on every anomaly. The audit trail is per account, so an anomaly is only audited when
+/// the request that tripped it was authenticated.
+async fn report_anomaly(
+    state: &AppState,
+    anomaly: SecurityAnomaly,
+    subject_kind: &'static str,
+    user_id: Option<Uuid>,
+) {
+    let enforced_for_seconds = state.rate_limiter.anomaly_enforced_for_seconds();
+    warn!(
+        alert = "security_anomaly",
+        signal = anomaly.signal.key_name(),
+        subject = subject_kind,
+        user_id = ?user_id,
+        observed = anomaly.observed,
+        window_seconds = anomaly.window_seconds,
+        enforced_for_seconds = ?enforced_for_seconds,
+        "security anomaly detected"
+    );
+    state
+        .metrics
+        .record_security_anomaly(anomaly.signal.key_name(), subject_kind);
+
+    let Some(user_id) = user_id else {
+        return;
+    };
+    if let Err(err) = state
+        .events
+        .publish(
+            user_id,
+            DomainEvent::SecurityAnomalyDetected {
+                signal: anomaly.signal.key_name(),
+                subject: subject_kind,
+                observed: anomaly.observed,
+                window_seconds: anomaly.window_seconds,
+                enforced_for_seconds,
+            },
+        )
+        .await
+    {
+        warn!(user_id = %user_id, "failed to persist security anomaly audit event: {err}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trips_once_threshold_is_crossed_and_then_cools_down() {
+        let tracker = AnomalyTracker::default().with_enforcement(true, 300);
+        let start = Instant::now();
+
+        for _ in 0..2 {
+            assert_eq!(
+                tracker.record_at(AnomalySignal::ReplayDetected, "user:a", start),
+                None
+            );
+        }
+        let anomaly = tracker
+            .record_at(AnomalySignal::ReplayDetected, "user:a", start)
+            .expect("third replay should trip");
+        assert_eq!(anomaly.observed, 3);
+        assert_eq!(anomaly.window_seconds, 600);
+        assert_eq!(tracker.cooldown_at("user:a", start), Some(300));
+        assert_eq!(tracker.cooldown_at("ip:203.0.113.7", start), None);
+
+        for _ in 0..3 {
+            assert_eq!(
+                tracker.record_at(AnomalySignal::ReplayDetected, "user:a", start),
+                None,
+                "a subject already cooling down is not reported again"
+            );
+        }
+        assert_eq!(
+            tracker.cooldown_at("user:a", start + Duration::from_secs(301)),
+            None
+        );
+    }
+
+    #[test]
+    fn failures_outside_the_window_do_not_count() {
+        let tracker = AnomalyTracker::default();
+        let start = Instant::now();
+
+        for _ in 0..19 {
+            tracker.record_at(AnomalySignal::AuthFailure, "ip:203.0.113.7", start);
+        }
+        let later = start + Duration::from_secs(61);
+        assert_eq!(
+            tracker.record_at(AnomalySignal::AuthFailure, "ip:203.0.113.7", later),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn cooldowns_only_enforce_when_enabled() {
+        let observing = AnomalyTracker::default();
+        let enforcing = AnomalyTracker::default().with_enforcement(true, 900);
+        for tracker in [&observing, &enforcing] {
+            for _ in 0..5 {
+                tracker
+                    .record(None, AnomalySignal::AttestationFailure, "user:a")
+                    .await;
+            }
+        }
+
+        assert_eq!(observing.enforced_for(None, "user:a").await, None);
+        assert_eq!(observing.enforced_for_seconds(), None);
+        assert!(enforcing.enforced_for(None, "user:a").await.is_some());
+        assert_eq!(enforcing.enforced_for_seconds(), Some(900));
+    }
+
+    #[test]
+    fn prune_drops_expired_signals_and_cooldowns() {
+        let tracker = AnomalyTracker::default();
+        let start = Instant::now();
+        tracker.record_at(AnomalySignal::AuthFailure, "ip:203.0.113.7", start);
+        for _ in 0..3 {
+            tracker.record_at(AnomalySignal::ReplayDetected, "user:a", start);
+        }
+
+        tracker.prune(start + Duration::from_secs(3601), Duration::from_secs(3600));
+        let state = tracker.state.lock().expect("mutex should not be poisoned");
+        assert!(state.signals.is_empty());
+        assert!(state.cooldowns.is_empty());
+    }
+}
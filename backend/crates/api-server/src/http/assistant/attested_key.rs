@@ -2,40 +2,51 @@ use axum::Json;
 use axum::extract::{Extension, State};
 use axum::response::{IntoResponse, Response};
 use shared::models::{
-    AssistantAttestedKeyAttestation, AssistantAttestedKeyRequest, AssistantAttestedKeyResponse,
+    ApiErrorCode, AssistantAttestedKeyAttestation, AssistantAttestedKeyRequest,
+    AssistantAttestedKeyResponse,
 };
 
-use super::super::errors::{bad_gateway_response, bad_request_response};
-use super::super::{AppState, AuthUser};
+use super::super::errors::error_response;
+use super::super::openapi::ApiOperation;
+use super::super::request_body::ApiJson;
+use super::super::{AppState, AuthUser, build_enclave_client};
+
+pub(crate) const FETCH_ATTESTED_KEY: ApiOperation = ApiOperation::post(
+    "/v1/assistant/attested-key",
+    "fetchAssistantAttestedKey",
+    "Assistant",
+    "Fetch attested enclave assistant encryption key",
+)
+.request::<AssistantAttestedKeyRequest>()
+.response::<AssistantAttestedKeyResponse>();
 
 pub(crate) async fn fetch_attested_key(
     State(state): State<AppState>,
     Extension(_user): Extension<AuthUser>,
-    Json(request): Json<AssistantAttestedKeyRequest>,
+    ApiJson(request): ApiJson<AssistantAttestedKeyRequest>,
 ) -> Response {
     if request.challenge_nonce.trim().is_empty() {
-        return bad_request_response("invalid_challenge_nonce", "challenge_nonce is required");
+        return error_response(
+            ApiErrorCode::InvalidChallengeNonce,
+            "challenge_nonce is required",
+        );
     }
     if request.request_id.trim().is_empty() {
-        return bad_request_response("invalid_request_id", "request_id is required");
+        return error_response(ApiErrorCode::InvalidRequestId, "request_id is required");
     }
     if request.expires_at <= request.issued_at {
-        return bad_request_response(
-            "invalid_challenge_window",
+        return error_response(
+            ApiErrorCode::InvalidChallengeWindow,
             "expires_at must be greater than issued_at",
         );
     }
 
     let now = chrono::Utc::now().timestamp();
     if now > request.expires_at {
-        return bad_request_response("challenge_expired", "challenge has expired");
+        return error_response(ApiErrorCode::ChallengeExpired, "challenge has expired");
     }
 
-    let enclave_client = shared::enclave::EnclaveRpcClient::new(
-        state.enclave_rpc.base_url.clone(),
-        state.enclave_rpc.auth.clone(),
-        state.http_client.clone(),
-    );
+    let enclave_client = build_enclave_client(&state);
     let response = match enclave_client
         .fetch_assistant_attested_key(
             request.challenge_nonce.clone(),
@@ -47,15 +58,18 @@ pub(crate) async fn fetch_attested_key(
     {
         Ok(response) => response,
         Err(_) => {
-            return bad_gateway_response("enclave_rpc_failed", "Secure enclave RPC request failed");
+            return error_response(
+                ApiErrorCode::EnclaveRpcFailed,
+                "Secure enclave RPC request failed",
+            );
         }
     };
 
     if response.challenge_nonce != request.challenge_nonce
         || response.request_id != request.request_id
     {
-        return bad_gateway_response(
-            "attestation_challenge_mismatch",
+        return error_response(
+            ApiErrorCode::AttestationChallengeMismatch,
             "Attested key response did not match challenge",
         );
     }
@@ -77,6 +91,7 @@ pub(crate) async fn fetch_attested_key(
                 evidence_issued_at: response.evidence_issued_at,
                 signature: response.signature,
             },
+            rotation: response.rotation,
         }),
     )
         .into_response()
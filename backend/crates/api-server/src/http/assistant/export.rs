@@ -0,0 +1,172 @@
+use axum::Json;
+use axum::extract::{Extension, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use base64::Engine as _;
+use chrono::Utc;
+use shared::enclave::{
+    EnclaveAssistantSessionExportItem, EnclaveRpcError, ExportAssistantSessionsRequest,
+};
+use shared::models::{
+    ApiErrorCode, AssistantSessionExportRequest, AssistantSessionExportResponse,
+    AssistantSessionSummary, AuditMetadata,
+};
+use shared::repos::AuditResult;
+use tracing::warn;
+
+use super::super::errors::{error_response, store_error_response};
+use super::super::openapi::ApiOperation;
+use super::super::request_body::ApiJson;
+use super::super::{AppState, AuthUser, build_enclave_client};
+
+/// Matches the enclave's per-export ceiling; older sessions beyond it are left out.
+const ASSISTANT_SESSION_EXPORT_MAX_SESSIONS: i64 = 500;
+
+pub(crate) const EXPORT_ASSISTANT_SESSIONS: ApiOperation = ApiOperation::post(
+    "/v1/assistant/sessions/export",
+    "exportAssistantSessions",
+    "Assistant",
+    "Export assistant thread sessions as a client-decryptable archive",
+)
+.request::<AssistantSessionExportRequest>()
+.response::<AssistantSessionExportResponse>();
+
+/// Packages the caller's live sessions into an archive only the requesting client can
+/// decrypt. The host forwards sealed session state to the enclave and never sees plaintext.
+pub(crate) async fn export_assistant_sessions(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    ApiJson(request): ApiJson<AssistantSessionExportRequest>,
+) -> Response {
+    if let Some(response) = validate_export_request(&request) {
+        return response;
+    }
+
+    let now = Utc::now();
+    let records = match state
+        .store
+        .list_assistant_encrypted_sessions_for_export(
+            user.user_id,
+            now,
+            ASSISTANT_SESSION_EXPORT_MAX_SESSIONS,
+        )
+        .await
+    {
+        Ok(records) => records,
+        Err(err) => return store_error_response(err),
+    };
+
+    let summaries = records
+        .iter()
+        .map(|record| AssistantSessionSummary {
+            session_id: record.session_id,
+            created_at: record.created_at,
+            updated_at: record.updated_at,
+            expires_at: record.expires_at,
+            title_envelope: record.title_envelope.clone(),
+            pinned: record.pinned,
+        })
+        .collect::<Vec<_>>();
+
+    let enclave_client = build_enclave_client(&state);
+    let response = match enclave_client
+        .export_assistant_sessions(ExportAssistantSessionsRequest {
+            user_id: user.user_id,
+            export_request_id: request.request_id.clone(),
+            client_ephemeral_public_key: request.client_ephemeral_public_key,
+            sessions: records
+                .into_iter()
+                .map(|record| EnclaveAssistantSessionExportItem {
+                    session_id: record.session_id,
+                    created_at: record.created_at,
+                    updated_at: record.updated_at,
+                    state: record.state,
+                })
+                .collect(),
+        })
+        .await
+    {
+        Ok(response) => response,
+        Err(err) => return map_export_enclave_error(err, &request.request_id),
+    };
+
+    let mut metadata = AuditMetadata::new();
+    metadata.insert("request_id".to_string(), request.request_id.clone().into());
+    metadata.insert(
+        "exported_sessions".to_string(),
+        response.exported_session_ids.len().into(),
+    );
+    metadata.insert(
+        "skipped_sessions".to_string(),
+        response.skipped_session_ids.len().into(),
+    );
+    if let Err(err) = state
+        .store
+        .add_audit_event(
+            user.user_id,
+            "ASSISTANT_SESSIONS_EXPORTED",
+            None,
+            AuditResult::Success,
+            &metadata,
+        )
+        .await
+    {
+        return store_error_response(err);
+    }
+
+    let sessions = summaries
+        .into_iter()
+        .filter(|summary| response.exported_session_ids.contains(&summary.session_id))
+        .collect();
+
+    (
+        StatusCode::OK,
+        Json(AssistantSessionExportResponse {
+            request_id: request.request_id,
+            exported_at: now,
+            sessions,
+            skipped_session_ids: response.skipped_session_ids,
+            envelope: response.envelope,
+        }),
+    )
+        .into_response()
+}
+
+fn validate_export_request(request: &AssistantSessionExportRequest) -> Option<Response> {
+    if request.request_id.trim().is_empty() {
+        return Some(error_response(
+            ApiErrorCode::InvalidRequestId,
+            "request_id is required",
+        ));
+    }
+
+    let is_valid_key = base64::engine::general_purpose::STANDARD
+        .decode(request.client_ephemeral_public_key.as_bytes())
+        .is_ok_and(|bytes| bytes.len() == 32);
+    if !is_valid_key {
+        return Some(error_response(
+            ApiErrorCode::InvalidClientPublicKey,
+            "client_ephemeral_public_key must be a base64 32-byte X25519 key",
+        ));
+    }
+
+    None
+}
+
+fn map_export_enclave_error(err: EnclaveRpcError, export_request_id: &str) -> Response {
+    warn!(
+        export_request_id,
+        code = err.code(),
+        "assistant session export enclave RPC failed"
+    );
+    match err {
+        EnclaveRpcError::RpcContractRejected { .. } => error_response(
+            ApiErrorCode::InvalidEnclaveRequest,
+            "Assistant session export request rejected",
+        ),
+        _ => error_response(
+            ApiErrorCode::EnclaveRpcFailed,
+            "Secure enclave RPC request failed",
+        ),
+    }
+}
@@ -1,9 +1,13 @@
 mod attested_key;
+mod export;
 mod query;
 mod sessions;
 
-pub(crate) use attested_key::fetch_attested_key;
-pub(crate) use query::query_assistant;
+pub(crate) use attested_key::{FETCH_ATTESTED_KEY, fetch_attested_key};
+pub(crate) use export::{EXPORT_ASSISTANT_SESSIONS, export_assistant_sessions};
+pub(crate) use query::{QUERY_ASSISTANT, query_assistant};
 pub(crate) use sessions::{
-    delete_all_assistant_sessions, delete_assistant_session, list_assistant_sessions,
+    DELETE_ALL_ASSISTANT_SESSIONS, DELETE_ASSISTANT_SESSION, LIST_ASSISTANT_SESSIONS,
+    UPDATE_ASSISTANT_SESSION, delete_all_assistant_sessions, delete_assistant_session,
+    list_assistant_sessions, update_assistant_session,
 };
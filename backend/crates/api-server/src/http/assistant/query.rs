@@ -9,26 +9,67 @@ use shared::assistant_crypto::{
     ASSISTANT_ENCRYPTION_ALGORITHM_X25519_CHACHA20POLY1305, ASSISTANT_ENVELOPE_VERSION_V1,
 };
 use shared::enclave::EnclaveRpcError;
-use shared::models::{AssistantQueryRequest, AssistantQueryResponse};
+use shared::models::{
+    ApiErrorCode, AssistantDegradedReason, AssistantQueryRequest, AssistantQueryResponse,
+    AvailabilityComponent, QuotaKind,
+};
+use shared::quota::llm_spend_month_reset;
+use shared::repos::{AssistantRequestIndexEntry, AssistantRequestOutcome, PromptEnvelopeUse};
 use tracing::{info, warn};
 use uuid::Uuid;
 
-use super::super::errors::{bad_gateway_response, bad_request_response, store_error_response};
-use super::super::{AppState, AuthUser};
+use super::super::anomaly::{AnomalySignal, flag_anomaly};
+use super::super::errors::{
+    error_response, provider_rate_limited_response, quota_exceeded_response, store_error_response,
+};
+use super::super::openapi::ApiOperation;
+use super::super::prompt_envelopes::claim_prompt_envelope_request_id;
+use super::super::quota::enforce_plan_quota;
+use super::super::request_body::ApiJson;
+use super::super::{AppState, AuthUser, build_enclave_client};
+
+pub(crate) const QUERY_ASSISTANT: ApiOperation = ApiOperation::post(
+    "/v1/assistant/query",
+    "queryAssistant",
+    "Assistant",
+    "Query assistant with encrypted envelope",
+)
+.request::<AssistantQueryRequest>()
+.response::<AssistantQueryResponse>();
 
 pub(crate) async fn query_assistant(
     State(state): State<AppState>,
     Extension(user): Extension<AuthUser>,
-    Json(request): Json<AssistantQueryRequest>,
+    ApiJson(request): ApiJson<AssistantQueryRequest>,
 ) -> Response {
     let handler_started = Instant::now();
     let assistant_request_id = request.envelope.request_id.clone();
     if let Some(response) = validate_envelope_shape(&request) {
         return response;
     }
+    for kind in [
+        QuotaKind::AssistantQueriesPerDay,
+        QuotaKind::LlmRequestsPerDay,
+    ] {
+        if let Err(response) = enforce_plan_quota(&state, user.user_id, kind).await {
+            return response;
+        }
+    }
+    if let Err(response) = claim_prompt_envelope_request_id(
+        &state,
+        user.user_id,
+        &request.envelope.key_id,
+        &assistant_request_id,
+        PromptEnvelopeUse::AssistantQuery,
+    )
+    .await
+    {
+        return response;
+    }
 
     let now = Utc::now();
-    let had_prior_session = request.session_id.is_some();
+    let requested_session_id = request.session_id;
+    let had_prior_session = requested_session_id.is_some();
     let mut load_prior_session_ms = 0_u64;
     let prior_session_state = match request.session_id {
         Some(session_id) => {
@@ -48,18 +89,31 @@ pub(crate) async fn query_assistant(
         None => None,
     };
 
-    let enclave_client = shared::enclave::EnclaveRpcClient::new(
-        state.enclave_rpc.base_url.clone(),
-        state.enclave_rpc.auth.clone(),
-        state.http_client.clone(),
-    );
+    let enclave_client = build_enclave_client(&state);
     let enclave_rpc_started = Instant::now();
     let response = match enclave_client
         .process_assistant_query(user.user_id, request, prior_session_state)
         .await
     {
         Ok(response) => response,
-        Err(err) => return map_assistant_enclave_error(err, user.user_id, &assistant_request_id),
+        Err(err) => {
+            let entry = AssistantRequestIndexEntry {
+                request_id: assistant_request_id.clone(),
+                session_id: requested_session_id,
+                outcome: AssistantRequestOutcome::Failed,
+                error_code: Some(err.code().to_string()),
+                route: None,
+                capability: None,
+                model: None,
+                planner_used_fallback: false,
+                output_used_fallback: false,
+                planner_ms: None,
+                lane_ms: None,
+                enclave_rpc_ms: enclave_rpc_started.elapsed().as_millis() as u64,
+            };
+            record_assistant_request(&state, user.user_id, &entry).await;
+            return map_assistant_enclave_error(err, user.user_id, &assistant_request_id);
+        }
     };
     let enclave_rpc_ms = enclave_rpc_started.elapsed().as_millis() as u64;
 
@@ -67,8 +121,8 @@ pub(crate) async fn query_assistant(
     if let Some(session_state) = &response.session_state {
         let ttl_seconds = (session_state.expires_at - now).num_seconds();
         if ttl_seconds <= 0 {
-            return bad_gateway_response(
-                "invalid_enclave_session_state",
+            return error_response(
+                ApiErrorCode::InvalidEnclaveSessionState,
                 "Secure enclave session state has expired",
             );
         }
@@ -90,6 +144,24 @@ pub(crate) async fn query_assistant(
         persist_session_ms = persist_started.elapsed().as_millis() as u64;
     }
 
+    let telemetry = response.telemetry.as_ref();
+    let entry = AssistantRequestIndexEntry {
+        request_id: assistant_request_id.clone(),
+        session_id: Some(response.session_id),
+        outcome: AssistantRequestOutcome::Succeeded,
+        error_code: None,
+        route: telemetry.map(|telemetry| telemetry.route.clone()),
+        capability: telemetry.map(|telemetry| telemetry.capability.as_str().to_string()),
+        model: telemetry.and_then(|telemetry| telemetry.model.clone()),
+        planner_used_fallback: telemetry.is_some_and(|telemetry| telemetry.planner_used_fallback),
+        output_used_fallback: telemetry.is_some_and(|telemetry| telemetry.output_used_fallback),
+        planner_ms: telemetry.map(|telemetry| telemetry.planner_ms),
+        lane_ms: telemetry.map(|telemetry| telemetry.lane_ms),
+        enclave_rpc_ms,
+    };
+    record_assistant_request(&state, user.user_id, &entry).await;
+    let degraded_reasons = degraded_reasons(&entry);
+
     info!(
         user_id = %user.user_id,
         assistant_request_id,
@@ -107,34 +179,73 @@ pub(crate) async fn query_assistant(
         Json(AssistantQueryResponse {
             session_id: response.session_id,
             envelope: response.envelope,
+            degraded_reasons,
         }),
     )
         .into_response()
 }
 
+fn degraded_reasons(entry: &AssistantRequestIndexEntry) -> Vec<AssistantDegradedReason> {
+    let mut reasons = Vec::new();
+    if entry.planner_used_fallback {
+        reasons.push(AssistantDegradedReason::PlannerFallback);
+    }
+    if entry.output_used_fallback {
+        reasons.push(AssistantDegradedReason::ResponseFallback);
+    }
+    reasons
+}
+
+/// Indexing is best effort: a failed write is logged and never fails the query itself.
+async fn record_assistant_request(
+    state: &AppState,
+    user_id: Uuid,
+    entry: &AssistantRequestIndexEntry,
+) {
+    if let Err(err) = state.store.record_assistant_request(user_id, entry).await {
+        warn!(
+            %user_id,
+            assistant_request_id = %entry.request_id,
+            "failed to index assistant request: {err}"
+        );
+    }
+
+    let succeeded = entry.outcome == AssistantRequestOutcome::Succeeded;
+    if let Err(err) = state
+        .store
+        .record_component_availability(AvailabilityComponent::Assistant, 1, i64::from(succeeded))
+        .await
+    {
+        warn!("failed to record assistant availability: {err}");
+    }
+}
+
 fn validate_envelope_shape(request: &AssistantQueryRequest) -> Option<Response> {
     let envelope = &request.envelope;
     if envelope.version != ASSISTANT_ENVELOPE_VERSION_V1 {
-        return Some(bad_request_response(
-            "invalid_envelope_version",
+        return Some(error_response(
+            ApiErrorCode::InvalidEnvelopeVersion,
             "assistant envelope version is not supported",
         ));
     }
 
     if envelope.algorithm != ASSISTANT_ENCRYPTION_ALGORITHM_X25519_CHACHA20POLY1305 {
-        return Some(bad_request_response(
-            "invalid_envelope_algorithm",
+        return Some(error_response(
+            ApiErrorCode::InvalidEnvelopeAlgorithm,
             "assistant envelope algorithm is not supported",
         ));
     }
 
     if envelope.key_id.trim().is_empty() {
-        return Some(bad_request_response("invalid_key_id", "key_id is required"));
+        return Some(error_response(
+            ApiErrorCode::InvalidKeyId,
+            "key_id is required",
+        ));
     }
 
     if envelope.request_id.trim().is_empty() {
-        return Some(bad_request_response(
-            "invalid_request_id",
+        return Some(error_response(
+            ApiErrorCode::InvalidRequestId,
             "request_id is required",
         ));
     }
@@ -144,15 +255,15 @@ fn validate_envelope_shape(request: &AssistantQueryRequest) -> Option<Response>
     {
         Ok(bytes) => bytes,
         Err(_) => {
-            return Some(bad_request_response(
-                "invalid_client_public_key",
+            return Some(error_response(
+                ApiErrorCode::InvalidClientPublicKey,
                 "client_ephemeral_public_key must be valid base64",
             ));
         }
     };
     if client_public_key.len() != 32 {
-        return Some(bad_request_response(
-            "invalid_client_public_key",
+        return Some(error_response(
+            ApiErrorCode::InvalidClientPublicKey,
             "client_ephemeral_public_key must decode to 32 bytes",
         ));
     }
@@ -160,15 +271,15 @@ fn validate_envelope_shape(request: &AssistantQueryRequest) -> Option<Response>
     let nonce = match base64::engine::general_purpose::STANDARD.decode(envelope.nonce.as_bytes()) {
         Ok(bytes) => bytes,
         Err(_) => {
-            return Some(bad_request_response(
-                "invalid_nonce",
+            return Some(error_response(
+                ApiErrorCode::InvalidNonce,
                 "nonce must be valid base64",
             ));
         }
     };
     if nonce.len() != 12 {
-        return Some(bad_request_response(
-            "invalid_nonce",
+        return Some(error_response(
+            ApiErrorCode::InvalidNonce,
             "nonce must decode to 12 bytes",
         ));
     }
@@ -177,8 +288,8 @@ fn validate_envelope_shape(request: &AssistantQueryRequest) -> Option<Response>
         .decode(envelope.ciphertext.as_bytes())
         .is_err()
     {
-        return Some(bad_request_response(
-            "invalid_ciphertext",
+        return Some(error_response(
+            ApiErrorCode::InvalidCiphertext,
             "ciphertext must be valid base64",
         ));
     }
@@ -199,8 +310,8 @@ fn map_assistant_enclave_error(
                 code = %code,
                 "assistant query rejected by enclave contract"
             );
-            bad_request_response(
-                "invalid_enclave_request",
+            error_response(
+                ApiErrorCode::InvalidEnclaveRequest,
                 "Encrypted assistant request rejected",
             )
         }
@@ -211,7 +322,10 @@ fn map_assistant_enclave_error(
                 code = %code,
                 "assistant query unauthorized by enclave RPC"
             );
-            bad_gateway_response("enclave_rpc_failed", "Secure enclave RPC request failed")
+            error_response(
+                ApiErrorCode::EnclaveRpcFailed,
+                "Secure enclave RPC request failed",
+            )
         }
         EnclaveRpcError::RpcTransportUnavailable { message: _ } => {
             warn!(
@@ -219,7 +333,10 @@ fn map_assistant_enclave_error(
                 assistant_request_id,
                 "assistant query enclave RPC transport unavailable"
             );
-            bad_gateway_response("enclave_rpc_failed", "Secure enclave RPC request failed")
+            error_response(
+                ApiErrorCode::EnclaveRpcFailed,
+                "Secure enclave RPC request failed",
+            )
         }
         EnclaveRpcError::RpcResponseInvalid { message: _ } => {
             warn!(
@@ -227,7 +344,10 @@ fn map_assistant_enclave_error(
                 assistant_request_id,
                 "assistant query enclave RPC response invalid"
             );
-            bad_gateway_response("enclave_rpc_failed", "Secure enclave RPC request failed")
+            error_response(
+                ApiErrorCode::EnclaveRpcFailed,
+                "Secure enclave RPC request failed",
+            )
         }
         EnclaveRpcError::DecryptNotAuthorized { message: _ } => {
             warn!(
@@ -235,7 +355,13 @@ fn map_assistant_enclave_error(
                 assistant_request_id,
                 "assistant query token decrypt not authorized"
             );
-            bad_gateway_response("enclave_rpc_failed", "Secure enclave RPC request failed")
+            flag_anomaly(
+                error_response(
+                    ApiErrorCode::EnclaveRpcFailed,
+                    "Secure enclave RPC request failed",
+                ),
+                AnomalySignal::AttestationFailure,
+            )
         }
         EnclaveRpcError::ConnectorTokenDecryptFailed { message: _ } => {
             warn!(
@@ -243,7 +369,10 @@ fn map_assistant_enclave_error(
                 assistant_request_id,
                 "assistant query connector token decrypt failed"
             );
-            bad_gateway_response("enclave_rpc_failed", "Secure enclave RPC request failed")
+            error_response(
+                ApiErrorCode::EnclaveRpcFailed,
+                "Secure enclave RPC request failed",
+            )
         }
         EnclaveRpcError::ConnectorTokenUnavailable => {
             warn!(
@@ -251,8 +380,8 @@ fn map_assistant_enclave_error(
                 assistant_request_id,
                 "assistant query connector token unavailable"
             );
-            bad_request_response(
-                "connector_token_unavailable",
+            error_response(
+                ApiErrorCode::ConnectorTokenUnavailable,
                 "Google connector is not active for this account; reconnect Google and retry",
             )
         }
@@ -266,7 +395,10 @@ fn map_assistant_enclave_error(
                 operation = %operation,
                 "assistant query provider request unavailable"
             );
-            bad_gateway_response("enclave_rpc_failed", "Secure enclave RPC request failed")
+            error_response(
+                ApiErrorCode::EnclaveRpcFailed,
+                "Secure enclave RPC request failed",
+            )
         }
         EnclaveRpcError::ProviderRequestFailed {
             operation,
@@ -280,7 +412,10 @@ fn map_assistant_enclave_error(
                 status,
                 "assistant query provider request failed"
             );
-            bad_gateway_response("enclave_rpc_failed", "Secure enclave RPC request failed")
+            error_response(
+                ApiErrorCode::EnclaveRpcFailed,
+                "Secure enclave RPC request failed",
+            )
         }
         EnclaveRpcError::ProviderResponseInvalid {
             operation,
@@ -292,7 +427,36 @@ fn map_assistant_enclave_error(
                 operation = %operation,
                 "assistant query provider response invalid"
             );
-            bad_gateway_response("enclave_rpc_failed", "Secure enclave RPC request failed")
+            error_response(
+                ApiErrorCode::EnclaveRpcFailed,
+                "Secure enclave RPC request failed",
+            )
+        }
+        EnclaveRpcError::ProviderRateLimited {
+            operation,
+            retry_after_ms,
+        } => {
+            warn!(
+                %user_id,
+                assistant_request_id,
+                operation = %operation,
+                retry_after_ms,
+                "assistant query provider call rate limited"
+            );
+            provider_rate_limited_response(retry_after_ms)
+        }
+        EnclaveRpcError::LlmBudgetExceeded { message: _ } => {
+            warn!(
+                %user_id,
+                assistant_request_id,
+                "assistant query refused by monthly llm spend budget"
+            );
+            let now = Utc::now();
+            quota_exceeded_response(
+                ApiErrorCode::BudgetExceeded,
+                "Monthly AI usage budget reached for your plan",
+                (llm_spend_month_reset(now) - now).num_seconds().max(1) as u64,
+            )
         }
     }
 }
@@ -1,68 +1,159 @@
 use axum::Json;
-use axum::extract::{Extension, Path, State};
+use axum::extract::{Extension, Path, Query, State};
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
+use base64::Engine as _;
 use chrono::Utc;
+use shared::assistant_crypto::{
+    ASSISTANT_ENVELOPE_VERSION_V1, ASSISTANT_SESSION_TITLE_ALGORITHM_CHACHA20POLY1305,
+};
 use shared::models::{
-    AssistantSessionSummary, ErrorBody, ErrorResponse, ListAssistantSessionsResponse, OkResponse,
+    ApiErrorCode, AssistantSessionSummary, AssistantSessionTitleEnvelope,
+    ListAssistantSessionsResponse, OkResponse, UpdateAssistantSessionRequest,
+};
+use shared::pagination::CursorResource;
+use shared::repos::{
+    AssistantEncryptedSessionMetadataRecord, AssistantEncryptedSessionMetadataUpdate,
 };
 use uuid::Uuid;
 
-use super::super::errors::store_error_response;
+use super::super::errors::{error_response, store_error_response};
+use super::super::openapi::ApiOperation;
+use super::super::pagination::{PageLimits, next_cursor, page_request};
+use super::super::request_body::ApiJson;
 use super::super::{AppState, AuthUser};
 
-const ASSISTANT_SESSIONS_LIST_LIMIT: i64 = 200;
+const ASSISTANT_SESSION_PAGE_LIMITS: PageLimits = PageLimits {
+    default: 200,
+    max: 200,
+};
+const ASSISTANT_SESSION_TITLE_MAX_KEY_ID_CHARS: usize = 128;
+/// Sealed title bytes, including the 16-byte AEAD tag.
+const ASSISTANT_SESSION_TITLE_MAX_CIPHERTEXT_BYTES: usize = 1024;
+
+#[derive(serde::Deserialize)]
+pub(crate) struct ListAssistantSessionsQuery {
+    cursor: Option<String>,
+    limit: Option<i64>,
+}
+
+pub(crate) const LIST_ASSISTANT_SESSIONS: ApiOperation = ApiOperation::get(
+    "/v1/assistant/sessions",
+    "listAssistantSessions",
+    "Assistant",
+    "List assistant thread sessions for the current user",
+)
+.response::<ListAssistantSessionsResponse>();
 
 pub(crate) async fn list_assistant_sessions(
     State(state): State<AppState>,
     Extension(user): Extension<AuthUser>,
+    Query(query): Query<ListAssistantSessionsQuery>,
 ) -> Response {
+    let page = match page_request(
+        &state,
+        CursorResource::AssistantSessions,
+        query.cursor.as_deref(),
+        query.limit,
+        ASSISTANT_SESSION_PAGE_LIMITS,
+    ) {
+        Ok(page) => page,
+        Err(err) => return err.into_response(),
+    };
+
     let now = Utc::now();
     let sessions = match state
         .store
-        .list_assistant_encrypted_sessions(user.user_id, now, ASSISTANT_SESSIONS_LIST_LIMIT)
+        .list_assistant_encrypted_sessions(user.user_id, now, page)
         .await
     {
         Ok(sessions) => sessions,
         Err(err) => return store_error_response(err),
     };
 
-    let items = sessions
-        .into_iter()
-        .map(|session| AssistantSessionSummary {
-            session_id: session.session_id,
-            created_at: session.created_at,
-            updated_at: session.updated_at,
-            expires_at: session.expires_at,
-        })
-        .collect();
+    let next_cursor = next_cursor(&state, CursorResource::AssistantSessions, &sessions);
+    let items = sessions.map(session_summary).items;
 
     (
         StatusCode::OK,
-        Json(ListAssistantSessionsResponse { items }),
+        Json(ListAssistantSessionsResponse { items, next_cursor }),
     )
         .into_response()
 }
 
-pub(crate) async fn delete_assistant_session(
+pub(crate) const UPDATE_ASSISTANT_SESSION: ApiOperation = ApiOperation::patch(
+    "/v1/assistant/sessions/{session_id}",
+    "updateAssistantSession",
+    "Assistant",
+    "Set the encrypted title or pinned flag of an assistant thread session",
+)
+.request::<UpdateAssistantSessionRequest>()
+.response::<AssistantSessionSummary>();
+
+pub(crate) async fn update_assistant_session(
     State(state): State<AppState>,
     Extension(user): Extension<AuthUser>,
     Path(session_id): Path<String>,
+    ApiJson(request): ApiJson<UpdateAssistantSessionRequest>,
 ) -> Response {
     let session_id = match Uuid::parse_str(&session_id) {
         Ok(session_id) => session_id,
-        Err(_) => {
-            return (
-                StatusCode::NOT_FOUND,
-                Json(ErrorResponse {
-                    error: ErrorBody {
-                        code: "not_found".to_string(),
-                        message: "Assistant session not found".to_string(),
-                    },
-                }),
-            )
-                .into_response();
+        Err(_) => return assistant_session_not_found_response(),
+    };
+
+    let title_envelope = match (request.title_envelope, request.clear_title) {
+        (Some(_), true) => {
+            return error_response(
+                ApiErrorCode::InvalidSessionUpdate,
+                "title_envelope and clear_title cannot be combined",
+            );
+        }
+        (Some(envelope), false) => {
+            if let Err((code, message)) = validate_title_envelope(&envelope) {
+                return error_response(code, message);
+            }
+            Some(Some(envelope))
         }
+        (None, true) => Some(None),
+        (None, false) => None,
+    };
+    if title_envelope.is_none() && request.pinned.is_none() {
+        return error_response(
+            ApiErrorCode::InvalidSessionUpdate,
+            "Provide at least one update field: title_envelope, clear_title, or pinned",
+        );
+    }
+
+    let update = AssistantEncryptedSessionMetadataUpdate {
+        title_envelope,
+        pinned: request.pinned,
+    };
+    match state
+        .store
+        .update_assistant_encrypted_session_metadata(user.user_id, session_id, &update, Utc::now())
+        .await
+    {
+        Ok(Some(session)) => (StatusCode::OK, Json(session_summary(session))).into_response(),
+        Ok(None) => assistant_session_not_found_response(),
+        Err(err) => store_error_response(err),
+    }
+}
+
+pub(crate) const DELETE_ASSISTANT_SESSION: ApiOperation = ApiOperation::delete(
+    "/v1/assistant/sessions/{session_id}",
+    "deleteAssistantSession",
+    "Assistant",
+    "Delete a single assistant thread session",
+);
+
+pub(crate) async fn delete_assistant_session(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(session_id): Path<String>,
+) -> Response {
+    let session_id = match Uuid::parse_str(&session_id) {
+        Ok(session_id) => session_id,
+        Err(_) => return assistant_session_not_found_response(),
     };
 
     let deleted = match state
@@ -78,18 +169,16 @@ pub(crate) async fn delete_assistant_session(
         return (StatusCode::OK, Json(OkResponse { ok: true })).into_response();
     }
 
-    (
-        StatusCode::NOT_FOUND,
-        Json(ErrorResponse {
-            error: ErrorBody {
-                code: "not_found".to_string(),
-                message: "Assistant session not found".to_string(),
-            },
-        }),
-    )
-        .into_response()
+    assistant_session_not_found_response()
 }
 
+pub(crate) const DELETE_ALL_ASSISTANT_SESSIONS: ApiOperation = ApiOperation::delete(
+    "/v1/assistant/sessions",
+    "deleteAllAssistantSessions",
+    "Assistant",
+    "Delete all assistant thread sessions for the current user",
+);
+
 pub(crate) async fn delete_all_assistant_sessions(
     State(state): State<AppState>,
     Extension(user): Extension<AuthUser>,
@@ -103,3 +192,69 @@ pub(crate) async fn delete_all_assistant_sessions(
         Err(err) => store_error_response(err),
     }
 }
+
+fn session_summary(session: AssistantEncryptedSessionMetadataRecord) -> AssistantSessionSummary {
+    AssistantSessionSummary {
+        session_id: session.session_id,
+        created_at: session.created_at,
+        updated_at: session.updated_at,
+        expires_at: session.expires_at,
+        title_envelope: session.title_envelope,
+        pinned: session.pinned,
+    }
+}
+
+/// Checks the envelope's shape only; the title itself is sealed with a key the host never has.
+fn validate_title_envelope(
+    envelope: &AssistantSessionTitleEnvelope,
+) -> Result<(), (ApiErrorCode, &'static str)> {
+    if envelope.version != ASSISTANT_ENVELOPE_VERSION_V1 {
+        return Err((
+            ApiErrorCode::InvalidEnvelopeVersion,
+            "title envelope version is not supported",
+        ));
+    }
+    if envelope.algorithm != ASSISTANT_SESSION_TITLE_ALGORITHM_CHACHA20POLY1305 {
+        return Err((
+            ApiErrorCode::InvalidEnvelopeAlgorithm,
+            "title envelope algorithm is not supported",
+        ));
+    }
+
+    let key_id = envelope.key_id.trim();
+    if key_id.is_empty() || key_id.len() > ASSISTANT_SESSION_TITLE_MAX_KEY_ID_CHARS {
+        return Err((
+            ApiErrorCode::InvalidKeyId,
+            "key_id is required and must be at most 128 characters",
+        ));
+    }
+
+    let base64 = base64::engine::general_purpose::STANDARD;
+    if !base64
+        .decode(envelope.nonce.as_bytes())
+        .is_ok_and(|nonce| nonce.len() == 12)
+    {
+        return Err((
+            ApiErrorCode::InvalidNonce,
+            "nonce must be base64 of 12 bytes",
+        ));
+    }
+    if !base64
+        .decode(envelope.ciphertext.as_bytes())
+        .is_ok_and(|ciphertext| {
+            !ciphertext.is_empty()
+                && ciphertext.len() <= ASSISTANT_SESSION_TITLE_MAX_CIPHERTEXT_BYTES
+        })
+    {
+        return Err((
+            ApiErrorCode::InvalidCiphertext,
+            "ciphertext must be non-empty base64 of at most 1024 bytes",
+        ));
+    }
+
+    Ok(())
+}
+
+fn assistant_session_not_found_response() -> Response {
+    error_response(ApiErrorCode::NotFound, "Assistant session not found")
+}
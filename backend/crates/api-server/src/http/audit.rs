@@ -1,32 +1,305 @@
 use axum::Json;
-use axum::extract::{Extension, Query, State};
-use axum::http::StatusCode;
+use axum::body::Body;
+use axum::extract::{Extension, Path, Query, State};
+use axum::http::{HeaderValue, StatusCode, header};
 use axum::response::{IntoResponse, Response};
-use shared::models::ListAuditEventsResponse;
+use futures_util::stream;
+use shared::audit_signing::{AuditEventSigningPayload, verify_audit_event_signature};
+use shared::models::{
+    ApiErrorCode, AuditEvent, AuditEventSignatureStatus, AuditEventSignatureVerification,
+    AuditMetadata, ListAuditEventsResponse,
+};
+use shared::pagination::CursorResource;
+use shared::repos::{AuditEventSignatureRecord, AuditResult};
+use tracing::warn;
+use uuid::Uuid;
 
-use super::errors::store_error_response;
+use super::errors::{error_response, store_error_response};
+use super::openapi::ApiOperation;
+use super::pagination::{PageLimits, next_cursor, page_request};
 use super::{AppState, AuthUser};
 
+const CSV_HEADER: &str = "id,timestamp,event_type,connector,result,metadata\n";
+const AUDIT_EVENT_PAGE_LIMITS: PageLimits = PageLimits {
+    default: 50,
+    max: 100,
+};
+
 #[derive(serde::Deserialize)]
 pub(super) struct AuditEventsQuery {
     cursor: Option<String>,
+    limit: Option<i64>,
+}
+
+#[derive(serde::Deserialize)]
+pub(super) struct AuditExportQuery {
+    format: Option<String>,
 }
 
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum AuditExportFormat {
+    Ndjson,
+    Csv,
+}
+
+impl AuditExportFormat {
+    fn parse(value: Option<&str>) -> Option<Self> {
+        match value.map(str::trim) {
+            None | Some("ndjson") => Some(Self::Ndjson),
+            Some("csv") => Some(Self::Csv),
+            Some(_) => None,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Ndjson => "ndjson",
+            Self::Csv => "csv",
+        }
+    }
+
+    fn content_type(self) -> &'static str {
+        match self {
+            Self::Ndjson => "application/x-ndjson",
+            Self::Csv => "text/csv; charset=utf-8",
+        }
+    }
+}
+
+pub(super) const LIST_AUDIT_EVENTS: ApiOperation = ApiOperation::get(
+    "/v1/audit-events",
+    "listAuditEvents",
+    "Audit",
+    "List redacted audit events",
+)
+.response::<ListAuditEventsResponse>();
+
 pub(super) async fn list_audit_events(
     State(state): State<AppState>,
     Extension(user): Extension<AuthUser>,
     Query(query): Query<AuditEventsQuery>,
 ) -> Response {
-    match state
-        .store
-        .list_audit_events(user.user_id, query.cursor.as_deref(), 50)
-        .await
-    {
-        Ok((items, next_cursor)) => (
+    let page = match page_request(
+        &state,
+        CursorResource::AuditEvents,
+        query.cursor.as_deref(),
+        query.limit,
+        AUDIT_EVENT_PAGE_LIMITS,
+    ) {
+        Ok(page) => page,
+        Err(err) => return err.into_response(),
+    };
+
+    match state.store.list_audit_events(user.user_id, page).await {
+        Ok(page) => (
             StatusCode::OK,
-            Json(ListAuditEventsResponse { items, next_cursor }),
+            Json(ListAuditEventsResponse {
+                next_cursor: next_cursor(&state, CursorResource::AuditEvents, &page),
+                items: page.items,
+            }),
         )
             .into_response(),
         Err(err) => store_error_response(err),
     }
 }
+
+pub(super) const EXPORT_AUDIT_EVENTS: ApiOperation = ApiOperation::get(
+    "/v1/audit-events/export",
+    "exportAuditEvents",
+    "Audit",
+    "Export full redacted audit history",
+)
+.ndjson_or_csv_response::<AuditEvent>();
+
+pub(super) async fn export_audit_events(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Query(query): Query<AuditExportQuery>,
+) -> Response {
+    let Some(format) = AuditExportFormat::parse(query.format.as_deref()) else {
+        return error_response(
+            ApiErrorCode::InvalidFormat,
+            "format must be one of: ndjson, csv",
+        );
+    };
+
+    let mut metadata = AuditMetadata::new();
+    metadata.insert("format".to_string(), format.as_str().into());
+    if let Err(err) = state
+        .store
+        .add_audit_event(
+            user.user_id,
+            "AUDIT_EXPORT_REQUESTED",
+            None,
+            AuditResult::Success,
+            &metadata,
+        )
+        .await
+    {
+        return store_error_response(err);
+    }
+
+    // Batches are fetched only when the client polls for more body, so a slow reader
+    // throttles the export instead of buffering the whole history in memory.
+    let user_id = user.user_id;
+    let events = state.store.stream_audit_events(user_id);
+    let body = stream::try_unfold(
+        (events, format == AuditExportFormat::Csv),
+        move |(mut events, mut needs_header)| async move {
+            let batch = match events.next_batch().await {
+                Ok(Some(batch)) => batch,
+                Ok(None) => return Ok(None),
+                Err(err) => {
+                    warn!(user_id = %user_id, "audit export aborted: {err}");
+                    return Err(err);
+                }
+            };
+
+            let mut chunk = String::new();
+            if needs_header {
+                chunk.push_str(CSV_HEADER);
+                needs_header = false;
+            }
+            for event in &batch {
+                match format {
+                    AuditExportFormat::Ndjson => {
+                        chunk.push_str(
+                            &serde_json::to_string(event).expect("audit event should serialize"),
+                        );
+                        chunk.push('\n');
+                    }
+                    AuditExportFormat::Csv => push_csv_row(&mut chunk, event),
+                }
+            }
+
+            Ok(Some((chunk, (events, needs_header))))
+        },
+    );
+
+    let mut response = Response::new(Body::from_stream(body));
+    let headers = response.headers_mut();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static(format.content_type()),
+    );
+    if let Ok(disposition) = HeaderValue::from_str(&format!(
+        "attachment; filename=\"audit-events.{}\"",
+        format.as_str()
+    )) {
+        headers.insert(header::CONTENT_DISPOSITION, disposition);
+    }
+    headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+    response
+}
+
+pub(super) const VERIFY_AUDIT_EVENT: ApiOperation = ApiOperation::get(
+    "/v1/audit-events/{event_id}/verification",
+    "verifyAuditEvent",
+    "Audit",
+    "Check that an audit event was signed by the enclave and is unchanged",
+)
+.response::<AuditEventSignatureVerification>();
+
+pub(super) async fn verify_audit_event(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(event_id): Path<String>,
+) -> Response {
+    let Ok(event_id) = Uuid::parse_str(&event_id) else {
+        return audit_event_not_found_response();
+    };
+
+    let record = match state
+        .store
+        .get_audit_event_signature(user.user_id, event_id)
+        .await
+    {
+        Ok(Some(record)) => record,
+        Ok(None) => return audit_event_not_found_response(),
+        Err(err) => return store_error_response(err),
+    };
+
+    let verification = audit_event_verification(
+        user.user_id,
+        record,
+        state.secret_runtime.attestation_public_key(),
+    );
+    (StatusCode::OK, Json(verification)).into_response()
+}
+
+fn audit_event_verification(
+    user_id: Uuid,
+    record: AuditEventSignatureRecord,
+    attestation_public_key: Option<&str>,
+) -> AuditEventSignatureVerification {
+    let payload = record
+        .entry_hash
+        .clone()
+        .map(|entry_hash| AuditEventSigningPayload {
+            event_id: record.event_id,
+            user_id,
+            event_type: record.event_type.clone(),
+            entry_hash,
+        });
+    let status = match (record.signature.as_deref(), payload.as_ref()) {
+        (None, _) | (_, None) => AuditEventSignatureStatus::Unsigned,
+        _ if !record.hash_matches => AuditEventSignatureStatus::HashMismatch,
+        (Some(signature), Some(payload)) => match attestation_public_key {
+            None => AuditEventSignatureStatus::Unverifiable,
+            Some(public_key) => {
+                match verify_audit_event_signature(public_key, signature, payload) {
+                    Ok(()) => AuditEventSignatureStatus::Verified,
+                    Err(_) => AuditEventSignatureStatus::InvalidSignature,
+                }
+            }
+        },
+    };
+
+    AuditEventSignatureVerification {
+        event_id: record.event_id.to_string(),
+        event_type: record.event_type,
+        status,
+        entry_hash: record.entry_hash,
+        signed_payload: record
+            .signature
+            .as_ref()
+            .and(payload.as_ref())
+            .map(AuditEventSigningPayload::canonical),
+        signature: record.signature,
+    }
+}
+
+fn audit_event_not_found_response() -> Response {
+    error_response(ApiErrorCode::NotFound, "audit event not found")
+}
+
+fn push_csv_row(chunk: &mut String, event: &AuditEvent) {
+    let metadata = serde_json::to_string(&event.metadata).expect("audit metadata should serialize");
+    let fields = [
+        event.id.as_str(),
+        &event.timestamp.to_rfc3339(),
+        event.event_type.as_str(),
+        event.connector.as_deref().unwrap_or(""),
+        event.result.as_str(),
+        metadata.as_str(),
+    ];
+
+    for (index, field) in fields.iter().enumerate() {
+        if index > 0 {
+            chunk.push(',');
+        }
+        push_csv_field(chunk, field);
+    }
+    chunk.push('\n');
+}
+
+fn push_csv_field(chunk: &mut String, field: &str) {
+    if !field.contains([',', '"', '\n', '\r']) {
+        chunk.push_str(field);
+        return;
+    }
+
+    chunk.push('"');
+    chunk.push_str(&field.replace('"', "\"\""));
+    chunk.push('"');
+}
@@ -6,7 +6,7 @@ use tracing::warn;
 use uuid::Uuid;
 
 use super::clerk_identity::{ClerkIdentityError, verify_identity_token};
-use super::errors::{bad_gateway_response, store_error_response, unauthorized_response};
+use super::errors::{error_response, store_error_response, unauthorized_response};
 use super::{AppState, AuthUser};
 
 const CLERK_SUBJECT_NAMESPACE: Uuid = Uuid::from_u128(0x10850be7d81f4f4ea2dc0bb96943a09e);
@@ -49,19 +49,36 @@ pub(super) async fn auth_middleware(
             return unauthorized_response();
         }
         Err(ClerkIdentityError::UpstreamUnavailable { code, message }) => {
-            warn!("clerk auth upstream unavailable: code={code}, message={message}");
-            return bad_gateway_response(code, message);
+            warn!(
+                "clerk auth upstream unavailable: code={}, message={message}",
+                code.as_str()
+            );
+            return error_response(code, message);
         }
     };
 
     let user_id = user_id_for_clerk_subject(&state.clerk_issuer, &identity.subject);
-    match state.store.ensure_user(user_id).await {
-        Ok(()) => {}
-        Err(err) => return store_error_response(err),
+    // The session's active organization is the user's membership; a session without one
+    // clears it, so removal from a Clerk organization takes effect on the next request.
+    let ensured = state
+        .store
+        .ensure_user_organization(
+            user_id,
+            identity
+                .organization
+                .as_ref()
+                .map(|organization| organization.id.as_str()),
+        )
+        .await;
+    if let Err(err) = ensured {
+        return store_error_response(err);
     }
 
     req.extensions_mut().insert(AuthUser { user_id });
-    next.run(req).await
+    let mut response = next.run(req).await;
+    // Lets the anomaly middleware outside this layer attribute failures to the account.
+    response.extensions_mut().insert(AuthUser { user_id });
+    response
 }
 
 fn user_id_for_clerk_subject(issuer: &str, subject: &str) -> Uuid {
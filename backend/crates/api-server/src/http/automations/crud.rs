@@ -0,0 +1,574 @@
+use axum::Json;
+use axum::extract::{Extension, Path, Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use chrono::Utc;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use shared::automation_schedule::next_run_after;
+use shared::events::DomainEvent;
+use shared::models::{
+    ApiErrorCode, AutomationRuleSummary, AutomationStatus, CreateAutomationRequest,
+    ListAutomationReportsResponse, ListAutomationsResponse, OkResponse, QuotaKind,
+    UpdateAutomationRequest,
+};
+use shared::pagination::CursorResource;
+use shared::repos::{
+    AutomationPromptMaterial, AutomationRuleOptions,
+    AutomationRuleStatus as RepoAutomationRuleStatus, PromptEnvelopeUse,
+};
+use uuid::Uuid;
+
+use super::super::abuse::AbuseSignal;
+use super::super::concurrency::expected_version;
+use super::super::errors::{error_response, event_publish_error_response};
+use super::super::openapi::ApiOperation;
+use super::super::pagination::{PageLimits, next_cursor, page_request};
+use super::super::prompt_envelopes::claim_prompt_envelope_request_id;
+use super::super::quota::enforce_plan_quota;
+use super::super::request_body::ApiJson;
+use super::super::{AppState, AuthUser};
+use super::helpers::{
+    automation_conflict_response, automation_not_found_response, automation_report_summary,
+    automation_rule_summary, automation_store_error_response, is_near_max_size_prompt,
+    record_abuse_signal, validated_condition, validated_prompt_payload,
+    validated_schedule_and_next_run, validated_template_schedule, validated_title,
+};
+
+const AUTOMATION_PAGE_LIMITS: PageLimits = PageLimits {
+    default: 50,
+    max: 200,
+};
+const AUTOMATION_REPORT_PAGE_LIMITS: PageLimits = PageLimits {
+    default: 20,
+    max: 100,
+};
+const MAX_DEVICE_ID_CHARS: usize = 128;
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ListAutomationsQuery {
+    pub(super) cursor: Option<String>,
+    pub(super) limit: Option<i64>,
+    pub(super) include_archived: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ListAutomationReportsQuery {
+    pub(super) device_id: String,
+    pub(super) cursor: Option<String>,
+    pub(super) limit: Option<i64>,
+}
+
+pub(crate) const CREATE_AUTOMATION: ApiOperation = ApiOperation::post(
+    "/v1/automations",
+    "createAutomation",
+    "Automations",
+    "Create a periodic automation rule",
+)
+.request::<CreateAutomationRequest>()
+.response::<AutomationRuleSummary>();
+
+pub(crate) async fn create_automation(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    ApiJson(request): ApiJson<CreateAutomationRequest>,
+) -> Response {
+    let title = match validated_title(request.title.as_str()) {
+        Ok(title) => title,
+        Err((code, message)) => return error_response(code, message),
+    };
+    let prompt_payload = match validated_prompt_payload(&request.prompt_envelope) {
+        Ok(payload) => payload,
+        Err((code, message)) => return error_response(code, message),
+    };
+    let now = Utc::now();
+    let (schedule, next_run_at) = match validated_schedule_and_next_run(&request.schedule, now) {
+        Ok(value) => value,
+        Err((code, message)) => return error_response(code, message),
+    };
+    if let Err((code, message)) =
+        validated_template_schedule(request.template, schedule.schedule_type)
+    {
+        return error_response(code, message);
+    }
+    if let Err((code, message)) = validated_condition(request.condition) {
+        return error_response(code, message);
+    }
+    if let Err(response) = claim_prompt_envelope_request_id(
+        &state,
+        user.user_id,
+        &request.prompt_envelope.key_id,
+        &request.prompt_envelope.request_id,
+        PromptEnvelopeUse::AutomationCreate,
+    )
+    .await
+    {
+        return response;
+    }
+    let prompt = AutomationPromptMaterial {
+        prompt_sha256: format!("{:x}", Sha256::digest(&prompt_payload)),
+        prompt_ciphertext: prompt_payload,
+    };
+
+    let created_rule = match state
+        .store
+        .create_automation_rule(
+            user.user_id,
+            &title,
+            AutomationRuleOptions {
+                template: request.template,
+                condition: request.condition,
+            },
+            &schedule,
+            next_run_at,
+            &prompt,
+        )
+        .await
+    {
+        Ok(rule) => rule,
+        Err(err) => return automation_store_error_response(err),
+    };
+
+    record_abuse_signal(&state, user.user_id, AbuseSignal::AutomationCreateSpike).await;
+    if is_near_max_size_prompt(&request.prompt_envelope) {
+        record_abuse_signal(&state, user.user_id, AbuseSignal::MaxSizePromptEnvelope).await;
+    }
+
+    if let Err(err) = state
+        .events
+        .publish(
+            user.user_id,
+            DomainEvent::AutomationRuleCreated {
+                rule_id: created_rule.id,
+                title: created_rule.title.clone(),
+                schedule_type: created_rule.schedule_type,
+                time_zone: created_rule.time_zone.clone(),
+                template: created_rule.template,
+                local_time_minutes: u16::try_from(created_rule.local_time_minutes).unwrap_or(0),
+            },
+        )
+        .await
+    {
+        return event_publish_error_response(err);
+    }
+
+    (StatusCode::OK, Json(automation_rule_summary(created_rule))).into_response()
+}
+
+pub(crate) const LIST_AUTOMATIONS: ApiOperation = ApiOperation::get(
+    "/v1/automations",
+    "listAutomations",
+    "Automations",
+    "List automation rules for the current user",
+)
+.response::<ListAutomationsResponse>();
+
+pub(crate) async fn list_automations(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Query(query): Query<ListAutomationsQuery>,
+) -> Response {
+    let page = match page_request(
+        &state,
+        CursorResource::AutomationRules,
+        query.cursor.as_deref(),
+        query.limit,
+        AUTOMATION_PAGE_LIMITS,
+    ) {
+        Ok(page) => page,
+        Err(err) => return err.into_response(),
+    };
+
+    let include_archived = query.include_archived.unwrap_or(false);
+    let rules = match state
+        .store
+        .list_automation_rules(user.user_id, include_archived, page)
+        .await
+    {
+        Ok(rules) => rules,
+        Err(err) => return automation_store_error_response(err),
+    };
+
+    let next_cursor = next_cursor(&state, CursorResource::AutomationRules, &rules);
+    let items = rules.map(automation_rule_summary).items;
+    (
+        StatusCode::OK,
+        Json(ListAutomationsResponse { items, next_cursor }),
+    )
+        .into_response()
+}
+
+pub(crate) const UPDATE_AUTOMATION: ApiOperation = ApiOperation::patch(
+    "/v1/automations/{rule_id}",
+    "updateAutomation",
+    "Automations",
+    "Update an automation rule (schedule, prompt envelope, or status)",
+)
+.request::<UpdateAutomationRequest>()
+.response::<AutomationRuleSummary>();
+
+pub(crate) async fn update_automation(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(rule_id): Path<String>,
+    headers: HeaderMap,
+    ApiJson(request): ApiJson<UpdateAutomationRequest>,
+) -> Response {
+    let rule_id = match Uuid::parse_str(&rule_id) {
+        Ok(rule_id) => rule_id,
+        Err(_) => return automation_not_found_response(),
+    };
+
+    let condition_update = match (request.condition, request.clear_condition) {
+        (Some(_), true) => {
+            return error_response(
+                ApiErrorCode::InvalidAutomationCondition,
+                "condition and clear_condition cannot be combined",
+            );
+        }
+        (Some(condition), false) => {
+            if let Err((code, message)) = validated_condition(Some(condition)) {
+                return error_response(code, message);
+            }
+            Some(Some(condition))
+        }
+        (None, true) => Some(None),
+        (None, false) => None,
+    };
+    if request.title.is_none()
+        && request.schedule.is_none()
+        && request.prompt_envelope.is_none()
+        && request.status.is_none()
+        && condition_update.is_none()
+    {
+        return error_response(
+            ApiErrorCode::InvalidAutomationUpdate,
+            "Provide at least one update field: title, schedule, prompt_envelope, status, condition, or clear_condition",
+        );
+    }
+    let expected_version = match expected_version(&headers, request.expected_version) {
+        Ok(expected_version) => expected_version,
+        Err(err) => return err.into_response(),
+    };
+
+    let mut rule = match state.store.get_automation_rule(user.user_id, rule_id).await {
+        Ok(Some(rule)) => rule,
+        Ok(None) => return automation_not_found_response(),
+        Err(err) => return automation_store_error_response(err),
+    };
+
+    // Archived rules only accept a status change that restores them.
+    if matches!(rule.status, RepoAutomationRuleStatus::Archived)
+        && !matches!(
+            request.status,
+            Some(AutomationStatus::Active | AutomationStatus::Paused)
+        )
+        && (request.title.is_some()
+            || request.schedule.is_some()
+            || request.prompt_envelope.is_some()
+            || condition_update.is_some())
+    {
+        return error_response(
+            ApiErrorCode::AutomationArchived,
+            "Restore the automation by setting status to ACTIVE or PAUSED before editing it",
+        );
+    }
+
+    if matches!(request.status, Some(AutomationStatus::Completed)) {
+        return error_response(
+            ApiErrorCode::InvalidAutomationUpdate,
+            "COMPLETED is set by the scheduler after a ONCE automation runs",
+        );
+    }
+
+    // Restoring counts against the plan's automation cap again.
+    if matches!(
+        rule.status,
+        RepoAutomationRuleStatus::Archived | RepoAutomationRuleStatus::Completed
+    ) && matches!(
+        request.status,
+        Some(AutomationStatus::Active | AutomationStatus::Paused)
+    ) && let Err(response) =
+        enforce_plan_quota(&state, user.user_id, QuotaKind::AutomationRules).await
+    {
+        return response;
+    }
+
+    // Validate every field before claiming the version so a rejected update leaves it as is.
+    let title = match request.title.as_deref().map(validated_title).transpose() {
+        Ok(title) => title,
+        Err((code, message)) => return error_response(code, message),
+    };
+
+    let schedule_update = match request.schedule {
+        Some(schedule_update) => {
+            let (schedule, next_run_at) =
+                match validated_schedule_and_next_run(&schedule_update, Utc::now()) {
+                    Ok(value) => value,
+                    Err((code, message)) => return error_response(code, message),
+                };
+            if let Err((code, message)) =
+                validated_template_schedule(rule.template, schedule.schedule_type)
+            {
+                return error_response(code, message);
+            }
+            Some((schedule, next_run_at))
+        }
+        None => None,
+    };
+
+    let prompt_update = match request.prompt_envelope {
+        Some(prompt_envelope) => {
+            let prompt_payload = match validated_prompt_payload(&prompt_envelope) {
+                Ok(payload) => payload,
+                Err((code, message)) => return error_response(code, message),
+            };
+            if let Err(response) = claim_prompt_envelope_request_id(
+                &state,
+                user.user_id,
+                &prompt_envelope.key_id,
+                &prompt_envelope.request_id,
+                PromptEnvelopeUse::AutomationUpdate,
+            )
+            .await
+            {
+                return response;
+            }
+            if is_near_max_size_prompt(&prompt_envelope) {
+                record_abuse_signal(&state, user.user_id, AbuseSignal::MaxSizePromptEnvelope).await;
+            }
+            let prompt_sha256 = format!("{:x}", Sha256::digest(&prompt_payload));
+            Some((prompt_payload, prompt_sha256))
+        }
+        None => None,
+    };
+
+    match state
+        .store
+        .claim_automation_rule_version(user.user_id, rule_id, expected_version)
+        .await
+    {
+        Ok(Some(_)) => {}
+        Ok(None) => return automation_conflict_response(&state, user, rule_id).await,
+        Err(err) => return automation_store_error_response(err),
+    }
+
+    let mut changed_fields: Vec<&str> = Vec::new();
+
+    if let Some(title) = title {
+        rule = match state
+            .store
+            .update_automation_rule_title(user.user_id, rule_id, &title)
+            .await
+        {
+            Ok(Some(rule)) => rule,
+            Ok(None) => return automation_not_found_response(),
+            Err(err) => return automation_store_error_response(err),
+        };
+        changed_fields.push("title");
+    }
+
+    if let Some((schedule, next_run_at)) = schedule_update {
+        rule = match state
+            .store
+            .update_automation_rule_schedule(user.user_id, rule_id, &schedule, next_run_at)
+            .await
+        {
+            Ok(Some(rule)) => rule,
+            Ok(None) => return automation_not_found_response(),
+            Err(err) => return automation_store_error_response(err),
+        };
+        changed_fields.push("schedule");
+    }
+
+    if let Some((prompt_payload, prompt_sha256)) = prompt_update {
+        rule = match state
+            .store
+            .update_automation_rule_prompt(user.user_id, rule_id, &prompt_payload, &prompt_sha256)
+            .await
+        {
+            Ok(Some(rule)) => rule,
+            Ok(None) => return automation_not_found_response(),
+            Err(err) => return automation_store_error_response(err),
+        };
+        changed_fields.push("prompt");
+    }
+
+    if let Some(condition) = condition_update {
+        rule = match state
+            .store
+            .update_automation_rule_condition(user.user_id, rule_id, condition)
+            .await
+        {
+            Ok(Some(rule)) => rule,
+            Ok(None) => return automation_not_found_response(),
+            Err(err) => return automation_store_error_response(err),
+        };
+        changed_fields.push("condition");
+    }
+
+    if let Some(status) = request.status {
+        record_abuse_signal(&state, user.user_id, AbuseSignal::PauseResumeLoop).await;
+        match status {
+            AutomationStatus::Paused => {
+                match state
+                    .store
+                    .pause_automation_rule(user.user_id, rule_id)
+                    .await
+                {
+                    Ok(true) => {}
+                    Ok(false) => return automation_not_found_response(),
+                    Err(err) => return automation_store_error_response(err),
+                }
+                changed_fields.push("status");
+            }
+            AutomationStatus::Archived => {
+                match state
+                    .store
+                    .archive_automation_rule(user.user_id, rule_id)
+                    .await
+                {
+                    Ok(true) => {}
+                    Ok(false) => return automation_not_found_response(),
+                    Err(err) => return automation_store_error_response(err),
+                }
+                changed_fields.push("status");
+            }
+            // Rejected before any field is updated.
+            AutomationStatus::Completed => {}
+            AutomationStatus::Active => {
+                let schedule = match rule.schedule_spec() {
+                    Ok(schedule) => schedule,
+                    Err(err) => return automation_store_error_response(err),
+                };
+                let Some(next_run_at) = next_run_after(Utc::now(), &schedule) else {
+                    return error_response(
+                        ApiErrorCode::InvalidSchedule,
+                        "unable to compute next run for automation schedule",
+                    );
+                };
+
+                match state
+                    .store
+                    .resume_automation_rule(user.user_id, rule_id, next_run_at)
+                    .await
+                {
+                    Ok(true) => {}
+                    Ok(false) => return automation_not_found_response(),
+                    Err(err) => return automation_store_error_response(err),
+                }
+                changed_fields.push("status");
+            }
+        }
+
+        rule = match state.store.get_automation_rule(user.user_id, rule_id).await {
+            Ok(Some(rule)) => rule,
+            Ok(None) => return automation_not_found_response(),
+            Err(err) => return automation_store_error_response(err),
+        };
+    }
+
+    if !changed_fields.is_empty()
+        && let Err(err) = state
+            .events
+            .publish(
+                user.user_id,
+                DomainEvent::AutomationRuleUpdated {
+                    rule_id: rule.id,
+                    updated_fields: changed_fields.iter().map(ToString::to_string).collect(),
+                },
+            )
+            .await
+    {
+        return event_publish_error_response(err);
+    }
+
+    (StatusCode::OK, Json(automation_rule_summary(rule))).into_response()
+}
+
+pub(crate) const DELETE_AUTOMATION: ApiOperation = ApiOperation::delete(
+    "/v1/automations/{rule_id}",
+    "deleteAutomation",
+    "Automations",
+    "Delete an automation rule",
+);
+
+pub(crate) async fn delete_automation(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(rule_id): Path<String>,
+) -> Response {
+    let rule_id = match Uuid::parse_str(&rule_id) {
+        Ok(rule_id) => rule_id,
+        Err(_) => return automation_not_found_response(),
+    };
+
+    match state
+        .store
+        .delete_automation_rule(user.user_id, rule_id)
+        .await
+    {
+        Ok(true) => {}
+        Ok(false) => return automation_not_found_response(),
+        Err(err) => return automation_store_error_response(err),
+    }
+
+    if let Err(err) = state
+        .events
+        .publish(user.user_id, DomainEvent::AutomationRuleDeleted { rule_id })
+        .await
+    {
+        return event_publish_error_response(err);
+    }
+
+    (StatusCode::OK, Json(OkResponse { ok: true })).into_response()
+}
+
+pub(crate) const LIST_AUTOMATION_REPORTS: ApiOperation = ApiOperation::get(
+    "/v1/automation-reports",
+    "listAutomationReports",
+    "Automations",
+    "List encrypted automation reports delivered to a device",
+)
+.response::<ListAutomationReportsResponse>();
+
+pub(crate) async fn list_automation_reports(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Query(query): Query<ListAutomationReportsQuery>,
+) -> Response {
+    let device_id = query.device_id.trim();
+    if device_id.is_empty() || device_id.chars().count() > MAX_DEVICE_ID_CHARS {
+        return error_response(
+            ApiErrorCode::InvalidDeviceId,
+            "device_id must be between 1 and 128 characters",
+        );
+    }
+    let page = match page_request(
+        &state,
+        CursorResource::AutomationReports,
+        query.cursor.as_deref(),
+        query.limit,
+        AUTOMATION_REPORT_PAGE_LIMITS,
+    ) {
+        Ok(page) => page,
+        Err(err) => return err.into_response(),
+    };
+
+    let reports = match state
+        .store
+        .list_automation_reports(user.user_id, device_id, page)
+        .await
+    {
+        Ok(reports) => reports,
+        Err(err) => return automation_store_error_response(err),
+    };
+
+    let next_cursor = next_cursor(&state, CursorResource::AutomationReports, &reports);
+    let items = reports.map(automation_report_summary).items;
+    (
+        StatusCode::OK,
+        Json(ListAutomationReportsResponse { items, next_cursor }),
+    )
+        .into_response()
+}
@@ -0,0 +1,432 @@
+use axum::response::Response;
+use base64::Engine as _;
+use chrono::{DateTime, Utc};
+use shared::assistant_crypto::{
+    ASSISTANT_ENCRYPTION_ALGORITHM_X25519_CHACHA20POLY1305, ASSISTANT_ENVELOPE_VERSION_V1,
+};
+use shared::automation_schedule::{
+    AutomationCondition, AutomationScheduleSpec, AutomationScheduleType, AutomationTemplate,
+    ScheduleAnchors, build_anchored_schedule_spec, build_cron_schedule_spec,
+    build_once_schedule_spec, format_local_time_hhmm, next_run_after, parse_local_time_hhmm,
+    weekday_mask, weekdays_from_mask,
+};
+use shared::events::DomainEvent;
+use shared::models::{
+    ApiErrorCode, AutomationReportSummary, AutomationRuleSummary, AutomationSchedule,
+    AutomationStatus,
+};
+use shared::repos::{
+    AutomationReportRecord, AutomationRuleRecord, AutomationRuleStatus as RepoAutomationRuleStatus,
+    StoreError,
+};
+use shared::timezone::normalize_time_zone;
+use tracing::warn;
+use uuid::Uuid;
+
+use super::super::abuse::AbuseSignal;
+use super::super::concurrency::version_conflict_response;
+use super::super::errors::{error_response, store_error_response};
+use super::super::{AppState, AuthUser};
+
+const MAX_PROMPT_ENVELOPE_CIPHERTEXT_BYTES: usize = 65_536;
+/// Legitimate prompts are far below the limit; envelopes padded to it are an abuse signal.
+const NEAR_MAX_PROMPT_ENVELOPE_CIPHERTEXT_BYTES: usize =
+    MAX_PROMPT_ENVELOPE_CIPHERTEXT_BYTES * 9 / 10;
+const MAX_AUTOMATION_TITLE_CHARS: usize = 120;
+type ConditionValidationError = (ApiErrorCode, &'static str);
+type PromptValidationError = (ApiErrorCode, &'static str);
+type ScheduleValidationError = (ApiErrorCode, &'static str);
+type TemplateValidationError = (ApiErrorCode, &'static str);
+type TitleValidationError = (ApiErrorCode, &'static str);
+
+pub(super) fn validated_template_schedule(
+    template: Option<AutomationTemplate>,
+    schedule_type: AutomationScheduleType,
+) -> Result<(), TemplateValidationError> {
+    match template {
+        Some(template) if template.required_schedule_type() != schedule_type => Err((
+            ApiErrorCode::InvalidTemplateSchedule,
+            "automation template requires a different schedule_type",
+        )),
+        _ => Ok(()),
+    }
+}
+
+pub(super) fn validated_condition(
+    condition: Option<AutomationCondition>,
+) -> Result<(), ConditionValidationError> {
+    match condition {
+        Some(condition) if condition.validate().is_err() => Err((
+            ApiErrorCode::InvalidAutomationCondition,
+            "condition min_count must be between 1 and 10",
+        )),
+        _ => Ok(()),
+    }
+}
+
+pub(super) fn validated_schedule_and_next_run(
+    schedule: &AutomationSchedule,
+    reference_utc: DateTime<Utc>,
+) -> Result<(AutomationScheduleSpec, DateTime<Utc>), ScheduleValidationError> {
+    if schedule.run_at.is_some() && schedule.schedule_type != AutomationScheduleType::Once {
+        return Err((
+            ApiErrorCode::InvalidSchedule,
+            "run_at is only allowed for ONCE schedules",
+        ));
+    }
+
+    let schedule_spec = if schedule.schedule_type == AutomationScheduleType::Once {
+        if schedule.local_time.is_some()
+            || schedule.days_of_week.is_some()
+            || schedule.day_of_month.is_some()
+            || schedule.cron_expression.is_some()
+        {
+            return Err((
+                ApiErrorCode::InvalidSchedule,
+                "ONCE schedules must not include local_time, days_of_week, day_of_month, or cron_expression",
+            ));
+        }
+        let run_at = schedule.run_at.ok_or((
+            ApiErrorCode::InvalidSchedule,
+            "run_at is required for ONCE schedules",
+        ))?;
+        if run_at <= reference_utc {
+            return Err((
+                ApiErrorCode::InvalidSchedule,
+                "run_at must be in the future",
+            ));
+        }
+        build_once_schedule_spec(schedule.time_zone.as_str(), run_at).map_err(|_| {
+            (
+                ApiErrorCode::InvalidSchedule,
+                "schedule contains invalid frequency/time/time_zone values",
+            )
+        })?
+    } else if schedule.schedule_type == AutomationScheduleType::Cron {
+        if schedule.local_time.is_some()
+            || schedule.days_of_week.is_some()
+            || schedule.day_of_month.is_some()
+        {
+            return Err((
+                ApiErrorCode::InvalidSchedule,
+                "CRON schedules must not include local_time, days_of_week, or day_of_month",
+            ));
+        }
+        let cron_expression = schedule.cron_expression.as_deref().ok_or((
+            ApiErrorCode::InvalidCronExpression,
+            "cron_expression is required for CRON schedules",
+        ))?;
+        if normalize_time_zone(schedule.time_zone.as_str()).is_none() {
+            return Err((
+                ApiErrorCode::InvalidSchedule,
+                "schedule contains invalid frequency/time/time_zone values",
+            ));
+        }
+        build_cron_schedule_spec(schedule.time_zone.as_str(), cron_expression).map_err(|_| {
+            (
+                ApiErrorCode::InvalidCronExpression,
+                "cron_expression must be a 5-field cron expression (minute hour day-of-month month day-of-week)",
+            )
+        })?
+    } else {
+        if schedule.cron_expression.is_some() {
+            return Err((
+                ApiErrorCode::InvalidSchedule,
+                "cron_expression is only allowed for CRON schedules",
+            ));
+        }
+        let local_time_minutes = schedule
+            .local_time
+            .as_deref()
+            .and_then(parse_local_time_hhmm)
+            .ok_or((
+                ApiErrorCode::InvalidLocalTime,
+                "local_time must use HH:MM 24-hour format",
+            ))?;
+        if schedule.days_of_week.is_some()
+            && schedule.schedule_type != AutomationScheduleType::Weekly
+        {
+            return Err((
+                ApiErrorCode::InvalidSchedule,
+                "days_of_week is only allowed for WEEKLY schedules",
+            ));
+        }
+        if schedule.day_of_month.is_some()
+            && schedule.schedule_type != AutomationScheduleType::Monthly
+        {
+            return Err((
+                ApiErrorCode::InvalidSchedule,
+                "day_of_month is only allowed for MONTHLY schedules",
+            ));
+        }
+        let days_of_week = schedule
+            .days_of_week
+            .as_deref()
+            .map(|days| {
+                weekday_mask(days).ok_or((
+                    ApiErrorCode::InvalidSchedule,
+                    "days_of_week must list weekdays from 1 (Monday) to 7 (Sunday)",
+                ))
+            })
+            .transpose()?;
+        if schedule
+            .day_of_month
+            .is_some_and(|day| !(1..=31).contains(&day))
+        {
+            return Err((
+                ApiErrorCode::InvalidSchedule,
+                "day_of_month must be between 1 and 31",
+            ));
+        }
+
+        build_anchored_schedule_spec(
+            schedule.schedule_type,
+            schedule.time_zone.as_str(),
+            local_time_minutes,
+            ScheduleAnchors {
+                days_of_week,
+                day_of_month: schedule.day_of_month,
+            },
+            reference_utc,
+        )
+        .map_err(|_| {
+            (
+                ApiErrorCode::InvalidSchedule,
+                "schedule contains invalid frequency/time/time_zone values",
+            )
+        })?
+    };
+
+    let next_run_at = next_run_after(reference_utc, &schedule_spec).ok_or((
+        ApiErrorCode::InvalidSchedule,
+        "unable to compute next run for schedule",
+    ))?;
+
+    Ok((schedule_spec, next_run_at))
+}
+
+pub(crate) fn validated_prompt_payload(
+    envelope: &shared::models::AutomationPromptEnvelope,
+) -> Result<Vec<u8>, PromptValidationError> {
+    if envelope.version != ASSISTANT_ENVELOPE_VERSION_V1 {
+        return Err((
+            ApiErrorCode::InvalidEnvelopeVersion,
+            "automation prompt envelope version is not supported",
+        ));
+    }
+
+    if envelope.algorithm != ASSISTANT_ENCRYPTION_ALGORITHM_X25519_CHACHA20POLY1305 {
+        return Err((
+            ApiErrorCode::InvalidEnvelopeAlgorithm,
+            "automation prompt envelope algorithm is not supported",
+        ));
+    }
+
+    if envelope.key_id.trim().is_empty() {
+        return Err((ApiErrorCode::InvalidKeyId, "key_id is required"));
+    }
+
+    if envelope.request_id.trim().is_empty() {
+        return Err((ApiErrorCode::InvalidRequestId, "request_id is required"));
+    }
+
+    let client_public_key = match base64::engine::general_purpose::STANDARD
+        .decode(envelope.client_ephemeral_public_key.as_bytes())
+    {
+        Ok(bytes) => bytes,
+        Err(_) => {
+            return Err((
+                ApiErrorCode::InvalidClientPublicKey,
+                "client_ephemeral_public_key must be valid base64",
+            ));
+        }
+    };
+    if client_public_key.len() != 32 {
+        return Err((
+            ApiErrorCode::InvalidClientPublicKey,
+            "client_ephemeral_public_key must decode to 32 bytes",
+        ));
+    }
+
+    let nonce = match base64::engine::general_purpose::STANDARD.decode(envelope.nonce.as_bytes()) {
+        Ok(bytes) => bytes,
+        Err(_) => return Err((ApiErrorCode::InvalidNonce, "nonce must be valid base64")),
+    };
+    if nonce.len() != 12 {
+        return Err((ApiErrorCode::InvalidNonce, "nonce must decode to 12 bytes"));
+    }
+
+    let ciphertext =
+        match base64::engine::general_purpose::STANDARD.decode(envelope.ciphertext.as_bytes()) {
+            Ok(ciphertext) => ciphertext,
+            Err(_) => {
+                return Err((
+                    ApiErrorCode::InvalidCiphertext,
+                    "ciphertext must be valid base64",
+                ));
+            }
+        };
+
+    if ciphertext.is_empty() {
+        return Err((
+            ApiErrorCode::InvalidCiphertext,
+            "ciphertext must not be empty",
+        ));
+    }
+
+    if ciphertext.len() > MAX_PROMPT_ENVELOPE_CIPHERTEXT_BYTES {
+        return Err((
+            ApiErrorCode::InvalidCiphertext,
+            "ciphertext exceeds size limit",
+        ));
+    }
+
+    serde_json::to_vec(envelope).map_err(|_| {
+        (
+            ApiErrorCode::InvalidPromptEnvelope,
+            "automation prompt envelope payload is invalid",
+        )
+    })
+}
+
+pub(super) fn is_near_max_size_prompt(envelope: &shared::models::AutomationPromptEnvelope) -> bool {
+    envelope.ciphertext.len() / 4 * 3 >= NEAR_MAX_PROMPT_ENVELOPE_CIPHERTEXT_BYTES
+}
+
+/// Feeds the abuse tracker. When a signal trips, the user is temporarily blocked from
+/// automation mutations by the rate-limit middleware and ops is alerted.
+pub(super) async fn record_abuse_signal(state: &AppState, user_id: Uuid, signal: AbuseSignal) {
+    let Some(escalation) = state.rate_limiter.record_abuse_signal(signal, user_id) else {
+        return;
+    };
+
+    warn!(
+        alert = "automation_abuse",
+        user_id = %user_id,
+        signal = escalation.signal.key_name(),
+        observed = escalation.observed,
+        blocked_for_seconds = escalation.blocked_for_seconds,
+        "automation abuse escalation"
+    );
+
+    if let Err(err) = state
+        .events
+        .publish(
+            user_id,
+            DomainEvent::AutomationAbuseEscalated {
+                signal: escalation.signal.key_name(),
+                observed: escalation.observed,
+                blocked_for_seconds: escalation.blocked_for_seconds,
+            },
+        )
+        .await
+    {
+        warn!(user_id = %user_id, "failed to persist automation abuse audit event: {err}");
+    }
+}
+
+pub(super) fn automation_rule_summary(rule: AutomationRuleRecord) -> AutomationRuleSummary {
+    let status = match rule.status {
+        RepoAutomationRuleStatus::Active => AutomationStatus::Active,
+        RepoAutomationRuleStatus::Paused => AutomationStatus::Paused,
+        RepoAutomationRuleStatus::Archived => AutomationStatus::Archived,
+        RepoAutomationRuleStatus::Completed => AutomationStatus::Completed,
+    };
+
+    let days_of_week = match rule.schedule_type {
+        AutomationScheduleType::Weekly => rule
+            .anchor_days_of_week
+            .and_then(|mask| u8::try_from(mask).ok())
+            .map(weekdays_from_mask),
+        _ => None,
+    };
+    let day_of_month = match rule.schedule_type {
+        AutomationScheduleType::Monthly => rule
+            .anchor_day_of_month
+            .and_then(|day| u8::try_from(day).ok()),
+        _ => None,
+    };
+    let local_time = match rule.schedule_type {
+        AutomationScheduleType::Cron | AutomationScheduleType::Once => None,
+        _ => Some(
+            u16::try_from(rule.local_time_minutes)
+                .ok()
+                .map(format_local_time_hhmm)
+                .unwrap_or_else(|| "00:00".to_string()),
+        ),
+    };
+
+    AutomationRuleSummary {
+        rule_id: rule.id.to_string(),
+        title: rule.title,
+        status,
+        template: rule.template,
+        condition: rule.condition,
+        schedule: AutomationSchedule {
+            schedule_type: rule.schedule_type,
+            time_zone: rule.time_zone,
+            local_time,
+            days_of_week,
+            day_of_month,
+            cron_expression: rule.cron_expression,
+            run_at: rule.run_at,
+        },
+        next_run_at: rule.next_run_at,
+        last_run_at: rule.last_run_at,
+        paused_until: rule.paused_until,
+        prompt_sha256: rule.prompt_sha256,
+        version: rule.version,
+        created_at: rule.created_at,
+        updated_at: rule.updated_at,
+    }
+}
+
+pub(super) fn automation_report_summary(report: AutomationReportRecord) -> AutomationReportSummary {
+    AutomationReportSummary {
+        report_id: report.id.to_string(),
+        rule_id: report.rule_id.to_string(),
+        run_id: report.run_id.to_string(),
+        template: report.template,
+        envelope: report.envelope,
+        created_at: report.created_at,
+    }
+}
+
+pub(super) fn validated_title(value: &str) -> Result<String, TitleValidationError> {
+    let title = value.trim();
+    if title.is_empty() {
+        return Err((ApiErrorCode::InvalidTitle, "title must not be empty"));
+    }
+    if title.chars().count() > MAX_AUTOMATION_TITLE_CHARS {
+        return Err((
+            ApiErrorCode::InvalidTitle,
+            "title exceeds maximum length of 120 characters",
+        ));
+    }
+    Ok(title.to_string())
+}
+
+pub(super) fn automation_store_error_response(err: StoreError) -> Response {
+    match err {
+        StoreError::InvalidData(message) => {
+            error_response(ApiErrorCode::InvalidAutomationRequest, &message)
+        }
+        other => store_error_response(other),
+    }
+}
+
+pub(super) async fn automation_conflict_response(
+    state: &AppState,
+    user: AuthUser,
+    rule_id: Uuid,
+) -> Response {
+    match state.store.get_automation_rule(user.user_id, rule_id).await {
+        Ok(Some(rule)) => version_conflict_response(automation_rule_summary(rule)),
+        Ok(None) => automation_not_found_response(),
+        Err(err) => automation_store_error_response(err),
+    }
+}
+
+pub(super) fn automation_not_found_response() -> Response {
+    error_response(ApiErrorCode::NotFound, "Automation rule not found")
+}
@@ -0,0 +1,102 @@
+use axum::Json;
+use axum::extract::{Extension, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use shared::enclave::{DraftAutomationRequest as EnclaveDraftAutomationRequest, EnclaveRpcError};
+use shared::models::{ApiErrorCode, DraftAutomationRequest, DraftAutomationResponse, QuotaKind};
+use shared::repos::PromptEnvelopeUse;
+use shared::timezone::normalize_time_zone;
+use tracing::warn;
+
+use super::super::errors::error_response;
+use super::super::openapi::ApiOperation;
+use super::super::prompt_envelopes::claim_prompt_envelope_request_id;
+use super::super::quota::enforce_plan_quota;
+use super::super::request_body::ApiJson;
+use super::super::{AppState, AuthUser, build_enclave_client};
+use super::helpers::validated_prompt_payload;
+
+pub(crate) const DRAFT_AUTOMATION: ApiOperation = ApiOperation::post(
+    "/v1/automations/draft",
+    "draftAutomation",
+    "Automations",
+    "Propose an automation from an encrypted natural-language request",
+)
+.request::<DraftAutomationRequest>()
+.response::<DraftAutomationResponse>();
+
+/// Relays an encrypted request such as "brief me every weekday at 7" to the enclave, which
+/// reads it and encrypts a proposed title, prompt, and schedule back to the client. Nothing is
+/// stored; the client saves a confirmed proposal through `createAutomation`.
+pub(crate) async fn draft_automation(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    ApiJson(request): ApiJson<DraftAutomationRequest>,
+) -> Response {
+    if let Err((code, message)) = validated_prompt_payload(&request.prompt_envelope) {
+        return error_response(code, message);
+    }
+    let Some(time_zone) = normalize_time_zone(request.time_zone.as_str()) else {
+        return error_response(
+            ApiErrorCode::InvalidTimeZone,
+            "time_zone must be a valid IANA time zone",
+        );
+    };
+    if let Err(response) =
+        enforce_plan_quota(&state, user.user_id, QuotaKind::LlmRequestsPerDay).await
+    {
+        return response;
+    }
+    let draft_request_id = request.prompt_envelope.request_id.clone();
+    if let Err(response) = claim_prompt_envelope_request_id(
+        &state,
+        user.user_id,
+        &request.prompt_envelope.key_id,
+        &draft_request_id,
+        PromptEnvelopeUse::AutomationDraft,
+    )
+    .await
+    {
+        return response;
+    }
+
+    let enclave_client = build_enclave_client(&state);
+    let response = match enclave_client
+        .draft_automation(EnclaveDraftAutomationRequest {
+            user_id: user.user_id,
+            time_zone,
+            prompt_envelope: request.prompt_envelope,
+        })
+        .await
+    {
+        Ok(response) => response,
+        Err(err) => return map_draft_enclave_error(err, &draft_request_id),
+    };
+
+    (
+        StatusCode::OK,
+        Json(DraftAutomationResponse {
+            request_id: draft_request_id,
+            envelope: response.envelope,
+        }),
+    )
+        .into_response()
+}
+
+fn map_draft_enclave_error(err: EnclaveRpcError, draft_request_id: &str) -> Response {
+    warn!(
+        draft_request_id,
+        code = err.code(),
+        "automation draft enclave RPC failed"
+    );
+    match err {
+        EnclaveRpcError::RpcContractRejected { .. } => error_response(
+            ApiErrorCode::InvalidEnclaveRequest,
+            "Automation draft request rejected",
+        ),
+        _ => error_response(
+            ApiErrorCode::EnclaveRpcFailed,
+            "Secure enclave RPC request failed",
+        ),
+    }
+}
@@ -0,0 +1,55 @@
+use axum::Json;
+use axum::extract::Extension;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use chrono::Utc;
+use shared::automation_schedule::upcoming_runs;
+use shared::models::{
+    AutomationRunPreview, AutomationSchedulePreview, PreviewAutomationScheduleRequest,
+};
+
+use super::super::AuthUser;
+use super::super::errors::error_response;
+use super::super::openapi::ApiOperation;
+use super::super::request_body::ApiJson;
+use super::helpers::validated_schedule_and_next_run;
+
+/// Upcoming runs returned by the schedule preview.
+const SCHEDULE_PREVIEW_RUN_COUNT: usize = 5;
+
+pub(crate) const PREVIEW_AUTOMATION_SCHEDULE: ApiOperation = ApiOperation::post(
+    "/v1/automations/preview-schedule",
+    "previewAutomationSchedule",
+    "Automations",
+    "Preview the next runs of a schedule before saving it",
+)
+.request::<PreviewAutomationScheduleRequest>()
+.response::<AutomationSchedulePreview>();
+
+/// Validates a schedule exactly as create and update do, without storing anything.
+pub(crate) async fn preview_automation_schedule(
+    Extension(_user): Extension<AuthUser>,
+    ApiJson(request): ApiJson<PreviewAutomationScheduleRequest>,
+) -> Response {
+    let now = Utc::now();
+    let (schedule, _) = match validated_schedule_and_next_run(&request.schedule, now) {
+        Ok(value) => value,
+        Err((code, message)) => return error_response(code, message),
+    };
+
+    let runs = upcoming_runs(now, &schedule, SCHEDULE_PREVIEW_RUN_COUNT)
+        .into_iter()
+        .map(|(run_at, local_run_at)| AutomationRunPreview {
+            run_at,
+            local_run_at,
+        })
+        .collect();
+    (
+        StatusCode::OK,
+        Json(AutomationSchedulePreview {
+            time_zone: schedule.time_zone,
+            runs,
+        }),
+    )
+        .into_response()
+}
@@ -0,0 +1,326 @@
+use axum::Json;
+use axum::extract::{Extension, Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use base64::Engine as _;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use shared::automation_schedule::AutomationTemplate;
+use shared::events::{DomainEvent, ManualRunMode};
+use shared::models::{
+    ApiErrorCode, QuotaKind, RunAutomationNowRequest, RunAutomationNowResponse,
+    TriggerAutomationDebugRunResponse,
+};
+use shared::repos::{
+    AutomationPromptMaterial, AutomationRuleRecord,
+    AutomationRuleStatus as RepoAutomationRuleStatus, JobType,
+};
+use uuid::Uuid;
+
+use super::super::errors::{error_response, event_publish_error_response, quota_exceeded_response};
+use super::super::openapi::ApiOperation;
+use super::super::quota::enforce_plan_quota;
+use super::super::request_body::ApiJson;
+use super::super::{AppState, AuthUser};
+use super::helpers::{automation_not_found_response, automation_store_error_response};
+
+const MAX_RUN_REQUEST_ID_CHARS: usize = 128;
+/// User-initiated runs each cost an enclave LLM call, so they are capped per UTC day.
+const MAX_MANUAL_AUTOMATION_RUNS_PER_DAY: i64 = 20;
+
+#[derive(Debug, Serialize)]
+struct AutomationRunJobPayload {
+    automation_run_id: Uuid,
+    automation_rule_id: Uuid,
+    scheduled_for: DateTime<Utc>,
+    prompt_sha256: String,
+    prompt_envelope_ciphertext_b64: String,
+    template: Option<AutomationTemplate>,
+    time_zone: String,
+}
+
+pub(crate) const TRIGGER_DEBUG_RUN: ApiOperation = ApiOperation::post(
+    "/v1/automations/{rule_id}/debug/run",
+    "triggerAutomationDebugRun",
+    "Automations",
+    "Enqueue an immediate debug run for an automation rule (local/dev only)",
+)
+.response::<TriggerAutomationDebugRunResponse>();
+
+pub(crate) async fn trigger_debug_run(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(rule_id): Path<String>,
+) -> Response {
+    if !state.allow_debug_automation_run {
+        return automation_not_found_response();
+    }
+
+    let rule_id = match Uuid::parse_str(&rule_id) {
+        Ok(rule_id) => rule_id,
+        Err(_) => return automation_not_found_response(),
+    };
+
+    let (rule, prompt_material) = match load_runnable_automation(
+        &state,
+        user,
+        rule_id,
+        "Automation rule must be ACTIVE to trigger a debug run",
+    )
+    .await
+    {
+        Ok(runnable) => runnable,
+        Err(response) => return response,
+    };
+
+    let automation_run_id = Uuid::new_v4();
+    let idempotency_key = format!("AUTOMATION_DEBUG_RUN:{rule_id}:{automation_run_id}");
+    let job_id = match enqueue_manual_automation_run(
+        &state,
+        user,
+        rule,
+        prompt_material,
+        automation_run_id,
+        &idempotency_key,
+        ManualRunMode::Debug,
+    )
+    .await
+    {
+        Ok(job_id) => job_id,
+        Err(response) => return response,
+    };
+
+    (
+        StatusCode::OK,
+        Json(TriggerAutomationDebugRunResponse {
+            queued_job_id: job_id.to_string(),
+            status: "QUEUED".to_string(),
+        }),
+    )
+        .into_response()
+}
+
+pub(crate) const RUN_AUTOMATION_NOW: ApiOperation = ApiOperation::post(
+    "/v1/automations/{rule_id}/run",
+    "runAutomationNow",
+    "Automations",
+    "Queue an immediate run of an automation rule",
+)
+.request::<RunAutomationNowRequest>()
+.response::<RunAutomationNowResponse>();
+
+pub(crate) async fn run_automation_now(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(rule_id): Path<String>,
+    ApiJson(request): ApiJson<RunAutomationNowRequest>,
+) -> Response {
+    let rule_id = match Uuid::parse_str(&rule_id) {
+        Ok(rule_id) => rule_id,
+        Err(_) => return automation_not_found_response(),
+    };
+
+    let request_id = request.request_id.trim();
+    if request_id.is_empty() || request_id.chars().count() > MAX_RUN_REQUEST_ID_CHARS {
+        return error_response(
+            ApiErrorCode::InvalidRequestId,
+            "request_id must be between 1 and 128 characters",
+        );
+    }
+
+    let now = Utc::now();
+    let day_start = now
+        .date_naive()
+        .and_hms_opt(0, 0, 0)
+        .expect("midnight should be a valid time")
+        .and_utc();
+    let runs_today = match state
+        .store
+        .count_automation_manual_runs_since(user.user_id, day_start)
+        .await
+    {
+        Ok(count) => count,
+        Err(err) => return automation_store_error_response(err),
+    };
+
+    match state
+        .store
+        .get_automation_manual_run(user.user_id, request_id)
+        .await
+    {
+        Ok(Some(existing)) if existing.rule_id == rule_id => {
+            return run_automation_now_response(existing.job_id, true, runs_today);
+        }
+        Ok(Some(_)) => {
+            return error_response(
+                ApiErrorCode::RequestIdReused,
+                "request_id was already used to run a different automation",
+            );
+        }
+        Ok(None) => {}
+        Err(err) => return automation_store_error_response(err),
+    }
+
+    let (rule, prompt_material) = match load_runnable_automation(
+        &state,
+        user,
+        rule_id,
+        "Automation rule must be ACTIVE to run it now",
+    )
+    .await
+    {
+        Ok(runnable) => runnable,
+        Err(response) => return response,
+    };
+
+    if runs_today >= MAX_MANUAL_AUTOMATION_RUNS_PER_DAY {
+        let next_day = day_start + Duration::days(1);
+        let retry_after_seconds = (next_day - now).num_seconds().max(1) as u64;
+        return quota_exceeded_response(
+            ApiErrorCode::AutomationRunLimitReached,
+            "Daily limit for running automations now has been reached",
+            retry_after_seconds,
+        );
+    }
+    if let Err(response) =
+        enforce_plan_quota(&state, user.user_id, QuotaKind::LlmRequestsPerDay).await
+    {
+        return response;
+    }
+
+    // Retries share the run id as well as the job, so a retry racing the first request
+    // re-enqueues an identical payload.
+    let automation_run_id = Uuid::new_v5(&rule_id, request_id.as_bytes());
+    let idempotency_key = format!("AUTOMATION_RUN_NOW:{rule_id}:{request_id}");
+    let job_id = match enqueue_manual_automation_run(
+        &state,
+        user,
+        rule,
+        prompt_material,
+        automation_run_id,
+        &idempotency_key,
+        ManualRunMode::UserRequested,
+    )
+    .await
+    {
+        Ok(job_id) => job_id,
+        Err(response) => return response,
+    };
+
+    if let Err(err) = state
+        .store
+        .record_automation_manual_run(user.user_id, rule_id, request_id, job_id, now)
+        .await
+    {
+        return automation_store_error_response(err);
+    }
+
+    run_automation_now_response(job_id, false, runs_today + 1)
+}
+
+fn run_automation_now_response(job_id: Uuid, replayed: bool, runs_today: i64) -> Response {
+    let runs_remaining_today = (MAX_MANUAL_AUTOMATION_RUNS_PER_DAY - runs_today).max(0) as u32;
+    (
+        StatusCode::OK,
+        Json(RunAutomationNowResponse {
+            queued_job_id: job_id.to_string(),
+            status: "QUEUED".to_string(),
+            replayed,
+            runs_remaining_today,
+        }),
+    )
+        .into_response()
+}
+
+/// Loads an automation that can be run outside its schedule: it must exist, belong to the
+/// user and be active.
+async fn load_runnable_automation(
+    state: &AppState,
+    user: AuthUser,
+    rule_id: Uuid,
+    inactive_message: &str,
+) -> Result<(AutomationRuleRecord, AutomationPromptMaterial), Response> {
+    let Some(rule) = state
+        .store
+        .get_automation_rule(user.user_id, rule_id)
+        .await
+        .map_err(automation_store_error_response)?
+    else {
+        return Err(automation_not_found_response());
+    };
+
+    if !matches!(rule.status, RepoAutomationRuleStatus::Active) {
+        return Err(error_response(
+            ApiErrorCode::AutomationNotActive,
+            inactive_message,
+        ));
+    }
+
+    let Some(prompt_material) = state
+        .store
+        .get_automation_rule_prompt_material(user.user_id, rule_id)
+        .await
+        .map_err(automation_store_error_response)?
+    else {
+        return Err(automation_not_found_response());
+    };
+
+    Ok((rule, prompt_material))
+}
+
+/// Enqueues an off-schedule automation run due now and publishes it as queued under `mode`.
+async fn enqueue_manual_automation_run(
+    state: &AppState,
+    user: AuthUser,
+    rule: AutomationRuleRecord,
+    prompt_material: AutomationPromptMaterial,
+    automation_run_id: Uuid,
+    idempotency_key: &str,
+    mode: ManualRunMode,
+) -> Result<Uuid, Response> {
+    let rule_id = rule.id;
+    let scheduled_for = Utc::now();
+    let payload = AutomationRunJobPayload {
+        automation_run_id,
+        automation_rule_id: rule_id,
+        scheduled_for,
+        prompt_sha256: prompt_material.prompt_sha256,
+        prompt_envelope_ciphertext_b64: base64::engine::general_purpose::STANDARD
+            .encode(prompt_material.prompt_ciphertext),
+        template: rule.template,
+        time_zone: rule.time_zone,
+    };
+    let payload_json = serde_json::to_vec(&payload).map_err(|_| {
+        error_response(
+            ApiErrorCode::InvalidAutomationPayload,
+            "failed to serialize automation run payload",
+        )
+    })?;
+
+    let job_id = state
+        .store
+        .enqueue_job_with_idempotency_key(
+            user.user_id,
+            JobType::AutomationRun,
+            scheduled_for,
+            Some(&payload_json),
+            idempotency_key,
+        )
+        .await
+        .map_err(automation_store_error_response)?;
+
+    state
+        .events
+        .publish(
+            user.user_id,
+            DomainEvent::AutomationRunQueued {
+                rule_id,
+                job_id,
+                mode,
+            },
+        )
+        .await
+        .map_err(event_publish_error_response)?;
+
+    Ok(job_id)
+}
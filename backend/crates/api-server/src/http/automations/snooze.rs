@@ -0,0 +1,153 @@
+use axum::Json;
+use axum::extract::{Extension, Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use chrono::{DateTime, Duration, Utc};
+use shared::events::DomainEvent;
+use shared::models::{ApiErrorCode, AutomationRuleSummary, SnoozeAutomationRequest, SnoozePreset};
+use shared::repos::AutomationRuleStatus as RepoAutomationRuleStatus;
+use uuid::Uuid;
+
+use super::super::abuse::AbuseSignal;
+use super::super::errors::{error_response, event_publish_error_response};
+use super::super::openapi::ApiOperation;
+use super::super::request_body::ApiJson;
+use super::super::{AppState, AuthUser};
+use super::helpers::{
+    automation_not_found_response, automation_rule_summary, automation_store_error_response,
+    record_abuse_signal,
+};
+
+const MAX_SNOOZE_DAYS: i64 = 365;
+type SnoozeValidationError = (ApiErrorCode, &'static str);
+
+pub(crate) const SNOOZE_AUTOMATION: ApiOperation = ApiOperation::post(
+    "/v1/automations/{rule_id}/snooze",
+    "snoozeAutomation",
+    "Automations",
+    "Pause an automation rule until a later time",
+)
+.request::<SnoozeAutomationRequest>()
+.response::<AutomationRuleSummary>();
+
+pub(crate) async fn snooze_automation(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(rule_id): Path<String>,
+    ApiJson(request): ApiJson<SnoozeAutomationRequest>,
+) -> Response {
+    let rule_id = match Uuid::parse_str(&rule_id) {
+        Ok(rule_id) => rule_id,
+        Err(_) => return automation_not_found_response(),
+    };
+
+    let paused_until = match validated_snooze_until(&request, Utc::now()) {
+        Ok(paused_until) => paused_until,
+        Err((code, message)) => return error_response(code, message),
+    };
+
+    let rule = match state.store.get_automation_rule(user.user_id, rule_id).await {
+        Ok(Some(rule)) => rule,
+        Ok(None) => return automation_not_found_response(),
+        Err(err) => return automation_store_error_response(err),
+    };
+    match rule.status {
+        RepoAutomationRuleStatus::Active | RepoAutomationRuleStatus::Paused => {}
+        RepoAutomationRuleStatus::Archived => {
+            return error_response(
+                ApiErrorCode::AutomationArchived,
+                "Restore the automation by setting status to ACTIVE or PAUSED before snoozing it",
+            );
+        }
+        RepoAutomationRuleStatus::Completed => {
+            return error_response(
+                ApiErrorCode::AutomationNotActive,
+                "Completed automations have no runs left to snooze",
+            );
+        }
+    }
+
+    match state
+        .store
+        .claim_automation_rule_version(user.user_id, rule_id, None)
+        .await
+    {
+        Ok(Some(_)) => {}
+        Ok(None) => return automation_not_found_response(),
+        Err(err) => return automation_store_error_response(err),
+    }
+
+    record_abuse_signal(&state, user.user_id, AbuseSignal::PauseResumeLoop).await;
+    match state
+        .store
+        .snooze_automation_rule(user.user_id, rule_id, paused_until)
+        .await
+    {
+        Ok(true) => {}
+        // The rule was archived or completed after it was loaded.
+        Ok(false) => {
+            return error_response(
+                ApiErrorCode::AutomationNotActive,
+                "Automation rule can no longer be snoozed",
+            );
+        }
+        Err(err) => return automation_store_error_response(err),
+    }
+
+    let rule = match state.store.get_automation_rule(user.user_id, rule_id).await {
+        Ok(Some(rule)) => rule,
+        Ok(None) => return automation_not_found_response(),
+        Err(err) => return automation_store_error_response(err),
+    };
+
+    if let Err(err) = state
+        .events
+        .publish(
+            user.user_id,
+            DomainEvent::AutomationRuleUpdated {
+                rule_id: rule.id,
+                updated_fields: vec!["status".to_string(), "paused_until".to_string()],
+            },
+        )
+        .await
+    {
+        return event_publish_error_response(err);
+    }
+
+    (StatusCode::OK, Json(automation_rule_summary(rule))).into_response()
+}
+
+fn validated_snooze_until(
+    request: &SnoozeAutomationRequest,
+    now: DateTime<Utc>,
+) -> Result<DateTime<Utc>, SnoozeValidationError> {
+    let paused_until = match (request.preset, request.until) {
+        (SnoozePreset::OneDay, None) => now + Duration::days(1),
+        (SnoozePreset::OneWeek, None) => now + Duration::weeks(1),
+        (SnoozePreset::Until, Some(until)) => until,
+        (SnoozePreset::Until, None) => {
+            return Err((
+                ApiErrorCode::InvalidSnooze,
+                "until is required for the UNTIL preset",
+            ));
+        }
+        (SnoozePreset::OneDay | SnoozePreset::OneWeek, Some(_)) => {
+            return Err((
+                ApiErrorCode::InvalidSnooze,
+                "until is only allowed for the UNTIL preset",
+            ));
+        }
+    };
+
+    if paused_until <= now {
+        return Err((ApiErrorCode::InvalidSnooze, "until must be in the future"));
+    }
+    if paused_until > now + Duration::days(MAX_SNOOZE_DAYS) {
+        return Err((
+            ApiErrorCode::InvalidSnooze,
+            "until must be at most 365 days away",
+        ));
+    }
+
+    Ok(paused_until)
+}
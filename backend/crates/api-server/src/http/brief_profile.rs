@@ -0,0 +1,136 @@
+use axum::Json;
+use axum::extract::{Extension, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use shared::brief_profile::{MorningBriefProfile, validate_brief_sections};
+use shared::models::{
+    ApiErrorCode, AuditMetadata, MorningBriefFeedbackRequest, MorningBriefProfileResponse,
+    UpdateMorningBriefProfileRequest,
+};
+use shared::repos::{AuditResult, MorningBriefProfileRecord};
+
+use super::errors::{error_response, store_error_response};
+use super::openapi::ApiOperation;
+use super::request_body::ApiJson;
+use super::{AppState, AuthUser};
+
+pub(super) const GET_BRIEF_PROFILE: ApiOperation = ApiOperation::get(
+    "/v1/brief-profile",
+    "getBriefProfile",
+    "Briefs",
+    "Get the morning brief personalization profile",
+)
+.response::<MorningBriefProfileResponse>();
+
+pub(super) async fn get_brief_profile(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+) -> Response {
+    match state.store.get_morning_brief_profile(user.user_id).await {
+        Ok(record) => (StatusCode::OK, Json(brief_profile_response(record))).into_response(),
+        Err(err) => store_error_response(err),
+    }
+}
+
+pub(super) const UPDATE_BRIEF_PROFILE: ApiOperation = ApiOperation::put(
+    "/v1/brief-profile",
+    "updateBriefProfile",
+    "Briefs",
+    "Replace the morning brief personalization profile",
+)
+.request::<UpdateMorningBriefProfileRequest>()
+.response::<MorningBriefProfileResponse>();
+
+pub(super) async fn update_brief_profile(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    ApiJson(request): ApiJson<UpdateMorningBriefProfileRequest>,
+) -> Response {
+    if let Err(message) = validate_brief_sections(&request.sections) {
+        return error_response(ApiErrorCode::InvalidBriefSections, message);
+    }
+
+    let learn_from_feedback = match request.learn_from_feedback {
+        Some(learn_from_feedback) => learn_from_feedback,
+        None => match state.store.get_morning_brief_profile(user.user_id).await {
+            Ok(record) => record.learn_from_feedback,
+            Err(err) => return store_error_response(err),
+        },
+    };
+
+    let profile = MorningBriefProfile {
+        sections: request.sections,
+        verbosity: request.verbosity,
+    };
+    let record = match state
+        .store
+        .upsert_morning_brief_profile(user.user_id, &profile, learn_from_feedback)
+        .await
+    {
+        Ok(record) => record,
+        Err(err) => return store_error_response(err),
+    };
+
+    let mut metadata = AuditMetadata::new();
+    metadata.insert(
+        "section_count".to_string(),
+        record.profile.sections.len().into(),
+    );
+    metadata.insert(
+        "verbosity".to_string(),
+        record.profile.verbosity.as_str().into(),
+    );
+    metadata.insert(
+        "learn_from_feedback".to_string(),
+        record.learn_from_feedback.into(),
+    );
+
+    if let Err(err) = state
+        .store
+        .add_audit_event(
+            user.user_id,
+            "MORNING_BRIEF_PROFILE_UPDATED",
+            None,
+            AuditResult::Success,
+            &metadata,
+        )
+        .await
+    {
+        return store_error_response(err);
+    }
+
+    (StatusCode::OK, Json(brief_profile_response(record))).into_response()
+}
+
+pub(super) const SUBMIT_BRIEF_FEEDBACK: ApiOperation = ApiOperation::post(
+    "/v1/brief-profile/feedback",
+    "submitBriefFeedback",
+    "Briefs",
+    "Rate a morning brief section",
+)
+.request::<MorningBriefFeedbackRequest>()
+.response::<MorningBriefProfileResponse>();
+
+pub(super) async fn submit_brief_feedback(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    ApiJson(request): ApiJson<MorningBriefFeedbackRequest>,
+) -> Response {
+    match state
+        .store
+        .record_morning_brief_feedback(user.user_id, request.section, request.rating)
+        .await
+    {
+        Ok(record) => (StatusCode::OK, Json(brief_profile_response(record))).into_response(),
+        Err(err) => store_error_response(err),
+    }
+}
+
+fn brief_profile_response(record: MorningBriefProfileRecord) -> MorningBriefProfileResponse {
+    MorningBriefProfileResponse {
+        sections: record.profile.sections,
+        verbosity: record.profile.verbosity,
+        learn_from_feedback: record.learn_from_feedback,
+        updated_at: record.updated_at,
+    }
+}
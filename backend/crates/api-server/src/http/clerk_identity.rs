@@ -1,6 +1,7 @@
 use chrono::Utc;
 use jsonwebtoken::{Algorithm, DecodingKey, Validation, decode, decode_header};
 use serde::Deserialize;
+use shared::models::ApiErrorCode;
 
 use super::clerk_jwks_cache::{ClerkJwksCache, ClerkJwksCacheError};
 
@@ -9,6 +10,15 @@ const MAX_CLOCK_SKEW_SECONDS: i64 = 60;
 #[derive(Debug, Clone)]
 pub(super) struct VerifiedClerkIdentity {
     pub(super) subject: String,
+    /// Active organization of the session, when the user picked one.
+    pub(super) organization: Option<ClerkOrganization>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct ClerkOrganization {
+    pub(super) id: String,
+    /// Role in Clerk's `org:<role>` form.
+    pub(super) role: String,
 }
 
 #[derive(Debug, Clone)]
@@ -18,7 +28,7 @@ pub(super) enum ClerkIdentityError {
         message: &'static str,
     },
     UpstreamUnavailable {
-        code: &'static str,
+        code: ApiErrorCode,
         message: &'static str,
     },
 }
@@ -27,6 +37,36 @@ pub(super) enum ClerkIdentityError {
 struct ClerkClaims {
     sub: String,
     iat: i64,
+    /// Session token v1 organization claims.
+    #[serde(default)]
+    org_id: Option<String>,
+    #[serde(default)]
+    org_role: Option<String>,
+    /// Session token v2 packs the organization into `o`, with the role unprefixed.
+    #[serde(default)]
+    o: Option<ClerkOrganizationClaim>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClerkOrganizationClaim {
+    id: String,
+    rol: String,
+}
+
+impl ClerkClaims {
+    fn organization(&self) -> Option<ClerkOrganization> {
+        if let Some(claim) = &self.o {
+            return Some(ClerkOrganization {
+                id: claim.id.clone(),
+                role: format!("org:{}", claim.rol),
+            });
+        }
+
+        Some(ClerkOrganization {
+            id: self.org_id.clone()?,
+            role: self.org_role.clone()?,
+        })
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -93,7 +133,7 @@ pub(super) async fn verify_identity_token(
         }
         Err(ClerkJwksCacheError::UpstreamUnavailable) => {
             return Err(ClerkIdentityError::UpstreamUnavailable {
-                code: "clerk_jwks_unavailable",
+                code: ApiErrorCode::ClerkJwksUnavailable,
                 message: "Unable to reach Clerk JWKS endpoint",
             });
         }
@@ -101,7 +141,7 @@ pub(super) async fn verify_identity_token(
 
     let jwks: ClerkJwks =
         serde_json::from_str(&jwks_raw).map_err(|_| ClerkIdentityError::UpstreamUnavailable {
-            code: "clerk_jwks_unavailable",
+            code: ApiErrorCode::ClerkJwksUnavailable,
             message: "Clerk JWKS response was invalid",
         })?;
 
@@ -188,6 +228,7 @@ fn verify_identity_token_with_jwks(
 
     Ok(VerifiedClerkIdentity {
         subject: subject.to_string(),
+        organization: token_data.claims.organization(),
     })
 }
 
@@ -9,7 +9,9 @@ use rsa::traits::PublicKeyParts;
 use serde::Serialize;
 use std::sync::OnceLock;
 
-use super::{ClerkIdentityError, ClerkJwk, ClerkJwks, verify_identity_token_with_jwks};
+use super::{
+    ClerkIdentityError, ClerkJwk, ClerkJwks, ClerkOrganization, verify_identity_token_with_jwks,
+};
 
 const TEST_KEY_ID: &str = "test-key-id";
 const TEST_ISSUER: &str = "https://clerk.example.test";
@@ -23,6 +25,8 @@ struct TestClaims {
     exp: i64,
     iss: String,
     aud: String,
+    #[serde(flatten)]
+    extra: serde_json::Map<String, serde_json::Value>,
 }
 
 struct TestKeyMaterial {
@@ -120,7 +124,55 @@ fn verify_identity_token_rejects_invalid_audience() {
     ));
 }
 
+#[test]
+fn verify_identity_token_reads_organization_claims_from_both_token_versions() {
+    let verify = |extra: serde_json::Value| {
+        let token = signed_token_with_claims(
+            TEST_ISSUER,
+            TEST_AUDIENCE,
+            Utc::now() + Duration::minutes(5),
+            extra
+                .as_object()
+                .cloned()
+                .expect("claims should be an object"),
+        );
+        verify_identity_token_with_jwks(
+            &token,
+            TEST_ISSUER,
+            TEST_AUDIENCE,
+            TEST_KEY_ID,
+            &test_jwks(),
+        )
+        .expect("valid token should verify")
+        .organization
+    };
+    let expected = Some(ClerkOrganization {
+        id: "org_ops".to_string(),
+        role: "org:admin".to_string(),
+    });
+
+    assert_eq!(
+        verify(serde_json::json!({ "org_id": "org_ops", "org_role": "org:admin" })),
+        expected
+    );
+    assert_eq!(
+        verify(serde_json::json!({ "v": 2, "o": { "id": "org_ops", "rol": "admin" } })),
+        expected
+    );
+    assert_eq!(verify(serde_json::json!({ "org_id": "org_ops" })), None);
+    assert_eq!(verify(serde_json::json!({})), None);
+}
+
 fn signed_token(issuer: &str, audience: &str, expires_at: chrono::DateTime<Utc>) -> String {
+    signed_token_with_claims(issuer, audience, expires_at, serde_json::Map::new())
+}
+
+fn signed_token_with_claims(
+    issuer: &str,
+    audience: &str,
+    expires_at: chrono::DateTime<Utc>,
+    extra: serde_json::Map<String, serde_json::Value>,
+) -> String {
     let key_material = test_key_material();
     let now = Utc::now();
     let claims = TestClaims {
@@ -129,6 +181,7 @@ fn signed_token(issuer: &str, audience: &str, expires_at: chrono::DateTime<Utc>)
         exp: expires_at.timestamp(),
         iss: issuer.to_string(),
         aud: audience.to_string(),
+        extra,
     };
     let mut header = Header::new(Algorithm::RS256);
     header.kid = Some(TEST_KEY_ID.to_string());
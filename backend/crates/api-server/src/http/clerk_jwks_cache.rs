@@ -24,6 +24,16 @@ pub struct ClerkJwksCache {
     refresh_lock: Arc<tokio::sync::Mutex<()>>,
 }
 
+/// How current the cached key set is, as reported by readiness checks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum ClerkJwksFreshness {
+    Fresh,
+    /// Refresh failed; tokens still verify against the stale entry until it lapses.
+    Stale,
+    /// Refresh failed and no usable entry is left, so no token can be verified.
+    Unavailable,
+}
+
 #[derive(Debug)]
 pub enum ClerkJwksCacheError {
     UnknownKeyId,
@@ -132,6 +142,54 @@ impl ClerkJwksCache {
         }
     }
 
+    pub(super) async fn ping(&self) -> Result<(), String> {
+        let mut connection = self.connection.clone();
+        redis::cmd("PING")
+            .query_async::<String>(&mut connection)
+            .await
+            .map(|_| ())
+            .map_err(|err| err.to_string())
+    }
+
+    /// Refreshes the key set first when its fresh TTL has passed, so a cold or expired cache
+    /// is only reported once Clerk has actually been tried.
+    pub(super) async fn freshness(
+        &self,
+        http_client: &reqwest::Client,
+        jwks_url: &str,
+    ) -> ClerkJwksFreshness {
+        if self
+            .read_cached_entry()
+            .await
+            .is_some_and(|cached| unix_timestamp() <= cached.expires_at)
+        {
+            return ClerkJwksFreshness::Fresh;
+        }
+
+        let _refresh_guard = self.refresh_lock.lock().await;
+
+        let now = unix_timestamp();
+        let cached_after_lock = self.read_cached_entry().await;
+        if cached_after_lock
+            .as_ref()
+            .is_some_and(|cached| now <= cached.expires_at)
+        {
+            return ClerkJwksFreshness::Fresh;
+        }
+
+        match self.fetch_and_cache_jwks(http_client, jwks_url).await {
+            Ok(_) => ClerkJwksFreshness::Fresh,
+            Err(_)
+                if cached_after_lock
+                    .as_ref()
+                    .is_some_and(|cached| now <= cached.stale_until) =>
+            {
+                ClerkJwksFreshness::Stale
+            }
+            Err(_) => ClerkJwksFreshness::Unavailable,
+        }
+    }
+
     async fn read_cached_entry(&self) -> Option<CachedJwksEntry> {
         let mut connection = self.connection.clone();
         let raw: Option<String> = match connection.get(&self.config.cache_key).await {
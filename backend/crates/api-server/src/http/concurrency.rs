@@ -0,0 +1,263 @@
+use axum::Json;
+use axum::http::{HeaderMap, HeaderValue, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use shared::models::{ApiErrorCode, ErrorBody, VersionConflictResponse};
+use uuid::Uuid;
+
+use super::errors::error_response;
+
+pub(super) enum ExpectedVersionError {
+    InvalidIfMatch,
+    Mismatch,
+}
+
+impl IntoResponse for ExpectedVersionError {
+    fn into_response(self) -> Response {
+        match self {
+            Self::InvalidIfMatch => error_response(
+                ApiErrorCode::InvalidIfMatch,
+                "If-Match must be a quoted resource version such as \"3\"",
+            ),
+            Self::Mismatch => error_response(
+                ApiErrorCode::InvalidExpectedVersion,
+                "If-Match and expected_version must name the same version",
+            ),
+        }
+    }
+}
+
+/// Resolves the version a client expects to overwrite from the `If-Match` header and the
+/// body's `expected_version`. `None` means the client opted out of the check; `If-Match: *`
+/// does the same.
+pub(super) fn expected_version(
+    headers: &HeaderMap,
+    body_version: Option<i64>,
+) -> Result<Option<i64>, ExpectedVersionError> {
+    let header_version = match headers.get(header::IF_MATCH) {
+        None => None,
+        Some(value) => {
+            let value = value
+                .to_str()
+                .map_err(|_| ExpectedVersionError::InvalidIfMatch)?
+                .trim();
+            if value == "*" {
+                None
+            } else {
+                Some(parse_if_match_version(value).ok_or(ExpectedVersionError::InvalidIfMatch)?)
+            }
+        }
+    };
+
+    match (header_version, body_version) {
+        (Some(header_version), Some(body_version)) if header_version != body_version => {
+            Err(ExpectedVersionError::Mismatch)
+        }
+        (Some(version), _) | (None, Some(version)) if version < 0 => {
+            Err(ExpectedVersionError::InvalidIfMatch)
+        }
+        (header_version, body_version) => Ok(header_version.or(body_version)),
+    }
+}
+
+/// 409 carrying the resource as currently stored so the client can merge and retry.
+pub(super) fn version_conflict_response<T: Serialize>(current: T) -> Response {
+    (
+        StatusCode::CONFLICT,
+        Json(VersionConflictResponse {
+            error: ErrorBody::new(
+                ApiErrorCode::VersionConflict,
+                "Resource was modified by another request; reload and retry",
+            ),
+            current,
+        }),
+    )
+        .into_response()
+}
+
+/// Weak `ETag` from the timestamps a representation is derived from, in order. A missing
+/// timestamp counts as zero, so a resource that was never saved still gets a stable tag.
+pub(super) fn weak_etag(stamps: &[Option<DateTime<Utc>>]) -> String {
+    let stamps = stamps
+        .iter()
+        .map(|stamp| {
+            stamp
+                .map_or(0, |stamp| stamp.timestamp_micros())
+                .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join(".");
+    format!("W/\"{stamps}\"")
+}
+
+/// Weak `ETag` for a collection from its members' ids and `updated_at` stamps: the newest
+/// stamp, the member count, and a digest of the ids. Removing a member that was not the newest
+/// leaves the newest stamp alone, so the count and digest are what change the tag.
+pub(super) fn weak_collection_etag(members: &[(Uuid, DateTime<Utc>)]) -> String {
+    let newest = members
+        .iter()
+        .map(|(_, updated_at)| updated_at.timestamp_micros())
+        .max()
+        .unwrap_or(0);
+    let mut ids = members.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+    ids.sort_unstable();
+    let mut digest = Sha256::new();
+    for id in &ids {
+        digest.update(id.as_bytes());
+    }
+    let digest = format!("{:x}", digest.finalize());
+    format!("W/\"{newest}.{}.{}\"", ids.len(), &digest[..16])
+}
+
+/// 200 with the body and its `ETag`, or an empty 304 when `If-None-Match` already names the tag.
+pub(super) fn etag_json_response<T: Serialize>(
+    headers: &HeaderMap,
+    etag: &str,
+    body: T,
+) -> Response {
+    if if_none_match_hits(headers, etag)
+        && let Ok(etag_value) = HeaderValue::from_str(etag)
+    {
+        return (StatusCode::NOT_MODIFIED, [(header::ETAG, etag_value)]).into_response();
+    }
+
+    json_with_etag(etag, body)
+}
+
+pub(super) fn json_with_etag<T: Serialize>(etag: &str, body: T) -> Response {
+    match HeaderValue::from_str(etag) {
+        Ok(etag_value) => {
+            (StatusCode::OK, [(header::ETAG, etag_value)], Json(body)).into_response()
+        }
+        Err(_) => (StatusCode::OK, Json(body)).into_response(),
+    }
+}
+
+/// The weak `ETag` a client sent in `If-Match`, if that is what it sent. Plain versions are
+/// left to [`expected_version`].
+pub(super) fn if_match_weak_etag(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::IF_MATCH)?
+        .to_str()
+        .ok()
+        .map(str::trim)
+        .filter(|value| value.starts_with("W/"))
+}
+
+/// The timestamps inside a tag built by [`weak_etag`], in the order they were given.
+pub(super) fn weak_etag_stamps(etag: &str) -> Vec<&str> {
+    opaque_tag(etag).split('.').collect()
+}
+
+/// `If-None-Match` uses the weak comparison, so `W/` prefixes are ignored on both sides.
+fn if_none_match_hits(headers: &HeaderMap, etag: &str) -> bool {
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .any(|candidate| candidate == "*" || opaque_tag(candidate) == opaque_tag(etag))
+}
+
+fn opaque_tag(tag: &str) -> &str {
+    let tag = tag.strip_prefix("W/").unwrap_or(tag);
+    tag.strip_prefix('"')
+        .and_then(|tag| tag.strip_suffix('"'))
+        .unwrap_or(tag)
+}
+
+fn parse_if_match_version(value: &str) -> Option<i64> {
+    let value = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .unwrap_or(value);
+    value.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{HeaderMap, HeaderValue, header};
+
+    use chrono::{TimeZone, Utc};
+    use uuid::Uuid;
+
+    use super::{
+        ExpectedVersionError, expected_version, if_none_match_hits, weak_collection_etag, weak_etag,
+    };
+
+    fn if_match(value: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_MATCH, HeaderValue::from_static(value));
+        headers
+    }
+
+    #[test]
+    fn if_match_accepts_quoted_and_bare_versions() {
+        assert_eq!(
+            expected_version(&if_match("\"4\""), None).ok(),
+            Some(Some(4))
+        );
+        assert_eq!(
+            expected_version(&if_match("4"), Some(4)).ok(),
+            Some(Some(4))
+        );
+        assert_eq!(expected_version(&if_match("*"), None).ok(), Some(None));
+        assert_eq!(
+            expected_version(&HeaderMap::new(), Some(2)).ok(),
+            Some(Some(2))
+        );
+        assert_eq!(expected_version(&HeaderMap::new(), None).ok(), Some(None));
+    }
+
+    #[test]
+    fn malformed_or_disagreeing_versions_are_rejected() {
+        assert!(matches!(
+            expected_version(&if_match("W/\"4\""), None),
+            Err(ExpectedVersionError::InvalidIfMatch)
+        ));
+        assert!(matches!(
+            expected_version(&HeaderMap::new(), Some(-1)),
+            Err(ExpectedVersionError::InvalidIfMatch)
+        ));
+        assert!(matches!(
+            expected_version(&if_match("\"4\""), Some(5)),
+            Err(ExpectedVersionError::Mismatch)
+        ));
+    }
+
+    #[test]
+    fn weak_etags_match_if_none_match_lists() {
+        let updated_at = Utc.timestamp_opt(1_700_000_000, 0).single();
+        let etag = weak_etag(&[updated_at, None]);
+        assert_eq!(etag, "W/\"1700000000000000.0\"");
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::IF_NONE_MATCH,
+            HeaderValue::from_static("W/\"1\", \"1700000000000000.0\""),
+        );
+        assert!(if_none_match_hits(&headers, &etag));
+        assert!(!if_none_match_hits(&headers, &weak_etag(&[None, None])));
+        assert!(!if_none_match_hits(&HeaderMap::new(), &etag));
+    }
+
+    #[test]
+    fn collection_etags_change_when_an_older_member_is_removed() {
+        let older = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let newest = Utc.timestamp_opt(1_700_000_500, 0).unwrap();
+        let older_id = Uuid::new_v4();
+        let newest_id = Uuid::new_v4();
+
+        let both = weak_collection_etag(&[(older_id, older), (newest_id, newest)]);
+        let reordered = weak_collection_etag(&[(newest_id, newest), (older_id, older)]);
+        let without_older = weak_collection_etag(&[(newest_id, newest)]);
+
+        assert_eq!(both, reordered);
+        assert_ne!(both, without_older);
+        assert!(without_older.starts_with("W/\"1700000500000000.1."));
+        assert_eq!(weak_collection_etag(&[]), weak_collection_etag(&[]));
+    }
+}
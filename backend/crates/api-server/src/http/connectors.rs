@@ -1,10 +1,18 @@
+mod caldav;
 mod callback;
 mod helpers;
+mod imap;
 mod list;
 mod revoke;
+mod scopes;
 mod start;
 
-pub(super) use callback::complete_google_connect;
-pub(super) use list::list_connectors;
-pub(super) use revoke::revoke_connector;
-pub(super) use start::start_google_connect;
+pub(super) use caldav::{CONNECT_CALDAV, connect_caldav};
+pub(super) use callback::{COMPLETE_GOOGLE_CONNECT, complete_google_connect};
+pub(super) use imap::{CONNECT_IMAP, connect_imap};
+pub(super) use list::{LIST_CONNECTORS, list_connectors};
+pub(super) use revoke::{
+    REVOKE_ALL_CONNECTORS, REVOKE_CONNECTOR, revoke_all_connectors, revoke_connector,
+};
+pub(super) use scopes::{UPGRADE_CONNECTOR_SCOPES, upgrade_connector_scopes};
+pub(super) use start::{START_GOOGLE_CONNECT, start_google_connect};
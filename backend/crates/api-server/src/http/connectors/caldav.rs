@@ -0,0 +1,84 @@
+use axum::Json;
+use axum::extract::{Extension, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use shared::caldav::{normalize_caldav_server_url, normalize_caldav_username};
+use shared::models::{
+    ApiErrorCode, AuditMetadata, ConnectCaldavRequest, ConnectCaldavResponse, ConnectorStatus,
+};
+use shared::repos::AuditResult;
+
+use super::super::automations::validated_prompt_payload;
+use super::super::errors::{error_response, store_error_response};
+use super::super::openapi::ApiOperation;
+use super::super::request_body::ApiJson;
+use super::super::{AppState, AuthUser, build_enclave_client};
+use super::helpers::map_caldav_connect_enclave_error;
+
+pub(crate) const CONNECT_CALDAV: ApiOperation = ApiOperation::post(
+    "/v1/connectors/caldav",
+    "connectCaldav",
+    "Connectors",
+    "Connect a CalDAV calendar",
+)
+.request::<ConnectCaldavRequest>()
+.response::<ConnectCaldavResponse>();
+
+pub(crate) async fn connect_caldav(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    ApiJson(req): ApiJson<ConnectCaldavRequest>,
+) -> Response {
+    let server_url = match normalize_caldav_server_url(&req.server_url) {
+        Ok(server_url) => server_url,
+        Err(message) => return error_response(ApiErrorCode::InvalidServerUrl, message),
+    };
+    let username = match normalize_caldav_username(&req.username) {
+        Ok(username) => username,
+        Err(message) => return error_response(ApiErrorCode::InvalidUsername, message),
+    };
+    if let Err((code, message)) = validated_prompt_payload(&req.app_password_envelope) {
+        return error_response(code, message);
+    }
+
+    let enclave_client = build_enclave_client(&state);
+    let connect_result = match enclave_client
+        .complete_caldav_connect(
+            user.user_id,
+            server_url,
+            username,
+            req.app_password_envelope,
+        )
+        .await
+    {
+        Ok(response) => response,
+        Err(err) => return map_caldav_connect_enclave_error(err),
+    };
+
+    let mut metadata = AuditMetadata::new();
+    metadata.insert(
+        "connector_id".to_string(),
+        connect_result.connector_id.to_string().into(),
+    );
+
+    if let Err(err) = state
+        .store
+        .add_audit_event(
+            user.user_id,
+            "CALDAV_CONNECT_COMPLETED",
+            Some("caldav"),
+            AuditResult::Success,
+            &metadata,
+        )
+        .await
+    {
+        return store_error_response(err);
+    }
+
+    let response = ConnectCaldavResponse {
+        connector_id: connect_result.connector_id.to_string(),
+        status: ConnectorStatus::Active,
+    };
+
+    (StatusCode::OK, Json(response)).into_response()
+}
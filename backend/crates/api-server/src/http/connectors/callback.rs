@@ -1,48 +1,62 @@
-use std::collections::HashMap;
-
 use axum::Json;
 use axum::extract::{Extension, State};
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
 use chrono::Utc;
 use shared::models::{
-    CompleteGoogleConnectRequest, CompleteGoogleConnectResponse, ConnectorStatus,
+    ApiErrorCode, AuditMetadata, CompleteGoogleConnectRequest, CompleteGoogleConnectResponse,
+    ConnectorStatus,
 };
-use shared::repos::AuditResult;
+use shared::repos::{AuditResult, OAuthScopeUpgrade};
+use uuid::Uuid;
 
-use super::super::errors::{bad_request_response, store_error_response};
+use super::super::errors::{error_response, store_error_response};
+use super::super::openapi::ApiOperation;
+use super::super::request_body::ApiJson;
 use super::super::tokens::hash_token;
-use super::super::{AppState, AuthUser};
-use super::helpers::{build_enclave_client, map_complete_connect_enclave_error};
+use super::super::{AppState, AuthUser, build_enclave_client};
+use super::helpers::map_complete_connect_enclave_error;
+
+pub(crate) const COMPLETE_GOOGLE_CONNECT: ApiOperation = ApiOperation::post(
+    "/v1/connectors/google/callback",
+    "completeGoogleOAuth",
+    "Connectors",
+    "Complete Google OAuth flow",
+)
+.request::<CompleteGoogleConnectRequest>()
+.response::<CompleteGoogleConnectResponse>();
 
 pub(crate) async fn complete_google_connect(
     State(state): State<AppState>,
     Extension(user): Extension<AuthUser>,
-    Json(req): Json<CompleteGoogleConnectRequest>,
+    ApiJson(req): ApiJson<CompleteGoogleConnectRequest>,
 ) -> Response {
-    let Some(redirect_uri) = (match state
+    let Some(oauth_state) = (match state
         .store
-        .consume_oauth_state(user.user_id, &hash_token(&req.state), Utc::now())
+        .consume_oauth_state_record(user.user_id, &hash_token(&req.state), Utc::now())
         .await
     {
-        Ok(redirect_uri) => redirect_uri,
+        Ok(oauth_state) => oauth_state,
         Err(err) => return store_error_response(err),
     }) else {
-        return bad_request_response("invalid_state", "OAuth state is invalid or expired");
+        return error_response(
+            ApiErrorCode::InvalidState,
+            "OAuth state is invalid or expired",
+        );
     };
 
     if let Some(error) = req.error.as_deref() {
         if error == "access_denied" {
-            return bad_request_response(
-                "oauth_consent_denied",
+            return error_response(
+                ApiErrorCode::OauthConsentDenied,
                 req.error_description
                     .as_deref()
                     .unwrap_or("Google consent was denied"),
             );
         }
 
-        return bad_request_response(
-            "oauth_callback_error",
+        return error_response(
+            ApiErrorCode::OauthCallbackError,
             "Google OAuth callback contained an error",
         );
     }
@@ -55,26 +69,50 @@ pub(crate) async fn complete_google_connect(
     {
         Some(code) => code,
         None => {
-            return bad_request_response(
-                "invalid_oauth_code",
+            return error_response(
+                ApiErrorCode::InvalidOauthCode,
                 "Authorization code is missing or invalid",
             );
         }
     };
 
+    // The enclave overwrites the connector's scopes with the new grant, so capture what the
+    // connector held before to merge it back and audit the delta.
+    let previous_scopes = match &oauth_state.scope_upgrade {
+        Some(upgrade) => match state.store.list_connectors(user.user_id).await {
+            Ok(connectors) => connectors
+                .into_iter()
+                .find(|connector| connector.connector_id == upgrade.connector_id)
+                .map(|connector| connector.scopes),
+            Err(err) => return store_error_response(err),
+        },
+        None => None,
+    };
+
     let enclave_client = build_enclave_client(&state);
     let connect_result = enclave_client
-        .complete_google_connect(user.user_id, code.to_string(), redirect_uri)
+        .complete_google_connect(user.user_id, code.to_string(), oauth_state.redirect_uri)
         .await;
     let connect_result = match connect_result {
         Ok(response) => response,
         Err(err) => return map_complete_connect_enclave_error(err),
     };
 
-    let mut metadata = HashMap::new();
+    if let Some(upgrade) = oauth_state.scope_upgrade {
+        return complete_scope_upgrade(
+            &state,
+            user,
+            upgrade,
+            previous_scopes.unwrap_or_default(),
+            connect_result.connector_id,
+        )
+        .await;
+    }
+
+    let mut metadata = AuditMetadata::new();
     metadata.insert(
         "connector_id".to_string(),
-        connect_result.connector_id.to_string(),
+        connect_result.connector_id.to_string().into(),
     );
 
     if let Err(err) = state
@@ -99,3 +137,99 @@ pub(crate) async fn complete_google_connect(
 
     (StatusCode::OK, Json(response)).into_response()
 }
+
+async fn complete_scope_upgrade(
+    state: &AppState,
+    user: AuthUser,
+    upgrade: OAuthScopeUpgrade,
+    previous_scopes: Vec<String>,
+    connector_id: Uuid,
+) -> Response {
+    let mut metadata = AuditMetadata::new();
+    metadata.insert(
+        "connector_id".to_string(),
+        upgrade.connector_id.to_string().into(),
+    );
+    metadata.insert(
+        "requested_scopes".to_string(),
+        upgrade.scopes.join(" ").into(),
+    );
+
+    // Consent under a different Google account links that account as its own connector
+    // instead of upgrading the one the flow started from.
+    if connector_id != upgrade.connector_id {
+        metadata.insert(
+            "linked_connector_id".to_string(),
+            connector_id.to_string().into(),
+        );
+        if let Err(err) = state
+            .store
+            .add_audit_event(
+                user.user_id,
+                "GOOGLE_SCOPE_UPGRADE_COMPLETED",
+                Some("google"),
+                AuditResult::Failure,
+                &metadata,
+            )
+            .await
+        {
+            return store_error_response(err);
+        }
+
+        return error_response(
+            ApiErrorCode::ScopeUpgradeAccountMismatch,
+            "Consent was granted for a different Google account than the connector",
+        );
+    }
+
+    let merged_scopes = match state
+        .store
+        .merge_connector_scopes(user.user_id, connector_id, &previous_scopes)
+        .await
+    {
+        Ok(Some(merged_scopes)) => merged_scopes,
+        Ok(None) => {
+            return error_response(
+                ApiErrorCode::InvalidState,
+                "OAuth state is invalid or expired",
+            );
+        }
+        Err(err) => return store_error_response(err),
+    };
+
+    let added_scopes = merged_scopes
+        .iter()
+        .filter(|scope| !previous_scopes.contains(scope))
+        .cloned()
+        .collect::<Vec<_>>();
+    let denied_scopes = upgrade
+        .scopes
+        .iter()
+        .filter(|scope| !merged_scopes.contains(scope))
+        .cloned()
+        .collect::<Vec<_>>();
+    metadata.insert("added_scopes".to_string(), added_scopes.join(" ").into());
+    metadata.insert("denied_scopes".to_string(), denied_scopes.join(" ").into());
+
+    if let Err(err) = state
+        .store
+        .add_audit_event(
+            user.user_id,
+            "GOOGLE_SCOPE_UPGRADE_COMPLETED",
+            Some("google"),
+            AuditResult::Success,
+            &metadata,
+        )
+        .await
+    {
+        return store_error_response(err);
+    }
+
+    let response = CompleteGoogleConnectResponse {
+        connector_id: connector_id.to_string(),
+        status: ConnectorStatus::Active,
+        granted_scopes: merged_scopes,
+    };
+
+    (StatusCode::OK, Json(response)).into_response()
+}
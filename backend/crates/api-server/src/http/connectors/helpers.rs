@@ -1,59 +1,61 @@
 use axum::response::Response;
-use shared::enclave::{EnclaveRpcClient, EnclaveRpcError};
+use shared::enclave::EnclaveRpcError;
+use shared::models::ApiErrorCode;
 use tracing::warn;
 use url::Url;
 
+use super::super::OAuthConfig;
 use super::super::errors::{
-    bad_gateway_response, bad_request_response, decrypt_not_authorized_response,
+    decrypt_not_authorized_response, error_response, provider_rate_limited_response,
 };
-use super::super::{AppState, OAuthConfig};
-
-pub(super) fn build_enclave_client(state: &AppState) -> EnclaveRpcClient {
-    EnclaveRpcClient::new(
-        state.enclave_rpc.base_url.clone(),
-        state.enclave_rpc.auth.clone(),
-        state.http_client.clone(),
-    )
-}
 
 pub(super) fn map_revoke_enclave_error(err: EnclaveRpcError) -> Response {
     match err {
         EnclaveRpcError::DecryptNotAuthorized { .. } => decrypt_not_authorized_response(),
-        EnclaveRpcError::ConnectorTokenDecryptFailed { .. } => bad_gateway_response(
-            "connector_token_decrypt_failed",
+        EnclaveRpcError::ConnectorTokenDecryptFailed { .. } => error_response(
+            ApiErrorCode::ConnectorTokenDecryptFailed,
             "Connector token decrypt failed",
         ),
-        EnclaveRpcError::ConnectorTokenUnavailable => bad_request_response(
-            "connector_token_unavailable",
+        EnclaveRpcError::ConnectorTokenUnavailable => error_response(
+            ApiErrorCode::ConnectorTokenUnavailable,
             "Connector token metadata changed; retry the request",
         ),
         EnclaveRpcError::ProviderRequestUnavailable { message, .. } => {
             warn!("oauth revoke request failed: {message}");
-            bad_gateway_response(
-                "oauth_revoke_unavailable",
+            error_response(
+                ApiErrorCode::OauthRevokeUnavailable,
                 "Unable to reach Google OAuth revoke endpoint",
             )
         }
         EnclaveRpcError::ProviderRequestFailed { status, .. } => {
             warn!("oauth revoke failed: status={status}");
-            bad_gateway_response("oauth_revoke_failed", "Google token revoke failed")
+            error_response(
+                ApiErrorCode::OauthRevokeFailed,
+                "Google token revoke failed",
+            )
         }
-        EnclaveRpcError::ProviderResponseInvalid { .. } => {
-            bad_gateway_response("oauth_revoke_failed", "Google token revoke failed")
+        EnclaveRpcError::ProviderResponseInvalid { .. } => error_response(
+            ApiErrorCode::OauthRevokeFailed,
+            "Google token revoke failed",
+        ),
+        EnclaveRpcError::ProviderRateLimited { retry_after_ms, .. } => {
+            provider_rate_limited_response(retry_after_ms)
         }
         EnclaveRpcError::RpcUnauthorized { .. }
         | EnclaveRpcError::RpcContractRejected { .. }
         | EnclaveRpcError::RpcTransportUnavailable { .. }
-        | EnclaveRpcError::RpcResponseInvalid { .. } => {
-            bad_gateway_response("enclave_rpc_failed", "Secure enclave RPC request failed")
-        }
+        | EnclaveRpcError::RpcResponseInvalid { .. }
+        | EnclaveRpcError::LlmBudgetExceeded { .. } => error_response(
+            ApiErrorCode::EnclaveRpcFailed,
+            "Secure enclave RPC request failed",
+        ),
     }
 }
 
 pub(super) fn map_complete_connect_enclave_error(err: EnclaveRpcError) -> Response {
     match err {
-        EnclaveRpcError::ProviderRequestUnavailable { .. } => bad_gateway_response(
-            "oauth_unavailable",
+        EnclaveRpcError::ProviderRequestUnavailable { .. } => error_response(
+            ApiErrorCode::OauthUnavailable,
             "Unable to reach Google OAuth token endpoint",
         ),
         EnclaveRpcError::ProviderRequestFailed {
@@ -65,44 +67,132 @@ pub(super) fn map_complete_connect_enclave_error(err: EnclaveRpcError) -> Respon
                 && let Some(error) = oauth_error.as_deref()
             {
                 if error == "invalid_grant" {
-                    return bad_request_response(
-                        "invalid_oauth_code",
+                    return error_response(
+                        ApiErrorCode::InvalidOauthCode,
                         "Authorization code is invalid or expired",
                     );
                 }
 
                 if error == "access_denied" {
-                    return bad_request_response(
-                        "oauth_consent_denied",
+                    return error_response(
+                        ApiErrorCode::OauthConsentDenied,
                         "Google consent was denied",
                     );
                 }
             }
 
-            bad_gateway_response(
-                "oauth_token_exchange_failed",
+            error_response(
+                ApiErrorCode::OauthTokenExchangeFailed,
                 "Google OAuth token exchange failed",
             )
         }
-        EnclaveRpcError::ProviderResponseInvalid { .. } => bad_gateway_response(
-            "oauth_invalid_response",
+        EnclaveRpcError::ProviderResponseInvalid { .. } => error_response(
+            ApiErrorCode::OauthInvalidResponse,
             "Google OAuth token response was invalid",
         ),
-        EnclaveRpcError::ConnectorTokenDecryptFailed { .. } => bad_gateway_response(
-            "oauth_token_store_failed",
+        EnclaveRpcError::ConnectorTokenDecryptFailed { .. } => error_response(
+            ApiErrorCode::OauthTokenStoreFailed,
             "Failed to persist connector token",
         ),
         EnclaveRpcError::DecryptNotAuthorized { .. } => decrypt_not_authorized_response(),
-        EnclaveRpcError::ConnectorTokenUnavailable => bad_gateway_response(
-            "oauth_token_store_failed",
+        EnclaveRpcError::ConnectorTokenUnavailable => error_response(
+            ApiErrorCode::OauthTokenStoreFailed,
             "Failed to persist connector token",
         ),
+        EnclaveRpcError::ProviderRateLimited { retry_after_ms, .. } => {
+            provider_rate_limited_response(retry_after_ms)
+        }
         EnclaveRpcError::RpcUnauthorized { .. }
         | EnclaveRpcError::RpcContractRejected { .. }
         | EnclaveRpcError::RpcTransportUnavailable { .. }
-        | EnclaveRpcError::RpcResponseInvalid { .. } => {
-            bad_gateway_response("enclave_rpc_failed", "Secure enclave RPC request failed")
+        | EnclaveRpcError::RpcResponseInvalid { .. }
+        | EnclaveRpcError::LlmBudgetExceeded { .. } => error_response(
+            ApiErrorCode::EnclaveRpcFailed,
+            "Secure enclave RPC request failed",
+        ),
+    }
+}
+
+pub(super) fn map_caldav_connect_enclave_error(err: EnclaveRpcError) -> Response {
+    match err {
+        EnclaveRpcError::ProviderRequestUnavailable { message, .. } => {
+            warn!("caldav connect request failed: {message}");
+            error_response(
+                ApiErrorCode::CaldavUnavailable,
+                "Unable to reach the CalDAV server",
+            )
         }
+        EnclaveRpcError::ProviderRequestFailed {
+            status: 401 | 403, ..
+        } => error_response(
+            ApiErrorCode::InvalidCaldavCredentials,
+            "CalDAV server rejected the username or app password",
+        ),
+        EnclaveRpcError::ProviderRequestFailed { status, .. } => {
+            warn!("caldav connect failed: status={status}");
+            error_response(
+                ApiErrorCode::InvalidCaldavCalendar,
+                "CalDAV server did not accept the calendar URL",
+            )
+        }
+        EnclaveRpcError::ProviderResponseInvalid { .. } => error_response(
+            ApiErrorCode::InvalidCaldavConnect,
+            "CalDAV connection details are invalid",
+        ),
+        EnclaveRpcError::ConnectorTokenDecryptFailed { .. }
+        | EnclaveRpcError::ConnectorTokenUnavailable => error_response(
+            ApiErrorCode::CaldavCredentialsStoreFailed,
+            "Failed to persist connector credentials",
+        ),
+        EnclaveRpcError::DecryptNotAuthorized { .. } => decrypt_not_authorized_response(),
+        EnclaveRpcError::ProviderRateLimited { retry_after_ms, .. } => {
+            provider_rate_limited_response(retry_after_ms)
+        }
+        EnclaveRpcError::RpcUnauthorized { .. }
+        | EnclaveRpcError::RpcContractRejected { .. }
+        | EnclaveRpcError::RpcTransportUnavailable { .. }
+        | EnclaveRpcError::RpcResponseInvalid { .. }
+        | EnclaveRpcError::LlmBudgetExceeded { .. } => error_response(
+            ApiErrorCode::EnclaveRpcFailed,
+            "Secure enclave RPC request failed",
+        ),
+    }
+}
+
+pub(super) fn map_imap_connect_enclave_error(err: EnclaveRpcError) -> Response {
+    match err {
+        EnclaveRpcError::ProviderRequestUnavailable { message, .. } => {
+            warn!("imap connect request failed: {message}");
+            error_response(
+                ApiErrorCode::ImapUnavailable,
+                "Unable to reach the IMAP server",
+            )
+        }
+        EnclaveRpcError::ProviderRequestFailed { .. } => error_response(
+            ApiErrorCode::InvalidImapCredentials,
+            "IMAP server rejected the username or password",
+        ),
+        EnclaveRpcError::ProviderResponseInvalid { .. } => error_response(
+            ApiErrorCode::InvalidImapConnect,
+            "IMAP connection details are invalid",
+        ),
+        EnclaveRpcError::ConnectorTokenDecryptFailed { .. }
+        | EnclaveRpcError::ConnectorTokenUnavailable => error_response(
+            ApiErrorCode::ImapCredentialsStoreFailed,
+            "Failed to persist connector credentials",
+        ),
+        EnclaveRpcError::DecryptNotAuthorized { .. } => decrypt_not_authorized_response(),
+        EnclaveRpcError::ProviderRateLimited { retry_after_ms, .. } => {
+            provider_rate_limited_response(retry_after_ms)
+        }
+        EnclaveRpcError::RpcUnauthorized { .. }
+        | EnclaveRpcError::RpcContractRejected { .. }
+        | EnclaveRpcError::RpcTransportUnavailable { .. }
+        | EnclaveRpcError::RpcResponseInvalid { .. }
+        | EnclaveRpcError::LlmBudgetExceeded { .. } => error_response(
+            ApiErrorCode::EnclaveRpcFailed,
+            "Secure enclave RPC request failed",
+        ),
     }
 }
 
@@ -117,6 +207,27 @@ pub(super) fn build_google_auth_url(
         .append_pair("response_type", "code")
         .append_pair("scope", &oauth.scopes.join(" "))
         .append_pair("access_type", "offline")
+        .append_pair("prompt", "select_account consent")
+        .append_pair("state", state_token);
+
+    Ok(url.to_string())
+}
+
+/// Builds an incremental consent URL: Google only asks for `scopes`, and the resulting
+/// grant also carries every scope the account already granted.
+pub(super) fn build_google_scope_upgrade_url(
+    oauth: &OAuthConfig,
+    state_token: &str,
+    scopes: &[String],
+) -> Result<String, url::ParseError> {
+    let mut url = Url::parse(&oauth.auth_url)?;
+    url.query_pairs_mut()
+        .append_pair("client_id", &oauth.client_id)
+        .append_pair("redirect_uri", &oauth.redirect_uri)
+        .append_pair("response_type", "code")
+        .append_pair("scope", &scopes.join(" "))
+        .append_pair("access_type", "offline")
+        .append_pair("include_granted_scopes", "true")
         .append_pair("prompt", "consent")
         .append_pair("state", state_token);
 
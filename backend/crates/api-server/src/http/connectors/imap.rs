@@ -0,0 +1,83 @@
+use axum::Json;
+use axum::extract::{Extension, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use shared::imap::{normalize_imap_host, normalize_imap_port, normalize_imap_username};
+use shared::models::{
+    ApiErrorCode, AuditMetadata, ConnectImapRequest, ConnectImapResponse, ConnectorStatus,
+};
+use shared::repos::AuditResult;
+
+use super::super::automations::validated_prompt_payload;
+use super::super::errors::{error_response, store_error_response};
+use super::super::openapi::ApiOperation;
+use super::super::request_body::ApiJson;
+use super::super::{AppState, AuthUser, build_enclave_client};
+use super::helpers::map_imap_connect_enclave_error;
+
+pub(crate) const CONNECT_IMAP: ApiOperation = ApiOperation::post(
+    "/v1/connectors/imap",
+    "connectImap",
+    "Connectors",
+    "Connect an IMAP mailbox",
+)
+.request::<ConnectImapRequest>()
+.response::<ConnectImapResponse>();
+
+pub(crate) async fn connect_imap(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    ApiJson(req): ApiJson<ConnectImapRequest>,
+) -> Response {
+    let host = match normalize_imap_host(&req.host) {
+        Ok(host) => host,
+        Err(message) => return error_response(ApiErrorCode::InvalidHost, message),
+    };
+    let port = match normalize_imap_port(req.port) {
+        Ok(port) => port,
+        Err(message) => return error_response(ApiErrorCode::InvalidPort, message),
+    };
+    let username = match normalize_imap_username(&req.username) {
+        Ok(username) => username,
+        Err(message) => return error_response(ApiErrorCode::InvalidUsername, message),
+    };
+    if let Err((code, message)) = validated_prompt_payload(&req.password_envelope) {
+        return error_response(code, message);
+    }
+
+    let enclave_client = build_enclave_client(&state);
+    let connect_result = match enclave_client
+        .complete_imap_connect(user.user_id, host, port, username, req.password_envelope)
+        .await
+    {
+        Ok(response) => response,
+        Err(err) => return map_imap_connect_enclave_error(err),
+    };
+
+    let mut metadata = AuditMetadata::new();
+    metadata.insert(
+        "connector_id".to_string(),
+        connect_result.connector_id.to_string().into(),
+    );
+
+    if let Err(err) = state
+        .store
+        .add_audit_event(
+            user.user_id,
+            "IMAP_CONNECT_COMPLETED",
+            Some("imap"),
+            AuditResult::Success,
+            &metadata,
+        )
+        .await
+    {
+        return store_error_response(err);
+    }
+
+    let response = ConnectImapResponse {
+        connector_id: connect_result.connector_id.to_string(),
+        status: ConnectorStatus::Active,
+    };
+
+    (StatusCode::OK, Json(response)).into_response()
+}
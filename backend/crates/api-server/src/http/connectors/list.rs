@@ -1,21 +1,39 @@
-use axum::Json;
 use axum::extract::{Extension, State};
-use axum::http::StatusCode;
-use axum::response::{IntoResponse, Response};
+use axum::http::HeaderMap;
+use axum::response::Response;
 use shared::models::{ConnectorStatus, ConnectorSummary, ListConnectorsResponse};
 use shared::repos::StoreError;
 
+use super::super::concurrency::{etag_json_response, weak_collection_etag};
 use super::super::errors::store_error_response;
+use super::super::openapi::ApiOperation;
 use super::super::{AppState, AuthUser};
 
+pub(crate) const LIST_CONNECTORS: ApiOperation = ApiOperation::get(
+    "/v1/connectors",
+    "listConnectors",
+    "Connectors",
+    "List connector metadata for the current user",
+)
+.response::<ListConnectorsResponse>();
+
+/// Carries a weak `ETag` over the connector ids and their newest `updated_at`; `If-None-Match`
+/// hits get 304.
 pub(crate) async fn list_connectors(
     State(state): State<AppState>,
     Extension(user): Extension<AuthUser>,
+    headers: HeaderMap,
 ) -> Response {
-    let connectors = match state.store.list_connector_states(user.user_id).await {
+    let connectors = match state.store.list_connectors(user.user_id).await {
         Ok(connectors) => connectors,
         Err(err) => return store_error_response(err),
     };
+    let etag = weak_collection_etag(
+        &connectors
+            .iter()
+            .map(|connector| (connector.connector_id, connector.updated_at))
+            .collect::<Vec<_>>(),
+    );
 
     let mut items = Vec::with_capacity(connectors.len());
     for connector in connectors {
@@ -33,8 +51,12 @@ pub(crate) async fn list_connectors(
             connector_id: connector.connector_id.to_string(),
             provider: connector.provider,
             status,
+            scopes: connector.scopes,
+            token_rotated_at: connector.token_rotated_at,
+            created_at: connector.created_at,
+            health_score: connector.health_score,
         });
     }
 
-    (StatusCode::OK, Json(ListConnectorsResponse { items })).into_response()
+    etag_json_response(&headers, &etag, ListConnectorsResponse { items })
 }
@@ -1,17 +1,27 @@
-use std::collections::HashMap;
-
 use axum::Json;
 use axum::extract::{Extension, Path, State};
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
 use shared::enclave::ConnectorSecretRequest;
-use shared::models::{ConnectorStatus, ErrorBody, ErrorResponse, RevokeConnectorResponse};
-use shared::repos::AuditResult;
+use shared::models::{
+    ApiErrorCode, AuditMetadata, ConnectorStatus, RevokeAllConnectorsResponse,
+    RevokeConnectorResponse, RevokedConnector,
+};
+use shared::repos::{AuditResult, ConnectorKeyMetadata, JobType};
 use uuid::Uuid;
 
-use super::super::errors::{bad_request_response, store_error_response};
-use super::super::{AppState, AuthUser};
-use super::helpers::{build_enclave_client, map_revoke_enclave_error};
+use super::super::errors::{error_response, store_error_response};
+use super::super::openapi::ApiOperation;
+use super::super::{AppState, AuthUser, build_enclave_client};
+use super::helpers::map_revoke_enclave_error;
+
+pub(crate) const REVOKE_CONNECTOR: ApiOperation = ApiOperation::delete(
+    "/v1/connectors/{connector_id}",
+    "revokeConnector",
+    "Connectors",
+    "Revoke connector",
+)
+.response::<RevokeConnectorResponse>();
 
 pub(crate) async fn revoke_connector(
     State(state): State<AppState>,
@@ -21,16 +31,7 @@ pub(crate) async fn revoke_connector(
     let connector_id = match Uuid::parse_str(&connector_id) {
         Ok(connector_id) => connector_id,
         Err(_) => {
-            return (
-                StatusCode::NOT_FOUND,
-                Json(ErrorResponse {
-                    error: ErrorBody {
-                        code: "not_found".to_string(),
-                        message: "Connector not found".to_string(),
-                    },
-                }),
-            )
-                .into_response();
+            return error_response(ApiErrorCode::NotFound, "Connector not found");
         }
     };
 
@@ -41,68 +42,17 @@ pub(crate) async fn revoke_connector(
     {
         Ok(Some(connector_metadata)) => connector_metadata,
         Ok(None) => {
-            return (
-                StatusCode::NOT_FOUND,
-                Json(ErrorResponse {
-                    error: ErrorBody {
-                        code: "not_found".to_string(),
-                        message: "Connector not found".to_string(),
-                    },
-                }),
-            )
-                .into_response();
+            return error_response(ApiErrorCode::NotFound, "Connector not found");
         }
         Err(err) => return store_error_response(err),
     };
 
-    if connector_metadata.provider != "google" {
-        return bad_request_response(
-            "unsupported_provider",
-            "Connector provider is not supported",
-        );
-    }
-
-    if connector_metadata.token_key_id != state.secret_runtime.kms_key_id()
-        || connector_metadata.token_version != state.secret_runtime.kms_key_version()
-    {
-        match state
-            .store
-            .ensure_active_connector_key_metadata(
-                user.user_id,
-                connector_id,
-                state.secret_runtime.kms_key_id(),
-                state.secret_runtime.kms_key_version(),
-            )
-            .await
-        {
-            Ok(Some(_)) => {}
-            Ok(None) => {
-                return (
-                    StatusCode::NOT_FOUND,
-                    Json(ErrorResponse {
-                        error: ErrorBody {
-                            code: "not_found".to_string(),
-                            message: "Connector not found".to_string(),
-                        },
-                    }),
-                )
-                    .into_response();
-            }
-            Err(err) => return store_error_response(err),
-        }
-    }
-
-    let enclave_client = build_enclave_client(&state);
-    let enclave_response = match enclave_client
-        .revoke_google_connector_token(ConnectorSecretRequest {
-            user_id: user.user_id,
-            connector_id,
-        })
-        .await
-    {
-        Ok(response) => response,
-        Err(err) => return map_revoke_enclave_error(err),
-    };
+    let provider = connector_metadata.provider.clone();
+    let attested_measurement =
+        match revoke_upstream(&state, user.user_id, connector_id, &connector_metadata).await {
+            Ok(attested_measurement) => attested_measurement,
+            Err(response) => return response,
+        };
 
     match state
         .store
@@ -110,19 +60,21 @@ pub(crate) async fn revoke_connector(
         .await
     {
         Ok(true) => {
-            let mut metadata = HashMap::new();
-            metadata.insert("connector_id".to_string(), connector_id.to_string());
-            metadata.insert(
-                "attested_measurement".to_string(),
-                enclave_response.attested_identity.measurement,
-            );
+            let mut metadata = AuditMetadata::new();
+            metadata.insert("connector_id".to_string(), connector_id.to_string().into());
+            if let Some(attested_measurement) = attested_measurement {
+                metadata.insert(
+                    "attested_measurement".to_string(),
+                    attested_measurement.into(),
+                );
+            }
 
             if let Err(err) = state
                 .store
                 .add_audit_event(
                     user.user_id,
                     "CONNECTOR_REVOKED",
-                    Some("google"),
+                    Some(provider.as_str()),
                     AuditResult::Success,
                     &metadata,
                 )
@@ -139,16 +91,193 @@ pub(crate) async fn revoke_connector(
             )
                 .into_response()
         }
-        Ok(false) => (
-            StatusCode::NOT_FOUND,
-            Json(ErrorResponse {
-                error: ErrorBody {
-                    code: "not_found".to_string(),
-                    message: "Connector not found".to_string(),
-                },
-            }),
-        )
-            .into_response(),
+        Ok(false) => error_response(ApiErrorCode::NotFound, "Connector not found"),
         Err(err) => store_error_response(err),
     }
 }
+
+pub(crate) const REVOKE_ALL_CONNECTORS: ApiOperation = ApiOperation::delete(
+    "/v1/connectors",
+    "revokeAllConnectors",
+    "Connectors",
+    "Revoke every active connector",
+)
+.response::<RevokeAllConnectorsResponse>();
+
+/// Revokes connectors one at a time, oldest first. If an upstream revoke fails the call stops
+/// there: connectors already revoked stay revoked and are still recorded in the audit event,
+/// and retrying picks up the remaining active ones.
+pub(crate) async fn revoke_all_connectors(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+) -> Response {
+    let connectors = match state
+        .store
+        .list_active_connector_metadata(user.user_id)
+        .await
+    {
+        Ok(connectors) => connectors,
+        Err(err) => return store_error_response(err),
+    };
+
+    let mut revoked = Vec::new();
+    let mut failure = None;
+    for connector in connectors {
+        let connector_metadata = ConnectorKeyMetadata {
+            provider: connector.provider,
+            token_key_id: connector.token_key_id,
+            token_version: connector.token_version,
+        };
+        if let Err(response) = revoke_upstream(
+            &state,
+            user.user_id,
+            connector.connector_id,
+            &connector_metadata,
+        )
+        .await
+        {
+            failure = Some(response);
+            break;
+        }
+
+        match state
+            .store
+            .revoke_connector(user.user_id, connector.connector_id)
+            .await
+        {
+            Ok(true) => revoked.push(RevokedConnector {
+                connector_id: connector.connector_id.to_string(),
+                provider: connector_metadata.provider,
+            }),
+            // Revoked concurrently by another request.
+            Ok(false) => {}
+            Err(err) => {
+                failure = Some(store_error_response(err));
+                break;
+            }
+        }
+    }
+
+    // Departure alerts read from connected calendars, so queued checks cannot run any more.
+    let mut cancelled_job_count = 0;
+    if failure.is_none() && !revoked.is_empty() {
+        match state
+            .store
+            .cancel_pending_jobs(
+                user.user_id,
+                JobType::DepartureAlert,
+                "CONNECTORS_REVOKED",
+                "connectors were revoked before the job ran",
+            )
+            .await
+        {
+            Ok(count) => cancelled_job_count = count,
+            Err(err) => failure = Some(store_error_response(err)),
+        }
+    }
+
+    if failure.is_none() || !revoked.is_empty() {
+        let mut metadata = AuditMetadata::new();
+        metadata.insert(
+            "connector_ids".to_string(),
+            revoked
+                .iter()
+                .map(|connector| connector.connector_id.clone())
+                .collect::<Vec<_>>()
+                .into(),
+        );
+        metadata.insert(
+            "providers".to_string(),
+            revoked
+                .iter()
+                .map(|connector| connector.provider.clone())
+                .collect::<Vec<_>>()
+                .into(),
+        );
+        metadata.insert(
+            "cancelled_job_count".to_string(),
+            cancelled_job_count.into(),
+        );
+        let result = if failure.is_some() {
+            AuditResult::Failure
+        } else {
+            AuditResult::Success
+        };
+
+        if let Err(err) = state
+            .store
+            .add_audit_event(user.user_id, "CONNECTORS_REVOKED", None, result, &metadata)
+            .await
+        {
+            return store_error_response(err);
+        }
+    }
+
+    if let Some(response) = failure {
+        return response;
+    }
+
+    (
+        StatusCode::OK,
+        Json(RevokeAllConnectorsResponse {
+            revoked,
+            cancelled_job_count,
+        }),
+    )
+        .into_response()
+}
+
+/// Revokes the provider token through the enclave and returns the attested measurement. CalDAV
+/// and IMAP passwords have no revoke endpoint; the user withdraws them at the provider, so
+/// revoking only drops the stored credentials.
+async fn revoke_upstream(
+    state: &AppState,
+    user_id: Uuid,
+    connector_id: Uuid,
+    connector_metadata: &ConnectorKeyMetadata,
+) -> Result<Option<String>, Response> {
+    match connector_metadata.provider.as_str() {
+        "google" => {
+            if connector_metadata.token_key_id != state.secret_runtime.kms_key_id()
+                || connector_metadata.token_version != state.secret_runtime.kms_key_version()
+            {
+                match state
+                    .store
+                    .ensure_active_connector_key_metadata(
+                        user_id,
+                        connector_id,
+                        state.secret_runtime.kms_key_id(),
+                        state.secret_runtime.kms_key_version(),
+                    )
+                    .await
+                {
+                    Ok(Some(_)) => {}
+                    Ok(None) => {
+                        return Err(error_response(
+                            ApiErrorCode::NotFound,
+                            "Connector not found",
+                        ));
+                    }
+                    Err(err) => return Err(store_error_response(err)),
+                }
+            }
+
+            let enclave_client = build_enclave_client(state);
+            match enclave_client
+                .revoke_google_connector_token(ConnectorSecretRequest {
+                    user_id,
+                    connector_id,
+                })
+                .await
+            {
+                Ok(response) => Ok(Some(response.attested_identity.measurement)),
+                Err(err) => Err(map_revoke_enclave_error(err)),
+            }
+        }
+        "caldav" | "imap" => Ok(None),
+        _ => Err(error_response(
+            ApiErrorCode::UnsupportedProvider,
+            "Connector provider is not supported",
+        )),
+    }
+}
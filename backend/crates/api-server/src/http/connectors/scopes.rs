@@ -0,0 +1,152 @@
+use axum::Json;
+use axum::extract::{Extension, Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use chrono::{Duration, Utc};
+use shared::connector_health::UPGRADEABLE_GOOGLE_SCOPES;
+use shared::models::{
+    ApiErrorCode, AuditMetadata, UpgradeConnectorScopesRequest, UpgradeConnectorScopesResponse,
+};
+use shared::repos::AuditResult;
+use tracing::warn;
+use uuid::Uuid;
+
+use super::super::errors::{error_response, store_error_response};
+use super::super::openapi::ApiOperation;
+use super::super::request_body::ApiJson;
+use super::super::tokens::{generate_secure_token, hash_token};
+use super::super::{AppState, AuthUser};
+use super::helpers::build_google_scope_upgrade_url;
+use super::start::IOS_OAUTH_CALLBACK_URI;
+
+pub(crate) const UPGRADE_CONNECTOR_SCOPES: ApiOperation = ApiOperation::post(
+    "/v1/connectors/{connector_id}/scopes/upgrade",
+    "upgradeConnectorScopes",
+    "Connectors",
+    "Start incremental consent for additional Google scopes",
+)
+.request::<UpgradeConnectorScopesRequest>()
+.response::<UpgradeConnectorScopesResponse>();
+
+pub(crate) async fn upgrade_connector_scopes(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(connector_id): Path<String>,
+    ApiJson(req): ApiJson<UpgradeConnectorScopesRequest>,
+) -> Response {
+    let Ok(connector_id) = Uuid::parse_str(&connector_id) else {
+        return connector_not_found_response();
+    };
+
+    if req.redirect_uri != state.oauth.redirect_uri && req.redirect_uri != IOS_OAUTH_CALLBACK_URI {
+        return error_response(
+            ApiErrorCode::InvalidRedirectUri,
+            "Provided redirect URI does not match configured redirect URI",
+        );
+    }
+
+    if req.scopes.is_empty()
+        || req
+            .scopes
+            .iter()
+            .any(|scope| !UPGRADEABLE_GOOGLE_SCOPES.contains(&scope.as_str()))
+    {
+        return error_response(
+            ApiErrorCode::InvalidScope,
+            "Requested scopes must be non-empty and supported for upgrade",
+        );
+    }
+
+    let connectors = match state.store.list_connectors(user.user_id).await {
+        Ok(connectors) => connectors,
+        Err(err) => return store_error_response(err),
+    };
+    let Some(connector) = connectors
+        .into_iter()
+        .find(|connector| connector.connector_id == connector_id && connector.status == "ACTIVE")
+    else {
+        return connector_not_found_response();
+    };
+    if connector.provider != "google" {
+        return error_response(
+            ApiErrorCode::UnsupportedProvider,
+            "Only Google connectors support scope upgrades",
+        );
+    }
+
+    let mut missing_scopes = Vec::new();
+    for scope in req.scopes {
+        if !connector.scopes.contains(&scope) && !missing_scopes.contains(&scope) {
+            missing_scopes.push(scope);
+        }
+    }
+    if missing_scopes.is_empty() {
+        return error_response(
+            ApiErrorCode::ScopesAlreadyGranted,
+            "Connector already has every requested scope",
+        );
+    }
+
+    let state_token = generate_secure_token("st");
+    if let Err(err) = state
+        .store
+        .store_oauth_scope_upgrade_state(
+            user.user_id,
+            &hash_token(&state_token),
+            &state.oauth.redirect_uri,
+            Utc::now() + Duration::seconds(state.oauth_state_ttl_seconds as i64),
+            connector_id,
+            &missing_scopes,
+        )
+        .await
+    {
+        return store_error_response(err);
+    }
+
+    let auth_url = match build_google_scope_upgrade_url(&state.oauth, &state_token, &missing_scopes)
+    {
+        Ok(auth_url) => auth_url,
+        Err(err) => {
+            warn!("failed to construct oauth scope upgrade url: {err}");
+            return error_response(
+                ApiErrorCode::OauthConfigError,
+                "Google OAuth configuration is invalid",
+            );
+        }
+    };
+
+    let mut metadata = AuditMetadata::new();
+    metadata.insert("connector_id".to_string(), connector_id.to_string().into());
+    metadata.insert(
+        "requested_scopes".to_string(),
+        missing_scopes.join(" ").into(),
+    );
+
+    if let Err(err) = state
+        .store
+        .add_audit_event(
+            user.user_id,
+            "GOOGLE_SCOPE_UPGRADE_STARTED",
+            Some("google"),
+            AuditResult::Success,
+            &metadata,
+        )
+        .await
+    {
+        return store_error_response(err);
+    }
+
+    (
+        StatusCode::OK,
+        Json(UpgradeConnectorScopesResponse {
+            auth_url,
+            state: state_token,
+            requested_scopes: missing_scopes,
+        }),
+    )
+        .into_response()
+}
+
+fn connector_not_found_response() -> Response {
+    error_response(ApiErrorCode::NotFound, "Connector not found")
+}
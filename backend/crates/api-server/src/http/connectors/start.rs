@@ -1,28 +1,39 @@
-use std::collections::HashMap;
-
 use axum::Json;
 use axum::extract::{Extension, State};
 use axum::response::{IntoResponse, Response};
 use chrono::{Duration, Utc};
-use shared::models::{StartGoogleConnectRequest, StartGoogleConnectResponse};
+use shared::models::{
+    ApiErrorCode, AuditMetadata, StartGoogleConnectRequest, StartGoogleConnectResponse,
+};
 use shared::repos::AuditResult;
 use tracing::warn;
 
-use super::super::errors::{bad_request_response, store_error_response};
+use super::super::errors::{error_response, store_error_response};
+use super::super::openapi::ApiOperation;
+use super::super::request_body::ApiJson;
 use super::super::tokens::{generate_secure_token, hash_token};
 use super::super::{AppState, AuthUser};
 use super::helpers::build_google_auth_url;
 
-const IOS_OAUTH_CALLBACK_URI: &str = "alfred://oauth/google/callback";
+pub(super) const IOS_OAUTH_CALLBACK_URI: &str = "alfred://oauth/google/callback";
+
+pub(crate) const START_GOOGLE_CONNECT: ApiOperation = ApiOperation::post(
+    "/v1/connectors/google/start",
+    "startGoogleOAuth",
+    "Connectors",
+    "Start Google OAuth flow",
+)
+.request::<StartGoogleConnectRequest>()
+.response::<StartGoogleConnectResponse>();
 
 pub(crate) async fn start_google_connect(
     State(state): State<AppState>,
     Extension(user): Extension<AuthUser>,
-    Json(req): Json<StartGoogleConnectRequest>,
+    ApiJson(req): ApiJson<StartGoogleConnectRequest>,
 ) -> Response {
     if req.redirect_uri != state.oauth.redirect_uri && req.redirect_uri != IOS_OAUTH_CALLBACK_URI {
-        return bad_request_response(
-            "invalid_redirect_uri",
+        return error_response(
+            ApiErrorCode::InvalidRedirectUri,
             "Provided redirect URI does not match configured redirect URI",
         );
     }
@@ -46,8 +57,8 @@ pub(crate) async fn start_google_connect(
         Ok(auth_url) => auth_url,
         Err(err) => {
             warn!("failed to construct oauth url: {err}");
-            return bad_request_response(
-                "oauth_config_error",
+            return error_response(
+                ApiErrorCode::OauthConfigError,
                 "Google OAuth configuration is invalid",
             );
         }
@@ -58,8 +69,8 @@ pub(crate) async fn start_google_connect(
         state: state_token,
     };
 
-    let mut metadata = HashMap::new();
-    metadata.insert("redirect_uri".to_string(), req.redirect_uri);
+    let mut metadata = AuditMetadata::new();
+    metadata.insert("redirect_uri".to_string(), req.redirect_uri.into());
 
     if let Err(err) = state
         .store
@@ -0,0 +1,36 @@
+use std::time::Duration;
+
+use axum::http::{HeaderName, HeaderValue, Method};
+use shared::config::CorsConfig;
+use tower_http::cors::{AllowOrigin, CorsLayer};
+
+/// `None` when no origin is allow-listed: without CORS headers browsers refuse every
+/// cross-origin call, which is the default.
+pub(super) fn cors_layer(config: &CorsConfig) -> Option<CorsLayer> {
+    let origins = config
+        .allowed_origins
+        .iter()
+        .filter_map(|origin| HeaderValue::from_str(origin).ok())
+        .collect::<Vec<_>>();
+    if origins.is_empty() {
+        return None;
+    }
+    let methods = config
+        .allowed_methods
+        .iter()
+        .filter_map(|method| Method::from_bytes(method.as_bytes()).ok())
+        .collect::<Vec<_>>();
+    let headers = config
+        .allowed_headers
+        .iter()
+        .filter_map(|name| HeaderName::from_bytes(name.as_bytes()).ok())
+        .collect::<Vec<_>>();
+
+    Some(
+        CorsLayer::new()
+            .allow_origin(AllowOrigin::list(origins))
+            .allow_methods(methods)
+            .allow_headers(headers)
+            .max_age(Duration::from_secs(config.max_age_seconds)),
+    )
+}
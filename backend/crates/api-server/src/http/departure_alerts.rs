@@ -0,0 +1,241 @@
+use axum::extract::{Extension, State};
+use axum::http::HeaderMap;
+use axum::response::{IntoResponse, Response};
+use chrono::Utc;
+use shared::automation_schedule::{format_local_time_hhmm, parse_local_time_hhmm};
+use shared::departure_alert::{DepartureAlertSettings, validate_departure_minutes};
+use shared::models::{
+    ApiErrorCode, AuditMetadata, DepartureAlertPreferencesResponse,
+    UpdateDepartureAlertPreferencesRequest,
+};
+use shared::repos::{AuditResult, DepartureAlertPreferencesRecord, StoreError};
+use shared::timezone::{DEFAULT_USER_TIME_ZONE, normalize_time_zone};
+
+use super::automations::validated_prompt_payload;
+use super::concurrency::{
+    ExpectedVersionError, etag_json_response, expected_version, if_match_weak_etag, json_with_etag,
+    version_conflict_response, weak_etag, weak_etag_stamps,
+};
+use super::errors::{error_response, store_error_response};
+use super::openapi::ApiOperation;
+use super::request_body::ApiJson;
+use super::{AppState, AuthUser};
+
+const DEFAULT_CHECK_LOCAL_TIME_MINUTES: u16 = 6 * 60;
+const DEFAULT_TRAVEL_MINUTES: u16 = 30;
+const DEFAULT_BUFFER_MINUTES: u16 = 10;
+
+pub(super) const GET_DEPARTURE_ALERT_PREFERENCES: ApiOperation = ApiOperation::get(
+    "/v1/departure-alerts/preferences",
+    "getDepartureAlertPreferences",
+    "Departure Alerts",
+    "Get departure alert preferences",
+)
+.response::<DepartureAlertPreferencesResponse>();
+
+/// Carries a weak `ETag` from `updated_at` and `next_check_at`; `If-None-Match` hits get 304.
+pub(super) async fn get_departure_alert_preferences(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    headers: HeaderMap,
+) -> Response {
+    match current_departure_preferences(&state, user).await {
+        Ok(preferences) => {
+            let etag = departure_preferences_etag(&preferences);
+            etag_json_response(&headers, &etag, preferences)
+        }
+        Err(err) => store_error_response(err),
+    }
+}
+
+pub(super) const UPDATE_DEPARTURE_ALERT_PREFERENCES: ApiOperation = ApiOperation::put(
+    "/v1/departure-alerts/preferences",
+    "updateDepartureAlertPreferences",
+    "Departure Alerts",
+    "Replace departure alert preferences",
+)
+.request::<UpdateDepartureAlertPreferencesRequest>()
+.response::<DepartureAlertPreferencesResponse>();
+
+pub(super) async fn update_departure_alert_preferences(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    headers: HeaderMap,
+    ApiJson(request): ApiJson<UpdateDepartureAlertPreferencesRequest>,
+) -> Response {
+    let expected_version = match if_match_weak_etag(&headers) {
+        Some(etag) => match version_for_etag(&state, user, etag, request.expected_version).await {
+            Ok(expected_version) => Some(expected_version),
+            Err(response) => return response,
+        },
+        None => match expected_version(&headers, request.expected_version) {
+            Ok(expected_version) => expected_version,
+            Err(err) => return err.into_response(),
+        },
+    };
+    let Some(time_zone) = normalize_time_zone(request.time_zone.as_str()) else {
+        return error_response(
+            ApiErrorCode::InvalidTimeZone,
+            "time_zone must be a valid IANA time zone",
+        );
+    };
+    let Some(check_local_time_minutes) = parse_local_time_hhmm(request.check_time.as_str()) else {
+        return error_response(
+            ApiErrorCode::InvalidCheckTime,
+            "check_time must use HH:MM 24-hour format",
+        );
+    };
+    if let Err(message) = validate_departure_minutes(request.travel_minutes, request.buffer_minutes)
+    {
+        return error_response(ApiErrorCode::InvalidDepartureMinutes, message);
+    }
+    if let Some(envelope) = request.home_location_envelope.as_ref()
+        && let Err((code, message)) = validated_prompt_payload(envelope)
+    {
+        return error_response(code, message);
+    }
+
+    let settings = DepartureAlertSettings {
+        enabled: request.enabled,
+        time_zone,
+        check_local_time_minutes,
+        travel_minutes: request.travel_minutes,
+        buffer_minutes: request.buffer_minutes,
+    };
+    // Saving schedules a check right away so a change made mid-morning still covers today.
+    let record = match state
+        .store
+        .upsert_departure_alert_preferences(
+            user.user_id,
+            &settings,
+            request.home_location_envelope.as_ref(),
+            Some(Utc::now()),
+            expected_version,
+        )
+        .await
+    {
+        Ok(Some(record)) => record,
+        Ok(None) => return departure_preferences_conflict_response(&state, user).await,
+        Err(err) => return departure_store_error_response(err),
+    };
+
+    let mut metadata = AuditMetadata::new();
+    metadata.insert("enabled".to_string(), record.settings.enabled.into());
+    metadata.insert(
+        "time_zone".to_string(),
+        record.settings.time_zone.clone().into(),
+    );
+    metadata.insert(
+        "check_time".to_string(),
+        format_local_time_hhmm(record.settings.check_local_time_minutes).into(),
+    );
+    metadata.insert(
+        "has_home_location".to_string(),
+        record.has_home_location.into(),
+    );
+
+    if let Err(err) = state
+        .store
+        .add_audit_event(
+            user.user_id,
+            "DEPARTURE_ALERT_PREFERENCES_UPDATED",
+            None,
+            AuditResult::Success,
+            &metadata,
+        )
+        .await
+    {
+        return store_error_response(err);
+    }
+
+    let preferences = departure_preferences_response(record);
+    let etag = departure_preferences_etag(&preferences);
+    json_with_etag(&etag, preferences)
+}
+
+/// Resolves an `If-Match` ETag to the stored version. Only the `updated_at` part has to match:
+/// the worker moves `next_check_at` on its own, and that is not an edit the client could lose.
+async fn version_for_etag(
+    state: &AppState,
+    user: AuthUser,
+    etag: &str,
+    body_version: Option<i64>,
+) -> Result<i64, Response> {
+    let current = current_departure_preferences(state, user)
+        .await
+        .map_err(store_error_response)?;
+    let updated_at = current
+        .updated_at
+        .map_or(0, |updated_at| updated_at.timestamp_micros())
+        .to_string();
+    if weak_etag_stamps(etag).first() != Some(&updated_at.as_str()) {
+        return Err(version_conflict_response(current));
+    }
+    if body_version.is_some_and(|body_version| body_version != current.version) {
+        return Err(ExpectedVersionError::Mismatch.into_response());
+    }
+
+    Ok(current.version)
+}
+
+fn departure_preferences_etag(preferences: &DepartureAlertPreferencesResponse) -> String {
+    weak_etag(&[preferences.updated_at, preferences.next_check_at])
+}
+
+async fn current_departure_preferences(
+    state: &AppState,
+    user: AuthUser,
+) -> Result<DepartureAlertPreferencesResponse, StoreError> {
+    Ok(state
+        .store
+        .get_departure_alert_preferences(user.user_id)
+        .await?
+        .map(departure_preferences_response)
+        .unwrap_or_else(default_departure_preferences))
+}
+
+fn departure_preferences_response(
+    record: DepartureAlertPreferencesRecord,
+) -> DepartureAlertPreferencesResponse {
+    DepartureAlertPreferencesResponse {
+        enabled: record.settings.enabled,
+        check_time: format_local_time_hhmm(record.settings.check_local_time_minutes),
+        time_zone: record.settings.time_zone,
+        travel_minutes: record.settings.travel_minutes,
+        buffer_minutes: record.settings.buffer_minutes,
+        has_home_location: record.has_home_location,
+        next_check_at: record.next_check_at,
+        version: record.version,
+        updated_at: Some(record.updated_at),
+    }
+}
+
+fn default_departure_preferences() -> DepartureAlertPreferencesResponse {
+    DepartureAlertPreferencesResponse {
+        enabled: false,
+        time_zone: DEFAULT_USER_TIME_ZONE.to_string(),
+        check_time: format_local_time_hhmm(DEFAULT_CHECK_LOCAL_TIME_MINUTES),
+        travel_minutes: DEFAULT_TRAVEL_MINUTES,
+        buffer_minutes: DEFAULT_BUFFER_MINUTES,
+        has_home_location: false,
+        next_check_at: None,
+        version: 0,
+        updated_at: None,
+    }
+}
+
+async fn departure_preferences_conflict_response(state: &AppState, user: AuthUser) -> Response {
+    match current_departure_preferences(state, user).await {
+        Ok(preferences) => version_conflict_response(preferences),
+        Err(err) => store_error_response(err),
+    }
+}
+
+fn departure_store_error_response(err: StoreError) -> Response {
+    match err {
+        StoreError::InvalidData(message) => {
+            error_response(ApiErrorCode::InvalidDepartureAlertRequest, &message)
+        }
+        other => store_error_response(other),
+    }
+}
@@ -1,33 +1,71 @@
-use std::collections::HashMap;
-
 use axum::Json;
-use axum::extract::{Extension, State};
+use axum::extract::{Extension, Path, State};
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
 use base64::Engine as _;
-use chrono::Utc;
+use chrono::{Duration, Utc};
 use serde_json::json;
 use shared::assistant_crypto::ASSISTANT_ENCRYPTION_ALGORITHM_X25519_CHACHA20POLY1305;
 use shared::models::{
-    OkResponse, RegisterDeviceRequest, SendTestNotificationRequest, SendTestNotificationResponse,
+    ApiErrorCode, AuditMetadata, DeviceSummary, ListDevicesResponse, OkResponse,
+    RegisterDeviceRequest, RegisterRequestSigningKeyRequest, RegisterRequestSigningKeyResponse,
+    RotateDeviceNotificationKeyRequest, RotateDeviceNotificationKeyResponse,
+    SendTestNotificationRequest, SendTestNotificationResponse, TestNotificationJobType,
+};
+use shared::repos::{AuditResult, DeviceNotificationKey, JobType};
+use shared::request_signing::{
+    REQUEST_SIGNING_ALGORITHM_ED25519, decode_request_signing_public_key,
 };
-use shared::repos::{AuditResult, JobType};
-use uuid::Uuid;
 
-use super::errors::{bad_request_response, store_error_response};
+use super::errors::{error_response, store_error_response};
 use super::observability::RequestContext;
+use super::openapi::ApiOperation;
+use super::request_body::ApiJson;
+use super::request_signing::SignedDevice;
 use super::{AppState, AuthUser};
 
+const DEFAULT_NOTIFICATION_KEY_OVERLAP_SECONDS: u64 = 7 * 24 * 60 * 60;
+const MAX_NOTIFICATION_KEY_OVERLAP_SECONDS: u64 = 30 * 24 * 60 * 60;
+const MAX_NOTIFICATION_KEY_ID_LEN: usize = 128;
+const MAX_APP_BUNDLE_ID_LEN: usize = 255;
+
+type NotificationKeyValidationError = (ApiErrorCode, &'static str);
+
+pub(super) const REGISTER_DEVICE: ApiOperation = ApiOperation::post(
+    "/v1/devices/apns",
+    "registerAPNSDevice",
+    "Devices",
+    "Register APNs token for device",
+)
+.request::<RegisterDeviceRequest>();
+
+/// Once the user has enrolled a request signing key, a device (new or re-registered) must be
+/// registered by a request signed with an enrolled key, so a stolen bearer token cannot add a
+/// device or overwrite an existing device's APNs token.
 pub(super) async fn register_device(
     State(state): State<AppState>,
     Extension(user): Extension<AuthUser>,
-    Json(req): Json<RegisterDeviceRequest>,
+    signed_device: Option<Extension<SignedDevice>>,
+    ApiJson(req): ApiJson<RegisterDeviceRequest>,
 ) -> Response {
-    if let Some(response) = validate_notification_key_fields(&req) {
+    if let Err(response) =
+        require_enrolled_device_signature(&state, &user, signed_device.as_ref()).await
+    {
         return response;
     }
-    let notification_key_algorithm = normalized_optional(req.notification_key_algorithm.as_deref());
-    let notification_public_key = normalized_optional(req.notification_public_key.as_deref());
+    let notification_key = match validate_notification_key_fields(&req) {
+        Ok(notification_key) => notification_key,
+        Err((code, message)) => return error_response(code, message),
+    };
+    let app_bundle_id = normalized_optional(req.app_bundle_id.as_deref());
+    if let Some(app_bundle_id) = app_bundle_id.as_deref()
+        && !is_valid_app_bundle_id(app_bundle_id)
+    {
+        return error_response(
+            ApiErrorCode::InvalidAppBundleId,
+            "app_bundle_id must be a reverse-DNS identifier of [A-Za-z0-9-] segments",
+        );
+    }
 
     if let Err(err) = state
         .store
@@ -36,20 +74,29 @@ pub(super) async fn register_device(
             &req.device_id,
             &req.apns_token,
             &req.environment,
-            notification_key_algorithm.as_deref(),
-            notification_public_key.as_deref(),
+            app_bundle_id.as_deref(),
+            notification_key.as_ref(),
         )
         .await
     {
         return store_error_response(err);
     }
 
-    let mut metadata = HashMap::new();
-    metadata.insert("device_id".to_string(), req.device_id);
+    let mut metadata = AuditMetadata::new();
+    metadata.insert("device_id".to_string(), req.device_id.into());
     metadata.insert(
         "notification_key_registered".to_string(),
-        notification_public_key.is_some().to_string(),
+        notification_key.is_some().into(),
     );
+    if let Some(app_bundle_id) = app_bundle_id {
+        metadata.insert("app_bundle_id".to_string(), app_bundle_id.into());
+    }
+    if let Some(notification_key) = notification_key {
+        metadata.insert(
+            "notification_key_id".to_string(),
+            notification_key.key_id.into(),
+        );
+    }
 
     if let Err(err) = state
         .store
@@ -68,17 +115,347 @@ pub(super) async fn register_device(
     (StatusCode::OK, Json(OkResponse { ok: true })).into_response()
 }
 
+pub(super) const LIST_DEVICES: ApiOperation = ApiOperation::get(
+    "/v1/devices",
+    "listDevices",
+    "Devices",
+    "List registered devices",
+)
+.response::<ListDevicesResponse>();
+
+pub(super) async fn list_devices(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+) -> Response {
+    let devices = match state.store.list_devices(user.user_id).await {
+        Ok(devices) => devices,
+        Err(err) => return store_error_response(err),
+    };
+
+    let items = devices
+        .into_iter()
+        .map(|device| DeviceSummary {
+            device_id: device.device_id,
+            environment: device.environment,
+            app_bundle_id: device.app_bundle_id,
+            created_at: device.created_at,
+            updated_at: device.updated_at,
+            last_delivery_at: device.last_delivery_at,
+            last_seen_at: device.last_seen_at,
+            disabled_at: device.disabled_at,
+            disabled_reason: device.disabled_reason,
+        })
+        .collect();
+
+    (StatusCode::OK, Json(ListDevicesResponse { items })).into_response()
+}
+
+pub(super) const DELETE_DEVICE: ApiOperation = ApiOperation::delete(
+    "/v1/devices/{device_id}",
+    "deleteDevice",
+    "Devices",
+    "Unregister a device",
+);
+
+pub(super) async fn delete_device(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(device_id): Path<String>,
+) -> Response {
+    match state.store.delete_device(user.user_id, &device_id).await {
+        Ok(true) => {}
+        Ok(false) => return device_not_found_response(),
+        Err(err) => return store_error_response(err),
+    }
+
+    let mut metadata = AuditMetadata::new();
+    metadata.insert("device_id".to_string(), device_id.into());
+
+    if let Err(err) = state
+        .store
+        .add_audit_event(
+            user.user_id,
+            "DEVICE_UNREGISTERED",
+            None,
+            AuditResult::Success,
+            &metadata,
+        )
+        .await
+    {
+        return store_error_response(err);
+    }
+
+    (StatusCode::OK, Json(OkResponse { ok: true })).into_response()
+}
+
+pub(super) const ENABLE_DEVICE: ApiOperation = ApiOperation::post(
+    "/v1/devices/{device_id}/enable",
+    "enableDevice",
+    "Devices",
+    "Re-enable a device disabled after delivery failures",
+);
+
+/// Clears a delivery-failure disablement so fan-out resumes for the device.
+pub(super) async fn enable_device(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(device_id): Path<String>,
+) -> Response {
+    match state.store.enable_device(user.user_id, &device_id).await {
+        Ok(true) => {}
+        Ok(false) => return device_not_found_response(),
+        Err(err) => return store_error_response(err),
+    }
+
+    let mut metadata = AuditMetadata::new();
+    metadata.insert("device_id".to_string(), device_id.into());
+
+    if let Err(err) = state
+        .store
+        .add_audit_event(
+            user.user_id,
+            "DEVICE_ENABLED",
+            None,
+            AuditResult::Success,
+            &metadata,
+        )
+        .await
+    {
+        return store_error_response(err);
+    }
+
+    (StatusCode::OK, Json(OkResponse { ok: true })).into_response()
+}
+
+pub(super) const ROTATE_NOTIFICATION_KEY: ApiOperation = ApiOperation::put(
+    "/v1/devices/{device_id}/notification-key",
+    "rotateDeviceNotificationKey",
+    "Devices",
+    "Rotate the notification encryption key for a device",
+)
+.request::<RotateDeviceNotificationKeyRequest>()
+.response::<RotateDeviceNotificationKeyResponse>();
+
+pub(super) async fn rotate_notification_key(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(device_id): Path<String>,
+    ApiJson(req): ApiJson<RotateDeviceNotificationKeyRequest>,
+) -> Response {
+    let notification_key = match validated_notification_key(
+        req.key_id.as_str(),
+        req.algorithm.as_str(),
+        req.public_key.as_str(),
+    ) {
+        Ok(notification_key) => notification_key,
+        Err((code, message)) => return error_response(code, message),
+    };
+    let overlap_seconds = req
+        .overlap_seconds
+        .unwrap_or(DEFAULT_NOTIFICATION_KEY_OVERLAP_SECONDS);
+    if overlap_seconds > MAX_NOTIFICATION_KEY_OVERLAP_SECONDS {
+        return error_response(
+            ApiErrorCode::InvalidOverlapSeconds,
+            "overlap_seconds must be at most 2592000 (30 days)",
+        );
+    }
+    let previous_key_expires_at = Utc::now() + Duration::seconds(overlap_seconds as i64);
+
+    let rotation = match state
+        .store
+        .rotate_device_notification_key(
+            user.user_id,
+            &device_id,
+            &notification_key,
+            previous_key_expires_at,
+        )
+        .await
+    {
+        Ok(Some(rotation)) => rotation,
+        Ok(None) => return device_not_found_response(),
+        Err(err) => return store_error_response(err),
+    };
+
+    let mut metadata = AuditMetadata::new();
+    metadata.insert("device_id".to_string(), device_id.clone().into());
+    metadata.insert(
+        "notification_key_id".to_string(),
+        rotation.key_id.clone().into(),
+    );
+    if let Some(previous_key_id) = rotation.previous_key_id.as_ref() {
+        metadata.insert(
+            "previous_notification_key_id".to_string(),
+            previous_key_id.clone().into(),
+        );
+    }
+    metadata.insert("overlap_seconds".to_string(), overlap_seconds.into());
+
+    if let Err(err) = state
+        .store
+        .add_audit_event(
+            user.user_id,
+            "DEVICE_NOTIFICATION_KEY_ROTATED",
+            None,
+            AuditResult::Success,
+            &metadata,
+        )
+        .await
+    {
+        return store_error_response(err);
+    }
+
+    (
+        StatusCode::OK,
+        Json(RotateDeviceNotificationKeyResponse {
+            device_id,
+            key_id: rotation.key_id,
+            rotated_at: rotation.rotated_at,
+            previous_key_id: rotation.previous_key_id,
+            previous_key_expires_at: rotation.previous_key_expires_at,
+        }),
+    )
+        .into_response()
+}
+
+pub(super) const REGISTER_REQUEST_SIGNING_KEY: ApiOperation = ApiOperation::put(
+    "/v1/devices/{device_id}/signing-key",
+    "registerDeviceRequestSigningKey",
+    "Devices",
+    "Enroll a device key for request signing",
+)
+.request::<RegisterRequestSigningKeyRequest>()
+.response::<RegisterRequestSigningKeyResponse>();
+
+/// Enrolls the Ed25519 key the device signs its requests with. The user's very first key can
+/// be enrolled with the bearer token alone. After that, a new device's first key needs a
+/// request signed by an already-enrolled device, and replacing a key needs one signed by the
+/// current key, so a stolen token can neither add nor swap in an attacker's key.
+pub(super) async fn register_request_signing_key(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    signed_device: Option<Extension<SignedDevice>>,
+    Path(device_id): Path<String>,
+    ApiJson(req): ApiJson<RegisterRequestSigningKeyRequest>,
+) -> Response {
+    let public_key = req.public_key.trim();
+    if decode_request_signing_public_key(public_key).is_none() {
+        return error_response(
+            ApiErrorCode::InvalidRequestSigningKey,
+            "public_key must be a base64-encoded 32-byte Ed25519 public key",
+        );
+    }
+
+    let replaced = match state
+        .store
+        .get_device_request_signing_key(user.user_id, &device_id)
+        .await
+    {
+        Ok(current_key) => current_key.is_some(),
+        Err(err) => return store_error_response(err),
+    };
+    let signed_by_device = signed_device
+        .as_ref()
+        .is_some_and(|Extension(signed)| signed.device_id == device_id);
+    if replaced && !signed_by_device {
+        return error_response(
+            ApiErrorCode::RequestSignatureRequired,
+            "Replacing a request signing key requires a request signed with the current key",
+        );
+    }
+    if !replaced
+        && let Err(response) =
+            require_enrolled_device_signature(&state, &user, signed_device.as_ref()).await
+    {
+        return response;
+    }
+
+    let registered_at = Utc::now();
+    match state
+        .store
+        .set_device_request_signing_key(user.user_id, &device_id, public_key, registered_at)
+        .await
+    {
+        Ok(true) => {}
+        Ok(false) => return device_not_found_response(),
+        Err(err) => return store_error_response(err),
+    }
+
+    let mut metadata = AuditMetadata::new();
+    metadata.insert("device_id".to_string(), device_id.clone().into());
+    metadata.insert("replaced".to_string(), replaced.into());
+
+    if let Err(err) = state
+        .store
+        .add_audit_event(
+            user.user_id,
+            "DEVICE_REQUEST_SIGNING_KEY_REGISTERED",
+            None,
+            AuditResult::Success,
+            &metadata,
+        )
+        .await
+    {
+        return store_error_response(err);
+    }
+
+    (
+        StatusCode::OK,
+        Json(RegisterRequestSigningKeyResponse {
+            device_id,
+            algorithm: REQUEST_SIGNING_ALGORITHM_ED25519.to_string(),
+            registered_at,
+        }),
+    )
+        .into_response()
+}
+
+/// Rejects an unsigned request once the user has enrolled a request signing key on any device.
+/// The middleware has already verified any signature that is present.
+async fn require_enrolled_device_signature(
+    state: &AppState,
+    user: &AuthUser,
+    signed_device: Option<&Extension<SignedDevice>>,
+) -> Result<(), Response> {
+    if signed_device.is_some() {
+        return Ok(());
+    }
+    match state
+        .store
+        .has_device_request_signing_keys(user.user_id)
+        .await
+    {
+        Ok(false) => Ok(()),
+        Ok(true) => Err(error_response(
+            ApiErrorCode::RequestSignatureRequired,
+            "Adding a device requires a request signed by an already-enrolled device",
+        )),
+        Err(err) => Err(store_error_response(err)),
+    }
+}
+
+pub(super) const SEND_TEST_NOTIFICATION: ApiOperation = ApiOperation::post(
+    "/v1/devices/apns/test",
+    "sendAPNSTestNotification",
+    "Devices",
+    "Queue a test push notification",
+)
+.request::<SendTestNotificationRequest>()
+.response::<SendTestNotificationResponse>();
+
+/// Queues the notification on the real job pipeline for the requested job type, so the
+/// worker's claiming, retries, dead-lettering, and delivery records all run. The request id
+/// doubles as the idempotency key: resending with the same `X-Request-Id` returns the same job.
 pub(super) async fn send_test_notification(
     State(state): State<AppState>,
     Extension(user): Extension<AuthUser>,
     Extension(request_context): Extension<RequestContext>,
-    Json(req): Json<SendTestNotificationRequest>,
+    ApiJson(req): ApiJson<SendTestNotificationRequest>,
 ) -> Response {
     match state.store.has_registered_device(user.user_id).await {
         Ok(true) => {}
         Ok(false) => {
-            return bad_request_response(
-                "no_registered_device",
+            return error_response(
+                ApiErrorCode::NoRegisteredDevice,
                 "Register an APNs device before requesting a test notification",
             );
         }
@@ -99,35 +476,49 @@ pub(super) async fn send_test_notification(
         .unwrap_or("This notification confirms your push pipeline is active.");
 
     if title.chars().count() > 120 {
-        return bad_request_response(
-            "invalid_title",
+        return error_response(
+            ApiErrorCode::InvalidTitle,
             "Notification title must be at most 120 characters",
         );
     }
 
     if body.chars().count() > 500 {
-        return bad_request_response(
-            "invalid_body",
+        return error_response(
+            ApiErrorCode::InvalidBody,
             "Notification body must be at most 500 characters",
         );
     }
 
-    let payload = super::observability::attach_request_trace(
-        json!({
-            "notification": {
-                "title": title,
-                "body": body
-            }
-        }),
-        &request_context.request_id,
-    );
+    let job_type = match req.job_type {
+        TestNotificationJobType::AutomationRun => JobType::AutomationRun,
+        TestNotificationJobType::DepartureAlert => JobType::DepartureAlert,
+    };
+    let simulated_failure = req
+        .simulated_payload
+        .as_ref()
+        .and_then(|simulated| simulated.failure);
 
-    let idempotency_key = format!("TEST_NOTIFICATION:{}", Uuid::new_v4());
+    let mut payload = json!({
+        "notification": {
+            "title": title,
+            "body": body
+        }
+    });
+    if let Some(failure) = simulated_failure {
+        payload["simulated_failure"] = json!({
+            "class": failure.as_str(),
+            "code": failure.error_code(),
+            "message": "Failure requested by a test notification",
+        });
+    }
+    let payload = super::observability::attach_request_trace(payload, &request_context.request_id);
+
+    let idempotency_key = format!("TEST_NOTIFICATION:{}", request_context.request_id);
     let job_id = match state
         .store
         .enqueue_job_with_idempotency_key(
             user.user_id,
-            JobType::AutomationRun,
+            job_type.clone(),
             Utc::now(),
             Some(&payload),
             &idempotency_key,
@@ -138,12 +529,12 @@ pub(super) async fn send_test_notification(
         Err(err) => return store_error_response(err),
     };
 
-    let mut metadata = HashMap::new();
-    metadata.insert("job_id".to_string(), job_id.to_string());
-    metadata.insert(
-        "job_type".to_string(),
-        JobType::AutomationRun.as_str().to_string(),
-    );
+    let mut metadata = AuditMetadata::new();
+    metadata.insert("job_id".to_string(), job_id.to_string().into());
+    metadata.insert("job_type".to_string(), job_type.as_str().into());
+    if let Some(failure) = simulated_failure {
+        metadata.insert("simulated_failure".to_string(), failure.as_str().into());
+    }
 
     if let Err(err) = state
         .store
@@ -163,67 +554,102 @@ pub(super) async fn send_test_notification(
         StatusCode::OK,
         Json(SendTestNotificationResponse {
             queued_job_id: job_id.to_string(),
+            job_type: req.job_type,
             status: "QUEUED".to_string(),
         }),
     )
         .into_response()
 }
 
-fn validate_notification_key_fields(req: &RegisterDeviceRequest) -> Option<Response> {
-    let has_algorithm = req
-        .notification_key_algorithm
-        .as_deref()
-        .is_some_and(|value| !value.trim().is_empty());
-    let has_public_key = req
-        .notification_public_key
-        .as_deref()
-        .is_some_and(|value| !value.trim().is_empty());
+fn validate_notification_key_fields(
+    req: &RegisterDeviceRequest,
+) -> Result<Option<DeviceNotificationKey>, NotificationKeyValidationError> {
+    let algorithm = normalized_optional(req.notification_key_algorithm.as_deref());
+    let public_key = normalized_optional(req.notification_public_key.as_deref());
 
-    if !has_algorithm && !has_public_key {
-        return None;
-    }
+    let (algorithm, public_key) = match (algorithm, public_key) {
+        (None, None) => return Ok(None),
+        (Some(algorithm), Some(public_key)) => (algorithm, public_key),
+        _ => {
+            return Err((
+                ApiErrorCode::InvalidNotificationKey,
+                "notification_key_algorithm and notification_public_key must both be provided",
+            ));
+        }
+    };
 
-    if !has_algorithm || !has_public_key {
-        return Some(bad_request_response(
-            "invalid_notification_key",
-            "notification_key_algorithm and notification_public_key must both be provided",
+    // Keys registered without an explicit id are addressed by the device identifier.
+    let key_id = normalized_optional(req.notification_key_id.as_deref())
+        .unwrap_or_else(|| req.device_id.trim().to_string());
+
+    validated_notification_key(&key_id, &algorithm, &public_key).map(Some)
+}
+
+fn validated_notification_key(
+    key_id: &str,
+    algorithm: &str,
+    public_key_b64: &str,
+) -> Result<DeviceNotificationKey, NotificationKeyValidationError> {
+    let key_id = key_id.trim();
+    if !is_valid_notification_key_id(key_id) {
+        return Err((
+            ApiErrorCode::InvalidNotificationKeyId,
+            "notification key id must be 1-128 characters of [A-Za-z0-9._-]",
         ));
     }
 
-    let algorithm = req
-        .notification_key_algorithm
-        .as_deref()
-        .unwrap_or_default()
-        .trim();
+    let algorithm = algorithm.trim();
     if algorithm != ASSISTANT_ENCRYPTION_ALGORITHM_X25519_CHACHA20POLY1305 {
-        return Some(bad_request_response(
-            "invalid_notification_key_algorithm",
+        return Err((
+            ApiErrorCode::InvalidNotificationKeyAlgorithm,
             "notification_key_algorithm is not supported",
         ));
     }
 
-    let public_key_b64 = req
-        .notification_public_key
-        .as_deref()
-        .unwrap_or_default()
-        .trim();
+    let public_key_b64 = public_key_b64.trim();
     let decoded = match base64::engine::general_purpose::STANDARD.decode(public_key_b64) {
         Ok(bytes) => bytes,
         Err(_) => {
-            return Some(bad_request_response(
-                "invalid_notification_public_key",
+            return Err((
+                ApiErrorCode::InvalidNotificationPublicKey,
                 "notification_public_key must be valid base64",
             ));
         }
     };
     if decoded.len() != 32 {
-        return Some(bad_request_response(
-            "invalid_notification_public_key",
+        return Err((
+            ApiErrorCode::InvalidNotificationPublicKey,
             "notification_public_key must decode to 32 bytes",
         ));
     }
 
-    None
+    Ok(DeviceNotificationKey {
+        key_id: key_id.to_string(),
+        algorithm: algorithm.to_string(),
+        public_key: public_key_b64.to_string(),
+    })
+}
+
+fn is_valid_notification_key_id(key_id: &str) -> bool {
+    !key_id.is_empty()
+        && key_id.len() <= MAX_NOTIFICATION_KEY_ID_LEN
+        && key_id
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.'))
+}
+
+fn is_valid_app_bundle_id(app_bundle_id: &str) -> bool {
+    app_bundle_id.len() <= MAX_APP_BUNDLE_ID_LEN
+        && app_bundle_id.split('.').all(|segment| {
+            !segment.is_empty()
+                && segment
+                    .bytes()
+                    .all(|byte| byte.is_ascii_alphanumeric() || byte == b'-')
+        })
+}
+
+fn device_not_found_response() -> Response {
+    error_response(ApiErrorCode::NotFound, "Device not found")
 }
 
 fn normalized_optional(value: Option<&str>) -> Option<String> {
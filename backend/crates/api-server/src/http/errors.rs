@@ -1,98 +1,114 @@
 use axum::Json;
 use axum::http::{HeaderValue, StatusCode, header};
 use axum::response::{IntoResponse, Response};
-use shared::models::{ErrorBody, ErrorResponse};
+use shared::events::EventSinkError;
+use shared::models::{ApiErrorCode, ErrorBody, ErrorResponse};
 use shared::repos::StoreError;
 use tracing::error;
 
-pub(super) fn bad_request_response(code: &str, message: &str) -> Response {
-    (
-        StatusCode::BAD_REQUEST,
-        Json(ErrorResponse {
-            error: ErrorBody {
-                code: code.to_string(),
-                message: message.to_string(),
-            },
-        }),
-    )
-        .into_response()
+use super::anomaly::{AnomalySignal, flag_anomaly};
+
+/// Error response with the status, `retryable` flag, and docs link the catalog assigns to
+/// `code`.
+pub(super) fn error_response(code: ApiErrorCode, message: &str) -> Response {
+    error_response_with_status(status_for(code), code, message)
+}
+
+fn status_for(code: ApiErrorCode) -> StatusCode {
+    StatusCode::from_u16(code.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
 }
 
-pub(super) fn bad_gateway_response(code: &str, message: &str) -> Response {
+fn error_response_with_status(status: StatusCode, code: ApiErrorCode, message: &str) -> Response {
     (
-        StatusCode::BAD_GATEWAY,
+        status,
         Json(ErrorResponse {
-            error: ErrorBody {
-                code: code.to_string(),
-                message: message.to_string(),
-            },
+            error: ErrorBody::new(code, message),
         }),
     )
         .into_response()
 }
 
 pub(super) fn unauthorized_response() -> Response {
-    (
-        StatusCode::UNAUTHORIZED,
-        Json(ErrorResponse {
-            error: ErrorBody {
-                code: "unauthorized".to_string(),
-                message: "Missing or invalid bearer token".to_string(),
-            },
-        }),
+    error_response(
+        ApiErrorCode::Unauthorized,
+        "Missing or invalid bearer token",
     )
-        .into_response()
 }
 
 pub(super) fn too_many_requests_response(retry_after_seconds: u64) -> Response {
-    let mut response = (
-        StatusCode::TOO_MANY_REQUESTS,
-        Json(ErrorResponse {
-            error: ErrorBody {
-                code: "rate_limited".to_string(),
-                message: "Too many requests; retry later".to_string(),
-            },
-        }),
+    quota_exceeded_response(
+        ApiErrorCode::RateLimited,
+        "Too many requests; retry later",
+        retry_after_seconds,
     )
-        .into_response();
+}
 
+/// A 429 with its own error code, for limits the client should explain to the user rather
+/// than silently retry.
+pub(super) fn quota_exceeded_response(
+    code: ApiErrorCode,
+    message: &str,
+    retry_after_seconds: u64,
+) -> Response {
+    let mut response = error_response(code, message);
+    insert_retry_after(&mut response, retry_after_seconds);
+    response
+}
+
+fn insert_retry_after(response: &mut Response, retry_after_seconds: u64) {
     if let Ok(retry_after_value) = HeaderValue::from_str(&retry_after_seconds.to_string()) {
         response
             .headers_mut()
             .insert(header::RETRY_AFTER, retry_after_value);
     }
+}
 
-    response
+/// The enclave's outbound limiter turned the call away before it reached the provider.
+pub(super) fn provider_rate_limited_response(retry_after_ms: u64) -> Response {
+    too_many_requests_response(retry_after_ms.div_ceil(1_000).max(1))
 }
 
 pub(super) fn decrypt_not_authorized_response() -> Response {
-    (
-        StatusCode::FORBIDDEN,
-        Json(ErrorResponse {
-            error: ErrorBody {
-                code: "decrypt_not_authorized".to_string(),
-                message: "Connector decrypt is denied by attestation policy".to_string(),
-            },
-        }),
+    flag_anomaly(
+        error_response(
+            ApiErrorCode::DecryptNotAuthorized,
+            "Connector decrypt is denied by attestation policy",
+        ),
+        AnomalySignal::AttestationFailure,
+    )
+}
+
+pub(super) fn payload_too_large_response() -> Response {
+    error_response(
+        ApiErrorCode::PayloadTooLarge,
+        "Request body exceeds the size limit for this endpoint",
+    )
+}
+
+pub(super) fn invalid_request_body_response(message: &str) -> Response {
+    error_response(ApiErrorCode::InvalidRequestBody, message)
+}
+
+/// Keeps the `invalid_request_body` code so clients handle every body rejection the same
+/// way, but answers with `415` as HTTP requires for a non-JSON content type.
+pub(super) fn unsupported_media_type_response() -> Response {
+    error_response_with_status(
+        StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        ApiErrorCode::InvalidRequestBody,
+        "Content-Type must be application/json",
     )
-        .into_response()
 }
 
 pub(super) fn store_error_response(err: StoreError) -> Response {
-    match err {
-        StoreError::InvalidCursor => bad_request_response("invalid_cursor", "Cursor is invalid"),
-        other => {
-            error!("database operation failed: {other}");
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: ErrorBody {
-                        code: "internal_error".to_string(),
-                        message: "Unexpected server error".to_string(),
-                    },
-                }),
-            )
-                .into_response()
-        }
-    }
+    error!("database operation failed: {err}");
+    internal_error_response()
+}
+
+pub(super) fn event_publish_error_response(err: EventSinkError) -> Response {
+    error!("domain event publish failed: {err}");
+    internal_error_response()
+}
+
+pub(super) fn internal_error_response() -> Response {
+    error_response(ApiErrorCode::InternalError, "Unexpected server error")
 }
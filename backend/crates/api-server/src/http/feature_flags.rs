@@ -0,0 +1,37 @@
+use axum::Json;
+use axum::extract::{Extension, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use shared::feature_flags::FeatureFlag;
+use shared::models::{FeatureFlagEvaluation, ListFeatureFlagsResponse};
+
+use super::openapi::ApiOperation;
+use super::{AppState, AuthUser};
+
+pub(super) const LIST_FEATURE_FLAGS: ApiOperation = ApiOperation::get(
+    "/v1/feature-flags",
+    "listFeatureFlags",
+    "Feature Flags",
+    "Evaluate client-visible feature flags for the signed-in user",
+)
+.response::<ListFeatureFlagsResponse>();
+
+/// Only client-visible flags are reported; server-side flags such as the enclave small-talk
+/// fast path stay internal.
+pub(super) async fn list_feature_flags(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+) -> Response {
+    let mut items = Vec::new();
+    for flag in FeatureFlag::ALL
+        .into_iter()
+        .filter(|flag| flag.client_visible())
+    {
+        items.push(FeatureFlagEvaluation {
+            flag,
+            enabled: state.feature_flags.is_enabled(flag, user.user_id).await,
+        });
+    }
+
+    (StatusCode::OK, Json(ListFeatureFlagsResponse { items })).into_response()
+}
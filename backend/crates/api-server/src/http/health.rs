@@ -1,31 +1,245 @@
+use std::future::Future;
+use std::time::{Duration, Instant};
+
 use axum::Json;
 use axum::extract::State;
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
-use shared::models::{ErrorBody, ErrorResponse, OkResponse};
+use chrono::Utc;
+use shared::models::{
+    ComponentStatus, DependencyReadiness, OkResponse, ReadinessDependency, ReadinessResponse,
+    ReadinessStatus,
+};
 use tracing::warn;
 
 use super::AppState;
+use super::clerk_jwks_cache::ClerkJwksFreshness;
+
+/// Probes run concurrently, so this also bounds how long `/readyz` takes to answer.
+const DEPENDENCY_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+struct ProbeFailure {
+    status: ComponentStatus,
+    reason: &'static str,
+}
+
+impl ProbeFailure {
+    fn unavailable(reason: &'static str) -> Self {
+        Self {
+            status: ComponentStatus::Unavailable,
+            reason,
+        }
+    }
+}
 
 pub(super) async fn healthz() -> impl IntoResponse {
     (StatusCode::OK, Json(OkResponse { ok: true }))
 }
 
+/// Only the database is critical: the rate limiter falls back to in-process windows without
+/// Redis, JWKS reads fall back to Clerk, and enclave-backed routes fail on their own while
+/// the rest of the API keeps serving. Anything else impaired reports `degraded` with a `200`.
 pub(super) async fn readyz(State(state): State<AppState>) -> Response {
-    match state.store.ping().await {
-        Ok(_) => (StatusCode::OK, Json(OkResponse { ok: true })).into_response(),
-        Err(err) => {
-            warn!("readiness check failed: {err}");
-            (
-                StatusCode::SERVICE_UNAVAILABLE,
-                Json(ErrorResponse {
-                    error: ErrorBody {
-                        code: "db_unavailable".to_string(),
-                        message: "Database not ready".to_string(),
-                    },
-                }),
-            )
-                .into_response()
+    let (database, redis, enclave, clerk_jwks) = tokio::join!(
+        probe(ReadinessDependency::Database, true, check_database(&state)),
+        probe(ReadinessDependency::Redis, false, check_redis(&state)),
+        probe(ReadinessDependency::Enclave, false, check_enclave(&state)),
+        probe(
+            ReadinessDependency::ClerkJwks,
+            false,
+            check_clerk_jwks(&state)
+        ),
+    );
+    let dependencies = vec![database, redis, enclave, clerk_jwks];
+    let status = readiness_status(&dependencies);
+
+    for dependency in dependencies
+        .iter()
+        .filter(|dependency| dependency.status != ComponentStatus::Operational)
+    {
+        warn!(
+            dependency = ?dependency.dependency,
+            status = ?dependency.status,
+            reason = dependency.reason.as_deref().unwrap_or_default(),
+            "readiness dependency check failed"
+        );
+    }
+
+    let http_status = match status {
+        ReadinessStatus::Unready => StatusCode::SERVICE_UNAVAILABLE,
+        ReadinessStatus::Ready | ReadinessStatus::Degraded => StatusCode::OK,
+    };
+    (
+        http_status,
+        Json(ReadinessResponse {
+            status,
+            dependencies,
+            checked_at: Utc::now(),
+        }),
+    )
+        .into_response()
+}
+
+async fn probe(
+    dependency: ReadinessDependency,
+    critical: bool,
+    check: impl Future<Output = Result<(), ProbeFailure>>,
+) -> DependencyReadiness {
+    let started = Instant::now();
+    let outcome = tokio::time::timeout(DEPENDENCY_CHECK_TIMEOUT, check)
+        .await
+        .unwrap_or_else(|_| Err(ProbeFailure::unavailable("timeout")));
+    let (status, reason) = match outcome {
+        Ok(()) => (ComponentStatus::Operational, None),
+        Err(failure) => (failure.status, Some(failure.reason.to_string())),
+    };
+
+    DependencyReadiness {
+        dependency,
+        status,
+        critical,
+        reason,
+        latency_ms: started.elapsed().as_millis() as u64,
+    }
+}
+
+async fn check_database(state: &AppState) -> Result<(), ProbeFailure> {
+    state.store.ping().await.map_err(|err| {
+        warn!("readiness database ping failed: {err}");
+        ProbeFailure::unavailable("db_unavailable")
+    })
+}
+
+async fn check_redis(state: &AppState) -> Result<(), ProbeFailure> {
+    state.clerk_jwks_cache.ping().await.map_err(|err| {
+        warn!("readiness redis ping failed: {err}");
+        ProbeFailure::unavailable("redis_unavailable")
+    })
+}
+
+async fn check_enclave(state: &AppState) -> Result<(), ProbeFailure> {
+    let url = format!(
+        "{}/healthz",
+        state.enclave_rpc.base_url.trim_end_matches('/')
+    );
+    let response = state.http_client.get(url).send().await.map_err(|err| {
+        warn!("readiness enclave health request failed: {err}");
+        ProbeFailure::unavailable("enclave_unreachable")
+    })?;
+
+    if response.status() == reqwest::StatusCode::OK {
+        Ok(())
+    } else {
+        warn!(
+            status = response.status().as_u16(),
+            "readiness enclave health check returned non-200"
+        );
+        Err(ProbeFailure::unavailable("enclave_unhealthy"))
+    }
+}
+
+async fn check_clerk_jwks(state: &AppState) -> Result<(), ProbeFailure> {
+    match state
+        .clerk_jwks_cache
+        .freshness(&state.http_client, &state.clerk_jwks_url)
+        .await
+    {
+        ClerkJwksFreshness::Fresh => Ok(()),
+        ClerkJwksFreshness::Stale => Err(ProbeFailure {
+            status: ComponentStatus::Degraded,
+            reason: "jwks_stale",
+        }),
+        ClerkJwksFreshness::Unavailable => Err(ProbeFailure::unavailable("jwks_unavailable")),
+    }
+}
+
+fn readiness_status(dependencies: &[DependencyReadiness]) -> ReadinessStatus {
+    if dependencies
+        .iter()
+        .any(|dependency| dependency.critical && dependency.status == ComponentStatus::Unavailable)
+    {
+        ReadinessStatus::Unready
+    } else if dependencies
+        .iter()
+        .any(|dependency| dependency.status != ComponentStatus::Operational)
+    {
+        ReadinessStatus::Degraded
+    } else {
+        ReadinessStatus::Ready
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use shared::models::{
+        ComponentStatus, DependencyReadiness, ReadinessDependency, ReadinessStatus,
+    };
+
+    use super::readiness_status;
+
+    fn dependency(
+        dependency: ReadinessDependency,
+        critical: bool,
+        status: ComponentStatus,
+    ) -> DependencyReadiness {
+        DependencyReadiness {
+            dependency,
+            status,
+            critical,
+            reason: None,
+            latency_ms: 1,
         }
     }
+
+    #[test]
+    fn only_critical_outages_make_the_instance_unready() {
+        let healthy = [
+            dependency(
+                ReadinessDependency::Database,
+                true,
+                ComponentStatus::Operational,
+            ),
+            dependency(
+                ReadinessDependency::Redis,
+                false,
+                ComponentStatus::Operational,
+            ),
+        ];
+        assert_eq!(readiness_status(&healthy), ReadinessStatus::Ready);
+
+        let redis_down = [
+            dependency(
+                ReadinessDependency::Database,
+                true,
+                ComponentStatus::Operational,
+            ),
+            dependency(
+                ReadinessDependency::Redis,
+                false,
+                ComponentStatus::Unavailable,
+            ),
+        ];
+        assert_eq!(readiness_status(&redis_down), ReadinessStatus::Degraded);
+
+        let jwks_stale = [dependency(
+            ReadinessDependency::ClerkJwks,
+            false,
+            ComponentStatus::Degraded,
+        )];
+        assert_eq!(readiness_status(&jwks_stale), ReadinessStatus::Degraded);
+
+        let database_down = [
+            dependency(
+                ReadinessDependency::Database,
+                true,
+                ComponentStatus::Unavailable,
+            ),
+            dependency(
+                ReadinessDependency::Redis,
+                false,
+                ComponentStatus::Unavailable,
+            ),
+        ];
+        assert_eq!(readiness_status(&database_down), ReadinessStatus::Unready);
+    }
 }
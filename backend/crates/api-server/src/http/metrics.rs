@@ -0,0 +1,355 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use axum::extract::State;
+use axum::http::{HeaderValue, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use shared::enclave::EnclaveRpcObserver;
+use shared::repos::StorePoolStats;
+
+use super::AppState;
+
+const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Upper bounds, in seconds, shared by the request and enclave RPC latency histograms.
+const LATENCY_BUCKETS_SECONDS: [f64; 12] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0,
+];
+
+/// In-process Prometheus registry served at `/metrics`. Series are keyed by matched route
+/// templates and fixed label sets, so cardinality stays bounded regardless of traffic.
+#[derive(Clone, Default)]
+pub struct ApiMetrics {
+    inner: Arc<Mutex<MetricsRegistry>>,
+}
+
+#[derive(Default)]
+struct MetricsRegistry {
+    requests: BTreeMap<RequestLabels, Histogram>,
+    enclave_rpcs: BTreeMap<EnclaveRpcLabels, Histogram>,
+    rate_limit_rejections: BTreeMap<RateLimitRejectionLabels, u64>,
+    security_anomalies: BTreeMap<SecurityAnomalyLabels, u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct RequestLabels {
+    method: String,
+    route: String,
+    status: u16,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct EnclaveRpcLabels {
+    path: String,
+    outcome: &'static str,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct RateLimitRejectionLabels {
+    route_class: &'static str,
+    reason: &'static str,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct SecurityAnomalyLabels {
+    signal: &'static str,
+    subject: &'static str,
+}
+
+#[derive(Default)]
+struct Histogram {
+    bucket_counts: [u64; LATENCY_BUCKETS_SECONDS.len()],
+    count: u64,
+    sum_seconds: f64,
+}
+
+impl Histogram {
+    fn observe(&mut self, elapsed: Duration) {
+        let seconds = elapsed.as_secs_f64();
+        for (bound, bucket_count) in LATENCY_BUCKETS_SECONDS
+            .iter()
+            .zip(self.bucket_counts.iter_mut())
+        {
+            if seconds <= *bound {
+                *bucket_count += 1;
+            }
+        }
+        self.count += 1;
+        self.sum_seconds += seconds;
+    }
+
+    fn render(&self, out: &mut String, name: &str, labels: &str) {
+        for (bound, bucket_count) in LATENCY_BUCKETS_SECONDS.iter().zip(self.bucket_counts) {
+            let _ = writeln!(
+                out,
+                "{name}_bucket{{{labels},le=\"{bound}\"}} {bucket_count}"
+            );
+        }
+        let _ = writeln!(out, "{name}_bucket{{{labels},le=\"+Inf\"}} {}", self.count);
+        let _ = writeln!(out, "{name}_sum{{{labels}}} {}", self.sum_seconds);
+        let _ = writeln!(out, "{name}_count{{{labels}}} {}", self.count);
+    }
+}
+
+impl ApiMetrics {
+    pub(super) fn record_request(&self, method: &str, route: &str, status: u16, elapsed: Duration) {
+        let labels = RequestLabels {
+            method: method.to_string(),
+            route: route.to_string(),
+            status,
+        };
+        self.registry()
+            .requests
+            .entry(labels)
+            .or_default()
+            .observe(elapsed);
+    }
+
+    /// `reason` is `window` for a sliding-window denial, `abuse_block` for a request turned
+    /// away by an active abuse escalation, and `anomaly_block` for an IP in an enforced
+    /// security anomaly cooldown.
+    pub(super) fn record_rate_limit_rejection(
+        &self,
+        route_class: &'static str,
+        reason: &'static str,
+    ) {
+        *self
+            .registry()
+            .rate_limit_rejections
+            .entry(RateLimitRejectionLabels {
+                route_class,
+                reason,
+            })
+            .or_default() += 1;
+    }
+
+    /// `subject` is `ip` or `user`, whichever crossed the signal's threshold.
+    pub(super) fn record_security_anomaly(&self, signal: &'static str, subject: &'static str) {
+        *self
+            .registry()
+            .security_anomalies
+            .entry(SecurityAnomalyLabels { signal, subject })
+            .or_default() += 1;
+    }
+
+    fn render(&self, pools: &[StorePoolStats]) -> String {
+        let registry = self.registry();
+        let mut out = String::new();
+
+        out.push_str(
+            "# HELP alfred_http_requests_total HTTP requests handled, by route and status.\n",
+        );
+        out.push_str("# TYPE alfred_http_requests_total counter\n");
+        for (labels, histogram) in &registry.requests {
+            let _ = writeln!(
+                out,
+                "alfred_http_requests_total{{{}}} {}",
+                labels.render(),
+                histogram.count
+            );
+        }
+
+        out.push_str(
+            "# HELP alfred_http_request_duration_seconds HTTP request latency, by route and status.\n",
+        );
+        out.push_str("# TYPE alfred_http_request_duration_seconds histogram\n");
+        for (labels, histogram) in &registry.requests {
+            histogram.render(
+                &mut out,
+                "alfred_http_request_duration_seconds",
+                &labels.render(),
+            );
+        }
+
+        out.push_str(
+            "# HELP alfred_rate_limit_rejections_total Requests rejected by the sensitive route rate limiter.\n",
+        );
+        out.push_str("# TYPE alfred_rate_limit_rejections_total counter\n");
+        for (labels, count) in &registry.rate_limit_rejections {
+            let _ = writeln!(
+                out,
+                "alfred_rate_limit_rejections_total{{route_class=\"{}\",reason=\"{}\"}} {count}",
+                labels.route_class, labels.reason
+            );
+        }
+
+        out.push_str(
+            "# HELP alfred_security_anomalies_total Auth, attestation, and replay failure spikes detected.\n",
+        );
+        out.push_str("# TYPE alfred_security_anomalies_total counter\n");
+        for (labels, count) in &registry.security_anomalies {
+            let _ = writeln!(
+                out,
+                "alfred_security_anomalies_total{{signal=\"{}\",subject=\"{}\"}} {count}",
+                labels.signal, labels.subject
+            );
+        }
+
+        out.push_str(
+            "# HELP alfred_enclave_rpc_duration_seconds Enclave RPC latency, by path and outcome.\n",
+        );
+        out.push_str("# TYPE alfred_enclave_rpc_duration_seconds histogram\n");
+        for (labels, histogram) in &registry.enclave_rpcs {
+            let labels = format!(
+                "path=\"{}\",outcome=\"{}\"",
+                escape_label_value(&labels.path),
+                labels.outcome
+            );
+            histogram.render(&mut out, "alfred_enclave_rpc_duration_seconds", &labels);
+        }
+        drop(registry);
+
+        out.push_str("# HELP alfred_db_pool_connections Store pool connections, by state.\n");
+        out.push_str("# TYPE alfred_db_pool_connections gauge\n");
+        for stats in pools {
+            let idle = stats.idle.min(stats.size);
+            let _ = writeln!(
+                out,
+                "alfred_db_pool_connections{{pool=\"{}\",state=\"idle\"}} {idle}",
+                stats.pool
+            );
+            let _ = writeln!(
+                out,
+                "alfred_db_pool_connections{{pool=\"{}\",state=\"in_use\"}} {}",
+                stats.pool,
+                stats.size - idle
+            );
+        }
+        out.push_str("# HELP alfred_db_pool_max_connections Configured Store pool size limit.\n");
+        out.push_str("# TYPE alfred_db_pool_max_connections gauge\n");
+        for stats in pools {
+            let _ = writeln!(
+                out,
+                "alfred_db_pool_max_connections{{pool=\"{}\"}} {}",
+                stats.pool, stats.max_connections
+            );
+        }
+
+        out
+    }
+
+    fn registry(&self) -> std::sync::MutexGuard<'_, MetricsRegistry> {
+        self.inner
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl EnclaveRpcObserver for ApiMetrics {
+    fn rpc_completed(&self, path: &str, succeeded: bool, elapsed: Duration) {
+        let labels = EnclaveRpcLabels {
+            path: path.to_string(),
+            outcome: if succeeded { "success" } else { "error" },
+        };
+        self.registry()
+            .enclave_rpcs
+            .entry(labels)
+            .or_default()
+            .observe(elapsed);
+    }
+}
+
+impl RequestLabels {
+    fn render(&self) -> String {
+        format!(
+            "method=\"{}\",route=\"{}\",status=\"{}\"",
+            escape_label_value(&self.method),
+            escape_label_value(&self.route),
+            self.status
+        )
+    }
+}
+
+pub(super) async fn get_metrics(State(state): State<AppState>) -> Response {
+    let body = state.metrics.render(&state.store.pool_stats());
+    (
+        StatusCode::OK,
+        [(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static(PROMETHEUS_CONTENT_TYPE),
+        )],
+        body,
+    )
+        .into_response()
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use shared::enclave::EnclaveRpcObserver;
+    use shared::repos::StorePoolStats;
+
+    use super::{ApiMetrics, escape_label_value};
+
+    #[test]
+    fn renders_request_counters_and_cumulative_latency_buckets() {
+        let metrics = ApiMetrics::default();
+        metrics.record_request("GET", "/v1/status", 200, Duration::from_millis(20));
+        metrics.record_request("GET", "/v1/status", 200, Duration::from_millis(700));
+        metrics.record_request("GET", "/v1/status", 500, Duration::from_millis(3));
+
+        let rendered = metrics.render(&[]);
+        let ok_labels = r#"method="GET",route="/v1/status",status="200""#;
+        assert!(rendered.contains(&format!("alfred_http_requests_total{{{ok_labels}}} 2")));
+        assert!(rendered.contains(&format!(
+            "alfred_http_request_duration_seconds_bucket{{{ok_labels},le=\"0.025\"}} 1"
+        )));
+        assert!(rendered.contains(&format!(
+            "alfred_http_request_duration_seconds_bucket{{{ok_labels},le=\"1\"}} 2"
+        )));
+        assert!(rendered.contains(&format!(
+            "alfred_http_request_duration_seconds_bucket{{{ok_labels},le=\"+Inf\"}} 2"
+        )));
+        assert!(rendered.contains(
+            r#"alfred_http_requests_total{method="GET",route="/v1/status",status="500"} 1"#
+        ));
+    }
+
+    #[test]
+    fn renders_rejections_enclave_latency_and_pool_gauges() {
+        let metrics = ApiMetrics::default();
+        metrics.record_rate_limit_rejection("automation_create", "window");
+        metrics.record_rate_limit_rejection("automation_create", "window");
+        metrics.record_rate_limit_rejection("automation_create", "abuse_block");
+        metrics.record_security_anomaly("replay_detected", "user");
+        metrics.rpc_completed("/v1/rpc/assistant/query", false, Duration::from_millis(40));
+
+        let rendered = metrics.render(&[StorePoolStats {
+            pool: "primary",
+            size: 5,
+            idle: 2,
+            max_connections: 10,
+        }]);
+        assert!(rendered.contains(
+            r#"alfred_rate_limit_rejections_total{route_class="automation_create",reason="window"} 2"#
+        ));
+        assert!(rendered.contains(
+            r#"alfred_rate_limit_rejections_total{route_class="automation_create",reason="abuse_block"} 1"#
+        ));
+        assert!(rendered.contains(
+            r#"alfred_security_anomalies_total{signal="replay_detected",subject="user"} 1"#
+        ));
+        assert!(rendered.contains(
+            r#"alfred_enclave_rpc_duration_seconds_count{path="/v1/rpc/assistant/query",outcome="error"} 1"#
+        ));
+        assert!(
+            rendered.contains(r#"alfred_db_pool_connections{pool="primary",state="in_use"} 3"#)
+        );
+        assert!(rendered.contains(r#"alfred_db_pool_max_connections{pool="primary"} 10"#));
+    }
+
+    #[test]
+    fn escapes_label_values() {
+        assert_eq!(escape_label_value("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+}
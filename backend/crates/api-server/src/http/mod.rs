@@ -1,28 +1,62 @@
-use axum::routing::{delete, get, post};
+use axum::extract::DefaultBodyLimit;
+use axum::routing::{delete, get, post, put};
 use axum::{Router, middleware};
-use shared::enclave::EnclaveRpcAuthConfig;
+use shared::config::{
+    AdminClerkOrgConfig, ApiDeprecationConfig, CorsConfig, RequestSigningConfig,
+    SecurityHeadersConfig,
+};
+use shared::enclave::{EnclaveRpcAuthConfig, EnclaveRpcClient, EnclaveRpcTls};
+use shared::events::EventBus;
+use shared::feature_flags::FeatureFlags;
+use shared::pagination::PaginationCursorCodec;
 use shared::repos::Store;
 use shared::security::SecretRuntime;
 use std::collections::HashSet;
 use std::net::IpAddr;
+use std::sync::Arc;
 use uuid::Uuid;
 
+mod abuse;
+mod admin;
+mod anomaly;
 mod assistant;
 mod audit;
 mod authn;
 mod automations;
+mod brief_profile;
 mod clerk_identity;
 mod clerk_jwks_cache;
+mod concurrency;
 mod connectors;
+mod cors;
+mod departure_alerts;
 mod devices;
 mod errors;
+mod feature_flags;
 mod health;
+mod metrics;
 mod oauth_bridge;
 mod observability;
+mod openapi;
+mod pagination;
 mod privacy;
+mod privacy_export;
+mod prompt_envelopes;
+mod quota;
 mod rate_limit;
+mod rate_limit_redis;
+mod request_body;
+mod request_signing;
+mod security_headers;
+mod status;
+mod support;
 mod tokens;
+mod usage;
+mod versioning;
+mod webhooks;
 pub use clerk_jwks_cache::{ClerkJwksCache, ClerkJwksCacheConfig};
+pub use metrics::ApiMetrics;
+pub use openapi::{contract_drift, openapi_document};
 pub use rate_limit::RateLimiter;
 
 #[derive(Clone)]
@@ -33,10 +67,22 @@ pub struct OAuthConfig {
     pub scopes: Vec<String>,
 }
 
+/// JSON body size caps. Bodies over the cap are rejected with `payload_too_large` before they
+/// are deserialized.
+#[derive(Clone, Copy)]
+pub struct RequestBodyLimits {
+    /// Applies to every route without a more specific cap.
+    pub default_bytes: usize,
+    /// Routes whose body carries an encrypted prompt or credential envelope.
+    pub prompt_envelope_bytes: usize,
+}
+
 #[derive(Clone)]
 pub struct EnclaveRpcConfig {
     pub base_url: String,
     pub auth: EnclaveRpcAuthConfig,
+    /// Set when RPCs go over mutual TLS.
+    pub tls: Option<EnclaveRpcTls>,
 }
 
 #[derive(Clone)]
@@ -55,6 +101,19 @@ pub struct AppState {
     pub clerk_jwks_url: String,
     pub clerk_jwks_cache: ClerkJwksCache,
     pub http_client: reqwest::Client,
+    pub cursor_codec: PaginationCursorCodec,
+    pub admin_api_token: Option<String>,
+    pub admin_clerk_org: Option<AdminClerkOrgConfig>,
+    pub org_admin_role: Option<String>,
+    pub events: EventBus,
+    pub request_body_limits: RequestBodyLimits,
+    pub metrics: ApiMetrics,
+    /// Advertised on `/v1` responses once `/v1` is scheduled for retirement.
+    pub api_v1_deprecation: Option<ApiDeprecationConfig>,
+    pub cors: CorsConfig,
+    pub feature_flags: FeatureFlags,
+    pub request_signing: RequestSigningConfig,
+    pub security_headers: SecurityHeadersConfig,
 }
 
 #[derive(Clone, Copy)]
@@ -62,18 +121,121 @@ pub(super) struct AuthUser {
     pub(super) user_id: Uuid,
 }
 
+/// Enclave RPC client whose call latencies are reported on `/metrics`.
+pub(super) fn build_enclave_client(state: &AppState) -> EnclaveRpcClient {
+    let client = EnclaveRpcClient::new(
+        state.enclave_rpc.base_url.clone(),
+        state.enclave_rpc.auth.clone(),
+        state.http_client.clone(),
+    )
+    .with_observer(Arc::new(state.metrics.clone()));
+    match state.enclave_rpc.tls.as_ref() {
+        Some(tls) => client.with_mtls(tls, state.secret_runtime.clone()),
+        None => client,
+    }
+}
+
 pub fn build_router(app_state: AppState) -> Router {
+    let body_limits = app_state.request_body_limits;
+    let metrics = app_state.metrics.clone();
+    let api_v1_deprecation = app_state.api_v1_deprecation;
+    let security_headers = app_state.security_headers;
+    let prompt_envelope_body_limit = DefaultBodyLimit::max(body_limits.prompt_envelope_bytes);
+
     let public_routes = Router::new()
         .route("/healthz", get(health::healthz))
         .route("/readyz", get(health::readyz))
+        .route("/openapi.json", get(openapi::get_openapi))
+        .route(
+            "/v1/privacy/export/{request_id}/download",
+            post(privacy_export::download_export),
+        )
         .route(
             "/oauth/google/callback",
             get(oauth_bridge::redirect_google_oauth_callback),
         )
         .with_state(app_state.clone());
 
+    let admin_routes = Router::new()
+        .route("/metrics", get(metrics::get_metrics))
+        .route(
+            "/admin/v1/users/{user_id}/audit-chain",
+            get(admin::verify_audit_chain),
+        )
+        .route(
+            "/admin/v1/users/{user_id}/jobs",
+            get(admin::get_job_queue_depth),
+        )
+        .route(
+            "/admin/v1/users/{user_id}/dead-letter-jobs",
+            get(admin::list_dead_letter_jobs),
+        )
+        .route(
+            "/admin/v1/users/{user_id}/dead-letter-jobs/{dead_letter_id}/replay",
+            post(admin::replay_dead_letter_job),
+        )
+        .route(
+            "/admin/v1/users/{user_id}/connectors/health",
+            get(admin::list_connector_health),
+        )
+        .route(
+            "/admin/v1/users/{user_id}/connectors/{connector_id}/health-check",
+            post(admin::force_connector_health_check),
+        )
+        .route(
+            "/admin/v1/users/{user_id}/rate-limits",
+            get(admin::get_rate_limit_state),
+        )
+        .route(
+            "/admin/v1/organizations/{org_id}/members",
+            get(admin::list_organization_members),
+        )
+        .route(
+            "/admin/v1/organizations/{org_id}/plan",
+            put(admin::set_organization_plan),
+        )
+        .route("/admin/v1/feature-flags", get(admin::list_feature_flags))
+        .route(
+            "/admin/v1/feature-flags/{flag}",
+            put(admin::set_feature_flag).delete(admin::clear_feature_flag),
+        )
+        .layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            admin::admin_auth_middleware,
+        ))
+        .with_state(app_state.clone());
+
+    // Read-only routes a browser dashboard may call cross-origin. Preflight requests carry no
+    // credentials, so CORS sits outside the auth check here.
+    let cors_routes = Router::new()
+        .route("/v1/status", get(status::get_status))
+        .route("/v1/devices", get(devices::list_devices))
+        .route("/v1/connectors", get(connectors::list_connectors))
+        .route(
+            "/v1/automation-reports",
+            get(automations::list_automation_reports),
+        )
+        .route("/v1/audit-events", get(audit::list_audit_events))
+        .route(
+            "/v1/audit-events/{event_id}/verification",
+            get(audit::verify_audit_event),
+        )
+        .route_layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            authn::auth_middleware,
+        ))
+        // Added after `route_layer`, so it stays unauthenticated.
+        .route("/v1/public/status", get(status::get_public_status))
+        .with_state(app_state.clone());
+    let cors_routes = match cors::cors_layer(&app_state.cors) {
+        Some(cors_layer) => cors_routes.layer(cors_layer),
+        None => cors_routes,
+    };
+
     let auth_layer_state = app_state.clone();
+    let request_signing_layer_state = app_state.clone();
     let protected_rate_limit_layer_state = app_state.clone();
+    let anomaly_layer_state = app_state.clone();
 
     let protected_routes = Router::new()
         .route("/v1/devices/apns", post(devices::register_device))
@@ -81,6 +243,19 @@ pub fn build_router(app_state: AppState) -> Router {
             "/v1/devices/apns/test",
             post(devices::send_test_notification),
         )
+        .route("/v1/devices/{device_id}", delete(devices::delete_device))
+        .route(
+            "/v1/devices/{device_id}/enable",
+            post(devices::enable_device),
+        )
+        .route(
+            "/v1/devices/{device_id}/notification-key",
+            put(devices::rotate_notification_key),
+        )
+        .route(
+            "/v1/devices/{device_id}/signing-key",
+            put(devices::register_request_signing_key),
+        )
         .route(
             "/v1/assistant/query",
             post(assistant::query_assistant).layer(middleware::from_fn_with_state(
@@ -100,9 +275,16 @@ pub fn build_router(app_state: AppState) -> Router {
             get(assistant::list_assistant_sessions)
                 .delete(assistant::delete_all_assistant_sessions),
         )
+        .route(
+            "/v1/assistant/sessions/export",
+            post(assistant::export_assistant_sessions).layer(middleware::from_fn_with_state(
+                protected_rate_limit_layer_state.clone(),
+                rate_limit::sensitive_rate_limit_middleware,
+            )),
+        )
         .route(
             "/v1/assistant/sessions/{session_id}",
-            delete(assistant::delete_assistant_session),
+            delete(assistant::delete_assistant_session).patch(assistant::update_assistant_session),
         )
         .route(
             "/v1/connectors/google/start",
@@ -118,7 +300,38 @@ pub fn build_router(app_state: AppState) -> Router {
                 rate_limit::sensitive_rate_limit_middleware,
             )),
         )
-        .route("/v1/connectors", get(connectors::list_connectors))
+        .route(
+            "/v1/connectors/caldav",
+            post(connectors::connect_caldav)
+                .layer(prompt_envelope_body_limit)
+                .layer(middleware::from_fn_with_state(
+                    protected_rate_limit_layer_state.clone(),
+                    rate_limit::sensitive_rate_limit_middleware,
+                )),
+        )
+        .route(
+            "/v1/connectors/imap",
+            post(connectors::connect_imap)
+                .layer(prompt_envelope_body_limit)
+                .layer(middleware::from_fn_with_state(
+                    protected_rate_limit_layer_state.clone(),
+                    rate_limit::sensitive_rate_limit_middleware,
+                )),
+        )
+        .route(
+            "/v1/connectors/{connector_id}/scopes/upgrade",
+            post(connectors::upgrade_connector_scopes).layer(middleware::from_fn_with_state(
+                protected_rate_limit_layer_state.clone(),
+                rate_limit::sensitive_rate_limit_middleware,
+            )),
+        )
+        .route(
+            "/v1/connectors",
+            delete(connectors::revoke_all_connectors).layer(middleware::from_fn_with_state(
+                protected_rate_limit_layer_state.clone(),
+                rate_limit::sensitive_rate_limit_middleware,
+            )),
+        )
         .route(
             "/v1/connectors/{connector_id}",
             delete(connectors::revoke_connector).layer(middleware::from_fn_with_state(
@@ -130,6 +343,20 @@ pub fn build_router(app_state: AppState) -> Router {
             "/v1/automations",
             get(automations::list_automations)
                 .post(automations::create_automation)
+                .layer(prompt_envelope_body_limit)
+                .layer(middleware::from_fn_with_state(
+                    protected_rate_limit_layer_state.clone(),
+                    rate_limit::sensitive_rate_limit_middleware,
+                )),
+        )
+        .route(
+            "/v1/automations/preview-schedule",
+            post(automations::preview_automation_schedule),
+        )
+        .route(
+            "/v1/automations/draft",
+            post(automations::draft_automation)
+                .layer(prompt_envelope_body_limit)
                 .layer(middleware::from_fn_with_state(
                     protected_rate_limit_layer_state.clone(),
                     rate_limit::sensitive_rate_limit_middleware,
@@ -139,11 +366,26 @@ pub fn build_router(app_state: AppState) -> Router {
             "/v1/automations/{rule_id}",
             delete(automations::delete_automation)
                 .patch(automations::update_automation)
+                .layer(prompt_envelope_body_limit)
                 .layer(middleware::from_fn_with_state(
                     protected_rate_limit_layer_state.clone(),
                     rate_limit::sensitive_rate_limit_middleware,
                 )),
         )
+        .route(
+            "/v1/automations/{rule_id}/run",
+            post(automations::run_automation_now).layer(middleware::from_fn_with_state(
+                protected_rate_limit_layer_state.clone(),
+                rate_limit::sensitive_rate_limit_middleware,
+            )),
+        )
+        .route(
+            "/v1/automations/{rule_id}/snooze",
+            post(automations::snooze_automation).layer(middleware::from_fn_with_state(
+                protected_rate_limit_layer_state.clone(),
+                rate_limit::sensitive_rate_limit_middleware,
+            )),
+        )
         .route(
             "/v1/automations/{rule_id}/debug/run",
             post(automations::trigger_debug_run).layer(middleware::from_fn_with_state(
@@ -151,7 +393,70 @@ pub fn build_router(app_state: AppState) -> Router {
                 rate_limit::sensitive_rate_limit_middleware,
             )),
         )
-        .route("/v1/audit-events", get(audit::list_audit_events))
+        .route(
+            "/v1/brief-profile",
+            get(brief_profile::get_brief_profile).put(brief_profile::update_brief_profile),
+        )
+        .route(
+            "/v1/brief-profile/feedback",
+            post(brief_profile::submit_brief_feedback),
+        )
+        .route(
+            "/v1/departure-alerts/preferences",
+            get(departure_alerts::get_departure_alert_preferences)
+                .put(departure_alerts::update_departure_alert_preferences)
+                .layer(prompt_envelope_body_limit),
+        )
+        .route(
+            "/v1/audit-events/export",
+            get(audit::export_audit_events).layer(middleware::from_fn_with_state(
+                protected_rate_limit_layer_state.clone(),
+                rate_limit::sensitive_rate_limit_middleware,
+            )),
+        )
+        .route(
+            "/v1/support/diagnostics",
+            post(support::upload_diagnostics)
+                .layer(DefaultBodyLimit::max(support::MAX_DIAGNOSTICS_BODY_BYTES))
+                .layer(middleware::from_fn_with_state(
+                    protected_rate_limit_layer_state.clone(),
+                    rate_limit::sensitive_rate_limit_middleware,
+                )),
+        )
+        .route(
+            "/v1/support/diagnostics/{diagnostic_id}",
+            delete(support::delete_diagnostics),
+        )
+        .route("/v1/feature-flags", get(feature_flags::list_feature_flags))
+        .route("/v1/usage/llm", get(usage::get_llm_usage))
+        .route(
+            "/v1/webhooks",
+            get(webhooks::list_webhooks)
+                .post(webhooks::create_webhook)
+                .layer(middleware::from_fn_with_state(
+                    protected_rate_limit_layer_state.clone(),
+                    rate_limit::sensitive_rate_limit_middleware,
+                )),
+        )
+        .route(
+            "/v1/webhooks/{webhook_id}",
+            delete(webhooks::delete_webhook),
+        )
+        .route(
+            "/v1/webhooks/{webhook_id}/deliveries",
+            get(webhooks::list_webhook_deliveries),
+        )
+        .route(
+            "/v1/privacy/export",
+            post(privacy_export::request_export).layer(middleware::from_fn_with_state(
+                protected_rate_limit_layer_state.clone(),
+                rate_limit::sensitive_rate_limit_middleware,
+            )),
+        )
+        .route(
+            "/v1/privacy/export/{request_id}",
+            get(privacy_export::get_export_status),
+        )
         .route(
             "/v1/privacy/delete-all",
             post(privacy::delete_all).layer(middleware::from_fn_with_state(
@@ -163,15 +468,43 @@ pub fn build_router(app_state: AppState) -> Router {
             "/v1/privacy/delete-all/{request_id}",
             get(privacy::get_delete_all_status),
         )
+        // Added before the auth layer, so it runs after the bearer token has been verified.
+        .layer(middleware::from_fn_with_state(
+            request_signing_layer_state,
+            request_signing::request_signing_middleware,
+        ))
         .layer(middleware::from_fn_with_state(
             auth_layer_state,
             authn::auth_middleware,
         ))
         .with_state(app_state);
 
-    public_routes
+    // Health checks and public downloads stay reachable from an IP in an anomaly cooldown.
+    let guarded_routes = cors_routes
+        .merge(admin_routes)
         .merge(protected_routes)
-        .layer(middleware::from_fn(
-            observability::request_observability_middleware,
+        .layer(middleware::from_fn_with_state(
+            anomaly_layer_state,
+            anomaly::security_anomaly_middleware,
+        ));
+
+    let router = public_routes
+        .merge(guarded_routes)
+        .layer(DefaultBodyLimit::max(body_limits.default_bytes))
+        .layer(middleware::from_fn_with_state(
+            security_headers,
+            security_headers::security_headers_middleware,
         ))
+        .layer(middleware::from_fn_with_state(
+            metrics,
+            observability::request_observability_middleware,
+        ));
+
+    versioning::mount_versions(
+        router,
+        api_v1_deprecation,
+        body_limits
+            .default_bytes
+            .max(body_limits.prompt_envelope_bytes),
+    )
 }
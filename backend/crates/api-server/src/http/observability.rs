@@ -1,4 +1,4 @@
-use axum::extract::{MatchedPath, Request};
+use axum::extract::{MatchedPath, Request, State};
 use axum::http::{HeaderValue, header};
 use axum::middleware::Next;
 use axum::response::Response;
@@ -7,6 +7,9 @@ use std::time::Instant;
 use tracing::{Instrument, debug, error, warn};
 use uuid::Uuid;
 
+use super::metrics::ApiMetrics;
+use super::versioning::ApiVersion;
+
 const REQUEST_ID_HEADER: &str = "x-request-id";
 const MAX_REQUEST_ID_LEN: usize = 128;
 
@@ -15,7 +18,11 @@ pub(super) struct RequestContext {
     pub(super) request_id: String,
 }
 
-pub(super) async fn request_observability_middleware(mut req: Request, next: Next) -> Response {
+pub(super) async fn request_observability_middleware(
+    State(metrics): State<ApiMetrics>,
+    mut req: Request,
+    next: Next,
+) -> Response {
     let request_id = resolve_request_id(&req);
     req.extensions_mut().insert(RequestContext {
         request_id: request_id.clone(),
@@ -25,7 +32,10 @@ pub(super) async fn request_observability_middleware(mut req: Request, next: Nex
     let route = req
         .extensions()
         .get::<MatchedPath>()
-        .map(|matched| matched.as_str().to_string())
+        .map(|matched| match req.extensions().get::<ApiVersion>() {
+            Some(version) => version.client_route(matched.as_str()),
+            None => matched.as_str().to_string(),
+        })
         .unwrap_or_else(|| "<unmatched>".to_string());
     let path = req.uri().path().to_string();
     let started_at = Instant::now();
@@ -46,7 +56,9 @@ pub(super) async fn request_observability_middleware(mut req: Request, next: Nex
     }
 
     let status = response.status().as_u16();
-    let latency_ms = started_at.elapsed().as_millis() as u64;
+    let elapsed = started_at.elapsed();
+    metrics.record_request(&method, &route, status, elapsed);
+    let latency_ms = elapsed.as_millis() as u64;
     let outcome = if status >= 500 {
         "server_error"
     } else if status >= 400 {
@@ -0,0 +1,570 @@
+//! OpenAPI document generated from the operation declarations that sit next to each handler.
+//!
+//! `api/openapi.yaml` stays the hand-edited contract the iOS app consumes; [`contract_drift`]
+//! compares it with the generated document so CI fails as soon as the two disagree.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use axum::Json;
+use axum::response::{IntoResponse, Response};
+use schemars::JsonSchema;
+use schemars::r#gen::{SchemaGenerator, SchemaSettings};
+use schemars::schema::Schema;
+use serde_json::{Map, Value, json};
+use shared::models::OkResponse;
+
+use super::{
+    admin, assistant, audit, automations, brief_profile, connectors, departure_alerts, devices,
+    feature_flags, privacy, privacy_export, status, support, usage, webhooks,
+};
+
+const OPENAPI_VERSION: &str = "3.0.3";
+const API_TITLE: &str = "Alfred API";
+const API_VERSION: &str = "1.0.0";
+const SCHEMA_REF_PREFIX: &str = "#/components/schemas/";
+
+type SchemaFn = fn(&mut SchemaGenerator) -> Schema;
+type SchemaFieldSet = fn(&Value) -> BTreeSet<String>;
+
+/// Every documented route. A route wired in `build_router` without an entry here shows up as
+/// drift against `api/openapi.yaml`.
+const API_OPERATIONS: &[ApiOperation] = &[
+    devices::LIST_DEVICES,
+    devices::REGISTER_DEVICE,
+    devices::SEND_TEST_NOTIFICATION,
+    devices::DELETE_DEVICE,
+    devices::ENABLE_DEVICE,
+    devices::ROTATE_NOTIFICATION_KEY,
+    devices::REGISTER_REQUEST_SIGNING_KEY,
+    assistant::QUERY_ASSISTANT,
+    assistant::FETCH_ATTESTED_KEY,
+    assistant::LIST_ASSISTANT_SESSIONS,
+    assistant::DELETE_ALL_ASSISTANT_SESSIONS,
+    assistant::EXPORT_ASSISTANT_SESSIONS,
+    assistant::UPDATE_ASSISTANT_SESSION,
+    assistant::DELETE_ASSISTANT_SESSION,
+    connectors::LIST_CONNECTORS,
+    connectors::START_GOOGLE_CONNECT,
+    connectors::COMPLETE_GOOGLE_CONNECT,
+    connectors::CONNECT_CALDAV,
+    connectors::CONNECT_IMAP,
+    connectors::UPGRADE_CONNECTOR_SCOPES,
+    connectors::REVOKE_ALL_CONNECTORS,
+    connectors::REVOKE_CONNECTOR,
+    automations::LIST_AUTOMATIONS,
+    automations::CREATE_AUTOMATION,
+    automations::PREVIEW_AUTOMATION_SCHEDULE,
+    automations::DRAFT_AUTOMATION,
+    automations::UPDATE_AUTOMATION,
+    automations::DELETE_AUTOMATION,
+    automations::RUN_AUTOMATION_NOW,
+    automations::SNOOZE_AUTOMATION,
+    automations::TRIGGER_DEBUG_RUN,
+    automations::LIST_AUTOMATION_REPORTS,
+    brief_profile::GET_BRIEF_PROFILE,
+    brief_profile::UPDATE_BRIEF_PROFILE,
+    brief_profile::SUBMIT_BRIEF_FEEDBACK,
+    departure_alerts::GET_DEPARTURE_ALERT_PREFERENCES,
+    departure_alerts::UPDATE_DEPARTURE_ALERT_PREFERENCES,
+    audit::LIST_AUDIT_EVENTS,
+    audit::EXPORT_AUDIT_EVENTS,
+    audit::VERIFY_AUDIT_EVENT,
+    admin::VERIFY_AUDIT_CHAIN,
+    admin::GET_JOB_QUEUE_DEPTH,
+    admin::LIST_DEAD_LETTER_JOBS,
+    admin::REPLAY_DEAD_LETTER_JOB,
+    admin::LIST_CONNECTOR_HEALTH,
+    admin::FORCE_CONNECTOR_HEALTH_CHECK,
+    admin::GET_RATE_LIMIT_STATE,
+    admin::LIST_ORGANIZATION_MEMBERS,
+    admin::SET_ORGANIZATION_PLAN,
+    admin::LIST_FEATURE_FLAGS,
+    admin::SET_FEATURE_FLAG,
+    admin::CLEAR_FEATURE_FLAG,
+    status::GET_PUBLIC_STATUS,
+    status::GET_STATUS,
+    support::UPLOAD_DIAGNOSTICS,
+    support::DELETE_DIAGNOSTICS,
+    feature_flags::LIST_FEATURE_FLAGS,
+    usage::GET_LLM_USAGE,
+    webhooks::CREATE_WEBHOOK,
+    webhooks::LIST_WEBHOOKS,
+    webhooks::DELETE_WEBHOOK,
+    webhooks::LIST_WEBHOOK_DELIVERIES,
+    privacy::DELETE_ALL,
+    privacy::GET_DELETE_ALL_STATUS,
+    privacy_export::REQUEST_EXPORT,
+    privacy_export::GET_EXPORT_STATUS,
+    privacy_export::DOWNLOAD_EXPORT,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ApiMethod {
+    Get,
+    Post,
+    Put,
+    Patch,
+    Delete,
+}
+
+impl ApiMethod {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Get => "get",
+            Self::Post => "post",
+            Self::Put => "put",
+            Self::Patch => "patch",
+            Self::Delete => "delete",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ApiAuth {
+    Bearer,
+    Admin,
+    Public,
+}
+
+impl ApiAuth {
+    fn security(self) -> Value {
+        match self {
+            Self::Bearer => json!([{ "bearerAuth": [] }]),
+            Self::Admin => json!([{ "adminToken": [] }]),
+            Self::Public => json!([]),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum ApiResponse {
+    Json(SchemaFn),
+    /// Untyped JSON document, such as the export archive the worker assembles.
+    JsonDocument,
+    /// Newline-delimited JSON items, or a CSV rendering of them.
+    NdjsonOrCsv(SchemaFn),
+}
+
+/// Contract for one route, declared next to its handler. Operations are bearer-authenticated
+/// and answer with `OkResponse` unless the builder says otherwise.
+#[derive(Clone, Copy)]
+pub(crate) struct ApiOperation {
+    method: ApiMethod,
+    path: &'static str,
+    operation_id: &'static str,
+    tag: &'static str,
+    summary: &'static str,
+    auth: ApiAuth,
+    request: Option<SchemaFn>,
+    response: ApiResponse,
+}
+
+impl ApiOperation {
+    const fn new(
+        method: ApiMethod,
+        path: &'static str,
+        operation_id: &'static str,
+        tag: &'static str,
+        summary: &'static str,
+    ) -> Self {
+        Self {
+            method,
+            path,
+            operation_id,
+            tag,
+            summary,
+            auth: ApiAuth::Bearer,
+            request: None,
+            response: ApiResponse::Json(schema_ref::<OkResponse>),
+        }
+    }
+
+    pub(crate) const fn get(
+        path: &'static str,
+        operation_id: &'static str,
+        tag: &'static str,
+        summary: &'static str,
+    ) -> Self {
+        Self::new(ApiMethod::Get, path, operation_id, tag, summary)
+    }
+
+    pub(crate) const fn post(
+        path: &'static str,
+        operation_id: &'static str,
+        tag: &'static str,
+        summary: &'static str,
+    ) -> Self {
+        Self::new(ApiMethod::Post, path, operation_id, tag, summary)
+    }
+
+    pub(crate) const fn put(
+        path: &'static str,
+        operation_id: &'static str,
+        tag: &'static str,
+        summary: &'static str,
+    ) -> Self {
+        Self::new(ApiMethod::Put, path, operation_id, tag, summary)
+    }
+
+    pub(crate) const fn patch(
+        path: &'static str,
+        operation_id: &'static str,
+        tag: &'static str,
+        summary: &'static str,
+    ) -> Self {
+        Self::new(ApiMethod::Patch, path, operation_id, tag, summary)
+    }
+
+    pub(crate) const fn delete(
+        path: &'static str,
+        operation_id: &'static str,
+        tag: &'static str,
+        summary: &'static str,
+    ) -> Self {
+        Self::new(ApiMethod::Delete, path, operation_id, tag, summary)
+    }
+
+    pub(crate) const fn public(mut self) -> Self {
+        self.auth = ApiAuth::Public;
+        self
+    }
+
+    pub(crate) const fn admin(mut self) -> Self {
+        self.auth = ApiAuth::Admin;
+        self
+    }
+
+    pub(crate) const fn request<T: JsonSchema>(mut self) -> Self {
+        self.request = Some(schema_ref::<T>);
+        self
+    }
+
+    pub(crate) const fn response<T: JsonSchema>(mut self) -> Self {
+        self.response = ApiResponse::Json(schema_ref::<T>);
+        self
+    }
+
+    pub(crate) const fn json_document_response(mut self) -> Self {
+        self.response = ApiResponse::JsonDocument;
+        self
+    }
+
+    pub(crate) const fn ndjson_or_csv_response<T: JsonSchema>(mut self) -> Self {
+        self.response = ApiResponse::NdjsonOrCsv(schema_ref::<T>);
+        self
+    }
+
+    fn document(&self, generator: &mut SchemaGenerator) -> Value {
+        let mut operation = json!({
+            "tags": [self.tag],
+            "summary": self.summary,
+            "operationId": self.operation_id,
+            "security": self.auth.security(),
+        });
+
+        let parameters = path_parameters(self.path)
+            .map(|name| {
+                json!({
+                    "in": "path",
+                    "name": name,
+                    "required": true,
+                    "schema": { "type": "string" },
+                })
+            })
+            .collect::<Vec<_>>();
+        if !parameters.is_empty() {
+            operation["parameters"] = Value::Array(parameters);
+        }
+
+        if let Some(request) = self.request {
+            operation["requestBody"] = json!({
+                "required": true,
+                "content": { "application/json": { "schema": request(generator) } },
+            });
+        }
+
+        let content = match self.response {
+            ApiResponse::Json(schema) => json!({
+                "application/json": { "schema": schema(generator) },
+            }),
+            ApiResponse::JsonDocument => json!({
+                "application/json": { "schema": { "type": "object" } },
+            }),
+            ApiResponse::NdjsonOrCsv(schema) => json!({
+                "application/x-ndjson": { "schema": schema(generator) },
+                "text/csv": { "schema": { "type": "string" } },
+            }),
+        };
+        operation["responses"] = json!({
+            "200": { "description": "Success", "content": content },
+        });
+
+        operation
+    }
+}
+
+fn schema_ref<T: JsonSchema>(generator: &mut SchemaGenerator) -> Schema {
+    generator.subschema_for::<T>()
+}
+
+fn path_parameters(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/').filter_map(|segment| {
+        segment
+            .strip_prefix('{')
+            .and_then(|segment| segment.strip_suffix('}'))
+    })
+}
+
+/// Builds the OpenAPI 3.0 document for every declared operation, with component schemas
+/// derived from the `shared::models` types the handlers actually serialize.
+pub fn openapi_document() -> Value {
+    let mut generator = SchemaSettings::openapi3().into_generator();
+
+    let mut paths = Map::new();
+    for operation in API_OPERATIONS {
+        let item = paths
+            .entry(operation.path)
+            .or_insert_with(|| Value::Object(Map::new()));
+        item[operation.method.as_str()] = operation.document(&mut generator);
+    }
+
+    let schemas = generator
+        .take_definitions()
+        .into_iter()
+        .map(|(name, schema)| {
+            let schema = serde_json::to_value(schema).unwrap_or(Value::Null);
+            (name, schema)
+        })
+        .collect::<Map<_, _>>();
+
+    json!({
+        "openapi": OPENAPI_VERSION,
+        "info": { "title": API_TITLE, "version": API_VERSION },
+        "paths": paths,
+        "components": {
+            "securitySchemes": {
+                "bearerAuth": { "type": "http", "scheme": "bearer", "bearerFormat": "JWT" },
+                "adminToken": { "type": "http", "scheme": "bearer" },
+            },
+            "schemas": schemas,
+        },
+    })
+}
+
+pub(super) async fn get_openapi() -> Response {
+    Json(openapi_document()).into_response()
+}
+
+/// Lists every difference between the generated document and the checked-in contract:
+/// routes present on only one side, operation metadata, request and response schema
+/// references, and the property, required and enum sets of each shared component schema.
+/// Descriptions, examples and error responses are left to the checked-in document.
+pub fn contract_drift(generated: &Value, checked_in: &Value) -> Vec<String> {
+    let mut drift = Vec::new();
+
+    let generated_operations = operations(generated);
+    let checked_in_operations = operations(checked_in);
+    for (key, generated_operation) in &generated_operations {
+        let Some(checked_in_operation) = checked_in_operations.get(key) else {
+            drift.push(format!("{key}: served but not documented"));
+            continue;
+        };
+        compare_operation(key, generated_operation, checked_in_operation, &mut drift);
+    }
+    for key in checked_in_operations.keys() {
+        if !generated_operations.contains_key(key) {
+            drift.push(format!("{key}: documented but not served"));
+        }
+    }
+
+    let empty = Map::new();
+    let generated_schemas = component_schemas(generated).unwrap_or(&empty);
+    let checked_in_schemas = component_schemas(checked_in).unwrap_or(&empty);
+    for (name, generated_schema) in generated_schemas {
+        if let Some(checked_in_schema) = checked_in_schemas.get(name) {
+            compare_schema(name, generated_schema, checked_in_schema, &mut drift);
+        }
+    }
+
+    drift
+}
+
+fn operations(document: &Value) -> BTreeMap<String, &Value> {
+    let mut operations = BTreeMap::new();
+    let Some(paths) = document.get("paths").and_then(Value::as_object) else {
+        return operations;
+    };
+
+    for (path, item) in paths {
+        let Some(item) = item.as_object() else {
+            continue;
+        };
+        for (method, operation) in item {
+            if method != "parameters" {
+                operations.insert(format!("{} {path}", method.to_uppercase()), operation);
+            }
+        }
+    }
+    operations
+}
+
+fn compare_operation(key: &str, generated: &Value, checked_in: &Value, drift: &mut Vec<String>) {
+    for field in ["operationId", "summary", "tags", "security"] {
+        if generated.get(field) != checked_in.get(field) {
+            drift.push(format!(
+                "{key}: {field} is {} in code but {} in the contract",
+                display(generated.get(field)),
+                display(checked_in.get(field)),
+            ));
+        }
+    }
+
+    let generated_request = content_schemas(generated.get("requestBody"));
+    let checked_in_request = content_schemas(checked_in.get("requestBody"));
+    if generated_request != checked_in_request {
+        drift.push(format!(
+            "{key}: request body is {generated_request:?} in code but {checked_in_request:?} in the contract"
+        ));
+    }
+
+    let generated_responses = success_responses(generated);
+    let checked_in_responses = success_responses(checked_in);
+    if generated_responses != checked_in_responses {
+        drift.push(format!(
+            "{key}: success response is {generated_responses:?} in code but {checked_in_responses:?} in the contract"
+        ));
+    }
+}
+
+/// Media type to schema name, with `inline` standing in for schemas without a `$ref`.
+fn content_schemas(body: Option<&Value>) -> BTreeMap<String, String> {
+    body.and_then(|body| body.get("content"))
+        .and_then(Value::as_object)
+        .map(|content| {
+            content
+                .iter()
+                .map(|(media_type, media)| {
+                    let schema = media
+                        .pointer("/schema/$ref")
+                        .and_then(Value::as_str)
+                        .map(|reference| reference.trim_start_matches(SCHEMA_REF_PREFIX))
+                        .unwrap_or("inline");
+                    (media_type.clone(), schema.to_string())
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn success_responses(operation: &Value) -> BTreeMap<String, BTreeMap<String, String>> {
+    operation
+        .get("responses")
+        .and_then(Value::as_object)
+        .map(|responses| {
+            responses
+                .iter()
+                .filter(|(status, _)| status.starts_with('2'))
+                .map(|(status, response)| (status.clone(), content_schemas(Some(response))))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn component_schemas(document: &Value) -> Option<&Map<String, Value>> {
+    document
+        .pointer("/components/schemas")
+        .and_then(Value::as_object)
+}
+
+fn compare_schema(name: &str, generated: &Value, checked_in: &Value, drift: &mut Vec<String>) {
+    let checks: [(&str, SchemaFieldSet); 3] = [
+        ("properties", property_names),
+        ("required", required_names),
+        ("enum", enum_values),
+    ];
+    for (label, collect) in checks {
+        let generated_set = collect(generated);
+        let checked_in_set = collect(checked_in);
+        if generated_set != checked_in_set {
+            drift.push(format!(
+                "schema {name}: {label} {generated_set:?} in code but {checked_in_set:?} in the contract"
+            ));
+        }
+    }
+}
+
+fn property_names(schema: &Value) -> BTreeSet<String> {
+    schema
+        .get("properties")
+        .and_then(Value::as_object)
+        .map(|properties| properties.keys().cloned().collect())
+        .unwrap_or_default()
+}
+
+fn required_names(schema: &Value) -> BTreeSet<String> {
+    string_set(schema.get("required"))
+}
+
+fn enum_values(schema: &Value) -> BTreeSet<String> {
+    string_set(schema.get("enum"))
+}
+
+fn string_set(values: Option<&Value>) -> BTreeSet<String> {
+    values
+        .and_then(Value::as_array)
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn display(value: Option<&Value>) -> String {
+    value.map_or_else(|| "absent".to_string(), Value::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{contract_drift, openapi_document};
+
+    const CHECKED_IN_CONTRACT: &str = include_str!("../../../../../api/openapi.yaml");
+
+    #[test]
+    fn checked_in_contract_matches_handler_declarations() {
+        let checked_in = serde_yaml::from_str(CHECKED_IN_CONTRACT)
+            .expect("api/openapi.yaml should parse as YAML");
+
+        let drift = contract_drift(&openapi_document(), &checked_in);
+
+        assert!(
+            drift.is_empty(),
+            "api/openapi.yaml drifted from the handlers:\n{}",
+            drift.join("\n")
+        );
+    }
+
+    #[test]
+    fn drift_reports_undocumented_routes_and_changed_schemas() {
+        let generated = openapi_document();
+        let mut checked_in = generated.clone();
+        checked_in["paths"]
+            .as_object_mut()
+            .expect("paths should be an object")
+            .remove("/v1/devices");
+        checked_in["components"]["schemas"]["OkResponse"]["properties"]["extra"] =
+            serde_json::json!({ "type": "string" });
+
+        let drift = contract_drift(&generated, &checked_in);
+
+        assert!(drift.contains(&"GET /v1/devices: served but not documented".to_string()));
+        assert!(
+            drift
+                .iter()
+                .any(|entry| entry.starts_with("schema OkResponse: properties"))
+        );
+        assert!(contract_drift(&generated, &generated).is_empty());
+    }
+}
@@ -0,0 +1,62 @@
+use axum::response::{IntoResponse, Response};
+use shared::models::ApiErrorCode;
+use shared::pagination::{CursorResource, Page, PageRequest};
+
+use super::AppState;
+use super::errors::error_response;
+
+/// Default and maximum page size of one list endpoint.
+#[derive(Clone, Copy)]
+pub(super) struct PageLimits {
+    pub(super) default: usize,
+    pub(super) max: usize,
+}
+
+pub(super) enum PageRequestError {
+    InvalidLimit { max: usize },
+    InvalidCursor,
+}
+
+impl IntoResponse for PageRequestError {
+    fn into_response(self) -> Response {
+        match self {
+            Self::InvalidLimit { max } => error_response(
+                ApiErrorCode::InvalidLimit,
+                &format!("limit must be between 1 and {max}"),
+            ),
+            Self::InvalidCursor => error_response(ApiErrorCode::InvalidCursor, "Cursor is invalid"),
+        }
+    }
+}
+
+/// Validates the `cursor` and `limit` query parameters shared by every list endpoint.
+pub(super) fn page_request(
+    state: &AppState,
+    resource: CursorResource,
+    cursor: Option<&str>,
+    limit: Option<i64>,
+    limits: PageLimits,
+) -> Result<PageRequest, PageRequestError> {
+    let limit = match limit {
+        None => limits.default,
+        Some(limit) => usize::try_from(limit)
+            .ok()
+            .filter(|limit| (1..=limits.max).contains(limit))
+            .ok_or(PageRequestError::InvalidLimit { max: limits.max })?,
+    };
+    let after = cursor
+        .map(|cursor| state.cursor_codec.decode(resource, cursor))
+        .transpose()
+        .map_err(|_| PageRequestError::InvalidCursor)?;
+
+    Ok(PageRequest { after, limit })
+}
+
+pub(super) fn next_cursor<T>(
+    state: &AppState,
+    resource: CursorResource,
+    page: &Page<T>,
+) -> Option<String> {
+    page.next
+        .map(|key| state.cursor_codec.encode(resource, &key))
+}
@@ -1,16 +1,23 @@
-use std::collections::HashMap;
-
 use axum::Json;
 use axum::extract::{Extension, Path, State};
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
-use shared::models::{DeleteAllResponse, DeleteAllStatusResponse, ErrorBody, ErrorResponse};
+use shared::models::{ApiErrorCode, AuditMetadata, DeleteAllResponse, DeleteAllStatusResponse};
 use shared::repos::AuditResult;
 use uuid::Uuid;
 
-use super::errors::store_error_response;
+use super::errors::{error_response, store_error_response};
+use super::openapi::ApiOperation;
 use super::{AppState, AuthUser};
 
+pub(super) const DELETE_ALL: ApiOperation = ApiOperation::post(
+    "/v1/privacy/delete-all",
+    "requestDeleteAll",
+    "Privacy",
+    "Queue delete-all request",
+)
+.response::<DeleteAllResponse>();
+
 pub(super) async fn delete_all(
     State(state): State<AppState>,
     Extension(user): Extension<AuthUser>,
@@ -20,8 +27,8 @@ pub(super) async fn delete_all(
         Err(err) => return store_error_response(err),
     };
 
-    let mut metadata = HashMap::new();
-    metadata.insert("request_id".to_string(), request_id.to_string());
+    let mut metadata = AuditMetadata::new();
+    metadata.insert("request_id".to_string(), request_id.to_string().into());
 
     if let Err(err) = state
         .store
@@ -47,6 +54,14 @@ pub(super) async fn delete_all(
         .into_response()
 }
 
+pub(super) const GET_DELETE_ALL_STATUS: ApiOperation = ApiOperation::get(
+    "/v1/privacy/delete-all/{request_id}",
+    "getDeleteAllStatus",
+    "Privacy",
+    "Get delete-all request status",
+)
+.response::<DeleteAllStatusResponse>();
+
 pub(super) async fn get_delete_all_status(
     State(state): State<AppState>,
     Extension(user): Extension<AuthUser>,
@@ -55,16 +70,7 @@ pub(super) async fn get_delete_all_status(
     let request_id = match Uuid::parse_str(&request_id) {
         Ok(request_id) => request_id,
         Err(_) => {
-            return (
-                StatusCode::NOT_FOUND,
-                Json(ErrorResponse {
-                    error: ErrorBody {
-                        code: "not_found".to_string(),
-                        message: "Delete request not found".to_string(),
-                    },
-                }),
-            )
-                .into_response();
+            return error_response(ApiErrorCode::NotFound, "Delete request not found");
         }
     };
 
@@ -75,16 +81,7 @@ pub(super) async fn get_delete_all_status(
     {
         Ok(Some(delete_status)) => delete_status,
         Ok(None) => {
-            return (
-                StatusCode::NOT_FOUND,
-                Json(ErrorResponse {
-                    error: ErrorBody {
-                        code: "not_found".to_string(),
-                        message: "Delete request not found".to_string(),
-                    },
-                }),
-            )
-                .into_response();
+            return error_response(ApiErrorCode::NotFound, "Delete request not found");
         }
         Err(err) => return store_error_response(err),
     };
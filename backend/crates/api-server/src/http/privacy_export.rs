@@ -0,0 +1,219 @@
+use axum::Json;
+use axum::body::Body;
+use axum::extract::{Extension, Path, State};
+use axum::http::{HeaderValue, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use chrono::{Duration, Utc};
+use shared::models::{
+    ApiErrorCode, AuditMetadata, DownloadPrivacyExportRequest, PrivacyExportResponse,
+    PrivacyExportStatusResponse,
+};
+use shared::repos::{AuditResult, PrivacyExportRequestStatus, PrivacyExportStatus};
+use uuid::Uuid;
+
+use super::errors::{error_response, store_error_response};
+use super::openapi::ApiOperation;
+use super::request_body::ApiJson;
+use super::tokens::{generate_secure_token, hash_token};
+use super::{AppState, AuthUser};
+
+/// Download links are minted on each status poll, so they only need to outlive a single fetch.
+const EXPORT_DOWNLOAD_TOKEN_TTL_SECONDS: i64 = 15 * 60;
+
+pub(super) const REQUEST_EXPORT: ApiOperation = ApiOperation::post(
+    "/v1/privacy/export",
+    "requestPrivacyExport",
+    "Privacy",
+    "Queue data export request",
+)
+.response::<PrivacyExportResponse>();
+
+pub(super) async fn request_export(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+) -> Response {
+    let request_id = match state.store.queue_privacy_export(user.user_id).await {
+        Ok(request_id) => request_id,
+        Err(err) => return store_error_response(err),
+    };
+
+    let mut metadata = AuditMetadata::new();
+    metadata.insert("request_id".to_string(), request_id.to_string().into());
+
+    if let Err(err) = state
+        .store
+        .add_audit_event(
+            user.user_id,
+            "PRIVACY_EXPORT_REQUESTED",
+            None,
+            AuditResult::Success,
+            &metadata,
+        )
+        .await
+    {
+        return store_error_response(err);
+    }
+
+    (
+        StatusCode::OK,
+        Json(PrivacyExportResponse {
+            request_id: request_id.to_string(),
+            status: PrivacyExportStatus::Queued.as_str().to_string(),
+        }),
+    )
+        .into_response()
+}
+
+pub(super) const GET_EXPORT_STATUS: ApiOperation = ApiOperation::get(
+    "/v1/privacy/export/{request_id}",
+    "getPrivacyExportStatus",
+    "Privacy",
+    "Get data export request status",
+)
+.response::<PrivacyExportStatusResponse>();
+
+/// Reports export progress. Once the archive is ready each call mints a fresh one-time
+/// download token, invalidating any token returned earlier.
+pub(super) async fn get_export_status(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(request_id): Path<String>,
+) -> Response {
+    let Ok(request_id) = Uuid::parse_str(&request_id) else {
+        return export_not_found_response();
+    };
+
+    let export_status = match state
+        .store
+        .get_export_request_status(user.user_id, request_id)
+        .await
+    {
+        Ok(Some(export_status)) => export_status,
+        Ok(None) => return export_not_found_response(),
+        Err(err) => return store_error_response(err),
+    };
+
+    let now = Utc::now();
+    let mut download_url = None;
+    let mut download_token = None;
+    let mut download_url_expires_at = None;
+    if is_downloadable(&export_status, now) {
+        let token = generate_secure_token("pex");
+        let token_expires_at = now + Duration::seconds(EXPORT_DOWNLOAD_TOKEN_TTL_SECONDS);
+        match state
+            .store
+            .issue_export_download_token(
+                user.user_id,
+                request_id,
+                &hash_token(&token),
+                token_expires_at,
+                now,
+            )
+            .await
+        {
+            Ok(true) => {
+                download_url = Some(format!("/v1/privacy/export/{request_id}/download"));
+                download_token = Some(token);
+                download_url_expires_at = Some(token_expires_at);
+            }
+            Ok(false) => {}
+            Err(err) => return store_error_response(err),
+        }
+    }
+
+    (
+        StatusCode::OK,
+        Json(PrivacyExportStatusResponse {
+            request_id: export_status.id.to_string(),
+            status: export_status.status.as_str().to_string(),
+            created_at: export_status.created_at,
+            started_at: export_status.started_at,
+            completed_at: export_status.completed_at,
+            failed_at: export_status.failed_at,
+            downloaded_at: export_status.downloaded_at,
+            expires_at: export_status.expires_at,
+            archive_size_bytes: export_status.archive_size_bytes,
+            download_url,
+            download_token,
+            download_url_expires_at,
+        }),
+    )
+        .into_response()
+}
+
+pub(super) const DOWNLOAD_EXPORT: ApiOperation = ApiOperation::post(
+    "/v1/privacy/export/{request_id}/download",
+    "downloadPrivacyExport",
+    "Privacy",
+    "Download data export archive",
+)
+.public()
+.request::<DownloadPrivacyExportRequest>()
+.json_document_response();
+
+/// Serves the archive for a valid download token. The token is the credential, so this
+/// route sits outside bearer auth; it travels in the body rather than the URL so proxies and
+/// access logs never record it. Redeeming it deletes the archive.
+pub(super) async fn download_export(
+    State(state): State<AppState>,
+    Path(request_id): Path<String>,
+    ApiJson(req): ApiJson<DownloadPrivacyExportRequest>,
+) -> Response {
+    let Ok(request_id) = Uuid::parse_str(&request_id) else {
+        return export_not_found_response();
+    };
+
+    let download = match state
+        .store
+        .consume_export_download(request_id, &hash_token(&req.token), Utc::now())
+        .await
+    {
+        Ok(Some(download)) => download,
+        Ok(None) => return export_not_found_response(),
+        Err(err) => return store_error_response(err),
+    };
+
+    let mut metadata = AuditMetadata::new();
+    metadata.insert("request_id".to_string(), request_id.to_string().into());
+    metadata.insert(
+        "archive_size_bytes".to_string(),
+        download.archive.len().into(),
+    );
+    if let Err(err) = state
+        .store
+        .add_audit_event(
+            download.user_id,
+            "PRIVACY_EXPORT_DOWNLOADED",
+            None,
+            AuditResult::Success,
+            &metadata,
+        )
+        .await
+    {
+        return store_error_response(err);
+    }
+
+    let mut response = Response::new(Body::from(download.archive));
+    let headers = response.headers_mut();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/json"),
+    );
+    if let Ok(disposition) = HeaderValue::from_str(&format!(
+        "attachment; filename=\"alfred-export-{request_id}.json\""
+    )) {
+        headers.insert(header::CONTENT_DISPOSITION, disposition);
+    }
+    headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+    response
+}
+
+fn is_downloadable(status: &PrivacyExportRequestStatus, now: chrono::DateTime<Utc>) -> bool {
+    status.status == PrivacyExportStatus::Completed
+        && status.archive_available
+        && status.expires_at.is_some_and(|expires_at| expires_at > now)
+}
+
+fn export_not_found_response() -> Response {
+    error_response(ApiErrorCode::NotFound, "Export request not found")
+}
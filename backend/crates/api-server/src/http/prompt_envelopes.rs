@@ -0,0 +1,39 @@
+use axum::response::Response;
+use shared::models::ApiErrorCode;
+use shared::repos::PromptEnvelopeUse;
+use tracing::warn;
+use uuid::Uuid;
+
+use super::AppState;
+use super::errors::{error_response, store_error_response};
+
+/// Rejects an encrypted prompt envelope whose request id the user already submitted anywhere.
+/// The id is burned even if the request later fails, so clients encrypt again to retry. The
+/// claim is kept until `key_id`, the ingress key the envelope was sealed to, expires.
+pub(super) async fn claim_prompt_envelope_request_id(
+    state: &AppState,
+    user_id: Uuid,
+    key_id: &str,
+    request_id: &str,
+    used_for: PromptEnvelopeUse,
+) -> Result<(), Response> {
+    match state
+        .store
+        .claim_prompt_envelope_request_id(user_id, key_id.trim(), request_id.trim(), used_for)
+        .await
+    {
+        Ok(true) => Ok(()),
+        Ok(false) => {
+            warn!(
+                user_id = %user_id,
+                used_for = used_for.as_str(),
+                "rejected replayed prompt envelope"
+            );
+            Err(error_response(
+                ApiErrorCode::RequestIdReused,
+                "prompt envelope request_id was already used",
+            ))
+        }
+        Err(err) => Err(store_error_response(err)),
+    }
+}
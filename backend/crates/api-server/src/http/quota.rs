@@ -0,0 +1,143 @@
+use axum::Json;
+use axum::http::{HeaderValue, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use chrono::{DateTime, Utc};
+use shared::models::{
+    ApiErrorCode, ErrorBody, QuotaExceededDetail, QuotaExceededResponse, QuotaKind, UserPlan,
+};
+use shared::quota::{PlanQuotas, quota_day_reset, quota_day_start};
+use tracing::info;
+use uuid::Uuid;
+
+use super::AppState;
+use super::errors::store_error_response;
+
+/// Rejects the request with a `quota_exceeded` 429 when the user's plan has no room left for
+/// one more use of `kind`.
+pub(super) async fn enforce_plan_quota(
+    state: &AppState,
+    user_id: Uuid,
+    kind: QuotaKind,
+) -> Result<(), Response> {
+    let now = Utc::now();
+    let plan = state
+        .store
+        .get_user_plan(user_id)
+        .await
+        .map_err(store_error_response)?;
+    let limit = PlanQuotas::for_plan(plan).limit(kind);
+    let used = state
+        .store
+        .count_quota_usage(user_id, kind, quota_day_start(now))
+        .await
+        .map_err(store_error_response)?;
+
+    if used < limit {
+        return Ok(());
+    }
+
+    info!(
+        user_id = %user_id,
+        plan = plan.as_str(),
+        quota = kind.as_str(),
+        limit,
+        used,
+        "request denied by plan quota"
+    );
+    Err(quota_exceeded_response(kind, plan, limit, used, now))
+}
+
+fn quota_exceeded_response(
+    kind: QuotaKind,
+    plan: UserPlan,
+    limit: i64,
+    used: i64,
+    now: DateTime<Utc>,
+) -> Response {
+    let resets_at = kind.is_daily().then(|| quota_day_reset(now));
+    let message = match kind {
+        QuotaKind::AssistantQueriesPerDay => "Daily assistant question limit reached for your plan",
+        QuotaKind::AutomationRules => {
+            "Automation limit reached for your plan; archive or delete one to add another"
+        }
+        QuotaKind::LlmRequestsPerDay => "Daily AI usage limit reached for your plan",
+    };
+
+    let mut response = (
+        StatusCode::TOO_MANY_REQUESTS,
+        Json(QuotaExceededResponse {
+            error: ErrorBody::new(ApiErrorCode::QuotaExceeded, message),
+            quota: QuotaExceededDetail {
+                kind,
+                plan,
+                limit,
+                used,
+                resets_at,
+            },
+        }),
+    )
+        .into_response();
+
+    if let Some(resets_at) = resets_at {
+        let retry_after_seconds = (resets_at - now).num_seconds().max(1);
+        response.headers_mut().insert(
+            header::RETRY_AFTER,
+            HeaderValue::from(retry_after_seconds as u64),
+        );
+    }
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::to_bytes;
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn daily_quota_denial_carries_reset_time_and_retry_after() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 14, 22, 0, 0).unwrap();
+        let response = quota_exceeded_response(
+            QuotaKind::AssistantQueriesPerDay,
+            UserPlan::Free,
+            50,
+            50,
+            now,
+        );
+
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(
+            response
+                .headers()
+                .get(header::RETRY_AFTER)
+                .and_then(|value| value.to_str().ok()),
+            Some("7200")
+        );
+        let body = to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body should read");
+        let body: QuotaExceededResponse =
+            serde_json::from_slice(&body).expect("body should decode");
+        assert_eq!(body.error.code, ApiErrorCode::QuotaExceeded);
+        assert_eq!(body.quota.kind, QuotaKind::AssistantQueriesPerDay);
+        assert_eq!(body.quota.plan, UserPlan::Free);
+        assert_eq!(
+            body.quota.resets_at,
+            Some(Utc.with_ymd_and_hms(2026, 3, 15, 0, 0, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn rule_cap_denial_has_no_reset() {
+        let response = quota_exceeded_response(
+            QuotaKind::AutomationRules,
+            UserPlan::Pro,
+            100,
+            100,
+            Utc::now(),
+        );
+        assert!(response.headers().get(header::RETRY_AFTER).is_none());
+    }
+}
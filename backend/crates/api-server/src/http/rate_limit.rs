@@ -1,32 +1,41 @@
-use std::collections::{HashMap, HashSet, VecDeque};
-use std::net::{IpAddr, SocketAddr};
+use std::collections::{HashMap, VecDeque};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
-use axum::extract::{ConnectInfo, Request, State};
-use axum::http::Method;
+use axum::extract::{Request, State};
+use axum::http::{HeaderName, HeaderValue};
 use axum::middleware::Next;
 use axum::response::Response;
 use tracing::warn;
+use uuid::Uuid;
 
+use super::abuse::{AbuseEscalation, AbuseSignal, AbuseTracker};
+use super::anomaly::{AnomalySignal, AnomalyTracker, SecurityAnomaly};
 use super::errors::too_many_requests_response;
+use super::quota::enforce_plan_quota;
+use super::rate_limit_redis::{RedisRateLimitStore, RedisWindowState};
 use super::{AppState, AuthUser};
 
+mod admin;
+mod buckets;
+mod policy;
+mod subject;
+
+use buckets::{RateLimitBucketKey, prune_entries};
+use policy::{MAX_TRACKED_WINDOW_SECONDS, RateLimitPolicy, SensitiveEndpoint};
+use subject::request_subject;
+pub(super) use subject::{ip_subject, remote_ip, user_subject};
+
+/// Sliding-window limits per route class. With Redis attached the windows are shared by
+/// every api-server instance; the in-process buckets serve as the fallback whenever Redis
+/// is unreachable.
 #[derive(Clone, Default)]
 pub struct RateLimiter {
     entries: Arc<Mutex<HashMap<RateLimitBucketKey, VecDeque<Instant>>>>,
-}
-
-#[derive(Debug, Clone, Copy)]
-enum SensitiveEndpoint {
-    GoogleConnectStart,
-    GoogleConnectCallback,
-    RevokeConnector,
-    PrivacyDeleteAll,
-    AutomationCreate,
-    AutomationUpdate,
-    AutomationDelete,
-    AutomationDebugRun,
+    abuse: AbuseTracker,
+    anomalies: AnomalyTracker,
+    policy_overrides: Arc<HashMap<SensitiveEndpoint, RateLimitPolicy>>,
+    redis: Option<RedisRateLimitStore>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -35,180 +44,143 @@ enum RateLimitDecision {
     Denied { retry_after_seconds: u64 },
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct RateLimitBucketKey {
-    endpoint: &'static str,
-    subject: String,
+/// Window state after a check, reported to clients as `RateLimit-*` headers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct RateLimitQuota {
+    limit: usize,
+    remaining: usize,
+    /// Seconds until the oldest request in the window expires and frees a slot.
+    reset_seconds: u64,
 }
 
-#[derive(Debug, Clone, Copy)]
-struct RateLimitPolicy {
-    max_requests: usize,
-    window_seconds: u64,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct RateLimitOutcome {
+    decision: RateLimitDecision,
+    quota: RateLimitQuota,
 }
 
-const MAX_TRACKED_WINDOW_SECONDS: u64 = 3600;
-
-impl SensitiveEndpoint {
-    fn from_request(req: &Request) -> Option<Self> {
-        let method = req.method();
-        let path = req.uri().path();
+const RATE_LIMIT_LIMIT_HEADER: HeaderName = HeaderName::from_static("ratelimit-limit");
+const RATE_LIMIT_REMAINING_HEADER: HeaderName = HeaderName::from_static("ratelimit-remaining");
+const RATE_LIMIT_RESET_HEADER: HeaderName = HeaderName::from_static("ratelimit-reset");
 
-        match (method, path) {
-            (&Method::POST, "/v1/connectors/google/start") => Some(Self::GoogleConnectStart),
-            (&Method::POST, "/v1/connectors/google/callback") => Some(Self::GoogleConnectCallback),
-            (&Method::DELETE, path) if path.starts_with("/v1/connectors/") => {
-                Some(Self::RevokeConnector)
-            }
-            (&Method::POST, "/v1/privacy/delete-all") => Some(Self::PrivacyDeleteAll),
-            (&Method::POST, "/v1/automations") => Some(Self::AutomationCreate),
-            (&Method::PATCH, path) if path.starts_with("/v1/automations/") => {
-                Some(Self::AutomationUpdate)
-            }
-            (&Method::DELETE, path) if path.starts_with("/v1/automations/") => {
-                Some(Self::AutomationDelete)
-            }
-            (&Method::POST, path)
-                if path.starts_with("/v1/automations/") && path.ends_with("/debug/run") =>
-            {
-                Some(Self::AutomationDebugRun)
-            }
-            _ => None,
-        }
-    }
+/// Sensitive route limits are divided by this for an account in an enforced anomaly cooldown.
+const ANOMALY_TIGHTENING_DIVISOR: usize = 4;
 
-    fn key_name(self) -> &'static str {
-        match self {
-            Self::GoogleConnectStart => "google_connect_start",
-            Self::GoogleConnectCallback => "google_connect_callback",
-            Self::RevokeConnector => "revoke_connector",
-            Self::PrivacyDeleteAll => "privacy_delete_all",
-            Self::AutomationCreate => "automation_create",
-            Self::AutomationUpdate => "automation_update",
-            Self::AutomationDelete => "automation_delete",
-            Self::AutomationDebugRun => "automation_debug_run",
-        }
+impl RateLimiter {
+    /// Once an account or IP trips a security anomaly, `enabled` blocks the IP or tightens the
+    /// account's sensitive route limits for `cooldown_seconds`.
+    pub fn with_security_anomaly_enforcement(
+        mut self,
+        enabled: bool,
+        cooldown_seconds: u64,
+    ) -> Self {
+        self.anomalies = self.anomalies.with_enforcement(enabled, cooldown_seconds);
+        self
     }
 
-    fn policy(self) -> RateLimitPolicy {
-        match self {
-            Self::GoogleConnectStart => RateLimitPolicy {
-                max_requests: 20,
-                window_seconds: 60,
-            },
-            Self::GoogleConnectCallback => RateLimitPolicy {
-                max_requests: 20,
-                window_seconds: 60,
-            },
-            Self::RevokeConnector => RateLimitPolicy {
-                max_requests: 10,
-                window_seconds: 60,
-            },
-            Self::PrivacyDeleteAll => RateLimitPolicy {
-                max_requests: 3,
-                window_seconds: 3600,
-            },
-            Self::AutomationCreate => RateLimitPolicy {
-                max_requests: 20,
-                window_seconds: 60,
-            },
-            Self::AutomationUpdate => RateLimitPolicy {
-                max_requests: 30,
-                window_seconds: 60,
-            },
-            Self::AutomationDelete => RateLimitPolicy {
-                max_requests: 20,
-                window_seconds: 60,
-            },
-            Self::AutomationDebugRun => RateLimitPolicy {
-                max_requests: 20,
-                window_seconds: 60,
-            },
+    /// Shares limits across instances through Redis. Redis being unreachable at startup is
+    /// logged and the limiter stays in-process.
+    pub async fn with_redis_from_config(mut self, redis_url: &str) -> Self {
+        match RedisRateLimitStore::connect(redis_url).await {
+            Ok(redis) => self.redis = Some(redis),
+            Err(err) => warn!("distributed rate limiting disabled, redis unavailable: {err}"),
         }
+        self
     }
-}
 
-impl RateLimiter {
     pub fn spawn_pruner(&self, interval: Duration) -> tokio::task::JoinHandle<()> {
         let entries = Arc::clone(&self.entries);
+        let abuse = self.abuse.clone();
+        let anomalies = self.anomalies.clone();
         tokio::spawn(async move {
             let mut ticker = tokio::time::interval(interval);
             loop {
                 ticker.tick().await;
-                prune_entries(&entries, Instant::now());
+                let now = Instant::now();
+                prune_entries(&entries, now);
+                abuse.prune(now, Duration::from_secs(MAX_TRACKED_WINDOW_SECONDS));
+                anomalies.prune(now, Duration::from_secs(MAX_TRACKED_WINDOW_SECONDS));
             }
         })
     }
 
-    fn check(&self, endpoint: SensitiveEndpoint, subject: &str) -> RateLimitDecision {
-        self.check_at(endpoint, subject, Instant::now())
+    pub(super) fn record_abuse_signal(
+        &self,
+        signal: AbuseSignal,
+        user_id: Uuid,
+    ) -> Option<AbuseEscalation> {
+        self.abuse
+            .record_at(signal, &user_subject(user_id), Instant::now())
     }
 
-    fn check_at(
+    pub(super) async fn record_security_signal(
         &self,
-        endpoint: SensitiveEndpoint,
+        signal: AnomalySignal,
         subject: &str,
-        now: Instant,
-    ) -> RateLimitDecision {
-        let policy = endpoint.policy();
-        let window = Duration::from_secs(policy.window_seconds);
-        let cutoff = now.checked_sub(window).unwrap_or(now);
-        let bucket_key = RateLimitBucketKey {
-            endpoint: endpoint.key_name(),
-            subject: subject.to_string(),
-        };
+    ) -> Option<SecurityAnomaly> {
+        self.anomalies
+            .record(self.redis.as_ref(), signal, subject)
+            .await
+    }
 
-        let mut entries = self
-            .entries
-            .lock()
-            .expect("rate limiter mutex should not be poisoned");
+    /// Seconds left on an enforced anomaly cooldown for `subject`.
+    pub(super) async fn anomaly_enforced_for(&self, subject: &str) -> Option<u64> {
+        self.anomalies
+            .enforced_for(self.redis.as_ref(), subject)
+            .await
+    }
 
-        let bucket = entries.entry(bucket_key).or_default();
-        prune_bucket(bucket, cutoff);
+    pub(super) fn anomaly_enforced_for_seconds(&self) -> Option<u64> {
+        self.anomalies.enforced_for_seconds()
+    }
 
-        if bucket.len() >= policy.max_requests {
-            let retry_after_seconds = bucket
-                .front()
-                .map(|first_seen| {
-                    let elapsed = now.saturating_duration_since(*first_seen);
-                    window.saturating_sub(elapsed).as_secs().max(1)
-                })
-                .unwrap_or(policy.window_seconds);
-            return RateLimitDecision::Denied {
-                retry_after_seconds,
-            };
+    async fn check(
+        &self,
+        endpoint: SensitiveEndpoint,
+        subject: &str,
+        tightened: bool,
+    ) -> RateLimitOutcome {
+        let mut policy = self.policy(endpoint);
+        if tightened {
+            policy.max_requests = (policy.max_requests / ANOMALY_TIGHTENING_DIVISOR).max(1);
+        }
+        if let Some(redis) = self.redis.as_ref() {
+            match redis
+                .check(
+                    endpoint.key_name(),
+                    subject,
+                    policy.max_requests,
+                    policy.window_seconds,
+                )
+                .await
+            {
+                Ok(state) => return redis_outcome(policy, state),
+                Err(err) => warn!(
+                    endpoint = endpoint.key_name(),
+                    "redis rate limit check failed, using in-process limits: {err}"
+                ),
+            }
         }
 
-        bucket.push_back(now);
-
-        RateLimitDecision::Allowed
+        self.check_policy_at(endpoint, policy, subject, Instant::now())
     }
 }
 
-fn prune_entries(
-    entries: &Arc<Mutex<HashMap<RateLimitBucketKey, VecDeque<Instant>>>>,
-    now: Instant,
-) {
-    let global_cutoff = now
-        .checked_sub(Duration::from_secs(MAX_TRACKED_WINDOW_SECONDS))
-        .unwrap_or(now);
-    let mut state = entries
-        .lock()
-        .expect("rate limiter prune mutex should not be poisoned");
-
-    state.retain(|_, bucket| {
-        prune_bucket(bucket, global_cutoff);
-        !bucket.is_empty()
-    });
-}
-
-fn prune_bucket(bucket: &mut VecDeque<Instant>, cutoff: Instant) {
-    while let Some(front) = bucket.front() {
-        if *front <= cutoff {
-            bucket.pop_front();
-        } else {
-            break;
+fn redis_outcome(policy: RateLimitPolicy, state: RedisWindowState) -> RateLimitOutcome {
+    let decision = if state.admitted {
+        RateLimitDecision::Allowed
+    } else {
+        RateLimitDecision::Denied {
+            retry_after_seconds: state.reset_seconds,
         }
+    };
+    RateLimitOutcome {
+        decision,
+        quota: RateLimitQuota {
+            limit: policy.max_requests,
+            remaining: policy.max_requests.saturating_sub(state.requests_in_window),
+            reset_seconds: state.reset_seconds,
+        },
     }
 }
 
@@ -223,8 +195,44 @@ pub(super) async fn sensitive_rate_limit_middleware(
 
     let subject = request_subject(&req, &state.trusted_proxy_ips);
 
-    match state.rate_limiter.check(endpoint, &subject) {
-        RateLimitDecision::Allowed => next.run(req).await,
+    if endpoint.is_automation_mutation()
+        && let Some(retry_after_seconds) = state
+            .rate_limiter
+            .abuse
+            .blocked_for_at(&subject, Instant::now())
+    {
+        warn!(
+            endpoint = endpoint.key_name(),
+            retry_after_seconds, "request denied by abuse escalation",
+        );
+        state
+            .metrics
+            .record_rate_limit_rejection(endpoint.key_name(), "abuse_block");
+        return too_many_requests_response(retry_after_seconds);
+    }
+
+    let tightened = state
+        .rate_limiter
+        .anomaly_enforced_for(&subject)
+        .await
+        .is_some();
+    let outcome = state
+        .rate_limiter
+        .check(endpoint, &subject, tightened)
+        .await;
+    let mut response = match outcome.decision {
+        RateLimitDecision::Allowed => {
+            let quota_user = endpoint
+                .plan_quota()
+                .zip(req.extensions().get::<AuthUser>().map(|user| user.user_id));
+            match quota_user {
+                Some((kind, user_id)) => match enforce_plan_quota(&state, user_id, kind).await {
+                    Ok(()) => next.run(req).await,
+                    Err(response) => response,
+                },
+                None => next.run(req).await,
+            }
+        }
         RateLimitDecision::Denied {
             retry_after_seconds,
         } => {
@@ -232,241 +240,67 @@ pub(super) async fn sensitive_rate_limit_middleware(
                 endpoint = endpoint.key_name(),
                 retry_after_seconds, "request denied by endpoint rate limit",
             );
+            state
+                .metrics
+                .record_rate_limit_rejection(endpoint.key_name(), "window");
             too_many_requests_response(retry_after_seconds)
         }
-    }
-}
-
-fn request_subject(req: &Request, trusted_proxy_ips: &HashSet<IpAddr>) -> String {
-    if let Some(user) = req.extensions().get::<AuthUser>() {
-        return format!("user:{}", user.user_id);
-    }
-
-    if let Some(ip) = remote_ip(req, trusted_proxy_ips) {
-        return format!("ip:{ip}");
-    }
-
-    "anonymous".to_string()
-}
-
-fn remote_ip(req: &Request, trusted_proxy_ips: &HashSet<IpAddr>) -> Option<IpAddr> {
-    let peer_ip = req
-        .extensions()
-        .get::<ConnectInfo<SocketAddr>>()
-        .map(|connect_info| connect_info.0.ip())?;
-
-    if !trusted_proxy_ips.contains(&peer_ip) {
-        return Some(peer_ip);
-    }
-
-    forwarded_client_ip(req, trusted_proxy_ips, peer_ip).or(Some(peer_ip))
+    };
+    insert_rate_limit_headers(&mut response, outcome.quota);
+    response
 }
 
-fn forwarded_client_ip(
-    req: &Request,
-    trusted_proxy_ips: &HashSet<IpAddr>,
-    peer_ip: IpAddr,
-) -> Option<IpAddr> {
-    let mut chain = forwarded_for_chain(req);
-    if !chain.is_empty() {
-        chain.push(peer_ip);
-        if let Some(client_ip) = first_untrusted_from_right(&chain, trusted_proxy_ips) {
-            return Some(client_ip);
-        }
+/// Lets clients pace themselves before hitting the limit; a denial also carries `Retry-After`.
+fn insert_rate_limit_headers(response: &mut Response, quota: RateLimitQuota) {
+    let headers = response.headers_mut();
+    for (name, value) in [
+        (RATE_LIMIT_LIMIT_HEADER, quota.limit as u64),
+        (RATE_LIMIT_REMAINING_HEADER, quota.remaining as u64),
+        (RATE_LIMIT_RESET_HEADER, quota.reset_seconds),
+    ] {
+        headers.insert(name, HeaderValue::from(value));
     }
-
-    req.headers()
-        .get("x-real-ip")
-        .and_then(|value| value.to_str().ok())
-        .and_then(|value| value.trim().parse::<IpAddr>().ok())
-}
-
-fn forwarded_for_chain(req: &Request) -> Vec<IpAddr> {
-    req.headers()
-        .get_all("x-forwarded-for")
-        .iter()
-        .filter_map(|value| value.to_str().ok())
-        .flat_map(parse_ip_chain)
-        .collect()
-}
-
-fn parse_ip_chain(raw: &str) -> Vec<IpAddr> {
-    raw.split(',')
-        .map(str::trim)
-        .filter(|entry| !entry.is_empty())
-        .filter_map(|entry| entry.parse::<IpAddr>().ok())
-        .collect()
-}
-
-fn first_untrusted_from_right(
-    chain: &[IpAddr],
-    trusted_proxy_ips: &HashSet<IpAddr>,
-) -> Option<IpAddr> {
-    chain
-        .iter()
-        .rev()
-        .find(|ip| !trusted_proxy_ips.contains(ip))
-        .copied()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use axum::body::Body;
-    use axum::http::header::HeaderName;
-    use std::collections::HashSet;
-
-    #[test]
-    fn allows_until_limit_then_denies() {
-        let limiter = RateLimiter::default();
-        let start = Instant::now();
-
-        for _ in 0..20 {
-            assert_eq!(
-                limiter.check_at(SensitiveEndpoint::GoogleConnectStart, "ip:1.2.3.4", start),
-                RateLimitDecision::Allowed
-            );
-        }
-
-        let denied = limiter.check_at(SensitiveEndpoint::GoogleConnectStart, "ip:1.2.3.4", start);
-        assert!(matches!(
-            denied,
-            RateLimitDecision::Denied {
-                retry_after_seconds: 1..=60
-            }
-        ));
-    }
-
-    #[test]
-    fn different_endpoints_have_independent_limits() {
-        let limiter = RateLimiter::default();
-        let start = Instant::now();
-
-        for _ in 0..20 {
-            assert_eq!(
-                limiter.check_at(SensitiveEndpoint::GoogleConnectStart, "ip:1.2.3.4", start),
-                RateLimitDecision::Allowed
-            );
-        }
-
-        assert_eq!(
-            limiter.check_at(
-                SensitiveEndpoint::GoogleConnectCallback,
-                "ip:1.2.3.4",
-                start
-            ),
-            RateLimitDecision::Allowed
-        );
-    }
 
-    #[test]
-    fn window_resets_after_expiration() {
-        let limiter = RateLimiter::default();
-        let start = Instant::now();
-        let after_window = start + Duration::from_secs(61);
-
-        for _ in 0..20 {
-            assert_eq!(
-                limiter.check_at(SensitiveEndpoint::GoogleConnectStart, "ip:1.2.3.4", start),
-                RateLimitDecision::Allowed
-            );
-        }
+    #[tokio::test]
+    async fn check_without_redis_uses_in_process_buckets() {
+        let limiter = RateLimiter::default()
+            .with_policy_overrides(&["privacy_delete_all=1/60".to_string()])
+            .expect("override should parse");
 
         assert_eq!(
-            limiter.check_at(
-                SensitiveEndpoint::GoogleConnectStart,
-                "ip:1.2.3.4",
-                after_window
-            ),
+            limiter
+                .check(SensitiveEndpoint::PrivacyDeleteAll, "user:a", false)
+                .await
+                .decision,
             RateLimitDecision::Allowed
         );
+        assert!(matches!(
+            limiter
+                .check(SensitiveEndpoint::PrivacyDeleteAll, "user:a", false)
+                .await
+                .decision,
+            RateLimitDecision::Denied { .. }
+        ));
     }
 
-    #[test]
-    fn stale_buckets_are_pruned() {
+    #[tokio::test]
+    async fn tightened_check_divides_the_route_class_limit() {
         let limiter = RateLimiter::default();
-        let start = Instant::now();
-        let stale_cutoff = start + Duration::from_secs(MAX_TRACKED_WINDOW_SECONDS + 1);
-
-        assert_eq!(
-            limiter.check_at(SensitiveEndpoint::GoogleConnectStart, "user:stale", start),
-            RateLimitDecision::Allowed
-        );
-        prune_entries(&limiter.entries, stale_cutoff);
-
-        let entries = limiter
-            .entries
-            .lock()
-            .expect("test mutex should not be poisoned");
-        assert!(entries.is_empty());
-    }
 
-    #[test]
-    fn request_subject_prefers_connect_info_over_spoofable_forward_headers() {
-        let trusted_proxy_ips = HashSet::new();
-        let mut request = Request::builder()
-            .uri("/v1/connectors/google/start")
-            .body(Body::empty())
-            .expect("request builder should work");
-
-        request.headers_mut().insert(
-            HeaderName::from_static("x-forwarded-for"),
-            "203.0.113.99".parse().expect("header value should parse"),
-        );
-        request
-            .extensions_mut()
-            .insert(ConnectInfo(SocketAddr::from(([10, 20, 30, 40], 8080))));
-
-        let subject = request_subject(&request, &trusted_proxy_ips);
-        assert_eq!(subject, "ip:10.20.30.40");
-    }
-
-    #[test]
-    fn request_subject_uses_forwarded_chain_when_peer_is_trusted_proxy() {
-        let trusted_proxy_ips = HashSet::from([IpAddr::from([10, 0, 0, 5])]);
-        let mut request = Request::builder()
-            .uri("/v1/connectors/google/start")
-            .body(Body::empty())
-            .expect("request builder should work");
-
-        request.headers_mut().insert(
-            HeaderName::from_static("x-forwarded-for"),
-            "198.51.100.20, 10.0.0.5"
-                .parse()
-                .expect("header value should parse"),
-        );
-        request
-            .extensions_mut()
-            .insert(ConnectInfo(SocketAddr::from(([10, 0, 0, 5], 8080))));
-
-        let subject = request_subject(&request, &trusted_proxy_ips);
-        assert_eq!(subject, "ip:198.51.100.20");
-    }
-
-    #[test]
-    fn request_subject_consumes_all_xff_header_values() {
-        let trusted_proxy_ips =
-            HashSet::from([IpAddr::from([10, 0, 0, 5]), IpAddr::from([10, 0, 0, 9])]);
-        let mut request = Request::builder()
-            .uri("/v1/connectors/google/start")
-            .body(Body::empty())
-            .expect("request builder should work");
-
-        request.headers_mut().append(
-            HeaderName::from_static("x-forwarded-for"),
-            "203.0.113.250".parse().expect("header value should parse"),
-        );
-        request.headers_mut().append(
-            HeaderName::from_static("x-forwarded-for"),
-            "198.51.100.20, 10.0.0.9"
-                .parse()
-                .expect("header value should parse"),
-        );
-        request
-            .extensions_mut()
-            .insert(ConnectInfo(SocketAddr::from(([10, 0, 0, 5], 8080))));
+        let outcome = limiter
+            .check(SensitiveEndpoint::AutomationCreate, "user:a", true)
+            .await;
+        assert_eq!(outcome.decision, RateLimitDecision::Allowed);
+        assert_eq!(outcome.quota.limit, 5);
 
-        let subject = request_subject(&request, &trusted_proxy_ips);
-        assert_eq!(subject, "ip:198.51.100.20");
+        let floor = limiter
+            .check(SensitiveEndpoint::PrivacyDeleteAll, "user:a", true)
+            .await;
+        assert_eq!(floor.quota.limit, 1);
     }
 }
@@ -0,0 +1,116 @@
+use std::time::Instant;
+
+use shared::models::{AdminRateLimitWindow, RateLimitBackend};
+use tracing::warn;
+use uuid::Uuid;
+
+use super::RateLimiter;
+use super::policy::{RateLimitPolicy, SensitiveEndpoint};
+use super::subject::user_subject;
+
+impl RateLimiter {
+    /// Current window of every route class for `user_id`, without recording a request.
+    pub(crate) async fn user_windows(&self, user_id: Uuid) -> Vec<AdminRateLimitWindow> {
+        let subject = user_subject(user_id);
+        let mut windows = Vec::with_capacity(SensitiveEndpoint::ALL.len());
+        for endpoint in SensitiveEndpoint::ALL {
+            windows.push(self.peek(endpoint, &subject).await);
+        }
+        windows
+    }
+
+    /// Seconds left on an abuse escalation block for `user_id` on this instance.
+    pub(crate) fn abuse_blocked_for(&self, user_id: Uuid) -> Option<u64> {
+        self.abuse
+            .blocked_for_at(&user_subject(user_id), Instant::now())
+    }
+
+    async fn peek(&self, endpoint: SensitiveEndpoint, subject: &str) -> AdminRateLimitWindow {
+        let policy = self.policy(endpoint);
+        if let Some(redis) = self.redis.as_ref() {
+            match redis
+                .peek(endpoint.key_name(), subject, policy.window_seconds)
+                .await
+            {
+                Ok((requests_in_window, reset_seconds)) => {
+                    return rate_limit_window(
+                        endpoint,
+                        policy,
+                        requests_in_window,
+                        reset_seconds,
+                        RateLimitBackend::Redis,
+                    );
+                }
+                Err(err) => warn!(
+                    endpoint = endpoint.key_name(),
+                    "redis rate limit peek failed, reporting in-process limits: {err}"
+                ),
+            }
+        }
+
+        let (requests_in_window, reset_seconds) =
+            self.peek_at(endpoint, policy, subject, Instant::now());
+        rate_limit_window(
+            endpoint,
+            policy,
+            requests_in_window,
+            reset_seconds,
+            RateLimitBackend::InProcess,
+        )
+    }
+}
+
+fn rate_limit_window(
+    endpoint: SensitiveEndpoint,
+    policy: RateLimitPolicy,
+    requests_in_window: usize,
+    reset_seconds: Option<u64>,
+    backend: RateLimitBackend,
+) -> AdminRateLimitWindow {
+    AdminRateLimitWindow {
+        route_class: endpoint.key_name().to_string(),
+        limit: policy.max_requests as u64,
+        window_seconds: policy.window_seconds,
+        requests_in_window: requests_in_window as u64,
+        remaining: policy.max_requests.saturating_sub(requests_in_window) as u64,
+        reset_seconds,
+        backend,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::super::policy::SensitiveEndpoint;
+    use super::RateLimiter;
+
+    #[test]
+    fn peek_reports_the_window_without_recording_a_request() {
+        let limiter = RateLimiter::default();
+        let endpoint = SensitiveEndpoint::AutomationRunNow;
+        let policy = limiter.policy(endpoint);
+        let start = Instant::now();
+
+        assert_eq!(
+            limiter.peek_at(endpoint, policy, "user:1", start),
+            (0, None)
+        );
+        limiter.check_at(endpoint, "user:1", start);
+        limiter.check_at(endpoint, "user:1", start + Duration::from_secs(10));
+
+        let later = start + Duration::from_secs(20);
+        assert_eq!(
+            limiter.peek_at(endpoint, policy, "user:1", later),
+            (2, Some(40))
+        );
+        assert_eq!(
+            limiter.peek_at(endpoint, policy, "user:1", later),
+            (2, Some(40))
+        );
+        assert_eq!(
+            limiter.peek_at(endpoint, policy, "user:1", start + Duration::from_secs(65)),
+            (1, Some(5))
+        );
+    }
+}
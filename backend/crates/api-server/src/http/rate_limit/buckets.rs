@@ -0,0 +1,288 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use super::policy::{MAX_TRACKED_WINDOW_SECONDS, RateLimitPolicy, SensitiveEndpoint};
+use super::{RateLimitDecision, RateLimitOutcome, RateLimitQuota, RateLimiter};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(super) struct RateLimitBucketKey {
+    endpoint: &'static str,
+    subject: String,
+}
+
+pub(super) fn prune_entries(
+    entries: &Arc<Mutex<HashMap<RateLimitBucketKey, VecDeque<Instant>>>>,
+    now: Instant,
+) {
+    let global_cutoff = now
+        .checked_sub(Duration::from_secs(MAX_TRACKED_WINDOW_SECONDS))
+        .unwrap_or(now);
+    let mut state = entries
+        .lock()
+        .expect("rate limiter prune mutex should not be poisoned");
+
+    state.retain(|_, bucket| {
+        prune_bucket(bucket, global_cutoff);
+        !bucket.is_empty()
+    });
+}
+
+fn prune_bucket(bucket: &mut VecDeque<Instant>, cutoff: Instant) {
+    while let Some(front) = bucket.front() {
+        if *front <= cutoff {
+            bucket.pop_front();
+        } else {
+            break;
+        }
+    }
+}
+
+impl RateLimiter {
+    #[cfg(test)]
+    pub(super) fn check_at(
+        &self,
+        endpoint: SensitiveEndpoint,
+        subject: &str,
+        now: Instant,
+    ) -> RateLimitOutcome {
+        self.check_policy_at(endpoint, self.policy(endpoint), subject, now)
+    }
+
+    pub(super) fn check_policy_at(
+        &self,
+        endpoint: SensitiveEndpoint,
+        policy: RateLimitPolicy,
+        subject: &str,
+        now: Instant,
+    ) -> RateLimitOutcome {
+        let window = Duration::from_secs(policy.window_seconds);
+        let cutoff = now.checked_sub(window).unwrap_or(now);
+        let bucket_key = RateLimitBucketKey {
+            endpoint: endpoint.key_name(),
+            subject: subject.to_string(),
+        };
+
+        let mut entries = self
+            .entries
+            .lock()
+            .expect("rate limiter mutex should not be poisoned");
+
+        let bucket = entries.entry(bucket_key).or_default();
+        prune_bucket(bucket, cutoff);
+
+        let admitted = bucket.len() < policy.max_requests;
+        if admitted {
+            bucket.push_back(now);
+        }
+        let reset_seconds = bucket
+            .front()
+            .map(|first_seen| {
+                let elapsed = now.saturating_duration_since(*first_seen);
+                window.saturating_sub(elapsed).as_secs().max(1)
+            })
+            .unwrap_or(policy.window_seconds);
+        let quota = RateLimitQuota {
+            limit: policy.max_requests,
+            remaining: policy.max_requests.saturating_sub(bucket.len()),
+            reset_seconds,
+        };
+
+        let decision = if admitted {
+            RateLimitDecision::Allowed
+        } else {
+            RateLimitDecision::Denied {
+                retry_after_seconds: reset_seconds,
+            }
+        };
+        RateLimitOutcome { decision, quota }
+    }
+
+    pub(super) fn peek_at(
+        &self,
+        endpoint: SensitiveEndpoint,
+        policy: RateLimitPolicy,
+        subject: &str,
+        now: Instant,
+    ) -> (usize, Option<u64>) {
+        let window = Duration::from_secs(policy.window_seconds);
+        let cutoff = now.checked_sub(window).unwrap_or(now);
+        let bucket_key = RateLimitBucketKey {
+            endpoint: endpoint.key_name(),
+            subject: subject.to_string(),
+        };
+        let entries = self
+            .entries
+            .lock()
+            .expect("rate limiter mutex should not be poisoned");
+        let Some(bucket) = entries.get(&bucket_key) else {
+            return (0, None);
+        };
+
+        let requests_in_window = bucket.iter().filter(|seen| **seen > cutoff).count();
+        let reset_seconds = bucket
+            .iter()
+            .find(|seen| **seen > cutoff)
+            .map(|first_seen| {
+                let elapsed = now.saturating_duration_since(*first_seen);
+                window.saturating_sub(elapsed).as_secs().max(1)
+            });
+        (requests_in_window, reset_seconds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use axum::response::Response;
+
+    use super::super::insert_rate_limit_headers;
+    use super::super::policy::{MAX_TRACKED_WINDOW_SECONDS, SensitiveEndpoint};
+    use super::{RateLimitDecision, RateLimitQuota, RateLimiter, prune_entries};
+
+    #[test]
+    fn allows_until_limit_then_denies() {
+        let limiter = RateLimiter::default();
+        let start = Instant::now();
+
+        for _ in 0..20 {
+            assert_eq!(
+                limiter
+                    .check_at(SensitiveEndpoint::GoogleConnectStart, "ip:1.2.3.4", start)
+                    .decision,
+                RateLimitDecision::Allowed
+            );
+        }
+
+        let denied = limiter
+            .check_at(SensitiveEndpoint::GoogleConnectStart, "ip:1.2.3.4", start)
+            .decision;
+        assert!(matches!(
+            denied,
+            RateLimitDecision::Denied {
+                retry_after_seconds: 1..=60
+            }
+        ));
+    }
+
+    #[test]
+    fn different_endpoints_have_independent_limits() {
+        let limiter = RateLimiter::default();
+        let start = Instant::now();
+
+        for _ in 0..20 {
+            assert_eq!(
+                limiter
+                    .check_at(SensitiveEndpoint::GoogleConnectStart, "ip:1.2.3.4", start)
+                    .decision,
+                RateLimitDecision::Allowed
+            );
+        }
+
+        assert_eq!(
+            limiter
+                .check_at(
+                    SensitiveEndpoint::GoogleConnectCallback,
+                    "ip:1.2.3.4",
+                    start
+                )
+                .decision,
+            RateLimitDecision::Allowed
+        );
+    }
+
+    #[test]
+    fn window_resets_after_expiration() {
+        let limiter = RateLimiter::default();
+        let start = Instant::now();
+        let after_window = start + Duration::from_secs(61);
+
+        for _ in 0..20 {
+            assert_eq!(
+                limiter
+                    .check_at(SensitiveEndpoint::GoogleConnectStart, "ip:1.2.3.4", start)
+                    .decision,
+                RateLimitDecision::Allowed
+            );
+        }
+
+        assert_eq!(
+            limiter
+                .check_at(
+                    SensitiveEndpoint::GoogleConnectStart,
+                    "ip:1.2.3.4",
+                    after_window
+                )
+                .decision,
+            RateLimitDecision::Allowed
+        );
+    }
+
+    #[test]
+    fn quota_counts_down_and_reports_reset_on_denial() {
+        let limiter = RateLimiter::default()
+            .with_policy_overrides(&["revoke_connector=2/60".to_string()])
+            .expect("override should parse");
+        let start = Instant::now();
+
+        let first = limiter.check_at(SensitiveEndpoint::RevokeConnector, "user:a", start);
+        assert_eq!(
+            first.quota,
+            RateLimitQuota {
+                limit: 2,
+                remaining: 1,
+                reset_seconds: 60,
+            }
+        );
+
+        let later = start + Duration::from_secs(20);
+        let second = limiter.check_at(SensitiveEndpoint::RevokeConnector, "user:a", later);
+        assert_eq!(second.decision, RateLimitDecision::Allowed);
+        assert_eq!(second.quota.remaining, 0);
+        assert_eq!(second.quota.reset_seconds, 40);
+
+        let denied = limiter.check_at(SensitiveEndpoint::RevokeConnector, "user:a", later);
+        assert_eq!(
+            denied.decision,
+            RateLimitDecision::Denied {
+                retry_after_seconds: 40
+            }
+        );
+        assert_eq!(denied.quota.remaining, 0);
+
+        let mut response = Response::new(axum::body::Body::empty());
+        insert_rate_limit_headers(&mut response, denied.quota);
+        let header = |name: &str| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+        assert_eq!(header("RateLimit-Limit").as_deref(), Some("2"));
+        assert_eq!(header("RateLimit-Remaining").as_deref(), Some("0"));
+        assert_eq!(header("RateLimit-Reset").as_deref(), Some("40"));
+    }
+
+    #[test]
+    fn stale_buckets_are_pruned() {
+        let limiter = RateLimiter::default();
+        let start = Instant::now();
+        let stale_cutoff = start + Duration::from_secs(MAX_TRACKED_WINDOW_SECONDS + 1);
+
+        assert_eq!(
+            limiter
+                .check_at(SensitiveEndpoint::GoogleConnectStart, "user:stale", start)
+                .decision,
+            RateLimitDecision::Allowed
+        );
+        prune_entries(&limiter.entries, stale_cutoff);
+
+        let entries = limiter
+            .entries
+            .lock()
+            .expect("test mutex should not be poisoned");
+        assert!(entries.is_empty());
+    }
+}
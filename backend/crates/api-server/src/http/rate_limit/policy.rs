@@ -0,0 +1,264 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::Request;
+use axum::http::Method;
+use shared::models::QuotaKind;
+
+use super::RateLimiter;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(super) enum SensitiveEndpoint {
+    GoogleConnectStart,
+    GoogleConnectCallback,
+    RevokeConnector,
+    PrivacyDeleteAll,
+    AutomationCreate,
+    AutomationUpdate,
+    AutomationDelete,
+    AutomationDebugRun,
+    AutomationRunNow,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(super) struct RateLimitPolicy {
+    pub(super) max_requests: usize,
+    pub(super) window_seconds: u64,
+}
+
+pub(super) const MAX_TRACKED_WINDOW_SECONDS: u64 = 3600;
+
+impl SensitiveEndpoint {
+    pub(super) const ALL: [Self; 9] = [
+        Self::GoogleConnectStart,
+        Self::GoogleConnectCallback,
+        Self::RevokeConnector,
+        Self::PrivacyDeleteAll,
+        Self::AutomationCreate,
+        Self::AutomationUpdate,
+        Self::AutomationDelete,
+        Self::AutomationDebugRun,
+        Self::AutomationRunNow,
+    ];
+
+    pub(super) fn from_request(req: &Request) -> Option<Self> {
+        let method = req.method();
+        let path = req.uri().path();
+
+        match (method, path) {
+            (&Method::POST, "/v1/connectors/google/start") => Some(Self::GoogleConnectStart),
+            (&Method::POST, "/v1/connectors/google/callback") => Some(Self::GoogleConnectCallback),
+            (&Method::DELETE, path) if path.starts_with("/v1/connectors/") => {
+                Some(Self::RevokeConnector)
+            }
+            (&Method::POST, "/v1/privacy/delete-all") => Some(Self::PrivacyDeleteAll),
+            (&Method::POST, "/v1/automations") => Some(Self::AutomationCreate),
+            (&Method::PATCH, path) if path.starts_with("/v1/automations/") => {
+                Some(Self::AutomationUpdate)
+            }
+            (&Method::DELETE, path) if path.starts_with("/v1/automations/") => {
+                Some(Self::AutomationDelete)
+            }
+            (&Method::POST, path)
+                if path.starts_with("/v1/automations/") && path.ends_with("/debug/run") =>
+            {
+                Some(Self::AutomationDebugRun)
+            }
+            (&Method::POST, path)
+                if path.starts_with("/v1/automations/") && path.ends_with("/run") =>
+            {
+                Some(Self::AutomationRunNow)
+            }
+            _ => None,
+        }
+    }
+
+    pub(super) fn key_name(self) -> &'static str {
+        match self {
+            Self::GoogleConnectStart => "google_connect_start",
+            Self::GoogleConnectCallback => "google_connect_callback",
+            Self::RevokeConnector => "revoke_connector",
+            Self::PrivacyDeleteAll => "privacy_delete_all",
+            Self::AutomationCreate => "automation_create",
+            Self::AutomationUpdate => "automation_update",
+            Self::AutomationDelete => "automation_delete",
+            Self::AutomationDebugRun => "automation_debug_run",
+            Self::AutomationRunNow => "automation_run_now",
+        }
+    }
+
+    /// Endpoints that are closed to a subject while an abuse escalation is active.
+    pub(super) fn is_automation_mutation(self) -> bool {
+        matches!(
+            self,
+            Self::AutomationCreate
+                | Self::AutomationUpdate
+                | Self::AutomationDelete
+                | Self::AutomationDebugRun
+                | Self::AutomationRunNow
+        )
+    }
+
+    /// Plan quota a request to this endpoint uses up. Run-now requests check theirs in the
+    /// handler so idempotent replays are not turned away.
+    pub(super) fn plan_quota(self) -> Option<QuotaKind> {
+        match self {
+            Self::AutomationCreate => Some(QuotaKind::AutomationRules),
+            Self::AutomationDebugRun => Some(QuotaKind::LlmRequestsPerDay),
+            _ => None,
+        }
+    }
+
+    pub(super) fn default_policy(self) -> RateLimitPolicy {
+        match self {
+            Self::GoogleConnectStart => RateLimitPolicy {
+                max_requests: 20,
+                window_seconds: 60,
+            },
+            Self::GoogleConnectCallback => RateLimitPolicy {
+                max_requests: 20,
+                window_seconds: 60,
+            },
+            Self::RevokeConnector => RateLimitPolicy {
+                max_requests: 10,
+                window_seconds: 60,
+            },
+            Self::PrivacyDeleteAll => RateLimitPolicy {
+                max_requests: 3,
+                window_seconds: 3600,
+            },
+            Self::AutomationCreate => RateLimitPolicy {
+                max_requests: 20,
+                window_seconds: 60,
+            },
+            Self::AutomationUpdate => RateLimitPolicy {
+                max_requests: 30,
+                window_seconds: 60,
+            },
+            Self::AutomationDelete => RateLimitPolicy {
+                max_requests: 20,
+                window_seconds: 60,
+            },
+            Self::AutomationDebugRun => RateLimitPolicy {
+                max_requests: 20,
+                window_seconds: 60,
+            },
+            Self::AutomationRunNow => RateLimitPolicy {
+                max_requests: 5,
+                window_seconds: 60,
+            },
+        }
+    }
+}
+
+fn parse_policy_override(entry: &str) -> Result<(&str, RateLimitPolicy), String> {
+    let invalid =
+        || format!("rate limit override must be route_class=max_requests/window_seconds: {entry}");
+    let (route_class, limit) = entry.split_once('=').ok_or_else(invalid)?;
+    let (max_requests, window_seconds) = limit.split_once('/').ok_or_else(invalid)?;
+    let max_requests = max_requests
+        .trim()
+        .parse::<usize>()
+        .map_err(|_| invalid())?;
+    let window_seconds = window_seconds
+        .trim()
+        .parse::<u64>()
+        .map_err(|_| invalid())?;
+    if max_requests == 0 || window_seconds == 0 || window_seconds > MAX_TRACKED_WINDOW_SECONDS {
+        return Err(format!(
+            "rate limit override needs max_requests > 0 and a window of 1-{MAX_TRACKED_WINDOW_SECONDS} seconds: {entry}"
+        ));
+    }
+
+    Ok((
+        route_class.trim(),
+        RateLimitPolicy {
+            max_requests,
+            window_seconds,
+        },
+    ))
+}
+
+impl RateLimiter {
+    /// Applies `route_class=max_requests/window_seconds` overrides, where `route_class` is an
+    /// endpoint key such as `automation_create`. Windows are capped at one hour.
+    pub fn with_policy_overrides(mut self, overrides: &[String]) -> Result<Self, String> {
+        let mut policies = HashMap::new();
+        for entry in overrides {
+            let (route_class, policy) = parse_policy_override(entry)?;
+            let endpoint = SensitiveEndpoint::ALL
+                .into_iter()
+                .find(|endpoint| endpoint.key_name() == route_class)
+                .ok_or_else(|| format!("unknown rate limit route class: {route_class}"))?;
+            policies.insert(endpoint, policy);
+        }
+        self.policy_overrides = Arc::new(policies);
+        Ok(self)
+    }
+
+    pub(super) fn policy(&self, endpoint: SensitiveEndpoint) -> RateLimitPolicy {
+        self.policy_overrides
+            .get(&endpoint)
+            .copied()
+            .unwrap_or_else(|| endpoint.default_policy())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use super::super::RateLimitDecision;
+    use super::{RateLimiter, SensitiveEndpoint};
+
+    #[test]
+    fn policy_overrides_replace_route_class_defaults() {
+        let limiter = RateLimiter::default()
+            .with_policy_overrides(&["automation_create=2/30".to_string()])
+            .expect("override should parse");
+        let start = Instant::now();
+
+        for _ in 0..2 {
+            assert_eq!(
+                limiter
+                    .check_at(SensitiveEndpoint::AutomationCreate, "user:a", start)
+                    .decision,
+                RateLimitDecision::Allowed
+            );
+        }
+        assert!(matches!(
+            limiter
+                .check_at(SensitiveEndpoint::AutomationCreate, "user:a", start)
+                .decision,
+            RateLimitDecision::Denied {
+                retry_after_seconds: 1..=30
+            }
+        ));
+        assert_eq!(
+            limiter
+                .policy(SensitiveEndpoint::AutomationUpdate)
+                .max_requests,
+            SensitiveEndpoint::AutomationUpdate
+                .default_policy()
+                .max_requests
+        );
+    }
+
+    #[test]
+    fn policy_overrides_reject_unknown_classes_and_bad_limits() {
+        for invalid in [
+            "automation_explode=1/60",
+            "automation_create=0/60",
+            "automation_create=5/7200",
+            "automation_create=5",
+            "automation_create",
+        ] {
+            assert!(
+                RateLimiter::default()
+                    .with_policy_overrides(&[invalid.to_string()])
+                    .is_err(),
+                "{invalid} should be rejected"
+            );
+        }
+    }
+}
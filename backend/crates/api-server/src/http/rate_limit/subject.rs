@@ -0,0 +1,168 @@
+use std::collections::HashSet;
+use std::net::{IpAddr, SocketAddr};
+
+use axum::extract::{ConnectInfo, Request};
+use uuid::Uuid;
+
+use super::super::AuthUser;
+
+pub(super) fn request_subject(req: &Request, trusted_proxy_ips: &HashSet<IpAddr>) -> String {
+    if let Some(user) = req.extensions().get::<AuthUser>() {
+        return user_subject(user.user_id);
+    }
+
+    if let Some(ip) = remote_ip(req, trusted_proxy_ips) {
+        return ip_subject(ip);
+    }
+
+    "anonymous".to_string()
+}
+
+pub(crate) fn user_subject(user_id: Uuid) -> String {
+    format!("user:{user_id}")
+}
+
+pub(crate) fn ip_subject(ip: IpAddr) -> String {
+    format!("ip:{ip}")
+}
+
+pub(crate) fn remote_ip(req: &Request, trusted_proxy_ips: &HashSet<IpAddr>) -> Option<IpAddr> {
+    let peer_ip = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|connect_info| connect_info.0.ip())?;
+
+    if !trusted_proxy_ips.contains(&peer_ip) {
+        return Some(peer_ip);
+    }
+
+    forwarded_client_ip(req, trusted_proxy_ips, peer_ip).or(Some(peer_ip))
+}
+
+fn forwarded_client_ip(
+    req: &Request,
+    trusted_proxy_ips: &HashSet<IpAddr>,
+    peer_ip: IpAddr,
+) -> Option<IpAddr> {
+    let mut chain = forwarded_for_chain(req);
+    if !chain.is_empty() {
+        chain.push(peer_ip);
+        if let Some(client_ip) = first_untrusted_from_right(&chain, trusted_proxy_ips) {
+            return Some(client_ip);
+        }
+    }
+
+    req.headers()
+        .get("x-real-ip")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<IpAddr>().ok())
+}
+
+fn forwarded_for_chain(req: &Request) -> Vec<IpAddr> {
+    req.headers()
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(parse_ip_chain)
+        .collect()
+}
+
+fn parse_ip_chain(raw: &str) -> Vec<IpAddr> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .filter_map(|entry| entry.parse::<IpAddr>().ok())
+        .collect()
+}
+
+fn first_untrusted_from_right(
+    chain: &[IpAddr],
+    trusted_proxy_ips: &HashSet<IpAddr>,
+) -> Option<IpAddr> {
+    chain
+        .iter()
+        .rev()
+        .find(|ip| !trusted_proxy_ips.contains(ip))
+        .copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::net::{IpAddr, SocketAddr};
+
+    use axum::body::Body;
+    use axum::extract::{ConnectInfo, Request};
+    use axum::http::header::HeaderName;
+
+    use super::request_subject;
+
+    #[test]
+    fn request_subject_prefers_connect_info_over_spoofable_forward_headers() {
+        let trusted_proxy_ips = HashSet::new();
+        let mut request = Request::builder()
+            .uri("/v1/connectors/google/start")
+            .body(Body::empty())
+            .expect("request builder should work");
+
+        request.headers_mut().insert(
+            HeaderName::from_static("x-forwarded-for"),
+            "203.0.113.99".parse().expect("header value should parse"),
+        );
+        request
+            .extensions_mut()
+            .insert(ConnectInfo(SocketAddr::from(([10, 20, 30, 40], 8080))));
+
+        let subject = request_subject(&request, &trusted_proxy_ips);
+        assert_eq!(subject, "ip:10.20.30.40");
+    }
+
+    #[test]
+    fn request_subject_uses_forwarded_chain_when_peer_is_trusted_proxy() {
+        let trusted_proxy_ips = HashSet::from([IpAddr::from([10, 0, 0, 5])]);
+        let mut request = Request::builder()
+            .uri("/v1/connectors/google/start")
+            .body(Body::empty())
+            .expect("request builder should work");
+
+        request.headers_mut().insert(
+            HeaderName::from_static("x-forwarded-for"),
+            "198.51.100.20, 10.0.0.5"
+                .parse()
+                .expect("header value should parse"),
+        );
+        request
+            .extensions_mut()
+            .insert(ConnectInfo(SocketAddr::from(([10, 0, 0, 5], 8080))));
+
+        let subject = request_subject(&request, &trusted_proxy_ips);
+        assert_eq!(subject, "ip:198.51.100.20");
+    }
+
+    #[test]
+    fn request_subject_consumes_all_xff_header_values() {
+        let trusted_proxy_ips =
+            HashSet::from([IpAddr::from([10, 0, 0, 5]), IpAddr::from([10, 0, 0, 9])]);
+        let mut request = Request::builder()
+            .uri("/v1/connectors/google/start")
+            .body(Body::empty())
+            .expect("request builder should work");
+
+        request.headers_mut().append(
+            HeaderName::from_static("x-forwarded-for"),
+            "203.0.113.250".parse().expect("header value should parse"),
+        );
+        request.headers_mut().append(
+            HeaderName::from_static("x-forwarded-for"),
+            "198.51.100.20, 10.0.0.9"
+                .parse()
+                .expect("header value should parse"),
+        );
+        request
+            .extensions_mut()
+            .insert(ConnectInfo(SocketAddr::from(([10, 0, 0, 5], 8080))));
+
+        let subject = request_subject(&request, &trusted_proxy_ips);
+        assert_eq!(subject, "ip:198.51.100.20");
+    }
+}
@@ -0,0 +1,248 @@
+use std::time::Duration;
+
+use redis::aio::{ConnectionManager, ConnectionManagerConfig};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+const RATE_LIMIT_KEY_PREFIX: &str = "alfred:api:rate_limit:v1";
+const SECURITY_ANOMALY_KEY_PREFIX: &str = "alfred:api:security_anomaly:v1";
+/// Every protected request waits on this round trip, so a slow Redis must fail fast and let
+/// the in-memory limiter take over.
+const RATE_LIMIT_RESPONSE_TIMEOUT: Duration = Duration::from_millis(100);
+const RATE_LIMIT_CONNECTION_TIMEOUT: Duration = Duration::from_secs(2);
+const RATE_LIMIT_CONNECTION_RETRIES: usize = 2;
+const RATE_LIMIT_MAX_RETRY_DELAY_MS: u64 = 500;
+
+/// Sliding-window log kept in a sorted set scored by Redis server time, so every instance
+/// measures the window against the same clock. Returns `{admitted, requests_in_window,
+/// reset_ms}` where `reset_ms` is the time until the oldest request leaves the window.
+const SLIDING_WINDOW_SCRIPT: &str = r"
+local time = redis.call('TIME')
+local now_ms = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+local window_ms = tonumber(ARGV[1])
+local max_requests = tonumber(ARGV[2])
+redis.call('ZREMRANGEBYSCORE', KEYS[1], '-inf', now_ms - window_ms)
+local admitted = 0
+local count = redis.call('ZCARD', KEYS[1])
+if count < max_requests then
+  redis.call('ZADD', KEYS[1], now_ms, ARGV[3])
+  redis.call('PEXPIRE', KEYS[1], window_ms)
+  admitted = 1
+  count = count + 1
+end
+local oldest = redis.call('ZRANGE', KEYS[1], 0, 0, 'WITHSCORES')
+return {admitted, count, tonumber(oldest[2]) + window_ms - now_ms}
+";
+
+/// Read-only view of the same window: `{requests_in_window, reset_ms}`, with `reset_ms` set to
+/// -1 when the window is empty.
+const PEEK_WINDOW_SCRIPT: &str = r"
+local time = redis.call('TIME')
+local now_ms = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+local window_ms = tonumber(ARGV[1])
+local cutoff = '(' .. (now_ms - window_ms)
+local count = redis.call('ZCOUNT', KEYS[1], cutoff, '+inf')
+local oldest = redis.call('ZRANGEBYSCORE', KEYS[1], cutoff, '+inf', 'WITHSCORES', 'LIMIT', 0, 1)
+if oldest[2] == nil then
+  return {count, -1}
+end
+return {count, tonumber(oldest[2]) + window_ms - now_ms}
+";
+
+/// Unconditionally logs one occurrence in the same kind of sorted-set window and returns how
+/// many occurrences it now holds.
+const RECORD_EVENT_SCRIPT: &str = r"
+local time = redis.call('TIME')
+local now_ms = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+local window_ms = tonumber(ARGV[1])
+redis.call('ZREMRANGEBYSCORE', KEYS[1], '-inf', now_ms - window_ms)
+redis.call('ZADD', KEYS[1], now_ms, ARGV[2])
+redis.call('PEXPIRE', KEYS[1], window_ms)
+return redis.call('ZCARD', KEYS[1])
+";
+
+/// Outcome of one shared window check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct RedisWindowState {
+    pub(super) admitted: bool,
+    pub(super) requests_in_window: usize,
+    pub(super) reset_seconds: u64,
+}
+
+/// Shared rate limit state so limits hold across api-server instances.
+#[derive(Clone)]
+pub(super) struct RedisRateLimitStore {
+    connection: ConnectionManager,
+}
+
+impl RedisRateLimitStore {
+    pub(super) async fn connect(redis_url: &str) -> Result<Self, String> {
+        let client = redis::Client::open(redis_url).map_err(|err| err.to_string())?;
+        let connection = ConnectionManager::new_with_config(
+            client,
+            ConnectionManagerConfig::new()
+                .set_response_timeout(RATE_LIMIT_RESPONSE_TIMEOUT)
+                .set_connection_timeout(RATE_LIMIT_CONNECTION_TIMEOUT)
+                .set_number_of_retries(RATE_LIMIT_CONNECTION_RETRIES)
+                .set_max_delay(RATE_LIMIT_MAX_RETRY_DELAY_MS),
+        )
+        .await
+        .map_err(|err| err.to_string())?;
+
+        let mut health_connection = connection.clone();
+        redis::cmd("PING")
+            .query_async::<String>(&mut health_connection)
+            .await
+            .map_err(|err| format!("failed to connect to redis: {err}"))?;
+
+        Ok(Self { connection })
+    }
+
+    /// Records the request when the window has room and reports the window afterwards.
+    pub(super) async fn check(
+        &self,
+        endpoint: &str,
+        subject: &str,
+        max_requests: usize,
+        window_seconds: u64,
+    ) -> redis::RedisResult<RedisWindowState> {
+        let mut connection = self.connection.clone();
+        let (admitted, requests_in_window, reset_ms): (i64, i64, i64) = redis::cmd("EVAL")
+            .arg(SLIDING_WINDOW_SCRIPT)
+            .arg(1)
+            .arg(rate_limit_key(endpoint, subject))
+            .arg(window_seconds.saturating_mul(1000))
+            .arg(max_requests)
+            .arg(Uuid::new_v4().to_string())
+            .query_async(&mut connection)
+            .await?;
+
+        Ok(RedisWindowState {
+            admitted: admitted == 1,
+            requests_in_window: usize::try_from(requests_in_window).unwrap_or(usize::MAX),
+            reset_seconds: reset_seconds(reset_ms),
+        })
+    }
+
+    /// Reports the window without recording a request. `reset_seconds` is `None` when the
+    /// window is empty.
+    pub(super) async fn peek(
+        &self,
+        endpoint: &str,
+        subject: &str,
+        window_seconds: u64,
+    ) -> redis::RedisResult<(usize, Option<u64>)> {
+        let mut connection = self.connection.clone();
+        let (requests_in_window, reset_ms): (i64, i64) = redis::cmd("EVAL")
+            .arg(PEEK_WINDOW_SCRIPT)
+            .arg(1)
+            .arg(rate_limit_key(endpoint, subject))
+            .arg(window_seconds.saturating_mul(1000))
+            .query_async(&mut connection)
+            .await?;
+
+        Ok((
+            usize::try_from(requests_in_window).unwrap_or(usize::MAX),
+            (reset_ms >= 0).then(|| reset_seconds(reset_ms)),
+        ))
+    }
+
+    /// Records one security signal occurrence and returns the occurrences now in the window.
+    pub(super) async fn record_security_signal(
+        &self,
+        signal: &str,
+        subject: &str,
+        window_seconds: u64,
+    ) -> redis::RedisResult<usize> {
+        let mut connection = self.connection.clone();
+        let observed: i64 = redis::cmd("EVAL")
+            .arg(RECORD_EVENT_SCRIPT)
+            .arg(1)
+            .arg(security_anomaly_key(signal, subject))
+            .arg(window_seconds.saturating_mul(1000))
+            .arg(Uuid::new_v4().to_string())
+            .query_async(&mut connection)
+            .await?;
+        Ok(usize::try_from(observed).unwrap_or(usize::MAX))
+    }
+
+    /// Starts an anomaly cooldown unless one is already running. Only the instance that starts
+    /// it gets `true`, so each anomaly is reported once across the fleet.
+    pub(super) async fn start_anomaly_cooldown(
+        &self,
+        subject: &str,
+        cooldown_seconds: u64,
+    ) -> redis::RedisResult<bool> {
+        let mut connection = self.connection.clone();
+        let started: Option<String> = redis::cmd("SET")
+            .arg(security_anomaly_key("cooldown", subject))
+            .arg(1)
+            .arg("NX")
+            .arg("EX")
+            .arg(cooldown_seconds)
+            .query_async(&mut connection)
+            .await?;
+        Ok(started.is_some())
+    }
+
+    /// Seconds left on the subject's anomaly cooldown, if one is running.
+    pub(super) async fn anomaly_cooldown(&self, subject: &str) -> redis::RedisResult<Option<u64>> {
+        let mut connection = self.connection.clone();
+        let remaining_ms: i64 = redis::cmd("PTTL")
+            .arg(security_anomaly_key("cooldown", subject))
+            .query_async(&mut connection)
+            .await?;
+        Ok((remaining_ms > 0).then(|| reset_seconds(remaining_ms)))
+    }
+}
+
+/// Subjects are user ids or client IPs, so only their hash reaches Redis.
+fn rate_limit_key(endpoint: &str, subject: &str) -> String {
+    hashed_subject_key(RATE_LIMIT_KEY_PREFIX, endpoint, subject)
+}
+
+fn security_anomaly_key(scope: &str, subject: &str) -> String {
+    hashed_subject_key(SECURITY_ANOMALY_KEY_PREFIX, scope, subject)
+}
+
+fn hashed_subject_key(prefix: &str, scope: &str, subject: &str) -> String {
+    let digest = Sha256::digest(subject.as_bytes());
+    let subject_hash = digest
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>();
+    format!("{prefix}:{scope}:{subject_hash}")
+}
+
+fn reset_seconds(reset_ms: i64) -> u64 {
+    u64::try_from(reset_ms).unwrap_or(0).div_ceil(1000).max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{rate_limit_key, reset_seconds, security_anomaly_key};
+
+    #[test]
+    fn keys_hash_the_subject_and_scope_by_endpoint() {
+        let key = rate_limit_key("automation_create", "user:42");
+        assert!(key.starts_with("alfred:api:rate_limit:v1:automation_create:"));
+        assert!(!key.contains("user:42"));
+        assert_ne!(key, rate_limit_key("automation_update", "user:42"));
+    }
+
+    #[test]
+    fn security_anomaly_keys_do_not_share_the_rate_limit_namespace() {
+        let key = security_anomaly_key("auth_failure", "ip:203.0.113.7");
+        assert!(key.starts_with("alfred:api:security_anomaly:v1:auth_failure:"));
+        assert!(!key.contains("203.0.113.7"));
+        assert_ne!(key, security_anomaly_key("cooldown", "ip:203.0.113.7"));
+    }
+
+    #[test]
+    fn reset_rounds_up_to_whole_seconds() {
+        assert_eq!(reset_seconds(1), 1);
+        assert_eq!(reset_seconds(1000), 1);
+        assert_eq!(reset_seconds(1001), 2);
+        assert_eq!(reset_seconds(-5), 1);
+    }
+}
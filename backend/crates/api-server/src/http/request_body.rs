@@ -0,0 +1,163 @@
+use axum::Json;
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{FromRequest, Request};
+use axum::http::StatusCode;
+use axum::response::Response;
+use serde::de::DeserializeOwned;
+
+use super::errors::{
+    invalid_request_body_response, payload_too_large_response, unsupported_media_type_response,
+};
+
+/// JSON body extractor for request DTOs. Behaves like `axum::Json` but rejects with the API's
+/// error envelope: `payload_too_large` past the route's body limit and `invalid_request_body`
+/// for a missing JSON `Content-Type`, malformed JSON, or fields the DTO does not declare.
+pub(super) struct ApiJson<T>(pub(super) T);
+
+impl<T, S> FromRequest<S> for ApiJson<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        Json::<T>::from_request(req, state)
+            .await
+            .map(|Json(value)| Self(value))
+            .map_err(json_rejection_response)
+    }
+}
+
+fn json_rejection_response(rejection: JsonRejection) -> Response {
+    match rejection {
+        JsonRejection::MissingJsonContentType(_) => unsupported_media_type_response(),
+        JsonRejection::BytesRejection(rejection)
+            if rejection.status() == StatusCode::PAYLOAD_TOO_LARGE =>
+        {
+            payload_too_large_response()
+        }
+        JsonRejection::JsonDataError(rejection) => {
+            invalid_request_body_response(&deserialize_error_detail(&rejection.body_text()))
+        }
+        JsonRejection::JsonSyntaxError(_) => {
+            invalid_request_body_response("Request body is not valid JSON")
+        }
+        _ => invalid_request_body_response("Request body could not be read"),
+    }
+}
+
+/// Keeps serde's explanation (e.g. "unknown field `x`") and drops axum's generic prefix.
+fn deserialize_error_detail(body_text: &str) -> String {
+    body_text
+        .strip_prefix("Failed to deserialize the JSON body into the target type: ")
+        .unwrap_or(body_text)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::{Body, to_bytes};
+    use axum::extract::FromRequest;
+    use axum::http::{Request, StatusCode, header};
+    use axum::response::Response;
+    use serde::Deserialize;
+    use serde_json::Value;
+
+    use super::ApiJson;
+
+    #[derive(Debug, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Payload {
+        name: String,
+    }
+
+    async fn extract(request: Request<Body>) -> Result<Payload, Response> {
+        ApiJson::<Payload>::from_request(request, &())
+            .await
+            .map(|ApiJson(payload)| payload)
+    }
+
+    fn json_request(body: &'static str) -> Request<Body> {
+        Request::post("/")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body))
+            .expect("request should build")
+    }
+
+    async fn error_code(response: Response) -> (StatusCode, String) {
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body should read");
+        let json: Value = serde_json::from_slice(&body).expect("error body should be json");
+        (
+            status,
+            json["error"]["code"].as_str().unwrap_or("").to_string(),
+        )
+    }
+
+    #[tokio::test]
+    async fn accepts_declared_fields() {
+        let payload = extract(json_request(r#"{"name":"alfred"}"#))
+            .await
+            .expect("payload should parse");
+        assert_eq!(payload.name, "alfred");
+    }
+
+    #[tokio::test]
+    async fn rejects_unknown_fields_and_malformed_json() {
+        let response = extract(json_request(r#"{"name":"alfred","extra":1}"#))
+            .await
+            .expect_err("unknown field should be rejected");
+        assert_eq!(
+            error_code(response).await,
+            (StatusCode::BAD_REQUEST, "invalid_request_body".to_string())
+        );
+
+        let response = extract(json_request("{"))
+            .await
+            .expect_err("malformed json should be rejected");
+        assert_eq!(
+            error_code(response).await,
+            (StatusCode::BAD_REQUEST, "invalid_request_body".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_non_json_content_type() {
+        let request = Request::post("/")
+            .header(header::CONTENT_TYPE, "text/plain")
+            .body(Body::from(r#"{"name":"alfred"}"#))
+            .expect("request should build");
+        let response = extract(request)
+            .await
+            .expect_err("text body should be rejected");
+        assert_eq!(
+            error_code(response).await,
+            (
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                "invalid_request_body".to_string()
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_bodies_over_the_body_limit() {
+        // No `DefaultBodyLimit` layer here, so axum's 2 MiB default applies.
+        let request = Request::post("/")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(" ".repeat(2 * 1024 * 1024 + 1)))
+            .expect("request should build");
+        let response = extract(request)
+            .await
+            .expect_err("oversized body should be rejected");
+        assert_eq!(
+            error_code(response).await,
+            (
+                StatusCode::PAYLOAD_TOO_LARGE,
+                "payload_too_large".to_string()
+            )
+        );
+    }
+}
@@ -0,0 +1,247 @@
+use axum::body::{Body, to_bytes};
+use axum::extract::{Request, State};
+use axum::http::HeaderMap;
+use axum::middleware::Next;
+use axum::response::Response;
+use chrono::{DateTime, Utc};
+use shared::models::ApiErrorCode;
+use shared::request_signing::{
+    REQUEST_SIGNING_DEVICE_ID_HEADER, REQUEST_SIGNING_NONCE_HEADER,
+    REQUEST_SIGNING_SIGNATURE_HEADER, REQUEST_SIGNING_TIMESTAMP_HEADER, canonical_request,
+    is_valid_request_nonce, verify_request_signature,
+};
+use tracing::warn;
+
+use super::anomaly::{AnomalySignal, flag_anomaly};
+use super::errors::{error_response, payload_too_large_response, store_error_response};
+use super::versioning::ApiVersion;
+use super::{AppState, AuthUser};
+
+/// Device whose enrolled key signed the current request.
+#[derive(Debug, Clone)]
+pub(super) struct SignedDevice {
+    pub(super) device_id: String,
+}
+
+struct SignatureHeaders {
+    device_id: String,
+    timestamp: String,
+    nonce: String,
+    signature: String,
+}
+
+enum SignatureHeaderState {
+    Unsigned,
+    Incomplete,
+    Present(SignatureHeaders),
+}
+
+/// Runs after bearer authentication. A signed request is verified against the named device's
+/// key and its nonce is burned; an unsigned one passes unless signing is required and the user
+/// has enrolled a key on any device. Device registration and key enrollment additionally check
+/// [`SignedDevice`] themselves, whether or not signing is required.
+pub(super) async fn request_signing_middleware(
+    State(state): State<AppState>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let Some(user) = req.extensions().get::<AuthUser>().copied() else {
+        return next.run(req).await;
+    };
+
+    let headers = match signature_headers(req.headers()) {
+        SignatureHeaderState::Present(headers) => headers,
+        SignatureHeaderState::Incomplete => {
+            return invalid_signature_response("request signature headers must be sent together");
+        }
+        SignatureHeaderState::Unsigned => {
+            if !state.request_signing.required {
+                return next.run(req).await;
+            }
+            return match state
+                .store
+                .has_device_request_signing_keys(user.user_id)
+                .await
+            {
+                Ok(false) => next.run(req).await,
+                Ok(true) => error_response(
+                    ApiErrorCode::RequestSignatureRequired,
+                    "Requests must be signed by an enrolled device key",
+                ),
+                Err(err) => store_error_response(err),
+            };
+        }
+    };
+
+    let Ok(timestamp) = headers.timestamp.parse::<i64>() else {
+        return invalid_signature_response("request timestamp must be unix seconds");
+    };
+    let max_skew_seconds =
+        i64::try_from(state.request_signing.max_skew_seconds).unwrap_or(i64::MAX);
+    if Utc::now().timestamp().abs_diff(timestamp) > state.request_signing.max_skew_seconds {
+        return invalid_signature_response("request timestamp is outside the allowed clock skew");
+    }
+    if !is_valid_request_nonce(&headers.nonce) {
+        return invalid_signature_response(
+            "request nonce must be 16-128 characters of [A-Za-z0-9_-]",
+        );
+    }
+
+    let public_key = match state
+        .store
+        .get_device_request_signing_key(user.user_id, &headers.device_id)
+        .await
+    {
+        Ok(Some(public_key)) => public_key,
+        Ok(None) => {
+            return invalid_signature_response("device has no enrolled request signing key");
+        }
+        Err(err) => return store_error_response(err),
+    };
+
+    let max_body_bytes = state
+        .request_body_limits
+        .default_bytes
+        .max(state.request_body_limits.prompt_envelope_bytes);
+    let (mut parts, body) = req.into_parts();
+    let Ok(body) = to_bytes(body, max_body_bytes).await else {
+        return payload_too_large_response();
+    };
+
+    let path_and_query = parts
+        .uri
+        .path_and_query()
+        .map_or_else(|| parts.uri.path().to_string(), ToString::to_string);
+    let path_and_query = match parts.extensions.get::<ApiVersion>() {
+        Some(version) => version.client_route(&path_and_query),
+        None => path_and_query,
+    };
+    let canonical = canonical_request(
+        parts.method.as_str(),
+        &path_and_query,
+        &body,
+        timestamp,
+        &headers.nonce,
+    );
+    if !verify_request_signature(&public_key, &canonical, &headers.signature) {
+        warn!(
+            user_id = %user.user_id,
+            device_id = %headers.device_id,
+            "request signature rejected"
+        );
+        return invalid_signature_response("request signature does not verify");
+    }
+
+    let expires_at = DateTime::from_timestamp(timestamp.saturating_add(max_skew_seconds), 0)
+        .unwrap_or(DateTime::<Utc>::MAX_UTC);
+    match state
+        .store
+        .record_request_signature_nonce(
+            user.user_id,
+            &headers.device_id,
+            &headers.nonce,
+            expires_at,
+        )
+        .await
+    {
+        Ok(true) => {}
+        Ok(false) => {
+            warn!(
+                user_id = %user.user_id,
+                device_id = %headers.device_id,
+                "replayed request signature nonce"
+            );
+            return flag_anomaly(
+                invalid_signature_response("request nonce was already used"),
+                AnomalySignal::ReplayDetected,
+            );
+        }
+        Err(err) => return store_error_response(err),
+    }
+
+    parts.extensions.insert(SignedDevice {
+        device_id: headers.device_id,
+    });
+    next.run(Request::from_parts(parts, Body::from(body))).await
+}
+
+fn signature_headers(headers: &HeaderMap) -> SignatureHeaderState {
+    let value = |name: &str| {
+        headers
+            .get(name)
+            .map(|value| value.to_str().map(|value| value.trim().to_string()))
+    };
+    let values = [
+        value(REQUEST_SIGNING_DEVICE_ID_HEADER),
+        value(REQUEST_SIGNING_TIMESTAMP_HEADER),
+        value(REQUEST_SIGNING_NONCE_HEADER),
+        value(REQUEST_SIGNING_SIGNATURE_HEADER),
+    ];
+    if values.iter().all(Option::is_none) {
+        return SignatureHeaderState::Unsigned;
+    }
+
+    match values {
+        [
+            Some(Ok(device_id)),
+            Some(Ok(timestamp)),
+            Some(Ok(nonce)),
+            Some(Ok(signature)),
+        ] if !device_id.is_empty() => SignatureHeaderState::Present(SignatureHeaders {
+            device_id,
+            timestamp,
+            nonce,
+            signature,
+        }),
+        _ => SignatureHeaderState::Incomplete,
+    }
+}
+
+fn invalid_signature_response(message: &str) -> Response {
+    error_response(ApiErrorCode::InvalidRequestSignature, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{HeaderMap, HeaderValue};
+
+    use super::{SignatureHeaderState, signature_headers};
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, HeaderValue::from_static(value));
+        }
+        headers
+    }
+
+    #[test]
+    fn signature_headers_must_be_sent_together() {
+        assert!(matches!(
+            signature_headers(&HeaderMap::new()),
+            SignatureHeaderState::Unsigned
+        ));
+        assert!(matches!(
+            signature_headers(&headers(&[("x-alfred-device-id", "device-1")])),
+            SignatureHeaderState::Incomplete
+        ));
+        assert!(matches!(
+            signature_headers(&headers(&[
+                ("x-alfred-device-id", " "),
+                ("x-alfred-request-timestamp", "1700000000"),
+                ("x-alfred-request-nonce", "nonce-0123456789"),
+                ("x-alfred-request-signature", "c2ln"),
+            ])),
+            SignatureHeaderState::Incomplete
+        ));
+        assert!(matches!(
+            signature_headers(&headers(&[
+                ("x-alfred-device-id", "device-1"),
+                ("x-alfred-request-timestamp", "1700000000"),
+                ("x-alfred-request-nonce", "nonce-0123456789"),
+                ("x-alfred-request-signature", "c2ln"),
+            ])),
+            SignatureHeaderState::Present(_)
+        ));
+    }
+}
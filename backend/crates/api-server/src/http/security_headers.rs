@@ -0,0 +1,112 @@
+use axum::extract::{Request, State};
+use axum::http::{HeaderMap, HeaderValue, header};
+use axum::middleware::Next;
+use axum::response::Response;
+use shared::config::SecurityHeadersConfig;
+
+/// Routes whose responses carry no user data or credentials, so shared caches may keep them.
+const CACHEABLE_ROUTES: [&str; 4] = ["/healthz", "/readyz", "/openapi.json", "/v1/public/status"];
+
+/// Adds HSTS, `X-Content-Type-Options`, and `Referrer-Policy` to every response, and
+/// `Cache-Control: no-store` to every response outside [`CACHEABLE_ROUTES`] that did not
+/// choose its own caching. The OAuth bridge redirect carries an authorization code in its
+/// `Location`, so it must never be stored or leaked through a referrer.
+pub(super) async fn security_headers_middleware(
+    State(config): State<SecurityHeadersConfig>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let cacheable = CACHEABLE_ROUTES.contains(&req.uri().path());
+    let mut response = next.run(req).await;
+    apply_security_headers(response.headers_mut(), config, cacheable);
+    response
+}
+
+fn apply_security_headers(headers: &mut HeaderMap, config: SecurityHeadersConfig, cacheable: bool) {
+    if let Some(hsts) = strict_transport_security(config) {
+        headers.insert(header::STRICT_TRANSPORT_SECURITY, hsts);
+    }
+    headers.insert(
+        header::X_CONTENT_TYPE_OPTIONS,
+        HeaderValue::from_static("nosniff"),
+    );
+    headers.insert(
+        header::REFERRER_POLICY,
+        HeaderValue::from_static(config.referrer_policy),
+    );
+    if !cacheable && !headers.contains_key(header::CACHE_CONTROL) {
+        headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+    }
+}
+
+fn strict_transport_security(config: SecurityHeadersConfig) -> Option<HeaderValue> {
+    if config.hsts_max_age_seconds == 0 {
+        return None;
+    }
+    let value = if config.hsts_include_subdomains {
+        format!("max-age={}; includeSubDomains", config.hsts_max_age_seconds)
+    } else {
+        format!("max-age={}", config.hsts_max_age_seconds)
+    };
+    HeaderValue::from_str(&value).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{HeaderMap, HeaderValue, header};
+    use shared::config::SecurityHeadersConfig;
+
+    use super::apply_security_headers;
+
+    #[test]
+    fn sensitive_responses_get_every_header() {
+        let mut headers = HeaderMap::new();
+        apply_security_headers(
+            &mut headers,
+            SecurityHeadersConfig {
+                hsts_include_subdomains: true,
+                ..SecurityHeadersConfig::default()
+            },
+            false,
+        );
+
+        assert_eq!(
+            headers[header::STRICT_TRANSPORT_SECURITY],
+            "max-age=31536000; includeSubDomains"
+        );
+        assert_eq!(headers[header::X_CONTENT_TYPE_OPTIONS], "nosniff");
+        assert_eq!(headers[header::REFERRER_POLICY], "no-referrer");
+        assert_eq!(headers[header::CACHE_CONTROL], "no-store");
+    }
+
+    #[test]
+    fn cacheable_routes_and_handler_cache_control_are_left_alone() {
+        let mut public = HeaderMap::new();
+        apply_security_headers(&mut public, SecurityHeadersConfig::default(), true);
+        assert!(!public.contains_key(header::CACHE_CONTROL));
+        assert_eq!(
+            public[header::STRICT_TRANSPORT_SECURITY],
+            "max-age=31536000"
+        );
+
+        let mut chosen = HeaderMap::new();
+        chosen.insert(header::CACHE_CONTROL, HeaderValue::from_static("private"));
+        apply_security_headers(&mut chosen, SecurityHeadersConfig::default(), false);
+        assert_eq!(chosen[header::CACHE_CONTROL], "private");
+    }
+
+    #[test]
+    fn zero_max_age_disables_hsts() {
+        let mut headers = HeaderMap::new();
+        apply_security_headers(
+            &mut headers,
+            SecurityHeadersConfig {
+                hsts_max_age_seconds: 0,
+                ..SecurityHeadersConfig::default()
+            },
+            false,
+        );
+        assert!(!headers.contains_key(header::STRICT_TRANSPORT_SECURITY));
+        assert_eq!(headers[header::X_CONTENT_TYPE_OPTIONS], "nosniff");
+    }
+}
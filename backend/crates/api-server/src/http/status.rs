@@ -0,0 +1,394 @@
+use axum::Json;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use chrono::{DateTime, Duration, NaiveTime, Utc};
+use shared::models::{
+    AvailabilityComponent, ComponentStatus, ComponentStatusEntry, DailyAvailability,
+    PublicComponentStatus, PublicSloStatus, PublicStatusResponse, StatusComponent,
+    SystemStatusResponse,
+};
+use shared::repos::{AssistantRequestWindowStats, ComponentAvailabilityBucket, SloStatusRecord};
+use tracing::warn;
+
+use super::AppState;
+use super::errors::store_error_response;
+use super::openapi::ApiOperation;
+
+const STATUS_WINDOW_MINUTES: i64 = 15;
+/// Below this many queries in the window the ratios are too noisy to flag anything.
+const MIN_SAMPLE_SIZE: i64 = 5;
+const DEGRADED_RATIO: f64 = 0.25;
+const UNAVAILABLE_RATIO: f64 = 0.75;
+
+const PUBLIC_HISTORY_DAYS: i64 = 7;
+const PUBLIC_CURRENT_WINDOW_HOURS: i64 = 1;
+const OPERATIONAL_AVAILABILITY: f64 = 0.99;
+const DEGRADED_AVAILABILITY: f64 = 0.90;
+
+pub(super) const GET_STATUS: ApiOperation = ApiOperation::get(
+    "/v1/status",
+    "getSystemStatus",
+    "Status",
+    "Summarize component degradation for the in-app banner",
+)
+.response::<SystemStatusResponse>();
+
+/// Summarizes component degradation for the in-app banner. Assistant and enclave health are
+/// derived from the content-free assistant request index, so no probe traffic is generated.
+pub(super) async fn get_status(State(state): State<AppState>) -> Response {
+    let now = Utc::now();
+    let mut components = Vec::new();
+
+    match state.store.ping().await {
+        Ok(_) => components.push(operational(StatusComponent::Database)),
+        Err(err) => {
+            warn!("status database check failed: {err}");
+            components.push(ComponentStatusEntry {
+                component: StatusComponent::Database,
+                status: ComponentStatus::Unavailable,
+                reason: Some("db_unavailable".to_string()),
+            });
+        }
+    }
+
+    if components[0].status == ComponentStatus::Operational {
+        let since = now - Duration::minutes(STATUS_WINDOW_MINUTES);
+        match state.store.assistant_request_window_stats(since).await {
+            Ok(stats) => components.extend(assistant_components(stats)),
+            Err(err) => warn!("status assistant window lookup failed: {err}"),
+        }
+    }
+
+    let status = components
+        .iter()
+        .map(|component| component.status)
+        .max()
+        .unwrap_or(ComponentStatus::Operational);
+
+    (
+        StatusCode::OK,
+        Json(SystemStatusResponse {
+            status,
+            components,
+            window_minutes: STATUS_WINDOW_MINUTES,
+            checked_at: now,
+        }),
+    )
+        .into_response()
+}
+
+pub(super) const GET_PUBLIC_STATUS: ApiOperation = ApiOperation::get(
+    "/v1/public/status",
+    "getPublicStatus",
+    "Status",
+    "Public rolling availability per component",
+)
+.public()
+.response::<PublicStatusResponse>();
+
+/// Unauthenticated rolling availability per component, for the status page and in-app
+/// incident messaging. Built from aggregate hourly counters only.
+pub(super) async fn get_public_status(State(state): State<AppState>) -> Response {
+    let now = Utc::now();
+    let history_start = (now.date_naive() - Duration::days(PUBLIC_HISTORY_DAYS - 1))
+        .and_time(NaiveTime::MIN)
+        .and_utc();
+    let buckets = match state.store.list_component_availability(history_start).await {
+        Ok(buckets) => buckets,
+        Err(err) => return store_error_response(err),
+    };
+
+    let push_delivery_slo = match state.store.get_push_delivery_slo_status().await {
+        Ok(slo) => slo,
+        Err(err) => {
+            warn!("public status push delivery SLO lookup failed: {err}");
+            None
+        }
+    };
+
+    let mut components = AvailabilityComponent::ALL
+        .into_iter()
+        .map(|component| public_component_status(component, &buckets, now))
+        .collect::<Vec<_>>();
+    if push_delivery_slo.as_ref().is_some_and(|slo| slo.breached) {
+        mark_push_delivery_slo_breached(&mut components);
+    }
+    let status = components
+        .iter()
+        .map(|component| component.status)
+        .max()
+        .unwrap_or(ComponentStatus::Operational);
+
+    (
+        StatusCode::OK,
+        Json(PublicStatusResponse {
+            status,
+            components,
+            push_delivery_slo: push_delivery_slo.map(public_slo_status),
+            generated_at: now,
+        }),
+    )
+        .into_response()
+}
+
+fn public_component_status(
+    component: AvailabilityComponent,
+    buckets: &[ComponentAvailabilityBucket],
+    now: DateTime<Utc>,
+) -> PublicComponentStatus {
+    let buckets = buckets
+        .iter()
+        .filter(|bucket| bucket.component == component)
+        .collect::<Vec<_>>();
+    let totals_since = |since: DateTime<Utc>| {
+        buckets
+            .iter()
+            .filter(|bucket| bucket.bucket_start >= since)
+            .fold((0_i64, 0_i64), |(attempts, successes), bucket| {
+                (attempts + bucket.attempts, successes + bucket.successes)
+            })
+    };
+
+    let (current_attempts, current_successes) =
+        totals_since(now - Duration::hours(PUBLIC_CURRENT_WINDOW_HOURS));
+    let status = match availability(current_attempts, current_successes) {
+        Some(_) if current_attempts < MIN_SAMPLE_SIZE => ComponentStatus::Operational,
+        Some(ratio) if ratio >= OPERATIONAL_AVAILABILITY => ComponentStatus::Operational,
+        Some(ratio) if ratio >= DEGRADED_AVAILABILITY => ComponentStatus::Degraded,
+        Some(_) => ComponentStatus::Unavailable,
+        None => ComponentStatus::Operational,
+    };
+    let (day_attempts, day_successes) = totals_since(now - Duration::hours(24));
+
+    let today = now.date_naive();
+    let history = (0..PUBLIC_HISTORY_DAYS)
+        .rev()
+        .map(|days_ago| {
+            let date = today - Duration::days(days_ago);
+            let (attempts, successes) = buckets
+                .iter()
+                .filter(|bucket| bucket.bucket_start.date_naive() == date)
+                .fold((0_i64, 0_i64), |(attempts, successes), bucket| {
+                    (attempts + bucket.attempts, successes + bucket.successes)
+                });
+            DailyAvailability {
+                date,
+                attempts,
+                availability: availability(attempts, successes),
+            }
+        })
+        .collect();
+
+    PublicComponentStatus {
+        component,
+        status,
+        message: (status != ComponentStatus::Operational)
+            .then(|| incident_message(component).to_string()),
+        availability_24h: availability(day_attempts, day_successes),
+        history,
+    }
+}
+
+/// Late notifications are an incident even when every push eventually succeeds.
+fn mark_push_delivery_slo_breached(components: &mut [PublicComponentStatus]) {
+    for component in components
+        .iter_mut()
+        .filter(|component| component.component == AvailabilityComponent::PushDelivery)
+    {
+        if component.status < ComponentStatus::Degraded {
+            component.status = ComponentStatus::Degraded;
+            component.message = Some(incident_message(component.component).to_string());
+        }
+    }
+}
+
+fn public_slo_status(slo: SloStatusRecord) -> PublicSloStatus {
+    PublicSloStatus {
+        objective_percent: slo.objective_percent,
+        target_seconds: slo.target_seconds,
+        window_minutes: slo.window_minutes,
+        compliance: availability(slo.total_events, slo.good_events),
+        breached: slo.breached,
+        breached_since: slo.breached_since,
+        evaluated_at: slo.evaluated_at,
+    }
+}
+
+fn availability(attempts: i64, successes: i64) -> Option<f64> {
+    (attempts > 0).then(|| successes.min(attempts) as f64 / attempts as f64)
+}
+
+fn incident_message(component: AvailabilityComponent) -> &'static str {
+    match component {
+        AvailabilityComponent::PushDelivery => "Notifications may be delayed.",
+        AvailabilityComponent::Assistant => {
+            "The assistant may be slower or give simpler answers than usual."
+        }
+        AvailabilityComponent::Connectors => "Syncing with connected accounts may be delayed.",
+        AvailabilityComponent::Automations => "Scheduled automations may run late.",
+    }
+}
+
+fn assistant_components(stats: AssistantRequestWindowStats) -> [ComponentStatusEntry; 2] {
+    if stats.total < MIN_SAMPLE_SIZE {
+        return [
+            operational(StatusComponent::Enclave),
+            operational(StatusComponent::Assistant),
+        ];
+    }
+
+    let ratio = |count: i64| count as f64 / stats.total as f64;
+    let enclave = match ratio(stats.enclave_failed) {
+        r if r >= UNAVAILABLE_RATIO => degraded(
+            StatusComponent::Enclave,
+            ComponentStatus::Unavailable,
+            "enclave_errors_elevated",
+        ),
+        r if r >= DEGRADED_RATIO => degraded(
+            StatusComponent::Enclave,
+            ComponentStatus::Degraded,
+            "enclave_errors_elevated",
+        ),
+        _ => operational(StatusComponent::Enclave),
+    };
+    let assistant = if ratio(stats.failed) >= DEGRADED_RATIO {
+        degraded(
+            StatusComponent::Assistant,
+            ComponentStatus::Degraded,
+            "assistant_errors_elevated",
+        )
+    } else if ratio(stats.fallback) >= DEGRADED_RATIO {
+        degraded(
+            StatusComponent::Assistant,
+            ComponentStatus::Degraded,
+            "llm_fallback_elevated",
+        )
+    } else {
+        operational(StatusComponent::Assistant)
+    };
+
+    [enclave, assistant]
+}
+
+fn operational(component: StatusComponent) -> ComponentStatusEntry {
+    ComponentStatusEntry {
+        component,
+        status: ComponentStatus::Operational,
+        reason: None,
+    }
+}
+
+fn degraded(
+    component: StatusComponent,
+    status: ComponentStatus,
+    reason: &str,
+) -> ComponentStatusEntry {
+    ComponentStatusEntry {
+        component,
+        status,
+        reason: Some(reason.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(
+        total: i64,
+        failed: i64,
+        enclave_failed: i64,
+        fallback: i64,
+    ) -> AssistantRequestWindowStats {
+        AssistantRequestWindowStats {
+            total,
+            failed,
+            enclave_failed,
+            fallback,
+        }
+    }
+
+    #[test]
+    fn public_status_degrades_on_recent_failures_and_fills_history() {
+        let now = Utc::now();
+        let bucket = |hours_ago: i64, attempts: i64, successes: i64| ComponentAvailabilityBucket {
+            component: AvailabilityComponent::PushDelivery,
+            bucket_start: now - Duration::hours(hours_ago),
+            attempts,
+            successes,
+        };
+        let buckets = vec![bucket(30, 100, 100), bucket(0, 20, 19)];
+
+        let push = public_component_status(AvailabilityComponent::PushDelivery, &buckets, now);
+        assert_eq!(push.status, ComponentStatus::Degraded);
+        assert_eq!(
+            push.message.as_deref(),
+            Some("Notifications may be delayed.")
+        );
+        assert_eq!(push.availability_24h, Some(0.95));
+        assert_eq!(push.history.len(), PUBLIC_HISTORY_DAYS as usize);
+        assert_eq!(
+            push.history.last().map(|day| day.date),
+            Some(now.date_naive())
+        );
+
+        let assistant = public_component_status(AvailabilityComponent::Assistant, &buckets, now);
+        assert_eq!(assistant.status, ComponentStatus::Operational);
+        assert!(assistant.message.is_none());
+        assert!(
+            assistant
+                .history
+                .iter()
+                .all(|day| day.availability.is_none())
+        );
+    }
+
+    #[test]
+    fn push_delivery_slo_breach_degrades_only_push_delivery() {
+        let now = Utc::now();
+        let mut components = AvailabilityComponent::ALL
+            .into_iter()
+            .map(|component| public_component_status(component, &[], now))
+            .collect::<Vec<_>>();
+
+        mark_push_delivery_slo_breached(&mut components);
+
+        for component in &components {
+            if component.component == AvailabilityComponent::PushDelivery {
+                assert_eq!(component.status, ComponentStatus::Degraded);
+                assert_eq!(
+                    component.message.as_deref(),
+                    Some("Notifications may be delayed.")
+                );
+            } else {
+                assert_eq!(component.status, ComponentStatus::Operational);
+            }
+        }
+    }
+
+    #[test]
+    fn small_samples_stay_operational() {
+        let [enclave, assistant] = assistant_components(stats(4, 4, 4, 0));
+        assert_eq!(enclave.status, ComponentStatus::Operational);
+        assert_eq!(assistant.status, ComponentStatus::Operational);
+    }
+
+    #[test]
+    fn elevated_fallbacks_degrade_assistant_only() {
+        let [enclave, assistant] = assistant_components(stats(10, 0, 0, 3));
+        assert_eq!(enclave.status, ComponentStatus::Operational);
+        assert_eq!(assistant.status, ComponentStatus::Degraded);
+        assert_eq!(assistant.reason.as_deref(), Some("llm_fallback_elevated"));
+    }
+
+    #[test]
+    fn enclave_outage_marks_enclave_unavailable() {
+        let [enclave, assistant] = assistant_components(stats(8, 8, 8, 0));
+        assert_eq!(enclave.status, ComponentStatus::Unavailable);
+        assert_eq!(
+            assistant.reason.as_deref(),
+            Some("assistant_errors_elevated")
+        );
+    }
+}
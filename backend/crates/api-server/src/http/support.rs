@@ -0,0 +1,217 @@
+use axum::Json;
+use axum::extract::{Extension, Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use base64::Engine as _;
+use chrono::{Duration, Utc};
+use shared::models::{
+    ApiErrorCode, AuditMetadata, OkResponse, UploadSupportDiagnosticsRequest,
+    UploadSupportDiagnosticsResponse,
+};
+use shared::repos::AuditResult;
+use uuid::Uuid;
+
+use super::errors::{error_response, store_error_response};
+use super::openapi::ApiOperation;
+use super::request_body::ApiJson;
+use super::{AppState, AuthUser};
+
+/// Upper bound on the decoded ciphertext; the base64 body stays under
+/// `MAX_DIAGNOSTICS_BODY_BYTES`.
+const MAX_DIAGNOSTICS_BYTES: usize = 1024 * 1024;
+/// Route body cap for diagnostics uploads, the only route larger than the configured limits.
+pub(super) const MAX_DIAGNOSTICS_BODY_BYTES: usize = 2 * 1024 * 1024;
+const DIAGNOSTICS_TTL_DAYS: i64 = 14;
+const MAX_TICKET_REFERENCE_LEN: usize = 64;
+const MAX_KEY_FIELD_LEN: usize = 128;
+
+pub(super) const UPLOAD_DIAGNOSTICS: ApiOperation = ApiOperation::post(
+    "/v1/support/diagnostics",
+    "uploadSupportDiagnostics",
+    "Support",
+    "Upload a client-encrypted diagnostic bundle",
+)
+.request::<UploadSupportDiagnosticsRequest>()
+.response::<UploadSupportDiagnosticsResponse>();
+
+pub(super) async fn upload_diagnostics(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    ApiJson(req): ApiJson<UploadSupportDiagnosticsRequest>,
+) -> Response {
+    let ticket_reference = match req.ticket_reference.as_deref().map(str::trim) {
+        Some(reference) if !is_valid_token(reference, MAX_TICKET_REFERENCE_LEN) => {
+            return error_response(
+                ApiErrorCode::InvalidTicketReference,
+                "ticket_reference must be 1-64 characters of [A-Za-z0-9._-]",
+            );
+        }
+        Some(reference) => reference.to_string(),
+        None => generated_ticket_reference(),
+    };
+
+    let algorithm = req.algorithm.trim();
+    let key_id = req.key_id.trim();
+    if !is_valid_token(algorithm, MAX_KEY_FIELD_LEN) || !is_valid_token(key_id, MAX_KEY_FIELD_LEN) {
+        return error_response(
+            ApiErrorCode::InvalidEncryptionMetadata,
+            "algorithm and key_id must be 1-128 characters of [A-Za-z0-9._-]",
+        );
+    }
+
+    let ciphertext = match base64::engine::general_purpose::STANDARD.decode(req.ciphertext.trim()) {
+        Ok(bytes) if !bytes.is_empty() => bytes,
+        _ => {
+            return error_response(
+                ApiErrorCode::InvalidCiphertext,
+                "ciphertext must be non-empty base64",
+            );
+        }
+    };
+    if ciphertext.len() > MAX_DIAGNOSTICS_BYTES {
+        return error_response(
+            ApiErrorCode::DiagnosticsTooLarge,
+            "Diagnostic bundle must be at most 1 MiB",
+        );
+    }
+
+    let expires_at = Utc::now() + Duration::days(DIAGNOSTICS_TTL_DAYS);
+    let record = match state
+        .store
+        .create_support_diagnostic(
+            user.user_id,
+            &ticket_reference,
+            algorithm,
+            key_id,
+            &ciphertext,
+            expires_at,
+        )
+        .await
+    {
+        Ok(record) => record,
+        Err(err) => return store_error_response(err),
+    };
+
+    let mut metadata = AuditMetadata::new();
+    metadata.insert(
+        "diagnostic_id".to_string(),
+        record.diagnostic_id.to_string().into(),
+    );
+    metadata.insert(
+        "ticket_reference".to_string(),
+        record.ticket_reference.clone().into(),
+    );
+    metadata.insert("size_bytes".to_string(), record.size_bytes.into());
+
+    if let Err(err) = state
+        .store
+        .add_audit_event(
+            user.user_id,
+            "SUPPORT_DIAGNOSTICS_UPLOADED",
+            None,
+            AuditResult::Success,
+            &metadata,
+        )
+        .await
+    {
+        return store_error_response(err);
+    }
+
+    (
+        StatusCode::OK,
+        Json(UploadSupportDiagnosticsResponse {
+            diagnostic_id: record.diagnostic_id.to_string(),
+            ticket_reference: record.ticket_reference,
+            size_bytes: record.size_bytes,
+            created_at: record.created_at,
+            expires_at: record.expires_at,
+        }),
+    )
+        .into_response()
+}
+
+pub(super) const DELETE_DIAGNOSTICS: ApiOperation = ApiOperation::delete(
+    "/v1/support/diagnostics/{diagnostic_id}",
+    "deleteSupportDiagnostics",
+    "Support",
+    "Delete an uploaded diagnostic bundle",
+);
+
+pub(super) async fn delete_diagnostics(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(diagnostic_id): Path<String>,
+) -> Response {
+    let Ok(diagnostic_id) = Uuid::parse_str(&diagnostic_id) else {
+        return diagnostics_not_found_response();
+    };
+
+    match state
+        .store
+        .delete_support_diagnostic(user.user_id, diagnostic_id)
+        .await
+    {
+        Ok(true) => {}
+        Ok(false) => return diagnostics_not_found_response(),
+        Err(err) => return store_error_response(err),
+    }
+
+    let mut metadata = AuditMetadata::new();
+    metadata.insert(
+        "diagnostic_id".to_string(),
+        diagnostic_id.to_string().into(),
+    );
+
+    if let Err(err) = state
+        .store
+        .add_audit_event(
+            user.user_id,
+            "SUPPORT_DIAGNOSTICS_DELETED",
+            None,
+            AuditResult::Success,
+            &metadata,
+        )
+        .await
+    {
+        return store_error_response(err);
+    }
+
+    (StatusCode::OK, Json(OkResponse { ok: true })).into_response()
+}
+
+fn generated_ticket_reference() -> String {
+    let id = Uuid::new_v4().simple().to_string();
+    format!("DIAG-{}", id[..10].to_ascii_uppercase())
+}
+
+fn is_valid_token(value: &str, max_len: usize) -> bool {
+    !value.is_empty()
+        && value.len() <= max_len
+        && value
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.'))
+}
+
+fn diagnostics_not_found_response() -> Response {
+    error_response(ApiErrorCode::NotFound, "Diagnostic bundle not found")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_ticket_references_are_valid_tokens() {
+        let reference = generated_ticket_reference();
+        assert!(reference.starts_with("DIAG-"));
+        assert!(is_valid_token(&reference, MAX_TICKET_REFERENCE_LEN));
+    }
+
+    #[test]
+    fn tokens_reject_separators_and_overlong_values() {
+        assert!(is_valid_token("SUP-1234", MAX_TICKET_REFERENCE_LEN));
+        assert!(!is_valid_token("", MAX_TICKET_REFERENCE_LEN));
+        assert!(!is_valid_token("ticket 12", MAX_TICKET_REFERENCE_LEN));
+        assert!(!is_valid_token(&"a".repeat(65), MAX_TICKET_REFERENCE_LEN));
+    }
+}
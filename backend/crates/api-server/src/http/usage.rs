@@ -0,0 +1,45 @@
+use axum::Json;
+use axum::extract::{Extension, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use chrono::Utc;
+use shared::llm::PlanLlmSpendBudget;
+use shared::models::LlmUsageResponse;
+
+use super::errors::store_error_response;
+use super::openapi::ApiOperation;
+use super::{AppState, AuthUser};
+
+pub(super) const GET_LLM_USAGE: ApiOperation = ApiOperation::get(
+    "/v1/usage/llm",
+    "getLlmUsage",
+    "Usage",
+    "Get the signed-in user's AI spend against their monthly plan budget",
+)
+.response::<LlmUsageResponse>();
+
+pub(super) async fn get_llm_usage(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+) -> Response {
+    let status = match PlanLlmSpendBudget::new(state.store.clone())
+        .status_for_user(user.user_id, Utc::now())
+        .await
+    {
+        Ok(status) => status,
+        Err(err) => return store_error_response(err),
+    };
+
+    (
+        StatusCode::OK,
+        Json(LlmUsageResponse {
+            plan: status.plan,
+            month_start: status.month_start,
+            limit_usd_micros: status.limit_micros,
+            spent_usd_micros: status.spent_micros,
+            remaining_usd_micros: status.remaining_micros(),
+            resets_at: status.resets_at,
+        }),
+    )
+        .into_response()
+}
@@ -0,0 +1,388 @@
+use axum::Router;
+use axum::body::{Body, Bytes, to_bytes};
+use axum::extract::{Request, State};
+use axum::http::uri::PathAndQuery;
+use axum::http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode, Uri, header};
+use axum::middleware::{self, Next};
+use axum::response::Response;
+use serde_json::Value;
+use shared::config::ApiDeprecationConfig;
+use tracing::error;
+
+use super::errors::{
+    internal_error_response, invalid_request_body_response, payload_too_large_response,
+};
+
+const DEPRECATION_HEADER: &str = "deprecation";
+const SUNSET_HEADER: &str = "sunset";
+
+/// Major versions of the `/vN` API. Every version is served by the `/v1` handlers: a newer
+/// version's request has its prefix rewritten to `/v1` and passes through that version's
+/// adapters on the way in and out, so a version only describes how its contract differs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum ApiVersion {
+    V1,
+    V2,
+}
+
+impl ApiVersion {
+    const ALL: [Self; 2] = [Self::V1, Self::V2];
+
+    fn prefix(self) -> &'static str {
+        match self {
+            Self::V1 => "/v1",
+            Self::V2 => "/v2",
+        }
+    }
+
+    fn from_path(path: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|version| {
+            path.strip_prefix(version.prefix())
+                .is_some_and(|rest| rest.starts_with('/'))
+        })
+    }
+
+    fn successor(self) -> Option<Self> {
+        match self {
+            Self::V1 => Some(Self::V2),
+            Self::V2 => None,
+        }
+    }
+
+    /// Contract changes this version makes on top of `/v1`, at most one per route.
+    fn adapters(self) -> &'static [&'static dyn VersionAdapter] {
+        match self {
+            Self::V1 => &[],
+            // No breaking change has shipped yet, so `/v2` serves the `/v1` contract as is.
+            Self::V2 => &[],
+        }
+    }
+
+    /// The `/v1` route template a request was served by, as the client addressed it.
+    pub(super) fn client_route(self, route: &str) -> String {
+        match route.strip_prefix(Self::V1.prefix()) {
+            Some(rest) if self != Self::V1 => format!("{}{rest}", self.prefix()),
+            _ => route.to_string(),
+        }
+    }
+
+    fn with_prefix_of(self, path_and_query: &str, target: Self) -> Option<PathAndQuery> {
+        let rest = path_and_query.strip_prefix(self.prefix())?;
+        format!("{}{rest}", target.prefix()).parse().ok()
+    }
+}
+
+/// Translates one route between a newer version's contract and the `/v1` handler serving it.
+/// Bodies are adapted as JSON documents; request bodies over the prompt-envelope cap are
+/// rejected before they reach an adapter.
+pub(super) trait VersionAdapter: Send + Sync {
+    /// `path` has already been rewritten to its `/v1` form.
+    fn applies_to(&self, method: &Method, path: &str) -> bool;
+
+    /// Turns a request body in this version's shape into the `/v1` shape. An `Err` message is
+    /// returned to the client as `invalid_request_body`.
+    fn adapt_request(&self, body: Value) -> Result<Value, String> {
+        Ok(body)
+    }
+
+    /// Turns a `/v1` response body, errors included, into this version's shape.
+    fn adapt_response(&self, _status: StatusCode, body: Value) -> Value {
+        body
+    }
+}
+
+#[derive(Clone)]
+struct VersioningState {
+    v1_deprecation: Option<ApiDeprecationConfig>,
+    max_adapted_body_bytes: usize,
+}
+
+impl VersioningState {
+    fn deprecation(&self, version: ApiVersion) -> Option<ApiDeprecationConfig> {
+        match version {
+            ApiVersion::V1 => self.v1_deprecation,
+            ApiVersion::V2 => None,
+        }
+    }
+}
+
+/// Serves `router`'s `/v1` routes under every [`ApiVersion`]. The prefix is rewritten before
+/// `router` matches the request, so route templates, rate-limit route classes, and handlers are
+/// the `/v1` ones whichever version the client called.
+pub(super) fn mount_versions(
+    router: Router,
+    v1_deprecation: Option<ApiDeprecationConfig>,
+    max_adapted_body_bytes: usize,
+) -> Router {
+    Router::new()
+        .fallback_service(router)
+        .layer(middleware::from_fn_with_state(
+            VersioningState {
+                v1_deprecation,
+                max_adapted_body_bytes,
+            },
+            versioned_request_middleware,
+        ))
+}
+
+async fn versioned_request_middleware(
+    State(state): State<VersioningState>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    let Some(version) = ApiVersion::from_path(req.uri().path()) else {
+        return next.run(req).await;
+    };
+    let successor_path = version
+        .successor()
+        .and_then(|successor| version.with_prefix_of(req.uri().path(), successor));
+
+    if version != ApiVersion::V1
+        && let Some(path_and_query) = req.uri().path_and_query().and_then(|path_and_query| {
+            version.with_prefix_of(path_and_query.as_str(), ApiVersion::V1)
+        })
+    {
+        let mut parts = req.uri().clone().into_parts();
+        parts.path_and_query = Some(path_and_query);
+        if let Ok(uri) = Uri::from_parts(parts) {
+            *req.uri_mut() = uri;
+        }
+    }
+    req.extensions_mut().insert(version);
+
+    let adapter = version
+        .adapters()
+        .iter()
+        .copied()
+        .find(|adapter| adapter.applies_to(req.method(), req.uri().path()));
+    if let Some(adapter) = adapter {
+        req = match adapt_request(adapter, req, state.max_adapted_body_bytes).await {
+            Ok(req) => req,
+            Err(response) => return response,
+        };
+    }
+
+    let mut response = next.run(req).await;
+    if let Some(adapter) = adapter {
+        response = adapt_response(adapter, response).await;
+    }
+    if let Some(deprecation) = state.deprecation(version) {
+        insert_deprecation_headers(
+            response.headers_mut(),
+            deprecation,
+            successor_path.as_ref().map(PathAndQuery::as_str),
+        );
+    }
+    response
+}
+
+async fn adapt_request(
+    adapter: &dyn VersionAdapter,
+    req: Request,
+    max_body_bytes: usize,
+) -> Result<Request, Response> {
+    let (mut parts, body) = req.into_parts();
+    let bytes = to_bytes(body, max_body_bytes)
+        .await
+        .map_err(|_| payload_too_large_response())?;
+    // Leave empty and malformed bodies for the handler's extractor to reject as usual.
+    let Ok(body) = serde_json::from_slice::<Value>(&bytes) else {
+        return Ok(Request::from_parts(parts, Body::from(bytes)));
+    };
+    let body = adapter
+        .adapt_request(body)
+        .map_err(|message| invalid_request_body_response(&message))?;
+
+    let bytes = Bytes::from(body.to_string());
+    parts
+        .headers
+        .insert(header::CONTENT_LENGTH, HeaderValue::from(bytes.len()));
+    Ok(Request::from_parts(parts, Body::from(bytes)))
+}
+
+async fn adapt_response(adapter: &dyn VersionAdapter, response: Response) -> Response {
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"));
+    if !is_json {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            error!("versioned response body could not be read: {err}");
+            return internal_error_response();
+        }
+    };
+    let Ok(body) = serde_json::from_slice::<Value>(&bytes) else {
+        return Response::from_parts(parts, Body::from(bytes));
+    };
+
+    let bytes = Bytes::from(adapter.adapt_response(parts.status, body).to_string());
+    parts
+        .headers
+        .insert(header::CONTENT_LENGTH, HeaderValue::from(bytes.len()));
+    Response::from_parts(parts, Body::from(bytes))
+}
+
+/// `Deprecation` (RFC 9745) and `Sunset` (RFC 8594), plus a `successor-version` link to the
+/// same resource under the next version.
+fn insert_deprecation_headers(
+    headers: &mut HeaderMap,
+    deprecation: ApiDeprecationConfig,
+    successor_path: Option<&str>,
+) {
+    headers.insert(
+        HeaderName::from_static(DEPRECATION_HEADER),
+        HeaderValue::from_str(&format!("@{}", deprecation.deprecated_at.timestamp()))
+            .expect("unix timestamp should be a valid header value"),
+    );
+    if let Some(sunset_at) = deprecation.sunset_at
+        && let Ok(value) =
+            HeaderValue::from_str(&sunset_at.format("%a, %d %b %Y %H:%M:%S GMT").to_string())
+    {
+        headers.insert(HeaderName::from_static(SUNSET_HEADER), value);
+    }
+    if let Some(successor_path) = successor_path
+        && let Ok(value) =
+            HeaderValue::from_str(&format!("<{successor_path}>; rel=\"successor-version\""))
+    {
+        headers.append(header::LINK, value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::{Body, to_bytes};
+    use axum::http::{HeaderMap, Method, Request, StatusCode, header};
+    use axum::response::{IntoResponse, Response};
+    use chrono::{TimeZone, Utc};
+    use serde_json::{Value, json};
+    use shared::config::ApiDeprecationConfig;
+
+    use super::{
+        ApiVersion, VersionAdapter, adapt_request, adapt_response, insert_deprecation_headers,
+    };
+
+    /// A `/v2` contract that calls `/v1`'s `title` field `name`.
+    struct RenamedTitle;
+
+    impl VersionAdapter for RenamedTitle {
+        fn applies_to(&self, method: &Method, path: &str) -> bool {
+            method == Method::POST && path == "/v1/automations"
+        }
+
+        fn adapt_request(&self, mut body: Value) -> Result<Value, String> {
+            let name = body
+                .as_object_mut()
+                .and_then(|body| body.remove("name"))
+                .ok_or_else(|| "name is required".to_string())?;
+            body["title"] = name;
+            Ok(body)
+        }
+
+        fn adapt_response(&self, _status: StatusCode, mut body: Value) -> Value {
+            if let Some(title) = body.as_object_mut().and_then(|body| body.remove("title")) {
+                body["name"] = title;
+            }
+            body
+        }
+    }
+
+    async fn json_body(body: Body) -> Value {
+        let bytes = to_bytes(body, usize::MAX).await.expect("body should read");
+        serde_json::from_slice(&bytes).expect("body should be json")
+    }
+
+    #[test]
+    fn versions_match_whole_prefix_segments_only() {
+        assert_eq!(ApiVersion::from_path("/v1/status"), Some(ApiVersion::V1));
+        assert_eq!(
+            ApiVersion::from_path("/v2/automations/abc"),
+            Some(ApiVersion::V2)
+        );
+        assert_eq!(ApiVersion::from_path("/v2"), None);
+        assert_eq!(ApiVersion::from_path("/v20/status"), None);
+        assert_eq!(ApiVersion::from_path("/admin/v1/users"), None);
+        assert_eq!(ApiVersion::from_path("/healthz"), None);
+    }
+
+    #[test]
+    fn rewrites_keep_the_query_and_label_routes_by_requested_version() {
+        let rewritten = ApiVersion::V2
+            .with_prefix_of("/v2/audit-events?limit=5", ApiVersion::V1)
+            .expect("path should rewrite");
+        assert_eq!(rewritten.as_str(), "/v1/audit-events?limit=5");
+
+        assert_eq!(
+            ApiVersion::V2.client_route("/v1/automations/{rule_id}"),
+            "/v2/automations/{rule_id}"
+        );
+        assert_eq!(ApiVersion::V1.client_route("/v1/status"), "/v1/status");
+        assert_eq!(ApiVersion::V2.client_route("/healthz"), "/healthz");
+    }
+
+    #[test]
+    fn deprecation_headers_carry_dates_and_successor_link() {
+        let mut headers = HeaderMap::new();
+        insert_deprecation_headers(
+            &mut headers,
+            ApiDeprecationConfig {
+                deprecated_at: Utc.with_ymd_and_hms(2026, 11, 1, 0, 0, 0).unwrap(),
+                sunset_at: Some(Utc.with_ymd_and_hms(2027, 5, 1, 0, 0, 0).unwrap()),
+            },
+            Some("/v2/status"),
+        );
+
+        assert_eq!(headers["deprecation"], "@1793491200");
+        assert_eq!(headers["sunset"], "Sat, 01 May 2027 00:00:00 GMT");
+        assert_eq!(
+            headers[header::LINK],
+            "</v2/status>; rel=\"successor-version\""
+        );
+    }
+
+    #[tokio::test]
+    async fn adapters_translate_request_and_response_bodies() {
+        let request = Request::post("/v1/automations")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(json!({"name": "Daily digest"}).to_string()))
+            .expect("request should build");
+        let request = adapt_request(&RenamedTitle, request, 1024)
+            .await
+            .unwrap_or_else(|_| panic!("request should adapt"));
+        assert_eq!(
+            json_body(request.into_body()).await,
+            json!({"title": "Daily digest"})
+        );
+
+        let response = axum::Json(json!({"id": "rule-1", "title": "Daily digest"})).into_response();
+        let response = adapt_response(&RenamedTitle, response).await;
+        assert_eq!(
+            json_body(response.into_body()).await,
+            json!({"id": "rule-1", "name": "Daily digest"})
+        );
+    }
+
+    #[tokio::test]
+    async fn adapter_rejections_are_invalid_request_bodies() {
+        let request = Request::post("/v1/automations")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(json!({"title": "Old shape"}).to_string()))
+            .expect("request should build");
+        let response: Response = match adapt_request(&RenamedTitle, request, 1024).await {
+            Ok(_) => panic!("request without name should be rejected"),
+            Err(response) => response,
+        };
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(
+            json_body(response.into_body()).await["error"]["code"],
+            "invalid_request_body"
+        );
+    }
+}
@@ -0,0 +1,308 @@
+use std::collections::HashSet;
+
+use axum::Json;
+use axum::extract::{Extension, Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use serde::Deserialize;
+use shared::models::{
+    ApiErrorCode, AuditMetadata, CreateWebhookRequest, CreateWebhookResponse,
+    ListWebhookDeliveriesResponse, ListWebhooksResponse, OkResponse, WebhookDeliverySummary,
+    WebhookSummary,
+};
+use shared::pagination::CursorResource;
+use shared::repos::{AuditResult, WebhookDeliveryRecord, WebhookSubscriptionRecord};
+use shared::webhooks::{WebhookEventType, normalize_webhook_url};
+use uuid::Uuid;
+
+use super::errors::{error_response, store_error_response};
+use super::openapi::ApiOperation;
+use super::pagination::{PageLimits, next_cursor, page_request};
+use super::request_body::ApiJson;
+use super::tokens::generate_secure_token;
+use super::{AppState, AuthUser};
+
+/// Every event fans out to each matching subscription, so the count is kept small.
+const MAX_WEBHOOK_SUBSCRIPTIONS: i64 = 5;
+const WEBHOOK_DELIVERY_PAGE_LIMITS: PageLimits = PageLimits {
+    default: 20,
+    max: 100,
+};
+
+#[derive(Debug, Deserialize)]
+pub(super) struct ListWebhookDeliveriesQuery {
+    pub(super) cursor: Option<String>,
+    pub(super) limit: Option<i64>,
+}
+
+pub(super) const CREATE_WEBHOOK: ApiOperation = ApiOperation::post(
+    "/v1/webhooks",
+    "createWebhook",
+    "Webhooks",
+    "Subscribe an endpoint to signed account events",
+)
+.request::<CreateWebhookRequest>()
+.response::<CreateWebhookResponse>();
+
+pub(super) async fn create_webhook(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    ApiJson(req): ApiJson<CreateWebhookRequest>,
+) -> Response {
+    let url = match normalize_webhook_url(&req.url) {
+        Ok(url) => url,
+        Err(message) => return error_response(ApiErrorCode::InvalidWebhookUrl, message),
+    };
+    if !are_valid_event_types(&req.event_types) {
+        return error_response(
+            ApiErrorCode::InvalidWebhookEventTypes,
+            "event_types must list at least one event type without repeats",
+        );
+    }
+
+    match state.store.count_webhook_subscriptions(user.user_id).await {
+        Ok(count) if count >= MAX_WEBHOOK_SUBSCRIPTIONS => {
+            return error_response(
+                ApiErrorCode::WebhookLimitReached,
+                "At most 5 webhook subscriptions are allowed",
+            );
+        }
+        Ok(_) => {}
+        Err(err) => return store_error_response(err),
+    }
+
+    let signing_secret = generate_secure_token("whsec");
+    let record = match state
+        .store
+        .create_webhook_subscription(user.user_id, &url, &req.event_types, &signing_secret)
+        .await
+    {
+        Ok(record) => record,
+        Err(err) => return store_error_response(err),
+    };
+
+    let mut metadata = AuditMetadata::new();
+    metadata.insert("webhook_id".to_string(), record.id.to_string().into());
+    metadata.insert(
+        "event_types".to_string(),
+        event_type_names(&record.event_types).into(),
+    );
+
+    if let Err(err) = state
+        .store
+        .add_audit_event(
+            user.user_id,
+            "WEBHOOK_CREATED",
+            None,
+            AuditResult::Success,
+            &metadata,
+        )
+        .await
+    {
+        return store_error_response(err);
+    }
+
+    (
+        StatusCode::OK,
+        Json(CreateWebhookResponse {
+            webhook_id: record.id.to_string(),
+            url: record.url,
+            event_types: record.event_types,
+            created_at: record.created_at,
+            signing_secret,
+        }),
+    )
+        .into_response()
+}
+
+pub(super) const LIST_WEBHOOKS: ApiOperation = ApiOperation::get(
+    "/v1/webhooks",
+    "listWebhooks",
+    "Webhooks",
+    "List webhook subscriptions",
+)
+.response::<ListWebhooksResponse>();
+
+pub(super) async fn list_webhooks(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+) -> Response {
+    match state.store.list_webhook_subscriptions(user.user_id).await {
+        Ok(records) => (
+            StatusCode::OK,
+            Json(ListWebhooksResponse {
+                items: records.into_iter().map(webhook_summary).collect(),
+            }),
+        )
+            .into_response(),
+        Err(err) => store_error_response(err),
+    }
+}
+
+pub(super) const DELETE_WEBHOOK: ApiOperation = ApiOperation::delete(
+    "/v1/webhooks/{webhook_id}",
+    "deleteWebhook",
+    "Webhooks",
+    "Delete a webhook subscription and its delivery log",
+);
+
+pub(super) async fn delete_webhook(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(webhook_id): Path<String>,
+) -> Response {
+    let Ok(webhook_id) = Uuid::parse_str(&webhook_id) else {
+        return webhook_not_found_response();
+    };
+
+    match state
+        .store
+        .delete_webhook_subscription(user.user_id, webhook_id)
+        .await
+    {
+        Ok(true) => {}
+        Ok(false) => return webhook_not_found_response(),
+        Err(err) => return store_error_response(err),
+    }
+
+    let mut metadata = AuditMetadata::new();
+    metadata.insert("webhook_id".to_string(), webhook_id.to_string().into());
+
+    if let Err(err) = state
+        .store
+        .add_audit_event(
+            user.user_id,
+            "WEBHOOK_DELETED",
+            None,
+            AuditResult::Success,
+            &metadata,
+        )
+        .await
+    {
+        return store_error_response(err);
+    }
+
+    (StatusCode::OK, Json(OkResponse { ok: true })).into_response()
+}
+
+pub(super) const LIST_WEBHOOK_DELIVERIES: ApiOperation = ApiOperation::get(
+    "/v1/webhooks/{webhook_id}/deliveries",
+    "listWebhookDeliveries",
+    "Webhooks",
+    "List delivery attempts for a webhook subscription",
+)
+.response::<ListWebhookDeliveriesResponse>();
+
+pub(super) async fn list_webhook_deliveries(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(webhook_id): Path<String>,
+    Query(query): Query<ListWebhookDeliveriesQuery>,
+) -> Response {
+    let Ok(webhook_id) = Uuid::parse_str(&webhook_id) else {
+        return webhook_not_found_response();
+    };
+    let page = match page_request(
+        &state,
+        CursorResource::WebhookDeliveries,
+        query.cursor.as_deref(),
+        query.limit,
+        WEBHOOK_DELIVERY_PAGE_LIMITS,
+    ) {
+        Ok(page) => page,
+        Err(err) => return err.into_response(),
+    };
+
+    match state
+        .store
+        .get_webhook_subscription(user.user_id, webhook_id)
+        .await
+    {
+        Ok(Some(_)) => {}
+        Ok(None) => return webhook_not_found_response(),
+        Err(err) => return store_error_response(err),
+    }
+
+    let deliveries = match state
+        .store
+        .list_webhook_deliveries(user.user_id, webhook_id, page)
+        .await
+    {
+        Ok(deliveries) => deliveries,
+        Err(err) => return store_error_response(err),
+    };
+
+    let next_cursor = next_cursor(&state, CursorResource::WebhookDeliveries, &deliveries);
+    let items = deliveries.map(webhook_delivery_summary).items;
+    (
+        StatusCode::OK,
+        Json(ListWebhookDeliveriesResponse { items, next_cursor }),
+    )
+        .into_response()
+}
+
+fn are_valid_event_types(event_types: &[WebhookEventType]) -> bool {
+    let mut seen = HashSet::new();
+    !event_types.is_empty()
+        && event_types
+            .iter()
+            .all(|event_type| seen.insert(*event_type))
+}
+
+fn event_type_names(event_types: &[WebhookEventType]) -> Vec<String> {
+    event_types
+        .iter()
+        .map(|event_type| event_type.as_str().to_string())
+        .collect()
+}
+
+fn webhook_summary(record: WebhookSubscriptionRecord) -> WebhookSummary {
+    WebhookSummary {
+        webhook_id: record.id.to_string(),
+        url: record.url,
+        event_types: record.event_types,
+        created_at: record.created_at,
+    }
+}
+
+fn webhook_delivery_summary(record: WebhookDeliveryRecord) -> WebhookDeliverySummary {
+    WebhookDeliverySummary {
+        delivery_id: record.id.to_string(),
+        event_id: record.event_id.to_string(),
+        event_type: record.event_type,
+        state: record.state,
+        attempts: record.attempts,
+        next_attempt_at: record.next_attempt_at,
+        last_status_code: record.last_status_code,
+        last_error: record.last_error,
+        created_at: record.created_at,
+        delivered_at: record.delivered_at,
+    }
+}
+
+fn webhook_not_found_response() -> Response {
+    error_response(
+        ApiErrorCode::WebhookNotFound,
+        "Webhook subscription not found",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use shared::webhooks::WebhookEventType;
+
+    use super::are_valid_event_types;
+
+    #[test]
+    fn event_types_must_be_present_and_distinct() {
+        assert!(are_valid_event_types(&[
+            WebhookEventType::AutomationRunCompleted,
+            WebhookEventType::ConnectorNeedsReauth,
+        ]));
+        assert!(!are_valid_event_types(&[]));
+        assert!(!are_valid_event_types(&[
+            WebhookEventType::PrivacyDeleteCompleted,
+            WebhookEventType::PrivacyDeleteCompleted,
+        ]));
+    }
+}
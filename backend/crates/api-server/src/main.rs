@@ -1,19 +1,28 @@
 use std::net::SocketAddr;
+use std::sync::Arc;
 use std::time::Duration;
 
 use shared::config::{ApiConfig, load_dotenv};
-use shared::enclave::EnclaveRpcAuthConfig;
+use shared::enclave::{EnclaveRpcAuthConfig, EnclaveRpcClient, EnclaveRpcTls};
 use shared::enclave_runtime::{
     AlfredEnvironment, EnclaveRuntimeEndpointConfig, verify_connectivity,
 };
+use shared::events::{AuditEventSink, EventBus, TracingEventSink};
+use shared::feature_flags::{FeatureFlagDefaults, FeatureFlags};
+use shared::log_redaction::{LogFormat, redacting_log_layer};
+use shared::pagination::PaginationCursorCodec;
+use shared::replay_guard::NonceReplayGuard;
 use shared::repos::Store;
+use shared::secrets::{SecretsResolver, resolve_env_secret_references, watch_secret_rotations};
 use shared::security::{KmsDecryptPolicy, SecretRuntime, TeeAttestationPolicy};
 use tracing::{error, info};
+use tracing_subscriber::EnvFilter;
+use tracing_subscriber::layer::SubscriberExt as _;
+use tracing_subscriber::util::SubscriberInitExt as _;
 
 use api_server::http;
 
-#[tokio::main]
-async fn main() {
+fn main() {
     if let Err(err) = load_dotenv() {
         eprintln!("{err}");
         std::process::exit(1);
@@ -21,6 +30,20 @@ async fn main() {
 
     init_tracing();
 
+    let secrets = match resolve_env_secret_references() {
+        Ok(secrets) => secrets,
+        Err(err) => {
+            error!(error = %err, "failed to resolve secret references");
+            std::process::exit(1);
+        }
+    };
+    run(secrets);
+}
+
+#[tokio::main]
+async fn run(secrets: SecretsResolver) {
+    tokio::spawn(watch_secret_rotations(secrets));
+
     let config = match ApiConfig::from_env() {
         Ok(cfg) => cfg,
         Err(err) => {
@@ -31,12 +54,18 @@ async fn main() {
 
     let store = match Store::connect(
         &config.database_url,
+        config.database_read_url.as_deref(),
         config.database_max_connections,
-        &config.data_encryption_key,
+        &config.data_encryption_keys,
     )
     .await
     {
-        Ok(store) => store,
+        Ok(store) => {
+            store
+                .with_audit_redaction_policy(config.audit_redaction_policy.clone())
+                .with_read_cache_from_config(&config.redis_url, config.store_read_cache_ttl_seconds)
+                .await
+        }
         Err(err) => {
             error!(error = %err, "failed to connect to postgres");
             std::process::exit(1);
@@ -56,7 +85,34 @@ async fn main() {
         std::process::exit(1);
     }
 
-    let rate_limiter = http::RateLimiter::default();
+    let rate_limiter =
+        match http::RateLimiter::default().with_policy_overrides(&config.rate_limit_overrides) {
+            Ok(rate_limiter) => rate_limiter,
+            Err(err) => {
+                error!(error = %err, "invalid API_RATE_LIMIT_OVERRIDES");
+                std::process::exit(1);
+            }
+        };
+    let rate_limiter = rate_limiter.with_security_anomaly_enforcement(
+        config.security_anomaly_enforcement_enabled,
+        config.security_anomaly_cooldown_seconds,
+    );
+    let rate_limiter = if config.rate_limit_redis_enabled {
+        rate_limiter.with_redis_from_config(&config.redis_url).await
+    } else {
+        rate_limiter
+    };
+    let feature_flags = match FeatureFlagDefaults::from_env() {
+        Ok(defaults) => {
+            FeatureFlags::new(defaults)
+                .with_redis_from_config(&config.redis_url)
+                .await
+        }
+        Err(err) => {
+            error!(error = %err, "invalid feature flag defaults");
+            std::process::exit(1);
+        }
+    };
     let _rate_limiter_pruner = rate_limiter.spawn_pruner(Duration::from_secs(60));
     let clerk_jwks_cache = match http::ClerkJwksCache::new(http::ClerkJwksCacheConfig {
         redis_url: config.redis_url.clone(),
@@ -87,7 +143,23 @@ async fn main() {
         base_url: config.enclave_runtime_base_url.clone(),
         probe_timeout_ms: config.enclave_runtime_probe_timeout_ms,
     };
-    if let Err(err) = verify_connectivity(&http_client, &enclave_runtime_config).await {
+    let enclave_rpc_tls = match config
+        .enclave_rpc_client_tls
+        .as_ref()
+        .map(|tls| EnclaveRpcTls::load(tls, Duration::from_millis(config.api_http_timeout_ms)))
+        .transpose()
+    {
+        Ok(enclave_rpc_tls) => enclave_rpc_tls,
+        Err(err) => {
+            error!(error = %err, "failed to load enclave rpc tls client identity");
+            std::process::exit(1);
+        }
+    };
+    let enclave_probe_client = match enclave_rpc_tls.as_ref() {
+        Some(tls) => tls.attestation_client(),
+        None => http_client.clone().into(),
+    };
+    if let Err(err) = verify_connectivity(&enclave_probe_client, &enclave_runtime_config).await {
         error!(error = %err, "failed enclave runtime startup connectivity check");
         std::process::exit(1);
     }
@@ -97,6 +169,57 @@ async fn main() {
         "enclave runtime connectivity verified"
     );
 
+    let secret_runtime = SecretRuntime::new(
+        TeeAttestationPolicy {
+            required: config.tee_attestation_required,
+            expected_runtime: config.tee_expected_runtime,
+            allowed_measurements: config.tee_allowed_measurements,
+            attestation_public_key: config.tee_attestation_public_key,
+            max_attestation_age_seconds: config.tee_attestation_max_age_seconds,
+            allow_insecure_dev_attestation: config.tee_allow_insecure_dev_attestation,
+        },
+        KmsDecryptPolicy {
+            key_id: config.kms_key_id,
+            key_version: config.kms_key_version,
+            allowed_measurements: config.kms_allowed_measurements,
+        },
+        config.enclave_runtime_base_url.clone(),
+        config.tee_attestation_challenge_timeout_ms,
+        http_client.clone(),
+    );
+    let secret_runtime = secret_runtime.with_replay_guard(
+        NonceReplayGuard::default()
+            .with_redis_from_config(&config.redis_url)
+            .await,
+    );
+    let secret_runtime = match enclave_rpc_tls.as_ref() {
+        Some(tls) => secret_runtime.with_enclave_tls(tls),
+        None => secret_runtime,
+    };
+
+    let store = if config.audit_event_signing_enabled {
+        let audit_signer = EnclaveRpcClient::new(
+            config.enclave_runtime_base_url.clone(),
+            EnclaveRpcAuthConfig {
+                signing_key: config.enclave_rpc_signing_key.clone(),
+                max_clock_skew_seconds: config.enclave_rpc_auth_max_skew_seconds,
+            },
+            http_client.clone(),
+        );
+        let audit_signer = match enclave_rpc_tls.as_ref() {
+            Some(tls) => audit_signer.with_mtls(tls, secret_runtime.clone()),
+            None => audit_signer,
+        };
+        store.with_audit_event_signer(Arc::new(audit_signer))
+    } else {
+        store
+    };
+
+    let events = EventBus::builder()
+        .inline_sink(AuditEventSink::new(store.clone()))
+        .background_sink(TracingEventSink)
+        .build();
+
     let app = http::build_router(http::AppState {
         store,
         oauth: http::OAuthConfig {
@@ -104,6 +227,7 @@ async fn main() {
             redirect_uri: config.google_redirect_uri,
             auth_url: config.google_auth_url,
             scopes: vec![
+                "openid".to_string(),
                 "https://www.googleapis.com/auth/gmail.readonly".to_string(),
                 "https://www.googleapis.com/auth/calendar.readonly".to_string(),
             ],
@@ -111,29 +235,13 @@ async fn main() {
         enclave_rpc: http::EnclaveRpcConfig {
             base_url: config.enclave_runtime_base_url.clone(),
             auth: EnclaveRpcAuthConfig {
-                shared_secret: config.enclave_rpc_shared_secret.clone(),
+                signing_key: config.enclave_rpc_signing_key.clone(),
                 max_clock_skew_seconds: config.enclave_rpc_auth_max_skew_seconds,
             },
+            tls: enclave_rpc_tls,
         },
         allow_debug_automation_run: matches!(config.alfred_environment, AlfredEnvironment::Local),
-        secret_runtime: SecretRuntime::new(
-            TeeAttestationPolicy {
-                required: config.tee_attestation_required,
-                expected_runtime: config.tee_expected_runtime,
-                allowed_measurements: config.tee_allowed_measurements,
-                attestation_public_key: config.tee_attestation_public_key,
-                max_attestation_age_seconds: config.tee_attestation_max_age_seconds,
-                allow_insecure_dev_attestation: config.tee_allow_insecure_dev_attestation,
-            },
-            KmsDecryptPolicy {
-                key_id: config.kms_key_id,
-                key_version: config.kms_key_version,
-                allowed_measurements: config.kms_allowed_measurements,
-            },
-            config.enclave_runtime_base_url.clone(),
-            config.tee_attestation_challenge_timeout_ms,
-            http_client.clone(),
-        ),
+        secret_runtime,
         rate_limiter,
         trusted_proxy_ips: config.trusted_proxy_ips.into_iter().collect(),
         oauth_state_ttl_seconds: config.oauth_state_ttl_seconds,
@@ -143,6 +251,21 @@ async fn main() {
         clerk_jwks_url: config.clerk_jwks_url,
         clerk_jwks_cache,
         http_client,
+        cursor_codec: PaginationCursorCodec::new(&config.pagination_cursor_secret),
+        admin_api_token: config.admin_api_token,
+        admin_clerk_org: config.admin_clerk_org,
+        org_admin_role: config.org_admin_role,
+        events,
+        request_body_limits: http::RequestBodyLimits {
+            default_bytes: config.max_request_body_bytes,
+            prompt_envelope_bytes: config.max_prompt_envelope_body_bytes,
+        },
+        metrics: http::ApiMetrics::default(),
+        api_v1_deprecation: config.api_v1_deprecation,
+        cors: config.cors,
+        feature_flags,
+        request_signing: config.request_signing,
+        security_headers: config.security_headers,
     });
 
     let addr: SocketAddr = config
@@ -164,13 +287,10 @@ async fn main() {
 }
 
 fn init_tracing() {
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            std::env::var("RUST_LOG")
-                .unwrap_or_else(|_| "api_server=debug,axum=info,tower_http=info".to_string()),
-        )
-        .json()
-        .flatten_event(true)
-        .with_current_span(true)
+    tracing_subscriber::registry()
+        .with(EnvFilter::new(std::env::var("RUST_LOG").unwrap_or_else(
+            |_| "api_server=debug,axum=info,tower_http=info".to_string(),
+        )))
+        .with(redacting_log_layer(LogFormat::Json))
         .init();
 }
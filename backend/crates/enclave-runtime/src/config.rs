@@ -10,14 +10,60 @@ use shared::assistant_crypto::{
     ASSISTANT_ENCRYPTION_ALGORITHM_X25519_CHACHA20POLY1305, AssistantIngressKeyMaterial,
     AssistantIngressKeyring, derive_public_key_b64,
 };
-use shared::enclave::{EnclaveRpcAuthConfig, GoogleEnclaveOauthConfig};
+use shared::audit_redaction::AuditRedactionPolicy;
+use shared::audit_signing::{AuditEventSigningPayload, is_signed_audit_event_type};
+use shared::config::{load_audit_redaction_policy, load_data_encryption_keyring};
+use shared::enclave::{
+    EnclaveRpcClientTlsConfig, EnclaveRpcSecurityMode, GoogleEnclaveOauthConfig, VsockAddr,
+};
 use shared::enclave_runtime::{
     AlfredEnvironment, AssistantAttestedKeyChallengeRequest, AssistantAttestedKeyChallengeResponse,
     AttestationChallengeRequest, AttestationChallengeResponse, EnclaveRuntimeMode,
     assistant_key_attestation_signing_payload, attestation_signing_payload,
 };
+use shared::outbound_rate_limit::{OutboundProviderLimit, OutboundRateLimitConfig};
+use shared::repos::DataEncryptionKeyring;
+
+use crate::keygen::UnsealedPrivateKeys;
+
+mod caller_keys;
+mod env_vars;
+mod kms;
+mod secrets;
+
+pub(crate) use caller_keys::EnclaveRpcVerifierConfig;
+#[cfg(test)]
+pub(crate) use caller_keys::SecondaryCallerKeys;
+use caller_keys::{parse_enclave_rpc_caller_keys, parse_enclave_rpc_secondary_caller_keys};
+use env_vars::{
+    optional_trimmed_env, parse_bool_env, parse_i32_env, parse_list_env,
+    parse_list_env_with_fallback, parse_u32_env, parse_u64_env, require_env,
+};
+use kms::parse_kms_provider_config;
+pub(crate) use kms::{KmsProviderConfig, kms_provider_from_env};
+pub(crate) use secrets::IngressKeyRotationConfig;
+use secrets::{
+    decode_signing_key_bytes, decode_x25519_private_key, parse_ingress_key_rotation_config,
+};
 
 const DEFAULT_ASSISTANT_INGRESS_SESSION_TTL_SECONDS: u64 = 5_184_000;
+const DEFAULT_TEE_ATTESTATION_CACHE_TTL_SECONDS: u64 = 30;
+const DEFAULT_LLM_WARMUP_RETRY_INTERVAL_MS: u64 = 5_000;
+const DEFAULT_OUTBOUND_GOOGLE_CALLS_PER_SECOND: u32 = 50;
+const DEFAULT_OUTBOUND_GOOGLE_BURST: u32 = 100;
+const DEFAULT_OUTBOUND_OPENROUTER_CALLS_PER_SECOND: u32 = 20;
+const DEFAULT_OUTBOUND_OPENROUTER_BURST: u32 = 40;
+const DEFAULT_OUTBOUND_ANTHROPIC_CALLS_PER_SECOND: u32 = 20;
+const DEFAULT_OUTBOUND_ANTHROPIC_BURST: u32 = 40;
+const DEFAULT_OUTBOUND_OPENAI_CALLS_PER_SECOND: u32 = 20;
+const DEFAULT_OUTBOUND_OPENAI_BURST: u32 = 40;
+const DEFAULT_OUTBOUND_BEDROCK_CALLS_PER_SECOND: u32 = 20;
+const DEFAULT_OUTBOUND_BEDROCK_BURST: u32 = 40;
+const DEFAULT_OUTBOUND_MAX_WAIT_MS: u64 = 2_000;
+const DEFAULT_ASSISTANT_INGRESS_PREVIOUS_KEY_GRACE_SECONDS: u64 = 3_600;
+const DEFAULT_ASSISTANT_INGRESS_KEY_SYNC_INTERVAL_SECONDS: u64 = 60;
+const DEFAULT_ASSISTANT_INGRESS_KEY_WRAPPING_KEY_ID: &str = "assistant-ingress-wrap-v1";
+const DEFAULT_RPC_TLS_RELOAD_INTERVAL_SECONDS: u64 = 300;
 
 #[derive(Debug, Clone)]
 pub(crate) struct RuntimeConfig {
@@ -28,27 +74,55 @@ pub(crate) struct RuntimeConfig {
     pub(crate) measurement: String,
     pub(crate) database_url: String,
     pub(crate) database_max_connections: u32,
-    pub(crate) data_encryption_key: String,
+    pub(crate) data_encryption_keys: DataEncryptionKeyring,
+    pub(crate) audit_redaction_policy: AuditRedactionPolicy,
     pub(crate) tee_attestation_required: bool,
     pub(crate) tee_expected_runtime: String,
     pub(crate) tee_allowed_measurements: Vec<String>,
     pub(crate) tee_attestation_public_key: Option<String>,
     pub(crate) tee_attestation_max_age_seconds: u64,
     pub(crate) tee_attestation_challenge_timeout_ms: u64,
+    /// How long a verified identity serves connector decrypts without a new challenge. Zero
+    /// disables the cache.
+    pub(crate) tee_attestation_cache_ttl_seconds: u64,
     pub(crate) tee_allow_insecure_dev_attestation: bool,
     pub(crate) kms_key_id: String,
     pub(crate) kms_key_version: i32,
     pub(crate) kms_allowed_measurements: Vec<String>,
+    pub(crate) kms_provider: KmsProviderConfig,
     pub(crate) enclave_runtime_base_url: String,
     pub(crate) oauth: GoogleEnclaveOauthConfig,
-    pub(crate) enclave_rpc_auth: EnclaveRpcAuthConfig,
+    pub(crate) enclave_rpc_auth: EnclaveRpcVerifierConfig,
+    pub(crate) enclave_rpc_security_mode: EnclaveRpcSecurityMode,
+    /// Listener certificate and client CA, set when the security mode includes mTLS.
+    pub(crate) rpc_server_tls: Option<RpcServerTlsConfig>,
+    /// Client certificate the runtime presents when it requests attestation from its own
+    /// listener, set when the security mode includes mTLS.
+    pub(crate) rpc_client_tls: Option<EnclaveRpcClientTlsConfig>,
+    /// Keyring configured at startup. Request handling reads `RuntimeState::ingress_keys`, which
+    /// starts from this keyring and follows rotations.
     pub(crate) assistant_ingress_keys: AssistantIngressKeyring,
+    pub(crate) assistant_ingress_key_rotation: Option<IngressKeyRotationConfig>,
     pub(crate) assistant_ingress_key_ttl_seconds: u64,
     pub(crate) assistant_session_ttl_seconds: u64,
+    pub(crate) llm_warmup_enabled: bool,
+    pub(crate) llm_warmup_retry_interval_ms: u64,
+    pub(crate) outbound_rate_limits: OutboundRateLimitConfig,
     attestation_source: AttestationSource,
     attestation_signing_private_key: [u8; 32],
 }
 
+/// mTLS listener material. The certificate and key are re-read every `reload_interval_seconds`
+/// so a rotated certificate takes effect without a restart; clients pick up the new fingerprint
+/// from their next attestation challenge.
+#[derive(Debug, Clone)]
+pub(crate) struct RpcServerTlsConfig {
+    pub(crate) cert_path: PathBuf,
+    pub(crate) key_path: PathBuf,
+    pub(crate) client_ca_path: PathBuf,
+    pub(crate) reload_interval_seconds: u64,
+}
+
 #[derive(Debug, Clone)]
 enum AttestationSource {
     Inline(String),
@@ -63,11 +137,9 @@ struct AttestationIdentityDocument {
 }
 
 impl RuntimeConfig {
-    pub(crate) fn from_env() -> Result<Self, String> {
-        let environment = env::var("ALFRED_ENV")
-            .unwrap_or_else(|_| "local".to_string())
-            .parse::<AlfredEnvironment>()
-            .map_err(|err| format!("invalid environment: {err}"))?;
+    /// `unsealed` supplies the private keys that were stored sealed under KMS.
+    pub(crate) fn from_env(unsealed: &UnsealedPrivateKeys) -> Result<Self, String> {
+        let environment = environment_from_env()?;
         let default_mode = if matches!(environment, AlfredEnvironment::Local) {
             "dev-shim"
         } else {
@@ -89,8 +161,8 @@ impl RuntimeConfig {
             AttestationSource::Missing
         };
 
-        let attestation_signing_private_key = if let Ok(encoded_key) =
-            env::var("TEE_ATTESTATION_SIGNING_PRIVATE_KEY")
+        let attestation_signing_private_key = if let Some(encoded_key) =
+            unsealed.env("TEE_ATTESTATION_SIGNING_PRIVATE_KEY")
         {
             decode_signing_key_bytes(encoded_key.as_str())?
         } else if matches!(mode, EnclaveRuntimeMode::DevShim) {
@@ -144,6 +216,18 @@ impl RuntimeConfig {
         if tee_attestation_challenge_timeout_ms == 0 {
             return Err("TEE_ATTESTATION_CHALLENGE_TIMEOUT_MS must be > 0".to_string());
         }
+        let tee_attestation_max_age_seconds =
+            parse_u64_env("TEE_ATTESTATION_MAX_AGE_SECONDS", 300)?;
+        let tee_attestation_cache_ttl_seconds = parse_u64_env(
+            "TEE_ATTESTATION_CACHE_TTL_SECONDS",
+            DEFAULT_TEE_ATTESTATION_CACHE_TTL_SECONDS,
+        )?;
+        if tee_attestation_cache_ttl_seconds > tee_attestation_max_age_seconds / 2 {
+            return Err(
+                "TEE_ATTESTATION_CACHE_TTL_SECONDS must be at most half of TEE_ATTESTATION_MAX_AGE_SECONDS"
+                    .to_string(),
+            );
+        }
         let assistant_session_ttl_seconds = parse_u64_env(
             "ASSISTANT_INGRESS_SESSION_TTL_SECONDS",
             DEFAULT_ASSISTANT_INGRESS_SESSION_TTL_SECONDS,
@@ -156,6 +240,55 @@ impl RuntimeConfig {
             return Err("ASSISTANT_INGRESS_KEY_TTL_SECONDS must be > 0".to_string());
         }
 
+        // Local runs often use placeholder provider keys, so only deployed enclaves gate
+        // readiness on a verified provider path by default.
+        let llm_warmup_enabled = parse_bool_env(
+            "ENCLAVE_LLM_WARMUP_ENABLED",
+            !matches!(environment, AlfredEnvironment::Local),
+        )?;
+        let llm_warmup_retry_interval_ms = parse_u64_env(
+            "ENCLAVE_LLM_WARMUP_RETRY_INTERVAL_MS",
+            DEFAULT_LLM_WARMUP_RETRY_INTERVAL_MS,
+        )?;
+        if llm_warmup_retry_interval_ms == 0 {
+            return Err("ENCLAVE_LLM_WARMUP_RETRY_INTERVAL_MS must be > 0".to_string());
+        }
+        let outbound_rate_limits = OutboundRateLimitConfig {
+            google: parse_outbound_provider_limit(
+                "ENCLAVE_OUTBOUND_GOOGLE",
+                DEFAULT_OUTBOUND_GOOGLE_CALLS_PER_SECOND,
+                DEFAULT_OUTBOUND_GOOGLE_BURST,
+            )?,
+            openrouter: parse_outbound_provider_limit(
+                "ENCLAVE_OUTBOUND_OPENROUTER",
+                DEFAULT_OUTBOUND_OPENROUTER_CALLS_PER_SECOND,
+                DEFAULT_OUTBOUND_OPENROUTER_BURST,
+            )?,
+            anthropic: parse_outbound_provider_limit(
+                "ENCLAVE_OUTBOUND_ANTHROPIC",
+                DEFAULT_OUTBOUND_ANTHROPIC_CALLS_PER_SECOND,
+                DEFAULT_OUTBOUND_ANTHROPIC_BURST,
+            )?,
+            openai: parse_outbound_provider_limit(
+                "ENCLAVE_OUTBOUND_OPENAI",
+                DEFAULT_OUTBOUND_OPENAI_CALLS_PER_SECOND,
+                DEFAULT_OUTBOUND_OPENAI_BURST,
+            )?,
+            bedrock: parse_outbound_provider_limit(
+                "ENCLAVE_OUTBOUND_BEDROCK",
+                DEFAULT_OUTBOUND_BEDROCK_CALLS_PER_SECOND,
+                DEFAULT_OUTBOUND_BEDROCK_BURST,
+            )?,
+            max_wait: std::time::Duration::from_millis(parse_u64_env(
+                "ENCLAVE_OUTBOUND_MAX_WAIT_MS",
+                DEFAULT_OUTBOUND_MAX_WAIT_MS,
+            )?),
+        };
+
+        let enclave_rpc_security_mode = env::var("ENCLAVE_RPC_SECURITY_MODE")
+            .unwrap_or_else(|_| "signed".to_string())
+            .parse::<EnclaveRpcSecurityMode>()?;
+        let (rpc_server_tls, rpc_client_tls) = parse_rpc_tls_config(enclave_rpc_security_mode)?;
         let enclave_rpc_auth_max_skew_seconds =
             parse_u64_env("ENCLAVE_RPC_AUTH_MAX_SKEW_SECONDS", 30)?;
         if enclave_rpc_auth_max_skew_seconds == 0 {
@@ -165,6 +298,7 @@ impl RuntimeConfig {
             parse_list_env_with_fallback("KMS_ALLOWED_MEASUREMENTS", &tee_allowed_measurements);
         let enclave_runtime_base_url = env::var("ENCLAVE_RUNTIME_BASE_URL")
             .unwrap_or_else(|_| "http://127.0.0.1:8181".to_string());
+        let kms_provider = parse_kms_provider_config(environment)?;
         validate_non_local_security_posture(
             environment,
             tee_attestation_required,
@@ -172,6 +306,7 @@ impl RuntimeConfig {
             &tee_allowed_measurements,
             &kms_allowed_measurements,
             enclave_runtime_base_url.as_str(),
+            &kms_provider,
         )?;
         let assistant_key_expires_at = Utc::now().timestamp() + assistant_key_ttl_seconds as i64;
         let active_key_id = env::var("ASSISTANT_INGRESS_ACTIVE_KEY_ID")
@@ -180,7 +315,7 @@ impl RuntimeConfig {
             return Err("ASSISTANT_INGRESS_ACTIVE_KEY_ID must not be empty".to_string());
         }
         let active_private_key =
-            if let Some(encoded) = optional_trimmed_env("ASSISTANT_INGRESS_ACTIVE_PRIVATE_KEY") {
+            if let Some(encoded) = unsealed.env("ASSISTANT_INGRESS_ACTIVE_PRIVATE_KEY") {
                 decode_x25519_private_key(encoded.as_str(), "ASSISTANT_INGRESS_ACTIVE_PRIVATE_KEY")?
             } else if matches!(environment, AlfredEnvironment::Local) {
                 [11_u8; 32]
@@ -205,7 +340,7 @@ impl RuntimeConfig {
                     );
                 }
                 let previous_key_encoded =
-                    optional_trimmed_env("ASSISTANT_INGRESS_PREVIOUS_PRIVATE_KEY").ok_or(
+                    unsealed.env("ASSISTANT_INGRESS_PREVIOUS_PRIVATE_KEY").ok_or(
                         "ASSISTANT_INGRESS_PREVIOUS_PRIVATE_KEY is required when previous key id is set"
                             .to_string(),
                     )?;
@@ -243,6 +378,7 @@ impl RuntimeConfig {
             }
             None => None,
         };
+        let assistant_ingress_key_rotation = parse_ingress_key_rotation_config(environment)?;
 
         Ok(Self {
             bind_addr: env::var("ENCLAVE_RUNTIME_BIND_ADDR")
@@ -253,19 +389,22 @@ impl RuntimeConfig {
             measurement,
             database_url: require_env("DATABASE_URL")?,
             database_max_connections: parse_u32_env("DATABASE_MAX_CONNECTIONS", 5)?,
-            data_encryption_key: require_env("DATA_ENCRYPTION_KEY")?,
+            data_encryption_keys: load_data_encryption_keyring().map_err(|err| err.to_string())?,
+            audit_redaction_policy: load_audit_redaction_policy().map_err(|err| err.to_string())?,
             tee_attestation_required,
             tee_expected_runtime: env::var("TEE_EXPECTED_RUNTIME")
                 .unwrap_or_else(|_| "nitro".to_string()),
             tee_allowed_measurements: tee_allowed_measurements.clone(),
             tee_attestation_public_key: env::var("TEE_ATTESTATION_PUBLIC_KEY").ok(),
-            tee_attestation_max_age_seconds: parse_u64_env("TEE_ATTESTATION_MAX_AGE_SECONDS", 300)?,
+            tee_attestation_max_age_seconds,
             tee_attestation_challenge_timeout_ms,
+            tee_attestation_cache_ttl_seconds,
             tee_allow_insecure_dev_attestation,
             kms_key_id: env::var("KMS_KEY_ID")
                 .unwrap_or_else(|_| "kms/local/alfred-refresh-token".to_string()),
             kms_key_version: parse_i32_env("KMS_KEY_VERSION", 1)?,
             kms_allowed_measurements,
+            kms_provider,
             enclave_runtime_base_url,
             oauth: GoogleEnclaveOauthConfig {
                 client_id: require_env("GOOGLE_OAUTH_CLIENT_ID")?,
@@ -275,16 +414,26 @@ impl RuntimeConfig {
                 revoke_url: env::var("GOOGLE_OAUTH_REVOKE_URL")
                     .unwrap_or_else(|_| "https://oauth2.googleapis.com/revoke".to_string()),
             },
-            enclave_rpc_auth: EnclaveRpcAuthConfig {
-                shared_secret: parse_enclave_rpc_shared_secret(environment)?,
+            enclave_rpc_auth: EnclaveRpcVerifierConfig {
+                caller_keys: parse_enclave_rpc_caller_keys(environment, enclave_rpc_security_mode)?,
+                secondary_caller_keys: parse_enclave_rpc_secondary_caller_keys(
+                    enclave_rpc_security_mode,
+                )?,
                 max_clock_skew_seconds: enclave_rpc_auth_max_skew_seconds,
             },
+            enclave_rpc_security_mode,
+            rpc_server_tls,
+            rpc_client_tls,
             assistant_ingress_keys: AssistantIngressKeyring {
                 active: active_key,
                 previous: previous_key,
             },
+            assistant_ingress_key_rotation,
             assistant_ingress_key_ttl_seconds: assistant_key_ttl_seconds,
             assistant_session_ttl_seconds,
+            llm_warmup_enabled,
+            llm_warmup_retry_interval_ms,
+            outbound_rate_limits,
             attestation_source,
             attestation_signing_private_key,
         })
@@ -316,9 +465,12 @@ impl RuntimeConfig {
             .map_err(|err| format!("failed to parse attestation document: {err}"))
     }
 
+    /// `tls_certificate_sha256` binds the certificate on the mTLS listener to this evidence so
+    /// clients can pin it.
     pub(crate) fn attestation_challenge_response(
         &self,
         challenge: AttestationChallengeRequest,
+        tls_certificate_sha256: Option<String>,
     ) -> Result<AttestationChallengeResponse, String> {
         if challenge.challenge_nonce.trim().is_empty() {
             return Err("invalid challenge: challenge_nonce is required".to_string());
@@ -350,6 +502,7 @@ impl RuntimeConfig {
             operation_purpose: challenge.operation_purpose,
             request_id: challenge.request_id,
             evidence_issued_at,
+            tls_certificate_sha256,
             signature: None,
         };
 
@@ -362,9 +515,13 @@ impl RuntimeConfig {
         Ok(response)
     }
 
+    /// Signs the current active ingress key. `key_usable_until` caps the advertised expiry so
+    /// clients refetch before a scheduled rotation retires the key.
     pub(crate) fn assistant_attested_key_challenge_response(
         &self,
         challenge: AssistantAttestedKeyChallengeRequest,
+        active_key: &AssistantIngressKeyMaterial,
+        key_usable_until: Option<i64>,
     ) -> Result<AssistantAttestedKeyChallengeResponse, String> {
         if challenge.challenge_nonce.trim().is_empty() {
             return Err("invalid challenge: challenge_nonce is required".to_string());
@@ -390,10 +547,13 @@ impl RuntimeConfig {
             expires_at: challenge.expires_at,
             request_id: challenge.request_id,
             evidence_issued_at: now,
-            key_id: self.assistant_ingress_keys.active.key_id.clone(),
+            key_id: active_key.key_id.clone(),
             algorithm: ASSISTANT_ENCRYPTION_ALGORITHM_X25519_CHACHA20POLY1305.to_string(),
-            public_key: self.assistant_ingress_keys.active.public_key.clone(),
-            key_expires_at: self.active_key_expires_at(now),
+            public_key: active_key.public_key.clone(),
+            key_expires_at: key_usable_until.map_or_else(
+                || self.active_key_expires_at(now),
+                |usable_until| self.active_key_expires_at(now).min(usable_until),
+            ),
             signature: None,
         };
 
@@ -406,6 +566,32 @@ impl RuntimeConfig {
         Ok(response)
     }
 
+    /// Signs a high-value audit event with the attestation key. Other event types are refused
+    /// so the key only ever vouches for the events it is documented to cover.
+    pub(crate) fn sign_audit_event(
+        &self,
+        event: &AuditEventSigningPayload,
+    ) -> Result<String, String> {
+        if !is_signed_audit_event_type(&event.event_type) {
+            return Err(format!(
+                "invalid audit event: {} is not a signed event type",
+                event.event_type
+            ));
+        }
+        if event.entry_hash.len() != 64
+            || !event
+                .entry_hash
+                .bytes()
+                .all(|byte| byte.is_ascii_hexdigit())
+        {
+            return Err("invalid audit event: entry_hash must be a hex SHA-256 digest".to_string());
+        }
+
+        let signing_key = SigningKey::from_bytes(&self.attestation_signing_private_key);
+        let signature = signing_key.sign(event.canonical().as_bytes());
+        Ok(base64::engine::general_purpose::STANDARD.encode(signature.to_bytes().as_ref()))
+    }
+
     fn active_key_expires_at(&self, now: i64) -> i64 {
         let ttl = if self.assistant_ingress_key_ttl_seconds > i64::MAX as u64 {
             i64::MAX
@@ -434,19 +620,46 @@ impl RuntimeConfig {
     }
 }
 
-fn parse_enclave_rpc_shared_secret(environment: AlfredEnvironment) -> Result<String, String> {
-    if let Some(secret) = optional_trimmed_env("ENCLAVE_RPC_SHARED_SECRET") {
-        if secret.len() < 16 {
-            return Err("ENCLAVE_RPC_SHARED_SECRET must be at least 16 characters".to_string());
-        }
-        return Ok(secret);
+/// Listener and self-call certificates for the mTLS modes; both are absent in signed-only mode.
+fn parse_rpc_tls_config(
+    security_mode: EnclaveRpcSecurityMode,
+) -> Result<
+    (
+        Option<RpcServerTlsConfig>,
+        Option<EnclaveRpcClientTlsConfig>,
+    ),
+    String,
+> {
+    if !security_mode.requires_mtls() {
+        return Ok((None, None));
     }
 
-    if matches!(environment, AlfredEnvironment::Local) {
-        return Ok("local-dev-enclave-rpc-secret".to_string());
+    let reload_interval_seconds = parse_u64_env(
+        "ENCLAVE_RUNTIME_TLS_RELOAD_INTERVAL_SECONDS",
+        DEFAULT_RPC_TLS_RELOAD_INTERVAL_SECONDS,
+    )?;
+    if reload_interval_seconds == 0 {
+        return Err("ENCLAVE_RUNTIME_TLS_RELOAD_INTERVAL_SECONDS must be > 0".to_string());
     }
+    let server = RpcServerTlsConfig {
+        cert_path: require_env("ENCLAVE_RUNTIME_TLS_CERT_PATH")?.into(),
+        key_path: require_env("ENCLAVE_RUNTIME_TLS_KEY_PATH")?.into(),
+        client_ca_path: require_env("ENCLAVE_RUNTIME_TLS_CLIENT_CA_PATH")?.into(),
+        reload_interval_seconds,
+    };
+    let client = EnclaveRpcClientTlsConfig {
+        client_cert_path: require_env("ENCLAVE_RPC_CLIENT_CERT_PATH")?.into(),
+        client_key_path: require_env("ENCLAVE_RPC_CLIENT_KEY_PATH")?.into(),
+    };
+
+    Ok((Some(server), Some(client)))
+}
 
-    Err("ENCLAVE_RPC_SHARED_SECRET is required outside local env".to_string())
+fn environment_from_env() -> Result<AlfredEnvironment, String> {
+    env::var("ALFRED_ENV")
+        .unwrap_or_else(|_| "local".to_string())
+        .parse::<AlfredEnvironment>()
+        .map_err(|err| format!("invalid environment: {err}"))
 }
 
 fn validate_non_local_security_posture(
@@ -456,6 +669,7 @@ fn validate_non_local_security_posture(
     tee_allowed_measurements: &[String],
     kms_allowed_measurements: &[String],
     enclave_runtime_base_url: &str,
+    kms_provider: &KmsProviderConfig,
 ) -> Result<(), String> {
     if matches!(environment, AlfredEnvironment::Local) {
         return Ok(());
@@ -474,6 +688,9 @@ fn validate_non_local_security_posture(
     validate_measurement_allowlist("TEE_ALLOWED_MEASUREMENTS", tee_allowed_measurements)?;
     validate_measurement_allowlist("KMS_ALLOWED_MEASUREMENTS", kms_allowed_measurements)?;
     validate_non_local_runtime_base_url(enclave_runtime_base_url)?;
+    if matches!(kms_provider, KmsProviderConfig::Disabled) {
+        return Err("KMS_PROVIDER must be aws outside local environment".to_string());
+    }
 
     Ok(())
 }
@@ -498,6 +715,12 @@ fn validate_measurement_allowlist(key: &str, measurements: &[String]) -> Result<
 }
 
 fn validate_non_local_runtime_base_url(base_url: &str) -> Result<(), String> {
+    if VsockAddr::is_vsock_url(base_url) {
+        return VsockAddr::parse_url(base_url)
+            .map(|_| ())
+            .map_err(|err| format!("ENCLAVE_RUNTIME_BASE_URL is invalid: {err}"));
+    }
+
     let parsed = reqwest::Url::parse(base_url)
         .map_err(|_| "ENCLAVE_RUNTIME_BASE_URL must be a valid URL".to_string())?;
     if parsed.scheme() == "https" {
@@ -514,116 +737,30 @@ fn validate_non_local_runtime_base_url(base_url: &str) -> Result<(), String> {
     }
 
     Err(
-        "ENCLAVE_RUNTIME_BASE_URL must use https outside local environment unless it is loopback http"
+        "ENCLAVE_RUNTIME_BASE_URL must use https outside local environment unless it is loopback http or vsock"
             .to_string(),
     )
 }
 
-fn decode_signing_key_bytes(encoded_key: &str) -> Result<[u8; 32], String> {
-    let key_bytes = base64::engine::general_purpose::STANDARD
-        .decode(encoded_key.as_bytes())
-        .map_err(|_| {
-            "TEE_ATTESTATION_SIGNING_PRIVATE_KEY must be valid base64 for a 32-byte Ed25519 key"
-                .to_string()
-        })?;
-
-    key_bytes.try_into().map_err(|_| {
-        "TEE_ATTESTATION_SIGNING_PRIVATE_KEY must decode to exactly 32 bytes".to_string()
-    })
-}
-
-fn decode_x25519_private_key(encoded_key: &str, key_name: &str) -> Result<[u8; 32], String> {
-    let key_bytes = base64::engine::general_purpose::STANDARD
-        .decode(encoded_key.as_bytes())
-        .map_err(|_| format!("{key_name} must be valid base64 for a 32-byte X25519 key"))?;
-
-    key_bytes
-        .try_into()
-        .map_err(|_| format!("{key_name} must decode to exactly 32 bytes"))
-}
-
-fn require_env(key: &str) -> Result<String, String> {
-    env::var(key).map_err(|_| format!("missing required env var {key}"))
-}
-
-fn parse_u32_env(key: &str, default: u32) -> Result<u32, String> {
-    match env::var(key) {
-        Ok(raw) => raw
-            .parse::<u32>()
-            .map_err(|_| format!("invalid integer in env var {key}")),
-        Err(_) => Ok(default),
-    }
-}
-
-fn parse_u64_env(key: &str, default: u64) -> Result<u64, String> {
-    match env::var(key) {
-        Ok(raw) => raw
-            .parse::<u64>()
-            .map_err(|_| format!("invalid integer in env var {key}")),
-        Err(_) => Ok(default),
-    }
-}
-
-fn parse_i32_env(key: &str, default: i32) -> Result<i32, String> {
-    match env::var(key) {
-        Ok(raw) => raw
-            .parse::<i32>()
-            .map_err(|_| format!("invalid integer in env var {key}")),
-        Err(_) => Ok(default),
-    }
-}
-
-fn parse_bool_env(key: &str, default: bool) -> Result<bool, String> {
-    match env::var(key) {
-        Ok(raw) => {
-            let normalized = raw.trim().to_ascii_lowercase();
-            match normalized.as_str() {
-                "true" | "1" | "yes" | "on" => Ok(true),
-                "false" | "0" | "no" | "off" => Ok(false),
-                _ => Err(format!("invalid boolean in env var {key}")),
-            }
-        }
-        Err(_) => Ok(default),
-    }
-}
-
-fn parse_list_env(key: &str, default: &[&str]) -> Vec<String> {
-    match env::var(key) {
-        Ok(raw) => parse_csv_list(raw),
-        Err(_) => default.iter().map(|item| (*item).to_string()).collect(),
-    }
-}
-
-fn parse_list_env_with_fallback(key: &str, fallback: &[String]) -> Vec<String> {
-    match env::var(key) {
-        Ok(raw) => parse_csv_list(raw),
-        Err(_) => fallback.to_vec(),
-    }
-}
-
-fn parse_csv_list(raw: String) -> Vec<String> {
-    let parsed = raw
-        .split(',')
-        .map(str::trim)
-        .filter(|item| !item.is_empty())
-        .map(ToString::to_string)
-        .collect::<Vec<_>>();
-
-    if parsed.is_empty() {
-        vec!["dev-local-enclave".to_string()]
-    } else {
-        parsed
+/// Reads `{prefix}_CALLS_PER_SECOND` (0 disables the limit) and `{prefix}_BURST`.
+fn parse_outbound_provider_limit(
+    prefix: &str,
+    default_calls_per_second: u32,
+    default_burst: u32,
+) -> Result<OutboundProviderLimit, String> {
+    let calls_per_second_key = format!("{prefix}_CALLS_PER_SECOND");
+    let burst_key = format!("{prefix}_BURST");
+    let calls_per_second = parse_u32_env(&calls_per_second_key, default_calls_per_second)?;
+    let burst = parse_u32_env(&burst_key, default_burst)?;
+    if calls_per_second > 0 && burst == 0 {
+        return Err(format!(
+            "{burst_key} must be > 0 when {calls_per_second_key} is set"
+        ));
     }
-}
 
-fn optional_trimmed_env(key: &str) -> Option<String> {
-    env::var(key).ok().and_then(|value| {
-        let trimmed = value.trim();
-        if trimmed.is_empty() {
-            None
-        } else {
-            Some(trimmed.to_string())
-        }
+    Ok(OutboundProviderLimit {
+        calls_per_second,
+        burst,
     })
 }
 
@@ -0,0 +1,88 @@
+use chrono::Utc;
+use shared::enclave::{EnclaveRpcCallerKeys, EnclaveRpcSecurityMode};
+use shared::enclave_runtime::AlfredEnvironment;
+
+use super::env_vars::optional_trimmed_env;
+
+/// Callers allowed to sign RPCs and how far their timestamps may drift. `caller_keys` is empty
+/// when the security mode does not sign requests.
+#[derive(Debug, Clone)]
+pub(crate) struct EnclaveRpcVerifierConfig {
+    pub(crate) caller_keys: EnclaveRpcCallerKeys,
+    /// Keys still accepted while callers move to the keys in `caller_keys`.
+    pub(crate) secondary_caller_keys: Option<SecondaryCallerKeys>,
+    pub(crate) max_clock_skew_seconds: u64,
+}
+
+/// Overlap window of a caller key rotation: these keys verify until `expires_at`, so callers
+/// can switch to their new key one at a time instead of redeploying together.
+#[derive(Debug, Clone)]
+pub(crate) struct SecondaryCallerKeys {
+    pub(crate) caller_keys: EnclaveRpcCallerKeys,
+    pub(crate) expires_at: i64,
+}
+
+/// Empty when the security mode does not sign requests. Local runs fall back to the dev keys
+/// of the api-server and worker.
+pub(super) fn parse_enclave_rpc_caller_keys(
+    environment: AlfredEnvironment,
+    security_mode: EnclaveRpcSecurityMode,
+) -> Result<EnclaveRpcCallerKeys, String> {
+    if !security_mode.requires_signature() {
+        return Ok(EnclaveRpcCallerKeys::default());
+    }
+
+    if let Some(raw) = optional_trimmed_env("ENCLAVE_RPC_CALLER_PUBLIC_KEYS") {
+        let caller_keys = EnclaveRpcCallerKeys::parse(&raw)
+            .map_err(|err| format!("ENCLAVE_RPC_CALLER_PUBLIC_KEYS is invalid: {err}"))?;
+        if caller_keys.is_empty() {
+            return Err("ENCLAVE_RPC_CALLER_PUBLIC_KEYS must list at least one caller".to_string());
+        }
+        return Ok(caller_keys);
+    }
+
+    if matches!(environment, AlfredEnvironment::Local) {
+        return Ok(EnclaveRpcCallerKeys::local_dev(&["api-server", "worker"]));
+    }
+
+    Err("ENCLAVE_RPC_CALLER_PUBLIC_KEYS is required outside local env".to_string())
+}
+
+/// Secondary keys are optional and always need an expiry, so a finished rotation cannot leave
+/// old keys trusted indefinitely.
+pub(super) fn parse_enclave_rpc_secondary_caller_keys(
+    security_mode: EnclaveRpcSecurityMode,
+) -> Result<Option<SecondaryCallerKeys>, String> {
+    if !security_mode.requires_signature() {
+        return Ok(None);
+    }
+    let Some(raw) = optional_trimmed_env("ENCLAVE_RPC_CALLER_SECONDARY_PUBLIC_KEYS") else {
+        return Ok(None);
+    };
+
+    let caller_keys = EnclaveRpcCallerKeys::parse(&raw)
+        .map_err(|err| format!("ENCLAVE_RPC_CALLER_SECONDARY_PUBLIC_KEYS is invalid: {err}"))?;
+    if caller_keys.is_empty() {
+        return Ok(None);
+    }
+    let expires_at = optional_trimmed_env("ENCLAVE_RPC_CALLER_SECONDARY_KEYS_EXPIRES_AT")
+        .ok_or_else(|| {
+            "ENCLAVE_RPC_CALLER_SECONDARY_KEYS_EXPIRES_AT is required when secondary caller keys are set"
+                .to_string()
+        })?
+        .parse::<i64>()
+        .map_err(|_| {
+            "ENCLAVE_RPC_CALLER_SECONDARY_KEYS_EXPIRES_AT must be a valid unix timestamp"
+                .to_string()
+        })?;
+    if expires_at <= Utc::now().timestamp() {
+        return Err(
+            "ENCLAVE_RPC_CALLER_SECONDARY_KEYS_EXPIRES_AT must be in the future".to_string(),
+        );
+    }
+
+    Ok(Some(SecondaryCallerKeys {
+        caller_keys,
+        expires_at,
+    }))
+}
@@ -0,0 +1,86 @@
+use std::env;
+
+pub(super) fn require_env(key: &str) -> Result<String, String> {
+    env::var(key).map_err(|_| format!("missing required env var {key}"))
+}
+
+pub(super) fn parse_u32_env(key: &str, default: u32) -> Result<u32, String> {
+    match env::var(key) {
+        Ok(raw) => raw
+            .parse::<u32>()
+            .map_err(|_| format!("invalid integer in env var {key}")),
+        Err(_) => Ok(default),
+    }
+}
+
+pub(super) fn parse_u64_env(key: &str, default: u64) -> Result<u64, String> {
+    match env::var(key) {
+        Ok(raw) => raw
+            .parse::<u64>()
+            .map_err(|_| format!("invalid integer in env var {key}")),
+        Err(_) => Ok(default),
+    }
+}
+
+pub(super) fn parse_i32_env(key: &str, default: i32) -> Result<i32, String> {
+    match env::var(key) {
+        Ok(raw) => raw
+            .parse::<i32>()
+            .map_err(|_| format!("invalid integer in env var {key}")),
+        Err(_) => Ok(default),
+    }
+}
+
+pub(super) fn parse_bool_env(key: &str, default: bool) -> Result<bool, String> {
+    match env::var(key) {
+        Ok(raw) => {
+            let normalized = raw.trim().to_ascii_lowercase();
+            match normalized.as_str() {
+                "true" | "1" | "yes" | "on" => Ok(true),
+                "false" | "0" | "no" | "off" => Ok(false),
+                _ => Err(format!("invalid boolean in env var {key}")),
+            }
+        }
+        Err(_) => Ok(default),
+    }
+}
+
+pub(super) fn parse_list_env(key: &str, default: &[&str]) -> Vec<String> {
+    match env::var(key) {
+        Ok(raw) => parse_csv_list(raw),
+        Err(_) => default.iter().map(|item| (*item).to_string()).collect(),
+    }
+}
+
+pub(super) fn parse_list_env_with_fallback(key: &str, fallback: &[String]) -> Vec<String> {
+    match env::var(key) {
+        Ok(raw) => parse_csv_list(raw),
+        Err(_) => fallback.to_vec(),
+    }
+}
+
+pub(super) fn parse_csv_list(raw: String) -> Vec<String> {
+    let parsed = raw
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+
+    if parsed.is_empty() {
+        vec!["dev-local-enclave".to_string()]
+    } else {
+        parsed
+    }
+}
+
+pub(super) fn optional_trimmed_env(key: &str) -> Option<String> {
+    env::var(key).ok().and_then(|value| {
+        let trimmed = value.trim();
+        if trimmed.is_empty() {
+            None
+        } else {
+            Some(trimmed.to_string())
+        }
+    })
+}
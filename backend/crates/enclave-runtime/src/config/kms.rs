@@ -0,0 +1,92 @@
+use std::path::PathBuf;
+
+use shared::enclave_runtime::AlfredEnvironment;
+use shared::security::AwsCredentials;
+
+use super::env_vars::{optional_trimmed_env, require_env};
+use super::environment_from_env;
+
+/// Where connector secrets are wrapped before they are stored. `Disabled` leaves them
+/// protected by database encryption alone.
+#[derive(Debug, Clone)]
+pub(crate) enum KmsProviderConfig {
+    Disabled,
+    LocalStub,
+    Aws(AwsKmsConfig),
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct AwsKmsConfig {
+    pub(crate) region: String,
+    pub(crate) endpoint: Option<String>,
+    pub(crate) credentials: AwsCredentials,
+    /// Recipient key and attestation document that bind decrypts to this enclave.
+    pub(crate) recipient: Option<KmsRecipientPaths>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct KmsRecipientPaths {
+    pub(crate) private_key_path: PathBuf,
+    pub(crate) attestation_document_path: PathBuf,
+}
+
+/// KMS backend alone, for unsealing private keys before the rest of the config is read.
+pub(crate) fn kms_provider_from_env() -> Result<KmsProviderConfig, String> {
+    parse_kms_provider_config(environment_from_env()?)
+}
+
+/// Local runs default to the stub so the wrap path is exercised. Other environments must set
+/// `KMS_PROVIDER=aws`: the stub is rejected here and `disabled` by
+/// [`validate_non_local_security_posture`].
+pub(super) fn parse_kms_provider_config(
+    environment: AlfredEnvironment,
+) -> Result<KmsProviderConfig, String> {
+    let local = matches!(environment, AlfredEnvironment::Local);
+    let provider = optional_trimmed_env("KMS_PROVIDER")
+        .unwrap_or_else(|| if local { "local" } else { "disabled" }.to_string());
+
+    match provider.as_str() {
+        "disabled" => Ok(KmsProviderConfig::Disabled),
+        "local" if local => Ok(KmsProviderConfig::LocalStub),
+        "local" => Err("KMS_PROVIDER=local is only allowed when ALFRED_ENV=local".to_string()),
+        "aws" => {
+            let recipient = match (
+                optional_trimmed_env("KMS_RECIPIENT_PRIVATE_KEY_PATH"),
+                optional_trimmed_env("KMS_RECIPIENT_ATTESTATION_DOCUMENT_PATH"),
+            ) {
+                (Some(private_key_path), Some(attestation_document_path)) => {
+                    Some(KmsRecipientPaths {
+                        private_key_path: private_key_path.into(),
+                        attestation_document_path: attestation_document_path.into(),
+                    })
+                }
+                (None, None) if local => None,
+                (None, None) => {
+                    return Err(
+                        "KMS_RECIPIENT_PRIVATE_KEY_PATH and KMS_RECIPIENT_ATTESTATION_DOCUMENT_PATH are required outside local env"
+                            .to_string(),
+                    );
+                }
+                _ => {
+                    return Err(
+                        "KMS_RECIPIENT_PRIVATE_KEY_PATH and KMS_RECIPIENT_ATTESTATION_DOCUMENT_PATH must be set together"
+                            .to_string(),
+                    );
+                }
+            };
+            Ok(KmsProviderConfig::Aws(AwsKmsConfig {
+                region: require_env("AWS_REGION")?,
+                endpoint: optional_trimmed_env("KMS_ENDPOINT"),
+                credentials: AwsCredentials {
+                    access_key_id: require_env("AWS_ACCESS_KEY_ID")?,
+                    secret_access_key: require_env("AWS_SECRET_ACCESS_KEY")?,
+                    session_token: optional_trimmed_env("AWS_SESSION_TOKEN"),
+                },
+                recipient,
+            }))
+        }
+        other => Err(format!(
+            "KMS_PROVIDER must be one of disabled, local, aws (got {other})"
+        )),
+    }
+}
@@ -0,0 +1,125 @@
+use base64::Engine as _;
+use shared::enclave_runtime::AlfredEnvironment;
+
+use super::env_vars::{optional_trimmed_env, parse_u64_env};
+use super::{
+    DEFAULT_ASSISTANT_INGRESS_KEY_SYNC_INTERVAL_SECONDS,
+    DEFAULT_ASSISTANT_INGRESS_KEY_WRAPPING_KEY_ID,
+    DEFAULT_ASSISTANT_INGRESS_PREVIOUS_KEY_GRACE_SECONDS,
+};
+
+/// Scheduled ingress key rotation. Rotated private keys are wrapped with `wrapping_key`, which
+/// is released to the enclave at startup and never leaves it, before they are persisted.
+/// Envelopes stored at rest (automation prompts, departure home locations) stay readable only
+/// while their key is active or previous, so they must be re-sealed before that window closes.
+#[derive(Clone)]
+pub(crate) struct IngressKeyRotationConfig {
+    pub(crate) interval_seconds: u64,
+    pub(crate) previous_key_grace_seconds: u64,
+    pub(crate) sync_interval_seconds: u64,
+    pub(crate) wrapping_key_id: String,
+    pub(crate) wrapping_key: [u8; 32],
+}
+
+impl std::fmt::Debug for IngressKeyRotationConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IngressKeyRotationConfig")
+            .field("interval_seconds", &self.interval_seconds)
+            .field(
+                "previous_key_grace_seconds",
+                &self.previous_key_grace_seconds,
+            )
+            .field("sync_interval_seconds", &self.sync_interval_seconds)
+            .field("wrapping_key_id", &self.wrapping_key_id)
+            .field("wrapping_key", &"<redacted>")
+            .finish()
+    }
+}
+
+/// Rotation is off unless `ASSISTANT_INGRESS_KEY_ROTATION_INTERVAL_SECONDS` is set. Outside
+/// local runs the wrapping key must be supplied explicitly.
+pub(super) fn parse_ingress_key_rotation_config(
+    environment: AlfredEnvironment,
+) -> Result<Option<IngressKeyRotationConfig>, String> {
+    let interval_seconds = parse_u64_env("ASSISTANT_INGRESS_KEY_ROTATION_INTERVAL_SECONDS", 0)?;
+    if interval_seconds == 0 {
+        return Ok(None);
+    }
+
+    let previous_key_grace_seconds = parse_u64_env(
+        "ASSISTANT_INGRESS_PREVIOUS_KEY_GRACE_SECONDS",
+        DEFAULT_ASSISTANT_INGRESS_PREVIOUS_KEY_GRACE_SECONDS,
+    )?;
+    if previous_key_grace_seconds == 0 || previous_key_grace_seconds >= interval_seconds {
+        return Err(
+            "ASSISTANT_INGRESS_PREVIOUS_KEY_GRACE_SECONDS must be > 0 and shorter than ASSISTANT_INGRESS_KEY_ROTATION_INTERVAL_SECONDS"
+                .to_string(),
+        );
+    }
+    let sync_interval_seconds = parse_u64_env(
+        "ASSISTANT_INGRESS_KEY_SYNC_INTERVAL_SECONDS",
+        DEFAULT_ASSISTANT_INGRESS_KEY_SYNC_INTERVAL_SECONDS,
+    )?;
+    if sync_interval_seconds == 0 {
+        return Err("ASSISTANT_INGRESS_KEY_SYNC_INTERVAL_SECONDS must be > 0".to_string());
+    }
+
+    let wrapping_key_id = optional_trimmed_env("ASSISTANT_INGRESS_KEY_WRAPPING_KEY_ID")
+        .unwrap_or_else(|| DEFAULT_ASSISTANT_INGRESS_KEY_WRAPPING_KEY_ID.to_string());
+    let wrapping_key = if let Some(encoded) =
+        optional_trimmed_env("ASSISTANT_INGRESS_KEY_WRAPPING_KEY")
+    {
+        decode_symmetric_key(encoded.as_str(), "ASSISTANT_INGRESS_KEY_WRAPPING_KEY")?
+    } else if matches!(environment, AlfredEnvironment::Local) {
+        [13_u8; 32]
+    } else {
+        return Err(
+            "ASSISTANT_INGRESS_KEY_WRAPPING_KEY is required outside local environment when key rotation is enabled"
+                .to_string(),
+        );
+    };
+
+    Ok(Some(IngressKeyRotationConfig {
+        interval_seconds,
+        previous_key_grace_seconds,
+        sync_interval_seconds,
+        wrapping_key_id,
+        wrapping_key,
+    }))
+}
+
+pub(super) fn decode_signing_key_bytes(encoded_key: &str) -> Result<[u8; 32], String> {
+    let key_bytes = base64::engine::general_purpose::STANDARD
+        .decode(encoded_key.as_bytes())
+        .map_err(|_| {
+            "TEE_ATTESTATION_SIGNING_PRIVATE_KEY must be valid base64 for a 32-byte Ed25519 key"
+                .to_string()
+        })?;
+
+    key_bytes.try_into().map_err(|_| {
+        "TEE_ATTESTATION_SIGNING_PRIVATE_KEY must decode to exactly 32 bytes".to_string()
+    })
+}
+
+pub(super) fn decode_x25519_private_key(
+    encoded_key: &str,
+    key_name: &str,
+) -> Result<[u8; 32], String> {
+    let key_bytes = base64::engine::general_purpose::STANDARD
+        .decode(encoded_key.as_bytes())
+        .map_err(|_| format!("{key_name} must be valid base64 for a 32-byte X25519 key"))?;
+
+    key_bytes
+        .try_into()
+        .map_err(|_| format!("{key_name} must decode to exactly 32 bytes"))
+}
+
+pub(super) fn decode_symmetric_key(encoded_key: &str, key_name: &str) -> Result<[u8; 32], String> {
+    let key_bytes = base64::engine::general_purpose::STANDARD
+        .decode(encoded_key.as_bytes())
+        .map_err(|_| format!("{key_name} must be valid base64 for a 32-byte key"))?;
+
+    key_bytes
+        .try_into()
+        .map_err(|_| format!("{key_name} must decode to exactly 32 bytes"))
+}
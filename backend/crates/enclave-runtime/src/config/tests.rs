@@ -1,13 +1,24 @@
+use std::time::Duration;
+
+use base64::Engine as _;
+use ed25519_dalek::SigningKey;
 use shared::assistant_crypto::{
     ASSISTANT_ENCRYPTION_ALGORITHM_X25519_CHACHA20POLY1305, AssistantIngressKeyMaterial,
     AssistantIngressKeyring, derive_public_key_b64,
 };
+use shared::audit_redaction::AuditRedactionPolicy;
+use shared::audit_signing::{AuditEventSigningPayload, verify_audit_event_signature};
 use shared::enclave_runtime::AssistantAttestedKeyChallengeRequest;
 use shared::enclave_runtime::{AlfredEnvironment, AttestationChallengeRequest, EnclaveRuntimeMode};
+use shared::outbound_rate_limit::{OutboundProviderLimit, OutboundRateLimitConfig};
+use shared::repos::DataEncryptionKeyring;
+use shared::security::AwsCredentials;
+use uuid::Uuid;
 
+use super::kms::AwsKmsConfig;
 use super::{
-    AttestationSource, DEFAULT_ASSISTANT_INGRESS_SESSION_TTL_SECONDS, RuntimeConfig,
-    validate_non_local_runtime_base_url, validate_non_local_security_posture,
+    AttestationSource, DEFAULT_ASSISTANT_INGRESS_SESSION_TTL_SECONDS, KmsProviderConfig,
+    RuntimeConfig, validate_non_local_runtime_base_url, validate_non_local_security_posture,
 };
 
 fn build_config(mode: EnclaveRuntimeMode) -> RuntimeConfig {
@@ -19,17 +30,23 @@ fn build_config(mode: EnclaveRuntimeMode) -> RuntimeConfig {
         measurement: "dev-local-enclave".to_string(),
         database_url: "postgres://localhost/alfred".to_string(),
         database_max_connections: 5,
-        data_encryption_key: "01234567890123456789012345678901".to_string(),
+        data_encryption_keys: DataEncryptionKeyring::single(
+            "v1",
+            "01234567890123456789012345678901",
+        ),
+        audit_redaction_policy: AuditRedactionPolicy::default(),
         tee_attestation_required: false,
         tee_expected_runtime: "nitro".to_string(),
         tee_allowed_measurements: vec!["dev-local-enclave".to_string()],
         tee_attestation_public_key: None,
         tee_attestation_max_age_seconds: 300,
+        tee_attestation_cache_ttl_seconds: 30,
         tee_attestation_challenge_timeout_ms: 2000,
         tee_allow_insecure_dev_attestation: true,
         kms_key_id: "kms/local/alfred-refresh-token".to_string(),
         kms_key_version: 1,
         kms_allowed_measurements: vec!["dev-local-enclave".to_string()],
+        kms_provider: KmsProviderConfig::LocalStub,
         enclave_runtime_base_url: "http://127.0.0.1:8181".to_string(),
         oauth: shared::enclave::GoogleEnclaveOauthConfig {
             client_id: "client-id".to_string(),
@@ -37,10 +54,17 @@ fn build_config(mode: EnclaveRuntimeMode) -> RuntimeConfig {
             token_url: "https://oauth2.googleapis.com/token".to_string(),
             revoke_url: "https://oauth2.googleapis.com/revoke".to_string(),
         },
-        enclave_rpc_auth: shared::enclave::EnclaveRpcAuthConfig {
-            shared_secret: "local-dev-enclave-rpc-secret".to_string(),
+        enclave_rpc_auth: super::EnclaveRpcVerifierConfig {
+            caller_keys: shared::enclave::EnclaveRpcCallerKeys::local_dev(&[
+                "api-server",
+                "worker",
+            ]),
+            secondary_caller_keys: None,
             max_clock_skew_seconds: 30,
         },
+        enclave_rpc_security_mode: shared::enclave::EnclaveRpcSecurityMode::Signed,
+        rpc_server_tls: None,
+        rpc_client_tls: None,
         assistant_ingress_keys: AssistantIngressKeyring {
             active: AssistantIngressKeyMaterial {
                 key_id: "assistant-ingress-v1".to_string(),
@@ -50,13 +74,52 @@ fn build_config(mode: EnclaveRuntimeMode) -> RuntimeConfig {
             },
             previous: None,
         },
+        assistant_ingress_key_rotation: None,
         assistant_ingress_key_ttl_seconds: 900,
         assistant_session_ttl_seconds: DEFAULT_ASSISTANT_INGRESS_SESSION_TTL_SECONDS,
+        llm_warmup_enabled: false,
+        llm_warmup_retry_interval_ms: 5_000,
+        outbound_rate_limits: OutboundRateLimitConfig {
+            google: OutboundProviderLimit {
+                calls_per_second: 50,
+                burst: 100,
+            },
+            openrouter: OutboundProviderLimit {
+                calls_per_second: 20,
+                burst: 40,
+            },
+            anthropic: OutboundProviderLimit {
+                calls_per_second: 20,
+                burst: 40,
+            },
+            openai: OutboundProviderLimit {
+                calls_per_second: 20,
+                burst: 40,
+            },
+            bedrock: OutboundProviderLimit {
+                calls_per_second: 20,
+                burst: 40,
+            },
+            max_wait: Duration::from_millis(2_000),
+        },
         attestation_source: AttestationSource::Missing,
         attestation_signing_private_key: [7_u8; 32],
     }
 }
 
+fn aws_kms_provider() -> KmsProviderConfig {
+    KmsProviderConfig::Aws(AwsKmsConfig {
+        region: "us-east-1".to_string(),
+        endpoint: None,
+        credentials: AwsCredentials {
+            access_key_id: "AKIDEXAMPLE".to_string(),
+            secret_access_key: "secret".to_string(),
+            session_token: None,
+        },
+        recipient: None,
+    })
+}
+
 #[test]
 fn assistant_session_ttl_default_targets_sixty_days() {
     assert_eq!(
@@ -103,12 +166,13 @@ fn challenge_response_is_signed_and_echoes_challenge_fields() {
     };
 
     let response = config
-        .attestation_challenge_response(challenge)
+        .attestation_challenge_response(challenge, Some("ab12".to_string()))
         .expect("challenge should succeed");
 
     assert_eq!(response.challenge_nonce, "nonce-1");
     assert_eq!(response.operation_purpose, "decrypt");
     assert_eq!(response.request_id, "req-1");
+    assert_eq!(response.tls_certificate_sha256.as_deref(), Some("ab12"));
     assert!(response.signature.is_some());
 }
 
@@ -124,7 +188,11 @@ fn assistant_attested_key_response_is_signed_and_binds_key_fields() {
     };
 
     let response = config
-        .assistant_attested_key_challenge_response(challenge)
+        .assistant_attested_key_challenge_response(
+            challenge,
+            &config.assistant_ingress_keys.active,
+            None,
+        )
         .expect("assistant key challenge should succeed");
 
     assert_eq!(response.challenge_nonce, "nonce-key-1");
@@ -138,6 +206,62 @@ fn assistant_attested_key_response_is_signed_and_binds_key_fields() {
     assert!(response.signature.is_some());
 }
 
+#[test]
+fn audit_event_signature_verifies_with_the_attestation_key() {
+    let config = build_config(EnclaveRuntimeMode::DevShim);
+    let public_key = base64::engine::general_purpose::STANDARD.encode(
+        SigningKey::from_bytes(&config.attestation_signing_private_key)
+            .verifying_key()
+            .to_bytes(),
+    );
+    let mut event = AuditEventSigningPayload {
+        event_id: Uuid::new_v4(),
+        user_id: Uuid::new_v4(),
+        event_type: "CONNECTOR_REVOKED".to_string(),
+        entry_hash: "0f".repeat(32),
+    };
+
+    let signature = config
+        .sign_audit_event(&event)
+        .expect("connector events should be signed");
+    assert!(verify_audit_event_signature(&public_key, &signature, &event).is_ok());
+
+    event.entry_hash = "zz".repeat(32);
+    assert!(config.sign_audit_event(&event).is_err());
+
+    event.entry_hash = "0f".repeat(32);
+    event.event_type = "DEVICE_REGISTERED".to_string();
+    let err = config
+        .sign_audit_event(&event)
+        .expect_err("low-value events should not be signed");
+    assert!(
+        err.contains("not a signed event type"),
+        "unexpected error: {err}"
+    );
+}
+
+#[test]
+fn assistant_attested_key_expiry_stops_at_the_scheduled_rotation() {
+    let config = build_config(EnclaveRuntimeMode::DevShim);
+    let usable_until = chrono::Utc::now().timestamp() + 60;
+    let challenge = AssistantAttestedKeyChallengeRequest {
+        challenge_nonce: "nonce-key-2".to_string(),
+        issued_at: chrono::Utc::now().timestamp() - 2,
+        expires_at: chrono::Utc::now().timestamp() + 30,
+        request_id: "req-key-2".to_string(),
+    };
+
+    let response = config
+        .assistant_attested_key_challenge_response(
+            challenge,
+            &config.assistant_ingress_keys.active,
+            Some(usable_until),
+        )
+        .expect("assistant key challenge should succeed");
+
+    assert_eq!(response.key_expires_at, usable_until);
+}
+
 #[test]
 fn non_local_security_posture_rejects_insecure_attestation_flags() {
     let err = validate_non_local_security_posture(
@@ -147,6 +271,7 @@ fn non_local_security_posture_rejects_insecure_attestation_flags() {
         &["mr-enclave-prod-a".to_string()],
         &["mr-enclave-prod-a".to_string()],
         "https://enclave.internal:8181",
+        &aws_kms_provider(),
     )
     .expect_err("insecure non-local posture should fail");
 
@@ -162,16 +287,39 @@ fn non_local_security_posture_rejects_dev_measurement() {
         &["dev-local-enclave".to_string()],
         &["mr-enclave-stage-a".to_string()],
         "https://enclave.internal:8181",
+        &aws_kms_provider(),
     )
     .expect_err("dev measurement should fail outside local");
 
     assert!(err.contains("TEE_ALLOWED_MEASUREMENTS"));
 }
 
+#[test]
+fn non_local_security_posture_requires_aws_kms() {
+    let validate = |kms_provider: &KmsProviderConfig| {
+        validate_non_local_security_posture(
+            AlfredEnvironment::Production,
+            true,
+            false,
+            &["mr-enclave-prod-a".to_string()],
+            &["mr-enclave-prod-a".to_string()],
+            "https://enclave.internal:8181",
+            kms_provider,
+        )
+    };
+
+    let err = validate(&KmsProviderConfig::Disabled).expect_err("disabled KMS should fail");
+    assert!(err.contains("KMS_PROVIDER"));
+    validate(&aws_kms_provider()).expect("aws KMS should pass");
+}
+
 #[test]
 fn non_local_runtime_base_url_allows_https_or_loopback_http() {
     validate_non_local_runtime_base_url("https://enclave.internal:8181")
         .expect("https URL should pass");
     validate_non_local_runtime_base_url("http://127.0.0.1:8181")
         .expect("loopback http URL should pass");
+    validate_non_local_runtime_base_url("vsock://16:8181").expect("vsock URL should pass");
+    validate_non_local_runtime_base_url("vsock://16")
+        .expect_err("vsock URL without a port should fail");
 }
@@ -4,24 +4,35 @@ use axum::extract::State;
 use axum::http::{HeaderMap, StatusCode};
 use axum::response::{IntoResponse, Response};
 use serde::Serialize;
-use serde_json::{Value, json};
+use serde_json::Value;
+use shared::assistant_crypto::decrypt_assistant_request;
 use shared::enclave::{
-    ENCLAVE_RPC_CONTRACT_VERSION, ENCLAVE_RPC_PATH_COMPLETE_GOOGLE_CONNECT,
-    ENCLAVE_RPC_PATH_EXCHANGE_GOOGLE_TOKEN, ENCLAVE_RPC_PATH_EXECUTE_AUTOMATION,
+    ENCLAVE_RPC_CONTRACT_VERSION, ENCLAVE_RPC_PATH_COMPLETE_CALDAV_CONNECT,
+    ENCLAVE_RPC_PATH_COMPLETE_GOOGLE_CONNECT, ENCLAVE_RPC_PATH_COMPLETE_IMAP_CONNECT,
+    ENCLAVE_RPC_PATH_DRAFT_AUTOMATION, ENCLAVE_RPC_PATH_EXCHANGE_GOOGLE_TOKEN,
+    ENCLAVE_RPC_PATH_EXECUTE_AUTOMATION, ENCLAVE_RPC_PATH_EXPORT_ASSISTANT_SESSIONS,
     ENCLAVE_RPC_PATH_FETCH_ASSISTANT_ATTESTED_KEY, ENCLAVE_RPC_PATH_FETCH_GOOGLE_CALENDAR_EVENTS,
     ENCLAVE_RPC_PATH_FETCH_GOOGLE_URGENT_EMAIL_CANDIDATES, ENCLAVE_RPC_PATH_GENERATE_MORNING_BRIEF,
-    ENCLAVE_RPC_PATH_GENERATE_URGENT_EMAIL_SUMMARY, ENCLAVE_RPC_PATH_PROCESS_ASSISTANT_QUERY,
-    ENCLAVE_RPC_PATH_REVOKE_GOOGLE_TOKEN, EnclaveRpcCompleteGoogleConnectRequest,
-    EnclaveRpcCompleteGoogleConnectResponse, EnclaveRpcExchangeGoogleTokenRequest,
+    ENCLAVE_RPC_PATH_GENERATE_URGENT_EMAIL_SUMMARY, ENCLAVE_RPC_PATH_PLAN_DEPARTURE_ALERT,
+    ENCLAVE_RPC_PATH_PROCESS_ASSISTANT_QUERY, ENCLAVE_RPC_PATH_REVOKE_GOOGLE_TOKEN,
+    ENCLAVE_RPC_PATH_SIGN_AUDIT_EVENT, EnclaveRpcCompleteCaldavConnectRequest,
+    EnclaveRpcCompleteCaldavConnectResponse, EnclaveRpcCompleteGoogleConnectRequest,
+    EnclaveRpcCompleteGoogleConnectResponse, EnclaveRpcCompleteImapConnectRequest,
+    EnclaveRpcCompleteImapConnectResponse, EnclaveRpcDraftAutomationRequest,
+    EnclaveRpcErrorEnvelope, EnclaveRpcExchangeGoogleTokenRequest,
     EnclaveRpcExchangeGoogleTokenResponse, EnclaveRpcExecuteAutomationRequest,
-    EnclaveRpcFetchAssistantAttestedKeyRequest, EnclaveRpcFetchAssistantAttestedKeyResponse,
-    EnclaveRpcFetchGoogleCalendarEventsRequest, EnclaveRpcFetchGoogleCalendarEventsResponse,
-    EnclaveRpcFetchGoogleUrgentEmailCandidatesRequest,
+    EnclaveRpcExportAssistantSessionsRequest, EnclaveRpcFetchAssistantAttestedKeyRequest,
+    EnclaveRpcFetchAssistantAttestedKeyResponse, EnclaveRpcFetchGoogleCalendarEventsRequest,
+    EnclaveRpcFetchGoogleCalendarEventsResponse, EnclaveRpcFetchGoogleUrgentEmailCandidatesRequest,
     EnclaveRpcFetchGoogleUrgentEmailCandidatesResponse, EnclaveRpcGenerateMorningBriefRequest,
-    EnclaveRpcGenerateUrgentEmailSummaryRequest, EnclaveRpcProcessAssistantQueryRequest,
-    EnclaveRpcRevokeGoogleTokenRequest, EnclaveRpcRevokeGoogleTokenResponse,
+    EnclaveRpcGenerateUrgentEmailSummaryRequest, EnclaveRpcPlanDepartureAlertRequest,
+    EnclaveRpcProcessAssistantQueryRequest, EnclaveRpcRevokeGoogleTokenRequest,
+    EnclaveRpcRevokeGoogleTokenResponse, EnclaveRpcSignAuditEventRequest,
+    EnclaveRpcSignAuditEventResponse,
 };
 use shared::enclave_runtime::{AttestationChallengeRequest, AttestationChallengeResponse};
+use shared::models::{ApiErrorCode, ErrorBody};
+use tracing::Instrument;
 
 use crate::RuntimeState;
 
@@ -48,48 +59,66 @@ pub(crate) async fn healthz(State(state): State<RuntimeState>) -> Json<HealthRes
     })
 }
 
+/// Readiness for load balancers: unlike `/healthz`, stays unavailable until the LLM provider
+/// warm-up has verified the provider path.
+pub(crate) async fn readyz(State(state): State<RuntimeState>) -> Response {
+    if !state.llm_readiness.is_ready() {
+        return error_response(
+            ApiErrorCode::LlmProviderNotReady,
+            "LLM provider warm-up has not completed",
+        )
+        .into_response();
+    }
+
+    Json(HealthResponse {
+        status: "ready",
+        environment: state.config.environment.as_str(),
+        mode: state.config.mode.as_str(),
+    })
+    .into_response()
+}
+
 pub(crate) async fn attestation_document(
     State(state): State<RuntimeState>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+) -> Result<Json<Value>, (StatusCode, Json<ErrorBody>)> {
     state
         .config
         .attestation_document()
         .map(Json)
-        .map_err(|err| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({
-                    "code": "attestation_document_unavailable",
-                    "message": err,
-                })),
-            )
-        })
+        .map_err(|err| error_response(ApiErrorCode::AttestationDocumentUnavailable, err))
 }
 
 pub(crate) async fn attestation_challenge(
     State(state): State<RuntimeState>,
     Json(challenge): Json<AttestationChallengeRequest>,
-) -> Result<Json<AttestationChallengeResponse>, (StatusCode, Json<Value>)> {
+) -> Result<Json<AttestationChallengeResponse>, (StatusCode, Json<ErrorBody>)> {
     state
         .config
-        .attestation_challenge_response(challenge)
+        .attestation_challenge_response(
+            challenge,
+            state
+                .rpc_tls
+                .as_ref()
+                .map(|rpc_tls| rpc_tls.certificate_sha256()),
+        )
         .map(Json)
         .map_err(|err| {
-            let status = if err.starts_with("invalid challenge") {
-                StatusCode::BAD_REQUEST
+            let code = if err.starts_with("invalid challenge") {
+                ApiErrorCode::InvalidAttestationChallenge
             } else {
-                StatusCode::INTERNAL_SERVER_ERROR
+                ApiErrorCode::AttestationChallengeFailed
             };
-            (
-                status,
-                Json(json!({
-                    "code": "attestation_challenge_failed",
-                    "message": err,
-                })),
-            )
+            error_response(code, err)
         })
 }
 
+/// Catalog error body, unwrapped: these routes answer the API server and operators, not the app.
+fn error_response(code: ApiErrorCode, message: impl Into<String>) -> (StatusCode, Json<ErrorBody>) {
+    let status =
+        StatusCode::from_u16(code.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+    (status, Json(ErrorBody::new(code, message)))
+}
+
 pub(crate) async fn exchange_google_access_token(
     State(state): State<RuntimeState>,
     headers: HeaderMap,
@@ -100,7 +129,9 @@ pub(crate) async fn exchange_google_access_token(
         &headers,
         ENCLAVE_RPC_PATH_EXCHANGE_GOOGLE_TOKEN,
         &body,
-    ) {
+    )
+    .await
+    {
         Ok(request) => request,
         Err(rejection) => return rejection.into_response(),
     };
@@ -132,7 +163,9 @@ pub(crate) async fn complete_google_connect(
         &headers,
         ENCLAVE_RPC_PATH_COMPLETE_GOOGLE_CONNECT,
         &body,
-    ) {
+    )
+    .await
+    {
         Ok(request) => request,
         Err(rejection) => return rejection.into_response(),
     };
@@ -154,6 +187,137 @@ pub(crate) async fn complete_google_connect(
     }
 }
 
+pub(crate) async fn complete_caldav_connect(
+    State(state): State<RuntimeState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let request = match validate_request::<EnclaveRpcCompleteCaldavConnectRequest>(
+        &state,
+        &headers,
+        ENCLAVE_RPC_PATH_COMPLETE_CALDAV_CONNECT,
+        &body,
+    )
+    .await
+    {
+        Ok(request) => request,
+        Err(rejection) => return rejection.into_response(),
+    };
+
+    let envelope = shared::models::AssistantEncryptedRequestEnvelope {
+        version: request.app_password_envelope.version.clone(),
+        algorithm: request.app_password_envelope.algorithm.clone(),
+        key_id: request.app_password_envelope.key_id.clone(),
+        request_id: request.app_password_envelope.request_id.clone(),
+        client_ephemeral_public_key: request
+            .app_password_envelope
+            .client_ephemeral_public_key
+            .clone(),
+        nonce: request.app_password_envelope.nonce.clone(),
+        ciphertext: request.app_password_envelope.ciphertext.clone(),
+    };
+    let app_password = match decrypt_assistant_request(&state.ingress_keys.keyring(), &envelope) {
+        Ok((plaintext, _)) => plaintext.query,
+        Err(_) => {
+            return rpc::error_response(
+                StatusCode::BAD_REQUEST,
+                EnclaveRpcErrorEnvelope::new(
+                    Some(request.request_id),
+                    "invalid_request_payload",
+                    "CalDAV app password envelope decrypt failed",
+                    false,
+                ),
+            );
+        }
+    };
+
+    let result = state
+        .enclave_service
+        .complete_caldav_connect(
+            request.user_id,
+            &request.server_url,
+            &request.username,
+            app_password,
+        )
+        .await;
+
+    match result {
+        Ok(connect_response) => Json(EnclaveRpcCompleteCaldavConnectResponse {
+            contract_version: ENCLAVE_RPC_CONTRACT_VERSION.to_string(),
+            request_id: request.request_id,
+            connector_id: connect_response.connector_id,
+        })
+        .into_response(),
+        Err(err) => rpc::map_rpc_service_error(err, Some(request.request_id)).into_response(),
+    }
+}
+
+pub(crate) async fn complete_imap_connect(
+    State(state): State<RuntimeState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let request = match validate_request::<EnclaveRpcCompleteImapConnectRequest>(
+        &state,
+        &headers,
+        ENCLAVE_RPC_PATH_COMPLETE_IMAP_CONNECT,
+        &body,
+    )
+    .await
+    {
+        Ok(request) => request,
+        Err(rejection) => return rejection.into_response(),
+    };
+
+    let envelope = shared::models::AssistantEncryptedRequestEnvelope {
+        version: request.password_envelope.version.clone(),
+        algorithm: request.password_envelope.algorithm.clone(),
+        key_id: request.password_envelope.key_id.clone(),
+        request_id: request.password_envelope.request_id.clone(),
+        client_ephemeral_public_key: request
+            .password_envelope
+            .client_ephemeral_public_key
+            .clone(),
+        nonce: request.password_envelope.nonce.clone(),
+        ciphertext: request.password_envelope.ciphertext.clone(),
+    };
+    let password = match decrypt_assistant_request(&state.ingress_keys.keyring(), &envelope) {
+        Ok((plaintext, _)) => plaintext.query,
+        Err(_) => {
+            return rpc::error_response(
+                StatusCode::BAD_REQUEST,
+                EnclaveRpcErrorEnvelope::new(
+                    Some(request.request_id),
+                    "invalid_request_payload",
+                    "IMAP password envelope decrypt failed",
+                    false,
+                ),
+            );
+        }
+    };
+
+    let result = state
+        .enclave_service
+        .complete_imap_connect(
+            request.user_id,
+            &request.host,
+            request.port,
+            &request.username,
+            password,
+        )
+        .await;
+
+    match result {
+        Ok(connect_response) => Json(EnclaveRpcCompleteImapConnectResponse {
+            contract_version: ENCLAVE_RPC_CONTRACT_VERSION.to_string(),
+            request_id: request.request_id,
+            connector_id: connect_response.connector_id,
+        })
+        .into_response(),
+        Err(err) => rpc::map_rpc_service_error(err, Some(request.request_id)).into_response(),
+    }
+}
+
 pub(crate) async fn revoke_google_connector_token(
     State(state): State<RuntimeState>,
     headers: HeaderMap,
@@ -164,7 +328,9 @@ pub(crate) async fn revoke_google_connector_token(
         &headers,
         ENCLAVE_RPC_PATH_REVOKE_GOOGLE_TOKEN,
         &body,
-    ) {
+    )
+    .await
+    {
         Ok(request) => request,
         Err(rejection) => return rejection.into_response(),
     };
@@ -195,7 +361,9 @@ pub(crate) async fn fetch_google_calendar_events(
         &headers,
         ENCLAVE_RPC_PATH_FETCH_GOOGLE_CALENDAR_EVENTS,
         &body,
-    ) {
+    )
+    .await
+    {
         Ok(request) => request,
         Err(rejection) => return rejection.into_response(),
     };
@@ -232,7 +400,9 @@ pub(crate) async fn fetch_google_urgent_email_candidates(
         &headers,
         ENCLAVE_RPC_PATH_FETCH_GOOGLE_URGENT_EMAIL_CANDIDATES,
         &body,
-    ) {
+    )
+    .await
+    {
         Ok(request) => request,
         Err(rejection) => return rejection.into_response(),
     };
@@ -264,11 +434,22 @@ pub(crate) async fn fetch_assistant_attested_key(
         &headers,
         ENCLAVE_RPC_PATH_FETCH_ASSISTANT_ATTESTED_KEY,
         &body,
-    ) {
+    )
+    .await
+    {
         Ok(request) => request,
         Err(rejection) => return rejection.into_response(),
     };
 
+    let rotation_config = state.config.assistant_ingress_key_rotation.as_ref();
+    let rotation = state
+        .ingress_keys
+        .rotation_status(rotation_config, chrono::Utc::now().timestamp());
+    let key_usable_until = rotation.next_rotation_at.zip(rotation_config).map(
+        |(next_rotation_at, rotation_config)| {
+            next_rotation_at.saturating_add(rotation_config.previous_key_grace_seconds as i64)
+        },
+    );
     let challenge_response = state.config.assistant_attested_key_challenge_response(
         shared::enclave_runtime::AssistantAttestedKeyChallengeRequest {
             challenge_nonce: request.challenge_nonce,
@@ -276,6 +457,8 @@ pub(crate) async fn fetch_assistant_attested_key(
             expires_at: request.expires_at,
             request_id: request.request_id.clone(),
         },
+        &state.ingress_keys.keyring().active,
+        key_usable_until,
     );
 
     match challenge_response {
@@ -293,6 +476,7 @@ pub(crate) async fn fetch_assistant_attested_key(
             public_key: response.public_key,
             key_expires_at: response.key_expires_at,
             signature: response.signature,
+            rotation: Some(rotation),
         })
         .into_response(),
         Err(err) => rpc::reject(
@@ -318,7 +502,9 @@ pub(crate) async fn process_assistant_query(
         &headers,
         ENCLAVE_RPC_PATH_PROCESS_ASSISTANT_QUERY,
         &body,
-    ) {
+    )
+    .await
+    {
         Ok(request) => request,
         Err(rejection) => return rejection.into_response(),
     };
@@ -336,7 +522,9 @@ pub(crate) async fn generate_morning_brief(
         &headers,
         ENCLAVE_RPC_PATH_GENERATE_MORNING_BRIEF,
         &body,
-    ) {
+    )
+    .await
+    {
         Ok(request) => request,
         Err(rejection) => return rejection.into_response(),
     };
@@ -354,7 +542,9 @@ pub(crate) async fn generate_urgent_email_summary(
         &headers,
         ENCLAVE_RPC_PATH_GENERATE_URGENT_EMAIL_SUMMARY,
         &body,
-    ) {
+    )
+    .await
+    {
         Ok(request) => request,
         Err(rejection) => return rejection.into_response(),
     };
@@ -372,10 +562,121 @@ pub(crate) async fn execute_automation(
         &headers,
         ENCLAVE_RPC_PATH_EXECUTE_AUTOMATION,
         &body,
-    ) {
+    )
+    .await
+    {
+        Ok(request) => request,
+        Err(rejection) => return rejection.into_response(),
+    };
+
+    let span = job_rpc_span(&request.request_id, request.job_id);
+    assistant::execute_automation(state, request)
+        .instrument(span)
+        .await
+}
+
+pub(crate) async fn plan_departure_alert(
+    State(state): State<RuntimeState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let request = match validate_request::<EnclaveRpcPlanDepartureAlertRequest>(
+        &state,
+        &headers,
+        ENCLAVE_RPC_PATH_PLAN_DEPARTURE_ALERT,
+        &body,
+    )
+    .await
+    {
+        Ok(request) => request,
+        Err(rejection) => return rejection.into_response(),
+    };
+
+    let span = job_rpc_span(&request.request_id, request.job_id);
+    assistant::plan_departure_alert(state, request)
+        .instrument(span)
+        .await
+}
+
+pub(crate) async fn export_assistant_sessions(
+    State(state): State<RuntimeState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let request = match validate_request::<EnclaveRpcExportAssistantSessionsRequest>(
+        &state,
+        &headers,
+        ENCLAVE_RPC_PATH_EXPORT_ASSISTANT_SESSIONS,
+        &body,
+    )
+    .await
+    {
         Ok(request) => request,
         Err(rejection) => return rejection.into_response(),
     };
 
-    assistant::execute_automation(state, request).await
+    assistant::export_assistant_sessions(state, request).await
+}
+
+pub(crate) async fn draft_automation(
+    State(state): State<RuntimeState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let request = match validate_request::<EnclaveRpcDraftAutomationRequest>(
+        &state,
+        &headers,
+        ENCLAVE_RPC_PATH_DRAFT_AUTOMATION,
+        &body,
+    )
+    .await
+    {
+        Ok(request) => request,
+        Err(rejection) => return rejection.into_response(),
+    };
+
+    assistant::draft_automation(state, request).await
+}
+
+pub(crate) async fn sign_audit_event(
+    State(state): State<RuntimeState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let request = match validate_request::<EnclaveRpcSignAuditEventRequest>(
+        &state,
+        &headers,
+        ENCLAVE_RPC_PATH_SIGN_AUDIT_EVENT,
+        &body,
+    )
+    .await
+    {
+        Ok(request) => request,
+        Err(rejection) => return rejection.into_response(),
+    };
+
+    match state.config.sign_audit_event(&request.event) {
+        Ok(signature) => Json(EnclaveRpcSignAuditEventResponse {
+            contract_version: ENCLAVE_RPC_CONTRACT_VERSION.to_string(),
+            request_id: request.request_id,
+            signature,
+        })
+        .into_response(),
+        Err(err) => rpc::reject(
+            StatusCode::BAD_REQUEST,
+            shared::enclave::EnclaveRpcErrorEnvelope::new(
+                Some(request.request_id),
+                "invalid_request_payload",
+                err,
+                false,
+            ),
+        )
+        .into_response(),
+    }
+}
+
+/// Tags everything logged while serving a worker job RPC with the job it belongs to, so an
+/// enclave log line can be traced back to the job and forward to its deliveries.
+fn job_rpc_span(request_id: &str, job_id: Option<uuid::Uuid>) -> tracing::Span {
+    tracing::info_span!("job_rpc", request_id = %request_id, job_id = ?job_id)
 }
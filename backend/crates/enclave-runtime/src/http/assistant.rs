@@ -1,19 +1,26 @@
 use axum::response::Response;
 use shared::enclave::{
-    EnclaveRpcExecuteAutomationRequest, EnclaveRpcGenerateMorningBriefRequest,
-    EnclaveRpcGenerateUrgentEmailSummaryRequest, EnclaveRpcProcessAssistantQueryRequest,
+    EnclaveRpcDraftAutomationRequest, EnclaveRpcExecuteAutomationRequest,
+    EnclaveRpcExportAssistantSessionsRequest, EnclaveRpcGenerateMorningBriefRequest,
+    EnclaveRpcGenerateUrgentEmailSummaryRequest, EnclaveRpcPlanDepartureAlertRequest,
+    EnclaveRpcProcessAssistantQueryRequest,
 };
 
 use crate::RuntimeState;
 
 mod automation;
+mod automation_draft;
+mod condition;
+mod departure;
 mod mapping;
 mod memory;
 mod notifications;
 mod orchestrator;
 mod proactive;
 mod query;
+mod session_export;
 mod session_state;
+mod weekly_review;
 
 pub(super) async fn process_assistant_query(
     state: RuntimeState,
@@ -42,3 +49,24 @@ pub(super) async fn execute_automation(
 ) -> Response {
     automation::execute_automation(state, request).await
 }
+
+pub(super) async fn plan_departure_alert(
+    state: RuntimeState,
+    request: EnclaveRpcPlanDepartureAlertRequest,
+) -> Response {
+    departure::plan_departure_alert(state, request).await
+}
+
+pub(super) async fn export_assistant_sessions(
+    state: RuntimeState,
+    request: EnclaveRpcExportAssistantSessionsRequest,
+) -> Response {
+    session_export::export_assistant_sessions(state, request).await
+}
+
+pub(super) async fn draft_automation(
+    state: RuntimeState,
+    request: EnclaveRpcDraftAutomationRequest,
+) -> Response {
+    automation_draft::draft_automation(state, request).await
+}
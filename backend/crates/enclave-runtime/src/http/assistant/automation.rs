@@ -13,10 +13,10 @@ use shared::assistant_crypto::{
     decrypt_assistant_request,
 };
 use shared::enclave::{
-    AttestedIdentityPayload, ENCLAVE_RPC_CONTRACT_VERSION,
-    EnclaveAutomationEncryptedNotificationEnvelope, EnclaveAutomationNotificationArtifact,
-    EnclaveAutomationRecipientDevice, EnclaveRpcExecuteAutomationRequest,
-    EnclaveRpcExecuteAutomationResponse,
+    AttestedIdentityPayload, ENCLAVE_RPC_CONTRACT_VERSION, EnclaveAutomationConditionRequest,
+    EnclaveAutomationConditionResult, EnclaveAutomationEncryptedNotificationEnvelope,
+    EnclaveAutomationNotificationArtifact, EnclaveAutomationRecipientDevice,
+    EnclaveRpcExecuteAutomationRequest, EnclaveRpcExecuteAutomationResponse,
 };
 use shared::models::AssistantQueryCapability;
 use tracing::warn;
@@ -26,15 +26,41 @@ use super::orchestrator::AssistantOrchestratorResult;
 use crate::RuntimeState;
 use crate::http::rpc;
 
+mod templates;
+
 const AUTOMATION_NOTIFICATION_TITLE_MAX_CHARS: usize = 64;
 const AUTOMATION_PROMPT_MAX_CHARS: usize = 4_000;
 const AUTOMATION_NOTIFICATION_DEFAULT_TITLE: &str = "Task update";
 const AUTOMATION_NOTIFICATION_DEFAULT_BODY: &str = "Your scheduled task ran.";
 
 #[derive(Debug, Clone, Serialize)]
-struct AutomationNotificationPlaintext {
-    title: String,
-    body: String,
+pub(super) struct AutomationNotificationPlaintext {
+    pub(super) title: String,
+    pub(super) body: String,
+}
+
+struct GeneratedAutomationContent {
+    notification: NotificationContent,
+    report_plaintext: Option<Vec<u8>>,
+    action_source: &'static str,
+    output_source: &'static str,
+    capability: &'static str,
+    metadata: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum ArtifactPurpose {
+    Notification,
+    Report,
+}
+
+impl ArtifactPurpose {
+    fn label(self) -> &'static [u8] {
+        match self {
+            Self::Notification => b"notification",
+            Self::Report => b"report",
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -54,68 +80,108 @@ pub(super) async fn execute_automation(
     request: EnclaveRpcExecuteAutomationRequest,
 ) -> Response {
     let request_id = request.request_id.clone();
-    let (prompt_query, decrypted_key_id) = match decrypt_automation_prompt(&state, &request) {
-        Ok(result) => result,
-        Err(err) => {
-            return rpc::reject(
-                StatusCode::BAD_REQUEST,
-                shared::enclave::EnclaveRpcErrorEnvelope::new(
-                    Some(request_id),
-                    "invalid_request_payload",
-                    err,
-                    false,
-                ),
+    let (prompt_query, decrypted_key_id) =
+        match decrypt_automation_prompt(&state, &request.prompt_envelope) {
+            Ok(result) => result,
+            Err(err) => {
+                return rpc::reject(
+                    StatusCode::BAD_REQUEST,
+                    shared::enclave::EnclaveRpcErrorEnvelope::new(
+                        Some(request_id),
+                        "invalid_request_payload",
+                        err,
+                        false,
+                    ),
+                )
+                .into_response();
+            }
+        };
+    let condition_result = match request.condition.as_ref() {
+        Some(condition) => {
+            match super::condition::evaluate_automation_condition(
+                &state,
+                request.user_id,
+                request.request_id.as_str(),
+                request.scheduled_for,
+                condition,
             )
-            .into_response();
+            .await
+            {
+                Ok(result) => Some((condition.clone(), result)),
+                Err(response) => return response,
+            }
         }
+        None => None,
     };
-    let execution = match super::orchestrator::execute_query(
-        &state,
-        request.user_id,
-        request.request_id.as_str(),
-        prompt_query.as_str(),
-        None,
-    )
-    .await
+    if let Some((condition, result)) = condition_result.as_ref()
+        && !result.met
     {
-        Ok(execution) => execution,
-        Err(response) => {
-            warn!(
-                user_id = %request.user_id,
-                status = response.status().as_u16(),
-                "automation orchestrator execution failed"
-            );
-            return response;
+        return condition_not_met_response(&state, &request, condition, result, decrypted_key_id);
+    }
+
+    let generated = match request.template.as_ref() {
+        Some(template) => {
+            templates::generate_template_content(&state, &request, template, prompt_query.as_str())
+                .await
         }
+        None => generate_orchestrator_content(&state, &request, prompt_query.as_str()).await,
+    };
+    let generated = match generated {
+        Ok(generated) => generated,
+        Err(response) => return response,
     };
-    let (notification, output_source) = resolve_notification_content(&execution);
 
     let mut notification_artifacts = Vec::with_capacity(request.recipient_devices.len());
+    let mut report_artifacts = Vec::new();
     for device in &request.recipient_devices {
-        let artifact =
-            match encrypt_for_recipient(&state, request.request_id.as_str(), device, &notification)
-            {
-                Ok(artifact) => artifact,
-                Err(err) => {
-                    return rpc::reject(
-                        StatusCode::BAD_REQUEST,
-                        shared::enclave::EnclaveRpcErrorEnvelope::new(
-                            Some(request.request_id),
-                            "invalid_request_payload",
-                            err,
-                            false,
-                        ),
-                    )
-                    .into_response();
-                }
-            };
+        let notification_plaintext = match serialize_plaintext(&AutomationNotificationPlaintext {
+            title: generated.notification.title.clone(),
+            body: generated.notification.body.clone(),
+        }) {
+            Ok(plaintext) => plaintext,
+            Err(err) => return internal_error(request.request_id.as_str(), err),
+        };
+        let artifact = match encrypt_for_recipient(
+            &state,
+            request.request_id.as_str(),
+            device,
+            notification_plaintext.as_slice(),
+            ArtifactPurpose::Notification,
+        ) {
+            Ok(artifact) => artifact,
+            Err(err) => {
+                return rpc::reject(
+                    StatusCode::BAD_REQUEST,
+                    shared::enclave::EnclaveRpcErrorEnvelope::new(
+                        Some(request.request_id),
+                        "invalid_request_payload",
+                        err,
+                        false,
+                    ),
+                )
+                .into_response();
+            }
+        };
         notification_artifacts.push(artifact);
+
+        if let Some(report_plaintext) = generated.report_plaintext.as_deref() {
+            match encrypt_for_recipient(
+                &state,
+                request.request_id.as_str(),
+                device,
+                report_plaintext,
+                ArtifactPurpose::Report,
+            ) {
+                Ok(artifact) => report_artifacts.push(artifact),
+                Err(err) => return internal_error(request.request_id.as_str(), err),
+            }
+        }
     }
 
     let mut metadata = HashMap::new();
     metadata.insert(
         "action_source".to_string(),
-        "enclave_automation_orchestrator".to_string(),
+        generated.action_source.to_string(),
     );
     metadata.insert(
         "automation_rule_id".to_string(),
@@ -131,16 +197,16 @@ pub(super) async fn execute_automation(
     );
     metadata.insert(
         "llm_output_source".to_string(),
-        match output_source {
-            AutomationNotificationSource::OrchestratorResult => "orchestrator_result",
-            AutomationNotificationSource::DeterministicFallback => "deterministic_fallback",
-        }
-        .to_string(),
+        generated.output_source.to_string(),
     );
     metadata.insert(
         "llm_capability".to_string(),
-        capability_label(&execution.capability).to_string(),
+        generated.capability.to_string(),
     );
+    metadata.extend(generated.metadata);
+    if let Some((condition, result)) = condition_result.as_ref() {
+        metadata.extend(super::condition::condition_metadata(condition, result));
+    }
     metadata.insert("prompt_key_id".to_string(), decrypted_key_id);
     metadata.insert(
         "recipient_device_count".to_string(),
@@ -148,7 +214,7 @@ pub(super) async fn execute_automation(
     );
     metadata.insert(
         "encrypted_artifact_count".to_string(),
-        notification_artifacts.len().to_string(),
+        (notification_artifacts.len() + report_artifacts.len()).to_string(),
     );
     metadata.insert(
         "attested_measurement".to_string(),
@@ -161,27 +227,130 @@ pub(super) async fn execute_automation(
         request_id: request.request_id,
         should_notify: !notification_artifacts.is_empty(),
         notification_artifacts,
+        report_artifacts,
+        condition_result: condition_result.map(|(_, result)| result),
         metadata,
         attested_identity,
     })
     .into_response()
 }
 
-fn decrypt_automation_prompt(
+/// A run whose condition is not met ends without generating content or artifacts.
+fn condition_not_met_response(
     state: &RuntimeState,
     request: &EnclaveRpcExecuteAutomationRequest,
+    condition: &EnclaveAutomationConditionRequest,
+    result: &EnclaveAutomationConditionResult,
+    decrypted_key_id: String,
+) -> Response {
+    let mut metadata = super::condition::condition_metadata(condition, result);
+    metadata.insert(
+        "action_source".to_string(),
+        "enclave_automation_condition".to_string(),
+    );
+    metadata.insert(
+        "automation_rule_id".to_string(),
+        request.automation_rule_id.to_string(),
+    );
+    metadata.insert(
+        "automation_run_id".to_string(),
+        request.automation_run_id.to_string(),
+    );
+    metadata.insert(
+        "scheduled_for".to_string(),
+        request.scheduled_for.to_rfc3339(),
+    );
+    metadata.insert("prompt_key_id".to_string(), decrypted_key_id);
+    metadata.insert(
+        "recipient_device_count".to_string(),
+        request.recipient_devices.len().to_string(),
+    );
+    metadata.insert("encrypted_artifact_count".to_string(), "0".to_string());
+    metadata.insert(
+        "attested_measurement".to_string(),
+        state.config.measurement.clone(),
+    );
+
+    Json(EnclaveRpcExecuteAutomationResponse {
+        contract_version: ENCLAVE_RPC_CONTRACT_VERSION.to_string(),
+        request_id: request.request_id.clone(),
+        should_notify: false,
+        notification_artifacts: Vec::new(),
+        report_artifacts: Vec::new(),
+        condition_result: Some(result.clone()),
+        metadata,
+        attested_identity: runtime_attested_identity(state),
+    })
+    .into_response()
+}
+
+async fn generate_orchestrator_content(
+    state: &RuntimeState,
+    request: &EnclaveRpcExecuteAutomationRequest,
+    prompt_query: &str,
+) -> Result<GeneratedAutomationContent, Response> {
+    let execution = super::orchestrator::execute_query(
+        state,
+        request.user_id,
+        request.request_id.as_str(),
+        prompt_query,
+        None,
+    )
+    .await
+    .inspect_err(|response| {
+        warn!(
+            user_id = %request.user_id,
+            status = response.status().as_u16(),
+            "automation orchestrator execution failed"
+        );
+    })?;
+    let (notification, output_source) = resolve_notification_content(&execution);
+
+    Ok(GeneratedAutomationContent {
+        notification,
+        report_plaintext: None,
+        action_source: "enclave_automation_orchestrator",
+        output_source: match output_source {
+            AutomationNotificationSource::OrchestratorResult => "orchestrator_result",
+            AutomationNotificationSource::DeterministicFallback => "deterministic_fallback",
+        },
+        capability: capability_label(&execution.capability),
+        metadata: HashMap::new(),
+    })
+}
+
+pub(super) fn serialize_plaintext<T: Serialize>(value: &T) -> Result<Vec<u8>, String> {
+    serde_json::to_vec(value).map_err(|_| "failed to serialize automation payload".to_string())
+}
+
+pub(super) fn internal_error(request_id: &str, message: String) -> Response {
+    rpc::reject(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        shared::enclave::EnclaveRpcErrorEnvelope::new(
+            Some(request_id.to_string()),
+            "rpc_internal_error",
+            message,
+            true,
+        ),
+    )
+    .into_response()
+}
+
+pub(super) fn decrypt_automation_prompt(
+    state: &RuntimeState,
+    prompt_envelope: &shared::models::AutomationPromptEnvelope,
 ) -> Result<(String, String), String> {
     let envelope = shared::models::AssistantEncryptedRequestEnvelope {
-        version: request.prompt_envelope.version.clone(),
-        algorithm: request.prompt_envelope.algorithm.clone(),
-        key_id: request.prompt_envelope.key_id.clone(),
-        request_id: request.prompt_envelope.request_id.clone(),
-        client_ephemeral_public_key: request.prompt_envelope.client_ephemeral_public_key.clone(),
-        nonce: request.prompt_envelope.nonce.clone(),
-        ciphertext: request.prompt_envelope.ciphertext.clone(),
+        version: prompt_envelope.version.clone(),
+        algorithm: prompt_envelope.algorithm.clone(),
+        key_id: prompt_envelope.key_id.clone(),
+        request_id: prompt_envelope.request_id.clone(),
+        client_ephemeral_public_key: prompt_envelope.client_ephemeral_public_key.clone(),
+        nonce: prompt_envelope.nonce.clone(),
+        ciphertext: prompt_envelope.ciphertext.clone(),
     };
     let (plaintext, selected_key) =
-        decrypt_assistant_request(&state.config.assistant_ingress_keys, &envelope)
+        decrypt_assistant_request(&state.ingress_keys.keyring(), &envelope)
             .map_err(|_| "automation prompt envelope decrypt failed".to_string())?;
 
     let prompt_query = validate_prompt_query(plaintext.query.as_str())?;
@@ -252,26 +421,31 @@ fn capability_label(capability: &AssistantQueryCapability) -> &'static str {
         AssistantQueryCapability::EmailLookup => "email_lookup",
         AssistantQueryCapability::GeneralChat => "general_chat",
         AssistantQueryCapability::Mixed => "mixed",
+        AssistantQueryCapability::FocusTime => "focus_time",
+        AssistantQueryCapability::EmailCleanup => "email_cleanup",
     }
 }
 
 fn default_title_for_capability(capability: &AssistantQueryCapability) -> &'static str {
     match capability {
-        AssistantQueryCapability::MeetingsToday | AssistantQueryCapability::CalendarLookup => {
-            "Calendar update"
+        AssistantQueryCapability::MeetingsToday
+        | AssistantQueryCapability::CalendarLookup
+        | AssistantQueryCapability::FocusTime => "Calendar update",
+        AssistantQueryCapability::EmailLookup | AssistantQueryCapability::EmailCleanup => {
+            "Email update"
         }
-        AssistantQueryCapability::EmailLookup => "Email update",
         AssistantQueryCapability::GeneralChat | AssistantQueryCapability::Mixed => {
             AUTOMATION_NOTIFICATION_DEFAULT_TITLE
         }
     }
 }
 
-fn encrypt_for_recipient(
+pub(super) fn encrypt_for_recipient(
     state: &RuntimeState,
     request_id: &str,
     device: &EnclaveAutomationRecipientDevice,
-    notification: &NotificationContent,
+    plaintext: &[u8],
+    purpose: ArtifactPurpose,
 ) -> Result<EnclaveAutomationNotificationArtifact, String> {
     if device.device_id.trim().is_empty() {
         return Err("recipient device_id is required".to_string());
@@ -284,20 +458,16 @@ fn encrypt_for_recipient(
     }
 
     let recipient_public_key = decode_public_key(device.public_key.as_str())?;
-    let sender_secret = StaticSecret::from(state.config.assistant_ingress_keys.active.private_key);
+    let sender_key = state.ingress_keys.keyring().active;
+    let sender_secret = StaticSecret::from(sender_key.private_key);
     let shared_secret = sender_secret.diffie_hellman(&recipient_public_key);
-    let derived_key = derive_notification_key(
+    let derived_key = derive_artifact_key(
         shared_secret.as_bytes(),
         request_id,
         device.device_id.as_str(),
+        purpose,
     );
 
-    let plaintext = serde_json::to_vec(&AutomationNotificationPlaintext {
-        title: notification.title.clone(),
-        body: notification.body.clone(),
-    })
-    .map_err(|_| "failed to serialize notification payload".to_string())?;
-
     let nonce_bytes = build_nonce_bytes();
     let aad = format!("{request_id}|{}", device.device_id);
     let cipher = ChaCha20Poly1305::new_from_slice(&derived_key)
@@ -306,7 +476,7 @@ fn encrypt_for_recipient(
         .encrypt(
             Nonce::from_slice(&nonce_bytes),
             Payload {
-                msg: plaintext.as_slice(),
+                msg: plaintext,
                 aad: aad.as_bytes(),
             },
         )
@@ -317,21 +487,16 @@ fn encrypt_for_recipient(
         envelope: EnclaveAutomationEncryptedNotificationEnvelope {
             version: ASSISTANT_ENVELOPE_VERSION_V1.to_string(),
             algorithm: ASSISTANT_ENCRYPTION_ALGORITHM_X25519_CHACHA20POLY1305.to_string(),
-            key_id: state.config.assistant_ingress_keys.active.key_id.clone(),
+            key_id: sender_key.key_id,
             request_id: request_id.to_string(),
-            sender_public_key: state
-                .config
-                .assistant_ingress_keys
-                .active
-                .public_key
-                .clone(),
+            sender_public_key: sender_key.public_key,
             nonce: base64::engine::general_purpose::STANDARD.encode(nonce_bytes),
             ciphertext: base64::engine::general_purpose::STANDARD.encode(ciphertext),
         },
     })
 }
 
-fn runtime_attested_identity(state: &RuntimeState) -> AttestedIdentityPayload {
+pub(super) fn runtime_attested_identity(state: &RuntimeState) -> AttestedIdentityPayload {
     AttestedIdentityPayload {
         runtime: state.config.runtime_id.clone(),
         measurement: state.config.measurement.clone(),
@@ -348,10 +513,13 @@ fn decode_public_key(value: &str) -> Result<PublicKey, String> {
     Ok(PublicKey::from(key_bytes))
 }
 
-fn derive_notification_key(
+/// Notification and report artifacts for the same run and device get distinct keys so one
+/// ciphertext can never be replayed as the other.
+fn derive_artifact_key(
     shared_secret_bytes: &[u8; 32],
     request_id: &str,
     device_id: &str,
+    purpose: ArtifactPurpose,
 ) -> [u8; 32] {
     let mut hasher = Sha256::new();
     hasher.update(shared_secret_bytes);
@@ -359,7 +527,8 @@ fn derive_notification_key(
     hasher.update(request_id.as_bytes());
     hasher.update(b"|");
     hasher.update(device_id.as_bytes());
-    hasher.update(b"|notification");
+    hasher.update(b"|");
+    hasher.update(purpose.label());
     hasher.finalize().into()
 }
 
@@ -370,7 +539,7 @@ fn build_nonce_bytes() -> [u8; 12] {
     nonce
 }
 
-fn truncate_for_notification(value: &str, max_chars: usize) -> String {
+pub(super) fn truncate_for_notification(value: &str, max_chars: usize) -> String {
     let trimmed = value.trim();
     if trimmed.chars().count() <= max_chars {
         return trimmed.to_string();
@@ -399,6 +568,7 @@ mod tests {
     use shared::enclave::AttestedIdentityPayload;
     use shared::models::{AssistantResponsePart, AssistantStructuredPayload};
 
+    use super::super::orchestrator::AssistantLaneTelemetry;
     use super::*;
 
     #[test]
@@ -419,6 +589,7 @@ mod tests {
                 runtime: "test-runtime".to_string(),
                 measurement: "test-measurement".to_string(),
             },
+            telemetry: AssistantLaneTelemetry::default(),
         };
 
         let (notification, source) = resolve_notification_content(&execution);
@@ -446,6 +617,7 @@ mod tests {
                 runtime: "test-runtime".to_string(),
                 measurement: "test-measurement".to_string(),
             },
+            telemetry: AssistantLaneTelemetry::default(),
         };
 
         let (notification, source) = resolve_notification_content(&execution);
@@ -474,6 +646,7 @@ mod tests {
                 runtime: "test-runtime".to_string(),
                 measurement: "test-measurement".to_string(),
             },
+            telemetry: AssistantLaneTelemetry::default(),
         };
 
         let (notification, source) = resolve_notification_content(&execution);
@@ -496,11 +669,35 @@ mod tests {
     #[test]
     fn derive_notification_key_is_device_scoped() {
         let shared_secret = [9_u8; 32];
-        let first = derive_notification_key(&shared_secret, "req-1", "device-a");
-        let second = derive_notification_key(&shared_secret, "req-1", "device-b");
+        let first = derive_artifact_key(
+            &shared_secret,
+            "req-1",
+            "device-a",
+            ArtifactPurpose::Notification,
+        );
+        let second = derive_artifact_key(
+            &shared_secret,
+            "req-1",
+            "device-b",
+            ArtifactPurpose::Notification,
+        );
         assert_ne!(first, second);
     }
 
+    #[test]
+    fn derive_artifact_key_is_purpose_scoped() {
+        let shared_secret = [9_u8; 32];
+        let notification = derive_artifact_key(
+            &shared_secret,
+            "req-1",
+            "device-a",
+            ArtifactPurpose::Notification,
+        );
+        let report =
+            derive_artifact_key(&shared_secret, "req-1", "device-a", ArtifactPurpose::Report);
+        assert_ne!(notification, report);
+    }
+
     #[test]
     fn validate_prompt_query_rejects_empty() {
         let err = validate_prompt_query("   ").expect_err("empty prompt should fail");
@@ -0,0 +1,106 @@
+use axum::response::Response;
+use serde::Serialize;
+use shared::automation_schedule::AutomationTemplate;
+use shared::enclave::{EnclaveAutomationTemplateRequest, EnclaveRpcExecuteAutomationRequest};
+use shared::llm::contracts::WeeklyReviewOutput;
+use shared::llm::{LlmExecutionSource, SafeOutputSource};
+use shared::timezone::user_local_time;
+
+use super::{
+    GeneratedAutomationContent, NotificationContent, internal_error, notification_candidate,
+    serialize_plaintext,
+};
+use crate::RuntimeState;
+
+const WEEKLY_REVIEW_NOTIFICATION_TITLE: &str = "Weekly review ready";
+const WEEKLY_REVIEW_NOTIFICATION_DEFAULT_BODY: &str = "Open Alfred to read your weekly review.";
+
+/// Long-form template output delivered as a separate encrypted artifact that the app
+/// fetches from `/v1/automation-reports`; the push notification only carries the headline.
+#[derive(Debug, Serialize)]
+struct WeeklyReviewReportPlaintext<'a> {
+    template: AutomationTemplate,
+    review_start_date: &'a str,
+    review_end_date: &'a str,
+    #[serde(flatten)]
+    review: &'a WeeklyReviewOutput,
+}
+
+pub(super) async fn generate_template_content(
+    state: &RuntimeState,
+    request: &EnclaveRpcExecuteAutomationRequest,
+    template: &EnclaveAutomationTemplateRequest,
+    prompt_query: &str,
+) -> Result<GeneratedAutomationContent, Response> {
+    match template.template {
+        AutomationTemplate::WeeklyReview => {
+            let review = super::super::weekly_review::generate_weekly_review(
+                state,
+                request.user_id,
+                request.request_id.as_str(),
+                request.scheduled_for,
+                template.time_zone.as_str(),
+                Some(prompt_query),
+            )
+            .await?;
+
+            let report_plaintext = serialize_plaintext(&WeeklyReviewReportPlaintext {
+                template: AutomationTemplate::WeeklyReview,
+                review_start_date: review.review_start_date.as_str(),
+                review_end_date: review.review_end_date.as_str(),
+                review: &review.review,
+            })
+            .map_err(|err| internal_error(request.request_id.as_str(), err))?;
+            let body = notification_candidate(review.review.headline.as_str())
+                .unwrap_or_else(|| WEEKLY_REVIEW_NOTIFICATION_DEFAULT_BODY.to_string());
+
+            Ok(GeneratedAutomationContent {
+                notification: NotificationContent {
+                    title: WEEKLY_REVIEW_NOTIFICATION_TITLE.to_string(),
+                    body,
+                },
+                report_plaintext: Some(report_plaintext),
+                action_source: "enclave_automation_template",
+                output_source: match review.output_source {
+                    SafeOutputSource::ModelOutput => "model_output",
+                    SafeOutputSource::DeterministicFallback => "deterministic_fallback",
+                },
+                capability: "weekly_review",
+                metadata: review.metadata,
+            })
+        }
+        AutomationTemplate::MorningBrief => {
+            let local_time = user_local_time(request.scheduled_for, template.time_zone.as_str())
+                .format("%H:%M")
+                .to_string();
+            let brief = super::super::proactive::build_morning_brief(
+                state,
+                super::super::proactive::MorningBriefInput {
+                    user_id: request.user_id,
+                    request_id: request.request_id.as_str(),
+                    now: request.scheduled_for,
+                    time_zone: template.time_zone.as_str(),
+                    local_time: local_time.as_str(),
+                    brief_profile: template.brief_profile.as_ref(),
+                    source: LlmExecutionSource::WorkerAutomationRun,
+                },
+            )
+            .await?;
+
+            Ok(GeneratedAutomationContent {
+                notification: NotificationContent {
+                    title: brief.notification.title,
+                    body: brief.notification.body,
+                },
+                report_plaintext: None,
+                action_source: "enclave_automation_template",
+                output_source: match brief.output_source {
+                    SafeOutputSource::ModelOutput => "model_output",
+                    SafeOutputSource::DeterministicFallback => "deterministic_fallback",
+                },
+                capability: "morning_brief",
+                metadata: brief.metadata,
+            })
+        }
+    }
+}
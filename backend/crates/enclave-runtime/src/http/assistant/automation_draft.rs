@@ -0,0 +1,233 @@
+use axum::Json;
+use axum::response::{IntoResponse, Response};
+use chrono::{DateTime, Utc};
+use serde_json::{Value, json};
+use shared::assistant_crypto::{AssistantCryptoError, encrypt_automation_draft};
+use shared::automation_draft::{fallback_automation_draft, normalize_automation_draft_output};
+use shared::enclave::{
+    ENCLAVE_RPC_CONTRACT_VERSION, EnclaveRpcDraftAutomationRequest,
+    EnclaveRpcDraftAutomationResponse,
+};
+use shared::llm::{
+    AssistantCapability, AssistantOutputContract, LlmExecutionSource, LlmGatewayError,
+    LlmGatewayRequest, generate_with_telemetry, sanitize_context_payload, template_for_capability,
+    validate_output_value,
+};
+use shared::models::AutomationPlaintextDraft;
+use shared::timezone::{normalize_time_zone, parse_time_zone_or_default};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use super::automation::{decrypt_automation_prompt, internal_error, runtime_attested_identity};
+use super::departure::invalid_request;
+use crate::RuntimeState;
+
+/// Reads a natural-language automation request and proposes a title, prompt, and schedule for
+/// the user to confirm. Nothing is stored here; the proposal is encrypted back to the client.
+pub(super) async fn draft_automation(
+    state: RuntimeState,
+    request: EnclaveRpcDraftAutomationRequest,
+) -> Response {
+    let Some(time_zone) = normalize_time_zone(request.time_zone.as_str()) else {
+        return invalid_request(
+            request.request_id,
+            "time_zone is not a valid IANA timezone".to_string(),
+        );
+    };
+    let (source_prompt, key_id) = match decrypt_automation_prompt(&state, &request.prompt_envelope)
+    {
+        Ok(result) => result,
+        Err(err) => return invalid_request(request.request_id, err),
+    };
+    let Some(key) = state
+        .ingress_keys
+        .keyring()
+        .key_for_id(key_id.as_str())
+        .cloned()
+    else {
+        return internal_error(
+            request.request_id.as_str(),
+            "decrypted prompt key is no longer available".to_string(),
+        );
+    };
+
+    let draft = resolve_automation_draft(
+        &state,
+        request.user_id,
+        request.request_id.as_str(),
+        source_prompt.as_str(),
+        time_zone.as_str(),
+    )
+    .await;
+
+    let envelope = match encrypt_automation_draft(
+        &key,
+        request.prompt_envelope.request_id.as_str(),
+        request.prompt_envelope.client_ephemeral_public_key.as_str(),
+        &draft,
+    ) {
+        Ok(envelope) => envelope,
+        Err(
+            err @ (AssistantCryptoError::InvalidPublicKey
+            | AssistantCryptoError::InvalidBase64Field { .. }
+            | AssistantCryptoError::MissingRequestId),
+        ) => return invalid_request(request.request_id, err.to_string()),
+        Err(err) => {
+            return internal_error(
+                request.request_id.as_str(),
+                format!("failed to encrypt automation draft: {err}"),
+            );
+        }
+    };
+
+    Json(EnclaveRpcDraftAutomationResponse {
+        contract_version: ENCLAVE_RPC_CONTRACT_VERSION.to_string(),
+        request_id: request.request_id,
+        envelope,
+        attested_identity: runtime_attested_identity(&state),
+    })
+    .into_response()
+}
+
+async fn resolve_automation_draft(
+    state: &RuntimeState,
+    user_id: Uuid,
+    request_id: &str,
+    source_prompt: &str,
+    time_zone: &str,
+) -> AutomationPlaintextDraft {
+    let now = Utc::now();
+    let context_payload = sanitize_context_payload(&draft_context(source_prompt, time_zone, now));
+    let llm_request = LlmGatewayRequest::from_template(
+        template_for_capability(AssistantCapability::AutomationDraft),
+        context_payload,
+    )
+    .with_requester_id(user_id.to_string());
+
+    let (llm_result, telemetry) = generate_with_telemetry(
+        state.assistant_planner_gateway(),
+        LlmExecutionSource::ApiAssistantQuery,
+        llm_request,
+    )
+    .await;
+    super::mapping::log_telemetry(user_id, &telemetry, "automation_draft");
+
+    let parsed = llm_result.and_then(|response| {
+        parse_automation_draft_output(&response.output, source_prompt, time_zone, now)
+    });
+    match parsed {
+        Ok(draft) => {
+            info!(
+                user_id = %user_id,
+                request_id,
+                needs_clarification = draft.needs_clarification,
+                "automation draft resolved model output"
+            );
+            draft
+        }
+        Err(err) => {
+            warn!(
+                user_id = %user_id,
+                request_id,
+                "automation draft planner failed, asking the user for a schedule: {err}"
+            );
+            fallback_automation_draft(source_prompt)
+        }
+    }
+}
+
+fn draft_context(source_prompt: &str, time_zone: &str, now: DateTime<Utc>) -> Value {
+    json!({
+        "request_context": source_prompt,
+        "user_time_zone": time_zone,
+        "current_time_utc": now.to_rfc3339(),
+        "current_time_local": now
+            .with_timezone(&parse_time_zone_or_default(time_zone))
+            .to_rfc3339(),
+    })
+}
+
+fn parse_automation_draft_output(
+    payload: &Value,
+    source_prompt: &str,
+    time_zone: &str,
+    now: DateTime<Utc>,
+) -> Result<AutomationPlaintextDraft, LlmGatewayError> {
+    let contract = validate_output_value(AssistantCapability::AutomationDraft, payload)
+        .map_err(|err| LlmGatewayError::InvalidProviderPayload(err.to_string()))?;
+    let AssistantOutputContract::AutomationDraft(contract) = contract else {
+        return Err(LlmGatewayError::InvalidProviderPayload(
+            "automation draft contract type mismatch".to_string(),
+        ));
+    };
+
+    normalize_automation_draft_output(contract.output, source_prompt, time_zone, now)
+        .map_err(|err| LlmGatewayError::InvalidProviderPayload(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use shared::automation_draft::AUTOMATION_DRAFT_VERSION_V1;
+    use shared::automation_schedule::AutomationScheduleType;
+
+    use super::*;
+
+    fn reference_now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 10, 14, 12, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn parses_a_weekday_brief_from_planner_output() {
+        let payload = json!({
+            "version": AUTOMATION_DRAFT_VERSION_V1,
+            "output": {
+                "title": "Weekday brief",
+                "prompt": "Brief me on today's meetings and urgent email.",
+                "confidence": 0.92,
+                "schedule": {
+                    "schedule_type": "WEEKLY",
+                    "local_time": "07:00",
+                    "days_of_week": [1, 2, 3, 4, 5]
+                }
+            }
+        });
+
+        let draft = parse_automation_draft_output(
+            &payload,
+            "brief me every weekday at 7",
+            "Europe/London",
+            reference_now(),
+        )
+        .expect("planner output should parse");
+
+        let schedule = draft.schedule.expect("schedule should be proposed");
+        assert_eq!(schedule.schedule_type, AutomationScheduleType::Weekly);
+        assert_eq!(schedule.days_of_week, Some(vec![1, 2, 3, 4, 5]));
+        assert!(!draft.needs_clarification);
+    }
+
+    #[test]
+    fn rejects_output_for_another_contract_version() {
+        let payload = json!({
+            "version": "2020-01-01",
+            "output": {
+                "title": "Weekday brief",
+                "prompt": "Brief me",
+                "confidence": 0.9
+            }
+        });
+
+        assert!(
+            parse_automation_draft_output(&payload, "brief me", "UTC", reference_now()).is_err()
+        );
+    }
+
+    #[test]
+    fn draft_context_carries_the_local_time() {
+        let context = draft_context("brief me at 7", "America/New_York", reference_now());
+
+        assert_eq!(context["request_context"], "brief me at 7");
+        assert_eq!(context["current_time_local"], "2026-10-14T08:00:00-04:00");
+    }
+}
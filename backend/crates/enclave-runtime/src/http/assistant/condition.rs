@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use chrono::{DateTime, Utc};
+use shared::automation_schedule::{AutomationConditionKind, MAX_AUTOMATION_CONDITION_MIN_COUNT};
+use shared::enclave::{EnclaveAutomationConditionRequest, EnclaveAutomationConditionResult};
+use shared::timezone::{local_day_bounds_utc, user_local_date};
+use uuid::Uuid;
+
+use crate::RuntimeState;
+use crate::http::rpc;
+
+/// Counts only need to reach the largest accepted `min_count`, so fetches stop there.
+const CONDITION_MAX_RESULTS: usize = MAX_AUTOMATION_CONDITION_MIN_COUNT as usize;
+
+/// Counts the data a condition watches: calendar events on the run's local day or current
+/// urgent email candidates.
+pub(super) async fn evaluate_automation_condition(
+    state: &RuntimeState,
+    user_id: Uuid,
+    request_id: &str,
+    scheduled_for: DateTime<Utc>,
+    request: &EnclaveAutomationConditionRequest,
+) -> Result<EnclaveAutomationConditionResult, Response> {
+    let map_error =
+        |err| rpc::map_rpc_service_error(err, Some(request_id.to_string())).into_response();
+
+    let observed_count = match request.condition.kind {
+        AutomationConditionKind::CalendarEventsToday => {
+            let time_zone = request.time_zone.as_str();
+            let local_date = user_local_date(scheduled_for, time_zone);
+            let Some((time_min, time_max)) = local_day_bounds_utc(local_date, time_zone) else {
+                return Err(rpc::reject(
+                    StatusCode::BAD_REQUEST,
+                    shared::enclave::EnclaveRpcErrorEnvelope::new(
+                        Some(request_id.to_string()),
+                        "invalid_request_payload",
+                        "unable to resolve local-day boundaries for the supplied time zone",
+                        false,
+                    ),
+                )
+                .into_response());
+            };
+
+            let connectors = state
+                .enclave_service
+                .resolve_active_calendar_connector_requests(user_id)
+                .await
+                .map_err(map_error)?;
+            state
+                .enclave_service
+                .fetch_calendar_events_for_connectors(
+                    &connectors,
+                    &time_min.to_rfc3339(),
+                    &time_max.to_rfc3339(),
+                    CONDITION_MAX_RESULTS,
+                )
+                .await
+                .map_err(map_error)?
+                .events
+                .len()
+        }
+        AutomationConditionKind::UrgentEmails => {
+            let connectors = state
+                .enclave_service
+                .resolve_active_email_connector_requests(user_id)
+                .await
+                .map_err(map_error)?;
+            state
+                .enclave_service
+                .fetch_email_candidates_for_connectors(&connectors, None, CONDITION_MAX_RESULTS)
+                .await
+                .map_err(map_error)?
+                .candidates
+                .len()
+        }
+    };
+
+    Ok(EnclaveAutomationConditionResult {
+        met: request.condition.is_met(observed_count),
+        observed_count: u32::try_from(observed_count).unwrap_or(u32::MAX),
+    })
+}
+
+pub(super) fn condition_metadata(
+    request: &EnclaveAutomationConditionRequest,
+    result: &EnclaveAutomationConditionResult,
+) -> HashMap<String, String> {
+    let kind = match request.condition.kind {
+        AutomationConditionKind::CalendarEventsToday => "calendar_events_today",
+        AutomationConditionKind::UrgentEmails => "urgent_emails",
+    };
+    HashMap::from([
+        ("automation_condition_kind".to_string(), kind.to_string()),
+        (
+            "automation_condition_min_count".to_string(),
+            request.condition.min_count.to_string(),
+        ),
+        (
+            "automation_condition_observed_count".to_string(),
+            result.observed_count.to_string(),
+        ),
+        (
+            "automation_condition_met".to_string(),
+            result.met.to_string(),
+        ),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use shared::automation_schedule::AutomationCondition;
+
+    use super::*;
+
+    #[test]
+    fn condition_metadata_records_the_evaluation() {
+        let request = EnclaveAutomationConditionRequest {
+            condition: AutomationCondition {
+                kind: AutomationConditionKind::UrgentEmails,
+                min_count: 2,
+            },
+            time_zone: "UTC".to_string(),
+        };
+        let metadata = condition_metadata(
+            &request,
+            &EnclaveAutomationConditionResult {
+                met: false,
+                observed_count: 1,
+            },
+        );
+
+        assert_eq!(metadata["automation_condition_kind"], "urgent_emails");
+        assert_eq!(metadata["automation_condition_min_count"], "2");
+        assert_eq!(metadata["automation_condition_observed_count"], "1");
+        assert_eq!(metadata["automation_condition_met"], "false");
+    }
+}
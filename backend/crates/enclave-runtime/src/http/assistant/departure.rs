@@ -0,0 +1,407 @@
+use std::collections::HashMap;
+
+use axum::Json;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use shared::assistant_crypto::decrypt_assistant_request;
+use shared::departure_alert::{departure_leave_by, is_departure_alert_due};
+use shared::enclave::{
+    ENCLAVE_RPC_CONTRACT_VERSION, EnclaveDepartureAlertStatus, EnclaveGoogleCalendarEvent,
+    EnclaveRpcPlanDepartureAlertRequest, EnclaveRpcPlanDepartureAlertResponse,
+};
+use shared::models::AutomationPromptEnvelope;
+use shared::timezone::{local_day_bounds_utc, parse_time_zone_or_default, user_local_date};
+use url::Url;
+use uuid::Uuid;
+
+use super::automation::{
+    ArtifactPurpose, AutomationNotificationPlaintext, encrypt_for_recipient, internal_error,
+    runtime_attested_identity, serialize_plaintext, truncate_for_notification,
+};
+use super::mapping::parse_utc_datetime;
+use crate::RuntimeState;
+use crate::http::rpc;
+
+const DEPARTURE_CALENDAR_MAX_RESULTS: usize = 20;
+const DEPARTURE_HOME_LOCATION_MAX_CHARS: usize = 512;
+const DEPARTURE_NOTIFICATION_TITLE: &str = "Time to leave";
+const DEPARTURE_NOTIFICATION_SUBJECT_MAX_CHARS: usize = 80;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct InPersonMeeting {
+    event_id: String,
+    title: String,
+    location: String,
+    start_at: DateTime<Utc>,
+}
+
+pub(super) async fn plan_departure_alert(
+    state: RuntimeState,
+    request: EnclaveRpcPlanDepartureAlertRequest,
+) -> Response {
+    let request_id = request.request_id.clone();
+    let home_location = match request.home_location_envelope.as_ref() {
+        Some(envelope) => match decrypt_home_location(&state, envelope) {
+            Ok(location) => Some(location),
+            Err(err) => return invalid_request(request_id, err),
+        },
+        None => None,
+    };
+
+    let now = Utc::now();
+    let local_date = user_local_date(now, request.time_zone.as_str());
+    let Some((_, day_end)) = local_day_bounds_utc(local_date, request.time_zone.as_str()) else {
+        return invalid_request(
+            request_id,
+            "unable to resolve local day boundaries for the supplied time zone".to_string(),
+        );
+    };
+
+    let connectors = match state
+        .enclave_service
+        .resolve_active_calendar_connector_requests(request.user_id)
+        .await
+    {
+        Ok(connectors) => connectors,
+        Err(err) => {
+            return rpc::map_rpc_service_error(err, Some(request_id)).into_response();
+        }
+    };
+    let events = match state
+        .enclave_service
+        .fetch_calendar_events_for_connectors(
+            &connectors,
+            &now.to_rfc3339(),
+            &day_end.to_rfc3339(),
+            DEPARTURE_CALENDAR_MAX_RESULTS,
+        )
+        .await
+    {
+        Ok(response) => response.events,
+        Err(err) => {
+            return rpc::map_rpc_service_error(err, Some(request_id)).into_response();
+        }
+    };
+
+    let mut metadata = HashMap::new();
+    metadata.insert("action_source".to_string(), "enclave_departure".to_string());
+    metadata.insert(
+        "calendar_events_checked".to_string(),
+        events.len().to_string(),
+    );
+    metadata.insert(
+        "home_location_configured".to_string(),
+        home_location.is_some().to_string(),
+    );
+
+    let Some(meeting) = first_in_person_meeting(&events, now, home_location.as_deref()) else {
+        return Json(EnclaveRpcPlanDepartureAlertResponse {
+            contract_version: ENCLAVE_RPC_CONTRACT_VERSION.to_string(),
+            request_id: request.request_id,
+            status: EnclaveDepartureAlertStatus::NoMeeting,
+            meeting_fingerprint: None,
+            meeting_start_at: None,
+            leave_by: None,
+            notification_artifacts: Vec::new(),
+            metadata,
+            attested_identity: runtime_attested_identity(&state),
+        })
+        .into_response();
+    };
+
+    let leave_by = departure_leave_by(
+        meeting.start_at,
+        request.travel_minutes,
+        request.buffer_minutes,
+    );
+    let due = is_departure_alert_due(now, leave_by);
+    let mut notification_artifacts = Vec::new();
+    if due {
+        let plaintext = match serialize_plaintext(&AutomationNotificationPlaintext {
+            title: DEPARTURE_NOTIFICATION_TITLE.to_string(),
+            body: departure_notification_body(&meeting, leave_by, request.time_zone.as_str()),
+        }) {
+            Ok(plaintext) => plaintext,
+            Err(err) => return internal_error(request.request_id.as_str(), err),
+        };
+
+        for device in &request.recipient_devices {
+            match encrypt_for_recipient(
+                &state,
+                request.request_id.as_str(),
+                device,
+                plaintext.as_slice(),
+                ArtifactPurpose::Notification,
+            ) {
+                Ok(artifact) => notification_artifacts.push(artifact),
+                Err(err) => return invalid_request(request.request_id, err),
+            }
+        }
+    }
+
+    metadata.insert(
+        "encrypted_artifact_count".to_string(),
+        notification_artifacts.len().to_string(),
+    );
+    metadata.insert(
+        "attested_measurement".to_string(),
+        state.config.measurement.clone(),
+    );
+
+    Json(EnclaveRpcPlanDepartureAlertResponse {
+        contract_version: ENCLAVE_RPC_CONTRACT_VERSION.to_string(),
+        request_id: request.request_id,
+        status: if due {
+            EnclaveDepartureAlertStatus::Due
+        } else {
+            EnclaveDepartureAlertStatus::Pending
+        },
+        meeting_fingerprint: Some(meeting_fingerprint(request.user_id, &meeting)),
+        meeting_start_at: Some(meeting.start_at),
+        leave_by: Some(leave_by),
+        notification_artifacts,
+        metadata,
+        attested_identity: runtime_attested_identity(&state),
+    })
+    .into_response()
+}
+
+fn decrypt_home_location(
+    state: &RuntimeState,
+    envelope: &AutomationPromptEnvelope,
+) -> Result<String, String> {
+    let envelope = shared::models::AssistantEncryptedRequestEnvelope {
+        version: envelope.version.clone(),
+        algorithm: envelope.algorithm.clone(),
+        key_id: envelope.key_id.clone(),
+        request_id: envelope.request_id.clone(),
+        client_ephemeral_public_key: envelope.client_ephemeral_public_key.clone(),
+        nonce: envelope.nonce.clone(),
+        ciphertext: envelope.ciphertext.clone(),
+    };
+    let (plaintext, _) = decrypt_assistant_request(&state.ingress_keys.keyring(), &envelope)
+        .map_err(|_| "home location envelope decrypt failed".to_string())?;
+
+    let location = plaintext.query.trim();
+    if location.chars().count() > DEPARTURE_HOME_LOCATION_MAX_CHARS {
+        return Err(format!(
+            "home location exceeds maximum length of {DEPARTURE_HOME_LOCATION_MAX_CHARS} characters"
+        ));
+    }
+    Ok(location.to_string())
+}
+
+/// First timed meeting starting after `now` that the user has to travel to. Events with a
+/// conference link, locations that are URLs, and the user's own home location do not count.
+fn first_in_person_meeting(
+    events: &[EnclaveGoogleCalendarEvent],
+    now: DateTime<Utc>,
+    home_location: Option<&str>,
+) -> Option<InPersonMeeting> {
+    events
+        .iter()
+        .filter_map(|event| {
+            let start_at = event
+                .start
+                .as_ref()
+                .and_then(|start| start.date_time.as_deref())
+                .and_then(parse_utc_datetime)?;
+            let location = event.location.as_deref()?.trim();
+            if start_at <= now || !is_physical_meeting(event, location, home_location) {
+                return None;
+            }
+
+            Some(InPersonMeeting {
+                event_id: event.id.clone().unwrap_or_default(),
+                title: event
+                    .summary
+                    .as_deref()
+                    .map(str::trim)
+                    .filter(|title| !title.is_empty())
+                    .unwrap_or("your meeting")
+                    .to_string(),
+                location: location.to_string(),
+                start_at,
+            })
+        })
+        .min_by_key(|meeting| meeting.start_at)
+}
+
+fn is_physical_meeting(
+    event: &EnclaveGoogleCalendarEvent,
+    location: &str,
+    home_location: Option<&str>,
+) -> bool {
+    if location.is_empty() || has_conference_link(event) || is_url(location) {
+        return false;
+    }
+
+    !home_location
+        .map(str::trim)
+        .filter(|home| !home.is_empty())
+        .is_some_and(|home| home.eq_ignore_ascii_case(location))
+}
+
+fn has_conference_link(event: &EnclaveGoogleCalendarEvent) -> bool {
+    event
+        .hangout_link
+        .as_deref()
+        .is_some_and(|link| !link.trim().is_empty())
+        || event.conference_data.as_ref().is_some_and(|conference| {
+            conference
+                .entry_points
+                .iter()
+                .any(|entry_point| entry_point.uri.is_some())
+        })
+}
+
+fn is_url(location: &str) -> bool {
+    Url::parse(location).is_ok_and(|url| url.has_host())
+}
+
+fn meeting_fingerprint(user_id: Uuid, meeting: &InPersonMeeting) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(user_id.as_bytes());
+    hasher.update(b"|");
+    hasher.update(meeting.event_id.as_bytes());
+    hasher.update(b"|");
+    hasher.update(meeting.start_at.to_rfc3339().as_bytes());
+    hasher.update(b"|");
+    hasher.update(meeting.location.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+fn departure_notification_body(
+    meeting: &InPersonMeeting,
+    leave_by: DateTime<Utc>,
+    time_zone: &str,
+) -> String {
+    let local_leave_by = leave_by.with_timezone(&parse_time_zone_or_default(time_zone));
+    format!(
+        "Leave by {} for {} at {}.",
+        local_leave_by.format("%H:%M"),
+        truncate_for_notification(
+            meeting.title.as_str(),
+            DEPARTURE_NOTIFICATION_SUBJECT_MAX_CHARS
+        ),
+        truncate_for_notification(
+            meeting.location.as_str(),
+            DEPARTURE_NOTIFICATION_SUBJECT_MAX_CHARS
+        ),
+    )
+}
+
+pub(super) fn invalid_request(request_id: String, message: String) -> Response {
+    rpc::reject(
+        StatusCode::BAD_REQUEST,
+        shared::enclave::EnclaveRpcErrorEnvelope::new(
+            Some(request_id),
+            "invalid_request_payload",
+            message,
+            false,
+        ),
+    )
+    .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use shared::enclave::{
+        EnclaveGoogleCalendarConferenceData, EnclaveGoogleCalendarConferenceEntryPoint,
+        EnclaveGoogleCalendarEventDateTime,
+    };
+
+    use super::*;
+
+    fn event(id: &str, start: &str, location: Option<&str>) -> EnclaveGoogleCalendarEvent {
+        EnclaveGoogleCalendarEvent {
+            id: Some(id.to_string()),
+            summary: Some(format!("Meeting {id}")),
+            start: Some(EnclaveGoogleCalendarEventDateTime {
+                date_time: Some(start.to_string()),
+            }),
+            end: None,
+            location: location.map(ToString::to_string),
+            hangout_link: None,
+            conference_data: None,
+            attendees: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn first_in_person_meeting_skips_virtual_and_home_locations() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 2, 7, 0, 0).unwrap();
+        let events = vec![
+            event(
+                "video",
+                "2026-03-02T08:00:00Z",
+                Some("https://zoom.us/j/123"),
+            ),
+            event("home", "2026-03-02T08:30:00Z", Some("12 Oak Street")),
+            event("none", "2026-03-02T08:45:00Z", None),
+            EnclaveGoogleCalendarEvent {
+                hangout_link: Some("https://meet.google.com/abc-defg-hij".to_string()),
+                ..event("meet", "2026-03-02T08:15:00Z", Some("Room 4B"))
+            },
+            EnclaveGoogleCalendarEvent {
+                conference_data: Some(EnclaveGoogleCalendarConferenceData {
+                    entry_points: vec![EnclaveGoogleCalendarConferenceEntryPoint {
+                        uri: Some("tel:+1-555-0100".to_string()),
+                    }],
+                }),
+                ..event("dial-in", "2026-03-02T08:20:00Z", Some("Board room"))
+            },
+            event("office", "2026-03-02T10:00:00Z", Some("HQ, Floor 4")),
+            event("cafe", "2026-03-02T09:00:00Z", Some("Blue Bottle Coffee")),
+        ];
+
+        let meeting = first_in_person_meeting(&events, now, Some("12 oak street"))
+            .expect("an in-person meeting should be selected");
+        assert_eq!(meeting.event_id, "cafe");
+        assert_eq!(meeting.location, "Blue Bottle Coffee");
+    }
+
+    #[test]
+    fn free_text_locations_are_physical_without_a_conference_link() {
+        let meeting = event("call", "2026-03-02T08:00:00Z", Some("Online sync"));
+
+        assert!(is_physical_meeting(&meeting, "Online sync", None));
+        assert!(is_physical_meeting(&meeting, "HQ: Floor 4", None));
+        assert!(!is_physical_meeting(
+            &meeting,
+            "https://example.com/room",
+            None
+        ));
+    }
+
+    #[test]
+    fn first_in_person_meeting_ignores_meetings_that_already_started() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 2, 9, 0, 0).unwrap();
+        let events = vec![event("cafe", "2026-03-02T09:00:00Z", Some("Cafe"))];
+
+        assert!(first_in_person_meeting(&events, now, None).is_none());
+    }
+
+    #[test]
+    fn meeting_fingerprint_changes_when_meeting_moves() {
+        let user_id = Uuid::new_v4();
+        let meeting = InPersonMeeting {
+            event_id: "evt-1".to_string(),
+            title: "Design review".to_string(),
+            location: "HQ".to_string(),
+            start_at: Utc.with_ymd_and_hms(2026, 3, 2, 9, 0, 0).unwrap(),
+        };
+        let moved = InPersonMeeting {
+            start_at: Utc.with_ymd_and_hms(2026, 3, 2, 9, 30, 0).unwrap(),
+            ..meeting.clone()
+        };
+
+        assert_ne!(
+            meeting_fingerprint(user_id, &meeting),
+            meeting_fingerprint(user_id, &moved)
+        );
+    }
+}
@@ -103,7 +103,7 @@ pub(super) fn log_telemetry(user_id: Uuid, telemetry: &shared::llm::LlmTelemetry
     );
 }
 
-fn parse_utc_datetime(value: &str) -> Option<DateTime<Utc>> {
+pub(super) fn parse_utc_datetime(value: &str) -> Option<DateTime<Utc>> {
     DateTime::parse_from_rfc3339(value)
         .ok()
         .map(|parsed| parsed.with_timezone(&Utc))
@@ -1,3 +1,4 @@
+use shared::brief_profile::{MorningBriefProfile, morning_brief_notification_body};
 use shared::llm::contracts::{MorningBriefOutput, UrgencyLevel, UrgentEmailSummaryOutput};
 
 const MORNING_BRIEF_TITLE_MAX_CHARS: usize = 64;
@@ -11,7 +12,10 @@ pub(super) struct NotificationContent {
     pub(super) body: String,
 }
 
-pub(super) fn notification_from_morning_brief(output: &MorningBriefOutput) -> NotificationContent {
+pub(super) fn notification_from_morning_brief(
+    output: &MorningBriefOutput,
+    profile: &MorningBriefProfile,
+) -> NotificationContent {
     let title = if output.headline.trim().is_empty() {
         "Morning brief".to_string()
     } else {
@@ -19,7 +23,7 @@ pub(super) fn notification_from_morning_brief(output: &MorningBriefOutput) -> No
     };
 
     let body = truncate_for_notification(
-        &build_morning_brief_notification_body(output),
+        &morning_brief_notification_body(output, profile),
         MORNING_BRIEF_BODY_MAX_CHARS,
     );
 
@@ -50,29 +54,6 @@ pub(super) fn urgency_label(urgency: &UrgencyLevel) -> &'static str {
     }
 }
 
-fn build_morning_brief_notification_body(output: &MorningBriefOutput) -> String {
-    let mut segments = Vec::new();
-
-    if let Some(summary) = non_empty(&output.summary) {
-        segments.push(summary.to_string());
-    }
-    if let Some(priority) = first_non_empty(&output.priorities) {
-        segments.push(format!("Priority: {priority}"));
-    }
-    if let Some(schedule) = first_non_empty(&output.schedule) {
-        segments.push(format!("Schedule: {schedule}"));
-    }
-    if let Some(alert) = first_non_empty(&output.alerts) {
-        segments.push(format!("Alert: {alert}"));
-    }
-
-    if segments.is_empty() {
-        return "Review your calendar and inbox for today.".to_string();
-    }
-
-    segments.join(" • ")
-}
-
 fn build_urgent_email_notification_body(output: &UrgentEmailSummaryOutput) -> String {
     let mut segments = Vec::new();
 
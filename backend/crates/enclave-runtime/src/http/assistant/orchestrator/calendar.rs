@@ -16,12 +16,12 @@ use uuid::Uuid;
 use super::super::mapping::{log_telemetry, map_calendar_event_to_meeting_source};
 use super::super::memory::{query_context_snippet, session_memory_context};
 use super::super::session_state::EnclaveAssistantSessionState;
-use super::AssistantOrchestratorResult;
 use super::calendar_fallback::{
     build_calendar_context_payload, compare_meetings_by_start_time, default_display_for_window,
     deterministic_calendar_fallback_payload,
 };
 use super::calendar_range::window_from_semantic_time_window;
+use super::{AssistantLaneTelemetry, AssistantOrchestratorResult};
 use crate::RuntimeState;
 use crate::http::rpc;
 
@@ -39,12 +39,12 @@ pub(super) async fn execute_calendar_query(
     let lane_started = Instant::now();
 
     let connector_started = Instant::now();
-    let connector = match state
+    let connectors = match state
         .enclave_service
-        .resolve_active_google_connector_request(user_id)
+        .resolve_active_calendar_connector_requests(user_id)
         .await
     {
-        Ok(connector) => connector,
+        Ok(connectors) => connectors,
         Err(err) => {
             return Err(
                 rpc::map_rpc_service_error(err, Some(request_id.to_string())).into_response(),
@@ -75,10 +75,10 @@ pub(super) async fn execute_calendar_query(
     let fetch_started = Instant::now();
     let fetch_response = match state
         .enclave_service
-        .fetch_google_calendar_events(
-            connector,
-            window.time_min.to_rfc3339(),
-            window.time_max.to_rfc3339(),
+        .fetch_calendar_events_for_connectors(
+            &connectors,
+            &window.time_min.to_rfc3339(),
+            &window.time_max.to_rfc3339(),
             CALENDAR_MAX_RESULTS,
         )
         .await
@@ -198,5 +198,6 @@ pub(super) async fn execute_calendar_query(
         payload,
         response_parts,
         attested_identity: fetch_response.attested_identity,
+        telemetry: AssistantLaneTelemetry::llm(telemetry.model, used_deterministic_fallback),
     })
 }
@@ -16,8 +16,8 @@ use super::super::{
     memory::{query_context_snippet, session_memory_context},
     notifications::non_empty,
 };
-use super::chat_fast_path::is_small_talk_fast_path_query;
-use super::{AssistantOrchestratorResult, local_attested_identity};
+use super::chat_fast_path::{is_small_talk_fast_path_query, small_talk_fast_path_enabled};
+use super::{AssistantLaneTelemetry, AssistantOrchestratorResult, local_attested_identity};
 use crate::RuntimeState;
 
 const QUERY_SNIPPET_MAX_CHARS: usize = 120;
@@ -28,6 +28,7 @@ const CHAT_CONTEXT_PROMPT: &str = "Use the supplied query context and optional s
 struct GeneralChatRenderPayload {
     payload: AssistantStructuredPayload,
     response_style: ChatResponseStyle,
+    telemetry: AssistantLaneTelemetry,
 }
 
 pub(super) async fn execute_general_chat(
@@ -43,6 +44,7 @@ pub(super) async fn execute_general_chat(
         request_id,
         query,
         prior_state,
+        small_talk_fast_path_enabled(state, user_id).await,
     )
     .await;
     let payload = resolved.payload;
@@ -67,6 +69,7 @@ pub(super) async fn execute_general_chat(
         payload: payload.clone(),
         response_parts,
         attested_identity: local_attested_identity(state),
+        telemetry: resolved.telemetry,
     }
 }
 
@@ -76,8 +79,9 @@ async fn resolve_general_chat_payload(
     request_id: &str,
     query: &str,
     prior_state: Option<&EnclaveAssistantSessionState>,
+    small_talk_fast_path: bool,
 ) -> GeneralChatRenderPayload {
-    if is_small_talk_fast_path_query(query) {
+    if small_talk_fast_path && is_small_talk_fast_path_query(query) {
         info!(
             user_id = %user_id,
             request_id,
@@ -133,7 +137,9 @@ async fn resolve_general_chat_payload(
         "assistant general chat llm stage"
     );
 
-    if used_deterministic_fallback {
+    let lane_telemetry = AssistantLaneTelemetry::llm(telemetry.model, used_deterministic_fallback);
+
+    let rendered = if used_deterministic_fallback {
         fallback_general_chat_payload(query, prior_state)
     } else if let AssistantOutputContract::GeneralChatSummary(contract) = resolved.contract {
         let summary = non_empty(contract.output.summary.as_str())
@@ -155,9 +161,14 @@ async fn resolve_general_chat_payload(
                 follow_ups: contract.output.follow_ups,
             },
             response_style: contract.output.response_style,
+            telemetry: AssistantLaneTelemetry::default(),
         }
     } else {
         fallback_general_chat_payload(query, prior_state)
+    };
+    GeneralChatRenderPayload {
+        telemetry: lane_telemetry,
+        ..rendered
     }
 }
 
@@ -243,6 +254,7 @@ pub(super) fn execute_clarification(
         },
         response_parts: vec![AssistantResponsePart::chat_text(text)],
         attested_identity: local_attested_identity(state),
+        telemetry: AssistantLaneTelemetry::default(),
     }
 }
 
@@ -258,6 +270,7 @@ fn fallback_general_chat_payload(
             follow_ups: vec![],
         },
         response_style: ChatResponseStyle::Conversational,
+        telemetry: AssistantLaneTelemetry::default(),
     }
 }
 
@@ -421,6 +434,8 @@ fn capability_label(capability: &AssistantQueryCapability) -> &'static str {
         AssistantQueryCapability::EmailLookup => "email",
         AssistantQueryCapability::GeneralChat => "chat",
         AssistantQueryCapability::Mixed => "calendar and email",
+        AssistantQueryCapability::FocusTime => "focus time",
+        AssistantQueryCapability::EmailCleanup => "inbox cleanup",
     }
 }
 
@@ -519,6 +534,7 @@ mod tests {
             "req-llm-success",
             "plan Alaska in July",
             None,
+            true,
         )
         .await;
         let payload = resolved.payload;
@@ -541,6 +557,7 @@ mod tests {
             "req-llm-failure",
             "how are you doing alfred",
             None,
+            true,
         )
         .await;
         let payload = resolved.payload;
@@ -567,6 +584,7 @@ mod tests {
             "req-robotic-summary",
             "can you help me plan a trip to alaska",
             None,
+            true,
         )
         .await;
         let payload = resolved.payload;
@@ -632,6 +650,7 @@ mod tests {
             "req-small-talk-fast-path",
             "hey, how are you?",
             None,
+            true,
         )
         .await;
 
@@ -641,6 +660,25 @@ mod tests {
         assert_eq!(calls.load(Ordering::Relaxed), 0);
     }
 
+    #[tokio::test]
+    async fn resolve_general_chat_payload_skips_fast_path_when_flag_is_off() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let gateway = CountingLlmGateway {
+            calls: Arc::clone(&calls),
+        };
+        resolve_general_chat_payload(
+            &gateway,
+            Uuid::new_v4(),
+            "req-small-talk-flag-off",
+            "hey, how are you?",
+            None,
+            false,
+        )
+        .await;
+
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+    }
+
     #[test]
     fn general_chat_response_parts_are_chat_text_only() {
         let parts = general_chat_response_parts("Here is your draft.");
@@ -729,6 +767,7 @@ mod tests {
                         model: "mock-model".to_string(),
                         provider_request_id: None,
                         output,
+                        tool_calls: Vec::new(),
                         usage: None,
                     }),
                     Err(message) => Err(LlmGatewayError::ProviderFailure(message)),
@@ -1,4 +1,17 @@
+use shared::feature_flags::FeatureFlag;
 use shared::llm::safety::sanitize_untrusted_text;
+use uuid::Uuid;
+
+use crate::RuntimeState;
+
+/// The fast path skips the planner and LLM, so it can be switched off per cohort when its
+/// canned replies need comparing against model output.
+pub(super) async fn small_talk_fast_path_enabled(state: &RuntimeState, user_id: Uuid) -> bool {
+    state
+        .feature_flags
+        .is_enabled(FeatureFlag::SmallTalkFastPath, user_id)
+        .await
+}
 
 pub(super) fn is_small_talk_fast_path_query(query: &str) -> bool {
     let normalized = normalize_small_talk_query(query);
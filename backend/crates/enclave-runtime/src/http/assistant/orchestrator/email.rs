@@ -17,11 +17,11 @@ use super::super::mapping::{log_telemetry, map_email_candidate_source};
 use super::super::memory::{query_context_snippet, session_memory_context};
 use super::super::notifications::non_empty;
 use super::super::session_state::EnclaveAssistantSessionState;
-use super::AssistantOrchestratorResult;
 use super::email_fallback::{
     deterministic_email_fallback_payload, format_email_key_point, title_for_email_results,
 };
 use super::email_plan::{apply_email_filters, build_gmail_query, plan_email_query};
+use super::{AssistantLaneTelemetry, AssistantOrchestratorResult};
 use crate::RuntimeState;
 use crate::http::rpc;
 
@@ -40,12 +40,12 @@ pub(super) async fn execute_email_query(
     let lane_started = Instant::now();
 
     let connector_started = Instant::now();
-    let connector = match state
+    let connectors = match state
         .enclave_service
-        .resolve_active_google_connector_request(user_id)
+        .resolve_active_email_connector_requests(user_id)
         .await
     {
-        Ok(connector) => connector,
+        Ok(connectors) => connectors,
         Err(err) => {
             return Err(
                 rpc::map_rpc_service_error(err, Some(request_id.to_string())).into_response(),
@@ -76,7 +76,11 @@ pub(super) async fn execute_email_query(
     let fetch_started = Instant::now();
     let fetch_response = match state
         .enclave_service
-        .fetch_google_email_candidates(connector, Some(build_gmail_query(&plan)), EMAIL_MAX_RESULTS)
+        .fetch_email_candidates_for_connectors(
+            &connectors,
+            Some(&build_gmail_query(&plan)),
+            EMAIL_MAX_RESULTS,
+        )
         .await
     {
         Ok(response) => response,
@@ -148,6 +152,7 @@ pub(super) async fn execute_email_query(
         context_prompt: EMAIL_SUMMARY_CONTEXT_PROMPT.to_string(),
         output_schema: output_schema(AssistantCapability::MeetingsSummary),
         context_payload: context_payload.clone(),
+        tools: Vec::new(),
     };
 
     let (llm_result, telemetry) = generate_with_telemetry(
@@ -247,5 +252,6 @@ pub(super) async fn execute_email_query(
         payload,
         response_parts,
         attested_identity: fetch_response.attested_identity,
+        telemetry: AssistantLaneTelemetry::llm(telemetry.model, used_deterministic_fallback),
     })
 }
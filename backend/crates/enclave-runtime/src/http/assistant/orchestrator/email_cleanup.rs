@@ -0,0 +1,279 @@
+use std::collections::{BTreeSet, HashMap};
+use std::time::Instant;
+
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use shared::assistant_semantic_plan::AssistantSemanticPlan;
+use shared::llm::GoogleEmailCandidateSource;
+use shared::models::{
+    AssistantQueryCapability, AssistantResponsePart, AssistantSenderFilterSuggestion,
+    AssistantStructuredPayload,
+};
+use tracing::info;
+use uuid::Uuid;
+
+use super::super::mapping::map_email_candidate_source;
+use super::{AssistantLaneTelemetry, AssistantOrchestratorResult};
+use crate::RuntimeState;
+use crate::http::rpc;
+
+const EMAIL_CLEANUP_MAX_RESULTS: usize = 50;
+const MIN_UNREAD_PER_SENDER: u32 = 2;
+const MAX_SENDER_FILTER_SUGGESTIONS: usize = 10;
+const BULK_CATEGORY_LABELS: [(&str, &str); 4] = [
+    ("CATEGORY_PROMOTIONS", "promotions"),
+    ("CATEGORY_UPDATES", "updates"),
+    ("CATEGORY_SOCIAL", "social"),
+    ("CATEGORY_FORUMS", "forums"),
+];
+
+pub(super) async fn execute_email_cleanup_query(
+    state: &RuntimeState,
+    user_id: Uuid,
+    request_id: &str,
+    semantic_plan: &AssistantSemanticPlan,
+) -> Result<AssistantOrchestratorResult, Response> {
+    let lane_started = Instant::now();
+
+    let connectors = match state
+        .enclave_service
+        .resolve_active_google_connector_requests(user_id)
+        .await
+    {
+        Ok(connectors) => connectors,
+        Err(err) => {
+            return Err(
+                rpc::map_rpc_service_error(err, Some(request_id.to_string())).into_response(),
+            );
+        }
+    };
+
+    let Some(semantic_window) = semantic_plan.time_window.as_ref() else {
+        return Err(rpc::reject(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            shared::enclave::EnclaveRpcErrorEnvelope::new(
+                Some(request_id.to_string()),
+                "rpc_internal_error",
+                "missing semantic time_window for email cleanup query",
+                true,
+            ),
+        )
+        .into_response());
+    };
+
+    let gmail_query = format!(
+        "is:unread after:{} before:{} {{{}}}",
+        semantic_window.start.timestamp(),
+        semantic_window.end.timestamp(),
+        BULK_CATEGORY_LABELS
+            .iter()
+            .map(|(_, category)| format!("category:{category}"))
+            .collect::<Vec<_>>()
+            .join(" ")
+    );
+
+    let fetch_started = Instant::now();
+    let fetch_response = match state
+        .enclave_service
+        .fetch_google_email_candidates_for_connectors(
+            &connectors,
+            Some(&gmail_query),
+            EMAIL_CLEANUP_MAX_RESULTS,
+        )
+        .await
+    {
+        Ok(response) => response,
+        Err(err) => {
+            return Err(
+                rpc::map_rpc_service_error(err, Some(request_id.to_string())).into_response(),
+            );
+        }
+    };
+    let email_fetch_ms = fetch_started.elapsed().as_millis() as u64;
+
+    let candidates = fetch_response
+        .candidates
+        .iter()
+        .map(map_email_candidate_source)
+        .collect::<Vec<_>>();
+    let suggestions = aggregate_bulk_senders(&candidates);
+    let payload = email_cleanup_payload(&suggestions);
+    let display_text = payload.summary.clone();
+    let suggestions_count = suggestions.len();
+    let response_parts = vec![
+        AssistantResponsePart::chat_text(display_text.clone()),
+        AssistantResponsePart::sender_filter_suggestions(payload.clone(), suggestions),
+    ];
+
+    info!(
+        user_id = %user_id,
+        request_id,
+        email_fetch_ms,
+        candidates_count = candidates.len(),
+        suggestions_count,
+        total_email_cleanup_lane_ms = lane_started.elapsed().as_millis() as u64,
+        "assistant email cleanup lane latency breakdown"
+    );
+
+    Ok(AssistantOrchestratorResult {
+        capability: AssistantQueryCapability::EmailCleanup,
+        display_text,
+        payload,
+        response_parts,
+        attested_identity: fetch_response.attested_identity,
+        telemetry: AssistantLaneTelemetry::default(),
+    })
+}
+
+/// Groups unread bulk mail by sender address using Gmail's category labels. Only addresses,
+/// counts, and category names survive aggregation; subjects and snippets are never read.
+fn aggregate_bulk_senders(
+    candidates: &[GoogleEmailCandidateSource],
+) -> Vec<AssistantSenderFilterSuggestion> {
+    let mut senders: HashMap<String, (u32, BTreeSet<&'static str>)> = HashMap::new();
+    for candidate in candidates {
+        let is_unread = candidate
+            .label_ids
+            .iter()
+            .any(|label| label.eq_ignore_ascii_case("UNREAD"));
+        let categories = BULK_CATEGORY_LABELS
+            .iter()
+            .filter(|(label_id, _)| {
+                candidate
+                    .label_ids
+                    .iter()
+                    .any(|label| label.eq_ignore_ascii_case(label_id))
+            })
+            .map(|(_, category)| *category)
+            .collect::<Vec<_>>();
+        if !is_unread || categories.is_empty() {
+            continue;
+        }
+        let Some(sender) = candidate.from.as_deref().and_then(sender_address) else {
+            continue;
+        };
+
+        let entry = senders.entry(sender).or_default();
+        entry.0 += 1;
+        entry.1.extend(categories);
+    }
+
+    let mut suggestions = senders
+        .into_iter()
+        .filter(|(_, (unread_count, _))| *unread_count >= MIN_UNREAD_PER_SENDER)
+        .map(
+            |(sender, (unread_count, categories))| AssistantSenderFilterSuggestion {
+                sender,
+                unread_count,
+                categories: categories.into_iter().map(str::to_string).collect(),
+            },
+        )
+        .collect::<Vec<_>>();
+    suggestions.sort_by(|left, right| {
+        right
+            .unread_count
+            .cmp(&left.unread_count)
+            .then_with(|| left.sender.cmp(&right.sender))
+    });
+    suggestions.truncate(MAX_SENDER_FILTER_SUGGESTIONS);
+    suggestions
+}
+
+fn sender_address(from: &str) -> Option<String> {
+    let address = match (from.rfind('<'), from.rfind('>')) {
+        (Some(start), Some(end)) if start < end => &from[start + 1..end],
+        _ => from,
+    }
+    .trim();
+
+    (address.contains('@') && !address.contains(char::is_whitespace))
+        .then(|| address.to_ascii_lowercase())
+}
+
+fn email_cleanup_payload(
+    suggestions: &[AssistantSenderFilterSuggestion],
+) -> AssistantStructuredPayload {
+    let summary = match suggestions.len() {
+        0 => "No newsletter or bulk sender is piling up unread mail in this window.".to_string(),
+        1 => "One bulk sender is piling up unread mail and is worth filtering.".to_string(),
+        count => format!("{count} bulk senders are piling up unread mail and are worth filtering."),
+    };
+
+    AssistantStructuredPayload {
+        title: "Inbox cleanup suggestions".to_string(),
+        summary,
+        key_points: suggestions
+            .iter()
+            .map(|suggestion| {
+                format!(
+                    "{}: {} unread ({})",
+                    suggestion.sender,
+                    suggestion.unread_count,
+                    suggestion.categories.join(", ")
+                )
+            })
+            .collect(),
+        follow_ups: suggestions
+            .iter()
+            .map(|suggestion| format!("Filter mail from {}", suggestion.sender))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use shared::llm::GoogleEmailCandidateSource;
+
+    use super::{aggregate_bulk_senders, sender_address};
+
+    fn candidate(from: &str, labels: &[&str]) -> GoogleEmailCandidateSource {
+        GoogleEmailCandidateSource {
+            message_id: None,
+            from: Some(from.to_string()),
+            subject: Some("Weekly deals".to_string()),
+            snippet: Some("Save big".to_string()),
+            received_at: None,
+            label_ids: labels.iter().map(|label| label.to_string()).collect(),
+            has_attachments: false,
+        }
+    }
+
+    #[test]
+    fn aggregate_bulk_senders_counts_unread_category_mail_per_sender() {
+        let candidates = vec![
+            candidate(
+                "Shop <Deals@Shop.example>",
+                &["UNREAD", "CATEGORY_PROMOTIONS"],
+            ),
+            candidate("deals@shop.example", &["UNREAD", "CATEGORY_UPDATES"]),
+            candidate("Shop <deals@shop.example>", &["CATEGORY_PROMOTIONS"]),
+            candidate("News <news@paper.example>", &["UNREAD", "CATEGORY_FORUMS"]),
+            candidate("Boss <boss@work.example>", &["UNREAD", "IMPORTANT"]),
+            candidate("Boss <boss@work.example>", &["UNREAD", "IMPORTANT"]),
+        ];
+
+        let suggestions = aggregate_bulk_senders(&candidates);
+
+        assert_eq!(
+            suggestions.len(),
+            1,
+            "single messages and personal mail are skipped"
+        );
+        assert_eq!(suggestions[0].sender, "deals@shop.example");
+        assert_eq!(suggestions[0].unread_count, 2);
+        assert_eq!(suggestions[0].categories, vec!["promotions", "updates"]);
+    }
+
+    #[test]
+    fn sender_address_extracts_bracketed_or_bare_addresses() {
+        assert_eq!(
+            sender_address("Alerts <Alerts@Bank.example>").as_deref(),
+            Some("alerts@bank.example")
+        );
+        assert_eq!(
+            sender_address(" digest@list.example ").as_deref(),
+            Some("digest@list.example")
+        );
+        assert_eq!(sender_address("Undisclosed recipients"), None);
+    }
+}
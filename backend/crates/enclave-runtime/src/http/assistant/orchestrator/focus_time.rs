@@ -0,0 +1,432 @@
+use std::time::Instant;
+
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use chrono::{DateTime, Datelike, Days, Duration, NaiveDate, TimeZone, Timelike, Utc, Weekday};
+use chrono_tz::Tz;
+use shared::assistant_semantic_plan::AssistantSemanticPlan;
+use shared::llm::GoogleCalendarMeetingSource;
+use shared::models::{
+    AssistantFocusBlockProposal, AssistantQueryCapability, AssistantResponsePart,
+    AssistantStructuredPayload,
+};
+use shared::timezone::parse_time_zone_or_default;
+use tracing::info;
+use uuid::Uuid;
+
+use super::super::mapping::map_calendar_event_to_meeting_source;
+use super::{AssistantLaneTelemetry, AssistantOrchestratorResult};
+use crate::RuntimeState;
+use crate::http::rpc;
+
+const FOCUS_TIME_CALENDAR_MAX_RESULTS: usize = 100;
+const WORKDAY_START_HOUR: u32 = 9;
+const WORKDAY_END_HOUR: u32 = 17;
+const MIN_FOCUS_BLOCK_MINUTES: i64 = 60;
+const MAX_FOCUS_BLOCK_MINUTES: i64 = 120;
+const FOCUS_BLOCK_ALIGNMENT_MINUTES: i64 = 15;
+const MAX_FOCUS_BLOCK_PROPOSALS: usize = 5;
+const FOCUS_BLOCK_TITLE: &str = "Focus time";
+
+pub(super) async fn execute_focus_time_query(
+    state: &RuntimeState,
+    user_id: Uuid,
+    request_id: &str,
+    semantic_plan: &AssistantSemanticPlan,
+) -> Result<AssistantOrchestratorResult, Response> {
+    let lane_started = Instant::now();
+
+    let connectors = match state
+        .enclave_service
+        .resolve_active_calendar_connector_requests(user_id)
+        .await
+    {
+        Ok(connectors) => connectors,
+        Err(err) => {
+            return Err(
+                rpc::map_rpc_service_error(err, Some(request_id.to_string())).into_response(),
+            );
+        }
+    };
+
+    let Some(semantic_window) = semantic_plan.time_window.as_ref() else {
+        return Err(rpc::reject(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            shared::enclave::EnclaveRpcErrorEnvelope::new(
+                Some(request_id.to_string()),
+                "rpc_internal_error",
+                "missing semantic time_window for focus time query",
+                true,
+            ),
+        )
+        .into_response());
+    };
+
+    let fetch_started = Instant::now();
+    let fetch_response = match state
+        .enclave_service
+        .fetch_calendar_events_for_connectors(
+            &connectors,
+            &semantic_window.start.to_rfc3339(),
+            &semantic_window.end.to_rfc3339(),
+            FOCUS_TIME_CALENDAR_MAX_RESULTS,
+        )
+        .await
+    {
+        Ok(response) => response,
+        Err(err) => {
+            return Err(
+                rpc::map_rpc_service_error(err, Some(request_id.to_string())).into_response(),
+            );
+        }
+    };
+    let calendar_fetch_ms = fetch_started.elapsed().as_millis() as u64;
+
+    let meetings = fetch_response
+        .events
+        .iter()
+        .map(map_calendar_event_to_meeting_source)
+        .collect::<Vec<_>>();
+    let tz = parse_time_zone_or_default(semantic_window.timezone.as_str());
+    let plan = plan_focus_blocks(
+        &meetings,
+        semantic_window.start.max(Utc::now()),
+        semantic_window.end,
+        &tz,
+    );
+    let payload = focus_time_payload(&plan, &tz);
+    let display_text = payload.summary.clone();
+    let proposals_count = plan.proposals.len();
+    let response_parts = vec![
+        AssistantResponsePart::chat_text(display_text.clone()),
+        AssistantResponsePart::focus_block_proposals(payload.clone(), plan.proposals),
+    ];
+
+    info!(
+        user_id = %user_id,
+        request_id,
+        calendar_fetch_ms,
+        meetings_count = meetings.len(),
+        analyzed_days = plan.days.len(),
+        proposals_count,
+        total_focus_time_lane_ms = lane_started.elapsed().as_millis() as u64,
+        "assistant focus time lane latency breakdown"
+    );
+
+    Ok(AssistantOrchestratorResult {
+        capability: AssistantQueryCapability::FocusTime,
+        display_text,
+        payload,
+        response_parts,
+        attested_identity: fetch_response.attested_identity,
+        telemetry: AssistantLaneTelemetry::default(),
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct DayDensity {
+    date: NaiveDate,
+    meeting_count: usize,
+    busy_minutes: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FocusTimePlan {
+    days: Vec<DayDensity>,
+    proposals: Vec<AssistantFocusBlockProposal>,
+}
+
+/// Measures how booked each weekday's working hours are and proposes one focus block on each
+/// of the busiest days that still has an open gap.
+fn plan_focus_blocks(
+    meetings: &[GoogleCalendarMeetingSource],
+    window_start: DateTime<Utc>,
+    window_end: DateTime<Utc>,
+    tz: &Tz,
+) -> FocusTimePlan {
+    let mut days = Vec::new();
+    let mut candidates = Vec::new();
+    if window_end <= window_start {
+        return FocusTimePlan {
+            days,
+            proposals: Vec::new(),
+        };
+    }
+
+    let last_date = window_end.with_timezone(tz).date_naive();
+    let mut date = window_start.with_timezone(tz).date_naive();
+    while date <= last_date {
+        if let Some((workday_start, workday_end)) = workday_bounds(date, tz) {
+            let day_start = workday_start.max(window_start);
+            let day_end = workday_end.min(window_end);
+            if day_start < day_end {
+                let busy = busy_intervals(meetings, day_start, day_end);
+                let density = DayDensity {
+                    date,
+                    meeting_count: busy.meeting_count,
+                    busy_minutes: busy
+                        .intervals
+                        .iter()
+                        .map(|(start, end)| (*end - *start).num_minutes())
+                        .sum(),
+                };
+                if let Some(proposal) = longest_focus_block(&busy.intervals, day_start, day_end) {
+                    candidates.push((density.busy_minutes, proposal));
+                }
+                days.push(density);
+            }
+        }
+        let Some(next) = date.checked_add_days(Days::new(1)) else {
+            break;
+        };
+        date = next;
+    }
+
+    candidates.sort_by(|left, right| {
+        right
+            .0
+            .cmp(&left.0)
+            .then(left.1.start_at.cmp(&right.1.start_at))
+    });
+    let mut proposals = candidates
+        .into_iter()
+        .take(MAX_FOCUS_BLOCK_PROPOSALS)
+        .map(|(_, proposal)| proposal)
+        .collect::<Vec<_>>();
+    proposals.sort_by_key(|proposal| proposal.start_at);
+
+    FocusTimePlan { days, proposals }
+}
+
+fn workday_bounds(date: NaiveDate, tz: &Tz) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    if matches!(date.weekday(), Weekday::Sat | Weekday::Sun) {
+        return None;
+    }
+
+    let start = tz
+        .from_local_datetime(&date.and_hms_opt(WORKDAY_START_HOUR, 0, 0)?)
+        .earliest()?;
+    let end = tz
+        .from_local_datetime(&date.and_hms_opt(WORKDAY_END_HOUR, 0, 0)?)
+        .earliest()?;
+    Some((start.with_timezone(&Utc), end.with_timezone(&Utc)))
+}
+
+struct BusyIntervals {
+    intervals: Vec<(DateTime<Utc>, DateTime<Utc>)>,
+    meeting_count: usize,
+}
+
+fn busy_intervals(
+    meetings: &[GoogleCalendarMeetingSource],
+    day_start: DateTime<Utc>,
+    day_end: DateTime<Utc>,
+) -> BusyIntervals {
+    let mut clipped = meetings
+        .iter()
+        .filter_map(|meeting| {
+            // All-day events carry no start/end time and do not block focus time.
+            let start = meeting.start_at?.max(day_start);
+            let end = meeting.end_at?.min(day_end);
+            (start < end).then_some((start, end))
+        })
+        .collect::<Vec<_>>();
+    let meeting_count = clipped.len();
+    clipped.sort();
+
+    let mut intervals: Vec<(DateTime<Utc>, DateTime<Utc>)> = Vec::new();
+    for (start, end) in clipped {
+        match intervals.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => intervals.push((start, end)),
+        }
+    }
+
+    BusyIntervals {
+        intervals,
+        meeting_count,
+    }
+}
+
+fn longest_focus_block(
+    busy: &[(DateTime<Utc>, DateTime<Utc>)],
+    day_start: DateTime<Utc>,
+    day_end: DateTime<Utc>,
+) -> Option<AssistantFocusBlockProposal> {
+    let mut gaps = Vec::with_capacity(busy.len() + 1);
+    let mut cursor = day_start;
+    for (start, end) in busy {
+        if *start > cursor {
+            gaps.push((cursor, *start));
+        }
+        cursor = cursor.max(*end);
+    }
+    if cursor < day_end {
+        gaps.push((cursor, day_end));
+    }
+
+    gaps.into_iter()
+        .filter_map(|(start, end)| {
+            let start = align_up(start);
+            let minutes = (end - start).num_minutes().min(MAX_FOCUS_BLOCK_MINUTES);
+            (minutes >= MIN_FOCUS_BLOCK_MINUTES).then_some((start, minutes))
+        })
+        .max_by(|left, right| left.1.cmp(&right.1).then(right.0.cmp(&left.0)))
+        .map(|(start, minutes)| AssistantFocusBlockProposal {
+            title: FOCUS_BLOCK_TITLE.to_string(),
+            start_at: start,
+            end_at: start + Duration::minutes(minutes),
+            duration_minutes: minutes as u32,
+        })
+}
+
+fn align_up(value: DateTime<Utc>) -> DateTime<Utc> {
+    let truncated = value
+        .with_second(0)
+        .and_then(|value| value.with_nanosecond(0))
+        .unwrap_or(value);
+    let minute = i64::from(truncated.minute());
+    let remainder = minute % FOCUS_BLOCK_ALIGNMENT_MINUTES;
+    if remainder == 0 && truncated == value {
+        return value;
+    }
+
+    truncated + Duration::minutes(FOCUS_BLOCK_ALIGNMENT_MINUTES - remainder)
+}
+
+fn focus_time_payload(plan: &FocusTimePlan, tz: &Tz) -> AssistantStructuredPayload {
+    let summary = match plan.proposals.len() {
+        0 => "Your working hours in this window are fully booked, so there is no open block of an hour or more to protect.".to_string(),
+        1 => "I found one open block worth protecting for focus time.".to_string(),
+        count => format!(
+            "I found {count} open blocks worth protecting for focus time, favoring your busiest days."
+        ),
+    };
+
+    AssistantStructuredPayload {
+        title: "Focus time suggestions".to_string(),
+        summary,
+        key_points: plan
+            .days
+            .iter()
+            .map(|day| {
+                format!(
+                    "{}: {} booked across {} meeting{}",
+                    day.date.format("%a %b %-d"),
+                    format_minutes(day.busy_minutes),
+                    day.meeting_count,
+                    if day.meeting_count == 1 { "" } else { "s" }
+                )
+            })
+            .collect(),
+        follow_ups: plan
+            .proposals
+            .iter()
+            .map(|proposal| {
+                let start = proposal.start_at.with_timezone(tz);
+                let end = proposal.end_at.with_timezone(tz);
+                format!(
+                    "Block {} {}-{} for focus time",
+                    start.format("%a %b %-d"),
+                    start.format("%H:%M"),
+                    end.format("%H:%M")
+                )
+            })
+            .collect(),
+    }
+}
+
+fn format_minutes(minutes: i64) -> String {
+    match (minutes / 60, minutes % 60) {
+        (0, minutes) => format!("{minutes}m"),
+        (hours, 0) => format!("{hours}h"),
+        (hours, minutes) => format!("{hours}h {minutes}m"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Utc};
+    use shared::llm::GoogleCalendarMeetingSource;
+
+    use super::{align_up, plan_focus_blocks};
+
+    fn utc(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value)
+            .expect("timestamp should parse")
+            .with_timezone(&Utc)
+    }
+
+    fn meeting(start: &str, end: &str) -> GoogleCalendarMeetingSource {
+        GoogleCalendarMeetingSource {
+            event_id: None,
+            title: Some("Sync".to_string()),
+            start_at: Some(utc(start)),
+            end_at: Some(utc(end)),
+            attendee_emails: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn plan_focus_blocks_prefers_longest_gap_on_busy_weekdays() {
+        // 2026-02-16 is a Monday; the window runs through Sunday.
+        let meetings = vec![
+            meeting("2026-02-16T09:00:00Z", "2026-02-16T11:00:00Z"),
+            meeting("2026-02-16T10:30:00Z", "2026-02-16T12:10:00Z"),
+            meeting("2026-02-16T15:00:00Z", "2026-02-16T16:00:00Z"),
+            meeting("2026-02-17T09:00:00Z", "2026-02-17T17:00:00Z"),
+        ];
+        let plan = plan_focus_blocks(
+            &meetings,
+            utc("2026-02-16T00:00:00Z"),
+            utc("2026-02-23T00:00:00Z"),
+            &chrono_tz::UTC,
+        );
+
+        assert_eq!(plan.days.len(), 5, "weekends are skipped");
+        assert_eq!(plan.days[0].meeting_count, 3);
+        assert_eq!(plan.days[0].busy_minutes, 250);
+        assert_eq!(plan.days[1].busy_minutes, 480);
+
+        let monday = &plan.proposals[0];
+        assert_eq!(monday.start_at, utc("2026-02-16T12:15:00Z"));
+        assert_eq!(monday.end_at, utc("2026-02-16T14:15:00Z"));
+        assert_eq!(monday.duration_minutes, 120);
+        assert!(
+            plan.proposals
+                .iter()
+                .all(|proposal| proposal.start_at.date_naive() != plan.days[1].date),
+            "fully booked days get no proposal"
+        );
+        assert_eq!(plan.proposals.len(), 4);
+    }
+
+    #[test]
+    fn plan_focus_blocks_uses_local_working_hours() {
+        let plan = plan_focus_blocks(
+            &[],
+            utc("2026-02-16T08:00:00Z"),
+            utc("2026-02-17T08:00:00Z"),
+            &chrono_tz::America::Los_Angeles,
+        );
+
+        assert_eq!(plan.proposals.len(), 1);
+        assert_eq!(plan.proposals[0].start_at, utc("2026-02-16T17:00:00Z"));
+    }
+
+    #[test]
+    fn align_up_rounds_to_quarter_hours() {
+        assert_eq!(
+            align_up(utc("2026-02-16T12:00:00Z")),
+            utc("2026-02-16T12:00:00Z")
+        );
+        assert_eq!(
+            align_up(utc("2026-02-16T12:00:30Z")),
+            utc("2026-02-16T12:15:00Z")
+        );
+        assert_eq!(
+            align_up(utc("2026-02-16T12:50:00Z")),
+            utc("2026-02-16T13:00:00Z")
+        );
+    }
+}
@@ -6,9 +6,9 @@ use tracing::warn;
 use uuid::Uuid;
 
 use super::super::session_state::EnclaveAssistantSessionState;
-use super::AssistantOrchestratorResult;
 use super::calendar;
 use super::email;
+use super::{AssistantLaneTelemetry, AssistantOrchestratorResult};
 use crate::RuntimeState;
 
 const MIXED_MAX_CALENDAR_KEY_POINTS: usize = 2;
@@ -62,6 +62,7 @@ pub(super) async fn execute_mixed_query(
                 payload,
                 response_parts,
                 attested_identity: calendar.attested_identity,
+                telemetry: AssistantLaneTelemetry::combine(calendar.telemetry, email.telemetry),
             })
         }
         (Ok(calendar), Err(_)) => {
@@ -83,6 +84,7 @@ pub(super) async fn execute_mixed_query(
                 payload,
                 response_parts,
                 attested_identity: calendar.attested_identity,
+                telemetry: calendar.telemetry,
             })
         }
         (Err(_), Ok(email)) => {
@@ -104,6 +106,7 @@ pub(super) async fn execute_mixed_query(
                 payload,
                 response_parts,
                 attested_identity: email.attested_identity,
+                telemetry: email.telemetry,
             })
         }
         (Err(primary_error), Err(_)) => {
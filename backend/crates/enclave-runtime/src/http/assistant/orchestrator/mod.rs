@@ -16,8 +16,10 @@ mod calendar_range;
 mod chat;
 mod chat_fast_path;
 mod email;
+mod email_cleanup;
 mod email_fallback;
 mod email_plan;
+mod focus_time;
 mod mixed;
 mod planner;
 mod policy;
@@ -28,6 +30,37 @@ pub(super) struct AssistantOrchestratorResult {
     pub(super) payload: AssistantStructuredPayload,
     pub(super) response_parts: Vec<AssistantResponsePart>,
     pub(super) attested_identity: AttestedIdentityPayload,
+    pub(super) telemetry: AssistantLaneTelemetry,
+}
+
+/// Content-free metadata on how a query was answered. Lanes record the model and whether the
+/// deterministic fallback replaced its output; `execute_query` adds the route and latencies.
+#[derive(Debug, Clone, Default)]
+pub(super) struct AssistantLaneTelemetry {
+    pub(super) route: &'static str,
+    pub(super) model: Option<String>,
+    pub(super) output_used_fallback: bool,
+    pub(super) planner_used_fallback: bool,
+    pub(super) planner_ms: u64,
+    pub(super) lane_ms: u64,
+}
+
+impl AssistantLaneTelemetry {
+    fn llm(model: Option<String>, output_used_fallback: bool) -> Self {
+        Self {
+            model,
+            output_used_fallback,
+            ..Self::default()
+        }
+    }
+
+    /// Mixed queries run two lanes; the first model wins and any fallback counts.
+    fn combine(first: Self, second: Self) -> Self {
+        Self::llm(
+            first.model.or(second.model),
+            first.output_used_fallback || second.output_used_fallback,
+        )
+    }
 }
 
 pub(super) async fn execute_query(
@@ -39,9 +72,11 @@ pub(super) async fn execute_query(
 ) -> Result<AssistantOrchestratorResult, Response> {
     let orchestrator_started = Instant::now();
 
-    if chat_fast_path::is_small_talk_fast_path_query(query) {
+    if chat_fast_path::is_small_talk_fast_path_query(query)
+        && chat_fast_path::small_talk_fast_path_enabled(state, user_id).await
+    {
         let lane_started = Instant::now();
-        let execution =
+        let mut execution =
             chat::execute_general_chat(state, user_id, request_id, query, prior_state).await;
         let lane_stage_ms = lane_started.elapsed().as_millis() as u64;
         let total_orchestrator_ms = orchestrator_started.elapsed().as_millis() as u64;
@@ -59,6 +94,8 @@ pub(super) async fn execute_query(
             total_orchestrator_ms,
             "assistant orchestrator latency breakdown"
         );
+        execution.telemetry.route = "general_chat_fast_path";
+        execution.telemetry.lane_ms = lane_stage_ms;
         return Ok(execution);
     }
 
@@ -75,13 +112,13 @@ pub(super) async fn execute_query(
         user_time_zone.as_str(),
         prior_state,
     )
-    .await;
+    .await?;
     let planner_stage_ms = planner_started.elapsed().as_millis() as u64;
     let route = policy::resolve_route_policy(&semantic_plan);
     let route_label = planned_route_label(&route);
 
     let lane_started = Instant::now();
-    let result = match route {
+    let mut result = match route {
         policy::PlannedRoute::Clarify(question) => Ok(chat::execute_clarification(
             state,
             question.as_str(),
@@ -122,6 +159,24 @@ pub(super) async fn execute_query(
                 )
                 .await
             }
+            AssistantQueryCapability::FocusTime => {
+                focus_time::execute_focus_time_query(
+                    state,
+                    user_id,
+                    request_id,
+                    &semantic_plan.plan,
+                )
+                .await
+            }
+            AssistantQueryCapability::EmailCleanup => {
+                email_cleanup::execute_email_cleanup_query(
+                    state,
+                    user_id,
+                    request_id,
+                    &semantic_plan.plan,
+                )
+                .await
+            }
             AssistantQueryCapability::GeneralChat => {
                 Ok(
                     chat::execute_general_chat(state, user_id, request_id, query, prior_state)
@@ -133,6 +188,13 @@ pub(super) async fn execute_query(
     let lane_stage_ms = lane_started.elapsed().as_millis() as u64;
     let total_orchestrator_ms = orchestrator_started.elapsed().as_millis() as u64;
 
+    if let Ok(execution) = &mut result {
+        execution.telemetry.route = route_label;
+        execution.telemetry.planner_used_fallback = semantic_plan.used_deterministic_fallback;
+        execution.telemetry.planner_ms = planner_stage_ms;
+        execution.telemetry.lane_ms = lane_stage_ms;
+    }
+
     match &result {
         Ok(execution) => {
             info!(
@@ -185,6 +247,8 @@ fn capability_label(capability: &AssistantQueryCapability) -> &'static str {
         AssistantQueryCapability::EmailLookup => "email_lookup",
         AssistantQueryCapability::GeneralChat => "general_chat",
         AssistantQueryCapability::Mixed => "mixed",
+        AssistantQueryCapability::FocusTime => "focus_time",
+        AssistantQueryCapability::EmailCleanup => "email_cleanup",
     }
 }
 
@@ -1,3 +1,4 @@
+use axum::response::{IntoResponse, Response};
 use chrono::{Days, Utc};
 use serde_json::{Value, json};
 use shared::assistant_semantic_plan::{
@@ -5,6 +6,7 @@ use shared::assistant_semantic_plan::{
     AssistantSemanticTimeWindow, AssistantTimeWindowResolutionSource,
     normalize_semantic_plan_output,
 };
+use shared::enclave::EnclaveRpcError;
 use shared::llm::{
     AssistantCapability, AssistantOutputContract, LlmExecutionSource, LlmGatewayError,
     LlmGatewayRequest, generate_with_telemetry, sanitize_context_payload, template_for_capability,
@@ -20,6 +22,7 @@ use super::super::memory::{
 };
 use super::super::session_state::EnclaveAssistantSessionState;
 use crate::RuntimeState;
+use crate::http::rpc;
 use shared::timezone::{local_day_bounds_utc, parse_time_zone_or_default};
 
 pub(super) struct SemanticPlanResolution {
@@ -34,7 +37,7 @@ pub(super) async fn resolve_semantic_plan(
     query: &str,
     user_time_zone: &str,
     prior_state: Option<&EnclaveAssistantSessionState>,
-) -> SemanticPlanResolution {
+) -> Result<SemanticPlanResolution, Response> {
     let now_utc = Utc::now();
     let now_local = now_utc
         .with_timezone(&parse_time_zone_or_default(user_time_zone))
@@ -92,10 +95,10 @@ pub(super) async fn resolve_semantic_plan(
                     needs_clarification = plan.needs_clarification,
                     "assistant semantic planner resolved model output"
                 );
-                SemanticPlanResolution {
+                Ok(SemanticPlanResolution {
                     plan,
                     used_deterministic_fallback: false,
-                }
+                })
             }
             Err(err) => {
                 warn!(
@@ -103,22 +106,36 @@ pub(super) async fn resolve_semantic_plan(
                     request_id,
                     "assistant semantic planner output was invalid, falling back deterministically: {err}"
                 );
-                SemanticPlanResolution {
+                Ok(SemanticPlanResolution {
                     plan: deterministic_fallback_plan(query, user_time_zone, prior_state),
                     used_deterministic_fallback: true,
-                }
+                })
             }
         },
+        // A spent budget refuses every later lane too, so the query fails instead of
+        // degrading into a deterministic answer the user cannot tell apart.
+        Err(LlmGatewayError::BudgetExceeded(message)) => {
+            warn!(
+                user_id = %user_id,
+                request_id,
+                "assistant semantic planner refused by llm spend budget: {message}"
+            );
+            Err(rpc::map_rpc_service_error(
+                EnclaveRpcError::LlmBudgetExceeded { message },
+                Some(request_id.to_string()),
+            )
+            .into_response())
+        }
         Err(err) => {
             warn!(
                 user_id = %user_id,
                 request_id,
                 "assistant semantic planner request failed, falling back deterministically: {err}"
             );
-            SemanticPlanResolution {
+            Ok(SemanticPlanResolution {
                 plan: deterministic_fallback_plan(query, user_time_zone, prior_state),
                 used_deterministic_fallback: true,
-            }
+            })
         }
     }
 }
@@ -196,6 +213,8 @@ fn requires_time_window(capability: &AssistantQueryCapability) -> bool {
             | &AssistantQueryCapability::CalendarLookup
             | &AssistantQueryCapability::EmailLookup
             | &AssistantQueryCapability::Mixed
+            | &AssistantQueryCapability::FocusTime
+            | &AssistantQueryCapability::EmailCleanup
     )
 }
 
@@ -255,6 +274,16 @@ fn default_window_for_capability(
     timezone_name: String,
 ) -> Option<AssistantSemanticTimeWindow> {
     match capability {
+        AssistantQueryCapability::EmailCleanup => {
+            let start_date = local_today.checked_sub_days(Days::new(30))?;
+            let (start_utc, _) = local_day_bounds_utc(start_date, user_time_zone)?;
+            Some(AssistantSemanticTimeWindow {
+                start: start_utc,
+                end: now_utc,
+                timezone: timezone_name,
+                resolution_source: AssistantTimeWindowResolutionSource::DefaultWindow,
+            })
+        }
         AssistantQueryCapability::EmailLookup => {
             let start_date = local_today.checked_sub_days(Days::new(7))?;
             let (start_utc, _) = local_day_bounds_utc(start_date, user_time_zone)?;
@@ -273,6 +302,17 @@ fn default_window_for_capability(
                 window
             })
         }
+        AssistantQueryCapability::FocusTime => {
+            let last_date = local_today.checked_add_days(Days::new(6))?;
+            let (start_utc, _) = local_day_bounds_utc(local_today, user_time_zone)?;
+            let (_, end_utc) = local_day_bounds_utc(last_date, user_time_zone)?;
+            Some(AssistantSemanticTimeWindow {
+                start: start_utc,
+                end: end_utc,
+                timezone: timezone_name,
+                resolution_source: AssistantTimeWindowResolutionSource::DefaultWindow,
+            })
+        }
         AssistantQueryCapability::GeneralChat => None,
     }
 }
@@ -285,6 +325,8 @@ fn map_to_semantic_capability(capability: AssistantQueryCapability) -> Assistant
         AssistantQueryCapability::EmailLookup => AssistantSemanticCapability::EmailLookup,
         AssistantQueryCapability::GeneralChat => AssistantSemanticCapability::GeneralChat,
         AssistantQueryCapability::Mixed => AssistantSemanticCapability::Mixed,
+        AssistantQueryCapability::FocusTime => AssistantSemanticCapability::FocusTime,
+        AssistantQueryCapability::EmailCleanup => AssistantSemanticCapability::EmailCleanup,
     }
 }
 
@@ -295,6 +337,8 @@ fn capability_label(capability: AssistantQueryCapability) -> &'static str {
         AssistantQueryCapability::EmailLookup => "email_lookup",
         AssistantQueryCapability::GeneralChat => "general_chat",
         AssistantQueryCapability::Mixed => "mixed",
+        AssistantQueryCapability::FocusTime => "focus_time",
+        AssistantQueryCapability::EmailCleanup => "email_cleanup",
     }
 }
 
@@ -84,6 +84,8 @@ fn requires_time_window(capability: &AssistantQueryCapability) -> bool {
             | AssistantQueryCapability::CalendarLookup
             | AssistantQueryCapability::EmailLookup
             | AssistantQueryCapability::Mixed
+            | AssistantQueryCapability::FocusTime
+            | AssistantQueryCapability::EmailCleanup
     )
 }
 
@@ -3,10 +3,11 @@ use std::collections::HashMap;
 use axum::Json;
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use serde_json::Value;
+use shared::brief_profile::MorningBriefProfile;
 use shared::enclave::{
-    ENCLAVE_RPC_CONTRACT_VERSION, EnclaveGeneratedNotificationPayload,
+    AttestedIdentityPayload, ENCLAVE_RPC_CONTRACT_VERSION, EnclaveGeneratedNotificationPayload,
     EnclaveRpcGenerateMorningBriefRequest, EnclaveRpcGenerateMorningBriefResponse,
     EnclaveRpcGenerateUrgentEmailSummaryRequest, EnclaveRpcGenerateUrgentEmailSummaryResponse,
 };
@@ -18,13 +19,15 @@ use shared::llm::{
 };
 use shared::timezone::{local_day_bounds_utc, user_local_date};
 use tracing::warn;
+use uuid::Uuid;
 
 use super::mapping::{
     append_llm_telemetry_metadata, log_telemetry, map_calendar_event_to_meeting_source,
     map_email_candidate_source,
 };
 use super::notifications::{
-    non_empty, notification_from_morning_brief, notification_from_urgent_email, urgency_label,
+    NotificationContent, non_empty, notification_from_morning_brief,
+    notification_from_urgent_email, urgency_label,
 };
 use crate::RuntimeState;
 use crate::http::rpc;
@@ -49,46 +52,127 @@ pub(super) async fn generate_morning_brief(
         .into_response();
     }
 
-    let local_date = user_local_date(Utc::now(), &request.time_zone);
-    let Some((time_min, time_max)) = local_day_bounds_utc(local_date, &request.time_zone) else {
-        return rpc::reject(
+    let brief = match build_morning_brief(
+        &state,
+        MorningBriefInput {
+            user_id: request.user_id,
+            request_id: request.request_id.as_str(),
+            now: Utc::now(),
+            time_zone: request.time_zone.as_str(),
+            local_time: request.morning_brief_local_time.as_str(),
+            brief_profile: request.brief_profile.as_ref(),
+            source: LlmExecutionSource::WorkerMorningBrief,
+        },
+    )
+    .await
+    {
+        Ok(brief) => brief,
+        Err(response) => return response,
+    };
+
+    let mut metadata = brief.metadata;
+    metadata.insert(
+        "action_source".to_string(),
+        "enclave_morning_brief_llm_orchestrator".to_string(),
+    );
+    metadata.insert(
+        "llm_output_source".to_string(),
+        match brief.output_source {
+            SafeOutputSource::ModelOutput => "model_output",
+            SafeOutputSource::DeterministicFallback => "deterministic_fallback",
+        }
+        .to_string(),
+    );
+    metadata.insert(
+        "attested_measurement".to_string(),
+        brief.attested_identity.measurement.clone(),
+    );
+
+    Json(EnclaveRpcGenerateMorningBriefResponse {
+        contract_version: ENCLAVE_RPC_CONTRACT_VERSION.to_string(),
+        request_id: request.request_id,
+        notification: EnclaveGeneratedNotificationPayload {
+            title: brief.notification.title,
+            body: brief.notification.body,
+        },
+        metadata,
+        attested_identity: brief.attested_identity,
+    })
+    .into_response()
+}
+
+pub(super) struct MorningBriefInput<'a> {
+    pub(super) user_id: Uuid,
+    pub(super) request_id: &'a str,
+    /// Picks the local day the brief covers.
+    pub(super) now: DateTime<Utc>,
+    pub(super) time_zone: &'a str,
+    pub(super) local_time: &'a str,
+    /// Falls back to the default profile when absent.
+    pub(super) brief_profile: Option<&'a MorningBriefProfile>,
+    pub(super) source: LlmExecutionSource,
+}
+
+pub(super) struct MorningBriefResult {
+    pub(super) notification: NotificationContent,
+    pub(super) output_source: SafeOutputSource,
+    pub(super) metadata: HashMap<String, String>,
+    pub(super) attested_identity: AttestedIdentityPayload,
+}
+
+/// Generates the user's brief for the local day of `input.now` from today's calendar and
+/// urgent email candidates, shaping the push notification with the brief profile.
+pub(super) async fn build_morning_brief(
+    state: &RuntimeState,
+    input: MorningBriefInput<'_>,
+) -> Result<MorningBriefResult, Response> {
+    let request_id = input.request_id;
+    let local_date = user_local_date(input.now, input.time_zone);
+    let Some((time_min, time_max)) = local_day_bounds_utc(local_date, input.time_zone) else {
+        return Err(rpc::reject(
             StatusCode::BAD_REQUEST,
             shared::enclave::EnclaveRpcErrorEnvelope::new(
-                Some(request.request_id),
+                Some(request_id.to_string()),
                 "invalid_request_payload",
                 "unable to resolve local-day boundaries for the supplied time zone",
                 false,
             ),
         )
-        .into_response();
+        .into_response());
     };
 
-    let calendar_response = match state
+    let connectors = state
+        .enclave_service
+        .resolve_active_google_connector_requests(input.user_id)
+        .await
+        .map_err(|err| {
+            rpc::map_rpc_service_error(err, Some(request_id.to_string())).into_response()
+        })?;
+
+    let calendar_response = state
         .enclave_service
-        .fetch_google_calendar_events(
-            request.connector.clone(),
-            time_min.to_rfc3339(),
-            time_max.to_rfc3339(),
+        .fetch_google_calendar_events_for_connectors(
+            &connectors,
+            &time_min.to_rfc3339(),
+            &time_max.to_rfc3339(),
             CALENDAR_MAX_RESULTS,
         )
         .await
-    {
-        Ok(response) => response,
-        Err(err) => {
-            return rpc::map_rpc_service_error(err, Some(request.request_id)).into_response();
-        }
-    };
+        .map_err(|err| {
+            rpc::map_rpc_service_error(err, Some(request_id.to_string())).into_response()
+        })?;
 
-    let urgent_response = match state
+    let urgent_response = state
         .enclave_service
-        .fetch_google_urgent_email_candidates(request.connector, URGENT_EMAIL_CANDIDATE_MAX_RESULTS)
+        .fetch_google_email_candidates_for_connectors(
+            &connectors,
+            None,
+            URGENT_EMAIL_CANDIDATE_MAX_RESULTS,
+        )
         .await
-    {
-        Ok(response) => response,
-        Err(err) => {
-            return rpc::map_rpc_service_error(err, Some(request.request_id)).into_response();
-        }
-    };
+        .map_err(|err| {
+            rpc::map_rpc_service_error(err, Some(request_id.to_string())).into_response()
+        })?;
 
     let meetings = calendar_response
         .events
@@ -101,47 +185,37 @@ pub(super) async fn generate_morning_brief(
         .map(map_email_candidate_source)
         .collect::<Vec<_>>();
 
-    let context = assemble_morning_brief_context(
-        local_date,
-        &request.morning_brief_local_time,
-        &meetings,
-        &candidates,
-    );
-    let raw_context_payload = match serde_json::to_value(&context) {
-        Ok(payload) => payload,
-        Err(_) => {
-            return rpc::reject(
-                StatusCode::INTERNAL_SERVER_ERROR,
-                shared::enclave::EnclaveRpcErrorEnvelope::new(
-                    Some(request.request_id),
-                    "rpc_internal_error",
-                    "failed to serialize morning brief context",
-                    true,
-                ),
-            )
-            .into_response();
-        }
-    };
+    let context =
+        assemble_morning_brief_context(local_date, input.local_time, &meetings, &candidates)
+            .with_brief_profile(input.brief_profile.cloned());
+    let raw_context_payload = serde_json::to_value(&context).map_err(|_| {
+        rpc::reject(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            shared::enclave::EnclaveRpcErrorEnvelope::new(
+                Some(request_id.to_string()),
+                "rpc_internal_error",
+                "failed to serialize morning brief context",
+                true,
+            ),
+        )
+        .into_response()
+    })?;
     let context_payload = sanitize_context_payload(&raw_context_payload);
 
     let llm_request = LlmGatewayRequest::from_template(
         template_for_capability(AssistantCapability::MorningBrief),
         context_payload.clone(),
     )
-    .with_requester_id(request.user_id.to_string());
+    .with_requester_id(input.user_id.to_string());
 
-    let (llm_result, telemetry) = generate_with_telemetry(
-        state.worker_gateway(),
-        LlmExecutionSource::WorkerMorningBrief,
-        llm_request,
-    )
-    .await;
-    log_telemetry(request.user_id, &telemetry, "morning_brief");
+    let (llm_result, telemetry) =
+        generate_with_telemetry(state.worker_gateway(), input.source, llm_request).await;
+    log_telemetry(input.user_id, &telemetry, "morning_brief");
 
     let model_output = match llm_result {
         Ok(response) => response.output,
         Err(err) => {
-            warn!(user_id = %request.user_id, "morning brief provider request failed: {err}");
+            warn!(user_id = %input.user_id, "morning brief provider request failed: {err}");
             Value::Null
         }
     };
@@ -157,24 +231,23 @@ pub(super) async fn generate_morning_brief(
     );
 
     let AssistantOutputContract::MorningBrief(contract) = resolved.contract else {
-        return rpc::reject(
+        return Err(rpc::reject(
             StatusCode::INTERNAL_SERVER_ERROR,
             shared::enclave::EnclaveRpcErrorEnvelope::new(
-                Some(request.request_id),
+                Some(request_id.to_string()),
                 "rpc_internal_error",
                 "morning brief contract resolution failed",
                 true,
             ),
         )
-        .into_response();
+        .into_response());
     };
 
-    let notification = notification_from_morning_brief(&contract.output);
-    let mut metadata = HashMap::new();
-    metadata.insert(
-        "action_source".to_string(),
-        "enclave_morning_brief_llm_orchestrator".to_string(),
+    let notification = notification_from_morning_brief(
+        &contract.output,
+        &input.brief_profile.cloned().unwrap_or_default(),
     );
+    let mut metadata = HashMap::new();
     metadata.insert(
         "meetings_in_context".to_string(),
         context.meetings_today_count.to_string(),
@@ -184,30 +257,17 @@ pub(super) async fn generate_morning_brief(
         context.urgent_email_candidate_count.to_string(),
     );
     metadata.insert(
-        "llm_output_source".to_string(),
-        match resolved.source {
-            SafeOutputSource::ModelOutput => "model_output",
-            SafeOutputSource::DeterministicFallback => "deterministic_fallback",
-        }
-        .to_string(),
-    );
-    metadata.insert(
-        "attested_measurement".to_string(),
-        calendar_response.attested_identity.measurement.clone(),
+        "brief_profile_applied".to_string(),
+        input.brief_profile.is_some().to_string(),
     );
     append_llm_telemetry_metadata(&mut metadata, &telemetry);
 
-    Json(EnclaveRpcGenerateMorningBriefResponse {
-        contract_version: ENCLAVE_RPC_CONTRACT_VERSION.to_string(),
-        request_id: request.request_id,
-        notification: EnclaveGeneratedNotificationPayload {
-            title: notification.title,
-            body: notification.body,
-        },
+    Ok(MorningBriefResult {
+        notification,
+        output_source: resolved.source,
         metadata,
         attested_identity: calendar_response.attested_identity,
     })
-    .into_response()
 }
 
 pub(super) async fn generate_urgent_email_summary(
@@ -230,9 +290,20 @@ pub(super) async fn generate_urgent_email_summary(
     let max_results = request
         .max_results
         .clamp(1, URGENT_EMAIL_CANDIDATE_MAX_RESULTS);
+    let connectors = match state
+        .enclave_service
+        .resolve_active_email_connector_requests(request.user_id)
+        .await
+    {
+        Ok(connectors) => connectors,
+        Err(err) => {
+            return rpc::map_rpc_service_error(err, Some(request.request_id)).into_response();
+        }
+    };
+
     let fetch_response = match state
         .enclave_service
-        .fetch_google_urgent_email_candidates(request.connector, max_results)
+        .fetch_email_candidates_for_connectors(&connectors, None, max_results)
         .await
     {
         Ok(response) => response,
@@ -5,8 +5,8 @@ use chrono::Utc;
 use shared::assistant_crypto::{decrypt_assistant_request, encrypt_assistant_response};
 use shared::assistant_memory::ASSISTANT_SESSION_MEMORY_VERSION_V1;
 use shared::enclave::{
-    ENCLAVE_RPC_CONTRACT_VERSION, EnclaveRpcProcessAssistantQueryRequest,
-    EnclaveRpcProcessAssistantQueryResponse,
+    ENCLAVE_RPC_CONTRACT_VERSION, EnclaveAssistantQueryTelemetry,
+    EnclaveRpcProcessAssistantQueryRequest, EnclaveRpcProcessAssistantQueryResponse,
 };
 use shared::models::AssistantPlaintextQueryResponse;
 use uuid::Uuid;
@@ -26,7 +26,7 @@ pub(super) async fn process_assistant_query(
     let request_id = request.request_id.clone();
 
     let (plaintext, selected_key) =
-        match decrypt_assistant_request(&state.config.assistant_ingress_keys, &request.envelope) {
+        match decrypt_assistant_request(&state.ingress_keys.keyring(), &request.envelope) {
             Ok(result) => result,
             Err(err) => {
                 return rpc::reject(
@@ -197,6 +197,15 @@ pub(super) async fn process_assistant_query(
         envelope: encrypted_response,
         session_state: Some(encrypted_session_state),
         attested_identity: execution.attested_identity,
+        telemetry: Some(EnclaveAssistantQueryTelemetry {
+            route: execution.telemetry.route.to_string(),
+            capability: response_contract.capability,
+            model: execution.telemetry.model,
+            planner_used_fallback: execution.telemetry.planner_used_fallback,
+            output_used_fallback: execution.telemetry.output_used_fallback,
+            planner_ms: execution.telemetry.planner_ms,
+            lane_ms: execution.telemetry.lane_ms,
+        }),
     })
     .into_response()
 }
@@ -0,0 +1,93 @@
+use axum::Json;
+use axum::response::{IntoResponse, Response};
+use chrono::Utc;
+use shared::assistant_crypto::{AssistantCryptoError, encrypt_assistant_session_export};
+use shared::enclave::{
+    ENCLAVE_RPC_CONTRACT_VERSION, EnclaveRpcExportAssistantSessionsRequest,
+    EnclaveRpcExportAssistantSessionsResponse,
+};
+use shared::models::{AssistantPlaintextExportedSession, AssistantPlaintextSessionExport};
+use tracing::warn;
+
+use super::automation::{internal_error, runtime_attested_identity};
+use super::departure::invalid_request;
+use super::session_state::decrypt_session_state;
+use crate::RuntimeState;
+
+const SESSION_EXPORT_FORMAT_VERSION: u32 = 1;
+const SESSION_EXPORT_MAX_SESSIONS: usize = 500;
+
+/// Opens each sealed session and re-encrypts the combined transcript to the requesting
+/// client. Sessions that no longer decrypt are reported back by id instead of failing the
+/// whole export.
+pub(super) async fn export_assistant_sessions(
+    state: RuntimeState,
+    request: EnclaveRpcExportAssistantSessionsRequest,
+) -> Response {
+    if request.sessions.len() > SESSION_EXPORT_MAX_SESSIONS {
+        return invalid_request(
+            request.request_id,
+            format!("session export is limited to {SESSION_EXPORT_MAX_SESSIONS} sessions"),
+        );
+    }
+
+    let now = Utc::now();
+    let mut sessions = Vec::with_capacity(request.sessions.len());
+    let mut skipped_session_ids = Vec::new();
+    for item in &request.sessions {
+        match decrypt_session_state(&state, &item.state, request.user_id, item.session_id, now) {
+            Ok(session_state) => sessions.push(AssistantPlaintextExportedSession {
+                session_id: item.session_id,
+                created_at: item.created_at,
+                updated_at: item.updated_at,
+                last_capability: session_state.last_capability,
+                memory: session_state.memory,
+            }),
+            Err(reason) => {
+                warn!(
+                    request_id = %request.request_id,
+                    session_id = %item.session_id,
+                    "skipping session in export: {reason}"
+                );
+                skipped_session_ids.push(item.session_id);
+            }
+        }
+    }
+
+    let exported_session_ids = sessions.iter().map(|session| session.session_id).collect();
+    let export = AssistantPlaintextSessionExport {
+        format_version: SESSION_EXPORT_FORMAT_VERSION,
+        exported_at: now,
+        sessions,
+    };
+
+    let envelope = match encrypt_assistant_session_export(
+        &state.ingress_keys.keyring().active,
+        request.export_request_id.as_str(),
+        request.client_ephemeral_public_key.as_str(),
+        &export,
+    ) {
+        Ok(envelope) => envelope,
+        Err(
+            err @ (AssistantCryptoError::InvalidPublicKey
+            | AssistantCryptoError::InvalidBase64Field { .. }
+            | AssistantCryptoError::MissingRequestId),
+        ) => return invalid_request(request.request_id, err.to_string()),
+        Err(err) => {
+            return internal_error(
+                request.request_id.as_str(),
+                format!("failed to encrypt session export: {err}"),
+            );
+        }
+    };
+
+    Json(EnclaveRpcExportAssistantSessionsResponse {
+        contract_version: ENCLAVE_RPC_CONTRACT_VERSION.to_string(),
+        request_id: request.request_id,
+        envelope,
+        exported_session_ids,
+        skipped_session_ids,
+        attested_identity: runtime_attested_identity(&state),
+    })
+    .into_response()
+}
@@ -31,12 +31,11 @@ pub(super) fn decrypt_session_state(
         return Err("session state has expired".to_string());
     }
 
-    let key = state
-        .config
-        .assistant_ingress_keys
+    let keyring = state.ingress_keys.keyring();
+    let key = keyring
         .key_for_id(envelope.key_id.as_str())
         .ok_or_else(|| "session state key is not recognized".to_string())?;
-    let is_active_key = key.key_id == state.config.assistant_ingress_keys.active.key_id;
+    let is_active_key = key.key_id == keyring.active.key_id;
     if !is_active_key && key.key_expires_at < now.timestamp() {
         return Err("session state key has expired".to_string());
     }
@@ -82,7 +81,7 @@ pub(super) fn encrypt_session_state(
     session_id: Uuid,
     now: DateTime<Utc>,
 ) -> Result<AssistantSessionStateEnvelope, String> {
-    let key = &state.config.assistant_ingress_keys.active;
+    let key = &state.ingress_keys.keyring().active;
     let nonce_source = Uuid::new_v4();
     let nonce_bytes = &nonce_source.as_bytes()[..12];
 
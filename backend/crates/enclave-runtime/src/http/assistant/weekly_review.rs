@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use chrono::{DateTime, Days, Utc};
+use serde_json::Value;
+use shared::llm::contracts::WeeklyReviewOutput;
+use shared::llm::{
+    AssistantCapability, AssistantOutputContract, LlmExecutionSource, LlmGatewayRequest,
+    SafeOutputSource, WEEKLY_REVIEW_DAYS, assemble_weekly_review_context, generate_with_telemetry,
+    resolve_safe_output, sanitize_context_payload, template_for_capability,
+};
+use shared::timezone::{local_day_bounds_utc, user_local_date};
+use tracing::warn;
+use uuid::Uuid;
+
+use super::mapping::{
+    append_llm_telemetry_metadata, log_telemetry, map_calendar_event_to_meeting_source,
+    map_email_candidate_source,
+};
+use crate::RuntimeState;
+use crate::http::rpc;
+
+const WEEKLY_REVIEW_CALENDAR_MAX_RESULTS: usize = 20;
+const WEEKLY_REVIEW_EMAIL_MAX_RESULTS: usize = 20;
+const WEEKLY_REVIEW_GMAIL_QUERY: &str = "newer_than:7d";
+
+pub(super) struct WeeklyReviewResult {
+    pub(super) review: WeeklyReviewOutput,
+    pub(super) review_start_date: String,
+    pub(super) review_end_date: String,
+    pub(super) output_source: SafeOutputSource,
+    pub(super) metadata: HashMap<String, String>,
+}
+
+/// Builds the weekly review for the seven local days ending on the run's scheduled date
+/// plus a preview of the following seven days. `focus` is the rule's decrypted prompt and
+/// only steers emphasis.
+pub(super) async fn generate_weekly_review(
+    state: &RuntimeState,
+    user_id: Uuid,
+    request_id: &str,
+    scheduled_for: DateTime<Utc>,
+    time_zone: &str,
+    focus: Option<&str>,
+) -> Result<WeeklyReviewResult, Response> {
+    let review_end_date = user_local_date(scheduled_for, time_zone);
+    let review_start_date = review_end_date - Days::new(WEEKLY_REVIEW_DAYS - 1);
+    let upcoming_end_date = review_end_date + Days::new(WEEKLY_REVIEW_DAYS);
+    let (Some((past_min, _)), Some((_, past_max)), Some((_, upcoming_max))) = (
+        local_day_bounds_utc(review_start_date, time_zone),
+        local_day_bounds_utc(review_end_date, time_zone),
+        local_day_bounds_utc(upcoming_end_date, time_zone),
+    ) else {
+        return Err(rpc::reject(
+            StatusCode::BAD_REQUEST,
+            shared::enclave::EnclaveRpcErrorEnvelope::new(
+                Some(request_id.to_string()),
+                "invalid_request_payload",
+                "unable to resolve weekly review boundaries for the supplied time zone",
+                false,
+            ),
+        )
+        .into_response());
+    };
+
+    let connectors = state
+        .enclave_service
+        .resolve_active_google_connector_requests(user_id)
+        .await
+        .map_err(|err| {
+            rpc::map_rpc_service_error(err, Some(request_id.to_string())).into_response()
+        })?;
+
+    let mut events = Vec::new();
+    for (time_min, time_max) in [(past_min, past_max), (past_max, upcoming_max)] {
+        let response = state
+            .enclave_service
+            .fetch_google_calendar_events_for_connectors(
+                &connectors,
+                &time_min.to_rfc3339(),
+                &time_max.to_rfc3339(),
+                WEEKLY_REVIEW_CALENDAR_MAX_RESULTS,
+            )
+            .await
+            .map_err(|err| {
+                rpc::map_rpc_service_error(err, Some(request_id.to_string())).into_response()
+            })?;
+        events.extend(response.events);
+    }
+
+    let email_response = state
+        .enclave_service
+        .fetch_google_email_candidates_for_connectors(
+            &connectors,
+            Some(WEEKLY_REVIEW_GMAIL_QUERY),
+            WEEKLY_REVIEW_EMAIL_MAX_RESULTS,
+        )
+        .await
+        .map_err(|err| {
+            rpc::map_rpc_service_error(err, Some(request_id.to_string())).into_response()
+        })?;
+
+    let meetings = events
+        .iter()
+        .map(map_calendar_event_to_meeting_source)
+        .collect::<Vec<_>>();
+    let emails = email_response
+        .candidates
+        .iter()
+        .map(map_email_candidate_source)
+        .collect::<Vec<_>>();
+
+    let context = assemble_weekly_review_context(review_end_date, &meetings, &emails, focus);
+    let raw_context_payload = serde_json::to_value(&context).map_err(|_| {
+        rpc::reject(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            shared::enclave::EnclaveRpcErrorEnvelope::new(
+                Some(request_id.to_string()),
+                "rpc_internal_error",
+                "failed to serialize weekly review context",
+                true,
+            ),
+        )
+        .into_response()
+    })?;
+    let context_payload = sanitize_context_payload(&raw_context_payload);
+
+    let llm_request = LlmGatewayRequest::from_template(
+        template_for_capability(AssistantCapability::WeeklyReview),
+        context_payload.clone(),
+    )
+    .with_requester_id(user_id.to_string());
+
+    let (llm_result, telemetry) = generate_with_telemetry(
+        state.worker_gateway(),
+        LlmExecutionSource::WorkerAutomationRun,
+        llm_request,
+    )
+    .await;
+    log_telemetry(user_id, &telemetry, "weekly_review");
+
+    let model_output = match llm_result {
+        Ok(response) => response.output,
+        Err(err) => {
+            warn!(user_id = %user_id, "weekly review provider request failed: {err}");
+            Value::Null
+        }
+    };
+
+    let resolved = resolve_safe_output(
+        AssistantCapability::WeeklyReview,
+        if model_output.is_null() {
+            None
+        } else {
+            Some(&model_output)
+        },
+        &context_payload,
+    );
+
+    let AssistantOutputContract::WeeklyReview(contract) = resolved.contract else {
+        return Err(rpc::reject(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            shared::enclave::EnclaveRpcErrorEnvelope::new(
+                Some(request_id.to_string()),
+                "rpc_internal_error",
+                "weekly review contract resolution failed",
+                true,
+            ),
+        )
+        .into_response());
+    };
+
+    let mut metadata = HashMap::new();
+    metadata.insert(
+        "llm_past_meetings_in_context".to_string(),
+        context.past_meeting_count.to_string(),
+    );
+    metadata.insert(
+        "llm_emails_in_context".to_string(),
+        context.email_count.to_string(),
+    );
+    metadata.insert(
+        "llm_upcoming_meetings_in_context".to_string(),
+        context.upcoming_meeting_count.to_string(),
+    );
+    append_llm_telemetry_metadata(&mut metadata, &telemetry);
+
+    Ok(WeeklyReviewResult {
+        review: contract.output,
+        review_start_date: context.review_start_date,
+        review_end_date: context.review_end_date,
+        output_source: resolved.source,
+        metadata,
+    })
+}
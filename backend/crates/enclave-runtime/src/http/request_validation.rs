@@ -1,12 +1,18 @@
+use std::sync::atomic::Ordering;
+
 use axum::http::{HeaderMap, StatusCode};
 use shared::enclave::{
-    ENCLAVE_RPC_CONTRACT_VERSION, EnclaveRpcCompleteGoogleConnectRequest,
-    EnclaveRpcExchangeGoogleTokenRequest, EnclaveRpcExecuteAutomationRequest,
+    ENCLAVE_RPC_CONTRACT_VERSION, EnclaveRpcCompleteCaldavConnectRequest,
+    EnclaveRpcCompleteGoogleConnectRequest, EnclaveRpcCompleteImapConnectRequest,
+    EnclaveRpcDraftAutomationRequest, EnclaveRpcExchangeGoogleTokenRequest,
+    EnclaveRpcExecuteAutomationRequest, EnclaveRpcExportAssistantSessionsRequest,
     EnclaveRpcFetchAssistantAttestedKeyRequest, EnclaveRpcFetchGoogleCalendarEventsRequest,
     EnclaveRpcFetchGoogleUrgentEmailCandidatesRequest, EnclaveRpcGenerateMorningBriefRequest,
-    EnclaveRpcGenerateUrgentEmailSummaryRequest, EnclaveRpcProcessAssistantQueryRequest,
-    EnclaveRpcRevokeGoogleTokenRequest,
+    EnclaveRpcGenerateUrgentEmailSummaryRequest, EnclaveRpcPlanDepartureAlertRequest,
+    EnclaveRpcProcessAssistantQueryRequest, EnclaveRpcRevokeGoogleTokenRequest,
+    EnclaveRpcSignAuditEventRequest,
 };
+use tracing::info;
 
 use super::rpc;
 use crate::RuntimeState;
@@ -36,6 +42,26 @@ impl RpcEnvelope for EnclaveRpcCompleteGoogleConnectRequest {
     }
 }
 
+impl RpcEnvelope for EnclaveRpcCompleteCaldavConnectRequest {
+    fn contract_version(&self) -> &str {
+        &self.contract_version
+    }
+
+    fn request_id(&self) -> &str {
+        &self.request_id
+    }
+}
+
+impl RpcEnvelope for EnclaveRpcCompleteImapConnectRequest {
+    fn contract_version(&self) -> &str {
+        &self.contract_version
+    }
+
+    fn request_id(&self) -> &str {
+        &self.request_id
+    }
+}
+
 impl RpcEnvelope for EnclaveRpcRevokeGoogleTokenRequest {
     fn contract_version(&self) -> &str {
         &self.contract_version
@@ -116,7 +142,47 @@ impl RpcEnvelope for EnclaveRpcExecuteAutomationRequest {
     }
 }
 
-pub(super) fn validate_request<Request>(
+impl RpcEnvelope for EnclaveRpcPlanDepartureAlertRequest {
+    fn contract_version(&self) -> &str {
+        &self.contract_version
+    }
+
+    fn request_id(&self) -> &str {
+        &self.request_id
+    }
+}
+
+impl RpcEnvelope for EnclaveRpcExportAssistantSessionsRequest {
+    fn contract_version(&self) -> &str {
+        &self.contract_version
+    }
+
+    fn request_id(&self) -> &str {
+        &self.request_id
+    }
+}
+
+impl RpcEnvelope for EnclaveRpcDraftAutomationRequest {
+    fn contract_version(&self) -> &str {
+        &self.contract_version
+    }
+
+    fn request_id(&self) -> &str {
+        &self.request_id
+    }
+}
+
+impl RpcEnvelope for EnclaveRpcSignAuditEventRequest {
+    fn contract_version(&self) -> &str {
+        &self.contract_version
+    }
+
+    fn request_id(&self) -> &str {
+        &self.request_id
+    }
+}
+
+pub(super) async fn validate_request<Request>(
     state: &RuntimeState,
     headers: &HeaderMap,
     path: &str,
@@ -125,13 +191,29 @@ pub(super) fn validate_request<Request>(
 where
     Request: serde::de::DeserializeOwned + RpcEnvelope,
 {
-    rpc::authorize_request(
-        &state.config.enclave_rpc_auth,
-        &state.rpc_replay_guard,
-        headers,
-        path,
-        body,
-    )?;
+    if state.config.enclave_rpc_security_mode.requires_signature() {
+        let caller = rpc::authorize_request(
+            &state.config.enclave_rpc_auth,
+            &state.rpc_replay_guard,
+            headers,
+            path,
+            body,
+        )
+        .await?;
+        if caller.key_slot == rpc::CallerKeySlot::Secondary {
+            // Operators watch this count reach zero before removing the secondary keys.
+            let secondary_key_uses =
+                state.rpc_secondary_key_uses.fetch_add(1, Ordering::Relaxed) + 1;
+            info!(
+                caller_id = %caller.caller_id,
+                path,
+                secondary_key_uses,
+                "enclave rpc secondary key metrics"
+            );
+        }
+    } else {
+        rpc::check_contract_version(headers)?;
+    }
 
     let request = serde_json::from_slice::<Request>(body).map_err(|_| {
         rpc::reject(
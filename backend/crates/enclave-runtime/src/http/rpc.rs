@@ -3,12 +3,19 @@ use axum::http::{HeaderMap, StatusCode};
 use axum::response::{IntoResponse, Response};
 use chrono::Utc;
 use shared::enclave::{
-    ENCLAVE_RPC_AUTH_NONCE_HEADER, ENCLAVE_RPC_AUTH_SIGNATURE_HEADER,
-    ENCLAVE_RPC_AUTH_TIMESTAMP_HEADER, ENCLAVE_RPC_CONTRACT_VERSION,
-    ENCLAVE_RPC_CONTRACT_VERSION_HEADER, EnclaveRpcError, EnclaveRpcErrorEnvelope,
-    sign_rpc_request,
+    ENCLAVE_RPC_AUTH_CALLER_HEADER, ENCLAVE_RPC_AUTH_NONCE_HEADER,
+    ENCLAVE_RPC_AUTH_SIGNATURE_HEADER, ENCLAVE_RPC_AUTH_TIMESTAMP_HEADER,
+    ENCLAVE_RPC_CONTRACT_VERSION, ENCLAVE_RPC_CONTRACT_VERSION_HEADER, EnclaveRpcCallerKeys,
+    EnclaveRpcError, EnclaveRpcErrorEnvelope, SignedRpcRequest,
 };
 
+use shared::replay_guard::NonceReplayGuard;
+
+use crate::config::EnclaveRpcVerifierConfig;
+
+const RPC_REPLAY_SCOPE: &str = "enclave_rpc";
+
+#[derive(Debug)]
 pub(super) struct RpcRejection {
     pub(super) status: StatusCode,
     pub(super) body: EnclaveRpcErrorEnvelope,
@@ -26,13 +33,8 @@ pub(super) fn reject(status: StatusCode, body: EnclaveRpcErrorEnvelope) -> Box<R
     Box::new(RpcRejection { status, body })
 }
 
-pub(super) fn authorize_request(
-    auth: &shared::enclave::EnclaveRpcAuthConfig,
-    replay_guard: &std::sync::Mutex<std::collections::HashMap<String, i64>>,
-    headers: &HeaderMap,
-    path: &str,
-    body: &[u8],
-) -> RpcResult<()> {
+/// The only header check left in mTLS-only mode, where the handshake authenticates the caller.
+pub(super) fn check_contract_version(headers: &HeaderMap) -> RpcResult<()> {
     let contract_header = require_header(headers, ENCLAVE_RPC_CONTRACT_VERSION_HEADER)?;
     if contract_header != ENCLAVE_RPC_CONTRACT_VERSION {
         return Err(reject(
@@ -46,6 +48,56 @@ pub(super) fn authorize_request(
         ));
     }
 
+    Ok(())
+}
+
+/// Which registered key a request verified against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum CallerKeySlot {
+    Primary,
+    /// A key from an unexpired rotation overlap window.
+    Secondary,
+}
+
+#[derive(Debug)]
+pub(super) struct AuthorizedCaller {
+    pub(super) caller_id: String,
+    pub(super) key_slot: CallerKeySlot,
+}
+
+/// Verifies the caller's Ed25519 signature and rejects replayed nonces. Callers missing from the
+/// registered keys are refused before their signature is checked.
+pub(super) async fn authorize_request(
+    auth: &EnclaveRpcVerifierConfig,
+    replay_guard: &NonceReplayGuard,
+    headers: &HeaderMap,
+    path: &str,
+    body: &[u8],
+) -> RpcResult<AuthorizedCaller> {
+    check_contract_version(headers)?;
+
+    let now = Utc::now().timestamp();
+    let secondary_keys = auth
+        .secondary_caller_keys
+        .as_ref()
+        .filter(|secondary| now <= secondary.expires_at)
+        .map(|secondary| &secondary.caller_keys);
+
+    let caller_id = require_header(headers, ENCLAVE_RPC_AUTH_CALLER_HEADER)?;
+    if !auth.caller_keys.contains(&caller_id)
+        && !secondary_keys.is_some_and(|keys| keys.contains(&caller_id))
+    {
+        return Err(reject(
+            StatusCode::UNAUTHORIZED,
+            EnclaveRpcErrorEnvelope::new(
+                None,
+                "unknown_rpc_caller",
+                "RPC caller is not registered",
+                false,
+            ),
+        ));
+    }
+
     let timestamp = require_header(headers, ENCLAVE_RPC_AUTH_TIMESTAMP_HEADER).and_then(|raw| {
         raw.parse::<i64>().map_err(|_| {
             reject(
@@ -74,7 +126,6 @@ pub(super) fn authorize_request(
     }
 
     let signature = require_header(headers, ENCLAVE_RPC_AUTH_SIGNATURE_HEADER)?;
-    let now = Utc::now().timestamp();
     let max_skew = auth.max_clock_skew_seconds as i64;
     if (now - timestamp).abs() > max_skew {
         return Err(reject(
@@ -88,9 +139,24 @@ pub(super) fn authorize_request(
         ));
     }
 
-    let expected_signature =
-        sign_rpc_request(&auth.shared_secret, "POST", path, timestamp, &nonce, body);
-    if !shared::enclave::constant_time_eq(&expected_signature, &signature) {
+    let verifies = |keys: &EnclaveRpcCallerKeys| {
+        keys.verify(
+            &caller_id,
+            SignedRpcRequest {
+                method: "POST",
+                path,
+                timestamp,
+                nonce: &nonce,
+                body,
+            },
+            &signature,
+        )
+    };
+    let key_slot = if verifies(&auth.caller_keys) {
+        CallerKeySlot::Primary
+    } else if secondary_keys.is_some_and(verifies) {
+        CallerKeySlot::Secondary
+    } else {
         return Err(reject(
             StatusCode::UNAUTHORIZED,
             EnclaveRpcErrorEnvelope::new(
@@ -100,7 +166,7 @@ pub(super) fn authorize_request(
                 false,
             ),
         ));
-    }
+    };
 
     let replay_window_expires = timestamp.checked_add(max_skew).ok_or_else(|| {
         reject(
@@ -114,22 +180,15 @@ pub(super) fn authorize_request(
         )
     })?;
 
-    let mut replay_guard = replay_guard.lock().map_err(|_| {
-        reject(
-            StatusCode::INTERNAL_SERVER_ERROR,
-            EnclaveRpcErrorEnvelope::new(
-                None,
-                "rpc_internal_error",
-                "Replay guard unavailable",
-                true,
-            ),
-        )
-    })?;
-    replay_guard.retain(|_, expires_at| *expires_at >= now);
-
     if replay_guard
-        .insert(nonce.to_string(), replay_window_expires)
-        .is_some()
+        .check_and_record(
+            RPC_REPLAY_SCOPE,
+            &format!("{caller_id}:{nonce}"),
+            replay_window_expires,
+            now,
+        )
+        .await
+        .is_err()
     {
         return Err(reject(
             StatusCode::UNAUTHORIZED,
@@ -142,7 +201,10 @@ pub(super) fn authorize_request(
         ));
     }
 
-    Ok(())
+    Ok(AuthorizedCaller {
+        caller_id,
+        key_slot,
+    })
 }
 
 fn require_header(headers: &HeaderMap, key: &str) -> RpcResult<String> {
@@ -236,6 +298,22 @@ pub(super) fn map_rpc_service_error(
                 true,
             )),
         ),
+        EnclaveRpcError::ProviderRateLimited { retry_after_ms, .. } => (
+            StatusCode::TOO_MANY_REQUESTS,
+            Json(EnclaveRpcErrorEnvelope::with_provider_rate_limit(
+                request_id,
+                retry_after_ms,
+            )),
+        ),
+        EnclaveRpcError::LlmBudgetExceeded { .. } => (
+            StatusCode::TOO_MANY_REQUESTS,
+            Json(EnclaveRpcErrorEnvelope::new(
+                request_id,
+                "llm_budget_exceeded",
+                "Monthly LLM spend budget exhausted",
+                false,
+            )),
+        ),
         EnclaveRpcError::RpcUnauthorized { code } => (
             StatusCode::UNAUTHORIZED,
             Json(EnclaveRpcErrorEnvelope::new(
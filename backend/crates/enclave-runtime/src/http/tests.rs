@@ -1,31 +1,41 @@
-use std::collections::HashMap;
-use std::sync::Mutex;
-
 use axum::http::{HeaderMap, HeaderValue, StatusCode};
 use chrono::Utc;
 use shared::enclave::{
-    ENCLAVE_RPC_AUTH_NONCE_HEADER, ENCLAVE_RPC_AUTH_SIGNATURE_HEADER,
-    ENCLAVE_RPC_AUTH_TIMESTAMP_HEADER, ENCLAVE_RPC_CONTRACT_VERSION,
-    ENCLAVE_RPC_CONTRACT_VERSION_HEADER, ENCLAVE_RPC_PATH_EXCHANGE_GOOGLE_TOKEN,
-    EnclaveRpcAuthConfig, sign_rpc_request,
+    ENCLAVE_RPC_AUTH_CALLER_HEADER, ENCLAVE_RPC_AUTH_NONCE_HEADER,
+    ENCLAVE_RPC_AUTH_SIGNATURE_HEADER, ENCLAVE_RPC_AUTH_TIMESTAMP_HEADER,
+    ENCLAVE_RPC_CONTRACT_VERSION, ENCLAVE_RPC_CONTRACT_VERSION_HEADER,
+    ENCLAVE_RPC_PATH_EXCHANGE_GOOGLE_TOKEN, EnclaveRpcCallerKeys, EnclaveRpcSigningKey,
+    SignedRpcRequest,
 };
+use shared::replay_guard::NonceReplayGuard;
 
-use super::rpc::authorize_request;
+use super::rpc::{CallerKeySlot, authorize_request};
+use crate::config::{EnclaveRpcVerifierConfig, SecondaryCallerKeys};
 
 fn signed_headers(
-    auth: &EnclaveRpcAuthConfig,
+    signing_key: &EnclaveRpcSigningKey,
     path: &str,
     body: &[u8],
     timestamp: i64,
     nonce: &str,
 ) -> HeaderMap {
-    let signature = sign_rpc_request(&auth.shared_secret, "POST", path, timestamp, nonce, body);
+    let signature = signing_key.sign(SignedRpcRequest {
+        method: "POST",
+        path,
+        timestamp,
+        nonce,
+        body,
+    });
 
     let mut headers = HeaderMap::new();
     headers.insert(
         ENCLAVE_RPC_CONTRACT_VERSION_HEADER,
         HeaderValue::from_static(ENCLAVE_RPC_CONTRACT_VERSION),
     );
+    headers.insert(
+        ENCLAVE_RPC_AUTH_CALLER_HEADER,
+        HeaderValue::from_str(signing_key.caller_id()).expect("caller header should parse"),
+    );
     headers.insert(
         ENCLAVE_RPC_AUTH_TIMESTAMP_HEADER,
         HeaderValue::from_str(&timestamp.to_string()).expect("timestamp header should parse"),
@@ -42,27 +52,65 @@ fn signed_headers(
     headers
 }
 
-fn default_auth() -> EnclaveRpcAuthConfig {
-    EnclaveRpcAuthConfig {
-        shared_secret: "unit-test-shared-secret-123".to_string(),
+fn api_server_key() -> EnclaveRpcSigningKey {
+    EnclaveRpcSigningKey::new("api-server", [21_u8; 32])
+}
+
+fn worker_key() -> EnclaveRpcSigningKey {
+    EnclaveRpcSigningKey::new("worker", [22_u8; 32])
+}
+
+fn caller_keys(callers: &[EnclaveRpcSigningKey]) -> EnclaveRpcCallerKeys {
+    let registered = callers
+        .iter()
+        .map(|key| format!("{}:{}", key.caller_id(), key.public_key_b64()))
+        .collect::<Vec<_>>()
+        .join(",");
+    EnclaveRpcCallerKeys::parse(&registered).expect("test caller keys should parse")
+}
+
+fn auth_for(callers: &[EnclaveRpcSigningKey]) -> EnclaveRpcVerifierConfig {
+    EnclaveRpcVerifierConfig {
+        caller_keys: caller_keys(callers),
+        secondary_caller_keys: None,
         max_clock_skew_seconds: 30,
     }
 }
 
-#[test]
-fn authorize_request_allows_valid_signed_request() {
+/// The worker is mid-rotation: its new key is primary and its old key stays accepted until
+/// `expires_at`.
+fn rotating_auth(expires_at: i64) -> EnclaveRpcVerifierConfig {
+    EnclaveRpcVerifierConfig {
+        secondary_caller_keys: Some(SecondaryCallerKeys {
+            caller_keys: caller_keys(&[worker_key()]),
+            expires_at,
+        }),
+        ..auth_for(&[api_server_key(), rotated_worker_key()])
+    }
+}
+
+fn rotated_worker_key() -> EnclaveRpcSigningKey {
+    EnclaveRpcSigningKey::new("worker", [23_u8; 32])
+}
+
+fn default_auth() -> EnclaveRpcVerifierConfig {
+    auth_for(&[api_server_key(), worker_key()])
+}
+
+#[tokio::test]
+async fn authorize_request_allows_valid_signed_request() {
     let auth = default_auth();
     let body = br#"{"request_id":"req-1"}"#;
     let nonce = "rpc-nonce-1";
     let timestamp = Utc::now().timestamp();
     let headers = signed_headers(
-        &auth,
+        &api_server_key(),
         ENCLAVE_RPC_PATH_EXCHANGE_GOOGLE_TOKEN,
         body,
         timestamp,
         nonce,
     );
-    let replay_guard = Mutex::new(HashMap::new());
+    let replay_guard = NonceReplayGuard::default();
 
     let result = authorize_request(
         &auth,
@@ -70,25 +118,26 @@ fn authorize_request_allows_valid_signed_request() {
         &headers,
         ENCLAVE_RPC_PATH_EXCHANGE_GOOGLE_TOKEN,
         body,
-    );
+    )
+    .await;
     assert!(result.is_ok(), "valid RPC auth request should pass");
 }
 
-#[test]
-fn authorize_request_rejects_missing_signature_header() {
+#[tokio::test]
+async fn authorize_request_rejects_missing_signature_header() {
     let auth = default_auth();
     let body = br#"{"request_id":"req-1"}"#;
     let nonce = "rpc-nonce-2";
     let timestamp = Utc::now().timestamp();
     let mut headers = signed_headers(
-        &auth,
+        &api_server_key(),
         ENCLAVE_RPC_PATH_EXCHANGE_GOOGLE_TOKEN,
         body,
         timestamp,
         nonce,
     );
     headers.remove(ENCLAVE_RPC_AUTH_SIGNATURE_HEADER);
-    let replay_guard = Mutex::new(HashMap::new());
+    let replay_guard = NonceReplayGuard::default();
 
     let err = authorize_request(
         &auth,
@@ -97,20 +146,21 @@ fn authorize_request_rejects_missing_signature_header() {
         ENCLAVE_RPC_PATH_EXCHANGE_GOOGLE_TOKEN,
         body,
     )
+    .await
     .expect_err("missing auth signature header must fail");
 
     assert_eq!(err.status, StatusCode::UNAUTHORIZED);
     assert_eq!(err.body.error.code, "missing_request_header");
 }
 
-#[test]
-fn authorize_request_rejects_invalid_signature() {
+#[tokio::test]
+async fn authorize_request_rejects_invalid_signature() {
     let auth = default_auth();
     let body = br#"{"request_id":"req-1"}"#;
     let nonce = "rpc-nonce-3";
     let timestamp = Utc::now().timestamp();
     let mut headers = signed_headers(
-        &auth,
+        &api_server_key(),
         ENCLAVE_RPC_PATH_EXCHANGE_GOOGLE_TOKEN,
         body,
         timestamp,
@@ -120,7 +170,7 @@ fn authorize_request_rejects_invalid_signature() {
         ENCLAVE_RPC_AUTH_SIGNATURE_HEADER,
         HeaderValue::from_static("deadbeef"),
     );
-    let replay_guard = Mutex::new(HashMap::new());
+    let replay_guard = NonceReplayGuard::default();
 
     let err = authorize_request(
         &auth,
@@ -129,26 +179,27 @@ fn authorize_request_rejects_invalid_signature() {
         ENCLAVE_RPC_PATH_EXCHANGE_GOOGLE_TOKEN,
         body,
     )
+    .await
     .expect_err("signature mismatch must fail");
 
     assert_eq!(err.status, StatusCode::UNAUTHORIZED);
     assert_eq!(err.body.error.code, "invalid_request_signature");
 }
 
-#[test]
-fn authorize_request_rejects_timestamp_outside_skew() {
+#[tokio::test]
+async fn authorize_request_rejects_timestamp_outside_skew() {
     let auth = default_auth();
     let body = br#"{"request_id":"req-1"}"#;
     let nonce = "rpc-nonce-4";
     let timestamp = Utc::now().timestamp() - 120;
     let headers = signed_headers(
-        &auth,
+        &api_server_key(),
         ENCLAVE_RPC_PATH_EXCHANGE_GOOGLE_TOKEN,
         body,
         timestamp,
         nonce,
     );
-    let replay_guard = Mutex::new(HashMap::new());
+    let replay_guard = NonceReplayGuard::default();
 
     let err = authorize_request(
         &auth,
@@ -157,26 +208,27 @@ fn authorize_request_rejects_timestamp_outside_skew() {
         ENCLAVE_RPC_PATH_EXCHANGE_GOOGLE_TOKEN,
         body,
     )
+    .await
     .expect_err("stale timestamp must fail");
 
     assert_eq!(err.status, StatusCode::UNAUTHORIZED);
     assert_eq!(err.body.error.code, "invalid_request_timestamp");
 }
 
-#[test]
-fn authorize_request_rejects_nonce_replay() {
+#[tokio::test]
+async fn authorize_request_rejects_nonce_replay() {
     let auth = default_auth();
     let body = br#"{"request_id":"req-1"}"#;
     let nonce = "rpc-replay-nonce";
     let timestamp = Utc::now().timestamp();
     let headers = signed_headers(
-        &auth,
+        &api_server_key(),
         ENCLAVE_RPC_PATH_EXCHANGE_GOOGLE_TOKEN,
         body,
         timestamp,
         nonce,
     );
-    let replay_guard = Mutex::new(HashMap::new());
+    let replay_guard = NonceReplayGuard::default();
 
     let first = authorize_request(
         &auth,
@@ -184,7 +236,8 @@ fn authorize_request_rejects_nonce_replay() {
         &headers,
         ENCLAVE_RPC_PATH_EXCHANGE_GOOGLE_TOKEN,
         body,
-    );
+    )
+    .await;
     assert!(first.is_ok(), "first nonce use should pass");
 
     let err = authorize_request(
@@ -194,8 +247,152 @@ fn authorize_request_rejects_nonce_replay() {
         ENCLAVE_RPC_PATH_EXCHANGE_GOOGLE_TOKEN,
         body,
     )
+    .await
     .expect_err("nonce replay should fail");
 
     assert_eq!(err.status, StatusCode::UNAUTHORIZED);
     assert_eq!(err.body.error.code, "request_replay_detected");
 }
+
+#[tokio::test]
+async fn authorize_request_rejects_revoked_caller() {
+    // The worker's key was removed from the registry; the api-server keeps working.
+    let auth = auth_for(&[api_server_key()]);
+    let body = br#"{"request_id":"req-1"}"#;
+    let timestamp = Utc::now().timestamp();
+    let replay_guard = NonceReplayGuard::default();
+
+    let err = authorize_request(
+        &auth,
+        &replay_guard,
+        &signed_headers(
+            &worker_key(),
+            ENCLAVE_RPC_PATH_EXCHANGE_GOOGLE_TOKEN,
+            body,
+            timestamp,
+            "rpc-nonce-revoked",
+        ),
+        ENCLAVE_RPC_PATH_EXCHANGE_GOOGLE_TOKEN,
+        body,
+    )
+    .await
+    .expect_err("revoked caller must fail");
+    assert_eq!(err.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(err.body.error.code, "unknown_rpc_caller");
+
+    let result = authorize_request(
+        &auth,
+        &replay_guard,
+        &signed_headers(
+            &api_server_key(),
+            ENCLAVE_RPC_PATH_EXCHANGE_GOOGLE_TOKEN,
+            body,
+            timestamp,
+            "rpc-nonce-revoked",
+        ),
+        ENCLAVE_RPC_PATH_EXCHANGE_GOOGLE_TOKEN,
+        body,
+    )
+    .await;
+    assert!(result.is_ok(), "remaining callers should be unaffected");
+}
+
+#[tokio::test]
+async fn authorize_request_rejects_signature_from_another_callers_key() {
+    let auth = default_auth();
+    let body = br#"{"request_id":"req-1"}"#;
+    let timestamp = Utc::now().timestamp();
+    let mut headers = signed_headers(
+        &worker_key(),
+        ENCLAVE_RPC_PATH_EXCHANGE_GOOGLE_TOKEN,
+        body,
+        timestamp,
+        "rpc-nonce-impersonation",
+    );
+    headers.insert(
+        ENCLAVE_RPC_AUTH_CALLER_HEADER,
+        HeaderValue::from_static("api-server"),
+    );
+    let replay_guard = NonceReplayGuard::default();
+
+    let err = authorize_request(
+        &auth,
+        &replay_guard,
+        &headers,
+        ENCLAVE_RPC_PATH_EXCHANGE_GOOGLE_TOKEN,
+        body,
+    )
+    .await
+    .expect_err("a caller must not sign as another caller");
+
+    assert_eq!(err.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(err.body.error.code, "invalid_request_signature");
+}
+
+#[tokio::test]
+async fn authorize_request_accepts_secondary_keys_during_the_rotation_window() {
+    let auth = rotating_auth(Utc::now().timestamp() + 600);
+    let body = br#"{"request_id":"req-1"}"#;
+    let timestamp = Utc::now().timestamp();
+    let replay_guard = NonceReplayGuard::default();
+
+    let old_key = authorize_request(
+        &auth,
+        &replay_guard,
+        &signed_headers(
+            &worker_key(),
+            ENCLAVE_RPC_PATH_EXCHANGE_GOOGLE_TOKEN,
+            body,
+            timestamp,
+            "rpc-nonce-rotation-old",
+        ),
+        ENCLAVE_RPC_PATH_EXCHANGE_GOOGLE_TOKEN,
+        body,
+    )
+    .await
+    .expect("the old key should verify during the overlap window");
+    assert_eq!(old_key.key_slot, CallerKeySlot::Secondary);
+
+    let new_key = authorize_request(
+        &auth,
+        &replay_guard,
+        &signed_headers(
+            &rotated_worker_key(),
+            ENCLAVE_RPC_PATH_EXCHANGE_GOOGLE_TOKEN,
+            body,
+            timestamp,
+            "rpc-nonce-rotation-new",
+        ),
+        ENCLAVE_RPC_PATH_EXCHANGE_GOOGLE_TOKEN,
+        body,
+    )
+    .await
+    .expect("the new key should verify");
+    assert_eq!(new_key.key_slot, CallerKeySlot::Primary);
+}
+
+#[tokio::test]
+async fn authorize_request_rejects_secondary_keys_after_the_rotation_window() {
+    let auth = rotating_auth(Utc::now().timestamp() - 1);
+    let body = br#"{"request_id":"req-1"}"#;
+    let replay_guard = NonceReplayGuard::default();
+
+    let err = authorize_request(
+        &auth,
+        &replay_guard,
+        &signed_headers(
+            &worker_key(),
+            ENCLAVE_RPC_PATH_EXCHANGE_GOOGLE_TOKEN,
+            body,
+            Utc::now().timestamp(),
+            "rpc-nonce-rotation-expired",
+        ),
+        ENCLAVE_RPC_PATH_EXCHANGE_GOOGLE_TOKEN,
+        body,
+    )
+    .await
+    .expect_err("an expired secondary key must not verify");
+
+    assert_eq!(err.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(err.body.error.code, "invalid_request_signature");
+}
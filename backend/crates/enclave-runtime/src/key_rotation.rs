@@ -0,0 +1,443 @@
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use shared::assistant_crypto::{
+    AssistantIngressKeyMaterial, AssistantIngressKeyring, WrappedAssistantIngressKey,
+    derive_public_key_b64, generate_assistant_ingress_private_key,
+    unwrap_assistant_ingress_private_key, wrap_assistant_ingress_private_key,
+};
+use shared::models::AssistantAttestedKeyRotation;
+use shared::repos::{
+    AssistantIngressKeyRecord, AssistantIngressKeyStatus, NewAssistantIngressKey, Store,
+};
+use tracing::{info, warn};
+
+use crate::config::IngressKeyRotationConfig;
+
+/// Ingress keyring shared by every request handler. Rotation swaps the whole keyring at once,
+/// so a handler always sees a consistent active/previous pair.
+#[derive(Clone)]
+pub(crate) struct IngressKeys {
+    state: Arc<RwLock<IngressKeysState>>,
+}
+
+struct IngressKeysState {
+    keyring: AssistantIngressKeyring,
+    activated_at: Option<i64>,
+}
+
+impl IngressKeys {
+    pub(crate) fn new(keyring: AssistantIngressKeyring) -> Self {
+        Self {
+            state: Arc::new(RwLock::new(IngressKeysState {
+                keyring,
+                activated_at: None,
+            })),
+        }
+    }
+
+    pub(crate) fn keyring(&self) -> AssistantIngressKeyring {
+        self.read().keyring.clone()
+    }
+
+    pub(crate) fn rotation_status(
+        &self,
+        rotation: Option<&IngressKeyRotationConfig>,
+        now: i64,
+    ) -> AssistantAttestedKeyRotation {
+        let state = self.read();
+        let previous = state
+            .keyring
+            .previous
+            .as_ref()
+            .filter(|key| key.key_expires_at > now);
+
+        AssistantAttestedKeyRotation {
+            rotation_enabled: rotation.is_some(),
+            activated_at: state.activated_at,
+            next_rotation_at: rotation.and_then(|rotation| {
+                state.activated_at.map(|activated_at| {
+                    activated_at.saturating_add(rotation.interval_seconds as i64)
+                })
+            }),
+            previous_key_id: previous.map(|key| key.key_id.clone()),
+            previous_key_expires_at: previous.map(|key| key.key_expires_at),
+        }
+    }
+
+    fn replace(&self, keyring: AssistantIngressKeyring, activated_at: i64) {
+        let mut state = self
+            .state
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        state.keyring = keyring;
+        state.activated_at = Some(activated_at);
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, IngressKeysState> {
+        self.state
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Keeps the in-memory keyring in step with the persisted one and rotates the active key once
+/// it is older than the configured interval. Every enclave instance runs this loop; the store
+/// only accepts a rotation from the instance that still saw the replaced key as active.
+pub(crate) async fn run_ingress_key_rotation(
+    store: Store,
+    keys: IngressKeys,
+    boot_key: AssistantIngressKeyMaterial,
+    rotation: IngressKeyRotationConfig,
+) {
+    let sync_interval = Duration::from_secs(rotation.sync_interval_seconds);
+    loop {
+        if let Err(err) = sync_ingress_keys(&store, &keys, &boot_key, &rotation, Utc::now()).await {
+            warn!(error = %err, "assistant ingress key sync failed; retrying");
+        }
+        tokio::time::sleep(sync_interval).await;
+    }
+}
+
+async fn sync_ingress_keys(
+    store: &Store,
+    keys: &IngressKeys,
+    boot_key: &AssistantIngressKeyMaterial,
+    rotation: &IngressKeyRotationConfig,
+    now: DateTime<Utc>,
+) -> Result<(), String> {
+    let mut records = store
+        .list_live_assistant_ingress_keys(now)
+        .await
+        .map_err(|err| err.to_string())?;
+
+    let active = records
+        .iter()
+        .find(|record| record.status == AssistantIngressKeyStatus::Active);
+    if rotation_due(active, rotation, now) {
+        let expected_active_key_id = active.map(|record| record.key_id.clone());
+        let key = new_wrapped_key(rotation, now)?;
+        let previous_expires_at =
+            now + chrono::Duration::seconds(rotation.previous_key_grace_seconds as i64);
+        let rotated = store
+            .rotate_assistant_ingress_key(
+                expected_active_key_id.as_deref(),
+                &key,
+                previous_expires_at,
+            )
+            .await
+            .map_err(|err| err.to_string())?;
+        if rotated {
+            let previous_key_id = expected_active_key_id
+                .as_deref()
+                .unwrap_or(boot_key.key_id.as_str());
+            info!(
+                key_id = %key.key_id,
+                previous_key_id,
+                "rotated assistant ingress key"
+            );
+        }
+        records = store
+            .list_live_assistant_ingress_keys(now)
+            .await
+            .map_err(|err| err.to_string())?;
+    }
+
+    let (keyring, activated_at) = keyring_from_records(&records, boot_key, rotation, now)?;
+    keys.replace(keyring, activated_at);
+    Ok(())
+}
+
+fn rotation_due(
+    active: Option<&AssistantIngressKeyRecord>,
+    rotation: &IngressKeyRotationConfig,
+    now: DateTime<Utc>,
+) -> bool {
+    active.is_none_or(|record| {
+        record.activated_at + chrono::Duration::seconds(rotation.interval_seconds as i64) <= now
+    })
+}
+
+fn new_wrapped_key(
+    rotation: &IngressKeyRotationConfig,
+    now: DateTime<Utc>,
+) -> Result<NewAssistantIngressKey, String> {
+    let key_id = format!("assistant-ingress-{}", now.format("%Y%m%dT%H%M%SZ"));
+    let private_key = generate_assistant_ingress_private_key();
+    let wrapped =
+        wrap_assistant_ingress_private_key(&rotation.wrapping_key, key_id.as_str(), &private_key)
+            .map_err(|err| format!("failed to wrap rotated ingress key: {err}"))?;
+
+    Ok(NewAssistantIngressKey {
+        public_key: derive_public_key_b64(private_key),
+        key_id,
+        wrapped_private_key: wrapped.ciphertext,
+        wrap_nonce: wrapped.nonce,
+        wrapping_key_id: rotation.wrapping_key_id.clone(),
+    })
+}
+
+/// Builds the keyring from persisted keys. Until a persisted key has been demoted, the key
+/// configured at startup stays readable as the previous key for the usual grace period.
+fn keyring_from_records(
+    records: &[AssistantIngressKeyRecord],
+    boot_key: &AssistantIngressKeyMaterial,
+    rotation: &IngressKeyRotationConfig,
+    now: DateTime<Utc>,
+) -> Result<(AssistantIngressKeyring, i64), String> {
+    let active_record = records
+        .iter()
+        .find(|record| record.status == AssistantIngressKeyStatus::Active)
+        .ok_or_else(|| "no active assistant ingress key is persisted".to_string())?;
+    let activated_at = active_record.activated_at.timestamp();
+    let grace_seconds = rotation.previous_key_grace_seconds as i64;
+    let active = AssistantIngressKeyMaterial {
+        key_expires_at: activated_at
+            .saturating_add(rotation.interval_seconds as i64)
+            .saturating_add(grace_seconds),
+        ..unwrap_record(active_record, rotation)?
+    };
+
+    let previous = match records
+        .iter()
+        .find(|record| record.status == AssistantIngressKeyStatus::Previous)
+    {
+        Some(record) => match unwrap_record(record, rotation) {
+            Ok(key) => Some(key),
+            Err(err) => {
+                warn!(key_id = %record.key_id, error = %err, "skipping unreadable previous ingress key");
+                None
+            }
+        },
+        None => {
+            let expires_at = activated_at.saturating_add(grace_seconds);
+            (boot_key.key_id != active.key_id && expires_at > now.timestamp()).then(|| {
+                AssistantIngressKeyMaterial {
+                    key_expires_at: expires_at,
+                    ..boot_key.clone()
+                }
+            })
+        }
+    };
+
+    Ok((AssistantIngressKeyring { active, previous }, activated_at))
+}
+
+fn unwrap_record(
+    record: &AssistantIngressKeyRecord,
+    rotation: &IngressKeyRotationConfig,
+) -> Result<AssistantIngressKeyMaterial, String> {
+    if record.wrapping_key_id != rotation.wrapping_key_id {
+        return Err(format!(
+            "ingress key {} is wrapped with {}, expected {}",
+            record.key_id, record.wrapping_key_id, rotation.wrapping_key_id
+        ));
+    }
+    let private_key = unwrap_assistant_ingress_private_key(
+        &rotation.wrapping_key,
+        record.key_id.as_str(),
+        &WrappedAssistantIngressKey {
+            nonce: record.wrap_nonce.clone(),
+            ciphertext: record.wrapped_private_key.clone(),
+        },
+    )
+    .map_err(|err| format!("failed to unwrap ingress key {}: {err}", record.key_id))?;
+
+    Ok(AssistantIngressKeyMaterial {
+        key_id: record.key_id.clone(),
+        private_key,
+        public_key: derive_public_key_b64(private_key),
+        key_expires_at: record
+            .expires_at
+            .map_or(i64::MAX, |expires_at| expires_at.timestamp()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn rotation_config() -> IngressKeyRotationConfig {
+        IngressKeyRotationConfig {
+            interval_seconds: 86_400,
+            previous_key_grace_seconds: 3_600,
+            sync_interval_seconds: 60,
+            wrapping_key_id: "assistant-ingress-wrap-v1".to_string(),
+            wrapping_key: [13_u8; 32],
+        }
+    }
+
+    fn boot_key() -> AssistantIngressKeyMaterial {
+        AssistantIngressKeyMaterial {
+            key_id: "assistant-ingress-v1".to_string(),
+            private_key: [11_u8; 32],
+            public_key: derive_public_key_b64([11_u8; 32]),
+            key_expires_at: 0,
+        }
+    }
+
+    fn persisted(
+        key: &NewAssistantIngressKey,
+        status: AssistantIngressKeyStatus,
+        activated_at: DateTime<Utc>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> AssistantIngressKeyRecord {
+        AssistantIngressKeyRecord {
+            key_id: key.key_id.clone(),
+            public_key: key.public_key.clone(),
+            wrapped_private_key: key.wrapped_private_key.clone(),
+            wrap_nonce: key.wrap_nonce.clone(),
+            wrapping_key_id: key.wrapping_key_id.clone(),
+            status,
+            activated_at,
+            expires_at,
+        }
+    }
+
+    fn reference_now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 10, 17, 12, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn first_persisted_key_keeps_the_boot_key_readable_during_grace() {
+        let rotation = rotation_config();
+        let now = reference_now();
+        let key = new_wrapped_key(&rotation, now).expect("key should wrap");
+        let records = vec![persisted(
+            &key,
+            AssistantIngressKeyStatus::Active,
+            now,
+            None,
+        )];
+
+        let (keyring, activated_at) = keyring_from_records(&records, &boot_key(), &rotation, now)
+            .expect("keyring should load");
+
+        assert_eq!(activated_at, now.timestamp());
+        assert_eq!(keyring.active.key_id, key.key_id);
+        assert_eq!(keyring.active.public_key, key.public_key);
+        let previous = keyring.previous.expect("boot key should stay readable");
+        assert_eq!(previous.key_id, "assistant-ingress-v1");
+        assert_eq!(previous.key_expires_at, now.timestamp() + 3_600);
+
+        let (keyring, _) = keyring_from_records(
+            &records,
+            &boot_key(),
+            &rotation,
+            now + chrono::Duration::hours(2),
+        )
+        .expect("keyring should load");
+        assert!(keyring.previous.is_none());
+    }
+
+    #[test]
+    fn persisted_previous_key_is_unwrapped_with_its_expiry() {
+        let rotation = rotation_config();
+        let now = reference_now();
+        let older =
+            new_wrapped_key(&rotation, now - chrono::Duration::days(1)).expect("key should wrap");
+        let newer = new_wrapped_key(&rotation, now).expect("key should wrap");
+        let expires_at = now + chrono::Duration::hours(1);
+        let records = vec![
+            persisted(&newer, AssistantIngressKeyStatus::Active, now, None),
+            persisted(
+                &older,
+                AssistantIngressKeyStatus::Previous,
+                now - chrono::Duration::days(1),
+                Some(expires_at),
+            ),
+        ];
+
+        let (keyring, _) = keyring_from_records(&records, &boot_key(), &rotation, now)
+            .expect("keyring should load");
+
+        let previous = keyring.previous.expect("previous key should load");
+        assert_eq!(previous.key_id, older.key_id);
+        assert_eq!(previous.public_key, older.public_key);
+        assert_eq!(previous.key_expires_at, expires_at.timestamp());
+    }
+
+    #[test]
+    fn keys_wrapped_under_another_wrapping_key_are_rejected() {
+        let rotation = rotation_config();
+        let now = reference_now();
+        let key = new_wrapped_key(&rotation, now).expect("key should wrap");
+        let records = vec![persisted(
+            &key,
+            AssistantIngressKeyStatus::Active,
+            now,
+            None,
+        )];
+        let mut other = rotation_config();
+        other.wrapping_key = [14_u8; 32];
+
+        assert!(keyring_from_records(&records, &boot_key(), &other, now).is_err());
+    }
+
+    #[test]
+    fn rotation_is_due_without_an_active_key_or_after_the_interval() {
+        let rotation = rotation_config();
+        let now = reference_now();
+        let key = new_wrapped_key(&rotation, now).expect("key should wrap");
+        let fresh = persisted(
+            &key,
+            AssistantIngressKeyStatus::Active,
+            now - chrono::Duration::hours(1),
+            None,
+        );
+        let stale = persisted(
+            &key,
+            AssistantIngressKeyStatus::Active,
+            now - chrono::Duration::days(1),
+            None,
+        );
+
+        assert!(rotation_due(None, &rotation, now));
+        assert!(!rotation_due(Some(&fresh), &rotation, now));
+        assert!(rotation_due(Some(&stale), &rotation, now));
+    }
+
+    #[test]
+    fn rotation_status_reports_the_schedule_and_previous_key() {
+        let rotation = rotation_config();
+        let now = reference_now();
+        let keys = IngressKeys::new(AssistantIngressKeyring {
+            active: boot_key(),
+            previous: None,
+        });
+        assert_eq!(
+            keys.rotation_status(None, now.timestamp()),
+            AssistantAttestedKeyRotation {
+                rotation_enabled: false,
+                activated_at: None,
+                next_rotation_at: None,
+                previous_key_id: None,
+                previous_key_expires_at: None,
+            }
+        );
+
+        let key = new_wrapped_key(&rotation, now).expect("key should wrap");
+        let records = vec![persisted(
+            &key,
+            AssistantIngressKeyStatus::Active,
+            now,
+            None,
+        )];
+        let (keyring, activated_at) = keyring_from_records(&records, &boot_key(), &rotation, now)
+            .expect("keyring should load");
+        keys.replace(keyring, activated_at);
+
+        let status = keys.rotation_status(Some(&rotation), now.timestamp());
+        assert!(status.rotation_enabled);
+        assert_eq!(status.activated_at, Some(now.timestamp()));
+        assert_eq!(status.next_rotation_at, Some(now.timestamp() + 86_400));
+        assert_eq!(
+            status.previous_key_id.as_deref(),
+            Some("assistant-ingress-v1")
+        );
+        assert_eq!(keys.keyring().active.key_id, key.key_id);
+    }
+}
@@ -0,0 +1,435 @@
+//! `enclave-runtime keygen`: generates the ingress and attestation signing keys for a new
+//! deployment or a manual rotation and prints them as the env vars the runtime reads.
+//!
+//! ```text
+//! cargo run -p enclave-runtime -- keygen --seal aws --kms-key-id alias/alfred-enclave-keys
+//! ```
+//!
+//! With `--seal aws` (or `local` for the KMS stub) each private key is encrypted under the KMS
+//! key and printed as `kms:<base64 ciphertext>`. The runtime unseals those values at startup
+//! through its own KMS client, so in remote mode only an attested enclave can read them.
+
+use std::collections::HashMap;
+use std::env;
+
+use base64::Engine as _;
+use chacha20poly1305::aead::{OsRng, rand_core::RngCore};
+use chrono::Utc;
+use ed25519_dalek::SigningKey;
+use shared::assistant_crypto::{derive_public_key_b64, generate_assistant_ingress_private_key};
+use shared::security::{AwsCredentials, AwsKmsClient, KmsClient, KmsEncryptionContext};
+use tracing::info;
+
+/// Prefix of a private key env var value that is a KMS ciphertext rather than the key itself.
+const SEALED_KEY_PREFIX: &str = "kms:";
+
+const INGRESS_KEY_KIND: &str = "assistant_ingress";
+const ATTESTATION_KEY_KIND: &str = "attestation_signing";
+
+/// Private key env vars the runtime unseals, with the kind bound into their KMS context. Both
+/// ingress vars share a kind so a sealed active key can be demoted to previous unchanged.
+const SEALABLE_PRIVATE_KEY_ENV_VARS: [(&str, &str); 3] = [
+    ("ASSISTANT_INGRESS_ACTIVE_PRIVATE_KEY", INGRESS_KEY_KIND),
+    ("ASSISTANT_INGRESS_PREVIOUS_PRIVATE_KEY", INGRESS_KEY_KIND),
+    ("TEE_ATTESTATION_SIGNING_PRIVATE_KEY", ATTESTATION_KEY_KIND),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SealTarget {
+    /// Plain base64, for local runs or when a secrets manager holds the value.
+    None,
+    Local,
+    Aws,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct KeygenArgs {
+    ingress_key_id: String,
+    seal: SealTarget,
+    kms_key_id: Option<String>,
+}
+
+struct GeneratedKeys {
+    ingress_key_id: String,
+    ingress_private_key: [u8; 32],
+    attestation_private_key: [u8; 32],
+}
+
+/// Entry point of the `keygen` subcommand. The key material is printed to stdout; everything
+/// else goes to stderr so the output can be redirected straight into an env file.
+pub(crate) async fn run(args: &[String]) -> Result<(), String> {
+    let args = parse_args(args)?;
+    let sealer = match args.seal {
+        SealTarget::None => None,
+        SealTarget::Local => Some((KmsClient::LocalStub, local_kms_key_id(&args))),
+        SealTarget::Aws => Some((aws_kms_client_from_env()?, aws_kms_key_id(&args)?)),
+    };
+
+    let keys = GeneratedKeys {
+        ingress_key_id: args.ingress_key_id,
+        ingress_private_key: generate_assistant_ingress_private_key(),
+        attestation_private_key: random_key(),
+    };
+    let rendered = render_env(
+        &keys,
+        sealer
+            .as_ref()
+            .map(|(client, key_id)| (client, key_id.as_str())),
+    )
+    .await?;
+    print!("{rendered}");
+    if sealer.is_none() {
+        eprintln!("private keys are printed unsealed; store them in the secrets manager only");
+    }
+    Ok(())
+}
+
+/// Private keys unsealed at startup, keyed by the env var that held the sealed value. Config
+/// reads private keys through [`UnsealedPrivateKeys::env`], so the environment itself is
+/// never rewritten.
+#[derive(Default)]
+pub(crate) struct UnsealedPrivateKeys(HashMap<&'static str, String>);
+
+impl UnsealedPrivateKeys {
+    /// The trimmed value of `env_var`, replaced by its unsealed key when it was sealed.
+    pub(crate) fn env(&self, env_var: &str) -> Option<String> {
+        self.0
+            .get(env_var)
+            .cloned()
+            .or_else(|| env::var(env_var).ok())
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    }
+}
+
+/// Unseals every sealed private key env var. Runs at startup after `secret://` references
+/// are resolved and before config is read.
+pub(crate) async fn unseal_env_private_keys(
+    kms: impl AsyncFnOnce() -> Result<Option<KmsClient>, String>,
+) -> Result<UnsealedPrivateKeys, String> {
+    let sealed = SEALABLE_PRIVATE_KEY_ENV_VARS
+        .into_iter()
+        .filter_map(|(env_var, kind)| {
+            let value = env::var(env_var).ok()?;
+            is_sealed(&value).then_some((env_var, kind, value))
+        })
+        .collect::<Vec<_>>();
+    if sealed.is_empty() {
+        return Ok(UnsealedPrivateKeys::default());
+    }
+
+    let kms = kms().await?.ok_or_else(|| {
+        "sealed enclave private keys need KMS_PROVIDER to be local or aws".to_string()
+    })?;
+    let mut unsealed = HashMap::with_capacity(sealed.len());
+    for (env_var, kind, value) in sealed {
+        let private_key = unseal_private_key(&kms, env_var, kind, &value).await?;
+        unsealed.insert(env_var, private_key);
+    }
+    info!(env_vars = ?unsealed.keys().collect::<Vec<_>>(), "unsealed enclave private keys");
+    Ok(UnsealedPrivateKeys(unsealed))
+}
+
+fn parse_args(args: &[String]) -> Result<KeygenArgs, String> {
+    let mut parsed = KeygenArgs {
+        ingress_key_id: format!("assistant-ingress-{}", Utc::now().format("%Y%m%dT%H%M%SZ")),
+        seal: SealTarget::None,
+        kms_key_id: None,
+    };
+    let mut args = args.iter().map(String::as_str);
+    while let Some(flag) = args.next() {
+        let mut value = || {
+            args.next()
+                .filter(|value| !value.trim().is_empty())
+                .ok_or_else(|| format!("{flag} needs a value\n{USAGE}"))
+        };
+        match flag {
+            "--ingress-key-id" => parsed.ingress_key_id = value()?.to_string(),
+            "--seal" => {
+                parsed.seal = match value()? {
+                    "none" => SealTarget::None,
+                    "local" => SealTarget::Local,
+                    "aws" => SealTarget::Aws,
+                    other => {
+                        return Err(format!(
+                            "--seal must be one of none, local, aws (got {other})"
+                        ));
+                    }
+                }
+            }
+            "--kms-key-id" => parsed.kms_key_id = Some(value()?.to_string()),
+            other => return Err(format!("unknown keygen argument '{other}'\n{USAGE}")),
+        }
+    }
+    if parsed.seal == SealTarget::None && parsed.kms_key_id.is_some() {
+        return Err("--kms-key-id needs --seal local or --seal aws".to_string());
+    }
+    Ok(parsed)
+}
+
+const USAGE: &str = "usage: enclave-runtime keygen [--ingress-key-id <id>] [--seal none|local|aws] [--kms-key-id <kms key id or alias>]";
+
+fn local_kms_key_id(args: &KeygenArgs) -> String {
+    args.kms_key_id
+        .clone()
+        .unwrap_or_else(|| "kms/local/alfred-enclave-keys".to_string())
+}
+
+fn aws_kms_key_id(args: &KeygenArgs) -> Result<String, String> {
+    args.kms_key_id
+        .clone()
+        .ok_or_else(|| "--seal aws needs --kms-key-id".to_string())
+}
+
+/// Sealing only calls `Encrypt`, so the operator's credentials are enough; no recipient
+/// attestation is involved until the enclave decrypts.
+fn aws_kms_client_from_env() -> Result<KmsClient, String> {
+    let require = |key: &str| {
+        env::var(key)
+            .ok()
+            .filter(|value| !value.trim().is_empty())
+            .ok_or_else(|| format!("{key} is required for --seal aws"))
+    };
+    let client = AwsKmsClient::new(
+        require("AWS_REGION")?,
+        env::var("KMS_ENDPOINT").ok(),
+        AwsCredentials {
+            access_key_id: require("AWS_ACCESS_KEY_ID")?,
+            secret_access_key: require("AWS_SECRET_ACCESS_KEY")?,
+            session_token: env::var("AWS_SESSION_TOKEN").ok(),
+        },
+        reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(15))
+            .build()
+            .map_err(|err| format!("failed to build kms http client: {err}"))?,
+    );
+    Ok(KmsClient::Aws(Box::new(client)))
+}
+
+async fn render_env(
+    keys: &GeneratedKeys,
+    sealer: Option<(&KmsClient, &str)>,
+) -> Result<String, String> {
+    let encode = |key: &[u8]| base64::engine::general_purpose::STANDARD.encode(key);
+    let seal = async |kind: &str, private_key: &[u8; 32]| match sealer {
+        Some((client, kms_key_id)) => client
+            .encrypt(kms_key_id, private_key, &sealing_context(kind))
+            .await
+            .map(|ciphertext| format!("{SEALED_KEY_PREFIX}{}", encode(&ciphertext)))
+            .map_err(|err| format!("failed to seal {kind} key: {err}")),
+        None => Ok(encode(private_key)),
+    };
+
+    let attestation_public_key = SigningKey::from_bytes(&keys.attestation_private_key)
+        .verifying_key()
+        .to_bytes();
+    Ok(format!(
+        "# Generated by enclave-runtime keygen at {generated_at}.\n\
+         # Assistant ingress X25519 public key: {ingress_public_key}\n\
+         ASSISTANT_INGRESS_ACTIVE_KEY_ID={ingress_key_id}\n\
+         ASSISTANT_INGRESS_ACTIVE_PRIVATE_KEY={ingress_private_key}\n\
+         TEE_ATTESTATION_SIGNING_PRIVATE_KEY={attestation_private_key}\n\
+         # Give TEE_ATTESTATION_PUBLIC_KEY to every service that verifies enclave evidence.\n\
+         TEE_ATTESTATION_PUBLIC_KEY={attestation_public_key}\n",
+        generated_at = Utc::now().to_rfc3339(),
+        ingress_public_key = derive_public_key_b64(keys.ingress_private_key),
+        ingress_key_id = keys.ingress_key_id,
+        ingress_private_key = seal(INGRESS_KEY_KIND, &keys.ingress_private_key).await?,
+        attestation_private_key = seal(ATTESTATION_KEY_KIND, &keys.attestation_private_key).await?,
+        attestation_public_key = encode(&attestation_public_key),
+    ))
+}
+
+async fn unseal_private_key(
+    kms: &KmsClient,
+    env_var: &str,
+    kind: &str,
+    value: &str,
+) -> Result<String, String> {
+    let ciphertext = value
+        .trim()
+        .strip_prefix(SEALED_KEY_PREFIX)
+        .and_then(|encoded| {
+            base64::engine::general_purpose::STANDARD
+                .decode(encoded)
+                .ok()
+        })
+        .ok_or_else(|| format!("{env_var} must be {SEALED_KEY_PREFIX}<base64 ciphertext>"))?;
+    let private_key = kms
+        .decrypt(&ciphertext, &sealing_context(kind))
+        .await
+        .map_err(|err| format!("failed to unseal {env_var}: {err}"))?;
+    if private_key.len() != 32 {
+        return Err(format!("{env_var} must unseal to exactly 32 bytes"));
+    }
+    Ok(base64::engine::general_purpose::STANDARD.encode(private_key))
+}
+
+fn is_sealed(value: &str) -> bool {
+    value.trim().starts_with(SEALED_KEY_PREFIX)
+}
+
+fn sealing_context(kind: &str) -> KmsEncryptionContext {
+    KmsEncryptionContext::from([
+        (
+            "purpose".to_string(),
+            "alfred_enclave_private_key".to_string(),
+        ),
+        ("key_kind".to_string(), kind.to_string()),
+    ])
+}
+
+fn random_key() -> [u8; 32] {
+    let mut key = [0_u8; 32];
+    OsRng.fill_bytes(&mut key);
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use shared::security::KmsClient;
+
+    use super::{
+        ATTESTATION_KEY_KIND, GeneratedKeys, INGRESS_KEY_KIND, KeygenArgs, SealTarget,
+        UnsealedPrivateKeys, parse_args, render_env, unseal_private_key,
+    };
+
+    fn args(raw: &[&str]) -> Vec<String> {
+        raw.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    fn keys() -> GeneratedKeys {
+        GeneratedKeys {
+            ingress_key_id: "assistant-ingress-ceremony".to_string(),
+            ingress_private_key: [3_u8; 32],
+            attestation_private_key: [7_u8; 32],
+        }
+    }
+
+    fn env_value<'a>(rendered: &'a str, key: &str) -> &'a str {
+        rendered
+            .lines()
+            .find_map(|line| line.strip_prefix(key)?.strip_prefix('='))
+            .unwrap_or_else(|| panic!("{key} should be rendered"))
+    }
+
+    #[test]
+    fn parses_flags_and_rejects_unusable_combinations() {
+        assert_eq!(
+            parse_args(&args(&[
+                "--ingress-key-id",
+                "ingress-2026",
+                "--seal",
+                "aws",
+                "--kms-key-id",
+                "alias/enclave",
+            ])),
+            Ok(KeygenArgs {
+                ingress_key_id: "ingress-2026".to_string(),
+                seal: SealTarget::Aws,
+                kms_key_id: Some("alias/enclave".to_string()),
+            })
+        );
+        let defaults = parse_args(&[]).expect("no flags should parse");
+        assert_eq!(defaults.seal, SealTarget::None);
+        assert!(defaults.ingress_key_id.starts_with("assistant-ingress-"));
+
+        assert!(parse_args(&args(&["--seal", "vault"])).is_err());
+        assert!(parse_args(&args(&["--kms-key-id", "alias/enclave"])).is_err());
+        assert!(parse_args(&args(&["--ingress-key-id"])).is_err());
+        assert!(parse_args(&args(&["--bogus"])).is_err());
+    }
+
+    #[tokio::test]
+    async fn unsealed_output_is_the_runtime_env_format() {
+        let rendered = render_env(&keys(), None).await.expect("render should work");
+
+        assert_eq!(
+            env_value(&rendered, "ASSISTANT_INGRESS_ACTIVE_KEY_ID"),
+            "assistant-ingress-ceremony"
+        );
+        assert_eq!(
+            env_value(&rendered, "ASSISTANT_INGRESS_ACTIVE_PRIVATE_KEY"),
+            "AwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwM="
+        );
+        assert_eq!(
+            env_value(&rendered, "TEE_ATTESTATION_SIGNING_PRIVATE_KEY"),
+            "BwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwc="
+        );
+        let public_key = env_value(&rendered, "TEE_ATTESTATION_PUBLIC_KEY");
+        let signature = ed25519_dalek::Signer::sign(
+            &ed25519_dalek::SigningKey::from_bytes(&[7_u8; 32]),
+            b"ceremony",
+        );
+        shared::security::verify_ed25519_signature(
+            public_key,
+            &base64::Engine::encode(
+                &base64::engine::general_purpose::STANDARD,
+                signature.to_bytes(),
+            ),
+            b"ceremony",
+        )
+        .expect("printed public key should verify the signing key");
+    }
+
+    #[tokio::test]
+    async fn sealed_keys_unseal_only_under_their_own_kind() {
+        let kms = KmsClient::LocalStub;
+        let rendered = render_env(&keys(), Some((&kms, "kms/local/test")))
+            .await
+            .expect("render should work");
+        let ingress = env_value(&rendered, "ASSISTANT_INGRESS_ACTIVE_PRIVATE_KEY");
+        let attestation = env_value(&rendered, "TEE_ATTESTATION_SIGNING_PRIVATE_KEY");
+        assert!(ingress.starts_with("kms:"));
+
+        assert_eq!(
+            unseal_private_key(
+                &kms,
+                "ASSISTANT_INGRESS_PREVIOUS_PRIVATE_KEY",
+                INGRESS_KEY_KIND,
+                ingress
+            )
+            .await,
+            Ok("AwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwM=".to_string())
+        );
+        assert_eq!(
+            unseal_private_key(
+                &kms,
+                "TEE_ATTESTATION_SIGNING_PRIVATE_KEY",
+                ATTESTATION_KEY_KIND,
+                attestation
+            )
+            .await,
+            Ok("BwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwc=".to_string())
+        );
+        assert!(
+            unseal_private_key(
+                &kms,
+                "TEE_ATTESTATION_SIGNING_PRIVATE_KEY",
+                ATTESTATION_KEY_KIND,
+                ingress
+            )
+            .await
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn unsealed_keys_are_read_in_place_of_their_env_vars() {
+        let unsealed = UnsealedPrivateKeys(
+            [(
+                "TEE_ATTESTATION_SIGNING_PRIVATE_KEY",
+                " BwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwc= ".to_string(),
+            )]
+            .into(),
+        );
+
+        assert_eq!(
+            unsealed
+                .env("TEE_ATTESTATION_SIGNING_PRIVATE_KEY")
+                .as_deref(),
+            Some("BwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwc=")
+        );
+        assert_eq!(unsealed.env("ALFRED_TEST_UNSET_PRIVATE_KEY"), None);
+    }
+}
@@ -2,9 +2,13 @@ use std::env;
 use std::sync::Arc;
 
 use shared::llm::{
-    LlmGateway, LlmReliabilityConfig, OpenRouterGatewayConfig, ReliableGatewayBuildError,
-    ReliableOpenRouterGateway,
+    AnthropicGateway, AnthropicGatewayConfig, BedrockGateway, BedrockGatewayConfig, LlmGateway,
+    LlmModelRouteConfig, LlmProviderRole, LlmReliabilityConfig, LlmRouteProfile, LlmRoutingConfig,
+    LlmSpendBudget, LlmStyleConfig, LocalLlmGateway, LocalLlmGatewayConfig, OpenAiGateway,
+    OpenAiGatewayConfig, OpenRouterGatewayConfig, ReliableGatewayBuildError,
+    ReliableProviderGateway, StyledLlmGateway,
 };
+use shared::outbound_rate_limit::OutboundCallLimiter;
 use tracing::warn;
 
 type DynLlmGateway = dyn LlmGateway + Send + Sync;
@@ -49,58 +53,118 @@ impl LlmGatewayProfiles {
     pub(crate) fn worker(&self) -> &DynLlmGateway {
         self.worker.as_ref()
     }
+
+    /// Warms every profile in turn. Each profile owns its own provider client, so one warm
+    /// connection pool does not cover the others.
+    pub(crate) async fn warm_up(&self) -> Result<(), String> {
+        let profiles = [
+            ("planner", &self.planner),
+            ("assistant_chat", &self.assistant_chat),
+            ("assistant_tool", &self.assistant_tool),
+            ("worker", &self.worker),
+        ];
+        for (profile, gateway) in profiles {
+            gateway
+                .warm_up()
+                .await
+                .map_err(|err| format!("{profile} profile: {err}"))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Providers called directly rather than through OpenRouter. Each is off unless configured.
+/// The local server is not a primary or fallback: it serves its capabilities ahead of both.
+#[derive(Clone, Copy)]
+pub(crate) struct DirectLlmProviders<'a> {
+    pub(crate) anthropic: Option<&'a AnthropicGatewayConfig>,
+    pub(crate) openai: Option<&'a OpenAiGatewayConfig>,
+    pub(crate) bedrock: Option<&'a BedrockGatewayConfig>,
+    pub(crate) local: Option<&'a LocalLlmGatewayConfig>,
+}
+
+impl DirectLlmProviders<'_> {
+    fn validate(self) -> Result<(), ReliableGatewayBuildError> {
+        let primary_count = [
+            self.anthropic.map(|config| config.role),
+            self.openai.map(|config| config.role),
+            self.bedrock.map(|config| config.role),
+        ]
+        .into_iter()
+        .filter(|role| *role == Some(LlmProviderRole::Primary))
+        .count();
+        if primary_count > 1 {
+            return Err(ReliableGatewayBuildError::ConflictingPrimaryProviders);
+        }
+        Ok(())
+    }
+}
+
+/// Shared by every profile's gateway; only the OpenRouter profile config differs between them.
+pub(crate) struct GatewayBuildContext<'a> {
+    pub(crate) direct_providers: DirectLlmProviders<'a>,
+    pub(crate) style_config: &'a LlmStyleConfig,
+    pub(crate) redis_url: &'a str,
+    pub(crate) outbound_limiter: &'a OutboundCallLimiter,
+    pub(crate) spend_budget: Arc<dyn LlmSpendBudget>,
 }
 
 pub(crate) async fn build_llm_gateway_profiles(
-    openrouter_config: OpenRouterGatewayConfig,
-    llm_reliability_config: LlmReliabilityConfig,
-    redis_url: &str,
+    mut openrouter_config: OpenRouterGatewayConfig,
+    mut llm_reliability_config: LlmReliabilityConfig,
+    llm_routing_config: &LlmRoutingConfig,
+    context: &GatewayBuildContext<'_>,
 ) -> Result<LlmGatewayProfiles, ReliableGatewayBuildError> {
+    context.direct_providers.validate()?;
+    openrouter_config.model_route = llm_routing_config.route(LlmRouteProfile::Worker).into();
+    llm_reliability_config.budget_model = Some(llm_routing_config.budget_model.clone());
+
     let planner_config = assistant_profile_config(
         &openrouter_config,
         ASSISTANT_PLANNER_PROFILE_PREFIX,
+        llm_routing_config.route(LlmRouteProfile::AssistantPlanner),
         AssistantProfileDefaults {
             timeout_ms: DEFAULT_ASSISTANT_PLANNER_TIMEOUT_MS,
             max_retries: DEFAULT_ASSISTANT_PLANNER_MAX_RETRIES,
             max_output_tokens: DEFAULT_ASSISTANT_PLANNER_MAX_OUTPUT_TOKENS,
-            use_model_fallback: false,
         },
     );
     let assistant_chat_config = assistant_profile_config(
         &openrouter_config,
         ASSISTANT_CHAT_PROFILE_PREFIX,
+        llm_routing_config.route(LlmRouteProfile::AssistantChat),
         AssistantProfileDefaults {
             timeout_ms: DEFAULT_ASSISTANT_CHAT_TIMEOUT_MS,
             max_retries: DEFAULT_ASSISTANT_CHAT_MAX_RETRIES,
             max_output_tokens: DEFAULT_ASSISTANT_CHAT_MAX_OUTPUT_TOKENS,
-            use_model_fallback: false,
         },
     );
     let assistant_tool_config = assistant_profile_config(
         &openrouter_config,
         ASSISTANT_TOOL_PROFILE_PREFIX,
+        llm_routing_config.route(LlmRouteProfile::AssistantTool),
         AssistantProfileDefaults {
             timeout_ms: DEFAULT_ASSISTANT_TOOL_TIMEOUT_MS,
             max_retries: DEFAULT_ASSISTANT_TOOL_MAX_RETRIES,
             max_output_tokens: DEFAULT_ASSISTANT_TOOL_MAX_OUTPUT_TOKENS,
-            use_model_fallback: false,
         },
     );
 
-    let planner = build_gateway(planner_config, llm_reliability_config.clone(), redis_url).await?;
+    let planner = build_gateway(planner_config, llm_reliability_config.clone(), context).await?;
     let assistant_chat = build_gateway(
         assistant_chat_config,
         llm_reliability_config.clone(),
-        redis_url,
+        context,
     )
     .await?;
     let assistant_tool = build_gateway(
         assistant_tool_config,
         llm_reliability_config.clone(),
-        redis_url,
+        context,
     )
     .await?;
-    let worker = build_gateway(openrouter_config, llm_reliability_config, redis_url).await?;
+    let worker = build_gateway(openrouter_config, llm_reliability_config, context).await?;
 
     Ok(LlmGatewayProfiles {
         planner,
@@ -115,12 +179,12 @@ struct AssistantProfileDefaults {
     timeout_ms: u64,
     max_retries: u32,
     max_output_tokens: u32,
-    use_model_fallback: bool,
 }
 
 fn assistant_profile_config(
     base: &OpenRouterGatewayConfig,
     profile_prefix: &str,
+    route: &LlmModelRouteConfig,
     defaults: AssistantProfileDefaults,
 ) -> OpenRouterGatewayConfig {
     let overrides = AssistantProfileEnvOverrides {
@@ -129,14 +193,8 @@ fn assistant_profile_config(
         max_output_tokens: optional_trimmed_env(
             profile_env_key(profile_prefix, "MAX_OUTPUT_TOKENS").as_str(),
         ),
-        model_primary: optional_trimmed_env(
-            profile_env_key(profile_prefix, "MODEL_PRIMARY").as_str(),
-        ),
-        model_fallback: optional_trimmed_env(
-            profile_env_key(profile_prefix, "MODEL_FALLBACK").as_str(),
-        ),
     };
-    assistant_profile_config_with_overrides(base, defaults, overrides)
+    assistant_profile_config_with_overrides(base, route, defaults, overrides)
 }
 
 #[derive(Default)]
@@ -144,12 +202,11 @@ struct AssistantProfileEnvOverrides {
     timeout_ms: Option<String>,
     max_retries: Option<String>,
     max_output_tokens: Option<String>,
-    model_primary: Option<String>,
-    model_fallback: Option<String>,
 }
 
 fn assistant_profile_config_with_overrides(
     base: &OpenRouterGatewayConfig,
+    route: &LlmModelRouteConfig,
     defaults: AssistantProfileDefaults,
     overrides: AssistantProfileEnvOverrides,
 ) -> OpenRouterGatewayConfig {
@@ -172,35 +229,105 @@ fn assistant_profile_config_with_overrides(
         defaults.max_output_tokens,
         true,
     );
-    if let Some(primary_model) = overrides.model_primary {
-        config.model_route.primary_model = primary_model;
-    }
-
-    let fallback_model = overrides.model_fallback.or_else(|| {
-        if defaults.use_model_fallback {
-            base.model_route.fallback_model.clone()
-        } else {
-            None
-        }
-    });
-    config.model_route.fallback_model = fallback_model
-        .filter(|model| model != &config.model_route.primary_model && !model.trim().is_empty());
+    config.model_route = route.into();
 
     config
 }
 
+/// Styles wrap the reliability layer so the response cache keys on the styled prompt.
 async fn build_gateway(
     openrouter_config: OpenRouterGatewayConfig,
     llm_reliability_config: LlmReliabilityConfig,
-    redis_url: &str,
+    context: &GatewayBuildContext<'_>,
 ) -> Result<Arc<DynLlmGateway>, ReliableGatewayBuildError> {
-    let gateway = ReliableOpenRouterGateway::from_openrouter_config_with_redis(
+    let direct_providers = context.direct_providers;
+    let anthropic_config = direct_providers
+        .anthropic
+        .map(|anthropic_config| anthropic_profile_config(anthropic_config, &openrouter_config));
+    let openai_config = direct_providers
+        .openai
+        .map(|openai_config| openai_profile_config(openai_config, &openrouter_config));
+    let bedrock_config = direct_providers
+        .bedrock
+        .map(|bedrock_config| bedrock_profile_config(bedrock_config, &openrouter_config));
+    let local_config = direct_providers
+        .local
+        .map(|local_config| local_profile_config(local_config, &openrouter_config));
+    let mut gateway = ReliableProviderGateway::from_openrouter_config_with_redis(
         openrouter_config,
         llm_reliability_config,
-        redis_url,
+        context.redis_url,
     )
     .await?;
-    Ok(Arc::new(gateway))
+    if let Some(anthropic_config) = anthropic_config {
+        gateway = gateway.with_anthropic(AnthropicGateway::new(anthropic_config)?);
+    }
+    if let Some(openai_config) = openai_config {
+        gateway = gateway.with_openai(OpenAiGateway::new(openai_config)?);
+    }
+    if let Some(bedrock_config) = bedrock_config {
+        gateway = gateway.with_bedrock(BedrockGateway::new(bedrock_config)?);
+    }
+    if let Some(local_config) = local_config {
+        gateway = gateway.with_local(LocalLlmGateway::new(local_config)?);
+    }
+    let gateway = gateway
+        .with_outbound_limiter(context.outbound_limiter.clone())
+        .with_spend_budget(context.spend_budget.clone());
+    Ok(Arc::new(StyledLlmGateway::new(
+        gateway,
+        context.style_config.clone(),
+    )))
+}
+
+/// Direct providers follow the profile's latency budget and temperature so a provider switch
+/// does not change how long or how creatively a profile answers.
+fn anthropic_profile_config(
+    base: &AnthropicGatewayConfig,
+    profile_config: &OpenRouterGatewayConfig,
+) -> AnthropicGatewayConfig {
+    let mut config = base.clone();
+    config.timeout_ms = profile_config.timeout_ms;
+    config.max_retries = profile_config.max_retries;
+    config.max_output_tokens = profile_config.max_output_tokens;
+    config.temperature = profile_config.model_route.temperature;
+    config
+}
+
+fn openai_profile_config(
+    base: &OpenAiGatewayConfig,
+    profile_config: &OpenRouterGatewayConfig,
+) -> OpenAiGatewayConfig {
+    let mut config = base.clone();
+    config.timeout_ms = profile_config.timeout_ms;
+    config.max_retries = profile_config.max_retries;
+    config.max_output_tokens = profile_config.max_output_tokens;
+    config.temperature = profile_config.model_route.temperature;
+    config
+}
+
+fn bedrock_profile_config(
+    base: &BedrockGatewayConfig,
+    profile_config: &OpenRouterGatewayConfig,
+) -> BedrockGatewayConfig {
+    let mut config = base.clone();
+    config.timeout_ms = profile_config.timeout_ms;
+    config.max_retries = profile_config.max_retries;
+    config.max_output_tokens = profile_config.max_output_tokens;
+    config.temperature = profile_config.model_route.temperature;
+    config
+}
+
+fn local_profile_config(
+    base: &LocalLlmGatewayConfig,
+    profile_config: &OpenRouterGatewayConfig,
+) -> LocalLlmGatewayConfig {
+    let mut config = base.clone();
+    config.timeout_ms = profile_config.timeout_ms;
+    config.max_retries = profile_config.max_retries;
+    config.max_output_tokens = profile_config.max_output_tokens;
+    config.temperature = profile_config.model_route.temperature;
+    config
 }
 
 fn profile_env_key(profile_prefix: &str, suffix: &str) -> String {
@@ -267,10 +394,15 @@ fn parse_u32_override(
 #[cfg(test)]
 mod tests {
     use super::{
-        AssistantProfileDefaults, AssistantProfileEnvOverrides,
-        assistant_profile_config_with_overrides,
+        AssistantProfileDefaults, AssistantProfileEnvOverrides, DirectLlmProviders,
+        anthropic_profile_config, assistant_profile_config_with_overrides,
+    };
+    use shared::aws::AwsCredentials;
+    use shared::llm::{
+        AnthropicGatewayConfig, BedrockGatewayConfig, LlmModelRouteConfig, LlmProviderRole,
+        OpenAiGatewayConfig, OpenRouterGatewayConfig, OpenRouterModelRoute,
+        ReliableGatewayBuildError,
     };
-    use shared::llm::{OpenRouterGatewayConfig, OpenRouterModelRoute};
 
     fn base_config() -> OpenRouterGatewayConfig {
         OpenRouterGatewayConfig {
@@ -286,44 +418,106 @@ mod tests {
             model_route: OpenRouterModelRoute {
                 primary_model: "openai/gpt-4o-mini".to_string(),
                 fallback_model: Some("anthropic/claude-3.5-haiku".to_string()),
+                temperature: 0.0,
+            },
+        }
+    }
+
+    fn anthropic_config(role: LlmProviderRole) -> AnthropicGatewayConfig {
+        AnthropicGatewayConfig {
+            messages_url: "https://api.anthropic.com/v1/messages".to_string(),
+            api_key: "test".to_string(),
+            api_version: "2023-06-01".to_string(),
+            role,
+            model: "claude-3-5-haiku-latest".to_string(),
+            temperature: 0.0,
+            timeout_ms: 15_000,
+            max_retries: 2,
+            retry_base_backoff_ms: 250,
+            max_output_tokens: 600,
+            allow_insecure_http: false,
+        }
+    }
+
+    fn openai_config(role: LlmProviderRole) -> OpenAiGatewayConfig {
+        OpenAiGatewayConfig {
+            chat_completions_url: "https://api.openai.com/v1/chat/completions".to_string(),
+            api_key: "test".to_string(),
+            organization: None,
+            role,
+            model: "gpt-4o-mini".to_string(),
+            temperature: 0.0,
+            timeout_ms: 15_000,
+            max_retries: 2,
+            retry_base_backoff_ms: 250,
+            max_output_tokens: 600,
+            allow_insecure_http: false,
+        }
+    }
+
+    fn bedrock_config(role: LlmProviderRole) -> BedrockGatewayConfig {
+        BedrockGatewayConfig {
+            endpoint_url: "https://bedrock-runtime.us-east-1.amazonaws.com".to_string(),
+            region: "us-east-1".to_string(),
+            credentials: AwsCredentials {
+                access_key_id: "AKIDEXAMPLE".to_string(),
+                secret_access_key: "test".to_string(),
+                session_token: None,
             },
+            role,
+            model: "anthropic.claude-3-5-haiku-20241022-v1:0".to_string(),
+            temperature: 0.0,
+            timeout_ms: 15_000,
+            max_retries: 2,
+            retry_base_backoff_ms: 250,
+            max_output_tokens: 600,
+            allow_insecure_http: false,
+        }
+    }
+
+    fn planner_defaults() -> AssistantProfileDefaults {
+        AssistantProfileDefaults {
+            timeout_ms: 4_000,
+            max_retries: 0,
+            max_output_tokens: 180,
+        }
+    }
+
+    fn route(primary_model: &str, fallback_model: Option<&str>) -> LlmModelRouteConfig {
+        LlmModelRouteConfig {
+            primary_model: primary_model.to_string(),
+            fallback_model: fallback_model.map(ToString::to_string),
+            temperature: 0.0,
         }
     }
 
     #[test]
-    fn profile_defaults_disable_fallback_and_apply_latency_defaults() {
+    fn profile_defaults_use_the_routed_model_and_latency_defaults() {
         let config = assistant_profile_config_with_overrides(
             &base_config(),
-            AssistantProfileDefaults {
-                timeout_ms: 4_000,
-                max_retries: 0,
-                max_output_tokens: 180,
-                use_model_fallback: false,
-            },
+            &route("openai/gpt-4o-mini", None),
+            planner_defaults(),
             AssistantProfileEnvOverrides::default(),
         );
         assert_eq!(config.timeout_ms, 4_000);
         assert_eq!(config.max_retries, 0);
         assert_eq!(config.max_output_tokens, 180);
+        assert_eq!(config.model_route.primary_model, "openai/gpt-4o-mini");
         assert!(config.model_route.fallback_model.is_none());
     }
 
     #[test]
     fn profile_overrides_apply_when_valid() {
+        let mut routed = route("openai/gpt-4.1-mini", Some("anthropic/claude-3.5-haiku"));
+        routed.temperature = 0.3;
         let config = assistant_profile_config_with_overrides(
             &base_config(),
-            AssistantProfileDefaults {
-                timeout_ms: 4_000,
-                max_retries: 0,
-                max_output_tokens: 180,
-                use_model_fallback: false,
-            },
+            &routed,
+            planner_defaults(),
             AssistantProfileEnvOverrides {
                 timeout_ms: Some("5200".to_string()),
                 max_retries: Some("1".to_string()),
                 max_output_tokens: Some("320".to_string()),
-                model_primary: Some("openai/gpt-4.1-mini".to_string()),
-                model_fallback: Some("anthropic/claude-3.5-haiku".to_string()),
             },
         );
         assert_eq!(config.timeout_ms, 5_200);
@@ -334,29 +528,81 @@ mod tests {
             config.model_route.fallback_model.as_deref(),
             Some("anthropic/claude-3.5-haiku")
         );
+        assert_eq!(config.model_route.temperature, 0.3);
     }
 
     #[test]
     fn invalid_overrides_fall_back_to_defaults() {
         let config = assistant_profile_config_with_overrides(
             &base_config(),
-            AssistantProfileDefaults {
-                timeout_ms: 4_000,
-                max_retries: 0,
-                max_output_tokens: 180,
-                use_model_fallback: false,
-            },
+            &route("openai/gpt-4o-mini", None),
+            planner_defaults(),
             AssistantProfileEnvOverrides {
                 timeout_ms: Some("invalid".to_string()),
                 max_retries: Some("-1".to_string()),
                 max_output_tokens: Some("0".to_string()),
-                model_primary: None,
-                model_fallback: Some("openai/gpt-4o-mini".to_string()),
             },
         );
         assert_eq!(config.timeout_ms, 4_000);
         assert_eq!(config.max_retries, 0);
         assert_eq!(config.max_output_tokens, 180);
-        assert!(config.model_route.fallback_model.is_none());
+    }
+
+    #[test]
+    fn anthropic_follows_the_profile_latency_budget_and_temperature() {
+        let mut profile = assistant_profile_config_with_overrides(
+            &base_config(),
+            &route("openai/gpt-4o-mini", None),
+            planner_defaults(),
+            AssistantProfileEnvOverrides::default(),
+        );
+        profile.model_route.temperature = 0.4;
+        let config =
+            anthropic_profile_config(&anthropic_config(LlmProviderRole::Fallback), &profile);
+        assert_eq!(config.timeout_ms, 4_000);
+        assert_eq!(config.max_retries, 0);
+        assert_eq!(config.max_output_tokens, 180);
+        assert_eq!(config.temperature, 0.4);
+        assert_eq!(config.model, "claude-3-5-haiku-latest");
+    }
+
+    #[test]
+    fn only_one_direct_provider_may_be_primary() {
+        let anthropic = anthropic_config(LlmProviderRole::Primary);
+        let openai_fallback = openai_config(LlmProviderRole::Fallback);
+        let openai_primary = openai_config(LlmProviderRole::Primary);
+        let bedrock_fallback = bedrock_config(LlmProviderRole::Fallback);
+        let bedrock_primary = bedrock_config(LlmProviderRole::Primary);
+
+        assert!(
+            DirectLlmProviders {
+                anthropic: Some(&anthropic),
+                openai: Some(&openai_fallback),
+                bedrock: Some(&bedrock_fallback),
+                local: None,
+            }
+            .validate()
+            .is_ok()
+        );
+        assert!(matches!(
+            DirectLlmProviders {
+                anthropic: Some(&anthropic),
+                openai: Some(&openai_primary),
+                bedrock: None,
+                local: None,
+            }
+            .validate(),
+            Err(ReliableGatewayBuildError::ConflictingPrimaryProviders)
+        ));
+        assert!(matches!(
+            DirectLlmProviders {
+                anthropic: None,
+                openai: Some(&openai_primary),
+                bedrock: Some(&bedrock_primary),
+                local: None,
+            }
+            .validate(),
+            Err(ReliableGatewayBuildError::ConflictingPrimaryProviders)
+        ));
     }
 }
@@ -1,25 +1,54 @@
 use std::net::SocketAddr;
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
+use std::sync::atomic::AtomicU64;
 
 use axum::Router;
 use axum::routing::{get, post};
 use shared::config::load_dotenv;
-use shared::enclave::EnclaveOperationService;
-use shared::llm::{LlmGateway, LlmReliabilityConfig, OpenRouterGatewayConfig};
+use shared::enclave::{EnclaveOperationService, EnclaveRpcTls, VsockAddr};
+use shared::feature_flags::{FeatureFlagDefaults, FeatureFlags};
+use shared::llm::{
+    AnthropicGatewayConfig, BedrockGatewayConfig, LlmGateway, LlmReliabilityConfig,
+    LlmRoutingConfig, LlmStyleConfig, LocalLlmGatewayConfig, OpenAiGatewayConfig,
+    OpenRouterGatewayConfig, PlanLlmSpendBudget,
+};
+use shared::log_redaction::{LogFormat, redacting_log_layer};
+use shared::outbound_rate_limit::OutboundCallLimiter;
+use shared::replay_guard::NonceReplayGuard;
 use shared::repos::Store;
-use shared::security::{KmsDecryptPolicy, SecretRuntime, TeeAttestationPolicy};
+use shared::secrets::{SecretsResolver, resolve_env_secret_references, watch_secret_rotations};
+use shared::security::{
+    AttestationCacheStats, AwsKmsClient, KmsClient, KmsDecryptPolicy, KmsRecipient, SecretRuntime,
+    TeeAttestationPolicy,
+};
 use tracing::{error, info, warn};
+use tracing_subscriber::EnvFilter;
+use tracing_subscriber::layer::SubscriberExt as _;
+use tracing_subscriber::util::SubscriberInitExt as _;
 
 mod config;
 mod http;
+mod key_rotation;
+mod keygen;
 mod llm_profiles;
+mod readiness;
+mod tls;
+mod vsock_listener;
+
+const ATTESTATION_CACHE_METRICS_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
 
 #[derive(Clone)]
 struct RuntimeState {
     config: config::RuntimeConfig,
     enclave_service: EnclaveOperationService,
-    rpc_replay_guard: Arc<Mutex<std::collections::HashMap<String, i64>>>,
+    ingress_keys: key_rotation::IngressKeys,
+    rpc_replay_guard: NonceReplayGuard,
+    /// RPCs verified with a secondary caller key since startup.
+    rpc_secondary_key_uses: Arc<AtomicU64>,
     llm_gateways: llm_profiles::LlmGatewayProfiles,
+    llm_readiness: readiness::LlmReadiness,
+    feature_flags: FeatureFlags,
+    rpc_tls: Option<tls::RpcServerTls>,
 }
 
 impl RuntimeState {
@@ -40,24 +69,64 @@ impl RuntimeState {
     }
 }
 
-#[tokio::main]
-async fn main() {
+fn main() {
     if let Err(err) = load_dotenv() {
         eprintln!("{err}");
         std::process::exit(1);
     }
 
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            std::env::var("RUST_LOG")
-                .unwrap_or_else(|_| "enclave_runtime=info,axum=info".to_string()),
-        )
-        .json()
-        .flatten_event(true)
-        .with_current_span(true)
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if let Some(("keygen", keygen_args)) = args.split_first().map(|(a, rest)| (a.as_str(), rest)) {
+        run_keygen(keygen_args);
+        return;
+    }
+
+    tracing_subscriber::registry()
+        .with(EnvFilter::new(std::env::var("RUST_LOG").unwrap_or_else(
+            |_| "enclave_runtime=info,axum=info".to_string(),
+        )))
+        .with(redacting_log_layer(LogFormat::Json))
         .init();
 
-    let config = match config::RuntimeConfig::from_env() {
+    let secrets = match resolve_env_secret_references() {
+        Ok(secrets) => secrets,
+        Err(err) => {
+            error!(error = %err, "failed to resolve secret references");
+            std::process::exit(1);
+        }
+    };
+    run(secrets);
+}
+
+#[tokio::main]
+async fn run_keygen(args: &[String]) {
+    if let Err(err) = keygen::run(args).await {
+        eprintln!("{err}");
+        std::process::exit(2);
+    }
+}
+
+#[tokio::main]
+async fn run(secrets: SecretsResolver) {
+    tokio::spawn(watch_secret_rotations(secrets));
+
+    let unsealed_keys = match keygen::unseal_env_private_keys(async || {
+        let http_client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(15))
+            .build()
+            .map_err(|err| format!("failed to build kms http client: {err}"))?;
+        build_kms_client(&config::kms_provider_from_env()?, &http_client)
+    })
+    .await
+    {
+        Ok(unsealed_keys) => unsealed_keys,
+        Err(err) => {
+            error!(error = %err, "failed to unseal enclave private keys");
+            std::process::exit(1);
+        }
+    };
+
+    let config = match config::RuntimeConfig::from_env(&unsealed_keys) {
         Ok(config) => config,
         Err(err) => {
             error!(error = %err, "failed to load enclave runtime config");
@@ -86,12 +155,13 @@ async fn main() {
     };
     let store = match Store::connect(
         &config.database_url,
+        None,
         config.database_max_connections,
-        &config.data_encryption_key,
+        &config.data_encryption_keys,
     )
     .await
     {
-        Ok(store) => store,
+        Ok(store) => store.with_audit_redaction_policy(config.audit_redaction_policy.clone()),
         Err(err) => {
             error!(error = %err, "failed to connect to postgres");
             std::process::exit(1);
@@ -114,9 +184,75 @@ async fn main() {
         config.enclave_runtime_base_url.clone(),
         config.tee_attestation_challenge_timeout_ms,
         http_client.clone(),
-    );
+    )
+    .with_attestation_cache_ttl(std::time::Duration::from_secs(
+        config.tee_attestation_cache_ttl_seconds,
+    ));
+    let secret_runtime = match build_kms_client(&config.kms_provider, &http_client) {
+        Ok(Some(kms_client)) => {
+            info!(
+                provider = kms_client.provider(),
+                "connector secret kms wrapping enabled"
+            );
+            secret_runtime.with_kms_client(kms_client)
+        }
+        Ok(None) => secret_runtime,
+        Err(err) => {
+            error!(error = %err, "failed to configure kms client");
+            std::process::exit(1);
+        }
+    };
+    if config.tee_attestation_cache_ttl_seconds > 0 {
+        tokio::spawn(log_attestation_cache_metrics(secret_runtime.clone()));
+    }
+    let secret_runtime = match config.rpc_client_tls.as_ref() {
+        Some(client_tls) => {
+            match EnclaveRpcTls::load(client_tls, std::time::Duration::from_secs(15)) {
+                Ok(client_tls) => secret_runtime.with_enclave_tls(&client_tls),
+                Err(err) => {
+                    error!(error = %err, "failed to load enclave rpc client tls identity");
+                    std::process::exit(1);
+                }
+            }
+        }
+        None => secret_runtime,
+    };
+    let rpc_tls = match config.rpc_server_tls.as_ref() {
+        Some(server_tls) => match tls::RpcServerTls::load(server_tls) {
+            Ok(rpc_tls) => {
+                info!(
+                    security_mode = config.enclave_rpc_security_mode.as_str(),
+                    tls_certificate_sha256 = %rpc_tls.certificate_sha256(),
+                    "enclave rpc mtls enabled"
+                );
+                tokio::spawn(tls::run_certificate_reload(rpc_tls.clone()));
+                Some(rpc_tls)
+            }
+            Err(err) => {
+                error!(error = %err, "failed to load enclave rpc tls certificate");
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
+    let ingress_keys = key_rotation::IngressKeys::new(config.assistant_ingress_keys.clone());
+    if let Some(rotation) = config.assistant_ingress_key_rotation.clone() {
+        info!(
+            interval_seconds = rotation.interval_seconds,
+            "assistant ingress key rotation enabled"
+        );
+        tokio::spawn(key_rotation::run_ingress_key_rotation(
+            store.clone(),
+            ingress_keys.clone(),
+            config.assistant_ingress_keys.active.clone(),
+            rotation,
+        ));
+    }
+    let outbound_limiter = OutboundCallLimiter::new(&config.outbound_rate_limits);
+    let llm_spend_budget = Arc::new(PlanLlmSpendBudget::new(store.clone()));
     let enclave_service =
-        EnclaveOperationService::new(store, secret_runtime, http_client, config.oauth.clone());
+        EnclaveOperationService::new(store, secret_runtime, http_client, config.oauth.clone())
+            .with_outbound_limiter(outbound_limiter.clone());
     let openrouter_config = match OpenRouterGatewayConfig::from_env() {
         Ok(cfg) => cfg,
         Err(err) => {
@@ -124,6 +260,34 @@ async fn main() {
             std::process::exit(1);
         }
     };
+    let anthropic_config = match AnthropicGatewayConfig::from_env() {
+        Ok(cfg) => cfg,
+        Err(err) => {
+            error!("failed to read Anthropic configuration for enclave startup: {err}");
+            std::process::exit(1);
+        }
+    };
+    let openai_config = match OpenAiGatewayConfig::from_env() {
+        Ok(cfg) => cfg,
+        Err(err) => {
+            error!("failed to read OpenAI configuration for enclave startup: {err}");
+            std::process::exit(1);
+        }
+    };
+    let bedrock_config = match BedrockGatewayConfig::from_env() {
+        Ok(cfg) => cfg,
+        Err(err) => {
+            error!("failed to read Bedrock configuration for enclave startup: {err}");
+            std::process::exit(1);
+        }
+    };
+    let local_llm_config = match LocalLlmGatewayConfig::from_env() {
+        Ok(cfg) => cfg,
+        Err(err) => {
+            error!("failed to read local LLM configuration for enclave startup: {err}");
+            std::process::exit(1);
+        }
+    };
     let llm_reliability_config = match LlmReliabilityConfig::from_env() {
         Ok(cfg) => cfg,
         Err(err) => {
@@ -131,12 +295,38 @@ async fn main() {
             std::process::exit(1);
         }
     };
+    let llm_routing_config = match LlmRoutingConfig::from_env() {
+        Ok(cfg) => cfg,
+        Err(err) => {
+            error!("failed to read LLM routing configuration for enclave startup: {err}");
+            std::process::exit(1);
+        }
+    };
+    let llm_style_config = match LlmStyleConfig::from_env() {
+        Ok(cfg) => cfg,
+        Err(err) => {
+            error!("failed to read LLM style configuration for enclave startup: {err}");
+            std::process::exit(1);
+        }
+    };
     let redis_url =
         std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379/0".to_string());
     let llm_gateways = match llm_profiles::build_llm_gateway_profiles(
         openrouter_config,
         llm_reliability_config,
-        &redis_url,
+        &llm_routing_config,
+        &llm_profiles::GatewayBuildContext {
+            direct_providers: llm_profiles::DirectLlmProviders {
+                anthropic: anthropic_config.as_ref(),
+                openai: openai_config.as_ref(),
+                bedrock: bedrock_config.as_ref(),
+                local: local_llm_config.as_ref(),
+            },
+            style_config: &llm_style_config,
+            redis_url: &redis_url,
+            outbound_limiter: &outbound_limiter,
+            spend_budget: llm_spend_budget,
+        },
     )
     .await
     {
@@ -147,8 +337,41 @@ async fn main() {
         }
     };
 
+    let rpc_replay_guard = NonceReplayGuard::default()
+        .with_redis_from_config(&redis_url)
+        .await;
+
+    let feature_flags = match FeatureFlagDefaults::from_env() {
+        Ok(defaults) => {
+            FeatureFlags::new(defaults)
+                .with_redis_from_config(&redis_url)
+                .await
+        }
+        Err(err) => {
+            error!("failed to read feature flag defaults for enclave startup: {err}");
+            std::process::exit(1);
+        }
+    };
+
+    let llm_readiness = readiness::LlmReadiness::default();
+    if config.llm_warmup_enabled {
+        let warm_up_gateways = llm_gateways.clone();
+        tokio::spawn(readiness::run_llm_warm_up(
+            llm_readiness.clone(),
+            std::time::Duration::from_millis(config.llm_warmup_retry_interval_ms),
+            move || {
+                let gateways = warm_up_gateways.clone();
+                async move { gateways.warm_up().await }
+            },
+        ));
+    } else {
+        info!("llm provider warm-up disabled; reporting ready without a provider probe");
+        llm_readiness.mark_ready();
+    }
+
     let app = Router::new()
         .route("/healthz", get(http::healthz))
+        .route("/readyz", get(http::readyz))
         .route("/v1/attestation/document", get(http::attestation_document))
         .route(
             "/v1/attestation/challenge",
@@ -162,6 +385,14 @@ async fn main() {
             "/v1/rpc/google/connect/complete",
             post(http::complete_google_connect),
         )
+        .route(
+            "/v1/rpc/caldav/connect/complete",
+            post(http::complete_caldav_connect),
+        )
+        .route(
+            "/v1/rpc/imap/connect/complete",
+            post(http::complete_imap_connect),
+        )
         .route(
             "/v1/rpc/google/token/revoke",
             post(http::revoke_google_connector_token),
@@ -194,13 +425,43 @@ async fn main() {
             "/v1/rpc/assistant/automation/execute",
             post(http::execute_automation),
         )
+        .route(
+            "/v1/rpc/assistant/departure-alert",
+            post(http::plan_departure_alert),
+        )
+        .route(
+            "/v1/rpc/assistant/sessions/export",
+            post(http::export_assistant_sessions),
+        )
+        .route(
+            "/v1/rpc/assistant/automation/draft",
+            post(http::draft_automation),
+        )
+        .route("/v1/rpc/audit/sign", post(http::sign_audit_event))
         .with_state(RuntimeState {
             config: config.clone(),
             enclave_service,
-            rpc_replay_guard: Arc::new(Mutex::new(std::collections::HashMap::new())),
+            ingress_keys,
+            rpc_replay_guard,
+            rpc_secondary_key_uses: Arc::new(AtomicU64::new(0)),
             llm_gateways,
+            llm_readiness,
+            feature_flags,
+            rpc_tls: rpc_tls.clone(),
         });
 
+    if VsockAddr::is_vsock_url(&config.bind_addr) {
+        vsock_listener::serve(
+            app,
+            &config.bind_addr,
+            config.environment.as_str(),
+            config.mode.as_str(),
+            rpc_tls,
+        )
+        .await;
+        return;
+    }
+
     let addr: SocketAddr = match config.bind_addr.parse() {
         Ok(addr) => addr,
         Err(err) => {
@@ -224,8 +485,59 @@ async fn main() {
         "enclave runtime listening"
     );
 
+    if let Some(rpc_tls) = rpc_tls {
+        tls::serve(listener, app, rpc_tls).await;
+        return;
+    }
+
     if let Err(err) = axum::serve(listener, app.into_make_service()).await {
         error!(error = %err, "enclave runtime failed");
         std::process::exit(1);
     }
 }
+
+/// Logs attestation cache counters once a minute while decrypts are happening.
+async fn log_attestation_cache_metrics(secret_runtime: SecretRuntime) {
+    let mut interval = tokio::time::interval(ATTESTATION_CACHE_METRICS_INTERVAL);
+    let mut last = AttestationCacheStats::default();
+    loop {
+        interval.tick().await;
+        let stats = secret_runtime.attestation_cache_stats();
+        if stats == last {
+            continue;
+        }
+        info!(
+            hits = stats.hits,
+            misses = stats.misses,
+            policy_revalidations = stats.policy_revalidations,
+            "attestation cache metrics"
+        );
+        last = stats;
+    }
+}
+
+fn build_kms_client(
+    provider: &config::KmsProviderConfig,
+    http_client: &reqwest::Client,
+) -> Result<Option<KmsClient>, String> {
+    match provider {
+        config::KmsProviderConfig::Disabled => Ok(None),
+        config::KmsProviderConfig::LocalStub => Ok(Some(KmsClient::LocalStub)),
+        config::KmsProviderConfig::Aws(aws) => {
+            let client = AwsKmsClient::new(
+                aws.region.clone(),
+                aws.endpoint.clone(),
+                aws.credentials.clone(),
+                http_client.clone(),
+            );
+            let client = match aws.recipient.as_ref() {
+                Some(recipient) => client.with_recipient(KmsRecipient::load(
+                    &recipient.private_key_path,
+                    recipient.attestation_document_path.clone(),
+                )?),
+                None => client,
+            };
+            Ok(Some(KmsClient::Aws(Box::new(client))))
+        }
+    }
+}
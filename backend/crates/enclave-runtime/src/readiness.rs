@@ -0,0 +1,80 @@
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use tracing::{info, warn};
+
+/// Tracks whether the LLM provider path has been verified since startup. `GET /readyz`
+/// reports not-ready until then so traffic only routes to enclaves that can reach the provider.
+#[derive(Clone, Default)]
+pub(crate) struct LlmReadiness {
+    ready: Arc<AtomicBool>,
+}
+
+impl LlmReadiness {
+    pub(crate) fn is_ready(&self) -> bool {
+        self.ready.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn mark_ready(&self) {
+        self.ready.store(true, Ordering::Release);
+    }
+}
+
+/// Runs `warm_up` until it succeeds, then marks the runtime ready. Failures are retried on a
+/// fixed interval; the reliability layer already backs off real traffic once it is flowing.
+pub(crate) async fn run_llm_warm_up<F, Fut>(
+    readiness: LlmReadiness,
+    retry_interval: Duration,
+    mut warm_up: F,
+) where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<(), String>>,
+{
+    let mut attempt = 1_u32;
+    loop {
+        match warm_up().await {
+            Ok(()) => {
+                readiness.mark_ready();
+                info!(attempt, "llm provider warm-up succeeded");
+                return;
+            }
+            Err(err) => {
+                warn!(attempt, error = %err, "llm provider warm-up failed; retrying");
+                tokio::time::sleep(retry_interval).await;
+                attempt = attempt.saturating_add(1);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{LlmReadiness, run_llm_warm_up};
+
+    #[tokio::test]
+    async fn retries_failed_warm_up_until_provider_path_is_verified() {
+        let readiness = LlmReadiness::default();
+        let mut failures_left = 2;
+        let mut attempts = 0;
+
+        assert!(!readiness.is_ready());
+        run_llm_warm_up(readiness.clone(), Duration::from_millis(1), || {
+            attempts += 1;
+            let result = if failures_left > 0 {
+                failures_left -= 1;
+                Err("provider unavailable".to_string())
+            } else {
+                Ok(())
+            };
+            async move { result }
+        })
+        .await;
+
+        assert_eq!(attempts, 3);
+        assert!(readiness.is_ready());
+    }
+}
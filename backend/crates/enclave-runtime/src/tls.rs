@@ -0,0 +1,323 @@
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use axum::Router;
+use hyper_util::rt::TokioIo;
+use hyper_util::service::TowerToHyperService;
+use shared::enclave::{certificate_sha256, load_certificate_chain, load_private_key};
+use tokio_rustls::TlsAcceptor;
+use tokio_rustls::rustls::crypto::{CryptoProvider, ring};
+use tokio_rustls::rustls::server::{ClientHello, ResolvesServerCert, WebPkiClientVerifier};
+use tokio_rustls::rustls::sign::CertifiedKey;
+use tokio_rustls::rustls::{RootCertStore, ServerConfig};
+use tracing::{debug, info, warn};
+
+use crate::config::RpcServerTlsConfig;
+
+/// Bound on the TLS handshake so a stalled peer cannot hold a connection slot open.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// mTLS material for the RPC listener. The served certificate can be swapped in place; its
+/// fingerprint is what the attestation challenge binds for clients to pin.
+#[derive(Clone)]
+pub(crate) struct RpcServerTls {
+    config: RpcServerTlsConfig,
+    provider: Arc<CryptoProvider>,
+    resolver: Arc<ReloadingCertResolver>,
+    acceptor: TlsAcceptor,
+}
+
+impl RpcServerTls {
+    pub(crate) fn load(config: &RpcServerTlsConfig) -> Result<Self, String> {
+        let provider = Arc::new(ring::default_provider());
+        let current = load_certified_key(config, &provider)?;
+        let resolver = Arc::new(ReloadingCertResolver {
+            current: RwLock::new(current),
+        });
+
+        let mut client_roots = RootCertStore::empty();
+        for certificate in load_certificate_chain(&config.client_ca_path)? {
+            client_roots
+                .add(certificate)
+                .map_err(|err| format!("invalid client CA certificate: {err}"))?;
+        }
+        let client_verifier =
+            WebPkiClientVerifier::builder_with_provider(Arc::new(client_roots), provider.clone())
+                .build()
+                .map_err(|err| format!("failed to configure client verification: {err}"))?;
+
+        let server_config = ServerConfig::builder_with_provider(provider.clone())
+            .with_safe_default_protocol_versions()
+            .map_err(|err| format!("failed to configure rpc tls: {err}"))?
+            .with_client_cert_verifier(client_verifier)
+            .with_cert_resolver(resolver.clone());
+
+        Ok(Self {
+            config: config.clone(),
+            provider,
+            resolver,
+            acceptor: TlsAcceptor::from(Arc::new(server_config)),
+        })
+    }
+
+    /// Fingerprint of the certificate new handshakes are served with.
+    pub(crate) fn certificate_sha256(&self) -> String {
+        let current = self.resolver.current();
+        certificate_sha256(current.cert[0].as_ref())
+    }
+
+    /// Re-reads the certificate and key. Returns `true` when the served certificate changed.
+    pub(crate) fn reload(&self) -> Result<bool, String> {
+        let before = self.certificate_sha256();
+        let next = load_certified_key(&self.config, &self.provider)?;
+        *self
+            .resolver
+            .current
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = next;
+        Ok(self.certificate_sha256() != before)
+    }
+}
+
+fn load_certified_key(
+    config: &RpcServerTlsConfig,
+    provider: &CryptoProvider,
+) -> Result<Arc<CertifiedKey>, String> {
+    let certificates = load_certificate_chain(&config.cert_path)?;
+    let key = load_private_key(&config.key_path)?;
+    CertifiedKey::from_der(certificates, key, provider)
+        .map(Arc::new)
+        .map_err(|err| format!("invalid rpc tls certificate or key: {err}"))
+}
+
+#[derive(Debug)]
+struct ReloadingCertResolver {
+    current: RwLock<Arc<CertifiedKey>>,
+}
+
+impl ReloadingCertResolver {
+    fn current(&self) -> Arc<CertifiedKey> {
+        self.current
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+}
+
+impl ResolvesServerCert for ReloadingCertResolver {
+    fn resolve(&self, _client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        Some(self.current())
+    }
+}
+
+/// Picks up rotated certificate files. A failed reload keeps serving the current certificate.
+pub(crate) async fn run_certificate_reload(tls: RpcServerTls) {
+    let interval = Duration::from_secs(tls.config.reload_interval_seconds);
+    loop {
+        tokio::time::sleep(interval).await;
+        match tls.reload() {
+            Ok(true) => info!(
+                tls_certificate_sha256 = %tls.certificate_sha256(),
+                "enclave rpc tls certificate rotated"
+            ),
+            Ok(false) => {}
+            Err(err) => warn!(error = %err, "failed to reload enclave rpc tls certificate"),
+        }
+    }
+}
+
+/// Serves `app` over mutual TLS on connections accepted from `listener`. Peers without a client
+/// certificate issued by the configured CA fail the handshake before any request is read.
+pub(crate) async fn serve<L>(mut listener: L, app: Router, tls: RpcServerTls)
+where
+    L: axum::serve::Listener,
+    L::Addr: std::fmt::Debug,
+{
+    loop {
+        let (io, remote_addr) = listener.accept().await;
+        let acceptor = tls.acceptor.clone();
+        let app = app.clone();
+        tokio::spawn(async move {
+            let stream = match tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(io)).await {
+                Ok(Ok(stream)) => stream,
+                Ok(Err(err)) => {
+                    debug!(error = %err, ?remote_addr, "enclave rpc tls handshake failed");
+                    return;
+                }
+                Err(_) => {
+                    debug!(?remote_addr, "enclave rpc tls handshake timed out");
+                    return;
+                }
+            };
+
+            if let Err(err) = hyper::server::conn::http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), TowerToHyperService::new(app))
+                .await
+            {
+                debug!(error = %err, ?remote_addr, "enclave rpc connection closed with error");
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    use axum::Json;
+    use axum::extract::State;
+    use axum::routing::post;
+    use shared::enclave::{EnclaveRpcClientTlsConfig, EnclaveRpcTls};
+    use shared::enclave_runtime::{AttestationChallengeRequest, AttestationChallengeResponse};
+    use shared::security::{KmsDecryptPolicy, SecretRuntime, TeeAttestationPolicy};
+
+    use super::*;
+
+    fn fixture(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("../shared/tests/fixtures/tls")
+            .join(name)
+    }
+
+    fn fixture_fingerprint(name: &str) -> String {
+        let chain =
+            load_certificate_chain(&fixture(name)).expect("fixture certificate should load");
+        certificate_sha256(chain[0].as_ref())
+    }
+
+    fn server_config(dir: &std::path::Path) -> RpcServerTlsConfig {
+        RpcServerTlsConfig {
+            cert_path: dir.join("server.pem"),
+            key_path: dir.join("server.key"),
+            client_ca_path: fixture("client-ca.pem"),
+            reload_interval_seconds: 300,
+        }
+    }
+
+    fn install_server_certificate(dir: &std::path::Path, name: &str) {
+        std::fs::copy(fixture(&format!("{name}.pem")), dir.join("server.pem"))
+            .expect("certificate should copy");
+        std::fs::copy(fixture(&format!("{name}.key")), dir.join("server.key"))
+            .expect("key should copy");
+    }
+
+    fn scratch_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("enclave-rpc-tls-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).expect("scratch dir should be created");
+        dir
+    }
+
+    async fn attest_with(base_url: &str, client: &str) -> Result<String, String> {
+        let tls = EnclaveRpcTls::load(
+            &EnclaveRpcClientTlsConfig {
+                client_cert_path: fixture(&format!("{client}.pem")),
+                client_key_path: fixture(&format!("{client}.key")),
+            },
+            Duration::from_secs(5),
+        )?;
+        SecretRuntime::new(
+            TeeAttestationPolicy {
+                required: false,
+                expected_runtime: "nitro".to_string(),
+                allowed_measurements: vec!["dev-local-enclave".to_string()],
+                attestation_public_key: None,
+                max_attestation_age_seconds: 300,
+                allow_insecure_dev_attestation: true,
+            },
+            KmsDecryptPolicy {
+                key_id: "kms/local/alfred-refresh-token".to_string(),
+                key_version: 1,
+                allowed_measurements: vec!["dev-local-enclave".to_string()],
+            },
+            base_url.to_string(),
+            2000,
+            reqwest::Client::new(),
+        )
+        .with_enclave_tls(&tls)
+        .attest_enclave_tls_certificate()
+        .await
+        .map_err(|err| err.to_string())
+    }
+
+    #[tokio::test]
+    async fn serves_the_attested_certificate_to_trusted_clients_only() {
+        let dir = scratch_dir();
+        install_server_certificate(&dir, "server-a");
+        let tls = RpcServerTls::load(&server_config(&dir)).expect("server tls should load");
+        let app = Router::new()
+            .route(
+                "/v1/attestation/challenge",
+                post(
+                    |State(tls): State<RpcServerTls>,
+                     Json(challenge): Json<AttestationChallengeRequest>| async move {
+                        Json(AttestationChallengeResponse {
+                            runtime: "nitro".to_string(),
+                            measurement: "dev-local-enclave".to_string(),
+                            challenge_nonce: challenge.challenge_nonce,
+                            issued_at: challenge.issued_at,
+                            expires_at: challenge.expires_at,
+                            operation_purpose: challenge.operation_purpose,
+                            request_id: challenge.request_id,
+                            evidence_issued_at: challenge.issued_at,
+                            tls_certificate_sha256: Some(tls.certificate_sha256()),
+                            signature: None,
+                        })
+                    },
+                ),
+            )
+            .with_state(tls.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("test listener should bind");
+        let base_url = format!(
+            "https://{}",
+            listener
+                .local_addr()
+                .expect("listener should expose local address")
+        );
+        let _server = tokio::spawn(serve(listener, app, tls));
+
+        assert_eq!(
+            attest_with(&base_url, "client")
+                .await
+                .expect("trusted client should complete the handshake"),
+            fixture_fingerprint("server-a.pem")
+        );
+        assert!(
+            attest_with(&base_url, "untrusted-client").await.is_err(),
+            "client certificates outside the configured CA must be refused"
+        );
+
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn reload_swaps_in_a_rotated_certificate() {
+        let dir = scratch_dir();
+        install_server_certificate(&dir, "server-a");
+        let tls = RpcServerTls::load(&server_config(&dir)).expect("server tls should load");
+        assert_eq!(
+            tls.certificate_sha256(),
+            fixture_fingerprint("server-a.pem")
+        );
+        assert!(!tls.reload().expect("unchanged files should reload"));
+
+        install_server_certificate(&dir, "server-b");
+        assert!(tls.reload().expect("rotated files should reload"));
+        assert_eq!(
+            tls.certificate_sha256(),
+            fixture_fingerprint("server-b.pem")
+        );
+
+        // A key that does not match the certificate leaves the current one in service.
+        std::fs::copy(fixture("server-a.key"), dir.join("server.key")).expect("key should copy");
+        assert!(tls.reload().is_err());
+        assert_eq!(
+            tls.certificate_sha256(),
+            fixture_fingerprint("server-b.pem")
+        );
+
+        std::fs::remove_dir_all(dir).ok();
+    }
+}
@@ -0,0 +1,105 @@
+use axum::Router;
+use shared::enclave::VsockAddr;
+use tracing::error;
+
+use crate::tls::RpcServerTls;
+
+/// Serves `app` on the vsock address in `bind_addr`. Inside a Nitro enclave this is the only
+/// channel the parent instance can use to reach the runtime. With `rpc_tls` every connection
+/// completes an mTLS handshake before HTTP is spoken.
+pub(crate) async fn serve(
+    app: Router,
+    bind_addr: &str,
+    environment: &str,
+    mode: &str,
+    rpc_tls: Option<RpcServerTls>,
+) {
+    let addr = match VsockAddr::parse_url(bind_addr) {
+        Ok(addr) => addr,
+        Err(err) => {
+            error!(error = %err, bind_addr, "invalid bind addr");
+            std::process::exit(1);
+        }
+    };
+
+    serve_on(app, addr, environment, mode, rpc_tls).await;
+}
+
+#[cfg(target_os = "linux")]
+async fn serve_on(
+    app: Router,
+    addr: VsockAddr,
+    environment: &str,
+    mode: &str,
+    rpc_tls: Option<RpcServerTls>,
+) {
+    let listener = match shared::enclave::VsockListener::bind(addr) {
+        Ok(listener) => listener,
+        Err(err) => {
+            error!(error = %err, bind_addr = %addr, "failed to bind enclave runtime vsock listener");
+            std::process::exit(1);
+        }
+    };
+
+    tracing::info!(
+        bind_addr = %listener.local_addr().unwrap_or(addr),
+        environment,
+        mode,
+        "enclave runtime listening on vsock"
+    );
+
+    if let Some(rpc_tls) = rpc_tls {
+        crate::tls::serve(linux::Incoming(listener), app, rpc_tls).await;
+        return;
+    }
+
+    if let Err(err) = axum::serve(linux::Incoming(listener), app.into_make_service()).await {
+        error!(error = %err, "enclave runtime failed");
+        std::process::exit(1);
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+async fn serve_on(
+    _app: Router,
+    addr: VsockAddr,
+    _environment: &str,
+    _mode: &str,
+    _rpc_tls: Option<RpcServerTls>,
+) {
+    error!(bind_addr = %addr, "vsock listener mode is only available on Linux");
+    std::process::exit(1);
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::io;
+    use std::time::Duration;
+
+    use shared::enclave::{VsockAddr, VsockListener, VsockStream};
+    use tracing::warn;
+
+    pub(super) struct Incoming(pub(super) VsockListener);
+
+    impl axum::serve::Listener for Incoming {
+        type Io = VsockStream;
+        type Addr = VsockAddr;
+
+        async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+            loop {
+                match self.0.accept().await {
+                    Ok(connection) => return connection,
+                    Err(err) => {
+                        // Same back-off axum applies to TCP accept failures such as EMFILE.
+                        warn!(error = %err, "failed to accept vsock connection");
+                        tokio::time::sleep(Duration::from_secs(1)).await;
+                    }
+                }
+            }
+        }
+
+        fn local_addr(&self) -> io::Result<Self::Addr> {
+            self.0.local_addr()
+        }
+    }
+}
@@ -0,0 +1,171 @@
+mod support;
+
+use chrono::{Duration, Utc};
+use serial_test::serial;
+use shared::connector_health::{ConnectorHealthSignal, REQUIRED_GOOGLE_SCOPES};
+use shared::repos::{ConnectorAccount, JobType, LEGACY_CONNECTOR_ACCOUNT_KEY};
+use uuid::Uuid;
+
+#[tokio::test]
+#[serial]
+async fn dead_letter_replay_requeues_the_payload_once_and_updates_queue_depth() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let user_id = Uuid::new_v4();
+    let now = Utc::now();
+    let payload = b"automation-run-payload".to_vec();
+    let job_id = store
+        .enqueue_job(user_id, JobType::AutomationRun, now, Some(&payload))
+        .await
+        .expect("job enqueue should succeed");
+    let pending = store
+        .enqueue_job(
+            user_id,
+            JobType::DepartureAlert,
+            now + Duration::hours(1),
+            None,
+        )
+        .await
+        .expect("future job enqueue should succeed");
+
+    let worker_id = Uuid::new_v4();
+    let claimed = store
+        .claim_due_jobs(now, worker_id, 10, 1, 60)
+        .await
+        .expect("claim should succeed");
+    assert_eq!(claimed.len(), 1);
+    assert!(
+        store
+            .mark_job_failed(
+                &claimed[0],
+                worker_id,
+                3,
+                "PROVIDER_FAILED",
+                "provider failed"
+            )
+            .await
+            .expect("failure should record")
+    );
+
+    let depth = store
+        .job_queue_depth(user_id, now)
+        .await
+        .expect("queue depth should load");
+    assert_eq!((depth.pending, depth.due, depth.running), (1, 0, 0));
+    assert_eq!(depth.dead_lettered, 1);
+
+    let dead_letters = store
+        .list_dead_letter_jobs(user_id, 10)
+        .await
+        .expect("dead letters should list");
+    assert_eq!(dead_letters.len(), 1);
+    assert_eq!(dead_letters[0].job_id, job_id);
+    assert_eq!(dead_letters[0].reason_code, "PROVIDER_FAILED");
+    assert!(dead_letters[0].replay_job_id.is_none());
+    let dead_letter_id = dead_letters[0].id;
+
+    assert!(
+        store
+            .replay_dead_letter_job(Uuid::new_v4(), dead_letter_id, now)
+            .await
+            .expect("foreign replay lookup should succeed")
+            .is_none(),
+        "dead letters are scoped to their user"
+    );
+
+    let replay = store
+        .replay_dead_letter_job(user_id, dead_letter_id, now)
+        .await
+        .expect("replay should succeed")
+        .expect("dead letter should exist");
+    assert!(replay.newly_queued);
+    assert_ne!(replay.job_id, job_id);
+    assert_ne!(replay.job_id, pending);
+
+    let again = store
+        .replay_dead_letter_job(user_id, dead_letter_id, now + Duration::minutes(1))
+        .await
+        .expect("second replay should succeed")
+        .expect("dead letter should exist");
+    assert!(!again.newly_queued);
+    assert_eq!(again.job_id, replay.job_id);
+
+    let depth = store
+        .job_queue_depth(user_id, now)
+        .await
+        .expect("queue depth should load");
+    assert_eq!((depth.pending, depth.due, depth.dead_lettered), (2, 1, 0));
+    assert_eq!(
+        depth.oldest_due_at.map(|at| at.timestamp_micros()),
+        Some(now.timestamp_micros())
+    );
+
+    let replayed = store
+        .claim_due_jobs(now, worker_id, 10, 1, 60)
+        .await
+        .expect("replayed job should be claimable");
+    assert_eq!(replayed.len(), 1);
+    assert_eq!(replayed[0].id, replay.job_id);
+    assert_eq!(
+        replayed[0].payload_ciphertext.as_deref(),
+        Some(&payload[..])
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn connector_health_listing_includes_counters_and_unknown_users_are_reported() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let user_id = Uuid::new_v4();
+    assert!(
+        !store
+            .user_exists(user_id)
+            .await
+            .expect("lookup should work")
+    );
+
+    let scopes = REQUIRED_GOOGLE_SCOPES
+        .iter()
+        .map(|scope| (*scope).to_string())
+        .collect::<Vec<_>>();
+    let connector_id = store
+        .upsert_google_connector(
+            ConnectorAccount {
+                user_id,
+                connector_id: Uuid::new_v4(),
+                account_key: LEGACY_CONNECTOR_ACCOUNT_KEY,
+            },
+            "refresh-token",
+            &scopes,
+            "kms/local/alfred-refresh-token",
+            1,
+        )
+        .await
+        .expect("connector insert should succeed");
+    assert!(
+        store
+            .user_exists(user_id)
+            .await
+            .expect("lookup should work")
+    );
+
+    store
+        .record_connector_health_signal(connector_id, ConnectorHealthSignal::RefreshFailed)
+        .await
+        .expect("health signal should persist");
+
+    let health = store
+        .list_connector_health(user_id)
+        .await
+        .expect("connector health should list");
+    assert_eq!(health.len(), 1);
+    assert_eq!(health[0].connector_id, connector_id);
+    assert_eq!(health[0].status, "ACTIVE");
+    assert_eq!(health[0].health_score, 60);
+    assert_eq!(health[0].refresh_failure_count, 1);
+    assert!(health[0].health_updated_at.is_some());
+    assert!(health[0].reauth_nudged_at.is_none());
+}
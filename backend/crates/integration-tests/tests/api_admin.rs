@@ -0,0 +1,386 @@
+mod support;
+
+use axum::body::{Body, to_bytes};
+use axum::http::{Method, Request, StatusCode, header};
+use chrono::Utc;
+use serde_json::{Value, json};
+use serial_test::serial;
+use shared::models::UserPlan;
+use shared::repos::JobType;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use support::api_app::{
+    TEST_ADMIN_API_TOKEN, build_test_router, build_test_router_with_org_admin_role,
+    user_id_for_subject,
+};
+use support::clerk::TestClerkAuth;
+
+#[tokio::test]
+#[serial]
+async fn admin_views_and_replay_are_audited_against_the_service_principal() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let user_id = user_id_for_subject(&clerk.issuer, "admin-target");
+    let now = Utc::now();
+    store
+        .enqueue_job(user_id, JobType::AutomationRun, now, None)
+        .await
+        .expect("job enqueue should succeed");
+    let worker_id = Uuid::new_v4();
+    let claimed = store
+        .claim_due_jobs(now, worker_id, 10, 1, 60)
+        .await
+        .expect("claim should succeed");
+    store
+        .mark_job_failed(
+            &claimed[0],
+            worker_id,
+            3,
+            "PROVIDER_FAILED",
+            "provider failed",
+        )
+        .await
+        .expect("failure should record");
+
+    let pool = store.pool().clone();
+    let app = build_test_router(store, &clerk).await;
+    let admin_auth = format!("Bearer {TEST_ADMIN_API_TOKEN}");
+
+    let user_auth = format!("Bearer {}", clerk.token_for_subject("admin-target"));
+    let rejected = send_json(
+        &app,
+        request(
+            Method::GET,
+            &format!("/admin/v1/users/{user_id}/jobs"),
+            &user_auth,
+        ),
+    )
+    .await;
+    assert_eq!(rejected.status, StatusCode::UNAUTHORIZED);
+
+    let unknown = send_json(
+        &app,
+        request(
+            Method::GET,
+            &format!("/admin/v1/users/{}/jobs", Uuid::new_v4()),
+            &admin_auth,
+        ),
+    )
+    .await;
+    assert_eq!(unknown.status, StatusCode::NOT_FOUND);
+
+    let depth = send_json(
+        &app,
+        request(
+            Method::GET,
+            &format!("/admin/v1/users/{user_id}/jobs"),
+            &admin_auth,
+        ),
+    )
+    .await;
+    assert_eq!(depth.status, StatusCode::OK);
+    assert_eq!(depth.body.get("dead_lettered"), Some(&json!(1)));
+
+    let dead_letters = send_json(
+        &app,
+        request(
+            Method::GET,
+            &format!("/admin/v1/users/{user_id}/dead-letter-jobs"),
+            &admin_auth,
+        ),
+    )
+    .await;
+    assert_eq!(dead_letters.status, StatusCode::OK);
+    let dead_letter_id = dead_letters
+        .body
+        .pointer("/items/0/dead_letter_id")
+        .and_then(Value::as_str)
+        .expect("dead letter should be listed")
+        .to_string();
+
+    let replay = send_json(
+        &app,
+        request(
+            Method::POST,
+            &format!("/admin/v1/users/{user_id}/dead-letter-jobs/{dead_letter_id}/replay"),
+            &admin_auth,
+        ),
+    )
+    .await;
+    assert_eq!(replay.status, StatusCode::OK);
+    assert_eq!(replay.body.get("newly_queued"), Some(&Value::Bool(true)));
+
+    let rate_limits = send_json(
+        &app,
+        request(
+            Method::GET,
+            &format!("/admin/v1/users/{user_id}/rate-limits"),
+            &admin_auth,
+        ),
+    )
+    .await;
+    assert_eq!(rate_limits.status, StatusCode::OK);
+    assert!(
+        rate_limits
+            .body
+            .get("items")
+            .and_then(Value::as_array)
+            .is_some_and(|items| !items.is_empty())
+    );
+
+    let actions: Vec<(String, String)> = sqlx::query_as(
+        "SELECT redacted_metadata->>'action', redacted_metadata->>'admin_principal'
+         FROM audit_events
+         WHERE user_id = $1 AND event_type = 'ADMIN_ACTION'
+         ORDER BY chain_seq",
+    )
+    .bind(user_id)
+    .fetch_all(&pool)
+    .await
+    .expect("admin audit events should load");
+    assert_eq!(
+        actions,
+        [
+            "view_job_queue",
+            "view_dead_letter_jobs",
+            "replay_dead_letter_job",
+            "view_rate_limits"
+        ]
+        .into_iter()
+        .map(|action| (action.to_string(), "service_token".to_string()))
+        .collect::<Vec<_>>()
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn org_admins_only_read_their_own_members() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let member_id = user_id_for_subject(&clerk.issuer, "acme-member");
+    let outsider_id = user_id_for_subject(&clerk.issuer, "outsider");
+    let pool = store.pool().clone();
+    let app = build_test_router_with_org_admin_role(store.clone(), &clerk, "org:admin").await;
+
+    let member_auth = format!(
+        "Bearer {}",
+        clerk.token_for_org_member("acme-member", "org_acme", "org:member")
+    );
+    let outsider_auth = format!("Bearer {}", clerk.token_for_subject("outsider"));
+    for auth in [&member_auth, &outsider_auth] {
+        let status = send_json(&app, request(Method::GET, "/v1/status", auth)).await;
+        assert_eq!(status.status, StatusCode::OK);
+    }
+
+    let rejected = send_json(
+        &app,
+        request(
+            Method::GET,
+            "/admin/v1/organizations/org_acme/members",
+            &member_auth,
+        ),
+    )
+    .await;
+    assert_eq!(rejected.status, StatusCode::UNAUTHORIZED);
+
+    let org_admin_auth = format!(
+        "Bearer {}",
+        clerk.token_for_org_member("acme-admin", "org_acme", "org:admin")
+    );
+    let members = send_json(
+        &app,
+        request(
+            Method::GET,
+            "/admin/v1/organizations/org_acme/members",
+            &org_admin_auth,
+        ),
+    )
+    .await;
+    assert_eq!(members.status, StatusCode::OK);
+    assert_eq!(members.body.get("plan"), Some(&json!("free")));
+    assert_eq!(
+        members.body.pointer("/items/0/user_id"),
+        Some(&json!(member_id.to_string()))
+    );
+    assert_eq!(
+        members.body.pointer("/items/0/connector_count"),
+        Some(&json!(0))
+    );
+    assert_eq!(members.body.pointer("/items/1"), None);
+
+    for (method, uri) in [
+        (
+            Method::GET,
+            "/admin/v1/organizations/org_other/members".to_string(),
+        ),
+        (Method::GET, "/admin/v1/feature-flags".to_string()),
+        (
+            Method::POST,
+            format!(
+                "/admin/v1/users/{member_id}/dead-letter-jobs/{}/replay",
+                Uuid::new_v4()
+            ),
+        ),
+    ] {
+        let forbidden = send_json(&app, request(method, &uri, &org_admin_auth)).await;
+        assert_eq!(forbidden.status, StatusCode::FORBIDDEN, "{uri}");
+        assert_eq!(
+            forbidden.body.pointer("/error/code"),
+            Some(&json!("admin_scope_forbidden"))
+        );
+    }
+
+    let member_limits = send_json(
+        &app,
+        request(
+            Method::GET,
+            &format!("/admin/v1/users/{member_id}/rate-limits"),
+            &org_admin_auth,
+        ),
+    )
+    .await;
+    assert_eq!(member_limits.status, StatusCode::OK);
+    let outsider_limits = send_json(
+        &app,
+        request(
+            Method::GET,
+            &format!("/admin/v1/users/{outsider_id}/rate-limits"),
+            &org_admin_auth,
+        ),
+    )
+    .await;
+    assert_eq!(outsider_limits.status, StatusCode::NOT_FOUND);
+
+    let principal: String = sqlx::query_scalar(
+        "SELECT redacted_metadata->>'admin_principal'
+         FROM audit_events
+         WHERE user_id = $1 AND event_type = 'ADMIN_ACTION'",
+    )
+    .bind(member_id)
+    .fetch_one(&pool)
+    .await
+    .expect("org admin view should be audited");
+    assert_eq!(principal, "org_admin:org_acme:acme-admin");
+
+    let plan_request = |auth: &str| {
+        Request::builder()
+            .method(Method::PUT)
+            .uri("/admin/v1/organizations/org_acme/plan")
+            .header(header::AUTHORIZATION, auth)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(json!({ "plan": "pro" }).to_string()))
+            .expect("request should build")
+    };
+    let denied = send_json(&app, plan_request(&org_admin_auth)).await;
+    assert_eq!(denied.status, StatusCode::FORBIDDEN);
+    let upgraded = send_json(
+        &app,
+        plan_request(&format!("Bearer {TEST_ADMIN_API_TOKEN}")),
+    )
+    .await;
+    assert_eq!(upgraded.status, StatusCode::OK);
+
+    assert_eq!(
+        store.get_user_plan(member_id).await.expect("plan lookup"),
+        UserPlan::Pro
+    );
+    assert_eq!(
+        store.get_user_plan(outsider_id).await.expect("plan lookup"),
+        UserPlan::Free
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn members_removed_from_an_org_lose_its_plan_and_admin_visibility() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let member_id = user_id_for_subject(&clerk.issuer, "leaving-member");
+    let app = build_test_router_with_org_admin_role(store.clone(), &clerk, "org:admin").await;
+    store
+        .set_organization_plan("org_acme", UserPlan::Pro)
+        .await
+        .expect("organization plan should be set");
+
+    let member_auth = format!(
+        "Bearer {}",
+        clerk.token_for_org_member("leaving-member", "org_acme", "org:member")
+    );
+    let status = send_json(&app, request(Method::GET, "/v1/status", &member_auth)).await;
+    assert_eq!(status.status, StatusCode::OK);
+    assert_eq!(
+        store.get_user_plan(member_id).await.expect("plan lookup"),
+        UserPlan::Pro
+    );
+
+    // Once removed from the organization, the user's sessions carry no organization claim.
+    let removed_auth = format!("Bearer {}", clerk.token_for_subject("leaving-member"));
+    let status = send_json(&app, request(Method::GET, "/v1/status", &removed_auth)).await;
+    assert_eq!(status.status, StatusCode::OK);
+    assert_eq!(
+        store.get_user_plan(member_id).await.expect("plan lookup"),
+        UserPlan::Free
+    );
+
+    let org_admin_auth = format!(
+        "Bearer {}",
+        clerk.token_for_org_member("acme-admin", "org_acme", "org:admin")
+    );
+    let members = send_json(
+        &app,
+        request(
+            Method::GET,
+            "/admin/v1/organizations/org_acme/members",
+            &org_admin_auth,
+        ),
+    )
+    .await;
+    assert_eq!(members.status, StatusCode::OK);
+    assert_eq!(members.body.pointer("/items/0"), None);
+    let member_limits = send_json(
+        &app,
+        request(
+            Method::GET,
+            &format!("/admin/v1/users/{member_id}/rate-limits"),
+            &org_admin_auth,
+        ),
+    )
+    .await;
+    assert_eq!(member_limits.status, StatusCode::NOT_FOUND);
+}
+
+struct JsonResponse {
+    status: StatusCode,
+    body: Value,
+}
+
+async fn send_json(app: &axum::Router, request: Request<Body>) -> JsonResponse {
+    let response = app
+        .clone()
+        .oneshot(request)
+        .await
+        .expect("request should succeed");
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("response body should read");
+    let body = serde_json::from_slice::<Value>(&body).unwrap_or_else(|_| json!({}));
+
+    JsonResponse { status, body }
+}
+
+fn request(method: Method, uri: &str, auth_header: &str) -> Request<Body> {
+    Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::AUTHORIZATION, auth_header)
+        .body(Body::empty())
+        .expect("request should build")
+}
@@ -20,15 +20,17 @@ use shared::assistant_crypto::{
 use shared::enclave::{
     AttestedIdentityPayload, ENCLAVE_RPC_CONTRACT_VERSION,
     ENCLAVE_RPC_PATH_FETCH_ASSISTANT_ATTESTED_KEY, ENCLAVE_RPC_PATH_PROCESS_ASSISTANT_QUERY,
-    EnclaveRpcFetchAssistantAttestedKeyRequest, EnclaveRpcFetchAssistantAttestedKeyResponse,
-    EnclaveRpcProcessAssistantQueryRequest, EnclaveRpcProcessAssistantQueryResponse,
+    EnclaveAssistantQueryTelemetry, EnclaveRpcFetchAssistantAttestedKeyRequest,
+    EnclaveRpcFetchAssistantAttestedKeyResponse, EnclaveRpcProcessAssistantQueryRequest,
+    EnclaveRpcProcessAssistantQueryResponse,
 };
 use shared::models::{
-    AssistantAttestedKeyResponse, AssistantEncryptedRequestEnvelope,
+    AssistantAttestedKeyResponse, AssistantDegradedReason, AssistantEncryptedRequestEnvelope,
     AssistantPlaintextQueryRequest, AssistantPlaintextQueryResponse, AssistantQueryCapability,
     AssistantQueryRequest, AssistantQueryResponse, AssistantResponsePart,
     AssistantSessionStateEnvelope, AssistantStructuredPayload,
 };
+use shared::repos::AssistantRequestOutcome;
 use tokio::sync::Mutex;
 use tower::ServiceExt;
 use uuid::Uuid;
@@ -39,6 +41,7 @@ use support::clerk::TestClerkAuth;
 use support::enclave_mock::MockEnclaveServer;
 
 const MOCK_DISPLAY_TEXT: &str = "Encrypted response from enclave for assistant-v2-round-trip";
+const MOCK_MODEL: &str = "mock/assistant-model";
 const MOCK_TOOL_SUMMARY_TEXT: &str =
     "Tool summary token assistant-v2-cal-email should never leak to host state";
 
@@ -115,6 +118,10 @@ async fn mock_ios_encrypted_query_round_trip_keeps_host_content_blind() {
 
     let api_response: AssistantQueryResponse = serde_json::from_value(assistant_query.body)
         .expect("assistant query response should decode");
+    assert_eq!(
+        api_response.degraded_reasons,
+        vec![AssistantDegradedReason::ResponseFallback]
+    );
     let raw_host_response = serde_json::to_string(&api_response)
         .expect("assistant query response should serialize for plaintext leak check");
     let decrypted_response = decrypt_mock_ios_response(
@@ -202,6 +209,22 @@ async fn mock_ios_encrypted_query_round_trip_keeps_host_content_blind() {
     .await
     .expect("response plaintext leak check query should succeed");
     assert_eq!(response_plaintext_leak_count, 0);
+
+    let indexed = store
+        .get_assistant_request(user_id, request_id.as_str())
+        .await
+        .expect("assistant request index lookup should succeed")
+        .expect("assistant request should be indexed");
+    assert_eq!(indexed.entry.outcome, AssistantRequestOutcome::Succeeded);
+    assert_eq!(indexed.entry.session_id, Some(api_response.session_id));
+    assert_eq!(indexed.entry.route.as_deref(), Some("calendar_lookup"));
+    assert_eq!(indexed.entry.capability.as_deref(), Some("calendar_lookup"));
+    assert_eq!(indexed.entry.model.as_deref(), Some(MOCK_MODEL));
+    assert!(!indexed.entry.planner_used_fallback);
+    assert!(indexed.entry.output_used_fallback);
+    assert_eq!(indexed.entry.planner_ms, Some(12));
+    assert_eq!(indexed.entry.lane_ms, Some(34));
+    assert_eq!(indexed.entry.error_code, None);
 }
 
 async fn start_assistant_mock_enclave(
@@ -236,6 +259,7 @@ async fn start_assistant_mock_enclave(
                                 public_key: attested_key.public_key,
                                 key_expires_at: attested_key.key_expires_at,
                                 signature: None,
+                                rotation: None,
                             })
                         }
                     },
@@ -309,6 +333,15 @@ async fn start_assistant_mock_enclave(
                                 session_id,
                                 envelope: response_envelope,
                                 session_state: Some(session_state),
+                                telemetry: Some(EnclaveAssistantQueryTelemetry {
+                                    route: "calendar_lookup".to_string(),
+                                    capability: AssistantQueryCapability::CalendarLookup,
+                                    model: Some(MOCK_MODEL.to_string()),
+                                    planner_used_fallback: false,
+                                    output_used_fallback: true,
+                                    planner_ms: 12,
+                                    lane_ms: 34,
+                                }),
                                 attested_identity: AttestedIdentityPayload {
                                     runtime: "nitro".to_string(),
                                     measurement: "dev-local-enclave".to_string(),
@@ -254,6 +254,7 @@ async fn start_multiturn_mock_enclave(
                                 public_key: attested_key.public_key,
                                 key_expires_at: attested_key.key_expires_at,
                                 signature: None,
+                                rotation: None,
                             })
                         }
                     },
@@ -325,6 +326,7 @@ async fn start_multiturn_mock_enclave(
                                 session_id: response_payload.session_id,
                                 envelope: encrypted_response,
                                 session_state: Some(session_state),
+                                telemetry: None,
                                 attested_identity: AttestedIdentityPayload {
                                     runtime: "nitro".to_string(),
                                     measurement: "dev-local-enclave".to_string(),
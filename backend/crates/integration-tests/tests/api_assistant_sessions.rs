@@ -1,16 +1,30 @@
 mod support;
 
 use axum::body::{Body, to_bytes};
+use axum::extract::Json as JsonBody;
 use axum::http::{Method, Request, StatusCode, header};
+use axum::routing::post;
+use base64::{Engine as _, engine::general_purpose::STANDARD};
 use chrono::{Duration, Utc};
 use serde_json::{Value, json};
 use serial_test::serial;
-use shared::models::{AssistantSessionStateEnvelope, ListAssistantSessionsResponse, OkResponse};
+use shared::enclave::{
+    AttestedIdentityPayload, ENCLAVE_RPC_CONTRACT_VERSION,
+    ENCLAVE_RPC_PATH_EXPORT_ASSISTANT_SESSIONS, EnclaveRpcExportAssistantSessionsRequest,
+    EnclaveRpcExportAssistantSessionsResponse,
+};
+use shared::models::{
+    AssistantEncryptedResponseEnvelope, AssistantSessionExportResponse,
+    AssistantSessionStateEnvelope, ListAssistantSessionsResponse, OkResponse,
+};
 use tower::ServiceExt;
 use uuid::Uuid;
 
-use support::api_app::{build_test_router, user_id_for_subject};
+use support::api_app::{
+    build_test_router, build_test_router_with_enclave_base_url, user_id_for_subject,
+};
 use support::clerk::TestClerkAuth;
+use support::enclave_mock::MockEnclaveServer;
 
 #[tokio::test]
 #[serial]
@@ -189,6 +203,333 @@ async fn assistant_sessions_list_and_delete_respect_user_boundaries() {
     assert_eq!(user_b_unchanged_body.items[0].session_id, session_b);
 }
 
+#[tokio::test]
+#[serial]
+async fn assistant_session_titles_and_pins_order_the_thread_list() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let subject = "assistant-sessions-title-user";
+    let user_id = user_id_for_subject(&clerk.issuer, subject);
+    let auth = format!("Bearer {}", clerk.token_for_subject(subject));
+    let auth_other = format!(
+        "Bearer {}",
+        clerk.token_for_subject("assistant-sessions-title-other")
+    );
+    let app = build_test_router(store.clone(), &clerk).await;
+
+    let now = Utc::now();
+    let session_old = Uuid::new_v4();
+    let session_new = Uuid::new_v4();
+    for (session_id, ciphertext, updated_at) in [
+        (session_old, "cipher-old", now - Duration::minutes(30)),
+        (session_new, "cipher-new", now - Duration::minutes(10)),
+    ] {
+        store
+            .upsert_assistant_encrypted_session(
+                user_id,
+                session_id,
+                &test_state(ciphertext, now + Duration::days(3)),
+                updated_at,
+                3600,
+            )
+            .await
+            .expect("session insert should succeed");
+    }
+
+    let title_envelope = json!({
+        "version": "v1",
+        "algorithm": "chacha20poly1305",
+        "key_id": "device-title-key-1",
+        "nonce": STANDARD.encode([1_u8; 12]),
+        "ciphertext": STANDARD.encode([2_u8; 40]),
+    });
+
+    let empty_update = send_json(
+        &app,
+        request(
+            Method::PATCH,
+            &format!("/v1/assistant/sessions/{session_old}"),
+            Some(auth.as_str()),
+            Some(json!({})),
+        ),
+    )
+    .await;
+    assert_eq!(empty_update.status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        error_code(&empty_update.body),
+        Some("invalid_session_update")
+    );
+
+    let bad_nonce = send_json(
+        &app,
+        request(
+            Method::PATCH,
+            &format!("/v1/assistant/sessions/{session_old}"),
+            Some(auth.as_str()),
+            Some(json!({
+                "title_envelope": {
+                    "version": "v1",
+                    "algorithm": "chacha20poly1305",
+                    "key_id": "device-title-key-1",
+                    "nonce": STANDARD.encode([1_u8; 8]),
+                    "ciphertext": STANDARD.encode([2_u8; 40]),
+                },
+            })),
+        ),
+    )
+    .await;
+    assert_eq!(bad_nonce.status, StatusCode::BAD_REQUEST);
+    assert_eq!(error_code(&bad_nonce.body), Some("invalid_nonce"));
+
+    let cross_user_update = send_json(
+        &app,
+        request(
+            Method::PATCH,
+            &format!("/v1/assistant/sessions/{session_old}"),
+            Some(auth_other.as_str()),
+            Some(json!({ "pinned": true })),
+        ),
+    )
+    .await;
+    assert_eq!(cross_user_update.status, StatusCode::NOT_FOUND);
+    assert_eq!(error_code(&cross_user_update.body), Some("not_found"));
+
+    let pinned = send_json(
+        &app,
+        request(
+            Method::PATCH,
+            &format!("/v1/assistant/sessions/{session_old}"),
+            Some(auth.as_str()),
+            Some(json!({ "title_envelope": title_envelope, "pinned": true })),
+        ),
+    )
+    .await;
+    assert_eq!(pinned.status, StatusCode::OK);
+    assert_eq!(pinned.body["pinned"], json!(true));
+    assert_eq!(pinned.body["title_envelope"], title_envelope);
+
+    let pinned_first = send_json(
+        &app,
+        request(
+            Method::GET,
+            "/v1/assistant/sessions?limit=1",
+            Some(auth.as_str()),
+            None,
+        ),
+    )
+    .await;
+    assert_eq!(pinned_first.status, StatusCode::OK);
+    let pinned_first: ListAssistantSessionsResponse =
+        serde_json::from_value(pinned_first.body).expect("list response should decode");
+    assert_eq!(pinned_first.items.len(), 1);
+    assert_eq!(pinned_first.items[0].session_id, session_old);
+    assert!(pinned_first.items[0].pinned);
+    assert_eq!(
+        pinned_first.items[0]
+            .title_envelope
+            .as_ref()
+            .map(|envelope| envelope.key_id.as_str()),
+        Some("device-title-key-1")
+    );
+    let cursor = pinned_first
+        .next_cursor
+        .expect("a second page should exist");
+
+    let second_page = send_json(
+        &app,
+        request(
+            Method::GET,
+            &format!("/v1/assistant/sessions?limit=1&cursor={cursor}"),
+            Some(auth.as_str()),
+            None,
+        ),
+    )
+    .await;
+    assert_eq!(second_page.status, StatusCode::OK);
+    let second_page: ListAssistantSessionsResponse =
+        serde_json::from_value(second_page.body).expect("list response should decode");
+    assert_eq!(second_page.items.len(), 1);
+    assert_eq!(second_page.items[0].session_id, session_new);
+    assert!(!second_page.items[0].pinned);
+    assert!(second_page.items[0].title_envelope.is_none());
+    assert!(second_page.next_cursor.is_none());
+
+    let cleared = send_json(
+        &app,
+        request(
+            Method::PATCH,
+            &format!("/v1/assistant/sessions/{session_old}"),
+            Some(auth.as_str()),
+            Some(json!({ "clear_title": true, "pinned": false })),
+        ),
+    )
+    .await;
+    assert_eq!(cleared.status, StatusCode::OK);
+    assert_eq!(cleared.body["pinned"], json!(false));
+    assert_eq!(cleared.body["title_envelope"], Value::Null);
+
+    let recency_order = send_json(
+        &app,
+        request(
+            Method::GET,
+            "/v1/assistant/sessions",
+            Some(auth.as_str()),
+            None,
+        ),
+    )
+    .await;
+    let recency_order: ListAssistantSessionsResponse =
+        serde_json::from_value(recency_order.body).expect("list response should decode");
+    let ids = recency_order
+        .items
+        .iter()
+        .map(|session| session.session_id)
+        .collect::<Vec<_>>();
+    assert_eq!(ids, vec![session_new, session_old]);
+}
+
+#[tokio::test]
+#[serial]
+async fn assistant_session_export_forwards_sealed_state_and_returns_client_envelope() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let subject = "assistant-sessions-export-user";
+    let user_id = user_id_for_subject(&clerk.issuer, subject);
+    let other_user = user_id_for_subject(&clerk.issuer, "assistant-sessions-export-other");
+    let auth = format!("Bearer {}", clerk.token_for_subject(subject));
+
+    let now = Utc::now();
+    let readable_session = Uuid::new_v4();
+    let stale_session = Uuid::new_v4();
+    for (owner, session_id, ciphertext, updated_at) in [
+        (
+            user_id,
+            stale_session,
+            "cipher-stale",
+            now - Duration::minutes(30),
+        ),
+        (
+            user_id,
+            readable_session,
+            "cipher-readable",
+            now - Duration::minutes(10),
+        ),
+        (
+            other_user,
+            Uuid::new_v4(),
+            "cipher-other",
+            now - Duration::minutes(5),
+        ),
+    ] {
+        store
+            .upsert_assistant_encrypted_session(
+                owner,
+                session_id,
+                &test_state(ciphertext, now + Duration::days(3)),
+                updated_at,
+                3600,
+            )
+            .await
+            .expect("session insert should succeed");
+    }
+
+    let mock_enclave = MockEnclaveServer::start(axum::Router::new().route(
+        ENCLAVE_RPC_PATH_EXPORT_ASSISTANT_SESSIONS,
+        post(
+            move |JsonBody(request): JsonBody<EnclaveRpcExportAssistantSessionsRequest>| async move {
+                assert_eq!(request.user_id, user_id);
+                assert_eq!(request.export_request_id, "export-req-1");
+                let ciphertexts = request
+                    .sessions
+                    .iter()
+                    .map(|session| session.state.ciphertext.as_str())
+                    .collect::<Vec<_>>();
+                assert_eq!(ciphertexts, vec!["cipher-readable", "cipher-stale"]);
+
+                axum::Json(EnclaveRpcExportAssistantSessionsResponse {
+                    contract_version: ENCLAVE_RPC_CONTRACT_VERSION.to_string(),
+                    request_id: request.request_id,
+                    envelope: AssistantEncryptedResponseEnvelope {
+                        version: "v1".to_string(),
+                        algorithm: "x25519-chacha20poly1305".to_string(),
+                        key_id: "assistant-ingress-v1".to_string(),
+                        request_id: request.export_request_id,
+                        nonce: "export-nonce".to_string(),
+                        ciphertext: "export-ciphertext".to_string(),
+                    },
+                    exported_session_ids: vec![readable_session],
+                    skipped_session_ids: vec![stale_session],
+                    attested_identity: AttestedIdentityPayload {
+                        runtime: "nitro".to_string(),
+                        measurement: "test-measurement".to_string(),
+                    },
+                })
+            },
+        ),
+    ))
+    .await;
+    let app =
+        build_test_router_with_enclave_base_url(store.clone(), &clerk, &mock_enclave.base_url)
+            .await;
+
+    let invalid_key = send_json(
+        &app,
+        request(
+            Method::POST,
+            "/v1/assistant/sessions/export",
+            Some(auth.as_str()),
+            Some(json!({
+                "request_id": "export-req-1",
+                "client_ephemeral_public_key": "not-a-key",
+            })),
+        ),
+    )
+    .await;
+    assert_eq!(invalid_key.status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        error_code(&invalid_key.body),
+        Some("invalid_client_public_key")
+    );
+
+    let exported = send_json(
+        &app,
+        request(
+            Method::POST,
+            "/v1/assistant/sessions/export",
+            Some(auth.as_str()),
+            Some(json!({
+                "request_id": "export-req-1",
+                "client_ephemeral_public_key": STANDARD.encode([7_u8; 32]),
+            })),
+        ),
+    )
+    .await;
+    assert_eq!(exported.status, StatusCode::OK);
+    let exported: AssistantSessionExportResponse =
+        serde_json::from_value(exported.body).expect("export response should decode");
+    assert_eq!(exported.request_id, "export-req-1");
+    assert_eq!(exported.sessions.len(), 1);
+    assert_eq!(exported.sessions[0].session_id, readable_session);
+    assert_eq!(exported.skipped_session_ids, vec![stale_session]);
+    assert_eq!(exported.envelope.request_id, "export-req-1");
+    assert_eq!(exported.envelope.ciphertext, "export-ciphertext");
+
+    let audit_metadata: Value = sqlx::query_scalar(
+        "SELECT redacted_metadata FROM audit_events
+         WHERE user_id = $1 AND event_type = 'ASSISTANT_SESSIONS_EXPORTED'",
+    )
+    .bind(user_id)
+    .fetch_one(store.pool())
+    .await
+    .expect("export audit event should exist");
+    assert_eq!(audit_metadata["exported_sessions"], "1");
+    assert_eq!(audit_metadata["skipped_sessions"], "1");
+}
+
 fn test_state(
     ciphertext: &str,
     expires_at: chrono::DateTime<Utc>,
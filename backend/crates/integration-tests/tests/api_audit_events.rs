@@ -0,0 +1,458 @@
+mod support;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::body::{Body, to_bytes};
+use axum::http::{Method, Request, StatusCode, header};
+use base64::Engine as _;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use ed25519_dalek::{Signer, SigningKey};
+use serde_json::{Value, json};
+use serial_test::serial;
+use shared::audit_signing::{
+    AuditEventSignError, AuditEventSignFuture, AuditEventSigner, AuditEventSigningPayload,
+};
+use shared::models::AuditMetadata;
+use shared::repos::AuditResult;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use support::api_app::{
+    TEST_ADMIN_API_TOKEN, build_test_router, build_test_router_with_attestation_public_key,
+    user_id_for_subject,
+};
+use support::clerk::TestClerkAuth;
+
+#[tokio::test]
+#[serial]
+async fn audit_event_cursors_are_opaque_and_reject_tampering() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let auth = format!("Bearer {}", clerk.token_for_subject("audit-owner"));
+    let user_id = user_id_for_subject(&clerk.issuer, "audit-owner");
+    for _ in 0..51 {
+        store
+            .add_audit_event(
+                user_id,
+                "TEST_PAGINATION",
+                None,
+                AuditResult::Success,
+                &HashMap::new(),
+            )
+            .await
+            .expect("audit event insert should succeed");
+    }
+    let app = build_test_router(store, &clerk).await;
+
+    let first_page = send_json(&app, request("/v1/audit-events", &auth)).await;
+    assert_eq!(first_page.status, StatusCode::OK);
+    assert_eq!(
+        first_page
+            .body
+            .get("items")
+            .and_then(Value::as_array)
+            .map(Vec::len),
+        Some(50)
+    );
+    let cursor = first_page
+        .body
+        .get("next_cursor")
+        .and_then(Value::as_str)
+        .expect("full page should return a cursor")
+        .to_string();
+    assert!(!cursor.contains('|'), "cursor should not expose page keys");
+
+    let second_page = send_json(
+        &app,
+        request(&format!("/v1/audit-events?cursor={cursor}"), &auth),
+    )
+    .await;
+    assert_eq!(second_page.status, StatusCode::OK);
+    assert_eq!(
+        second_page
+            .body
+            .get("items")
+            .and_then(Value::as_array)
+            .map(Vec::len),
+        Some(1)
+    );
+    assert_eq!(second_page.body.get("next_cursor"), Some(&Value::Null));
+
+    let (_, signature) = cursor.split_once('.').expect("cursor should be signed");
+    let forged_payload = URL_SAFE_NO_PAD.encode(
+        json!({
+            "v": 1,
+            "r": "audit_events",
+            "k": {
+                "created_at": "2100-01-01T00:00:00Z",
+                "id": "00000000-0000-0000-0000-000000000000"
+            }
+        })
+        .to_string(),
+    );
+    for tampered in [
+        format!("{forged_payload}.{signature}"),
+        "4102444800000000|00000000-0000-0000-0000-000000000000".to_string(),
+    ] {
+        let response = send_json(
+            &app,
+            request(&format!("/v1/audit-events?cursor={tampered}"), &auth),
+        )
+        .await;
+        assert_eq!(response.status, StatusCode::BAD_REQUEST);
+        assert_eq!(error_code(&response.body), Some("invalid_cursor"));
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn audit_event_export_streams_full_history_as_ndjson_or_csv() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let auth = format!("Bearer {}", clerk.token_for_subject("audit-exporter"));
+    let user_id = user_id_for_subject(&clerk.issuer, "audit-exporter");
+    let mut metadata = AuditMetadata::new();
+    metadata.insert("note".to_string(), json!("has, comma"));
+    for _ in 0..3 {
+        store
+            .add_audit_event(
+                user_id,
+                "TEST_EXPORT",
+                Some("google"),
+                AuditResult::Success,
+                &metadata,
+            )
+            .await
+            .expect("audit event insert should succeed");
+    }
+    let app = build_test_router(store, &clerk).await;
+
+    let response = app
+        .clone()
+        .oneshot(request("/v1/audit-events/export", &auth))
+        .await
+        .expect("request should succeed");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers().get(header::CONTENT_TYPE),
+        Some(&header::HeaderValue::from_static("application/x-ndjson"))
+    );
+    let body = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("response body should read");
+    let events = String::from_utf8(body.to_vec())
+        .expect("export should be utf-8")
+        .lines()
+        .map(|line| serde_json::from_str::<Value>(line).expect("each line should be json"))
+        .collect::<Vec<_>>();
+    assert_eq!(events.len(), 4, "export includes its own audit event");
+    assert_eq!(
+        events[0].get("event_type").and_then(Value::as_str),
+        Some("AUDIT_EXPORT_REQUESTED")
+    );
+
+    let response = app
+        .clone()
+        .oneshot(request("/v1/audit-events/export?format=csv", &auth))
+        .await
+        .expect("request should succeed");
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("response body should read");
+    let csv = String::from_utf8(body.to_vec()).expect("export should be utf-8");
+    let lines = csv.lines().collect::<Vec<_>>();
+    assert_eq!(
+        lines[0],
+        "id,timestamp,event_type,connector,result,metadata"
+    );
+    assert_eq!(lines.len(), 6);
+    assert!(lines[3].contains(r#","{""note"":""has, comma""}""#));
+
+    let invalid = send_json(&app, request("/v1/audit-events/export?format=xml", &auth)).await;
+    assert_eq!(invalid.status, StatusCode::BAD_REQUEST);
+    assert_eq!(error_code(&invalid.body), Some("invalid_format"));
+}
+
+#[tokio::test]
+#[serial]
+async fn audit_metadata_keeps_json_types_and_reads_legacy_rows() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let auth = format!("Bearer {}", clerk.token_for_subject("audit-typed"));
+    let user_id = user_id_for_subject(&clerk.issuer, "audit-typed");
+    let mut metadata = AuditMetadata::new();
+    metadata.insert("attempts".to_string(), json!(2));
+    metadata.insert("delivered".to_string(), json!(true));
+    metadata.insert("scopes".to_string(), json!(["calendar"]));
+    store
+        .add_audit_event(user_id, "TEST_TYPED", None, AuditResult::Success, &metadata)
+        .await
+        .expect("audit event insert should succeed");
+    sqlx::query(
+        "INSERT INTO audit_events (user_id, event_type, result, redacted_metadata, created_at)
+         VALUES ($1, 'TEST_LEGACY', 'SUCCESS', '{\"attempts\":\"2\"}'::jsonb, NOW() - INTERVAL '1 day')",
+    )
+    .bind(user_id)
+    .execute(store.pool())
+    .await
+    .expect("legacy row insert should succeed");
+    let legacy_column: Value = sqlx::query_scalar(
+        "SELECT redacted_metadata FROM audit_events WHERE event_type = 'TEST_TYPED'",
+    )
+    .fetch_one(store.pool())
+    .await
+    .expect("legacy column should read");
+    assert_eq!(
+        legacy_column,
+        json!({"attempts": "2", "delivered": "true", "scopes": "[\"calendar\"]"}),
+        "string-map column keeps serving older readers"
+    );
+    let app = build_test_router(store, &clerk).await;
+
+    let page = send_json(&app, request("/v1/audit-events", &auth)).await;
+    assert_eq!(page.status, StatusCode::OK);
+    let items = page
+        .body
+        .get("items")
+        .and_then(Value::as_array)
+        .expect("items should be an array");
+    assert_eq!(
+        items[0].get("metadata"),
+        Some(&json!({"attempts": 2, "delivered": true, "scopes": ["calendar"]}))
+    );
+    assert_eq!(items[1].get("metadata"), Some(&json!({"attempts": "2"})));
+}
+
+#[tokio::test]
+#[serial]
+async fn audit_chain_verification_detects_tampered_history() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let user_id = user_id_for_subject(&clerk.issuer, "audit-chain-owner");
+    let mut metadata = AuditMetadata::new();
+    metadata.insert("reason".to_string(), json!("user_requested"));
+    metadata.insert("attempt".to_string(), json!(1));
+    for _ in 0..3 {
+        store
+            .add_audit_event(
+                user_id,
+                "TEST_CHAIN",
+                Some("google"),
+                AuditResult::Success,
+                &metadata,
+            )
+            .await
+            .expect("audit event insert should succeed");
+    }
+    let pool = store.pool().clone();
+    let app = build_test_router(store, &clerk).await;
+    let admin_auth = format!("Bearer {TEST_ADMIN_API_TOKEN}");
+    let uri = format!("/admin/v1/users/{user_id}/audit-chain");
+
+    let verified = send_json(&app, request(&uri, &admin_auth)).await;
+    assert_eq!(verified.status, StatusCode::OK);
+    assert_eq!(verified.body.get("valid"), Some(&Value::Bool(true)));
+    assert_eq!(verified.body.get("verified_events"), Some(&json!(3)));
+    assert_eq!(verified.body.get("head_seq"), Some(&json!(3)));
+
+    let user_auth = format!("Bearer {}", clerk.token_for_subject("audit-chain-owner"));
+    let rejected = send_json(&app, request(&uri, &user_auth)).await;
+    assert_eq!(rejected.status, StatusCode::UNAUTHORIZED);
+
+    sqlx::query(
+        "UPDATE audit_events
+         SET redacted_metadata = '{\"reason\":\"edited\"}'::jsonb
+         WHERE user_id = $1 AND chain_seq = 2",
+    )
+    .bind(user_id)
+    .execute(&pool)
+    .await
+    .expect("tamper update should succeed");
+
+    let tampered = send_json(&app, request(&uri, &admin_auth)).await;
+    assert_eq!(tampered.status, StatusCode::OK);
+    assert_eq!(tampered.body.get("valid"), Some(&Value::Bool(false)));
+    assert_eq!(
+        tampered.body.pointer("/first_break/chain_seq"),
+        Some(&json!(2))
+    );
+    assert_eq!(
+        tampered.body.pointer("/first_break/reason"),
+        Some(&json!("hash_mismatch"))
+    );
+
+    sqlx::query("DELETE FROM audit_events WHERE user_id = $1 AND chain_seq >= 2")
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .expect("tail delete should succeed");
+
+    let truncated = send_json(&app, request(&uri, &admin_auth)).await;
+    assert_eq!(truncated.body.get("valid"), Some(&Value::Bool(false)));
+    assert_eq!(
+        truncated.body.pointer("/first_break/reason"),
+        Some(&json!("head_mismatch"))
+    );
+}
+
+/// Stands in for the enclave runtime: signs with a fixed attestation key, or fails.
+struct TestAuditSigner {
+    signing_key: SigningKey,
+    fail: bool,
+}
+
+impl AuditEventSigner for TestAuditSigner {
+    fn sign_audit_event<'a>(
+        &'a self,
+        payload: &'a AuditEventSigningPayload,
+    ) -> AuditEventSignFuture<'a> {
+        Box::pin(async move {
+            if self.fail {
+                return Err(AuditEventSignError("enclave unavailable".to_string()));
+            }
+            let signature = self.signing_key.sign(payload.canonical().as_bytes());
+            Ok(base64::engine::general_purpose::STANDARD.encode(signature.to_bytes()))
+        })
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn signed_audit_events_verify_against_the_enclave_key() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let auth = format!("Bearer {}", clerk.token_for_subject("audit-signed"));
+    let user_id = user_id_for_subject(&clerk.issuer, "audit-signed");
+    let signing_key = SigningKey::from_bytes(&[5_u8; 32]);
+    let public_key =
+        base64::engine::general_purpose::STANDARD.encode(signing_key.verifying_key().to_bytes());
+    let signing_store = store
+        .clone()
+        .with_audit_event_signer(Arc::new(TestAuditSigner {
+            signing_key,
+            fail: false,
+        }));
+    let failing_store = store
+        .clone()
+        .with_audit_event_signer(Arc::new(TestAuditSigner {
+            signing_key: SigningKey::from_bytes(&[6_u8; 32]),
+            fail: true,
+        }));
+
+    let mut metadata = AuditMetadata::new();
+    metadata.insert("connector_id".to_string(), json!("c-1"));
+    for (store, event_type) in [
+        (&signing_store, "CONNECTOR_REVOKED"),
+        (&signing_store, "DEVICE_REGISTERED"),
+        (&failing_store, "PRIVACY_DELETE_ALL_REQUESTED"),
+    ] {
+        store
+            .add_audit_event(
+                user_id,
+                event_type,
+                Some("google"),
+                AuditResult::Success,
+                &metadata,
+            )
+            .await
+            .expect("audit writes succeed whether or not signing does");
+    }
+    let event_ids: HashMap<String, Uuid> =
+        sqlx::query_as("SELECT event_type, id FROM audit_events WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_all(store.pool())
+            .await
+            .expect("audit events should read")
+            .into_iter()
+            .collect();
+    let pool = store.pool().clone();
+    let app = build_test_router_with_attestation_public_key(store, &clerk, &public_key).await;
+    let verification_uri =
+        |event_type: &str| format!("/v1/audit-events/{}/verification", event_ids[event_type]);
+
+    let signed = send_json(&app, request(&verification_uri("CONNECTOR_REVOKED"), &auth)).await;
+    assert_eq!(signed.status, StatusCode::OK);
+    assert_eq!(signed.body.get("status"), Some(&json!("verified")));
+    let signed_payload = signed
+        .body
+        .get("signed_payload")
+        .and_then(Value::as_str)
+        .expect("signed events expose the signed payload");
+    assert!(signed_payload.contains(&event_ids["CONNECTOR_REVOKED"].to_string()));
+
+    for event_type in ["DEVICE_REGISTERED", "PRIVACY_DELETE_ALL_REQUESTED"] {
+        let unsigned = send_json(&app, request(&verification_uri(event_type), &auth)).await;
+        assert_eq!(unsigned.status, StatusCode::OK);
+        assert_eq!(unsigned.body.get("status"), Some(&json!("unsigned")));
+        assert_eq!(unsigned.body.get("signature"), Some(&Value::Null));
+    }
+
+    sqlx::query(
+        "UPDATE audit_events
+         SET redacted_metadata = '{\"connector_id\":\"c-2\"}'::jsonb,
+             typed_metadata = '{\"connector_id\":\"c-2\"}'::jsonb
+         WHERE id = $1",
+    )
+    .bind(event_ids["CONNECTOR_REVOKED"])
+    .execute(&pool)
+    .await
+    .expect("tamper update should succeed");
+    let tampered = send_json(&app, request(&verification_uri("CONNECTOR_REVOKED"), &auth)).await;
+    assert_eq!(tampered.body.get("status"), Some(&json!("hash_mismatch")));
+
+    let other_auth = format!("Bearer {}", clerk.token_for_subject("audit-signed-other"));
+    let foreign = send_json(
+        &app,
+        request(&verification_uri("CONNECTOR_REVOKED"), &other_auth),
+    )
+    .await;
+    assert_eq!(foreign.status, StatusCode::NOT_FOUND);
+    assert_eq!(error_code(&foreign.body), Some("not_found"));
+}
+
+struct JsonResponse {
+    status: StatusCode,
+    body: Value,
+}
+
+async fn send_json(app: &axum::Router, request: Request<Body>) -> JsonResponse {
+    let response = app
+        .clone()
+        .oneshot(request)
+        .await
+        .expect("request should succeed");
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("response body should read");
+    let body = serde_json::from_slice::<Value>(&body).unwrap_or_else(|_| json!({}));
+
+    JsonResponse { status, body }
+}
+
+fn request(uri: &str, auth_header: &str) -> Request<Body> {
+    Request::builder()
+        .method(Method::GET)
+        .uri(uri)
+        .header(header::AUTHORIZATION, auth_header)
+        .body(Body::empty())
+        .expect("request should build")
+}
+
+fn error_code(body: &Value) -> Option<&str> {
+    body.get("error")
+        .and_then(|error| error.get("code"))
+        .and_then(Value::as_str)
+}
@@ -6,7 +6,9 @@ use chrono::{Duration, Utc};
 use serde_json::{Value, json};
 use serial_test::serial;
 use sha2::{Digest, Sha256};
+use shared::repos::{ConnectorAccount, LEGACY_CONNECTOR_ACCOUNT_KEY};
 use tower::ServiceExt;
+use uuid::Uuid;
 
 use support::api_app::{build_test_router, user_id_for_subject};
 use support::clerk::TestClerkAuth;
@@ -187,7 +189,11 @@ async fn user_cannot_read_or_mutate_other_users_privacy_or_connector_resources()
 
     let connector_id = store
         .upsert_google_connector(
-            user_a_id,
+            ConnectorAccount {
+                user_id: user_a_id,
+                connector_id: Uuid::new_v4(),
+                account_key: LEGACY_CONNECTOR_ACCOUNT_KEY,
+            },
             "refresh-token-a",
             &["https://www.googleapis.com/auth/calendar.readonly".to_string()],
             "kms/local/alfred-refresh-token",
@@ -234,6 +240,28 @@ async fn user_cannot_read_or_mutate_other_users_privacy_or_connector_resources()
         user_a_items[0].get("status").and_then(Value::as_str),
         Some("ACTIVE")
     );
+    assert_eq!(
+        user_a_items[0].get("scopes"),
+        Some(&json!([
+            "https://www.googleapis.com/auth/calendar.readonly"
+        ]))
+    );
+    assert!(
+        user_a_items[0]
+            .get("token_rotated_at")
+            .and_then(Value::as_str)
+            .is_some()
+    );
+    assert!(
+        user_a_items[0]
+            .get("created_at")
+            .and_then(Value::as_str)
+            .is_some()
+    );
+    assert!(
+        user_a_items[0].get("refresh_token_ciphertext").is_none(),
+        "listing must stay metadata-only"
+    );
 
     let revoke_other_user_connector = send_json(
         &app,
@@ -0,0 +1,114 @@
+mod support;
+
+use axum::http::{Method, StatusCode};
+use serde_json::{Value, json};
+use serial_test::serial;
+
+use support::api_app::build_test_router;
+use support::assistant_encrypted::{request, send_json};
+use support::automations::{error_code, prompt_envelope, schedule_payload};
+use support::clerk::TestClerkAuth;
+
+#[tokio::test]
+#[serial]
+async fn automation_conditions_round_trip_and_can_be_cleared() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let auth = format!("Bearer {}", clerk.token_for_subject("automation-condition"));
+    let app = build_test_router(store, &clerk).await;
+
+    let create = |request_id: &str, condition: Value| {
+        request(
+            Method::POST,
+            "/v1/automations",
+            Some(&auth),
+            Some(json!({
+                "title": "Meeting digest",
+                "schedule": schedule_payload("DAILY", "UTC", "07:00"),
+                "prompt_envelope": prompt_envelope(request_id),
+                "condition": condition
+            })),
+        )
+    };
+
+    for (request_id, condition) in [
+        (
+            "condition-zero",
+            json!({"kind": "CALENDAR_EVENTS_TODAY", "min_count": 0}),
+        ),
+        (
+            "condition-too-many",
+            json!({"kind": "URGENT_EMAILS", "min_count": 11}),
+        ),
+    ] {
+        let rejected = send_json(&app, create(request_id, condition.clone())).await;
+        assert_eq!(rejected.status, StatusCode::BAD_REQUEST, "{condition}");
+        assert_eq!(
+            error_code(&rejected.body),
+            Some("invalid_automation_condition")
+        );
+    }
+
+    let created = send_json(
+        &app,
+        create(
+            "condition-create",
+            json!({"kind": "CALENDAR_EVENTS_TODAY", "min_count": 1}),
+        ),
+    )
+    .await;
+    assert_eq!(created.status, StatusCode::OK);
+    assert_eq!(
+        created.body["condition"],
+        json!({"kind": "CALENDAR_EVENTS_TODAY", "min_count": 1})
+    );
+    let rule_uri = format!(
+        "/v1/automations/{}",
+        created.body["rule_id"]
+            .as_str()
+            .expect("create response should include rule_id")
+    );
+    let update = |body: Value| request(Method::PATCH, &rule_uri, Some(&auth), Some(body));
+
+    let combined = send_json(
+        &app,
+        update(json!({
+            "condition": {"kind": "URGENT_EMAILS", "min_count": 1},
+            "clear_condition": true
+        })),
+    )
+    .await;
+    assert_eq!(combined.status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        error_code(&combined.body),
+        Some("invalid_automation_condition")
+    );
+
+    let changed = send_json(
+        &app,
+        update(json!({"condition": {"kind": "URGENT_EMAILS", "min_count": 3}})),
+    )
+    .await;
+    assert_eq!(changed.status, StatusCode::OK);
+    assert_eq!(
+        changed.body["condition"],
+        json!({"kind": "URGENT_EMAILS", "min_count": 3})
+    );
+    assert!(changed.body["version"].as_i64() > created.body["version"].as_i64());
+
+    let cleared = send_json(&app, update(json!({"clear_condition": true}))).await;
+    assert_eq!(cleared.status, StatusCode::OK);
+    assert!(cleared.body["condition"].is_null());
+
+    let archived = send_json(&app, update(json!({"status": "ARCHIVED"}))).await;
+    assert_eq!(archived.status, StatusCode::OK);
+    let archived_edit = send_json(
+        &app,
+        update(json!({"condition": {"kind": "URGENT_EMAILS", "min_count": 1}})),
+    )
+    .await;
+    assert_eq!(archived_edit.status, StatusCode::BAD_REQUEST);
+    assert_eq!(error_code(&archived_edit.body), Some("automation_archived"));
+}
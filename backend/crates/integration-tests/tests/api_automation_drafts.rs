@@ -0,0 +1,225 @@
+mod support;
+
+use axum::extract::Json as JsonBody;
+use axum::http::{Method, StatusCode};
+use axum::routing::post;
+use serde_json::json;
+use serial_test::serial;
+use shared::enclave::{
+    AttestedIdentityPayload, ENCLAVE_RPC_CONTRACT_VERSION, ENCLAVE_RPC_PATH_DRAFT_AUTOMATION,
+    EnclaveRpcDraftAutomationRequest, EnclaveRpcDraftAutomationResponse,
+};
+use shared::models::{AssistantEncryptedResponseEnvelope, DraftAutomationResponse};
+
+use support::api_app::{
+    build_test_router, build_test_router_with_enclave_base_url, user_id_for_subject,
+};
+use support::assistant_encrypted::{request, send_json};
+use support::automations::{error_code, prompt_envelope, schedule_payload};
+use support::clerk::TestClerkAuth;
+use support::enclave_mock::MockEnclaveServer;
+
+#[tokio::test]
+#[serial]
+async fn automation_draft_relays_the_encrypted_proposal_without_saving() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let subject = "automation-draft-user";
+    let user_id = user_id_for_subject(&clerk.issuer, subject);
+    let auth = format!("Bearer {}", clerk.token_for_subject(subject));
+
+    let mock_enclave = MockEnclaveServer::start(axum::Router::new().route(
+        ENCLAVE_RPC_PATH_DRAFT_AUTOMATION,
+        post(
+            move |JsonBody(request): JsonBody<EnclaveRpcDraftAutomationRequest>| async move {
+                assert_eq!(request.user_id, user_id);
+                assert_eq!(request.time_zone, "America/New_York");
+
+                axum::Json(EnclaveRpcDraftAutomationResponse {
+                    contract_version: ENCLAVE_RPC_CONTRACT_VERSION.to_string(),
+                    request_id: request.request_id,
+                    envelope: AssistantEncryptedResponseEnvelope {
+                        version: "v1".to_string(),
+                        algorithm: "x25519-chacha20poly1305".to_string(),
+                        key_id: "assistant-ingress-v1".to_string(),
+                        request_id: request.prompt_envelope.request_id,
+                        nonce: "draft-nonce".to_string(),
+                        ciphertext: "draft-ciphertext".to_string(),
+                    },
+                    attested_identity: AttestedIdentityPayload {
+                        runtime: "nitro".to_string(),
+                        measurement: "test-measurement".to_string(),
+                    },
+                })
+            },
+        ),
+    ))
+    .await;
+    let app =
+        build_test_router_with_enclave_base_url(store.clone(), &clerk, &mock_enclave.base_url)
+            .await;
+
+    let draft = |request_id: &str, time_zone: &str| {
+        request(
+            Method::POST,
+            "/v1/automations/draft",
+            Some(&auth),
+            Some(json!({
+                "prompt_envelope": prompt_envelope(request_id),
+                "time_zone": time_zone
+            })),
+        )
+    };
+
+    let invalid_time_zone = send_json(&app, draft("draft-invalid-tz", "Mars/Olympus")).await;
+    assert_eq!(invalid_time_zone.status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        error_code(&invalid_time_zone.body),
+        Some("invalid_time_zone")
+    );
+
+    let drafted = send_json(&app, draft("draft-request", "America/New_York")).await;
+    assert_eq!(drafted.status, StatusCode::OK);
+    let drafted: DraftAutomationResponse =
+        serde_json::from_value(drafted.body).expect("draft response should decode");
+    assert_eq!(drafted.request_id, "draft-request");
+    assert_eq!(drafted.envelope.request_id, "draft-request");
+    assert_eq!(drafted.envelope.ciphertext, "draft-ciphertext");
+
+    let replayed = send_json(&app, draft("draft-request", "America/New_York")).await;
+    assert_eq!(replayed.status, StatusCode::BAD_REQUEST);
+    assert_eq!(error_code(&replayed.body), Some("request_id_reused"));
+
+    let listed = send_json(
+        &app,
+        request(Method::GET, "/v1/automations", Some(&auth), None),
+    )
+    .await;
+    assert_eq!(listed.status, StatusCode::OK);
+    assert_eq!(listed.body["items"].as_array().map(Vec::len), Some(0));
+
+    // The confirmed proposal is saved through the regular create flow with a fresh envelope.
+    let created = send_json(
+        &app,
+        request(
+            Method::POST,
+            "/v1/automations",
+            Some(&auth),
+            Some(json!({
+                "title": "Weekday brief",
+                "schedule": {
+                    "schedule_type": "WEEKLY",
+                    "time_zone": "America/New_York",
+                    "local_time": "07:00",
+                    "days_of_week": [1, 2, 3, 4, 5]
+                },
+                "prompt_envelope": prompt_envelope("draft-confirmed")
+            })),
+        ),
+    )
+    .await;
+    assert_eq!(created.status, StatusCode::OK);
+}
+
+#[tokio::test]
+#[serial]
+async fn automation_weekly_review_template_requires_weekly_schedule() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let auth = format!(
+        "Bearer {}",
+        clerk.token_for_subject("automation-weekly-review")
+    );
+    let app = build_test_router(store, &clerk).await;
+
+    let rejected = send_json(
+        &app,
+        request(
+            Method::POST,
+            "/v1/automations",
+            Some(&auth),
+            Some(json!({
+                "title": "Weekly review",
+                "schedule": schedule_payload("DAILY", "UTC", "17:00"),
+                "prompt_envelope": prompt_envelope("weekly-review-daily"),
+                "template": "WEEKLY_REVIEW"
+            })),
+        ),
+    )
+    .await;
+    assert_eq!(rejected.status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        error_code(&rejected.body),
+        Some("invalid_template_schedule")
+    );
+
+    let created = send_json(
+        &app,
+        request(
+            Method::POST,
+            "/v1/automations",
+            Some(&auth),
+            Some(json!({
+                "title": "Weekly review",
+                "schedule": schedule_payload("WEEKLY", "UTC", "17:00"),
+                "prompt_envelope": prompt_envelope("weekly-review-weekly"),
+                "template": "WEEKLY_REVIEW"
+            })),
+        ),
+    )
+    .await;
+    assert_eq!(created.status, StatusCode::OK);
+    assert_eq!(created.body["template"], "WEEKLY_REVIEW");
+    let rule_id = created.body["rule_id"]
+        .as_str()
+        .expect("rule_id should be present")
+        .to_string();
+
+    let downgraded = send_json(
+        &app,
+        request(
+            Method::PATCH,
+            &format!("/v1/automations/{rule_id}"),
+            Some(&auth),
+            Some(json!({
+                "schedule": schedule_payload("DAILY", "UTC", "17:00")
+            })),
+        ),
+    )
+    .await;
+    assert_eq!(downgraded.status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        error_code(&downgraded.body),
+        Some("invalid_template_schedule")
+    );
+
+    let reports = send_json(
+        &app,
+        request(
+            Method::GET,
+            "/v1/automation-reports?device_id=ios-device-1",
+            Some(&auth),
+            None,
+        ),
+    )
+    .await;
+    assert_eq!(reports.status, StatusCode::OK);
+    assert_eq!(reports.body["items"], json!([]));
+
+    let invalid_limit = send_json(
+        &app,
+        request(
+            Method::GET,
+            "/v1/automation-reports?device_id=ios-device-1&limit=0",
+            Some(&auth),
+            None,
+        ),
+    )
+    .await;
+    assert_eq!(invalid_limit.status, StatusCode::BAD_REQUEST);
+    assert_eq!(error_code(&invalid_limit.body), Some("invalid_limit"));
+}
@@ -0,0 +1,203 @@
+mod support;
+
+use axum::http::{Method, StatusCode};
+use serde_json::{Value, json};
+use serial_test::serial;
+
+use support::api_app::{build_test_router, user_id_for_subject};
+use support::assistant_encrypted::{request, send_json};
+use support::automations::{error_code, prompt_envelope, schedule_payload};
+use support::clerk::TestClerkAuth;
+
+#[tokio::test]
+#[serial]
+async fn automation_pause_resume_loop_escalates_to_temporary_block() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let auth = format!("Bearer {}", clerk.token_for_subject("automation-toggler"));
+    let other_auth = format!("Bearer {}", clerk.token_for_subject("automation-bystander"));
+    let app = build_test_router(store.clone(), &clerk).await;
+
+    let create = send_json(
+        &app,
+        request(
+            Method::POST,
+            "/v1/automations",
+            Some(&auth),
+            Some(json!({
+                "title": "Toggled task",
+                "schedule": schedule_payload("DAILY", "UTC", "09:00"),
+                "prompt_envelope": prompt_envelope("toggle-create")
+            })),
+        ),
+    )
+    .await;
+    assert_eq!(create.status, StatusCode::OK);
+    let rule_id = create
+        .body
+        .get("rule_id")
+        .and_then(Value::as_str)
+        .expect("create response should include rule_id")
+        .to_string();
+
+    for status in ["PAUSED", "ACTIVE", "PAUSED", "ACTIVE", "PAUSED", "ACTIVE"] {
+        let toggle = send_json(
+            &app,
+            request(
+                Method::PATCH,
+                &format!("/v1/automations/{rule_id}"),
+                Some(&auth),
+                Some(json!({"status": status})),
+            ),
+        )
+        .await;
+        assert_eq!(toggle.status, StatusCode::OK);
+    }
+
+    let blocked = send_json(
+        &app,
+        request(
+            Method::PATCH,
+            &format!("/v1/automations/{rule_id}"),
+            Some(&auth),
+            Some(json!({"title": "Still toggling"})),
+        ),
+    )
+    .await;
+    assert_eq!(blocked.status, StatusCode::TOO_MANY_REQUESTS);
+
+    let listed = send_json(
+        &app,
+        request(Method::GET, "/v1/automations", Some(&auth), None),
+    )
+    .await;
+    assert_eq!(listed.status, StatusCode::OK);
+
+    let other_user = send_json(
+        &app,
+        request(
+            Method::POST,
+            "/v1/automations",
+            Some(&other_auth),
+            Some(json!({
+                "title": "Unaffected task",
+                "schedule": schedule_payload("DAILY", "UTC", "09:00"),
+                "prompt_envelope": prompt_envelope("bystander-create")
+            })),
+        ),
+    )
+    .await;
+    assert_eq!(other_user.status, StatusCode::OK);
+
+    let escalations: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM audit_events WHERE event_type = 'AUTOMATION_ABUSE_ESCALATED'",
+    )
+    .fetch_one(store.pool())
+    .await
+    .expect("audit count should load");
+    assert_eq!(escalations, 1);
+}
+
+#[tokio::test]
+#[serial]
+async fn automation_run_now_replays_request_ids_and_enforces_daily_cap() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let subject = "automation-run-now-owner";
+    let user_id = user_id_for_subject(&clerk.issuer, subject);
+    let auth = format!("Bearer {}", clerk.token_for_subject(subject));
+    let app = build_test_router(store.clone(), &clerk).await;
+
+    let create = send_json(
+        &app,
+        request(
+            Method::POST,
+            "/v1/automations",
+            Some(&auth),
+            Some(json!({
+                "title": "Inbox triage",
+                "schedule": schedule_payload("DAILY", "UTC", "09:00"),
+                "prompt_envelope": prompt_envelope("run-now")
+            })),
+        ),
+    )
+    .await;
+    assert_eq!(create.status, StatusCode::OK);
+    let rule_id = create
+        .body
+        .get("rule_id")
+        .and_then(Value::as_str)
+        .expect("create response should include rule_id")
+        .to_string();
+    let run_uri = format!("/v1/automations/{rule_id}/run");
+
+    let first = send_json(
+        &app,
+        request(
+            Method::POST,
+            &run_uri,
+            Some(&auth),
+            Some(json!({"request_id": "tap-1"})),
+        ),
+    )
+    .await;
+    assert_eq!(first.status, StatusCode::OK);
+    assert_eq!(first.body["status"], json!("QUEUED"));
+    assert_eq!(first.body["replayed"], json!(false));
+    assert_eq!(first.body["runs_remaining_today"], json!(19));
+
+    let retry = send_json(
+        &app,
+        request(
+            Method::POST,
+            &run_uri,
+            Some(&auth),
+            Some(json!({"request_id": "tap-1"})),
+        ),
+    )
+    .await;
+    assert_eq!(retry.status, StatusCode::OK);
+    assert_eq!(retry.body["replayed"], json!(true));
+    assert_eq!(retry.body["queued_job_id"], first.body["queued_job_id"]);
+    assert_eq!(retry.body["runs_remaining_today"], json!(19));
+
+    let queued_jobs: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*)::bigint FROM jobs WHERE user_id = $1 AND type = 'AUTOMATION_RUN'",
+    )
+    .bind(user_id)
+    .fetch_one(store.pool())
+    .await
+    .expect("job count should load");
+    assert_eq!(queued_jobs, 1);
+
+    sqlx::query(
+        "INSERT INTO automation_manual_runs (user_id, rule_id, request_id, job_id)
+         SELECT $1, $2::uuid, 'seeded-' || seq, gen_random_uuid()
+         FROM generate_series(1, 19) AS seq",
+    )
+    .bind(user_id)
+    .bind(&rule_id)
+    .execute(store.pool())
+    .await
+    .expect("manual runs should seed");
+
+    let capped = send_json(
+        &app,
+        request(
+            Method::POST,
+            &run_uri,
+            Some(&auth),
+            Some(json!({"request_id": "tap-2"})),
+        ),
+    )
+    .await;
+    assert_eq!(capped.status, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(
+        error_code(&capped.body),
+        Some("automation_run_limit_reached")
+    );
+}
@@ -0,0 +1,500 @@
+mod support;
+
+use axum::http::{Method, StatusCode};
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, Utc, Weekday};
+use serde_json::{Value, json};
+use serial_test::serial;
+
+use support::api_app::build_test_router;
+use support::assistant_encrypted::{request, send_json};
+use support::automations::{error_code, prompt_envelope, schedule_payload};
+use support::clerk::TestClerkAuth;
+
+#[tokio::test]
+#[serial]
+async fn automation_cron_schedules_validate_and_round_trip() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let auth = format!("Bearer {}", clerk.token_for_subject("automation-cron"));
+    let app = build_test_router(store, &clerk).await;
+
+    let create = |schedule: Value, request_id: &str| {
+        request(
+            Method::POST,
+            "/v1/automations",
+            Some(&auth),
+            Some(json!({
+                "title": "Weekday standup",
+                "schedule": schedule,
+                "prompt_envelope": prompt_envelope(request_id)
+            })),
+        )
+    };
+
+    let invalid = send_json(
+        &app,
+        create(
+            json!({"schedule_type": "CRON", "time_zone": "UTC", "cron_expression": "30 7 * *"}),
+            "cron-invalid",
+        ),
+    )
+    .await;
+    assert_eq!(invalid.status, StatusCode::BAD_REQUEST);
+    assert_eq!(error_code(&invalid.body), Some("invalid_cron_expression"));
+
+    let with_local_time = send_json(
+        &app,
+        create(
+            json!({
+                "schedule_type": "CRON",
+                "time_zone": "UTC",
+                "local_time": "07:30",
+                "cron_expression": "30 7 * * 1-5"
+            }),
+            "cron-local-time",
+        ),
+    )
+    .await;
+    assert_eq!(with_local_time.status, StatusCode::BAD_REQUEST);
+    assert_eq!(error_code(&with_local_time.body), Some("invalid_schedule"));
+
+    let created = send_json(
+        &app,
+        create(
+            json!({
+                "schedule_type": "CRON",
+                "time_zone": "America/New_York",
+                "cron_expression": "30 7 * * MON-FRI"
+            }),
+            "cron-create",
+        ),
+    )
+    .await;
+    assert_eq!(created.status, StatusCode::OK);
+    assert_eq!(created.body["schedule"]["schedule_type"], "CRON");
+    assert_eq!(
+        created.body["schedule"]["cron_expression"],
+        "30 7 * * MON-FRI"
+    );
+    assert!(created.body["schedule"].get("local_time").is_none());
+    // 07:30 in New York is 11:30 or 12:30 UTC on the same weekday.
+    let next_run_at = created.body["next_run_at"]
+        .as_str()
+        .and_then(|value| DateTime::parse_from_rfc3339(value).ok())
+        .expect("next_run_at should be RFC 3339")
+        .with_timezone(&Utc);
+    assert!(matches!(
+        next_run_at.format("%H:%M").to_string().as_str(),
+        "11:30" | "12:30"
+    ));
+    assert!(!matches!(
+        next_run_at.weekday(),
+        Weekday::Sat | Weekday::Sun
+    ));
+
+    let rule_id = created.body["rule_id"]
+        .as_str()
+        .expect("create response should include rule_id")
+        .to_string();
+    let every_two_hours = send_json(
+        &app,
+        request(
+            Method::PATCH,
+            &format!("/v1/automations/{rule_id}"),
+            Some(&auth),
+            Some(json!({"schedule": {
+                "schedule_type": "CRON",
+                "time_zone": "UTC",
+                "cron_expression": "0 */2 * * *"
+            }})),
+        ),
+    )
+    .await;
+    assert_eq!(every_two_hours.status, StatusCode::OK);
+    assert_eq!(
+        every_two_hours.body["schedule"]["cron_expression"],
+        "0 */2 * * *"
+    );
+
+    let back_to_daily = send_json(
+        &app,
+        request(
+            Method::PATCH,
+            &format!("/v1/automations/{rule_id}"),
+            Some(&auth),
+            Some(json!({"schedule": schedule_payload("DAILY", "UTC", "09:00")})),
+        ),
+    )
+    .await;
+    assert_eq!(back_to_daily.status, StatusCode::OK);
+    assert_eq!(back_to_daily.body["schedule"]["local_time"], "09:00");
+    assert!(
+        back_to_daily.body["schedule"]
+            .get("cron_expression")
+            .is_none()
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn automation_weekly_days_and_monthly_day_round_trip() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let auth = format!("Bearer {}", clerk.token_for_subject("automation-anchors"));
+    let app = build_test_router(store, &clerk).await;
+
+    let create = |schedule: Value, request_id: &str| {
+        request(
+            Method::POST,
+            "/v1/automations",
+            Some(&auth),
+            Some(json!({
+                "title": "Anchored",
+                "schedule": schedule,
+                "prompt_envelope": prompt_envelope(request_id)
+            })),
+        )
+    };
+
+    let weekly = send_json(
+        &app,
+        create(
+            json!({
+                "schedule_type": "WEEKLY",
+                "time_zone": "UTC",
+                "local_time": "07:30",
+                "days_of_week": [5, 1, 3]
+            }),
+            "anchors-weekly",
+        ),
+    )
+    .await;
+    assert_eq!(weekly.status, StatusCode::OK);
+    assert_eq!(weekly.body["schedule"]["days_of_week"], json!([1, 3, 5]));
+    let next_run_at = weekly.body["next_run_at"]
+        .as_str()
+        .and_then(|value| DateTime::parse_from_rfc3339(value).ok())
+        .expect("next_run_at should be RFC 3339")
+        .with_timezone(&Utc);
+    assert!(matches!(
+        next_run_at.weekday(),
+        Weekday::Mon | Weekday::Wed | Weekday::Fri
+    ));
+
+    let monthly = send_json(
+        &app,
+        create(
+            json!({
+                "schedule_type": "MONTHLY",
+                "time_zone": "UTC",
+                "local_time": "08:00",
+                "day_of_month": 31
+            }),
+            "anchors-monthly",
+        ),
+    )
+    .await;
+    assert_eq!(monthly.status, StatusCode::OK);
+    assert_eq!(monthly.body["schedule"]["day_of_month"], 31);
+    assert!(monthly.body["schedule"].get("days_of_week").is_none());
+
+    let defaulted = send_json(
+        &app,
+        create(
+            schedule_payload("WEEKLY", "UTC", "09:00"),
+            "anchors-defaulted",
+        ),
+    )
+    .await;
+    assert_eq!(defaulted.status, StatusCode::OK);
+    assert_eq!(
+        defaulted.body["schedule"]["days_of_week"]
+            .as_array()
+            .map(Vec::len),
+        Some(1)
+    );
+
+    for (schedule, request_id) in [
+        (
+            json!({
+                "schedule_type": "DAILY",
+                "time_zone": "UTC",
+                "local_time": "09:00",
+                "days_of_week": [1]
+            }),
+            "anchors-daily-days",
+        ),
+        (
+            json!({
+                "schedule_type": "WEEKLY",
+                "time_zone": "UTC",
+                "local_time": "09:00",
+                "days_of_week": [0, 8]
+            }),
+            "anchors-bad-days",
+        ),
+        (
+            json!({
+                "schedule_type": "WEEKLY",
+                "time_zone": "UTC",
+                "local_time": "09:00",
+                "day_of_month": 3
+            }),
+            "anchors-weekly-month-day",
+        ),
+        (
+            json!({
+                "schedule_type": "MONTHLY",
+                "time_zone": "UTC",
+                "local_time": "09:00",
+                "day_of_month": 32
+            }),
+            "anchors-bad-month-day",
+        ),
+    ] {
+        let rejected = send_json(&app, create(schedule, request_id)).await;
+        assert_eq!(rejected.status, StatusCode::BAD_REQUEST, "{request_id}");
+        assert_eq!(error_code(&rejected.body), Some("invalid_schedule"));
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn automation_once_schedules_complete_and_can_be_rearmed() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let auth = format!("Bearer {}", clerk.token_for_subject("automation-once"));
+    let app = build_test_router(store.clone(), &clerk).await;
+
+    let now = Utc::now();
+    let run_at = DateTime::from_timestamp(now.timestamp() + 2 * 86_400, 0)
+        .expect("run_at should be representable");
+    let create = |schedule: Value, request_id: &str| {
+        request(
+            Method::POST,
+            "/v1/automations",
+            Some(&auth),
+            Some(json!({
+                "title": "Follow up with Sam",
+                "schedule": schedule,
+                "prompt_envelope": prompt_envelope(request_id)
+            })),
+        )
+    };
+
+    for (schedule, request_id) in [
+        (
+            json!({"schedule_type": "ONCE", "time_zone": "UTC"}),
+            "once-missing",
+        ),
+        (
+            json!({
+                "schedule_type": "ONCE",
+                "time_zone": "UTC",
+                "run_at": (now - ChronoDuration::minutes(5)).to_rfc3339()
+            }),
+            "once-past",
+        ),
+        (
+            json!({
+                "schedule_type": "ONCE",
+                "time_zone": "UTC",
+                "local_time": "09:00",
+                "run_at": run_at.to_rfc3339()
+            }),
+            "once-local-time",
+        ),
+        (
+            json!({
+                "schedule_type": "DAILY",
+                "time_zone": "UTC",
+                "local_time": "09:00",
+                "run_at": run_at.to_rfc3339()
+            }),
+            "daily-run-at",
+        ),
+    ] {
+        let rejected = send_json(&app, create(schedule, request_id)).await;
+        assert_eq!(rejected.status, StatusCode::BAD_REQUEST, "{request_id}");
+        assert_eq!(error_code(&rejected.body), Some("invalid_schedule"));
+    }
+
+    let created = send_json(
+        &app,
+        create(
+            json!({
+                "schedule_type": "ONCE",
+                "time_zone": "America/New_York",
+                "run_at": run_at.to_rfc3339()
+            }),
+            "once-create",
+        ),
+    )
+    .await;
+    assert_eq!(created.status, StatusCode::OK);
+    assert_eq!(created.body["status"], "ACTIVE");
+    assert_eq!(created.body["schedule"]["schedule_type"], "ONCE");
+    assert!(created.body["schedule"].get("local_time").is_none());
+    let parse = |value: &Value| {
+        value
+            .as_str()
+            .and_then(|value| DateTime::parse_from_rfc3339(value).ok())
+            .map(|value| value.with_timezone(&Utc))
+    };
+    assert_eq!(parse(&created.body["schedule"]["run_at"]), Some(run_at));
+    assert_eq!(parse(&created.body["next_run_at"]), Some(run_at));
+
+    let rule_id = created.body["rule_id"]
+        .as_str()
+        .expect("create response should include rule_id")
+        .to_string();
+    let rule_uri = format!("/v1/automations/{rule_id}");
+    let set_completed = send_json(
+        &app,
+        request(
+            Method::PATCH,
+            &rule_uri,
+            Some(&auth),
+            Some(json!({"status": "COMPLETED"})),
+        ),
+    )
+    .await;
+    assert_eq!(set_completed.status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        error_code(&set_completed.body),
+        Some("invalid_automation_update")
+    );
+
+    sqlx::query("UPDATE automation_rules SET status = 'COMPLETED' WHERE id = $1::uuid")
+        .bind(&rule_id)
+        .execute(store.pool())
+        .await
+        .expect("rule should complete");
+    let run_completed = send_json(
+        &app,
+        request(
+            Method::POST,
+            &format!("{rule_uri}/run"),
+            Some(&auth),
+            Some(json!({"request_id": "once-run-now"})),
+        ),
+    )
+    .await;
+    assert_eq!(run_completed.status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        error_code(&run_completed.body),
+        Some("automation_not_active")
+    );
+
+    let rerun_at = run_at + ChronoDuration::days(7);
+    let rearmed = send_json(
+        &app,
+        request(
+            Method::PATCH,
+            &rule_uri,
+            Some(&auth),
+            Some(json!({
+                "status": "ACTIVE",
+                "schedule": {
+                    "schedule_type": "ONCE",
+                    "time_zone": "America/New_York",
+                    "run_at": rerun_at.to_rfc3339()
+                }
+            })),
+        ),
+    )
+    .await;
+    assert_eq!(rearmed.status, StatusCode::OK);
+    assert_eq!(rearmed.body["status"], "ACTIVE");
+    assert_eq!(parse(&rearmed.body["next_run_at"]), Some(rerun_at));
+}
+
+#[tokio::test]
+#[serial]
+async fn automation_schedule_preview_lists_upcoming_runs_without_saving() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let auth = format!("Bearer {}", clerk.token_for_subject("automation-preview"));
+    let app = build_test_router(store, &clerk).await;
+    let preview = |schedule: Value| {
+        request(
+            Method::POST,
+            "/v1/automations/preview-schedule",
+            Some(&auth),
+            Some(json!({ "schedule": schedule })),
+        )
+    };
+
+    let weekdays = send_json(
+        &app,
+        preview(json!({
+            "schedule_type": "WEEKLY",
+            "time_zone": "Asia/Kolkata",
+            "local_time": "07:00",
+            "days_of_week": [1, 2, 3, 4, 5]
+        })),
+    )
+    .await;
+    assert_eq!(weekdays.status, StatusCode::OK);
+    assert_eq!(weekdays.body["time_zone"], "Asia/Kolkata");
+    let runs = weekdays.body["runs"]
+        .as_array()
+        .expect("preview should list runs");
+    assert_eq!(runs.len(), 5);
+    let mut previous = None;
+    for run in runs {
+        let run_at = run["run_at"]
+            .as_str()
+            .and_then(|value| DateTime::parse_from_rfc3339(value).ok())
+            .expect("run_at should be RFC 3339");
+        let local_run_at = run["local_run_at"]
+            .as_str()
+            .and_then(|value| DateTime::parse_from_rfc3339(value).ok())
+            .expect("local_run_at should be RFC 3339");
+        assert_eq!(run_at, local_run_at);
+        // Kolkata has no DST, so every run is 07:00 at +05:30 on a weekday.
+        assert_eq!(local_run_at.format("%H:%M %:z").to_string(), "07:00 +05:30");
+        assert!(!matches!(
+            local_run_at.weekday(),
+            Weekday::Sat | Weekday::Sun
+        ));
+        assert!(previous.is_none_or(|previous| previous < run_at));
+        previous = Some(run_at);
+    }
+
+    let once = send_json(
+        &app,
+        preview(json!({
+            "schedule_type": "ONCE",
+            "time_zone": "UTC",
+            "run_at": (Utc::now() + ChronoDuration::hours(3)).to_rfc3339()
+        })),
+    )
+    .await;
+    assert_eq!(once.status, StatusCode::OK);
+    assert_eq!(once.body["runs"].as_array().map(Vec::len), Some(1));
+
+    let invalid = send_json(
+        &app,
+        preview(json!({"schedule_type": "DAILY", "time_zone": "UTC", "local_time": "7am"})),
+    )
+    .await;
+    assert_eq!(invalid.status, StatusCode::BAD_REQUEST);
+    assert_eq!(error_code(&invalid.body), Some("invalid_local_time"));
+
+    let listed = send_json(
+        &app,
+        request(Method::GET, "/v1/automations", Some(&auth), None),
+    )
+    .await;
+    assert_eq!(listed.status, StatusCode::OK);
+    assert_eq!(listed.body["items"].as_array().map(Vec::len), Some(0));
+}
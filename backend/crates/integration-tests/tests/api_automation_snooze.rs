@@ -0,0 +1,133 @@
+mod support;
+
+use axum::http::{Method, StatusCode};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde_json::{Value, json};
+use serial_test::serial;
+use uuid::Uuid;
+
+use support::api_app::build_test_router;
+use support::assistant_encrypted::{request, send_json};
+use support::automations::{error_code, prompt_envelope, schedule_payload};
+use support::clerk::TestClerkAuth;
+
+#[tokio::test]
+#[serial]
+async fn automation_snooze_pauses_until_a_preset_or_date() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let auth = format!("Bearer {}", clerk.token_for_subject("automation-snooze"));
+    let app = build_test_router(store, &clerk).await;
+
+    let created = send_json(
+        &app,
+        request(
+            Method::POST,
+            "/v1/automations",
+            Some(&auth),
+            Some(json!({
+                "title": "Morning digest",
+                "schedule": schedule_payload("DAILY", "UTC", "09:00"),
+                "prompt_envelope": prompt_envelope("snooze-create")
+            })),
+        ),
+    )
+    .await;
+    assert_eq!(created.status, StatusCode::OK);
+    assert!(created.body["paused_until"].is_null());
+    let rule_id = created.body["rule_id"]
+        .as_str()
+        .expect("create response should include rule_id")
+        .to_string();
+    let rule_uri = format!("/v1/automations/{rule_id}");
+    let snooze = |body: Value| {
+        request(
+            Method::POST,
+            &format!("{rule_uri}/snooze"),
+            Some(&auth),
+            Some(body),
+        )
+    };
+    let parse = |value: &Value| {
+        value
+            .as_str()
+            .and_then(|value| DateTime::parse_from_rfc3339(value).ok())
+            .map(|value| value.with_timezone(&Utc))
+    };
+
+    let now = Utc::now();
+    for body in [
+        json!({"preset": "UNTIL"}),
+        json!({"preset": "ONE_DAY", "until": (now + ChronoDuration::days(2)).to_rfc3339()}),
+        json!({"preset": "UNTIL", "until": (now - ChronoDuration::minutes(5)).to_rfc3339()}),
+        json!({"preset": "UNTIL", "until": (now + ChronoDuration::days(400)).to_rfc3339()}),
+    ] {
+        let rejected = send_json(&app, snooze(body.clone())).await;
+        assert_eq!(rejected.status, StatusCode::BAD_REQUEST, "{body}");
+        assert_eq!(error_code(&rejected.body), Some("invalid_snooze"));
+    }
+
+    let week = send_json(&app, snooze(json!({"preset": "ONE_WEEK"}))).await;
+    assert_eq!(week.status, StatusCode::OK);
+    assert_eq!(week.body["status"], "PAUSED");
+    let paused_until = parse(&week.body["paused_until"]).expect("paused_until should be set");
+    let expected = now + ChronoDuration::weeks(1);
+    assert!(paused_until >= expected && paused_until <= expected + ChronoDuration::minutes(1));
+    assert!(week.body["version"].as_i64() > created.body["version"].as_i64());
+
+    let until = DateTime::from_timestamp(now.timestamp() + 3 * 86_400, 0)
+        .expect("until should be representable");
+    let dated = send_json(
+        &app,
+        snooze(json!({"preset": "UNTIL", "until": until.to_rfc3339()})),
+    )
+    .await;
+    assert_eq!(dated.status, StatusCode::OK);
+    assert_eq!(parse(&dated.body["paused_until"]), Some(until));
+
+    let resumed = send_json(
+        &app,
+        request(
+            Method::PATCH,
+            &rule_uri,
+            Some(&auth),
+            Some(json!({"status": "ACTIVE"})),
+        ),
+    )
+    .await;
+    assert_eq!(resumed.status, StatusCode::OK);
+    assert_eq!(resumed.body["status"], "ACTIVE");
+    assert!(resumed.body["paused_until"].is_null());
+
+    let archived = send_json(
+        &app,
+        request(
+            Method::PATCH,
+            &rule_uri,
+            Some(&auth),
+            Some(json!({"status": "ARCHIVED"})),
+        ),
+    )
+    .await;
+    assert_eq!(archived.status, StatusCode::OK);
+    let snooze_archived = send_json(&app, snooze(json!({"preset": "ONE_DAY"}))).await;
+    assert_eq!(snooze_archived.status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        error_code(&snooze_archived.body),
+        Some("automation_archived")
+    );
+
+    let missing = send_json(
+        &app,
+        request(
+            Method::POST,
+            &format!("/v1/automations/{}/snooze", Uuid::new_v4()),
+            Some(&auth),
+            Some(json!({"preset": "ONE_DAY"})),
+        ),
+    )
+    .await;
+    assert_eq!(missing.status, StatusCode::NOT_FOUND);
+}
@@ -1,13 +1,12 @@
 mod support;
 
-use axum::body::{Body, to_bytes};
-use axum::http::{Method, Request, StatusCode, header};
-use base64::{Engine as _, engine::general_purpose::STANDARD};
+use axum::http::{Method, StatusCode, header};
 use serde_json::{Value, json};
 use serial_test::serial;
-use tower::ServiceExt;
 
 use support::api_app::build_test_router;
+use support::assistant_encrypted::{request, send_json};
+use support::automations::{error_code, prompt_envelope, schedule_payload};
 use support::clerk::TestClerkAuth;
 
 #[tokio::test]
@@ -348,70 +347,274 @@ async fn automation_mutations_are_user_scoped() {
     )
     .await;
     assert_eq!(debug_other_user.status, StatusCode::NOT_FOUND);
-}
 
-struct JsonResponse {
-    status: StatusCode,
-    body: Value,
+    let run_other_user = send_json(
+        &app,
+        request(
+            Method::POST,
+            &format!("/v1/automations/{rule_id}/run"),
+            Some(&auth_b),
+            Some(json!({"request_id": "run-other-user"})),
+        ),
+    )
+    .await;
+    assert_eq!(run_other_user.status, StatusCode::NOT_FOUND);
 }
 
-async fn send_json(app: &axum::Router, request: Request<Body>) -> JsonResponse {
-    let response = app
-        .clone()
-        .oneshot(request)
-        .await
-        .expect("request should succeed");
-    let status = response.status();
-    let body = to_bytes(response.into_body(), usize::MAX)
-        .await
-        .expect("response body should read");
-    let body = serde_json::from_slice::<Value>(&body).unwrap_or_else(|_| json!({}));
-
-    JsonResponse { status, body }
+#[tokio::test]
+#[serial]
+async fn automation_update_with_stale_version_returns_conflict() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let auth = format!("Bearer {}", clerk.token_for_subject("automation-owner"));
+    let app = build_test_router(store, &clerk).await;
+
+    let create = send_json(
+        &app,
+        request(
+            Method::POST,
+            "/v1/automations",
+            Some(&auth),
+            Some(json!({
+                "title": "Shared task",
+                "schedule": schedule_payload("DAILY", "UTC", "09:00"),
+                "prompt_envelope": prompt_envelope("versioned")
+            })),
+        ),
+    )
+    .await;
+    assert_eq!(create.status, StatusCode::OK);
+    assert_eq!(create.body.get("version").and_then(Value::as_i64), Some(1));
+    let rule_id = create
+        .body
+        .get("rule_id")
+        .and_then(Value::as_str)
+        .expect("create response should include rule_id");
+    let uri = format!("/v1/automations/{rule_id}");
+
+    let first = send_json(
+        &app,
+        request(
+            Method::PATCH,
+            &uri,
+            Some(&auth),
+            Some(json!({"title": "Renamed on phone", "expected_version": 1})),
+        ),
+    )
+    .await;
+    assert_eq!(first.status, StatusCode::OK);
+    assert_eq!(first.body.get("version").and_then(Value::as_i64), Some(2));
+
+    let mut stale = request(
+        Method::PATCH,
+        &uri,
+        Some(&auth),
+        Some(json!({"title": "Renamed on tablet"})),
+    );
+    stale
+        .headers_mut()
+        .insert(header::IF_MATCH, "\"1\"".parse().expect("valid header"));
+    let stale = send_json(&app, stale).await;
+    assert_eq!(stale.status, StatusCode::CONFLICT);
+    assert_eq!(error_code(&stale.body), Some("version_conflict"));
+    assert_eq!(
+        stale.body["current"]["title"].as_str(),
+        Some("Renamed on phone")
+    );
+    assert_eq!(stale.body["current"]["version"].as_i64(), Some(2));
+
+    let rejected = send_json(
+        &app,
+        request(
+            Method::PATCH,
+            &uri,
+            Some(&auth),
+            Some(json!({"title": " ", "expected_version": 2})),
+        ),
+    )
+    .await;
+    assert_eq!(rejected.status, StatusCode::BAD_REQUEST);
+
+    let unconditional = send_json(
+        &app,
+        request(
+            Method::PATCH,
+            &uri,
+            Some(&auth),
+            Some(json!({"status": "PAUSED"})),
+        ),
+    )
+    .await;
+    assert_eq!(unconditional.status, StatusCode::OK);
+    assert_eq!(
+        unconditional.body.get("version").and_then(Value::as_i64),
+        Some(3),
+        "a rejected update must not consume a version"
+    );
 }
 
-fn request(
-    method: Method,
-    uri: &str,
-    auth_header: Option<&str>,
-    json_body: Option<Value>,
-) -> Request<Body> {
-    let mut builder = Request::builder().method(method).uri(uri);
-    if let Some(auth_header) = auth_header {
-        builder = builder.header(header::AUTHORIZATION, auth_header);
-    }
+#[tokio::test]
+#[serial]
+async fn automation_list_pages_with_opaque_cursors() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
 
-    match json_body {
-        Some(body) => builder
-            .header(header::CONTENT_TYPE, "application/json")
-            .body(Body::from(body.to_string()))
-            .expect("request should build"),
-        None => builder.body(Body::empty()).expect("request should build"),
+    let clerk = TestClerkAuth::start().await;
+    let auth = format!("Bearer {}", clerk.token_for_subject("automation-pager"));
+    let app = build_test_router(store, &clerk).await;
+
+    for index in 0..3 {
+        let create = send_json(
+            &app,
+            request(
+                Method::POST,
+                "/v1/automations",
+                Some(&auth),
+                Some(json!({
+                    "title": format!("Automation {index}"),
+                    "schedule": schedule_payload("DAILY", "UTC", "09:00"),
+                    "prompt_envelope": prompt_envelope(&format!("page-request-{index}"))
+                })),
+            ),
+        )
+        .await;
+        assert_eq!(create.status, StatusCode::OK);
     }
-}
 
-fn prompt_envelope(request_id: &str) -> Value {
-    json!({
-        "version": "v1",
-        "algorithm": "x25519-chacha20poly1305",
-        "key_id": "assistant-ingress-v1",
-        "request_id": request_id,
-        "client_ephemeral_public_key": STANDARD.encode([7_u8; 32]),
-        "nonce": STANDARD.encode([9_u8; 12]),
-        "ciphertext": STANDARD.encode(b"encrypted-automation-prompt")
-    })
-}
+    let first_page = send_json(
+        &app,
+        request(Method::GET, "/v1/automations?limit=2", Some(&auth), None),
+    )
+    .await;
+    assert_eq!(first_page.status, StatusCode::OK);
+    assert_eq!(
+        first_page
+            .body
+            .get("items")
+            .and_then(Value::as_array)
+            .map(Vec::len),
+        Some(2)
+    );
+    let cursor = first_page
+        .body
+        .get("next_cursor")
+        .and_then(Value::as_str)
+        .expect("partial list should return a cursor")
+        .to_string();
 
-fn schedule_payload(schedule_type: &str, time_zone: &str, local_time: &str) -> Value {
-    json!({
-        "schedule_type": schedule_type,
-        "time_zone": time_zone,
-        "local_time": local_time
-    })
+    let second_page = send_json(
+        &app,
+        request(
+            Method::GET,
+            &format!("/v1/automations?limit=2&cursor={cursor}"),
+            Some(&auth),
+            None,
+        ),
+    )
+    .await;
+    assert_eq!(second_page.status, StatusCode::OK);
+    assert_eq!(
+        second_page
+            .body
+            .get("items")
+            .and_then(Value::as_array)
+            .map(Vec::len),
+        Some(1)
+    );
+    assert_eq!(second_page.body.get("next_cursor"), Some(&Value::Null));
+
+    let wrong_resource = send_json(
+        &app,
+        request(
+            Method::GET,
+            &format!("/v1/audit-events?cursor={cursor}"),
+            Some(&auth),
+            None,
+        ),
+    )
+    .await;
+    assert_eq!(wrong_resource.status, StatusCode::BAD_REQUEST);
+    assert_eq!(error_code(&wrong_resource.body), Some("invalid_cursor"));
 }
 
-fn error_code(body: &Value) -> Option<&str> {
-    body.get("error")
-        .and_then(|error| error.get("code"))
+#[tokio::test]
+#[serial]
+async fn replayed_prompt_envelopes_are_rejected_across_automations_and_assistant() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let auth = format!("Bearer {}", clerk.token_for_subject("envelope-replay"));
+    let other_auth = format!("Bearer {}", clerk.token_for_subject("envelope-other"));
+    let app = build_test_router(store, &clerk).await;
+
+    let create = |auth: &str, request_id: &str| {
+        request(
+            Method::POST,
+            "/v1/automations",
+            Some(auth),
+            Some(json!({
+                "title": "Replay check",
+                "schedule": schedule_payload("DAILY", "UTC", "09:00"),
+                "prompt_envelope": prompt_envelope(request_id)
+            })),
+        )
+    };
+
+    let created = send_json(&app, create(&auth, "captured-envelope")).await;
+    assert_eq!(created.status, StatusCode::OK);
+    let rule_id = created
+        .body
+        .get("rule_id")
         .and_then(Value::as_str)
+        .expect("create response should include rule_id")
+        .to_string();
+
+    let replayed_create = send_json(&app, create(&auth, "captured-envelope")).await;
+    assert_eq!(replayed_create.status, StatusCode::BAD_REQUEST);
+    assert_eq!(error_code(&replayed_create.body), Some("request_id_reused"));
+
+    let replayed_update = send_json(
+        &app,
+        request(
+            Method::PATCH,
+            &format!("/v1/automations/{rule_id}"),
+            Some(&auth),
+            Some(json!({"prompt_envelope": prompt_envelope("captured-envelope")})),
+        ),
+    )
+    .await;
+    assert_eq!(replayed_update.status, StatusCode::BAD_REQUEST);
+    assert_eq!(error_code(&replayed_update.body), Some("request_id_reused"));
+
+    let replayed_query = send_json(
+        &app,
+        request(
+            Method::POST,
+            "/v1/assistant/query",
+            Some(&auth),
+            Some(json!({"envelope": prompt_envelope("captured-envelope")})),
+        ),
+    )
+    .await;
+    assert_eq!(replayed_query.status, StatusCode::BAD_REQUEST);
+    assert_eq!(error_code(&replayed_query.body), Some("request_id_reused"));
+
+    let fresh_update = send_json(
+        &app,
+        request(
+            Method::PATCH,
+            &format!("/v1/automations/{rule_id}"),
+            Some(&auth),
+            Some(json!({"prompt_envelope": prompt_envelope("fresh-envelope")})),
+        ),
+    )
+    .await;
+    assert_eq!(fresh_update.status, StatusCode::OK);
+
+    // Request ids are scoped per user.
+    let other_user = send_json(&app, create(&other_auth, "captured-envelope")).await;
+    assert_eq!(other_user.status, StatusCode::OK);
 }
@@ -0,0 +1,168 @@
+mod support;
+
+use axum::body::{Body, to_bytes};
+use axum::http::{Method, Request, StatusCode, header};
+use serde_json::{Value, json};
+use serial_test::serial;
+use tower::ServiceExt;
+
+use support::api_app::build_test_router;
+use support::clerk::TestClerkAuth;
+
+#[tokio::test]
+#[serial]
+async fn brief_profile_defaults_updates_and_learns_from_feedback() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let auth = format!("Bearer {}", clerk.token_for_subject("brief-profile-owner"));
+    let app = build_test_router(store, &clerk).await;
+
+    let default_profile = send_json(
+        &app,
+        request(Method::GET, "/v1/brief-profile", Some(&auth), None),
+    )
+    .await;
+    assert_eq!(default_profile.status, StatusCode::OK);
+    assert_eq!(
+        default_profile.body.get("sections"),
+        Some(&json!(["priorities", "schedule", "alerts"]))
+    );
+    assert_eq!(
+        default_profile
+            .body
+            .get("verbosity")
+            .and_then(Value::as_str),
+        Some("standard")
+    );
+
+    let updated = send_json(
+        &app,
+        request(
+            Method::PUT,
+            "/v1/brief-profile",
+            Some(&auth),
+            Some(json!({
+                "sections": ["schedule", "priorities"],
+                "verbosity": "concise"
+            })),
+        ),
+    )
+    .await;
+    assert_eq!(updated.status, StatusCode::OK);
+    assert_eq!(
+        updated.body.get("sections"),
+        Some(&json!(["schedule", "priorities"]))
+    );
+    assert_eq!(
+        updated
+            .body
+            .get("learn_from_feedback")
+            .and_then(Value::as_bool),
+        Some(true)
+    );
+
+    let feedback = send_json(
+        &app,
+        request(
+            Method::POST,
+            "/v1/brief-profile/feedback",
+            Some(&auth),
+            Some(json!({ "section": "alerts", "rating": "up" })),
+        ),
+    )
+    .await;
+    assert_eq!(feedback.status, StatusCode::OK);
+    assert_eq!(
+        feedback.body.get("sections"),
+        Some(&json!(["alerts", "schedule", "priorities"]))
+    );
+    assert_eq!(
+        feedback.body.get("verbosity").and_then(Value::as_str),
+        Some("concise")
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn brief_profile_rejects_invalid_sections() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let auth = format!("Bearer {}", clerk.token_for_subject("brief-profile-owner"));
+    let app = build_test_router(store, &clerk).await;
+
+    let empty = send_json(
+        &app,
+        request(
+            Method::PUT,
+            "/v1/brief-profile",
+            Some(&auth),
+            Some(json!({ "sections": [], "verbosity": "standard" })),
+        ),
+    )
+    .await;
+    assert_eq!(empty.status, StatusCode::BAD_REQUEST);
+    assert_eq!(error_code(&empty.body), Some("invalid_brief_sections"));
+
+    let duplicate = send_json(
+        &app,
+        request(
+            Method::PUT,
+            "/v1/brief-profile",
+            Some(&auth),
+            Some(json!({ "sections": ["alerts", "alerts"], "verbosity": "standard" })),
+        ),
+    )
+    .await;
+    assert_eq!(duplicate.status, StatusCode::BAD_REQUEST);
+    assert_eq!(error_code(&duplicate.body), Some("invalid_brief_sections"));
+}
+
+struct JsonResponse {
+    status: StatusCode,
+    body: Value,
+}
+
+async fn send_json(app: &axum::Router, request: Request<Body>) -> JsonResponse {
+    let response = app
+        .clone()
+        .oneshot(request)
+        .await
+        .expect("request should succeed");
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("response body should read");
+    let body = serde_json::from_slice::<Value>(&body).unwrap_or_else(|_| json!({}));
+
+    JsonResponse { status, body }
+}
+
+fn request(
+    method: Method,
+    uri: &str,
+    auth_header: Option<&str>,
+    json_body: Option<Value>,
+) -> Request<Body> {
+    let mut builder = Request::builder().method(method).uri(uri);
+    if let Some(auth_header) = auth_header {
+        builder = builder.header(header::AUTHORIZATION, auth_header);
+    }
+
+    match json_body {
+        Some(body) => builder
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .expect("request should build"),
+        None => builder.body(Body::empty()).expect("request should build"),
+    }
+}
+
+fn error_code(body: &Value) -> Option<&str> {
+    body.get("error")
+        .and_then(|error| error.get("code"))
+        .and_then(Value::as_str)
+}
@@ -0,0 +1,213 @@
+mod support;
+
+use axum::body::{Body, to_bytes};
+use axum::extract::Json as JsonBody;
+use axum::http::{Method, Request, StatusCode, header};
+use axum::routing::post;
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use serde_json::{Value, json};
+use serial_test::serial;
+use shared::enclave::{
+    ENCLAVE_RPC_CONTRACT_VERSION, ENCLAVE_RPC_PATH_COMPLETE_CALDAV_CONNECT,
+    EnclaveRpcCompleteCaldavConnectRequest, EnclaveRpcCompleteCaldavConnectResponse,
+};
+use shared::repos::ConnectorAccount;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use support::api_app::{
+    build_test_router, build_test_router_with_enclave_base_url, user_id_for_subject,
+};
+use support::clerk::TestClerkAuth;
+use support::enclave_mock::MockEnclaveServer;
+
+#[tokio::test]
+#[serial]
+async fn caldav_connect_stores_connector_and_revokes_locally() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let enclave_store = store.clone();
+    let mock_enclave = MockEnclaveServer::start(axum::Router::new().route(
+        ENCLAVE_RPC_PATH_COMPLETE_CALDAV_CONNECT,
+        post(
+            move |JsonBody(request): JsonBody<EnclaveRpcCompleteCaldavConnectRequest>| {
+                let store = enclave_store.clone();
+                async move {
+                    assert_eq!(
+                        request.server_url,
+                        "https://caldav.example.com/calendars/me/work/"
+                    );
+                    assert_eq!(request.username, "me@example.com");
+                    let connector_id = store
+                        .upsert_caldav_connector(
+                            ConnectorAccount {
+                                user_id: request.user_id,
+                                connector_id: Uuid::new_v4(),
+                                account_key: "caldav-account",
+                            },
+                            "{\"app_password\":\"sealed\"}",
+                            "kms/local/alfred-refresh-token",
+                            1,
+                        )
+                        .await
+                        .expect("caldav connector should store");
+
+                    axum::Json(EnclaveRpcCompleteCaldavConnectResponse {
+                        contract_version: ENCLAVE_RPC_CONTRACT_VERSION.to_string(),
+                        request_id: request.request_id,
+                        connector_id,
+                    })
+                }
+            },
+        ),
+    ))
+    .await;
+
+    let clerk = TestClerkAuth::start().await;
+    let subject = "caldav-connect-user";
+    let user_id = user_id_for_subject(&clerk.issuer, subject);
+    let auth = format!("Bearer {}", clerk.token_for_subject(subject));
+    let app =
+        build_test_router_with_enclave_base_url(store.clone(), &clerk, &mock_enclave.base_url)
+            .await;
+
+    let connect = send_json(
+        &app,
+        request(
+            Method::POST,
+            "/v1/connectors/caldav",
+            Some(&auth),
+            Some(connect_payload(
+                " https://caldav.example.com/calendars/me/work/#main ",
+            )),
+        ),
+    )
+    .await;
+    assert_eq!(connect.status, StatusCode::OK);
+    assert_eq!(connect.body["status"], "ACTIVE");
+    let connector_id = connect.body["connector_id"]
+        .as_str()
+        .expect("connector_id should be present")
+        .to_string();
+
+    let list = send_json(
+        &app,
+        request(Method::GET, "/v1/connectors", Some(&auth), None),
+    )
+    .await;
+    assert_eq!(list.status, StatusCode::OK);
+    assert_eq!(list.body["items"][0]["connector_id"], connector_id.as_str());
+    assert_eq!(list.body["items"][0]["provider"], "caldav");
+
+    let revoke = send_json(
+        &app,
+        request(
+            Method::DELETE,
+            &format!("/v1/connectors/{connector_id}"),
+            Some(&auth),
+            None,
+        ),
+    )
+    .await;
+    assert_eq!(revoke.status, StatusCode::OK);
+    assert_eq!(revoke.body["status"], "REVOKED");
+
+    let active = store
+        .list_active_connector_metadata(user_id)
+        .await
+        .expect("active listing should succeed");
+    assert!(active.is_empty());
+}
+
+#[tokio::test]
+#[serial]
+async fn caldav_connect_rejects_invalid_server_urls() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let auth = format!("Bearer {}", clerk.token_for_subject("caldav-invalid-user"));
+    let app = build_test_router(store.clone(), &clerk).await;
+
+    for server_url in [
+        "http://caldav.example.com/calendars/me/",
+        "https://localhost/calendars/me/",
+        "https://10.1.2.3/calendars/me/",
+    ] {
+        let response = send_json(
+            &app,
+            request(
+                Method::POST,
+                "/v1/connectors/caldav",
+                Some(&auth),
+                Some(connect_payload(server_url)),
+            ),
+        )
+        .await;
+        assert_eq!(response.status, StatusCode::BAD_REQUEST, "{server_url}");
+        assert_eq!(error_code(&response.body), Some("invalid_server_url"));
+    }
+}
+
+fn connect_payload(server_url: &str) -> Value {
+    json!({
+        "server_url": server_url,
+        "username": " me@example.com ",
+        "app_password_envelope": {
+            "version": "v1",
+            "algorithm": "x25519-chacha20poly1305",
+            "key_id": "assistant-ingress-v1",
+            "request_id": "caldav-app-password",
+            "client_ephemeral_public_key": STANDARD.encode([7_u8; 32]),
+            "nonce": STANDARD.encode([9_u8; 12]),
+            "ciphertext": STANDARD.encode(b"encrypted-app-password")
+        }
+    })
+}
+
+struct JsonResponse {
+    status: StatusCode,
+    body: Value,
+}
+
+async fn send_json(app: &axum::Router, request: Request<Body>) -> JsonResponse {
+    let response = app
+        .clone()
+        .oneshot(request)
+        .await
+        .expect("request should succeed");
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("response body should read");
+    let body = serde_json::from_slice::<Value>(&body).unwrap_or_else(|_| json!({}));
+
+    JsonResponse { status, body }
+}
+
+fn request(
+    method: Method,
+    uri: &str,
+    auth_header: Option<&str>,
+    json_body: Option<Value>,
+) -> Request<Body> {
+    let mut builder = Request::builder().method(method).uri(uri);
+    if let Some(auth_header) = auth_header {
+        builder = builder.header(header::AUTHORIZATION, auth_header);
+    }
+
+    match json_body {
+        Some(body) => builder
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .expect("request should build"),
+        None => builder.body(Body::empty()).expect("request should build"),
+    }
+}
+
+fn error_code(body: &Value) -> Option<&str> {
+    body.get("error")
+        .and_then(|error| error.get("code"))
+        .and_then(Value::as_str)
+}
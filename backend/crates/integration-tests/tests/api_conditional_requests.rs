@@ -0,0 +1,250 @@
+mod support;
+
+use axum::body::{Body, to_bytes};
+use axum::http::{HeaderMap, Method, Request, StatusCode, header};
+use serde_json::{Value, json};
+use serial_test::serial;
+use shared::repos::ConnectorAccount;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use support::api_app::{build_test_router, user_id_for_subject};
+use support::clerk::TestClerkAuth;
+
+const PREFERENCES: &str = "/v1/departure-alerts/preferences";
+
+#[tokio::test]
+#[serial]
+async fn preferences_etags_revalidate_reads_and_guard_concurrent_updates() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let auth = format!(
+        "Bearer {}",
+        clerk.token_for_subject("etag-preferences-user")
+    );
+    let app = build_test_router(store, &clerk).await;
+
+    let defaults = send(&app, request(Method::GET, PREFERENCES, &auth, &[], None)).await;
+    assert_eq!(defaults.status, StatusCode::OK);
+    let default_etag = etag(&defaults);
+    assert!(default_etag.starts_with("W/\""));
+
+    let unchanged = send(
+        &app,
+        request(
+            Method::GET,
+            PREFERENCES,
+            &auth,
+            &[(header::IF_NONE_MATCH, &default_etag)],
+            None,
+        ),
+    )
+    .await;
+    assert_eq!(unchanged.status, StatusCode::NOT_MODIFIED);
+    assert_eq!(unchanged.body, json!({}));
+
+    // Two devices read the same state; the first save wins and the second is refused.
+    let saved = send(
+        &app,
+        request(
+            Method::PUT,
+            PREFERENCES,
+            &auth,
+            &[(header::IF_MATCH, &default_etag)],
+            Some(preferences_payload("06:00")),
+        ),
+    )
+    .await;
+    assert_eq!(saved.status, StatusCode::OK);
+    let saved_etag = etag(&saved);
+    assert_ne!(saved_etag, default_etag);
+
+    let lost_update = send(
+        &app,
+        request(
+            Method::PUT,
+            PREFERENCES,
+            &auth,
+            &[(header::IF_MATCH, &default_etag)],
+            Some(preferences_payload("07:30")),
+        ),
+    )
+    .await;
+    assert_eq!(lost_update.status, StatusCode::CONFLICT);
+    assert_eq!(lost_update.body["error"]["code"], "version_conflict");
+    assert_eq!(lost_update.body["current"]["check_time"], "06:00");
+
+    let changed = send(
+        &app,
+        request(
+            Method::GET,
+            PREFERENCES,
+            &auth,
+            &[(header::IF_NONE_MATCH, &default_etag)],
+            None,
+        ),
+    )
+    .await;
+    assert_eq!(changed.status, StatusCode::OK);
+    assert_eq!(etag(&changed), saved_etag);
+    assert_eq!(changed.body["check_time"], "06:00");
+
+    let disagreeing = send(
+        &app,
+        request(
+            Method::PUT,
+            PREFERENCES,
+            &auth,
+            &[(header::IF_MATCH, &saved_etag)],
+            Some({
+                let mut payload = preferences_payload("07:30");
+                payload["expected_version"] = json!(7);
+                payload
+            }),
+        ),
+    )
+    .await;
+    assert_eq!(disagreeing.status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        disagreeing.body["error"]["code"],
+        "invalid_expected_version"
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn connector_list_etag_changes_when_a_connector_does() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let subject = "etag-connectors-user";
+    let user_id = user_id_for_subject(&clerk.issuer, subject);
+    let auth = format!("Bearer {}", clerk.token_for_subject(subject));
+    let app = build_test_router(store.clone(), &clerk).await;
+
+    let connector_id = store
+        .upsert_caldav_connector(
+            ConnectorAccount {
+                user_id,
+                connector_id: Uuid::new_v4(),
+                account_key: "caldav-account",
+            },
+            "{\"app_password\":\"sealed\"}",
+            "kms/local/alfred-refresh-token",
+            1,
+        )
+        .await
+        .expect("caldav connector should store");
+
+    let listed = send(
+        &app,
+        request(Method::GET, "/v1/connectors", &auth, &[], None),
+    )
+    .await;
+    assert_eq!(listed.status, StatusCode::OK);
+    let listed_etag = etag(&listed);
+
+    let unchanged = send(
+        &app,
+        request(
+            Method::GET,
+            "/v1/connectors",
+            &auth,
+            &[(header::IF_NONE_MATCH, &listed_etag)],
+            None,
+        ),
+    )
+    .await;
+    assert_eq!(unchanged.status, StatusCode::NOT_MODIFIED);
+
+    store
+        .revoke_connector(user_id, connector_id)
+        .await
+        .expect("connector should revoke");
+    let changed = send(
+        &app,
+        request(
+            Method::GET,
+            "/v1/connectors",
+            &auth,
+            &[(header::IF_NONE_MATCH, &listed_etag)],
+            None,
+        ),
+    )
+    .await;
+    assert_eq!(changed.status, StatusCode::OK);
+    assert_ne!(etag(&changed), listed_etag);
+    assert_eq!(changed.body["items"][0]["status"], "REVOKED");
+}
+
+fn preferences_payload(check_time: &str) -> Value {
+    json!({
+        "enabled": true,
+        "time_zone": "UTC",
+        "check_time": check_time,
+        "travel_minutes": 30,
+        "buffer_minutes": 5
+    })
+}
+
+fn etag(response: &JsonResponse) -> String {
+    response
+        .headers
+        .get(header::ETAG)
+        .and_then(|value| value.to_str().ok())
+        .expect("response should carry an ETag")
+        .to_string()
+}
+
+struct JsonResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Value,
+}
+
+async fn send(app: &axum::Router, request: Request<Body>) -> JsonResponse {
+    let response = app
+        .clone()
+        .oneshot(request)
+        .await
+        .expect("request should succeed");
+    let status = response.status();
+    let headers = response.headers().clone();
+    let body = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("response body should read");
+    let body = serde_json::from_slice::<Value>(&body).unwrap_or_else(|_| json!({}));
+
+    JsonResponse {
+        status,
+        headers,
+        body,
+    }
+}
+
+fn request(
+    method: Method,
+    uri: &str,
+    auth_header: &str,
+    extra_headers: &[(header::HeaderName, &str)],
+    json_body: Option<Value>,
+) -> Request<Body> {
+    let mut builder = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::AUTHORIZATION, auth_header);
+    for (name, value) in extra_headers {
+        builder = builder.header(name, *value);
+    }
+
+    match json_body {
+        Some(body) => builder
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string())),
+        None => builder.body(Body::empty()),
+    }
+    .expect("request should build")
+}
@@ -0,0 +1,310 @@
+mod support;
+
+use axum::body::{Body, to_bytes};
+use axum::extract::Json as JsonBody;
+use axum::http::{Method, Request, StatusCode, header};
+use axum::routing::post;
+use serde_json::{Value, json};
+use serial_test::serial;
+use shared::connector_health::{REQUIRED_GOOGLE_SCOPES, UPGRADEABLE_GOOGLE_SCOPES};
+use shared::enclave::{
+    ENCLAVE_RPC_CONTRACT_VERSION, ENCLAVE_RPC_PATH_COMPLETE_GOOGLE_CONNECT,
+    EnclaveRpcCompleteGoogleConnectRequest, EnclaveRpcCompleteGoogleConnectResponse,
+};
+use shared::repos::ConnectorAccount;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use support::api_app::{
+    build_test_router, build_test_router_with_enclave_base_url, user_id_for_subject,
+};
+use support::clerk::TestClerkAuth;
+use support::enclave_mock::MockEnclaveServer;
+
+const GOOGLE_ACCOUNT_KEY: &str = "google-account-1";
+
+#[tokio::test]
+#[serial]
+async fn scope_upgrade_merges_granted_scopes_and_audits_delta() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let subject = "scope-upgrade-user";
+    let user_id = user_id_for_subject(&clerk.issuer, subject);
+    let auth = format!("Bearer {}", clerk.token_for_subject(subject));
+    let connector_id = store
+        .upsert_google_connector(
+            ConnectorAccount {
+                user_id,
+                connector_id: Uuid::new_v4(),
+                account_key: GOOGLE_ACCOUNT_KEY,
+            },
+            "refresh-token-1",
+            &required_scopes(),
+            "kms/local/alfred-refresh-token",
+            1,
+        )
+        .await
+        .expect("google connector should store");
+
+    // Google answers an incremental grant with only the newly consented scope here, so the
+    // callback has to merge the connector's earlier scopes back in.
+    let enclave_store = store.clone();
+    let mock_enclave = MockEnclaveServer::start(axum::Router::new().route(
+        ENCLAVE_RPC_PATH_COMPLETE_GOOGLE_CONNECT,
+        post(
+            move |JsonBody(request): JsonBody<EnclaveRpcCompleteGoogleConnectRequest>| {
+                let store = enclave_store.clone();
+                async move {
+                    let granted_scopes = vec![UPGRADEABLE_GOOGLE_SCOPES[0].to_string()];
+                    let connector_id = store
+                        .upsert_google_connector(
+                            ConnectorAccount {
+                                user_id: request.user_id,
+                                connector_id: Uuid::new_v4(),
+                                account_key: GOOGLE_ACCOUNT_KEY,
+                            },
+                            "refresh-token-2",
+                            &granted_scopes,
+                            "kms/local/alfred-refresh-token",
+                            1,
+                        )
+                        .await
+                        .expect("google connector should refresh");
+
+                    axum::Json(EnclaveRpcCompleteGoogleConnectResponse {
+                        contract_version: ENCLAVE_RPC_CONTRACT_VERSION.to_string(),
+                        request_id: request.request_id,
+                        connector_id,
+                        granted_scopes,
+                    })
+                }
+            },
+        ),
+    ))
+    .await;
+    let app =
+        build_test_router_with_enclave_base_url(store.clone(), &clerk, &mock_enclave.base_url)
+            .await;
+
+    let upgrade = send_json(
+        &app,
+        request(
+            Method::POST,
+            &format!("/v1/connectors/{connector_id}/scopes/upgrade"),
+            Some(&auth),
+            Some(json!({
+                "redirect_uri": "alfred://oauth/google/callback",
+                "scopes": [UPGRADEABLE_GOOGLE_SCOPES[0], UPGRADEABLE_GOOGLE_SCOPES[0]]
+            })),
+        ),
+    )
+    .await;
+    assert_eq!(upgrade.status, StatusCode::OK);
+    assert_eq!(
+        upgrade.body["requested_scopes"],
+        json!([UPGRADEABLE_GOOGLE_SCOPES[0]])
+    );
+    let auth_url = upgrade.body["auth_url"]
+        .as_str()
+        .expect("auth_url should be present");
+    assert!(auth_url.contains("include_granted_scopes=true"));
+    let state_token = upgrade.body["state"]
+        .as_str()
+        .expect("state should be present");
+
+    let callback = send_json(
+        &app,
+        request(
+            Method::POST,
+            "/v1/connectors/google/callback",
+            Some(&auth),
+            Some(json!({"code": "upgrade-code", "state": state_token})),
+        ),
+    )
+    .await;
+    assert_eq!(callback.status, StatusCode::OK);
+    assert_eq!(callback.body["connector_id"], connector_id.to_string());
+    let mut expected_scopes = vec![UPGRADEABLE_GOOGLE_SCOPES[0].to_string()];
+    expected_scopes.extend(required_scopes());
+    assert_eq!(callback.body["granted_scopes"], json!(expected_scopes));
+
+    let connectors = store
+        .list_connectors(user_id)
+        .await
+        .expect("connectors should list");
+    assert_eq!(connectors.len(), 1);
+    assert_eq!(connectors[0].scopes, expected_scopes);
+    assert_eq!(connectors[0].health_score, 100);
+
+    let audit_events: Vec<(String, String)> = sqlx::query_as(
+        "SELECT event_type, result
+         FROM audit_events
+         WHERE user_id = $1
+           AND event_type LIKE 'GOOGLE_SCOPE_UPGRADE_%'
+         ORDER BY chain_seq ASC",
+    )
+    .bind(user_id)
+    .fetch_all(store.pool())
+    .await
+    .expect("audit events should load");
+    assert_eq!(
+        audit_events,
+        vec![
+            (
+                "GOOGLE_SCOPE_UPGRADE_STARTED".to_string(),
+                "SUCCESS".to_string()
+            ),
+            (
+                "GOOGLE_SCOPE_UPGRADE_COMPLETED".to_string(),
+                "SUCCESS".to_string()
+            ),
+        ]
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn scope_upgrade_rejects_unsupported_or_already_granted_scopes() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let subject = "scope-upgrade-invalid-user";
+    let user_id = user_id_for_subject(&clerk.issuer, subject);
+    let auth = format!("Bearer {}", clerk.token_for_subject(subject));
+    let app = build_test_router(store.clone(), &clerk).await;
+    let connector_id = store
+        .upsert_google_connector(
+            ConnectorAccount {
+                user_id,
+                connector_id: Uuid::new_v4(),
+                account_key: GOOGLE_ACCOUNT_KEY,
+            },
+            "refresh-token-1",
+            &required_scopes(),
+            "kms/local/alfred-refresh-token",
+            1,
+        )
+        .await
+        .expect("google connector should store");
+
+    for (scopes, expected_code) in [
+        (json!([]), "invalid_scope"),
+        (json!(["https://mail.google.com/"]), "invalid_scope"),
+        (json!([REQUIRED_GOOGLE_SCOPES[1]]), "invalid_scope"),
+    ] {
+        let response = send_json(
+            &app,
+            request(
+                Method::POST,
+                &format!("/v1/connectors/{connector_id}/scopes/upgrade"),
+                Some(&auth),
+                Some(json!({
+                    "redirect_uri": "alfred://oauth/google/callback",
+                    "scopes": scopes
+                })),
+            ),
+        )
+        .await;
+        assert_eq!(response.status, StatusCode::BAD_REQUEST);
+        assert_eq!(error_code(&response.body), Some(expected_code));
+    }
+
+    store
+        .merge_connector_scopes(
+            user_id,
+            connector_id,
+            &[UPGRADEABLE_GOOGLE_SCOPES[1].to_string()],
+        )
+        .await
+        .expect("scopes should merge")
+        .expect("connector should be active");
+    let already_granted = send_json(
+        &app,
+        request(
+            Method::POST,
+            &format!("/v1/connectors/{connector_id}/scopes/upgrade"),
+            Some(&auth),
+            Some(json!({
+                "redirect_uri": "alfred://oauth/google/callback",
+                "scopes": [UPGRADEABLE_GOOGLE_SCOPES[1]]
+            })),
+        ),
+    )
+    .await;
+    assert_eq!(already_granted.status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        error_code(&already_granted.body),
+        Some("scopes_already_granted")
+    );
+
+    let unknown = send_json(
+        &app,
+        request(
+            Method::POST,
+            "/v1/connectors/00000000-0000-0000-0000-000000000000/scopes/upgrade",
+            Some(&auth),
+            Some(json!({
+                "redirect_uri": "alfred://oauth/google/callback",
+                "scopes": [UPGRADEABLE_GOOGLE_SCOPES[0]]
+            })),
+        ),
+    )
+    .await;
+    assert_eq!(unknown.status, StatusCode::NOT_FOUND);
+}
+
+fn required_scopes() -> Vec<String> {
+    REQUIRED_GOOGLE_SCOPES
+        .iter()
+        .map(|scope| (*scope).to_string())
+        .collect()
+}
+
+struct JsonResponse {
+    status: StatusCode,
+    body: Value,
+}
+
+async fn send_json(app: &axum::Router, request: Request<Body>) -> JsonResponse {
+    let response = app
+        .clone()
+        .oneshot(request)
+        .await
+        .expect("request should succeed");
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("response body should read");
+    let body = serde_json::from_slice::<Value>(&body).unwrap_or_else(|_| json!({}));
+
+    JsonResponse { status, body }
+}
+
+fn request(
+    method: Method,
+    uri: &str,
+    auth_header: Option<&str>,
+    json_body: Option<Value>,
+) -> Request<Body> {
+    let mut builder = Request::builder().method(method).uri(uri);
+    if let Some(auth_header) = auth_header {
+        builder = builder.header(header::AUTHORIZATION, auth_header);
+    }
+
+    match json_body {
+        Some(body) => builder
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .expect("request should build"),
+        None => builder.body(Body::empty()).expect("request should build"),
+    }
+}
+
+fn error_code(body: &Value) -> Option<&str> {
+    body.get("error")
+        .and_then(|error| error.get("code"))
+        .and_then(Value::as_str)
+}
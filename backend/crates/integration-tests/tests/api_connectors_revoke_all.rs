@@ -0,0 +1,262 @@
+mod support;
+
+use axum::body::{Body, to_bytes};
+use axum::http::{Method, Request, StatusCode, header};
+use axum::routing::post;
+use chrono::{Duration, Utc};
+use serde_json::{Value, json};
+use serial_test::serial;
+use shared::enclave::{
+    AttestedIdentityPayload, ENCLAVE_RPC_CONTRACT_VERSION, ENCLAVE_RPC_PATH_REVOKE_GOOGLE_TOKEN,
+    EnclaveRpcErrorEnvelope, EnclaveRpcRevokeGoogleTokenRequest,
+    EnclaveRpcRevokeGoogleTokenResponse,
+};
+use shared::repos::{ConnectorAccount, JobType, LEGACY_CONNECTOR_ACCOUNT_KEY, Store};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use support::api_app::{build_test_router_with_enclave_base_url, user_id_for_subject};
+use support::clerk::TestClerkAuth;
+use support::enclave_mock::MockEnclaveServer;
+
+#[tokio::test]
+#[serial]
+async fn revoke_all_revokes_every_connector_and_cancels_departure_alerts() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let mock_enclave = MockEnclaveServer::start(axum::Router::new().route(
+        ENCLAVE_RPC_PATH_REVOKE_GOOGLE_TOKEN,
+        post(
+            |axum::Json(request): axum::Json<EnclaveRpcRevokeGoogleTokenRequest>| async move {
+                axum::Json(EnclaveRpcRevokeGoogleTokenResponse {
+                    contract_version: ENCLAVE_RPC_CONTRACT_VERSION.to_string(),
+                    request_id: request.request_id,
+                    attested_identity: AttestedIdentityPayload {
+                        runtime: "nitro".to_string(),
+                        measurement: "dev-local-enclave".to_string(),
+                    },
+                })
+            },
+        ),
+    ))
+    .await;
+
+    let clerk = TestClerkAuth::start().await;
+    let subject = "revoke-all-user";
+    let user_id = user_id_for_subject(&clerk.issuer, subject);
+    let auth = format!("Bearer {}", clerk.token_for_subject(subject));
+    let app =
+        build_test_router_with_enclave_base_url(store.clone(), &clerk, &mock_enclave.base_url)
+            .await;
+
+    let google_id = insert_google_connector(&store, user_id).await;
+    let caldav_id = store
+        .upsert_caldav_connector(
+            ConnectorAccount {
+                user_id,
+                connector_id: Uuid::new_v4(),
+                account_key: "caldav-account",
+            },
+            "{\"app_password\":\"sealed\"}",
+            "kms/local/alfred-refresh-token",
+            1,
+        )
+        .await
+        .expect("caldav connector should store");
+    let due_at = Utc::now() + Duration::hours(1);
+    let departure_job_id = store
+        .enqueue_job(user_id, JobType::DepartureAlert, due_at, None)
+        .await
+        .expect("departure alert should enqueue");
+    let automation_job_id = store
+        .enqueue_job(user_id, JobType::AutomationRun, due_at, None)
+        .await
+        .expect("automation run should enqueue");
+
+    let revoked = send_json(&app, request(Method::DELETE, "/v1/connectors", &auth)).await;
+    assert_eq!(revoked.status, StatusCode::OK);
+    assert_eq!(
+        revoked.body,
+        json!({
+            "revoked": [
+                {"connector_id": google_id.to_string(), "provider": "google"},
+                {"connector_id": caldav_id.to_string(), "provider": "caldav"},
+            ],
+            "cancelled_job_count": 1,
+        })
+    );
+
+    for connector_id in [google_id, caldav_id] {
+        assert!(
+            store
+                .get_active_connector_key_metadata(user_id, connector_id)
+                .await
+                .expect("connector lookup should succeed")
+                .is_none()
+        );
+    }
+    assert_eq!(job_state(&store, departure_job_id).await, "FAILED");
+    assert_eq!(job_state(&store, automation_job_id).await, "PENDING");
+
+    let audit = send_json(&app, request(Method::GET, "/v1/audit-events", &auth)).await;
+    let event = audit.body["items"]
+        .as_array()
+        .expect("items should be an array")
+        .iter()
+        .find(|event| event["event_type"] == "CONNECTORS_REVOKED")
+        .expect("revoke-all should be audited")
+        .clone();
+    assert_eq!(event["result"], "SUCCESS");
+    assert_eq!(
+        event["metadata"]["connector_ids"],
+        json!([google_id.to_string(), caldav_id.to_string()])
+    );
+
+    let nothing_left = send_json(&app, request(Method::DELETE, "/v1/connectors", &auth)).await;
+    assert_eq!(nothing_left.status, StatusCode::OK);
+    assert_eq!(nothing_left.body["revoked"], json!([]));
+    assert_eq!(nothing_left.body["cancelled_job_count"], 0);
+}
+
+#[tokio::test]
+#[serial]
+async fn revoke_all_stops_at_the_first_upstream_failure() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let mock_enclave = MockEnclaveServer::start(axum::Router::new().route(
+        ENCLAVE_RPC_PATH_REVOKE_GOOGLE_TOKEN,
+        post(|| async move {
+            (
+                StatusCode::BAD_REQUEST,
+                axum::Json(EnclaveRpcErrorEnvelope::new(
+                    None,
+                    "connector_token_unavailable",
+                    "connector token unavailable",
+                    false,
+                )),
+            )
+        }),
+    ))
+    .await;
+
+    let clerk = TestClerkAuth::start().await;
+    let subject = "revoke-all-failure-user";
+    let user_id = user_id_for_subject(&clerk.issuer, subject);
+    let auth = format!("Bearer {}", clerk.token_for_subject(subject));
+    let app =
+        build_test_router_with_enclave_base_url(store.clone(), &clerk, &mock_enclave.base_url)
+            .await;
+
+    let imap_id = store
+        .upsert_imap_connector(
+            ConnectorAccount {
+                user_id,
+                connector_id: Uuid::new_v4(),
+                account_key: "imap-account",
+            },
+            "{\"password\":\"sealed\"}",
+            "kms/local/alfred-refresh-token",
+            1,
+        )
+        .await
+        .expect("imap connector should store");
+    let google_id = insert_google_connector(&store, user_id).await;
+    let departure_job_id = store
+        .enqueue_job(
+            user_id,
+            JobType::DepartureAlert,
+            Utc::now() + Duration::hours(1),
+            None,
+        )
+        .await
+        .expect("departure alert should enqueue");
+
+    let failed = send_json(&app, request(Method::DELETE, "/v1/connectors", &auth)).await;
+    assert_eq!(failed.status, StatusCode::BAD_REQUEST);
+    assert_eq!(failed.body["error"]["code"], "connector_token_unavailable");
+
+    let imap_active = store
+        .get_active_connector_key_metadata(user_id, imap_id)
+        .await
+        .expect("connector lookup should succeed");
+    assert!(
+        imap_active.is_none(),
+        "connectors before the failure stay revoked"
+    );
+    let google_active = store
+        .get_active_connector_key_metadata(user_id, google_id)
+        .await
+        .expect("connector lookup should succeed");
+    assert!(google_active.is_some(), "the failed connector stays active");
+    assert_eq!(job_state(&store, departure_job_id).await, "PENDING");
+
+    let audit = send_json(&app, request(Method::GET, "/v1/audit-events", &auth)).await;
+    let event = audit.body["items"]
+        .as_array()
+        .expect("items should be an array")
+        .iter()
+        .find(|event| event["event_type"] == "CONNECTORS_REVOKED")
+        .expect("partial revoke should be audited")
+        .clone();
+    assert_eq!(event["result"], "FAILURE");
+    assert_eq!(
+        event["metadata"]["connector_ids"],
+        json!([imap_id.to_string()])
+    );
+}
+
+async fn insert_google_connector(store: &Store, user_id: Uuid) -> Uuid {
+    store
+        .upsert_google_connector(
+            ConnectorAccount {
+                user_id,
+                connector_id: Uuid::new_v4(),
+                account_key: LEGACY_CONNECTOR_ACCOUNT_KEY,
+            },
+            "refresh-token",
+            &["https://www.googleapis.com/auth/calendar.readonly".to_string()],
+            "kms/local/alfred-refresh-token",
+            1,
+        )
+        .await
+        .expect("google connector should store")
+}
+
+async fn job_state(store: &Store, job_id: Uuid) -> String {
+    sqlx::query_scalar("SELECT state FROM jobs WHERE id = $1")
+        .bind(job_id)
+        .fetch_one(store.pool())
+        .await
+        .expect("job should exist")
+}
+
+struct JsonResponse {
+    status: StatusCode,
+    body: Value,
+}
+
+async fn send_json(app: &axum::Router, request: Request<Body>) -> JsonResponse {
+    let response = app
+        .clone()
+        .oneshot(request)
+        .await
+        .expect("request should succeed");
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("response body should read");
+    let body = serde_json::from_slice::<Value>(&body).unwrap_or_else(|_| json!({}));
+
+    JsonResponse { status, body }
+}
+
+fn request(method: Method, uri: &str, auth_header: &str) -> Request<Body> {
+    Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::AUTHORIZATION, auth_header)
+        .body(Body::empty())
+        .expect("request should build")
+}
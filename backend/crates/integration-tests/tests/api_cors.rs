@@ -0,0 +1,133 @@
+mod support;
+
+use axum::body::Body;
+use axum::http::{HeaderMap, Method, Request, StatusCode, header};
+use serial_test::serial;
+use shared::config::CorsConfig;
+use tower::ServiceExt;
+
+use support::api_app::{build_test_router, build_test_router_with_cors};
+use support::clerk::TestClerkAuth;
+
+const DASHBOARD_ORIGIN: &str = "https://dashboard.alfred.test";
+
+fn dashboard_cors() -> CorsConfig {
+    CorsConfig {
+        allowed_origins: vec![DASHBOARD_ORIGIN.to_string()],
+        allowed_methods: vec!["GET".to_string()],
+        allowed_headers: vec!["authorization".to_string()],
+        max_age_seconds: 600,
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn allow_listed_origins_can_call_read_only_routes() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let auth = format!("Bearer {}", clerk.token_for_subject("cors-user"));
+    let app = build_test_router_with_cors(store.clone(), &clerk, dashboard_cors()).await;
+
+    let (status, headers) = send(&app, preflight("/v1/status", DASHBOARD_ORIGIN)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        headers[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+        DASHBOARD_ORIGIN
+    );
+    assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_METHODS], "GET");
+    assert_eq!(
+        headers[header::ACCESS_CONTROL_ALLOW_HEADERS],
+        "authorization"
+    );
+    assert_eq!(headers[header::ACCESS_CONTROL_MAX_AGE], "600");
+
+    let (status, headers) = send(&app, get("/v1/status", DASHBOARD_ORIGIN, Some(&auth))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        headers[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+        DASHBOARD_ORIGIN
+    );
+
+    let (status, headers) = send(&app, get("/v1/public/status", DASHBOARD_ORIGIN, None)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        headers[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+        DASHBOARD_ORIGIN
+    );
+
+    let (status, headers) = send(&app, get("/v1/status", DASHBOARD_ORIGIN, None)).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        headers[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+        DASHBOARD_ORIGIN
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn other_origins_and_sensitive_routes_get_no_cors_headers() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let auth = format!("Bearer {}", clerk.token_for_subject("cors-denied-user"));
+    let app = build_test_router_with_cors(store.clone(), &clerk, dashboard_cors()).await;
+
+    let (_, headers) = send(&app, preflight("/v1/status", "https://evil.example")).await;
+    assert!(headers.get(header::ACCESS_CONTROL_ALLOW_ORIGIN).is_none());
+
+    for path in [
+        "/v1/assistant/query",
+        "/v1/automations",
+        "/v1/privacy/delete-all",
+    ] {
+        let (_, headers) = send(&app, preflight(path, DASHBOARD_ORIGIN)).await;
+        assert!(
+            headers.get(header::ACCESS_CONTROL_ALLOW_ORIGIN).is_none(),
+            "{path} should not answer CORS preflights"
+        );
+    }
+
+    let default_app = build_test_router(store.clone(), &clerk).await;
+    let (status, headers) = send(
+        &default_app,
+        get("/v1/status", DASHBOARD_ORIGIN, Some(&auth)),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(headers.get(header::ACCESS_CONTROL_ALLOW_ORIGIN).is_none());
+}
+
+async fn send(app: &axum::Router, request: Request<Body>) -> (StatusCode, HeaderMap) {
+    let response = app
+        .clone()
+        .oneshot(request)
+        .await
+        .expect("request should succeed");
+    (response.status(), response.headers().clone())
+}
+
+fn preflight(uri: &str, origin: &str) -> Request<Body> {
+    Request::builder()
+        .method(Method::OPTIONS)
+        .uri(uri)
+        .header(header::ORIGIN, origin)
+        .header(header::ACCESS_CONTROL_REQUEST_METHOD, "GET")
+        .header(header::ACCESS_CONTROL_REQUEST_HEADERS, "authorization")
+        .body(Body::empty())
+        .expect("request should build")
+}
+
+fn get(uri: &str, origin: &str, auth_header: Option<&str>) -> Request<Body> {
+    let mut builder = Request::builder()
+        .method(Method::GET)
+        .uri(uri)
+        .header(header::ORIGIN, origin);
+    if let Some(auth_header) = auth_header {
+        builder = builder.header(header::AUTHORIZATION, auth_header);
+    }
+
+    builder.body(Body::empty()).expect("request should build")
+}
@@ -0,0 +1,259 @@
+mod support;
+
+use axum::body::{Body, to_bytes};
+use axum::http::{Method, Request, StatusCode, header};
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use serde_json::{Value, json};
+use serial_test::serial;
+use tower::ServiceExt;
+
+use support::api_app::build_test_router;
+use support::clerk::TestClerkAuth;
+
+#[tokio::test]
+#[serial]
+async fn departure_preferences_default_then_update() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let auth = format!("Bearer {}", clerk.token_for_subject("departure-owner"));
+    let app = build_test_router(store, &clerk).await;
+
+    let defaults = send_json(
+        &app,
+        request(
+            Method::GET,
+            "/v1/departure-alerts/preferences",
+            Some(&auth),
+            None,
+        ),
+    )
+    .await;
+    assert_eq!(defaults.status, StatusCode::OK);
+    assert_eq!(
+        defaults.body.get("enabled").and_then(Value::as_bool),
+        Some(false)
+    );
+    assert_eq!(
+        defaults.body.get("check_time").and_then(Value::as_str),
+        Some("06:00")
+    );
+
+    let updated = send_json(
+        &app,
+        request(
+            Method::PUT,
+            "/v1/departure-alerts/preferences",
+            Some(&auth),
+            Some(json!({
+                "enabled": true,
+                "time_zone": "Europe/London",
+                "check_time": "06:30",
+                "travel_minutes": 35,
+                "buffer_minutes": 10,
+                "home_location_envelope": home_location_envelope()
+            })),
+        ),
+    )
+    .await;
+    assert_eq!(updated.status, StatusCode::OK);
+    assert_eq!(
+        updated
+            .body
+            .get("has_home_location")
+            .and_then(Value::as_bool),
+        Some(true)
+    );
+    assert_eq!(
+        updated.body.get("check_time").and_then(Value::as_str),
+        Some("06:30")
+    );
+    assert!(
+        updated
+            .body
+            .get("next_check_at")
+            .is_some_and(|value| value.is_string()),
+        "enabling should schedule an immediate check"
+    );
+
+    let fetched = send_json(
+        &app,
+        request(
+            Method::GET,
+            "/v1/departure-alerts/preferences",
+            Some(&auth),
+            None,
+        ),
+    )
+    .await;
+    assert_eq!(fetched.status, StatusCode::OK);
+    assert_eq!(
+        fetched.body.get("travel_minutes").and_then(Value::as_u64),
+        Some(35)
+    );
+    assert_eq!(
+        fetched.body.get("time_zone").and_then(Value::as_str),
+        Some("Europe/London")
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn departure_preferences_reject_invalid_values() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let auth = format!("Bearer {}", clerk.token_for_subject("departure-owner"));
+    let app = build_test_router(store, &clerk).await;
+
+    for (payload, expected_code) in [
+        (
+            preferences_payload("Mars/Base", "06:00", 30),
+            "invalid_time_zone",
+        ),
+        (preferences_payload("UTC", "6am", 30), "invalid_check_time"),
+        (
+            preferences_payload("UTC", "06:00", 0),
+            "invalid_departure_minutes",
+        ),
+    ] {
+        let response = send_json(
+            &app,
+            request(
+                Method::PUT,
+                "/v1/departure-alerts/preferences",
+                Some(&auth),
+                Some(payload),
+            ),
+        )
+        .await;
+        assert_eq!(response.status, StatusCode::BAD_REQUEST);
+        assert_eq!(error_code(&response.body), Some(expected_code));
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn departure_preferences_reject_stale_version() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let auth = format!("Bearer {}", clerk.token_for_subject("departure-owner"));
+    let app = build_test_router(store, &clerk).await;
+
+    let mut first_payload = preferences_payload("UTC", "06:00", 30);
+    first_payload["expected_version"] = json!(0);
+    let first = send_json(
+        &app,
+        request(
+            Method::PUT,
+            "/v1/departure-alerts/preferences",
+            Some(&auth),
+            Some(first_payload),
+        ),
+    )
+    .await;
+    assert_eq!(first.status, StatusCode::OK);
+    assert_eq!(first.body.get("version").and_then(Value::as_i64), Some(1));
+
+    let mut stale = request(
+        Method::PUT,
+        "/v1/departure-alerts/preferences",
+        Some(&auth),
+        Some(preferences_payload("UTC", "07:15", 20)),
+    );
+    stale
+        .headers_mut()
+        .insert(header::IF_MATCH, "\"0\"".parse().expect("valid header"));
+    let stale = send_json(&app, stale).await;
+    assert_eq!(stale.status, StatusCode::CONFLICT);
+    assert_eq!(error_code(&stale.body), Some("version_conflict"));
+    assert_eq!(stale.body["current"]["check_time"].as_str(), Some("06:00"));
+    assert_eq!(stale.body["current"]["version"].as_i64(), Some(1));
+
+    let mut current_payload = preferences_payload("UTC", "07:15", 20);
+    current_payload["expected_version"] = json!(1);
+    let current = send_json(
+        &app,
+        request(
+            Method::PUT,
+            "/v1/departure-alerts/preferences",
+            Some(&auth),
+            Some(current_payload),
+        ),
+    )
+    .await;
+    assert_eq!(current.status, StatusCode::OK);
+    assert_eq!(current.body.get("version").and_then(Value::as_i64), Some(2));
+}
+
+fn preferences_payload(time_zone: &str, check_time: &str, travel_minutes: u16) -> Value {
+    json!({
+        "enabled": true,
+        "time_zone": time_zone,
+        "check_time": check_time,
+        "travel_minutes": travel_minutes,
+        "buffer_minutes": 5
+    })
+}
+
+fn home_location_envelope() -> Value {
+    json!({
+        "version": "v1",
+        "algorithm": "x25519-chacha20poly1305",
+        "key_id": "assistant-ingress-v1",
+        "request_id": "home-location",
+        "client_ephemeral_public_key": STANDARD.encode([7_u8; 32]),
+        "nonce": STANDARD.encode([9_u8; 12]),
+        "ciphertext": STANDARD.encode(b"encrypted-home-location")
+    })
+}
+
+struct JsonResponse {
+    status: StatusCode,
+    body: Value,
+}
+
+async fn send_json(app: &axum::Router, request: Request<Body>) -> JsonResponse {
+    let response = app
+        .clone()
+        .oneshot(request)
+        .await
+        .expect("request should succeed");
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("response body should read");
+    let body = serde_json::from_slice::<Value>(&body).unwrap_or_else(|_| json!({}));
+
+    JsonResponse { status, body }
+}
+
+fn request(
+    method: Method,
+    uri: &str,
+    auth_header: Option<&str>,
+    json_body: Option<Value>,
+) -> Request<Body> {
+    let mut builder = Request::builder().method(method).uri(uri);
+    if let Some(auth_header) = auth_header {
+        builder = builder.header(header::AUTHORIZATION, auth_header);
+    }
+
+    match json_body {
+        Some(body) => builder
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .expect("request should build"),
+        None => builder.body(Body::empty()).expect("request should build"),
+    }
+}
+
+fn error_code(body: &Value) -> Option<&str> {
+    body.get("error")
+        .and_then(|error| error.get("code"))
+        .and_then(Value::as_str)
+}
@@ -0,0 +1,326 @@
+mod support;
+
+use axum::body::{Body, to_bytes};
+use axum::http::{Method, Request, StatusCode, header};
+use serde_json::{Value, json};
+use serial_test::serial;
+use shared::models::ApnsEnvironment;
+use tower::ServiceExt;
+
+use support::api_app::{build_test_router, user_id_for_subject};
+use support::clerk::TestClerkAuth;
+
+#[tokio::test]
+#[serial]
+async fn devices_can_be_listed_and_unregistered() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let subject = "device-management-user";
+    let user_id = user_id_for_subject(&clerk.issuer, subject);
+    let auth = format!("Bearer {}", clerk.token_for_subject(subject));
+    let app = build_test_router(store.clone(), &clerk).await;
+
+    for (device_id, environment) in [
+        ("device-1", ApnsEnvironment::Sandbox),
+        ("device-2", ApnsEnvironment::Production),
+    ] {
+        store
+            .register_device(user_id, device_id, "apns-token", &environment, None, None)
+            .await
+            .expect("device registration should succeed");
+    }
+    store
+        .record_device_delivery(user_id, "device-1")
+        .await
+        .expect("delivery should record");
+
+    let listed = send_json(&app, request(Method::GET, "/v1/devices", &auth)).await;
+    assert_eq!(listed.status, StatusCode::OK);
+    let items = listed.body["items"]
+        .as_array()
+        .expect("items should be an array");
+    assert_eq!(items.len(), 2);
+    let device = |device_id: &str| {
+        items
+            .iter()
+            .find(|item| item["device_id"] == device_id)
+            .unwrap_or_else(|| panic!("{device_id} should be listed"))
+    };
+    assert_eq!(device("device-1")["environment"], "sandbox");
+    assert!(device("device-1")["last_delivery_at"].is_string());
+    assert!(device("device-1")["last_seen_at"].is_string());
+    assert_eq!(device("device-2")["environment"], "production");
+    assert!(device("device-2")["last_delivery_at"].is_null());
+    assert!(device("device-2").get("apns_token").is_none());
+
+    store
+        .record_device_delivery_failure(user_id, "device-2", "APNS_UNREGISTERED", 3, true)
+        .await
+        .expect("failure should record");
+    let disabled = send_json(&app, request(Method::GET, "/v1/devices", &auth)).await;
+    let disabled_device = disabled.body["items"]
+        .as_array()
+        .and_then(|items| items.iter().find(|item| item["device_id"] == "device-2"))
+        .expect("device-2 should be listed");
+    assert!(disabled_device["disabled_at"].is_string());
+    assert_eq!(disabled_device["disabled_reason"], "APNS_UNREGISTERED");
+
+    let enabled = send_json(
+        &app,
+        request(Method::POST, "/v1/devices/device-2/enable", &auth),
+    )
+    .await;
+    assert_eq!(enabled.status, StatusCode::OK);
+    assert!(
+        store
+            .has_registered_device(user_id)
+            .await
+            .expect("device lookup should succeed")
+    );
+    let missing_enable = send_json(
+        &app,
+        request(Method::POST, "/v1/devices/unknown/enable", &auth),
+    )
+    .await;
+    assert_eq!(missing_enable.status, StatusCode::NOT_FOUND);
+
+    let deleted = send_json(&app, request(Method::DELETE, "/v1/devices/device-1", &auth)).await;
+    assert_eq!(deleted.status, StatusCode::OK);
+    assert_eq!(deleted.body["ok"], true);
+
+    let missing = send_json(&app, request(Method::DELETE, "/v1/devices/device-1", &auth)).await;
+    assert_eq!(missing.status, StatusCode::NOT_FOUND);
+    assert_eq!(missing.body["error"]["code"], "not_found");
+
+    let remaining = store
+        .list_devices(user_id)
+        .await
+        .expect("device listing should succeed");
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0].device_id, "device-2");
+
+    let audit_count: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM audit_events WHERE user_id = $1 AND event_type = 'DEVICE_UNREGISTERED'",
+    )
+    .bind(user_id)
+    .fetch_one(store.pool())
+    .await
+    .expect("audit count should load");
+    assert_eq!(audit_count, 1);
+}
+
+#[tokio::test]
+#[serial]
+async fn devices_of_other_users_cannot_be_unregistered() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let owner_id = user_id_for_subject(&clerk.issuer, "device-owner");
+    let auth = format!("Bearer {}", clerk.token_for_subject("device-intruder"));
+    let app = build_test_router(store.clone(), &clerk).await;
+
+    store
+        .register_device(
+            owner_id,
+            "device-1",
+            "apns-token",
+            &ApnsEnvironment::Sandbox,
+            None,
+            None,
+        )
+        .await
+        .expect("device registration should succeed");
+
+    let listed = send_json(&app, request(Method::GET, "/v1/devices", &auth)).await;
+    assert_eq!(listed.status, StatusCode::OK);
+    assert_eq!(listed.body["items"], json!([]));
+
+    let deleted = send_json(&app, request(Method::DELETE, "/v1/devices/device-1", &auth)).await;
+    assert_eq!(deleted.status, StatusCode::NOT_FOUND);
+    assert_eq!(
+        store
+            .list_devices(owner_id)
+            .await
+            .expect("device listing should succeed")
+            .len(),
+        1
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn device_registration_records_app_bundle_id() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let subject = "device-bundle-user";
+    let user_id = user_id_for_subject(&clerk.issuer, subject);
+    let auth = format!("Bearer {}", clerk.token_for_subject(subject));
+    let app = build_test_router(store.clone(), &clerk).await;
+
+    let registered = send_json(
+        &app,
+        json_request(
+            Method::POST,
+            "/v1/devices/apns",
+            &auth,
+            json!({
+                "device_id": "beta-device",
+                "apns_token": "apns-token",
+                "environment": "sandbox",
+                "app_bundle_id": "com.example.alfred.beta"
+            }),
+        ),
+    )
+    .await;
+    assert_eq!(registered.status, StatusCode::OK);
+
+    let invalid = send_json(
+        &app,
+        json_request(
+            Method::POST,
+            "/v1/devices/apns",
+            &auth,
+            json!({
+                "device_id": "bad-device",
+                "apns_token": "apns-token",
+                "environment": "sandbox",
+                "app_bundle_id": "com..alfred beta"
+            }),
+        ),
+    )
+    .await;
+    assert_eq!(invalid.status, StatusCode::BAD_REQUEST);
+    assert_eq!(invalid.body["error"]["code"], "invalid_app_bundle_id");
+
+    let listed = send_json(&app, request(Method::GET, "/v1/devices", &auth)).await;
+    assert_eq!(listed.body["items"].as_array().map(Vec::len), Some(1));
+    assert_eq!(
+        listed.body["items"][0]["app_bundle_id"],
+        "com.example.alfred.beta"
+    );
+
+    let registrations = store
+        .list_registered_devices(user_id)
+        .await
+        .expect("registered devices should load");
+    assert_eq!(
+        registrations[0].app_bundle_id.as_deref(),
+        Some("com.example.alfred.beta")
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn test_notifications_are_queued_per_job_type_and_deduplicated_by_request_id() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let subject = "device-test-notification-user";
+    let user_id = user_id_for_subject(&clerk.issuer, subject);
+    let auth = format!("Bearer {}", clerk.token_for_subject(subject));
+    let app = build_test_router(store.clone(), &clerk).await;
+    store
+        .register_device(
+            user_id,
+            "qa-device",
+            "apns-token",
+            &ApnsEnvironment::Sandbox,
+            None,
+            None,
+        )
+        .await
+        .expect("device registration should succeed");
+
+    let test_request = || {
+        let mut request = json_request(
+            Method::POST,
+            "/v1/devices/apns/test",
+            &auth,
+            json!({
+                "job_type": "DEPARTURE_ALERT",
+                "simulated_payload": {"failure": "transient"}
+            }),
+        );
+        request
+            .headers_mut()
+            .insert("x-request-id", "qa-departure-alert-1".parse().unwrap());
+        request
+    };
+
+    let queued = send_json(&app, test_request()).await;
+    assert_eq!(queued.status, StatusCode::OK);
+    assert_eq!(queued.body["job_type"], "DEPARTURE_ALERT");
+    assert_eq!(queued.body["status"], "QUEUED");
+    let job_id = queued.body["queued_job_id"]
+        .as_str()
+        .expect("job id should be a string")
+        .to_string();
+
+    let resent = send_json(&app, test_request()).await;
+    assert_eq!(resent.status, StatusCode::OK);
+    assert_eq!(resent.body["queued_job_id"], job_id.as_str());
+
+    let job_types: Vec<String> = sqlx::query_scalar("SELECT type FROM jobs WHERE user_id = $1")
+        .bind(user_id)
+        .fetch_all(store.pool())
+        .await
+        .expect("jobs should load");
+    assert_eq!(job_types, vec!["DEPARTURE_ALERT".to_string()]);
+
+    let unknown_job_type = send_json(
+        &app,
+        json_request(
+            Method::POST,
+            "/v1/devices/apns/test",
+            &auth,
+            json!({"job_type": "MEETING_REMINDER"}),
+        ),
+    )
+    .await;
+    assert_eq!(unknown_job_type.status, StatusCode::BAD_REQUEST);
+}
+
+struct JsonResponse {
+    status: StatusCode,
+    body: Value,
+}
+
+async fn send_json(app: &axum::Router, request: Request<Body>) -> JsonResponse {
+    let response = app
+        .clone()
+        .oneshot(request)
+        .await
+        .expect("request should succeed");
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("response body should read");
+    let body = serde_json::from_slice::<Value>(&body).unwrap_or_else(|_| json!({}));
+
+    JsonResponse { status, body }
+}
+
+fn request(method: Method, uri: &str, auth_header: &str) -> Request<Body> {
+    Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::AUTHORIZATION, auth_header)
+        .body(Body::empty())
+        .expect("request should build")
+}
+
+fn json_request(method: Method, uri: &str, auth_header: &str, body: Value) -> Request<Body> {
+    Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::AUTHORIZATION, auth_header)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .expect("request should build")
+}
@@ -0,0 +1,212 @@
+mod support;
+
+use axum::body::{Body, to_bytes};
+use axum::http::{Method, Request, StatusCode, header};
+use serde_json::{Value, json};
+use serial_test::serial;
+use shared::feature_flags::{FeatureFlag, FeatureFlagDefaults, FeatureFlags};
+use tower::ServiceExt;
+
+use support::api_app::{
+    TEST_ADMIN_API_TOKEN, build_test_router, build_test_router_with_feature_flags,
+    user_id_for_subject,
+};
+use support::clerk::TestClerkAuth;
+
+#[tokio::test]
+#[serial]
+async fn admin_overrides_flip_flags_for_listed_users_without_a_restart() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let flags = FeatureFlags::new(FeatureFlagDefaults::default())
+        .with_redis_from_config(&support::test_redis_url())
+        .await;
+    for flag in FeatureFlag::ALL {
+        flags
+            .clear_override(flag)
+            .await
+            .expect("redis should be reachable");
+    }
+    let app = build_test_router_with_feature_flags(store, &clerk, flags).await;
+    let admin_auth = format!("Bearer {TEST_ADMIN_API_TOKEN}");
+    let beta_auth = format!("Bearer {}", clerk.token_for_subject("flag-beta"));
+    let other_auth = format!("Bearer {}", clerk.token_for_subject("flag-other"));
+    let beta_user_id = user_id_for_subject(&clerk.issuer, "flag-beta");
+
+    let listed = send_json(
+        &app,
+        request(Method::GET, "/admin/v1/feature-flags", &admin_auth, None),
+    )
+    .await;
+    assert_eq!(listed.status, StatusCode::OK);
+    assert_eq!(
+        listed.body["items"][0],
+        json!({
+            "flag": "small_talk_fast_path",
+            "rule": {"enabled": true, "rollout_percent": 100, "user_ids": []},
+            "default_rule": {"enabled": true, "rollout_percent": 100, "user_ids": []},
+            "overridden": false,
+        })
+    );
+
+    let user_flags = send_json(
+        &app,
+        request(Method::GET, "/v1/feature-flags", &beta_auth, None),
+    )
+    .await;
+    assert_eq!(user_flags.status, StatusCode::OK);
+    assert_eq!(
+        user_flags.body["items"],
+        json!([{"flag": "assistant_streaming", "enabled": false}])
+    );
+
+    let set = send_json(
+        &app,
+        request(
+            Method::PUT,
+            "/admin/v1/feature-flags/assistant_streaming",
+            &admin_auth,
+            Some(json!({"enabled": true, "rollout_percent": 0, "user_ids": [beta_user_id]})),
+        ),
+    )
+    .await;
+    assert_eq!(set.status, StatusCode::OK);
+    assert_eq!(set.body["overridden"], true);
+    assert_eq!(set.body["rule"]["user_ids"], json!([beta_user_id]));
+    assert_eq!(set.body["default_rule"]["enabled"], false);
+
+    assert!(streaming_enabled(&app, &beta_auth).await);
+    assert!(!streaming_enabled(&app, &other_auth).await);
+
+    let cleared = send_json(
+        &app,
+        request(
+            Method::DELETE,
+            "/admin/v1/feature-flags/assistant_streaming",
+            &admin_auth,
+            None,
+        ),
+    )
+    .await;
+    assert_eq!(cleared.status, StatusCode::OK);
+    assert_eq!(cleared.body["overridden"], false);
+    assert!(!streaming_enabled(&app, &beta_auth).await);
+
+    let invalid = send_json(
+        &app,
+        request(
+            Method::PUT,
+            "/admin/v1/feature-flags/assistant_streaming",
+            &admin_auth,
+            Some(json!({"enabled": true, "rollout_percent": 101})),
+        ),
+    )
+    .await;
+    assert_eq!(invalid.status, StatusCode::BAD_REQUEST);
+    assert_eq!(invalid.body["error"]["code"], "invalid_feature_flag_rule");
+
+    let unknown = send_json(
+        &app,
+        request(
+            Method::PUT,
+            "/admin/v1/feature-flags/not_a_flag",
+            &admin_auth,
+            Some(json!({"enabled": true})),
+        ),
+    )
+    .await;
+    assert_eq!(unknown.status, StatusCode::NOT_FOUND);
+    assert_eq!(unknown.body["error"]["code"], "feature_flag_not_found");
+
+    let rejected = send_json(
+        &app,
+        request(
+            Method::PUT,
+            "/admin/v1/feature-flags/assistant_streaming",
+            &beta_auth,
+            Some(json!({"enabled": true})),
+        ),
+    )
+    .await;
+    assert_eq!(rejected.status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+#[serial]
+async fn overrides_are_unavailable_without_redis() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let app = build_test_router(store, &clerk).await;
+    let admin_auth = format!("Bearer {TEST_ADMIN_API_TOKEN}");
+
+    let listed = send_json(
+        &app,
+        request(Method::GET, "/admin/v1/feature-flags", &admin_auth, None),
+    )
+    .await;
+    assert_eq!(listed.status, StatusCode::OK);
+
+    let set = send_json(
+        &app,
+        request(
+            Method::PUT,
+            "/admin/v1/feature-flags/assistant_streaming",
+            &admin_auth,
+            Some(json!({"enabled": true})),
+        ),
+    )
+    .await;
+    assert_eq!(set.status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(set.body["error"]["code"], "feature_flag_store_unavailable");
+    assert_eq!(set.body["error"]["retryable"], true);
+}
+
+async fn streaming_enabled(app: &axum::Router, auth_header: &str) -> bool {
+    let response = send_json(
+        app,
+        request(Method::GET, "/v1/feature-flags", auth_header, None),
+    )
+    .await;
+    assert_eq!(response.status, StatusCode::OK);
+    response.body["items"][0]["enabled"]
+        .as_bool()
+        .expect("evaluation should be a bool")
+}
+
+struct JsonResponse {
+    status: StatusCode,
+    body: Value,
+}
+
+async fn send_json(app: &axum::Router, request: Request<Body>) -> JsonResponse {
+    let response = app
+        .clone()
+        .oneshot(request)
+        .await
+        .expect("request should succeed");
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("response body should read");
+    let body = serde_json::from_slice::<Value>(&body).unwrap_or_else(|_| json!({}));
+
+    JsonResponse { status, body }
+}
+
+fn request(method: Method, uri: &str, auth_header: &str, body: Option<Value>) -> Request<Body> {
+    let builder = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::AUTHORIZATION, auth_header);
+    match body {
+        Some(body) => builder
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string())),
+        None => builder.body(Body::empty()),
+    }
+    .expect("request should build")
+}
@@ -0,0 +1,89 @@
+mod support;
+
+use axum::body::{Body, to_bytes};
+use axum::http::{Method, Request, StatusCode, header};
+use chrono::Utc;
+use serde_json::Value;
+use serial_test::serial;
+use shared::models::UserPlan;
+use shared::quota::{PlanQuotas, llm_spend_month_reset, llm_spend_month_start};
+use tower::ServiceExt;
+
+use support::api_app::{build_test_router, user_id_for_subject};
+use support::clerk::TestClerkAuth;
+
+#[tokio::test]
+#[serial]
+async fn llm_usage_reports_monthly_spend_against_the_plan_budget() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let app = build_test_router(store.clone(), &clerk).await;
+    let auth = format!("Bearer {}", clerk.token_for_subject("llm-usage-user"));
+    let user_id = user_id_for_subject(&clerk.issuer, "llm-usage-user");
+    let free_limit = PlanQuotas::for_plan(UserPlan::Free).llm_spend_micros_per_month;
+
+    let (status, body) = get_usage(&app, &auth).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["plan"], "free");
+    assert_eq!(body["limit_usd_micros"], free_limit);
+    assert_eq!(body["spent_usd_micros"], 0);
+    assert_eq!(body["remaining_usd_micros"], free_limit);
+
+    let now = Utc::now();
+    store
+        .add_llm_monthly_spend_micros(user_id, llm_spend_month_start(now), 300_000)
+        .await
+        .expect("spend should record");
+
+    let (status, body) = get_usage(&app, &auth).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["spent_usd_micros"], 300_000);
+    assert_eq!(body["remaining_usd_micros"], free_limit - 300_000);
+    assert_eq!(
+        body["month_start"],
+        llm_spend_month_start(now).to_string().as_str()
+    );
+    let resets_at = body["resets_at"]
+        .as_str()
+        .and_then(|value| chrono::DateTime::parse_from_rfc3339(value).ok())
+        .expect("resets_at should be a timestamp");
+    assert_eq!(resets_at, llm_spend_month_reset(now));
+
+    let unauthenticated = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/v1/usage/llm")
+                .body(Body::empty())
+                .expect("request should build"),
+        )
+        .await
+        .expect("request should succeed");
+    assert_eq!(unauthenticated.status(), StatusCode::UNAUTHORIZED);
+}
+
+async fn get_usage(app: &axum::Router, auth_header: &str) -> (StatusCode, Value) {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method(Method::GET)
+                .uri("/v1/usage/llm")
+                .header(header::AUTHORIZATION, auth_header)
+                .body(Body::empty())
+                .expect("request should build"),
+        )
+        .await
+        .expect("request should succeed");
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("response body should read");
+
+    (
+        status,
+        serde_json::from_slice(&body).expect("response should be json"),
+    )
+}
@@ -12,7 +12,9 @@ use shared::enclave::{
     ENCLAVE_RPC_PATH_REVOKE_GOOGLE_TOKEN, EnclaveRpcErrorEnvelope,
     EnclaveRpcFetchAssistantAttestedKeyRequest, EnclaveRpcFetchAssistantAttestedKeyResponse,
 };
+use shared::repos::{ConnectorAccount, LEGACY_CONNECTOR_ACCOUNT_KEY};
 use tower::ServiceExt;
+use uuid::Uuid;
 
 use support::api_app::{
     build_test_router, build_test_router_with_enclave_base_url, oauth_redirect_uri,
@@ -129,7 +131,11 @@ async fn revoke_fails_closed_when_enclave_reports_connector_token_unavailable()
 
     let connector_id = store
         .upsert_google_connector(
-            user_id,
+            ConnectorAccount {
+                user_id,
+                connector_id: Uuid::new_v4(),
+                account_key: LEGACY_CONNECTOR_ACCOUNT_KEY,
+            },
             "refresh-token",
             &["https://www.googleapis.com/auth/calendar.readonly".to_string()],
             "kms/local/alfred-refresh-token",
@@ -192,6 +198,7 @@ async fn assistant_attested_key_fails_closed_on_nonce_or_request_mismatch() {
                             public_key: "AA==".to_string(),
                             key_expires_at: request.expires_at + 60,
                             signature: None,
+                            rotation: None,
                         })
                     },
                 ),
@@ -0,0 +1,48 @@
+mod support;
+
+use axum::body::{Body, to_bytes};
+use axum::http::{Method, Request, StatusCode};
+use serde_json::Value;
+use serial_test::serial;
+use tower::ServiceExt;
+
+use support::api_app::build_test_router;
+use support::clerk::TestClerkAuth;
+
+#[tokio::test]
+#[serial]
+async fn openapi_document_is_served_without_auth() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let app = build_test_router(store, &clerk).await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::GET)
+                .uri("/openapi.json")
+                .body(Body::empty())
+                .expect("request should build"),
+        )
+        .await
+        .expect("request should succeed");
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("response body should read");
+    let document = serde_json::from_slice::<Value>(&body).expect("body should be JSON");
+
+    assert_eq!(document["openapi"], "3.0.3");
+    assert_eq!(
+        document["paths"]["/v1/devices"]["get"]["operationId"],
+        "listDevices"
+    );
+    assert_eq!(
+        document["paths"]["/v1/privacy/export/{request_id}/download"]["post"]["security"],
+        serde_json::json!([])
+    );
+    assert!(document["components"]["schemas"]["ListDevicesResponse"].is_object());
+}
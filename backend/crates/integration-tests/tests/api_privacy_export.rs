@@ -0,0 +1,245 @@
+mod support;
+
+use std::collections::HashMap;
+
+use axum::body::{Body, to_bytes};
+use axum::http::{Method, Request, StatusCode, header};
+use chrono::{Duration, Utc};
+use serde_json::{Value, json};
+use serial_test::serial;
+use shared::models::ApnsEnvironment;
+use shared::repos::AuditResult;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use support::api_app::{build_test_router, user_id_for_subject};
+use support::clerk::TestClerkAuth;
+
+#[tokio::test]
+#[serial]
+async fn export_archive_is_built_and_downloadable_once() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let subject = "privacy-export-user";
+    let user_id = user_id_for_subject(&clerk.issuer, subject);
+    let auth = format!("Bearer {}", clerk.token_for_subject(subject));
+    let app = build_test_router(store.clone(), &clerk).await;
+
+    store
+        .register_device(
+            user_id,
+            "device-1",
+            "apns-secret-token",
+            &ApnsEnvironment::Sandbox,
+            None,
+            None,
+        )
+        .await
+        .expect("device registration should succeed");
+    store
+        .add_audit_event(
+            user_id,
+            "CONNECTOR_SYNC_RETRIED",
+            Some("google"),
+            AuditResult::Success,
+            &HashMap::from([("attempt".to_string(), json!(3))]),
+        )
+        .await
+        .expect("audit event should store");
+
+    let queued = send_json(
+        &app,
+        request(Method::POST, "/v1/privacy/export", Some(&auth)),
+    )
+    .await;
+    assert_eq!(queued.status, StatusCode::OK);
+    assert_eq!(queued.body["status"], "QUEUED");
+    let request_id = queued.body["request_id"]
+        .as_str()
+        .expect("request id should be returned")
+        .to_string();
+
+    let requeued = send_json(
+        &app,
+        request(Method::POST, "/v1/privacy/export", Some(&auth)),
+    )
+    .await;
+    assert_eq!(requeued.body["request_id"], request_id.as_str());
+
+    let status_uri = format!("/v1/privacy/export/{request_id}");
+    let pending = send_json(&app, request(Method::GET, &status_uri, Some(&auth))).await;
+    assert_eq!(pending.status, StatusCode::OK);
+    assert_eq!(pending.body["status"], "QUEUED");
+    assert!(pending.body["download_url"].is_null());
+    assert!(pending.body["download_token"].is_null());
+
+    complete_pending_exports(&store).await;
+
+    let ready = send_json(&app, request(Method::GET, &status_uri, Some(&auth))).await;
+    assert_eq!(ready.body["status"], "COMPLETED");
+    assert!(ready.body["expires_at"].is_string());
+    let stale_token = download_token(&ready);
+
+    // Polling again replaces the token, so the earlier one stops working.
+    let refreshed = send_json(&app, request(Method::GET, &status_uri, Some(&auth))).await;
+    let download_url = refreshed.body["download_url"]
+        .as_str()
+        .expect("download url should be issued")
+        .to_string();
+    assert_eq!(
+        download_url,
+        format!("/v1/privacy/export/{request_id}/download")
+    );
+    let token = download_token(&refreshed);
+    assert_ne!(token, stale_token);
+    let stale = send_json(&app, download_request(&download_url, &stale_token)).await;
+    assert_eq!(stale.status, StatusCode::NOT_FOUND);
+
+    // The token is only accepted in the body, never in the query string.
+    let queried = send_json(
+        &app,
+        request(Method::POST, &format!("{download_url}?token={token}"), None),
+    )
+    .await;
+    assert_ne!(queried.status, StatusCode::OK);
+
+    let downloaded = send_json(&app, download_request(&download_url, &token)).await;
+    assert_eq!(downloaded.status, StatusCode::OK);
+    assert_eq!(downloaded.body["format_version"], 1);
+    let data = &downloaded.body["data"];
+    assert_eq!(data["account"]["user_id"], json!(user_id));
+    assert_eq!(data["devices"][0]["device_id"], "device-1");
+    assert!(!downloaded.body.to_string().contains("apns-secret-token"));
+    assert!(
+        data["audit_events"]
+            .as_array()
+            .expect("audit events should be an array")
+            .iter()
+            .any(|event| event["event_type"] == "PRIVACY_EXPORT_REQUESTED")
+    );
+    let retried = data["audit_events"]
+        .as_array()
+        .expect("audit events should be an array")
+        .iter()
+        .find(|event| event["event_type"] == "CONNECTOR_SYNC_RETRIED")
+        .expect("the seeded audit event should be exported");
+    assert_eq!(retried["metadata"]["attempt"], json!(3));
+
+    let replay = send_json(&app, download_request(&download_url, &token)).await;
+    assert_eq!(replay.status, StatusCode::NOT_FOUND);
+
+    let after = send_json(&app, request(Method::GET, &status_uri, Some(&auth))).await;
+    assert!(after.body["downloaded_at"].is_string());
+    assert!(after.body["download_url"].is_null());
+    assert!(after.body["download_token"].is_null());
+
+    let other_auth = format!("Bearer {}", clerk.token_for_subject("privacy-export-other"));
+    let foreign = send_json(&app, request(Method::GET, &status_uri, Some(&other_auth))).await;
+    assert_eq!(foreign.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+#[serial]
+async fn expired_export_archives_are_purged() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let user_id = Uuid::new_v4();
+    let request_id = store
+        .queue_privacy_export(user_id)
+        .await
+        .expect("export should queue");
+    complete_pending_exports(&store).await;
+
+    let purged = store
+        .purge_expired_export_archives_batch(Utc::now() + Duration::days(2), 10)
+        .await
+        .expect("purge should succeed");
+    assert_eq!(purged, 1);
+
+    let status = store
+        .get_export_request_status(user_id, request_id)
+        .await
+        .expect("status lookup should succeed")
+        .expect("export request should exist");
+    assert_eq!(status.status.as_str(), "EXPIRED");
+    assert!(!status.archive_available);
+}
+
+/// Mirrors the worker's export pass: claim queued requests and store their archives.
+async fn complete_pending_exports(store: &shared::repos::Store) {
+    let worker_id = Uuid::new_v4();
+    let now = Utc::now();
+    let claimed = store
+        .claim_export_requests(now, worker_id, 10, 60)
+        .await
+        .expect("claim should succeed");
+    assert!(!claimed.is_empty());
+
+    for request in claimed {
+        let data = store
+            .collect_user_export_data(request.user_id)
+            .await
+            .expect("export data should collect");
+        let archive = serde_json::to_vec(&json!({ "format_version": 1, "data": data }))
+            .expect("archive should serialize");
+        assert!(
+            store
+                .mark_export_request_completed(
+                    request.id,
+                    worker_id,
+                    &archive,
+                    now,
+                    now + Duration::hours(24),
+                )
+                .await
+                .expect("completion should succeed")
+        );
+    }
+}
+
+struct JsonResponse {
+    status: StatusCode,
+    body: Value,
+}
+
+async fn send_json(app: &axum::Router, request: Request<Body>) -> JsonResponse {
+    let response = app
+        .clone()
+        .oneshot(request)
+        .await
+        .expect("request should succeed");
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("response body should read");
+    let body = serde_json::from_slice::<Value>(&body).unwrap_or_else(|_| json!({}));
+
+    JsonResponse { status, body }
+}
+
+fn download_token(status: &JsonResponse) -> String {
+    status.body["download_token"]
+        .as_str()
+        .expect("download token should be issued")
+        .to_string()
+}
+
+fn download_request(uri: &str, token: &str) -> Request<Body> {
+    Request::builder()
+        .method(Method::POST)
+        .uri(uri)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(json!({ "token": token }).to_string()))
+        .expect("request should build")
+}
+
+fn request(method: Method, uri: &str, auth_header: Option<&str>) -> Request<Body> {
+    let mut builder = Request::builder().method(method).uri(uri);
+    if let Some(auth_header) = auth_header {
+        builder = builder.header(header::AUTHORIZATION, auth_header);
+    }
+    builder.body(Body::empty()).expect("request should build")
+}
@@ -0,0 +1,133 @@
+mod support;
+
+use axum::body::{Body, to_bytes};
+use axum::http::{Method, Request, StatusCode, header};
+use serde_json::{Value, json};
+use serial_test::serial;
+use tower::ServiceExt;
+
+use support::api_app::build_test_router;
+use support::clerk::TestClerkAuth;
+
+#[tokio::test]
+#[serial]
+async fn json_bodies_reject_unknown_fields_wrong_content_type_and_oversized_payloads() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let auth = format!("Bearer {}", clerk.token_for_subject("request-body-user"));
+    let app = build_test_router(store.clone(), &clerk).await;
+
+    let unknown_field = send(
+        &app,
+        request(
+            "/v1/brief-profile/feedback",
+            &auth,
+            "application/json",
+            json!({"section": "schedule", "rating": "up", "mood": "great"}).to_string(),
+        ),
+    )
+    .await;
+    assert_eq!(unknown_field.status, StatusCode::BAD_REQUEST);
+    assert_eq!(unknown_field.body["error"]["code"], "invalid_request_body");
+    assert!(
+        unknown_field.body["error"]["message"]
+            .as_str()
+            .is_some_and(|message| message.contains("unknown field `mood`"))
+    );
+
+    let malformed = send(
+        &app,
+        request(
+            "/v1/brief-profile/feedback",
+            &auth,
+            "application/json",
+            "{\"section\":".to_string(),
+        ),
+    )
+    .await;
+    assert_eq!(malformed.status, StatusCode::BAD_REQUEST);
+    assert_eq!(malformed.body["error"]["code"], "invalid_request_body");
+
+    let wrong_content_type = send(
+        &app,
+        request(
+            "/v1/brief-profile/feedback",
+            &auth,
+            "text/plain",
+            json!({"section": "schedule", "rating": "up"}).to_string(),
+        ),
+    )
+    .await;
+    assert_eq!(
+        wrong_content_type.status,
+        StatusCode::UNSUPPORTED_MEDIA_TYPE
+    );
+    assert_eq!(
+        wrong_content_type.body["error"]["code"],
+        "invalid_request_body"
+    );
+
+    let oversized = send(
+        &app,
+        request(
+            "/v1/brief-profile/feedback",
+            &auth,
+            "application/json",
+            json!({"section": "x".repeat(70_000), "rating": "up"}).to_string(),
+        ),
+    )
+    .await;
+    assert_eq!(oversized.status, StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(oversized.body["error"]["code"], "payload_too_large");
+
+    // Prompt envelope routes get the larger cap, so the same size reaches the handler.
+    let envelope_route = send(
+        &app,
+        request(
+            "/v1/automations",
+            &auth,
+            "application/json",
+            json!({"title": "x".repeat(70_000)}).to_string(),
+        ),
+    )
+    .await;
+    assert_eq!(envelope_route.status, StatusCode::BAD_REQUEST);
+    assert_eq!(envelope_route.body["error"]["code"], "invalid_request_body");
+    assert!(
+        envelope_route.body["error"]["message"]
+            .as_str()
+            .is_some_and(|message| message.contains("missing field"))
+    );
+}
+
+struct JsonResponse {
+    status: StatusCode,
+    body: Value,
+}
+
+async fn send(app: &axum::Router, request: Request<Body>) -> JsonResponse {
+    let response = app
+        .clone()
+        .oneshot(request)
+        .await
+        .expect("request should succeed");
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("response body should read");
+    let body = serde_json::from_slice::<Value>(&body).unwrap_or_else(|_| json!({}));
+
+    JsonResponse { status, body }
+}
+
+fn request(uri: &str, auth_header: &str, content_type: &str, body: String) -> Request<Body> {
+    Request::builder()
+        .method(Method::POST)
+        .uri(uri)
+        .header(header::AUTHORIZATION, auth_header)
+        .header(header::CONTENT_TYPE, content_type)
+        .body(Body::from(body))
+        .expect("request should build")
+}
@@ -0,0 +1,464 @@
+mod support;
+
+use axum::body::{Body, to_bytes};
+use axum::http::{Method, Request, StatusCode, header};
+use base64::Engine as _;
+use chrono::{Duration, Utc};
+use ed25519_dalek::{Signer, SigningKey};
+use serde_json::{Value, json};
+use serial_test::serial;
+use shared::config::RequestSigningConfig;
+use shared::request_signing::canonical_request;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use support::api_app::build_test_router_with_request_signing;
+use support::clerk::TestClerkAuth;
+
+#[tokio::test]
+#[serial]
+async fn signed_requests_verify_once_and_key_replacement_needs_the_current_key() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let app = build_test_router_with_request_signing(
+        store.clone(),
+        &clerk,
+        RequestSigningConfig::default(),
+    )
+    .await;
+    let auth = format!("Bearer {}", clerk.token_for_subject("signing-user"));
+    register_device(&app, &auth, "device-a").await;
+
+    let first_key = SigningKey::from_bytes(&[11_u8; 32]);
+    let invalid_key = send_json(
+        &app,
+        unsigned(
+            Method::PUT,
+            "/v1/devices/device-a/signing-key",
+            &auth,
+            Some(json!({"public_key": "bm90LWEta2V5"})),
+        ),
+    )
+    .await;
+    assert_eq!(invalid_key.status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        invalid_key.body["error"]["code"],
+        "invalid_request_signing_key"
+    );
+
+    let missing_device = enroll(&app, &auth, "device-missing", &first_key, None).await;
+    assert_eq!(missing_device.status, StatusCode::NOT_FOUND);
+
+    let enrolled = enroll(&app, &auth, "device-a", &first_key, None).await;
+    assert_eq!(enrolled.status, StatusCode::OK);
+    assert_eq!(enrolled.body["device_id"], "device-a");
+    assert_eq!(enrolled.body["algorithm"], "ed25519");
+
+    let signed_request =
+        SignedRequest::new(&first_key, "device-a", Method::GET, "/v1/feature-flags");
+    let accepted = send_json(&app, signed_request.build(&auth)).await;
+    assert_eq!(accepted.status, StatusCode::OK);
+
+    let replayed = send_json(&app, signed_request.build(&auth)).await;
+    assert_eq!(replayed.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(replayed.body["error"]["code"], "invalid_request_signature");
+
+    let versioned = SignedRequest::new(&first_key, "device-a", Method::GET, "/v2/feature-flags");
+    assert_eq!(
+        send_json(&app, versioned.build(&auth)).await.status,
+        StatusCode::OK
+    );
+
+    let mut stale = SignedRequest::new(&first_key, "device-a", Method::GET, "/v1/feature-flags");
+    stale.timestamp -= 3_600;
+    let stale = send_json(&app, stale.build(&auth)).await;
+    assert_eq!(stale.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(stale.body["error"]["code"], "invalid_request_signature");
+
+    let mut tampered = SignedRequest::new(&first_key, "device-a", Method::GET, "/v1/feature-flags");
+    tampered.uri = "/v1/feature-flags?tampered=1".to_string();
+    let tampered = send_json(&app, tampered.build(&auth)).await;
+    assert_eq!(tampered.status, StatusCode::UNAUTHORIZED);
+
+    let partial = Request::builder()
+        .method(Method::GET)
+        .uri("/v1/feature-flags")
+        .header(header::AUTHORIZATION, &auth)
+        .header("x-alfred-device-id", "device-a")
+        .body(Body::empty())
+        .expect("request should build");
+    let partial = send_json(&app, partial).await;
+    assert_eq!(partial.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(partial.body["error"]["code"], "invalid_request_signature");
+
+    let unsigned_read = send_json(
+        &app,
+        unsigned(Method::GET, "/v1/feature-flags", &auth, None),
+    )
+    .await;
+    assert_eq!(unsigned_read.status, StatusCode::OK);
+
+    let second_key = SigningKey::from_bytes(&[12_u8; 32]);
+    let unsigned_replacement = enroll(&app, &auth, "device-a", &second_key, None).await;
+    assert_eq!(unsigned_replacement.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        unsigned_replacement.body["error"]["code"],
+        "request_signature_required"
+    );
+
+    let replaced = enroll(
+        &app,
+        &auth,
+        "device-a",
+        &second_key,
+        Some((&first_key, "device-a")),
+    )
+    .await;
+    assert_eq!(replaced.status, StatusCode::OK);
+
+    let retired_key = SignedRequest::new(&first_key, "device-a", Method::GET, "/v1/feature-flags");
+    assert_eq!(
+        send_json(&app, retired_key.build(&auth)).await.status,
+        StatusCode::UNAUTHORIZED
+    );
+    let current_key = SignedRequest::new(&second_key, "device-a", Method::GET, "/v1/feature-flags");
+    assert_eq!(
+        send_json(&app, current_key.build(&auth)).await.status,
+        StatusCode::OK
+    );
+
+    let reclaimed = store
+        .purge_expired_request_signature_nonces_batch(Utc::now() + Duration::hours(1), 100)
+        .await
+        .expect("expired nonces should purge");
+    assert!(reclaimed >= 3);
+}
+
+#[tokio::test]
+#[serial]
+async fn required_signing_rejects_unsigned_requests_once_a_key_is_enrolled() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let app = build_test_router_with_request_signing(
+        store,
+        &clerk,
+        RequestSigningConfig {
+            required: true,
+            max_skew_seconds: 300,
+        },
+    )
+    .await;
+    let auth = format!("Bearer {}", clerk.token_for_subject("signing-required"));
+
+    let before_enrollment = send_json(
+        &app,
+        unsigned(Method::GET, "/v1/feature-flags", &auth, None),
+    )
+    .await;
+    assert_eq!(before_enrollment.status, StatusCode::OK);
+
+    register_device(&app, &auth, "device-a").await;
+    let device_key = SigningKey::from_bytes(&[21_u8; 32]);
+    assert_eq!(
+        enroll(&app, &auth, "device-a", &device_key, None)
+            .await
+            .status,
+        StatusCode::OK
+    );
+
+    let unsigned_read = send_json(
+        &app,
+        unsigned(Method::GET, "/v1/feature-flags", &auth, None),
+    )
+    .await;
+    assert_eq!(unsigned_read.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        unsigned_read.body["error"]["code"],
+        "request_signature_required"
+    );
+
+    let signed_read = SignedRequest::new(&device_key, "device-a", Method::GET, "/v1/feature-flags");
+    assert_eq!(
+        send_json(&app, signed_read.build(&auth)).await.status,
+        StatusCode::OK
+    );
+
+    // A second device is added with requests signed by the already-enrolled one.
+    let registered = register(&app, &auth, "device-b", Some((&device_key, "device-a"))).await;
+    assert_eq!(registered.status, StatusCode::OK);
+    let second_device_key = SigningKey::from_bytes(&[22_u8; 32]);
+    assert_eq!(
+        enroll(
+            &app,
+            &auth,
+            "device-b",
+            &second_device_key,
+            Some((&device_key, "device-a")),
+        )
+        .await
+        .status,
+        StatusCode::OK
+    );
+    let second_device_read = SignedRequest::new(
+        &second_device_key,
+        "device-b",
+        Method::GET,
+        "/v1/feature-flags",
+    );
+    assert_eq!(
+        send_json(&app, second_device_read.build(&auth))
+            .await
+            .status,
+        StatusCode::OK
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn stolen_token_cannot_add_a_device_or_enroll_a_key_once_one_is_enrolled() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let app = build_test_router_with_request_signing(
+        store.clone(),
+        &clerk,
+        RequestSigningConfig {
+            required: true,
+            max_skew_seconds: 300,
+        },
+    )
+    .await;
+    let auth = format!("Bearer {}", clerk.token_for_subject("stolen-token-user"));
+    register_device(&app, &auth, "device-a").await;
+    let device_key = SigningKey::from_bytes(&[41_u8; 32]);
+    assert_eq!(
+        enroll(&app, &auth, "device-a", &device_key, None)
+            .await
+            .status,
+        StatusCode::OK
+    );
+
+    let device_updated_at = || {
+        sqlx::query_scalar::<_, chrono::DateTime<Utc>>(
+            "SELECT updated_at FROM devices WHERE device_identifier = 'device-a'",
+        )
+        .fetch_one(store.pool())
+    };
+    let updated_before = device_updated_at().await.expect("device should load");
+
+    for device_id in ["attacker-device", "device-a"] {
+        let registered = register(&app, &auth, device_id, None).await;
+        assert_eq!(registered.status, StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            registered.body["error"]["code"],
+            "request_signature_required"
+        );
+    }
+    let attacker_key = SigningKey::from_bytes(&[42_u8; 32]);
+    let enrolled = enroll(&app, &auth, "attacker-device", &attacker_key, None).await;
+    assert_eq!(enrolled.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(enrolled.body["error"]["code"], "request_signature_required");
+
+    assert_eq!(
+        device_updated_at().await.expect("device should load"),
+        updated_before,
+        "unsigned re-registration must not overwrite the device"
+    );
+    let devices: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM devices")
+        .fetch_one(store.pool())
+        .await
+        .expect("device count should load");
+    assert_eq!(devices, 1);
+}
+
+#[tokio::test]
+#[serial]
+async fn repeated_nonce_replays_raise_one_security_anomaly_audit_event() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let app = build_test_router_with_request_signing(
+        store.clone(),
+        &clerk,
+        RequestSigningConfig::default(),
+    )
+    .await;
+    let auth = format!("Bearer {}", clerk.token_for_subject("replaying-user"));
+    register_device(&app, &auth, "device-a").await;
+    let device_key = SigningKey::from_bytes(&[31_u8; 32]);
+    assert_eq!(
+        enroll(&app, &auth, "device-a", &device_key, None)
+            .await
+            .status,
+        StatusCode::OK
+    );
+
+    let signed_request =
+        SignedRequest::new(&device_key, "device-a", Method::GET, "/v1/feature-flags");
+    assert_eq!(
+        send_json(&app, signed_request.build(&auth)).await.status,
+        StatusCode::OK
+    );
+    for _ in 0..5 {
+        let replayed = send_json(&app, signed_request.build(&auth)).await;
+        assert_eq!(replayed.status, StatusCode::UNAUTHORIZED);
+    }
+
+    let anomalies: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM audit_events WHERE event_type = 'SECURITY_ANOMALY_DETECTED'",
+    )
+    .fetch_one(store.pool())
+    .await
+    .expect("audit count should load");
+    assert_eq!(anomalies, 1);
+}
+
+struct SignedRequest<'a> {
+    signing_key: &'a SigningKey,
+    device_id: &'a str,
+    method: Method,
+    uri: String,
+    signed_uri: String,
+    body: Option<Value>,
+    timestamp: i64,
+    nonce: String,
+}
+
+impl<'a> SignedRequest<'a> {
+    fn new(signing_key: &'a SigningKey, device_id: &'a str, method: Method, uri: &str) -> Self {
+        Self {
+            signing_key,
+            device_id,
+            method,
+            uri: uri.to_string(),
+            signed_uri: uri.to_string(),
+            body: None,
+            timestamp: Utc::now().timestamp(),
+            nonce: Uuid::new_v4().simple().to_string(),
+        }
+    }
+
+    fn build(&self, auth_header: &str) -> Request<Body> {
+        let body = self.body.as_ref().map(Value::to_string).unwrap_or_default();
+        let canonical = canonical_request(
+            self.method.as_str(),
+            &self.signed_uri,
+            body.as_bytes(),
+            self.timestamp,
+            &self.nonce,
+        );
+        let signature = base64::engine::general_purpose::STANDARD
+            .encode(self.signing_key.sign(canonical.as_bytes()).to_bytes());
+
+        let builder = Request::builder()
+            .method(self.method.clone())
+            .uri(&self.uri)
+            .header(header::AUTHORIZATION, auth_header)
+            .header("x-alfred-device-id", self.device_id)
+            .header("x-alfred-request-timestamp", self.timestamp.to_string())
+            .header("x-alfred-request-nonce", &self.nonce)
+            .header("x-alfred-request-signature", signature);
+        match self.body {
+            Some(_) => builder
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body)),
+            None => builder.body(Body::empty()),
+        }
+        .expect("request should build")
+    }
+}
+
+async fn register_device(app: &axum::Router, auth_header: &str, device_id: &str) {
+    let registered = register(app, auth_header, device_id, None).await;
+    assert_eq!(registered.status, StatusCode::OK);
+}
+
+async fn register(
+    app: &axum::Router,
+    auth_header: &str,
+    device_id: &str,
+    signed_by: Option<(&SigningKey, &str)>,
+) -> JsonResponse {
+    let body = json!({
+        "device_id": device_id,
+        "apns_token": "apns-token",
+        "environment": "sandbox",
+    });
+    let request = match signed_by {
+        Some((signing_key, signing_device_id)) => {
+            let mut signed = SignedRequest::new(
+                signing_key,
+                signing_device_id,
+                Method::POST,
+                "/v1/devices/apns",
+            );
+            signed.body = Some(body);
+            signed.build(auth_header)
+        }
+        None => unsigned(Method::POST, "/v1/devices/apns", auth_header, Some(body)),
+    };
+    send_json(app, request).await
+}
+
+async fn enroll(
+    app: &axum::Router,
+    auth_header: &str,
+    device_id: &str,
+    new_key: &SigningKey,
+    signed_by: Option<(&SigningKey, &str)>,
+) -> JsonResponse {
+    let uri = format!("/v1/devices/{device_id}/signing-key");
+    let body = json!({
+        "public_key": base64::engine::general_purpose::STANDARD
+            .encode(new_key.verifying_key().as_bytes()),
+    });
+    let request = match signed_by {
+        Some((signing_key, signing_device_id)) => {
+            let mut signed = SignedRequest::new(signing_key, signing_device_id, Method::PUT, &uri);
+            signed.body = Some(body);
+            signed.build(auth_header)
+        }
+        None => unsigned(Method::PUT, &uri, auth_header, Some(body)),
+    };
+    send_json(app, request).await
+}
+
+struct JsonResponse {
+    status: StatusCode,
+    body: Value,
+}
+
+async fn send_json(app: &axum::Router, request: Request<Body>) -> JsonResponse {
+    let response = app
+        .clone()
+        .oneshot(request)
+        .await
+        .expect("request should succeed");
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("response body should read");
+    let body = serde_json::from_slice::<Value>(&body).unwrap_or_else(|_| json!({}));
+
+    JsonResponse { status, body }
+}
+
+fn unsigned(method: Method, uri: &str, auth_header: &str, body: Option<Value>) -> Request<Body> {
+    let builder = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::AUTHORIZATION, auth_header);
+    match body {
+        Some(body) => builder
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string())),
+        None => builder.body(Body::empty()),
+    }
+    .expect("request should build")
+}
@@ -0,0 +1,90 @@
+mod support;
+
+use axum::body::Body;
+use axum::http::{HeaderMap, Request, StatusCode, header};
+use serial_test::serial;
+use tower::ServiceExt;
+
+use support::api_app::build_test_router;
+use support::clerk::TestClerkAuth;
+
+#[tokio::test]
+#[serial]
+async fn sensitive_routes_are_hardened_and_never_cached() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let auth = format!(
+        "Bearer {}",
+        clerk.token_for_subject("security-headers-user")
+    );
+    let app = build_test_router(store, &clerk).await;
+
+    let (status, headers) = send(
+        &app,
+        get(
+            "/oauth/google/callback?code=oauth-code&state=state-123",
+            None,
+        ),
+    )
+    .await;
+    assert!(status.is_redirection());
+    assert_hardened(&headers);
+    assert_eq!(headers[header::CACHE_CONTROL], "no-store");
+
+    for uri in ["/v1/status", "/v2/status"] {
+        let (status, headers) = send(&app, get(uri, Some(&auth))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_hardened(&headers);
+        assert_eq!(headers[header::CACHE_CONTROL], "no-store");
+    }
+
+    let (status, headers) = send(&app, get("/v1/status", None)).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_hardened(&headers);
+    assert_eq!(headers[header::CACHE_CONTROL], "no-store");
+}
+
+#[tokio::test]
+#[serial]
+async fn public_routes_keep_their_caching() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let app = build_test_router(store, &clerk).await;
+
+    for uri in ["/healthz", "/v1/public/status", "/v2/public/status"] {
+        let (status, headers) = send(&app, get(uri, None)).await;
+        assert_eq!(status, StatusCode::OK, "{uri}");
+        assert_hardened(&headers);
+        assert!(!headers.contains_key(header::CACHE_CONTROL), "{uri}");
+    }
+}
+
+fn assert_hardened(headers: &HeaderMap) {
+    assert_eq!(
+        headers[header::STRICT_TRANSPORT_SECURITY],
+        "max-age=31536000"
+    );
+    assert_eq!(headers[header::X_CONTENT_TYPE_OPTIONS], "nosniff");
+    assert_eq!(headers[header::REFERRER_POLICY], "no-referrer");
+}
+
+fn get(uri: &str, auth_header: Option<&str>) -> Request<Body> {
+    let mut builder = Request::builder().uri(uri);
+    if let Some(auth_header) = auth_header {
+        builder = builder.header(header::AUTHORIZATION, auth_header);
+    }
+    builder.body(Body::empty()).expect("request should build")
+}
+
+async fn send(app: &axum::Router, request: Request<Body>) -> (StatusCode, HeaderMap) {
+    let response = app
+        .clone()
+        .oneshot(request)
+        .await
+        .expect("request should succeed");
+    (response.status(), response.headers().clone())
+}
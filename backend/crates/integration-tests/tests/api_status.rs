@@ -0,0 +1,177 @@
+mod support;
+
+use axum::body::{Body, to_bytes};
+use axum::http::{Method, Request, StatusCode, header};
+use serde_json::{Value, json};
+use serial_test::serial;
+use shared::models::AvailabilityComponent;
+use shared::repos::{AssistantRequestIndexEntry, AssistantRequestOutcome};
+use tower::ServiceExt;
+
+use support::api_app::{build_test_router, user_id_for_subject};
+use support::clerk::TestClerkAuth;
+
+#[tokio::test]
+#[serial]
+async fn status_reports_operational_without_recent_traffic() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let auth = format!("Bearer {}", clerk.token_for_subject("status-quiet-user"));
+    let app = build_test_router(store.clone(), &clerk).await;
+
+    let response = send_json(&app, request("/v1/status", Some(&auth))).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["status"], "operational");
+    assert_eq!(response.body["window_minutes"], 15);
+    let components = response.body["components"]
+        .as_array()
+        .expect("components should be an array");
+    assert_eq!(components.len(), 3);
+    assert!(
+        components
+            .iter()
+            .all(|component| component["status"] == "operational")
+    );
+
+    let unauthenticated = send_json(&app, request("/v1/status", None)).await;
+    assert_eq!(unauthenticated.status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+#[serial]
+async fn status_flags_assistant_when_llm_fallbacks_are_elevated() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let subject = "status-degraded-user";
+    let user_id = user_id_for_subject(&clerk.issuer, subject);
+    let auth = format!("Bearer {}", clerk.token_for_subject(subject));
+    let app = build_test_router(store.clone(), &clerk).await;
+
+    store
+        .ensure_user(user_id)
+        .await
+        .expect("user should be created");
+    for index in 0..6 {
+        store
+            .record_assistant_request(
+                user_id,
+                &AssistantRequestIndexEntry {
+                    request_id: format!("status-req-{index}"),
+                    session_id: None,
+                    outcome: AssistantRequestOutcome::Succeeded,
+                    error_code: None,
+                    route: Some("general_chat".to_string()),
+                    capability: Some("general_chat".to_string()),
+                    model: None,
+                    planner_used_fallback: false,
+                    output_used_fallback: index % 2 == 0,
+                    planner_ms: Some(10),
+                    lane_ms: Some(20),
+                    enclave_rpc_ms: 40,
+                },
+            )
+            .await
+            .expect("assistant request should be indexed");
+    }
+
+    let response = send_json(&app, request("/v1/status", Some(&auth))).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["status"], "degraded");
+    let assistant = response.body["components"]
+        .as_array()
+        .expect("components should be an array")
+        .iter()
+        .find(|component| component["component"] == "assistant")
+        .expect("assistant component should be reported");
+    assert_eq!(assistant["status"], "degraded");
+    assert_eq!(assistant["reason"], "llm_fallback_elevated");
+}
+
+#[tokio::test]
+#[serial]
+async fn public_status_is_unauthenticated_and_reports_component_history() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let app = build_test_router(store.clone(), &clerk).await;
+
+    store
+        .record_component_availability(AvailabilityComponent::PushDelivery, 10, 5)
+        .await
+        .expect("push availability should record");
+    store
+        .record_component_availability(AvailabilityComponent::Automations, 4, 4)
+        .await
+        .expect("automation availability should record");
+    store
+        .record_component_availability(AvailabilityComponent::Automations, 6, 6)
+        .await
+        .expect("automation availability should accumulate");
+
+    let response = send_json(&app, request("/v1/public/status", None)).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["status"], "unavailable");
+
+    let components = response.body["components"]
+        .as_array()
+        .expect("components should be an array");
+    let component = |name: &str| {
+        components
+            .iter()
+            .find(|component| component["component"] == name)
+            .unwrap_or_else(|| panic!("{name} should be reported"))
+    };
+
+    let push = component("push_delivery");
+    assert_eq!(push["status"], "unavailable");
+    assert_eq!(push["availability_24h"], 0.5);
+    assert!(push["message"].is_string());
+    let history = push["history"]
+        .as_array()
+        .expect("history should be an array");
+    assert_eq!(history.len(), 7);
+    assert_eq!(history[6]["attempts"], 10);
+
+    let automations = component("automations");
+    assert_eq!(automations["status"], "operational");
+    assert_eq!(automations["availability_24h"], 1.0);
+    assert!(automations.get("message").is_none());
+
+    let connectors = component("connectors");
+    assert_eq!(connectors["status"], "operational");
+    assert!(connectors.get("availability_24h").is_none());
+}
+
+struct JsonResponse {
+    status: StatusCode,
+    body: Value,
+}
+
+async fn send_json(app: &axum::Router, request: Request<Body>) -> JsonResponse {
+    let response = app
+        .clone()
+        .oneshot(request)
+        .await
+        .expect("request should succeed");
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("response body should read");
+    let body = serde_json::from_slice::<Value>(&body).unwrap_or_else(|_| json!({}));
+
+    JsonResponse { status, body }
+}
+
+fn request(uri: &str, auth_header: Option<&str>) -> Request<Body> {
+    let mut builder = Request::builder().method(Method::GET).uri(uri);
+    if let Some(auth_header) = auth_header {
+        builder = builder.header(header::AUTHORIZATION, auth_header);
+    }
+
+    builder.body(Body::empty()).expect("request should build")
+}
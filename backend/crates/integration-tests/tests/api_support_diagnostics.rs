@@ -0,0 +1,225 @@
+mod support;
+
+use axum::body::{Body, to_bytes};
+use axum::http::{Method, Request, StatusCode, header};
+use base64::Engine as _;
+use chrono::{Duration, Utc};
+use serde_json::{Value, json};
+use serial_test::serial;
+use tower::ServiceExt;
+
+use support::api_app::{build_test_router, user_id_for_subject};
+use support::clerk::TestClerkAuth;
+
+#[tokio::test]
+#[serial]
+async fn diagnostics_upload_is_stored_audited_and_user_deletable() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let subject = "support-diagnostics-user";
+    let user_id = user_id_for_subject(&clerk.issuer, subject);
+    let auth = format!("Bearer {}", clerk.token_for_subject(subject));
+    let app = build_test_router(store.clone(), &clerk).await;
+
+    let ciphertext = base64::engine::general_purpose::STANDARD.encode([7_u8; 64]);
+    let uploaded = send_json(
+        &app,
+        request(
+            Method::POST,
+            "/v1/support/diagnostics",
+            &auth,
+            Some(json!({
+                "ticket_reference": "SUP-1042",
+                "algorithm": "x25519-chacha20poly1305",
+                "key_id": "support-2026",
+                "ciphertext": ciphertext,
+            })),
+        ),
+    )
+    .await;
+    assert_eq!(uploaded.status, StatusCode::OK);
+    assert_eq!(uploaded.body["ticket_reference"], "SUP-1042");
+    assert_eq!(uploaded.body["size_bytes"], 64);
+    let diagnostic_id = uploaded.body["diagnostic_id"]
+        .as_str()
+        .expect("diagnostic id should be returned")
+        .to_string();
+
+    let stored: Vec<u8> =
+        sqlx::query_scalar("SELECT ciphertext FROM support_diagnostics WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_one(store.pool())
+            .await
+            .expect("diagnostic row should exist");
+    assert_eq!(stored, vec![7_u8; 64]);
+
+    let deleted = send_json(
+        &app,
+        request(
+            Method::DELETE,
+            &format!("/v1/support/diagnostics/{diagnostic_id}"),
+            &auth,
+            None,
+        ),
+    )
+    .await;
+    assert_eq!(deleted.status, StatusCode::OK);
+
+    let deleted_again = send_json(
+        &app,
+        request(
+            Method::DELETE,
+            &format!("/v1/support/diagnostics/{diagnostic_id}"),
+            &auth,
+            None,
+        ),
+    )
+    .await;
+    assert_eq!(deleted_again.status, StatusCode::NOT_FOUND);
+
+    let audit_events: Vec<String> = sqlx::query_scalar(
+        "SELECT event_type FROM audit_events
+         WHERE user_id = $1 AND event_type LIKE 'SUPPORT_DIAGNOSTICS_%'
+         ORDER BY created_at ASC",
+    )
+    .bind(user_id)
+    .fetch_all(store.pool())
+    .await
+    .expect("audit events should load");
+    assert_eq!(
+        audit_events,
+        vec![
+            "SUPPORT_DIAGNOSTICS_UPLOADED",
+            "SUPPORT_DIAGNOSTICS_DELETED"
+        ]
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn diagnostics_upload_rejects_invalid_bundles_and_expired_rows_are_purged() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let subject = "support-diagnostics-invalid-user";
+    let user_id = user_id_for_subject(&clerk.issuer, subject);
+    let auth = format!("Bearer {}", clerk.token_for_subject(subject));
+    let app = build_test_router(store.clone(), &clerk).await;
+
+    let invalid = send_json(
+        &app,
+        request(
+            Method::POST,
+            "/v1/support/diagnostics",
+            &auth,
+            Some(json!({
+                "algorithm": "x25519-chacha20poly1305",
+                "key_id": "support-2026",
+                "ciphertext": "not base64!",
+            })),
+        ),
+    )
+    .await;
+    assert_eq!(invalid.status, StatusCode::BAD_REQUEST);
+    assert_eq!(invalid.body["error"]["code"], "invalid_ciphertext");
+
+    let oversized = send_json(
+        &app,
+        request(
+            Method::POST,
+            "/v1/support/diagnostics",
+            &auth,
+            Some(json!({
+                "algorithm": "x25519-chacha20poly1305",
+                "key_id": "support-2026",
+                "ciphertext": base64::engine::general_purpose::STANDARD
+                    .encode(vec![0_u8; 1024 * 1024 + 1]),
+            })),
+        ),
+    )
+    .await;
+    assert_eq!(oversized.status, StatusCode::PAYLOAD_TOO_LARGE);
+
+    let generated = send_json(
+        &app,
+        request(
+            Method::POST,
+            "/v1/support/diagnostics",
+            &auth,
+            Some(json!({
+                "algorithm": "x25519-chacha20poly1305",
+                "key_id": "support-2026",
+                "ciphertext": "AQID",
+            })),
+        ),
+    )
+    .await;
+    assert_eq!(generated.status, StatusCode::OK);
+    assert!(
+        generated.body["ticket_reference"]
+            .as_str()
+            .is_some_and(|reference| reference.starts_with("DIAG-"))
+    );
+
+    store
+        .create_support_diagnostic(
+            user_id,
+            "SUP-expired",
+            "x25519-chacha20poly1305",
+            "support-2026",
+            &[1, 2, 3],
+            Utc::now() - Duration::minutes(1),
+        )
+        .await
+        .expect("expired diagnostic should insert");
+    let purged = store
+        .purge_expired_support_diagnostics_batch(Utc::now(), 10)
+        .await
+        .expect("purge should succeed");
+    assert_eq!(purged, 1);
+
+    let remaining: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM support_diagnostics WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_one(store.pool())
+            .await
+            .expect("count should load");
+    assert_eq!(remaining, 1);
+}
+
+struct JsonResponse {
+    status: StatusCode,
+    body: Value,
+}
+
+async fn send_json(app: &axum::Router, request: Request<Body>) -> JsonResponse {
+    let response = app
+        .clone()
+        .oneshot(request)
+        .await
+        .expect("request should succeed");
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("response body should read");
+    let body = serde_json::from_slice::<Value>(&body).unwrap_or_else(|_| json!({}));
+
+    JsonResponse { status, body }
+}
+
+fn request(method: Method, uri: &str, auth_header: &str, body: Option<Value>) -> Request<Body> {
+    let builder = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::AUTHORIZATION, auth_header);
+    match body {
+        Some(body) => builder
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string())),
+        None => builder.body(Body::empty()),
+    }
+    .expect("request should build")
+}
@@ -0,0 +1,112 @@
+mod support;
+
+use axum::body::{Body, to_bytes};
+use axum::http::{HeaderMap, Method, Request, StatusCode, header};
+use chrono::{TimeZone, Utc};
+use serde_json::{Value, json};
+use serial_test::serial;
+use shared::config::ApiDeprecationConfig;
+use tower::ServiceExt;
+
+use support::api_app::{build_test_router, build_test_router_with_api_v1_deprecation};
+use support::clerk::TestClerkAuth;
+
+#[tokio::test]
+#[serial]
+async fn v2_serves_the_v1_handlers_and_only_v1_paths() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let auth = format!("Bearer {}", clerk.token_for_subject("versioning-user"));
+    let app = build_test_router(store.clone(), &clerk).await;
+
+    let v1 = send(&app, request("/v1/status", Some(&auth))).await;
+    let v2 = send(&app, request("/v2/status", Some(&auth))).await;
+    assert_eq!(v1.status, StatusCode::OK);
+    assert_eq!(v2.status, StatusCode::OK);
+    assert_eq!(v2.body["status"], v1.body["status"]);
+    assert_eq!(v2.body["components"], v1.body["components"]);
+    assert!(v1.headers.get("deprecation").is_none());
+
+    let unauthenticated = send(&app, request("/v2/status", None)).await;
+    assert_eq!(unauthenticated.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(unauthenticated.body["error"]["code"], "unauthorized");
+
+    let not_versioned = send(&app, request("/v2/healthz", Some(&auth))).await;
+    assert_eq!(not_versioned.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+#[serial]
+async fn deprecated_v1_responses_point_clients_at_v2() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let auth = format!(
+        "Bearer {}",
+        clerk.token_for_subject("versioning-deprecated-user")
+    );
+    let app = build_test_router_with_api_v1_deprecation(
+        store.clone(),
+        &clerk,
+        ApiDeprecationConfig {
+            deprecated_at: Utc.with_ymd_and_hms(2026, 11, 1, 0, 0, 0).unwrap(),
+            sunset_at: Some(Utc.with_ymd_and_hms(2027, 5, 1, 0, 0, 0).unwrap()),
+        },
+    )
+    .await;
+
+    let v1 = send(&app, request("/v1/status", Some(&auth))).await;
+    assert_eq!(v1.status, StatusCode::OK);
+    assert_eq!(v1.headers["deprecation"], "@1793491200");
+    assert_eq!(v1.headers["sunset"], "Sat, 01 May 2027 00:00:00 GMT");
+    assert_eq!(
+        v1.headers[header::LINK],
+        "</v2/status>; rel=\"successor-version\""
+    );
+
+    let v2 = send(&app, request("/v2/status", Some(&auth))).await;
+    assert_eq!(v2.status, StatusCode::OK);
+    assert!(v2.headers.get("deprecation").is_none());
+    assert!(v2.headers.get("sunset").is_none());
+
+    let health = send(&app, request("/healthz", None)).await;
+    assert!(health.headers.get("deprecation").is_none());
+}
+
+struct JsonResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Value,
+}
+
+async fn send(app: &axum::Router, request: Request<Body>) -> JsonResponse {
+    let response = app
+        .clone()
+        .oneshot(request)
+        .await
+        .expect("request should succeed");
+    let status = response.status();
+    let headers = response.headers().clone();
+    let body = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("response body should read");
+    let body = serde_json::from_slice::<Value>(&body).unwrap_or_else(|_| json!({}));
+
+    JsonResponse {
+        status,
+        headers,
+        body,
+    }
+}
+
+fn request(uri: &str, auth_header: Option<&str>) -> Request<Body> {
+    let mut builder = Request::builder().method(Method::GET).uri(uri);
+    if let Some(auth_header) = auth_header {
+        builder = builder.header(header::AUTHORIZATION, auth_header);
+    }
+
+    builder.body(Body::empty()).expect("request should build")
+}
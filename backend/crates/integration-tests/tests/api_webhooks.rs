@@ -0,0 +1,237 @@
+mod support;
+
+use axum::body::{Body, to_bytes};
+use axum::http::{Method, Request, StatusCode, header};
+use serde_json::{Value, json};
+use serial_test::serial;
+use shared::webhooks::{WebhookEvent, WebhookEventType};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use support::api_app::{build_test_router, user_id_for_subject};
+use support::clerk::TestClerkAuth;
+
+#[tokio::test]
+#[serial]
+async fn webhooks_return_the_secret_once_and_list_queued_deliveries() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let app = build_test_router(store.clone(), &clerk).await;
+    let auth = format!("Bearer {}", clerk.token_for_subject("webhook-owner"));
+    let other_auth = format!("Bearer {}", clerk.token_for_subject("webhook-other"));
+    let user_id = user_id_for_subject(&clerk.issuer, "webhook-owner");
+
+    let created = send_json(
+        &app,
+        request(
+            Method::POST,
+            "/v1/webhooks",
+            &auth,
+            Some(json!({
+                "url": "https://hooks.example.com/alfred#ignored",
+                "event_types": ["automation_run.completed", "connector.needs_reauth"],
+            })),
+        ),
+    )
+    .await;
+    assert_eq!(created.status, StatusCode::OK);
+    assert_eq!(created.body["url"], "https://hooks.example.com/alfred");
+    let signing_secret = created.body["signing_secret"]
+        .as_str()
+        .expect("signing secret should be returned on create");
+    assert!(signing_secret.starts_with("whsec_"));
+    let webhook_id = created.body["webhook_id"]
+        .as_str()
+        .expect("webhook id should be a string")
+        .to_string();
+
+    let listed = send_json(&app, request(Method::GET, "/v1/webhooks", &auth, None)).await;
+    assert_eq!(listed.status, StatusCode::OK);
+    assert_eq!(listed.body["items"].as_array().map(Vec::len), Some(1));
+    assert_eq!(listed.body["items"][0]["webhook_id"], webhook_id.as_str());
+    assert!(listed.body["items"][0].get("signing_secret").is_none());
+
+    let other_listed = send_json(
+        &app,
+        request(Method::GET, "/v1/webhooks", &other_auth, None),
+    )
+    .await;
+    assert_eq!(other_listed.body["items"], json!([]));
+
+    let event = WebhookEvent::new(
+        WebhookEventType::AutomationRunCompleted,
+        json!({"automation_run_id": Uuid::new_v4(), "outcome": "succeeded"}),
+    );
+    let queued = store
+        .enqueue_webhook_event(user_id, &event)
+        .await
+        .expect("event should queue");
+    assert_eq!(queued, 1);
+    let unsubscribed = store
+        .enqueue_webhook_event(
+            user_id,
+            &WebhookEvent::new(WebhookEventType::PrivacyDeleteCompleted, json!({})),
+        )
+        .await
+        .expect("unsubscribed event should be ignored");
+    assert_eq!(unsubscribed, 0);
+
+    let deliveries_uri = format!("/v1/webhooks/{webhook_id}/deliveries");
+    let deliveries = send_json(&app, request(Method::GET, &deliveries_uri, &auth, None)).await;
+    assert_eq!(deliveries.status, StatusCode::OK);
+    assert_eq!(deliveries.body["items"].as_array().map(Vec::len), Some(1));
+    assert_eq!(
+        deliveries.body["items"][0]["event_id"],
+        event.id.to_string().as_str()
+    );
+    assert_eq!(
+        deliveries.body["items"][0]["event_type"],
+        "automation_run.completed"
+    );
+    assert_eq!(deliveries.body["items"][0]["state"], "PENDING");
+    assert_eq!(deliveries.body["items"][0]["attempts"], 0);
+    assert_eq!(deliveries.body["next_cursor"], Value::Null);
+
+    let foreign = send_json(
+        &app,
+        request(Method::GET, &deliveries_uri, &other_auth, None),
+    )
+    .await;
+    assert_eq!(foreign.status, StatusCode::NOT_FOUND);
+    assert_eq!(foreign.body["error"]["code"], "webhook_not_found");
+
+    let webhook_uri = format!("/v1/webhooks/{webhook_id}");
+    let deleted = send_json(&app, request(Method::DELETE, &webhook_uri, &auth, None)).await;
+    assert_eq!(deleted.status, StatusCode::OK);
+
+    let deleted_again = send_json(&app, request(Method::DELETE, &webhook_uri, &auth, None)).await;
+    assert_eq!(deleted_again.status, StatusCode::NOT_FOUND);
+    assert_eq!(deleted_again.body["error"]["code"], "webhook_not_found");
+
+    let gone = send_json(&app, request(Method::GET, &deliveries_uri, &auth, None)).await;
+    assert_eq!(gone.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+#[serial]
+async fn webhook_creation_rejects_private_urls_bad_event_types_and_excess_subscriptions() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let clerk = TestClerkAuth::start().await;
+    let app = build_test_router(store, &clerk).await;
+    let auth = format!("Bearer {}", clerk.token_for_subject("webhook-limits"));
+
+    for url in [
+        "http://hooks.example.com/alfred",
+        "https://127.0.0.1/alfred",
+        "https://metadata.google.internal/",
+        "not a url",
+    ] {
+        let rejected = create(
+            &app,
+            &auth,
+            json!({"url": url, "event_types": ["connector.needs_reauth"]}),
+        )
+        .await;
+        assert_eq!(rejected.status, StatusCode::BAD_REQUEST, "{url}");
+        assert_eq!(
+            rejected.body["error"]["code"], "invalid_webhook_url",
+            "{url}"
+        );
+    }
+
+    for event_types in [
+        json!([]),
+        json!(["connector.needs_reauth", "connector.needs_reauth"]),
+    ] {
+        let rejected = create(
+            &app,
+            &auth,
+            json!({"url": "https://hooks.example.com/alfred", "event_types": event_types}),
+        )
+        .await;
+        assert_eq!(rejected.status, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            rejected.body["error"]["code"],
+            "invalid_webhook_event_types"
+        );
+    }
+
+    let unknown_type = create(
+        &app,
+        &auth,
+        json!({"url": "https://hooks.example.com/alfred", "event_types": ["user.created"]}),
+    )
+    .await;
+    assert_eq!(unknown_type.status, StatusCode::BAD_REQUEST);
+
+    for index in 0..5 {
+        let created = create(
+            &app,
+            &auth,
+            json!({
+                "url": format!("https://hooks.example.com/alfred/{index}"),
+                "event_types": ["privacy_delete.completed"],
+            }),
+        )
+        .await;
+        assert_eq!(created.status, StatusCode::OK);
+    }
+
+    let over_limit = create(
+        &app,
+        &auth,
+        json!({
+            "url": "https://hooks.example.com/alfred/extra",
+            "event_types": ["privacy_delete.completed"],
+        }),
+    )
+    .await;
+    assert_eq!(over_limit.status, StatusCode::BAD_REQUEST);
+    assert_eq!(over_limit.body["error"]["code"], "webhook_limit_reached");
+}
+
+async fn create(app: &axum::Router, auth_header: &str, body: Value) -> JsonResponse {
+    send_json(
+        app,
+        request(Method::POST, "/v1/webhooks", auth_header, Some(body)),
+    )
+    .await
+}
+
+struct JsonResponse {
+    status: StatusCode,
+    body: Value,
+}
+
+async fn send_json(app: &axum::Router, request: Request<Body>) -> JsonResponse {
+    let response = app
+        .clone()
+        .oneshot(request)
+        .await
+        .expect("request should succeed");
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("response body should read");
+    let body = serde_json::from_slice::<Value>(&body).unwrap_or_else(|_| json!({}));
+
+    JsonResponse { status, body }
+}
+
+fn request(method: Method, uri: &str, auth_header: &str, body: Option<Value>) -> Request<Body> {
+    let builder = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::AUTHORIZATION, auth_header);
+    match body {
+        Some(body) => builder
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string())),
+        None => builder.body(Body::empty()),
+    }
+    .expect("request should build")
+}
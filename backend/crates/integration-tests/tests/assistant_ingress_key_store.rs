@@ -0,0 +1,167 @@
+mod support;
+
+use chrono::{Duration, Utc};
+use serial_test::serial;
+use shared::repos::{AssistantIngressKeyStatus, NewAssistantIngressKey, PromptEnvelopeUse};
+
+fn new_key(key_id: &str) -> NewAssistantIngressKey {
+    NewAssistantIngressKey {
+        key_id: key_id.to_string(),
+        public_key: format!("{key_id}-public"),
+        wrapped_private_key: vec![1, 2, 3],
+        wrap_nonce: vec![0; 12],
+        wrapping_key_id: "assistant-ingress-wrap-v1".to_string(),
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn rotation_demotes_the_active_key_and_drops_the_older_previous_key() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let now = Utc::now();
+    let grace = now + Duration::hours(1);
+    assert!(
+        store
+            .rotate_assistant_ingress_key(None, &new_key("ingress-r1"), grace)
+            .await
+            .expect("first rotation should succeed")
+    );
+    assert!(
+        store
+            .rotate_assistant_ingress_key(Some("ingress-r1"), &new_key("ingress-r2"), grace)
+            .await
+            .expect("second rotation should succeed")
+    );
+    assert!(
+        store
+            .rotate_assistant_ingress_key(Some("ingress-r2"), &new_key("ingress-r3"), grace)
+            .await
+            .expect("third rotation should succeed")
+    );
+
+    let keys = store
+        .list_live_assistant_ingress_keys(now)
+        .await
+        .expect("keys should load");
+    let summary = keys
+        .iter()
+        .map(|key| (key.key_id.as_str(), key.status, key.expires_at.is_some()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        summary,
+        vec![
+            ("ingress-r3", AssistantIngressKeyStatus::Active, false),
+            ("ingress-r2", AssistantIngressKeyStatus::Previous, true),
+        ]
+    );
+    assert_eq!(keys[0].wrapped_private_key, vec![1, 2, 3]);
+
+    let after_grace = store
+        .list_live_assistant_ingress_keys(grace + Duration::seconds(1))
+        .await
+        .expect("keys should load");
+    assert_eq!(after_grace.len(), 1);
+    assert_eq!(after_grace[0].key_id, "ingress-r3");
+}
+
+#[tokio::test]
+#[serial]
+async fn rotation_is_skipped_when_another_instance_rotated_first() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let grace = Utc::now() + Duration::hours(1);
+    assert!(
+        store
+            .rotate_assistant_ingress_key(None, &new_key("ingress-r1"), grace)
+            .await
+            .expect("first rotation should succeed")
+    );
+
+    assert!(
+        !store
+            .rotate_assistant_ingress_key(None, &new_key("ingress-late"), grace)
+            .await
+            .expect("stale bootstrap rotation should not fail")
+    );
+    assert!(
+        !store
+            .rotate_assistant_ingress_key(Some("ingress-r0"), &new_key("ingress-late"), grace)
+            .await
+            .expect("stale rotation should not fail")
+    );
+
+    let keys = store
+        .list_live_assistant_ingress_keys(Utc::now())
+        .await
+        .expect("keys should load");
+    assert_eq!(keys.len(), 1);
+    assert_eq!(keys[0].key_id, "ingress-r1");
+}
+
+#[tokio::test]
+#[serial]
+async fn prompt_envelope_request_ids_expire_with_their_ingress_key() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let user_id = store.create_user().await.expect("user should be created");
+    let grace = Utc::now() + Duration::hours(1);
+    let after_grace = grace + Duration::seconds(1);
+    store
+        .rotate_assistant_ingress_key(None, &new_key("ingress-r1"), grace)
+        .await
+        .expect("first rotation should succeed");
+    assert!(
+        store
+            .claim_prompt_envelope_request_id(
+                user_id,
+                "ingress-r1",
+                "request-1",
+                PromptEnvelopeUse::AssistantQuery,
+            )
+            .await
+            .expect("claim should succeed")
+    );
+    assert_eq!(
+        store
+            .purge_expired_prompt_envelope_request_ids_batch(after_grace, 10)
+            .await
+            .expect("purge should succeed"),
+        0,
+        "claims under the active key do not expire"
+    );
+
+    store
+        .rotate_assistant_ingress_key(Some("ingress-r1"), &new_key("ingress-r2"), grace)
+        .await
+        .expect("second rotation should succeed");
+    assert!(
+        !store
+            .claim_prompt_envelope_request_id(
+                user_id,
+                "ingress-r1",
+                "request-1",
+                PromptEnvelopeUse::AutomationCreate,
+            )
+            .await
+            .expect("replayed claim should not fail"),
+        "the claim is still held during the grace window"
+    );
+    assert_eq!(
+        store
+            .purge_expired_prompt_envelope_request_ids_batch(grace - Duration::seconds(1), 10)
+            .await
+            .expect("purge should succeed"),
+        0
+    );
+    assert_eq!(
+        store
+            .purge_expired_prompt_envelope_request_ids_batch(after_grace, 10)
+            .await
+            .expect("purge should succeed"),
+        1
+    );
+}
@@ -0,0 +1,85 @@
+mod support;
+
+use std::collections::HashMap;
+
+use chrono::{Duration, Utc};
+use serial_test::serial;
+use shared::audit_retention::AuditRetentionPolicy;
+use shared::repos::AuditResult;
+use uuid::Uuid;
+
+#[tokio::test]
+#[serial]
+async fn audit_retention_purges_per_event_type_and_keeps_chain_verifiable() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let user_id = Uuid::new_v4();
+    for event_type in ["CONNECTOR_REVOKED", "ASSISTANT_QUERY", "CONNECTOR_REVOKED"] {
+        store
+            .add_audit_event(
+                user_id,
+                event_type,
+                None,
+                AuditResult::Success,
+                &HashMap::new(),
+            )
+            .await
+            .expect("audit event insert should succeed");
+    }
+    let policy = AuditRetentionPolicy::with_overrides(365, &["ASSISTANT_QUERY=30".to_string()])
+        .expect("policy should parse");
+
+    let nothing_expired = store
+        .purge_expired_audit_events_batch(&policy, Utc::now(), 100)
+        .await
+        .expect("purge should succeed");
+    assert!(nothing_expired.is_empty());
+
+    let summaries = store
+        .purge_expired_audit_events_batch(&policy, Utc::now() + Duration::days(31), 100)
+        .await
+        .expect("purge should succeed");
+    assert_eq!(summaries.len(), 1);
+    assert_eq!(summaries[0].user_id, user_id);
+    assert_eq!(summaries[0].purged_count, 1);
+    assert!(summaries[0].oldest_created_at <= summaries[0].newest_created_at);
+
+    let verification = store
+        .verify_audit_chain(user_id)
+        .await
+        .expect("verification should succeed");
+    assert!(verification.valid, "tombstone should bridge the purged row");
+    assert_eq!(verification.verified_events, 2);
+    assert_eq!(verification.purged_events, 1);
+
+    let summaries = store
+        .purge_expired_audit_events_batch(&policy, Utc::now() + Duration::days(366), 1)
+        .await
+        .expect("purge should succeed");
+    assert_eq!(summaries[0].purged_count, 1, "batch size bounds each purge");
+    store
+        .purge_expired_audit_events_batch(&policy, Utc::now() + Duration::days(366), 100)
+        .await
+        .expect("purge should succeed");
+
+    let verification = store
+        .verify_audit_chain(user_id)
+        .await
+        .expect("verification should succeed");
+    assert!(verification.valid);
+    assert_eq!(verification.verified_events, 0);
+    assert_eq!(
+        verification.purged_events, 1,
+        "only the newest tombstone survives compaction"
+    );
+    assert_eq!(verification.head_seq, 3);
+
+    let tombstones: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM audit_chain_tombstones WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_one(store.pool())
+            .await
+            .expect("tombstone count should succeed");
+    assert_eq!(tombstones, 1);
+}
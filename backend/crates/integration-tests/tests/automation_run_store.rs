@@ -0,0 +1,579 @@
+mod support;
+
+use chrono::{Duration as ChronoDuration, Utc};
+use serial_test::serial;
+use shared::automation_schedule::AutomationTemplate;
+use shared::pagination::PageRequest;
+use shared::repos::{AutomationRuleOptions, JobType, StoreError};
+use tokio::join;
+use uuid::Uuid;
+
+use support::automations::{
+    PROMPT_HASH_A, PROMPT_HASH_B, daily_schedule, prompt_material, report_envelope, weekly_schedule,
+};
+
+#[tokio::test]
+#[serial]
+async fn due_claims_are_lease_safe_and_split_across_workers() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let now = Utc::now();
+    let rule_a = store
+        .create_automation_rule(
+            Uuid::new_v4(),
+            "Rule A",
+            AutomationRuleOptions::default(),
+            &daily_schedule("UTC", 8, 0),
+            now - ChronoDuration::minutes(1),
+            &prompt_material(b"prompt-a", PROMPT_HASH_A),
+        )
+        .await
+        .expect("rule a should be created");
+    let rule_b = store
+        .create_automation_rule(
+            Uuid::new_v4(),
+            "Rule B",
+            AutomationRuleOptions::default(),
+            &daily_schedule("UTC", 9, 0),
+            now - ChronoDuration::minutes(1),
+            &prompt_material(b"prompt-b", PROMPT_HASH_B),
+        )
+        .await
+        .expect("rule b should be created");
+
+    let worker_a = Uuid::new_v4();
+    let worker_b = Uuid::new_v4();
+    let (claims_a, claims_b) = join!(
+        store.claim_due_automation_rules(now, worker_a, 1, 300),
+        store.claim_due_automation_rules(now, worker_b, 1, 300),
+    );
+    let claims_a = claims_a.expect("worker a claim should succeed");
+    let claims_b = claims_b.expect("worker b claim should succeed");
+
+    assert_eq!(claims_a.len(), 1);
+    assert_eq!(claims_b.len(), 1);
+
+    let mut claimed_ids = vec![claims_a[0].id, claims_b[0].id];
+    claimed_ids.sort();
+    claimed_ids.dedup();
+    assert_eq!(
+        claimed_ids.len(),
+        2,
+        "workers should not claim the same rule"
+    );
+    assert!(claimed_ids.contains(&rule_a.id));
+    assert!(claimed_ids.contains(&rule_b.id));
+
+    let prompt_bytes = if claims_a[0].id == rule_a.id {
+        &claims_a[0].prompt_ciphertext
+    } else {
+        &claims_b[0].prompt_ciphertext
+    };
+    assert_eq!(prompt_bytes, b"prompt-a");
+}
+
+#[tokio::test]
+#[serial]
+async fn run_materialization_is_idempotent_for_same_rule_and_scheduled_time() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let user_id = Uuid::new_v4();
+    let now = Utc::now();
+    let scheduled_for = now - ChronoDuration::minutes(1);
+    let next_run_at = now + ChronoDuration::minutes(14);
+
+    let rule = store
+        .create_automation_rule(
+            user_id,
+            "Idempotency Task",
+            AutomationRuleOptions::default(),
+            &daily_schedule("UTC", 12, 0),
+            scheduled_for,
+            &prompt_material(b"prompt-c", PROMPT_HASH_A),
+        )
+        .await
+        .expect("rule should be created");
+
+    let worker_a = Uuid::new_v4();
+    let claims_a = store
+        .claim_due_automation_rules(now, worker_a, 1, 300)
+        .await
+        .expect("claim should succeed");
+    assert_eq!(claims_a.len(), 1);
+
+    let run_first = store
+        .materialize_automation_run(
+            rule.id,
+            worker_a,
+            scheduled_for,
+            Some(next_run_at),
+            "automation:run:001",
+        )
+        .await
+        .expect("first materialization should succeed")
+        .expect("lease owner should materialize run");
+
+    store
+        .update_automation_rule_schedule(
+            user_id,
+            rule.id,
+            &daily_schedule("UTC", 12, 0),
+            scheduled_for,
+        )
+        .await
+        .expect("rule schedule reset should succeed")
+        .expect("rule should still exist");
+
+    let worker_b = Uuid::new_v4();
+    let claims_b = store
+        .claim_due_automation_rules(now, worker_b, 1, 300)
+        .await
+        .expect("second claim should succeed");
+    assert_eq!(claims_b.len(), 1);
+
+    let run_second = store
+        .materialize_automation_run(
+            rule.id,
+            worker_b,
+            scheduled_for,
+            Some(next_run_at + ChronoDuration::minutes(15)),
+            "automation:run:001",
+        )
+        .await
+        .expect("second materialization should succeed")
+        .expect("lease owner should materialize run");
+
+    assert_eq!(run_first.id, run_second.id);
+
+    let runs = store
+        .list_automation_runs_for_rule(user_id, rule.id, 10)
+        .await
+        .expect("run list should succeed");
+    assert_eq!(runs.len(), 1);
+    assert_eq!(
+        runs[0].scheduled_for.timestamp_micros(),
+        scheduled_for.timestamp_micros()
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn materialized_run_can_be_enqueued_with_stable_job_reference() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let user_id = Uuid::new_v4();
+    let now = Utc::now();
+    let scheduled_for = now - ChronoDuration::minutes(1);
+    let next_run_at = now + ChronoDuration::minutes(9);
+    let idempotency_key = format!("{}:{}", user_id, scheduled_for.timestamp_micros());
+
+    let rule = store
+        .create_automation_rule(
+            user_id,
+            "Stable Job Task",
+            AutomationRuleOptions::default(),
+            &daily_schedule("UTC", 14, 30),
+            scheduled_for,
+            &prompt_material(b"prompt-z", PROMPT_HASH_A),
+        )
+        .await
+        .expect("rule should be created");
+    let worker_id = Uuid::new_v4();
+    let claims = store
+        .claim_due_automation_rules(now, worker_id, 1, 300)
+        .await
+        .expect("claim should succeed");
+    assert_eq!(claims.len(), 1);
+
+    let run = store
+        .materialize_automation_run(
+            rule.id,
+            worker_id,
+            scheduled_for,
+            Some(next_run_at),
+            &idempotency_key,
+        )
+        .await
+        .expect("materialization should succeed")
+        .expect("lease owner should materialize run");
+
+    let job_id = store
+        .enqueue_job_with_idempotency_key(
+            user_id,
+            JobType::AutomationRun,
+            now,
+            Some(b"{\"automation_run_id\":\"placeholder\"}"),
+            &idempotency_key,
+        )
+        .await
+        .expect("job enqueue should succeed");
+
+    let marked = store
+        .mark_automation_run_enqueued(run.id, user_id, job_id)
+        .await
+        .expect("mark enqueued should succeed");
+    assert!(marked);
+
+    let runs = store
+        .list_automation_runs_for_rule(user_id, rule.id, 10)
+        .await
+        .expect("run list should succeed");
+    assert_eq!(runs.len(), 1);
+    assert_eq!(runs[0].id, run.id);
+    assert_eq!(runs[0].state.as_str(), "ENQUEUED");
+    assert_eq!(runs[0].job_id, Some(job_id));
+}
+
+#[tokio::test]
+#[serial]
+async fn in_flight_runs_hold_back_the_next_run_of_their_rule() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let user_id = Uuid::new_v4();
+    let now = Utc::now();
+    let scheduled_for = now - ChronoDuration::minutes(10);
+    let next_run_at = now - ChronoDuration::minutes(1);
+    let rule = store
+        .create_automation_rule(
+            user_id,
+            "Slow Task",
+            AutomationRuleOptions::default(),
+            &daily_schedule("UTC", 7, 0),
+            scheduled_for,
+            &prompt_material(b"prompt-slow", PROMPT_HASH_A),
+        )
+        .await
+        .expect("rule should be created");
+    assert!(
+        !store
+            .automation_rule_has_in_flight_run(rule.id)
+            .await
+            .expect("in-flight check should succeed")
+    );
+
+    let worker_id = Uuid::new_v4();
+    store
+        .claim_due_automation_rules(now, worker_id, 1, 300)
+        .await
+        .expect("claim should succeed");
+    let idempotency_key = format!("{}:{}", rule.id, scheduled_for.timestamp_micros());
+    let run = store
+        .materialize_automation_run(
+            rule.id,
+            worker_id,
+            scheduled_for,
+            Some(next_run_at),
+            &idempotency_key,
+        )
+        .await
+        .expect("materialization should succeed")
+        .expect("lease owner should materialize run");
+    let job_id = store
+        .enqueue_job_with_idempotency_key(
+            user_id,
+            JobType::AutomationRun,
+            now,
+            Some(b"{}"),
+            &idempotency_key,
+        )
+        .await
+        .expect("job enqueue should succeed");
+    store
+        .mark_automation_run_enqueued(run.id, user_id, job_id)
+        .await
+        .expect("mark enqueued should succeed");
+    assert!(
+        store
+            .automation_rule_has_in_flight_run(rule.id)
+            .await
+            .expect("in-flight check should succeed")
+    );
+
+    let second_worker = Uuid::new_v4();
+    let claims = store
+        .claim_due_automation_rules(now, second_worker, 1, 300)
+        .await
+        .expect("claim should succeed");
+    assert_eq!(claims.len(), 1);
+    assert!(
+        !store
+            .release_automation_rule_lease(rule.id, worker_id)
+            .await
+            .expect("release should succeed"),
+        "only the lease owner can release the rule"
+    );
+    assert!(
+        store
+            .release_automation_rule_lease(rule.id, second_worker)
+            .await
+            .expect("release should succeed")
+    );
+    let released = store
+        .get_automation_rule(user_id, rule.id)
+        .await
+        .expect("rule fetch should succeed")
+        .expect("rule should exist");
+    assert_eq!(released.next_run_at, claims[0].next_run_at);
+
+    let job_worker = Uuid::new_v4();
+    let jobs = store
+        .claim_due_jobs(now, job_worker, 10, 300, 1)
+        .await
+        .expect("job claim should succeed");
+    assert_eq!(jobs.len(), 1);
+    assert!(
+        store
+            .automation_rule_has_in_flight_run(rule.id)
+            .await
+            .expect("in-flight check should succeed"),
+        "a running job is still in flight"
+    );
+    store
+        .mark_job_done(job_id, job_worker)
+        .await
+        .expect("job completion should succeed");
+    assert!(
+        !store
+            .automation_rule_has_in_flight_run(rule.id)
+            .await
+            .expect("in-flight check should succeed")
+    );
+    let claims = store
+        .claim_due_automation_rules(now, Uuid::new_v4(), 1, 300)
+        .await
+        .expect("claim should succeed");
+    assert_eq!(claims.len(), 1, "the held back run is claimable again");
+}
+
+#[tokio::test]
+#[serial]
+async fn automation_reports_are_upserted_per_run_and_device_and_user_scoped() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let user_id = Uuid::new_v4();
+    let other_user_id = Uuid::new_v4();
+    let now = Utc::now();
+
+    let rule = store
+        .create_automation_rule(
+            user_id,
+            "Weekly Review",
+            AutomationRuleOptions {
+                template: Some(AutomationTemplate::WeeklyReview),
+                ..Default::default()
+            },
+            &weekly_schedule("UTC", 17, 0, 5),
+            now - ChronoDuration::minutes(1),
+            &prompt_material(b"prompt-weekly", PROMPT_HASH_A),
+        )
+        .await
+        .expect("rule should be created");
+    assert_eq!(rule.template, Some(AutomationTemplate::WeeklyReview));
+
+    let claims = store
+        .claim_due_automation_rules(now, Uuid::new_v4(), 1, 300)
+        .await
+        .expect("claim should succeed");
+    assert_eq!(claims.len(), 1);
+    assert_eq!(claims[0].template, Some(AutomationTemplate::WeeklyReview));
+
+    let run_id = Uuid::new_v4();
+    store
+        .store_automation_report(
+            user_id,
+            rule.id,
+            run_id,
+            "ios-device-1",
+            AutomationTemplate::WeeklyReview,
+            &report_envelope("first"),
+        )
+        .await
+        .expect("report should be stored");
+    let replaced = store
+        .store_automation_report(
+            user_id,
+            rule.id,
+            run_id,
+            "ios-device-1",
+            AutomationTemplate::WeeklyReview,
+            &report_envelope("second"),
+        )
+        .await
+        .expect("retried report should replace the earlier envelope");
+    assert_eq!(replaced.envelope.ciphertext, "second");
+
+    let reports = store
+        .list_automation_reports(user_id, "ios-device-1", PageRequest::first(10))
+        .await
+        .expect("report list should succeed")
+        .items;
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].run_id, run_id);
+    assert_eq!(reports[0].rule_id, rule.id);
+    assert_eq!(reports[0].envelope.ciphertext, "second");
+
+    let other_device = store
+        .list_automation_reports(user_id, "ios-device-2", PageRequest::first(10))
+        .await
+        .expect("report list should succeed")
+        .items;
+    assert!(other_device.is_empty());
+
+    let foreign = store
+        .store_automation_report(
+            other_user_id,
+            rule.id,
+            Uuid::new_v4(),
+            "ios-device-1",
+            AutomationTemplate::WeeklyReview,
+            &report_envelope("foreign"),
+        )
+        .await;
+    assert!(matches!(foreign, Err(StoreError::InvalidData(_))));
+}
+
+#[tokio::test]
+#[serial]
+async fn consecutive_run_failures_pause_the_rule_once() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let user_id = Uuid::new_v4();
+    let now = Utc::now();
+    let scheduled = [40, 30, 20, 10].map(|minutes| now - ChronoDuration::minutes(minutes));
+
+    let rule = store
+        .create_automation_rule(
+            user_id,
+            "Morning brief",
+            AutomationRuleOptions::default(),
+            &daily_schedule("UTC", 7, 0),
+            scheduled[0],
+            &prompt_material(b"prompt-failures", PROMPT_HASH_A),
+        )
+        .await
+        .expect("rule should be created");
+
+    let mut runs = Vec::new();
+    for (index, scheduled_for) in scheduled.iter().enumerate() {
+        let worker = Uuid::new_v4();
+        let claims = store
+            .claim_due_automation_rules(now, worker, 1, 300)
+            .await
+            .expect("claim should succeed");
+        assert_eq!(claims.len(), 1);
+        let run = store
+            .materialize_automation_run(
+                rule.id,
+                worker,
+                *scheduled_for,
+                scheduled
+                    .get(index + 1)
+                    .copied()
+                    .or(Some(now + ChronoDuration::days(1))),
+                &format!("automation:failures:{index}"),
+            )
+            .await
+            .expect("materialization should succeed")
+            .expect("lease owner should materialize run");
+        runs.push(run);
+    }
+    // The first run did not fail, so the streak starts with the second.
+
+    let mut outcomes = Vec::new();
+    for run in &runs[1..] {
+        outcomes.push(
+            store
+                .record_automation_run_failure(
+                    run.id,
+                    user_id,
+                    "AUTOMATION_CONNECTOR_UNAVAILABLE",
+                    3,
+                )
+                .await
+                .expect("failure should be recorded")
+                .expect("run should exist"),
+        );
+    }
+    assert_eq!(
+        outcomes
+            .iter()
+            .map(|outcome| (outcome.consecutive_failures, outcome.paused_rule))
+            .collect::<Vec<_>>(),
+        vec![(1, false), (2, false), (3, true)]
+    );
+    assert!(outcomes.iter().all(|outcome| outcome.rule_id == rule.id));
+
+    let paused = store
+        .get_automation_rule(user_id, rule.id)
+        .await
+        .expect("rule fetch should succeed")
+        .expect("rule should exist");
+    assert_eq!(paused.status.as_str(), "PAUSED");
+
+    // A later failure of an already paused rule does not pause (or alert) again.
+    let again = store
+        .record_automation_run_failure(runs[3].id, user_id, "AUTOMATION_CONNECTOR_UNAVAILABLE", 3)
+        .await
+        .expect("failure should be recorded")
+        .expect("run should exist");
+    assert!(!again.paused_rule);
+
+    // After the user resumes the rule, failures start a new streak.
+    assert!(
+        store
+            .resume_automation_rule(user_id, rule.id, now - ChronoDuration::minutes(5))
+            .await
+            .expect("resume should succeed")
+    );
+    let worker = Uuid::new_v4();
+    store
+        .claim_due_automation_rules(now, worker, 1, 300)
+        .await
+        .expect("claim should succeed");
+    let resumed_run = store
+        .materialize_automation_run(
+            rule.id,
+            worker,
+            now - ChronoDuration::minutes(5),
+            Some(now + ChronoDuration::days(1)),
+            "automation:failures:resumed",
+        )
+        .await
+        .expect("materialization should succeed")
+        .expect("lease owner should materialize run");
+    let after_resume = store
+        .record_automation_run_failure(resumed_run.id, user_id, "AUTOMATION_ENCLAVE_REJECTED", 3)
+        .await
+        .expect("failure should be recorded")
+        .expect("run should exist");
+    assert_eq!(after_resume.consecutive_failures, 1);
+    assert!(!after_resume.paused_rule);
+    assert!(
+        store
+            .record_automation_run_failure(runs[3].id, Uuid::new_v4(), "X", 3)
+            .await
+            .expect("foreign failure should not error")
+            .is_none()
+    );
+
+    let history = store
+        .list_automation_runs_for_rule(user_id, rule.id, 10)
+        .await
+        .expect("run history should load");
+    let pausing_run = &history[1];
+    assert_eq!(pausing_run.id, runs[3].id);
+    assert_eq!(pausing_run.state.as_str(), "FAILED");
+    assert_eq!(
+        pausing_run.failure_code.as_deref(),
+        Some("AUTOMATION_CONNECTOR_UNAVAILABLE")
+    );
+    assert!(pausing_run.paused_rule);
+    assert_eq!(history.iter().filter(|run| run.paused_rule).count(), 1);
+    assert_eq!(history[4].failure_code, None);
+}
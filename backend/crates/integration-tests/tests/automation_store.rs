@@ -1,14 +1,17 @@
 mod support;
 
-use chrono::{Duration as ChronoDuration, Utc};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
 use serial_test::serial;
-use shared::automation_schedule::{AutomationScheduleSpec, AutomationScheduleType};
-use shared::repos::JobType;
-use tokio::join;
+use shared::automation_schedule::{
+    AutomationCondition, AutomationConditionKind, AutomationScheduleSpec, AutomationScheduleType,
+};
+use shared::pagination::PageRequest;
+use shared::repos::{AutomationRuleOptions, StoreError};
 use uuid::Uuid;
 
-const PROMPT_HASH_A: &str = "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
-const PROMPT_HASH_B: &str = "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb";
+use support::automations::{
+    PROMPT_HASH_A, PROMPT_HASH_B, daily_schedule, prompt_material, weekly_schedule,
+};
 
 #[tokio::test]
 #[serial]
@@ -25,10 +28,10 @@ async fn automation_rule_crud_pause_resume_and_delete_flow() {
         .create_automation_rule(
             user_id,
             "Morning Task",
+            AutomationRuleOptions::default(),
             &daily_schedule("America/Los_Angeles", 9, 0),
             next_run_at,
-            prompt_ciphertext,
-            PROMPT_HASH_A,
+            &prompt_material(prompt_ciphertext, PROMPT_HASH_A),
         )
         .await
         .expect("rule should be created");
@@ -60,7 +63,13 @@ async fn automation_rule_crud_pause_resume_and_delete_flow() {
         .expect("rule should exist");
     assert_eq!(updated_schedule.schedule_type.as_str(), "WEEKLY");
     assert_eq!(updated_schedule.local_time_minutes, 630);
-    assert_eq!(updated_schedule.anchor_day_of_week, Some(5));
+    assert_eq!(
+        updated_schedule
+            .schedule_spec()
+            .expect("schedule should decode")
+            .days_of_week(),
+        vec![5]
+    );
     assert_eq!(updated_schedule.time_zone, "America/New_York");
 
     let updated_prompt = store
@@ -92,9 +101,10 @@ async fn automation_rule_crud_pause_resume_and_delete_flow() {
     assert!(resumed);
 
     let listed = store
-        .list_automation_rules(user_id, 10)
+        .list_automation_rules(user_id, false, PageRequest::first(10))
         .await
-        .expect("list should succeed");
+        .expect("list should succeed")
+        .items;
     assert_eq!(listed.len(), 1);
     assert_eq!(listed[0].id, created.id);
 
@@ -113,242 +123,368 @@ async fn automation_rule_crud_pause_resume_and_delete_flow() {
 
 #[tokio::test]
 #[serial]
-async fn due_claims_are_lease_safe_and_split_across_workers() {
+async fn archived_rules_keep_history_and_are_hidden_and_unscheduled_until_restored() {
     let store = support::test_store().await;
     support::reset_database(store.pool()).await;
 
+    let user_id = Uuid::new_v4();
     let now = Utc::now();
-    let rule_a = store
+    let scheduled_for = now - ChronoDuration::minutes(1);
+
+    let rule = store
         .create_automation_rule(
-            Uuid::new_v4(),
-            "Rule A",
-            &daily_schedule("UTC", 8, 0),
-            now - ChronoDuration::minutes(1),
-            b"prompt-a",
-            PROMPT_HASH_A,
+            user_id,
+            "Retired Task",
+            AutomationRuleOptions::default(),
+            &daily_schedule("UTC", 7, 0),
+            scheduled_for,
+            &prompt_material(b"prompt-archive", PROMPT_HASH_A),
         )
         .await
-        .expect("rule a should be created");
-    let rule_b = store
-        .create_automation_rule(
-            Uuid::new_v4(),
-            "Rule B",
-            &daily_schedule("UTC", 9, 0),
-            now - ChronoDuration::minutes(1),
-            b"prompt-b",
-            PROMPT_HASH_B,
+        .expect("rule should be created");
+
+    let worker = Uuid::new_v4();
+    let claims = store
+        .claim_due_automation_rules(now, worker, 1, 300)
+        .await
+        .expect("claim should succeed");
+    assert_eq!(claims.len(), 1);
+    store
+        .materialize_automation_run(
+            rule.id,
+            worker,
+            scheduled_for,
+            Some(scheduled_for),
+            "automation:archive:001",
         )
         .await
-        .expect("rule b should be created");
+        .expect("materialization should succeed")
+        .expect("lease owner should materialize run");
 
-    let worker_a = Uuid::new_v4();
-    let worker_b = Uuid::new_v4();
-    let (claims_a, claims_b) = join!(
-        store.claim_due_automation_rules(now, worker_a, 1, 300),
-        store.claim_due_automation_rules(now, worker_b, 1, 300),
+    let archived = store
+        .archive_automation_rule(user_id, rule.id)
+        .await
+        .expect("archive should succeed");
+    assert!(archived);
+    assert!(
+        !store
+            .archive_automation_rule(Uuid::new_v4(), rule.id)
+            .await
+            .expect("cross-user archive should not fail")
     );
-    let claims_a = claims_a.expect("worker a claim should succeed");
-    let claims_b = claims_b.expect("worker b claim should succeed");
 
-    assert_eq!(claims_a.len(), 1);
-    assert_eq!(claims_b.len(), 1);
+    let fetched = store
+        .get_automation_rule(user_id, rule.id)
+        .await
+        .expect("rule fetch should succeed")
+        .expect("archived rule should still exist");
+    assert_eq!(fetched.status.as_str(), "ARCHIVED");
 
-    let mut claimed_ids = vec![claims_a[0].id, claims_b[0].id];
-    claimed_ids.sort();
-    claimed_ids.dedup();
-    assert_eq!(
-        claimed_ids.len(),
-        2,
-        "workers should not claim the same rule"
-    );
-    assert!(claimed_ids.contains(&rule_a.id));
-    assert!(claimed_ids.contains(&rule_b.id));
+    let claims = store
+        .claim_due_automation_rules(now + ChronoDuration::minutes(10), Uuid::new_v4(), 10, 300)
+        .await
+        .expect("claim should succeed");
+    assert!(claims.is_empty(), "archived rules must not be scheduled");
 
-    let prompt_bytes = if claims_a[0].id == rule_a.id {
-        &claims_a[0].prompt_ciphertext
-    } else {
-        &claims_b[0].prompt_ciphertext
-    };
-    assert_eq!(prompt_bytes, b"prompt-a");
+    let default_list = store
+        .list_automation_rules(user_id, false, PageRequest::first(10))
+        .await
+        .expect("list should succeed")
+        .items;
+    assert!(default_list.is_empty());
+    let full_list = store
+        .list_automation_rules(user_id, true, PageRequest::first(10))
+        .await
+        .expect("list should succeed")
+        .items;
+    assert_eq!(full_list.len(), 1);
+    assert_eq!(full_list[0].id, rule.id);
+
+    let runs = store
+        .list_automation_runs_for_rule(user_id, rule.id, 10)
+        .await
+        .expect("run list should succeed");
+    assert_eq!(runs.len(), 1, "archiving must keep run history");
+
+    let restored = store
+        .resume_automation_rule(user_id, rule.id, scheduled_for)
+        .await
+        .expect("restore should succeed");
+    assert!(restored);
+    let claims = store
+        .claim_due_automation_rules(now + ChronoDuration::minutes(10), Uuid::new_v4(), 10, 300)
+        .await
+        .expect("claim should succeed");
+    assert_eq!(claims.len(), 1);
+    assert_eq!(claims[0].id, rule.id);
 }
 
 #[tokio::test]
 #[serial]
-async fn run_materialization_is_idempotent_for_same_rule_and_scheduled_time() {
+async fn one_shot_rules_complete_when_their_run_is_materialized() {
     let store = support::test_store().await;
     support::reset_database(store.pool()).await;
 
     let user_id = Uuid::new_v4();
     let now = Utc::now();
-    let scheduled_for = now - ChronoDuration::minutes(1);
-    let next_run_at = now + ChronoDuration::minutes(14);
+    // Whole seconds so the stored instant compares equal after the Postgres round trip.
+    let run_at = DateTime::from_timestamp(now.timestamp() - 60, 0).expect("valid run_at");
+    let schedule = AutomationScheduleSpec {
+        schedule_type: AutomationScheduleType::Once,
+        time_zone: "UTC".to_string(),
+        local_time_minutes: 0,
+        anchor_days_of_week: None,
+        anchor_day_of_month: None,
+        anchor_month: None,
+        cron_expression: None,
+        run_at: Some(run_at),
+    };
 
     let rule = store
         .create_automation_rule(
             user_id,
-            "Idempotency Task",
-            &daily_schedule("UTC", 12, 0),
-            scheduled_for,
-            b"prompt-c",
-            PROMPT_HASH_A,
+            "Follow up",
+            AutomationRuleOptions::default(),
+            &schedule,
+            run_at,
+            &prompt_material(b"prompt-once", PROMPT_HASH_A),
         )
         .await
         .expect("rule should be created");
+    assert_eq!(rule.run_at, Some(run_at));
 
-    let worker_a = Uuid::new_v4();
-    let claims_a = store
-        .claim_due_automation_rules(now, worker_a, 1, 300)
+    let worker = Uuid::new_v4();
+    let claims = store
+        .claim_due_automation_rules(now, worker, 10, 300)
         .await
         .expect("claim should succeed");
-    assert_eq!(claims_a.len(), 1);
-
-    let run_first = store
-        .materialize_automation_run(
-            rule.id,
-            worker_a,
-            scheduled_for,
-            next_run_at,
-            "automation:run:001",
-        )
+    assert_eq!(claims.len(), 1);
+    assert_eq!(
+        claims[0].schedule_spec().expect("schedule should load"),
+        schedule
+    );
+    store
+        .materialize_automation_run(rule.id, worker, run_at, None, "automation:once:001")
         .await
-        .expect("first materialization should succeed")
+        .expect("materialization should succeed")
         .expect("lease owner should materialize run");
 
-    store
-        .update_automation_rule_schedule(
-            user_id,
-            rule.id,
-            &daily_schedule("UTC", 12, 0),
-            scheduled_for,
-        )
+    let completed = store
+        .get_automation_rule(user_id, rule.id)
         .await
-        .expect("rule schedule reset should succeed")
-        .expect("rule should still exist");
+        .expect("rule fetch should succeed")
+        .expect("completed rule should still exist");
+    assert_eq!(completed.status.as_str(), "COMPLETED");
+    assert_eq!(completed.next_run_at, run_at);
+    assert_eq!(completed.last_run_at, Some(run_at));
 
-    let worker_b = Uuid::new_v4();
-    let claims_b = store
-        .claim_due_automation_rules(now, worker_b, 1, 300)
+    let claims = store
+        .claim_due_automation_rules(now + ChronoDuration::days(1), Uuid::new_v4(), 10, 300)
         .await
-        .expect("second claim should succeed");
-    assert_eq!(claims_b.len(), 1);
+        .expect("claim should succeed");
+    assert!(claims.is_empty(), "completed rules must not be scheduled");
+}
 
-    let run_second = store
-        .materialize_automation_run(
-            rule.id,
-            worker_b,
-            scheduled_for,
-            next_run_at + ChronoDuration::minutes(15),
-            "automation:run:001",
+#[tokio::test]
+#[serial]
+async fn snoozed_rules_are_skipped_until_their_window_ends() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let user_id = Uuid::new_v4();
+    let now = Utc::now();
+    let rule = store
+        .create_automation_rule(
+            user_id,
+            "Standup prep",
+            AutomationRuleOptions::default(),
+            &daily_schedule("UTC", 9, 0),
+            now - ChronoDuration::minutes(1),
+            &prompt_material(b"prompt-snooze", PROMPT_HASH_A),
         )
         .await
-        .expect("second materialization should succeed")
-        .expect("lease owner should materialize run");
+        .expect("rule should be created");
+    assert_eq!(rule.paused_until, None);
+
+    let paused_until = DateTime::from_timestamp(now.timestamp() + 3_600, 0).expect("valid time");
+    assert!(
+        store
+            .snooze_automation_rule(user_id, rule.id, paused_until)
+            .await
+            .expect("snooze should succeed")
+    );
+    let snoozed = store
+        .get_automation_rule(user_id, rule.id)
+        .await
+        .expect("rule fetch should succeed")
+        .expect("rule should exist");
+    assert_eq!(snoozed.status.as_str(), "PAUSED");
+    assert_eq!(snoozed.paused_until, Some(paused_until));
 
-    assert_eq!(run_first.id, run_second.id);
+    let claims = store
+        .claim_due_automation_rules(now, Uuid::new_v4(), 10, 300)
+        .await
+        .expect("claim should succeed");
+    assert!(claims.is_empty(), "snoozed rules must not be scheduled");
+    assert!(
+        store
+            .list_due_automation_snoozes(now, 10)
+            .await
+            .expect("due snoozes should load")
+            .is_empty()
+    );
 
-    let runs = store
-        .list_automation_runs_for_rule(user_id, rule.id, 10)
+    let after_window = paused_until + ChronoDuration::seconds(1);
+    let due = store
+        .list_due_automation_snoozes(after_window, 10)
         .await
-        .expect("run list should succeed");
-    assert_eq!(runs.len(), 1);
-    assert_eq!(
-        runs[0].scheduled_for.timestamp_micros(),
-        scheduled_for.timestamp_micros()
+        .expect("due snoozes should load");
+    assert_eq!(due.len(), 1);
+    assert_eq!(due[0].id, rule.id);
+
+    let next_run_at = paused_until + ChronoDuration::hours(2);
+    assert!(
+        !store
+            .resume_snoozed_automation_rule(
+                rule.id,
+                paused_until + ChronoDuration::minutes(5),
+                next_run_at
+            )
+            .await
+            .expect("stale resume should not error"),
+        "a snooze changed since listing must not be resumed"
+    );
+    assert!(
+        store
+            .resume_snoozed_automation_rule(rule.id, paused_until, next_run_at)
+            .await
+            .expect("resume should succeed")
+    );
+    let resumed = store
+        .get_automation_rule(user_id, rule.id)
+        .await
+        .expect("rule fetch should succeed")
+        .expect("rule should exist");
+    assert_eq!(resumed.status.as_str(), "ACTIVE");
+    assert_eq!(resumed.paused_until, None);
+    assert_eq!(resumed.next_run_at, next_run_at);
+
+    store
+        .snooze_automation_rule(user_id, rule.id, paused_until)
+        .await
+        .expect("snooze should succeed");
+    store
+        .archive_automation_rule(user_id, rule.id)
+        .await
+        .expect("archive should succeed");
+    assert!(
+        store
+            .list_due_automation_snoozes(after_window, 10)
+            .await
+            .expect("due snoozes should load")
+            .is_empty(),
+        "archiving cancels the snooze"
+    );
+    assert!(
+        !store
+            .snooze_automation_rule(user_id, rule.id, paused_until)
+            .await
+            .expect("snooze should not error"),
+        "archived rules cannot be snoozed"
     );
 }
 
 #[tokio::test]
 #[serial]
-async fn materialized_run_can_be_enqueued_with_stable_job_reference() {
+async fn automation_conditions_persist_and_runs_record_their_evaluation() {
     let store = support::test_store().await;
     support::reset_database(store.pool()).await;
 
     let user_id = Uuid::new_v4();
     let now = Utc::now();
-    let scheduled_for = now - ChronoDuration::minutes(1);
-    let next_run_at = now + ChronoDuration::minutes(9);
-    let idempotency_key = format!("{}:{}", user_id, scheduled_for.timestamp_micros());
+    let condition = AutomationCondition {
+        kind: AutomationConditionKind::UrgentEmails,
+        min_count: 2,
+    };
+    let invalid = store
+        .create_automation_rule(
+            user_id,
+            "Inbox alert",
+            AutomationRuleOptions {
+                condition: Some(AutomationCondition {
+                    min_count: 0,
+                    ..condition
+                }),
+                ..Default::default()
+            },
+            &daily_schedule("UTC", 8, 0),
+            now - ChronoDuration::minutes(1),
+            &prompt_material(b"prompt-condition", PROMPT_HASH_A),
+        )
+        .await
+        .expect_err("a zero min_count should be rejected");
+    assert!(matches!(invalid, StoreError::InvalidData(_)));
 
     let rule = store
         .create_automation_rule(
             user_id,
-            "Stable Job Task",
-            &daily_schedule("UTC", 14, 30),
-            scheduled_for,
-            b"prompt-z",
-            PROMPT_HASH_A,
+            "Inbox alert",
+            AutomationRuleOptions {
+                condition: Some(condition),
+                ..Default::default()
+            },
+            &daily_schedule("UTC", 8, 0),
+            now - ChronoDuration::minutes(1),
+            &prompt_material(b"prompt-condition", PROMPT_HASH_A),
         )
         .await
         .expect("rule should be created");
-    let worker_id = Uuid::new_v4();
+    assert_eq!(rule.condition, Some(condition));
+
+    let worker = Uuid::new_v4();
     let claims = store
-        .claim_due_automation_rules(now, worker_id, 1, 300)
+        .claim_due_automation_rules(now, worker, 10, 300)
         .await
         .expect("claim should succeed");
     assert_eq!(claims.len(), 1);
+    assert_eq!(claims[0].condition, Some(condition));
 
     let run = store
         .materialize_automation_run(
             rule.id,
-            worker_id,
-            scheduled_for,
-            next_run_at,
-            &idempotency_key,
+            worker,
+            rule.next_run_at,
+            Some(now + ChronoDuration::days(1)),
+            "automation:condition:001",
         )
         .await
         .expect("materialization should succeed")
         .expect("lease owner should materialize run");
-
-    let job_id = store
-        .enqueue_job_with_idempotency_key(
-            user_id,
-            JobType::AutomationRun,
-            now,
-            Some(b"{\"automation_run_id\":\"placeholder\"}"),
-            &idempotency_key,
-        )
-        .await
-        .expect("job enqueue should succeed");
-
-    let marked = store
-        .mark_automation_run_enqueued(run.id, user_id, job_id)
-        .await
-        .expect("mark enqueued should succeed");
-    assert!(marked);
-
+    assert_eq!(run.condition_met, None);
+    assert!(
+        !store
+            .record_automation_run_condition(run.id, Uuid::new_v4(), false, 1)
+            .await
+            .expect("cross-user record should not fail"),
+        "another user's run must not be updated"
+    );
+    assert!(
+        store
+            .record_automation_run_condition(run.id, user_id, false, 1)
+            .await
+            .expect("condition result should be recorded")
+    );
     let runs = store
         .list_automation_runs_for_rule(user_id, rule.id, 10)
         .await
-        .expect("run list should succeed");
-    assert_eq!(runs.len(), 1);
-    assert_eq!(runs[0].id, run.id);
-    assert_eq!(runs[0].state.as_str(), "ENQUEUED");
-    assert_eq!(runs[0].job_id, Some(job_id));
-}
-
-fn daily_schedule(time_zone: &str, hour: u16, minute: u16) -> AutomationScheduleSpec {
-    AutomationScheduleSpec {
-        schedule_type: AutomationScheduleType::Daily,
-        time_zone: time_zone.to_string(),
-        local_time_minutes: (hour * 60) + minute,
-        anchor_day_of_week: None,
-        anchor_day_of_month: None,
-        anchor_month: None,
-    }
-}
+        .expect("runs should load");
+    assert_eq!(runs[0].condition_met, Some(false));
+    assert_eq!(runs[0].condition_observed_count, Some(1));
 
-fn weekly_schedule(
-    time_zone: &str,
-    hour: u16,
-    minute: u16,
-    day_of_week: u8,
-) -> AutomationScheduleSpec {
-    AutomationScheduleSpec {
-        schedule_type: AutomationScheduleType::Weekly,
-        time_zone: time_zone.to_string(),
-        local_time_minutes: (hour * 60) + minute,
-        anchor_day_of_week: Some(day_of_week),
-        anchor_day_of_month: None,
-        anchor_month: None,
-    }
+    let cleared = store
+        .update_automation_rule_condition(user_id, rule.id, None)
+        .await
+        .expect("condition update should succeed")
+        .expect("rule should exist");
+    assert_eq!(cleared.condition, None);
 }
@@ -0,0 +1,123 @@
+mod support;
+
+use chrono::Utc;
+use serial_test::serial;
+use shared::automation_schedule::{
+    AutomationScheduleSpec, AutomationScheduleType, AutomationTemplate,
+};
+use shared::brief_profile::{
+    MorningBriefProfile, MorningBriefSection, MorningBriefVerbosity,
+    morning_brief_notification_body,
+};
+use shared::llm::contracts::MorningBriefOutput;
+use shared::repos::{AutomationPromptMaterial, AutomationRuleOptions};
+use uuid::Uuid;
+
+#[tokio::test]
+#[serial]
+async fn saved_brief_profile_shapes_the_morning_brief_notification() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let user_id = Uuid::new_v4();
+    let output = MorningBriefOutput {
+        headline: "Busy morning".to_string(),
+        summary: "Three meetings and one urgent thread.".to_string(),
+        priorities: vec!["Send the budget".to_string()],
+        schedule: vec![
+            "9:00 standup".to_string(),
+            "11:00 design review".to_string(),
+        ],
+        alerts: vec!["Legal replied".to_string(), "Invoice overdue".to_string()],
+    };
+
+    let unsaved = store
+        .get_morning_brief_profile(user_id)
+        .await
+        .expect("default profile should load");
+    assert_eq!(
+        morning_brief_notification_body(&output, &unsaved.profile),
+        "Three meetings and one urgent thread. • Priority: Send the budget • \
+         Schedule: 9:00 standup • Alert: Legal replied"
+    );
+
+    store
+        .upsert_morning_brief_profile(
+            user_id,
+            &MorningBriefProfile {
+                sections: vec![MorningBriefSection::Alerts, MorningBriefSection::Schedule],
+                verbosity: MorningBriefVerbosity::Concise,
+            },
+            false,
+        )
+        .await
+        .expect("profile should save");
+    let concise = store
+        .get_morning_brief_profile(user_id)
+        .await
+        .expect("saved profile should load");
+    assert_eq!(
+        morning_brief_notification_body(&output, &concise.profile),
+        "Alert: Legal replied • Schedule: 9:00 standup"
+    );
+
+    store
+        .upsert_morning_brief_profile(
+            user_id,
+            &MorningBriefProfile {
+                sections: vec![MorningBriefSection::Alerts],
+                verbosity: MorningBriefVerbosity::Detailed,
+            },
+            false,
+        )
+        .await
+        .expect("profile should save");
+    let detailed = store
+        .get_morning_brief_profile(user_id)
+        .await
+        .expect("saved profile should load");
+    assert_eq!(
+        morning_brief_notification_body(&output, &detailed.profile),
+        "Three meetings and one urgent thread. • Alert: Legal replied • Alert: Invoice overdue"
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn morning_brief_template_rules_persist() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let user_id = Uuid::new_v4();
+    let rule = store
+        .create_automation_rule(
+            user_id,
+            "Morning brief",
+            AutomationRuleOptions {
+                template: Some(AutomationTemplate::MorningBrief),
+                ..AutomationRuleOptions::default()
+            },
+            &AutomationScheduleSpec {
+                schedule_type: AutomationScheduleType::Daily,
+                time_zone: "UTC".to_string(),
+                local_time_minutes: 7 * 60,
+                anchor_days_of_week: None,
+                anchor_day_of_month: None,
+                anchor_month: None,
+                cron_expression: None,
+                run_at: None,
+            },
+            Utc::now(),
+            &AutomationPromptMaterial {
+                prompt_ciphertext: b"prompt".to_vec(),
+                prompt_sha256: "a".repeat(64),
+            },
+        )
+        .await
+        .expect("morning brief rule should be created");
+    assert_eq!(rule.template, Some(AutomationTemplate::MorningBrief));
+    assert_eq!(
+        AutomationTemplate::MorningBrief.required_schedule_type(),
+        AutomationScheduleType::Daily
+    );
+}
@@ -0,0 +1,140 @@
+mod support;
+
+use serial_test::serial;
+use shared::enclave::{CalendarProvider, EnclaveRpcError};
+use shared::repos::ConnectorAccount;
+use uuid::Uuid;
+
+#[tokio::test]
+#[serial]
+async fn each_google_account_gets_its_own_connector() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let user_id = Uuid::new_v4();
+    let scopes = vec!["https://www.googleapis.com/auth/calendar.readonly".to_string()];
+    let reserved_id = store
+        .connector_id_for_account(user_id, "google", "account-work")
+        .await
+        .expect("id lookup should succeed");
+    let work_connector = store
+        .upsert_google_connector(
+            ConnectorAccount {
+                user_id,
+                connector_id: reserved_id,
+                account_key: "account-work",
+            },
+            "refresh-token-work",
+            &scopes,
+            "kms/local/alfred-refresh-token",
+            1,
+        )
+        .await
+        .expect("work account insert should succeed");
+    let personal_connector = store
+        .upsert_google_connector(
+            ConnectorAccount {
+                user_id,
+                connector_id: Uuid::new_v4(),
+                account_key: "account-personal",
+            },
+            "refresh-token-personal",
+            &scopes,
+            "kms/local/alfred-refresh-token",
+            1,
+        )
+        .await
+        .expect("personal account insert should succeed");
+    assert_eq!(
+        work_connector, reserved_id,
+        "a new row takes the settled id"
+    );
+    assert_ne!(work_connector, personal_connector);
+    assert_eq!(
+        store
+            .connector_id_for_account(user_id, "google", "account-work")
+            .await
+            .expect("id lookup should succeed"),
+        work_connector
+    );
+
+    let reconnected = store
+        .upsert_google_connector(
+            ConnectorAccount {
+                user_id,
+                connector_id: Uuid::new_v4(),
+                account_key: "account-work",
+            },
+            "refresh-token-work-2",
+            &scopes,
+            "kms/local/alfred-refresh-token",
+            1,
+        )
+        .await
+        .expect("work account reconnect should succeed");
+    assert_eq!(reconnected, work_connector, "reconnect updates in place");
+
+    let connectors = store
+        .list_connectors(user_id)
+        .await
+        .expect("listing should succeed");
+    assert_eq!(
+        connectors
+            .iter()
+            .map(|connector| connector.connector_id)
+            .collect::<Vec<_>>(),
+        vec![work_connector, personal_connector]
+    );
+
+    store
+        .revoke_connector(user_id, work_connector)
+        .await
+        .expect("revoke should succeed");
+    let active = store
+        .list_active_connector_metadata(user_id)
+        .await
+        .expect("active listing should succeed");
+    assert_eq!(active.len(), 1);
+    assert_eq!(active[0].connector_id, personal_connector);
+}
+
+#[tokio::test]
+#[serial]
+async fn calendar_conditions_resolve_caldav_only_users() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let user_id = Uuid::new_v4();
+    let caldav_connector = store
+        .upsert_caldav_connector(
+            ConnectorAccount {
+                user_id,
+                connector_id: Uuid::new_v4(),
+                account_key: "caldav-account",
+            },
+            "{\"app_password\":\"sealed\"}",
+            "kms/local/alfred-refresh-token",
+            1,
+        )
+        .await
+        .expect("caldav connector should store");
+    let service = support::enclave_service::test_enclave_service(store);
+
+    let calendar_connectors = service
+        .resolve_active_calendar_connector_requests(user_id)
+        .await
+        .expect("a caldav connector should serve calendar conditions");
+    assert_eq!(calendar_connectors.len(), 1);
+    assert_eq!(calendar_connectors[0].provider, CalendarProvider::Caldav);
+    assert_eq!(
+        calendar_connectors[0].connector.connector_id,
+        caldav_connector
+    );
+
+    assert!(matches!(
+        service
+            .resolve_active_google_connector_requests(user_id)
+            .await,
+        Err(EnclaveRpcError::ConnectorTokenUnavailable)
+    ));
+}
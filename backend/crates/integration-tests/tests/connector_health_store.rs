@@ -0,0 +1,121 @@
+mod support;
+
+use chrono::{Duration, Utc};
+use serial_test::serial;
+use shared::connector_health::{ConnectorHealthSignal, REQUIRED_GOOGLE_SCOPES};
+use shared::repos::{ConnectorAccount, LEGACY_CONNECTOR_ACCOUNT_KEY};
+use uuid::Uuid;
+
+#[tokio::test]
+#[serial]
+async fn connector_health_drops_on_failures_and_reauth_nudges_are_rate_limited() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let user_id = Uuid::new_v4();
+    let all_scopes = REQUIRED_GOOGLE_SCOPES
+        .iter()
+        .map(|scope| (*scope).to_string())
+        .collect::<Vec<_>>();
+    let connector_id = store
+        .upsert_google_connector(
+            ConnectorAccount {
+                user_id,
+                connector_id: Uuid::new_v4(),
+                account_key: LEGACY_CONNECTOR_ACCOUNT_KEY,
+            },
+            "refresh-token",
+            &all_scopes,
+            "kms/local/alfred-refresh-token",
+            1,
+        )
+        .await
+        .expect("connector insert should succeed");
+
+    let score = store
+        .record_connector_health_signal(connector_id, ConnectorHealthSignal::Healthy)
+        .await
+        .expect("health signal should persist");
+    assert_eq!(score, Some(100));
+    let score = store
+        .record_connector_health_signal(connector_id, ConnectorHealthSignal::RefreshFailed)
+        .await
+        .expect("health signal should persist");
+    assert_eq!(score, Some(60));
+
+    let now = Utc::now();
+    let none_due = store
+        .claim_connector_reauth_nudges(60, now, now - Duration::hours(72), 10)
+        .await
+        .expect("claim should succeed");
+    assert!(none_due.is_empty(), "score at the threshold is not nudged");
+
+    store
+        .record_connector_health_signal(connector_id, ConnectorHealthSignal::RefreshFailed)
+        .await
+        .expect("health signal should persist");
+    let nudges = store
+        .claim_connector_reauth_nudges(60, now, now - Duration::hours(72), 10)
+        .await
+        .expect("claim should succeed");
+    assert_eq!(nudges.len(), 1);
+    assert_eq!(nudges[0].connector_id, connector_id);
+    assert_eq!(nudges[0].health_score, 20);
+    assert_eq!(nudges[0].refresh_failure_count, 2);
+
+    let later = now + Duration::hours(1);
+    let rate_limited = store
+        .claim_connector_reauth_nudges(60, later, later - Duration::hours(72), 10)
+        .await
+        .expect("claim should succeed");
+    assert!(rate_limited.is_empty(), "nudge interval has not elapsed");
+
+    let much_later = now + Duration::hours(73);
+    let renudged = store
+        .claim_connector_reauth_nudges(60, much_later, much_later - Duration::hours(72), 10)
+        .await
+        .expect("claim should succeed");
+    assert_eq!(renudged.len(), 1);
+
+    store
+        .upsert_google_connector(
+            ConnectorAccount {
+                user_id,
+                connector_id: Uuid::new_v4(),
+                account_key: LEGACY_CONNECTOR_ACCOUNT_KEY,
+            },
+            "refresh-token-2",
+            &all_scopes[1..],
+            "kms/local/alfred-refresh-token",
+            1,
+        )
+        .await
+        .expect("reconnect should succeed");
+    let connectors = store
+        .list_connectors(user_id)
+        .await
+        .expect("listing should succeed");
+    assert_eq!(
+        connectors[0].health_score, 55,
+        "reconnect resets failures but keeps the scope gap"
+    );
+    let scope_gap = store
+        .claim_connector_reauth_nudges(60, much_later, much_later - Duration::hours(72), 10)
+        .await
+        .expect("claim should succeed");
+    assert_eq!(scope_gap.len(), 1, "reconnect clears the previous nudge");
+    assert_eq!(
+        scope_gap[0].missing_scopes,
+        vec![REQUIRED_GOOGLE_SCOPES[0].to_string()]
+    );
+
+    store
+        .revoke_connector(user_id, connector_id)
+        .await
+        .expect("revoke should succeed");
+    let revoked = store
+        .record_connector_health_signal(connector_id, ConnectorHealthSignal::RefreshFailed)
+        .await
+        .expect("health signal should be ignored");
+    assert_eq!(revoked, None);
+}
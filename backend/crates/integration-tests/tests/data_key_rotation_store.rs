@@ -0,0 +1,143 @@
+mod support;
+
+use chrono::{Duration, Utc};
+use serial_test::serial;
+use shared::automation_schedule::{AutomationScheduleSpec, AutomationScheduleType};
+use shared::models::ApnsEnvironment;
+use shared::repos::{
+    AutomationPromptMaterial, AutomationRuleOptions, DataEncryptionKey, DataEncryptionKeyring,
+    JobType,
+};
+use uuid::Uuid;
+
+const PROMPT_HASH: &str = "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+const ROTATED_DATA_ENCRYPTION_KEY: &str = "integration-tests-data-key-v2";
+
+#[tokio::test]
+#[serial]
+async fn secondary_key_rows_stay_readable_and_are_reencrypted_to_primary() {
+    let legacy_store = support::test_store().await;
+    support::reset_database(legacy_store.pool()).await;
+
+    let user_id = Uuid::new_v4();
+    let now = Utc::now();
+    legacy_store
+        .register_device(
+            user_id,
+            "device-1",
+            "apns-token",
+            &ApnsEnvironment::Sandbox,
+            None,
+            None,
+        )
+        .await
+        .expect("device registration should succeed");
+    let rule = legacy_store
+        .create_automation_rule(
+            user_id,
+            "Morning Task",
+            AutomationRuleOptions::default(),
+            &daily_schedule(),
+            now + Duration::minutes(5),
+            &prompt_material(b"sealed-automation-prompt", PROMPT_HASH),
+        )
+        .await
+        .expect("rule should be created");
+    legacy_store
+        .enqueue_job(
+            user_id,
+            JobType::AutomationRun,
+            now - Duration::seconds(1),
+            Some(b"opaque-payload"),
+        )
+        .await
+        .expect("job enqueue should succeed");
+
+    let rotating_store = support::test_store_with_keyring(&DataEncryptionKeyring {
+        primary: DataEncryptionKey {
+            key_id: "v2".to_string(),
+            key: ROTATED_DATA_ENCRYPTION_KEY.to_string(),
+        },
+        secondary: Some(DataEncryptionKey {
+            key_id: support::DEFAULT_DATA_ENCRYPTION_KEY_ID.to_string(),
+            key: support::DEFAULT_DATA_ENCRYPTION_KEY.to_string(),
+        }),
+    })
+    .await;
+
+    let devices = rotating_store
+        .list_registered_devices(user_id)
+        .await
+        .expect("secondary key rows should remain readable");
+    assert_eq!(devices[0].apns_token, "apns-token");
+
+    let counts = rotating_store
+        .reencrypt_secondary_key_rows(100)
+        .await
+        .expect("re-encryption should succeed");
+    assert_eq!(counts.devices, 1);
+    assert_eq!(counts.automation_rules, 1);
+    assert_eq!(counts.jobs, 1);
+    assert_eq!(counts.total(), 3);
+
+    let second_pass = rotating_store
+        .reencrypt_secondary_key_rows(100)
+        .await
+        .expect("second re-encryption pass should succeed");
+    assert_eq!(second_pass.total(), 0);
+
+    let rotated_store = support::test_store_with_keyring(&DataEncryptionKeyring::single(
+        "v2",
+        ROTATED_DATA_ENCRYPTION_KEY,
+    ))
+    .await;
+
+    let devices = rotated_store
+        .list_registered_devices(user_id)
+        .await
+        .expect("rotated rows should be readable with primary key only");
+    assert_eq!(devices[0].apns_token, "apns-token");
+
+    let prompt = rotated_store
+        .get_automation_rule_prompt_material(user_id, rule.id)
+        .await
+        .expect("prompt lookup should succeed")
+        .expect("rule should exist");
+    assert_eq!(prompt.prompt_ciphertext, b"sealed-automation-prompt");
+
+    let claimed = rotated_store
+        .claim_due_jobs(now, Uuid::new_v4(), 10, 1, 60)
+        .await
+        .expect("job claim should succeed");
+    assert_eq!(claimed.len(), 1);
+    assert_eq!(
+        claimed[0].payload_ciphertext.as_deref(),
+        Some(b"opaque-payload".as_slice())
+    );
+
+    let legacy_read = legacy_store.list_registered_devices(user_id).await;
+    assert!(
+        legacy_read.is_err(),
+        "stores without the primary key must fail closed"
+    );
+}
+
+fn daily_schedule() -> AutomationScheduleSpec {
+    AutomationScheduleSpec {
+        schedule_type: AutomationScheduleType::Daily,
+        time_zone: "America/Los_Angeles".to_string(),
+        local_time_minutes: 540,
+        anchor_days_of_week: None,
+        anchor_day_of_month: None,
+        anchor_month: None,
+        cron_expression: None,
+        run_at: None,
+    }
+}
+
+fn prompt_material(ciphertext: &[u8], prompt_sha256: &str) -> AutomationPromptMaterial {
+    AutomationPromptMaterial {
+        prompt_ciphertext: ciphertext.to_vec(),
+        prompt_sha256: prompt_sha256.to_string(),
+    }
+}
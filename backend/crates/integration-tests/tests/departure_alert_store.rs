@@ -0,0 +1,172 @@
+mod support;
+
+use chrono::{Duration as ChronoDuration, NaiveDate, Utc};
+use serial_test::serial;
+use shared::departure_alert::DepartureAlertSettings;
+use shared::models::AutomationPromptEnvelope;
+use shared::repos::StoreError;
+use uuid::Uuid;
+
+#[tokio::test]
+#[serial]
+async fn departure_preferences_round_trip_home_location_and_claim_due_checks() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let user_id = Uuid::new_v4();
+    let now = Utc::now();
+    let saved = store
+        .upsert_departure_alert_preferences(
+            user_id,
+            &settings(true),
+            Some(&home_location_envelope()),
+            Some(now),
+            None,
+        )
+        .await
+        .expect("preferences should save")
+        .expect("unconditional save should apply");
+    assert!(saved.has_home_location);
+    assert!(saved.next_check_at.is_some());
+    assert_eq!(saved.settings.time_zone, "America/New_York");
+
+    let material = store
+        .get_departure_alert_job_material(user_id)
+        .await
+        .expect("job material should load")
+        .expect("job material should exist");
+    let envelope = material
+        .home_location_envelope
+        .expect("home location envelope should decrypt at rest");
+    assert_eq!(envelope.ciphertext, "c2VhbGVkLWhvbWU=");
+    assert_eq!(material.settings.travel_minutes, 25);
+
+    let due = store
+        .list_due_departure_checks(now + ChronoDuration::seconds(1), 10)
+        .await
+        .expect("due checks should list");
+    assert_eq!(due.len(), 1);
+    assert_eq!(due[0].user_id, user_id);
+
+    let next_check_at = now + ChronoDuration::days(1);
+    assert!(
+        store
+            .advance_departure_check(user_id, due[0].next_check_at, next_check_at)
+            .await
+            .expect("first advance should succeed")
+    );
+    assert!(
+        !store
+            .advance_departure_check(user_id, due[0].next_check_at, next_check_at)
+            .await
+            .expect("second advance should run"),
+        "only one worker may claim a due check"
+    );
+    assert!(
+        store
+            .list_due_departure_checks(now + ChronoDuration::seconds(1), 10)
+            .await
+            .expect("due checks should list")
+            .is_empty()
+    );
+
+    let disabled = store
+        .upsert_departure_alert_preferences(user_id, &settings(false), None, Some(now), None)
+        .await
+        .expect("disabling should save")
+        .expect("unconditional save should apply");
+    assert!(!disabled.has_home_location);
+    assert_eq!(disabled.next_check_at, None);
+}
+
+#[tokio::test]
+#[serial]
+async fn departure_deliveries_are_recorded_per_local_day() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let user_id = Uuid::new_v4();
+    store
+        .upsert_departure_alert_preferences(user_id, &settings(true), None, None, None)
+        .await
+        .expect("preferences should save");
+    let day = NaiveDate::from_ymd_opt(2026, 3, 2).expect("date should be valid");
+    let leave_by = Utc::now();
+
+    assert_eq!(
+        store
+            .get_departure_alert_delivery(user_id, day)
+            .await
+            .expect("delivery lookup should succeed"),
+        None
+    );
+
+    store
+        .record_departure_alert_delivery(user_id, day, "fingerprint-a", leave_by)
+        .await
+        .expect("delivery should record");
+    store
+        .record_departure_alert_delivery(user_id, day, "fingerprint-b", leave_by)
+        .await
+        .expect("moved meeting delivery should replace the first");
+
+    assert_eq!(
+        store
+            .get_departure_alert_delivery(user_id, day)
+            .await
+            .expect("delivery lookup should succeed")
+            .as_deref(),
+        Some("fingerprint-b")
+    );
+    assert_eq!(
+        store
+            .get_departure_alert_delivery(user_id, day.succ_opt().expect("next day"))
+            .await
+            .expect("delivery lookup should succeed"),
+        None
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn departure_preferences_reject_out_of_range_minutes() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let err = store
+        .upsert_departure_alert_preferences(
+            Uuid::new_v4(),
+            &DepartureAlertSettings {
+                travel_minutes: 0,
+                ..settings(true)
+            },
+            None,
+            None,
+            None,
+        )
+        .await
+        .expect_err("zero travel minutes should be rejected");
+    assert!(matches!(err, StoreError::InvalidData(_)));
+}
+
+fn settings(enabled: bool) -> DepartureAlertSettings {
+    DepartureAlertSettings {
+        enabled,
+        time_zone: "America/New_York".to_string(),
+        check_local_time_minutes: 6 * 60,
+        travel_minutes: 25,
+        buffer_minutes: 5,
+    }
+}
+
+fn home_location_envelope() -> AutomationPromptEnvelope {
+    AutomationPromptEnvelope {
+        version: "v1".to_string(),
+        algorithm: "x25519-chacha20poly1305".to_string(),
+        key_id: "assistant-ingress-v1".to_string(),
+        request_id: "home-location".to_string(),
+        client_ephemeral_public_key: "cHVibGlj".to_string(),
+        nonce: "bm9uY2U=".to_string(),
+        ciphertext: "c2VhbGVkLWhvbWU=".to_string(),
+    }
+}
@@ -0,0 +1,358 @@
+mod support;
+
+use chrono::{Duration, Utc};
+use serial_test::serial;
+use shared::models::ApnsEnvironment;
+use shared::repos::DeviceNotificationKey;
+use uuid::Uuid;
+
+const NOTIFICATION_KEY_ALGORITHM: &str = "x25519-chacha20poly1305";
+
+#[tokio::test]
+#[serial]
+async fn notification_key_rotation_keeps_previous_key_during_overlap_window() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let user_id = Uuid::new_v4();
+    let first_key = notification_key("key-1", "AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE=");
+    store
+        .register_device(
+            user_id,
+            "device-1",
+            "apns-token",
+            &ApnsEnvironment::Sandbox,
+            None,
+            Some(&first_key),
+        )
+        .await
+        .expect("device registration should succeed");
+
+    let second_key = notification_key("key-2", "AgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgI=");
+    let rotation = store
+        .rotate_device_notification_key(
+            user_id,
+            "device-1",
+            &second_key,
+            Utc::now() + Duration::hours(1),
+        )
+        .await
+        .expect("rotation should succeed")
+        .expect("registered device should be rotated");
+    assert_eq!(rotation.key_id, "key-2");
+    assert_eq!(rotation.previous_key_id.as_deref(), Some("key-1"));
+    assert!(rotation.previous_key_expires_at.is_some());
+
+    let devices = store
+        .list_registered_devices(user_id)
+        .await
+        .expect("device listing should succeed");
+    assert_eq!(devices.len(), 1);
+    assert_eq!(devices[0].notification_key.as_ref(), Some(&second_key));
+    assert_eq!(
+        devices[0].previous_notification_key.as_ref(),
+        Some(&first_key)
+    );
+
+    // Re-submitting the active key id must not replace the overlap key with itself.
+    let retried = store
+        .rotate_device_notification_key(
+            user_id,
+            "device-1",
+            &second_key,
+            Utc::now() + Duration::hours(2),
+        )
+        .await
+        .expect("retried rotation should succeed")
+        .expect("registered device should be rotated");
+    assert_eq!(retried.previous_key_id.as_deref(), Some("key-1"));
+    assert_eq!(
+        retried.previous_key_expires_at,
+        rotation.previous_key_expires_at
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn expired_previous_notification_key_is_not_returned() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let user_id = Uuid::new_v4();
+    store
+        .register_device(
+            user_id,
+            "device-1",
+            "apns-token",
+            &ApnsEnvironment::Production,
+            None,
+            Some(&notification_key(
+                "key-1",
+                "AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE=",
+            )),
+        )
+        .await
+        .expect("device registration should succeed");
+
+    store
+        .rotate_device_notification_key(
+            user_id,
+            "device-1",
+            &notification_key("key-2", "AgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgI="),
+            Utc::now() - Duration::seconds(1),
+        )
+        .await
+        .expect("rotation should succeed")
+        .expect("registered device should be rotated");
+
+    let devices = store
+        .list_registered_devices(user_id)
+        .await
+        .expect("device listing should succeed");
+    assert_eq!(
+        devices[0]
+            .notification_key
+            .as_ref()
+            .map(|key| key.key_id.as_str()),
+        Some("key-2")
+    );
+    assert!(devices[0].previous_notification_key.is_none());
+
+    let missing = store
+        .rotate_device_notification_key(
+            Uuid::new_v4(),
+            "device-1",
+            &notification_key("key-3", "AwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwM="),
+            Utc::now(),
+        )
+        .await
+        .expect("rotation lookup should succeed");
+    assert!(missing.is_none());
+}
+
+#[tokio::test]
+#[serial]
+async fn stale_devices_are_purged_while_recently_seen_devices_are_kept() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let user_id = Uuid::new_v4();
+    for device_id in ["stale-device", "delivered-device", "fresh-device"] {
+        store
+            .register_device(
+                user_id,
+                device_id,
+                "apns-token",
+                &ApnsEnvironment::Sandbox,
+                None,
+                None,
+            )
+            .await
+            .expect("device registration should succeed");
+    }
+    sqlx::query(
+        "UPDATE devices
+         SET last_seen_at = NOW() - INTERVAL '200 days'
+         WHERE device_identifier IN ('stale-device', 'delivered-device')",
+    )
+    .execute(store.pool())
+    .await
+    .expect("last seen backdate should succeed");
+    store
+        .record_device_delivery(user_id, "delivered-device")
+        .await
+        .expect("delivery should refresh last seen");
+
+    let removed = store
+        .purge_stale_devices_batch(Utc::now() - Duration::days(120), 10)
+        .await
+        .expect("stale purge should succeed");
+    assert_eq!(removed.len(), 1);
+    assert_eq!(removed[0].user_id, user_id);
+    assert_eq!(removed[0].device_id, "stale-device");
+
+    let mut remaining = store
+        .list_devices(user_id)
+        .await
+        .expect("device listing should succeed")
+        .into_iter()
+        .map(|device| device.device_id)
+        .collect::<Vec<_>>();
+    remaining.sort();
+    assert_eq!(remaining, vec!["delivered-device", "fresh-device"]);
+}
+
+#[tokio::test]
+#[serial]
+async fn repeated_destination_failures_disable_device_until_reenabled() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let user_id = Uuid::new_v4();
+    for device_id in ["flaky-device", "gone-device"] {
+        store
+            .register_device(
+                user_id,
+                device_id,
+                "apns-token",
+                &ApnsEnvironment::Sandbox,
+                None,
+                None,
+            )
+            .await
+            .expect("device registration should succeed");
+    }
+
+    for expected in 1..=2 {
+        let score = store
+            .record_device_delivery_failure(
+                user_id,
+                "flaky-device",
+                "APNS_BADDEVICETOKEN",
+                3,
+                false,
+            )
+            .await
+            .expect("failure should record")
+            .expect("device should exist");
+        assert_eq!(score.consecutive_failures, expected);
+        assert!(!score.newly_disabled);
+    }
+    store
+        .record_device_delivery(user_id, "flaky-device")
+        .await
+        .expect("delivery should reset the score");
+    for _ in 0..2 {
+        store
+            .record_device_delivery_failure(
+                user_id,
+                "flaky-device",
+                "APNS_BADDEVICETOKEN",
+                3,
+                false,
+            )
+            .await
+            .expect("failure should record");
+    }
+    let third = store
+        .record_device_delivery_failure(user_id, "flaky-device", "APNS_BADDEVICETOKEN", 3, false)
+        .await
+        .expect("failure should record")
+        .expect("device should exist");
+    assert!(third.newly_disabled);
+
+    let gone = store
+        .record_device_delivery_failure(user_id, "gone-device", "APNS_UNREGISTERED", 3, true)
+        .await
+        .expect("failure should record")
+        .expect("device should exist");
+    assert!(gone.newly_disabled);
+    let repeat = store
+        .record_device_delivery_failure(user_id, "gone-device", "APNS_UNREGISTERED", 3, true)
+        .await
+        .expect("failure should record")
+        .expect("device should exist");
+    assert!(!repeat.newly_disabled);
+
+    assert!(
+        store
+            .list_registered_devices(user_id)
+            .await
+            .expect("fan-out listing should succeed")
+            .is_empty()
+    );
+    assert!(
+        !store
+            .has_registered_device(user_id)
+            .await
+            .expect("device lookup should succeed")
+    );
+    let listed = store
+        .list_devices(user_id)
+        .await
+        .expect("device listing should succeed");
+    assert!(listed.iter().all(|device| device.disabled_at.is_some()));
+
+    assert!(
+        store
+            .enable_device(user_id, "flaky-device")
+            .await
+            .expect("enable should succeed")
+    );
+    let active = store
+        .list_registered_devices(user_id)
+        .await
+        .expect("fan-out listing should succeed");
+    assert_eq!(active.len(), 1);
+    assert_eq!(active[0].device_id, "flaky-device");
+}
+
+#[tokio::test]
+#[serial]
+async fn backlog_summary_is_claimed_once_per_device_registration() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let user_id = Uuid::new_v4();
+    store
+        .register_device(
+            user_id,
+            "device-1",
+            "apns-token",
+            &ApnsEnvironment::Sandbox,
+            None,
+            None,
+        )
+        .await
+        .expect("device registration should succeed");
+    let registered_at = store
+        .list_registered_devices(user_id)
+        .await
+        .expect("device lookup should succeed")[0]
+        .registered_at;
+
+    assert!(
+        store
+            .claim_device_backlog_summary(user_id, "device-1")
+            .await
+            .expect("summary claim should succeed")
+    );
+    assert!(
+        !store
+            .claim_device_backlog_summary(user_id, "device-1")
+            .await
+            .expect("summary claim should succeed")
+    );
+
+    // A token refresh is not a first registration.
+    store
+        .register_device(
+            user_id,
+            "device-1",
+            "apns-token-refreshed",
+            &ApnsEnvironment::Sandbox,
+            None,
+            None,
+        )
+        .await
+        .expect("device re-registration should succeed");
+    let devices = store
+        .list_registered_devices(user_id)
+        .await
+        .expect("device lookup should succeed");
+    assert_eq!(devices[0].registered_at, registered_at);
+    assert!(
+        !store
+            .claim_device_backlog_summary(user_id, "device-1")
+            .await
+            .expect("summary claim should succeed")
+    );
+}
+
+fn notification_key(key_id: &str, public_key: &str) -> DeviceNotificationKey {
+    DeviceNotificationKey {
+        key_id: key_id.to_string(),
+        algorithm: NOTIFICATION_KEY_ALGORITHM.to_string(),
+        public_key: public_key.to_string(),
+    }
+}
@@ -0,0 +1,176 @@
+mod support;
+
+use chrono::{Duration, Utc};
+use serial_test::serial;
+use shared::repos::{JobType, StoreError};
+use uuid::Uuid;
+
+#[tokio::test]
+#[serial]
+async fn oauth_state_purge_reclaims_consumed_and_expired_states_only() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let now = Utc::now();
+    let user_id = Uuid::new_v4();
+    for (state_hash, expires_at) in [
+        (b"state-expired".as_slice(), now - Duration::minutes(1)),
+        (b"state-consumed".as_slice(), now + Duration::minutes(5)),
+        (b"state-active".as_slice(), now + Duration::minutes(5)),
+    ] {
+        store
+            .store_oauth_state(user_id, state_hash, "alfred://oauth/google", expires_at)
+            .await
+            .expect("oauth state should store");
+    }
+    assert!(
+        store
+            .consume_oauth_state(user_id, b"state-consumed", now)
+            .await
+            .expect("consume should succeed")
+            .is_some()
+    );
+
+    assert!(matches!(
+        store.purge_expired_oauth_states_batch(now, 0).await,
+        Err(StoreError::InvalidData(_))
+    ));
+    assert_eq!(
+        store
+            .purge_expired_oauth_states_batch(now, 1)
+            .await
+            .expect("purge should succeed"),
+        1
+    );
+    assert_eq!(
+        store
+            .purge_expired_oauth_states_batch(now, 10)
+            .await
+            .expect("purge should succeed"),
+        1
+    );
+
+    let remaining: Vec<Vec<u8>> = sqlx::query_scalar("SELECT state_hash FROM oauth_states")
+        .fetch_all(store.pool())
+        .await
+        .expect("oauth states should load");
+    assert_eq!(remaining, vec![b"state-active".to_vec()]);
+}
+
+#[tokio::test]
+#[serial]
+async fn idempotency_purge_reclaims_keys_older_than_the_retention_window() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let now = Utc::now();
+    let user_id = Uuid::new_v4();
+    let job_id = store
+        .enqueue_job(user_id, JobType::AutomationRun, now, None)
+        .await
+        .expect("job enqueue should succeed");
+    for action_key in ["action-old", "action-recent"] {
+        assert!(
+            store
+                .record_outbound_action_idempotency(user_id, action_key, job_id)
+                .await
+                .expect("idempotency key should record")
+        );
+    }
+    sqlx::query(
+        "UPDATE outbound_action_idempotency
+         SET created_at = $1
+         WHERE action_key = 'action-old'",
+    )
+    .bind(now - Duration::days(45))
+    .execute(store.pool())
+    .await
+    .expect("idempotency key should backdate");
+
+    assert_eq!(
+        store
+            .purge_expired_outbound_action_idempotency_batch(now - Duration::days(30), 10)
+            .await
+            .expect("purge should succeed"),
+        1
+    );
+
+    // A reclaimed key no longer blocks the action; a retained one still does.
+    assert!(
+        store
+            .record_outbound_action_idempotency(user_id, "action-old", job_id)
+            .await
+            .expect("idempotency key should record")
+    );
+    assert!(
+        !store
+            .record_outbound_action_idempotency(user_id, "action-recent", job_id)
+            .await
+            .expect("idempotency key should record")
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn automation_manual_run_purge_keeps_records_inside_the_cap_window() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let now = Utc::now();
+    let user_id = Uuid::new_v4();
+    let rule_id = Uuid::new_v4();
+    let job_id = store
+        .enqueue_job(user_id, JobType::AutomationRun, now, None)
+        .await
+        .expect("job enqueue should succeed");
+    for (request_id, created_at) in [
+        ("run-old", now - Duration::days(3)),
+        ("run-recent", now - Duration::hours(1)),
+    ] {
+        store
+            .record_automation_manual_run(user_id, rule_id, request_id, job_id, created_at)
+            .await
+            .expect("manual run should record");
+    }
+    // A concurrent retry keeps the first record.
+    store
+        .record_automation_manual_run(user_id, Uuid::new_v4(), "run-recent", job_id, now)
+        .await
+        .expect("duplicate manual run should be ignored");
+
+    assert_eq!(
+        store
+            .count_automation_manual_runs_since(user_id, now - Duration::days(1))
+            .await
+            .expect("manual runs should count"),
+        1
+    );
+    assert!(matches!(
+        store
+            .purge_expired_automation_manual_runs_batch(now - Duration::days(2), 0)
+            .await,
+        Err(StoreError::InvalidData(_))
+    ));
+    assert_eq!(
+        store
+            .purge_expired_automation_manual_runs_batch(now - Duration::days(2), 10)
+            .await
+            .expect("purge should succeed"),
+        1
+    );
+
+    assert!(
+        store
+            .get_automation_manual_run(user_id, "run-old")
+            .await
+            .expect("manual run lookup should succeed")
+            .is_none()
+    );
+    let recent = store
+        .get_automation_manual_run(user_id, "run-recent")
+        .await
+        .expect("manual run lookup should succeed")
+        .expect("recent manual run should remain");
+    assert_eq!(recent.rule_id, rule_id);
+    assert_eq!(recent.job_id, job_id);
+}
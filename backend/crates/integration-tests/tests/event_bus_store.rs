@@ -0,0 +1,55 @@
+mod support;
+
+use serial_test::serial;
+use shared::events::{AuditEventSink, DomainEvent, EventBus, ManualRunMode, TracingEventSink};
+use shared::pagination::PageRequest;
+use uuid::Uuid;
+
+#[tokio::test]
+#[serial]
+async fn published_events_land_in_the_audit_trail() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let events = EventBus::builder()
+        .inline_sink(AuditEventSink::new(store.clone()))
+        .background_sink(TracingEventSink)
+        .build();
+    let user_id = store.create_user().await.expect("user should be created");
+    let rule_id = Uuid::new_v4();
+    let job_id = Uuid::new_v4();
+
+    events
+        .publish(
+            user_id,
+            DomainEvent::AutomationRunQueued {
+                rule_id,
+                job_id,
+                mode: ManualRunMode::Debug,
+            },
+        )
+        .await
+        .expect("run queued event should publish");
+    events
+        .publish(user_id, DomainEvent::AutomationRuleDeleted { rule_id })
+        .await
+        .expect("delete event should publish");
+
+    let page = store
+        .list_audit_events(user_id, PageRequest::first(10))
+        .await
+        .expect("audit events should load");
+    let event_types = page
+        .items
+        .iter()
+        .map(|event| event.event_type.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        event_types,
+        vec!["AUTOMATION_RULE_DELETED", "AUTOMATION_DEBUG_RUN_QUEUED"]
+    );
+    let queued = &page.items[1];
+    assert_eq!(queued.result, "SUCCESS");
+    assert_eq!(queued.metadata["job_id"], job_id.to_string());
+    assert_eq!(queued.metadata["mode"], "DEBUG_MANUAL");
+}
@@ -0,0 +1,376 @@
+mod support;
+
+use chrono::{TimeZone, Utc};
+use serial_test::serial;
+use shared::automation_schedule::{AutomationScheduleSpec, AutomationScheduleType};
+use shared::repos::{AutomationPromptMaterial, AutomationRuleOptions, JobStageTimings, JobType};
+use sqlx::Row;
+use uuid::Uuid;
+
+#[tokio::test]
+#[serial]
+async fn finished_jobs_move_out_of_active_partition_and_keep_idempotency() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let user_id = Uuid::new_v4();
+    let worker_id = Uuid::new_v4();
+    let now = Utc::now();
+    let job_id = store
+        .enqueue_job_with_idempotency_key(user_id, JobType::AutomationRun, now, None, "run:1")
+        .await
+        .expect("job enqueue should succeed");
+    assert_eq!(partition_of(&store, job_id).await, "jobs_active");
+
+    let claimed = store
+        .claim_due_jobs(now, worker_id, 10, 30, 1)
+        .await
+        .expect("claim should succeed");
+    assert_eq!(claimed.len(), 1);
+    assert!(
+        store
+            .mark_job_done(job_id, worker_id)
+            .await
+            .expect("mark done should succeed")
+    );
+    assert_ne!(partition_of(&store, job_id).await, "jobs_active");
+    assert_eq!(
+        store
+            .count_due_jobs(now)
+            .await
+            .expect("count should succeed"),
+        0
+    );
+
+    let replayed_id = store
+        .enqueue_job_with_idempotency_key(user_id, JobType::AutomationRun, now, None, "run:1")
+        .await
+        .expect("replayed enqueue should succeed");
+    assert_eq!(
+        replayed_id, job_id,
+        "finished jobs keep their idempotency key"
+    );
+    assert!(
+        store
+            .claim_due_jobs(now, worker_id, 10, 30, 1)
+            .await
+            .expect("claim should succeed")
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn concurrent_enqueues_share_one_job_while_it_finishes() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let user_id = Uuid::new_v4();
+    let now = Utc::now();
+    let first_enqueues = (0..8).map(|_| {
+        let store = store.clone();
+        tokio::spawn(async move {
+            store
+                .enqueue_job_with_idempotency_key(
+                    user_id,
+                    JobType::AutomationRun,
+                    now,
+                    None,
+                    "run:race",
+                )
+                .await
+                .expect("job enqueue should succeed")
+        })
+    });
+    let mut job_ids = Vec::new();
+    for enqueue in first_enqueues.collect::<Vec<_>>() {
+        job_ids.push(enqueue.await.expect("enqueue task should not panic"));
+    }
+    let job_id = job_ids[0];
+    assert!(job_ids.iter().all(|id| *id == job_id));
+
+    let worker_id = Uuid::new_v4();
+    store
+        .claim_due_jobs(now, worker_id, 10, 30, 1)
+        .await
+        .expect("claim should succeed");
+    let replays = (0..8)
+        .map(|_| {
+            let store = store.clone();
+            tokio::spawn(async move {
+                store
+                    .enqueue_job_with_idempotency_key(
+                        user_id,
+                        JobType::AutomationRun,
+                        now,
+                        None,
+                        "run:race",
+                    )
+                    .await
+                    .expect("replayed enqueue should succeed")
+            })
+        })
+        .collect::<Vec<_>>();
+    assert!(
+        store
+            .mark_job_done(job_id, worker_id)
+            .await
+            .expect("mark done should succeed")
+    );
+    for replay in replays {
+        assert_eq!(replay.await.expect("enqueue task should not panic"), job_id);
+    }
+
+    let jobs: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*)::bigint FROM jobs WHERE user_id = $1 AND idempotency_key = 'run:race'",
+    )
+    .bind(user_id)
+    .fetch_one(store.pool())
+    .await
+    .expect("count should succeed");
+    assert_eq!(jobs, 1, "a finished job must not be enqueued again");
+    assert_ne!(partition_of(&store, job_id).await, "jobs_active");
+}
+
+#[tokio::test]
+#[serial]
+async fn finished_job_partitions_are_created_and_pruned() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let user_id = Uuid::new_v4();
+    let worker_id = Uuid::new_v4();
+    let now = Utc::now();
+    let job_id = store
+        .enqueue_job_with_idempotency_key(user_id, JobType::AutomationRun, now, None, "run:old")
+        .await
+        .expect("job enqueue should succeed");
+    store
+        .claim_due_jobs(now, worker_id, 10, 30, 1)
+        .await
+        .expect("claim should succeed");
+    store
+        .mark_job_done(job_id, worker_id)
+        .await
+        .expect("mark done should succeed");
+    let run_id = record_job_dependents(&store, user_id, job_id).await;
+
+    let old_month = Utc
+        .with_ymd_and_hms(2020, 1, 15, 0, 0, 0)
+        .single()
+        .expect("timestamp should be valid");
+    sqlx::query("UPDATE jobs SET updated_at = $2 WHERE id = $1")
+        .bind(job_id)
+        .bind(old_month)
+        .execute(store.pool())
+        .await
+        .expect("backdating should succeed");
+    assert_eq!(partition_of(&store, job_id).await, "jobs_finished_default");
+
+    let created = store
+        .ensure_finished_job_partitions(old_month, 1)
+        .await
+        .expect("partitions should be created");
+    assert_eq!(
+        created,
+        vec![
+            "jobs_finished_p202001".to_string(),
+            "jobs_finished_p202002".to_string()
+        ]
+    );
+    assert_eq!(partition_of(&store, job_id).await, "jobs_finished_p202001");
+    assert!(
+        store
+            .ensure_finished_job_partitions(old_month, 1)
+            .await
+            .expect("repeat ensure should succeed")
+            .is_empty()
+    );
+
+    let cutoff = Utc
+        .with_ymd_and_hms(2020, 2, 10, 0, 0, 0)
+        .single()
+        .expect("timestamp should be valid");
+    let counts = store
+        .prune_finished_job_partitions(cutoff)
+        .await
+        .expect("prune should succeed");
+    assert_eq!(
+        counts.dropped_partitions,
+        vec!["jobs_finished_p202001".to_string()]
+    );
+
+    let remaining: i64 = sqlx::query_scalar("SELECT COUNT(*)::bigint FROM jobs WHERE id = $1")
+        .bind(job_id)
+        .fetch_one(store.pool())
+        .await
+        .expect("count should succeed");
+    assert_eq!(remaining, 0);
+    for table in [
+        "job_idempotency_keys",
+        "dead_letter_jobs",
+        "outbound_action_idempotency",
+    ] {
+        let dependents: i64 = sqlx::query_scalar(&format!(
+            "SELECT COUNT(*)::bigint FROM {table} WHERE job_id = $1"
+        ))
+        .bind(job_id)
+        .fetch_one(store.pool())
+        .await
+        .expect("count should succeed");
+        assert_eq!(
+            dependents, 0,
+            "{table} rows should be pruned with their job"
+        );
+    }
+    let run_job_id: Option<Uuid> =
+        sqlx::query_scalar("SELECT job_id FROM automation_runs WHERE id = $1")
+            .bind(run_id)
+            .fetch_one(store.pool())
+            .await
+            .expect("automation run should be kept");
+    assert_eq!(run_job_id, None);
+
+    let reenqueued_id = store
+        .enqueue_job_with_idempotency_key(user_id, JobType::AutomationRun, now, None, "run:old")
+        .await
+        .expect("enqueue after prune should succeed");
+    assert_ne!(reenqueued_id, job_id);
+
+    store
+        .prune_finished_job_partitions(
+            Utc.with_ymd_and_hms(2020, 3, 1, 0, 0, 0)
+                .single()
+                .expect("timestamp should be valid"),
+        )
+        .await
+        .expect("cleanup prune should succeed");
+}
+
+#[tokio::test]
+#[serial]
+async fn job_stage_timings_are_recorded_for_the_settled_attempt() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let user_id = Uuid::new_v4();
+    let worker_id = Uuid::new_v4();
+    let now = Utc::now();
+    let job_id = store
+        .enqueue_job_with_idempotency_key(user_id, JobType::AutomationRun, now, None, "run:1")
+        .await
+        .expect("job enqueue should succeed");
+    store
+        .claim_due_jobs(now, worker_id, 10, 30, 1)
+        .await
+        .expect("claim should succeed");
+    store
+        .mark_job_done(job_id, worker_id)
+        .await
+        .expect("mark done should succeed");
+
+    let timings = JobStageTimings {
+        claim_ms: 4,
+        fetch_ms: Some(12),
+        generate_ms: Some(850),
+        deliver_ms: Some(40),
+    };
+    let finished_at = now + chrono::Duration::seconds(1);
+    store
+        .record_job_stage_timings(job_id, worker_id, now, finished_at, &timings)
+        .await
+        .expect("timings should persist");
+
+    let row = sqlx::query("SELECT started_at, finished_at, stage_timings FROM jobs WHERE id = $1")
+        .bind(job_id)
+        .fetch_one(store.pool())
+        .await
+        .expect("job row should exist");
+    let stored_finished_at: chrono::DateTime<Utc> = row
+        .try_get("finished_at")
+        .expect("finished_at should decode");
+    let stage_timings: serde_json::Value =
+        row.try_get("stage_timings").expect("timings should decode");
+    assert!(
+        row.try_get::<Option<chrono::DateTime<Utc>>, _>("started_at")
+            .expect("started_at should decode")
+            .is_some()
+    );
+    assert_eq!(
+        stored_finished_at.timestamp_millis(),
+        finished_at.timestamp_millis()
+    );
+    assert_eq!(
+        serde_json::from_value::<JobStageTimings>(stage_timings).expect("timings should parse"),
+        timings
+    );
+}
+
+/// Points a dead letter, an outbound action key, and an automation run at `job_id`, returning
+/// the automation run id.
+async fn record_job_dependents(store: &shared::repos::Store, user_id: Uuid, job_id: Uuid) -> Uuid {
+    sqlx::query(
+        "INSERT INTO dead_letter_jobs (
+           job_id, user_id, type, idempotency_key, attempts, reason_code, reason_message, data_key_id
+         )
+         VALUES ($1, $2, 'AUTOMATION_RUN', 'run:old', 1, 'TEST', 'test', 'test-key')",
+    )
+    .bind(job_id)
+    .bind(user_id)
+    .execute(store.pool())
+    .await
+    .expect("dead letter should insert");
+    assert!(
+        store
+            .record_outbound_action_idempotency(user_id, "push:old", job_id)
+            .await
+            .expect("outbound action should record")
+    );
+
+    let now = Utc::now();
+    let rule = store
+        .create_automation_rule(
+            user_id,
+            "Pruned Job Task",
+            AutomationRuleOptions::default(),
+            &AutomationScheduleSpec {
+                schedule_type: AutomationScheduleType::Daily,
+                time_zone: "UTC".to_string(),
+                local_time_minutes: 9 * 60,
+                anchor_days_of_week: None,
+                anchor_day_of_month: None,
+                anchor_month: None,
+                cron_expression: None,
+                run_at: None,
+            },
+            now,
+            &AutomationPromptMaterial {
+                prompt_ciphertext: b"prompt".to_vec(),
+                prompt_sha256: "a".repeat(64),
+            },
+        )
+        .await
+        .expect("rule should be created");
+    sqlx::query_scalar(
+        "INSERT INTO automation_runs (rule_id, user_id, scheduled_for, job_id, idempotency_key, state)
+         VALUES ($1, $2, $3, $4, 'run:old', 'ENQUEUED')
+         RETURNING id",
+    )
+    .bind(rule.id)
+    .bind(user_id)
+    .bind(now)
+    .bind(job_id)
+    .fetch_one(store.pool())
+    .await
+    .expect("automation run should insert")
+}
+
+async fn partition_of(store: &shared::repos::Store, job_id: Uuid) -> String {
+    sqlx::query("SELECT tableoid::regclass::text AS partition FROM jobs WHERE id = $1")
+        .bind(job_id)
+        .fetch_one(store.pool())
+        .await
+        .expect("job row should exist")
+        .try_get("partition")
+        .expect("partition should decode")
+}
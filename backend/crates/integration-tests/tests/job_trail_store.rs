@@ -0,0 +1,148 @@
+mod support;
+
+use chrono::{Duration, Utc};
+use serial_test::serial;
+use shared::repos::{
+    JobState, JobType, NewNotificationDelivery, NotificationDeliveryOutcome, StoreError,
+};
+use uuid::Uuid;
+
+#[tokio::test]
+#[serial]
+async fn job_execution_trail_links_job_enclave_request_and_deliveries() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let user_id = Uuid::new_v4();
+    let worker_id = Uuid::new_v4();
+    let now = Utc::now();
+    let brief_job_id = store
+        .enqueue_job_with_idempotency_key(user_id, JobType::AutomationRun, now, None, "brief")
+        .await
+        .expect("job enqueue should succeed");
+    store
+        .enqueue_job_with_idempotency_key(
+            user_id,
+            JobType::AutomationRun,
+            now + Duration::hours(2),
+            None,
+            "later",
+        )
+        .await
+        .expect("job enqueue should succeed");
+
+    let claimed = store
+        .claim_due_jobs(now, worker_id, 10, 30, 1)
+        .await
+        .expect("claim should succeed");
+    assert_eq!(claimed.len(), 1);
+    store
+        .record_job_enclave_request(brief_job_id, "enclave-req-1")
+        .await
+        .expect("enclave request should record");
+
+    for (device_id, outcome, error_code) in [
+        ("device-1", NotificationDeliveryOutcome::Delivered, None),
+        (
+            "device-2",
+            NotificationDeliveryOutcome::Failed,
+            Some("APNS_BAD_DEVICE_TOKEN"),
+        ),
+    ] {
+        store
+            .record_notification_delivery(&NewNotificationDelivery {
+                user_id,
+                job_id: brief_job_id,
+                job_attempt: claimed[0].attempts,
+                enclave_request_id: Some("enclave-req-1"),
+                device_id,
+                outcome,
+                push_payload_mode: Some("encrypted"),
+                error_code,
+            })
+            .await
+            .expect("delivery should record");
+    }
+    assert!(
+        store
+            .mark_job_done(brief_job_id, worker_id)
+            .await
+            .expect("mark done should succeed")
+    );
+
+    let trails = store
+        .list_job_execution_trails(
+            user_id,
+            now - Duration::minutes(1),
+            now + Duration::hours(1),
+            10,
+        )
+        .await
+        .expect("trail lookup should succeed");
+    assert_eq!(trails.len(), 1);
+    let trail = &trails[0];
+    assert_eq!(trail.job_id, brief_job_id);
+    assert_eq!(trail.state, JobState::Done);
+    assert_eq!(trail.enclave_request_id.as_deref(), Some("enclave-req-1"));
+    assert_eq!(trail.deliveries.len(), 2);
+    assert!(
+        trail
+            .deliveries
+            .iter()
+            .all(|delivery| { delivery.enclave_request_id.as_deref() == Some("enclave-req-1") })
+    );
+    assert_eq!(trail.deliveries[0].device_id, "device-1");
+    assert_eq!(
+        trail.deliveries[0].outcome,
+        NotificationDeliveryOutcome::Delivered
+    );
+    assert_eq!(
+        trail.deliveries[1].error_code.as_deref(),
+        Some("APNS_BAD_DEVICE_TOKEN")
+    );
+
+    let other_user_trails = store
+        .list_job_execution_trails(
+            Uuid::new_v4(),
+            now - Duration::minutes(1),
+            now + Duration::hours(1),
+            10,
+        )
+        .await
+        .expect("trail lookup should succeed");
+    assert!(other_user_trails.is_empty());
+}
+
+#[tokio::test]
+#[serial]
+async fn enclave_request_is_only_recorded_on_running_jobs() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let user_id = Uuid::new_v4();
+    let now = Utc::now();
+    let job_id = store
+        .enqueue_job(user_id, JobType::DepartureAlert, now, None)
+        .await
+        .expect("job enqueue should succeed");
+    store
+        .record_job_enclave_request(job_id, "enclave-req-1")
+        .await
+        .expect("enclave request update should succeed");
+
+    let trails = store
+        .list_job_execution_trails(user_id, now, now + Duration::minutes(1), 10)
+        .await
+        .expect("trail lookup should succeed");
+    assert_eq!(trails.len(), 1);
+    assert_eq!(trails[0].state, JobState::Pending);
+    assert_eq!(trails[0].enclave_request_id, None);
+    assert!(trails[0].deliveries.is_empty());
+
+    assert!(matches!(
+        store
+            .list_job_execution_trails(user_id, now, now + Duration::minutes(1), 0)
+            .await,
+        Err(StoreError::InvalidData(_))
+    ));
+}
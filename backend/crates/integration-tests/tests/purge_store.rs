@@ -5,7 +5,7 @@ use std::collections::HashMap;
 use chrono::{Duration, Utc};
 use serial_test::serial;
 use shared::models::{ApnsEnvironment, AssistantSessionStateEnvelope};
-use shared::repos::{AuditResult, JobType};
+use shared::repos::{AuditResult, ConnectorAccount, JobType, LEGACY_CONNECTOR_ACCOUNT_KEY};
 use sqlx::Row;
 use uuid::Uuid;
 
@@ -24,7 +24,11 @@ async fn purge_user_operational_data_removes_sensitive_rows_and_marks_user_delet
 
     store
         .upsert_google_connector(
-            user_id,
+            ConnectorAccount {
+                user_id,
+                connector_id: Uuid::new_v4(),
+                account_key: LEGACY_CONNECTOR_ACCOUNT_KEY,
+            },
             "refresh-token",
             &["https://www.googleapis.com/auth/calendar.readonly".to_string()],
             "kms/local/alfred-refresh-token",
@@ -66,7 +70,7 @@ async fn purge_user_operational_data_removes_sensitive_rows_and_marks_user_delet
         .expect("oauth state should store");
 
     let mut metadata = HashMap::new();
-    metadata.insert("refresh_token".to_string(), "leak-me".to_string());
+    metadata.insert("refresh_token".to_string(), "leak-me".into());
     store
         .add_audit_event(
             user_id,
@@ -93,12 +97,33 @@ async fn purge_user_operational_data_removes_sensitive_rows_and_marks_user_delet
         .expect("session upsert should succeed");
 
     store
+        .add_llm_monthly_spend_micros(user_id, now.date_naive(), 1_250)
+        .await
+        .expect("llm spend should record");
+
+    let deleted_rows = store
         .purge_user_operational_data(user_id)
         .await
         .expect("purge should succeed");
+    let deleted_count = |table: &str| {
+        deleted_rows
+            .iter()
+            .find(|(name, _)| *name == table)
+            .map(|(_, count)| *count)
+    };
+    assert_eq!(deleted_count("devices"), Some(1));
+    assert_eq!(deleted_count("job_idempotency_keys"), Some(1));
+    assert_eq!(deleted_count("jobs"), Some(1));
+    assert_eq!(deleted_count("assistant_encrypted_sessions"), Some(1));
+    assert_eq!(deleted_count("automation_rules"), Some(0));
+    assert_eq!(deleted_count("llm_monthly_spend"), Some(1));
 
     assert_eq!(row_count(store.pool(), "connectors", user_id).await, 0);
     assert_eq!(row_count(store.pool(), "devices", user_id).await, 0);
+    assert_eq!(
+        row_count(store.pool(), "job_idempotency_keys", user_id).await,
+        0
+    );
     assert_eq!(row_count(store.pool(), "jobs", user_id).await, 0);
     assert_eq!(row_count(store.pool(), "oauth_states", user_id).await, 0);
     assert_eq!(row_count(store.pool(), "audit_events", user_id).await, 0);
@@ -107,6 +132,11 @@ async fn purge_user_operational_data_removes_sensitive_rows_and_marks_user_delet
         0
     );
 
+    assert_eq!(
+        row_count(store.pool(), "llm_monthly_spend", user_id).await,
+        0
+    );
+
     let user_row = sqlx::query("SELECT status FROM users WHERE id = $1")
         .bind(user_id)
         .fetch_one(store.pool())
@@ -0,0 +1,276 @@
+mod support;
+
+use chrono::{Duration, TimeZone, Utc};
+use serial_test::serial;
+use shared::automation_schedule::{AutomationScheduleSpec, AutomationScheduleType};
+use shared::llm::PlanLlmSpendBudget;
+use shared::models::{QuotaKind, UserPlan};
+use shared::quota::{PlanQuotas, llm_spend_month_start, quota_day_start};
+use shared::repos::{
+    AssistantRequestIndexEntry, AssistantRequestOutcome, AutomationPromptMaterial,
+    AutomationRuleOptions,
+};
+use uuid::Uuid;
+
+const PROMPT_HASH: &str = "cccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccc";
+
+#[tokio::test]
+#[serial]
+async fn users_default_to_free_plan_until_upgraded() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let user_id = store.create_user().await.expect("user should be created");
+    assert_eq!(
+        store.get_user_plan(user_id).await.expect("plan lookup"),
+        UserPlan::Free
+    );
+
+    store
+        .set_user_plan(user_id, UserPlan::Pro)
+        .await
+        .expect("plan should be set");
+    assert_eq!(
+        store.get_user_plan(user_id).await.expect("plan lookup"),
+        UserPlan::Pro
+    );
+
+    store
+        .set_user_plan(user_id, UserPlan::Free)
+        .await
+        .expect("plan should be downgraded");
+    assert_eq!(
+        store.get_user_plan(user_id).await.expect("plan lookup"),
+        UserPlan::Free
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn members_get_the_higher_of_their_own_and_their_organization_plan() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let member_id = Uuid::new_v4();
+    let pro_member_id = Uuid::new_v4();
+    for user_id in [member_id, pro_member_id] {
+        store
+            .ensure_user_organization(user_id, Some("org_team"))
+            .await
+            .expect("member should be recorded");
+    }
+    store
+        .set_user_plan(pro_member_id, UserPlan::Pro)
+        .await
+        .expect("plan should be set");
+
+    assert_eq!(
+        store.get_user_plan(member_id).await.expect("plan lookup"),
+        UserPlan::Free
+    );
+    store
+        .set_organization_plan("org_team", UserPlan::Pro)
+        .await
+        .expect("organization plan should be set");
+    assert_eq!(
+        store.get_user_plan(member_id).await.expect("plan lookup"),
+        UserPlan::Pro
+    );
+
+    store
+        .set_organization_plan("org_team", UserPlan::Free)
+        .await
+        .expect("organization plan should be downgraded");
+    assert_eq!(
+        store.get_user_plan(member_id).await.expect("plan lookup"),
+        UserPlan::Free
+    );
+    assert_eq!(
+        store
+            .get_user_plan(pro_member_id)
+            .await
+            .expect("plan lookup"),
+        UserPlan::Pro
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn quota_usage_counts_daily_requests_and_active_rules() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let user_id = store.create_user().await.expect("user should be created");
+    let now = Utc::now();
+    let day_start = quota_day_start(now);
+
+    for index in 0..3 {
+        store
+            .record_assistant_request(user_id, &request_entry(index))
+            .await
+            .expect("assistant request should record");
+    }
+
+    let mut rule_ids = Vec::new();
+    for title in ["Kept", "Retired"] {
+        let rule = store
+            .create_automation_rule(
+                user_id,
+                title,
+                AutomationRuleOptions::default(),
+                &daily_schedule(),
+                now + Duration::hours(1),
+                &AutomationPromptMaterial {
+                    prompt_ciphertext: b"quota-prompt".to_vec(),
+                    prompt_sha256: PROMPT_HASH.to_string(),
+                },
+            )
+            .await
+            .expect("rule should be created");
+        rule_ids.push(rule.id);
+    }
+    store
+        .archive_automation_rule(user_id, rule_ids[1])
+        .await
+        .expect("archive should succeed");
+
+    store
+        .record_automation_manual_run(user_id, rule_ids[0], "quota-run-today", Uuid::new_v4(), now)
+        .await
+        .expect("manual run should record");
+    store
+        .record_automation_manual_run(
+            user_id,
+            rule_ids[0],
+            "quota-run-yesterday",
+            Uuid::new_v4(),
+            day_start - Duration::minutes(1),
+        )
+        .await
+        .expect("manual run should record");
+
+    let usage = |kind| store.count_quota_usage(user_id, kind, day_start);
+    assert_eq!(
+        usage(QuotaKind::AssistantQueriesPerDay)
+            .await
+            .expect("usage should load"),
+        3
+    );
+    assert_eq!(
+        usage(QuotaKind::AutomationRules)
+            .await
+            .expect("usage should load"),
+        1,
+        "archived rules should not count against the plan"
+    );
+    assert_eq!(
+        usage(QuotaKind::LlmRequestsPerDay)
+            .await
+            .expect("usage should load"),
+        4,
+        "yesterday's manual run should not count against today's budget"
+    );
+
+    assert_eq!(
+        store
+            .count_quota_usage(Uuid::new_v4(), QuotaKind::LlmRequestsPerDay, day_start)
+            .await
+            .expect("usage should load"),
+        0
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn llm_spend_accumulates_per_month_against_the_plan_budget() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let user_id = store.create_user().await.expect("user should be created");
+    let now = Utc
+        .with_ymd_and_hms(2026, 2, 20, 12, 0, 0)
+        .single()
+        .expect("valid timestamp");
+    let month_start = llm_spend_month_start(now);
+    let free_limit = PlanQuotas::for_plan(UserPlan::Free).llm_spend_micros_per_month;
+
+    store
+        .add_llm_monthly_spend_micros(user_id, month_start, 400_000)
+        .await
+        .expect("spend should record");
+    let total = store
+        .add_llm_monthly_spend_micros(user_id, month_start, free_limit)
+        .await
+        .expect("spend should accumulate");
+    assert_eq!(total, free_limit + 400_000);
+    store
+        .add_llm_monthly_spend_micros(
+            user_id,
+            llm_spend_month_start(now - Duration::days(30)),
+            250_000,
+        )
+        .await
+        .expect("last month's spend should record");
+
+    let budget = PlanLlmSpendBudget::new(store.clone());
+    let status = budget
+        .status_for_user(user_id, now)
+        .await
+        .expect("status should load");
+    assert_eq!(status.spent_micros, free_limit + 400_000);
+    assert_eq!(status.remaining_micros(), 0);
+    assert!(status.is_exhausted());
+    assert_eq!(
+        status.resets_at,
+        Utc.with_ymd_and_hms(2026, 3, 1, 0, 0, 0)
+            .single()
+            .expect("valid timestamp")
+    );
+
+    store
+        .set_user_plan(user_id, UserPlan::Pro)
+        .await
+        .expect("plan should be set");
+    let status = budget
+        .status_for_user(user_id, now)
+        .await
+        .expect("status should load");
+    assert_eq!(status.plan, UserPlan::Pro);
+    assert!(!status.is_exhausted(), "upgrading should lift the ceiling");
+
+    let next_month = budget
+        .status_for_user(user_id, now + Duration::days(10))
+        .await
+        .expect("status should load");
+    assert_eq!(next_month.spent_micros, 0);
+}
+
+fn request_entry(index: usize) -> AssistantRequestIndexEntry {
+    AssistantRequestIndexEntry {
+        request_id: format!("quota-req-{index}"),
+        session_id: None,
+        outcome: AssistantRequestOutcome::Succeeded,
+        error_code: None,
+        route: Some("general_chat".to_string()),
+        capability: Some("general_chat".to_string()),
+        model: None,
+        planner_used_fallback: false,
+        output_used_fallback: false,
+        planner_ms: Some(10),
+        lane_ms: Some(20),
+        enclave_rpc_ms: 40,
+    }
+}
+
+fn daily_schedule() -> AutomationScheduleSpec {
+    AutomationScheduleSpec {
+        schedule_type: AutomationScheduleType::Daily,
+        time_zone: "UTC".to_string(),
+        local_time_minutes: 9 * 60,
+        anchor_days_of_week: None,
+        anchor_day_of_month: None,
+        anchor_month: None,
+        cron_expression: None,
+        run_at: None,
+    }
+}
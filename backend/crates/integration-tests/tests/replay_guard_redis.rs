@@ -0,0 +1,51 @@
+mod support;
+
+use chrono::Utc;
+use shared::replay_guard::{NonceReplayGuard, NonceReplayed};
+use uuid::Uuid;
+
+const UNREACHABLE_REDIS_URL: &str = "redis://127.0.0.1:1/0";
+
+#[tokio::test]
+async fn nonces_recorded_by_one_instance_are_rejected_by_another() {
+    let first = NonceReplayGuard::default()
+        .with_redis_from_config(&support::test_redis_url())
+        .await;
+    let second = NonceReplayGuard::default()
+        .with_redis_from_config(&support::test_redis_url())
+        .await;
+    let nonce = Uuid::new_v4().to_string();
+    let now = Utc::now().timestamp();
+
+    first
+        .check_and_record("enclave_rpc", &nonce, now + 30, now)
+        .await
+        .expect("first use should pass");
+    assert_eq!(
+        second
+            .check_and_record("enclave_rpc", &nonce, now + 30, now)
+            .await,
+        Err(NonceReplayed),
+        "a restarted or sibling instance must still see the nonce"
+    );
+}
+
+#[tokio::test]
+async fn unreachable_redis_leaves_the_in_memory_guard_in_charge() {
+    let guard = NonceReplayGuard::default()
+        .with_redis_from_config(UNREACHABLE_REDIS_URL)
+        .await;
+    let nonce = Uuid::new_v4().to_string();
+    let now = Utc::now().timestamp();
+
+    guard
+        .check_and_record("enclave_rpc", &nonce, now + 30, now)
+        .await
+        .expect("first use should pass");
+    assert_eq!(
+        guard
+            .check_and_record("enclave_rpc", &nonce, now + 30, now)
+            .await,
+        Err(NonceReplayed)
+    );
+}
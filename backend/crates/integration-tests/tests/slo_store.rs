@@ -0,0 +1,101 @@
+mod support;
+
+use chrono::{Duration, Utc};
+use serial_test::serial;
+use shared::repos::SloEvaluation;
+
+#[tokio::test]
+#[serial]
+async fn push_delivery_latency_buckets_accumulate_and_prune() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let now = Utc::now();
+    store
+        .record_push_delivery_latency(10, 9)
+        .await
+        .expect("latency should record");
+    store
+        .record_push_delivery_latency(5, 2)
+        .await
+        .expect("latency should accumulate");
+
+    let totals = store
+        .push_delivery_latency_totals(now - Duration::minutes(60))
+        .await
+        .expect("totals should load");
+    assert_eq!(totals, (15, 11));
+
+    store
+        .prune_push_delivery_latency(now + Duration::minutes(2))
+        .await
+        .expect("prune should succeed");
+    let totals = store
+        .push_delivery_latency_totals(now - Duration::minutes(60))
+        .await
+        .expect("totals should load");
+    assert_eq!(totals, (0, 0));
+}
+
+#[tokio::test]
+#[serial]
+async fn push_delivery_slo_status_tracks_breach_transitions() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    assert!(
+        store
+            .get_push_delivery_slo_status()
+            .await
+            .expect("status lookup should succeed")
+            .is_none()
+    );
+
+    let first_breach_at = Utc::now() - Duration::minutes(2);
+    let evaluation = |breached: bool, evaluated_at| SloEvaluation {
+        objective_percent: 95,
+        target_seconds: 60,
+        window_minutes: 60,
+        total_events: 100,
+        good_events: if breached { 80 } else { 99 },
+        breached,
+        evaluated_at,
+    };
+
+    let (status, previously_breached) = store
+        .record_push_delivery_slo_evaluation(&evaluation(true, first_breach_at))
+        .await
+        .expect("evaluation should record");
+    assert!(status.breached);
+    assert!(!previously_breached);
+    assert_eq!(
+        status.breached_since.map(|at| at.timestamp_micros()),
+        Some(first_breach_at.timestamp_micros())
+    );
+
+    let (status, previously_breached) = store
+        .record_push_delivery_slo_evaluation(&evaluation(true, Utc::now()))
+        .await
+        .expect("evaluation should record");
+    assert!(previously_breached);
+    assert_eq!(
+        status.breached_since.map(|at| at.timestamp_micros()),
+        Some(first_breach_at.timestamp_micros()),
+        "breached_since should hold while the breach continues"
+    );
+
+    let (status, previously_breached) = store
+        .record_push_delivery_slo_evaluation(&evaluation(false, Utc::now()))
+        .await
+        .expect("evaluation should record");
+    assert!(previously_breached);
+    assert!(!status.breached);
+    assert!(status.breached_since.is_none());
+
+    let stored = store
+        .get_push_delivery_slo_status()
+        .await
+        .expect("status lookup should succeed")
+        .expect("status should exist");
+    assert_eq!(stored, status);
+}
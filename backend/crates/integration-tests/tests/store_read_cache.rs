@@ -0,0 +1,96 @@
+mod support;
+
+use serial_test::serial;
+use shared::models::ApnsEnvironment;
+use shared::repos::{Store, StoreReadCache};
+use uuid::Uuid;
+
+const UNREACHABLE_REDIS_URL: &str = "redis://127.0.0.1:1/0";
+
+#[tokio::test]
+#[serial]
+async fn registered_devices_are_cached_and_invalidated_on_device_writes() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+    let read_cache = StoreReadCache::connect(&support::test_redis_url(), 60)
+        .await
+        .expect("read cache should connect");
+    let store = store.with_read_cache(read_cache);
+
+    let user_id = Uuid::new_v4();
+    register(&store, user_id, "device-1").await;
+    assert_eq!(device_ids(&store, user_id).await, vec!["device-1"]);
+
+    // A row removed behind the store's back stays cached until a store write invalidates it.
+    sqlx::query("DELETE FROM devices WHERE user_id = $1")
+        .bind(user_id)
+        .execute(store.pool())
+        .await
+        .expect("direct delete should succeed");
+    assert_eq!(device_ids(&store, user_id).await, vec!["device-1"]);
+
+    register(&store, user_id, "device-2").await;
+    assert_eq!(device_ids(&store, user_id).await, vec!["device-2"]);
+
+    store
+        .record_device_delivery_failure(user_id, "device-2", "BadDeviceToken", 3, true)
+        .await
+        .expect("failure should record");
+    assert!(device_ids(&store, user_id).await.is_empty());
+
+    assert!(
+        store
+            .enable_device(user_id, "device-2")
+            .await
+            .expect("enable should succeed")
+    );
+    assert_eq!(device_ids(&store, user_id).await, vec!["device-2"]);
+
+    assert!(
+        store
+            .delete_device(user_id, "device-2")
+            .await
+            .expect("delete should succeed")
+    );
+    assert!(device_ids(&store, user_id).await.is_empty());
+}
+
+#[tokio::test]
+#[serial]
+async fn unreachable_redis_leaves_store_reading_from_postgres() {
+    let store = support::test_store()
+        .await
+        .with_read_cache_from_config(UNREACHABLE_REDIS_URL, 60)
+        .await;
+    support::reset_database(store.pool()).await;
+
+    let user_id = Uuid::new_v4();
+    register(&store, user_id, "device-1").await;
+    assert_eq!(device_ids(&store, user_id).await, vec!["device-1"]);
+}
+
+async fn register(store: &Store, user_id: Uuid, device_id: &str) {
+    store
+        .register_device(
+            user_id,
+            device_id,
+            "apns-token",
+            &ApnsEnvironment::Sandbox,
+            None,
+            None,
+        )
+        .await
+        .expect("device registration should succeed");
+}
+
+async fn device_ids(store: &Store, user_id: Uuid) -> Vec<String> {
+    let mut device_ids = store
+        .list_registered_devices(user_id)
+        .await
+        .expect("device listing should succeed")
+        .into_iter()
+        .map(|device| device.device_id)
+        .collect::<Vec<_>>();
+    device_ids.sort();
+    device_ids
+}
@@ -1,10 +1,14 @@
 mod support;
 
-use std::collections::HashMap;
-
 use chrono::{Duration as ChronoDuration, Utc};
+use serde_json::{Value, json};
 use serial_test::serial;
-use shared::repos::{AuditResult, JobType, PrivacyDeleteStatus, StoreError};
+use shared::models::AuditMetadata;
+use shared::pagination::PageRequest;
+use shared::repos::{
+    AuditResult, ConnectorAccount, JobType, LEGACY_CONNECTOR_ACCOUNT_KEY, PrivacyDeleteStatus,
+    StoreError,
+};
 use sqlx::Row;
 use tokio::time::{Duration, sleep};
 use uuid::Uuid;
@@ -21,7 +25,11 @@ async fn privacy_delete_execution_flow_completes_and_marks_user_deleted() {
 
     let connector_id = store
         .upsert_google_connector(
-            user_id,
+            ConnectorAccount {
+                user_id,
+                connector_id: Uuid::new_v4(),
+                account_key: LEGACY_CONNECTOR_ACCOUNT_KEY,
+            },
             "refresh-token",
             &["https://www.googleapis.com/auth/calendar.readonly".to_string()],
             "kms/local/alfred-refresh-token",
@@ -119,12 +127,12 @@ async fn audit_metadata_redaction_masks_token_bearing_values() {
 
     let user_id = Uuid::new_v4();
 
-    let mut metadata = HashMap::new();
+    let mut metadata = AuditMetadata::new();
     metadata.insert(
         "error_detail".to_string(),
-        "authorization=Bearer secret".to_string(),
+        json!("authorization=Bearer secret"),
     );
-    metadata.insert("status".to_string(), "failed".to_string());
+    metadata.insert("status".to_string(), json!("failed"));
 
     store
         .add_audit_event(
@@ -137,27 +145,28 @@ async fn audit_metadata_redaction_masks_token_bearing_values() {
         .await
         .expect("audit event insert should succeed");
 
-    let (events, _cursor) = store
-        .list_audit_events(user_id, None, 10)
+    let events = store
+        .list_audit_events(user_id, PageRequest::first(10))
         .await
-        .expect("audit list should succeed");
+        .expect("audit list should succeed")
+        .items;
     assert_eq!(events.len(), 1);
 
     let event = &events[0];
     assert_eq!(event.event_type, "TEST_REDACTION");
     assert_eq!(
-        event.metadata.get("error_detail").map(String::as_str),
+        event.metadata.get("error_detail").and_then(Value::as_str),
         Some("[REDACTED]")
     );
     assert_eq!(
-        event.metadata.get("status").map(String::as_str),
+        event.metadata.get("status").and_then(Value::as_str),
         Some("failed")
     );
     assert!(
         !event
             .metadata
             .values()
-            .any(|value| value.to_ascii_lowercase().contains("bearer")),
+            .any(|value| value.to_string().to_ascii_lowercase().contains("bearer")),
         "redacted metadata should never include token-bearing markers"
     );
 }
@@ -171,7 +180,11 @@ async fn connector_key_metadata_drift_conflict_fails_closed() {
     let user_id = Uuid::new_v4();
     let connector_id = store
         .upsert_google_connector(
-            user_id,
+            ConnectorAccount {
+                user_id,
+                connector_id: Uuid::new_v4(),
+                account_key: LEGACY_CONNECTOR_ACCOUNT_KEY,
+            },
             "refresh-token",
             &["https://www.googleapis.com/auth/calendar.readonly".to_string()],
             "__legacy__",
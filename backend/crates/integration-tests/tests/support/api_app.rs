@@ -5,9 +5,15 @@ use std::net::IpAddr;
 use std::time::Duration;
 
 use api_server::http::{
-    AppState, ClerkJwksCache, ClerkJwksCacheConfig, EnclaveRpcConfig, OAuthConfig, RateLimiter,
-    build_router,
+    ApiMetrics, AppState, ClerkJwksCache, ClerkJwksCacheConfig, EnclaveRpcConfig, OAuthConfig,
+    RateLimiter, RequestBodyLimits, build_router,
 };
+use shared::config::{
+    ApiDeprecationConfig, CorsConfig, RequestSigningConfig, SecurityHeadersConfig,
+};
+use shared::events::{AuditEventSink, EventBus, TracingEventSink};
+use shared::feature_flags::{FeatureFlagDefaults, FeatureFlags};
+use shared::pagination::PaginationCursorCodec;
 use shared::repos::Store;
 use shared::security::{KmsDecryptPolicy, SecretRuntime, TeeAttestationPolicy};
 use uuid::Uuid;
@@ -17,6 +23,7 @@ use super::test_redis_url;
 
 const OAUTH_REDIRECT_URI: &str = "alfred://oauth/google/callback";
 const CLERK_SUBJECT_NAMESPACE: Uuid = Uuid::from_u128(0x10850be7d81f4f4ea2dc0bb96943a09e);
+pub const TEST_ADMIN_API_TOKEN: &str = "integration-test-admin-api-token-0001";
 const DEFAULT_ENCLAVE_RPC_BASE_URL: &str = "http://127.0.0.1:65530";
 
 pub async fn build_test_router(store: Store, clerk: &TestClerkAuth) -> axum::Router {
@@ -28,19 +35,99 @@ pub async fn build_test_router_with_enclave_base_url(
     clerk: &TestClerkAuth,
     enclave_rpc_base_url: &str,
 ) -> axum::Router {
+    build_router(test_app_state(store, clerk, enclave_rpc_base_url).await)
+}
+
+pub async fn build_test_router_with_api_v1_deprecation(
+    store: Store,
+    clerk: &TestClerkAuth,
+    deprecation: ApiDeprecationConfig,
+) -> axum::Router {
+    let mut state = test_app_state(store, clerk, DEFAULT_ENCLAVE_RPC_BASE_URL).await;
+    state.api_v1_deprecation = Some(deprecation);
+    build_router(state)
+}
+
+pub async fn build_test_router_with_cors(
+    store: Store,
+    clerk: &TestClerkAuth,
+    cors: CorsConfig,
+) -> axum::Router {
+    let mut state = test_app_state(store, clerk, DEFAULT_ENCLAVE_RPC_BASE_URL).await;
+    state.cors = cors;
+    build_router(state)
+}
+
+pub async fn build_test_router_with_feature_flags(
+    store: Store,
+    clerk: &TestClerkAuth,
+    feature_flags: FeatureFlags,
+) -> axum::Router {
+    let mut state = test_app_state(store, clerk, DEFAULT_ENCLAVE_RPC_BASE_URL).await;
+    state.feature_flags = feature_flags;
+    build_router(state)
+}
+
+pub async fn build_test_router_with_request_signing(
+    store: Store,
+    clerk: &TestClerkAuth,
+    request_signing: RequestSigningConfig,
+) -> axum::Router {
+    let mut state = test_app_state(store, clerk, DEFAULT_ENCLAVE_RPC_BASE_URL).await;
+    state.request_signing = request_signing;
+    build_router(state)
+}
+
+/// Router that gives sessions holding `org_admin_role` scoped admin access to their organization.
+pub async fn build_test_router_with_org_admin_role(
+    store: Store,
+    clerk: &TestClerkAuth,
+    org_admin_role: &str,
+) -> axum::Router {
+    let mut state = test_app_state(store, clerk, DEFAULT_ENCLAVE_RPC_BASE_URL).await;
+    state.org_admin_role = Some(org_admin_role.to_string());
+    build_router(state)
+}
+
+/// Router whose API server checks enclave signatures against `attestation_public_key`.
+pub async fn build_test_router_with_attestation_public_key(
+    store: Store,
+    clerk: &TestClerkAuth,
+    attestation_public_key: &str,
+) -> axum::Router {
+    let mut state = test_app_state(store, clerk, DEFAULT_ENCLAVE_RPC_BASE_URL).await;
+    state.secret_runtime = test_secret_runtime(
+        DEFAULT_ENCLAVE_RPC_BASE_URL,
+        Some(attestation_public_key.to_string()),
+        state.http_client.clone(),
+    );
+    build_router(state)
+}
+
+async fn test_app_state(
+    store: Store,
+    clerk: &TestClerkAuth,
+    enclave_rpc_base_url: &str,
+) -> AppState {
     let clerk_jwks_cache = build_clerk_jwks_cache().await;
     let http_client = reqwest::Client::builder()
         .timeout(Duration::from_secs(5))
         .build()
         .expect("http client should initialize");
 
-    let state = AppState {
+    let events = EventBus::builder()
+        .inline_sink(AuditEventSink::new(store.clone()))
+        .background_sink(TracingEventSink)
+        .build();
+
+    AppState {
         store,
         oauth: OAuthConfig {
             client_id: "test-google-client".to_string(),
             redirect_uri: OAUTH_REDIRECT_URI.to_string(),
             auth_url: "https://accounts.google.com/o/oauth2/v2/auth".to_string(),
             scopes: vec![
+                "openid".to_string(),
                 "https://www.googleapis.com/auth/gmail.readonly".to_string(),
                 "https://www.googleapis.com/auth/calendar.readonly".to_string(),
             ],
@@ -48,29 +135,15 @@ pub async fn build_test_router_with_enclave_base_url(
         enclave_rpc: EnclaveRpcConfig {
             base_url: enclave_rpc_base_url.to_string(),
             auth: shared::enclave::EnclaveRpcAuthConfig {
-                shared_secret: "integration-test-secret".to_string(),
+                signing_key: Some(shared::enclave::EnclaveRpcSigningKey::local_dev(
+                    "api-server",
+                )),
                 max_clock_skew_seconds: 30,
             },
+            tls: None,
         },
         allow_debug_automation_run: true,
-        secret_runtime: SecretRuntime::new(
-            TeeAttestationPolicy {
-                required: false,
-                expected_runtime: "nitro".to_string(),
-                allowed_measurements: vec!["dev-local-enclave".to_string()],
-                attestation_public_key: None,
-                max_attestation_age_seconds: 300,
-                allow_insecure_dev_attestation: true,
-            },
-            KmsDecryptPolicy {
-                key_id: "kms/local/alfred-refresh-token".to_string(),
-                key_version: 1,
-                allowed_measurements: vec!["dev-local-enclave".to_string()],
-            },
-            enclave_rpc_base_url.to_string(),
-            2000,
-            http_client.clone(),
-        ),
+        secret_runtime: test_secret_runtime(enclave_rpc_base_url, None, http_client.clone()),
         rate_limiter: RateLimiter::default(),
         trusted_proxy_ips: HashSet::<IpAddr>::new(),
         oauth_state_ttl_seconds: 300,
@@ -80,9 +153,47 @@ pub async fn build_test_router_with_enclave_base_url(
         clerk_jwks_url: clerk.jwks_url.clone(),
         clerk_jwks_cache,
         http_client,
-    };
+        cursor_codec: PaginationCursorCodec::new("integration-test-pagination-cursor-secret"),
+        admin_api_token: Some(TEST_ADMIN_API_TOKEN.to_string()),
+        admin_clerk_org: None,
+        org_admin_role: None,
+        events,
+        request_body_limits: RequestBodyLimits {
+            default_bytes: 65_536,
+            prompt_envelope_bytes: 131_072,
+        },
+        metrics: ApiMetrics::default(),
+        api_v1_deprecation: None,
+        cors: CorsConfig::default(),
+        feature_flags: FeatureFlags::new(FeatureFlagDefaults::default()),
+        request_signing: RequestSigningConfig::default(),
+        security_headers: SecurityHeadersConfig::default(),
+    }
+}
 
-    build_router(state)
+pub fn test_secret_runtime(
+    enclave_rpc_base_url: &str,
+    attestation_public_key: Option<String>,
+    http_client: reqwest::Client,
+) -> SecretRuntime {
+    SecretRuntime::new(
+        TeeAttestationPolicy {
+            required: false,
+            expected_runtime: "nitro".to_string(),
+            allowed_measurements: vec!["dev-local-enclave".to_string()],
+            attestation_public_key,
+            max_attestation_age_seconds: 300,
+            allow_insecure_dev_attestation: true,
+        },
+        KmsDecryptPolicy {
+            key_id: "kms/local/alfred-refresh-token".to_string(),
+            key_version: 1,
+            allowed_measurements: vec!["dev-local-enclave".to_string()],
+        },
+        enclave_rpc_base_url.to_string(),
+        2000,
+        http_client,
+    )
 }
 
 pub fn oauth_redirect_uri() -> &'static str {
@@ -0,0 +1,84 @@
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use serde_json::{Value, json};
+use shared::automation_schedule::{AutomationScheduleSpec, AutomationScheduleType};
+use shared::models::AutomationReportEnvelope;
+use shared::repos::AutomationPromptMaterial;
+
+pub const PROMPT_HASH_A: &str = "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+pub const PROMPT_HASH_B: &str = "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb";
+
+pub fn prompt_envelope(request_id: &str) -> Value {
+    json!({
+        "version": "v1",
+        "algorithm": "x25519-chacha20poly1305",
+        "key_id": "assistant-ingress-v1",
+        "request_id": request_id,
+        "client_ephemeral_public_key": STANDARD.encode([7_u8; 32]),
+        "nonce": STANDARD.encode([9_u8; 12]),
+        "ciphertext": STANDARD.encode(b"encrypted-automation-prompt")
+    })
+}
+
+pub fn schedule_payload(schedule_type: &str, time_zone: &str, local_time: &str) -> Value {
+    json!({
+        "schedule_type": schedule_type,
+        "time_zone": time_zone,
+        "local_time": local_time
+    })
+}
+
+pub fn error_code(body: &Value) -> Option<&str> {
+    body.get("error")
+        .and_then(|error| error.get("code"))
+        .and_then(Value::as_str)
+}
+
+pub fn report_envelope(ciphertext: &str) -> AutomationReportEnvelope {
+    AutomationReportEnvelope {
+        version: "v1".to_string(),
+        algorithm: "x25519-chacha20poly1305".to_string(),
+        key_id: "assistant-ingress-v1".to_string(),
+        request_id: "automation-report-req".to_string(),
+        sender_public_key: "c2VuZGVy".to_string(),
+        nonce: "bm9uY2U=".to_string(),
+        ciphertext: ciphertext.to_string(),
+    }
+}
+
+pub fn daily_schedule(time_zone: &str, hour: u16, minute: u16) -> AutomationScheduleSpec {
+    AutomationScheduleSpec {
+        schedule_type: AutomationScheduleType::Daily,
+        time_zone: time_zone.to_string(),
+        local_time_minutes: (hour * 60) + minute,
+        anchor_days_of_week: None,
+        anchor_day_of_month: None,
+        anchor_month: None,
+        cron_expression: None,
+        run_at: None,
+    }
+}
+
+pub fn weekly_schedule(
+    time_zone: &str,
+    hour: u16,
+    minute: u16,
+    day_of_week: u8,
+) -> AutomationScheduleSpec {
+    AutomationScheduleSpec {
+        schedule_type: AutomationScheduleType::Weekly,
+        time_zone: time_zone.to_string(),
+        local_time_minutes: (hour * 60) + minute,
+        anchor_days_of_week: Some(1 << (day_of_week - 1)),
+        anchor_day_of_month: None,
+        anchor_month: None,
+        cron_expression: None,
+        run_at: None,
+    }
+}
+
+pub fn prompt_material(ciphertext: &[u8], prompt_sha256: &str) -> AutomationPromptMaterial {
+    AutomationPromptMaterial {
+        prompt_ciphertext: ciphertext.to_vec(),
+        prompt_sha256: prompt_sha256.to_string(),
+    }
+}
@@ -46,6 +46,10 @@ struct TestClaims {
     exp: i64,
     iss: String,
     aud: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    org_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    org_role: Option<String>,
 }
 
 impl TestClerkAuth {
@@ -73,6 +77,17 @@ impl TestClerkAuth {
         )
     }
 
+    /// Session token with `org_id` active and `org_role` (in `org:<role>` form) held in it.
+    pub fn token_for_org_member(&self, subject: &str, org_id: &str, org_role: &str) -> String {
+        signed_token_with_organization(
+            subject,
+            &self.issuer,
+            &self.audience,
+            Utc::now() + Duration::minutes(5),
+            Some((org_id, org_role)),
+        )
+    }
+
     pub fn expired_token_for_subject(&self, subject: &str) -> String {
         signed_token(
             subject,
@@ -145,6 +160,16 @@ fn signed_token(
     issuer: &str,
     audience: &str,
     expires_at: chrono::DateTime<Utc>,
+) -> String {
+    signed_token_with_organization(subject, issuer, audience, expires_at, None)
+}
+
+fn signed_token_with_organization(
+    subject: &str,
+    issuer: &str,
+    audience: &str,
+    expires_at: chrono::DateTime<Utc>,
+    organization: Option<(&str, &str)>,
 ) -> String {
     let key_material = test_key_material();
     let now = Utc::now();
@@ -154,6 +179,8 @@ fn signed_token(
         exp: expires_at.timestamp(),
         iss: issuer.to_string(),
         aud: audience.to_string(),
+        org_id: organization.map(|(org_id, _)| org_id.to_string()),
+        org_role: organization.map(|(_, org_role)| org_role.to_string()),
     };
 
     let mut header = Header::new(Algorithm::RS256);
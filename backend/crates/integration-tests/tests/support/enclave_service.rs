@@ -0,0 +1,23 @@
+use shared::enclave::{EnclaveOperationService, GoogleEnclaveOauthConfig};
+use shared::repos::Store;
+
+use super::api_app::test_secret_runtime;
+
+const UNREACHABLE_BASE_URL: &str = "http://127.0.0.1:65530";
+
+/// Enclave operation service over the test store. Provider endpoints point at a closed port,
+/// so tests only exercise the paths that stay inside the database.
+pub fn test_enclave_service(store: Store) -> EnclaveOperationService {
+    let http_client = reqwest::Client::new();
+    EnclaveOperationService::new(
+        store,
+        test_secret_runtime(UNREACHABLE_BASE_URL, None, http_client.clone()),
+        http_client,
+        GoogleEnclaveOauthConfig {
+            client_id: "integration-test-client".to_string(),
+            client_secret: "integration-test-secret".to_string(),
+            token_url: format!("{UNREACHABLE_BASE_URL}/token"),
+            revoke_url: format!("{UNREACHABLE_BASE_URL}/revoke"),
+        },
+    )
+}
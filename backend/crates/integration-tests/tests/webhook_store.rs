@@ -0,0 +1,190 @@
+mod support;
+
+use chrono::{Duration as ChronoDuration, Utc};
+use serde_json::json;
+use serial_test::serial;
+use shared::pagination::PageRequest;
+use shared::webhooks::{WebhookDeliveryState, WebhookEvent, WebhookEventType};
+use uuid::Uuid;
+
+#[tokio::test]
+#[serial]
+async fn claimed_deliveries_carry_the_decrypted_secret_and_retry_until_failed() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let user_id = Uuid::new_v4();
+    let subscription = store
+        .create_webhook_subscription(
+            user_id,
+            "https://hooks.example.com/alfred",
+            &[WebhookEventType::ConnectorNeedsReauth],
+            "whsec_store_test",
+        )
+        .await
+        .expect("subscription should save");
+
+    let stored_secret: Vec<u8> = sqlx::query_scalar(
+        "SELECT signing_secret_ciphertext FROM webhook_subscriptions WHERE id = $1",
+    )
+    .bind(subscription.id)
+    .fetch_one(store.pool())
+    .await
+    .expect("secret ciphertext should load");
+    assert!(
+        !String::from_utf8_lossy(&stored_secret).contains("whsec_store_test"),
+        "signing secret must be encrypted at rest"
+    );
+
+    let event = WebhookEvent::new(
+        WebhookEventType::ConnectorNeedsReauth,
+        json!({"connector_id": Uuid::new_v4()}),
+    );
+    assert_eq!(
+        store
+            .enqueue_webhook_event(user_id, &event)
+            .await
+            .expect("event should queue"),
+        1
+    );
+    assert_eq!(
+        store
+            .enqueue_webhook_event(user_id, &event)
+            .await
+            .expect("duplicate event should be ignored"),
+        0
+    );
+
+    let now = Utc::now();
+    let worker_id = Uuid::new_v4();
+    let claimed = store
+        .claim_due_webhook_deliveries(now, worker_id, 60, 2, 10)
+        .await
+        .expect("deliveries should claim");
+    assert_eq!(claimed.len(), 1);
+    assert_eq!(claimed[0].attempts, 1);
+    assert_eq!(claimed[0].target.signing_secret, "whsec_store_test");
+    assert_eq!(claimed[0].event.id, event.id);
+    assert_eq!(claimed[0].event.data, event.data);
+    assert!(
+        store
+            .claim_due_webhook_deliveries(now, Uuid::new_v4(), 60, 2, 10)
+            .await
+            .expect("leased deliveries should be skipped")
+            .is_empty()
+    );
+
+    let delivery_id = claimed[0].id;
+    assert!(
+        !store
+            .mark_webhook_delivery_delivered(delivery_id, Uuid::new_v4(), 200, now)
+            .await
+            .expect("foreign settle should run"),
+        "only the lease owner may settle a delivery"
+    );
+    store
+        .record_webhook_delivery_failure(
+            delivery_id,
+            worker_id,
+            Some(503),
+            "endpoint answered HTTP 503",
+            Some(now),
+        )
+        .await
+        .expect("failure should record");
+    let retried = store
+        .claim_due_webhook_deliveries(now, worker_id, 60, 2, 10)
+        .await
+        .expect("retry should claim");
+    assert_eq!(retried.len(), 1);
+    assert_eq!(retried[0].attempts, 2);
+
+    store
+        .record_webhook_delivery_failure(delivery_id, worker_id, None, "connection failed", None)
+        .await
+        .expect("final failure should record");
+    assert!(
+        store
+            .claim_due_webhook_deliveries(now + ChronoDuration::hours(1), worker_id, 60, 2, 10)
+            .await
+            .expect("failed deliveries should not claim")
+            .is_empty()
+    );
+
+    let log = store
+        .list_webhook_deliveries(user_id, subscription.id, PageRequest::first(10))
+        .await
+        .expect("delivery log should list");
+    assert_eq!(log.items.len(), 1);
+    assert_eq!(log.items[0].state, WebhookDeliveryState::Failed);
+    assert_eq!(log.items[0].attempts, 2);
+    assert_eq!(log.items[0].last_status_code, None);
+    assert_eq!(
+        log.items[0].last_error.as_deref(),
+        Some("connection failed")
+    );
+    assert_eq!(log.items[0].next_attempt_at, None);
+}
+
+#[tokio::test]
+#[serial]
+async fn lapsed_final_attempts_fail_and_cannot_be_settled_late() {
+    let store = support::test_store().await;
+    support::reset_database(store.pool()).await;
+
+    let user_id = Uuid::new_v4();
+    let subscription = store
+        .create_webhook_subscription(
+            user_id,
+            "https://hooks.example.com/alfred",
+            &[WebhookEventType::ConnectorNeedsReauth],
+            "whsec_store_test",
+        )
+        .await
+        .expect("subscription should save");
+    let event = WebhookEvent::new(
+        WebhookEventType::ConnectorNeedsReauth,
+        json!({"connector_id": Uuid::new_v4()}),
+    );
+    store
+        .enqueue_webhook_event(user_id, &event)
+        .await
+        .expect("event should queue");
+
+    // The only allowed attempt is leased and its worker never reports back.
+    let now = Utc::now();
+    let stalled_worker = Uuid::new_v4();
+    let claimed = store
+        .claim_due_webhook_deliveries(now, stalled_worker, 60, 1, 10)
+        .await
+        .expect("deliveries should claim");
+    assert_eq!(claimed.len(), 1);
+
+    let after_lease = now + ChronoDuration::seconds(61);
+    assert!(
+        store
+            .claim_due_webhook_deliveries(after_lease, Uuid::new_v4(), 60, 1, 10)
+            .await
+            .expect("exhausted deliveries should not claim")
+            .is_empty()
+    );
+    assert!(
+        !store
+            .mark_webhook_delivery_delivered(claimed[0].id, stalled_worker, 200, after_lease)
+            .await
+            .expect("late settle should run"),
+        "a delivery given up after its lease lapsed stays failed"
+    );
+
+    let log = store
+        .list_webhook_deliveries(user_id, subscription.id, PageRequest::first(10))
+        .await
+        .expect("delivery log should list");
+    assert_eq!(log.items.len(), 1);
+    assert_eq!(log.items[0].state, WebhookDeliveryState::Failed);
+    assert_eq!(log.items[0].attempts, 1);
+    assert_eq!(
+        log.items[0].last_error.as_deref(),
+        Some("delivery attempt did not finish")
+    );
+}
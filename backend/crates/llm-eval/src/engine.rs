@@ -358,6 +358,12 @@ fn contract_to_value(contract: &AssistantOutputContract) -> Value {
         AssistantOutputContract::AssistantSemanticPlan(plan) => {
             serde_json::to_value(plan).expect("assistant semantic plan contract should serialize")
         }
+        AssistantOutputContract::WeeklyReview(review) => {
+            serde_json::to_value(review).expect("weekly review contract should serialize")
+        }
+        AssistantOutputContract::AutomationDraft(draft) => {
+            serde_json::to_value(draft).expect("automation draft contract should serialize")
+        }
     }
 }
 
@@ -382,6 +388,8 @@ fn capability_label(capability: Option<&AssistantQueryCapability>) -> &'static s
         Some(AssistantQueryCapability::EmailLookup) => "email_lookup",
         Some(AssistantQueryCapability::GeneralChat) => "general_chat",
         Some(AssistantQueryCapability::Mixed) => "mixed",
+        Some(AssistantQueryCapability::FocusTime) => "focus_time",
+        Some(AssistantQueryCapability::EmailCleanup) => "email_cleanup",
         None => "none",
     }
 }
@@ -395,7 +403,9 @@ fn expected_response_part_types_for(
         }
         AssistantQueryCapability::MeetingsToday
         | AssistantQueryCapability::CalendarLookup
-        | AssistantQueryCapability::EmailLookup => vec![
+        | AssistantQueryCapability::EmailLookup
+        | AssistantQueryCapability::FocusTime
+        | AssistantQueryCapability::EmailCleanup => vec![
             expected_part_type_to_fixture(AssistantResponsePartType::ChatText),
             expected_part_type_to_fixture(AssistantResponsePartType::ToolSummary),
         ],
@@ -98,6 +98,44 @@ pub fn evaluate_quality(
                 );
             }
         }
+        AssistantOutputContract::WeeklyReview(review) => {
+            require_non_empty_text("output.headline", &review.output.headline, &mut issues);
+            require_non_empty_text("output.summary", &review.output.summary, &mut issues);
+            require_all_non_empty(
+                "output.meetings_recap",
+                &review.output.meetings_recap,
+                &mut issues,
+            );
+            require_all_non_empty(
+                "output.email_recap",
+                &review.output.email_recap,
+                &mut issues,
+            );
+            require_all_non_empty(
+                "output.upcoming_week",
+                &review.output.upcoming_week,
+                &mut issues,
+            );
+        }
+        AssistantOutputContract::AutomationDraft(draft) => {
+            require_non_empty_text("output.title", &draft.output.title, &mut issues);
+            require_non_empty_text("output.prompt", &draft.output.prompt, &mut issues);
+            if !(0.0..=1.0).contains(&draft.output.confidence) {
+                issues.push("output.confidence: must be between 0.0 and 1.0".to_string());
+            }
+            if draft.output.needs_clarification
+                && draft
+                    .output
+                    .clarifying_question
+                    .as_deref()
+                    .is_none_or(|value| value.trim().is_empty())
+            {
+                issues.push(
+                    "output.clarifying_question: required when needs_clarification=true"
+                        .to_string(),
+                );
+            }
+        }
         AssistantOutputContract::AssistantSemanticPlan(plan) => {
             if plan.output.capabilities.is_empty() {
                 issues
@@ -1,5 +1,6 @@
 use base64::Engine as _;
-use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, KeyInit, OsRng, Payload};
 use chacha20poly1305::{ChaCha20Poly1305, Nonce};
 use sha2::{Digest, Sha256};
 use thiserror::Error;
@@ -8,10 +9,13 @@ use x25519_dalek::{PublicKey, StaticSecret};
 use crate::models::{
     AssistantEncryptedRequestEnvelope, AssistantEncryptedResponseEnvelope,
     AssistantPlaintextQueryRequest, AssistantPlaintextQueryResponse,
+    AssistantPlaintextSessionExport, AutomationPlaintextDraft,
 };
 
 pub const ASSISTANT_ENVELOPE_VERSION_V1: &str = "v1";
 pub const ASSISTANT_ENCRYPTION_ALGORITHM_X25519_CHACHA20POLY1305: &str = "x25519-chacha20poly1305";
+/// Session titles are sealed under a symmetric key that never leaves the user's devices.
+pub const ASSISTANT_SESSION_TITLE_ALGORITHM_CHACHA20POLY1305: &str = "chacha20poly1305";
 
 #[derive(Debug, Clone)]
 pub struct AssistantIngressKeyMaterial {
@@ -127,6 +131,62 @@ pub fn encrypt_assistant_response(
     request_id: &str,
     client_ephemeral_public_key_b64: &str,
     response: &AssistantPlaintextQueryResponse,
+) -> Result<AssistantEncryptedResponseEnvelope, AssistantCryptoError> {
+    let plaintext = serde_json::to_vec(response)
+        .map_err(|err| AssistantCryptoError::InvalidPlaintextPayload(err.to_string()))?;
+    encrypt_to_client(
+        key,
+        request_id,
+        client_ephemeral_public_key_b64,
+        &plaintext,
+        b"response",
+    )
+}
+
+/// Encrypts a session export archive to the requesting client. It uses its own key
+/// direction so an export can never be replayed as a query response for the same request_id.
+pub fn encrypt_assistant_session_export(
+    key: &AssistantIngressKeyMaterial,
+    request_id: &str,
+    client_ephemeral_public_key_b64: &str,
+    export: &AssistantPlaintextSessionExport,
+) -> Result<AssistantEncryptedResponseEnvelope, AssistantCryptoError> {
+    let plaintext = serde_json::to_vec(export)
+        .map_err(|err| AssistantCryptoError::InvalidPlaintextPayload(err.to_string()))?;
+    encrypt_to_client(
+        key,
+        request_id,
+        client_ephemeral_public_key_b64,
+        &plaintext,
+        b"export",
+    )
+}
+
+/// Encrypts an automation proposal to the client that sent the request, under a direction of
+/// its own so a proposal cannot be read back as a query response.
+pub fn encrypt_automation_draft(
+    key: &AssistantIngressKeyMaterial,
+    request_id: &str,
+    client_ephemeral_public_key_b64: &str,
+    draft: &AutomationPlaintextDraft,
+) -> Result<AssistantEncryptedResponseEnvelope, AssistantCryptoError> {
+    let plaintext = serde_json::to_vec(draft)
+        .map_err(|err| AssistantCryptoError::InvalidPlaintextPayload(err.to_string()))?;
+    encrypt_to_client(
+        key,
+        request_id,
+        client_ephemeral_public_key_b64,
+        &plaintext,
+        b"automation_draft",
+    )
+}
+
+fn encrypt_to_client(
+    key: &AssistantIngressKeyMaterial,
+    request_id: &str,
+    client_ephemeral_public_key_b64: &str,
+    plaintext: &[u8],
+    direction: &[u8],
 ) -> Result<AssistantEncryptedResponseEnvelope, AssistantCryptoError> {
     validate_common_envelope_fields(
         ASSISTANT_ENVELOPE_VERSION_V1,
@@ -144,18 +204,16 @@ pub fn encrypt_assistant_response(
     let client_public_key = PublicKey::from(client_public_key_bytes);
 
     let encrypt_key =
-        derive_directional_key(key.private_key, client_public_key, request_id, b"response");
+        derive_directional_key(key.private_key, client_public_key, request_id, direction);
     let cipher = ChaCha20Poly1305::new_from_slice(&encrypt_key)
         .map_err(|_| AssistantCryptoError::EncryptFailed)?;
 
-    let plaintext = serde_json::to_vec(response)
-        .map_err(|err| AssistantCryptoError::InvalidPlaintextPayload(err.to_string()))?;
     let nonce_bytes = build_nonce_bytes();
     let ciphertext = cipher
         .encrypt(
             Nonce::from_slice(&nonce_bytes),
             Payload {
-                msg: plaintext.as_slice(),
+                msg: plaintext,
                 aad: request_id.as_bytes(),
             },
         )
@@ -177,6 +235,71 @@ pub fn derive_public_key_b64(private_key: [u8; 32]) -> String {
     base64::engine::general_purpose::STANDARD.encode(public.as_bytes())
 }
 
+/// Fresh ingress private key for a rotation, drawn from the operating system RNG.
+pub fn generate_assistant_ingress_private_key() -> [u8; 32] {
+    let mut private_key = [0_u8; 32];
+    OsRng.fill_bytes(&mut private_key);
+    private_key
+}
+
+/// Ingress private key sealed under the enclave wrapping key so it can be stored outside the
+/// enclave. The key id is bound as associated data, so a row cannot be replayed under another id.
+#[derive(Clone)]
+pub struct WrappedAssistantIngressKey {
+    pub nonce: Vec<u8>,
+    pub ciphertext: Vec<u8>,
+}
+
+pub fn wrap_assistant_ingress_private_key(
+    wrapping_key: &[u8; 32],
+    key_id: &str,
+    private_key: &[u8; 32],
+) -> Result<WrappedAssistantIngressKey, AssistantCryptoError> {
+    let cipher = ChaCha20Poly1305::new_from_slice(wrapping_key)
+        .map_err(|_| AssistantCryptoError::EncryptFailed)?;
+    let mut nonce_bytes = [0_u8; 12];
+    OsRng.fill_bytes(&mut nonce_bytes);
+    let ciphertext = cipher
+        .encrypt(
+            Nonce::from_slice(&nonce_bytes),
+            Payload {
+                msg: private_key,
+                aad: key_id.as_bytes(),
+            },
+        )
+        .map_err(|_| AssistantCryptoError::EncryptFailed)?;
+
+    Ok(WrappedAssistantIngressKey {
+        nonce: nonce_bytes.to_vec(),
+        ciphertext,
+    })
+}
+
+pub fn unwrap_assistant_ingress_private_key(
+    wrapping_key: &[u8; 32],
+    key_id: &str,
+    wrapped: &WrappedAssistantIngressKey,
+) -> Result<[u8; 32], AssistantCryptoError> {
+    if wrapped.nonce.len() != 12 {
+        return Err(AssistantCryptoError::InvalidNonceLength);
+    }
+    let cipher = ChaCha20Poly1305::new_from_slice(wrapping_key)
+        .map_err(|_| AssistantCryptoError::DecryptFailed)?;
+    let private_key = cipher
+        .decrypt(
+            Nonce::from_slice(&wrapped.nonce),
+            Payload {
+                msg: wrapped.ciphertext.as_slice(),
+                aad: key_id.as_bytes(),
+            },
+        )
+        .map_err(|_| AssistantCryptoError::DecryptFailed)?;
+
+    private_key
+        .try_into()
+        .map_err(|_| AssistantCryptoError::DecryptFailed)
+}
+
 fn decode_base64_field(value: &str, field: &'static str) -> Result<Vec<u8>, AssistantCryptoError> {
     base64::engine::general_purpose::STANDARD
         .decode(value.as_bytes())
@@ -236,11 +359,16 @@ mod tests {
     use super::{
         ASSISTANT_ENCRYPTION_ALGORITHM_X25519_CHACHA20POLY1305, ASSISTANT_ENVELOPE_VERSION_V1,
         AssistantIngressKeyMaterial, AssistantIngressKeyring, decrypt_assistant_request,
-        derive_public_key_b64, encrypt_assistant_response,
+        derive_public_key_b64, encrypt_assistant_response, encrypt_assistant_session_export,
+        encrypt_automation_draft, generate_assistant_ingress_private_key,
+        unwrap_assistant_ingress_private_key, wrap_assistant_ingress_private_key,
     };
+    use crate::assistant_memory::{ASSISTANT_SESSION_MEMORY_VERSION_V1, AssistantSessionMemory};
     use crate::models::{
-        AssistantEncryptedRequestEnvelope, AssistantPlaintextQueryRequest,
-        AssistantPlaintextQueryResponse, AssistantQueryCapability, AssistantStructuredPayload,
+        AssistantEncryptedRequestEnvelope, AssistantPlaintextExportedSession,
+        AssistantPlaintextQueryRequest, AssistantPlaintextQueryResponse,
+        AssistantPlaintextSessionExport, AssistantQueryCapability, AssistantStructuredPayload,
+        AutomationPlaintextDraft,
     };
 
     #[test]
@@ -307,6 +435,142 @@ mod tests {
         );
     }
 
+    #[test]
+    fn session_export_is_only_readable_with_the_export_key_direction() {
+        let server_private_key = [9_u8; 32];
+        let client_private_key = StaticSecret::from([5_u8; 32]);
+        let client_public_key = base64::engine::general_purpose::STANDARD
+            .encode(PublicKey::from(&client_private_key).as_bytes());
+        let key = AssistantIngressKeyMaterial {
+            key_id: "assistant-ingress-v1".to_string(),
+            private_key: server_private_key,
+            public_key: derive_public_key_b64(server_private_key),
+            key_expires_at: chrono::Utc::now().timestamp() + 3600,
+        };
+        let now = chrono::Utc::now();
+        let export = AssistantPlaintextSessionExport {
+            format_version: 1,
+            exported_at: now,
+            sessions: vec![AssistantPlaintextExportedSession {
+                session_id: uuid::Uuid::new_v4(),
+                created_at: now,
+                updated_at: now,
+                last_capability: AssistantQueryCapability::MeetingsToday,
+                memory: AssistantSessionMemory {
+                    version: ASSISTANT_SESSION_MEMORY_VERSION_V1.to_string(),
+                    turns: vec![],
+                },
+            }],
+        };
+
+        let envelope =
+            encrypt_assistant_session_export(&key, "req-export", &client_public_key, &export)
+                .expect("export encryption should pass");
+
+        let plaintext = decrypt_for_test(
+            &client_private_key,
+            "req-export",
+            envelope.nonce.as_str(),
+            envelope.ciphertext.as_str(),
+            server_private_key,
+            b"export",
+        )
+        .expect("export direction should decrypt");
+        let decrypted: AssistantPlaintextSessionExport =
+            serde_json::from_slice(&plaintext).expect("export should parse");
+        assert_eq!(decrypted.sessions.len(), 1);
+        assert_eq!(
+            decrypted.sessions[0].session_id,
+            export.sessions[0].session_id
+        );
+
+        assert!(
+            decrypt_for_test(
+                &client_private_key,
+                "req-export",
+                envelope.nonce.as_str(),
+                envelope.ciphertext.as_str(),
+                server_private_key,
+                b"response",
+            )
+            .is_none()
+        );
+    }
+
+    #[test]
+    fn wrapped_ingress_key_only_unwraps_under_its_own_key_id() {
+        let wrapping_key = [21_u8; 32];
+        let private_key = generate_assistant_ingress_private_key();
+        let wrapped =
+            wrap_assistant_ingress_private_key(&wrapping_key, "assistant-ingress-r1", &private_key)
+                .expect("wrap should succeed");
+
+        assert_ne!(wrapped.ciphertext[..32], private_key[..]);
+        assert_eq!(
+            unwrap_assistant_ingress_private_key(&wrapping_key, "assistant-ingress-r1", &wrapped)
+                .expect("unwrap should succeed"),
+            private_key
+        );
+        assert!(
+            unwrap_assistant_ingress_private_key(&wrapping_key, "assistant-ingress-r2", &wrapped)
+                .is_err()
+        );
+        assert!(
+            unwrap_assistant_ingress_private_key(&[22_u8; 32], "assistant-ingress-r1", &wrapped)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn automation_draft_is_only_readable_with_the_draft_key_direction() {
+        let server_private_key = [9_u8; 32];
+        let client_private_key = StaticSecret::from([5_u8; 32]);
+        let client_public_key = base64::engine::general_purpose::STANDARD
+            .encode(PublicKey::from(&client_private_key).as_bytes());
+        let key = AssistantIngressKeyMaterial {
+            key_id: "assistant-ingress-v1".to_string(),
+            private_key: server_private_key,
+            public_key: derive_public_key_b64(server_private_key),
+            key_expires_at: chrono::Utc::now().timestamp() + 3600,
+        };
+        let draft = AutomationPlaintextDraft {
+            title: "Weekday brief".to_string(),
+            prompt: "Brief me on my day".to_string(),
+            schedule: None,
+            next_run_at: None,
+            needs_clarification: true,
+            clarifying_question: Some("When should this automation run?".to_string()),
+        };
+
+        let envelope = encrypt_automation_draft(&key, "req-draft", &client_public_key, &draft)
+            .expect("draft encryption should pass");
+
+        let plaintext = decrypt_for_test(
+            &client_private_key,
+            "req-draft",
+            envelope.nonce.as_str(),
+            envelope.ciphertext.as_str(),
+            server_private_key,
+            b"automation_draft",
+        )
+        .expect("draft direction should decrypt");
+        let decrypted: AutomationPlaintextDraft =
+            serde_json::from_slice(&plaintext).expect("draft should parse");
+        assert_eq!(decrypted.title, "Weekday brief");
+
+        assert!(
+            decrypt_for_test(
+                &client_private_key,
+                "req-draft",
+                envelope.nonce.as_str(),
+                envelope.ciphertext.as_str(),
+                server_private_key,
+                b"response",
+            )
+            .is_none()
+        );
+    }
+
     #[test]
     fn decrypt_rejects_unknown_key_id() {
         let keyring = AssistantIngressKeyring {
@@ -446,6 +710,27 @@ mod tests {
         ciphertext_b64: &str,
         server_private_key: [u8; 32],
     ) -> AssistantPlaintextQueryResponse {
+        let plaintext = decrypt_for_test(
+            client_private_key,
+            request_id,
+            nonce_b64,
+            ciphertext_b64,
+            server_private_key,
+            b"response",
+        )
+        .expect("response decryption should pass");
+
+        serde_json::from_slice(&plaintext).expect("response should parse")
+    }
+
+    fn decrypt_for_test(
+        client_private_key: &StaticSecret,
+        request_id: &str,
+        nonce_b64: &str,
+        ciphertext_b64: &str,
+        server_private_key: [u8; 32],
+        direction: &[u8],
+    ) -> Option<Vec<u8>> {
         let server_public_key = PublicKey::from(&StaticSecret::from(server_private_key));
         let shared_secret = client_private_key.diffie_hellman(&server_public_key);
 
@@ -454,7 +739,7 @@ mod tests {
         hasher.update(b"|");
         hasher.update(request_id.as_bytes());
         hasher.update(b"|");
-        hasher.update(b"response");
+        hasher.update(direction);
         let derived_key: [u8; 32] = hasher.finalize().into();
 
         let cipher = ChaCha20Poly1305::new_from_slice(&derived_key).expect("cipher should init");
@@ -464,7 +749,7 @@ mod tests {
         let ciphertext = base64::engine::general_purpose::STANDARD
             .decode(ciphertext_b64.as_bytes())
             .expect("ciphertext should decode");
-        let plaintext = cipher
+        cipher
             .decrypt(
                 Nonce::from_slice(&nonce),
                 Payload {
@@ -472,8 +757,6 @@ mod tests {
                     aad: request_id.as_bytes(),
                 },
             )
-            .expect("response decryption should pass");
-
-        serde_json::from_slice(&plaintext).expect("response should parse")
+            .ok()
     }
 }
@@ -23,6 +23,8 @@ pub enum AssistantSemanticCapability {
     EmailLookup,
     Mixed,
     GeneralChat,
+    FocusTime,
+    EmailCleanup,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
@@ -180,6 +182,8 @@ fn normalize_capabilities(
     let mut has_email = false;
     let mut has_mixed = false;
     let mut has_chat = false;
+    let mut has_focus_time = false;
+    let mut has_email_cleanup = false;
 
     for capability in capabilities {
         match capability {
@@ -187,12 +191,20 @@ fn normalize_capabilities(
             AssistantSemanticCapability::EmailLookup => has_email = true,
             AssistantSemanticCapability::Mixed => has_mixed = true,
             AssistantSemanticCapability::GeneralChat => has_chat = true,
+            AssistantSemanticCapability::FocusTime => has_focus_time = true,
+            AssistantSemanticCapability::EmailCleanup => has_email_cleanup = true,
         }
     }
 
     if has_mixed || (has_calendar && has_email) {
         return vec![AssistantQueryCapability::Mixed];
     }
+    if has_focus_time {
+        return vec![AssistantQueryCapability::FocusTime];
+    }
+    if has_email_cleanup {
+        return vec![AssistantQueryCapability::EmailCleanup];
+    }
     if has_calendar {
         return vec![AssistantQueryCapability::CalendarLookup];
     }
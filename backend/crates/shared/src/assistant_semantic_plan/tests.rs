@@ -42,6 +42,61 @@ fn normalize_promotes_calendar_and_email_to_mixed() {
     assert_eq!(plan.language.as_deref(), Some("en-us"));
 }
 
+#[test]
+fn normalize_prefers_focus_time_over_calendar_lookup() {
+    let plan = normalize_semantic_plan_contract(
+        AssistantSemanticPlanContract {
+            version: ASSISTANT_SEMANTIC_PLAN_VERSION_V1.to_string(),
+            output: AssistantSemanticPlanOutput {
+                capabilities: vec![
+                    AssistantSemanticCapability::CalendarLookup,
+                    AssistantSemanticCapability::FocusTime,
+                ],
+                confidence: 0.8,
+                needs_clarification: false,
+                clarifying_question: None,
+                time_window: None,
+                email_filters: None,
+                language: Some("en".to_string()),
+            },
+        },
+        "UTC",
+        utc("2026-02-18T00:00:00Z"),
+    )
+    .expect("plan should normalize");
+
+    assert_eq!(plan.capabilities, vec![AssistantQueryCapability::FocusTime]);
+}
+
+#[test]
+fn normalize_prefers_email_cleanup_over_email_lookup() {
+    let plan = normalize_semantic_plan_contract(
+        AssistantSemanticPlanContract {
+            version: ASSISTANT_SEMANTIC_PLAN_VERSION_V1.to_string(),
+            output: AssistantSemanticPlanOutput {
+                capabilities: vec![
+                    AssistantSemanticCapability::EmailLookup,
+                    AssistantSemanticCapability::EmailCleanup,
+                ],
+                confidence: 0.8,
+                needs_clarification: false,
+                clarifying_question: None,
+                time_window: None,
+                email_filters: None,
+                language: Some("en".to_string()),
+            },
+        },
+        "UTC",
+        utc("2026-02-18T00:00:00Z"),
+    )
+    .expect("plan should normalize");
+
+    assert_eq!(
+        plan.capabilities,
+        vec![AssistantQueryCapability::EmailCleanup]
+    );
+}
+
 #[test]
 fn normalize_clamps_email_filters() {
     let plan = normalize_semantic_plan_contract(
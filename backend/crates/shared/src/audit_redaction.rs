@@ -0,0 +1,334 @@
+use serde_json::Value;
+use tracing::debug;
+
+use crate::models::AuditMetadata;
+
+pub const REDACTED_METADATA_VALUE: &str = "[REDACTED]";
+
+/// Keys that carry credentials in current or plausible audit metadata. Matching is exact (or by
+/// prefix) on the lowercased key, so operational fields such as `reason_code` or `error_code`
+/// are no longer caught by a bare `code` substring.
+const DEFAULT_DENY_RULES: [&str; 16] = [
+    "access_token",
+    "api_key",
+    "apns_token",
+    "auth_code",
+    "authorization",
+    "authorization_code",
+    "client_secret",
+    "code",
+    "cookie",
+    "id_token",
+    "identity_token",
+    "oauth_code",
+    "password*",
+    "refresh_token",
+    "secret*",
+    "token",
+];
+
+const SENSITIVE_VALUE_MARKERS: [&str; 10] = [
+    "refresh_token",
+    "access_token",
+    "client_secret",
+    "apns_token",
+    "authorization=",
+    "authorization:",
+    "bearer ",
+    "oauth_code",
+    "identity_token",
+    "id_token",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MetadataKeyRule {
+    Exact(String),
+    Prefix(String),
+}
+
+impl MetadataKeyRule {
+    /// Parses `name` as an exact rule and `name*` as a prefix rule.
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        let raw = raw.trim().to_ascii_lowercase();
+        match raw.strip_suffix('*') {
+            Some(prefix) if !prefix.is_empty() && !prefix.contains('*') => {
+                Ok(Self::Prefix(prefix.to_string()))
+            }
+            None if !raw.is_empty() && !raw.contains('*') => Ok(Self::Exact(raw)),
+            _ => Err(format!(
+                "invalid metadata key rule '{raw}': use 'key' or 'prefix*'"
+            )),
+        }
+    }
+
+    fn matches(&self, key: &str) -> bool {
+        match self {
+            Self::Exact(exact) => key == exact,
+            Self::Prefix(prefix) => key.starts_with(prefix.as_str()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RedactionDecision {
+    /// An allow rule matched; only a secret-looking value can still redact the entry.
+    Allowed,
+    /// No rule matched the key.
+    Unlisted,
+    /// A deny rule matched the key.
+    DeniedKey,
+    /// The value itself looks like a credential.
+    SensitiveValue,
+}
+
+impl RedactionDecision {
+    pub fn redacts(self) -> bool {
+        matches!(self, Self::DeniedKey | Self::SensitiveValue)
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Allowed => "allowed",
+            Self::Unlisted => "unlisted",
+            Self::DeniedKey => "denied_key",
+            Self::SensitiveValue => "sensitive_value",
+        }
+    }
+}
+
+/// Decides which audit metadata values are stored verbatim. Allow rules win over deny rules so
+/// operators can carve exceptions out of a broad prefix; value scanning applies to every key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditRedactionPolicy {
+    allow: Vec<MetadataKeyRule>,
+    deny: Vec<MetadataKeyRule>,
+}
+
+impl Default for AuditRedactionPolicy {
+    fn default() -> Self {
+        Self {
+            allow: Vec::new(),
+            deny: DEFAULT_DENY_RULES
+                .iter()
+                .map(|rule| MetadataKeyRule::parse(rule).expect("default rules are valid"))
+                .collect(),
+        }
+    }
+}
+
+impl AuditRedactionPolicy {
+    /// Extends the default deny list with `extra_deny` and adds `allow` exceptions.
+    pub fn with_rules(allow: &[String], extra_deny: &[String]) -> Result<Self, String> {
+        let mut policy = Self::default();
+        for rule in allow {
+            policy.allow.push(MetadataKeyRule::parse(rule)?);
+        }
+        for rule in extra_deny {
+            policy.deny.push(MetadataKeyRule::parse(rule)?);
+        }
+        Ok(policy)
+    }
+
+    pub fn decide(&self, key: &str, value: &str) -> RedactionDecision {
+        if is_sensitive_metadata_value(value) {
+            return RedactionDecision::SensitiveValue;
+        }
+
+        let key = key.to_ascii_lowercase();
+        if self.allow.iter().any(|rule| rule.matches(&key)) {
+            return RedactionDecision::Allowed;
+        }
+        if self.deny.iter().any(|rule| rule.matches(&key)) {
+            return RedactionDecision::DeniedKey;
+        }
+        RedactionDecision::Unlisted
+    }
+
+    /// Redacts `metadata` while keeping the JSON type of every retained value. Non-string
+    /// values are scanned through their JSON text, so secrets nested in arrays or objects are
+    /// still caught.
+    pub fn redact(&self, metadata: &AuditMetadata) -> Value {
+        Value::Object(
+            metadata
+                .iter()
+                .map(|(key, value)| {
+                    let decision = match value {
+                        Value::String(text) => self.decide(key, text),
+                        other => self.decide(key, &other.to_string()),
+                    };
+                    if decision.redacts() {
+                        debug!(
+                            metadata_key = %key,
+                            decision = decision.as_str(),
+                            "audit metadata value redacted"
+                        );
+                        (
+                            key.clone(),
+                            Value::String(REDACTED_METADATA_VALUE.to_string()),
+                        )
+                    } else {
+                        (key.clone(), value.clone())
+                    }
+                })
+                .collect(),
+        )
+    }
+}
+
+fn is_sensitive_metadata_value(value: &str) -> bool {
+    let value = value.to_ascii_lowercase();
+    SENSITIVE_VALUE_MARKERS
+        .iter()
+        .any(|marker| value.contains(marker))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use crate::models::AuditMetadata;
+
+    use super::{
+        AuditRedactionPolicy, MetadataKeyRule, RedactionDecision, is_sensitive_metadata_value,
+    };
+
+    #[test]
+    fn default_policy_matches_exact_keys_case_insensitively() {
+        let policy = AuditRedactionPolicy::default();
+
+        assert_eq!(
+            policy.decide("refresh_token", "rt"),
+            RedactionDecision::DeniedKey
+        );
+        assert_eq!(
+            policy.decide("Authorization", "x"),
+            RedactionDecision::DeniedKey
+        );
+        assert_eq!(
+            policy.decide("OAUTH_CODE", "x"),
+            RedactionDecision::DeniedKey
+        );
+        assert_eq!(
+            policy.decide("secretValue", "x"),
+            RedactionDecision::DeniedKey
+        );
+        assert_eq!(
+            policy.decide("request_id", "req-1"),
+            RedactionDecision::Unlisted
+        );
+    }
+
+    #[test]
+    fn default_policy_no_longer_over_redacts_code_like_fields() {
+        let policy = AuditRedactionPolicy::default();
+
+        assert_eq!(
+            policy.decide("reason_code", "LEASE_EXPIRED"),
+            RedactionDecision::Unlisted
+        );
+        assert_eq!(
+            policy.decide("error_code", "PUSH_FAILED"),
+            RedactionDecision::Unlisted
+        );
+        assert_eq!(policy.decide("code", "abc"), RedactionDecision::DeniedKey);
+    }
+
+    #[test]
+    fn allow_rules_override_deny_rules_but_not_value_scanning() {
+        let policy = AuditRedactionPolicy::with_rules(
+            &["secret_rotation_id".to_string()],
+            &["session_*".to_string()],
+        )
+        .expect("rules should parse");
+
+        assert_eq!(
+            policy.decide("secret_rotation_id", "rot-1"),
+            RedactionDecision::Allowed
+        );
+        assert_eq!(
+            policy.decide("secret_rotation_id", "Bearer abc"),
+            RedactionDecision::SensitiveValue
+        );
+        assert_eq!(
+            policy.decide("session_memory", "notes"),
+            RedactionDecision::DeniedKey
+        );
+    }
+
+    #[test]
+    fn rule_parsing_supports_exact_and_prefix_rules() {
+        assert_eq!(
+            MetadataKeyRule::parse(" Api_Key "),
+            Ok(MetadataKeyRule::Exact("api_key".to_string()))
+        );
+        assert_eq!(
+            MetadataKeyRule::parse("x_secret_*"),
+            Ok(MetadataKeyRule::Prefix("x_secret_".to_string()))
+        );
+        assert!(MetadataKeyRule::parse("*").is_err());
+        assert!(MetadataKeyRule::parse("").is_err());
+        assert!(MetadataKeyRule::parse("*_token").is_err());
+    }
+
+    #[test]
+    fn redaction_masks_sensitive_fields_and_preserves_non_sensitive_fields() {
+        let mut metadata = AuditMetadata::new();
+        metadata.insert("refresh_token".to_string(), json!("rt-123"));
+        metadata.insert("Authorization".to_string(), json!("Bearer abc"));
+        metadata.insert(
+            "reason".to_string(),
+            json!("dial failed; authorization=Bearer secret"),
+        );
+        metadata.insert("request_id".to_string(), json!("req-1"));
+        metadata.insert("status".to_string(), json!("ok"));
+
+        let redacted = AuditRedactionPolicy::default().redact(&metadata);
+        let object = redacted
+            .as_object()
+            .expect("redacted metadata should always be a JSON object");
+
+        for key in ["refresh_token", "Authorization", "reason"] {
+            assert_eq!(
+                object.get(key),
+                Some(&Value::String("[REDACTED]".to_string()))
+            );
+        }
+        assert_eq!(
+            object.get("request_id"),
+            Some(&Value::String("req-1".to_string()))
+        );
+        assert_eq!(object.get("status"), Some(&Value::String("ok".to_string())));
+    }
+
+    #[test]
+    fn redaction_keeps_value_types_and_scans_nested_values() {
+        let mut metadata = AuditMetadata::new();
+        metadata.insert("attempts".to_string(), json!(3));
+        metadata.insert("delivered".to_string(), json!(true));
+        metadata.insert("scopes".to_string(), json!(["calendar", "gmail"]));
+        metadata.insert(
+            "upstream".to_string(),
+            json!({"headers": ["Authorization: Bearer abc"]}),
+        );
+
+        let redacted = AuditRedactionPolicy::default().redact(&metadata);
+
+        assert_eq!(
+            redacted,
+            json!({
+                "attempts": 3,
+                "delivered": true,
+                "scopes": ["calendar", "gmail"],
+                "upstream": "[REDACTED]",
+            })
+        );
+    }
+
+    #[test]
+    fn sensitive_metadata_value_detection_is_case_insensitive() {
+        assert!(is_sensitive_metadata_value("authorization: Bearer abc"));
+        assert!(is_sensitive_metadata_value("contains refresh_token value"));
+        assert!(is_sensitive_metadata_value("ID_TOKEN present"));
+        assert!(!is_sensitive_metadata_value("provider timeout"));
+    }
+}
@@ -0,0 +1,125 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, Utc};
+
+pub const DEFAULT_AUDIT_RETENTION_DAYS: u32 = 365;
+
+/// How long audit events are kept, with optional per-event-type overrides. Event types are
+/// matched exactly; every other type falls back to `default_days`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditRetentionPolicy {
+    default_days: u32,
+    event_type_days: BTreeMap<String, u32>,
+}
+
+impl Default for AuditRetentionPolicy {
+    fn default() -> Self {
+        Self {
+            default_days: DEFAULT_AUDIT_RETENTION_DAYS,
+            event_type_days: BTreeMap::new(),
+        }
+    }
+}
+
+impl AuditRetentionPolicy {
+    /// Builds a policy from `default_days` and `EVENT_TYPE=days` overrides.
+    pub fn with_overrides(default_days: u32, overrides: &[String]) -> Result<Self, String> {
+        if default_days == 0 {
+            return Err("audit retention days must be greater than 0".to_string());
+        }
+
+        let mut event_type_days = BTreeMap::new();
+        for raw in overrides {
+            let (event_type, days) = raw.split_once('=').ok_or_else(|| {
+                format!("invalid audit retention override '{raw}': use 'EVENT_TYPE=days'")
+            })?;
+            let event_type = event_type.trim();
+            let days = days
+                .trim()
+                .parse::<u32>()
+                .ok()
+                .filter(|days| *days > 0)
+                .ok_or_else(|| {
+                    format!("invalid audit retention days in '{raw}': use a positive integer")
+                })?;
+            if event_type.is_empty() {
+                return Err(format!(
+                    "invalid audit retention override '{raw}': event type is empty"
+                ));
+            }
+            event_type_days.insert(event_type.to_string(), days);
+        }
+
+        Ok(Self {
+            default_days,
+            event_type_days,
+        })
+    }
+
+    pub fn default_days(&self) -> u32 {
+        self.default_days
+    }
+
+    pub fn days_for(&self, event_type: &str) -> u32 {
+        self.event_type_days
+            .get(event_type)
+            .copied()
+            .unwrap_or(self.default_days)
+    }
+
+    /// Events created before the returned cutoff are expired.
+    pub fn default_cutoff(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        now - Duration::days(i64::from(self.default_days))
+    }
+
+    /// Per-event-type cutoffs for every override, in event type order.
+    pub fn override_cutoffs(&self, now: DateTime<Utc>) -> Vec<(String, DateTime<Utc>)> {
+        self.event_type_days
+            .iter()
+            .map(|(event_type, days)| (event_type.clone(), now - Duration::days(i64::from(*days))))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use super::AuditRetentionPolicy;
+
+    #[test]
+    fn overrides_apply_per_event_type_and_fall_back_to_default() {
+        let policy = AuditRetentionPolicy::with_overrides(
+            180,
+            &[
+                "ASSISTANT_QUERY=30".to_string(),
+                " CONNECTOR_REVOKED = 730 ".to_string(),
+            ],
+        )
+        .expect("overrides should parse");
+        let now = Utc.with_ymd_and_hms(2026, 6, 1, 0, 0, 0).unwrap();
+
+        assert_eq!(policy.days_for("ASSISTANT_QUERY"), 30);
+        assert_eq!(policy.days_for("CONNECTOR_REVOKED"), 730);
+        assert_eq!(policy.days_for("DEVICE_REGISTERED"), 180);
+        assert_eq!(policy.default_cutoff(now), now - Duration::days(180));
+        assert_eq!(
+            policy.override_cutoffs(now),
+            vec![
+                ("ASSISTANT_QUERY".to_string(), now - Duration::days(30)),
+                ("CONNECTOR_REVOKED".to_string(), now - Duration::days(730)),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_overrides_are_rejected() {
+        for raw in ["ASSISTANT_QUERY", "ASSISTANT_QUERY=0", "=30", "X=abc"] {
+            assert!(
+                AuditRetentionPolicy::with_overrides(365, &[raw.to_string()]).is_err(),
+                "{raw} should be rejected"
+            );
+        }
+        assert!(AuditRetentionPolicy::with_overrides(0, &[]).is_err());
+    }
+}
@@ -0,0 +1,134 @@
+use std::future::Future;
+use std::pin::Pin;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::security::{SecurityError, verify_ed25519_signature};
+
+const AUDIT_EVENT_SIGNING_DOMAIN: &str = "alfred.audit_event.v1";
+
+/// Event types the enclave signs when audit signing is enabled: connector changes, privacy
+/// deletes, and automation runs. Everything else stays hash-chained only.
+pub const SIGNED_AUDIT_EVENT_TYPES: [&str; 14] = [
+    "GOOGLE_CONNECT_COMPLETED",
+    "GOOGLE_SCOPE_UPGRADE_COMPLETED",
+    "CALDAV_CONNECT_COMPLETED",
+    "IMAP_CONNECT_COMPLETED",
+    "CONNECTOR_REVOKED",
+    "CONNECTORS_REVOKED",
+    "PRIVACY_DELETE_ALL_REQUESTED",
+    "PRIVACY_DELETE_ALL_COMPLETED",
+    "PRIVACY_DELETE_ALL_FAILED",
+    "AUTOMATION_RUN_NOW_QUEUED",
+    "AUTOMATION_DEBUG_RUN_QUEUED",
+    "AUTOMATION_RUN_TIMED_OUT",
+    "JOB_ACTION_GENERATED",
+    "JOB_ACTION_SKIPPED",
+];
+
+pub fn is_signed_audit_event_type(event_type: &str) -> bool {
+    SIGNED_AUDIT_EVENT_TYPES.contains(&event_type)
+}
+
+/// What the enclave signs for an audit event. `entry_hash` is the row's hex-encoded audit
+/// chain hash, which already covers every stored field and the previous link.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEventSigningPayload {
+    pub event_id: Uuid,
+    pub user_id: Uuid,
+    pub event_type: String,
+    pub entry_hash: String,
+}
+
+impl AuditEventSigningPayload {
+    pub fn canonical(&self) -> String {
+        format!(
+            "{AUDIT_EVENT_SIGNING_DOMAIN}|{}|{}|{}|{}",
+            self.event_id, self.user_id, self.event_type, self.entry_hash
+        )
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("audit event signing failed: {0}")]
+pub struct AuditEventSignError(pub String);
+
+pub type AuditEventSignFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<String, AuditEventSignError>> + Send + 'a>>;
+
+/// Produces the base64 Ed25519 signature stored alongside a signed audit event.
+pub trait AuditEventSigner: Send + Sync {
+    fn sign_audit_event<'a>(
+        &'a self,
+        payload: &'a AuditEventSigningPayload,
+    ) -> AuditEventSignFuture<'a>;
+}
+
+/// Checks `signature` over `payload` against the enclave's base64 attestation public key.
+pub fn verify_audit_event_signature(
+    encoded_public_key: &str,
+    signature: &str,
+    payload: &AuditEventSigningPayload,
+) -> Result<(), SecurityError> {
+    verify_ed25519_signature(
+        encoded_public_key,
+        signature,
+        payload.canonical().as_bytes(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::Engine as _;
+    use ed25519_dalek::{Signer, SigningKey};
+    use uuid::Uuid;
+
+    use super::{
+        AuditEventSigningPayload, is_signed_audit_event_type, verify_audit_event_signature,
+    };
+
+    fn payload() -> AuditEventSigningPayload {
+        AuditEventSigningPayload {
+            event_id: Uuid::from_u128(1),
+            user_id: Uuid::from_u128(2),
+            event_type: "CONNECTOR_REVOKED".to_string(),
+            entry_hash: "ab".repeat(32),
+        }
+    }
+
+    #[test]
+    fn only_high_value_event_types_are_signed() {
+        assert!(is_signed_audit_event_type("CONNECTOR_REVOKED"));
+        assert!(is_signed_audit_event_type("PRIVACY_DELETE_ALL_COMPLETED"));
+        assert!(is_signed_audit_event_type("JOB_ACTION_GENERATED"));
+        assert!(!is_signed_audit_event_type("DEVICE_REGISTERED"));
+        assert!(!is_signed_audit_event_type("AUDIT_EXPORT_REQUESTED"));
+    }
+
+    #[test]
+    fn signature_verifies_only_for_the_signed_payload() {
+        let engine = base64::engine::general_purpose::STANDARD;
+        let signing_key = SigningKey::from_bytes(&[9_u8; 32]);
+        let public_key = engine.encode(signing_key.verifying_key().to_bytes());
+        let signature = engine.encode(
+            signing_key
+                .sign(payload().canonical().as_bytes())
+                .to_bytes(),
+        );
+
+        assert!(verify_audit_event_signature(&public_key, &signature, &payload()).is_ok());
+
+        let mut tampered = payload();
+        tampered.entry_hash = "cd".repeat(32);
+        assert!(verify_audit_event_signature(&public_key, &signature, &tampered).is_err());
+
+        let other_key = engine.encode(
+            SigningKey::from_bytes(&[8_u8; 32])
+                .verifying_key()
+                .to_bytes(),
+        );
+        assert!(verify_audit_event_signature(&other_key, &signature, &payload()).is_err());
+    }
+}
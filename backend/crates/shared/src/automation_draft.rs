@@ -0,0 +1,337 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::automation_schedule::{
+    AutomationScheduleSpec, AutomationScheduleType, ScheduleAnchors, build_anchored_schedule_spec,
+    build_cron_schedule_spec, build_once_schedule_spec, next_run_after, parse_local_time_hhmm,
+    weekday_mask,
+};
+use crate::models::{AutomationPlaintextDraft, AutomationSchedule};
+
+pub const AUTOMATION_DRAFT_VERSION_V1: &str = "2026-10-17";
+const MAX_DRAFT_TITLE_CHARS: usize = 120;
+const MAX_DRAFT_PROMPT_CHARS: usize = 4_000;
+const MAX_CLARIFYING_QUESTION_CHARS: usize = 240;
+const DEFAULT_DRAFT_TITLE: &str = "New automation";
+const DEFAULT_CLARIFYING_QUESTION: &str = "When should this automation run?";
+
+/// Schedule the planner read out of the request. `run_at` is an RFC3339 timestamp; every other
+/// field follows the `createAutomation` schedule rules.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AutomationDraftScheduleOutput {
+    pub schedule_type: AutomationScheduleType,
+    #[serde(default)]
+    pub local_time: Option<String>,
+    #[serde(default)]
+    pub days_of_week: Vec<u8>,
+    #[serde(default)]
+    pub day_of_month: Option<u8>,
+    #[serde(default)]
+    pub cron_expression: Option<String>,
+    #[serde(default)]
+    pub run_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AutomationDraftOutput {
+    pub title: String,
+    pub prompt: String,
+    pub confidence: f64,
+    #[serde(default)]
+    pub needs_clarification: bool,
+    #[serde(default)]
+    pub clarifying_question: Option<String>,
+    #[serde(default)]
+    pub schedule: Option<AutomationDraftScheduleOutput>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AutomationDraftContract {
+    pub version: String,
+    pub output: AutomationDraftOutput,
+}
+
+#[derive(Debug, Error)]
+pub enum AutomationDraftNormalizationError {
+    #[error("automation draft confidence must be a finite number between 0.0 and 1.0")]
+    InvalidConfidence,
+    #[error("automation draft schedule is invalid: {0}")]
+    InvalidSchedule(String),
+}
+
+/// Turns planner output into the proposal shown to the user. A draft without a usable schedule
+/// always asks a clarifying question, and an empty prompt falls back to the user's own words.
+pub fn normalize_automation_draft_output(
+    output: AutomationDraftOutput,
+    source_prompt: &str,
+    time_zone: &str,
+    now: DateTime<Utc>,
+) -> Result<AutomationPlaintextDraft, AutomationDraftNormalizationError> {
+    if !output.confidence.is_finite() || !(0.0..=1.0).contains(&output.confidence) {
+        return Err(AutomationDraftNormalizationError::InvalidConfidence);
+    }
+
+    let (schedule, next_run_at) = match output.schedule {
+        Some(schedule) => {
+            let spec = draft_schedule_spec(&schedule, time_zone, now)
+                .map_err(AutomationDraftNormalizationError::InvalidSchedule)?;
+            let next_run_at = next_run_after(now, &spec).ok_or_else(|| {
+                AutomationDraftNormalizationError::InvalidSchedule(
+                    "schedule has no upcoming run".to_string(),
+                )
+            })?;
+            (Some(schedule_from_spec(spec)), Some(next_run_at))
+        }
+        None => (None, None),
+    };
+
+    let needs_clarification = output.needs_clarification || schedule.is_none();
+    let clarifying_question = needs_clarification.then(|| {
+        normalize_optional_text(
+            output.clarifying_question.as_deref(),
+            MAX_CLARIFYING_QUESTION_CHARS,
+        )
+        .unwrap_or_else(|| DEFAULT_CLARIFYING_QUESTION.to_string())
+    });
+
+    Ok(AutomationPlaintextDraft {
+        title: normalize_optional_text(Some(output.title.as_str()), MAX_DRAFT_TITLE_CHARS)
+            .unwrap_or_else(|| DEFAULT_DRAFT_TITLE.to_string()),
+        prompt: normalize_optional_text(Some(output.prompt.as_str()), MAX_DRAFT_PROMPT_CHARS)
+            .unwrap_or_else(|| fallback_prompt(source_prompt)),
+        schedule,
+        next_run_at,
+        needs_clarification,
+        clarifying_question,
+    })
+}
+
+/// Proposal returned when the planner is unavailable or its output is unusable: the user's
+/// request becomes the prompt and they are asked for a schedule.
+pub fn fallback_automation_draft(source_prompt: &str) -> AutomationPlaintextDraft {
+    AutomationPlaintextDraft {
+        title: DEFAULT_DRAFT_TITLE.to_string(),
+        prompt: fallback_prompt(source_prompt),
+        schedule: None,
+        next_run_at: None,
+        needs_clarification: true,
+        clarifying_question: Some(DEFAULT_CLARIFYING_QUESTION.to_string()),
+    }
+}
+
+fn draft_schedule_spec(
+    schedule: &AutomationDraftScheduleOutput,
+    time_zone: &str,
+    now: DateTime<Utc>,
+) -> Result<AutomationScheduleSpec, String> {
+    match schedule.schedule_type {
+        AutomationScheduleType::Once => {
+            let run_at = schedule
+                .run_at
+                .as_deref()
+                .ok_or_else(|| "run_at is required for ONCE schedules".to_string())?;
+            let run_at = DateTime::parse_from_rfc3339(run_at)
+                .map_err(|_| "run_at must be an RFC3339 timestamp".to_string())?
+                .with_timezone(&Utc);
+            build_once_schedule_spec(time_zone, run_at)
+        }
+        AutomationScheduleType::Cron => {
+            let cron_expression = schedule
+                .cron_expression
+                .as_deref()
+                .ok_or_else(|| "cron_expression is required for CRON schedules".to_string())?;
+            build_cron_schedule_spec(time_zone, cron_expression)
+        }
+        schedule_type => {
+            let local_time_minutes = schedule
+                .local_time
+                .as_deref()
+                .and_then(parse_local_time_hhmm)
+                .ok_or_else(|| "local_time must use HH:MM 24-hour format".to_string())?;
+            let days_of_week = if schedule.days_of_week.is_empty() {
+                None
+            } else {
+                Some(weekday_mask(&schedule.days_of_week).ok_or_else(|| {
+                    "days_of_week must list weekdays from 1 (Monday) to 7 (Sunday)".to_string()
+                })?)
+            };
+            build_anchored_schedule_spec(
+                schedule_type,
+                time_zone,
+                local_time_minutes,
+                ScheduleAnchors {
+                    days_of_week,
+                    day_of_month: schedule.day_of_month,
+                },
+                now,
+            )
+        }
+    }
+}
+
+/// The schedule as a client submits it to `createAutomation`.
+fn schedule_from_spec(spec: AutomationScheduleSpec) -> AutomationSchedule {
+    let local_time = match spec.schedule_type {
+        AutomationScheduleType::Cron | AutomationScheduleType::Once => None,
+        _ => Some(spec.local_time_hhmm()),
+    };
+    let days_of_week =
+        (spec.schedule_type == AutomationScheduleType::Weekly).then(|| spec.days_of_week());
+    let day_of_month = match spec.schedule_type {
+        AutomationScheduleType::Monthly => spec.anchor_day_of_month,
+        _ => None,
+    };
+
+    AutomationSchedule {
+        schedule_type: spec.schedule_type,
+        time_zone: spec.time_zone,
+        local_time,
+        days_of_week,
+        day_of_month,
+        cron_expression: spec.cron_expression,
+        run_at: spec.run_at,
+    }
+}
+
+fn fallback_prompt(source_prompt: &str) -> String {
+    source_prompt
+        .trim()
+        .chars()
+        .take(MAX_DRAFT_PROMPT_CHARS)
+        .collect()
+}
+
+fn normalize_optional_text(value: Option<&str>, max_chars: usize) -> Option<String> {
+    let trimmed = value?.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+    Some(trimmed.chars().take(max_chars).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn reference_now() -> DateTime<Utc> {
+        // Wednesday 2026-10-14 12:00 UTC.
+        Utc.with_ymd_and_hms(2026, 10, 14, 12, 0, 0).unwrap()
+    }
+
+    fn weekday_output() -> AutomationDraftOutput {
+        AutomationDraftOutput {
+            title: "  Weekday brief ".to_string(),
+            prompt: "Brief me on my calendar and urgent email.".to_string(),
+            confidence: 0.9,
+            needs_clarification: false,
+            clarifying_question: None,
+            schedule: Some(AutomationDraftScheduleOutput {
+                schedule_type: AutomationScheduleType::Weekly,
+                local_time: Some("07:00".to_string()),
+                days_of_week: vec![1, 2, 3, 4, 5],
+                day_of_month: None,
+                cron_expression: None,
+                run_at: None,
+            }),
+        }
+    }
+
+    #[test]
+    fn normalizes_a_weekday_schedule() {
+        let draft = normalize_automation_draft_output(
+            weekday_output(),
+            "brief me every weekday at 7",
+            "America/New_York",
+            reference_now(),
+        )
+        .expect("draft should normalize");
+
+        assert_eq!(draft.title, "Weekday brief");
+        assert!(!draft.needs_clarification);
+        assert_eq!(draft.clarifying_question, None);
+        let schedule = draft.schedule.expect("schedule should be present");
+        assert_eq!(schedule.schedule_type, AutomationScheduleType::Weekly);
+        assert_eq!(schedule.time_zone, "America/New_York");
+        assert_eq!(schedule.local_time.as_deref(), Some("07:00"));
+        assert_eq!(schedule.days_of_week, Some(vec![1, 2, 3, 4, 5]));
+        // Thursday 07:00 in New York (EDT).
+        assert_eq!(
+            draft.next_run_at,
+            Some(Utc.with_ymd_and_hms(2026, 10, 15, 11, 0, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn missing_schedule_asks_for_clarification() {
+        let mut output = weekday_output();
+        output.schedule = None;
+        output.prompt = "   ".to_string();
+
+        let draft =
+            normalize_automation_draft_output(output, "brief me sometimes", "UTC", reference_now())
+                .expect("draft should normalize");
+
+        assert!(draft.needs_clarification);
+        assert_eq!(
+            draft.clarifying_question.as_deref(),
+            Some(DEFAULT_CLARIFYING_QUESTION)
+        );
+        assert_eq!(draft.prompt, "brief me sometimes");
+        assert_eq!(draft.next_run_at, None);
+    }
+
+    #[test]
+    fn rejects_schedules_the_create_endpoint_would_reject() {
+        let mut misplaced_anchor = weekday_output();
+        if let Some(schedule) = misplaced_anchor.schedule.as_mut() {
+            schedule.schedule_type = AutomationScheduleType::Daily;
+        }
+        let mut past_once = weekday_output();
+        past_once.schedule = Some(AutomationDraftScheduleOutput {
+            schedule_type: AutomationScheduleType::Once,
+            local_time: None,
+            days_of_week: Vec::new(),
+            day_of_month: None,
+            cron_expression: None,
+            run_at: Some("2026-10-13T09:00:00Z".to_string()),
+        });
+        let mut bad_time = weekday_output();
+        if let Some(schedule) = bad_time.schedule.as_mut() {
+            schedule.local_time = Some("7am".to_string());
+        }
+
+        for output in [misplaced_anchor, past_once, bad_time] {
+            assert!(matches!(
+                normalize_automation_draft_output(output, "prompt", "UTC", reference_now()),
+                Err(AutomationDraftNormalizationError::InvalidSchedule(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn rejects_out_of_range_confidence() {
+        let mut output = weekday_output();
+        output.confidence = 1.5;
+
+        assert!(matches!(
+            normalize_automation_draft_output(output, "prompt", "UTC", reference_now()),
+            Err(AutomationDraftNormalizationError::InvalidConfidence)
+        ));
+    }
+
+    #[test]
+    fn fallback_keeps_the_users_request() {
+        let draft = fallback_automation_draft("  brief me every weekday at 7 ");
+
+        assert_eq!(draft.prompt, "brief me every weekday at 7");
+        assert!(draft.needs_clarification);
+        assert!(draft.schedule.is_none());
+    }
+}
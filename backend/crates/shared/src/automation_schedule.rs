@@ -1,21 +1,50 @@
 use chrono::{
-    DateTime, Datelike, Days, Duration, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, TimeZone,
-    Utc,
+    DateTime, Datelike, Days, Duration, FixedOffset, LocalResult, NaiveDate, NaiveDateTime,
+    NaiveTime, TimeZone, Utc,
 };
 use chrono_tz::Tz;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::timezone::normalize_time_zone;
 
+mod condition;
+mod cron;
+
+pub use condition::{
+    AutomationCondition, AutomationConditionKind, MAX_AUTOMATION_CONDITION_MIN_COUNT,
+};
+pub use cron::CronExpression;
+
 const MAX_DST_FORWARD_SHIFT_MINUTES: i64 = 180;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum AutomationScheduleType {
     Daily,
     Weekly,
     Monthly,
     Annually,
+    Cron,
+    Once,
+}
+
+/// Built-in automation generators that run in the enclave instead of the user's free-form
+/// prompt. `MorningBrief` follows the user's saved morning brief profile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AutomationTemplate {
+    WeeklyReview,
+    MorningBrief,
+}
+
+impl AutomationTemplate {
+    pub fn required_schedule_type(self) -> AutomationScheduleType {
+        match self {
+            Self::WeeklyReview => AutomationScheduleType::Weekly,
+            Self::MorningBrief => AutomationScheduleType::Daily,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -23,15 +52,50 @@ pub struct AutomationScheduleSpec {
     pub schedule_type: AutomationScheduleType,
     pub time_zone: String,
     pub local_time_minutes: u16,
-    pub anchor_day_of_week: Option<u8>,
+    /// Weekday bitmap for weekly schedules: bit 0 is Monday through bit 6 for Sunday.
+    pub anchor_days_of_week: Option<u8>,
     pub anchor_day_of_month: Option<u8>,
     pub anchor_month: Option<u8>,
+    pub cron_expression: Option<String>,
+    /// The single run instant of a one-shot schedule.
+    pub run_at: Option<DateTime<Utc>>,
 }
 
 impl AutomationScheduleSpec {
     pub fn local_time_hhmm(&self) -> String {
         format_local_time_hhmm(self.local_time_minutes)
     }
+
+    /// ISO weekday numbers (1 = Monday) a weekly schedule runs on, in order.
+    pub fn days_of_week(&self) -> Vec<u8> {
+        self.anchor_days_of_week
+            .map(weekdays_from_mask)
+            .unwrap_or_default()
+    }
+}
+
+/// Anchors a client picked explicitly. Missing anchors are derived from the creation date.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScheduleAnchors {
+    pub days_of_week: Option<u8>,
+    pub day_of_month: Option<u8>,
+}
+
+const ALL_WEEKDAYS_MASK: u8 = 0b111_1111;
+
+/// Folds ISO weekday numbers (1 = Monday through 7 = Sunday) into a weekday bitmap. Returns
+/// `None` for an empty list or a day outside 1-7; duplicates are ignored.
+pub fn weekday_mask(days: &[u8]) -> Option<u8> {
+    if days.is_empty() {
+        return None;
+    }
+    days.iter().try_fold(0_u8, |mask, day| {
+        (1..=7).contains(day).then(|| mask | (1 << (day - 1)))
+    })
+}
+
+pub fn weekdays_from_mask(mask: u8) -> Vec<u8> {
+    (1..=7).filter(|day| mask & (1 << (day - 1)) != 0).collect()
 }
 
 pub fn parse_local_time_hhmm(value: &str) -> Option<u16> {
@@ -62,6 +126,10 @@ pub fn interval_seconds_hint(schedule_type: AutomationScheduleType) -> i32 {
         AutomationScheduleType::Weekly => 604_800,
         AutomationScheduleType::Monthly => 2_629_746,
         AutomationScheduleType::Annually => 31_556_952,
+        // A cron rule can fire as often as once a minute.
+        AutomationScheduleType::Cron => 60,
+        // One-shot rules never repeat; the hint only has to satisfy the column's range check.
+        AutomationScheduleType::Once => 86_400,
     }
 }
 
@@ -71,6 +139,32 @@ pub fn build_schedule_spec(
     local_time_minutes: u16,
     reference_utc: DateTime<Utc>,
 ) -> Result<AutomationScheduleSpec, String> {
+    build_anchored_schedule_spec(
+        schedule_type,
+        time_zone,
+        local_time_minutes,
+        ScheduleAnchors::default(),
+        reference_utc,
+    )
+}
+
+/// Builds a schedule from explicit anchors. Weekly schedules take `days_of_week` and monthly
+/// schedules take `day_of_month`; either falls back to the day of `reference_utc` in the
+/// schedule's time zone when omitted.
+pub fn build_anchored_schedule_spec(
+    schedule_type: AutomationScheduleType,
+    time_zone: &str,
+    local_time_minutes: u16,
+    anchors: ScheduleAnchors,
+    reference_utc: DateTime<Utc>,
+) -> Result<AutomationScheduleSpec, String> {
+    if anchors.days_of_week.is_some() && schedule_type != AutomationScheduleType::Weekly {
+        return Err("only weekly schedules may include days_of_week".to_string());
+    }
+    if anchors.day_of_month.is_some() && schedule_type != AutomationScheduleType::Monthly {
+        return Err("only monthly schedules may include day_of_month".to_string());
+    }
+
     let Some(normalized_time_zone) = normalize_time_zone(time_zone) else {
         return Err("time_zone is not a valid IANA timezone".to_string());
     };
@@ -84,16 +178,25 @@ pub fn build_schedule_spec(
         .map_err(|_| "time_zone is not a valid IANA timezone".to_string())?;
     let local_date = reference_utc.with_timezone(&tz).date_naive();
 
-    let (anchor_day_of_week, anchor_day_of_month, anchor_month) = match schedule_type {
+    let (anchor_days_of_week, anchor_day_of_month, anchor_month) = match schedule_type {
         AutomationScheduleType::Daily => (None, None, None),
         AutomationScheduleType::Weekly => {
-            let day = u8::try_from(local_date.weekday().number_from_monday())
-                .map_err(|_| "failed to derive weekly anchor day".to_string())?;
-            (Some(day), None, None)
+            let days = match anchors.days_of_week {
+                Some(days) => days,
+                None => {
+                    let day = u8::try_from(local_date.weekday().number_from_monday())
+                        .map_err(|_| "failed to derive weekly anchor day".to_string())?;
+                    1 << (day - 1)
+                }
+            };
+            (Some(days), None, None)
         }
         AutomationScheduleType::Monthly => {
-            let day = u8::try_from(local_date.day())
-                .map_err(|_| "failed to derive monthly anchor day".to_string())?;
+            let day = match anchors.day_of_month {
+                Some(day) => day,
+                None => u8::try_from(local_date.day())
+                    .map_err(|_| "failed to derive monthly anchor day".to_string())?,
+            };
             (None, Some(day), None)
         }
         AutomationScheduleType::Annually => {
@@ -103,15 +206,76 @@ pub fn build_schedule_spec(
                 .map_err(|_| "failed to derive annual anchor month".to_string())?;
             (None, Some(day), Some(month))
         }
+        AutomationScheduleType::Cron => {
+            return Err("cron schedules are built with build_cron_schedule_spec".to_string());
+        }
+        AutomationScheduleType::Once => {
+            return Err("one-shot schedules are built with build_once_schedule_spec".to_string());
+        }
     };
 
     let spec = AutomationScheduleSpec {
         schedule_type,
         time_zone: normalized_time_zone,
         local_time_minutes,
-        anchor_day_of_week,
+        anchor_days_of_week,
         anchor_day_of_month,
         anchor_month,
+        cron_expression: None,
+        run_at: None,
+    };
+    validate_schedule_spec(&spec)?;
+    Ok(spec)
+}
+
+/// Builds a cron schedule. The expression is stored with its fields separated by single spaces.
+pub fn build_cron_schedule_spec(
+    time_zone: &str,
+    cron_expression: &str,
+) -> Result<AutomationScheduleSpec, String> {
+    let Some(normalized_time_zone) = normalize_time_zone(time_zone) else {
+        return Err("time_zone is not a valid IANA timezone".to_string());
+    };
+    CronExpression::parse(cron_expression)?;
+
+    let spec = AutomationScheduleSpec {
+        schedule_type: AutomationScheduleType::Cron,
+        time_zone: normalized_time_zone,
+        local_time_minutes: 0,
+        anchor_days_of_week: None,
+        anchor_day_of_month: None,
+        anchor_month: None,
+        cron_expression: Some(
+            cron_expression
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" "),
+        ),
+        run_at: None,
+    };
+    validate_schedule_spec(&spec)?;
+    Ok(spec)
+}
+
+/// Builds a one-shot schedule that runs exactly once at `run_at`. The time zone is kept so the
+/// run is reported in the user's local time.
+pub fn build_once_schedule_spec(
+    time_zone: &str,
+    run_at: DateTime<Utc>,
+) -> Result<AutomationScheduleSpec, String> {
+    let Some(normalized_time_zone) = normalize_time_zone(time_zone) else {
+        return Err("time_zone is not a valid IANA timezone".to_string());
+    };
+
+    let spec = AutomationScheduleSpec {
+        schedule_type: AutomationScheduleType::Once,
+        time_zone: normalized_time_zone,
+        local_time_minutes: 0,
+        anchor_days_of_week: None,
+        anchor_day_of_month: None,
+        anchor_month: None,
+        cron_expression: None,
+        run_at: Some(run_at),
     };
     validate_schedule_spec(&spec)?;
     Ok(spec)
@@ -126,9 +290,17 @@ pub fn validate_schedule_spec(spec: &AutomationScheduleSpec) -> Result<(), Strin
         return Err("local_time must be between 00:00 and 23:59".to_string());
     }
 
+    if spec.schedule_type != AutomationScheduleType::Cron && spec.cron_expression.is_some() {
+        return Err("only cron schedules may include cron_expression".to_string());
+    }
+
+    if spec.schedule_type != AutomationScheduleType::Once && spec.run_at.is_some() {
+        return Err("only one-shot schedules may include run_at".to_string());
+    }
+
     match spec.schedule_type {
         AutomationScheduleType::Daily => {
-            if spec.anchor_day_of_week.is_some()
+            if spec.anchor_days_of_week.is_some()
                 || spec.anchor_day_of_month.is_some()
                 || spec.anchor_month.is_some()
             {
@@ -136,11 +308,11 @@ pub fn validate_schedule_spec(spec: &AutomationScheduleSpec) -> Result<(), Strin
             }
         }
         AutomationScheduleType::Weekly => {
-            let Some(day_of_week) = spec.anchor_day_of_week else {
-                return Err("weekly schedules require anchor_day_of_week".to_string());
+            let Some(days_of_week) = spec.anchor_days_of_week else {
+                return Err("weekly schedules require anchor_days_of_week".to_string());
             };
-            if !(1..=7).contains(&day_of_week) {
-                return Err("anchor_day_of_week must be between 1 and 7".to_string());
+            if days_of_week == 0 || days_of_week & !ALL_WEEKDAYS_MASK != 0 {
+                return Err("anchor_days_of_week must name at least one weekday".to_string());
             }
             if spec.anchor_day_of_month.is_some() || spec.anchor_month.is_some() {
                 return Err(
@@ -155,7 +327,7 @@ pub fn validate_schedule_spec(spec: &AutomationScheduleSpec) -> Result<(), Strin
             if !(1..=31).contains(&day_of_month) {
                 return Err("anchor_day_of_month must be between 1 and 31".to_string());
             }
-            if spec.anchor_day_of_week.is_some() || spec.anchor_month.is_some() {
+            if spec.anchor_days_of_week.is_some() || spec.anchor_month.is_some() {
                 return Err("monthly schedules must not include weekly/annual anchors".to_string());
             }
         }
@@ -172,10 +344,35 @@ pub fn validate_schedule_spec(spec: &AutomationScheduleSpec) -> Result<(), Strin
             if !(1..=12).contains(&month) {
                 return Err("anchor_month must be between 1 and 12".to_string());
             }
-            if spec.anchor_day_of_week.is_some() {
+            if spec.anchor_days_of_week.is_some() {
                 return Err("annual schedules must not include weekly anchors".to_string());
             }
         }
+        AutomationScheduleType::Cron => {
+            let Some(cron_expression) = spec.cron_expression.as_deref() else {
+                return Err("cron schedules require cron_expression".to_string());
+            };
+            CronExpression::parse(cron_expression)?;
+            if spec.local_time_minutes != 0
+                || spec.anchor_days_of_week.is_some()
+                || spec.anchor_day_of_month.is_some()
+                || spec.anchor_month.is_some()
+            {
+                return Err("cron schedules must not include local_time or anchors".to_string());
+            }
+        }
+        AutomationScheduleType::Once => {
+            if spec.run_at.is_none() {
+                return Err("one-shot schedules require run_at".to_string());
+            }
+            if spec.local_time_minutes != 0
+                || spec.anchor_days_of_week.is_some()
+                || spec.anchor_day_of_month.is_some()
+                || spec.anchor_month.is_some()
+            {
+                return Err("one-shot schedules must not include local_time or anchors".to_string());
+            }
+        }
     }
 
     Ok(())
@@ -186,13 +383,26 @@ pub fn next_run_after(
     spec: &AutomationScheduleSpec,
 ) -> Option<DateTime<Utc>> {
     validate_schedule_spec(spec).ok()?;
+    // A one-shot schedule has no recurrence: its only run is `run_at`, and nothing follows it.
+    if let Some(run_at) = spec.run_at {
+        return (run_at > reference_utc).then_some(run_at);
+    }
     let tz = spec.time_zone.parse::<Tz>().ok()?;
     let local_time = local_time_from_minutes(spec.local_time_minutes)?;
+    let cron = spec
+        .cron_expression
+        .as_deref()
+        .map(CronExpression::parse)
+        .transpose()
+        .ok()?;
 
     let mut cursor_utc = reference_utc;
     for _ in 0..4 {
         let local_reference = cursor_utc.with_timezone(&tz).naive_local();
-        let candidate_local = next_local_candidate(local_reference, local_time, spec)?;
+        let candidate_local = match &cron {
+            Some(cron) => cron.next_after(local_reference)?,
+            None => next_local_candidate(local_reference, local_time, spec)?,
+        };
         let candidate_utc = resolve_local_datetime_to_utc(&tz, candidate_local)?;
         if candidate_utc > reference_utc {
             return Some(candidate_utc);
@@ -203,6 +413,29 @@ pub fn next_run_after(
     None
 }
 
+/// The next `count` runs after `reference_utc`, each also given as wall-clock time with the
+/// UTC offset of the schedule's time zone. One-shot schedules yield at most one run.
+pub fn upcoming_runs(
+    reference_utc: DateTime<Utc>,
+    spec: &AutomationScheduleSpec,
+    count: usize,
+) -> Vec<(DateTime<Utc>, DateTime<FixedOffset>)> {
+    let Ok(tz) = spec.time_zone.parse::<Tz>() else {
+        return Vec::new();
+    };
+
+    let mut runs = Vec::with_capacity(count);
+    let mut cursor = reference_utc;
+    while runs.len() < count {
+        let Some(run_at) = next_run_after(cursor, spec) else {
+            break;
+        };
+        runs.push((run_at, run_at.with_timezone(&tz).fixed_offset()));
+        cursor = run_at;
+    }
+    runs
+}
+
 fn next_local_candidate(
     local_reference: NaiveDateTime,
     local_time: NaiveTime,
@@ -219,22 +452,12 @@ fn next_local_candidate(
             Some(candidate)
         }
         AutomationScheduleType::Weekly => {
-            let target_day = u32::from(spec.anchor_day_of_week?);
-            let current_day = local_reference.date().weekday().number_from_monday();
-            let mut days_until = i64::from(target_day) - i64::from(current_day);
-            if days_until < 0 {
-                days_until += 7;
-            }
-
-            let mut candidate_date = local_reference
-                .date()
-                .checked_add_days(Days::new(u64::try_from(days_until).ok()?))?;
-            let mut candidate = candidate_date.and_time(local_time);
-            if candidate <= local_reference {
-                candidate_date = candidate_date.checked_add_days(Days::new(7))?;
-                candidate = candidate_date.and_time(local_time);
-            }
-            Some(candidate)
+            let days_of_week = spec.anchor_days_of_week?;
+            (0..=7)
+                .filter_map(|offset| local_reference.date().checked_add_days(Days::new(offset)))
+                .filter(|date| days_of_week & (1 << (date.weekday().number_from_monday() - 1)) != 0)
+                .map(|date| date.and_time(local_time))
+                .find(|candidate| *candidate > local_reference)
         }
         AutomationScheduleType::Monthly => {
             let anchor_day = u32::from(spec.anchor_day_of_month?);
@@ -266,6 +489,7 @@ fn next_local_candidate(
             }
             Some(candidate)
         }
+        AutomationScheduleType::Cron | AutomationScheduleType::Once => None,
     }
 }
 
@@ -330,8 +554,9 @@ mod tests {
     use chrono::{TimeZone, Utc};
 
     use super::{
-        AutomationScheduleSpec, AutomationScheduleType, build_schedule_spec, next_run_after,
-        parse_local_time_hhmm,
+        AutomationScheduleSpec, AutomationScheduleType, ScheduleAnchors,
+        build_anchored_schedule_spec, build_cron_schedule_spec, build_once_schedule_spec,
+        build_schedule_spec, next_run_after, parse_local_time_hhmm, upcoming_runs, weekday_mask,
     };
 
     #[test]
@@ -361,9 +586,11 @@ mod tests {
             schedule_type: AutomationScheduleType::Monthly,
             time_zone: "UTC".to_string(),
             local_time_minutes: 10 * 60,
-            anchor_day_of_week: None,
+            anchor_days_of_week: None,
             anchor_day_of_month: Some(31),
             anchor_month: None,
+            cron_expression: None,
+            run_at: None,
         };
 
         let jan_31 = Utc
@@ -376,4 +603,161 @@ mod tests {
         let mar_run = next_run_after(feb_run, &spec).expect("next run should exist");
         assert_eq!(mar_run.to_rfc3339(), "2026-03-31T10:00:00+00:00");
     }
+
+    #[test]
+    fn cron_schedule_runs_in_the_rule_time_zone() {
+        // 2026-03-06 is a Friday; New York is on UTC-5 until March 8.
+        let reference = Utc
+            .with_ymd_and_hms(2026, 3, 6, 13, 0, 0)
+            .single()
+            .expect("valid datetime");
+        let spec =
+            build_cron_schedule_spec("America/New_York", "30  7 * * 1-5").expect("valid schedule");
+        assert_eq!(spec.cron_expression.as_deref(), Some("30 7 * * 1-5"));
+
+        let monday = next_run_after(reference, &spec).expect("next run should exist");
+        assert_eq!(monday.to_rfc3339(), "2026-03-09T11:30:00+00:00");
+
+        assert!(build_cron_schedule_spec("UTC", "0 9 * *").is_err());
+        assert!(build_cron_schedule_spec("Mars/Base", "0 9 * * *").is_err());
+    }
+
+    #[test]
+    fn once_schedule_runs_only_at_its_run_at() {
+        let run_at = Utc
+            .with_ymd_and_hms(2026, 3, 10, 13, 0, 0)
+            .single()
+            .expect("valid datetime");
+        let spec = build_once_schedule_spec("America/New_York", run_at).expect("valid schedule");
+
+        let before = run_at - chrono::Duration::days(2);
+        assert_eq!(next_run_after(before, &spec), Some(run_at));
+        assert_eq!(next_run_after(run_at, &spec), None);
+        assert!(build_schedule_spec(AutomationScheduleType::Once, "UTC", 9 * 60, before).is_err());
+        assert!(build_once_schedule_spec("Mars/Base", run_at).is_err());
+    }
+
+    #[test]
+    fn weekly_schedule_runs_on_each_selected_day_across_dst() {
+        // 2026-03-06 is a Friday; New York springs forward at 02:00 on Sunday March 8.
+        let reference = Utc
+            .with_ymd_and_hms(2026, 3, 6, 12, 0, 0)
+            .single()
+            .expect("valid datetime");
+        let spec = build_anchored_schedule_spec(
+            AutomationScheduleType::Weekly,
+            "America/New_York",
+            2 * 60 + 30,
+            ScheduleAnchors {
+                days_of_week: weekday_mask(&[1, 7]),
+                day_of_month: None,
+            },
+            reference,
+        )
+        .expect("valid schedule");
+        assert_eq!(spec.days_of_week(), vec![1, 7]);
+
+        // 02:30 does not exist on the Sunday, so that run moves to 03:00 EDT.
+        let sunday = next_run_after(reference, &spec).expect("next run should exist");
+        assert_eq!(sunday.to_rfc3339(), "2026-03-08T07:00:00+00:00");
+        let monday = next_run_after(sunday, &spec).expect("next run should exist");
+        assert_eq!(monday.to_rfc3339(), "2026-03-09T06:30:00+00:00");
+        let next_sunday = next_run_after(monday, &spec).expect("next run should exist");
+        assert_eq!(next_sunday.to_rfc3339(), "2026-03-15T06:30:00+00:00");
+    }
+
+    #[test]
+    fn upcoming_runs_report_local_offsets_across_dst() {
+        // New York springs forward on Sunday March 8, 2026.
+        let reference = Utc
+            .with_ymd_and_hms(2026, 3, 6, 20, 0, 0)
+            .single()
+            .expect("valid datetime");
+        let spec = build_schedule_spec(
+            AutomationScheduleType::Daily,
+            "America/New_York",
+            7 * 60,
+            reference,
+        )
+        .expect("valid schedule");
+
+        let runs = upcoming_runs(reference, &spec, 3)
+            .into_iter()
+            .map(|(utc, local)| (utc.to_rfc3339(), local.to_rfc3339()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            runs,
+            vec![
+                (
+                    "2026-03-07T12:00:00+00:00".to_string(),
+                    "2026-03-07T07:00:00-05:00".to_string()
+                ),
+                (
+                    "2026-03-08T11:00:00+00:00".to_string(),
+                    "2026-03-08T07:00:00-04:00".to_string()
+                ),
+                (
+                    "2026-03-09T11:00:00+00:00".to_string(),
+                    "2026-03-09T07:00:00-04:00".to_string()
+                ),
+            ]
+        );
+
+        let once = build_once_schedule_spec("UTC", reference + chrono::Duration::hours(1))
+            .expect("valid schedule");
+        assert_eq!(upcoming_runs(reference, &once, 5).len(), 1);
+    }
+
+    #[test]
+    fn explicit_anchors_must_match_the_schedule_type() {
+        let reference = Utc
+            .with_ymd_and_hms(2026, 1, 10, 12, 0, 0)
+            .single()
+            .expect("valid datetime");
+        let monthly = build_anchored_schedule_spec(
+            AutomationScheduleType::Monthly,
+            "UTC",
+            8 * 60,
+            ScheduleAnchors {
+                days_of_week: None,
+                day_of_month: Some(31),
+            },
+            reference,
+        )
+        .expect("valid schedule");
+        let next = next_run_after(reference, &monthly).expect("next run should exist");
+        assert_eq!(next.to_rfc3339(), "2026-01-31T08:00:00+00:00");
+
+        for (schedule_type, anchors) in [
+            (
+                AutomationScheduleType::Daily,
+                ScheduleAnchors {
+                    days_of_week: weekday_mask(&[1]),
+                    day_of_month: None,
+                },
+            ),
+            (
+                AutomationScheduleType::Weekly,
+                ScheduleAnchors {
+                    days_of_week: None,
+                    day_of_month: Some(1),
+                },
+            ),
+            (
+                AutomationScheduleType::Monthly,
+                ScheduleAnchors {
+                    days_of_week: None,
+                    day_of_month: Some(32),
+                },
+            ),
+        ] {
+            assert!(
+                build_anchored_schedule_spec(schedule_type, "UTC", 0, anchors, reference).is_err()
+            );
+        }
+        assert_eq!(weekday_mask(&[]), None);
+        assert_eq!(weekday_mask(&[0]), None);
+        assert_eq!(weekday_mask(&[8]), None);
+        assert_eq!(weekday_mask(&[1, 1, 3]), Some(0b101));
+    }
 }
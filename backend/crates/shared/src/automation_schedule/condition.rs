@@ -0,0 +1,64 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Largest `min_count` a condition accepts. The enclave reads at most this many calendar
+/// events or urgent email candidates when it evaluates a condition.
+pub const MAX_AUTOMATION_CONDITION_MIN_COUNT: u16 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AutomationConditionKind {
+    CalendarEventsToday,
+    UrgentEmails,
+}
+
+/// Gate the enclave evaluates before a scheduled run builds its notification, so quiet days
+/// finish without an empty digest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AutomationCondition {
+    pub kind: AutomationConditionKind,
+    /// The run notifies only when at least this many matching items are found.
+    pub min_count: u16,
+}
+
+impl AutomationCondition {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=MAX_AUTOMATION_CONDITION_MIN_COUNT).contains(&self.min_count) {
+            return Err(format!(
+                "condition min_count must be between 1 and {MAX_AUTOMATION_CONDITION_MIN_COUNT}"
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn is_met(&self, observed_count: usize) -> bool {
+        observed_count >= usize::from(self.min_count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AutomationCondition, AutomationConditionKind};
+
+    #[test]
+    fn automation_condition_bounds_min_count_and_compares_counts() {
+        let condition = AutomationCondition {
+            kind: AutomationConditionKind::CalendarEventsToday,
+            min_count: 2,
+        };
+        assert!(condition.validate().is_ok());
+        assert!(!condition.is_met(1));
+        assert!(condition.is_met(2));
+        for min_count in [0, 11] {
+            assert!(
+                AutomationCondition {
+                    min_count,
+                    ..condition
+                }
+                .validate()
+                .is_err()
+            );
+        }
+    }
+}
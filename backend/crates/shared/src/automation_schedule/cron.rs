@@ -0,0 +1,278 @@
+use chrono::{Datelike, Days, Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+
+/// Longest gap between two matches of a valid expression: `0 0 29 2 *` skips the 2100 leap day.
+const MAX_SEARCH_DAYS: u64 = 366 * 9;
+const MAX_EXPRESSION_CHARS: usize = 120;
+
+const MONTH_NAMES: [&str; 12] = [
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+const WEEKDAY_NAMES: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+/// A standard 5-field cron expression: minute, hour, day of month, month, day of week.
+///
+/// Fields accept `*`, values, `a-b` ranges, `/n` steps and comma lists. Months and weekdays
+/// also accept three-letter names, and both `0` and `7` mean Sunday. As in Vixie cron, when
+/// day of month and day of week are both restricted a day matching either one runs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronExpression {
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    day_of_month_restricted: bool,
+    day_of_week_restricted: bool,
+}
+
+impl CronExpression {
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        if expression.len() > MAX_EXPRESSION_CHARS {
+            return Err(format!(
+                "cron_expression must be at most {MAX_EXPRESSION_CHARS} characters"
+            ));
+        }
+
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minute, hour, day_of_month, month, day_of_week] = fields.as_slice() else {
+            return Err("cron_expression must have exactly 5 fields".to_string());
+        };
+
+        let mut days_of_week = parse_field(day_of_week, 0, 7, &WEEKDAY_NAMES, "day of week")?;
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week = (days_of_week | 1) & !(1 << 7);
+        }
+
+        let parsed = Self {
+            minutes: parse_field(minute, 0, 59, &[], "minute")?,
+            hours: parse_field(hour, 0, 23, &[], "hour")?,
+            days_of_month: parse_field(day_of_month, 1, 31, &[], "day of month")?,
+            months: parse_field(month, 1, 12, &MONTH_NAMES, "month")?,
+            days_of_week,
+            day_of_month_restricted: !day_of_month.starts_with('*'),
+            day_of_week_restricted: !day_of_week.starts_with('*'),
+        };
+        if !parsed.any_month_has_matching_day() {
+            return Err("cron_expression never matches a calendar date".to_string());
+        }
+        Ok(parsed)
+    }
+
+    /// First matching minute strictly after `reference`, in the same local wall clock.
+    pub fn next_after(&self, reference: NaiveDateTime) -> Option<NaiveDateTime> {
+        let start = reference
+            .with_second(0)?
+            .with_nanosecond(0)?
+            .checked_add_signed(Duration::minutes(1))?;
+        let mut date = start.date();
+        let mut earliest_time = Some(start.time());
+        let last_date = date.checked_add_days(Days::new(MAX_SEARCH_DAYS))?;
+
+        while date <= last_date {
+            if self.matches_date(date)
+                && let Some(time) = self.first_time_from(earliest_time.unwrap_or(NaiveTime::MIN))
+            {
+                return Some(date.and_time(time));
+            }
+            date = date.checked_add_days(Days::new(1))?;
+            earliest_time = None;
+        }
+
+        None
+    }
+
+    fn matches_date(&self, date: NaiveDate) -> bool {
+        if !has_bit(self.months, date.month()) {
+            return false;
+        }
+
+        let day_of_month = has_bit(self.days_of_month, date.day());
+        let day_of_week = has_bit(self.days_of_week, date.weekday().num_days_from_sunday());
+        if self.day_of_month_restricted && self.day_of_week_restricted {
+            day_of_month || day_of_week
+        } else {
+            day_of_month && day_of_week
+        }
+    }
+
+    fn first_time_from(&self, earliest: NaiveTime) -> Option<NaiveTime> {
+        (earliest.hour()..24)
+            .filter(|hour| has_bit(self.hours, *hour))
+            .find_map(|hour| {
+                let first_minute = if hour == earliest.hour() {
+                    earliest.minute()
+                } else {
+                    0
+                };
+                (first_minute..60)
+                    .find(|minute| has_bit(self.minutes, *minute))
+                    .and_then(|minute| NaiveTime::from_hms_opt(hour, minute, 0))
+            })
+    }
+
+    /// Rejects day-of-month-only expressions such as `0 9 31 2 *` that no month can satisfy.
+    fn any_month_has_matching_day(&self) -> bool {
+        if !self.day_of_month_restricted || self.day_of_week_restricted {
+            return true;
+        }
+
+        const MAX_DAYS_IN_MONTH: [u32; 12] = [31, 29, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+        (1..=12).any(|month| {
+            has_bit(self.months, month)
+                && (1..=MAX_DAYS_IN_MONTH[month as usize - 1])
+                    .any(|day| has_bit(self.days_of_month, day))
+        })
+    }
+}
+
+fn has_bit(mask: u64, value: u32) -> bool {
+    mask & (1 << value) != 0
+}
+
+fn parse_field(
+    field: &str,
+    min: u32,
+    max: u32,
+    names: &[&str],
+    label: &str,
+) -> Result<u64, String> {
+    let mut mask = 0_u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step = step
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|step| (1..=max).contains(step))
+                    .ok_or_else(|| format!("cron {label} step is invalid"))?;
+                (range, step)
+            }
+            None => (part, 1),
+        };
+
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (
+                parse_value(start, min, max, names, label)?,
+                parse_value(end, min, max, names, label)?,
+            )
+        } else {
+            let start = parse_value(range, min, max, names, label)?;
+            // `5/15` means every 15 starting at 5, as in Vixie cron.
+            (start, if part.contains('/') { max } else { start })
+        };
+        if start > end {
+            return Err(format!("cron {label} range must not run backwards"));
+        }
+
+        for value in (start..=end).step_by(step as usize) {
+            mask |= 1 << value;
+        }
+    }
+
+    Ok(mask)
+}
+
+fn parse_value(
+    value: &str,
+    min: u32,
+    max: u32,
+    names: &[&str],
+    label: &str,
+) -> Result<u32, String> {
+    let parsed = match names
+        .iter()
+        .position(|name| name.eq_ignore_ascii_case(value))
+    {
+        Some(index) => u32::try_from(index).ok().map(|index| index + min),
+        None => value.parse::<u32>().ok(),
+    };
+
+    parsed
+        .filter(|parsed| (min..=max).contains(parsed))
+        .ok_or_else(|| format!("cron {label} must be between {min} and {max}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, NaiveDateTime};
+
+    use super::CronExpression;
+
+    fn at(value: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M").expect("valid datetime")
+    }
+
+    fn next(expression: &str, reference: &str) -> String {
+        CronExpression::parse(expression)
+            .expect("valid expression")
+            .next_after(at(reference))
+            .expect("next run should exist")
+            .format("%Y-%m-%d %H:%M")
+            .to_string()
+    }
+
+    #[test]
+    fn weekday_and_step_expressions_find_the_next_minute() {
+        // 2026-02-20 is a Friday.
+        assert_eq!(next("30 7 * * 1-5", "2026-02-20 07:30"), "2026-02-23 07:30");
+        assert_eq!(
+            next("30 7 * * MON-FRI", "2026-02-20 07:29"),
+            "2026-02-20 07:30"
+        );
+        assert_eq!(next("0 */2 * * *", "2026-02-20 07:30"), "2026-02-20 08:00");
+        assert_eq!(next("0 */2 * * *", "2026-02-20 23:00"), "2026-02-21 00:00");
+        assert_eq!(
+            next("15,45 9 * * 7", "2026-02-20 10:00"),
+            "2026-02-22 09:15"
+        );
+        assert_eq!(next("0 9 1 jan *", "2026-02-20 10:00"), "2027-01-01 09:00");
+    }
+
+    #[test]
+    fn restricted_day_of_month_and_weekday_match_either() {
+        // The 1st of March 2026 is a Sunday; the first Monday after the 20th is the 23rd.
+        assert_eq!(next("0 8 1 * MON", "2026-02-20 10:00"), "2026-02-23 08:00");
+        assert_eq!(next("0 8 1 * MON", "2026-02-27 10:00"), "2026-03-01 08:00");
+    }
+
+    #[test]
+    fn leap_day_expressions_wait_for_the_next_leap_year() {
+        assert_eq!(next("0 0 29 2 *", "2026-03-01 00:00"), "2028-02-29 00:00");
+        let reference = NaiveDate::from_ymd_opt(2096, 3, 1)
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+            .expect("valid datetime");
+        assert_eq!(
+            CronExpression::parse("0 0 29 2 *")
+                .expect("valid expression")
+                .next_after(reference)
+                .map(|next| next.format("%Y-%m-%d").to_string()),
+            Some("2104-02-29".to_string())
+        );
+    }
+
+    #[test]
+    fn invalid_expressions_are_rejected() {
+        for expression in [
+            "",
+            "* * * *",
+            "* * * * * *",
+            "60 * * * *",
+            "* 24 * * *",
+            "* * 0 * *",
+            "* * * 13 *",
+            "* * * * 8",
+            "*/0 * * * *",
+            "10-5 * * * *",
+            "0 9 31 2 *",
+            "0 9 * * funday",
+            "a b c d e",
+        ] {
+            assert!(
+                CronExpression::parse(expression).is_err(),
+                "{expression:?} should be rejected"
+            );
+        }
+    }
+}
@@ -0,0 +1,320 @@
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+const SIGV4_ALGORITHM: &str = "AWS4-HMAC-SHA256";
+const AWS_JSON_CONTENT_TYPE: &str = "application/x-amz-json-1.1";
+
+/// Static AWS credentials. Inside a Nitro enclave these are the parent instance's role
+/// credentials, handed in at boot.
+#[derive(Clone)]
+pub struct AwsCredentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+}
+
+impl fmt::Debug for AwsCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AwsCredentials")
+            .field("access_key_id", &self.access_key_id)
+            .field("secret_access_key", &"<redacted>")
+            .field(
+                "session_token",
+                &self.session_token.as_ref().map(|_| "<redacted>"),
+            )
+            .finish()
+    }
+}
+
+#[derive(Debug, Error)]
+pub(crate) enum AwsCallError {
+    #[error("{0}")]
+    Transport(String),
+    #[error("rejected with status={status}: {error_type}")]
+    Rejected { status: u16, error_type: String },
+    #[error("{0}")]
+    InvalidResponse(String),
+}
+
+/// Client for the AWS JSON 1.1 protocol shared by KMS and Secrets Manager: a SigV4-signed
+/// POST to `/` naming the operation in `x-amz-target`.
+#[derive(Debug, Clone)]
+pub(crate) struct AwsJsonClient {
+    service: &'static str,
+    target_prefix: &'static str,
+    region: String,
+    endpoint: String,
+    credentials: AwsCredentials,
+    http_client: reqwest::Client,
+}
+
+impl AwsJsonClient {
+    /// `endpoint` defaults to the regional endpoint of `service`.
+    pub(crate) fn new(
+        service: &'static str,
+        target_prefix: &'static str,
+        region: String,
+        endpoint: Option<String>,
+        credentials: AwsCredentials,
+        http_client: reqwest::Client,
+    ) -> Self {
+        let endpoint =
+            endpoint.unwrap_or_else(|| format!("https://{service}.{region}.amazonaws.com"));
+        Self {
+            service,
+            target_prefix,
+            region,
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            credentials,
+            http_client,
+        }
+    }
+
+    pub(crate) async fn call<Req: Serialize, Resp: DeserializeOwned>(
+        &self,
+        operation: &str,
+        request: &Req,
+    ) -> Result<Resp, AwsCallError> {
+        let body =
+            serde_json::to_vec(request).map_err(|err| AwsCallError::Transport(err.to_string()))?;
+        let host = endpoint_host(&self.endpoint).ok_or_else(|| {
+            AwsCallError::Transport(format!(
+                "{} endpoint is not a valid url: {}",
+                self.service, self.endpoint
+            ))
+        })?;
+        let target = format!("{}.{operation}", self.target_prefix);
+        let mut signed_headers = vec![
+            ("content-type", AWS_JSON_CONTENT_TYPE),
+            ("x-amz-target", target.as_str()),
+        ];
+        if let Some(session_token) = self.credentials.session_token.as_deref() {
+            signed_headers.push(("x-amz-security-token", session_token));
+        }
+        let signature = sign(
+            &self.credentials,
+            &SigV4Request {
+                region: &self.region,
+                service: self.service,
+                method: "POST",
+                host: &host,
+                canonical_uri: "/",
+                headers: &signed_headers,
+                body: &body,
+            },
+            Utc::now(),
+        );
+
+        let mut request = self
+            .http_client
+            .post(format!("{}/", self.endpoint))
+            .header("x-amz-date", signature.amz_date)
+            .header("authorization", signature.authorization);
+        for (name, value) in signed_headers {
+            request = request.header(name, value);
+        }
+        let response = request
+            .body(body)
+            .send()
+            .await
+            .map_err(|err| AwsCallError::Transport(err.to_string()))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_type = response
+                .json::<AwsErrorResponse>()
+                .await
+                .ok()
+                .and_then(|error| error.error_type)
+                .map(|error_type| {
+                    // Types may arrive namespaced, e.g. `com.amazonaws.kms#AccessDeniedException`.
+                    error_type
+                        .rsplit('#')
+                        .next()
+                        .unwrap_or_default()
+                        .to_string()
+                })
+                .unwrap_or_else(|| "unknown".to_string());
+            return Err(AwsCallError::Rejected {
+                status: status.as_u16(),
+                error_type,
+            });
+        }
+
+        response
+            .json::<Resp>()
+            .await
+            .map_err(|err| AwsCallError::InvalidResponse(err.to_string()))
+    }
+}
+
+#[derive(Deserialize)]
+struct AwsErrorResponse {
+    #[serde(rename = "__type")]
+    error_type: Option<String>,
+}
+
+/// The `host` header value for `endpoint`, including a non-default port.
+pub(crate) fn endpoint_host(endpoint: &str) -> Option<String> {
+    reqwest::Url::parse(endpoint).ok().and_then(|url| {
+        url.host_str().map(|host| match url.port() {
+            Some(port) => format!("{host}:{port}"),
+            None => host.to_string(),
+        })
+    })
+}
+
+/// Percent-encodes everything outside the RFC 3986 unreserved set, as SigV4 requires.
+pub(crate) fn uri_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+/// Headers that carry an AWS Signature Version 4 signature.
+pub(crate) struct SignedHeaders {
+    pub(crate) amz_date: String,
+    pub(crate) authorization: String,
+}
+
+/// A request to sign, without a query string. `canonical_uri` is the path as it appears in the
+/// canonical request, already encoded the way the target service expects. `headers` are the
+/// headers to sign besides `host` and `x-amz-date`, with lowercase names.
+pub(crate) struct SigV4Request<'a> {
+    pub(crate) region: &'a str,
+    pub(crate) service: &'a str,
+    pub(crate) method: &'a str,
+    pub(crate) host: &'a str,
+    pub(crate) canonical_uri: &'a str,
+    pub(crate) headers: &'a [(&'a str, &'a str)],
+    pub(crate) body: &'a [u8],
+}
+
+pub(crate) fn sign(
+    credentials: &AwsCredentials,
+    request: &SigV4Request<'_>,
+    now: DateTime<Utc>,
+) -> SignedHeaders {
+    let SigV4Request {
+        region,
+        service,
+        method,
+        host,
+        canonical_uri,
+        headers,
+        body,
+    } = *request;
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = now.format("%Y%m%d").to_string();
+
+    let mut signed: Vec<(&str, &str)> = headers.to_vec();
+    signed.push(("host", host));
+    signed.push(("x-amz-date", amz_date.as_str()));
+    signed.sort_by(|left, right| left.0.cmp(right.0));
+
+    let canonical_headers = signed
+        .iter()
+        .map(|(name, value)| format!("{name}:{}\n", value.trim()))
+        .collect::<String>();
+    let signed_header_names = signed
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(";");
+    let canonical_request = format!(
+        "{method}\n{canonical_uri}\n\n{canonical_headers}\n{signed_header_names}\n{}",
+        hex_sha256(body)
+    );
+
+    let scope = format!("{date}/{region}/{service}/aws4_request");
+    let string_to_sign = format!(
+        "{SIGV4_ALGORITHM}\n{amz_date}\n{scope}\n{}",
+        hex_sha256(canonical_request.as_bytes())
+    );
+
+    let date_key = hmac_sha256(
+        format!("AWS4{}", credentials.secret_access_key).as_bytes(),
+        date.as_bytes(),
+    );
+    let region_key = hmac_sha256(&date_key, region.as_bytes());
+    let service_key = hmac_sha256(&region_key, service.as_bytes());
+    let signing_key = hmac_sha256(&service_key, b"aws4_request");
+    let signature = hex(&hmac_sha256(&signing_key, string_to_sign.as_bytes()));
+
+    SignedHeaders {
+        authorization: format!(
+            "{SIGV4_ALGORITHM} Credential={}/{scope}, SignedHeaders={signed_header_names}, Signature={signature}",
+            credentials.access_key_id
+        ),
+        amz_date,
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("hmac accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn hex_sha256(data: &[u8]) -> String {
+    hex(&Sha256::digest(data))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    /// `post-vanilla` from the AWS Signature Version 4 test suite.
+    #[test]
+    fn signature_matches_the_aws_test_suite() {
+        let credentials = AwsCredentials {
+            access_key_id: "AKIDEXAMPLE".to_string(),
+            secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_string(),
+            session_token: None,
+        };
+        let now = Utc
+            .with_ymd_and_hms(2015, 8, 30, 12, 36, 0)
+            .single()
+            .expect("valid timestamp");
+
+        let signed = sign(
+            &credentials,
+            &SigV4Request {
+                region: "us-east-1",
+                service: "service",
+                method: "POST",
+                host: "example.amazonaws.com",
+                canonical_uri: "/",
+                headers: &[],
+                body: b"",
+            },
+            now,
+        );
+
+        assert_eq!(signed.amz_date, "20150830T123600Z");
+        assert_eq!(
+            signed.authorization,
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, \
+             SignedHeaders=host;x-amz-date, \
+             Signature=5da7c1a2acd57cee7505fc6676e4e544621c30862966e37dddb68e92efbe5d6b"
+        );
+    }
+}
@@ -0,0 +1,290 @@
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::llm::contracts::MorningBriefOutput;
+
+/// Score at which repeated negative feedback disables a section.
+const DISABLE_SECTION_SCORE: i32 = -3;
+const MAX_SECTION_SCORE_MAGNITUDE: i32 = 5;
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum MorningBriefSection {
+    Priorities,
+    Schedule,
+    Alerts,
+}
+
+impl MorningBriefSection {
+    pub const ALL: [Self; 3] = [Self::Priorities, Self::Schedule, Self::Alerts];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Priorities => "priorities",
+            Self::Schedule => "schedule",
+            Self::Alerts => "alerts",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|section| section.as_str() == value)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MorningBriefVerbosity {
+    Concise,
+    #[default]
+    Standard,
+    Detailed,
+}
+
+impl MorningBriefVerbosity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Concise => "concise",
+            Self::Standard => "standard",
+            Self::Detailed => "detailed",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "concise" => Some(Self::Concise),
+            "standard" => Some(Self::Standard),
+            "detailed" => Some(Self::Detailed),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BriefFeedbackRating {
+    Up,
+    Down,
+}
+
+/// Which morning brief sections a user sees and in what order. Sections not listed are
+/// omitted from the brief.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct MorningBriefProfile {
+    pub sections: Vec<MorningBriefSection>,
+    pub verbosity: MorningBriefVerbosity,
+}
+
+impl Default for MorningBriefProfile {
+    fn default() -> Self {
+        Self {
+            sections: MorningBriefSection::ALL.to_vec(),
+            verbosity: MorningBriefVerbosity::default(),
+        }
+    }
+}
+
+impl MorningBriefProfile {
+    pub fn includes(&self, section: MorningBriefSection) -> bool {
+        self.sections.contains(&section)
+    }
+}
+
+pub fn validate_brief_sections(sections: &[MorningBriefSection]) -> Result<(), &'static str> {
+    if sections.is_empty() {
+        return Err("sections must include at least one section");
+    }
+
+    for (index, section) in sections.iter().enumerate() {
+        if sections[..index].contains(section) {
+            return Err("sections must not contain duplicates");
+        }
+    }
+
+    Ok(())
+}
+
+/// Applies one thumbs-up/down rating to the per-section feedback scores and, when
+/// `learn_from_feedback` is enabled, nudges the profile: enabled sections are ordered by
+/// score (ties keep their current order), a thumbs-up re-enables a hidden section, and a
+/// section whose score falls to the disable threshold is hidden unless it is the last one.
+pub fn apply_brief_feedback(
+    profile: &mut MorningBriefProfile,
+    section_scores: &mut BTreeMap<MorningBriefSection, i32>,
+    learn_from_feedback: bool,
+    section: MorningBriefSection,
+    rating: BriefFeedbackRating,
+) {
+    let delta = match rating {
+        BriefFeedbackRating::Up => 1,
+        BriefFeedbackRating::Down => -1,
+    };
+    let score = section_scores.entry(section).or_insert(0);
+    *score = (*score + delta).clamp(-MAX_SECTION_SCORE_MAGNITUDE, MAX_SECTION_SCORE_MAGNITUDE);
+    let score = *score;
+
+    if !learn_from_feedback {
+        return;
+    }
+
+    match rating {
+        BriefFeedbackRating::Up if !profile.includes(section) => {
+            profile.sections.push(section);
+        }
+        BriefFeedbackRating::Down
+            if score <= DISABLE_SECTION_SCORE && profile.sections.len() > 1 =>
+        {
+            profile.sections.retain(|enabled| *enabled != section);
+        }
+        _ => {}
+    }
+
+    profile.sections.sort_by_key(|enabled| {
+        std::cmp::Reverse(section_scores.get(enabled).copied().unwrap_or(0))
+    });
+}
+
+/// Push body of a morning brief: the summary unless the profile is concise, then the leading
+/// items of each enabled section in the profile's order. Callers truncate for delivery.
+pub fn morning_brief_notification_body(
+    output: &MorningBriefOutput,
+    profile: &MorningBriefProfile,
+) -> String {
+    let mut segments = Vec::new();
+
+    if profile.verbosity != MorningBriefVerbosity::Concise && !output.summary.trim().is_empty() {
+        segments.push(output.summary.trim().to_string());
+    }
+
+    let items_per_section = match profile.verbosity {
+        MorningBriefVerbosity::Detailed => 2,
+        MorningBriefVerbosity::Concise | MorningBriefVerbosity::Standard => 1,
+    };
+    for section in &profile.sections {
+        let (label, values) = match section {
+            MorningBriefSection::Priorities => ("Priority", &output.priorities),
+            MorningBriefSection::Schedule => ("Schedule", &output.schedule),
+            MorningBriefSection::Alerts => ("Alert", &output.alerts),
+        };
+        for value in values
+            .iter()
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+            .take(items_per_section)
+        {
+            segments.push(format!("{label}: {value}"));
+        }
+    }
+
+    if segments.is_empty() {
+        return "Review your calendar and inbox for today.".to_string();
+    }
+
+    segments.join(" • ")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::{
+        BriefFeedbackRating, MorningBriefProfile, MorningBriefSection, apply_brief_feedback,
+        validate_brief_sections,
+    };
+
+    #[test]
+    fn thumbs_up_moves_section_ahead_of_unrated_sections() {
+        let mut profile = MorningBriefProfile::default();
+        let mut scores = BTreeMap::new();
+
+        apply_brief_feedback(
+            &mut profile,
+            &mut scores,
+            true,
+            MorningBriefSection::Alerts,
+            BriefFeedbackRating::Up,
+        );
+
+        assert_eq!(
+            profile.sections,
+            vec![
+                MorningBriefSection::Alerts,
+                MorningBriefSection::Priorities,
+                MorningBriefSection::Schedule,
+            ]
+        );
+    }
+
+    #[test]
+    fn repeated_thumbs_down_hides_section_but_never_the_last_one() {
+        let mut profile = MorningBriefProfile {
+            sections: vec![MorningBriefSection::Schedule, MorningBriefSection::Alerts],
+            ..MorningBriefProfile::default()
+        };
+        let mut scores = BTreeMap::new();
+
+        for _ in 0..3 {
+            apply_brief_feedback(
+                &mut profile,
+                &mut scores,
+                true,
+                MorningBriefSection::Schedule,
+                BriefFeedbackRating::Down,
+            );
+        }
+        assert_eq!(profile.sections, vec![MorningBriefSection::Alerts]);
+
+        for _ in 0..3 {
+            apply_brief_feedback(
+                &mut profile,
+                &mut scores,
+                true,
+                MorningBriefSection::Alerts,
+                BriefFeedbackRating::Down,
+            );
+        }
+        assert_eq!(profile.sections, vec![MorningBriefSection::Alerts]);
+
+        apply_brief_feedback(
+            &mut profile,
+            &mut scores,
+            true,
+            MorningBriefSection::Schedule,
+            BriefFeedbackRating::Up,
+        );
+        assert!(profile.includes(MorningBriefSection::Schedule));
+    }
+
+    #[test]
+    fn feedback_without_learning_only_records_scores() {
+        let mut profile = MorningBriefProfile::default();
+        let mut scores = BTreeMap::new();
+
+        apply_brief_feedback(
+            &mut profile,
+            &mut scores,
+            false,
+            MorningBriefSection::Alerts,
+            BriefFeedbackRating::Up,
+        );
+
+        assert_eq!(profile, MorningBriefProfile::default());
+        assert_eq!(scores.get(&MorningBriefSection::Alerts), Some(&1));
+    }
+
+    #[test]
+    fn validate_brief_sections_rejects_empty_and_duplicate_lists() {
+        assert!(validate_brief_sections(&[]).is_err());
+        assert!(
+            validate_brief_sections(&[MorningBriefSection::Alerts, MorningBriefSection::Alerts])
+                .is_err()
+        );
+        assert!(validate_brief_sections(&MorningBriefSection::ALL).is_ok());
+    }
+}
@@ -0,0 +1,72 @@
+use reqwest::Url;
+
+use crate::network_policy::is_public_host;
+
+pub const CALDAV_SERVER_URL_MAX_CHARS: usize = 2048;
+pub const CALDAV_USERNAME_MAX_CHARS: usize = 256;
+
+/// Validates a CalDAV calendar collection URL supplied by the user. Only HTTPS URLs pointing
+/// at public hosts are accepted, since the enclave fetches them with the user's credentials.
+pub fn normalize_caldav_server_url(value: &str) -> Result<String, &'static str> {
+    let trimmed = value.trim();
+    if trimmed.is_empty() || trimmed.chars().count() > CALDAV_SERVER_URL_MAX_CHARS {
+        return Err("server_url must be between 1 and 2048 characters");
+    }
+
+    let mut url = Url::parse(trimmed).map_err(|_| "server_url must be a valid URL")?;
+    if url.scheme() != "https" {
+        return Err("server_url must use https");
+    }
+    if !url.username().is_empty() || url.password().is_some() {
+        return Err("server_url must not embed credentials");
+    }
+
+    let host = url.host_str().ok_or("server_url must include a host")?;
+    if !is_public_host(host) {
+        return Err("server_url must point at a public host");
+    }
+
+    url.set_fragment(None);
+    Ok(url.to_string())
+}
+
+pub fn normalize_caldav_username(value: &str) -> Result<String, &'static str> {
+    let trimmed = value.trim();
+    if trimmed.is_empty() || trimmed.chars().count() > CALDAV_USERNAME_MAX_CHARS {
+        return Err("username must be between 1 and 256 characters");
+    }
+
+    Ok(trimmed.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize_caldav_server_url, normalize_caldav_username};
+
+    #[test]
+    fn server_url_requires_https_and_a_public_host() {
+        assert_eq!(
+            normalize_caldav_server_url(" https://caldav.fastmail.com/dav/calendars/user/a/b/#x "),
+            Ok("https://caldav.fastmail.com/dav/calendars/user/a/b/".to_string())
+        );
+        assert!(normalize_caldav_server_url("http://caldav.example.com/cal/").is_err());
+        assert!(normalize_caldav_server_url("https://user:pw@caldav.example.com/").is_err());
+        assert!(normalize_caldav_server_url("https://localhost/cal/").is_err());
+        assert!(normalize_caldav_server_url("https://127.0.0.1/cal/").is_err());
+        assert!(normalize_caldav_server_url("https://10.0.0.8/cal/").is_err());
+        assert!(normalize_caldav_server_url("https://169.254.169.254/latest/").is_err());
+        assert!(normalize_caldav_server_url("https://[::1]/cal/").is_err());
+        assert!(normalize_caldav_server_url("https://[::ffff:192.168.1.2]/cal/").is_err());
+        assert!(normalize_caldav_server_url("not a url").is_err());
+    }
+
+    #[test]
+    fn username_is_trimmed_and_bounded() {
+        assert_eq!(
+            normalize_caldav_username("  me@example.com "),
+            Ok("me@example.com".to_string())
+        );
+        assert!(normalize_caldav_username("   ").is_err());
+        assert!(normalize_caldav_username(&"a".repeat(257)).is_err());
+    }
+}
@@ -1,37 +1,68 @@
 use std::env;
-use std::fs;
 use std::io::ErrorKind;
 use std::net::IpAddr;
 use std::path::PathBuf;
 
-use base64::Engine as _;
 use thiserror::Error;
 
+use crate::audit_redaction::AuditRedactionPolicy;
+use crate::audit_retention::{AuditRetentionPolicy, DEFAULT_AUDIT_RETENTION_DAYS};
+use crate::config_admin::{clerk_org_role, parse_admin_api_token, parse_admin_clerk_org};
+use crate::config_api_versioning::parse_api_deprecation;
+use crate::config_apns::{load_apns_additional_apps, load_apns_auth_key_p8};
+use crate::config_cors::parse_cors_config;
 use crate::config_enclave_runtime::{
-    parse_alfred_environment, parse_enclave_rpc_shared_secret, parse_enclave_runtime_mode,
-    validate_enclave_runtime_guards, validate_non_local_enclave_security_posture,
+    parse_alfred_environment, parse_enclave_rpc_client_tls, parse_enclave_rpc_security_mode,
+    parse_enclave_rpc_signing_key, parse_enclave_runtime_mode, validate_enclave_runtime_guards,
+    validate_non_local_enclave_security_posture,
 };
 use crate::config_env::{
     optional_trimmed_env, parse_bool_env, parse_i32_env, parse_ip_list_env, parse_list_env,
     parse_list_env_with_fallback, parse_u32_env, parse_u64_env, require_env,
 };
+use crate::config_request_signing::parse_request_signing_config;
+use crate::config_security_headers::parse_security_headers_config;
+use crate::connector_health::DEFAULT_REAUTH_NUDGE_THRESHOLD;
+use crate::enclave::{EnclaveRpcClientTlsConfig, EnclaveRpcSecurityMode, EnclaveRpcSigningKey};
 use crate::enclave_runtime::{AlfredEnvironment, EnclaveRuntimeMode};
+use crate::repos::DataEncryptionKeyring;
+
+pub use crate::config_admin::AdminClerkOrgConfig;
+pub use crate::config_api_versioning::ApiDeprecationConfig;
+pub use crate::config_apns::ApnsAppConfig;
+pub use crate::config_cors::CorsConfig;
+pub use crate::config_data_keys::load_data_encryption_keyring;
+pub use crate::config_request_signing::RequestSigningConfig;
+pub use crate::config_security_headers::{REFERRER_POLICIES, SecurityHeadersConfig};
 
 #[derive(Debug, Clone)]
 pub struct ApiConfig {
     pub alfred_environment: AlfredEnvironment,
     pub bind_addr: String,
     pub api_http_timeout_ms: u64,
+    pub max_request_body_bytes: usize,
+    pub max_prompt_envelope_body_bytes: usize,
     pub database_url: String,
+    pub database_read_url: Option<String>,
     pub database_max_connections: u32,
     pub migrations_dir: PathBuf,
-    pub data_encryption_key: String,
+    pub data_encryption_keys: DataEncryptionKeyring,
+    pub audit_redaction_policy: AuditRedactionPolicy,
+    /// Has the enclave sign connector, privacy delete, and automation run audit events.
+    pub audit_event_signing_enabled: bool,
     pub oauth_state_ttl_seconds: u64,
     pub clerk_issuer: String,
     pub clerk_audience: String,
     pub clerk_secret_key: String,
     pub clerk_jwks_url: String,
     pub redis_url: String,
+    pub store_read_cache_ttl_seconds: u64,
+    pub rate_limit_redis_enabled: bool,
+    pub rate_limit_overrides: Vec<String>,
+    /// Blocks anomalous IPs and tightens sensitive limits for anomalous users while a
+    /// security anomaly cooldown is active. Anomalies are audited either way.
+    pub security_anomaly_enforcement_enabled: bool,
+    pub security_anomaly_cooldown_seconds: u64,
     pub clerk_jwks_cache_key: String,
     pub clerk_jwks_cache_default_ttl_seconds: u64,
     pub clerk_jwks_cache_stale_ttl_seconds: u64,
@@ -55,8 +86,20 @@ pub struct ApiConfig {
     pub enclave_runtime_mode: EnclaveRuntimeMode,
     pub enclave_runtime_base_url: String,
     pub enclave_runtime_probe_timeout_ms: u64,
-    pub enclave_rpc_shared_secret: String,
+    pub enclave_rpc_security_mode: EnclaveRpcSecurityMode,
+    pub enclave_rpc_client_tls: Option<EnclaveRpcClientTlsConfig>,
+    pub enclave_rpc_signing_key: Option<EnclaveRpcSigningKey>,
     pub enclave_rpc_auth_max_skew_seconds: u64,
+    pub pagination_cursor_secret: String,
+    pub admin_api_token: Option<String>,
+    pub admin_clerk_org: Option<AdminClerkOrgConfig>,
+    /// Clerk organization role, in `org:<role>` form, whose holders get read-only `/admin/v1`
+    /// access to the members of their own organization. `None` keeps tenants out of admin routes.
+    pub org_admin_role: Option<String>,
+    pub api_v1_deprecation: Option<ApiDeprecationConfig>,
+    pub cors: CorsConfig,
+    pub request_signing: RequestSigningConfig,
+    pub security_headers: SecurityHeadersConfig,
 }
 
 #[derive(Debug, Clone)]
@@ -72,6 +115,7 @@ pub struct WorkerConfig {
     pub apns_team_id: String,
     pub apns_topic: String,
     pub apns_auth_key_p8: String,
+    pub apns_additional_apps: Vec<ApnsAppConfig>,
     pub google_client_id: String,
     pub google_client_secret: String,
     pub google_token_url: String,
@@ -92,12 +136,35 @@ pub struct WorkerConfig {
     pub enclave_runtime_mode: EnclaveRuntimeMode,
     pub enclave_runtime_base_url: String,
     pub enclave_runtime_probe_timeout_ms: u64,
-    pub enclave_rpc_shared_secret: String,
+    pub enclave_rpc_security_mode: EnclaveRpcSecurityMode,
+    pub enclave_rpc_client_tls: Option<EnclaveRpcClientTlsConfig>,
+    pub enclave_rpc_signing_key: Option<EnclaveRpcSigningKey>,
     pub enclave_rpc_auth_max_skew_seconds: u64,
     pub database_url: String,
+    pub database_read_url: Option<String>,
     pub database_max_connections: u32,
-    pub data_encryption_key: String,
+    pub data_encryption_keys: DataEncryptionKeyring,
+    pub audit_redaction_policy: AuditRedactionPolicy,
+    /// Has the enclave sign connector, privacy delete, and automation run audit events.
+    pub audit_event_signing_enabled: bool,
+    pub audit_retention_policy: AuditRetentionPolicy,
+    pub audit_purge_batch_size: u32,
+    pub connector_health_nudge_threshold: i16,
+    pub connector_reauth_nudge_interval_hours: u64,
+    pub automation_failure_pause_threshold: u32,
+    pub automation_max_runtime_seconds: u64,
+    pub data_key_reencrypt_batch_size: u32,
+    pub job_history_retention_days: u32,
+    pub stale_device_retention_days: u32,
+    pub stale_device_purge_batch_size: u32,
+    pub outbound_idempotency_retention_days: u32,
+    pub ephemeral_state_purge_batch_size: u32,
+    pub new_device_backlog_max_age_seconds: u64,
+    pub push_delivery_slo_target_seconds: u64,
+    pub push_delivery_slo_objective_percent: u32,
+    pub push_delivery_slo_window_minutes: u64,
     pub redis_url: String,
+    pub store_read_cache_ttl_seconds: u64,
 }
 
 #[derive(Debug, Error)]
@@ -169,6 +236,9 @@ impl ApiConfig {
                 "API_HTTP_TIMEOUT_MS must be greater than 0".to_string(),
             ));
         }
+        let max_request_body_bytes = parse_body_limit_env("API_MAX_REQUEST_BODY_BYTES", 65_536)?;
+        let max_prompt_envelope_body_bytes =
+            parse_body_limit_env("API_MAX_PROMPT_ENVELOPE_BODY_BYTES", 131_072)?;
         let enclave_rpc_auth_max_skew_seconds =
             parse_u64_env("ENCLAVE_RPC_AUTH_MAX_SKEW_SECONDS", 30)?;
         if enclave_rpc_auth_max_skew_seconds == 0 {
@@ -176,7 +246,22 @@ impl ApiConfig {
                 "ENCLAVE_RPC_AUTH_MAX_SKEW_SECONDS must be greater than 0".to_string(),
             ));
         }
-        let enclave_rpc_shared_secret = parse_enclave_rpc_shared_secret(alfred_environment)?;
+        let enclave_rpc_security_mode = parse_enclave_rpc_security_mode()?;
+        let enclave_rpc_client_tls =
+            parse_enclave_rpc_client_tls(enclave_rpc_security_mode, &enclave_runtime_base_url)?;
+        let enclave_rpc_signing_key = parse_enclave_rpc_signing_key(
+            alfred_environment,
+            enclave_rpc_security_mode,
+            "api-server",
+        )?;
+        let pagination_cursor_secret = parse_pagination_cursor_secret(alfred_environment)?;
+        let admin_api_token = parse_admin_api_token()?;
+        let admin_clerk_org = parse_admin_clerk_org();
+        let org_admin_role = optional_trimmed_env("ORG_ADMIN_ROLE").map(clerk_org_role);
+        let api_v1_deprecation = parse_api_deprecation("API_V1_DEPRECATED_AT", "API_V1_SUNSET_AT")?;
+        let cors = parse_cors_config(alfred_environment)?;
+        let request_signing = parse_request_signing_config()?;
+        let security_headers = parse_security_headers_config(alfred_environment)?;
 
         let clerk_issuer = require_env("CLERK_ISSUER")?;
         if clerk_issuer.trim().is_empty() {
@@ -217,19 +302,31 @@ impl ApiConfig {
                 "CLERK_JWKS_CACHE_STALE_TTL_SECONDS must be greater than 0".to_string(),
             ));
         }
+        let security_anomaly_cooldown_seconds =
+            parse_u64_env("SECURITY_ANOMALY_COOLDOWN_SECONDS", 900)?;
+        if !(60..=86_400).contains(&security_anomaly_cooldown_seconds) {
+            return Err(ConfigError::InvalidConfiguration(
+                "SECURITY_ANOMALY_COOLDOWN_SECONDS must be between 60 and 86400".to_string(),
+            ));
+        }
 
         Ok(Self {
             alfred_environment,
             bind_addr: env::var("API_BIND_ADDR").unwrap_or_else(|_| "127.0.0.1:8080".to_string()),
             api_http_timeout_ms,
+            max_request_body_bytes,
+            max_prompt_envelope_body_bytes,
             database_url: require_env("DATABASE_URL")?,
+            database_read_url: optional_trimmed_env("DATABASE_READ_URL"),
             database_max_connections: parse_u32_env("DATABASE_MAX_CONNECTIONS", 10)?,
             migrations_dir: env::var("MIGRATIONS_DIR")
                 .map(PathBuf::from)
                 .unwrap_or_else(|_| {
                     PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../../db/migrations")
                 }),
-            data_encryption_key: require_env("DATA_ENCRYPTION_KEY")?,
+            data_encryption_keys: load_data_encryption_keyring()?,
+            audit_redaction_policy: load_audit_redaction_policy()?,
+            audit_event_signing_enabled: parse_bool_env("AUDIT_EVENT_SIGNING_ENABLED", false)?,
             oauth_state_ttl_seconds: parse_u64_env("OAUTH_STATE_TTL_SECONDS", 600)?,
             clerk_issuer,
             clerk_audience,
@@ -237,6 +334,14 @@ impl ApiConfig {
             clerk_jwks_url,
             redis_url: optional_trimmed_env("REDIS_URL")
                 .unwrap_or_else(|| "redis://127.0.0.1:6379/0".to_string()),
+            store_read_cache_ttl_seconds: parse_u64_env("STORE_READ_CACHE_TTL_SECONDS", 30)?,
+            rate_limit_redis_enabled: parse_bool_env("API_RATE_LIMIT_REDIS_ENABLED", true)?,
+            rate_limit_overrides: parse_list_env("API_RATE_LIMIT_OVERRIDES", &[]),
+            security_anomaly_enforcement_enabled: parse_bool_env(
+                "SECURITY_ANOMALY_ENFORCEMENT_ENABLED",
+                false,
+            )?,
+            security_anomaly_cooldown_seconds,
             clerk_jwks_cache_key: optional_trimmed_env("CLERK_JWKS_CACHE_KEY")
                 .unwrap_or_else(|| "alfred:clerk:jwks:v1".to_string()),
             clerk_jwks_cache_default_ttl_seconds,
@@ -266,12 +371,47 @@ impl ApiConfig {
             enclave_runtime_mode,
             enclave_runtime_base_url,
             enclave_runtime_probe_timeout_ms,
-            enclave_rpc_shared_secret,
+            enclave_rpc_security_mode,
+            enclave_rpc_client_tls,
+            enclave_rpc_signing_key,
             enclave_rpc_auth_max_skew_seconds,
+            pagination_cursor_secret,
+            admin_api_token,
+            admin_clerk_org,
+            org_admin_role,
+            api_v1_deprecation,
+            cors,
+            request_signing,
+            security_headers,
         })
     }
 }
 
+fn parse_pagination_cursor_secret(environment: AlfredEnvironment) -> Result<String, ConfigError> {
+    match optional_trimmed_env("PAGINATION_CURSOR_SECRET") {
+        Some(secret) if secret.len() < 32 => Err(ConfigError::InvalidConfiguration(
+            "PAGINATION_CURSOR_SECRET must be at least 32 characters".to_string(),
+        )),
+        Some(secret) => Ok(secret),
+        None if matches!(environment, AlfredEnvironment::Local) => {
+            Ok("local-dev-pagination-cursor-secret".to_string())
+        }
+        None => Err(ConfigError::MissingVar(
+            "PAGINATION_CURSOR_SECRET".to_string(),
+        )),
+    }
+}
+
+fn parse_body_limit_env(key: &str, default: u64) -> Result<usize, ConfigError> {
+    let bytes = parse_u64_env(key, default)?;
+    if bytes < 1024 {
+        return Err(ConfigError::InvalidConfiguration(format!(
+            "{key} must be at least 1024"
+        )));
+    }
+    Ok(usize::try_from(bytes).unwrap_or(usize::MAX))
+}
+
 fn default_clerk_jwks_url(clerk_issuer: &str) -> String {
     format!(
         "{}/.well-known/jwks.json",
@@ -301,6 +441,35 @@ impl WorkerConfig {
         let privacy_delete_lease_seconds =
             parse_u64_env("WORKER_PRIVACY_DELETE_LEASE_SECONDS", 120)?;
         let privacy_delete_sla_hours = parse_u64_env("PRIVACY_DELETE_SLA_HOURS", 24)?;
+        let data_key_reencrypt_batch_size =
+            parse_u32_env("WORKER_DATA_KEY_REENCRYPT_BATCH_SIZE", 100)?;
+        let job_history_retention_days = parse_u32_env("WORKER_JOB_HISTORY_RETENTION_DAYS", 30)?;
+        let audit_purge_batch_size = parse_u32_env("WORKER_AUDIT_PURGE_BATCH_SIZE", 500)?;
+        let stale_device_retention_days = parse_u32_env("WORKER_STALE_DEVICE_RETENTION_DAYS", 120)?;
+        let stale_device_purge_batch_size =
+            parse_u32_env("WORKER_STALE_DEVICE_PURGE_BATCH_SIZE", 200)?;
+        let outbound_idempotency_retention_days =
+            parse_u32_env("WORKER_OUTBOUND_IDEMPOTENCY_RETENTION_DAYS", 30)?;
+        let ephemeral_state_purge_batch_size =
+            parse_u32_env("WORKER_EPHEMERAL_STATE_PURGE_BATCH_SIZE", 500)?;
+        let new_device_backlog_max_age_seconds =
+            parse_u64_env("WORKER_NEW_DEVICE_BACKLOG_MAX_AGE_SECONDS", 900)?;
+        let push_delivery_slo_target_seconds =
+            parse_u64_env("PUSH_DELIVERY_SLO_TARGET_SECONDS", 60)?;
+        let push_delivery_slo_objective_percent =
+            parse_u32_env("PUSH_DELIVERY_SLO_OBJECTIVE_PERCENT", 95)?;
+        let push_delivery_slo_window_minutes =
+            parse_u64_env("PUSH_DELIVERY_SLO_WINDOW_MINUTES", 60)?;
+        let connector_health_nudge_threshold = parse_u32_env(
+            "WORKER_CONNECTOR_HEALTH_NUDGE_THRESHOLD",
+            DEFAULT_REAUTH_NUDGE_THRESHOLD as u32,
+        )?;
+        let connector_reauth_nudge_interval_hours =
+            parse_u64_env("WORKER_CONNECTOR_REAUTH_NUDGE_INTERVAL_HOURS", 72)?;
+        let automation_failure_pause_threshold =
+            parse_u32_env("WORKER_AUTOMATION_FAILURE_PAUSE_THRESHOLD", 3)?;
+        let automation_max_runtime_seconds =
+            parse_u64_env("WORKER_AUTOMATION_MAX_RUNTIME_SECONDS", 45)?;
 
         if batch_size == 0 {
             return Err(ConfigError::InvalidConfiguration(
@@ -343,11 +512,86 @@ impl WorkerConfig {
                 "WORKER_PRIVACY_DELETE_LEASE_SECONDS must be greater than 0".to_string(),
             ));
         }
+        if push_delivery_slo_target_seconds == 0 {
+            return Err(ConfigError::InvalidConfiguration(
+                "PUSH_DELIVERY_SLO_TARGET_SECONDS must be greater than 0".to_string(),
+            ));
+        }
+        if !(1..=100).contains(&push_delivery_slo_objective_percent) {
+            return Err(ConfigError::InvalidConfiguration(
+                "PUSH_DELIVERY_SLO_OBJECTIVE_PERCENT must be between 1 and 100".to_string(),
+            ));
+        }
+        if push_delivery_slo_window_minutes == 0 {
+            return Err(ConfigError::InvalidConfiguration(
+                "PUSH_DELIVERY_SLO_WINDOW_MINUTES must be greater than 0".to_string(),
+            ));
+        }
         if privacy_delete_sla_hours == 0 {
             return Err(ConfigError::InvalidConfiguration(
                 "PRIVACY_DELETE_SLA_HOURS must be greater than 0".to_string(),
             ));
         }
+        if data_key_reencrypt_batch_size == 0 {
+            return Err(ConfigError::InvalidConfiguration(
+                "WORKER_DATA_KEY_REENCRYPT_BATCH_SIZE must be greater than 0".to_string(),
+            ));
+        }
+        if job_history_retention_days == 0 {
+            return Err(ConfigError::InvalidConfiguration(
+                "WORKER_JOB_HISTORY_RETENTION_DAYS must be greater than 0".to_string(),
+            ));
+        }
+        if audit_purge_batch_size == 0 {
+            return Err(ConfigError::InvalidConfiguration(
+                "WORKER_AUDIT_PURGE_BATCH_SIZE must be greater than 0".to_string(),
+            ));
+        }
+        if stale_device_retention_days == 0 {
+            return Err(ConfigError::InvalidConfiguration(
+                "WORKER_STALE_DEVICE_RETENTION_DAYS must be greater than 0".to_string(),
+            ));
+        }
+        if stale_device_purge_batch_size == 0 {
+            return Err(ConfigError::InvalidConfiguration(
+                "WORKER_STALE_DEVICE_PURGE_BATCH_SIZE must be greater than 0".to_string(),
+            ));
+        }
+        if outbound_idempotency_retention_days == 0 {
+            return Err(ConfigError::InvalidConfiguration(
+                "WORKER_OUTBOUND_IDEMPOTENCY_RETENTION_DAYS must be greater than 0".to_string(),
+            ));
+        }
+        if ephemeral_state_purge_batch_size == 0 {
+            return Err(ConfigError::InvalidConfiguration(
+                "WORKER_EPHEMERAL_STATE_PURGE_BATCH_SIZE must be greater than 0".to_string(),
+            ));
+        }
+        let connector_health_nudge_threshold = i16::try_from(connector_health_nudge_threshold)
+            .ok()
+            .filter(|threshold| (1..=100).contains(threshold))
+            .ok_or_else(|| {
+                ConfigError::InvalidConfiguration(
+                    "WORKER_CONNECTOR_HEALTH_NUDGE_THRESHOLD must be between 1 and 100".to_string(),
+                )
+            })?;
+        if connector_reauth_nudge_interval_hours == 0 {
+            return Err(ConfigError::InvalidConfiguration(
+                "WORKER_CONNECTOR_REAUTH_NUDGE_INTERVAL_HOURS must be greater than 0".to_string(),
+            ));
+        }
+        if automation_failure_pause_threshold == 0 {
+            return Err(ConfigError::InvalidConfiguration(
+                "WORKER_AUTOMATION_FAILURE_PAUSE_THRESHOLD must be greater than 0".to_string(),
+            ));
+        }
+        // Runs stop before their job lease expires, so another worker never reclaims a job
+        // whose run is still going.
+        if automation_max_runtime_seconds == 0 || automation_max_runtime_seconds >= lease_seconds {
+            return Err(ConfigError::InvalidConfiguration(
+                "WORKER_AUTOMATION_MAX_RUNTIME_SECONDS must be greater than 0 and less than WORKER_LEASE_SECONDS".to_string(),
+            ));
+        }
 
         let tee_attestation_required = parse_bool_env("TEE_ATTESTATION_REQUIRED", true)?;
         let tee_allow_insecure_dev_attestation =
@@ -392,8 +636,26 @@ impl WorkerConfig {
                 "ENCLAVE_RPC_AUTH_MAX_SKEW_SECONDS must be greater than 0".to_string(),
             ));
         }
-        let enclave_rpc_shared_secret = parse_enclave_rpc_shared_secret(alfred_environment)?;
-        let apns_auth_key_p8 = load_apns_auth_key_p8()?;
+        let enclave_rpc_security_mode = parse_enclave_rpc_security_mode()?;
+        let enclave_rpc_client_tls =
+            parse_enclave_rpc_client_tls(enclave_rpc_security_mode, &enclave_runtime_base_url)?;
+        let enclave_rpc_signing_key =
+            parse_enclave_rpc_signing_key(alfred_environment, enclave_rpc_security_mode, "worker")?;
+        let apns_key_id = require_env("APNS_KEY_ID")?;
+        let apns_team_id = require_env("APNS_TEAM_ID")?;
+        let apns_topic = require_env("APNS_TOPIC")?;
+        let apns_auth_key_p8 = load_apns_auth_key_p8("APNS")?.ok_or_else(|| {
+            ConfigError::InvalidConfiguration(
+                "one of APNS_AUTH_KEY_P8, APNS_AUTH_KEY_P8_BASE64, or APNS_AUTH_KEY_P8_PATH must be set"
+                    .to_string(),
+            )
+        })?;
+        let apns_additional_apps = load_apns_additional_apps(
+            apns_topic.as_str(),
+            apns_key_id.as_str(),
+            apns_team_id.as_str(),
+            apns_auth_key_p8.as_str(),
+        )?;
 
         Ok(Self {
             tick_seconds,
@@ -403,10 +665,11 @@ impl WorkerConfig {
             per_user_concurrency_limit,
             retry_base_delay_seconds,
             retry_max_delay_seconds,
-            apns_key_id: require_env("APNS_KEY_ID")?,
-            apns_team_id: require_env("APNS_TEAM_ID")?,
-            apns_topic: require_env("APNS_TOPIC")?,
+            apns_key_id,
+            apns_team_id,
+            apns_topic,
             apns_auth_key_p8,
+            apns_additional_apps,
             google_client_id: require_env("GOOGLE_OAUTH_CLIENT_ID")?,
             google_client_secret: require_env("GOOGLE_OAUTH_CLIENT_SECRET")?,
             google_token_url: env::var("GOOGLE_OAUTH_TOKEN_URL")
@@ -431,67 +694,57 @@ impl WorkerConfig {
             enclave_runtime_mode,
             enclave_runtime_base_url,
             enclave_runtime_probe_timeout_ms,
-            enclave_rpc_shared_secret,
+            enclave_rpc_security_mode,
+            enclave_rpc_client_tls,
+            enclave_rpc_signing_key,
             enclave_rpc_auth_max_skew_seconds,
             database_url: require_env("DATABASE_URL")?,
+            database_read_url: optional_trimmed_env("DATABASE_READ_URL"),
             database_max_connections: parse_u32_env("DATABASE_MAX_CONNECTIONS", 5)?,
-            data_encryption_key: require_env("DATA_ENCRYPTION_KEY")?,
+            data_encryption_keys: load_data_encryption_keyring()?,
+            audit_redaction_policy: load_audit_redaction_policy()?,
+            audit_event_signing_enabled: parse_bool_env("AUDIT_EVENT_SIGNING_ENABLED", false)?,
+            audit_retention_policy: load_audit_retention_policy()?,
+            audit_purge_batch_size,
+            connector_health_nudge_threshold,
+            connector_reauth_nudge_interval_hours,
+            automation_failure_pause_threshold,
+            automation_max_runtime_seconds,
+            data_key_reencrypt_batch_size,
+            job_history_retention_days,
+            stale_device_retention_days,
+            stale_device_purge_batch_size,
+            outbound_idempotency_retention_days,
+            ephemeral_state_purge_batch_size,
+            new_device_backlog_max_age_seconds,
+            push_delivery_slo_target_seconds,
+            push_delivery_slo_objective_percent,
+            push_delivery_slo_window_minutes,
             redis_url: optional_trimmed_env("REDIS_URL")
                 .unwrap_or_else(|| "redis://127.0.0.1:6379/0".to_string()),
+            store_read_cache_ttl_seconds: parse_u64_env("STORE_READ_CACHE_TTL_SECONDS", 30)?,
         })
     }
 }
 
-fn load_apns_auth_key_p8() -> Result<String, ConfigError> {
-    if let Some(inline) = optional_trimmed_env("APNS_AUTH_KEY_P8") {
-        return normalize_pem(inline);
-    }
-
-    if let Some(encoded) = optional_trimmed_env("APNS_AUTH_KEY_P8_BASE64") {
-        let decoded = base64::engine::general_purpose::STANDARD
-            .decode(encoded.as_bytes())
-            .map_err(|_| {
-                ConfigError::InvalidConfiguration(
-                    "APNS_AUTH_KEY_P8_BASE64 must be valid base64".to_string(),
-                )
-            })?;
-        let pem = String::from_utf8(decoded).map_err(|_| {
-            ConfigError::InvalidConfiguration(
-                "APNS_AUTH_KEY_P8_BASE64 must decode to UTF-8 PEM text".to_string(),
-            )
-        })?;
-        return normalize_pem(pem);
-    }
-
-    if let Some(path) = optional_trimmed_env("APNS_AUTH_KEY_P8_PATH") {
-        let pem = fs::read_to_string(path.as_str()).map_err(|err| {
-            ConfigError::InvalidConfiguration(format!(
-                "failed to read APNS_AUTH_KEY_P8_PATH ({path}): {err}"
-            ))
-        })?;
-        return normalize_pem(pem);
-    }
-
-    Err(ConfigError::InvalidConfiguration(
-        "one of APNS_AUTH_KEY_P8, APNS_AUTH_KEY_P8_BASE64, or APNS_AUTH_KEY_P8_PATH must be set"
-            .to_string(),
-    ))
+/// Builds the audit metadata redaction policy from the defaults plus comma-separated
+/// `AUDIT_METADATA_ALLOW_KEYS` / `AUDIT_METADATA_DENY_KEYS` rules (`key` or `prefix*`).
+pub fn load_audit_redaction_policy() -> Result<AuditRedactionPolicy, ConfigError> {
+    AuditRedactionPolicy::with_rules(
+        &parse_list_env("AUDIT_METADATA_ALLOW_KEYS", &[]),
+        &parse_list_env("AUDIT_METADATA_DENY_KEYS", &[]),
+    )
+    .map_err(ConfigError::InvalidConfiguration)
 }
 
-fn normalize_pem(raw: String) -> Result<String, ConfigError> {
-    let normalized = raw.replace("\\n", "\n");
-    let trimmed = normalized.trim().to_string();
-    if trimmed.is_empty() {
-        return Err(ConfigError::InvalidConfiguration(
-            "APNS auth key PEM must not be empty".to_string(),
-        ));
-    }
-    if !trimmed.contains("BEGIN PRIVATE KEY") || !trimmed.contains("END PRIVATE KEY") {
-        return Err(ConfigError::InvalidConfiguration(
-            "APNS auth key PEM must contain BEGIN/END PRIVATE KEY markers".to_string(),
-        ));
-    }
-    Ok(trimmed)
+/// Builds the audit retention policy from `AUDIT_RETENTION_DEFAULT_DAYS` plus comma-separated
+/// `AUDIT_RETENTION_EVENT_TYPE_DAYS` overrides (`EVENT_TYPE=days`).
+pub fn load_audit_retention_policy() -> Result<AuditRetentionPolicy, ConfigError> {
+    AuditRetentionPolicy::with_overrides(
+        parse_u32_env("AUDIT_RETENTION_DEFAULT_DAYS", DEFAULT_AUDIT_RETENTION_DAYS)?,
+        &parse_list_env("AUDIT_RETENTION_EVENT_TYPE_DAYS", &[]),
+    )
+    .map_err(ConfigError::InvalidConfiguration)
 }
 
 #[cfg(test)]
@@ -0,0 +1,39 @@
+use crate::config::ConfigError;
+use crate::config_env::optional_trimmed_env;
+
+/// Clerk organization whose members may call `/admin/v1` with their own session token.
+#[derive(Debug, Clone)]
+pub struct AdminClerkOrgConfig {
+    pub org_id: String,
+    /// Required organization role, in Clerk's `org:<role>` form.
+    pub role: String,
+}
+
+/// Admin routes stay disabled unless an operator configures a service token.
+pub(crate) fn parse_admin_api_token() -> Result<Option<String>, ConfigError> {
+    match optional_trimmed_env("ADMIN_API_TOKEN") {
+        Some(token) if token.len() < 32 => Err(ConfigError::InvalidConfiguration(
+            "ADMIN_API_TOKEN must be at least 32 characters".to_string(),
+        )),
+        token => Ok(token),
+    }
+}
+
+/// Clerk admin access is off unless an organization is named. Roles may be given with or
+/// without Clerk's `org:` prefix.
+pub(crate) fn parse_admin_clerk_org() -> Option<AdminClerkOrgConfig> {
+    let org_id = optional_trimmed_env("ADMIN_CLERK_ORG_ID")?;
+    let role = optional_trimmed_env("ADMIN_CLERK_ORG_ROLE").unwrap_or_else(|| "admin".to_string());
+    Some(AdminClerkOrgConfig {
+        org_id,
+        role: clerk_org_role(role),
+    })
+}
+
+pub(crate) fn clerk_org_role(role: String) -> String {
+    if role.starts_with("org:") {
+        role
+    } else {
+        format!("org:{role}")
+    }
+}
@@ -0,0 +1,37 @@
+use chrono::{DateTime, Utc};
+
+use crate::config::ConfigError;
+use crate::config_env::parse_rfc3339_env;
+
+/// Announced retirement of an API version, advertised to clients on each of its responses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ApiDeprecationConfig {
+    pub deprecated_at: DateTime<Utc>,
+    /// When the version stops being served, if that has been decided.
+    pub sunset_at: Option<DateTime<Utc>>,
+}
+
+/// A version is only deprecated once its deprecation date is set, and its sunset must come
+/// after that date.
+pub(crate) fn parse_api_deprecation(
+    deprecated_at_key: &str,
+    sunset_at_key: &str,
+) -> Result<Option<ApiDeprecationConfig>, ConfigError> {
+    let deprecated_at = parse_rfc3339_env(deprecated_at_key)?;
+    let sunset_at = parse_rfc3339_env(sunset_at_key)?;
+    match (deprecated_at, sunset_at) {
+        (None, None) => Ok(None),
+        (None, Some(_)) => Err(ConfigError::InvalidConfiguration(format!(
+            "{sunset_at_key} requires {deprecated_at_key}"
+        ))),
+        (Some(deprecated_at), Some(sunset_at)) if sunset_at <= deprecated_at => {
+            Err(ConfigError::InvalidConfiguration(format!(
+                "{sunset_at_key} must be later than {deprecated_at_key}"
+            )))
+        }
+        (Some(deprecated_at), sunset_at) => Ok(Some(ApiDeprecationConfig {
+            deprecated_at,
+            sunset_at,
+        })),
+    }
+}
@@ -0,0 +1,156 @@
+use std::fs;
+
+use base64::Engine as _;
+
+use crate::config::ConfigError;
+use crate::config_env::optional_trimmed_env;
+
+/// APNs credentials for an additional app build (for example a beta bundle id) that
+/// devices can register against alongside the default `APNS_TOPIC`.
+#[derive(Debug, Clone)]
+pub struct ApnsAppConfig {
+    pub topic: String,
+    pub key_id: String,
+    pub team_id: String,
+    pub auth_key_p8: String,
+}
+
+/// Loads additional APNs app builds from comma-separated `APNS_ADDITIONAL_TOPICS`. Each topic
+/// may override credentials via `APNS_<TOPIC>_KEY_ID`, `APNS_<TOPIC>_TEAM_ID`, and
+/// `APNS_<TOPIC>_AUTH_KEY_P8[_BASE64|_PATH]`, where `<TOPIC>` is the bundle id uppercased with
+/// non-alphanumeric characters replaced by `_`; unset values fall back to the default key.
+pub(crate) fn load_apns_additional_apps(
+    default_topic: &str,
+    default_key_id: &str,
+    default_team_id: &str,
+    default_auth_key_p8: &str,
+) -> Result<Vec<ApnsAppConfig>, ConfigError> {
+    let Some(raw) = optional_trimmed_env("APNS_ADDITIONAL_TOPICS") else {
+        return Ok(Vec::new());
+    };
+
+    let mut apps: Vec<ApnsAppConfig> = Vec::new();
+    for topic in raw
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+    {
+        if !is_valid_apns_topic(topic) {
+            return Err(ConfigError::InvalidConfiguration(format!(
+                "APNS_ADDITIONAL_TOPICS contains invalid topic '{topic}'"
+            )));
+        }
+        if topic == default_topic || apps.iter().any(|app| app.topic == topic) {
+            return Err(ConfigError::InvalidConfiguration(format!(
+                "APNS_ADDITIONAL_TOPICS contains duplicate topic '{topic}'"
+            )));
+        }
+
+        let prefix = apns_topic_env_prefix(topic);
+        apps.push(ApnsAppConfig {
+            topic: topic.to_string(),
+            key_id: optional_trimmed_env(&format!("{prefix}_KEY_ID"))
+                .unwrap_or_else(|| default_key_id.to_string()),
+            team_id: optional_trimmed_env(&format!("{prefix}_TEAM_ID"))
+                .unwrap_or_else(|| default_team_id.to_string()),
+            auth_key_p8: load_apns_auth_key_p8(&prefix)?
+                .unwrap_or_else(|| default_auth_key_p8.to_string()),
+        });
+    }
+
+    Ok(apps)
+}
+
+fn is_valid_apns_topic(topic: &str) -> bool {
+    topic.len() <= 255
+        && topic.split('.').all(|segment| {
+            !segment.is_empty()
+                && segment
+                    .bytes()
+                    .all(|byte| byte.is_ascii_alphanumeric() || byte == b'-')
+        })
+}
+
+fn apns_topic_env_prefix(topic: &str) -> String {
+    let sanitized = topic
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() {
+                ch.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    format!("APNS_{sanitized}")
+}
+
+pub(crate) fn load_apns_auth_key_p8(prefix: &str) -> Result<Option<String>, ConfigError> {
+    if let Some(inline) = optional_trimmed_env(&format!("{prefix}_AUTH_KEY_P8")) {
+        return normalize_pem(inline).map(Some);
+    }
+
+    if let Some(encoded) = optional_trimmed_env(&format!("{prefix}_AUTH_KEY_P8_BASE64")) {
+        let decoded = base64::engine::general_purpose::STANDARD
+            .decode(encoded.as_bytes())
+            .map_err(|_| {
+                ConfigError::InvalidConfiguration(format!(
+                    "{prefix}_AUTH_KEY_P8_BASE64 must be valid base64"
+                ))
+            })?;
+        let pem = String::from_utf8(decoded).map_err(|_| {
+            ConfigError::InvalidConfiguration(format!(
+                "{prefix}_AUTH_KEY_P8_BASE64 must decode to UTF-8 PEM text"
+            ))
+        })?;
+        return normalize_pem(pem).map(Some);
+    }
+
+    if let Some(path) = optional_trimmed_env(&format!("{prefix}_AUTH_KEY_P8_PATH")) {
+        let pem = fs::read_to_string(path.as_str()).map_err(|err| {
+            ConfigError::InvalidConfiguration(format!(
+                "failed to read {prefix}_AUTH_KEY_P8_PATH ({path}): {err}"
+            ))
+        })?;
+        return normalize_pem(pem).map(Some);
+    }
+
+    Ok(None)
+}
+
+fn normalize_pem(raw: String) -> Result<String, ConfigError> {
+    let normalized = raw.replace("\\n", "\n");
+    let trimmed = normalized.trim().to_string();
+    if trimmed.is_empty() {
+        return Err(ConfigError::InvalidConfiguration(
+            "APNS auth key PEM must not be empty".to_string(),
+        ));
+    }
+    if !trimmed.contains("BEGIN PRIVATE KEY") || !trimmed.contains("END PRIVATE KEY") {
+        return Err(ConfigError::InvalidConfiguration(
+            "APNS auth key PEM must contain BEGIN/END PRIVATE KEY markers".to_string(),
+        ));
+    }
+    Ok(trimmed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apns_topic_env_prefix, is_valid_apns_topic};
+
+    #[test]
+    fn apns_topic_env_prefix_sanitizes_bundle_id() {
+        assert_eq!(
+            apns_topic_env_prefix("com.prodata.alfred-beta"),
+            "APNS_COM_PRODATA_ALFRED_BETA"
+        );
+    }
+
+    #[test]
+    fn apns_topic_validation_requires_reverse_dns_segments() {
+        assert!(is_valid_apns_topic("com.prodata.alfred.beta"));
+        assert!(!is_valid_apns_topic("com..alfred"));
+        assert!(!is_valid_apns_topic("com.alfred beta"));
+        assert!(!is_valid_apns_topic(""));
+    }
+}
@@ -0,0 +1,97 @@
+use crate::config::ConfigError;
+use crate::config_env::{parse_list_env, parse_u64_env};
+use crate::enclave_runtime::AlfredEnvironment;
+
+/// Cross-origin access for browser clients. No origins means no CORS headers, so browsers
+/// keep refusing cross-origin calls.
+#[derive(Debug, Clone, Default)]
+pub struct CorsConfig {
+    /// Exact `scheme://host[:port]` origins; wildcards are not accepted.
+    pub allowed_origins: Vec<String>,
+    /// Uppercase method names.
+    pub allowed_methods: Vec<String>,
+    /// Lowercase request header names.
+    pub allowed_headers: Vec<String>,
+    pub max_age_seconds: u64,
+}
+
+pub(crate) fn parse_cors_config(environment: AlfredEnvironment) -> Result<CorsConfig, ConfigError> {
+    let allowed_origins = parse_list_env("API_CORS_ALLOWED_ORIGINS", &[]);
+    for origin in &allowed_origins {
+        if !is_valid_cors_origin(origin) {
+            return Err(ConfigError::InvalidConfiguration(format!(
+                "API_CORS_ALLOWED_ORIGINS entry must be an exact http(s)://host[:port] origin: {origin}"
+            )));
+        }
+        if !matches!(environment, AlfredEnvironment::Local) && !origin.starts_with("https://") {
+            return Err(ConfigError::InvalidConfiguration(format!(
+                "API_CORS_ALLOWED_ORIGINS must use https outside local: {origin}"
+            )));
+        }
+    }
+
+    let allowed_methods = parse_list_env("API_CORS_ALLOWED_METHODS", &["GET"])
+        .into_iter()
+        .map(|method| method.to_ascii_uppercase())
+        .collect::<Vec<_>>();
+    if let Some(method) = allowed_methods
+        .iter()
+        .find(|method| method.is_empty() || !method.chars().all(|ch| ch.is_ascii_uppercase()))
+    {
+        return Err(ConfigError::InvalidConfiguration(format!(
+            "API_CORS_ALLOWED_METHODS entry is not an HTTP method: {method}"
+        )));
+    }
+
+    let allowed_headers = parse_list_env("API_CORS_ALLOWED_HEADERS", &["authorization"])
+        .into_iter()
+        .map(|name| name.to_ascii_lowercase())
+        .collect::<Vec<_>>();
+    if let Some(name) = allowed_headers.iter().find(|name| {
+        name.is_empty()
+            || !name
+                .chars()
+                .all(|ch| ch.is_ascii_lowercase() || ch.is_ascii_digit() || ch == '-')
+    }) {
+        return Err(ConfigError::InvalidConfiguration(format!(
+            "API_CORS_ALLOWED_HEADERS entry is not a header name: {name}"
+        )));
+    }
+
+    Ok(CorsConfig {
+        allowed_origins,
+        allowed_methods,
+        allowed_headers,
+        max_age_seconds: parse_u64_env("API_CORS_MAX_AGE_SECONDS", 600)?,
+    })
+}
+
+/// Browsers send `Origin` as scheme, host, and optional port with no path or trailing slash,
+/// and CORS matches it byte for byte.
+fn is_valid_cors_origin(origin: &str) -> bool {
+    let Some((scheme, authority)) = origin.split_once("://") else {
+        return false;
+    };
+    matches!(scheme, "http" | "https")
+        && !authority.is_empty()
+        && authority.chars().all(|ch| {
+            ch.is_ascii_lowercase() || ch.is_ascii_digit() || matches!(ch, '.' | '-' | ':')
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_valid_cors_origin;
+
+    #[test]
+    fn cors_origins_must_be_exact_lowercase_origins() {
+        assert!(is_valid_cors_origin("https://dashboard.alfred.app"));
+        assert!(is_valid_cors_origin("http://localhost:5173"));
+        assert!(!is_valid_cors_origin("*"));
+        assert!(!is_valid_cors_origin("https://*.alfred.app"));
+        assert!(!is_valid_cors_origin("https://dashboard.alfred.app/"));
+        assert!(!is_valid_cors_origin("https://Dashboard.alfred.app"));
+        assert!(!is_valid_cors_origin("ftp://dashboard.alfred.app"));
+        assert!(!is_valid_cors_origin("dashboard.alfred.app"));
+    }
+}
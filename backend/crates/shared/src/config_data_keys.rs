@@ -0,0 +1,103 @@
+use crate::config::ConfigError;
+use crate::config_env::{optional_trimmed_env, require_env};
+use crate::repos::{DataEncryptionKey, DataEncryptionKeyring};
+
+const DEFAULT_DATA_ENCRYPTION_KEY_ID: &str = "v1";
+
+const MAX_DATA_ENCRYPTION_KEY_ID_LEN: usize = 64;
+
+/// Loads the primary data encryption key plus an optional secondary key. During a rotation
+/// the new key is deployed as primary and the retiring key as secondary; once the worker has
+/// re-encrypted every row the secondary key can be removed.
+pub fn load_data_encryption_keyring() -> Result<DataEncryptionKeyring, ConfigError> {
+    let primary = DataEncryptionKey {
+        key_id: optional_trimmed_env("DATA_ENCRYPTION_KEY_ID")
+            .unwrap_or_else(|| DEFAULT_DATA_ENCRYPTION_KEY_ID.to_string()),
+        key: require_env("DATA_ENCRYPTION_KEY")?,
+    };
+    let secondary = match (
+        optional_trimmed_env("DATA_ENCRYPTION_KEY_SECONDARY_ID"),
+        optional_trimmed_env("DATA_ENCRYPTION_KEY_SECONDARY"),
+    ) {
+        (Some(key_id), Some(key)) => Some(DataEncryptionKey { key_id, key }),
+        (None, None) => None,
+        _ => {
+            return Err(ConfigError::InvalidConfiguration(
+                "DATA_ENCRYPTION_KEY_SECONDARY and DATA_ENCRYPTION_KEY_SECONDARY_ID must be set together"
+                    .to_string(),
+            ));
+        }
+    };
+
+    validate_data_encryption_keyring(DataEncryptionKeyring { primary, secondary })
+}
+
+fn validate_data_encryption_keyring(
+    keyring: DataEncryptionKeyring,
+) -> Result<DataEncryptionKeyring, ConfigError> {
+    if keyring.primary.key.trim().is_empty() {
+        return Err(ConfigError::InvalidConfiguration(
+            "DATA_ENCRYPTION_KEY must not be empty".to_string(),
+        ));
+    }
+    if !is_valid_data_encryption_key_id(&keyring.primary.key_id) {
+        return Err(ConfigError::InvalidConfiguration(
+            "DATA_ENCRYPTION_KEY_ID must be 1-64 characters of [A-Za-z0-9._-]".to_string(),
+        ));
+    }
+    if let Some(secondary) = keyring.secondary.as_ref() {
+        if !is_valid_data_encryption_key_id(&secondary.key_id) {
+            return Err(ConfigError::InvalidConfiguration(
+                "DATA_ENCRYPTION_KEY_SECONDARY_ID must be 1-64 characters of [A-Za-z0-9._-]"
+                    .to_string(),
+            ));
+        }
+        if secondary.key_id == keyring.primary.key_id {
+            return Err(ConfigError::InvalidConfiguration(
+                "DATA_ENCRYPTION_KEY_SECONDARY_ID must differ from DATA_ENCRYPTION_KEY_ID"
+                    .to_string(),
+            ));
+        }
+    }
+
+    Ok(keyring)
+}
+
+fn is_valid_data_encryption_key_id(key_id: &str) -> bool {
+    !key_id.is_empty()
+        && key_id.len() <= MAX_DATA_ENCRYPTION_KEY_ID_LEN
+        && key_id
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::validate_data_encryption_keyring;
+    use crate::repos::{DataEncryptionKey, DataEncryptionKeyring};
+
+    #[test]
+    fn data_encryption_keyring_rejects_duplicate_secondary_key_id() {
+        let keyring = DataEncryptionKeyring {
+            primary: DataEncryptionKey {
+                key_id: "v2".to_string(),
+                key: "primary-key".to_string(),
+            },
+            secondary: Some(DataEncryptionKey {
+                key_id: "v2".to_string(),
+                key: "secondary-key".to_string(),
+            }),
+        };
+
+        assert!(validate_data_encryption_keyring(keyring).is_err());
+    }
+
+    #[test]
+    fn data_encryption_keyring_rejects_invalid_key_id() {
+        let keyring = DataEncryptionKeyring::single("key id with spaces", "primary-key");
+        assert!(validate_data_encryption_keyring(keyring).is_err());
+
+        let keyring = DataEncryptionKeyring::single("v1", "primary-key");
+        assert!(validate_data_encryption_keyring(keyring).is_ok());
+    }
+}
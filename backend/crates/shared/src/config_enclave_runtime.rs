@@ -1,6 +1,12 @@
 use std::env;
 
+use base64::Engine as _;
+
 use crate::config::ConfigError;
+use crate::config_env::{optional_trimmed_env, require_env};
+use crate::enclave::{
+    EnclaveRpcClientTlsConfig, EnclaveRpcSecurityMode, EnclaveRpcSigningKey, VsockAddr,
+};
 use crate::enclave_runtime::{AlfredEnvironment, EnclaveRuntimeMode};
 
 pub(crate) fn parse_alfred_environment() -> Result<AlfredEnvironment, ConfigError> {
@@ -98,6 +104,11 @@ fn validate_measurement_allowlist(key: &str, measurements: &[String]) -> Result<
 }
 
 fn validate_non_local_runtime_base_url(base_url: &str) -> Result<(), ConfigError> {
+    if VsockAddr::is_vsock_url(base_url) {
+        return VsockAddr::parse_url(base_url).map(|_| ()).map_err(|err| {
+            ConfigError::InvalidConfiguration(format!("ENCLAVE_RUNTIME_BASE_URL is invalid: {err}"))
+        });
+    }
     let parsed = reqwest::Url::parse(base_url).map_err(|_| {
         ConfigError::InvalidConfiguration(
             "ENCLAVE_RUNTIME_BASE_URL must be a valid URL".to_string(),
@@ -117,40 +128,92 @@ fn validate_non_local_runtime_base_url(base_url: &str) -> Result<(), ConfigError
     }
 
     Err(ConfigError::InvalidConfiguration(
-        "ENCLAVE_RUNTIME_BASE_URL must use https outside local environment unless it is loopback http".to_string(),
+        "ENCLAVE_RUNTIME_BASE_URL must use https outside local environment unless it is loopback http or vsock".to_string(),
     ))
 }
 
-pub(crate) fn parse_enclave_rpc_shared_secret(
+pub(crate) fn parse_enclave_rpc_security_mode() -> Result<EnclaveRpcSecurityMode, ConfigError> {
+    env::var("ENCLAVE_RPC_SECURITY_MODE")
+        .unwrap_or_else(|_| "signed".to_string())
+        .parse::<EnclaveRpcSecurityMode>()
+        .map_err(ConfigError::InvalidConfiguration)
+}
+
+/// Client certificate for mTLS modes. `reqwest` only applies TLS to https URLs, so plain http
+/// base URLs are rejected rather than silently skipping the handshake.
+pub(crate) fn parse_enclave_rpc_client_tls(
+    security_mode: EnclaveRpcSecurityMode,
+    enclave_runtime_base_url: &str,
+) -> Result<Option<EnclaveRpcClientTlsConfig>, ConfigError> {
+    if !security_mode.requires_mtls() {
+        return Ok(None);
+    }
+
+    let scheme = enclave_runtime_base_url
+        .split_once("://")
+        .map(|(scheme, _)| scheme.to_ascii_lowercase());
+    if !matches!(scheme.as_deref(), Some("https") | Some("vsock")) {
+        return Err(ConfigError::InvalidConfiguration(format!(
+            "ENCLAVE_RUNTIME_BASE_URL must use https or vsock when ENCLAVE_RPC_SECURITY_MODE={}",
+            security_mode.as_str()
+        )));
+    }
+
+    Ok(Some(EnclaveRpcClientTlsConfig {
+        client_cert_path: require_env("ENCLAVE_RPC_CLIENT_CERT_PATH")?.into(),
+        client_key_path: require_env("ENCLAVE_RPC_CLIENT_KEY_PATH")?.into(),
+    }))
+}
+
+/// Key this service signs enclave RPCs with; `None` when the security mode does not sign.
+/// Local runs fall back to a deterministic dev key the local enclave runtime already trusts.
+pub(crate) fn parse_enclave_rpc_signing_key(
     environment: AlfredEnvironment,
-) -> Result<String, ConfigError> {
-    if let Ok(value) = env::var("ENCLAVE_RPC_SHARED_SECRET") {
-        let trimmed = value.trim().to_string();
-        if trimmed.is_empty() {
-            return Err(ConfigError::MissingVar(
-                "ENCLAVE_RPC_SHARED_SECRET".to_string(),
-            ));
-        }
-        if trimmed.len() < 16 {
-            return Err(ConfigError::InvalidConfiguration(
-                "ENCLAVE_RPC_SHARED_SECRET must be at least 16 characters".to_string(),
-            ));
-        }
-        return Ok(trimmed);
+    security_mode: EnclaveRpcSecurityMode,
+    default_caller_id: &str,
+) -> Result<Option<EnclaveRpcSigningKey>, ConfigError> {
+    if !security_mode.requires_signature() {
+        return Ok(None);
+    }
+
+    let caller_id = optional_trimmed_env("ENCLAVE_RPC_CALLER_ID")
+        .unwrap_or_else(|| default_caller_id.to_string());
+    if caller_id.contains([':', ',']) {
+        return Err(ConfigError::InvalidConfiguration(
+            "ENCLAVE_RPC_CALLER_ID must not contain ':' or ','".to_string(),
+        ));
+    }
+
+    if let Some(encoded) = optional_trimmed_env("ENCLAVE_RPC_SIGNING_PRIVATE_KEY") {
+        let private_key = base64::engine::general_purpose::STANDARD
+            .decode(encoded.as_bytes())
+            .ok()
+            .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+            .ok_or_else(|| {
+                ConfigError::InvalidConfiguration(
+                    "ENCLAVE_RPC_SIGNING_PRIVATE_KEY must be a base64 32-byte Ed25519 key"
+                        .to_string(),
+                )
+            })?;
+        return Ok(Some(EnclaveRpcSigningKey::new(caller_id, private_key)));
     }
 
     if matches!(environment, AlfredEnvironment::Local) {
-        return Ok("local-dev-enclave-rpc-secret".to_string());
+        return Ok(Some(EnclaveRpcSigningKey::local_dev(&caller_id)));
     }
 
     Err(ConfigError::MissingVar(
-        "ENCLAVE_RPC_SHARED_SECRET".to_string(),
+        "ENCLAVE_RPC_SIGNING_PRIVATE_KEY".to_string(),
     ))
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{validate_non_local_enclave_security_posture, validate_non_local_runtime_base_url};
+    use super::{
+        parse_enclave_rpc_client_tls, parse_enclave_rpc_signing_key,
+        validate_non_local_enclave_security_posture, validate_non_local_runtime_base_url,
+    };
+    use crate::enclave::{EnclaveRpcCallerKeys, EnclaveRpcSecurityMode, SignedRpcRequest};
     use crate::enclave_runtime::AlfredEnvironment;
 
     fn prod_measurements() -> Vec<String> {
@@ -219,5 +282,65 @@ mod tests {
             .expect("https runtime URL should pass");
         validate_non_local_runtime_base_url("http://127.0.0.1:8181")
             .expect("loopback runtime URL should pass");
+        validate_non_local_runtime_base_url("vsock://16:8181")
+            .expect("vsock runtime URL should pass");
+    }
+
+    #[test]
+    fn non_local_rejects_malformed_vsock_runtime_url() {
+        let err = validate_non_local_runtime_base_url("vsock://parent:8181")
+            .expect_err("vsock URL needs a numeric CID");
+
+        assert!(err.to_string().contains("ENCLAVE_RUNTIME_BASE_URL"));
+    }
+
+    #[test]
+    fn mtls_modes_reject_plain_http_runtime_url() {
+        let err = parse_enclave_rpc_client_tls(
+            EnclaveRpcSecurityMode::SignedAndMtls,
+            "http://127.0.0.1:8181",
+        )
+        .expect_err("mtls cannot run over plain http");
+
+        assert!(err.to_string().contains("ENCLAVE_RUNTIME_BASE_URL"));
+        assert!(
+            parse_enclave_rpc_client_tls(EnclaveRpcSecurityMode::Signed, "http://127.0.0.1:8181")
+                .expect("signed mode needs no client certificate")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn local_signing_key_matches_the_runtime_dev_registration() {
+        let key = parse_enclave_rpc_signing_key(
+            AlfredEnvironment::Local,
+            EnclaveRpcSecurityMode::Signed,
+            "worker",
+        )
+        .expect("local signing key should resolve")
+        .expect("signed mode should produce a signing key");
+        let request = SignedRpcRequest {
+            method: "POST",
+            path: "/v1/rpc/test",
+            timestamp: 1,
+            nonce: "nonce",
+            body: b"{}",
+        };
+        let signature = key.sign(request);
+
+        assert_eq!(key.caller_id(), "worker");
+        assert!(
+            EnclaveRpcCallerKeys::local_dev(&["api-server", "worker"])
+                .verify("worker", request, &signature,)
+        );
+        assert!(
+            parse_enclave_rpc_signing_key(
+                AlfredEnvironment::Production,
+                EnclaveRpcSecurityMode::Mtls,
+                "worker",
+            )
+            .expect("mtls mode needs no signing key")
+            .is_none()
+        );
     }
 }
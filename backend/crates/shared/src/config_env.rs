@@ -1,6 +1,8 @@
 use std::env;
 use std::net::IpAddr;
 
+use chrono::{DateTime, Utc};
+
 use crate::config::ConfigError;
 
 pub(crate) fn require_env(key: &str) -> Result<String, ConfigError> {
@@ -91,6 +93,20 @@ pub(crate) fn optional_trimmed_env(key: &str) -> Option<String> {
     })
 }
 
+pub(crate) fn parse_rfc3339_env(key: &str) -> Result<Option<DateTime<Utc>>, ConfigError> {
+    optional_trimmed_env(key)
+        .map(|raw| {
+            DateTime::parse_from_rfc3339(&raw)
+                .map(|timestamp| timestamp.with_timezone(&Utc))
+                .map_err(|_| {
+                    ConfigError::InvalidConfiguration(format!(
+                        "{key} must be an RFC 3339 timestamp"
+                    ))
+                })
+        })
+        .transpose()
+}
+
 fn parse_csv_list(raw: String) -> Vec<String> {
     let parsed = raw
         .split(',')
@@ -0,0 +1,39 @@
+use crate::config::ConfigError;
+use crate::config_env::{parse_bool_env, parse_u64_env};
+
+/// Device-bound request signing on authenticated mobile routes. Signed requests are always
+/// verified; `required` additionally rejects unsigned requests from users who have enrolled a
+/// signing key on any device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequestSigningConfig {
+    pub required: bool,
+    /// How far a signed timestamp may drift from server time; nonces are kept this long.
+    pub max_skew_seconds: u64,
+}
+
+impl Default for RequestSigningConfig {
+    fn default() -> Self {
+        Self {
+            required: false,
+            max_skew_seconds: 300,
+        }
+    }
+}
+
+pub(crate) fn parse_request_signing_config() -> Result<RequestSigningConfig, ConfigError> {
+    let defaults = RequestSigningConfig::default();
+    let max_skew_seconds = parse_u64_env(
+        "REQUEST_SIGNING_MAX_SKEW_SECONDS",
+        defaults.max_skew_seconds,
+    )?;
+    if max_skew_seconds == 0 {
+        return Err(ConfigError::InvalidConfiguration(
+            "REQUEST_SIGNING_MAX_SKEW_SECONDS must be greater than 0".to_string(),
+        ));
+    }
+
+    Ok(RequestSigningConfig {
+        required: parse_bool_env("REQUEST_SIGNING_REQUIRED", defaults.required)?,
+        max_skew_seconds,
+    })
+}
@@ -0,0 +1,72 @@
+use crate::config::ConfigError;
+use crate::config_env::{optional_trimmed_env, parse_bool_env, parse_u64_env};
+use crate::enclave_runtime::AlfredEnvironment;
+
+/// Hardening headers the api-server adds to its responses. An `hsts_max_age_seconds` of `0`
+/// sends no `Strict-Transport-Security`, which local runs over plain HTTP rely on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SecurityHeadersConfig {
+    pub hsts_max_age_seconds: u64,
+    pub hsts_include_subdomains: bool,
+    /// One of [`REFERRER_POLICIES`].
+    pub referrer_policy: &'static str,
+}
+
+impl Default for SecurityHeadersConfig {
+    fn default() -> Self {
+        Self {
+            hsts_max_age_seconds: 31_536_000,
+            hsts_include_subdomains: false,
+            referrer_policy: "no-referrer",
+        }
+    }
+}
+
+pub const REFERRER_POLICIES: [&str; 8] = [
+    "no-referrer",
+    "no-referrer-when-downgrade",
+    "origin",
+    "origin-when-cross-origin",
+    "same-origin",
+    "strict-origin",
+    "strict-origin-when-cross-origin",
+    "unsafe-url",
+];
+
+pub(crate) fn parse_security_headers_config(
+    environment: AlfredEnvironment,
+) -> Result<SecurityHeadersConfig, ConfigError> {
+    let defaults = SecurityHeadersConfig::default();
+    let default_hsts_max_age_seconds = match environment {
+        AlfredEnvironment::Local => 0,
+        _ => defaults.hsts_max_age_seconds,
+    };
+    let hsts_max_age_seconds =
+        parse_u64_env("API_HSTS_MAX_AGE_SECONDS", default_hsts_max_age_seconds)?;
+    if hsts_max_age_seconds == 0 && !matches!(environment, AlfredEnvironment::Local) {
+        return Err(ConfigError::InvalidConfiguration(
+            "API_HSTS_MAX_AGE_SECONDS must be greater than 0 outside local".to_string(),
+        ));
+    }
+
+    let referrer_policy = match optional_trimmed_env("API_REFERRER_POLICY") {
+        Some(raw) => REFERRER_POLICIES
+            .into_iter()
+            .find(|policy| policy.eq_ignore_ascii_case(&raw))
+            .ok_or_else(|| {
+                ConfigError::InvalidConfiguration(format!(
+                    "API_REFERRER_POLICY is not a Referrer-Policy value: {raw}"
+                ))
+            })?,
+        None => defaults.referrer_policy,
+    };
+
+    Ok(SecurityHeadersConfig {
+        hsts_max_age_seconds,
+        hsts_include_subdomains: parse_bool_env(
+            "API_HSTS_INCLUDE_SUBDOMAINS",
+            defaults.hsts_include_subdomains,
+        )?,
+        referrer_policy,
+    })
+}
@@ -0,0 +1,136 @@
+pub const HEALTHY_CONNECTOR_SCORE: i16 = 100;
+pub const DEFAULT_REAUTH_NUDGE_THRESHOLD: i16 = 60;
+
+/// Scopes Alfred needs from a Google connector; anything missing is a scope gap.
+pub const REQUIRED_GOOGLE_SCOPES: [&str; 2] = [
+    "https://www.googleapis.com/auth/gmail.readonly",
+    "https://www.googleapis.com/auth/calendar.readonly",
+];
+
+/// Optional write scopes a Google connector can add later through incremental consent.
+pub const UPGRADEABLE_GOOGLE_SCOPES: [&str; 2] = [
+    "https://www.googleapis.com/auth/gmail.send",
+    "https://www.googleapis.com/auth/calendar.events",
+];
+
+const REFRESH_FAILURE_PENALTY: i32 = 40;
+const PROVIDER_UNAUTHORIZED_PENALTY: i32 = 20;
+const MISSING_SCOPE_PENALTY: i32 = 45;
+
+/// Outcome of a connector-backed provider call, as observed by the enclave.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectorHealthSignal {
+    /// The call succeeded end to end; failure counters reset.
+    Healthy,
+    /// Google rejected the refresh token exchange.
+    RefreshFailed,
+    /// A Google API rejected a freshly minted access token with 401.
+    ProviderUnauthorized,
+}
+
+impl ConnectorHealthSignal {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Healthy => "healthy",
+            Self::RefreshFailed => "refresh_failed",
+            Self::ProviderUnauthorized => "provider_unauthorized",
+        }
+    }
+}
+
+/// Consecutive failure counters and scope gaps persisted on the connector row.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConnectorHealthState {
+    pub refresh_failure_count: i32,
+    pub provider_unauthorized_count: i32,
+    pub missing_scopes: Vec<String>,
+}
+
+impl ConnectorHealthState {
+    pub fn for_scopes(granted_scopes: &[String]) -> Self {
+        Self {
+            missing_scopes: missing_google_scopes(granted_scopes),
+            ..Self::default()
+        }
+    }
+
+    pub fn apply(&self, signal: ConnectorHealthSignal) -> Self {
+        let mut next = self.clone();
+        match signal {
+            ConnectorHealthSignal::Healthy => {
+                next.refresh_failure_count = 0;
+                next.provider_unauthorized_count = 0;
+            }
+            ConnectorHealthSignal::RefreshFailed => {
+                next.refresh_failure_count = next.refresh_failure_count.saturating_add(1);
+            }
+            ConnectorHealthSignal::ProviderUnauthorized => {
+                next.provider_unauthorized_count =
+                    next.provider_unauthorized_count.saturating_add(1);
+            }
+        }
+        next
+    }
+
+    /// Scores from 100 (healthy) down to 0. A rejected refresh token weighs most because it
+    /// never recovers without the user; a missing scope alone is enough to warrant a nudge.
+    pub fn score(&self) -> i16 {
+        let missing_scopes = i32::try_from(self.missing_scopes.len()).unwrap_or(i32::MAX);
+        let penalty = self
+            .refresh_failure_count
+            .saturating_mul(REFRESH_FAILURE_PENALTY)
+            .saturating_add(
+                self.provider_unauthorized_count
+                    .saturating_mul(PROVIDER_UNAUTHORIZED_PENALTY),
+            )
+            .saturating_add(missing_scopes.saturating_mul(MISSING_SCOPE_PENALTY));
+
+        (i32::from(HEALTHY_CONNECTOR_SCORE) - penalty).clamp(0, 100) as i16
+    }
+}
+
+pub fn missing_google_scopes(granted_scopes: &[String]) -> Vec<String> {
+    REQUIRED_GOOGLE_SCOPES
+        .iter()
+        .filter(|required| !granted_scopes.iter().any(|granted| granted == *required))
+        .map(|required| (*required).to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ConnectorHealthSignal, ConnectorHealthState, DEFAULT_REAUTH_NUDGE_THRESHOLD,
+        REQUIRED_GOOGLE_SCOPES,
+    };
+
+    #[test]
+    fn failures_lower_the_score_and_success_resets_counters() {
+        let granted = REQUIRED_GOOGLE_SCOPES
+            .iter()
+            .map(|scope| (*scope).to_string())
+            .collect::<Vec<_>>();
+        let healthy = ConnectorHealthState::for_scopes(&granted);
+        assert_eq!(healthy.score(), 100);
+
+        let once = healthy.apply(ConnectorHealthSignal::RefreshFailed);
+        assert_eq!(once.score(), 60);
+        assert!(once.score() >= DEFAULT_REAUTH_NUDGE_THRESHOLD);
+
+        let twice = once
+            .apply(ConnectorHealthSignal::ProviderUnauthorized)
+            .apply(ConnectorHealthSignal::RefreshFailed);
+        assert_eq!(twice.score(), 0);
+
+        assert_eq!(twice.apply(ConnectorHealthSignal::Healthy), healthy);
+    }
+
+    #[test]
+    fn scope_gaps_persist_across_successful_calls() {
+        let state = ConnectorHealthState::for_scopes(&[REQUIRED_GOOGLE_SCOPES[1].to_string()]);
+
+        assert_eq!(state.missing_scopes, vec![REQUIRED_GOOGLE_SCOPES[0]]);
+        assert_eq!(state.apply(ConnectorHealthSignal::Healthy).score(), 55);
+        assert!(state.score() < DEFAULT_REAUTH_NUDGE_THRESHOLD);
+    }
+}
@@ -0,0 +1,136 @@
+use chrono::{DateTime, Duration, Utc};
+
+use crate::automation_schedule::{AutomationScheduleSpec, AutomationScheduleType, next_run_after};
+
+pub const MIN_TRAVEL_MINUTES: u16 = 1;
+pub const MAX_TRAVEL_MINUTES: u16 = 240;
+pub const MAX_BUFFER_MINUTES: u16 = 120;
+/// How long before the leave-by time the departure alert is delivered.
+pub const DEPARTURE_ALERT_LEAD_MINUTES: i64 = 10;
+/// Upper bound between re-checks of the day's first in-person meeting, so moved or
+/// cancelled meetings are reconciled before the alert fires.
+pub const DEPARTURE_RECHECK_INTERVAL_MINUTES: i64 = 30;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DepartureAlertSettings {
+    pub enabled: bool,
+    pub time_zone: String,
+    /// Local time of the first daily check, in minutes after midnight.
+    pub check_local_time_minutes: u16,
+    pub travel_minutes: u16,
+    pub buffer_minutes: u16,
+}
+
+impl DepartureAlertSettings {
+    /// Next daily check strictly after `reference_utc`.
+    pub fn next_daily_check_after(&self, reference_utc: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        next_run_after(
+            reference_utc,
+            &AutomationScheduleSpec {
+                schedule_type: AutomationScheduleType::Daily,
+                time_zone: self.time_zone.clone(),
+                local_time_minutes: self.check_local_time_minutes,
+                anchor_days_of_week: None,
+                anchor_day_of_month: None,
+                anchor_month: None,
+                cron_expression: None,
+                run_at: None,
+            },
+        )
+    }
+}
+
+pub fn validate_departure_minutes(
+    travel_minutes: u16,
+    buffer_minutes: u16,
+) -> Result<(), &'static str> {
+    if !(MIN_TRAVEL_MINUTES..=MAX_TRAVEL_MINUTES).contains(&travel_minutes) {
+        return Err("travel_minutes must be between 1 and 240");
+    }
+    if buffer_minutes > MAX_BUFFER_MINUTES {
+        return Err("buffer_minutes must be between 0 and 120");
+    }
+    Ok(())
+}
+
+pub fn departure_leave_by(
+    meeting_start_at: DateTime<Utc>,
+    travel_minutes: u16,
+    buffer_minutes: u16,
+) -> DateTime<Utc> {
+    meeting_start_at - Duration::minutes(i64::from(travel_minutes) + i64::from(buffer_minutes))
+}
+
+pub fn is_departure_alert_due(now: DateTime<Utc>, leave_by: DateTime<Utc>) -> bool {
+    now >= leave_by - Duration::minutes(DEPARTURE_ALERT_LEAD_MINUTES)
+}
+
+/// Picks when to look at the meeting again. Before the alert fires the check lands on the
+/// alert time at the latest; afterwards checks continue until the meeting starts so a moved
+/// meeting produces a corrected alert.
+pub fn next_departure_recheck_at(
+    now: DateTime<Utc>,
+    leave_by: DateTime<Utc>,
+    meeting_start_at: DateTime<Utc>,
+    alert_sent: bool,
+) -> Option<DateTime<Utc>> {
+    if now >= meeting_start_at {
+        return None;
+    }
+
+    let interval = now + Duration::minutes(DEPARTURE_RECHECK_INTERVAL_MINUTES);
+    if !alert_sent {
+        let alert_at = leave_by - Duration::minutes(DEPARTURE_ALERT_LEAD_MINUTES);
+        return Some(alert_at.max(now).min(interval));
+    }
+
+    (interval < meeting_start_at).then_some(interval)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+
+    #[test]
+    fn leave_by_subtracts_travel_and_buffer() {
+        let start = Utc.with_ymd_and_hms(2026, 3, 2, 9, 0, 0).unwrap();
+        assert_eq!(
+            departure_leave_by(start, 25, 5),
+            Utc.with_ymd_and_hms(2026, 3, 2, 8, 30, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn recheck_waits_for_alert_time_when_it_is_sooner_than_interval() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 2, 8, 0, 0).unwrap();
+        let leave_by = Utc.with_ymd_and_hms(2026, 3, 2, 8, 20, 0).unwrap();
+        let start = Utc.with_ymd_and_hms(2026, 3, 2, 9, 0, 0).unwrap();
+
+        assert_eq!(
+            next_departure_recheck_at(now, leave_by, start, false),
+            Some(Utc.with_ymd_and_hms(2026, 3, 2, 8, 10, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn recheck_stops_once_meeting_is_about_to_start() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 2, 8, 40, 0).unwrap();
+        let leave_by = Utc.with_ymd_and_hms(2026, 3, 2, 8, 20, 0).unwrap();
+        let start = Utc.with_ymd_and_hms(2026, 3, 2, 9, 0, 0).unwrap();
+
+        assert_eq!(next_departure_recheck_at(now, leave_by, start, true), None);
+        assert_eq!(
+            next_departure_recheck_at(start, leave_by, start, false),
+            None
+        );
+    }
+
+    #[test]
+    fn validate_departure_minutes_enforces_bounds() {
+        assert!(validate_departure_minutes(30, 10).is_ok());
+        assert!(validate_departure_minutes(0, 10).is_err());
+        assert!(validate_departure_minutes(30, 121).is_err());
+    }
+}
@@ -1,39 +1,73 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
 use chrono::Utc;
 
+use crate::brief_profile::MorningBriefProfile;
+
+mod assistant;
+mod audit;
+mod connectors;
 mod conversions;
 
+use super::tls::{EnclaveRpcTls, EnclaveTlsPins};
+use super::transport::{
+    EnclaveHttpClient, EnclaveRequest, EnclaveRequestMethod, EnclaveTransportError,
+    send_enclave_request,
+};
+use crate::security::SecretRuntime;
+
 use super::{
-    AutomationRecipientDevice, CompleteGoogleConnectResponse, ENCLAVE_RPC_AUTH_NONCE_HEADER,
-    ENCLAVE_RPC_AUTH_SIGNATURE_HEADER, ENCLAVE_RPC_AUTH_TIMESTAMP_HEADER,
-    ENCLAVE_RPC_CONTRACT_VERSION, ENCLAVE_RPC_CONTRACT_VERSION_HEADER,
-    ENCLAVE_RPC_PATH_COMPLETE_GOOGLE_CONNECT, ENCLAVE_RPC_PATH_EXCHANGE_GOOGLE_TOKEN,
-    ENCLAVE_RPC_PATH_EXECUTE_AUTOMATION, ENCLAVE_RPC_PATH_FETCH_ASSISTANT_ATTESTED_KEY,
-    ENCLAVE_RPC_PATH_FETCH_GOOGLE_CALENDAR_EVENTS,
+    CompleteCaldavConnectResponse, CompleteGoogleConnectResponse, CompleteImapConnectResponse,
+    DepartureAlertPlan, DraftAutomationResponse, ENCLAVE_RPC_AUTH_CALLER_HEADER,
+    ENCLAVE_RPC_AUTH_NONCE_HEADER, ENCLAVE_RPC_AUTH_SIGNATURE_HEADER,
+    ENCLAVE_RPC_AUTH_TIMESTAMP_HEADER, ENCLAVE_RPC_CONTRACT_VERSION,
+    ENCLAVE_RPC_CONTRACT_VERSION_HEADER, ENCLAVE_RPC_PATH_COMPLETE_GOOGLE_CONNECT,
+    ENCLAVE_RPC_PATH_EXCHANGE_GOOGLE_TOKEN, ENCLAVE_RPC_PATH_EXECUTE_AUTOMATION,
+    ENCLAVE_RPC_PATH_FETCH_ASSISTANT_ATTESTED_KEY, ENCLAVE_RPC_PATH_FETCH_GOOGLE_CALENDAR_EVENTS,
     ENCLAVE_RPC_PATH_FETCH_GOOGLE_URGENT_EMAIL_CANDIDATES, ENCLAVE_RPC_PATH_GENERATE_MORNING_BRIEF,
     ENCLAVE_RPC_PATH_GENERATE_URGENT_EMAIL_SUMMARY, ENCLAVE_RPC_PATH_PROCESS_ASSISTANT_QUERY,
-    ENCLAVE_RPC_PATH_REVOKE_GOOGLE_TOKEN, EnclaveRpcAuthConfig,
-    EnclaveRpcCompleteGoogleConnectRequest, EnclaveRpcCompleteGoogleConnectResponse,
-    EnclaveRpcError, EnclaveRpcErrorEnvelope, EnclaveRpcExchangeGoogleTokenRequest,
-    EnclaveRpcExchangeGoogleTokenResponse, EnclaveRpcExecuteAutomationRequest,
-    EnclaveRpcExecuteAutomationResponse, EnclaveRpcFetchAssistantAttestedKeyRequest,
+    ENCLAVE_RPC_PATH_REVOKE_GOOGLE_TOKEN, EnclaveDepartureAlertStatus, EnclaveRpcAuthConfig,
+    EnclaveRpcCompleteCaldavConnectResponse, EnclaveRpcCompleteGoogleConnectRequest,
+    EnclaveRpcCompleteGoogleConnectResponse, EnclaveRpcCompleteImapConnectResponse,
+    EnclaveRpcDraftAutomationResponse, EnclaveRpcError, EnclaveRpcErrorEnvelope,
+    EnclaveRpcExchangeGoogleTokenRequest, EnclaveRpcExchangeGoogleTokenResponse,
+    EnclaveRpcExecuteAutomationRequest, EnclaveRpcExecuteAutomationResponse,
+    EnclaveRpcExportAssistantSessionsResponse, EnclaveRpcFetchAssistantAttestedKeyRequest,
     EnclaveRpcFetchAssistantAttestedKeyResponse, EnclaveRpcFetchGoogleCalendarEventsRequest,
     EnclaveRpcFetchGoogleCalendarEventsResponse, EnclaveRpcFetchGoogleUrgentEmailCandidatesRequest,
     EnclaveRpcFetchGoogleUrgentEmailCandidatesResponse, EnclaveRpcGenerateMorningBriefRequest,
     EnclaveRpcGenerateMorningBriefResponse, EnclaveRpcGenerateUrgentEmailSummaryRequest,
-    EnclaveRpcGenerateUrgentEmailSummaryResponse, EnclaveRpcProcessAssistantQueryRequest,
-    EnclaveRpcProcessAssistantQueryResponse, EnclaveRpcRevokeGoogleTokenRequest,
-    EnclaveRpcRevokeGoogleTokenResponse, ExchangeGoogleTokenResponse, ExecuteAutomationResponse,
-    FetchAssistantAttestedKeyResponse, FetchGoogleCalendarEventsResponse,
-    FetchGoogleUrgentEmailCandidatesResponse, GenerateMorningBriefResponse,
-    GenerateUrgentEmailSummaryResponse, ProcessAssistantQueryResponse, ProviderOperation,
-    RevokeGoogleTokenResponse, sign_rpc_request,
+    EnclaveRpcGenerateUrgentEmailSummaryResponse, EnclaveRpcPlanDepartureAlertResponse,
+    EnclaveRpcProcessAssistantQueryRequest, EnclaveRpcProcessAssistantQueryResponse,
+    EnclaveRpcRevokeGoogleTokenRequest, EnclaveRpcRevokeGoogleTokenResponse,
+    ExchangeGoogleTokenResponse, ExecuteAutomationRequest, ExecuteAutomationResponse,
+    ExportAssistantSessionsResponse, FetchAssistantAttestedKeyResponse,
+    FetchGoogleCalendarEventsResponse, FetchGoogleUrgentEmailCandidatesResponse,
+    GenerateMorningBriefResponse, GenerateUrgentEmailSummaryResponse, PlanDepartureAlertResponse,
+    ProcessAssistantQueryResponse, ProviderOperation, RevokeGoogleTokenResponse, SignedRpcRequest,
 };
 
+/// Told about every RPC a client sends, once the call has resolved either way.
+pub trait EnclaveRpcObserver: Send + Sync {
+    fn rpc_completed(&self, path: &str, succeeded: bool, elapsed: Duration);
+}
+
 #[derive(Clone)]
 pub struct EnclaveRpcClient {
     base_url: String,
     auth: EnclaveRpcAuthConfig,
-    http_client: reqwest::Client,
+    http_client: EnclaveHttpClient,
+    observer: Option<Arc<dyn EnclaveRpcObserver>>,
+    mtls: Option<MtlsBinding>,
+}
+
+/// Pins the runtime's certificate from attestation before the first RPC and again whenever the
+/// runtime presents a certificate that is not pinned, which is how rotations are picked up.
+#[derive(Clone)]
+struct MtlsBinding {
+    pins: EnclaveTlsPins,
+    attestation: SecretRuntime,
 }
 
 impl EnclaveRpcClient {
@@ -41,10 +75,28 @@ impl EnclaveRpcClient {
         Self {
             base_url,
             auth,
-            http_client,
+            http_client: http_client.into(),
+            observer: None,
+            mtls: None,
         }
     }
 
+    pub fn with_observer(mut self, observer: Arc<dyn EnclaveRpcObserver>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Sends RPCs over mutual TLS. `attestation` must reach the runtime through
+    /// [`EnclaveRpcTls::attestation_client`].
+    pub fn with_mtls(mut self, tls: &EnclaveRpcTls, attestation: SecretRuntime) -> Self {
+        self.http_client = tls.rpc_client();
+        self.mtls = Some(MtlsBinding {
+            pins: tls.pins().clone(),
+            attestation,
+        });
+        self
+    }
+
     pub async fn exchange_google_access_token(
         &self,
         request: super::ConnectorSecretRequest,
@@ -260,22 +312,19 @@ impl EnclaveRpcClient {
 
     pub async fn execute_automation_run(
         &self,
-        user_id: uuid::Uuid,
-        automation_rule_id: uuid::Uuid,
-        automation_run_id: uuid::Uuid,
-        scheduled_for: chrono::DateTime<chrono::Utc>,
-        prompt_envelope: crate::models::AutomationPromptEnvelope,
-        recipient_devices: Vec<AutomationRecipientDevice>,
+        request: ExecuteAutomationRequest,
     ) -> Result<ExecuteAutomationResponse, EnclaveRpcError> {
         let payload = EnclaveRpcExecuteAutomationRequest {
             contract_version: ENCLAVE_RPC_CONTRACT_VERSION.to_string(),
             request_id: uuid::Uuid::new_v4().to_string(),
-            user_id,
-            automation_rule_id,
-            automation_run_id,
-            scheduled_for,
-            prompt_envelope,
-            recipient_devices: recipient_devices
+            job_id: request.job_id,
+            user_id: request.user_id,
+            automation_rule_id: request.automation_rule_id,
+            automation_run_id: request.automation_run_id,
+            scheduled_for: request.scheduled_for,
+            prompt_envelope: request.prompt_envelope,
+            recipient_devices: request
+                .recipient_devices
                 .into_iter()
                 .map(|device| super::EnclaveAutomationRecipientDevice {
                     device_id: device.device_id,
@@ -284,6 +333,19 @@ impl EnclaveRpcClient {
                     public_key: device.public_key,
                 })
                 .collect(),
+            template: request
+                .template
+                .map(|template| super::EnclaveAutomationTemplateRequest {
+                    template: template.template,
+                    time_zone: template.time_zone,
+                    brief_profile: template.brief_profile,
+                }),
+            condition: request.condition.map(|condition| {
+                super::EnclaveAutomationConditionRequest {
+                    condition: condition.condition,
+                    time_zone: condition.time_zone,
+                }
+            }),
         };
 
         let response: EnclaveRpcExecuteAutomationResponse = self
@@ -309,6 +371,7 @@ impl EnclaveRpcClient {
         connector: super::ConnectorSecretRequest,
         time_zone: String,
         morning_brief_local_time: String,
+        brief_profile: Option<MorningBriefProfile>,
     ) -> Result<GenerateMorningBriefResponse, EnclaveRpcError> {
         let payload = EnclaveRpcGenerateMorningBriefRequest {
             contract_version: ENCLAVE_RPC_CONTRACT_VERSION.to_string(),
@@ -317,6 +380,7 @@ impl EnclaveRpcClient {
             connector,
             time_zone,
             morning_brief_local_time,
+            brief_profile,
         };
 
         let response: EnclaveRpcGenerateMorningBriefResponse = self
@@ -373,6 +437,53 @@ impl EnclaveRpcClient {
         path: &str,
         payload: &Req,
     ) -> Result<Res, EnclaveRpcError>
+    where
+        Req: serde::Serialize,
+        Res: serde::de::DeserializeOwned,
+    {
+        let started = Instant::now();
+        let result = self
+            .dispatch_pinned_enclave_rpc(operation, path, payload)
+            .await;
+        if let Some(observer) = self.observer.as_ref() {
+            observer.rpc_completed(path, result.is_ok(), started.elapsed());
+        }
+        result
+    }
+
+    async fn dispatch_pinned_enclave_rpc<Req, Res>(
+        &self,
+        operation: ProviderOperation,
+        path: &str,
+        payload: &Req,
+    ) -> Result<Res, EnclaveRpcError>
+    where
+        Req: serde::Serialize,
+        Res: serde::de::DeserializeOwned,
+    {
+        let Some(mtls) = self.mtls.as_ref() else {
+            return self.dispatch_enclave_rpc(operation, path, payload).await;
+        };
+
+        if mtls.pins.is_empty() {
+            pin_attested_certificate(mtls).await?;
+        }
+        let result = self.dispatch_enclave_rpc(operation, path, payload).await;
+        if matches!(result, Err(EnclaveRpcError::RpcTransportUnavailable { .. }))
+            && mtls.pins.take_unpinned_rejection()
+        {
+            pin_attested_certificate(mtls).await?;
+            return self.dispatch_enclave_rpc(operation, path, payload).await;
+        }
+        result
+    }
+
+    async fn dispatch_enclave_rpc<Req, Res>(
+        &self,
+        operation: ProviderOperation,
+        path: &str,
+        payload: &Req,
+    ) -> Result<Res, EnclaveRpcError>
     where
         Req: serde::Serialize,
         Res: serde::de::DeserializeOwned,
@@ -382,47 +493,53 @@ impl EnclaveRpcClient {
                 message: format!("failed to serialize enclave rpc payload: {err}"),
             })?;
 
-        let timestamp = Utc::now().timestamp();
-        let nonce = uuid::Uuid::new_v4().simple().to_string();
-        let signature = sign_rpc_request(
-            &self.auth.shared_secret,
-            "POST",
-            path,
-            timestamp,
-            &nonce,
-            &body,
-        );
-
-        let url = format!("{}{}", self.base_url.trim_end_matches('/'), path);
-        let response = self
-            .http_client
-            .post(url)
-            .header(
-                ENCLAVE_RPC_CONTRACT_VERSION_HEADER,
-                ENCLAVE_RPC_CONTRACT_VERSION,
-            )
-            .header(ENCLAVE_RPC_AUTH_TIMESTAMP_HEADER, timestamp.to_string())
-            .header(ENCLAVE_RPC_AUTH_NONCE_HEADER, nonce)
-            .header(ENCLAVE_RPC_AUTH_SIGNATURE_HEADER, signature)
-            .header(reqwest::header::CONTENT_TYPE, "application/json")
-            .body(body)
-            .send()
-            .await
-            .map_err(|err| EnclaveRpcError::RpcTransportUnavailable {
-                message: format!(
-                    "{err} (is_timeout={}, is_connect={})",
-                    err.is_timeout(),
-                    err.is_connect()
-                ),
-            })?;
-
-        let status = response.status().as_u16();
-        let bytes = response
-            .bytes()
-            .await
-            .map_err(|err| EnclaveRpcError::RpcResponseInvalid {
-                message: format!("failed to read enclave rpc response body: {err}"),
-            })?;
+        let mut headers = vec![(
+            ENCLAVE_RPC_CONTRACT_VERSION_HEADER,
+            ENCLAVE_RPC_CONTRACT_VERSION.to_string(),
+        )];
+        // mTLS-only deployments do not sign; the channel authenticates the caller.
+        if let Some(signing_key) = self.auth.signing_key.as_ref() {
+            let timestamp = Utc::now().timestamp();
+            let nonce = uuid::Uuid::new_v4().simple().to_string();
+            let signature = signing_key.sign(SignedRpcRequest {
+                method: "POST",
+                path,
+                timestamp,
+                nonce: &nonce,
+                body: &body,
+            });
+            headers.push((
+                ENCLAVE_RPC_AUTH_CALLER_HEADER,
+                signing_key.caller_id().to_string(),
+            ));
+            headers.push((ENCLAVE_RPC_AUTH_TIMESTAMP_HEADER, timestamp.to_string()));
+            headers.push((ENCLAVE_RPC_AUTH_NONCE_HEADER, nonce));
+            headers.push((ENCLAVE_RPC_AUTH_SIGNATURE_HEADER, signature));
+        }
+        headers.push(("content-type", "application/json".to_string()));
+
+        let response = send_enclave_request(
+            &self.base_url,
+            &self.http_client,
+            EnclaveRequest {
+                method: EnclaveRequestMethod::Post,
+                path,
+                headers,
+                body: Some(body),
+                timeout: None,
+            },
+        )
+        .await
+        .map_err(|err| match err {
+            EnclaveTransportError::Send(message) => {
+                EnclaveRpcError::RpcTransportUnavailable { message }
+            }
+            EnclaveTransportError::Body(message) => EnclaveRpcError::RpcResponseInvalid {
+                message: format!("failed to read enclave rpc response body: {message}"),
+            },
+        })?;
+        let status = response.status;
+        let bytes = response.body;
 
         if (200..300).contains(&status) {
             let parsed = serde_json::from_slice::<Res>(&bytes).map_err(|err| {
@@ -455,3 +572,15 @@ impl EnclaveRpcClient {
         ))
     }
 }
+
+async fn pin_attested_certificate(mtls: &MtlsBinding) -> Result<(), EnclaveRpcError> {
+    let fingerprint = mtls
+        .attestation
+        .attest_enclave_tls_certificate()
+        .await
+        .map_err(|err| EnclaveRpcError::RpcTransportUnavailable {
+            message: format!("failed to attest enclave runtime tls certificate: {err}"),
+        })?;
+    mtls.pins.pin(fingerprint);
+    Ok(())
+}
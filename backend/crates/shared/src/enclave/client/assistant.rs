@@ -0,0 +1,125 @@
+use super::super::{
+    DraftAutomationRequest, DraftAutomationResponse, ENCLAVE_RPC_CONTRACT_VERSION,
+    ENCLAVE_RPC_PATH_DRAFT_AUTOMATION, ENCLAVE_RPC_PATH_EXPORT_ASSISTANT_SESSIONS,
+    ENCLAVE_RPC_PATH_PLAN_DEPARTURE_ALERT, EnclaveAutomationRecipientDevice,
+    EnclaveRpcDraftAutomationRequest, EnclaveRpcDraftAutomationResponse, EnclaveRpcError,
+    EnclaveRpcExportAssistantSessionsRequest, EnclaveRpcExportAssistantSessionsResponse,
+    EnclaveRpcPlanDepartureAlertRequest, EnclaveRpcPlanDepartureAlertResponse,
+    ExportAssistantSessionsRequest, ExportAssistantSessionsResponse, PlanDepartureAlertRequest,
+    PlanDepartureAlertResponse, ProviderOperation,
+};
+use super::EnclaveRpcClient;
+
+impl EnclaveRpcClient {
+    pub async fn plan_departure_alert(
+        &self,
+        request: PlanDepartureAlertRequest,
+    ) -> Result<PlanDepartureAlertResponse, EnclaveRpcError> {
+        let payload = EnclaveRpcPlanDepartureAlertRequest {
+            contract_version: ENCLAVE_RPC_CONTRACT_VERSION.to_string(),
+            request_id: uuid::Uuid::new_v4().to_string(),
+            job_id: request.job_id,
+            user_id: request.user_id,
+            time_zone: request.time_zone,
+            travel_minutes: request.travel_minutes,
+            buffer_minutes: request.buffer_minutes,
+            home_location_envelope: request.home_location_envelope,
+            recipient_devices: request
+                .recipient_devices
+                .into_iter()
+                .map(|device| EnclaveAutomationRecipientDevice {
+                    device_id: device.device_id,
+                    key_id: device.key_id,
+                    algorithm: device.algorithm,
+                    public_key: device.public_key,
+                })
+                .collect(),
+        };
+
+        let response: EnclaveRpcPlanDepartureAlertResponse = self
+            .send_enclave_rpc(
+                ProviderOperation::AssistantDepartureAlert,
+                ENCLAVE_RPC_PATH_PLAN_DEPARTURE_ALERT,
+                &payload,
+            )
+            .await?;
+
+        if response.request_id != payload.request_id {
+            return Err(EnclaveRpcError::RpcResponseInvalid {
+                message: "enclave rpc response request_id mismatch for departure alert".to_string(),
+            });
+        }
+
+        response.try_into()
+    }
+
+    pub async fn export_assistant_sessions(
+        &self,
+        request: ExportAssistantSessionsRequest,
+    ) -> Result<ExportAssistantSessionsResponse, EnclaveRpcError> {
+        let payload = EnclaveRpcExportAssistantSessionsRequest {
+            contract_version: ENCLAVE_RPC_CONTRACT_VERSION.to_string(),
+            request_id: uuid::Uuid::new_v4().to_string(),
+            user_id: request.user_id,
+            export_request_id: request.export_request_id,
+            client_ephemeral_public_key: request.client_ephemeral_public_key,
+            sessions: request.sessions,
+        };
+
+        let response: EnclaveRpcExportAssistantSessionsResponse = self
+            .send_enclave_rpc(
+                ProviderOperation::AssistantSessionExport,
+                ENCLAVE_RPC_PATH_EXPORT_ASSISTANT_SESSIONS,
+                &payload,
+            )
+            .await?;
+
+        if response.request_id != payload.request_id {
+            return Err(EnclaveRpcError::RpcResponseInvalid {
+                message: "enclave rpc response request_id mismatch for session export".to_string(),
+            });
+        }
+        if response.envelope.request_id != payload.export_request_id {
+            return Err(EnclaveRpcError::RpcResponseInvalid {
+                message: "session export envelope is bound to a different request_id".to_string(),
+            });
+        }
+
+        response.try_into()
+    }
+
+    pub async fn draft_automation(
+        &self,
+        request: DraftAutomationRequest,
+    ) -> Result<DraftAutomationResponse, EnclaveRpcError> {
+        let payload = EnclaveRpcDraftAutomationRequest {
+            contract_version: ENCLAVE_RPC_CONTRACT_VERSION.to_string(),
+            request_id: uuid::Uuid::new_v4().to_string(),
+            user_id: request.user_id,
+            time_zone: request.time_zone,
+            prompt_envelope: request.prompt_envelope,
+        };
+
+        let response: EnclaveRpcDraftAutomationResponse = self
+            .send_enclave_rpc(
+                ProviderOperation::AssistantAutomationDraft,
+                ENCLAVE_RPC_PATH_DRAFT_AUTOMATION,
+                &payload,
+            )
+            .await?;
+
+        if response.request_id != payload.request_id {
+            return Err(EnclaveRpcError::RpcResponseInvalid {
+                message: "enclave rpc response request_id mismatch for automation draft"
+                    .to_string(),
+            });
+        }
+        if response.envelope.request_id != payload.prompt_envelope.request_id {
+            return Err(EnclaveRpcError::RpcResponseInvalid {
+                message: "automation draft envelope is bound to a different request_id".to_string(),
+            });
+        }
+
+        response.try_into()
+    }
+}
@@ -0,0 +1,54 @@
+use crate::audit_signing::{
+    AuditEventSignError, AuditEventSignFuture, AuditEventSigner, AuditEventSigningPayload,
+};
+
+use super::super::{
+    ENCLAVE_RPC_CONTRACT_VERSION, ENCLAVE_RPC_PATH_SIGN_AUDIT_EVENT, EnclaveRpcError,
+    EnclaveRpcSignAuditEventRequest, EnclaveRpcSignAuditEventResponse, ProviderOperation,
+};
+use super::EnclaveRpcClient;
+
+impl EnclaveRpcClient {
+    /// Asks the enclave to sign an audit event with its attestation key. Returns the base64
+    /// signature to store with the event.
+    pub async fn sign_audit_event(
+        &self,
+        event: AuditEventSigningPayload,
+    ) -> Result<String, EnclaveRpcError> {
+        let payload = EnclaveRpcSignAuditEventRequest {
+            contract_version: ENCLAVE_RPC_CONTRACT_VERSION.to_string(),
+            request_id: uuid::Uuid::new_v4().to_string(),
+            event,
+        };
+
+        let response: EnclaveRpcSignAuditEventResponse = self
+            .send_enclave_rpc(
+                ProviderOperation::AuditEventSign,
+                ENCLAVE_RPC_PATH_SIGN_AUDIT_EVENT,
+                &payload,
+            )
+            .await?;
+
+        if response.request_id != payload.request_id {
+            return Err(EnclaveRpcError::RpcResponseInvalid {
+                message: "enclave rpc response request_id mismatch for audit event signing"
+                    .to_string(),
+            });
+        }
+
+        Ok(response.signature)
+    }
+}
+
+impl AuditEventSigner for EnclaveRpcClient {
+    fn sign_audit_event<'a>(
+        &'a self,
+        payload: &'a AuditEventSigningPayload,
+    ) -> AuditEventSignFuture<'a> {
+        Box::pin(async move {
+            EnclaveRpcClient::sign_audit_event(self, payload.clone())
+                .await
+                .map_err(|err| AuditEventSignError(err.to_string()))
+        })
+    }
+}
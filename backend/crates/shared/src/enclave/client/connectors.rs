@@ -0,0 +1,78 @@
+use super::super::{
+    CompleteCaldavConnectResponse, CompleteImapConnectResponse, ENCLAVE_RPC_CONTRACT_VERSION,
+    ENCLAVE_RPC_PATH_COMPLETE_CALDAV_CONNECT, ENCLAVE_RPC_PATH_COMPLETE_IMAP_CONNECT,
+    EnclaveRpcCompleteCaldavConnectRequest, EnclaveRpcCompleteCaldavConnectResponse,
+    EnclaveRpcCompleteImapConnectRequest, EnclaveRpcCompleteImapConnectResponse, EnclaveRpcError,
+    ProviderOperation,
+};
+use super::EnclaveRpcClient;
+
+impl EnclaveRpcClient {
+    pub async fn complete_caldav_connect(
+        &self,
+        user_id: uuid::Uuid,
+        server_url: String,
+        username: String,
+        app_password_envelope: crate::models::AutomationPromptEnvelope,
+    ) -> Result<CompleteCaldavConnectResponse, EnclaveRpcError> {
+        let payload = EnclaveRpcCompleteCaldavConnectRequest {
+            contract_version: ENCLAVE_RPC_CONTRACT_VERSION.to_string(),
+            request_id: uuid::Uuid::new_v4().to_string(),
+            user_id,
+            server_url,
+            username,
+            app_password_envelope,
+        };
+
+        let response: EnclaveRpcCompleteCaldavConnectResponse = self
+            .send_enclave_rpc(
+                ProviderOperation::CaldavConnect,
+                ENCLAVE_RPC_PATH_COMPLETE_CALDAV_CONNECT,
+                &payload,
+            )
+            .await?;
+
+        if response.request_id != payload.request_id {
+            return Err(EnclaveRpcError::RpcResponseInvalid {
+                message: "enclave rpc response request_id mismatch for caldav connect".to_string(),
+            });
+        }
+
+        response.try_into()
+    }
+
+    pub async fn complete_imap_connect(
+        &self,
+        user_id: uuid::Uuid,
+        host: String,
+        port: u16,
+        username: String,
+        password_envelope: crate::models::AutomationPromptEnvelope,
+    ) -> Result<CompleteImapConnectResponse, EnclaveRpcError> {
+        let payload = EnclaveRpcCompleteImapConnectRequest {
+            contract_version: ENCLAVE_RPC_CONTRACT_VERSION.to_string(),
+            request_id: uuid::Uuid::new_v4().to_string(),
+            user_id,
+            host,
+            port,
+            username,
+            password_envelope,
+        };
+
+        let response: EnclaveRpcCompleteImapConnectResponse = self
+            .send_enclave_rpc(
+                ProviderOperation::ImapConnect,
+                ENCLAVE_RPC_PATH_COMPLETE_IMAP_CONNECT,
+                &payload,
+            )
+            .await?;
+
+        if response.request_id != payload.request_id {
+            return Err(EnclaveRpcError::RpcResponseInvalid {
+                message: "enclave rpc response request_id mismatch for imap connect".to_string(),
+            });
+        }
+
+        response.try_into()
+    }
+}
@@ -52,6 +52,56 @@ impl TryFrom<EnclaveRpcCompleteGoogleConnectResponse> for CompleteGoogleConnectR
     }
 }
 
+impl TryFrom<EnclaveRpcCompleteCaldavConnectResponse> for CompleteCaldavConnectResponse {
+    type Error = EnclaveRpcError;
+
+    fn try_from(value: EnclaveRpcCompleteCaldavConnectResponse) -> Result<Self, Self::Error> {
+        if value.contract_version != ENCLAVE_RPC_CONTRACT_VERSION {
+            return Err(EnclaveRpcError::RpcResponseInvalid {
+                message: format!(
+                    "enclave rpc contract mismatch: expected={}, got={}",
+                    ENCLAVE_RPC_CONTRACT_VERSION, value.contract_version
+                ),
+            });
+        }
+
+        if value.request_id.trim().is_empty() {
+            return Err(EnclaveRpcError::RpcResponseInvalid {
+                message: "missing request_id in caldav connect response".to_string(),
+            });
+        }
+
+        Ok(Self {
+            connector_id: value.connector_id,
+        })
+    }
+}
+
+impl TryFrom<EnclaveRpcCompleteImapConnectResponse> for CompleteImapConnectResponse {
+    type Error = EnclaveRpcError;
+
+    fn try_from(value: EnclaveRpcCompleteImapConnectResponse) -> Result<Self, Self::Error> {
+        if value.contract_version != ENCLAVE_RPC_CONTRACT_VERSION {
+            return Err(EnclaveRpcError::RpcResponseInvalid {
+                message: format!(
+                    "enclave rpc contract mismatch: expected={}, got={}",
+                    ENCLAVE_RPC_CONTRACT_VERSION, value.contract_version
+                ),
+            });
+        }
+
+        if value.request_id.trim().is_empty() {
+            return Err(EnclaveRpcError::RpcResponseInvalid {
+                message: "missing request_id in imap connect response".to_string(),
+            });
+        }
+
+        Ok(Self {
+            connector_id: value.connector_id,
+        })
+    }
+}
+
 impl TryFrom<EnclaveRpcRevokeGoogleTokenResponse> for RevokeGoogleTokenResponse {
     type Error = EnclaveRpcError;
 
@@ -165,6 +215,7 @@ impl TryFrom<EnclaveRpcFetchAssistantAttestedKeyResponse> for FetchAssistantAtte
             public_key: value.public_key,
             key_expires_at: value.key_expires_at,
             signature: value.signature,
+            rotation: value.rotation,
         })
     }
 }
@@ -193,6 +244,7 @@ impl TryFrom<EnclaveRpcProcessAssistantQueryResponse> for ProcessAssistantQueryR
             envelope: value.envelope,
             session_state: value.session_state,
             attested_identity: value.attested_identity,
+            telemetry: value.telemetry,
         })
     }
 }
@@ -217,23 +269,24 @@ impl TryFrom<EnclaveRpcExecuteAutomationResponse> for ExecuteAutomationResponse
         }
 
         Ok(Self {
+            request_id: value.request_id,
             should_notify: value.should_notify,
             notification_artifacts: value
                 .notification_artifacts
                 .into_iter()
-                .map(|artifact| super::super::AutomationNotificationArtifact {
-                    device_id: artifact.device_id,
-                    envelope: super::super::EncryptedAutomationNotificationEnvelope {
-                        version: artifact.envelope.version,
-                        algorithm: artifact.envelope.algorithm,
-                        key_id: artifact.envelope.key_id,
-                        request_id: artifact.envelope.request_id,
-                        sender_public_key: artifact.envelope.sender_public_key,
-                        nonce: artifact.envelope.nonce,
-                        ciphertext: artifact.envelope.ciphertext,
-                    },
-                })
+                .map(automation_artifact_from_rpc)
                 .collect(),
+            report_artifacts: value
+                .report_artifacts
+                .into_iter()
+                .map(automation_artifact_from_rpc)
+                .collect(),
+            condition_result: value.condition_result.map(|result| {
+                super::super::AutomationConditionResult {
+                    met: result.met,
+                    observed_count: result.observed_count,
+                }
+            }),
             metadata: value.metadata,
             attested_identity: value.attested_identity,
         })
@@ -302,3 +355,135 @@ impl TryFrom<EnclaveRpcGenerateUrgentEmailSummaryResponse> for GenerateUrgentEma
         })
     }
 }
+
+impl TryFrom<EnclaveRpcPlanDepartureAlertResponse> for PlanDepartureAlertResponse {
+    type Error = EnclaveRpcError;
+
+    fn try_from(value: EnclaveRpcPlanDepartureAlertResponse) -> Result<Self, Self::Error> {
+        if value.contract_version != ENCLAVE_RPC_CONTRACT_VERSION {
+            return Err(EnclaveRpcError::RpcResponseInvalid {
+                message: format!(
+                    "enclave rpc contract mismatch: expected={}, got={}",
+                    ENCLAVE_RPC_CONTRACT_VERSION, value.contract_version
+                ),
+            });
+        }
+
+        if value.request_id.trim().is_empty() {
+            return Err(EnclaveRpcError::RpcResponseInvalid {
+                message: "missing request_id in departure alert response".to_string(),
+            });
+        }
+
+        let plan = match value.status {
+            EnclaveDepartureAlertStatus::NoMeeting => None,
+            EnclaveDepartureAlertStatus::Pending | EnclaveDepartureAlertStatus::Due => {
+                let (Some(meeting_fingerprint), Some(meeting_start_at), Some(leave_by)) = (
+                    value.meeting_fingerprint,
+                    value.meeting_start_at,
+                    value.leave_by,
+                ) else {
+                    return Err(EnclaveRpcError::RpcResponseInvalid {
+                        message: "departure alert response is missing meeting timing".to_string(),
+                    });
+                };
+                if meeting_fingerprint.trim().is_empty() {
+                    return Err(EnclaveRpcError::RpcResponseInvalid {
+                        message: "departure alert response has empty meeting fingerprint"
+                            .to_string(),
+                    });
+                }
+
+                Some(DepartureAlertPlan {
+                    meeting_fingerprint,
+                    meeting_start_at,
+                    leave_by,
+                    due: value.status == EnclaveDepartureAlertStatus::Due,
+                })
+            }
+        };
+
+        Ok(Self {
+            request_id: value.request_id,
+            plan,
+            notification_artifacts: value
+                .notification_artifacts
+                .into_iter()
+                .map(automation_artifact_from_rpc)
+                .collect(),
+            metadata: value.metadata,
+            attested_identity: value.attested_identity,
+        })
+    }
+}
+
+fn automation_artifact_from_rpc(
+    artifact: super::super::EnclaveAutomationNotificationArtifact,
+) -> super::super::AutomationNotificationArtifact {
+    super::super::AutomationNotificationArtifact {
+        device_id: artifact.device_id,
+        envelope: super::super::EncryptedAutomationNotificationEnvelope {
+            version: artifact.envelope.version,
+            algorithm: artifact.envelope.algorithm,
+            key_id: artifact.envelope.key_id,
+            request_id: artifact.envelope.request_id,
+            sender_public_key: artifact.envelope.sender_public_key,
+            nonce: artifact.envelope.nonce,
+            ciphertext: artifact.envelope.ciphertext,
+        },
+    }
+}
+
+impl TryFrom<EnclaveRpcExportAssistantSessionsResponse> for ExportAssistantSessionsResponse {
+    type Error = EnclaveRpcError;
+
+    fn try_from(value: EnclaveRpcExportAssistantSessionsResponse) -> Result<Self, Self::Error> {
+        if value.contract_version != ENCLAVE_RPC_CONTRACT_VERSION {
+            return Err(EnclaveRpcError::RpcResponseInvalid {
+                message: format!(
+                    "enclave rpc contract mismatch: expected={}, got={}",
+                    ENCLAVE_RPC_CONTRACT_VERSION, value.contract_version
+                ),
+            });
+        }
+
+        if value.request_id.trim().is_empty() {
+            return Err(EnclaveRpcError::RpcResponseInvalid {
+                message: "missing request_id in session export response".to_string(),
+            });
+        }
+
+        Ok(Self {
+            envelope: value.envelope,
+            exported_session_ids: value.exported_session_ids,
+            skipped_session_ids: value.skipped_session_ids,
+            attested_identity: value.attested_identity,
+        })
+    }
+}
+
+impl TryFrom<EnclaveRpcDraftAutomationResponse> for DraftAutomationResponse {
+    type Error = EnclaveRpcError;
+
+    fn try_from(value: EnclaveRpcDraftAutomationResponse) -> Result<Self, Self::Error> {
+        if value.contract_version != ENCLAVE_RPC_CONTRACT_VERSION {
+            return Err(EnclaveRpcError::RpcResponseInvalid {
+                message: format!(
+                    "enclave rpc contract mismatch: expected={}, got={}",
+                    ENCLAVE_RPC_CONTRACT_VERSION, value.contract_version
+                ),
+            });
+        }
+
+        if value.request_id.trim().is_empty() {
+            return Err(EnclaveRpcError::RpcResponseInvalid {
+                message: "missing request_id in automation draft response".to_string(),
+            });
+        }
+
+        Ok(Self {
+            envelope: value.envelope,
+            attested_identity: value.attested_identity,
+        })
+    }
+}
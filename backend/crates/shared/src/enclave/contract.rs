@@ -2,10 +2,14 @@ use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
 
+use crate::brief_profile::MorningBriefProfile;
+
 pub const ENCLAVE_RPC_CONTRACT_VERSION: &str = "v1";
 pub const ENCLAVE_RPC_PATH_EXCHANGE_GOOGLE_TOKEN: &str = "/v1/rpc/google/token/exchange";
 pub const ENCLAVE_RPC_PATH_COMPLETE_GOOGLE_CONNECT: &str = "/v1/rpc/google/connect/complete";
 pub const ENCLAVE_RPC_PATH_REVOKE_GOOGLE_TOKEN: &str = "/v1/rpc/google/token/revoke";
+pub const ENCLAVE_RPC_PATH_COMPLETE_CALDAV_CONNECT: &str = "/v1/rpc/caldav/connect/complete";
+pub const ENCLAVE_RPC_PATH_COMPLETE_IMAP_CONNECT: &str = "/v1/rpc/imap/connect/complete";
 pub const ENCLAVE_RPC_PATH_FETCH_GOOGLE_CALENDAR_EVENTS: &str = "/v1/rpc/google/calendar/events";
 pub const ENCLAVE_RPC_PATH_FETCH_GOOGLE_URGENT_EMAIL_CANDIDATES: &str =
     "/v1/rpc/google/gmail/urgent-candidates";
@@ -14,6 +18,10 @@ pub const ENCLAVE_RPC_PATH_PROCESS_ASSISTANT_QUERY: &str = "/v1/rpc/assistant/qu
 pub const ENCLAVE_RPC_PATH_GENERATE_MORNING_BRIEF: &str = "/v1/rpc/assistant/morning-brief";
 pub const ENCLAVE_RPC_PATH_GENERATE_URGENT_EMAIL_SUMMARY: &str = "/v1/rpc/assistant/urgent-email";
 pub const ENCLAVE_RPC_PATH_EXECUTE_AUTOMATION: &str = "/v1/rpc/assistant/automation/execute";
+pub const ENCLAVE_RPC_PATH_PLAN_DEPARTURE_ALERT: &str = "/v1/rpc/assistant/departure-alert";
+pub const ENCLAVE_RPC_PATH_EXPORT_ASSISTANT_SESSIONS: &str = "/v1/rpc/assistant/sessions/export";
+pub const ENCLAVE_RPC_PATH_DRAFT_AUTOMATION: &str = "/v1/rpc/assistant/automation/draft";
+pub const ENCLAVE_RPC_PATH_SIGN_AUDIT_EVENT: &str = "/v1/rpc/audit/sign";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AttestedIdentityPayload {
@@ -53,6 +61,45 @@ pub struct EnclaveRpcCompleteGoogleConnectResponse {
     pub granted_scopes: Vec<String>,
 }
 
+/// The app password travels encrypted to the enclave ingress key; the host only sees the
+/// server URL and username.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnclaveRpcCompleteCaldavConnectRequest {
+    pub contract_version: String,
+    pub request_id: String,
+    pub user_id: uuid::Uuid,
+    pub server_url: String,
+    pub username: String,
+    pub app_password_envelope: crate::models::AutomationPromptEnvelope,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnclaveRpcCompleteCaldavConnectResponse {
+    pub contract_version: String,
+    pub request_id: String,
+    pub connector_id: uuid::Uuid,
+}
+
+/// The password travels encrypted to the enclave ingress key; the host only sees the server
+/// address and username.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnclaveRpcCompleteImapConnectRequest {
+    pub contract_version: String,
+    pub request_id: String,
+    pub user_id: uuid::Uuid,
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+    pub password_envelope: crate::models::AutomationPromptEnvelope,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnclaveRpcCompleteImapConnectResponse {
+    pub contract_version: String,
+    pub request_id: String,
+    pub connector_id: uuid::Uuid,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EnclaveRpcRevokeGoogleTokenRequest {
     pub contract_version: String,
@@ -92,9 +139,26 @@ pub struct EnclaveGoogleCalendarEvent {
     pub start: Option<EnclaveGoogleCalendarEventDateTime>,
     pub end: Option<EnclaveGoogleCalendarEventDateTime>,
     #[serde(default)]
+    pub location: Option<String>,
+    #[serde(default, rename = "hangoutLink")]
+    pub hangout_link: Option<String>,
+    #[serde(default, rename = "conferenceData")]
+    pub conference_data: Option<EnclaveGoogleCalendarConferenceData>,
+    #[serde(default)]
     pub attendees: Vec<EnclaveGoogleCalendarAttendee>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnclaveGoogleCalendarConferenceData {
+    #[serde(default, rename = "entryPoints")]
+    pub entry_points: Vec<EnclaveGoogleCalendarConferenceEntryPoint>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnclaveGoogleCalendarConferenceEntryPoint {
+    pub uri: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EnclaveGoogleCalendarEventDateTime {
     #[serde(rename = "dateTime")]
@@ -122,6 +186,20 @@ pub struct EnclaveRpcFetchGoogleUrgentEmailCandidatesResponse {
     pub attested_identity: AttestedIdentityPayload,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnclaveRpcSignAuditEventRequest {
+    pub contract_version: String,
+    pub request_id: String,
+    pub event: crate::audit_signing::AuditEventSigningPayload,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnclaveRpcSignAuditEventResponse {
+    pub contract_version: String,
+    pub request_id: String,
+    pub signature: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EnclaveRpcFetchAssistantAttestedKeyRequest {
     pub contract_version: String,
@@ -146,6 +224,8 @@ pub struct EnclaveRpcFetchAssistantAttestedKeyResponse {
     pub public_key: String,
     pub key_expires_at: i64,
     pub signature: Option<String>,
+    #[serde(default)]
+    pub rotation: Option<crate::models::AssistantAttestedKeyRotation>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -169,6 +249,21 @@ pub struct EnclaveRpcProcessAssistantQueryResponse {
     #[serde(default)]
     pub session_state: Option<crate::models::AssistantSessionStateEnvelope>,
     pub attested_identity: AttestedIdentityPayload,
+    #[serde(default)]
+    pub telemetry: Option<EnclaveAssistantQueryTelemetry>,
+}
+
+/// Content-free description of how the enclave answered a query: no query or response text,
+/// only routing, model, fallback, and latency metadata the host may index.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnclaveAssistantQueryTelemetry {
+    pub route: String,
+    pub capability: crate::models::AssistantQueryCapability,
+    pub model: Option<String>,
+    pub planner_used_fallback: bool,
+    pub output_used_fallback: bool,
+    pub planner_ms: u64,
+    pub lane_ms: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -176,6 +271,9 @@ pub struct EnclaveRpcProcessAssistantQueryResponse {
 pub struct EnclaveRpcExecuteAutomationRequest {
     pub contract_version: String,
     pub request_id: String,
+    /// Worker job this run belongs to, carried for log correlation only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub job_id: Option<uuid::Uuid>,
     pub user_id: uuid::Uuid,
     pub automation_rule_id: uuid::Uuid,
     pub automation_run_id: uuid::Uuid,
@@ -183,6 +281,35 @@ pub struct EnclaveRpcExecuteAutomationRequest {
     pub prompt_envelope: crate::models::AutomationPromptEnvelope,
     #[serde(default)]
     pub recipient_devices: Vec<EnclaveAutomationRecipientDevice>,
+    #[serde(default)]
+    pub template: Option<EnclaveAutomationTemplateRequest>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub condition: Option<EnclaveAutomationConditionRequest>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EnclaveAutomationTemplateRequest {
+    pub template: crate::automation_schedule::AutomationTemplate,
+    pub time_zone: String,
+    /// The user's saved profile, sent with `MORNING_BRIEF` runs.
+    #[serde(default)]
+    pub brief_profile: Option<MorningBriefProfile>,
+}
+
+/// `time_zone` sets the local day `CALENDAR_EVENTS_TODAY` counts events in.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EnclaveAutomationConditionRequest {
+    pub condition: crate::automation_schedule::AutomationCondition,
+    pub time_zone: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EnclaveAutomationConditionResult {
+    pub met: bool,
+    pub observed_count: u32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -221,6 +348,114 @@ pub struct EnclaveRpcExecuteAutomationResponse {
     pub should_notify: bool,
     #[serde(default)]
     pub notification_artifacts: Vec<EnclaveAutomationNotificationArtifact>,
+    #[serde(default)]
+    pub report_artifacts: Vec<EnclaveAutomationNotificationArtifact>,
+    /// Set when the request carried a condition. A run whose condition is not met has no
+    /// artifacts and `should_notify` false.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub condition_result: Option<EnclaveAutomationConditionResult>,
+    pub metadata: HashMap<String, String>,
+    pub attested_identity: AttestedIdentityPayload,
+}
+
+/// Sealed session state the host holds for one conversation, plus the metadata it already knows.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EnclaveAssistantSessionExportItem {
+    pub session_id: uuid::Uuid,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+    pub state: crate::models::AssistantSessionStateEnvelope,
+}
+
+/// `export_request_id` is the client's request id; the archive is encrypted to
+/// `client_ephemeral_public_key` under it, so the host never sees the plaintext.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EnclaveRpcExportAssistantSessionsRequest {
+    pub contract_version: String,
+    pub request_id: String,
+    pub user_id: uuid::Uuid,
+    pub export_request_id: String,
+    pub client_ephemeral_public_key: String,
+    pub sessions: Vec<EnclaveAssistantSessionExportItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EnclaveRpcExportAssistantSessionsResponse {
+    pub contract_version: String,
+    pub request_id: String,
+    pub envelope: crate::models::AssistantEncryptedResponseEnvelope,
+    pub exported_session_ids: Vec<uuid::Uuid>,
+    #[serde(default)]
+    pub skipped_session_ids: Vec<uuid::Uuid>,
+    pub attested_identity: AttestedIdentityPayload,
+}
+
+/// The proposal is encrypted to the prompt envelope's client key under its request_id, so the
+/// host only ever relays ciphertext.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EnclaveRpcDraftAutomationRequest {
+    pub contract_version: String,
+    pub request_id: String,
+    pub user_id: uuid::Uuid,
+    pub time_zone: String,
+    pub prompt_envelope: crate::models::AutomationPromptEnvelope,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EnclaveRpcDraftAutomationResponse {
+    pub contract_version: String,
+    pub request_id: String,
+    pub envelope: crate::models::AssistantEncryptedResponseEnvelope,
+    pub attested_identity: AttestedIdentityPayload,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EnclaveRpcPlanDepartureAlertRequest {
+    pub contract_version: String,
+    pub request_id: String,
+    /// Worker job this plan belongs to, carried for log correlation only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub job_id: Option<uuid::Uuid>,
+    pub user_id: uuid::Uuid,
+    pub time_zone: String,
+    pub travel_minutes: u16,
+    pub buffer_minutes: u16,
+    #[serde(default)]
+    pub home_location_envelope: Option<crate::models::AutomationPromptEnvelope>,
+    #[serde(default)]
+    pub recipient_devices: Vec<EnclaveAutomationRecipientDevice>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum EnclaveDepartureAlertStatus {
+    NoMeeting,
+    Pending,
+    Due,
+}
+
+/// Timing for the day's first in-person meeting. The fingerprint is an opaque hash of the
+/// meeting identity, time, and location so the worker can detect changes without seeing them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EnclaveRpcPlanDepartureAlertResponse {
+    pub contract_version: String,
+    pub request_id: String,
+    pub status: EnclaveDepartureAlertStatus,
+    #[serde(default)]
+    pub meeting_fingerprint: Option<String>,
+    #[serde(default)]
+    pub meeting_start_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    pub leave_by: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    pub notification_artifacts: Vec<EnclaveAutomationNotificationArtifact>,
     pub metadata: HashMap<String, String>,
     pub attested_identity: AttestedIdentityPayload,
 }
@@ -233,6 +468,8 @@ pub struct EnclaveRpcGenerateMorningBriefRequest {
     pub connector: super::ConnectorSecretRequest,
     pub time_zone: String,
     pub morning_brief_local_time: String,
+    #[serde(default)]
+    pub brief_profile: Option<MorningBriefProfile>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -298,6 +535,8 @@ pub struct EnclaveRpcErrorPayload {
     pub provider_status: Option<u16>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub oauth_error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_after_ms: Option<u64>,
 }
 
 impl EnclaveRpcErrorEnvelope {
@@ -316,6 +555,7 @@ impl EnclaveRpcErrorEnvelope {
                 retryable,
                 provider_status: None,
                 oauth_error: None,
+                retry_after_ms: None,
             },
         }
     }
@@ -334,6 +574,22 @@ impl EnclaveRpcErrorEnvelope {
                 retryable: status >= 500,
                 provider_status: Some(status),
                 oauth_error,
+                retry_after_ms: None,
+            },
+        }
+    }
+
+    pub fn with_provider_rate_limit(request_id: Option<String>, retry_after_ms: u64) -> Self {
+        Self {
+            contract_version: ENCLAVE_RPC_CONTRACT_VERSION.to_string(),
+            request_id,
+            error: EnclaveRpcErrorPayload {
+                code: "provider_rate_limited".to_string(),
+                message: "Provider call rate limit reached".to_string(),
+                retryable: true,
+                provider_status: None,
+                oauth_error: None,
+                retry_after_ms: Some(retry_after_ms),
             },
         }
     }
@@ -1,7 +1,10 @@
 mod client;
 mod contract;
 mod service;
+mod tls;
+pub(crate) mod transport;
 mod transport_auth;
+mod vsock;
 
 #[cfg(test)]
 mod tests;
@@ -12,36 +15,61 @@ use std::fmt;
 use thiserror::Error;
 use uuid::Uuid;
 
-pub use client::EnclaveRpcClient;
+pub use client::{EnclaveRpcClient, EnclaveRpcObserver};
 pub use contract::{
     AttestedIdentityPayload, ENCLAVE_RPC_CONTRACT_VERSION,
-    ENCLAVE_RPC_PATH_COMPLETE_GOOGLE_CONNECT, ENCLAVE_RPC_PATH_EXCHANGE_GOOGLE_TOKEN,
-    ENCLAVE_RPC_PATH_EXECUTE_AUTOMATION, ENCLAVE_RPC_PATH_FETCH_ASSISTANT_ATTESTED_KEY,
+    ENCLAVE_RPC_PATH_COMPLETE_CALDAV_CONNECT, ENCLAVE_RPC_PATH_COMPLETE_GOOGLE_CONNECT,
+    ENCLAVE_RPC_PATH_COMPLETE_IMAP_CONNECT, ENCLAVE_RPC_PATH_DRAFT_AUTOMATION,
+    ENCLAVE_RPC_PATH_EXCHANGE_GOOGLE_TOKEN, ENCLAVE_RPC_PATH_EXECUTE_AUTOMATION,
+    ENCLAVE_RPC_PATH_EXPORT_ASSISTANT_SESSIONS, ENCLAVE_RPC_PATH_FETCH_ASSISTANT_ATTESTED_KEY,
     ENCLAVE_RPC_PATH_FETCH_GOOGLE_CALENDAR_EVENTS,
     ENCLAVE_RPC_PATH_FETCH_GOOGLE_URGENT_EMAIL_CANDIDATES, ENCLAVE_RPC_PATH_GENERATE_MORNING_BRIEF,
-    ENCLAVE_RPC_PATH_GENERATE_URGENT_EMAIL_SUMMARY, ENCLAVE_RPC_PATH_PROCESS_ASSISTANT_QUERY,
-    ENCLAVE_RPC_PATH_REVOKE_GOOGLE_TOKEN, EnclaveAutomationEncryptedNotificationEnvelope,
+    ENCLAVE_RPC_PATH_GENERATE_URGENT_EMAIL_SUMMARY, ENCLAVE_RPC_PATH_PLAN_DEPARTURE_ALERT,
+    ENCLAVE_RPC_PATH_PROCESS_ASSISTANT_QUERY, ENCLAVE_RPC_PATH_REVOKE_GOOGLE_TOKEN,
+    ENCLAVE_RPC_PATH_SIGN_AUDIT_EVENT, EnclaveAssistantQueryTelemetry,
+    EnclaveAssistantSessionExportItem, EnclaveAutomationConditionRequest,
+    EnclaveAutomationConditionResult, EnclaveAutomationEncryptedNotificationEnvelope,
     EnclaveAutomationNotificationArtifact, EnclaveAutomationRecipientDevice,
-    EnclaveGeneratedNotificationPayload, EnclaveGoogleCalendarAttendee, EnclaveGoogleCalendarEvent,
-    EnclaveGoogleCalendarEventDateTime, EnclaveGoogleEmailCandidate,
+    EnclaveAutomationTemplateRequest, EnclaveDepartureAlertStatus,
+    EnclaveGeneratedNotificationPayload, EnclaveGoogleCalendarAttendee,
+    EnclaveGoogleCalendarConferenceData, EnclaveGoogleCalendarConferenceEntryPoint,
+    EnclaveGoogleCalendarEvent, EnclaveGoogleCalendarEventDateTime, EnclaveGoogleEmailCandidate,
+    EnclaveRpcCompleteCaldavConnectRequest, EnclaveRpcCompleteCaldavConnectResponse,
     EnclaveRpcCompleteGoogleConnectRequest, EnclaveRpcCompleteGoogleConnectResponse,
-    EnclaveRpcErrorEnvelope, EnclaveRpcErrorPayload, EnclaveRpcExchangeGoogleTokenRequest,
+    EnclaveRpcCompleteImapConnectRequest, EnclaveRpcCompleteImapConnectResponse,
+    EnclaveRpcDraftAutomationRequest, EnclaveRpcDraftAutomationResponse, EnclaveRpcErrorEnvelope,
+    EnclaveRpcErrorPayload, EnclaveRpcExchangeGoogleTokenRequest,
     EnclaveRpcExchangeGoogleTokenResponse, EnclaveRpcExecuteAutomationRequest,
-    EnclaveRpcExecuteAutomationResponse, EnclaveRpcFetchAssistantAttestedKeyRequest,
+    EnclaveRpcExecuteAutomationResponse, EnclaveRpcExportAssistantSessionsRequest,
+    EnclaveRpcExportAssistantSessionsResponse, EnclaveRpcFetchAssistantAttestedKeyRequest,
     EnclaveRpcFetchAssistantAttestedKeyResponse, EnclaveRpcFetchGoogleCalendarEventsRequest,
     EnclaveRpcFetchGoogleCalendarEventsResponse, EnclaveRpcFetchGoogleUrgentEmailCandidatesRequest,
     EnclaveRpcFetchGoogleUrgentEmailCandidatesResponse, EnclaveRpcGenerateMorningBriefRequest,
     EnclaveRpcGenerateMorningBriefResponse, EnclaveRpcGenerateUrgentEmailSummaryRequest,
-    EnclaveRpcGenerateUrgentEmailSummaryResponse, EnclaveRpcProcessAssistantQueryRequest,
+    EnclaveRpcGenerateUrgentEmailSummaryResponse, EnclaveRpcPlanDepartureAlertRequest,
+    EnclaveRpcPlanDepartureAlertResponse, EnclaveRpcProcessAssistantQueryRequest,
     EnclaveRpcProcessAssistantQueryResponse, EnclaveRpcRevokeGoogleTokenRequest,
-    EnclaveRpcRevokeGoogleTokenResponse,
+    EnclaveRpcRevokeGoogleTokenResponse, EnclaveRpcSignAuditEventRequest,
+    EnclaveRpcSignAuditEventResponse,
 };
-pub use service::EnclaveOperationService;
+pub use service::{
+    CalendarConnectorRequest, CalendarProvider, EmailConnectorRequest, EmailProvider,
+    EnclaveOperationService,
+};
+pub use tls::{
+    EnclaveRpcClientTlsConfig, EnclaveRpcSecurityMode, EnclaveRpcTls, EnclaveTlsPins,
+    certificate_sha256, load_certificate_chain, load_private_key,
+};
+pub use transport::EnclaveHttpClient;
 pub use transport_auth::{
-    ENCLAVE_RPC_AUTH_NONCE_HEADER, ENCLAVE_RPC_AUTH_SIGNATURE_HEADER,
-    ENCLAVE_RPC_AUTH_TIMESTAMP_HEADER, ENCLAVE_RPC_CONTRACT_VERSION_HEADER, EnclaveRpcAuthConfig,
-    constant_time_eq, sign_rpc_request,
+    ENCLAVE_RPC_AUTH_CALLER_HEADER, ENCLAVE_RPC_AUTH_NONCE_HEADER,
+    ENCLAVE_RPC_AUTH_SIGNATURE_HEADER, ENCLAVE_RPC_AUTH_TIMESTAMP_HEADER,
+    ENCLAVE_RPC_CONTRACT_VERSION_HEADER, EnclaveRpcAuthConfig, EnclaveRpcCallerKeys,
+    EnclaveRpcSigningKey, SignedRpcRequest, constant_time_eq, rpc_signing_payload,
 };
+pub use vsock::{VSOCK_CID_ANY, VSOCK_URL_SCHEME, VsockAddr};
+#[cfg(target_os = "linux")]
+pub use vsock::{VsockListener, VsockStream};
 
 #[derive(Debug, Clone)]
 pub struct GoogleEnclaveOauthConfig {
@@ -69,6 +97,16 @@ pub struct CompleteGoogleConnectResponse {
     pub granted_scopes: Vec<String>,
 }
 
+#[derive(Debug, Clone)]
+pub struct CompleteCaldavConnectResponse {
+    pub connector_id: Uuid,
+}
+
+#[derive(Debug, Clone)]
+pub struct CompleteImapConnectResponse {
+    pub connector_id: Uuid,
+}
+
 #[derive(Debug, Clone)]
 pub struct RevokeGoogleTokenResponse {
     pub attested_identity: AttestedIdentityPayload,
@@ -100,6 +138,7 @@ pub struct FetchAssistantAttestedKeyResponse {
     pub public_key: String,
     pub key_expires_at: i64,
     pub signature: Option<String>,
+    pub rotation: Option<crate::models::AssistantAttestedKeyRotation>,
 }
 
 #[derive(Debug, Clone)]
@@ -108,6 +147,7 @@ pub struct ProcessAssistantQueryResponse {
     pub envelope: crate::models::AssistantEncryptedResponseEnvelope,
     pub session_state: Option<crate::models::AssistantSessionStateEnvelope>,
     pub attested_identity: AttestedIdentityPayload,
+    pub telemetry: Option<EnclaveAssistantQueryTelemetry>,
 }
 
 #[derive(Debug, Clone)]
@@ -156,14 +196,111 @@ pub struct AutomationNotificationArtifact {
     pub envelope: EncryptedAutomationNotificationEnvelope,
 }
 
+#[derive(Debug, Clone)]
+pub struct AutomationTemplateRequest {
+    pub template: crate::automation_schedule::AutomationTemplate,
+    pub time_zone: String,
+    pub brief_profile: Option<crate::brief_profile::MorningBriefProfile>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AutomationConditionRequest {
+    pub condition: crate::automation_schedule::AutomationCondition,
+    pub time_zone: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AutomationConditionResult {
+    pub met: bool,
+    pub observed_count: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExecuteAutomationRequest {
+    pub job_id: Option<Uuid>,
+    pub user_id: Uuid,
+    pub automation_rule_id: Uuid,
+    pub automation_run_id: Uuid,
+    pub scheduled_for: chrono::DateTime<chrono::Utc>,
+    pub prompt_envelope: crate::models::AutomationPromptEnvelope,
+    pub recipient_devices: Vec<AutomationRecipientDevice>,
+    pub template: Option<AutomationTemplateRequest>,
+    pub condition: Option<AutomationConditionRequest>,
+}
+
 #[derive(Debug, Clone)]
 pub struct ExecuteAutomationResponse {
+    /// Id of the RPC that produced this response, for correlating worker and enclave logs.
+    pub request_id: String,
     pub should_notify: bool,
     pub notification_artifacts: Vec<AutomationNotificationArtifact>,
+    /// Long-form report envelopes for template runs, meant for in-app retrieval.
+    pub report_artifacts: Vec<AutomationNotificationArtifact>,
+    pub condition_result: Option<AutomationConditionResult>,
     pub metadata: HashMap<String, String>,
     pub attested_identity: AttestedIdentityPayload,
 }
 
+#[derive(Debug, Clone)]
+pub struct PlanDepartureAlertRequest {
+    pub job_id: Option<Uuid>,
+    pub user_id: Uuid,
+    pub time_zone: String,
+    pub travel_minutes: u16,
+    pub buffer_minutes: u16,
+    pub home_location_envelope: Option<crate::models::AutomationPromptEnvelope>,
+    pub recipient_devices: Vec<AutomationRecipientDevice>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DepartureAlertPlan {
+    pub meeting_fingerprint: String,
+    pub meeting_start_at: chrono::DateTime<chrono::Utc>,
+    pub leave_by: chrono::DateTime<chrono::Utc>,
+    pub due: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct PlanDepartureAlertResponse {
+    /// Id of the RPC that produced this response, for correlating worker and enclave logs.
+    pub request_id: String,
+    /// `None` when there is no upcoming in-person meeting today.
+    pub plan: Option<DepartureAlertPlan>,
+    pub notification_artifacts: Vec<AutomationNotificationArtifact>,
+    pub metadata: HashMap<String, String>,
+    pub attested_identity: AttestedIdentityPayload,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportAssistantSessionsRequest {
+    pub user_id: Uuid,
+    pub export_request_id: String,
+    pub client_ephemeral_public_key: String,
+    pub sessions: Vec<EnclaveAssistantSessionExportItem>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportAssistantSessionsResponse {
+    pub envelope: crate::models::AssistantEncryptedResponseEnvelope,
+    pub exported_session_ids: Vec<Uuid>,
+    /// Sessions whose sealed state the enclave could not open; they are left out of the archive.
+    pub skipped_session_ids: Vec<Uuid>,
+    pub attested_identity: AttestedIdentityPayload,
+}
+
+#[derive(Debug, Clone)]
+pub struct DraftAutomationRequest {
+    pub user_id: Uuid,
+    pub time_zone: String,
+    pub prompt_envelope: crate::models::AutomationPromptEnvelope,
+}
+
+#[derive(Debug, Clone)]
+pub struct DraftAutomationResponse {
+    pub envelope: crate::models::AssistantEncryptedResponseEnvelope,
+    pub attested_identity: AttestedIdentityPayload,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ProviderOperation {
     TokenRefresh,
@@ -176,6 +313,14 @@ pub enum ProviderOperation {
     AssistantMorningBrief,
     AssistantUrgentEmail,
     AssistantAutomationRun,
+    AssistantDepartureAlert,
+    AssistantSessionExport,
+    AssistantAutomationDraft,
+    CaldavConnect,
+    CaldavFetch,
+    ImapConnect,
+    ImapFetch,
+    AuditEventSign,
 }
 
 impl fmt::Display for ProviderOperation {
@@ -191,6 +336,14 @@ impl fmt::Display for ProviderOperation {
             Self::AssistantMorningBrief => write!(f, "assistant_morning_brief"),
             Self::AssistantUrgentEmail => write!(f, "assistant_urgent_email"),
             Self::AssistantAutomationRun => write!(f, "assistant_automation_run"),
+            Self::AssistantDepartureAlert => write!(f, "assistant_departure_alert"),
+            Self::AssistantSessionExport => write!(f, "assistant_session_export"),
+            Self::AssistantAutomationDraft => write!(f, "assistant_automation_draft"),
+            Self::CaldavConnect => write!(f, "caldav_connect"),
+            Self::CaldavFetch => write!(f, "caldav_fetch"),
+            Self::ImapConnect => write!(f, "imap_connect"),
+            Self::ImapFetch => write!(f, "imap_fetch"),
+            Self::AuditEventSign => write!(f, "audit_event_sign"),
         }
     }
 }
@@ -227,9 +380,36 @@ pub enum EnclaveRpcError {
         operation: ProviderOperation,
         message: String,
     },
+    /// Rejected by the enclave's own outbound limiter before reaching the provider.
+    #[error("provider request rate limited for {operation}: retry_after_ms={retry_after_ms}")]
+    ProviderRateLimited {
+        operation: ProviderOperation,
+        retry_after_ms: u64,
+    },
+    /// The requester's monthly LLM spend budget is exhausted until the next UTC month.
+    #[error("llm spend budget exceeded: {message}")]
+    LlmBudgetExceeded { message: String },
 }
 
 impl EnclaveRpcError {
+    /// Stable, content-free label of the failure kind, safe to persist and aggregate.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::RpcUnauthorized { .. } => "rpc_unauthorized",
+            Self::RpcContractRejected { .. } => "rpc_contract_rejected",
+            Self::RpcTransportUnavailable { .. } => "rpc_transport_unavailable",
+            Self::RpcResponseInvalid { .. } => "rpc_response_invalid",
+            Self::DecryptNotAuthorized { .. } => "decrypt_not_authorized",
+            Self::ConnectorTokenDecryptFailed { .. } => "connector_token_decrypt_failed",
+            Self::ConnectorTokenUnavailable => "connector_token_unavailable",
+            Self::ProviderRequestUnavailable { .. } => "provider_unavailable",
+            Self::ProviderRequestFailed { .. } => "provider_failed",
+            Self::ProviderResponseInvalid { .. } => "provider_response_invalid",
+            Self::ProviderRateLimited { .. } => "provider_rate_limited",
+            Self::LlmBudgetExceeded { .. } => "llm_budget_exceeded",
+        }
+    }
+
     pub fn from_error_envelope(
         operation: ProviderOperation,
         status: u16,
@@ -256,6 +436,13 @@ impl EnclaveRpcError {
                 operation,
                 message: envelope.error.message,
             },
+            "provider_rate_limited" => Self::ProviderRateLimited {
+                operation,
+                retry_after_ms: envelope.error.retry_after_ms.unwrap_or_default(),
+            },
+            "llm_budget_exceeded" => Self::LlmBudgetExceeded {
+                message: envelope.error.message,
+            },
             "missing_request_header"
             | "invalid_request_header"
             | "invalid_request_signature"
@@ -1,12 +1,27 @@
 use reqwest::{RequestBuilder, StatusCode};
 use serde::de::DeserializeOwned;
-use uuid::Uuid;
+use tracing::warn;
 
-use crate::repos::{ConnectorKeyMetadata as PersistedConnectorKeyMetadata, Store};
+use crate::connector_health::ConnectorHealthSignal;
+use crate::models::AvailabilityComponent;
+use crate::outbound_rate_limit::{OutboundCallLimiter, OutboundProvider};
+use crate::repos::{
+    ConnectorAccount, ConnectorKeyMetadata as PersistedConnectorKeyMetadata, Store, StoreError,
+};
 use crate::security::{ConnectorKeyMetadata as AuthorizedConnectorKeyMetadata, SecretRuntime};
 
+mod caldav;
+mod caldav_ical;
+mod connectors;
+mod google_accounts;
 mod google_types;
+mod imap;
+mod imap_client;
 
+pub use self::connectors::{
+    CalendarConnectorRequest, CalendarProvider, EmailConnectorRequest, EmailProvider,
+};
+use self::google_accounts::google_account_key;
 use self::google_types::{
     GmailMessageMetadataResponse, GmailMessagesResponse, GoogleCalendarEventsResponse,
     GoogleOAuthCodeExchangeResponse, GoogleRefreshTokenResponse, parse_google_error_code,
@@ -14,10 +29,11 @@ use self::google_types::{
 
 use super::{
     AttestedIdentityPayload, CompleteGoogleConnectResponse, ConnectorSecretRequest,
-    EnclaveGoogleCalendarAttendee, EnclaveGoogleCalendarEvent, EnclaveGoogleCalendarEventDateTime,
-    EnclaveRpcError, ExchangeGoogleTokenResponse, FetchGoogleCalendarEventsResponse,
-    FetchGoogleUrgentEmailCandidatesResponse, GoogleEnclaveOauthConfig, ProviderOperation,
-    RevokeGoogleTokenResponse,
+    EnclaveGoogleCalendarAttendee, EnclaveGoogleCalendarConferenceData,
+    EnclaveGoogleCalendarConferenceEntryPoint, EnclaveGoogleCalendarEvent,
+    EnclaveGoogleCalendarEventDateTime, EnclaveRpcError, ExchangeGoogleTokenResponse,
+    FetchGoogleCalendarEventsResponse, FetchGoogleUrgentEmailCandidatesResponse,
+    GoogleEnclaveOauthConfig, ProviderOperation, RevokeGoogleTokenResponse,
 };
 
 const GOOGLE_CALENDAR_EVENTS_URL: &str =
@@ -35,6 +51,7 @@ pub struct EnclaveOperationService {
     secret_runtime: SecretRuntime,
     http_client: reqwest::Client,
     oauth: GoogleEnclaveOauthConfig,
+    outbound_limiter: OutboundCallLimiter,
 }
 
 impl EnclaveOperationService {
@@ -49,15 +66,31 @@ impl EnclaveOperationService {
             secret_runtime,
             http_client,
             oauth,
+            outbound_limiter: OutboundCallLimiter::unlimited(),
         }
     }
 
+    /// Routes every Google call through the enclave-wide outbound limiter.
+    pub fn with_outbound_limiter(mut self, outbound_limiter: OutboundCallLimiter) -> Self {
+        self.outbound_limiter = outbound_limiter;
+        self
+    }
+
     pub async fn exchange_google_access_token(
         &self,
         request: ConnectorSecretRequest,
+    ) -> Result<ExchangeGoogleTokenResponse, EnclaveRpcError> {
+        let result = self.exchange_google_access_token_inner(&request).await;
+        self.observe_connector_health(&request, &result).await;
+        result
+    }
+
+    async fn exchange_google_access_token_inner(
+        &self,
+        request: &ConnectorSecretRequest,
     ) -> Result<ExchangeGoogleTokenResponse, EnclaveRpcError> {
         let (refresh_token, attested_identity) =
-            self.load_authorized_refresh_token(&request).await?;
+            self.load_authorized_refresh_token(request).await?;
         let access_token = self.exchange_access_token(&refresh_token).await?;
 
         Ok(ExchangeGoogleTokenResponse {
@@ -72,6 +105,8 @@ impl EnclaveOperationService {
         code: String,
         redirect_uri: String,
     ) -> Result<CompleteGoogleConnectResponse, EnclaveRpcError> {
+        self.reserve_google_call(ProviderOperation::OAuthCodeExchange)
+            .await?;
         let response = self
             .http_client
             .post(&self.oauth.token_url)
@@ -118,6 +153,7 @@ impl EnclaveOperationService {
                 message: "oauth code exchange response missing refresh token".to_string(),
             })?;
 
+        let account_key = google_account_key(payload.id_token.as_deref())?;
         let granted_scopes = payload
             .scope
             .map(|scope| {
@@ -135,18 +171,15 @@ impl EnclaveOperationService {
             });
 
         let connector_id = self
-            .store
-            .upsert_google_connector(
+            .store_connector_secret(
                 user_id,
+                &account_key,
+                StoredConnector::Google {
+                    scopes: &granted_scopes,
+                },
                 &refresh_token,
-                &granted_scopes,
-                self.secret_runtime.kms_key_id(),
-                self.secret_runtime.kms_key_version(),
             )
-            .await
-            .map_err(|err| EnclaveRpcError::ConnectorTokenDecryptFailed {
-                message: err.to_string(),
-            })?;
+            .await?;
 
         Ok(CompleteGoogleConnectResponse {
             connector_id,
@@ -161,6 +194,8 @@ impl EnclaveOperationService {
         let (refresh_token, attested_identity) =
             self.load_authorized_refresh_token(&request).await?;
 
+        self.reserve_google_call(ProviderOperation::TokenRevoke)
+            .await?;
         let response = self
             .http_client
             .post(&self.oauth.revoke_url)
@@ -199,9 +234,23 @@ impl EnclaveOperationService {
         time_min: String,
         time_max: String,
         max_results: usize,
+    ) -> Result<FetchGoogleCalendarEventsResponse, EnclaveRpcError> {
+        let result = self
+            .fetch_google_calendar_events_inner(&request, time_min, time_max, max_results)
+            .await;
+        self.observe_connector_health(&request, &result).await;
+        result
+    }
+
+    async fn fetch_google_calendar_events_inner(
+        &self,
+        request: &ConnectorSecretRequest,
+        time_min: String,
+        time_max: String,
+        max_results: usize,
     ) -> Result<FetchGoogleCalendarEventsResponse, EnclaveRpcError> {
         let (refresh_token, attested_identity) =
-            self.load_authorized_refresh_token(&request).await?;
+            self.load_authorized_refresh_token(request).await?;
         let access_token = self.exchange_access_token(&refresh_token).await?;
         let max_results = max_results.to_string();
 
@@ -233,6 +282,19 @@ impl EnclaveOperationService {
                 end: event.end.map(|end| EnclaveGoogleCalendarEventDateTime {
                     date_time: end.date_time,
                 }),
+                location: event.location,
+                hangout_link: event.hangout_link,
+                conference_data: event.conference_data.map(|conference| {
+                    EnclaveGoogleCalendarConferenceData {
+                        entry_points: conference
+                            .entry_points
+                            .into_iter()
+                            .map(|entry_point| EnclaveGoogleCalendarConferenceEntryPoint {
+                                uri: entry_point.uri,
+                            })
+                            .collect(),
+                    }
+                }),
                 attendees: event
                     .attendees
                     .into_iter()
@@ -263,9 +325,22 @@ impl EnclaveOperationService {
         request: ConnectorSecretRequest,
         gmail_query: Option<String>,
         max_results: usize,
+    ) -> Result<FetchGoogleUrgentEmailCandidatesResponse, EnclaveRpcError> {
+        let result = self
+            .fetch_google_email_candidates_inner(&request, gmail_query, max_results)
+            .await;
+        self.observe_connector_health(&request, &result).await;
+        result
+    }
+
+    async fn fetch_google_email_candidates_inner(
+        &self,
+        request: &ConnectorSecretRequest,
+        gmail_query: Option<String>,
+        max_results: usize,
     ) -> Result<FetchGoogleUrgentEmailCandidatesResponse, EnclaveRpcError> {
         let (refresh_token, attested_identity) =
-            self.load_authorized_refresh_token(&request).await?;
+            self.load_authorized_refresh_token(request).await?;
         let access_token = self.exchange_access_token(&refresh_token).await?;
         let max_results = max_results.clamp(1, MAX_GMAIL_CANDIDATES).to_string();
         let mut query_params = vec![
@@ -312,51 +387,46 @@ impl EnclaveOperationService {
         })
     }
 
-    pub async fn resolve_active_google_connector_request(
+    /// Feeds the outcome of a connector-backed call into the connector's health score.
+    /// Health bookkeeping never fails the provider call itself.
+    async fn observe_connector_health<T>(
         &self,
-        user_id: Uuid,
-    ) -> Result<ConnectorSecretRequest, EnclaveRpcError> {
-        let connector = self
-            .store
-            .list_active_connector_metadata(user_id)
-            .await
-            .map_err(|err| EnclaveRpcError::ConnectorTokenDecryptFailed {
-                message: err.to_string(),
-            })?
-            .into_iter()
-            .find(|connector| connector.provider == "google")
-            .ok_or(EnclaveRpcError::ConnectorTokenUnavailable)?;
-
-        if connector.token_key_id != self.secret_runtime.kms_key_id()
-            || connector.token_version != self.secret_runtime.kms_key_version()
-        {
-            match self
+        request: &ConnectorSecretRequest,
+        result: &Result<T, EnclaveRpcError>,
+    ) {
+        if let Some(succeeded) = connector_availability_outcome(result)
+            && let Err(err) = self
                 .store
-                .ensure_active_connector_key_metadata(
-                    user_id,
-                    connector.connector_id,
-                    self.secret_runtime.kms_key_id(),
-                    self.secret_runtime.kms_key_version(),
+                .record_component_availability(
+                    AvailabilityComponent::Connectors,
+                    1,
+                    i64::from(succeeded),
                 )
                 .await
-            {
-                Ok(Some(_)) => {}
-                Ok(None) => return Err(EnclaveRpcError::ConnectorTokenUnavailable),
-                Err(err) => {
-                    return Err(EnclaveRpcError::ConnectorTokenDecryptFailed {
-                        message: err.to_string(),
-                    });
-                }
-            }
+        {
+            warn!("failed to record connector availability: {err}");
         }
 
-        Ok(ConnectorSecretRequest {
-            user_id,
-            connector_id: connector.connector_id,
-        })
+        let Some(signal) = connector_health_signal(result) else {
+            return;
+        };
+
+        if let Err(err) = self
+            .store
+            .record_connector_health_signal(request.connector_id, signal)
+            .await
+        {
+            warn!(
+                connector_id = %request.connector_id,
+                signal = signal.as_str(),
+                "failed to record connector health signal: {err}"
+            );
+        }
     }
 
     async fn exchange_access_token(&self, refresh_token: &str) -> Result<String, EnclaveRpcError> {
+        self.reserve_google_call(ProviderOperation::TokenRefresh)
+            .await?;
         let response = self
             .http_client
             .post(&self.oauth.token_url)
@@ -394,6 +464,26 @@ impl EnclaveOperationService {
         Ok(payload.access_token)
     }
 
+    async fn reserve_google_call(
+        &self,
+        operation: ProviderOperation,
+    ) -> Result<(), EnclaveRpcError> {
+        self.outbound_limiter
+            .acquire(OutboundProvider::Google)
+            .await
+            .map_err(|err| {
+                warn!(
+                    operation = %operation,
+                    retry_after_ms = err.retry_after_ms(),
+                    "google call rejected by outbound rate limit"
+                );
+                EnclaveRpcError::ProviderRateLimited {
+                    operation,
+                    retry_after_ms: err.retry_after_ms(),
+                }
+            })
+    }
+
     async fn send_google_json_request<T>(
         &self,
         request: RequestBuilder,
@@ -402,6 +492,7 @@ impl EnclaveOperationService {
     where
         T: DeserializeOwned,
     {
+        self.reserve_google_call(operation).await?;
         let response =
             request
                 .send()
@@ -430,6 +521,70 @@ impl EnclaveOperationService {
             })
     }
 
+    /// Seals `secret` for the user's connector for `account_key` and stores it. With a KMS
+    /// client the secret is sealed under a per-connector data key bound to the connector id,
+    /// so the id is settled first. If a concurrent connect of the same account created the
+    /// row in between, the secret is sealed again for that row's id.
+    async fn store_connector_secret(
+        &self,
+        user_id: uuid::Uuid,
+        account_key: &str,
+        connector: StoredConnector<'_>,
+        secret: &str,
+    ) -> Result<uuid::Uuid, EnclaveRpcError> {
+        let store_failed = |err: StoreError| EnclaveRpcError::ConnectorTokenDecryptFailed {
+            message: err.to_string(),
+        };
+        let provider = connector.provider();
+        let mut connector_id = self
+            .store
+            .connector_id_for_account(user_id, provider, account_key)
+            .await
+            .map_err(store_failed)?;
+        for _ in 0..2 {
+            let sealed = self
+                .secret_runtime
+                .wrap_connector_secret(user_id, provider, connector_id, secret)
+                .await
+                .map_err(|err| EnclaveRpcError::ConnectorTokenDecryptFailed {
+                    message: err.to_string(),
+                })?;
+            let account = ConnectorAccount {
+                user_id,
+                connector_id,
+                account_key,
+            };
+            let key_id = self.secret_runtime.kms_key_id();
+            let key_version = self.secret_runtime.kms_key_version();
+            let stored_id = match connector {
+                StoredConnector::Google { scopes } => {
+                    self.store
+                        .upsert_google_connector(account, &sealed, scopes, key_id, key_version)
+                        .await
+                }
+                StoredConnector::Caldav => {
+                    self.store
+                        .upsert_caldav_connector(account, &sealed, key_id, key_version)
+                        .await
+                }
+                StoredConnector::Imap => {
+                    self.store
+                        .upsert_imap_connector(account, &sealed, key_id, key_version)
+                        .await
+                }
+            }
+            .map_err(store_failed)?;
+            if stored_id == connector_id {
+                return Ok(connector_id);
+            }
+            connector_id = stored_id;
+        }
+
+        Err(EnclaveRpcError::ConnectorTokenDecryptFailed {
+            message: "connector was replaced while its secret was being stored".to_string(),
+        })
+    }
+
     async fn load_authorized_refresh_token(
         &self,
         request: &ConnectorSecretRequest,
@@ -460,7 +615,7 @@ impl EnclaveOperationService {
                 request.user_id,
                 request.connector_id,
                 &PersistedConnectorKeyMetadata {
-                    provider: connector_metadata.provider,
+                    provider: connector_metadata.provider.clone(),
                     token_key_id: connector_metadata.token_key_id,
                     token_version: connector_metadata.token_version,
                 },
@@ -470,6 +625,18 @@ impl EnclaveOperationService {
                 message: err.to_string(),
             })?
             .ok_or(EnclaveRpcError::ConnectorTokenUnavailable)?;
+        let refresh_token = self
+            .secret_runtime
+            .unwrap_connector_secret(
+                request.user_id,
+                &connector_metadata.provider,
+                request.connector_id,
+                refresh_token,
+            )
+            .await
+            .map_err(|err| EnclaveRpcError::ConnectorTokenDecryptFailed {
+                message: err.to_string(),
+            })?;
 
         Ok((
             refresh_token,
@@ -480,3 +647,54 @@ impl EnclaveOperationService {
         ))
     }
 }
+
+/// Which connector [`EnclaveOperationService::store_connector_secret`] writes.
+#[derive(Clone, Copy)]
+pub(super) enum StoredConnector<'a> {
+    Google { scopes: &'a [String] },
+    Caldav,
+    Imap,
+}
+
+impl StoredConnector<'_> {
+    fn provider(self) -> &'static str {
+        match self {
+            Self::Google { .. } => "google",
+            Self::Caldav => "caldav",
+            Self::Imap => "imap",
+        }
+    }
+}
+
+/// Maps a provider call outcome to a health signal. Transport errors and 5xx responses say
+/// nothing about the grant itself, so they are ignored.
+/// Whether a provider call counts toward connector availability. Failures caused by the
+/// user's own grant (revoked or unauthorized tokens) are left out: they are not outages.
+fn connector_availability_outcome<T>(result: &Result<T, EnclaveRpcError>) -> Option<bool> {
+    match result {
+        Ok(_) => Some(true),
+        Err(EnclaveRpcError::ProviderRequestUnavailable { .. })
+        | Err(EnclaveRpcError::ProviderResponseInvalid { .. }) => Some(false),
+        Err(EnclaveRpcError::ProviderRequestFailed { status, .. }) if *status >= 500 => Some(false),
+        Err(_) => None,
+    }
+}
+
+fn connector_health_signal<T>(
+    result: &Result<T, EnclaveRpcError>,
+) -> Option<ConnectorHealthSignal> {
+    match result {
+        Ok(_) => Some(ConnectorHealthSignal::Healthy),
+        Err(EnclaveRpcError::ProviderRequestFailed {
+            operation: ProviderOperation::TokenRefresh,
+            status: 400 | 401,
+            ..
+        }) => Some(ConnectorHealthSignal::RefreshFailed),
+        Err(EnclaveRpcError::ProviderRequestFailed {
+            operation: ProviderOperation::CalendarFetch | ProviderOperation::GmailFetch,
+            status: 401,
+            ..
+        }) => Some(ConnectorHealthSignal::ProviderUnauthorized),
+        Err(_) => None,
+    }
+}
@@ -0,0 +1,215 @@
+use std::time::Duration;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use reqwest::{Method, StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::caldav::{normalize_caldav_server_url, normalize_caldav_username};
+use crate::network_policy::resolve_public_address;
+
+use super::super::{
+    CompleteCaldavConnectResponse, ConnectorSecretRequest, EnclaveRpcError,
+    FetchGoogleCalendarEventsResponse, ProviderOperation,
+};
+use super::caldav_ical::{event_overlaps_window, extract_calendar_data, parse_calendar_events};
+use super::connectors::{account_key_digest, merge_calendar_events, parse_timestamp};
+use super::{EnclaveOperationService, StoredConnector};
+
+const CALDAV_REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+const CALDAV_PROBE_BODY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<D:propfind xmlns:D="DAV:"><D:prop><D:resourcetype/></D:prop></D:propfind>"#;
+
+/// Credentials of a CalDAV connector. They are serialized into the connector's encrypted
+/// secret column, so the app password never leaves the enclave in plaintext.
+#[derive(Debug, Serialize, Deserialize)]
+struct CaldavCredentials {
+    server_url: String,
+    username: String,
+    app_password: String,
+}
+
+impl EnclaveOperationService {
+    /// Verifies the CalDAV credentials against the calendar collection and stores them as a
+    /// connector. Reconnecting the same collection and username updates it in place.
+    pub async fn complete_caldav_connect(
+        &self,
+        user_id: Uuid,
+        server_url: &str,
+        username: &str,
+        app_password: String,
+    ) -> Result<CompleteCaldavConnectResponse, EnclaveRpcError> {
+        let invalid = |message: &str| EnclaveRpcError::ProviderResponseInvalid {
+            operation: ProviderOperation::CaldavConnect,
+            message: message.to_string(),
+        };
+        let credentials = CaldavCredentials {
+            server_url: normalize_caldav_server_url(server_url).map_err(invalid)?,
+            username: normalize_caldav_username(username).map_err(invalid)?,
+            app_password,
+        };
+        if credentials.app_password.trim().is_empty() {
+            return Err(invalid("app_password must not be empty"));
+        }
+
+        self.send_caldav_request(
+            &credentials,
+            Method::from_bytes(b"PROPFIND").expect("PROPFIND is a valid method"),
+            "0",
+            CALDAV_PROBE_BODY.to_string(),
+            ProviderOperation::CaldavConnect,
+        )
+        .await?;
+
+        let account_key = account_key_digest(&format!(
+            "caldav:{}\n{}",
+            credentials.server_url,
+            credentials.username.to_lowercase()
+        ));
+        let secret = serde_json::to_string(&credentials).map_err(|err| {
+            EnclaveRpcError::ConnectorTokenDecryptFailed {
+                message: err.to_string(),
+            }
+        })?;
+        let connector_id = self
+            .store_connector_secret(user_id, &account_key, StoredConnector::Caldav, &secret)
+            .await?;
+
+        Ok(CompleteCaldavConnectResponse { connector_id })
+    }
+
+    /// Fetches the events of a CalDAV collection overlapping `[time_min, time_max)`, with
+    /// recurring events expanded by the server.
+    pub(super) async fn fetch_caldav_calendar_events(
+        &self,
+        request: &ConnectorSecretRequest,
+        time_min: &str,
+        time_max: &str,
+        max_results: usize,
+    ) -> Result<FetchGoogleCalendarEventsResponse, EnclaveRpcError> {
+        let invalid = |message: &str| EnclaveRpcError::ProviderResponseInvalid {
+            operation: ProviderOperation::CaldavFetch,
+            message: message.to_string(),
+        };
+        let window_start =
+            parse_timestamp(time_min).ok_or_else(|| invalid("time_min must be RFC3339"))?;
+        let window_end =
+            parse_timestamp(time_max).ok_or_else(|| invalid("time_max must be RFC3339"))?;
+
+        let (secret, attested_identity) = self.load_authorized_refresh_token(request).await?;
+        let credentials = serde_json::from_str::<CaldavCredentials>(&secret)
+            .map_err(|_| invalid("stored caldav credentials are malformed"))?;
+
+        let body = self
+            .send_caldav_request(
+                &credentials,
+                Method::from_bytes(b"REPORT").expect("REPORT is a valid method"),
+                "1",
+                calendar_query_body(window_start, window_end),
+                ProviderOperation::CaldavFetch,
+            )
+            .await?;
+
+        let events = extract_calendar_data(&body)
+            .iter()
+            .flat_map(|ics| parse_calendar_events(ics))
+            .filter(|event| event_overlaps_window(event, window_start, window_end))
+            .collect::<Vec<_>>();
+
+        Ok(FetchGoogleCalendarEventsResponse {
+            attested_identity,
+            events: merge_calendar_events(events, max_results),
+        })
+    }
+
+    async fn send_caldav_request(
+        &self,
+        credentials: &CaldavCredentials,
+        method: Method,
+        depth: &str,
+        body: String,
+        operation: ProviderOperation,
+    ) -> Result<String, EnclaveRpcError> {
+        let response = pinned_caldav_client(&credentials.server_url, operation)
+            .await?
+            .request(method, &credentials.server_url)
+            .basic_auth(&credentials.username, Some(&credentials.app_password))
+            .header("Depth", depth)
+            .header(
+                reqwest::header::CONTENT_TYPE,
+                "application/xml; charset=utf-8",
+            )
+            .body(body)
+            .send()
+            .await
+            .map_err(|err| EnclaveRpcError::ProviderRequestUnavailable {
+                operation,
+                message: err.to_string(),
+            })?;
+
+        if response.status() != StatusCode::MULTI_STATUS {
+            return Err(EnclaveRpcError::ProviderRequestFailed {
+                operation,
+                status: response.status().as_u16(),
+                oauth_error: None,
+            });
+        }
+
+        response
+            .text()
+            .await
+            .map_err(|err| EnclaveRpcError::ProviderResponseInvalid {
+                operation,
+                message: err.to_string(),
+            })
+    }
+}
+
+/// The server URL is user-supplied, so the request goes to an address checked to be public,
+/// never to a second DNS answer, and redirects are not followed: a 30x to an internal host
+/// fails like any other non-207 response.
+async fn pinned_caldav_client(
+    server_url: &str,
+    operation: ProviderOperation,
+) -> Result<reqwest::Client, EnclaveRpcError> {
+    let unavailable =
+        |message: String| EnclaveRpcError::ProviderRequestUnavailable { operation, message };
+    let url = Url::parse(server_url).map_err(|err| unavailable(err.to_string()))?;
+    let host = url
+        .host_str()
+        .ok_or_else(|| unavailable("caldav server_url has no host".to_string()))?;
+    let address = resolve_public_address(host, url.port_or_known_default().unwrap_or(443))
+        .await
+        .map_err(unavailable)?;
+
+    reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .timeout(CALDAV_REQUEST_TIMEOUT)
+        .resolve(host, address)
+        .build()
+        .map_err(|err| unavailable(err.to_string()))
+}
+
+fn calendar_query_body(window_start: DateTime<Utc>, window_end: DateTime<Utc>) -> String {
+    let format = |value: DateTime<Utc>| {
+        value
+            .to_rfc3339_opts(SecondsFormat::Secs, true)
+            .replace(['-', ':'], "")
+    };
+    let (start, end) = (format(window_start), format(window_end));
+
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<C:calendar-query xmlns:D="DAV:" xmlns:C="urn:ietf:params:xml:ns:caldav">
+  <D:prop>
+    <C:calendar-data><C:expand start="{start}" end="{end}"/></C:calendar-data>
+  </D:prop>
+  <C:filter>
+    <C:comp-filter name="VCALENDAR">
+      <C:comp-filter name="VEVENT"><C:time-range start="{start}" end="{end}"/></C:comp-filter>
+    </C:comp-filter>
+  </C:filter>
+</C:calendar-query>"#
+    )
+}
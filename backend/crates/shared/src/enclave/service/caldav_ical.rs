@@ -0,0 +1,430 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, SecondsFormat, TimeZone, Utc};
+use chrono_tz::Tz;
+
+use crate::enclave::{
+    EnclaveGoogleCalendarAttendee, EnclaveGoogleCalendarEvent, EnclaveGoogleCalendarEventDateTime,
+};
+
+/// Pulls the iCalendar payloads out of a CalDAV `calendar-query` multistatus response. The
+/// namespace prefix varies by server, so elements are matched on their local name.
+pub(super) fn extract_calendar_data(multistatus: &str) -> Vec<String> {
+    let mut payloads = Vec::new();
+    let mut rest = multistatus;
+
+    while let Some(open_start) = rest.find('<') {
+        rest = &rest[open_start + 1..];
+        let name_end = rest
+            .find(|ch: char| ch.is_whitespace() || ch == '>' || ch == '/')
+            .unwrap_or(rest.len());
+        let qualified_name = &rest[..name_end];
+        let local_name = qualified_name.rsplit(':').next().unwrap_or(qualified_name);
+        if local_name != "calendar-data" {
+            continue;
+        }
+
+        let Some(tag_end) = rest.find('>') else {
+            break;
+        };
+        if rest[..tag_end].ends_with('/') {
+            rest = &rest[tag_end + 1..];
+            continue;
+        }
+
+        let content = &rest[tag_end + 1..];
+        let closing_tag = format!("</{qualified_name}>");
+        let Some(close_start) = content.find(&closing_tag) else {
+            break;
+        };
+        payloads.push(decode_xml_text(&content[..close_start]));
+        rest = &content[close_start + closing_tag.len()..];
+    }
+
+    payloads
+}
+
+fn decode_xml_text(raw: &str) -> String {
+    let trimmed = raw.trim();
+    if let Some(cdata) = trimmed
+        .strip_prefix("<![CDATA[")
+        .and_then(|value| value.strip_suffix("]]>"))
+    {
+        return cdata.to_string();
+    }
+
+    let mut decoded = String::with_capacity(raw.len());
+    let mut rest = raw;
+    while let Some(amp) = rest.find('&') {
+        decoded.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let Some(semicolon) = rest.find(';') else {
+            break;
+        };
+        let entity = &rest[1..semicolon];
+        let replacement = match entity {
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "amp" => Some('&'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity
+                .strip_prefix("#x")
+                .or_else(|| entity.strip_prefix("#X"))
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| entity.strip_prefix('#').map(str::parse::<u32>))
+                .and_then(Result::ok)
+                .and_then(char::from_u32),
+        };
+        match replacement {
+            Some(ch) => {
+                decoded.push(ch);
+                rest = &rest[semicolon + 1..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+#[derive(Debug, Default)]
+struct EventBuilder {
+    uid: Option<String>,
+    recurrence_id: Option<String>,
+    summary: Option<String>,
+    location: Option<String>,
+    conference: Option<String>,
+    start: Option<String>,
+    end: Option<String>,
+    attendees: Vec<String>,
+    cancelled: bool,
+}
+
+impl EventBuilder {
+    fn build(self) -> Option<EnclaveGoogleCalendarEvent> {
+        if self.cancelled {
+            return None;
+        }
+
+        // Expanded recurring instances share a UID, so the recurrence id keeps them apart.
+        let id = match (self.uid, self.recurrence_id) {
+            (Some(uid), Some(recurrence_id)) => Some(format!("{uid}/{recurrence_id}")),
+            (uid, _) => uid,
+        };
+
+        Some(EnclaveGoogleCalendarEvent {
+            id,
+            summary: self.summary,
+            start: Some(EnclaveGoogleCalendarEventDateTime {
+                date_time: self.start,
+            }),
+            end: Some(EnclaveGoogleCalendarEventDateTime {
+                date_time: self.end,
+            }),
+            location: self.location,
+            hangout_link: self.conference,
+            conference_data: None,
+            attendees: self
+                .attendees
+                .into_iter()
+                .map(|email| EnclaveGoogleCalendarAttendee { email: Some(email) })
+                .collect(),
+        })
+    }
+}
+
+/// Parses the VEVENTs of an iCalendar document into the calendar event shape the assistant
+/// already consumes. All-day events keep an empty `date_time`, matching Google's output.
+pub(super) fn parse_calendar_events(ics: &str) -> Vec<EnclaveGoogleCalendarEvent> {
+    let mut events = Vec::new();
+    let mut current: Option<EventBuilder> = None;
+    let mut nested_depth = 0_usize;
+
+    for line in unfold_lines(ics) {
+        let Some(property) = ContentLine::parse(&line) else {
+            continue;
+        };
+
+        match (property.name.as_str(), property.value) {
+            ("BEGIN", "VEVENT") if current.is_none() => current = Some(EventBuilder::default()),
+            ("END", "VEVENT") if nested_depth == 0 => {
+                if let Some(event) = current.take().and_then(EventBuilder::build) {
+                    events.push(event);
+                }
+            }
+            ("BEGIN", _) if current.is_some() => nested_depth += 1,
+            ("END", _) if current.is_some() => nested_depth = nested_depth.saturating_sub(1),
+            _ if nested_depth > 0 => {}
+            (name, value) => {
+                if let Some(event) = current.as_mut() {
+                    apply_property(event, name, value, &property);
+                }
+            }
+        }
+    }
+
+    events
+}
+
+fn apply_property(event: &mut EventBuilder, name: &str, value: &str, property: &ContentLine) {
+    match name {
+        "UID" => event.uid = Some(value.to_string()),
+        "RECURRENCE-ID" => event.recurrence_id = Some(value.to_string()),
+        "SUMMARY" => event.summary = Some(unescape_text(value)),
+        "LOCATION" => event.location = Some(unescape_text(value)).filter(|value| !value.is_empty()),
+        "CONFERENCE" | "X-GOOGLE-CONFERENCE" => event.conference = Some(value.to_string()),
+        "DTSTART" => event.start = parse_ical_datetime(value, property),
+        "DTEND" => event.end = parse_ical_datetime(value, property),
+        "STATUS" => event.cancelled = value.eq_ignore_ascii_case("CANCELLED"),
+        "ATTENDEE" => {
+            let email = value
+                .get(..7)
+                .filter(|scheme| scheme.eq_ignore_ascii_case("mailto:"))
+                .map_or(value, |_| &value[7..]);
+            if !email.is_empty() {
+                event.attendees.push(email.to_string());
+            }
+        }
+        _ => {}
+    }
+}
+
+fn unfold_lines(ics: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for raw in ics.split('\n') {
+        let raw = raw.strip_suffix('\r').unwrap_or(raw);
+        match (raw.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(previous)) => previous.push_str(continuation),
+            _ => lines.push(raw.to_string()),
+        }
+    }
+    lines
+}
+
+struct ContentLine<'a> {
+    name: String,
+    params: Vec<(String, String)>,
+    value: &'a str,
+}
+
+impl<'a> ContentLine<'a> {
+    /// Splits `NAME;PARAM=VALUE:value`, ignoring colons inside quoted parameter values.
+    fn parse(line: &'a str) -> Option<Self> {
+        let mut in_quotes = false;
+        let value_start = line.char_indices().find_map(|(index, ch)| match ch {
+            '"' => {
+                in_quotes = !in_quotes;
+                None
+            }
+            ':' if !in_quotes => Some(index),
+            _ => None,
+        })?;
+
+        let mut parts = line[..value_start].split(';');
+        let name = parts.next()?.trim().to_ascii_uppercase();
+        let params = parts
+            .filter_map(|param| {
+                let (key, value) = param.split_once('=')?;
+                Some((
+                    key.trim().to_ascii_uppercase(),
+                    value.trim().trim_matches('"').to_string(),
+                ))
+            })
+            .collect();
+
+        Some(Self {
+            name,
+            params,
+            value: line[value_start + 1..].trim(),
+        })
+    }
+
+    fn param(&self, key: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+fn parse_ical_datetime(value: &str, property: &ContentLine) -> Option<String> {
+    if property
+        .param("VALUE")
+        .is_some_and(|kind| kind.eq_ignore_ascii_case("DATE"))
+        || NaiveDate::parse_from_str(value, "%Y%m%d").is_ok()
+    {
+        return None;
+    }
+
+    let utc = if let Some(utc_value) = value.strip_suffix('Z') {
+        NaiveDateTime::parse_from_str(utc_value, "%Y%m%dT%H%M%S")
+            .ok()?
+            .and_utc()
+    } else {
+        let local = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+        // Floating times and unknown zone names are read as UTC rather than dropped.
+        match property
+            .param("TZID")
+            .and_then(|tzid| tzid.trim_start_matches('/').parse::<Tz>().ok())
+        {
+            Some(tz) => tz
+                .from_local_datetime(&local)
+                .earliest()?
+                .with_timezone(&Utc),
+            None => Utc.from_utc_datetime(&local),
+        }
+    };
+
+    Some(utc.to_rfc3339_opts(SecondsFormat::Secs, true))
+}
+
+/// Whether a timed event overlaps `[time_min, time_max)`. All-day events are kept, since the
+/// server already matched them against the window.
+pub(super) fn event_overlaps_window(
+    event: &EnclaveGoogleCalendarEvent,
+    time_min: DateTime<Utc>,
+    time_max: DateTime<Utc>,
+) -> bool {
+    let parse = |value: Option<&EnclaveGoogleCalendarEventDateTime>| {
+        value
+            .and_then(|value| value.date_time.as_deref())
+            .and_then(|value| DateTime::parse_from_rfc3339(value).ok())
+            .map(|value| value.with_timezone(&Utc))
+    };
+    let Some(start) = parse(event.start.as_ref()) else {
+        return true;
+    };
+    let end = parse(event.end.as_ref()).unwrap_or(start);
+
+    start < time_max && (end > time_min || start >= time_min)
+}
+
+fn unescape_text(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            unescaped.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => unescaped.push('\n'),
+            Some(other) => unescaped.push(other),
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Utc};
+
+    use super::{event_overlaps_window, extract_calendar_data, parse_calendar_events};
+
+    const MULTISTATUS: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:multistatus xmlns:d="DAV:" xmlns:cal="urn:ietf:params:xml:ns:caldav">
+  <d:response>
+    <d:href>/dav/calendars/user/me/default/standup.ics</d:href>
+    <d:propstat><d:prop><cal:calendar-data>BEGIN:VCALENDAR
+BEGIN:VEVENT
+UID:standup-1
+SUMMARY:Standup \, daily
+DTSTART;TZID=Europe/Berlin:20260302T090000
+DTEND;TZID=Europe/Berlin:20260302T091500
+LOCATION:Room &lt;4&gt; &amp; lobby
+ATTENDEE;CN="Team: core";ROLE=REQ-PARTICIPANT:mailto:team@example.com
+BEGIN:VALARM
+ACTION:DISPLAY
+SUMMARY:ignored alarm text
+END:VALARM
+END:VEVENT
+END:VCALENDAR
+</cal:calendar-data></d:prop></d:propstat>
+  </d:response>
+  <d:response>
+    <d:href>/dav/calendars/user/me/default/offsite.ics</d:href>
+    <d:propstat><d:prop><C:calendar-data xmlns:C="urn:ietf:params:xml:ns:caldav"><![CDATA[BEGIN:VCALENDAR
+BEGIN:VEVENT
+UID:offsite
+RECURRENCE-ID:20260302T140000Z
+SUMMARY:Offsite planning with a very long title that the server folded across
+  two lines
+DTSTART:20260302T140000Z
+DTEND:20260302T150000Z
+END:VEVENT
+BEGIN:VEVENT
+UID:holiday
+SUMMARY:Holiday
+DTSTART;VALUE=DATE:20260302
+END:VEVENT
+BEGIN:VEVENT
+UID:cancelled
+STATUS:CANCELLED
+DTSTART:20260302T100000Z
+END:VEVENT
+END:VCALENDAR]]></C:calendar-data></d:prop></d:propstat>
+  </d:response>
+  <d:response><d:propstat><d:prop><cal:calendar-data/></d:prop></d:propstat></d:response>
+</d:multistatus>"#;
+
+    #[test]
+    fn extracts_events_from_a_multistatus_response() {
+        let events = extract_calendar_data(MULTISTATUS)
+            .iter()
+            .flat_map(|payload| parse_calendar_events(payload))
+            .collect::<Vec<_>>();
+
+        assert_eq!(events.len(), 3);
+        let standup = &events[0];
+        assert_eq!(standup.id.as_deref(), Some("standup-1"));
+        assert_eq!(standup.summary.as_deref(), Some("Standup , daily"));
+        assert_eq!(standup.location.as_deref(), Some("Room <4> & lobby"));
+        assert_eq!(
+            standup
+                .start
+                .as_ref()
+                .and_then(|start| start.date_time.as_deref()),
+            Some("2026-03-02T08:00:00Z")
+        );
+        assert_eq!(
+            standup.attendees[0].email.as_deref(),
+            Some("team@example.com")
+        );
+
+        let offsite = &events[1];
+        assert_eq!(offsite.id.as_deref(), Some("offsite/20260302T140000Z"));
+        assert_eq!(
+            offsite.summary.as_deref(),
+            Some("Offsite planning with a very long title that the server folded across two lines")
+        );
+
+        let holiday = &events[2];
+        assert_eq!(
+            holiday
+                .start
+                .as_ref()
+                .and_then(|start| start.date_time.as_deref()),
+            None
+        );
+    }
+
+    #[test]
+    fn window_filter_keeps_overlapping_and_all_day_events() {
+        let events = extract_calendar_data(MULTISTATUS)
+            .iter()
+            .flat_map(|payload| parse_calendar_events(payload))
+            .collect::<Vec<_>>();
+        let time_min = "2026-03-02T08:10:00Z".parse::<DateTime<Utc>>().unwrap();
+        let time_max = "2026-03-02T14:00:00Z".parse::<DateTime<Utc>>().unwrap();
+
+        let kept = events
+            .iter()
+            .filter(|event| event_overlaps_window(event, time_min, time_max))
+            .filter_map(|event| event.id.as_deref())
+            .collect::<Vec<_>>();
+        assert_eq!(kept, vec!["standup-1", "holiday"]);
+    }
+}
@@ -0,0 +1,405 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::repos::ActiveConnectorMetadata;
+
+use super::super::{
+    ConnectorSecretRequest, EnclaveGoogleCalendarEvent, EnclaveGoogleEmailCandidate,
+    EnclaveRpcError, FetchGoogleCalendarEventsResponse, FetchGoogleUrgentEmailCandidatesResponse,
+};
+use super::EnclaveOperationService;
+
+/// Calendar backends the assistant reads events from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CalendarProvider {
+    Google,
+    Caldav,
+}
+
+impl CalendarProvider {
+    fn from_connector_provider(provider: &str) -> Option<Self> {
+        match provider {
+            "google" => Some(Self::Google),
+            "caldav" => Some(Self::Caldav),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CalendarConnectorRequest {
+    pub provider: CalendarProvider,
+    pub connector: ConnectorSecretRequest,
+}
+
+/// Mailbox backends the assistant reads email candidates from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmailProvider {
+    Google,
+    Imap,
+}
+
+impl EmailProvider {
+    fn from_connector_provider(provider: &str) -> Option<Self> {
+        match provider {
+            "google" => Some(Self::Google),
+            "imap" => Some(Self::Imap),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EmailConnectorRequest {
+    pub provider: EmailProvider,
+    pub connector: ConnectorSecretRequest,
+}
+
+impl EnclaveOperationService {
+    /// Resolves every active connector of the user, oldest first, with key metadata brought up
+    /// to the current KMS key. Connectors revoked meanwhile are left out.
+    pub(super) async fn resolve_active_connectors(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<(String, ConnectorSecretRequest)>, EnclaveRpcError> {
+        let connectors = self
+            .store
+            .list_active_connector_metadata(user_id)
+            .await
+            .map_err(|err| EnclaveRpcError::ConnectorTokenDecryptFailed {
+                message: err.to_string(),
+            })?;
+
+        let mut requests = Vec::new();
+        for connector in connectors {
+            if self
+                .ensure_current_connector_key_metadata(user_id, &connector)
+                .await?
+            {
+                requests.push((
+                    connector.provider,
+                    ConnectorSecretRequest {
+                        user_id,
+                        connector_id: connector.connector_id,
+                    },
+                ));
+            }
+        }
+
+        Ok(requests)
+    }
+
+    /// Returns `false` when the connector stopped being active while its key metadata was
+    /// being brought up to the current KMS key.
+    async fn ensure_current_connector_key_metadata(
+        &self,
+        user_id: Uuid,
+        connector: &ActiveConnectorMetadata,
+    ) -> Result<bool, EnclaveRpcError> {
+        if connector.token_key_id == self.secret_runtime.kms_key_id()
+            && connector.token_version == self.secret_runtime.kms_key_version()
+        {
+            return Ok(true);
+        }
+
+        self.store
+            .ensure_active_connector_key_metadata(
+                user_id,
+                connector.connector_id,
+                self.secret_runtime.kms_key_id(),
+                self.secret_runtime.kms_key_version(),
+            )
+            .await
+            .map(|metadata| metadata.is_some())
+            .map_err(|err| EnclaveRpcError::ConnectorTokenDecryptFailed {
+                message: err.to_string(),
+            })
+    }
+
+    /// Resolves every active connector that can serve calendar events: Google accounts and
+    /// CalDAV calendars. Fails with `ConnectorTokenUnavailable` when the user has none.
+    pub async fn resolve_active_calendar_connector_requests(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<CalendarConnectorRequest>, EnclaveRpcError> {
+        let requests = self
+            .resolve_active_connectors(user_id)
+            .await?
+            .into_iter()
+            .filter_map(|(provider, connector)| {
+                CalendarProvider::from_connector_provider(&provider).map(|provider| {
+                    CalendarConnectorRequest {
+                        provider,
+                        connector,
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        if requests.is_empty() {
+            return Err(EnclaveRpcError::ConnectorTokenUnavailable);
+        }
+        Ok(requests)
+    }
+
+    /// Fetches events from every calendar connector and merges them by start time. A failing
+    /// connector is skipped as long as another one answered; the first error is returned only
+    /// when every connector failed.
+    pub async fn fetch_calendar_events_for_connectors(
+        &self,
+        connectors: &[CalendarConnectorRequest],
+        time_min: &str,
+        time_max: &str,
+        max_results: usize,
+    ) -> Result<FetchGoogleCalendarEventsResponse, EnclaveRpcError> {
+        let mut merged: Option<FetchGoogleCalendarEventsResponse> = None;
+        let mut first_error = None;
+        for connector in connectors {
+            let result = match connector.provider {
+                CalendarProvider::Google => {
+                    self.fetch_google_calendar_events(
+                        connector.connector.clone(),
+                        time_min.to_string(),
+                        time_max.to_string(),
+                        max_results,
+                    )
+                    .await
+                }
+                CalendarProvider::Caldav => {
+                    self.fetch_caldav_calendar_events(
+                        &connector.connector,
+                        time_min,
+                        time_max,
+                        max_results,
+                    )
+                    .await
+                }
+            };
+
+            match result {
+                Ok(response) => match merged.as_mut() {
+                    Some(merged) => merged.events.extend(response.events),
+                    None => merged = Some(response),
+                },
+                Err(err) => {
+                    warn!(
+                        connector_id = %connector.connector.connector_id,
+                        provider = ?connector.provider,
+                        "skipping calendar connector for event fetch: {err}"
+                    );
+                    first_error.get_or_insert(err);
+                }
+            }
+        }
+
+        let Some(mut merged) = merged else {
+            return Err(first_error.unwrap_or(EnclaveRpcError::ConnectorTokenUnavailable));
+        };
+        merged.events = merge_calendar_events(merged.events, max_results);
+        Ok(merged)
+    }
+
+    /// Resolves every active connector that can serve email candidates: Google accounts and
+    /// IMAP mailboxes. Fails with `ConnectorTokenUnavailable` when the user has none.
+    pub async fn resolve_active_email_connector_requests(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<EmailConnectorRequest>, EnclaveRpcError> {
+        let requests = self
+            .resolve_active_connectors(user_id)
+            .await?
+            .into_iter()
+            .filter_map(|(provider, connector)| {
+                EmailProvider::from_connector_provider(&provider).map(|provider| {
+                    EmailConnectorRequest {
+                        provider,
+                        connector,
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        if requests.is_empty() {
+            return Err(EnclaveRpcError::ConnectorTokenUnavailable);
+        }
+        Ok(requests)
+    }
+
+    /// Fetches email candidates from every email connector and merges them newest first, with
+    /// the same partial-failure handling as calendar fetches. `gmail_query` only narrows Gmail
+    /// accounts; IMAP mailboxes return their recent unread messages and callers filter those.
+    pub async fn fetch_email_candidates_for_connectors(
+        &self,
+        connectors: &[EmailConnectorRequest],
+        gmail_query: Option<&str>,
+        max_results: usize,
+    ) -> Result<FetchGoogleUrgentEmailCandidatesResponse, EnclaveRpcError> {
+        let mut merged: Option<FetchGoogleUrgentEmailCandidatesResponse> = None;
+        let mut first_error = None;
+        for connector in connectors {
+            let result = match connector.provider {
+                EmailProvider::Google => {
+                    self.fetch_google_email_candidates(
+                        connector.connector.clone(),
+                        gmail_query.map(ToString::to_string),
+                        max_results,
+                    )
+                    .await
+                }
+                EmailProvider::Imap => {
+                    self.fetch_imap_email_candidates(&connector.connector, max_results)
+                        .await
+                }
+            };
+
+            match result {
+                Ok(response) => match merged.as_mut() {
+                    Some(merged) => merged.candidates.extend(response.candidates),
+                    None => merged = Some(response),
+                },
+                Err(err) => {
+                    warn!(
+                        connector_id = %connector.connector.connector_id,
+                        provider = ?connector.provider,
+                        "skipping email connector for email fetch: {err}"
+                    );
+                    first_error.get_or_insert(err);
+                }
+            }
+        }
+
+        let Some(mut merged) = merged else {
+            return Err(first_error.unwrap_or(EnclaveRpcError::ConnectorTokenUnavailable));
+        };
+        merged.candidates = merge_email_candidates(merged.candidates, max_results);
+        Ok(merged)
+    }
+}
+
+/// Hex SHA-256 of a provider account identity; the raw identity is never stored.
+pub(super) fn account_key_digest(identity: &str) -> String {
+    Sha256::digest(identity.as_bytes())
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+pub(super) fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|value| value.with_timezone(&Utc))
+}
+
+/// Orders events by start time and drops duplicates of an event that appears on several
+/// connected calendars.
+pub(super) fn merge_calendar_events(
+    mut events: Vec<EnclaveGoogleCalendarEvent>,
+    max_results: usize,
+) -> Vec<EnclaveGoogleCalendarEvent> {
+    events.sort_by_key(|event| {
+        event
+            .start
+            .as_ref()
+            .and_then(|start| start.date_time.as_deref())
+            .and_then(parse_timestamp)
+            .unwrap_or(DateTime::<Utc>::MAX_UTC)
+    });
+
+    let mut seen = HashSet::new();
+    events.retain(|event| event.id.as_ref().is_none_or(|id| seen.insert(id.clone())));
+    events.truncate(max_results);
+    events
+}
+
+pub(super) fn merge_email_candidates(
+    mut candidates: Vec<EnclaveGoogleEmailCandidate>,
+    max_results: usize,
+) -> Vec<EnclaveGoogleEmailCandidate> {
+    candidates.sort_by_key(|candidate| {
+        std::cmp::Reverse(
+            candidate
+                .received_at
+                .as_deref()
+                .and_then(parse_timestamp)
+                .unwrap_or(DateTime::<Utc>::MIN_UTC),
+        )
+    });
+    candidates.truncate(max_results);
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{merge_calendar_events, merge_email_candidates};
+    use crate::enclave::{
+        EnclaveGoogleCalendarEvent, EnclaveGoogleCalendarEventDateTime, EnclaveGoogleEmailCandidate,
+    };
+
+    fn event(id: &str, start: &str) -> EnclaveGoogleCalendarEvent {
+        EnclaveGoogleCalendarEvent {
+            id: Some(id.to_string()),
+            summary: None,
+            start: Some(EnclaveGoogleCalendarEventDateTime {
+                date_time: Some(start.to_string()),
+            }),
+            end: None,
+            location: None,
+            hangout_link: None,
+            conference_data: None,
+            attendees: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn merged_events_are_ordered_deduplicated_and_capped() {
+        let merged = merge_calendar_events(
+            vec![
+                event("b", "2026-03-02T10:00:00Z"),
+                event("a", "2026-03-02T09:00:00+01:00"),
+                event("b", "2026-03-02T10:00:00Z"),
+                event("c", "2026-03-02T11:00:00Z"),
+            ],
+            2,
+        );
+
+        let ids = merged
+            .iter()
+            .map(|event| event.id.as_deref().unwrap_or_default())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec!["a", "b"]);
+    }
+
+    fn candidate(id: &str, received_at: &str) -> EnclaveGoogleEmailCandidate {
+        EnclaveGoogleEmailCandidate {
+            message_id: Some(id.to_string()),
+            from: None,
+            subject: None,
+            snippet: None,
+            received_at: Some(received_at.to_string()),
+            label_ids: Vec::new(),
+            has_attachments: false,
+        }
+    }
+
+    #[test]
+    fn merged_email_candidates_are_newest_first() {
+        let merged = merge_email_candidates(
+            vec![
+                candidate("old", "2026-03-01T08:00:00Z"),
+                candidate("new", "2026-03-02T08:00:00Z"),
+                candidate("mid", "2026-03-01T20:00:00Z"),
+            ],
+            2,
+        );
+
+        let ids = merged
+            .iter()
+            .map(|candidate| candidate.message_id.as_deref().unwrap_or_default())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec!["new", "mid"]);
+    }
+}
@@ -0,0 +1,149 @@
+use base64::Engine as _;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::repos::LEGACY_CONNECTOR_ACCOUNT_KEY;
+
+use super::super::{
+    ConnectorSecretRequest, EnclaveRpcError, FetchGoogleCalendarEventsResponse,
+    FetchGoogleUrgentEmailCandidatesResponse, ProviderOperation,
+};
+use super::EnclaveOperationService;
+use super::connectors::{
+    CalendarConnectorRequest, CalendarProvider, EmailConnectorRequest, EmailProvider,
+    account_key_digest,
+};
+
+#[derive(Debug, Deserialize)]
+struct GoogleIdTokenClaims {
+    sub: String,
+}
+
+/// Derives the connector account key from the `id_token` returned by the code exchange. The
+/// token arrives directly from Google's token endpoint over TLS, so only its payload is read.
+/// Only a hash of the stable `sub` claim is stored; exchanges without an `id_token` fall back
+/// to the legacy key so existing single-account connectors keep updating in place.
+pub(super) fn google_account_key(id_token: Option<&str>) -> Result<String, EnclaveRpcError> {
+    let Some(id_token) = id_token.map(str::trim).filter(|token| !token.is_empty()) else {
+        return Ok(LEGACY_CONNECTOR_ACCOUNT_KEY.to_string());
+    };
+
+    let invalid = |message: &str| EnclaveRpcError::ProviderResponseInvalid {
+        operation: ProviderOperation::OAuthCodeExchange,
+        message: message.to_string(),
+    };
+    let payload = id_token
+        .split('.')
+        .nth(1)
+        .ok_or_else(|| invalid("oauth code exchange id_token is malformed"))?;
+    let payload = URL_SAFE_NO_PAD
+        .decode(payload.trim_end_matches('='))
+        .map_err(|_| invalid("oauth code exchange id_token is malformed"))?;
+    let claims = serde_json::from_slice::<GoogleIdTokenClaims>(&payload)
+        .map_err(|_| invalid("oauth code exchange id_token is missing sub"))?;
+    if claims.sub.trim().is_empty() {
+        return Err(invalid("oauth code exchange id_token is missing sub"));
+    }
+
+    Ok(account_key_digest(&format!("google:{}", claims.sub)))
+}
+
+impl EnclaveOperationService {
+    /// Resolves every active Google connector of the user, oldest first. Fails with
+    /// `ConnectorTokenUnavailable` when the user has none.
+    pub async fn resolve_active_google_connector_requests(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<ConnectorSecretRequest>, EnclaveRpcError> {
+        let requests = self
+            .resolve_active_connectors(user_id)
+            .await?
+            .into_iter()
+            .filter(|(provider, _)| provider == "google")
+            .map(|(_, connector)| connector)
+            .collect::<Vec<_>>();
+
+        if requests.is_empty() {
+            return Err(EnclaveRpcError::ConnectorTokenUnavailable);
+        }
+        Ok(requests)
+    }
+
+    /// Fetches calendar events from every Google connector, merged like
+    /// `fetch_calendar_events_for_connectors`.
+    pub async fn fetch_google_calendar_events_for_connectors(
+        &self,
+        connectors: &[ConnectorSecretRequest],
+        time_min: &str,
+        time_max: &str,
+        max_results: usize,
+    ) -> Result<FetchGoogleCalendarEventsResponse, EnclaveRpcError> {
+        let connectors = connectors
+            .iter()
+            .map(|connector| CalendarConnectorRequest {
+                provider: CalendarProvider::Google,
+                connector: connector.clone(),
+            })
+            .collect::<Vec<_>>();
+        self.fetch_calendar_events_for_connectors(&connectors, time_min, time_max, max_results)
+            .await
+    }
+
+    /// Fetches email candidates from every Google connector, merged like
+    /// `fetch_email_candidates_for_connectors`.
+    pub async fn fetch_google_email_candidates_for_connectors(
+        &self,
+        connectors: &[ConnectorSecretRequest],
+        gmail_query: Option<&str>,
+        max_results: usize,
+    ) -> Result<FetchGoogleUrgentEmailCandidatesResponse, EnclaveRpcError> {
+        let connectors = connectors
+            .iter()
+            .map(|connector| EmailConnectorRequest {
+                provider: EmailProvider::Google,
+                connector: connector.clone(),
+            })
+            .collect::<Vec<_>>();
+        self.fetch_email_candidates_for_connectors(&connectors, gmail_query, max_results)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::Engine as _;
+    use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+
+    use super::google_account_key;
+    use crate::repos::LEGACY_CONNECTOR_ACCOUNT_KEY;
+
+    fn id_token(claims: &str) -> String {
+        format!(
+            "{}.{}.signature",
+            URL_SAFE_NO_PAD.encode(r#"{"alg":"RS256"}"#),
+            URL_SAFE_NO_PAD.encode(claims)
+        )
+    }
+
+    #[test]
+    fn account_key_hashes_the_id_token_subject() {
+        let first = google_account_key(Some(&id_token(r#"{"sub":"1001","email":"a@x.com"}"#)))
+            .expect("id_token should parse");
+        let again = google_account_key(Some(&id_token(r#"{"sub":"1001"}"#)))
+            .expect("id_token should parse");
+        let second = google_account_key(Some(&id_token(r#"{"sub":"2002"}"#)))
+            .expect("id_token should parse");
+
+        assert_eq!(first, again);
+        assert_ne!(first, second);
+        assert_eq!(first.len(), 64);
+        assert!(!first.contains("1001"));
+        assert_eq!(
+            google_account_key(None).expect("missing id_token falls back"),
+            LEGACY_CONNECTOR_ACCOUNT_KEY
+        );
+        assert!(google_account_key(Some("not-a-jwt")).is_err());
+        assert!(google_account_key(Some(&id_token(r#"{"email":"a@x.com"}"#))).is_err());
+    }
+}
@@ -12,6 +12,7 @@ pub(super) struct GoogleRefreshTokenResponse {
 pub(super) struct GoogleOAuthCodeExchangeResponse {
     pub(super) refresh_token: Option<String>,
     pub(super) scope: Option<String>,
+    pub(super) id_token: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -26,10 +27,26 @@ pub(super) struct GoogleCalendarEvent {
     pub(super) summary: Option<String>,
     pub(super) start: Option<GoogleCalendarEventDateTime>,
     pub(super) end: Option<GoogleCalendarEventDateTime>,
+    pub(super) location: Option<String>,
+    #[serde(rename = "hangoutLink")]
+    pub(super) hangout_link: Option<String>,
+    #[serde(rename = "conferenceData")]
+    pub(super) conference_data: Option<GoogleCalendarConferenceData>,
     #[serde(default)]
     pub(super) attendees: Vec<GoogleCalendarAttendee>,
 }
 
+#[derive(Debug, Deserialize)]
+pub(super) struct GoogleCalendarConferenceData {
+    #[serde(default, rename = "entryPoints")]
+    pub(super) entry_points: Vec<GoogleCalendarConferenceEntryPoint>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct GoogleCalendarConferenceEntryPoint {
+    pub(super) uri: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 pub(super) struct GoogleCalendarEventDateTime {
     #[serde(rename = "dateTime")]
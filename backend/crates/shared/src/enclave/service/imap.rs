@@ -0,0 +1,151 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::imap::{normalize_imap_host, normalize_imap_port, normalize_imap_username};
+
+use super::super::{
+    CompleteImapConnectResponse, ConnectorSecretRequest, EnclaveRpcError,
+    FetchGoogleUrgentEmailCandidatesResponse, ProviderOperation,
+};
+use super::connectors::{account_key_digest, merge_email_candidates};
+use super::imap_client::{ImapError, ImapSession, email_candidate_from_header};
+use super::{EnclaveOperationService, StoredConnector};
+
+const IMAP_SESSION_TIMEOUT: Duration = Duration::from_secs(30);
+const IMAP_LOOKBACK_DAYS: i64 = 7;
+const MAX_IMAP_CANDIDATES: usize = 50;
+
+/// Credentials of an IMAP connector. They are serialized into the connector's encrypted secret
+/// column, so the password never leaves the enclave in plaintext.
+#[derive(Debug, Serialize, Deserialize)]
+struct ImapCredentials {
+    host: String,
+    port: u16,
+    username: String,
+    password: String,
+}
+
+impl EnclaveOperationService {
+    /// Verifies the IMAP credentials by logging in and opening INBOX read-only, then stores
+    /// them as a connector. Reconnecting the same host and username updates it in place.
+    pub async fn complete_imap_connect(
+        &self,
+        user_id: Uuid,
+        host: &str,
+        port: u16,
+        username: &str,
+        password: String,
+    ) -> Result<CompleteImapConnectResponse, EnclaveRpcError> {
+        let invalid = |message: &str| EnclaveRpcError::ProviderResponseInvalid {
+            operation: ProviderOperation::ImapConnect,
+            message: message.to_string(),
+        };
+        let credentials = ImapCredentials {
+            host: normalize_imap_host(host).map_err(invalid)?,
+            port: normalize_imap_port(Some(port)).map_err(invalid)?,
+            username: normalize_imap_username(username).map_err(invalid)?,
+            password,
+        };
+        if credentials.password.is_empty() {
+            return Err(invalid("password must not be empty"));
+        }
+
+        run_imap_session(&credentials, ProviderOperation::ImapConnect, |session| {
+            Box::pin(async move { session.examine_inbox().await })
+        })
+        .await?;
+
+        let account_key = account_key_digest(&format!(
+            "imap:{}\n{}",
+            credentials.host,
+            credentials.username.to_lowercase()
+        ));
+        let secret = serde_json::to_string(&credentials).map_err(|err| {
+            EnclaveRpcError::ConnectorTokenDecryptFailed {
+                message: err.to_string(),
+            }
+        })?;
+        let connector_id = self
+            .store_connector_secret(user_id, &account_key, StoredConnector::Imap, &secret)
+            .await?;
+
+        Ok(CompleteImapConnectResponse { connector_id })
+    }
+
+    /// Lists the headers of recent unread INBOX messages, newest first. Message bodies are
+    /// never fetched, so candidates carry no snippet.
+    pub(super) async fn fetch_imap_email_candidates(
+        &self,
+        request: &ConnectorSecretRequest,
+        max_results: usize,
+    ) -> Result<FetchGoogleUrgentEmailCandidatesResponse, EnclaveRpcError> {
+        let (secret, attested_identity) = self.load_authorized_refresh_token(request).await?;
+        let credentials = serde_json::from_str::<ImapCredentials>(&secret).map_err(|_| {
+            EnclaveRpcError::ProviderResponseInvalid {
+                operation: ProviderOperation::ImapFetch,
+                message: "stored imap credentials are malformed".to_string(),
+            }
+        })?;
+
+        let max_results = max_results.clamp(1, MAX_IMAP_CANDIDATES);
+        let since = (Utc::now() - chrono::Duration::days(IMAP_LOOKBACK_DAYS)).date_naive();
+        let messages = run_imap_session(&credentials, ProviderOperation::ImapFetch, |session| {
+            Box::pin(async move {
+                session.examine_inbox().await?;
+                let uids = session.search_unseen_since(since).await?;
+                let newest = &uids[uids.len().saturating_sub(max_results)..];
+                session.fetch_headers(newest).await
+            })
+        })
+        .await?;
+
+        let candidates = messages.iter().map(email_candidate_from_header).collect();
+        Ok(FetchGoogleUrgentEmailCandidatesResponse {
+            attested_identity,
+            candidates: merge_email_candidates(candidates, max_results),
+        })
+    }
+}
+
+/// Logs in, runs `work`, and logs out, all under one deadline.
+async fn run_imap_session<T>(
+    credentials: &ImapCredentials,
+    operation: ProviderOperation,
+    work: impl for<'a> FnOnce(
+        &'a mut ImapSession,
+    ) -> std::pin::Pin<
+        Box<dyn std::future::Future<Output = Result<T, ImapError>> + Send + 'a>,
+    >,
+) -> Result<T, EnclaveRpcError> {
+    let session = async {
+        let mut session = ImapSession::connect(&credentials.host, credentials.port).await?;
+        session
+            .login(&credentials.username, &credentials.password)
+            .await?;
+        let result = work(&mut session).await;
+        session.logout().await;
+        result
+    };
+
+    match tokio::time::timeout(IMAP_SESSION_TIMEOUT, session).await {
+        Ok(Ok(value)) => Ok(value),
+        Ok(Err(ImapError::Unavailable(message))) => {
+            Err(EnclaveRpcError::ProviderRequestUnavailable { operation, message })
+        }
+        Ok(Err(ImapError::AuthenticationFailed)) => Err(EnclaveRpcError::ProviderRequestFailed {
+            operation,
+            status: 401,
+            oauth_error: None,
+        }),
+        Ok(Err(ImapError::Protocol(message))) => {
+            Err(EnclaveRpcError::ProviderResponseInvalid { operation, message })
+        }
+        Err(_) => Err(EnclaveRpcError::ProviderRequestUnavailable {
+            operation,
+            message: "imap session timed out".to_string(),
+        }),
+    }
+}
@@ -0,0 +1,495 @@
+use std::sync::Arc;
+
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD;
+use chrono::{DateTime, NaiveDate, SecondsFormat, Utc};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio_rustls::TlsConnector;
+use tokio_rustls::client::TlsStream;
+use tokio_rustls::rustls::pki_types::ServerName;
+use tokio_rustls::rustls::{ClientConfig, RootCertStore, crypto::ring};
+
+use crate::network_policy::resolve_public_address;
+
+use super::super::EnclaveGoogleEmailCandidate;
+
+/// Upper bound for one response line including its literals, so a hostile server cannot make
+/// the enclave buffer unbounded data.
+const MAX_RESPONSE_BYTES: usize = 1024 * 1024;
+const HEADER_FIELDS: &str = "FROM SUBJECT DATE MESSAGE-ID CONTENT-TYPE";
+
+#[derive(Debug)]
+pub(super) enum ImapError {
+    /// The server could not be reached or the connection broke.
+    Unavailable(String),
+    /// The server rejected the username or password.
+    AuthenticationFailed,
+    /// The server answered with something this client does not understand.
+    Protocol(String),
+}
+
+/// One response line. Literals (`{N}` followed by N raw bytes) are kept apart from the text so
+/// header bytes can never be mistaken for protocol tokens.
+#[derive(Debug, Default)]
+struct ImapResponse {
+    text: String,
+    literals: Vec<Vec<u8>>,
+}
+
+/// Header-only view of an unread message; bodies are never requested.
+#[derive(Debug)]
+pub(super) struct ImapMessageHeader {
+    pub(super) uid: u32,
+    pub(super) flags: Vec<String>,
+    pub(super) internal_date: Option<DateTime<Utc>>,
+    pub(super) header: Vec<u8>,
+}
+
+/// Minimal IMAP4rev1 client over implicit TLS, limited to the read-only commands the email
+/// connector needs.
+pub(super) struct ImapSession {
+    stream: BufReader<TlsStream<TcpStream>>,
+    next_tag: u32,
+}
+
+impl ImapSession {
+    pub(super) async fn connect(host: &str, port: u16) -> Result<Self, ImapError> {
+        let address = resolve_public_address(host, port)
+            .await
+            .map_err(ImapError::Unavailable)?;
+
+        let tcp = TcpStream::connect(address)
+            .await
+            .map_err(|err| ImapError::Unavailable(err.to_string()))?;
+        let server_name = ServerName::try_from(host.to_string())
+            .map_err(|err| ImapError::Unavailable(err.to_string()))?;
+        let tls = TlsConnector::from(tls_config()?)
+            .connect(server_name, tcp)
+            .await
+            .map_err(|err| ImapError::Unavailable(err.to_string()))?;
+
+        let mut session = Self {
+            stream: BufReader::new(tls),
+            next_tag: 1,
+        };
+        let greeting = session.read_response().await?;
+        if !(greeting.text.starts_with("* OK") || greeting.text.starts_with("* PREAUTH")) {
+            return Err(ImapError::Unavailable(format!(
+                "imap server refused the connection: {}",
+                greeting.text.trim_end()
+            )));
+        }
+
+        Ok(session)
+    }
+
+    pub(super) async fn login(&mut self, username: &str, password: &str) -> Result<(), ImapError> {
+        let command = format!("LOGIN {} {}", quote(username)?, quote(password)?);
+        match self.command(&command).await {
+            Err(ImapError::Protocol(_)) => Err(ImapError::AuthenticationFailed),
+            result => result.map(|_| ()),
+        }
+    }
+
+    /// Opens INBOX read-only, so fetching headers never changes the `\Seen` flag.
+    pub(super) async fn examine_inbox(&mut self) -> Result<(), ImapError> {
+        self.command("EXAMINE INBOX").await.map(|_| ())
+    }
+
+    pub(super) async fn search_unseen_since(
+        &mut self,
+        since: NaiveDate,
+    ) -> Result<Vec<u32>, ImapError> {
+        let responses = self
+            .command(&format!(
+                "UID SEARCH UNSEEN SINCE {}",
+                since.format("%-d-%b-%Y")
+            ))
+            .await?;
+
+        let mut uids = responses
+            .iter()
+            .filter_map(|response| response.text.trim_end().strip_prefix("* SEARCH"))
+            .flat_map(str::split_whitespace)
+            .filter_map(|uid| uid.parse::<u32>().ok())
+            .collect::<Vec<_>>();
+        uids.sort_unstable();
+        Ok(uids)
+    }
+
+    pub(super) async fn fetch_headers(
+        &mut self,
+        uids: &[u32],
+    ) -> Result<Vec<ImapMessageHeader>, ImapError> {
+        if uids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let uid_set = uids
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(",");
+        let responses = self
+            .command(&format!(
+                "UID FETCH {uid_set} (UID FLAGS INTERNALDATE BODY.PEEK[HEADER.FIELDS ({HEADER_FIELDS})])"
+            ))
+            .await?;
+
+        Ok(responses.iter().filter_map(parse_fetch_response).collect())
+    }
+
+    pub(super) async fn logout(mut self) {
+        let _ = self.command("LOGOUT").await;
+    }
+
+    /// Sends a tagged command and returns its untagged responses once the server completes it.
+    async fn command(&mut self, command: &str) -> Result<Vec<ImapResponse>, ImapError> {
+        let tag = format!("A{:04}", self.next_tag);
+        self.next_tag += 1;
+        self.stream
+            .get_mut()
+            .write_all(format!("{tag} {command}\r\n").as_bytes())
+            .await
+            .map_err(|err| ImapError::Unavailable(err.to_string()))?;
+        self.stream
+            .get_mut()
+            .flush()
+            .await
+            .map_err(|err| ImapError::Unavailable(err.to_string()))?;
+
+        let mut untagged = Vec::new();
+        loop {
+            let response = self.read_response().await?;
+            let Some(status) = response.text.strip_prefix(&format!("{tag} ")) else {
+                untagged.push(response);
+                continue;
+            };
+
+            return if status.starts_with("OK") {
+                Ok(untagged)
+            } else {
+                Err(ImapError::Protocol(status.trim_end().to_string()))
+            };
+        }
+    }
+
+    async fn read_response(&mut self) -> Result<ImapResponse, ImapError> {
+        let mut response = ImapResponse::default();
+        let mut total = 0;
+        loop {
+            let mut line = Vec::new();
+            let limit = (MAX_RESPONSE_BYTES - total + 1) as u64;
+            let read = (&mut self.stream)
+                .take(limit)
+                .read_until(b'\n', &mut line)
+                .await
+                .map_err(|err| ImapError::Unavailable(err.to_string()))?;
+            if read == 0 {
+                return Err(ImapError::Unavailable(
+                    "imap server closed the connection".to_string(),
+                ));
+            }
+            total += read;
+            if total > MAX_RESPONSE_BYTES {
+                return Err(ImapError::Protocol(
+                    "imap response is too large".to_string(),
+                ));
+            }
+
+            let line = String::from_utf8_lossy(&line).into_owned();
+            response.text.push_str(&line);
+            let Some(length) = literal_length(&line) else {
+                return Ok(response);
+            };
+
+            total += length;
+            if total > MAX_RESPONSE_BYTES {
+                return Err(ImapError::Protocol(
+                    "imap response is too large".to_string(),
+                ));
+            }
+            let mut literal = vec![0; length];
+            self.stream
+                .read_exact(&mut literal)
+                .await
+                .map_err(|err| ImapError::Unavailable(err.to_string()))?;
+            response.literals.push(literal);
+        }
+    }
+}
+
+fn tls_config() -> Result<Arc<ClientConfig>, ImapError> {
+    let roots = RootCertStore::from_iter(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    let config = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .map_err(|err| ImapError::Unavailable(err.to_string()))?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    Ok(Arc::new(config))
+}
+
+/// Quotes a LOGIN argument. Control characters and non-ASCII text cannot be sent as a quoted
+/// string, and literals are not needed for the credentials accepted at connect time.
+fn quote(value: &str) -> Result<String, ImapError> {
+    if value.chars().any(|ch| ch.is_control() || !ch.is_ascii()) {
+        return Err(ImapError::AuthenticationFailed);
+    }
+    Ok(format!(
+        "\"{}\"",
+        value.replace('\\', "\\\\").replace('"', "\\\"")
+    ))
+}
+
+fn literal_length(line: &str) -> Option<usize> {
+    line.trim_end_matches(['\r', '\n'])
+        .strip_suffix('}')?
+        .rsplit_once('{')?
+        .1
+        .parse()
+        .ok()
+}
+
+fn parse_fetch_response(response: &ImapResponse) -> Option<ImapMessageHeader> {
+    let text = response.text.as_str();
+    if !text.starts_with("* ") || !text.contains(" FETCH (") {
+        return None;
+    }
+
+    let uid = token_after(text, "UID ")?
+        .trim_end_matches(')')
+        .parse()
+        .ok()?;
+    let flags = text
+        .split_once("FLAGS (")
+        .and_then(|(_, rest)| rest.split_once(')'))
+        .map(|(flags, _)| flags.split_whitespace().map(str::to_string).collect())
+        .unwrap_or_default();
+    let internal_date = text
+        .split_once("INTERNALDATE \"")
+        .and_then(|(_, rest)| rest.split_once('"'))
+        .and_then(|(date, _)| DateTime::parse_from_str(date.trim(), "%d-%b-%Y %H:%M:%S %z").ok())
+        .map(|date| date.with_timezone(&Utc));
+
+    Some(ImapMessageHeader {
+        uid,
+        flags,
+        internal_date,
+        header: response.literals.first().cloned().unwrap_or_default(),
+    })
+}
+
+fn token_after<'a>(text: &'a str, prefix: &str) -> Option<&'a str> {
+    text.split([' ', '('])
+        .skip_while(|token| *token != prefix.trim())
+        .nth(1)
+}
+
+/// Maps an IMAP message onto the Gmail candidate shape used by the urgent-email rules and the
+/// email lane: IMAP flags become the Gmail labels those consumers already understand.
+pub(super) fn email_candidate_from_header(
+    message: &ImapMessageHeader,
+) -> EnclaveGoogleEmailCandidate {
+    let headers = parse_header_fields(&message.header);
+    let header = |name: &str| {
+        headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.clone())
+            .filter(|value| !value.is_empty())
+    };
+    let has_flag = |flag: &str| {
+        message
+            .flags
+            .iter()
+            .any(|value| value.eq_ignore_ascii_case(flag))
+    };
+
+    let mut label_ids = vec!["INBOX".to_string()];
+    if !has_flag("\\Seen") {
+        label_ids.push("UNREAD".to_string());
+    }
+    if has_flag("\\Flagged") {
+        label_ids.push("STARRED".to_string());
+    }
+    if has_flag("$Important") || has_flag("\\Important") {
+        label_ids.push("IMPORTANT".to_string());
+    }
+
+    let received_at = message
+        .internal_date
+        .or_else(|| {
+            header("Date")
+                .and_then(|date| DateTime::parse_from_rfc2822(&date).ok())
+                .map(|date| date.with_timezone(&Utc))
+        })
+        .map(|date| date.to_rfc3339_opts(SecondsFormat::Secs, true));
+
+    EnclaveGoogleEmailCandidate {
+        message_id: Some(
+            header("Message-ID").unwrap_or_else(|| format!("imap-uid-{}", message.uid)),
+        ),
+        from: header("From").map(|value| decode_encoded_words(&value)),
+        subject: header("Subject").map(|value| decode_encoded_words(&value)),
+        snippet: None,
+        received_at,
+        label_ids,
+        has_attachments: header("Content-Type").is_some_and(|value| {
+            value
+                .trim_start()
+                .to_ascii_lowercase()
+                .starts_with("multipart/mixed")
+        }),
+    }
+}
+
+/// Splits an RFC 5322 header block into fields, unfolding continuation lines.
+fn parse_header_fields(header: &[u8]) -> Vec<(String, String)> {
+    let mut fields: Vec<(String, String)> = Vec::new();
+    for line in String::from_utf8_lossy(header).lines() {
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = fields.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+            continue;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            fields.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+    fields
+}
+
+/// Decodes RFC 2047 encoded words (`=?charset?B|Q?text?=`) in UTF-8, US-ASCII, and
+/// ISO-8859-1; words in other charsets are left as they are.
+fn decode_encoded_words(value: &str) -> String {
+    let mut decoded = String::new();
+    let mut rest = value;
+    let mut previous_was_word = false;
+    while let Some(start) = rest.find("=?") {
+        let (before, candidate) = rest.split_at(start);
+        let Some((word, after)) = decode_encoded_word(candidate) else {
+            decoded.push_str(before);
+            decoded.push_str("=?");
+            rest = &candidate[2..];
+            previous_was_word = false;
+            continue;
+        };
+
+        // Whitespace between adjacent encoded words is not part of the text.
+        if !(previous_was_word && before.trim().is_empty()) {
+            decoded.push_str(before);
+        }
+        decoded.push_str(&word);
+        rest = after;
+        previous_was_word = true;
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+fn decode_encoded_word(value: &str) -> Option<(String, &str)> {
+    let inner = value.strip_prefix("=?")?;
+    let (charset, inner) = inner.split_once('?')?;
+    let (encoding, inner) = inner.split_once('?')?;
+    let (text, after) = inner.split_once("?=")?;
+
+    let bytes = match encoding {
+        "B" | "b" => STANDARD.decode(text).ok()?,
+        "Q" | "q" => decode_q(text)?,
+        _ => return None,
+    };
+    let word = match charset.to_ascii_lowercase().as_str() {
+        "utf-8" | "us-ascii" => String::from_utf8_lossy(&bytes).into_owned(),
+        "iso-8859-1" | "latin1" => bytes.iter().map(|byte| char::from(*byte)).collect(),
+        _ => return None,
+    };
+    Some((word, after))
+}
+
+fn decode_q(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut chars = text.bytes();
+    while let Some(byte) = chars.next() {
+        match byte {
+            b'_' => bytes.push(b' '),
+            b'=' => {
+                let hex = [chars.next()?, chars.next()?];
+                bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+            }
+            byte => bytes.push(byte),
+        }
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ImapMessageHeader, ImapResponse, decode_encoded_words, email_candidate_from_header,
+        literal_length, parse_fetch_response,
+    };
+
+    #[test]
+    fn fetch_response_keeps_header_literal_apart_from_flags() {
+        let response = ImapResponse {
+            text: "* 4 FETCH (UID 812 FLAGS (\\Flagged $Important) INTERNALDATE \
+                   \" 7-Mar-2026 08:15:00 +0100\" BODY[HEADER.FIELDS (FROM SUBJECT)] {64}\r\n)\r\n"
+                .to_string(),
+            literals: vec![
+                b"From: Boss <boss@example.com>\r\nSubject: =?UTF-8?Q?Caf=C3=A9_today?=\r\n\r\n"
+                    .to_vec(),
+            ],
+        };
+
+        let message = parse_fetch_response(&response).expect("fetch response should parse");
+        assert_eq!(message.uid, 812);
+        let candidate = email_candidate_from_header(&message);
+        assert_eq!(candidate.from.as_deref(), Some("Boss <boss@example.com>"));
+        assert_eq!(candidate.subject.as_deref(), Some("Café today"));
+        assert_eq!(
+            candidate.received_at.as_deref(),
+            Some("2026-03-07T07:15:00Z")
+        );
+        assert_eq!(candidate.message_id.as_deref(), Some("imap-uid-812"));
+        assert_eq!(
+            candidate.label_ids,
+            vec!["INBOX", "UNREAD", "STARRED", "IMPORTANT"]
+        );
+        assert!(candidate.snippet.is_none());
+    }
+
+    #[test]
+    fn multipart_mixed_messages_have_attachments() {
+        let message = ImapMessageHeader {
+            uid: 1,
+            flags: vec!["\\Seen".to_string()],
+            internal_date: None,
+            header: b"Content-Type: multipart/mixed;\r\n boundary=\"x\"\r\nDate: Sat, 7 Mar 2026 08:15:00 +0000\r\n".to_vec(),
+        };
+
+        let candidate = email_candidate_from_header(&message);
+        assert!(candidate.has_attachments);
+        assert_eq!(candidate.label_ids, vec!["INBOX"]);
+        assert_eq!(
+            candidate.received_at.as_deref(),
+            Some("2026-03-07T08:15:00Z")
+        );
+    }
+
+    #[test]
+    fn literal_markers_and_encoded_words_are_recognized() {
+        assert_eq!(
+            literal_length("* 1 FETCH (BODY[HEADER] {120}\r\n"),
+            Some(120)
+        );
+        assert_eq!(literal_length("* OK ready\r\n"), None);
+        assert_eq!(
+            decode_encoded_words("=?utf-8?B?SGVsbG8=?= =?utf-8?B?IHdvcmxk?= again"),
+            "Hello world again"
+        );
+        assert_eq!(decode_encoded_words("plain =?x"), "plain =?x");
+    }
+}
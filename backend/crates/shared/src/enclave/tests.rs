@@ -1,27 +1,34 @@
 use std::time::Duration;
 
+use axum::body::Bytes;
 use axum::extract::Json;
-use axum::http::StatusCode;
+use axum::http::{HeaderMap, StatusCode};
 use axum::routing::post;
 use axum::{Router, response::IntoResponse};
 use base64::Engine as _;
 use tokio::time::sleep;
 use uuid::Uuid;
 
+use super::transport::{EnclaveRequest, EnclaveRequestMethod, exchange_http1};
 use super::{
-    AttestedIdentityPayload, ConnectorSecretRequest, ENCLAVE_RPC_CONTRACT_VERSION,
-    ENCLAVE_RPC_PATH_EXCHANGE_GOOGLE_TOKEN, ENCLAVE_RPC_PATH_EXECUTE_AUTOMATION,
-    ENCLAVE_RPC_PATH_FETCH_GOOGLE_CALENDAR_EVENTS,
-    ENCLAVE_RPC_PATH_FETCH_GOOGLE_URGENT_EMAIL_CANDIDATES, EnclaveRpcAuthConfig, EnclaveRpcClient,
-    EnclaveRpcError, EnclaveRpcErrorEnvelope, EnclaveRpcExchangeGoogleTokenRequest,
-    EnclaveRpcExchangeGoogleTokenResponse, EnclaveRpcExecuteAutomationRequest,
-    EnclaveRpcExecuteAutomationResponse, EnclaveRpcFetchGoogleCalendarEventsRequest,
-    EnclaveRpcFetchGoogleCalendarEventsResponse, EnclaveRpcFetchGoogleUrgentEmailCandidatesRequest,
+    AttestedIdentityPayload, ConnectorSecretRequest, ENCLAVE_RPC_AUTH_CALLER_HEADER,
+    ENCLAVE_RPC_AUTH_NONCE_HEADER, ENCLAVE_RPC_AUTH_SIGNATURE_HEADER,
+    ENCLAVE_RPC_AUTH_TIMESTAMP_HEADER, ENCLAVE_RPC_CONTRACT_VERSION,
+    ENCLAVE_RPC_CONTRACT_VERSION_HEADER, ENCLAVE_RPC_PATH_EXCHANGE_GOOGLE_TOKEN,
+    ENCLAVE_RPC_PATH_EXECUTE_AUTOMATION, ENCLAVE_RPC_PATH_FETCH_GOOGLE_CALENDAR_EVENTS,
+    ENCLAVE_RPC_PATH_FETCH_GOOGLE_URGENT_EMAIL_CANDIDATES, EnclaveRpcAuthConfig,
+    EnclaveRpcCallerKeys, EnclaveRpcClient, EnclaveRpcError, EnclaveRpcErrorEnvelope,
+    EnclaveRpcExchangeGoogleTokenRequest, EnclaveRpcExchangeGoogleTokenResponse,
+    EnclaveRpcExecuteAutomationRequest, EnclaveRpcExecuteAutomationResponse,
+    EnclaveRpcFetchGoogleCalendarEventsRequest, EnclaveRpcFetchGoogleCalendarEventsResponse,
+    EnclaveRpcFetchGoogleUrgentEmailCandidatesRequest,
     EnclaveRpcFetchGoogleUrgentEmailCandidatesResponse, EnclaveRpcRevokeGoogleTokenRequest,
-    EnclaveRpcRevokeGoogleTokenResponse,
+    EnclaveRpcRevokeGoogleTokenResponse, EnclaveRpcSigningKey, ExecuteAutomationRequest,
+    SignedRpcRequest,
 };
 
 mod boundary_guards;
+mod mtls;
 
 #[tokio::test]
 async fn rpc_client_maps_timeout_to_transport_unavailable() {
@@ -51,7 +58,7 @@ async fn rpc_client_maps_timeout_to_transport_unavailable() {
     let client = EnclaveRpcClient::new(
         base_url,
         EnclaveRpcAuthConfig {
-            shared_secret: "local-secret".to_string(),
+            signing_key: Some(EnclaveRpcSigningKey::local_dev("api-server")),
             max_clock_skew_seconds: 30,
         },
         http_client,
@@ -94,7 +101,7 @@ async fn rpc_client_maps_provider_failure_error_contract() {
     let client = EnclaveRpcClient::new(
         base_url,
         EnclaveRpcAuthConfig {
-            shared_secret: "local-secret".to_string(),
+            signing_key: Some(EnclaveRpcSigningKey::local_dev("api-server")),
             max_clock_skew_seconds: 30,
         },
         reqwest::Client::new(),
@@ -142,7 +149,7 @@ async fn rpc_client_maps_transport_auth_rejection() {
     let client = EnclaveRpcClient::new(
         base_url,
         EnclaveRpcAuthConfig {
-            shared_secret: "local-secret".to_string(),
+            signing_key: Some(EnclaveRpcSigningKey::local_dev("api-server")),
             max_clock_skew_seconds: 30,
         },
         reqwest::Client::new(),
@@ -182,7 +189,7 @@ async fn rpc_client_rejects_contract_version_drift() {
     let client = EnclaveRpcClient::new(
         base_url,
         EnclaveRpcAuthConfig {
-            shared_secret: "local-secret".to_string(),
+            signing_key: Some(EnclaveRpcSigningKey::local_dev("api-server")),
             max_clock_skew_seconds: 30,
         },
         reqwest::Client::new(),
@@ -222,7 +229,7 @@ async fn rpc_client_rejects_request_id_mismatch_in_exchange_response() {
     let client = EnclaveRpcClient::new(
         base_url,
         EnclaveRpcAuthConfig {
-            shared_secret: "local-secret".to_string(),
+            signing_key: Some(EnclaveRpcSigningKey::local_dev("api-server")),
             max_clock_skew_seconds: 30,
         },
         reqwest::Client::new(),
@@ -263,7 +270,7 @@ async fn rpc_client_revoke_maps_transport_auth_rejection() {
     let client = EnclaveRpcClient::new(
         base_url,
         EnclaveRpcAuthConfig {
-            shared_secret: "local-secret".to_string(),
+            signing_key: Some(EnclaveRpcSigningKey::local_dev("api-server")),
             max_clock_skew_seconds: 30,
         },
         reqwest::Client::new(),
@@ -302,7 +309,7 @@ async fn rpc_client_rejects_request_id_mismatch_in_revoke_response() {
     let client = EnclaveRpcClient::new(
         base_url,
         EnclaveRpcAuthConfig {
-            shared_secret: "local-secret".to_string(),
+            signing_key: Some(EnclaveRpcSigningKey::local_dev("api-server")),
             max_clock_skew_seconds: 30,
         },
         reqwest::Client::new(),
@@ -342,7 +349,7 @@ async fn rpc_client_rejects_request_id_mismatch_in_calendar_fetch_response() {
     let client = EnclaveRpcClient::new(
         base_url,
         EnclaveRpcAuthConfig {
-            shared_secret: "local-secret".to_string(),
+            signing_key: Some(EnclaveRpcSigningKey::local_dev("api-server")),
             max_clock_skew_seconds: 30,
         },
         reqwest::Client::new(),
@@ -387,7 +394,7 @@ async fn rpc_client_rejects_request_id_mismatch_in_gmail_fetch_response() {
     let client = EnclaveRpcClient::new(
         base_url,
         EnclaveRpcAuthConfig {
-            shared_secret: "local-secret".to_string(),
+            signing_key: Some(EnclaveRpcSigningKey::local_dev("api-server")),
             max_clock_skew_seconds: 30,
         },
         reqwest::Client::new(),
@@ -418,6 +425,8 @@ async fn rpc_client_rejects_request_id_mismatch_in_automation_execution_response
                     request_id: "mismatched-request-id".to_string(),
                     should_notify: true,
                     notification_artifacts: Vec::new(),
+                    report_artifacts: Vec::new(),
+                    condition_result: None,
                     metadata: std::collections::HashMap::new(),
                     attested_identity: AttestedIdentityPayload {
                         runtime: "nitro".to_string(),
@@ -432,19 +441,20 @@ async fn rpc_client_rejects_request_id_mismatch_in_automation_execution_response
     let client = EnclaveRpcClient::new(
         base_url,
         EnclaveRpcAuthConfig {
-            shared_secret: "local-secret".to_string(),
+            signing_key: Some(EnclaveRpcSigningKey::local_dev("api-server")),
             max_clock_skew_seconds: 30,
         },
         reqwest::Client::new(),
     );
 
     let err = client
-        .execute_automation_run(
-            Uuid::new_v4(),
-            Uuid::new_v4(),
-            Uuid::new_v4(),
-            chrono::Utc::now(),
-            crate::models::AutomationPromptEnvelope {
+        .execute_automation_run(ExecuteAutomationRequest {
+            job_id: None,
+            user_id: Uuid::new_v4(),
+            automation_rule_id: Uuid::new_v4(),
+            automation_run_id: Uuid::new_v4(),
+            scheduled_for: chrono::Utc::now(),
+            prompt_envelope: crate::models::AutomationPromptEnvelope {
                 version: "v1".to_string(),
                 algorithm: "x25519-chacha20poly1305".to_string(),
                 key_id: "assistant-ingress-v1".to_string(),
@@ -454,8 +464,10 @@ async fn rpc_client_rejects_request_id_mismatch_in_automation_execution_response
                 nonce: base64::engine::general_purpose::STANDARD.encode([8_u8; 12]),
                 ciphertext: base64::engine::general_purpose::STANDARD.encode([9_u8; 24]),
             },
-            Vec::new(),
-        )
+            recipient_devices: Vec::new(),
+            template: None,
+            condition: None,
+        })
         .await
         .expect_err("automation response request_id mismatch must fail closed");
 
@@ -486,19 +498,20 @@ async fn rpc_client_rejects_automation_response_with_plaintext_fields() {
     let client = EnclaveRpcClient::new(
         base_url,
         EnclaveRpcAuthConfig {
-            shared_secret: "local-secret".to_string(),
+            signing_key: Some(EnclaveRpcSigningKey::local_dev("api-server")),
             max_clock_skew_seconds: 30,
         },
         reqwest::Client::new(),
     );
 
     let err = client
-        .execute_automation_run(
-            Uuid::new_v4(),
-            Uuid::new_v4(),
-            Uuid::new_v4(),
-            chrono::Utc::now(),
-            crate::models::AutomationPromptEnvelope {
+        .execute_automation_run(ExecuteAutomationRequest {
+            job_id: None,
+            user_id: Uuid::new_v4(),
+            automation_rule_id: Uuid::new_v4(),
+            automation_run_id: Uuid::new_v4(),
+            scheduled_for: chrono::Utc::now(),
+            prompt_envelope: crate::models::AutomationPromptEnvelope {
                 version: "v1".to_string(),
                 algorithm: "x25519-chacha20poly1305".to_string(),
                 key_id: "assistant-ingress-v1".to_string(),
@@ -508,14 +521,93 @@ async fn rpc_client_rejects_automation_response_with_plaintext_fields() {
                 nonce: base64::engine::general_purpose::STANDARD.encode([8_u8; 12]),
                 ciphertext: base64::engine::general_purpose::STANDARD.encode([9_u8; 24]),
             },
-            Vec::new(),
-        )
+            recipient_devices: Vec::new(),
+            template: None,
+            condition: None,
+        })
         .await
         .expect_err("automation response with plaintext fields must fail closed");
 
     assert!(matches!(err, EnclaveRpcError::RpcResponseInvalid { .. }));
 }
 
+#[tokio::test]
+async fn http1_exchange_delivers_a_signed_rpc_that_verifies() {
+    let app = Router::new().route(
+        ENCLAVE_RPC_PATH_EXCHANGE_GOOGLE_TOKEN,
+        post(|headers: HeaderMap, body: Bytes| async move {
+            let header = |name: &str| {
+                headers
+                    .get(name)
+                    .and_then(|value| value.to_str().ok())
+                    .unwrap_or_default()
+                    .to_string()
+            };
+            let timestamp = header(ENCLAVE_RPC_AUTH_TIMESTAMP_HEADER)
+                .parse::<i64>()
+                .unwrap_or_default();
+            let nonce = header(ENCLAVE_RPC_AUTH_NONCE_HEADER);
+            let verified = EnclaveRpcCallerKeys::local_dev(&["worker"]).verify(
+                &header(ENCLAVE_RPC_AUTH_CALLER_HEADER),
+                SignedRpcRequest {
+                    method: "POST",
+                    path: ENCLAVE_RPC_PATH_EXCHANGE_GOOGLE_TOKEN,
+                    timestamp,
+                    nonce: &nonce,
+                    body: &body,
+                },
+                &header(ENCLAVE_RPC_AUTH_SIGNATURE_HEADER),
+            );
+            if verified
+                && header(ENCLAVE_RPC_CONTRACT_VERSION_HEADER) == ENCLAVE_RPC_CONTRACT_VERSION
+            {
+                StatusCode::OK
+            } else {
+                StatusCode::UNAUTHORIZED
+            }
+        }),
+    );
+    let (base_url, _server) = start_test_server(app).await;
+    let stream = tokio::net::TcpStream::connect(base_url.trim_start_matches("http://"))
+        .await
+        .expect("test server should accept connections");
+
+    let body = br#"{"request_id":"req-1"}"#.to_vec();
+    let timestamp = 1_700_000_000;
+    let signature = EnclaveRpcSigningKey::local_dev("worker").sign(SignedRpcRequest {
+        method: "POST",
+        path: ENCLAVE_RPC_PATH_EXCHANGE_GOOGLE_TOKEN,
+        timestamp,
+        nonce: "nonce-1",
+        body: &body,
+    });
+    let response = exchange_http1(
+        stream,
+        "16:8181",
+        EnclaveRequest {
+            method: EnclaveRequestMethod::Post,
+            path: ENCLAVE_RPC_PATH_EXCHANGE_GOOGLE_TOKEN,
+            headers: vec![
+                (
+                    ENCLAVE_RPC_CONTRACT_VERSION_HEADER,
+                    ENCLAVE_RPC_CONTRACT_VERSION.to_string(),
+                ),
+                (ENCLAVE_RPC_AUTH_CALLER_HEADER, "worker".to_string()),
+                (ENCLAVE_RPC_AUTH_TIMESTAMP_HEADER, timestamp.to_string()),
+                (ENCLAVE_RPC_AUTH_NONCE_HEADER, "nonce-1".to_string()),
+                (ENCLAVE_RPC_AUTH_SIGNATURE_HEADER, signature),
+                ("content-type", "application/json".to_string()),
+            ],
+            body: Some(body),
+            timeout: None,
+        },
+    )
+    .await
+    .expect("exchange should complete");
+
+    assert_eq!(response.status, 200);
+}
+
 async fn start_test_server(app: Router) -> (String, tokio::task::JoinHandle<()>) {
     let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
         .await
@@ -111,7 +111,7 @@ fn assistant_query_contracts_do_not_reintroduce_plaintext_query_fields() {
         "OpenAPI AssistantQueryRequest must not include plaintext query field"
     );
 
-    let rust_models_path = shared_root.join("src/models.rs");
+    let rust_models_path = shared_root.join("src/models/assistant.rs");
     let rust_models = fs::read_to_string(&rust_models_path)
         .expect("failed to read shared models for plaintext guard");
     let rust_block = extract_rust_struct_block(&rust_models, "pub struct AssistantQueryRequest")
@@ -162,7 +162,7 @@ fn assistant_query_host_response_contracts_remain_envelope_only() {
         "OpenAPI AssistantQueryResponse must not expose plaintext payload"
     );
 
-    let rust_models_path = shared_root.join("src/models.rs");
+    let rust_models_path = shared_root.join("src/models/assistant.rs");
     let rust_models = fs::read_to_string(&rust_models_path)
         .expect("failed to read shared models for assistant response guard");
     let rust_block = extract_rust_struct_block(&rust_models, "pub struct AssistantQueryResponse")
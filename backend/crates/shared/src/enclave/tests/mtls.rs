@@ -0,0 +1,327 @@
+use std::io;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use axum::Router;
+use axum::extract::{Json, State};
+use axum::http::header;
+use axum::response::IntoResponse;
+use axum::routing::post;
+use tokio::net::{TcpListener, TcpStream};
+use tokio_rustls::TlsAcceptor;
+use tokio_rustls::rustls::crypto::ring;
+use tokio_rustls::rustls::server::WebPkiClientVerifier;
+use tokio_rustls::rustls::{RootCertStore, ServerConfig};
+use uuid::Uuid;
+
+use super::super::{
+    AttestedIdentityPayload, ConnectorSecretRequest, ENCLAVE_RPC_CONTRACT_VERSION,
+    ENCLAVE_RPC_PATH_EXCHANGE_GOOGLE_TOKEN, EnclaveRpcAuthConfig, EnclaveRpcClient,
+    EnclaveRpcClientTlsConfig, EnclaveRpcExchangeGoogleTokenRequest,
+    EnclaveRpcExchangeGoogleTokenResponse, EnclaveRpcTls, certificate_sha256,
+    load_certificate_chain, load_private_key,
+};
+use crate::enclave_runtime::{AttestationChallengeRequest, AttestationChallengeResponse};
+use crate::security::{KmsDecryptPolicy, SecretRuntime, TeeAttestationPolicy};
+
+#[tokio::test]
+async fn mtls_client_pins_the_attested_certificate_and_follows_rotation() {
+    let server = MtlsTestServer::start("server-a").await;
+    let tls = EnclaveRpcTls::load(
+        &EnclaveRpcClientTlsConfig {
+            client_cert_path: fixture("client.pem"),
+            client_key_path: fixture("client.key"),
+        },
+        Duration::from_secs(5),
+    )
+    .expect("client identity should load");
+    let client = EnclaveRpcClient::new(
+        server.base_url.clone(),
+        EnclaveRpcAuthConfig {
+            signing_key: None,
+            max_clock_skew_seconds: 30,
+        },
+        reqwest::Client::new(),
+    )
+    .with_mtls(
+        &tls,
+        secret_runtime(&server.base_url).with_enclave_tls(&tls),
+    );
+
+    exchange(&client)
+        .await
+        .expect("rpc should succeed once the attested certificate is pinned");
+    assert_eq!(server.state.challenges.load(Ordering::SeqCst), 1);
+    assert!(tls.pins().contains(&server_fingerprint("server-a")));
+
+    server.rotate_to("server-b");
+    exchange(&client)
+        .await
+        .expect("rpc should succeed after re-pinning the rotated certificate");
+    assert_eq!(server.state.challenges.load(Ordering::SeqCst), 2);
+    assert!(tls.pins().contains(&server_fingerprint("server-b")));
+    assert!(
+        tls.pins().contains(&server_fingerprint("server-a")),
+        "the replaced certificate stays pinned for in-flight connections"
+    );
+}
+
+#[tokio::test]
+async fn mtls_client_refuses_a_certificate_the_runtime_did_not_attest() {
+    let server = MtlsTestServer::start("server-a").await;
+    *server
+        .state
+        .attested_fingerprint
+        .write()
+        .expect("fingerprint lock should not be poisoned") = server_fingerprint("server-b");
+    let tls = EnclaveRpcTls::load(
+        &EnclaveRpcClientTlsConfig {
+            client_cert_path: fixture("client.pem"),
+            client_key_path: fixture("client.key"),
+        },
+        Duration::from_secs(5),
+    )
+    .expect("client identity should load");
+    let client = EnclaveRpcClient::new(
+        server.base_url.clone(),
+        EnclaveRpcAuthConfig {
+            signing_key: None,
+            max_clock_skew_seconds: 30,
+        },
+        reqwest::Client::new(),
+    )
+    .with_mtls(
+        &tls,
+        secret_runtime(&server.base_url).with_enclave_tls(&tls),
+    );
+
+    let err = exchange(&client)
+        .await
+        .expect_err("an unattested certificate must not be trusted");
+
+    assert!(matches!(
+        err,
+        super::super::EnclaveRpcError::RpcTransportUnavailable { .. }
+    ));
+    assert_eq!(server.state.handled_rpcs.load(Ordering::SeqCst), 0);
+}
+
+async fn exchange(
+    client: &EnclaveRpcClient,
+) -> Result<super::super::ExchangeGoogleTokenResponse, super::super::EnclaveRpcError> {
+    client
+        .exchange_google_access_token(ConnectorSecretRequest {
+            user_id: Uuid::new_v4(),
+            connector_id: Uuid::new_v4(),
+        })
+        .await
+}
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/tls")
+        .join(name)
+}
+
+fn server_fingerprint(name: &str) -> String {
+    let chain = load_certificate_chain(&fixture(&format!("{name}.pem")))
+        .expect("server certificate should load");
+    certificate_sha256(chain[0].as_ref())
+}
+
+fn secret_runtime(base_url: &str) -> SecretRuntime {
+    SecretRuntime::new(
+        TeeAttestationPolicy {
+            required: false,
+            expected_runtime: "nitro".to_string(),
+            allowed_measurements: vec!["dev-local-enclave".to_string()],
+            attestation_public_key: None,
+            max_attestation_age_seconds: 300,
+            allow_insecure_dev_attestation: true,
+        },
+        KmsDecryptPolicy {
+            key_id: "kms/local/alfred-refresh-token".to_string(),
+            key_version: 1,
+            allowed_measurements: vec!["dev-local-enclave".to_string()],
+        },
+        base_url.to_string(),
+        2000,
+        reqwest::Client::new(),
+    )
+}
+
+#[derive(Clone, Default)]
+struct TestRuntimeState {
+    attested_fingerprint: Arc<RwLock<String>>,
+    challenges: Arc<AtomicUsize>,
+    handled_rpcs: Arc<AtomicUsize>,
+}
+
+struct MtlsTestServer {
+    base_url: String,
+    state: TestRuntimeState,
+    acceptor: Arc<RwLock<TlsAcceptor>>,
+    _server: tokio::task::JoinHandle<()>,
+}
+
+impl MtlsTestServer {
+    async fn start(certificate: &str) -> Self {
+        let state = TestRuntimeState::default();
+        *state
+            .attested_fingerprint
+            .write()
+            .expect("fingerprint lock should not be poisoned") = server_fingerprint(certificate);
+        let acceptor = Arc::new(RwLock::new(server_acceptor(certificate)));
+        let tcp = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("test listener should bind");
+        let base_url = format!(
+            "https://{}",
+            tcp.local_addr()
+                .expect("listener should expose local address")
+        );
+
+        // Responses close the connection so every call performs a fresh handshake.
+        let app = Router::new()
+            .route(
+                "/v1/attestation/challenge",
+                post(
+                    |State(state): State<TestRuntimeState>,
+                     Json(challenge): Json<AttestationChallengeRequest>| async move {
+                        state.challenges.fetch_add(1, Ordering::SeqCst);
+                        let fingerprint = state
+                            .attested_fingerprint
+                            .read()
+                            .expect("fingerprint lock should not be poisoned")
+                            .clone();
+                        (
+                            [(header::CONNECTION, "close")],
+                            Json(AttestationChallengeResponse {
+                                runtime: "nitro".to_string(),
+                                measurement: "dev-local-enclave".to_string(),
+                                challenge_nonce: challenge.challenge_nonce,
+                                issued_at: challenge.issued_at,
+                                expires_at: challenge.expires_at,
+                                operation_purpose: challenge.operation_purpose,
+                                request_id: challenge.request_id,
+                                evidence_issued_at: challenge.issued_at,
+                                tls_certificate_sha256: Some(fingerprint),
+                                signature: None,
+                            }),
+                        )
+                            .into_response()
+                    },
+                ),
+            )
+            .route(
+                ENCLAVE_RPC_PATH_EXCHANGE_GOOGLE_TOKEN,
+                post(
+                    |State(state): State<TestRuntimeState>,
+                     Json(req): Json<EnclaveRpcExchangeGoogleTokenRequest>| async move {
+                        state.handled_rpcs.fetch_add(1, Ordering::SeqCst);
+                        (
+                            [(header::CONNECTION, "close")],
+                            Json(EnclaveRpcExchangeGoogleTokenResponse {
+                                contract_version: ENCLAVE_RPC_CONTRACT_VERSION.to_string(),
+                                request_id: req.request_id,
+                                access_token: "access-token".to_string(),
+                                attested_identity: AttestedIdentityPayload {
+                                    runtime: "nitro".to_string(),
+                                    measurement: "dev-local-enclave".to_string(),
+                                },
+                            }),
+                        )
+                            .into_response()
+                    },
+                ),
+            )
+            .with_state(state.clone());
+
+        let listener = MtlsListener {
+            tcp,
+            acceptor: acceptor.clone(),
+        };
+        let server = tokio::spawn(async move {
+            axum::serve(listener, app)
+                .await
+                .expect("test server should run");
+        });
+
+        Self {
+            base_url,
+            state,
+            acceptor,
+            _server: server,
+        }
+    }
+
+    fn rotate_to(&self, certificate: &str) {
+        *self
+            .acceptor
+            .write()
+            .expect("acceptor lock should not be poisoned") = server_acceptor(certificate);
+        *self
+            .state
+            .attested_fingerprint
+            .write()
+            .expect("fingerprint lock should not be poisoned") = server_fingerprint(certificate);
+    }
+}
+
+fn server_acceptor(certificate: &str) -> TlsAcceptor {
+    let provider = Arc::new(ring::default_provider());
+    let mut client_roots = RootCertStore::empty();
+    for ca in load_certificate_chain(&fixture("client-ca.pem")).expect("client CA should load") {
+        client_roots.add(ca).expect("client CA should be valid");
+    }
+    let verifier =
+        WebPkiClientVerifier::builder_with_provider(Arc::new(client_roots), provider.clone())
+            .build()
+            .expect("client verifier should build");
+    let config = ServerConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .expect("protocol versions should be supported")
+        .with_client_cert_verifier(verifier)
+        .with_single_cert(
+            load_certificate_chain(&fixture(&format!("{certificate}.pem")))
+                .expect("server certificate should load"),
+            load_private_key(&fixture(&format!("{certificate}.key")))
+                .expect("server key should load"),
+        )
+        .expect("server certificate should match its key");
+    TlsAcceptor::from(Arc::new(config))
+}
+
+/// Completes the TLS handshake during accept; failed handshakes are dropped.
+struct MtlsListener {
+    tcp: TcpListener,
+    acceptor: Arc<RwLock<TlsAcceptor>>,
+}
+
+impl axum::serve::Listener for MtlsListener {
+    type Io = tokio_rustls::server::TlsStream<TcpStream>;
+    type Addr = SocketAddr;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        loop {
+            let Ok((stream, addr)) = self.tcp.accept().await else {
+                continue;
+            };
+            let acceptor = self
+                .acceptor
+                .read()
+                .expect("acceptor lock should not be poisoned")
+                .clone();
+            if let Ok(stream) = acceptor.accept(stream).await {
+                return (stream, addr);
+            }
+        }
+    }
+
+    fn local_addr(&self) -> io::Result<Self::Addr> {
+        self.tcp.local_addr()
+    }
+}
@@ -0,0 +1,376 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use sha2::{Digest, Sha256};
+use tokio_rustls::rustls::client::danger::{
+    HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier,
+};
+use tokio_rustls::rustls::crypto::{
+    CryptoProvider, ring, verify_tls12_signature, verify_tls13_signature,
+};
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use tokio_rustls::rustls::{
+    CertificateError, ClientConfig, DigitallySignedStruct, Error as TlsError, SignatureScheme,
+};
+
+use super::transport::EnclaveHttpClient;
+
+/// Server certificates pinned at once: the current one and the one it replaced, so connections
+/// opened just before a rotation still verify.
+const MAX_PINNED_CERTIFICATES: usize = 2;
+
+/// How callers authenticate to the enclave runtime's RPC endpoints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnclaveRpcSecurityMode {
+    /// Ed25519-signed requests over any transport.
+    Signed,
+    /// Mutual TLS; requests are not signed.
+    Mtls,
+    /// Mutual TLS carrying signed requests.
+    SignedAndMtls,
+}
+
+impl EnclaveRpcSecurityMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Signed => "signed",
+            Self::Mtls => "mtls",
+            Self::SignedAndMtls => "signed+mtls",
+        }
+    }
+
+    pub fn requires_signature(self) -> bool {
+        matches!(self, Self::Signed | Self::SignedAndMtls)
+    }
+
+    pub fn requires_mtls(self) -> bool {
+        matches!(self, Self::Mtls | Self::SignedAndMtls)
+    }
+}
+
+impl FromStr for EnclaveRpcSecurityMode {
+    type Err = String;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "signed" => Ok(Self::Signed),
+            "mtls" => Ok(Self::Mtls),
+            "signed+mtls" => Ok(Self::SignedAndMtls),
+            _ => Err(format!(
+                "ENCLAVE_RPC_SECURITY_MODE must be one of signed, mtls, signed+mtls; got '{}'",
+                raw
+            )),
+        }
+    }
+}
+
+/// Client certificate the API server and worker present to the enclave runtime.
+#[derive(Debug, Clone)]
+pub struct EnclaveRpcClientTlsConfig {
+    pub client_cert_path: PathBuf,
+    pub client_key_path: PathBuf,
+}
+
+/// Lowercase hex SHA-256 of a DER certificate, as attested by the runtime and pinned by clients.
+pub fn certificate_sha256(der: &[u8]) -> String {
+    Sha256::digest(der)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+pub fn load_certificate_chain(path: &Path) -> Result<Vec<CertificateDer<'static>>, String> {
+    let pem = std::fs::read(path)
+        .map_err(|err| format!("failed to read certificate {}: {err}", path.display()))?;
+    let certificates = CertificateDer::pem_slice_iter(&pem)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| format!("failed to parse certificate {}: {err}", path.display()))?;
+    if certificates.is_empty() {
+        return Err(format!("{} contains no certificates", path.display()));
+    }
+    Ok(certificates)
+}
+
+pub fn load_private_key(path: &Path) -> Result<PrivateKeyDer<'static>, String> {
+    let pem = std::fs::read(path)
+        .map_err(|err| format!("failed to read private key {}: {err}", path.display()))?;
+    PrivateKeyDer::from_pem_slice(&pem)
+        .map_err(|err| format!("failed to parse private key {}: {err}", path.display()))
+}
+
+/// Enclave runtime certificates the client currently trusts. Pins come from attestation
+/// challenges, never from the TLS handshake itself.
+#[derive(Debug, Clone, Default)]
+pub struct EnclaveTlsPins {
+    fingerprints: Arc<RwLock<Vec<String>>>,
+    unpinned_rejected: Arc<AtomicBool>,
+}
+
+impl EnclaveTlsPins {
+    /// Trusts `fingerprint`, dropping the oldest pin beyond the rotation overlap. Returns
+    /// `false` when it was already pinned.
+    pub fn pin(&self, fingerprint: String) -> bool {
+        let mut fingerprints = self
+            .fingerprints
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if fingerprints.contains(&fingerprint) {
+            return false;
+        }
+        fingerprints.push(fingerprint);
+        let excess = fingerprints.len().saturating_sub(MAX_PINNED_CERTIFICATES);
+        fingerprints.drain(..excess);
+        true
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fingerprints
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .is_empty()
+    }
+
+    pub fn contains(&self, fingerprint: &str) -> bool {
+        self.fingerprints
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .iter()
+            .any(|pinned| pinned == fingerprint)
+    }
+
+    /// Whether a handshake was refused for an unpinned certificate since the last call, which is
+    /// how a client notices that the runtime rotated its certificate.
+    pub fn take_unpinned_rejection(&self) -> bool {
+        self.unpinned_rejected.swap(false, Ordering::SeqCst)
+    }
+}
+
+/// Client-side mTLS material for talking to the enclave runtime.
+#[derive(Clone)]
+pub struct EnclaveRpcTls {
+    pins: EnclaveTlsPins,
+    rpc_client: EnclaveHttpClient,
+    attestation_client: EnclaveHttpClient,
+}
+
+impl EnclaveRpcTls {
+    pub fn load(config: &EnclaveRpcClientTlsConfig, timeout: Duration) -> Result<Self, String> {
+        let certificates = load_certificate_chain(&config.client_cert_path)?;
+        let key = load_private_key(&config.client_key_path)?;
+        let provider = Arc::new(ring::default_provider());
+        let pins = EnclaveTlsPins::default();
+
+        let rpc_tls = client_config(
+            &provider,
+            Arc::new(PinnedServerCertVerifier {
+                pins: pins.clone(),
+                provider: provider.clone(),
+            }),
+            certificates.clone(),
+            key.clone_key(),
+        )?;
+        let attestation_tls = client_config(
+            &provider,
+            Arc::new(AttestationServerCertVerifier {
+                provider: provider.clone(),
+            }),
+            certificates,
+            key,
+        )?;
+
+        Ok(Self {
+            pins,
+            rpc_client: EnclaveHttpClient::with_tls(rpc_tls, timeout)?,
+            attestation_client: EnclaveHttpClient::with_tls(attestation_tls, timeout)?,
+        })
+    }
+
+    pub fn pins(&self) -> &EnclaveTlsPins {
+        &self.pins
+    }
+
+    /// Client for signed RPCs; only accepts pinned runtime certificates.
+    pub fn rpc_client(&self) -> EnclaveHttpClient {
+        self.rpc_client.clone()
+    }
+
+    /// Client for attestation challenges and the startup probe. It presents the client
+    /// certificate but accepts any runtime certificate: challenge responses are signed and bound
+    /// to a fresh nonce, so they do not rely on the channel for authenticity.
+    pub fn attestation_client(&self) -> EnclaveHttpClient {
+        self.attestation_client.clone()
+    }
+}
+
+impl fmt::Debug for EnclaveRpcTls {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EnclaveRpcTls")
+            .field("pins", &self.pins)
+            .finish_non_exhaustive()
+    }
+}
+
+fn client_config(
+    provider: &Arc<CryptoProvider>,
+    verifier: Arc<dyn ServerCertVerifier>,
+    certificates: Vec<CertificateDer<'static>>,
+    key: PrivateKeyDer<'static>,
+) -> Result<ClientConfig, String> {
+    ClientConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .map_err(|err| format!("failed to configure enclave rpc tls: {err}"))?
+        .dangerous()
+        .with_custom_certificate_verifier(verifier)
+        .with_client_auth_cert(certificates, key)
+        .map_err(|err| format!("invalid enclave rpc client certificate: {err}"))
+}
+
+/// Accepts the runtime's certificate only when its fingerprint is pinned. The runtime has no
+/// stable hostname or public CA; its identity is whatever it attested to.
+#[derive(Debug)]
+struct PinnedServerCertVerifier {
+    pins: EnclaveTlsPins,
+    provider: Arc<CryptoProvider>,
+}
+
+impl ServerCertVerifier for PinnedServerCertVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, TlsError> {
+        if self.pins.contains(&certificate_sha256(end_entity)) {
+            return Ok(ServerCertVerified::assertion());
+        }
+        self.pins.unpinned_rejected.store(true, Ordering::SeqCst);
+        Err(TlsError::InvalidCertificate(
+            CertificateError::ApplicationVerificationFailure,
+        ))
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, TlsError> {
+        verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, TlsError> {
+        verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
+
+/// Checks only that the runtime holds the key for the certificate it presents.
+#[derive(Debug)]
+struct AttestationServerCertVerifier {
+    provider: Arc<CryptoProvider>,
+}
+
+impl ServerCertVerifier for AttestationServerCertVerifier {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, TlsError> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, TlsError> {
+        verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, TlsError> {
+        verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::{EnclaveRpcSecurityMode, EnclaveTlsPins};
+
+    #[test]
+    fn parses_security_modes() {
+        assert_eq!(
+            EnclaveRpcSecurityMode::from_str("Signed+mTLS").expect("mixed case should parse"),
+            EnclaveRpcSecurityMode::SignedAndMtls
+        );
+        assert!(EnclaveRpcSecurityMode::Mtls.requires_mtls());
+        assert!(!EnclaveRpcSecurityMode::Mtls.requires_signature());
+        assert!(EnclaveRpcSecurityMode::from_str("tls").is_err());
+    }
+
+    #[test]
+    fn pins_keep_the_current_and_previous_certificate() {
+        let pins = EnclaveTlsPins::default();
+        assert!(pins.pin("cert-a".to_string()));
+        assert!(!pins.pin("cert-a".to_string()));
+        assert!(pins.pin("cert-b".to_string()));
+        assert!(pins.pin("cert-c".to_string()));
+
+        assert!(!pins.contains("cert-a"));
+        assert!(pins.contains("cert-b"));
+        assert!(pins.contains("cert-c"));
+    }
+}
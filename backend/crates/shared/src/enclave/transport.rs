@@ -0,0 +1,229 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use thiserror::Error;
+use tokio_rustls::rustls::ClientConfig;
+
+use super::vsock::VsockAddr;
+
+/// Bound on a vsock exchange when the caller sets none. HTTP callers inherit the timeout of their
+/// `reqwest::Client`, which the vsock path does not go through.
+const DEFAULT_VSOCK_REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// HTTP client for the enclave runtime. With mTLS it also carries the TLS settings, which the
+/// vsock path applies itself because it does not go through `reqwest`.
+#[derive(Debug, Clone)]
+pub struct EnclaveHttpClient {
+    http: reqwest::Client,
+    tls: Option<Arc<ClientConfig>>,
+}
+
+impl EnclaveHttpClient {
+    pub(crate) fn with_tls(config: ClientConfig, timeout: Duration) -> Result<Self, String> {
+        let http = reqwest::Client::builder()
+            .use_preconfigured_tls(config.clone())
+            .timeout(timeout)
+            .build()
+            .map_err(|err| format!("failed to build enclave rpc tls client: {err}"))?;
+        Ok(Self {
+            http,
+            tls: Some(Arc::new(config)),
+        })
+    }
+}
+
+impl From<reqwest::Client> for EnclaveHttpClient {
+    fn from(http: reqwest::Client) -> Self {
+        Self { http, tls: None }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum EnclaveRequestMethod {
+    Get,
+    Post,
+}
+
+pub(crate) struct EnclaveRequest<'a> {
+    pub(crate) method: EnclaveRequestMethod,
+    pub(crate) path: &'a str,
+    pub(crate) headers: Vec<(&'static str, String)>,
+    pub(crate) body: Option<Vec<u8>>,
+    pub(crate) timeout: Option<Duration>,
+}
+
+pub(crate) struct EnclaveResponse {
+    pub(crate) status: u16,
+    pub(crate) body: Vec<u8>,
+}
+
+#[derive(Debug, Error)]
+pub(crate) enum EnclaveTransportError {
+    /// The request never reached the runtime or no response came back.
+    #[error("{0}")]
+    Send(String),
+    /// A response arrived but its body could not be read.
+    #[error("{0}")]
+    Body(String),
+}
+
+/// Sends one request to the enclave runtime at `base_url`. A `vsock://<cid>:<port>` base URL is
+/// spoken to as HTTP/1.1 over vsock; anything else goes through the `reqwest` client. Both paths
+/// put the same method, path, headers, and body on the wire, so signed RPCs verify either way.
+pub(crate) async fn send_enclave_request(
+    base_url: &str,
+    client: &EnclaveHttpClient,
+    request: EnclaveRequest<'_>,
+) -> Result<EnclaveResponse, EnclaveTransportError> {
+    if !VsockAddr::is_vsock_url(base_url) {
+        return send_over_http(base_url, &client.http, request).await;
+    }
+
+    let addr = VsockAddr::parse_url(base_url).map_err(EnclaveTransportError::Send)?;
+    let timeout = request.timeout.unwrap_or(DEFAULT_VSOCK_REQUEST_TIMEOUT);
+    tokio::time::timeout(timeout, send_over_vsock(addr, client.tls.clone(), request))
+        .await
+        .map_err(|_| {
+            EnclaveTransportError::Send(format!(
+                "request to {addr} timed out after {}ms",
+                timeout.as_millis()
+            ))
+        })?
+}
+
+async fn send_over_http(
+    base_url: &str,
+    http_client: &reqwest::Client,
+    request: EnclaveRequest<'_>,
+) -> Result<EnclaveResponse, EnclaveTransportError> {
+    let url = format!("{}{}", base_url.trim_end_matches('/'), request.path);
+    let mut builder = match request.method {
+        EnclaveRequestMethod::Get => http_client.get(&url),
+        EnclaveRequestMethod::Post => http_client.post(&url),
+    };
+    for (name, value) in request.headers {
+        builder = builder.header(name, value);
+    }
+    if let Some(body) = request.body {
+        builder = builder.body(body);
+    }
+    if let Some(timeout) = request.timeout {
+        builder = builder.timeout(timeout);
+    }
+
+    let response = builder.send().await.map_err(|err| {
+        EnclaveTransportError::Send(format!(
+            "{err} (is_timeout={}, is_connect={})",
+            err.is_timeout(),
+            err.is_connect()
+        ))
+    })?;
+    let status = response.status().as_u16();
+    let body = response
+        .bytes()
+        .await
+        .map_err(|err| EnclaveTransportError::Body(err.to_string()))?;
+
+    Ok(EnclaveResponse {
+        status,
+        body: body.to_vec(),
+    })
+}
+
+#[cfg(target_os = "linux")]
+async fn send_over_vsock(
+    addr: VsockAddr,
+    tls: Option<Arc<ClientConfig>>,
+    request: EnclaveRequest<'_>,
+) -> Result<EnclaveResponse, EnclaveTransportError> {
+    let stream = super::vsock::VsockStream::connect(addr)
+        .await
+        .map_err(|err| {
+            EnclaveTransportError::Send(format!("failed to connect to {addr}: {err}"))
+        })?;
+    let authority = format!("{}:{}", addr.cid, addr.port);
+    let Some(tls) = tls else {
+        return exchange_http1(stream, &authority, request).await;
+    };
+
+    // The runtime's certificate is pinned by fingerprint, so the name only feeds SNI.
+    let server_name = tokio_rustls::rustls::pki_types::ServerName::try_from("enclave-runtime")
+        .map_err(|err| {
+            EnclaveTransportError::Send(format!("invalid enclave tls server name: {err}"))
+        })?;
+    let stream = tokio_rustls::TlsConnector::from(tls)
+        .connect(server_name, stream)
+        .await
+        .map_err(|err| {
+            EnclaveTransportError::Send(format!("tls handshake with {addr} failed: {err}"))
+        })?;
+    exchange_http1(stream, &authority, request).await
+}
+
+/// Runs one HTTP/1.1 exchange over an already connected stream.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub(crate) async fn exchange_http1<S>(
+    stream: S,
+    authority: &str,
+    request: EnclaveRequest<'_>,
+) -> Result<EnclaveResponse, EnclaveTransportError>
+where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin + Send + 'static,
+{
+    use http_body_util::{BodyExt, Full};
+    use hyper::body::Bytes;
+    use hyper_util::rt::TokioIo;
+
+    let (mut sender, connection) = hyper::client::conn::http1::handshake(TokioIo::new(stream))
+        .await
+        .map_err(|err| {
+            EnclaveTransportError::Send(format!("http handshake with {authority} failed: {err}"))
+        })?;
+    tokio::spawn(async move {
+        if let Err(err) = connection.await {
+            tracing::debug!(error = %err, "enclave runtime connection closed with error");
+        }
+    });
+
+    let method = match request.method {
+        EnclaveRequestMethod::Get => hyper::Method::GET,
+        EnclaveRequestMethod::Post => hyper::Method::POST,
+    };
+    let mut builder = hyper::Request::builder()
+        .method(method)
+        .uri(request.path)
+        .header(hyper::header::HOST, authority);
+    for (name, value) in request.headers {
+        builder = builder.header(name, value);
+    }
+    let http_request = builder
+        .body(Full::new(Bytes::from(request.body.unwrap_or_default())))
+        .map_err(|err| EnclaveTransportError::Send(format!("invalid enclave request: {err}")))?;
+
+    let response = sender.send_request(http_request).await.map_err(|err| {
+        EnclaveTransportError::Send(format!("request to {authority} failed: {err}"))
+    })?;
+    let status = response.status().as_u16();
+    let body = response
+        .into_body()
+        .collect()
+        .await
+        .map_err(|err| EnclaveTransportError::Body(err.to_string()))?
+        .to_bytes();
+
+    Ok(EnclaveResponse {
+        status,
+        body: body.to_vec(),
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+async fn send_over_vsock(
+    addr: VsockAddr,
+    _tls: Option<Arc<ClientConfig>>,
+    _request: EnclaveRequest<'_>,
+) -> Result<EnclaveResponse, EnclaveTransportError> {
+    Err(EnclaveTransportError::Send(format!(
+        "cannot reach {addr}: the vsock transport is only available on Linux"
+    )))
+}
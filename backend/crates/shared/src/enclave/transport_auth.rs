@@ -1,39 +1,177 @@
-use hmac::{Hmac, Mac};
-use sha2::Sha256;
+use std::collections::HashMap;
+use std::fmt;
+
+use base64::Engine as _;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use sha2::{Digest, Sha256};
 
 pub const ENCLAVE_RPC_CONTRACT_VERSION_HEADER: &str = "x-alfred-rpc-version";
+pub const ENCLAVE_RPC_AUTH_CALLER_HEADER: &str = "x-alfred-rpc-caller";
 pub const ENCLAVE_RPC_AUTH_TIMESTAMP_HEADER: &str = "x-alfred-rpc-ts";
 pub const ENCLAVE_RPC_AUTH_NONCE_HEADER: &str = "x-alfred-rpc-nonce";
 pub const ENCLAVE_RPC_AUTH_SIGNATURE_HEADER: &str = "x-alfred-rpc-signature";
 
+/// The parts of an enclave RPC a caller signs, shared by signing and verification so both sides
+/// build the same canonical payload.
+#[derive(Debug, Clone, Copy)]
+pub struct SignedRpcRequest<'a> {
+    pub method: &'a str,
+    pub path: &'a str,
+    pub timestamp: i64,
+    pub nonce: &'a str,
+    pub body: &'a [u8],
+}
+
+/// How a host service authenticates its enclave RPCs.
 #[derive(Debug, Clone)]
 pub struct EnclaveRpcAuthConfig {
-    pub shared_secret: String,
+    /// `None` in mTLS-only mode, where the handshake authenticates the caller.
+    pub signing_key: Option<EnclaveRpcSigningKey>,
     pub max_clock_skew_seconds: u64,
 }
 
-pub fn sign_rpc_request(
-    shared_secret: &str,
-    method: &str,
-    path: &str,
-    timestamp: i64,
-    nonce: &str,
-    body: &[u8],
-) -> String {
-    let mut mac = Hmac::<Sha256>::new_from_slice(shared_secret.as_bytes())
-        .expect("HMAC accepts shared secret key of any size");
-    mac.update(method.as_bytes());
-    mac.update(&[0u8]);
-    mac.update(path.as_bytes());
-    mac.update(&[0u8]);
-    mac.update(timestamp.to_string().as_bytes());
-    mac.update(&[0u8]);
-    mac.update(nonce.as_bytes());
-    mac.update(&[0u8]);
-    mac.update(body);
-
-    let digest = mac.finalize().into_bytes();
-    to_lower_hex(digest.as_slice())
+/// Ed25519 key a caller signs enclave RPCs with. The enclave runtime looks up the matching
+/// public key by `caller_id`, so each service can be rotated or revoked on its own.
+#[derive(Clone)]
+pub struct EnclaveRpcSigningKey {
+    caller_id: String,
+    key: SigningKey,
+}
+
+impl EnclaveRpcSigningKey {
+    pub fn new(caller_id: impl Into<String>, private_key: [u8; 32]) -> Self {
+        Self {
+            caller_id: caller_id.into(),
+            key: SigningKey::from_bytes(&private_key),
+        }
+    }
+
+    /// Deterministic key for local runs, matching the keys the runtime registers by default in
+    /// the local environment.
+    pub fn local_dev(caller_id: &str) -> Self {
+        Self::new(caller_id, local_dev_private_key(caller_id))
+    }
+
+    pub fn caller_id(&self) -> &str {
+        &self.caller_id
+    }
+
+    pub fn public_key_b64(&self) -> String {
+        base64::engine::general_purpose::STANDARD.encode(self.key.verifying_key().as_bytes())
+    }
+
+    /// Base64 signature over the canonical request; see [`rpc_signing_payload`].
+    pub fn sign(&self, request: SignedRpcRequest<'_>) -> String {
+        let payload = rpc_signing_payload(&self.caller_id, request);
+        base64::engine::general_purpose::STANDARD.encode(self.key.sign(&payload).to_bytes())
+    }
+}
+
+impl fmt::Debug for EnclaveRpcSigningKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EnclaveRpcSigningKey")
+            .field("caller_id", &self.caller_id)
+            .field("key", &"<redacted>")
+            .finish()
+    }
+}
+
+/// Callers the enclave runtime accepts signed RPCs from. Removing a caller revokes it without
+/// touching the others.
+#[derive(Debug, Clone, Default)]
+pub struct EnclaveRpcCallerKeys {
+    keys: HashMap<String, VerifyingKey>,
+}
+
+impl EnclaveRpcCallerKeys {
+    /// Parses `caller-id:<base64 Ed25519 public key>` entries separated by commas.
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        let mut callers = Self::default();
+        for entry in raw
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+        {
+            let (caller_id, public_key) = entry
+                .split_once(':')
+                .ok_or_else(|| format!("caller key '{entry}' must have the form caller:key"))?;
+            let caller_id = caller_id.trim();
+            if caller_id.is_empty() {
+                return Err(format!("caller key '{entry}' has an empty caller id"));
+            }
+            let public_key = base64::engine::general_purpose::STANDARD
+                .decode(public_key.trim())
+                .ok()
+                .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+                .and_then(|bytes| VerifyingKey::from_bytes(&bytes).ok())
+                .ok_or_else(|| {
+                    format!("caller '{caller_id}' must have a base64 32-byte Ed25519 public key")
+                })?;
+            if callers
+                .keys
+                .insert(caller_id.to_string(), public_key)
+                .is_some()
+            {
+                return Err(format!("caller '{caller_id}' is listed more than once"));
+            }
+        }
+        Ok(callers)
+    }
+
+    /// Registers the local dev key of each caller.
+    pub fn local_dev(caller_ids: &[&str]) -> Self {
+        let keys = caller_ids
+            .iter()
+            .map(|caller_id| {
+                let key = SigningKey::from_bytes(&local_dev_private_key(caller_id));
+                ((*caller_id).to_string(), key.verifying_key())
+            })
+            .collect();
+        Self { keys }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    pub fn contains(&self, caller_id: &str) -> bool {
+        self.keys.contains_key(caller_id)
+    }
+
+    /// Whether `signature` is `caller_id`'s signature over the request. Unknown callers and
+    /// malformed signatures both verify as `false`.
+    pub fn verify(&self, caller_id: &str, request: SignedRpcRequest<'_>, signature: &str) -> bool {
+        let Some(public_key) = self.keys.get(caller_id) else {
+            return false;
+        };
+        let Some(signature) = base64::engine::general_purpose::STANDARD
+            .decode(signature)
+            .ok()
+            .and_then(|bytes| Signature::from_slice(&bytes).ok())
+        else {
+            return false;
+        };
+        let payload = rpc_signing_payload(caller_id, request);
+        public_key.verify(&payload, &signature).is_ok()
+    }
+}
+
+/// Canonical bytes a caller signs: method, path, caller id, timestamp, and nonce, each followed
+/// by a NUL separator, then the raw body.
+pub fn rpc_signing_payload(caller_id: &str, request: SignedRpcRequest<'_>) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(request.body.len() + 128);
+    for field in [
+        request.method,
+        request.path,
+        caller_id,
+        request.timestamp.to_string().as_str(),
+        request.nonce,
+    ] {
+        payload.extend_from_slice(field.as_bytes());
+        payload.push(0);
+    }
+    payload.extend_from_slice(request.body);
+    payload
 }
 
 pub fn constant_time_eq(left: &str, right: &str) -> bool {
@@ -49,11 +187,6 @@ pub fn constant_time_eq(left: &str, right: &str) -> bool {
     diff == 0
 }
 
-fn to_lower_hex(bytes: &[u8]) -> String {
-    let mut hex = String::with_capacity(bytes.len() * 2);
-    for byte in bytes {
-        use std::fmt::Write;
-        let _ = write!(hex, "{byte:02x}");
-    }
-    hex
+fn local_dev_private_key(caller_id: &str) -> [u8; 32] {
+    Sha256::digest(format!("alfred-local-dev-enclave-rpc:{caller_id}").as_bytes()).into()
 }
@@ -0,0 +1,220 @@
+use std::fmt;
+
+pub const VSOCK_URL_SCHEME: &str = "vsock";
+
+/// Wildcard CID a listener binds to so the parent instance can reach it.
+pub const VSOCK_CID_ANY: u32 = u32::MAX;
+
+/// Address of a vsock endpoint, written as `vsock://<cid>:<port>` in configuration. Listeners
+/// may use `any` as the CID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VsockAddr {
+    pub cid: u32,
+    pub port: u32,
+}
+
+impl VsockAddr {
+    pub fn is_vsock_url(raw: &str) -> bool {
+        raw.trim()
+            .get(..VSOCK_URL_SCHEME.len() + 3)
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case("vsock://"))
+    }
+
+    pub fn parse_url(raw: &str) -> Result<Self, String> {
+        let trimmed = raw.trim();
+        if !Self::is_vsock_url(trimmed) {
+            return Err(format!("'{raw}' is not a vsock:// address"));
+        }
+        let authority = trimmed[VSOCK_URL_SCHEME.len() + 3..].trim_end_matches('/');
+        let (cid, port) = authority
+            .split_once(':')
+            .ok_or_else(|| format!("'{raw}' must have the form vsock://<cid>:<port>"))?;
+
+        let cid = if cid.eq_ignore_ascii_case("any") {
+            VSOCK_CID_ANY
+        } else {
+            cid.parse::<u32>()
+                .map_err(|_| format!("'{raw}' has an invalid vsock CID"))?
+        };
+        let port = port
+            .parse::<u32>()
+            .map_err(|_| format!("'{raw}' has an invalid vsock port"))?;
+
+        Ok(Self { cid, port })
+    }
+}
+
+impl fmt::Display for VsockAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.cid == VSOCK_CID_ANY {
+            write!(f, "{VSOCK_URL_SCHEME}://any:{}", self.port)
+        } else {
+            write!(f, "{VSOCK_URL_SCHEME}://{}:{}", self.cid, self.port)
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub use sys::{VsockListener, VsockStream};
+
+#[cfg(target_os = "linux")]
+mod sys {
+    use std::io::{self, Read, Write};
+    use std::net::Shutdown;
+    use std::pin::Pin;
+    use std::task::{Context, Poll, ready};
+
+    use socket2::{Domain, SockAddr, Socket, Type};
+    use tokio::io::unix::AsyncFd;
+    use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+    use super::VsockAddr;
+
+    const LISTEN_BACKLOG: i32 = 128;
+
+    /// Non-blocking `AF_VSOCK` stream socket driven by the tokio reactor.
+    #[derive(Debug)]
+    pub struct VsockStream {
+        inner: AsyncFd<Socket>,
+    }
+
+    impl VsockStream {
+        pub async fn connect(addr: VsockAddr) -> io::Result<Self> {
+            // A local vsock connect completes or fails promptly, so it runs blocking off the
+            // reactor instead of tracking EINPROGRESS by hand.
+            let socket = tokio::task::spawn_blocking(move || {
+                let socket = Socket::new(Domain::VSOCK, Type::STREAM, None)?;
+                socket.connect(&SockAddr::vsock(addr.cid, addr.port))?;
+                Ok::<_, io::Error>(socket)
+            })
+            .await
+            .map_err(io::Error::other)??;
+
+            Self::from_socket(socket)
+        }
+
+        fn from_socket(socket: Socket) -> io::Result<Self> {
+            socket.set_nonblocking(true)?;
+            Ok(Self {
+                inner: AsyncFd::new(socket)?,
+            })
+        }
+    }
+
+    impl AsyncRead for VsockStream {
+        fn poll_read(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<io::Result<()>> {
+            loop {
+                let mut guard = ready!(self.inner.poll_read_ready(cx))?;
+                let unfilled = buf.initialize_unfilled();
+                match guard.try_io(|inner| inner.get_ref().read(unfilled)) {
+                    Ok(Ok(read)) => {
+                        buf.advance(read);
+                        return Poll::Ready(Ok(()));
+                    }
+                    Ok(Err(err)) => return Poll::Ready(Err(err)),
+                    Err(_would_block) => continue,
+                }
+            }
+        }
+    }
+
+    impl AsyncWrite for VsockStream {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            loop {
+                let mut guard = ready!(self.inner.poll_write_ready(cx))?;
+                match guard.try_io(|inner| inner.get_ref().write(buf)) {
+                    Ok(result) => return Poll::Ready(result),
+                    Err(_would_block) => continue,
+                }
+            }
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(self.inner.get_ref().shutdown(Shutdown::Write))
+        }
+    }
+
+    /// Listening `AF_VSOCK` socket for the enclave side of the RPC channel.
+    #[derive(Debug)]
+    pub struct VsockListener {
+        inner: AsyncFd<Socket>,
+    }
+
+    impl VsockListener {
+        pub fn bind(addr: VsockAddr) -> io::Result<Self> {
+            let socket = Socket::new(Domain::VSOCK, Type::STREAM, None)?;
+            socket.set_nonblocking(true)?;
+            socket.bind(&SockAddr::vsock(addr.cid, addr.port))?;
+            socket.listen(LISTEN_BACKLOG)?;
+            Ok(Self {
+                inner: AsyncFd::new(socket)?,
+            })
+        }
+
+        pub async fn accept(&self) -> io::Result<(VsockStream, VsockAddr)> {
+            loop {
+                let mut guard = self.inner.readable().await?;
+                match guard.try_io(|inner| inner.get_ref().accept()) {
+                    Ok(Ok((socket, peer))) => {
+                        let (cid, port) = peer.as_vsock_address().unwrap_or_default();
+                        return Ok((VsockStream::from_socket(socket)?, VsockAddr { cid, port }));
+                    }
+                    Ok(Err(err)) => return Err(err),
+                    Err(_would_block) => continue,
+                }
+            }
+        }
+
+        pub fn local_addr(&self) -> io::Result<VsockAddr> {
+            let local = self.inner.get_ref().local_addr()?;
+            let (cid, port) = local.as_vsock_address().ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidData, "listener is not a vsock socket")
+            })?;
+            Ok(VsockAddr { cid, port })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{VSOCK_CID_ANY, VsockAddr};
+
+    #[test]
+    fn parses_vsock_urls() {
+        assert_eq!(
+            VsockAddr::parse_url("vsock://16:8181").expect("cid and port should parse"),
+            VsockAddr {
+                cid: 16,
+                port: 8181
+            }
+        );
+        assert_eq!(
+            VsockAddr::parse_url("VSOCK://any:8181/").expect("wildcard cid should parse"),
+            VsockAddr {
+                cid: VSOCK_CID_ANY,
+                port: 8181
+            }
+        );
+        assert!(!VsockAddr::is_vsock_url("http://127.0.0.1:8181"));
+        assert!(VsockAddr::parse_url("vsock://16").is_err());
+        assert!(VsockAddr::parse_url("vsock://parent:8181").is_err());
+        assert_eq!(
+            VsockAddr::parse_url("vsock://any:8181")
+                .expect("wildcard cid should parse")
+                .to_string(),
+            "vsock://any:8181"
+        );
+    }
+}
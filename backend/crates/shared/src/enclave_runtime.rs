@@ -2,10 +2,13 @@ use std::str::FromStr;
 use std::time::Duration;
 
 use chrono::Utc;
-use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::enclave::transport::{
+    EnclaveHttpClient, EnclaveRequest, EnclaveRequestMethod, send_enclave_request,
+};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AlfredEnvironment {
     Local,
@@ -98,6 +101,9 @@ pub struct AttestationChallengeResponse {
     pub operation_purpose: String,
     pub request_id: String,
     pub evidence_issued_at: i64,
+    /// SHA-256 of the certificate on the runtime's mTLS listener, when it serves one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls_certificate_sha256: Option<String>,
     pub signature: Option<String>,
 }
 
@@ -126,7 +132,7 @@ pub struct AssistantAttestedKeyChallengeResponse {
 }
 
 pub fn attestation_signing_payload(response: &AttestationChallengeResponse) -> String {
-    format!(
+    let payload = format!(
         "{}|{}|{}|{}|{}|{}|{}|{}",
         response.runtime,
         response.measurement,
@@ -136,7 +142,11 @@ pub fn attestation_signing_payload(response: &AttestationChallengeResponse) -> S
         response.operation_purpose,
         response.request_id,
         response.evidence_issued_at
-    )
+    );
+    match response.tls_certificate_sha256.as_deref() {
+        Some(fingerprint) => format!("{payload}|{fingerprint}"),
+        None => payload,
+    }
 }
 
 pub fn assistant_key_attestation_signing_payload(
@@ -169,7 +179,7 @@ pub enum EnclaveRuntimeProbeError {
 }
 
 pub async fn verify_connectivity(
-    client: &reqwest::Client,
+    client: &EnclaveHttpClient,
     config: &EnclaveRuntimeEndpointConfig,
 ) -> Result<(), EnclaveRuntimeProbeError> {
     if matches!(config.mode, EnclaveRuntimeMode::Disabled) {
@@ -177,46 +187,26 @@ pub async fn verify_connectivity(
     }
 
     let timeout = Duration::from_millis(config.probe_timeout_ms);
-    let healthz_url = format!("{}/healthz", config.base_url.trim_end_matches('/'));
-    let healthz_response = client
-        .get(&healthz_url)
-        .timeout(timeout)
-        .send()
-        .await
-        .map_err(|err| EnclaveRuntimeProbeError::RequestFailed {
-            url: healthz_url.clone(),
-            message: err.to_string(),
-        })?;
-    if healthz_response.status() != StatusCode::OK {
-        return Err(EnclaveRuntimeProbeError::UnexpectedStatus {
-            url: healthz_url,
-            status: healthz_response.status().as_u16(),
-        });
-    }
-
-    let attestation_url = format!(
-        "{}/v1/attestation/document",
-        config.base_url.trim_end_matches('/')
-    );
-    let attestation_response = client
-        .get(&attestation_url)
-        .timeout(timeout)
-        .send()
-        .await
-        .map_err(|err| EnclaveRuntimeProbeError::RequestFailed {
-            url: attestation_url.clone(),
-            message: err.to_string(),
-        })?;
-    if attestation_response.status() != StatusCode::OK {
-        return Err(EnclaveRuntimeProbeError::UnexpectedStatus {
-            url: attestation_url,
-            status: attestation_response.status().as_u16(),
-        });
-    }
+    probe_endpoint(
+        client,
+        config,
+        EnclaveRequestMethod::Get,
+        "/healthz",
+        None,
+        timeout,
+    )
+    .await?;
 
-    let attestation = attestation_response
-        .json::<AttestationDocument>()
-        .await
+    let attestation_body = probe_endpoint(
+        client,
+        config,
+        EnclaveRequestMethod::Get,
+        "/v1/attestation/document",
+        None,
+        timeout,
+    )
+    .await?;
+    let attestation = serde_json::from_slice::<AttestationDocument>(&attestation_body)
         .map_err(|err| EnclaveRuntimeProbeError::InvalidAttestationResponse(err.to_string()))?;
     if attestation.runtime.trim().is_empty() || attestation.measurement.trim().is_empty() {
         return Err(EnclaveRuntimeProbeError::InvalidAttestationResponse(
@@ -232,31 +222,21 @@ pub async fn verify_connectivity(
         operation_purpose: "startup_probe".to_string(),
         request_id: "startup-probe".to_string(),
     };
-    let challenge_url = format!(
-        "{}/v1/attestation/challenge",
-        config.base_url.trim_end_matches('/')
-    );
-    let challenge_response = client
-        .post(&challenge_url)
-        .timeout(timeout)
-        .json(&challenge)
-        .send()
-        .await
-        .map_err(|err| EnclaveRuntimeProbeError::RequestFailed {
-            url: challenge_url.clone(),
-            message: err.to_string(),
-        })?;
-    if challenge_response.status() != StatusCode::OK {
-        return Err(EnclaveRuntimeProbeError::UnexpectedStatus {
-            url: challenge_url,
-            status: challenge_response.status().as_u16(),
-        });
-    }
-
-    let challenge_payload = challenge_response
-        .json::<AttestationChallengeResponse>()
-        .await
+    let challenge_body = serde_json::to_vec(&challenge)
         .map_err(|err| EnclaveRuntimeProbeError::InvalidAttestationResponse(err.to_string()))?;
+    let challenge_response_body = probe_endpoint(
+        client,
+        config,
+        EnclaveRequestMethod::Post,
+        "/v1/attestation/challenge",
+        Some(challenge_body),
+        timeout,
+    )
+    .await?;
+
+    let challenge_payload =
+        serde_json::from_slice::<AttestationChallengeResponse>(&challenge_response_body)
+            .map_err(|err| EnclaveRuntimeProbeError::InvalidAttestationResponse(err.to_string()))?;
     if challenge_payload.challenge_nonce != challenge.challenge_nonce
         || challenge_payload.request_id != challenge.request_id
         || challenge_payload.operation_purpose != challenge.operation_purpose
@@ -276,6 +256,46 @@ pub async fn verify_connectivity(
     Ok(())
 }
 
+async fn probe_endpoint(
+    client: &EnclaveHttpClient,
+    config: &EnclaveRuntimeEndpointConfig,
+    method: EnclaveRequestMethod,
+    path: &str,
+    body: Option<Vec<u8>>,
+    timeout: Duration,
+) -> Result<Vec<u8>, EnclaveRuntimeProbeError> {
+    let url = format!("{}{}", config.base_url.trim_end_matches('/'), path);
+    let headers = if body.is_some() {
+        vec![("content-type", "application/json".to_string())]
+    } else {
+        Vec::new()
+    };
+    let response = send_enclave_request(
+        &config.base_url,
+        client,
+        EnclaveRequest {
+            method,
+            path,
+            headers,
+            body,
+            timeout: Some(timeout),
+        },
+    )
+    .await
+    .map_err(|err| EnclaveRuntimeProbeError::RequestFailed {
+        url: url.clone(),
+        message: err.to_string(),
+    })?;
+    if response.status != 200 {
+        return Err(EnclaveRuntimeProbeError::UnexpectedStatus {
+            url,
+            status: response.status,
+        });
+    }
+
+    Ok(response.body)
+}
+
 #[derive(Debug, Deserialize)]
 struct AttestationDocument {
     runtime: String,
@@ -0,0 +1,368 @@
+//! Typed domain events shared by the api-server and worker.
+//!
+//! Handlers describe what happened with a [`DomainEvent`] and publish it on the [`EventBus`];
+//! cross-cutting features (audit trail, logs, metrics) subscribe as [`EventSink`]s instead of
+//! being called from business logic. Inline sinks run before `publish` returns and can fail the
+//! caller, which the audit trail relies on. Background sinks are fed through an in-process
+//! queue and only ever log their failures; that queue is the seam where a Redis stream can
+//! later fan events out across instances.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use thiserror::Error;
+use tokio::sync::mpsc;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::automation_schedule::{AutomationScheduleType, AutomationTemplate};
+use crate::models::AuditMetadata;
+use crate::repos::NotificationDeliveryOutcome;
+
+mod audit_sink;
+mod tracing_sink;
+
+pub use audit_sink::AuditEventSink;
+pub use tracing_sink::TracingEventSink;
+
+const DEFAULT_BACKGROUND_QUEUE_CAPACITY: usize = 1024;
+
+pub type EventSinkFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<(), EventSinkError>> + Send + 'a>>;
+
+#[derive(Debug, Error)]
+#[error("{sink} event sink failed: {message}")]
+pub struct EventSinkError {
+    pub sink: &'static str,
+    pub message: String,
+}
+
+/// How an off-schedule automation run was requested.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ManualRunMode {
+    /// Local-only debug trigger.
+    Debug,
+    /// User-facing "run now".
+    UserRequested,
+}
+
+impl ManualRunMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Debug => "DEBUG_MANUAL",
+            Self::UserRequested => "USER_MANUAL",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum DomainEvent {
+    AutomationRuleCreated {
+        rule_id: Uuid,
+        title: String,
+        schedule_type: AutomationScheduleType,
+        time_zone: String,
+        template: Option<AutomationTemplate>,
+        local_time_minutes: u16,
+    },
+    AutomationRuleUpdated {
+        rule_id: Uuid,
+        updated_fields: Vec<String>,
+    },
+    AutomationRuleDeleted {
+        rule_id: Uuid,
+    },
+    AutomationRunQueued {
+        rule_id: Uuid,
+        job_id: Uuid,
+        mode: ManualRunMode,
+    },
+    AutomationAbuseEscalated {
+        signal: &'static str,
+        observed: usize,
+        blocked_for_seconds: u64,
+    },
+    /// Authentication, attestation, or replay failures crossed their threshold for the
+    /// requesting IP or account. `subject` is `ip` or `user`; the address itself is not kept.
+    SecurityAnomalyDetected {
+        signal: &'static str,
+        subject: &'static str,
+        observed: usize,
+        window_seconds: u64,
+        enforced_for_seconds: Option<u64>,
+    },
+    /// A job resolved to no notification. `details` is the job's action metadata.
+    JobActionSkipped {
+        job_id: Uuid,
+        details: AuditMetadata,
+    },
+    /// A job produced a notification that is about to be delivered.
+    JobActionGenerated {
+        job_id: Uuid,
+        details: AuditMetadata,
+    },
+    NotificationDeliveryAttempted {
+        job_id: Uuid,
+        device_id: String,
+        outcome: NotificationDeliveryOutcome,
+        details: AuditMetadata,
+    },
+    /// An operator used the admin API on the user's data. `principal` names the operator
+    /// credential and `details` carries the action's target and outcome.
+    AdminActionPerformed {
+        principal: String,
+        action: &'static str,
+        details: AuditMetadata,
+    },
+}
+
+impl DomainEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::AutomationRuleCreated { .. } => "automation_rule_created",
+            Self::AutomationRuleUpdated { .. } => "automation_rule_updated",
+            Self::AutomationRuleDeleted { .. } => "automation_rule_deleted",
+            Self::AutomationRunQueued { .. } => "automation_run_queued",
+            Self::AutomationAbuseEscalated { .. } => "automation_abuse_escalated",
+            Self::SecurityAnomalyDetected { .. } => "security_anomaly_detected",
+            Self::JobActionSkipped { .. } => "job_action_skipped",
+            Self::JobActionGenerated { .. } => "job_action_generated",
+            Self::NotificationDeliveryAttempted { .. } => "notification_delivery_attempted",
+            Self::AdminActionPerformed { .. } => "admin_action_performed",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EventEnvelope {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub occurred_at: DateTime<Utc>,
+    pub event: DomainEvent,
+}
+
+pub trait EventSink: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn handle<'a>(&'a self, envelope: &'a EventEnvelope) -> EventSinkFuture<'a>;
+}
+
+#[derive(Default)]
+pub struct EventBusBuilder {
+    inline_sinks: Vec<Arc<dyn EventSink>>,
+    background_sinks: Vec<Arc<dyn EventSink>>,
+    queue_capacity: Option<usize>,
+}
+
+impl EventBusBuilder {
+    /// Adds a sink that runs before `publish` returns; its failure fails the publish.
+    pub fn inline_sink(mut self, sink: impl EventSink + 'static) -> Self {
+        self.inline_sinks.push(Arc::new(sink));
+        self
+    }
+
+    /// Adds a sink fed from the background queue; its failures are logged and dropped.
+    pub fn background_sink(mut self, sink: impl EventSink + 'static) -> Self {
+        self.background_sinks.push(Arc::new(sink));
+        self
+    }
+
+    pub fn queue_capacity(mut self, capacity: usize) -> Self {
+        self.queue_capacity = Some(capacity.max(1));
+        self
+    }
+
+    /// Builds the bus, spawning the background dispatcher when any background sink is
+    /// registered. Must be called inside a tokio runtime in that case.
+    pub fn build(self) -> EventBus {
+        let queue = (!self.background_sinks.is_empty()).then(|| {
+            let (sender, receiver) = mpsc::channel(
+                self.queue_capacity
+                    .unwrap_or(DEFAULT_BACKGROUND_QUEUE_CAPACITY),
+            );
+            tokio::spawn(dispatch_background_events(receiver, self.background_sinks));
+            sender
+        });
+
+        EventBus {
+            inner: Arc::new(EventBusInner {
+                inline_sinks: self.inline_sinks,
+                queue,
+            }),
+        }
+    }
+}
+
+struct EventBusInner {
+    inline_sinks: Vec<Arc<dyn EventSink>>,
+    queue: Option<mpsc::Sender<Arc<EventEnvelope>>>,
+}
+
+#[derive(Clone)]
+pub struct EventBus {
+    inner: Arc<EventBusInner>,
+}
+
+impl EventBus {
+    pub fn builder() -> EventBusBuilder {
+        EventBusBuilder::default()
+    }
+
+    /// Delivers `event` to every inline sink in registration order, then queues it for the
+    /// background sinks. Background sinks do not see events whose inline delivery failed.
+    pub async fn publish(&self, user_id: Uuid, event: DomainEvent) -> Result<(), EventSinkError> {
+        let envelope = Arc::new(EventEnvelope {
+            id: Uuid::new_v4(),
+            user_id,
+            occurred_at: Utc::now(),
+            event,
+        });
+
+        for sink in &self.inner.inline_sinks {
+            sink.handle(&envelope).await?;
+        }
+
+        if let Some(queue) = &self.inner.queue
+            && let Err(err) = queue.try_send(envelope)
+        {
+            let envelope = match err {
+                mpsc::error::TrySendError::Full(envelope)
+                | mpsc::error::TrySendError::Closed(envelope) => envelope,
+            };
+            warn!(
+                event = envelope.event.name(),
+                event_id = %envelope.id,
+                "background event queue unavailable; dropping event"
+            );
+        }
+
+        Ok(())
+    }
+}
+
+async fn dispatch_background_events(
+    mut receiver: mpsc::Receiver<Arc<EventEnvelope>>,
+    sinks: Vec<Arc<dyn EventSink>>,
+) {
+    while let Some(envelope) = receiver.recv().await {
+        for sink in &sinks {
+            if let Err(err) = sink.handle(&envelope).await {
+                warn!(
+                    event = envelope.event.name(),
+                    event_id = %envelope.id,
+                    "{err}"
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use tokio::sync::Notify;
+    use uuid::Uuid;
+
+    use super::{DomainEvent, EventBus, EventEnvelope, EventSink, EventSinkError, EventSinkFuture};
+
+    #[derive(Clone, Default)]
+    struct RecordingSink {
+        seen: Arc<Mutex<Vec<&'static str>>>,
+        notify: Arc<Notify>,
+        fail: bool,
+    }
+
+    impl EventSink for RecordingSink {
+        fn name(&self) -> &'static str {
+            "recording"
+        }
+
+        fn handle<'a>(&'a self, envelope: &'a EventEnvelope) -> EventSinkFuture<'a> {
+            Box::pin(async move {
+                self.seen
+                    .lock()
+                    .expect("recording lock")
+                    .push(envelope.event.name());
+                self.notify.notify_one();
+                if self.fail {
+                    return Err(EventSinkError {
+                        sink: self.name(),
+                        message: "boom".to_string(),
+                    });
+                }
+                Ok(())
+            })
+        }
+    }
+
+    fn deleted() -> DomainEvent {
+        DomainEvent::AutomationRuleDeleted {
+            rule_id: Uuid::new_v4(),
+        }
+    }
+
+    #[tokio::test]
+    async fn inline_failure_fails_publish_and_skips_background_sinks() {
+        let inline = RecordingSink {
+            fail: true,
+            ..RecordingSink::default()
+        };
+        let background = RecordingSink::default();
+        let bus = EventBus::builder()
+            .inline_sink(inline.clone())
+            .background_sink(background.clone())
+            .build();
+
+        let err = bus
+            .publish(Uuid::new_v4(), deleted())
+            .await
+            .expect_err("inline failure should surface");
+        assert_eq!(err.sink, "recording");
+        assert_eq!(inline.seen.lock().expect("lock").len(), 1);
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(background.seen.lock().expect("lock").is_empty());
+    }
+
+    #[tokio::test]
+    async fn background_sinks_receive_events_in_order_and_failures_are_contained() {
+        let failing = RecordingSink {
+            fail: true,
+            ..RecordingSink::default()
+        };
+        let background = RecordingSink::default();
+        let bus = EventBus::builder()
+            .background_sink(failing)
+            .background_sink(background.clone())
+            .build();
+
+        bus.publish(Uuid::new_v4(), deleted())
+            .await
+            .expect("publish should succeed");
+        bus.publish(
+            Uuid::new_v4(),
+            DomainEvent::AutomationRuleUpdated {
+                rule_id: Uuid::new_v4(),
+                updated_fields: vec!["title".to_string()],
+            },
+        )
+        .await
+        .expect("publish should succeed");
+
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while background.seen.lock().expect("lock").len() < 2 {
+                background.notify.notified().await;
+            }
+        })
+        .await
+        .expect("background sink should drain the queue");
+        assert_eq!(
+            *background.seen.lock().expect("lock"),
+            vec!["automation_rule_deleted", "automation_rule_updated"]
+        );
+    }
+}
@@ -0,0 +1,225 @@
+use crate::automation_schedule::format_local_time_hhmm;
+use crate::models::AuditMetadata;
+use crate::repos::{AuditResult, NotificationDeliveryOutcome, Store};
+
+use super::{DomainEvent, EventEnvelope, EventSink, EventSinkError, EventSinkFuture};
+
+/// Writes the user-visible audit trail. Registered inline so a failed audit write still fails
+/// the request that caused it.
+#[derive(Clone)]
+pub struct AuditEventSink {
+    store: Store,
+}
+
+impl AuditEventSink {
+    pub fn new(store: Store) -> Self {
+        Self { store }
+    }
+}
+
+impl EventSink for AuditEventSink {
+    fn name(&self) -> &'static str {
+        "audit"
+    }
+
+    fn handle<'a>(&'a self, envelope: &'a EventEnvelope) -> EventSinkFuture<'a> {
+        Box::pin(async move {
+            let (event_type, result, metadata) = audit_record(&envelope.event);
+            self.store
+                .add_audit_event(envelope.user_id, event_type, None, result, &metadata)
+                .await
+                .map_err(|err| EventSinkError {
+                    sink: self.name(),
+                    message: err.to_string(),
+                })
+        })
+    }
+}
+
+fn audit_record(event: &DomainEvent) -> (&'static str, AuditResult, AuditMetadata) {
+    let mut metadata = AuditMetadata::new();
+    match event {
+        DomainEvent::AutomationRuleCreated {
+            rule_id,
+            title,
+            schedule_type,
+            time_zone,
+            template,
+            local_time_minutes,
+        } => {
+            metadata.insert("rule_id".to_string(), rule_id.to_string().into());
+            metadata.insert("title".to_string(), title.clone().into());
+            metadata.insert("schedule_type".to_string(), schedule_type.as_str().into());
+            metadata.insert("time_zone".to_string(), time_zone.clone().into());
+            if let Some(template) = template {
+                metadata.insert("template".to_string(), template.as_str().into());
+            }
+            metadata.insert(
+                "local_time".to_string(),
+                format_local_time_hhmm(*local_time_minutes).into(),
+            );
+            ("AUTOMATION_RULE_CREATED", AuditResult::Success, metadata)
+        }
+        DomainEvent::AutomationRuleUpdated {
+            rule_id,
+            updated_fields,
+        } => {
+            metadata.insert("rule_id".to_string(), rule_id.to_string().into());
+            metadata.insert(
+                "updated_fields".to_string(),
+                updated_fields.join(",").into(),
+            );
+            ("AUTOMATION_RULE_UPDATED", AuditResult::Success, metadata)
+        }
+        DomainEvent::AutomationRuleDeleted { rule_id } => {
+            metadata.insert("rule_id".to_string(), rule_id.to_string().into());
+            ("AUTOMATION_RULE_DELETED", AuditResult::Success, metadata)
+        }
+        DomainEvent::AutomationRunQueued {
+            rule_id,
+            job_id,
+            mode,
+        } => {
+            metadata.insert("rule_id".to_string(), rule_id.to_string().into());
+            metadata.insert("job_id".to_string(), job_id.to_string().into());
+            metadata.insert("job_type".to_string(), "AUTOMATION_RUN".into());
+            metadata.insert("mode".to_string(), mode.as_str().into());
+            let event_type = match mode {
+                super::ManualRunMode::Debug => "AUTOMATION_DEBUG_RUN_QUEUED",
+                super::ManualRunMode::UserRequested => "AUTOMATION_RUN_NOW_QUEUED",
+            };
+            (event_type, AuditResult::Success, metadata)
+        }
+        DomainEvent::AutomationAbuseEscalated {
+            signal,
+            observed,
+            blocked_for_seconds,
+        } => {
+            metadata.insert("signal".to_string(), (*signal).into());
+            metadata.insert("observed".to_string(), (*observed).into());
+            metadata.insert(
+                "blocked_for_seconds".to_string(),
+                (*blocked_for_seconds).into(),
+            );
+            ("AUTOMATION_ABUSE_ESCALATED", AuditResult::Failure, metadata)
+        }
+        DomainEvent::SecurityAnomalyDetected {
+            signal,
+            subject,
+            observed,
+            window_seconds,
+            enforced_for_seconds,
+        } => {
+            metadata.insert("signal".to_string(), (*signal).into());
+            metadata.insert("subject".to_string(), (*subject).into());
+            metadata.insert("observed".to_string(), (*observed).into());
+            metadata.insert("window_seconds".to_string(), (*window_seconds).into());
+            if let Some(enforced_for_seconds) = enforced_for_seconds {
+                metadata.insert(
+                    "enforced_for_seconds".to_string(),
+                    (*enforced_for_seconds).into(),
+                );
+            }
+            ("SECURITY_ANOMALY_DETECTED", AuditResult::Failure, metadata)
+        }
+        DomainEvent::JobActionSkipped { details, .. } => {
+            ("JOB_ACTION_SKIPPED", AuditResult::Success, details.clone())
+        }
+        DomainEvent::JobActionGenerated { details, .. } => (
+            "JOB_ACTION_GENERATED",
+            AuditResult::Success,
+            details.clone(),
+        ),
+        DomainEvent::NotificationDeliveryAttempted {
+            outcome, details, ..
+        } => {
+            let result = match outcome {
+                NotificationDeliveryOutcome::Failed => AuditResult::Failure,
+                NotificationDeliveryOutcome::Delivered
+                | NotificationDeliveryOutcome::SuppressedBacklog => AuditResult::Success,
+            };
+            ("NOTIFICATION_DELIVERY_ATTEMPT", result, details.clone())
+        }
+        DomainEvent::AdminActionPerformed {
+            principal,
+            action,
+            details,
+        } => {
+            let mut metadata = details.clone();
+            metadata.insert("action".to_string(), (*action).into());
+            metadata.insert("admin_principal".to_string(), principal.clone().into());
+            ("ADMIN_ACTION", AuditResult::Success, metadata)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::audit_record;
+    use crate::automation_schedule::{AutomationScheduleType, AutomationTemplate};
+    use crate::events::{DomainEvent, ManualRunMode};
+    use crate::models::AuditMetadata;
+    use crate::repos::{AuditResult, NotificationDeliveryOutcome};
+
+    #[test]
+    fn automation_events_keep_their_audit_shape() {
+        let rule_id = Uuid::new_v4();
+        let (event_type, result, metadata) = audit_record(&DomainEvent::AutomationRuleCreated {
+            rule_id,
+            title: "Morning".to_string(),
+            schedule_type: AutomationScheduleType::Daily,
+            time_zone: "UTC".to_string(),
+            template: Some(AutomationTemplate::WeeklyReview),
+            local_time_minutes: 9 * 60 + 5,
+        });
+        assert_eq!(event_type, "AUTOMATION_RULE_CREATED");
+        assert!(matches!(result, AuditResult::Success));
+        assert_eq!(metadata["rule_id"], rule_id.to_string());
+        assert_eq!(metadata["schedule_type"], "DAILY");
+        assert_eq!(metadata["template"], "WEEKLY_REVIEW");
+        assert_eq!(metadata["local_time"], "09:05");
+
+        let (event_type, _, metadata) = audit_record(&DomainEvent::AutomationRunQueued {
+            rule_id,
+            job_id: Uuid::new_v4(),
+            mode: ManualRunMode::UserRequested,
+        });
+        assert_eq!(event_type, "AUTOMATION_RUN_NOW_QUEUED");
+        assert_eq!(metadata["mode"], "USER_MANUAL");
+        assert_eq!(metadata["job_type"], "AUTOMATION_RUN");
+    }
+
+    #[test]
+    fn failed_deliveries_audit_as_failures_with_their_details() {
+        let mut details = AuditMetadata::new();
+        details.insert("outcome".to_string(), "failed".into());
+        let (event_type, result, metadata) =
+            audit_record(&DomainEvent::NotificationDeliveryAttempted {
+                job_id: Uuid::new_v4(),
+                device_id: "device-1".to_string(),
+                outcome: NotificationDeliveryOutcome::Failed,
+                details: details.clone(),
+            });
+        assert_eq!(event_type, "NOTIFICATION_DELIVERY_ATTEMPT");
+        assert!(matches!(result, AuditResult::Failure));
+        assert_eq!(metadata, details);
+    }
+
+    #[test]
+    fn admin_actions_are_attributed_to_the_principal() {
+        let mut details = AuditMetadata::new();
+        details.insert("dead_letter_id".to_string(), "dl-1".into());
+        let (event_type, result, metadata) = audit_record(&DomainEvent::AdminActionPerformed {
+            principal: "service_token".to_string(),
+            action: "replay_dead_letter_job",
+            details,
+        });
+        assert_eq!(event_type, "ADMIN_ACTION");
+        assert!(matches!(result, AuditResult::Success));
+        assert_eq!(metadata["action"], "replay_dead_letter_job");
+        assert_eq!(metadata["admin_principal"], "service_token");
+        assert_eq!(metadata["dead_letter_id"], "dl-1");
+    }
+}
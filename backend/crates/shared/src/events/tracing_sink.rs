@@ -0,0 +1,45 @@
+use tracing::info;
+
+use super::{DomainEvent, EventEnvelope, EventSink, EventSinkFuture};
+
+/// Emits one structured log line per event under the `domain_event` target, which is what log
+/// based dashboards and alerts count.
+#[derive(Clone, Copy, Default)]
+pub struct TracingEventSink;
+
+impl EventSink for TracingEventSink {
+    fn name(&self) -> &'static str {
+        "tracing"
+    }
+
+    fn handle<'a>(&'a self, envelope: &'a EventEnvelope) -> EventSinkFuture<'a> {
+        Box::pin(async move {
+            info!(
+                target: "domain_event",
+                event = envelope.event.name(),
+                event_id = %envelope.id,
+                user_id = %envelope.user_id,
+                occurred_at = %envelope.occurred_at.to_rfc3339(),
+                subject = %subject(&envelope.event),
+                "domain event"
+            );
+            Ok(())
+        })
+    }
+}
+
+/// The entity the event is about, so events can be grouped without parsing details.
+fn subject(event: &DomainEvent) -> String {
+    match event {
+        DomainEvent::AutomationRuleCreated { rule_id, .. }
+        | DomainEvent::AutomationRuleUpdated { rule_id, .. }
+        | DomainEvent::AutomationRuleDeleted { rule_id }
+        | DomainEvent::AutomationRunQueued { rule_id, .. } => rule_id.to_string(),
+        DomainEvent::AutomationAbuseEscalated { signal, .. }
+        | DomainEvent::SecurityAnomalyDetected { signal, .. } => (*signal).to_string(),
+        DomainEvent::JobActionSkipped { job_id, .. }
+        | DomainEvent::JobActionGenerated { job_id, .. }
+        | DomainEvent::NotificationDeliveryAttempted { job_id, .. } => job_id.to_string(),
+        DomainEvent::AdminActionPerformed { action, .. } => (*action).to_string(),
+    }
+}
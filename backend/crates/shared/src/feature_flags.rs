@@ -0,0 +1,575 @@
+//! Runtime feature flags shared by the api-server and the enclave runtime.
+//!
+//! Each flag has a default rule from the environment. Operators can replace that rule at
+//! runtime through `/admin/v1/feature-flags`; overrides live in one Redis hash, so every
+//! instance picks up a flip within [`OVERRIDE_REFRESH_INTERVAL`] without a redeploy.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use redis::AsyncCommands;
+use redis::aio::{ConnectionManager, ConnectionManagerConfig};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use tracing::warn;
+use uuid::Uuid;
+
+const FEATURE_FLAG_OVERRIDES_KEY: &str = "alfred:feature_flags:v1:overrides";
+/// Evaluations reuse the last override snapshot for this long, so a flip reaches every
+/// instance within seconds without a Redis round trip per request.
+const OVERRIDE_REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+/// Flags are read on request paths; a slow Redis must fail fast and leave the defaults.
+const FEATURE_FLAG_RESPONSE_TIMEOUT: Duration = Duration::from_millis(100);
+const FEATURE_FLAG_CONNECTION_TIMEOUT: Duration = Duration::from_secs(2);
+const FEATURE_FLAG_CONNECTION_RETRIES: usize = 2;
+const FEATURE_FLAG_MAX_RETRY_DELAY_MS: u64 = 500;
+pub const MAX_FEATURE_FLAG_USER_IDS: usize = 1000;
+
+/// Every flag the services know about. Removing a variant strands its Redis override, which
+/// is skipped with a warning until an operator clears it.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum FeatureFlag {
+    // Enclave: answer greetings deterministically instead of running the planner and LLM.
+    SmallTalkFastPath,
+    // App: request streamed assistant responses. Reported on `GET /v1/feature-flags`.
+    AssistantStreaming,
+}
+
+impl FeatureFlag {
+    pub const ALL: [Self; 2] = [Self::SmallTalkFastPath, Self::AssistantStreaming];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::SmallTalkFastPath => "small_talk_fast_path",
+            Self::AssistantStreaming => "assistant_streaming",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|flag| flag.as_str() == value)
+    }
+
+    /// Whether signed-in users may read their own evaluation of the flag.
+    pub fn client_visible(self) -> bool {
+        matches!(self, Self::AssistantStreaming)
+    }
+
+    fn env_key(self) -> String {
+        format!("FEATURE_FLAG_{}", self.as_str().to_ascii_uppercase())
+    }
+
+    fn built_in_rule(self) -> FeatureFlagRule {
+        match self {
+            Self::SmallTalkFastPath => FeatureFlagRule::on(),
+            Self::AssistantStreaming => FeatureFlagRule::off(),
+        }
+    }
+}
+
+/// Who a flag is on for: nobody while `enabled` is false, otherwise every listed user plus
+/// a stable `rollout_percent` cohort of everyone else.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct FeatureFlagRule {
+    pub enabled: bool,
+    #[serde(default = "full_rollout_percent")]
+    pub rollout_percent: u8,
+    #[serde(default)]
+    pub user_ids: Vec<Uuid>,
+}
+
+fn full_rollout_percent() -> u8 {
+    100
+}
+
+impl FeatureFlagRule {
+    pub fn on() -> Self {
+        Self {
+            enabled: true,
+            rollout_percent: 100,
+            user_ids: Vec::new(),
+        }
+    }
+
+    pub fn off() -> Self {
+        Self {
+            enabled: false,
+            rollout_percent: 0,
+            user_ids: Vec::new(),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.rollout_percent > 100 {
+            return Err("rollout_percent must be between 0 and 100".to_string());
+        }
+        if self.user_ids.len() > MAX_FEATURE_FLAG_USER_IDS {
+            return Err(format!(
+                "user_ids must list at most {MAX_FEATURE_FLAG_USER_IDS} users"
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn evaluate(&self, flag: FeatureFlag, user_id: Uuid) -> bool {
+        self.enabled
+            && (self.user_ids.contains(&user_id)
+                || rollout_bucket(flag, user_id) < self.rollout_percent)
+    }
+}
+
+/// Buckets are salted with the flag name, so each flag samples a different cohort while a
+/// given user stays in or out of one flag's rollout as the percentage grows.
+fn rollout_bucket(flag: FeatureFlag, user_id: Uuid) -> u8 {
+    let mut hasher = Sha256::new();
+    hasher.update(flag.as_str().as_bytes());
+    hasher.update(b":");
+    hasher.update(user_id.as_bytes());
+    let digest = hasher.finalize();
+    (u16::from_be_bytes([digest[0], digest[1]]) % 100) as u8
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum FeatureFlagConfigError {
+    #[error("{key} must be 'on', 'off', or a rollout percentage between 0 and 100, got '{value}'")]
+    InvalidDefault { key: String, value: String },
+}
+
+/// Rules used when no override is set. `FEATURE_FLAG_<NAME>` accepts `on`, `off`, or a
+/// rollout percentage, e.g. `FEATURE_FLAG_ASSISTANT_STREAMING=10`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeatureFlagDefaults {
+    rules: BTreeMap<FeatureFlag, FeatureFlagRule>,
+}
+
+impl Default for FeatureFlagDefaults {
+    fn default() -> Self {
+        Self {
+            rules: FeatureFlag::ALL
+                .into_iter()
+                .map(|flag| (flag, flag.built_in_rule()))
+                .collect(),
+        }
+    }
+}
+
+impl FeatureFlagDefaults {
+    pub fn from_env() -> Result<Self, FeatureFlagConfigError> {
+        Self::from_lookup(|key| env::var(key).ok())
+    }
+
+    pub fn from_lookup(
+        lookup: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, FeatureFlagConfigError> {
+        let mut defaults = Self::default();
+        for flag in FeatureFlag::ALL {
+            let key = flag.env_key();
+            let Some(value) = lookup(&key) else {
+                continue;
+            };
+            let rule = parse_default_rule(value.trim()).ok_or_else(|| {
+                FeatureFlagConfigError::InvalidDefault {
+                    key: key.clone(),
+                    value: value.clone(),
+                }
+            })?;
+            defaults.rules.insert(flag, rule);
+        }
+        Ok(defaults)
+    }
+
+    pub fn rule(&self, flag: FeatureFlag) -> FeatureFlagRule {
+        self.rules
+            .get(&flag)
+            .cloned()
+            .unwrap_or_else(|| flag.built_in_rule())
+    }
+}
+
+fn parse_default_rule(value: &str) -> Option<FeatureFlagRule> {
+    match value.to_ascii_lowercase().as_str() {
+        "" => None,
+        "on" | "true" => Some(FeatureFlagRule::on()),
+        "off" | "false" => Some(FeatureFlagRule::off()),
+        percent => percent
+            .parse::<u8>()
+            .ok()
+            .filter(|percent| *percent <= 100)
+            .map(|rollout_percent| FeatureFlagRule {
+                enabled: true,
+                rollout_percent,
+                user_ids: Vec::new(),
+            }),
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum FeatureFlagStoreError {
+    #[error("feature flag overrides require redis, which is not attached")]
+    NotConfigured,
+    #[error("feature flag store request failed: {0}")]
+    Redis(String),
+}
+
+/// One flag as operators see it: the environment default and the override, if any.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeatureFlagState {
+    pub flag: FeatureFlag,
+    pub default_rule: FeatureFlagRule,
+    pub override_rule: Option<FeatureFlagRule>,
+}
+
+impl FeatureFlagState {
+    pub fn effective_rule(&self) -> &FeatureFlagRule {
+        self.override_rule.as_ref().unwrap_or(&self.default_rule)
+    }
+}
+
+struct OverrideSnapshot {
+    fetched_at: Instant,
+    overrides: BTreeMap<FeatureFlag, FeatureFlagRule>,
+}
+
+/// Flag evaluation handle. Without Redis, or while Redis is unreachable, every flag follows
+/// its default rule.
+#[derive(Clone)]
+pub struct FeatureFlags {
+    defaults: Arc<FeatureFlagDefaults>,
+    connection: Option<ConnectionManager>,
+    snapshot: Arc<Mutex<Option<OverrideSnapshot>>>,
+}
+
+impl FeatureFlags {
+    pub fn new(defaults: FeatureFlagDefaults) -> Self {
+        Self {
+            defaults: Arc::new(defaults),
+            connection: None,
+            snapshot: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Reads and writes overrides in Redis. Redis being unreachable at startup is logged and
+    /// the defaults stay in force.
+    pub async fn with_redis_from_config(mut self, redis_url: &str) -> Self {
+        match connect(redis_url).await {
+            Ok(connection) => {
+                self.connection = Some(connection);
+                self
+            }
+            Err(err) => {
+                warn!("feature flag overrides disabled, redis unavailable: {err}");
+                self
+            }
+        }
+    }
+
+    pub async fn is_enabled(&self, flag: FeatureFlag, user_id: Uuid) -> bool {
+        self.rule(flag).await.evaluate(flag, user_id)
+    }
+
+    /// Effective rule from the cached override snapshot, refreshed at most every
+    /// [`OVERRIDE_REFRESH_INTERVAL`].
+    pub async fn rule(&self, flag: FeatureFlag) -> FeatureFlagRule {
+        if self.connection.is_some() {
+            if let Some(rule) = self.cached_override(flag) {
+                return rule.unwrap_or_else(|| self.defaults.rule(flag));
+            }
+
+            match self.read_overrides().await {
+                Ok(overrides) => {
+                    let rule = overrides.get(&flag).cloned();
+                    self.store_snapshot(overrides);
+                    if let Some(rule) = rule {
+                        return rule;
+                    }
+                }
+                Err(err) => {
+                    warn!(
+                        flag = flag.as_str(),
+                        "feature flag override read failed: {err}"
+                    );
+                    // Back off until the next refresh instead of retrying on every request.
+                    self.store_snapshot(BTreeMap::new());
+                }
+            }
+        }
+
+        self.defaults.rule(flag)
+    }
+
+    /// Current state of every flag, read straight from Redis rather than the snapshot.
+    pub async fn states(&self) -> Result<Vec<FeatureFlagState>, FeatureFlagStoreError> {
+        let overrides = if self.connection.is_some() {
+            self.read_overrides().await?
+        } else {
+            BTreeMap::new()
+        };
+
+        Ok(FeatureFlag::ALL
+            .into_iter()
+            .map(|flag| FeatureFlagState {
+                flag,
+                default_rule: self.defaults.rule(flag),
+                override_rule: overrides.get(&flag).cloned(),
+            })
+            .collect())
+    }
+
+    pub async fn set_override(
+        &self,
+        flag: FeatureFlag,
+        rule: &FeatureFlagRule,
+    ) -> Result<(), FeatureFlagStoreError> {
+        let mut connection = self.connection()?;
+        let value = serde_json::to_string(rule)
+            .map_err(|err| FeatureFlagStoreError::Redis(err.to_string()))?;
+        connection
+            .hset::<_, _, _, ()>(FEATURE_FLAG_OVERRIDES_KEY, flag.as_str(), value)
+            .await
+            .map_err(|err| FeatureFlagStoreError::Redis(err.to_string()))?;
+        self.invalidate_snapshot();
+        Ok(())
+    }
+
+    pub async fn clear_override(&self, flag: FeatureFlag) -> Result<(), FeatureFlagStoreError> {
+        let mut connection = self.connection()?;
+        connection
+            .hdel::<_, _, ()>(FEATURE_FLAG_OVERRIDES_KEY, flag.as_str())
+            .await
+            .map_err(|err| FeatureFlagStoreError::Redis(err.to_string()))?;
+        self.invalidate_snapshot();
+        Ok(())
+    }
+
+    fn connection(&self) -> Result<ConnectionManager, FeatureFlagStoreError> {
+        self.connection
+            .clone()
+            .ok_or(FeatureFlagStoreError::NotConfigured)
+    }
+
+    async fn read_overrides(
+        &self,
+    ) -> Result<BTreeMap<FeatureFlag, FeatureFlagRule>, FeatureFlagStoreError> {
+        let mut connection = self.connection()?;
+        let raw = connection
+            .hgetall::<_, BTreeMap<String, String>>(FEATURE_FLAG_OVERRIDES_KEY)
+            .await
+            .map_err(|err| FeatureFlagStoreError::Redis(err.to_string()))?;
+
+        Ok(raw
+            .into_iter()
+            .filter_map(|(name, value)| {
+                let Some(flag) = FeatureFlag::parse(&name) else {
+                    warn!(flag = name, "ignoring override for unknown feature flag");
+                    return None;
+                };
+                match serde_json::from_str::<FeatureFlagRule>(&value) {
+                    Ok(rule) if rule.validate().is_ok() => Some((flag, rule)),
+                    _ => {
+                        warn!(flag = name, "ignoring unreadable feature flag override");
+                        None
+                    }
+                }
+            })
+            .collect())
+    }
+
+    /// `Some(None)` is a fresh snapshot without an override for `flag`; `None` means the
+    /// snapshot needs a refresh.
+    fn cached_override(&self, flag: FeatureFlag) -> Option<Option<FeatureFlagRule>> {
+        let snapshot = self.snapshot_guard();
+        snapshot
+            .as_ref()
+            .filter(|snapshot| snapshot.fetched_at.elapsed() < OVERRIDE_REFRESH_INTERVAL)
+            .map(|snapshot| snapshot.overrides.get(&flag).cloned())
+    }
+
+    fn store_snapshot(&self, overrides: BTreeMap<FeatureFlag, FeatureFlagRule>) {
+        *self.snapshot_guard() = Some(OverrideSnapshot {
+            fetched_at: Instant::now(),
+            overrides,
+        });
+    }
+
+    fn invalidate_snapshot(&self) {
+        *self.snapshot_guard() = None;
+    }
+
+    fn snapshot_guard(&self) -> std::sync::MutexGuard<'_, Option<OverrideSnapshot>> {
+        self.snapshot
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+async fn connect(redis_url: &str) -> Result<ConnectionManager, String> {
+    let client = redis::Client::open(redis_url).map_err(|err| err.to_string())?;
+    let connection = ConnectionManager::new_with_config(
+        client,
+        ConnectionManagerConfig::new()
+            .set_response_timeout(FEATURE_FLAG_RESPONSE_TIMEOUT)
+            .set_connection_timeout(FEATURE_FLAG_CONNECTION_TIMEOUT)
+            .set_number_of_retries(FEATURE_FLAG_CONNECTION_RETRIES)
+            .set_max_delay(FEATURE_FLAG_MAX_RETRY_DELAY_MS),
+    )
+    .await
+    .map_err(|err| err.to_string())?;
+
+    let mut health_connection = connection.clone();
+    redis::cmd("PING")
+        .query_async::<String>(&mut health_connection)
+        .await
+        .map_err(|err| format!("failed to connect to redis: {err}"))?;
+
+    Ok(connection)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use uuid::Uuid;
+
+    use super::{
+        FeatureFlag, FeatureFlagConfigError, FeatureFlagDefaults, FeatureFlagRule, FeatureFlags,
+        MAX_FEATURE_FLAG_USER_IDS,
+    };
+
+    fn defaults(vars: &[(&str, &str)]) -> Result<FeatureFlagDefaults, FeatureFlagConfigError> {
+        let vars = vars
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect::<HashMap<_, _>>();
+        FeatureFlagDefaults::from_lookup(|key| vars.get(key).cloned())
+    }
+
+    #[test]
+    fn defaults_come_from_built_ins_and_env() {
+        let built_in = defaults(&[]).expect("built-in defaults are valid");
+        assert_eq!(
+            built_in.rule(FeatureFlag::SmallTalkFastPath),
+            FeatureFlagRule::on()
+        );
+        assert_eq!(
+            built_in.rule(FeatureFlag::AssistantStreaming),
+            FeatureFlagRule::off()
+        );
+
+        let configured = defaults(&[
+            ("FEATURE_FLAG_SMALL_TALK_FAST_PATH", "off"),
+            ("FEATURE_FLAG_ASSISTANT_STREAMING", " 25 "),
+        ])
+        .expect("configured defaults are valid");
+        assert_eq!(
+            configured.rule(FeatureFlag::SmallTalkFastPath),
+            FeatureFlagRule::off()
+        );
+        let streaming = configured.rule(FeatureFlag::AssistantStreaming);
+        assert!(streaming.enabled);
+        assert_eq!(streaming.rollout_percent, 25);
+
+        for value in ["101", "maybe", ""] {
+            assert_eq!(
+                defaults(&[("FEATURE_FLAG_ASSISTANT_STREAMING", value)]),
+                Err(FeatureFlagConfigError::InvalidDefault {
+                    key: "FEATURE_FLAG_ASSISTANT_STREAMING".to_string(),
+                    value: value.to_string(),
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn rules_gate_on_enabled_user_list_and_stable_rollout_cohort() {
+        let flag = FeatureFlag::AssistantStreaming;
+        let user_ids = (0..1000).map(|_| Uuid::new_v4()).collect::<Vec<_>>();
+
+        assert!(
+            user_ids
+                .iter()
+                .all(|id| FeatureFlagRule::on().evaluate(flag, *id))
+        );
+        assert!(
+            !user_ids
+                .iter()
+                .any(|id| FeatureFlagRule::off().evaluate(flag, *id))
+        );
+
+        let listed = FeatureFlagRule {
+            enabled: true,
+            rollout_percent: 0,
+            user_ids: vec![user_ids[0]],
+        };
+        assert!(listed.evaluate(flag, user_ids[0]));
+        assert!(!listed.evaluate(flag, user_ids[1]));
+        assert!(
+            !FeatureFlagRule {
+                enabled: false,
+                ..listed
+            }
+            .evaluate(flag, user_ids[0])
+        );
+
+        let rollout = |rollout_percent| FeatureFlagRule {
+            enabled: true,
+            rollout_percent,
+            user_ids: Vec::new(),
+        };
+        let in_ten = user_ids
+            .iter()
+            .filter(|id| rollout(10).evaluate(flag, **id))
+            .collect::<Vec<_>>();
+        assert!((50..=150).contains(&in_ten.len()), "got {}", in_ten.len());
+        assert!(in_ten.iter().all(|id| rollout(50).evaluate(flag, **id)));
+    }
+
+    #[test]
+    fn rules_are_validated() {
+        assert!(FeatureFlagRule::on().validate().is_ok());
+        assert!(
+            FeatureFlagRule {
+                enabled: true,
+                rollout_percent: 101,
+                user_ids: Vec::new(),
+            }
+            .validate()
+            .is_err()
+        );
+        assert!(
+            FeatureFlagRule {
+                enabled: true,
+                rollout_percent: 0,
+                user_ids: vec![Uuid::nil(); MAX_FEATURE_FLAG_USER_IDS + 1],
+            }
+            .validate()
+            .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn without_redis_flags_follow_defaults_and_reject_overrides() {
+        let flags = FeatureFlags::new(FeatureFlagDefaults::default());
+
+        assert!(
+            flags
+                .is_enabled(FeatureFlag::SmallTalkFastPath, Uuid::new_v4())
+                .await
+        );
+        assert!(
+            flags
+                .set_override(FeatureFlag::SmallTalkFastPath, &FeatureFlagRule::off())
+                .await
+                .is_err()
+        );
+
+        let states = flags.states().await.expect("defaults are always readable");
+        assert_eq!(states.len(), FeatureFlag::ALL.len());
+        assert!(states.iter().all(|state| state.override_rule.is_none()));
+    }
+}
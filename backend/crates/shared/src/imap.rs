@@ -0,0 +1,86 @@
+use crate::network_policy::is_public_host;
+
+pub const IMAP_HOST_MAX_CHARS: usize = 253;
+pub const IMAP_USERNAME_MAX_CHARS: usize = 256;
+pub const IMAP_DEFAULT_PORT: u16 = 993;
+
+/// Validates an IMAP server host name supplied by the user. Only bare public host names are
+/// accepted, since the enclave connects to them with the user's credentials.
+pub fn normalize_imap_host(value: &str) -> Result<String, &'static str> {
+    let host = value.trim().trim_end_matches('.').to_ascii_lowercase();
+    if host.is_empty() || host.chars().count() > IMAP_HOST_MAX_CHARS {
+        return Err("host must be between 1 and 253 characters");
+    }
+    if !host
+        .chars()
+        .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '.' | '-' | ':' | '[' | ']'))
+    {
+        return Err("host must be a bare host name without scheme, port, or path");
+    }
+    if host.contains(':') && !(host.starts_with('[') && host.ends_with(']')) {
+        return Err("host must be a bare host name without scheme, port, or path");
+    }
+    if !is_public_host(&host) {
+        return Err("host must point at a public server");
+    }
+
+    Ok(host)
+}
+
+/// IMAP connections always use implicit TLS, so plaintext port 143 is rejected.
+pub fn normalize_imap_port(value: Option<u16>) -> Result<u16, &'static str> {
+    match value.unwrap_or(IMAP_DEFAULT_PORT) {
+        0 | 143 => Err("port must be an implicit-TLS IMAP port such as 993"),
+        port => Ok(port),
+    }
+}
+
+pub fn normalize_imap_username(value: &str) -> Result<String, &'static str> {
+    let trimmed = value.trim();
+    if trimmed.is_empty() || trimmed.chars().count() > IMAP_USERNAME_MAX_CHARS {
+        return Err("username must be between 1 and 256 characters");
+    }
+    if trimmed.chars().any(char::is_control) {
+        return Err("username must not contain control characters");
+    }
+
+    Ok(trimmed.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize_imap_host, normalize_imap_port, normalize_imap_username};
+
+    #[test]
+    fn host_must_be_a_bare_public_name() {
+        assert_eq!(
+            normalize_imap_host(" IMAP.Fastmail.com. "),
+            Ok("imap.fastmail.com".to_string())
+        );
+        assert!(normalize_imap_host("imaps://imap.example.com").is_err());
+        assert!(normalize_imap_host("imap.example.com:993").is_err());
+        assert!(normalize_imap_host("imap.example.com/inbox").is_err());
+        assert!(normalize_imap_host("localhost").is_err());
+        assert!(normalize_imap_host("192.168.1.10").is_err());
+        assert!(normalize_imap_host("[::1]").is_err());
+        assert!(normalize_imap_host("").is_err());
+    }
+
+    #[test]
+    fn port_defaults_to_implicit_tls() {
+        assert_eq!(normalize_imap_port(None), Ok(993));
+        assert_eq!(normalize_imap_port(Some(1993)), Ok(1993));
+        assert!(normalize_imap_port(Some(143)).is_err());
+        assert!(normalize_imap_port(Some(0)).is_err());
+    }
+
+    #[test]
+    fn username_rejects_control_characters() {
+        assert_eq!(
+            normalize_imap_username(" me@example.com "),
+            Ok("me@example.com".to_string())
+        );
+        assert!(normalize_imap_username("me\r\nA1 LOGOUT").is_err());
+        assert!(normalize_imap_username(" ").is_err());
+    }
+}
@@ -2,14 +2,42 @@ pub mod assistant_crypto;
 pub mod assistant_memory;
 pub mod assistant_planner;
 pub mod assistant_semantic_plan;
+pub mod audit_redaction;
+pub mod audit_retention;
+pub mod audit_signing;
+pub mod automation_draft;
 pub mod automation_schedule;
+pub mod aws;
+pub mod brief_profile;
+pub mod caldav;
 pub mod config;
+mod config_admin;
+mod config_api_versioning;
+mod config_apns;
+mod config_cors;
+mod config_data_keys;
 mod config_enclave_runtime;
 mod config_env;
+mod config_request_signing;
+mod config_security_headers;
+pub mod connector_health;
+pub mod departure_alert;
 pub mod enclave;
 pub mod enclave_runtime;
+pub mod events;
+pub mod feature_flags;
+pub mod imap;
 pub mod llm;
+pub mod log_redaction;
 pub mod models;
+mod network_policy;
+pub mod outbound_rate_limit;
+pub mod pagination;
+pub mod quota;
+pub mod replay_guard;
 pub mod repos;
+pub mod request_signing;
+pub mod secrets;
 pub mod security;
 pub mod timezone;
+pub mod webhooks;
@@ -0,0 +1,507 @@
+use std::env;
+use std::time::Duration;
+
+use reqwest::StatusCode;
+use serde::Deserialize;
+use serde_json::{Number, Value, json};
+use thiserror::Error;
+use tokio::time::sleep;
+
+use super::gateway::{
+    LlmGateway, LlmGatewayError, LlmGatewayFuture, LlmGatewayRequest, LlmGatewayResponse,
+    LlmProviderRole, LlmTokenUsage, LlmToolCall, LlmToolDefinition, LlmWarmUpFuture,
+};
+use super::openrouter::{is_retryable_status, parse_token_count};
+use crate::outbound_rate_limit::{OutboundCallLimiter, OutboundProvider};
+
+const DEFAULT_MESSAGES_URL: &str = "https://api.anthropic.com/v1/messages";
+const DEFAULT_API_VERSION: &str = "2023-06-01";
+const DEFAULT_MODEL: &str = "claude-3-5-haiku-latest";
+const DEFAULT_TIMEOUT_MS: u64 = 15_000;
+const DEFAULT_MAX_RETRIES: u32 = 2;
+const DEFAULT_RETRY_BASE_BACKOFF_MS: u64 = 250;
+const DEFAULT_MAX_OUTPUT_TOKENS: u32 = 600;
+const DEFAULT_ALLOW_INSECURE_HTTP: bool = false;
+/// The Messages API has no JSON response format, so the assistant turn is prefilled with the
+/// opening brace and the model continues the object from there.
+const JSON_PREFILL: &str = "{";
+const WARM_UP_PROMPT: &str = "ping";
+const WARM_UP_MAX_OUTPUT_TOKENS: u32 = 1;
+
+#[derive(Debug, Clone)]
+pub struct AnthropicGatewayConfig {
+    pub messages_url: String,
+    pub api_key: String,
+    pub api_version: String,
+    pub role: LlmProviderRole,
+    pub model: String,
+    pub temperature: f32,
+    pub timeout_ms: u64,
+    pub max_retries: u32,
+    pub retry_base_backoff_ms: u64,
+    pub max_output_tokens: u32,
+    pub allow_insecure_http: bool,
+}
+
+impl AnthropicGatewayConfig {
+    /// Returns `None` unless `ANTHROPIC_PROVIDER_ROLE` is set; the gateway is opt-in, so an
+    /// OpenRouter-only deployment needs no Anthropic credentials.
+    pub fn from_env() -> Result<Option<Self>, AnthropicConfigError> {
+        let Some(role) = optional_trimmed_env("ANTHROPIC_PROVIDER_ROLE") else {
+            return Ok(None);
+        };
+        let role = LlmProviderRole::parse(&role).ok_or_else(|| {
+            AnthropicConfigError::InvalidConfiguration(format!(
+                "ANTHROPIC_PROVIDER_ROLE must be primary or fallback, got {role}"
+            ))
+        })?;
+        let api_key = require_non_empty_env("ANTHROPIC_API_KEY")?;
+        let messages_url = optional_trimmed_env("ANTHROPIC_MESSAGES_URL")
+            .unwrap_or_else(|| DEFAULT_MESSAGES_URL.to_string());
+        let allow_insecure_http =
+            parse_bool_env("ANTHROPIC_ALLOW_INSECURE_HTTP", DEFAULT_ALLOW_INSECURE_HTTP)?;
+        let uses_https = messages_url.starts_with("https://");
+        let uses_insecure_http = allow_insecure_http && messages_url.starts_with("http://");
+        if !(uses_https || uses_insecure_http) {
+            return Err(AnthropicConfigError::InvalidConfiguration(
+                "ANTHROPIC_MESSAGES_URL must use https:// (or set ANTHROPIC_ALLOW_INSECURE_HTTP=true for local development)"
+                    .to_string(),
+            ));
+        }
+
+        Ok(Some(Self {
+            messages_url,
+            api_key,
+            api_version: optional_trimmed_env("ANTHROPIC_API_VERSION")
+                .unwrap_or_else(|| DEFAULT_API_VERSION.to_string()),
+            role,
+            model: optional_trimmed_env("ANTHROPIC_MODEL")
+                .unwrap_or_else(|| DEFAULT_MODEL.to_string()),
+            temperature: 0.0,
+            timeout_ms: parse_u64_env("ANTHROPIC_TIMEOUT_MS", DEFAULT_TIMEOUT_MS)?,
+            max_retries: parse_u32_env("ANTHROPIC_MAX_RETRIES", DEFAULT_MAX_RETRIES)?,
+            retry_base_backoff_ms: parse_u64_env(
+                "ANTHROPIC_RETRY_BASE_BACKOFF_MS",
+                DEFAULT_RETRY_BASE_BACKOFF_MS,
+            )?,
+            max_output_tokens: parse_u32_env(
+                "ANTHROPIC_MAX_OUTPUT_TOKENS",
+                DEFAULT_MAX_OUTPUT_TOKENS,
+            )?,
+            allow_insecure_http,
+        }))
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum AnthropicConfigError {
+    #[error("missing required env var {0}")]
+    MissingVar(String),
+    #[error("invalid integer in env var {key}: {value}")]
+    ParseInt { key: String, value: String },
+    #[error("invalid configuration: {0}")]
+    InvalidConfiguration(String),
+    #[error("failed to build Anthropic http client: {0}")]
+    HttpClient(String),
+}
+
+/// Calls the Anthropic Messages API directly. Routing and fallback between providers live in
+/// the reliability layer, so this gateway only retries its one configured model.
+#[derive(Clone)]
+pub struct AnthropicGateway {
+    client: reqwest::Client,
+    config: AnthropicGatewayConfig,
+    outbound_limiter: OutboundCallLimiter,
+}
+
+impl AnthropicGateway {
+    pub fn new(config: AnthropicGatewayConfig) -> Result<Self, AnthropicConfigError> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(config.timeout_ms))
+            .build()
+            .map_err(|err| AnthropicConfigError::HttpClient(err.to_string()))?;
+
+        Ok(Self {
+            client,
+            config,
+            outbound_limiter: OutboundCallLimiter::unlimited(),
+        })
+    }
+
+    /// Routes messages through the enclave-wide outbound limiter. Warm-up probes bypass it.
+    pub fn with_outbound_limiter(mut self, outbound_limiter: OutboundCallLimiter) -> Self {
+        self.outbound_limiter = outbound_limiter;
+        self
+    }
+
+    pub fn role(&self) -> LlmProviderRole {
+        self.config.role
+    }
+
+    async fn send_once(
+        &self,
+        request: &LlmGatewayRequest,
+    ) -> Result<LlmGatewayResponse, SendAttemptError> {
+        let user_prompt = json!({
+            "instruction": request.context_prompt,
+            "contract_version": request.contract_version,
+            "output_schema": request.output_schema,
+            "context_payload": request.context_payload,
+        })
+        .to_string();
+
+        self.outbound_limiter
+            .acquire(OutboundProvider::Anthropic)
+            .await
+            .map_err(|err| {
+                SendAttemptError::non_retryable(LlmGatewayError::RateLimited(format!(
+                    "outbound_rate_limited retry_after_ms={}",
+                    err.retry_after_ms()
+                )))
+            })?;
+
+        let mut request_body = json!({
+            "model": self.config.model,
+            "system": request.system_prompt,
+            "messages": [
+                { "role": "user", "content": user_prompt },
+                { "role": "assistant", "content": JSON_PREFILL }
+            ],
+            "temperature": self.config.temperature,
+            "max_tokens": self.config.max_output_tokens
+        });
+        // A prefilled brace would force a text answer, so tool requests let the model choose.
+        if !request.tools.is_empty() {
+            request_body["messages"] = json!([{ "role": "user", "content": user_prompt }]);
+            request_body["tools"] = anthropic_tools_payload(&request.tools);
+        }
+        let response = self
+            .messages_request()
+            .json(&request_body)
+            .send()
+            .await
+            .map_err(send_error)?;
+
+        let status = response.status();
+        let header_request_id = header_request_id(response.headers());
+        let body = response.text().await.map_err(|_| {
+            SendAttemptError::non_retryable(LlmGatewayError::InvalidProviderPayload(
+                "response_body_read_failed".to_string(),
+            ))
+        })?;
+
+        if !status.is_success() {
+            return Err(provider_status_error(status, &body));
+        }
+
+        let parsed: AnthropicMessageResponse = serde_json::from_str(&body).map_err(|_| {
+            SendAttemptError::non_retryable(LlmGatewayError::InvalidProviderPayload(
+                "response_json_parse_failed".to_string(),
+            ))
+        })?;
+
+        let text = parsed
+            .content
+            .iter()
+            .filter(|block| block.block_type == "text")
+            .filter_map(|block| block.text.as_deref())
+            .collect::<String>();
+        let tool_calls = parsed
+            .content
+            .into_iter()
+            .filter(|block| block.block_type == "tool_use")
+            .map(|block| {
+                block
+                    .name
+                    .map(|name| LlmToolCall {
+                        id: block.id,
+                        name,
+                        arguments: block.input.unwrap_or_else(|| json!({})),
+                    })
+                    .ok_or_else(|| {
+                        SendAttemptError::non_retryable(LlmGatewayError::InvalidProviderPayload(
+                            "tool_use_missing_name".to_string(),
+                        ))
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        // Prose alongside a tool call is commentary, not a contract answer.
+        let output = match parse_prefilled_json(&text) {
+            Some(output) => output,
+            None if !tool_calls.is_empty() => Value::Null,
+            None if text.is_empty() => {
+                return Err(SendAttemptError::non_retryable(
+                    LlmGatewayError::InvalidProviderPayload("missing_text_content".to_string()),
+                ));
+            }
+            None => {
+                return Err(SendAttemptError::non_retryable(
+                    LlmGatewayError::InvalidProviderPayload("content_not_json".to_string()),
+                ));
+            }
+        };
+
+        Ok(LlmGatewayResponse {
+            model: parsed.model.unwrap_or_else(|| self.config.model.clone()),
+            provider_request_id: header_request_id.or(parsed.id),
+            output,
+            tool_calls,
+            usage: parsed.usage.map(AnthropicUsage::into_token_usage),
+        })
+    }
+
+    /// Sends the smallest message the provider accepts. Only the HTTP status matters, so the
+    /// single output token is never parsed.
+    async fn warm_up_once(&self) -> Result<(), SendAttemptError> {
+        let request_body = json!({
+            "model": self.config.model,
+            "messages": [
+                { "role": "user", "content": WARM_UP_PROMPT }
+            ],
+            "max_tokens": WARM_UP_MAX_OUTPUT_TOKENS
+        });
+        let response = self
+            .messages_request()
+            .json(&request_body)
+            .send()
+            .await
+            .map_err(send_error)?;
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+
+        let body = response.text().await.unwrap_or_default();
+        Err(provider_status_error(status, &body))
+    }
+
+    fn messages_request(&self) -> reqwest::RequestBuilder {
+        self.client
+            .post(&self.config.messages_url)
+            .header("x-api-key", &self.config.api_key)
+            .header("anthropic-version", &self.config.api_version)
+    }
+}
+
+impl LlmGateway for AnthropicGateway {
+    fn generate<'a>(&'a self, request: LlmGatewayRequest) -> LlmGatewayFuture<'a> {
+        Box::pin(async move {
+            let mut attempt = 0_u32;
+
+            loop {
+                match self.send_once(&request).await {
+                    Ok(response) => return Ok(response),
+                    Err(err) => {
+                        if err.retryable && attempt < self.config.max_retries {
+                            let backoff_multiplier = 2_u64.saturating_pow(attempt);
+                            let backoff_ms = self
+                                .config
+                                .retry_base_backoff_ms
+                                .saturating_mul(backoff_multiplier);
+                            sleep(Duration::from_millis(backoff_ms)).await;
+                            attempt = attempt.saturating_add(1);
+                            continue;
+                        }
+
+                        return Err(err.error);
+                    }
+                }
+            }
+        })
+    }
+
+    fn warm_up<'a>(&'a self) -> LlmWarmUpFuture<'a> {
+        Box::pin(async move { self.warm_up_once().await.map_err(|err| err.error) })
+    }
+}
+
+#[derive(Debug)]
+struct SendAttemptError {
+    error: LlmGatewayError,
+    retryable: bool,
+}
+
+impl SendAttemptError {
+    fn retryable(error: LlmGatewayError) -> Self {
+        Self {
+            error,
+            retryable: true,
+        }
+    }
+
+    fn non_retryable(error: LlmGatewayError) -> Self {
+        Self {
+            error,
+            retryable: false,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct AnthropicMessageResponse {
+    id: Option<String>,
+    model: Option<String>,
+    #[serde(default)]
+    content: Vec<AnthropicContentBlock>,
+    usage: Option<AnthropicUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AnthropicContentBlock {
+    #[serde(rename = "type")]
+    block_type: String,
+    text: Option<String>,
+    /// Set on `tool_use` blocks only.
+    id: Option<String>,
+    name: Option<String>,
+    input: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AnthropicUsage {
+    input_tokens: Option<Number>,
+    output_tokens: Option<Number>,
+    cache_creation_input_tokens: Option<Number>,
+    cache_read_input_tokens: Option<Number>,
+}
+
+impl AnthropicUsage {
+    /// Cached prompt tokens are reported apart from `input_tokens` but are still prompt input.
+    fn into_token_usage(self) -> LlmTokenUsage {
+        let prompt_tokens = parse_token_count(self.input_tokens)
+            .saturating_add(parse_token_count(self.cache_creation_input_tokens))
+            .saturating_add(parse_token_count(self.cache_read_input_tokens));
+        let completion_tokens = parse_token_count(self.output_tokens);
+        LlmTokenUsage {
+            prompt_tokens,
+            completion_tokens,
+            total_tokens: prompt_tokens.saturating_add(completion_tokens),
+        }
+    }
+}
+
+fn anthropic_tools_payload(tools: &[LlmToolDefinition]) -> Value {
+    Value::Array(
+        tools
+            .iter()
+            .map(|tool| {
+                json!({
+                    "name": tool.name,
+                    "description": tool.description,
+                    "input_schema": tool.parameters
+                })
+            })
+            .collect(),
+    )
+}
+
+/// The reply continues the prefilled brace, but a model may still restate it, so the raw text
+/// is accepted as a fallback.
+fn parse_prefilled_json(text: &str) -> Option<Value> {
+    let candidates = [format!("{JSON_PREFILL}{text}"), text.to_string()];
+    candidates
+        .iter()
+        .filter_map(|candidate| serde_json::from_str::<Value>(candidate).ok())
+        .find(|value| value.is_object())
+}
+
+fn require_non_empty_env(key: &str) -> Result<String, AnthropicConfigError> {
+    optional_trimmed_env(key).ok_or_else(|| AnthropicConfigError::MissingVar(key.to_string()))
+}
+
+fn parse_u64_env(key: &str, default: u64) -> Result<u64, AnthropicConfigError> {
+    match optional_trimmed_env(key) {
+        Some(value) => value
+            .parse::<u64>()
+            .map_err(|_| AnthropicConfigError::ParseInt {
+                key: key.to_string(),
+                value,
+            }),
+        None => Ok(default),
+    }
+}
+
+fn parse_u32_env(key: &str, default: u32) -> Result<u32, AnthropicConfigError> {
+    match optional_trimmed_env(key) {
+        Some(value) => value
+            .parse::<u32>()
+            .map_err(|_| AnthropicConfigError::ParseInt {
+                key: key.to_string(),
+                value,
+            }),
+        None => Ok(default),
+    }
+}
+
+fn parse_bool_env(key: &str, default: bool) -> Result<bool, AnthropicConfigError> {
+    match optional_trimmed_env(key) {
+        Some(value) => match value.to_ascii_lowercase().as_str() {
+            "true" | "1" | "yes" | "on" => Ok(true),
+            "false" | "0" | "no" | "off" => Ok(false),
+            _ => Err(AnthropicConfigError::InvalidConfiguration(format!(
+                "{key} must be a boolean value"
+            ))),
+        },
+        None => Ok(default),
+    }
+}
+
+fn optional_trimmed_env(key: &str) -> Option<String> {
+    env::var(key).ok().and_then(|value| {
+        let trimmed = value.trim();
+        if trimmed.is_empty() {
+            None
+        } else {
+            Some(trimmed.to_string())
+        }
+    })
+}
+
+fn send_error(err: reqwest::Error) -> SendAttemptError {
+    if err.is_timeout() {
+        SendAttemptError::retryable(LlmGatewayError::Timeout)
+    } else {
+        SendAttemptError::retryable(LlmGatewayError::ProviderFailure(
+            "request_unavailable".to_string(),
+        ))
+    }
+}
+
+fn provider_status_error(status: StatusCode, body: &str) -> SendAttemptError {
+    SendAttemptError {
+        error: LlmGatewayError::ProviderFailure(format!(
+            "status={} code={}",
+            status.as_u16(),
+            parse_provider_error_type(body)
+        )),
+        retryable: is_retryable_status(status),
+    }
+}
+
+fn header_request_id(headers: &reqwest::header::HeaderMap) -> Option<String> {
+    headers
+        .get("request-id")
+        .and_then(|value| value.to_str().ok())
+        .map(ToString::to_string)
+}
+
+/// Anthropic errors are `{"type": "error", "error": {"type": "overloaded_error", ...}}`.
+fn parse_provider_error_type(body: &str) -> String {
+    #[derive(Deserialize)]
+    struct ProviderErrorEnvelope {
+        error: Option<ProviderErrorDetails>,
+    }
+
+    #[derive(Deserialize)]
+    struct ProviderErrorDetails {
+        #[serde(rename = "type")]
+        error_type: Option<String>,
+    }
+
+    serde_json::from_str::<ProviderErrorEnvelope>(body)
+        .ok()
+        .and_then(|envelope| envelope.error)
+        .and_then(|details| details.error_type)
+        .unwrap_or_else(|| "unknown".to_string())
+}
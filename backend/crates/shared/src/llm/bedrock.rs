@@ -0,0 +1,722 @@
+use std::env;
+use std::time::Duration;
+
+use chrono::Utc;
+use reqwest::StatusCode;
+use serde::Deserialize;
+use serde_json::{Number, Value, json};
+use thiserror::Error;
+use tokio::time::sleep;
+
+use super::gateway::{
+    LlmGateway, LlmGatewayError, LlmGatewayFuture, LlmGatewayRequest, LlmGatewayResponse,
+    LlmProviderRole, LlmTokenUsage, LlmToolCall, LlmToolDefinition, LlmWarmUpFuture,
+};
+use super::openrouter::{is_retryable_status, parse_token_count};
+use crate::aws::{AwsCredentials, SigV4Request, endpoint_host, sign, uri_encode};
+use crate::outbound_rate_limit::{OutboundCallLimiter, OutboundProvider};
+
+const SIGNING_SERVICE: &str = "bedrock";
+const DEFAULT_MODEL: &str = "anthropic.claude-3-5-haiku-20241022-v1:0";
+const DEFAULT_TIMEOUT_MS: u64 = 15_000;
+const DEFAULT_MAX_RETRIES: u32 = 2;
+const DEFAULT_RETRY_BASE_BACKOFF_MS: u64 = 250;
+const DEFAULT_MAX_OUTPUT_TOKENS: u32 = 600;
+const DEFAULT_ALLOW_INSECURE_HTTP: bool = false;
+/// Converse has no JSON response format. Claude continues a prefilled assistant turn; Llama
+/// chat templates do not reliably do so, and are steered by the system prompt alone.
+const JSON_PREFILL: &str = "{";
+const WARM_UP_PROMPT: &str = "ping";
+const WARM_UP_MAX_OUTPUT_TOKENS: u32 = 1;
+/// Geographic prefixes of cross-region inference profile ids, e.g. `us.anthropic.claude-...`.
+const INFERENCE_PROFILE_PREFIXES: [&str; 6] = ["us.", "us-gov.", "eu.", "apac.", "jp.", "global."];
+
+#[derive(Debug, Clone)]
+pub struct BedrockGatewayConfig {
+    pub endpoint_url: String,
+    pub region: String,
+    pub credentials: AwsCredentials,
+    pub role: LlmProviderRole,
+    pub model: String,
+    pub temperature: f32,
+    pub timeout_ms: u64,
+    pub max_retries: u32,
+    pub retry_base_backoff_ms: u64,
+    pub max_output_tokens: u32,
+    pub allow_insecure_http: bool,
+}
+
+impl BedrockGatewayConfig {
+    /// Returns `None` unless `BEDROCK_PROVIDER_ROLE` is set; the gateway is opt-in, so an
+    /// OpenRouter-only deployment needs no AWS credentials.
+    pub fn from_env() -> Result<Option<Self>, BedrockConfigError> {
+        let Some(role) = optional_trimmed_env("BEDROCK_PROVIDER_ROLE") else {
+            return Ok(None);
+        };
+        let role = LlmProviderRole::parse(&role).ok_or_else(|| {
+            BedrockConfigError::InvalidConfiguration(format!(
+                "BEDROCK_PROVIDER_ROLE must be primary or fallback, got {role}"
+            ))
+        })?;
+        let region = optional_trimmed_env("BEDROCK_REGION")
+            .map(Ok)
+            .unwrap_or_else(|| require_non_empty_env("AWS_REGION"))?;
+        let model =
+            optional_trimmed_env("BEDROCK_MODEL_ID").unwrap_or_else(|| DEFAULT_MODEL.to_string());
+        if BedrockModelFamily::from_model_id(&model).is_none() {
+            return Err(BedrockConfigError::InvalidConfiguration(format!(
+                "BEDROCK_MODEL_ID must be an Anthropic Claude or Meta Llama model id, got {model}"
+            )));
+        }
+        // A VPC interface endpoint keeps inference traffic on the AWS network.
+        let endpoint_url = optional_trimmed_env("BEDROCK_ENDPOINT_URL")
+            .unwrap_or_else(|| format!("https://bedrock-runtime.{region}.amazonaws.com"));
+        let allow_insecure_http =
+            parse_bool_env("BEDROCK_ALLOW_INSECURE_HTTP", DEFAULT_ALLOW_INSECURE_HTTP)?;
+        let uses_https = endpoint_url.starts_with("https://");
+        let uses_insecure_http = allow_insecure_http && endpoint_url.starts_with("http://");
+        if !(uses_https || uses_insecure_http) {
+            return Err(BedrockConfigError::InvalidConfiguration(
+                "BEDROCK_ENDPOINT_URL must use https:// (or set BEDROCK_ALLOW_INSECURE_HTTP=true for local development)"
+                    .to_string(),
+            ));
+        }
+
+        Ok(Some(Self {
+            endpoint_url,
+            region,
+            credentials: AwsCredentials {
+                access_key_id: require_non_empty_env("AWS_ACCESS_KEY_ID")?,
+                secret_access_key: require_non_empty_env("AWS_SECRET_ACCESS_KEY")?,
+                session_token: optional_trimmed_env("AWS_SESSION_TOKEN"),
+            },
+            role,
+            model,
+            temperature: 0.0,
+            timeout_ms: parse_u64_env("BEDROCK_TIMEOUT_MS", DEFAULT_TIMEOUT_MS)?,
+            max_retries: parse_u32_env("BEDROCK_MAX_RETRIES", DEFAULT_MAX_RETRIES)?,
+            retry_base_backoff_ms: parse_u64_env(
+                "BEDROCK_RETRY_BASE_BACKOFF_MS",
+                DEFAULT_RETRY_BASE_BACKOFF_MS,
+            )?,
+            max_output_tokens: parse_u32_env(
+                "BEDROCK_MAX_OUTPUT_TOKENS",
+                DEFAULT_MAX_OUTPUT_TOKENS,
+            )?,
+            allow_insecure_http,
+        }))
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum BedrockConfigError {
+    #[error("missing required env var {0}")]
+    MissingVar(String),
+    #[error("invalid integer in env var {key}: {value}")]
+    ParseInt { key: String, value: String },
+    #[error("invalid configuration: {0}")]
+    InvalidConfiguration(String),
+    #[error("failed to build Bedrock http client: {0}")]
+    HttpClient(String),
+}
+
+/// Model families the gateway knows how to prompt for JSON output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BedrockModelFamily {
+    Claude,
+    Llama,
+}
+
+impl BedrockModelFamily {
+    fn from_model_id(model_id: &str) -> Option<Self> {
+        let foundation_model_id = foundation_model_id(model_id);
+        if foundation_model_id.starts_with("anthropic.claude") {
+            Some(Self::Claude)
+        } else if foundation_model_id.starts_with("meta.llama") {
+            Some(Self::Llama)
+        } else {
+            None
+        }
+    }
+}
+
+/// Strips a cross-region inference profile prefix, leaving the `vendor.model` id.
+pub(crate) fn foundation_model_id(model_id: &str) -> &str {
+    INFERENCE_PROFILE_PREFIXES
+        .iter()
+        .find_map(|prefix| model_id.strip_prefix(prefix))
+        .unwrap_or(model_id)
+}
+
+/// Calls the Bedrock Runtime Converse API with SigV4-signed requests, so deployments that keep
+/// inference inside AWS need no egress to OpenRouter. Routing and fallback between providers
+/// live in the reliability layer, so this gateway only retries its one configured model.
+#[derive(Clone)]
+pub struct BedrockGateway {
+    client: reqwest::Client,
+    config: BedrockGatewayConfig,
+    family: BedrockModelFamily,
+    host: String,
+    outbound_limiter: OutboundCallLimiter,
+}
+
+impl BedrockGateway {
+    pub fn new(config: BedrockGatewayConfig) -> Result<Self, BedrockConfigError> {
+        let family = BedrockModelFamily::from_model_id(&config.model).ok_or_else(|| {
+            BedrockConfigError::InvalidConfiguration(format!(
+                "unsupported Bedrock model id {}",
+                config.model
+            ))
+        })?;
+        let host = endpoint_host(&config.endpoint_url).ok_or_else(|| {
+            BedrockConfigError::InvalidConfiguration(format!(
+                "Bedrock endpoint is not a valid url: {}",
+                config.endpoint_url
+            ))
+        })?;
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(config.timeout_ms))
+            .build()
+            .map_err(|err| BedrockConfigError::HttpClient(err.to_string()))?;
+
+        Ok(Self {
+            client,
+            config,
+            family,
+            host,
+            outbound_limiter: OutboundCallLimiter::unlimited(),
+        })
+    }
+
+    /// Routes conversations through the enclave-wide outbound limiter. Warm-up probes bypass
+    /// it.
+    pub fn with_outbound_limiter(mut self, outbound_limiter: OutboundCallLimiter) -> Self {
+        self.outbound_limiter = outbound_limiter;
+        self
+    }
+
+    pub fn role(&self) -> LlmProviderRole {
+        self.config.role
+    }
+
+    async fn send_once(
+        &self,
+        request: &LlmGatewayRequest,
+    ) -> Result<LlmGatewayResponse, SendAttemptError> {
+        let user_prompt = json!({
+            "instruction": request.context_prompt,
+            "contract_version": request.contract_version,
+            "output_schema": request.output_schema,
+            "context_payload": request.context_payload,
+        })
+        .to_string();
+
+        self.outbound_limiter
+            .acquire(OutboundProvider::Bedrock)
+            .await
+            .map_err(|err| {
+                SendAttemptError::non_retryable(LlmGatewayError::RateLimited(format!(
+                    "outbound_rate_limited retry_after_ms={}",
+                    err.retry_after_ms()
+                )))
+            })?;
+
+        let mut messages = vec![json!({ "role": "user", "content": [{ "text": user_prompt }] })];
+        // A prefilled brace would force a text answer, so tool requests let the model choose.
+        if self.family == BedrockModelFamily::Claude && request.tools.is_empty() {
+            messages.push(json!({ "role": "assistant", "content": [{ "text": JSON_PREFILL }] }));
+        }
+        let mut request_body = json!({
+            "system": [{ "text": request.system_prompt }],
+            "messages": messages,
+            "inferenceConfig": {
+                "maxTokens": self.config.max_output_tokens,
+                "temperature": self.config.temperature
+            }
+        });
+        if !request.tools.is_empty() {
+            request_body["toolConfig"] = converse_tool_config(&request.tools);
+        }
+        let response = self.converse(&request_body).await?;
+
+        let status = response.status();
+        let header_request_id = header_value(response.headers(), "x-amzn-requestid");
+        let header_error_type = header_value(response.headers(), "x-amzn-errortype");
+        let body = response.text().await.map_err(|_| {
+            SendAttemptError::non_retryable(LlmGatewayError::InvalidProviderPayload(
+                "response_body_read_failed".to_string(),
+            ))
+        })?;
+
+        if !status.is_success() {
+            return Err(provider_status_error(
+                status,
+                header_error_type.as_deref(),
+                &body,
+            ));
+        }
+
+        let parsed: ConverseResponse = serde_json::from_str(&body).map_err(|_| {
+            SendAttemptError::non_retryable(LlmGatewayError::InvalidProviderPayload(
+                "response_json_parse_failed".to_string(),
+            ))
+        })?;
+
+        if matches!(
+            parsed.stop_reason.as_deref(),
+            Some("guardrail_intervened" | "content_filtered")
+        ) {
+            return Err(SendAttemptError::non_retryable(
+                LlmGatewayError::InvalidProviderPayload("content_filtered".to_string()),
+            ));
+        }
+        let mut text = String::new();
+        let mut tool_calls = Vec::new();
+        let content = parsed
+            .output
+            .and_then(|output| output.message)
+            .map(|message| message.content)
+            .unwrap_or_default();
+        for block in content {
+            if let Some(block_text) = block.text {
+                text.push_str(&block_text);
+            }
+            if let Some(tool_use) = block.tool_use {
+                tool_calls.push(LlmToolCall {
+                    id: tool_use.tool_use_id,
+                    name: tool_use.name,
+                    arguments: tool_use.input.unwrap_or_else(|| json!({})),
+                });
+            }
+        }
+        // Prose alongside a tool call is commentary, not a contract answer.
+        let output = match parse_json_object(&text, self.family) {
+            Some(output) => output,
+            None if !tool_calls.is_empty() => Value::Null,
+            None if text.is_empty() => {
+                return Err(SendAttemptError::non_retryable(
+                    LlmGatewayError::InvalidProviderPayload("missing_text_content".to_string()),
+                ));
+            }
+            None => {
+                return Err(SendAttemptError::non_retryable(
+                    LlmGatewayError::InvalidProviderPayload("content_not_json".to_string()),
+                ));
+            }
+        };
+
+        Ok(LlmGatewayResponse {
+            model: self.config.model.clone(),
+            provider_request_id: header_request_id,
+            output,
+            tool_calls,
+            usage: parsed.usage.map(ConverseUsage::into_token_usage),
+        })
+    }
+
+    /// Sends the smallest conversation the provider accepts. Only the HTTP status matters, so
+    /// the single output token is never parsed.
+    async fn warm_up_once(&self) -> Result<(), SendAttemptError> {
+        let request_body = json!({
+            "messages": [
+                { "role": "user", "content": [{ "text": WARM_UP_PROMPT }] }
+            ],
+            "inferenceConfig": { "maxTokens": WARM_UP_MAX_OUTPUT_TOKENS }
+        });
+        let response = self.converse(&request_body).await?;
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+
+        let header_error_type = header_value(response.headers(), "x-amzn-errortype");
+        let body = response.text().await.unwrap_or_default();
+        Err(provider_status_error(
+            status,
+            header_error_type.as_deref(),
+            &body,
+        ))
+    }
+
+    /// Signs and sends a Converse call. The body is signed as sent, so it is serialized here
+    /// rather than by reqwest.
+    async fn converse(&self, request_body: &Value) -> Result<reqwest::Response, SendAttemptError> {
+        let body = serde_json::to_vec(request_body).map_err(|_| {
+            SendAttemptError::non_retryable(LlmGatewayError::InvalidProviderPayload(
+                "request_json_encode_failed".to_string(),
+            ))
+        })?;
+        let paths = ConversePaths::for_model(&self.config.model);
+
+        let mut signed_headers = vec![("content-type", "application/json")];
+        if let Some(session_token) = self.config.credentials.session_token.as_deref() {
+            signed_headers.push(("x-amz-security-token", session_token));
+        }
+        let signature = sign(
+            &self.config.credentials,
+            &SigV4Request {
+                region: &self.config.region,
+                service: SIGNING_SERVICE,
+                method: "POST",
+                host: &self.host,
+                canonical_uri: &paths.canonical_uri,
+                headers: &signed_headers,
+                body: &body,
+            },
+            Utc::now(),
+        );
+
+        let mut request_builder = self
+            .client
+            .post(format!(
+                "{}{}",
+                self.config.endpoint_url.trim_end_matches('/'),
+                paths.request_path
+            ))
+            .header("x-amz-date", signature.amz_date)
+            .header("authorization", signature.authorization);
+        for (name, value) in signed_headers {
+            request_builder = request_builder.header(name, value);
+        }
+        request_builder.body(body).send().await.map_err(send_error)
+    }
+}
+
+impl LlmGateway for BedrockGateway {
+    fn generate<'a>(&'a self, request: LlmGatewayRequest) -> LlmGatewayFuture<'a> {
+        Box::pin(async move {
+            let mut attempt = 0_u32;
+
+            loop {
+                match self.send_once(&request).await {
+                    Ok(response) => return Ok(response),
+                    Err(err) => {
+                        if err.retryable && attempt < self.config.max_retries {
+                            let backoff_multiplier = 2_u64.saturating_pow(attempt);
+                            let backoff_ms = self
+                                .config
+                                .retry_base_backoff_ms
+                                .saturating_mul(backoff_multiplier);
+                            sleep(Duration::from_millis(backoff_ms)).await;
+                            attempt = attempt.saturating_add(1);
+                            continue;
+                        }
+
+                        return Err(err.error);
+                    }
+                }
+            }
+        })
+    }
+
+    fn warm_up<'a>(&'a self) -> LlmWarmUpFuture<'a> {
+        Box::pin(async move { self.warm_up_once().await.map_err(|err| err.error) })
+    }
+}
+
+/// Model ids contain `:`, which is percent-encoded once in the request path. SigV4 encodes the
+/// path again for every service but S3, so the canonical URI carries `%253A`.
+#[derive(Debug, PartialEq, Eq)]
+struct ConversePaths {
+    request_path: String,
+    canonical_uri: String,
+}
+
+impl ConversePaths {
+    fn for_model(model_id: &str) -> Self {
+        let encoded_model_id = uri_encode(model_id);
+        Self {
+            canonical_uri: format!("/model/{}/converse", uri_encode(&encoded_model_id)),
+            request_path: format!("/model/{encoded_model_id}/converse"),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct SendAttemptError {
+    error: LlmGatewayError,
+    retryable: bool,
+}
+
+impl SendAttemptError {
+    fn retryable(error: LlmGatewayError) -> Self {
+        Self {
+            error,
+            retryable: true,
+        }
+    }
+
+    fn non_retryable(error: LlmGatewayError) -> Self {
+        Self {
+            error,
+            retryable: false,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConverseResponse {
+    output: Option<ConverseOutput>,
+    stop_reason: Option<String>,
+    usage: Option<ConverseUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ConverseOutput {
+    message: Option<ConverseMessage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ConverseMessage {
+    #[serde(default)]
+    content: Vec<ConverseContentBlock>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConverseContentBlock {
+    text: Option<String>,
+    tool_use: Option<ConverseToolUse>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConverseToolUse {
+    tool_use_id: Option<String>,
+    name: String,
+    input: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConverseUsage {
+    input_tokens: Option<Number>,
+    output_tokens: Option<Number>,
+    cache_read_input_tokens: Option<Number>,
+    cache_write_input_tokens: Option<Number>,
+}
+
+impl ConverseUsage {
+    /// Cached prompt tokens are reported apart from `inputTokens` but are still prompt input.
+    fn into_token_usage(self) -> LlmTokenUsage {
+        let prompt_tokens = parse_token_count(self.input_tokens)
+            .saturating_add(parse_token_count(self.cache_read_input_tokens))
+            .saturating_add(parse_token_count(self.cache_write_input_tokens));
+        let completion_tokens = parse_token_count(self.output_tokens);
+        LlmTokenUsage {
+            prompt_tokens,
+            completion_tokens,
+            total_tokens: prompt_tokens.saturating_add(completion_tokens),
+        }
+    }
+}
+
+fn converse_tool_config(tools: &[LlmToolDefinition]) -> Value {
+    let tools = tools
+        .iter()
+        .map(|tool| {
+            json!({
+                "toolSpec": {
+                    "name": tool.name,
+                    "description": tool.description,
+                    "inputSchema": { "json": tool.parameters }
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    json!({ "tools": tools })
+}
+
+/// Claude continues the prefilled brace but may restate it. Llama is unprompted and may wrap
+/// the object in prose, so the outermost braces are tried last.
+fn parse_json_object(text: &str, family: BedrockModelFamily) -> Option<Value> {
+    let mut candidates = Vec::with_capacity(3);
+    if family == BedrockModelFamily::Claude {
+        candidates.push(format!("{JSON_PREFILL}{text}"));
+    }
+    candidates.push(text.trim().to_string());
+    if let (Some(start), Some(end)) = (text.find('{'), text.rfind('}'))
+        && start < end
+    {
+        candidates.push(text[start..=end].to_string());
+    }
+    candidates
+        .iter()
+        .filter_map(|candidate| serde_json::from_str::<Value>(candidate).ok())
+        .find(|value| value.is_object())
+}
+
+fn require_non_empty_env(key: &str) -> Result<String, BedrockConfigError> {
+    optional_trimmed_env(key).ok_or_else(|| BedrockConfigError::MissingVar(key.to_string()))
+}
+
+fn parse_u64_env(key: &str, default: u64) -> Result<u64, BedrockConfigError> {
+    match optional_trimmed_env(key) {
+        Some(value) => value
+            .parse::<u64>()
+            .map_err(|_| BedrockConfigError::ParseInt {
+                key: key.to_string(),
+                value,
+            }),
+        None => Ok(default),
+    }
+}
+
+fn parse_u32_env(key: &str, default: u32) -> Result<u32, BedrockConfigError> {
+    match optional_trimmed_env(key) {
+        Some(value) => value
+            .parse::<u32>()
+            .map_err(|_| BedrockConfigError::ParseInt {
+                key: key.to_string(),
+                value,
+            }),
+        None => Ok(default),
+    }
+}
+
+fn parse_bool_env(key: &str, default: bool) -> Result<bool, BedrockConfigError> {
+    match optional_trimmed_env(key) {
+        Some(value) => match value.to_ascii_lowercase().as_str() {
+            "true" | "1" | "yes" | "on" => Ok(true),
+            "false" | "0" | "no" | "off" => Ok(false),
+            _ => Err(BedrockConfigError::InvalidConfiguration(format!(
+                "{key} must be a boolean value"
+            ))),
+        },
+        None => Ok(default),
+    }
+}
+
+fn optional_trimmed_env(key: &str) -> Option<String> {
+    env::var(key).ok().and_then(|value| {
+        let trimmed = value.trim();
+        if trimmed.is_empty() {
+            None
+        } else {
+            Some(trimmed.to_string())
+        }
+    })
+}
+
+fn send_error(err: reqwest::Error) -> SendAttemptError {
+    if err.is_timeout() {
+        SendAttemptError::retryable(LlmGatewayError::Timeout)
+    } else {
+        SendAttemptError::retryable(LlmGatewayError::ProviderFailure(
+            "request_unavailable".to_string(),
+        ))
+    }
+}
+
+fn provider_status_error(
+    status: StatusCode,
+    header_error_type: Option<&str>,
+    body: &str,
+) -> SendAttemptError {
+    SendAttemptError {
+        error: LlmGatewayError::ProviderFailure(format!(
+            "status={} code={}",
+            status.as_u16(),
+            parse_provider_error_type(header_error_type, body)
+        )),
+        retryable: is_retryable_status(status),
+    }
+}
+
+fn header_value(headers: &reqwest::header::HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(ToString::to_string)
+}
+
+/// Bedrock names the error in `x-amzn-ErrorType` (`ThrottlingException:http://...`) and, on
+/// some paths, only in a namespaced `__type` body field.
+fn parse_provider_error_type(header_error_type: Option<&str>, body: &str) -> String {
+    #[derive(Deserialize)]
+    struct ProviderErrorEnvelope {
+        #[serde(rename = "__type")]
+        error_type: Option<String>,
+    }
+
+    header_error_type
+        .and_then(|value| value.split(':').next())
+        .map(ToString::to_string)
+        .or_else(|| {
+            serde_json::from_str::<ProviderErrorEnvelope>(body)
+                .ok()
+                .and_then(|envelope| envelope.error_type)
+                .and_then(|value| value.rsplit('#').next().map(ToString::to_string))
+        })
+        .filter(|value| !value.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{BedrockModelFamily, ConversePaths, parse_json_object};
+
+    #[test]
+    fn accepts_claude_and_llama_ids_including_inference_profiles() {
+        let cases = [
+            (
+                "anthropic.claude-3-5-haiku-20241022-v1:0",
+                Some(BedrockModelFamily::Claude),
+            ),
+            (
+                "us.anthropic.claude-3-5-sonnet-20241022-v2:0",
+                Some(BedrockModelFamily::Claude),
+            ),
+            (
+                "meta.llama3-1-70b-instruct-v1:0",
+                Some(BedrockModelFamily::Llama),
+            ),
+            (
+                "eu.meta.llama3-2-3b-instruct-v1:0",
+                Some(BedrockModelFamily::Llama),
+            ),
+            ("amazon.titan-text-express-v1", None),
+            ("anthropic/claude-3.5-haiku", None),
+        ];
+
+        for (model_id, expected) in cases {
+            assert_eq!(
+                BedrockModelFamily::from_model_id(model_id),
+                expected,
+                "{model_id}"
+            );
+        }
+    }
+
+    #[test]
+    fn model_id_is_encoded_once_on_the_wire_and_twice_when_signed() {
+        let paths = ConversePaths::for_model("anthropic.claude-3-5-haiku-20241022-v1:0");
+
+        assert_eq!(
+            paths.request_path,
+            "/model/anthropic.claude-3-5-haiku-20241022-v1%3A0/converse"
+        );
+        assert_eq!(
+            paths.canonical_uri,
+            "/model/anthropic.claude-3-5-haiku-20241022-v1%253A0/converse"
+        );
+    }
+
+    #[test]
+    fn llama_output_is_extracted_from_surrounding_prose() {
+        let text = "Here is the JSON:\n{\"version\": \"1\", \"output\": {}}\nLet me know!";
+
+        assert_eq!(
+            parse_json_object(text, BedrockModelFamily::Llama),
+            Some(json!({ "version": "1", "output": {} }))
+        );
+        assert_eq!(
+            parse_json_object("\"version\": \"1\"}", BedrockModelFamily::Claude),
+            Some(json!({ "version": "1" }))
+        );
+    }
+}
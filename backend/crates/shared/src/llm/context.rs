@@ -5,6 +5,8 @@ use chrono::{DateTime, NaiveDate, SecondsFormat, Utc};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::brief_profile::MorningBriefProfile;
+
 pub const CONTEXT_CONTRACT_VERSION_V1: &str = "2026-02-15";
 
 const DEFAULT_MORNING_BRIEF_LOCAL_TIME: &str = "08:00";
@@ -19,6 +21,8 @@ const MAX_SENDER_CHARS: usize = 120;
 const MAX_SNIPPET_CHARS: usize = 280;
 const MAX_LABEL_CHARS: usize = 32;
 const MAX_LOCAL_TIME_CHARS: usize = 16;
+const MAX_FOCUS_CHARS: usize = 500;
+pub const WEEKLY_REVIEW_DAYS: u64 = 7;
 
 #[derive(Debug, Clone, Default)]
 pub struct GoogleCalendarMeetingSource {
@@ -93,15 +97,58 @@ pub struct MorningBriefContext {
     pub urgent_email_candidate_count: usize,
     pub meetings_today: Vec<MeetingContextEntry>,
     pub urgent_email_candidates: Vec<UrgentEmailCandidateContextEntry>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub brief_profile: Option<MorningBriefProfile>,
+}
+
+impl MorningBriefContext {
+    /// Attaches the user's section and verbosity preferences so generation can follow them.
+    pub fn with_brief_profile(mut self, brief_profile: Option<MorningBriefProfile>) -> Self {
+        self.brief_profile = brief_profile;
+        self
+    }
+}
+
+/// Context for the weekly review automation template: the past seven local days of
+/// meetings and inbox activity plus a preview of the next seven days.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct WeeklyReviewContext {
+    pub version: String,
+    pub review_start_date: String,
+    pub review_end_date: String,
+    pub past_meeting_count: usize,
+    pub email_count: usize,
+    pub upcoming_meeting_count: usize,
+    pub past_meetings: Vec<MeetingContextEntry>,
+    pub emails: Vec<UrgentEmailCandidateContextEntry>,
+    pub upcoming_meetings: Vec<MeetingContextEntry>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub focus: Option<String>,
 }
 
 pub fn assemble_meetings_today_context(
     calendar_day: NaiveDate,
     meetings: &[GoogleCalendarMeetingSource],
 ) -> MeetingsTodayContext {
+    let meetings = meeting_entries_between(calendar_day, calendar_day, meetings);
+
+    MeetingsTodayContext {
+        version: CONTEXT_CONTRACT_VERSION_V1.to_string(),
+        calendar_day: calendar_day.to_string(),
+        meeting_count: meetings.len(),
+        meetings,
+    }
+}
+
+fn meeting_entries_between(
+    first_day: NaiveDate,
+    last_day: NaiveDate,
+    meetings: &[GoogleCalendarMeetingSource],
+) -> Vec<MeetingContextEntry> {
     let mut normalized: Vec<NormalizedMeeting> = meetings
         .iter()
-        .filter_map(|meeting| normalize_meeting(calendar_day, meeting))
+        .filter_map(|meeting| normalize_meeting(first_day, last_day, meeting))
         .collect();
 
     normalized.sort_by(|left, right| {
@@ -112,7 +159,7 @@ pub fn assemble_meetings_today_context(
     });
 
     let mut fallback_index = 0usize;
-    let meetings = normalized
+    normalized
         .into_iter()
         .take(MAX_MEETINGS)
         .map(|meeting| {
@@ -133,14 +180,7 @@ pub fn assemble_meetings_today_context(
                 attendee_count: meeting.attendee_count,
             }
         })
-        .collect::<Vec<_>>();
-
-    MeetingsTodayContext {
-        version: CONTEXT_CONTRACT_VERSION_V1.to_string(),
-        calendar_day: calendar_day.to_string(),
-        meeting_count: meetings.len(),
-        meetings,
-    }
+        .collect()
 }
 
 pub fn assemble_urgent_email_candidates_context(
@@ -203,6 +243,36 @@ pub fn assemble_morning_brief_context(
         urgent_email_candidate_count: urgent_email_context.candidate_count,
         meetings_today: meetings_today_context.meetings,
         urgent_email_candidates: urgent_email_context.candidates,
+        brief_profile: None,
+    }
+}
+
+pub fn assemble_weekly_review_context(
+    review_end_date: NaiveDate,
+    meetings: &[GoogleCalendarMeetingSource],
+    emails: &[GoogleEmailCandidateSource],
+    focus: Option<&str>,
+) -> WeeklyReviewContext {
+    let review_start_date = review_end_date - chrono::Days::new(WEEKLY_REVIEW_DAYS - 1);
+    let upcoming_start_date = review_end_date + chrono::Days::new(1);
+    let upcoming_end_date = review_end_date + chrono::Days::new(WEEKLY_REVIEW_DAYS);
+
+    let past_meetings = meeting_entries_between(review_start_date, review_end_date, meetings);
+    let upcoming_meetings =
+        meeting_entries_between(upcoming_start_date, upcoming_end_date, meetings);
+    let emails = assemble_urgent_email_candidates_context(emails).candidates;
+
+    WeeklyReviewContext {
+        version: CONTEXT_CONTRACT_VERSION_V1.to_string(),
+        review_start_date: review_start_date.to_string(),
+        review_end_date: review_end_date.to_string(),
+        past_meeting_count: past_meetings.len(),
+        email_count: emails.len(),
+        upcoming_meeting_count: upcoming_meetings.len(),
+        past_meetings,
+        emails,
+        upcoming_meetings,
+        focus: focus.and_then(|focus| normalize_identifier(Some(focus), MAX_FOCUS_CHARS)),
     }
 }
 
@@ -216,11 +286,13 @@ struct NormalizedMeeting {
 }
 
 fn normalize_meeting(
-    calendar_day: NaiveDate,
+    first_day: NaiveDate,
+    last_day: NaiveDate,
     meeting: &GoogleCalendarMeetingSource,
 ) -> Option<NormalizedMeeting> {
     let start_at = meeting.start_at?;
-    if start_at.date_naive() != calendar_day {
+    let start_day = start_at.date_naive();
+    if start_day < first_day || start_day > last_day {
         return None;
     }
 
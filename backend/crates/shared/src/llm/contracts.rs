@@ -6,6 +6,7 @@ use thiserror::Error;
 use crate::assistant_semantic_plan::{
     ASSISTANT_SEMANTIC_PLAN_VERSION_V1, AssistantSemanticPlanContract,
 };
+use crate::automation_draft::{AUTOMATION_DRAFT_VERSION_V1, AutomationDraftContract};
 
 pub const OUTPUT_CONTRACT_VERSION_V1: &str = "2026-02-15";
 
@@ -17,12 +18,27 @@ pub enum AssistantCapability {
     MorningBrief,
     UrgentEmailSummary,
     AssistantSemanticPlan,
+    WeeklyReview,
+    AutomationDraft,
 }
 
 impl AssistantCapability {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::MeetingsSummary => "meetings_summary",
+            Self::GeneralChatSummary => "general_chat_summary",
+            Self::MorningBrief => "morning_brief",
+            Self::UrgentEmailSummary => "urgent_email_summary",
+            Self::AssistantSemanticPlan => "assistant_semantic_plan",
+            Self::WeeklyReview => "weekly_review",
+            Self::AutomationDraft => "automation_draft",
+        }
+    }
+
     pub const fn contract_version(self) -> &'static str {
         match self {
             Self::AssistantSemanticPlan => ASSISTANT_SEMANTIC_PLAN_VERSION_V1,
+            Self::AutomationDraft => AUTOMATION_DRAFT_VERSION_V1,
             _ => OUTPUT_CONTRACT_VERSION_V1,
         }
     }
@@ -56,6 +72,13 @@ pub struct UrgentEmailSummaryContract {
     pub output: UrgentEmailSummaryOutput,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct WeeklyReviewContract {
+    pub version: String,
+    pub output: WeeklyReviewOutput,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct MeetingsSummaryOutput {
@@ -102,6 +125,16 @@ pub struct UrgentEmailSummaryOutput {
     pub suggested_actions: Vec<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct WeeklyReviewOutput {
+    pub headline: String,
+    pub summary: String,
+    pub meetings_recap: Vec<String>,
+    pub email_recap: Vec<String>,
+    pub upcoming_week: Vec<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum UrgencyLevel {
@@ -118,6 +151,8 @@ pub enum AssistantOutputContract {
     MorningBrief(MorningBriefContract),
     UrgentEmailSummary(UrgentEmailSummaryContract),
     AssistantSemanticPlan(AssistantSemanticPlanContract),
+    WeeklyReview(WeeklyReviewContract),
+    AutomationDraft(AutomationDraftContract),
 }
 
 #[derive(Debug, Error)]
@@ -156,6 +191,14 @@ pub fn output_schema(capability: AssistantCapability) -> Value {
             serde_json::to_value(schema_for!(AssistantSemanticPlanContract))
                 .expect("assistant semantic plan schema should be serializable")
         }
+        AssistantCapability::WeeklyReview => {
+            serde_json::to_value(schema_for!(WeeklyReviewContract))
+                .expect("weekly review schema should be serializable")
+        }
+        AssistantCapability::AutomationDraft => {
+            serde_json::to_value(schema_for!(AutomationDraftContract))
+                .expect("automation draft schema should be serializable")
+        }
     }
 }
 
@@ -189,6 +232,16 @@ pub fn parse_contract(
             ensure_contract_version(capability, &contract.version)?;
             Ok(AssistantOutputContract::AssistantSemanticPlan(contract))
         }
+        AssistantCapability::WeeklyReview => {
+            let contract: WeeklyReviewContract = serde_json::from_value(payload)?;
+            ensure_contract_version(capability, &contract.version)?;
+            Ok(AssistantOutputContract::WeeklyReview(contract))
+        }
+        AssistantCapability::AutomationDraft => {
+            let contract: AutomationDraftContract = serde_json::from_value(payload)?;
+            ensure_contract_version(capability, &contract.version)?;
+            Ok(AssistantOutputContract::AutomationDraft(contract))
+        }
     }
 }
 
@@ -10,6 +10,8 @@ use super::prompts::PromptTemplate;
 
 pub type LlmGatewayFuture<'a> =
     Pin<Box<dyn Future<Output = Result<LlmGatewayResponse, LlmGatewayError>> + Send + 'a>>;
+pub type LlmWarmUpFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<(), LlmGatewayError>> + Send + 'a>>;
 
 #[derive(Debug, Clone)]
 pub struct LlmGatewayRequest {
@@ -20,6 +22,9 @@ pub struct LlmGatewayRequest {
     pub context_prompt: String,
     pub output_schema: Value,
     pub context_payload: Value,
+    /// Functions the model may call instead of, or alongside, answering with the contract.
+    /// Empty unless set with [`LlmGatewayRequest::with_tools`].
+    pub tools: Vec<LlmToolDefinition>,
 }
 
 impl LlmGatewayRequest {
@@ -32,6 +37,7 @@ impl LlmGatewayRequest {
             context_prompt: template.context_prompt.to_string(),
             output_schema: template.output_schema,
             context_payload,
+            tools: Vec::new(),
         }
     }
 
@@ -42,6 +48,46 @@ impl LlmGatewayRequest {
         }
         self
     }
+
+    pub fn with_tools(mut self, tools: Vec<LlmToolDefinition>) -> Self {
+        self.tools = tools;
+        self
+    }
+}
+
+/// A function offered to the model. `parameters` is the JSON schema of its arguments.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LlmToolDefinition {
+    pub name: String,
+    pub description: String,
+    pub parameters: Value,
+}
+
+/// A function call chosen by the model. Arguments are parsed but not yet validated; see
+/// [`super::validation::validate_tool_calls`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LlmToolCall {
+    pub id: Option<String>,
+    pub name: String,
+    pub arguments: Value,
+}
+
+/// Where a direct provider gateway sits in the reliability layer: in front of OpenRouter, or
+/// only behind it when OpenRouter fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LlmProviderRole {
+    Primary,
+    Fallback,
+}
+
+impl LlmProviderRole {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "primary" => Some(Self::Primary),
+            "fallback" => Some(Self::Fallback),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -55,8 +101,12 @@ pub struct LlmTokenUsage {
 pub struct LlmGatewayResponse {
     pub model: String,
     pub provider_request_id: Option<String>,
+    /// The contract JSON, or `Value::Null` when the model answered only with tool calls.
     pub output: Value,
     pub usage: Option<LlmTokenUsage>,
+    /// Defaults to empty so responses cached before tools existed still deserialize.
+    #[serde(default)]
+    pub tool_calls: Vec<LlmToolCall>,
 }
 
 #[derive(Debug, Error)]
@@ -67,8 +117,21 @@ pub enum LlmGatewayError {
     ProviderFailure(String),
     #[error("llm provider returned an invalid payload: {0}")]
     InvalidProviderPayload(String),
+    /// Turned away by the enclave's outbound limiter; the provider was never called.
+    #[error("llm provider call rate limited: {0}")]
+    RateLimited(String),
+    /// The requester's monthly spend budget is used up; the provider was never called.
+    #[error("llm spend budget exceeded: {0}")]
+    BudgetExceeded(String),
 }
 
 pub trait LlmGateway: Send + Sync {
     fn generate<'a>(&'a self, request: LlmGatewayRequest) -> LlmGatewayFuture<'a>;
+
+    /// Verifies the provider path with the cheapest request the gateway supports, opening
+    /// connections ahead of the first user request. Gateways without a remote provider have
+    /// nothing to warm and succeed immediately.
+    fn warm_up<'a>(&'a self) -> LlmWarmUpFuture<'a> {
+        Box::pin(async { Ok(()) })
+    }
 }
@@ -0,0 +1,506 @@
+use std::env;
+use std::net::IpAddr;
+use std::time::Duration;
+
+use reqwest::StatusCode;
+use serde::Deserialize;
+use serde_json::{Number, Value, json};
+use thiserror::Error;
+use tokio::time::sleep;
+
+use super::contracts::AssistantCapability;
+use super::gateway::{
+    LlmGateway, LlmGatewayError, LlmGatewayFuture, LlmGatewayRequest, LlmGatewayResponse,
+    LlmTokenUsage, LlmWarmUpFuture,
+};
+use super::openrouter::{
+    ChatToolCall, chat_tools_payload, is_retryable_status, parse_chat_tool_calls, parse_token_count,
+};
+
+const DEFAULT_TIMEOUT_MS: u64 = 10_000;
+const DEFAULT_MAX_RETRIES: u32 = 0;
+const DEFAULT_RETRY_BASE_BACKOFF_MS: u64 = 100;
+const DEFAULT_MAX_OUTPUT_TOKENS: u32 = 600;
+const DEFAULT_ALLOW_NON_LOOPBACK: bool = false;
+
+#[derive(Debug, Clone)]
+pub struct LocalLlmGatewayConfig {
+    /// OpenAI-compatible API root, e.g. `http://127.0.0.1:11434/v1` for Ollama.
+    pub base_url: String,
+    pub api_key: Option<String>,
+    pub model: String,
+    /// Capabilities served by the local model; every other request stays on the remote chain.
+    pub capabilities: Vec<AssistantCapability>,
+    pub temperature: f32,
+    pub timeout_ms: u64,
+    pub max_retries: u32,
+    pub retry_base_backoff_ms: u64,
+    pub max_output_tokens: u32,
+}
+
+impl LocalLlmGatewayConfig {
+    /// Returns `None` unless `LOCAL_LLM_CAPABILITIES` names at least one capability.
+    pub fn from_env() -> Result<Option<Self>, LocalLlmConfigError> {
+        let Some(capabilities) = optional_trimmed_env("LOCAL_LLM_CAPABILITIES") else {
+            return Ok(None);
+        };
+        let capabilities = parse_capabilities(&capabilities)?;
+        let base_url = require_non_empty_env("LOCAL_LLM_BASE_URL")?;
+        let allow_non_loopback =
+            parse_bool_env("LOCAL_LLM_ALLOW_NON_LOOPBACK", DEFAULT_ALLOW_NON_LOOPBACK)?;
+        if !allow_non_loopback && !is_loopback_url(&base_url) {
+            return Err(LocalLlmConfigError::InvalidConfiguration(
+                "LOCAL_LLM_BASE_URL must point at a loopback address so prompts stay inside the enclave (or set LOCAL_LLM_ALLOW_NON_LOOPBACK=true for local development)"
+                    .to_string(),
+            ));
+        }
+
+        Ok(Some(Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key: optional_trimmed_env("LOCAL_LLM_API_KEY"),
+            model: require_non_empty_env("LOCAL_LLM_MODEL")?,
+            capabilities,
+            temperature: 0.0,
+            timeout_ms: parse_u64_env("LOCAL_LLM_TIMEOUT_MS", DEFAULT_TIMEOUT_MS)?,
+            max_retries: parse_u32_env("LOCAL_LLM_MAX_RETRIES", DEFAULT_MAX_RETRIES)?,
+            retry_base_backoff_ms: parse_u64_env(
+                "LOCAL_LLM_RETRY_BASE_BACKOFF_MS",
+                DEFAULT_RETRY_BASE_BACKOFF_MS,
+            )?,
+            max_output_tokens: parse_u32_env(
+                "LOCAL_LLM_MAX_OUTPUT_TOKENS",
+                DEFAULT_MAX_OUTPUT_TOKENS,
+            )?,
+        }))
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum LocalLlmConfigError {
+    #[error("missing required env var {0}")]
+    MissingVar(String),
+    #[error("invalid integer in env var {key}: {value}")]
+    ParseInt { key: String, value: String },
+    #[error("invalid configuration: {0}")]
+    InvalidConfiguration(String),
+    #[error("failed to build local LLM http client: {0}")]
+    HttpClient(String),
+}
+
+/// Calls a self-hosted OpenAI-compatible server (Ollama, vLLM) running next to the enclave
+/// runtime, so small models can serve latency-bound capabilities without prompts leaving the
+/// trust boundary. The server is local, so calls skip the outbound limiter.
+#[derive(Clone)]
+pub struct LocalLlmGateway {
+    client: reqwest::Client,
+    config: LocalLlmGatewayConfig,
+}
+
+impl LocalLlmGateway {
+    pub fn new(config: LocalLlmGatewayConfig) -> Result<Self, LocalLlmConfigError> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(config.timeout_ms))
+            .build()
+            .map_err(|err| LocalLlmConfigError::HttpClient(err.to_string()))?;
+
+        Ok(Self { client, config })
+    }
+
+    pub fn capabilities(&self) -> &[AssistantCapability] {
+        &self.config.capabilities
+    }
+
+    async fn send_once(
+        &self,
+        request: &LlmGatewayRequest,
+    ) -> Result<LlmGatewayResponse, SendAttemptError> {
+        let user_prompt = json!({
+            "instruction": request.context_prompt,
+            "contract_version": request.contract_version,
+            "output_schema": request.output_schema,
+            "context_payload": request.context_payload,
+        })
+        .to_string();
+
+        // `json_object` is the structured-output mode Ollama and vLLM both accept; the schema
+        // travels in the prompt and the reply is validated downstream like any other.
+        let mut request_body = json!({
+            "model": self.config.model,
+            "messages": [
+                { "role": "system", "content": request.system_prompt },
+                { "role": "user", "content": user_prompt }
+            ],
+            "response_format": { "type": "json_object" },
+            "temperature": self.config.temperature,
+            "max_tokens": self.config.max_output_tokens,
+            "stream": false
+        });
+        // Servers that honour `json_object` refuse to emit tool calls under it.
+        if !request.tools.is_empty() {
+            request_body["tools"] = chat_tools_payload(&request.tools);
+            if let Some(body) = request_body.as_object_mut() {
+                body.remove("response_format");
+            }
+        }
+        let response = self
+            .request(
+                self.client
+                    .post(format!("{}/chat/completions", self.config.base_url)),
+            )
+            .json(&request_body)
+            .send()
+            .await
+            .map_err(send_error)?;
+
+        let status = response.status();
+        let body = response.text().await.map_err(|_| {
+            SendAttemptError::non_retryable(LlmGatewayError::InvalidProviderPayload(
+                "response_body_read_failed".to_string(),
+            ))
+        })?;
+
+        if !status.is_success() {
+            return Err(provider_status_error(status, &body));
+        }
+
+        let parsed: ChatCompletionResponse = serde_json::from_str(&body).map_err(|_| {
+            SendAttemptError::non_retryable(LlmGatewayError::InvalidProviderPayload(
+                "response_json_parse_failed".to_string(),
+            ))
+        })?;
+
+        let message = parsed
+            .choices
+            .into_iter()
+            .next()
+            .map(|choice| choice.message)
+            .ok_or_else(|| {
+                SendAttemptError::non_retryable(LlmGatewayError::InvalidProviderPayload(
+                    "content_not_json".to_string(),
+                ))
+            })?;
+        let tool_calls =
+            parse_chat_tool_calls(message.tool_calls).map_err(SendAttemptError::non_retryable)?;
+        let output = match message
+            .content
+            .and_then(|raw| serde_json::from_str::<Value>(&raw).ok())
+            .filter(Value::is_object)
+        {
+            Some(output) => output,
+            None if !tool_calls.is_empty() => Value::Null,
+            None => {
+                return Err(SendAttemptError::non_retryable(
+                    LlmGatewayError::InvalidProviderPayload("content_not_json".to_string()),
+                ));
+            }
+        };
+
+        Ok(LlmGatewayResponse {
+            model: parsed.model.unwrap_or_else(|| self.config.model.clone()),
+            provider_request_id: parsed.id,
+            output,
+            tool_calls,
+            usage: parsed.usage.map(|usage| LlmTokenUsage {
+                prompt_tokens: parse_token_count(usage.prompt_tokens),
+                completion_tokens: parse_token_count(usage.completion_tokens),
+                total_tokens: parse_token_count(usage.total_tokens),
+            }),
+        })
+    }
+
+    /// A health probe rather than a completion: the server must be up and already serving the
+    /// configured model, since a local server that still has to pull or load it would miss
+    /// every latency budget it was brought in for.
+    async fn probe_once(&self) -> Result<(), LlmGatewayError> {
+        let response = self
+            .request(self.client.get(format!("{}/models", self.config.base_url)))
+            .send()
+            .await
+            .map_err(|err| send_error(err).error)?;
+
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        if !status.is_success() {
+            return Err(provider_status_error(status, &body).error);
+        }
+
+        let models: ModelListResponse = serde_json::from_str(&body).map_err(|_| {
+            LlmGatewayError::InvalidProviderPayload("model_list_parse_failed".to_string())
+        })?;
+        if models
+            .data
+            .iter()
+            .any(|model| model.id == self.config.model)
+        {
+            Ok(())
+        } else {
+            Err(LlmGatewayError::ProviderFailure(format!(
+                "model_not_served model={}",
+                self.config.model
+            )))
+        }
+    }
+
+    fn request(&self, request_builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match self.config.api_key.as_deref() {
+            Some(api_key) => request_builder.bearer_auth(api_key),
+            None => request_builder,
+        }
+    }
+}
+
+impl LlmGateway for LocalLlmGateway {
+    fn generate<'a>(&'a self, request: LlmGatewayRequest) -> LlmGatewayFuture<'a> {
+        Box::pin(async move {
+            let mut attempt = 0_u32;
+
+            loop {
+                match self.send_once(&request).await {
+                    Ok(response) => return Ok(response),
+                    Err(err) => {
+                        if err.retryable && attempt < self.config.max_retries {
+                            let backoff_multiplier = 2_u64.saturating_pow(attempt);
+                            let backoff_ms = self
+                                .config
+                                .retry_base_backoff_ms
+                                .saturating_mul(backoff_multiplier);
+                            sleep(Duration::from_millis(backoff_ms)).await;
+                            attempt = attempt.saturating_add(1);
+                            continue;
+                        }
+
+                        return Err(err.error);
+                    }
+                }
+            }
+        })
+    }
+
+    fn warm_up<'a>(&'a self) -> LlmWarmUpFuture<'a> {
+        Box::pin(self.probe_once())
+    }
+}
+
+#[derive(Debug)]
+struct SendAttemptError {
+    error: LlmGatewayError,
+    retryable: bool,
+}
+
+impl SendAttemptError {
+    fn retryable(error: LlmGatewayError) -> Self {
+        Self {
+            error,
+            retryable: true,
+        }
+    }
+
+    fn non_retryable(error: LlmGatewayError) -> Self {
+        Self {
+            error,
+            retryable: false,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatCompletionResponse {
+    id: Option<String>,
+    model: Option<String>,
+    #[serde(default)]
+    choices: Vec<ChatCompletionChoice>,
+    usage: Option<ChatCompletionUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatCompletionChoice {
+    message: ChatCompletionMessage,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatCompletionMessage {
+    content: Option<String>,
+    #[serde(default)]
+    tool_calls: Vec<ChatToolCall>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatCompletionUsage {
+    prompt_tokens: Option<Number>,
+    completion_tokens: Option<Number>,
+    total_tokens: Option<Number>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ModelListResponse {
+    #[serde(default)]
+    data: Vec<ModelListEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ModelListEntry {
+    id: String,
+}
+
+fn parse_capabilities(value: &str) -> Result<Vec<AssistantCapability>, LocalLlmConfigError> {
+    let mut capabilities = Vec::new();
+    for name in value
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+    {
+        let capability =
+            serde_json::from_value::<AssistantCapability>(Value::String(name.to_string()))
+                .map_err(|_| {
+                    LocalLlmConfigError::InvalidConfiguration(format!(
+                        "LOCAL_LLM_CAPABILITIES names unknown capability '{name}'"
+                    ))
+                })?;
+        if !capabilities.contains(&capability) {
+            capabilities.push(capability);
+        }
+    }
+    if capabilities.is_empty() {
+        return Err(LocalLlmConfigError::InvalidConfiguration(
+            "LOCAL_LLM_CAPABILITIES must name at least one capability".to_string(),
+        ));
+    }
+    Ok(capabilities)
+}
+
+fn is_loopback_url(value: &str) -> bool {
+    let Ok(url) = reqwest::Url::parse(value) else {
+        return false;
+    };
+    match url.host_str() {
+        Some("localhost") => true,
+        Some(host) => host
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse::<IpAddr>()
+            .is_ok_and(|address| address.is_loopback()),
+        None => false,
+    }
+}
+
+fn require_non_empty_env(key: &str) -> Result<String, LocalLlmConfigError> {
+    optional_trimmed_env(key).ok_or_else(|| LocalLlmConfigError::MissingVar(key.to_string()))
+}
+
+fn parse_u64_env(key: &str, default: u64) -> Result<u64, LocalLlmConfigError> {
+    match optional_trimmed_env(key) {
+        Some(value) => value
+            .parse::<u64>()
+            .map_err(|_| LocalLlmConfigError::ParseInt {
+                key: key.to_string(),
+                value,
+            }),
+        None => Ok(default),
+    }
+}
+
+fn parse_u32_env(key: &str, default: u32) -> Result<u32, LocalLlmConfigError> {
+    match optional_trimmed_env(key) {
+        Some(value) => value
+            .parse::<u32>()
+            .map_err(|_| LocalLlmConfigError::ParseInt {
+                key: key.to_string(),
+                value,
+            }),
+        None => Ok(default),
+    }
+}
+
+fn parse_bool_env(key: &str, default: bool) -> Result<bool, LocalLlmConfigError> {
+    match optional_trimmed_env(key) {
+        Some(value) => match value.to_ascii_lowercase().as_str() {
+            "true" | "1" | "yes" | "on" => Ok(true),
+            "false" | "0" | "no" | "off" => Ok(false),
+            _ => Err(LocalLlmConfigError::InvalidConfiguration(format!(
+                "{key} must be a boolean value"
+            ))),
+        },
+        None => Ok(default),
+    }
+}
+
+fn optional_trimmed_env(key: &str) -> Option<String> {
+    env::var(key).ok().and_then(|value| {
+        let trimmed = value.trim();
+        if trimmed.is_empty() {
+            None
+        } else {
+            Some(trimmed.to_string())
+        }
+    })
+}
+
+fn send_error(err: reqwest::Error) -> SendAttemptError {
+    if err.is_timeout() {
+        SendAttemptError::retryable(LlmGatewayError::Timeout)
+    } else {
+        SendAttemptError::retryable(LlmGatewayError::ProviderFailure(
+            "request_unavailable".to_string(),
+        ))
+    }
+}
+
+fn provider_status_error(status: StatusCode, body: &str) -> SendAttemptError {
+    SendAttemptError {
+        error: LlmGatewayError::ProviderFailure(format!(
+            "status={} code={}",
+            status.as_u16(),
+            parse_provider_error_code(body)
+        )),
+        retryable: is_retryable_status(status),
+    }
+}
+
+/// vLLM mirrors OpenAI's `{"error": {"type": ...}}`; Ollama sends `{"error": "message"}`, which
+/// is free text and not worth echoing into logs.
+fn parse_provider_error_code(body: &str) -> String {
+    serde_json::from_str::<Value>(body)
+        .ok()
+        .and_then(|value| {
+            value
+                .get("error")
+                .and_then(|error| error.get("type"))
+                .and_then(Value::as_str)
+                .map(ToString::to_string)
+        })
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_loopback_url, parse_capabilities};
+    use crate::llm::contracts::AssistantCapability;
+
+    #[test]
+    fn only_loopback_hosts_count_as_enclave_local() {
+        assert!(is_loopback_url("http://127.0.0.1:11434/v1"));
+        assert!(is_loopback_url("http://localhost:8000/v1"));
+        assert!(is_loopback_url("http://[::1]:8000/v1"));
+        assert!(!is_loopback_url("http://ollama:11434/v1"));
+        assert!(!is_loopback_url("https://10.0.0.5/v1"));
+        assert!(!is_loopback_url("not a url"));
+    }
+
+    #[test]
+    fn capabilities_parse_by_contract_name() {
+        let capabilities = parse_capabilities(
+            "assistant_semantic_plan, general_chat_summary,assistant_semantic_plan",
+        )
+        .expect("known capabilities should parse");
+
+        assert_eq!(
+            capabilities,
+            vec![
+                AssistantCapability::AssistantSemanticPlan,
+                AssistantCapability::GeneralChatSummary,
+            ]
+        );
+        assert!(parse_capabilities("assistant_semantic_plan,poetry").is_err());
+        assert!(parse_capabilities(" , ").is_err());
+    }
+}
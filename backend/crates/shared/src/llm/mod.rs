@@ -1,34 +1,58 @@
+pub mod anthropic;
+pub mod bedrock;
 pub mod context;
 pub mod contracts;
 pub mod gateway;
+pub mod local;
 pub mod observability;
+pub mod openai;
 pub mod openrouter;
 pub mod prompts;
 pub mod reliability;
+pub mod routing;
 pub mod safety;
+pub mod style;
 pub mod validation;
 
+pub use anthropic::{AnthropicConfigError, AnthropicGateway, AnthropicGatewayConfig};
+pub use bedrock::{BedrockConfigError, BedrockGateway, BedrockGatewayConfig};
 pub use context::{
     CONTEXT_CONTRACT_VERSION_V1, GoogleCalendarMeetingSource, GoogleEmailCandidateSource,
     MeetingContextEntry, MeetingsTodayContext, MorningBriefContext,
-    UrgentEmailCandidateContextEntry, UrgentEmailCandidatesContext,
-    assemble_meetings_today_context, assemble_morning_brief_context,
-    assemble_urgent_email_candidates_context,
+    UrgentEmailCandidateContextEntry, UrgentEmailCandidatesContext, WEEKLY_REVIEW_DAYS,
+    WeeklyReviewContext, assemble_meetings_today_context, assemble_morning_brief_context,
+    assemble_urgent_email_candidates_context, assemble_weekly_review_context,
 };
 pub use contracts::{
     AssistantCapability, AssistantOutputContract, ChatResponseStyle, ContractError,
     GeneralChatSummaryContract, MeetingsSummaryContract, MorningBriefContract,
-    UrgentEmailSummaryContract, output_schema,
+    UrgentEmailSummaryContract, WeeklyReviewContract, output_schema,
 };
-pub use gateway::{LlmGateway, LlmGatewayError, LlmGatewayRequest, LlmGatewayResponse};
+pub use gateway::{
+    LlmGateway, LlmGatewayError, LlmGatewayRequest, LlmGatewayResponse, LlmProviderRole,
+    LlmToolCall, LlmToolDefinition, LlmWarmUpFuture,
+};
+pub use local::{LocalLlmConfigError, LocalLlmGateway, LocalLlmGatewayConfig};
 pub use observability::{LlmExecutionSource, LlmTelemetryEvent, generate_with_telemetry};
+pub use openai::{OpenAiConfigError, OpenAiGateway, OpenAiGatewayConfig};
 pub use openrouter::{
     OpenRouterConfigError, OpenRouterGateway, OpenRouterGatewayConfig, OpenRouterModelRoute,
 };
 pub use prompts::{PromptTemplate, template_for_capability};
 pub use reliability::{
-    LlmReliabilityConfig, LlmReliabilityConfigError, ReliableGatewayBuildError,
-    ReliableOpenRouterGateway,
+    LlmProviderGateway, LlmReliabilityConfig, LlmReliabilityConfigError, LlmSpendBudget,
+    LlmSpendBudgetFuture, LlmSpendBudgetStatus, PlanLlmSpendBudget, ReliableGatewayBuildError,
+    ReliableProviderGateway,
+};
+pub use routing::{
+    KNOWN_LLM_MODELS, LlmModelRouteConfig, LlmRouteProfile, LlmRoutingConfig, LlmRoutingConfigError,
 };
 pub use safety::{SafeOutputSource, resolve_safe_output, sanitize_context_payload};
-pub use validation::{OutputValidationError, validate_output_json, validate_output_value};
+pub use style::{
+    LlmBulletPreference, LlmResponseTone, LlmStyleConfig, LlmStyleConfigError, LlmStyleProfile,
+    StyledLlmGateway,
+};
+pub use validation::{
+    OutputValidationError, ToolCallValidationError, validate_output_json, validate_output_value,
+    validate_tool_calls,
+};
@@ -156,6 +156,8 @@ fn capability_label(capability: AssistantCapability) -> &'static str {
         AssistantCapability::MorningBrief => "morning_brief",
         AssistantCapability::UrgentEmailSummary => "urgent_email_summary",
         AssistantCapability::AssistantSemanticPlan => "assistant_semantic_plan",
+        AssistantCapability::WeeklyReview => "weekly_review",
+        AssistantCapability::AutomationDraft => "automation_draft",
     }
 }
 
@@ -174,6 +176,8 @@ fn error_type(error: &LlmGatewayError) -> &'static str {
         LlmGatewayError::Timeout => "timeout",
         LlmGatewayError::ProviderFailure(_) => "provider_failure",
         LlmGatewayError::InvalidProviderPayload(_) => "invalid_provider_payload",
+        LlmGatewayError::RateLimited(_) => "rate_limited",
+        LlmGatewayError::BudgetExceeded(_) => "budget_exceeded",
     }
 }
 
@@ -0,0 +1,603 @@
+use std::env;
+use std::time::Duration;
+
+use reqwest::StatusCode;
+use serde::Deserialize;
+use serde_json::{Map, Number, Value, json};
+use thiserror::Error;
+use tokio::time::sleep;
+
+use super::contracts::output_schema;
+use super::gateway::{
+    LlmGateway, LlmGatewayError, LlmGatewayFuture, LlmGatewayRequest, LlmGatewayResponse,
+    LlmProviderRole, LlmTokenUsage, LlmWarmUpFuture,
+};
+use super::openrouter::{
+    ChatToolCall, chat_tools_payload, is_retryable_status, parse_chat_tool_calls, parse_token_count,
+};
+use crate::outbound_rate_limit::{OutboundCallLimiter, OutboundProvider};
+
+const DEFAULT_CHAT_COMPLETIONS_URL: &str = "https://api.openai.com/v1/chat/completions";
+const DEFAULT_MODEL: &str = "gpt-4o-mini";
+const DEFAULT_TIMEOUT_MS: u64 = 15_000;
+const DEFAULT_MAX_RETRIES: u32 = 2;
+const DEFAULT_RETRY_BASE_BACKOFF_MS: u64 = 250;
+const DEFAULT_MAX_OUTPUT_TOKENS: u32 = 600;
+const DEFAULT_ALLOW_INSECURE_HTTP: bool = false;
+const WARM_UP_PROMPT: &str = "ping";
+const WARM_UP_MAX_OUTPUT_TOKENS: u32 = 1;
+/// Keywords schemars emits that strict structured outputs reject.
+const UNSUPPORTED_SCHEMA_KEYWORDS: [&str; 4] = ["$schema", "title", "default", "format"];
+
+#[derive(Debug, Clone)]
+pub struct OpenAiGatewayConfig {
+    pub chat_completions_url: String,
+    pub api_key: String,
+    pub organization: Option<String>,
+    pub role: LlmProviderRole,
+    pub model: String,
+    pub temperature: f32,
+    pub timeout_ms: u64,
+    pub max_retries: u32,
+    pub retry_base_backoff_ms: u64,
+    pub max_output_tokens: u32,
+    pub allow_insecure_http: bool,
+}
+
+impl OpenAiGatewayConfig {
+    /// Returns `None` unless `OPENAI_PROVIDER_ROLE` is set; the gateway is opt-in, so an
+    /// OpenRouter-only deployment needs no OpenAI credentials.
+    pub fn from_env() -> Result<Option<Self>, OpenAiConfigError> {
+        let Some(role) = optional_trimmed_env("OPENAI_PROVIDER_ROLE") else {
+            return Ok(None);
+        };
+        let role = LlmProviderRole::parse(&role).ok_or_else(|| {
+            OpenAiConfigError::InvalidConfiguration(format!(
+                "OPENAI_PROVIDER_ROLE must be primary or fallback, got {role}"
+            ))
+        })?;
+        let api_key = require_non_empty_env("OPENAI_API_KEY")?;
+        let chat_completions_url = optional_trimmed_env("OPENAI_CHAT_COMPLETIONS_URL")
+            .unwrap_or_else(|| DEFAULT_CHAT_COMPLETIONS_URL.to_string());
+        let allow_insecure_http =
+            parse_bool_env("OPENAI_ALLOW_INSECURE_HTTP", DEFAULT_ALLOW_INSECURE_HTTP)?;
+        let uses_https = chat_completions_url.starts_with("https://");
+        let uses_insecure_http = allow_insecure_http && chat_completions_url.starts_with("http://");
+        if !(uses_https || uses_insecure_http) {
+            return Err(OpenAiConfigError::InvalidConfiguration(
+                "OPENAI_CHAT_COMPLETIONS_URL must use https:// (or set OPENAI_ALLOW_INSECURE_HTTP=true for local development)"
+                    .to_string(),
+            ));
+        }
+
+        Ok(Some(Self {
+            chat_completions_url,
+            api_key,
+            organization: optional_trimmed_env("OPENAI_ORGANIZATION"),
+            role,
+            model: optional_trimmed_env("OPENAI_MODEL")
+                .unwrap_or_else(|| DEFAULT_MODEL.to_string()),
+            temperature: 0.0,
+            timeout_ms: parse_u64_env("OPENAI_TIMEOUT_MS", DEFAULT_TIMEOUT_MS)?,
+            max_retries: parse_u32_env("OPENAI_MAX_RETRIES", DEFAULT_MAX_RETRIES)?,
+            retry_base_backoff_ms: parse_u64_env(
+                "OPENAI_RETRY_BASE_BACKOFF_MS",
+                DEFAULT_RETRY_BASE_BACKOFF_MS,
+            )?,
+            max_output_tokens: parse_u32_env(
+                "OPENAI_MAX_OUTPUT_TOKENS",
+                DEFAULT_MAX_OUTPUT_TOKENS,
+            )?,
+            allow_insecure_http,
+        }))
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum OpenAiConfigError {
+    #[error("missing required env var {0}")]
+    MissingVar(String),
+    #[error("invalid integer in env var {key}: {value}")]
+    ParseInt { key: String, value: String },
+    #[error("invalid configuration: {0}")]
+    InvalidConfiguration(String),
+    #[error("failed to build OpenAI http client: {0}")]
+    HttpClient(String),
+}
+
+/// Calls the OpenAI chat completions API directly with strict structured outputs, so the
+/// provider itself enforces the capability's output contract. Routing and fallback between
+/// providers live in the reliability layer, so this gateway only retries its one model.
+#[derive(Clone)]
+pub struct OpenAiGateway {
+    client: reqwest::Client,
+    config: OpenAiGatewayConfig,
+    outbound_limiter: OutboundCallLimiter,
+}
+
+impl OpenAiGateway {
+    pub fn new(config: OpenAiGatewayConfig) -> Result<Self, OpenAiConfigError> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(config.timeout_ms))
+            .build()
+            .map_err(|err| OpenAiConfigError::HttpClient(err.to_string()))?;
+
+        Ok(Self {
+            client,
+            config,
+            outbound_limiter: OutboundCallLimiter::unlimited(),
+        })
+    }
+
+    /// Routes completions through the enclave-wide outbound limiter. Warm-up probes bypass it.
+    pub fn with_outbound_limiter(mut self, outbound_limiter: OutboundCallLimiter) -> Self {
+        self.outbound_limiter = outbound_limiter;
+        self
+    }
+
+    pub fn role(&self) -> LlmProviderRole {
+        self.config.role
+    }
+
+    async fn send_once(
+        &self,
+        request: &LlmGatewayRequest,
+    ) -> Result<LlmGatewayResponse, SendAttemptError> {
+        let user_prompt = json!({
+            "instruction": request.context_prompt,
+            "contract_version": request.contract_version,
+            "context_payload": request.context_payload,
+        })
+        .to_string();
+
+        self.outbound_limiter
+            .acquire(OutboundProvider::OpenAi)
+            .await
+            .map_err(|err| {
+                SendAttemptError::non_retryable(LlmGatewayError::RateLimited(format!(
+                    "outbound_rate_limited retry_after_ms={}",
+                    err.retry_after_ms()
+                )))
+            })?;
+
+        let mut request_body = json!({
+            "model": self.config.model,
+            "messages": [
+                { "role": "system", "content": request.system_prompt },
+                { "role": "user", "content": user_prompt }
+            ],
+            "response_format": {
+                "type": "json_schema",
+                "json_schema": {
+                    "name": request.capability.as_str(),
+                    "strict": true,
+                    "schema": strict_json_schema(output_schema(request.capability))
+                }
+            },
+            "temperature": self.config.temperature,
+            "max_completion_tokens": self.config.max_output_tokens
+        });
+        if !request.tools.is_empty() {
+            request_body["tools"] = chat_tools_payload(&request.tools);
+        }
+        let response = self
+            .chat_completions_request()
+            .json(&request_body)
+            .send()
+            .await
+            .map_err(send_error)?;
+
+        let status = response.status();
+        let header_request_id = header_request_id(response.headers());
+        let body = response.text().await.map_err(|_| {
+            SendAttemptError::non_retryable(LlmGatewayError::InvalidProviderPayload(
+                "response_body_read_failed".to_string(),
+            ))
+        })?;
+
+        if !status.is_success() {
+            return Err(provider_status_error(status, &body));
+        }
+
+        let parsed: OpenAiSuccessResponse = serde_json::from_str(&body).map_err(|_| {
+            SendAttemptError::non_retryable(LlmGatewayError::InvalidProviderPayload(
+                "response_json_parse_failed".to_string(),
+            ))
+        })?;
+
+        let message = parsed
+            .choices
+            .into_iter()
+            .next()
+            .ok_or_else(|| {
+                SendAttemptError::non_retryable(LlmGatewayError::InvalidProviderPayload(
+                    "missing_choice".to_string(),
+                ))
+            })?
+            .message;
+        // A refusal carries no content, and schema-constrained output cannot explain itself.
+        if message.refusal.is_some() {
+            return Err(SendAttemptError::non_retryable(
+                LlmGatewayError::InvalidProviderPayload("model_refused".to_string()),
+            ));
+        }
+        let tool_calls =
+            parse_chat_tool_calls(message.tool_calls).map_err(SendAttemptError::non_retryable)?;
+        let output = match message
+            .content
+            .as_deref()
+            .and_then(|raw| serde_json::from_str::<Value>(raw).ok())
+        {
+            Some(output) => output,
+            None if !tool_calls.is_empty() => Value::Null,
+            None => {
+                return Err(SendAttemptError::non_retryable(
+                    LlmGatewayError::InvalidProviderPayload("content_not_json".to_string()),
+                ));
+            }
+        };
+
+        Ok(LlmGatewayResponse {
+            model: parsed.model.unwrap_or_else(|| self.config.model.clone()),
+            provider_request_id: header_request_id.or(parsed.id),
+            output,
+            tool_calls,
+            usage: parsed.usage.map(|usage| LlmTokenUsage {
+                prompt_tokens: parse_token_count(usage.prompt_tokens),
+                completion_tokens: parse_token_count(usage.completion_tokens),
+                total_tokens: parse_token_count(usage.total_tokens),
+            }),
+        })
+    }
+
+    /// Sends the smallest completion the provider accepts. Only the HTTP status matters, so
+    /// the single output token is never parsed.
+    async fn warm_up_once(&self) -> Result<(), SendAttemptError> {
+        let request_body = json!({
+            "model": self.config.model,
+            "messages": [
+                { "role": "user", "content": WARM_UP_PROMPT }
+            ],
+            "max_completion_tokens": WARM_UP_MAX_OUTPUT_TOKENS
+        });
+        let response = self
+            .chat_completions_request()
+            .json(&request_body)
+            .send()
+            .await
+            .map_err(send_error)?;
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+
+        let body = response.text().await.unwrap_or_default();
+        Err(provider_status_error(status, &body))
+    }
+
+    fn chat_completions_request(&self) -> reqwest::RequestBuilder {
+        let mut request_builder = self
+            .client
+            .post(&self.config.chat_completions_url)
+            .bearer_auth(&self.config.api_key);
+        if let Some(organization) = self.config.organization.as_deref() {
+            request_builder = request_builder.header("OpenAI-Organization", organization);
+        }
+        request_builder
+    }
+}
+
+impl LlmGateway for OpenAiGateway {
+    fn generate<'a>(&'a self, request: LlmGatewayRequest) -> LlmGatewayFuture<'a> {
+        Box::pin(async move {
+            let mut attempt = 0_u32;
+
+            loop {
+                match self.send_once(&request).await {
+                    Ok(response) => return Ok(response),
+                    Err(err) => {
+                        if err.retryable && attempt < self.config.max_retries {
+                            let backoff_multiplier = 2_u64.saturating_pow(attempt);
+                            let backoff_ms = self
+                                .config
+                                .retry_base_backoff_ms
+                                .saturating_mul(backoff_multiplier);
+                            sleep(Duration::from_millis(backoff_ms)).await;
+                            attempt = attempt.saturating_add(1);
+                            continue;
+                        }
+
+                        return Err(err.error);
+                    }
+                }
+            }
+        })
+    }
+
+    fn warm_up<'a>(&'a self) -> LlmWarmUpFuture<'a> {
+        Box::pin(async move { self.warm_up_once().await.map_err(|err| err.error) })
+    }
+}
+
+/// Rewrites a schemars contract schema into the subset strict structured outputs accept:
+/// `definitions` become `$defs`, every object closes with all of its properties required
+/// (optional fields are already nullable, so the model sends `null` for them), `oneOf` becomes
+/// `anyOf`, and keywords the provider rejects are dropped.
+fn strict_json_schema(schema: Value) -> Value {
+    match schema {
+        Value::Object(object) => {
+            let mut strict = Map::new();
+            for (key, value) in object {
+                if UNSUPPORTED_SCHEMA_KEYWORDS.contains(&key.as_str()) {
+                    continue;
+                }
+                let key = match key.as_str() {
+                    "definitions" => "$defs".to_string(),
+                    "oneOf" => "anyOf".to_string(),
+                    _ => key,
+                };
+                let value = match (key.as_str(), value) {
+                    ("$ref", Value::String(reference)) => {
+                        Value::String(reference.replacen("#/definitions/", "#/$defs/", 1))
+                    }
+                    // Property and definition maps are keyed by name, not schema keywords.
+                    ("properties" | "$defs", Value::Object(named)) => Value::Object(
+                        named
+                            .into_iter()
+                            .map(|(name, schema)| (name, strict_json_schema(schema)))
+                            .collect(),
+                    ),
+                    (_, value) => strict_json_schema(value),
+                };
+                strict.insert(key, value);
+            }
+
+            if let Some(Value::Object(properties)) = strict.get("properties") {
+                let required = properties
+                    .keys()
+                    .map(|name| Value::String(name.clone()))
+                    .collect();
+                strict.insert("required".to_string(), Value::Array(required));
+                strict.insert("additionalProperties".to_string(), Value::Bool(false));
+            }
+            Value::Object(strict)
+        }
+        Value::Array(items) => Value::Array(items.into_iter().map(strict_json_schema).collect()),
+        value => value,
+    }
+}
+
+#[derive(Debug)]
+struct SendAttemptError {
+    error: LlmGatewayError,
+    retryable: bool,
+}
+
+impl SendAttemptError {
+    fn retryable(error: LlmGatewayError) -> Self {
+        Self {
+            error,
+            retryable: true,
+        }
+    }
+
+    fn non_retryable(error: LlmGatewayError) -> Self {
+        Self {
+            error,
+            retryable: false,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiSuccessResponse {
+    id: Option<String>,
+    model: Option<String>,
+    choices: Vec<OpenAiChoice>,
+    usage: Option<OpenAiUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiChoice {
+    message: OpenAiMessage,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiMessage {
+    content: Option<String>,
+    refusal: Option<String>,
+    #[serde(default)]
+    tool_calls: Vec<ChatToolCall>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiUsage {
+    prompt_tokens: Option<Number>,
+    completion_tokens: Option<Number>,
+    total_tokens: Option<Number>,
+}
+
+fn require_non_empty_env(key: &str) -> Result<String, OpenAiConfigError> {
+    optional_trimmed_env(key).ok_or_else(|| OpenAiConfigError::MissingVar(key.to_string()))
+}
+
+fn parse_u64_env(key: &str, default: u64) -> Result<u64, OpenAiConfigError> {
+    match optional_trimmed_env(key) {
+        Some(value) => value
+            .parse::<u64>()
+            .map_err(|_| OpenAiConfigError::ParseInt {
+                key: key.to_string(),
+                value,
+            }),
+        None => Ok(default),
+    }
+}
+
+fn parse_u32_env(key: &str, default: u32) -> Result<u32, OpenAiConfigError> {
+    match optional_trimmed_env(key) {
+        Some(value) => value
+            .parse::<u32>()
+            .map_err(|_| OpenAiConfigError::ParseInt {
+                key: key.to_string(),
+                value,
+            }),
+        None => Ok(default),
+    }
+}
+
+fn parse_bool_env(key: &str, default: bool) -> Result<bool, OpenAiConfigError> {
+    match optional_trimmed_env(key) {
+        Some(value) => match value.to_ascii_lowercase().as_str() {
+            "true" | "1" | "yes" | "on" => Ok(true),
+            "false" | "0" | "no" | "off" => Ok(false),
+            _ => Err(OpenAiConfigError::InvalidConfiguration(format!(
+                "{key} must be a boolean value"
+            ))),
+        },
+        None => Ok(default),
+    }
+}
+
+fn optional_trimmed_env(key: &str) -> Option<String> {
+    env::var(key).ok().and_then(|value| {
+        let trimmed = value.trim();
+        if trimmed.is_empty() {
+            None
+        } else {
+            Some(trimmed.to_string())
+        }
+    })
+}
+
+fn send_error(err: reqwest::Error) -> SendAttemptError {
+    if err.is_timeout() {
+        SendAttemptError::retryable(LlmGatewayError::Timeout)
+    } else {
+        SendAttemptError::retryable(LlmGatewayError::ProviderFailure(
+            "request_unavailable".to_string(),
+        ))
+    }
+}
+
+fn provider_status_error(status: StatusCode, body: &str) -> SendAttemptError {
+    SendAttemptError {
+        error: LlmGatewayError::ProviderFailure(format!(
+            "status={} code={}",
+            status.as_u16(),
+            parse_provider_error_code(body)
+        )),
+        retryable: is_retryable_status(status),
+    }
+}
+
+fn header_request_id(headers: &reqwest::header::HeaderMap) -> Option<String> {
+    headers
+        .get("x-request-id")
+        .and_then(|value| value.to_str().ok())
+        .map(ToString::to_string)
+}
+
+/// OpenAI errors carry a `code` that is often null, so the error `type` is the fallback.
+fn parse_provider_error_code(body: &str) -> String {
+    #[derive(Deserialize)]
+    struct ProviderErrorEnvelope {
+        error: Option<ProviderErrorDetails>,
+    }
+
+    #[derive(Deserialize)]
+    struct ProviderErrorDetails {
+        code: Option<String>,
+        #[serde(rename = "type")]
+        error_type: Option<String>,
+    }
+
+    serde_json::from_str::<ProviderErrorEnvelope>(body)
+        .ok()
+        .and_then(|envelope| envelope.error)
+        .and_then(|details| details.code.or(details.error_type))
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::{UNSUPPORTED_SCHEMA_KEYWORDS, strict_json_schema};
+    use crate::llm::contracts::{AssistantCapability, output_schema};
+
+    const ALL_CAPABILITIES: [AssistantCapability; 7] = [
+        AssistantCapability::MeetingsSummary,
+        AssistantCapability::GeneralChatSummary,
+        AssistantCapability::MorningBrief,
+        AssistantCapability::UrgentEmailSummary,
+        AssistantCapability::AssistantSemanticPlan,
+        AssistantCapability::WeeklyReview,
+        AssistantCapability::AutomationDraft,
+    ];
+
+    fn assert_strict(schema: &Value, path: &str) {
+        match schema {
+            Value::Object(object) => {
+                for keyword in UNSUPPORTED_SCHEMA_KEYWORDS
+                    .iter()
+                    .chain(["definitions", "oneOf"].iter())
+                {
+                    assert!(!object.contains_key(*keyword), "{path} keeps {keyword}");
+                }
+                if let Some(Value::Object(properties)) = object.get("properties") {
+                    let required = object["required"]
+                        .as_array()
+                        .unwrap_or_else(|| panic!("{path} has no required list"));
+                    assert_eq!(required.len(), properties.len(), "{path}");
+                    assert_eq!(object["additionalProperties"], json!(false), "{path}");
+                }
+                if let Some(Value::String(reference)) = object.get("$ref") {
+                    assert!(reference.starts_with("#/$defs/"), "{path} refs {reference}");
+                }
+                for (key, value) in object {
+                    match (key.as_str(), value) {
+                        ("properties" | "$defs", Value::Object(named)) => {
+                            for (name, schema) in named {
+                                assert_strict(schema, &format!("{path}/{key}/{name}"));
+                            }
+                        }
+                        _ => assert_strict(value, &format!("{path}/{key}")),
+                    }
+                }
+            }
+            Value::Array(items) => {
+                for (index, item) in items.iter().enumerate() {
+                    assert_strict(item, &format!("{path}/{index}"));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn every_contract_schema_converts_to_a_strict_schema() {
+        for capability in ALL_CAPABILITIES {
+            let schema = strict_json_schema(output_schema(capability));
+            assert_strict(&schema, capability.as_str());
+        }
+    }
+
+    #[test]
+    fn optional_fields_become_required_but_stay_nullable() {
+        let schema = strict_json_schema(output_schema(AssistantCapability::AutomationDraft));
+        let draft = &schema["$defs"]["AutomationDraftOutput"];
+        let required = draft["required"]
+            .as_array()
+            .expect("draft output should list required fields");
+        assert!(required.contains(&json!("clarifying_question")));
+        assert_eq!(
+            draft["properties"]["clarifying_question"]["type"],
+            json!(["string", "null"])
+        );
+        assert_eq!(
+            draft["properties"]["schedule"]["anyOf"][0]["$ref"],
+            json!("#/$defs/AutomationDraftScheduleOutput")
+        );
+    }
+}
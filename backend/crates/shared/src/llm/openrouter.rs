@@ -9,8 +9,10 @@ use tokio::time::sleep;
 
 use super::gateway::{
     LlmGateway, LlmGatewayError, LlmGatewayFuture, LlmGatewayRequest, LlmGatewayResponse,
-    LlmTokenUsage,
+    LlmTokenUsage, LlmToolCall, LlmToolDefinition, LlmWarmUpFuture,
 };
+use super::routing::{LlmModelRouteConfig, LlmRouteProfile, LlmRoutingConfig};
+use crate::outbound_rate_limit::{OutboundCallLimiter, OutboundProvider};
 
 const DEFAULT_CHAT_COMPLETIONS_URL: &str = "https://openrouter.ai/api/v1/chat/completions";
 const DEFAULT_TIMEOUT_MS: u64 = 15_000;
@@ -18,14 +20,24 @@ const DEFAULT_MAX_RETRIES: u32 = 2;
 const DEFAULT_RETRY_BASE_BACKOFF_MS: u64 = 250;
 const DEFAULT_MAX_OUTPUT_TOKENS: u32 = 600;
 const DEFAULT_ALLOW_INSECURE_HTTP: bool = false;
-
-const DEFAULT_PRIMARY_MODEL: &str = "openai/gpt-4o-mini";
-const DEFAULT_FALLBACK_MODEL: &str = "anthropic/claude-3.5-haiku";
+const WARM_UP_PROMPT: &str = "ping";
+const WARM_UP_MAX_OUTPUT_TOKENS: u32 = 1;
 
 #[derive(Debug, Clone)]
 pub struct OpenRouterModelRoute {
     pub primary_model: String,
     pub fallback_model: Option<String>,
+    pub temperature: f32,
+}
+
+impl From<&LlmModelRouteConfig> for OpenRouterModelRoute {
+    fn from(route: &LlmModelRouteConfig) -> Self {
+        Self {
+            primary_model: route.primary_model.clone(),
+            fallback_model: route.fallback_model.clone(),
+            temperature: route.temperature,
+        }
+    }
 }
 
 impl OpenRouterModelRoute {
@@ -77,6 +89,8 @@ impl OpenRouterGatewayConfig {
                     .to_string(),
             ));
         }
+        let routing = LlmRoutingConfig::from_env()
+            .map_err(|err| OpenRouterConfigError::InvalidConfiguration(err.to_string()))?;
 
         Ok(Self {
             chat_completions_url,
@@ -94,7 +108,7 @@ impl OpenRouterGatewayConfig {
                 DEFAULT_MAX_OUTPUT_TOKENS,
             )?,
             allow_insecure_http,
-            model_route: parse_model_route(),
+            model_route: routing.route(LlmRouteProfile::Worker).into(),
         })
     }
 }
@@ -115,6 +129,7 @@ pub enum OpenRouterConfigError {
 pub struct OpenRouterGateway {
     client: reqwest::Client,
     config: OpenRouterGatewayConfig,
+    outbound_limiter: OutboundCallLimiter,
 }
 
 impl OpenRouterGateway {
@@ -124,7 +139,17 @@ impl OpenRouterGateway {
             .build()
             .map_err(|err| OpenRouterConfigError::HttpClient(err.to_string()))?;
 
-        Ok(Self { client, config })
+        Ok(Self {
+            client,
+            config,
+            outbound_limiter: OutboundCallLimiter::unlimited(),
+        })
+    }
+
+    /// Routes completions through the enclave-wide outbound limiter. Warm-up probes bypass it.
+    pub fn with_outbound_limiter(mut self, outbound_limiter: OutboundCallLimiter) -> Self {
+        self.outbound_limiter = outbound_limiter;
+        self
     }
 
     async fn generate_for_model(
@@ -171,45 +196,42 @@ impl OpenRouterGateway {
         })
         .to_string();
 
-        let request_body = json!({
+        // Every model shares the same provider quota, so a rejected call skips the fallback.
+        self.outbound_limiter
+            .acquire(OutboundProvider::OpenRouter)
+            .await
+            .map_err(|err| {
+                SendAttemptError::non_retryable(
+                    LlmGatewayError::RateLimited(format!(
+                        "outbound_rate_limited retry_after_ms={}",
+                        err.retry_after_ms()
+                    )),
+                    false,
+                )
+            })?;
+
+        let mut request_body = json!({
             "model": model,
             "messages": [
                 { "role": "system", "content": request.system_prompt },
                 { "role": "user", "content": user_prompt }
             ],
-            "response_format": {
-                "type": "json_object"
-            },
-            "temperature": 0,
+            "temperature": self.config.model_route.temperature,
             "max_tokens": self.config.max_output_tokens
         });
-        let mut request_builder = self
-            .client
-            .post(&self.config.chat_completions_url)
-            .bearer_auth(&self.config.api_key);
-        if let Some(http_referer) = self.config.app_http_referer.as_deref() {
-            request_builder = request_builder.header("HTTP-Referer", http_referer);
-        }
-        if let Some(app_title) = self.config.app_title.as_deref() {
-            request_builder = request_builder.header("X-Title", app_title);
+        // JSON mode keeps many routed models from emitting tool calls, so it is only requested
+        // when there are no tools to call.
+        if request.tools.is_empty() {
+            request_body["response_format"] = json!({ "type": "json_object" });
+        } else {
+            request_body["tools"] = chat_tools_payload(&request.tools);
         }
-        let response = request_builder
+        let response = self
+            .chat_completions_request()
             .json(&request_body)
             .send()
             .await
-            .map_err(|err| {
-                if err.is_timeout() {
-                    SendAttemptError::retryable(
-                        LlmGatewayError::Timeout,
-                        true, // allow fallback to alternate model on timeout.
-                    )
-                } else {
-                    SendAttemptError::retryable(
-                        LlmGatewayError::ProviderFailure("request_unavailable".to_string()),
-                        true,
-                    )
-                }
-            })?;
+            .map_err(send_error)?;
 
         let status = response.status();
         let header_request_id = header_request_id(response.headers());
@@ -221,18 +243,7 @@ impl OpenRouterGateway {
         })?;
 
         if !status.is_success() {
-            let provider_code = parse_provider_error_code(&body);
-            let is_retryable = is_retryable_status(status);
-            let fallback_allowed =
-                status != StatusCode::UNAUTHORIZED && status != StatusCode::FORBIDDEN;
-            return Err(SendAttemptError {
-                error: LlmGatewayError::ProviderFailure(format!(
-                    "status={} code={provider_code}",
-                    status.as_u16()
-                )),
-                retryable: is_retryable,
-                fallback_allowed,
-            });
+            return Err(provider_status_error(status, &body));
         }
 
         let parsed: OpenRouterSuccessResponse = serde_json::from_str(&body).map_err(|_| {
@@ -242,26 +253,33 @@ impl OpenRouterGateway {
             )
         })?;
 
-        let content = parsed
+        let message = parsed
             .choices
-            .first()
+            .into_iter()
+            .next()
             .ok_or_else(|| {
                 SendAttemptError::non_retryable(
                     LlmGatewayError::InvalidProviderPayload("missing_choice".to_string()),
                     true,
                 )
             })?
-            .message
-            .content
-            .clone();
-
-        let output = match content {
-            Value::String(raw) => serde_json::from_str::<Value>(&raw).map_err(|_| {
-                SendAttemptError::non_retryable(
-                    LlmGatewayError::InvalidProviderPayload("content_not_json".to_string()),
-                    true,
-                )
-            })?,
+            .message;
+        let tool_calls = parse_chat_tool_calls(message.tool_calls)
+            .map_err(|err| SendAttemptError::non_retryable(err, true))?;
+
+        // Prose alongside a tool call is commentary, not a contract answer.
+        let output = match message.content {
+            Value::Null if !tool_calls.is_empty() => Value::Null,
+            Value::String(raw) => match serde_json::from_str::<Value>(&raw) {
+                Ok(output) => output,
+                Err(_) if !tool_calls.is_empty() => Value::Null,
+                Err(_) => {
+                    return Err(SendAttemptError::non_retryable(
+                        LlmGatewayError::InvalidProviderPayload("content_not_json".to_string()),
+                        true,
+                    ));
+                }
+            },
             value @ (Value::Object(_) | Value::Array(_)) => value,
             _ => {
                 return Err(SendAttemptError::non_retryable(
@@ -282,8 +300,49 @@ impl OpenRouterGateway {
                 completion_tokens: parse_token_count(usage.completion_tokens),
                 total_tokens: parse_token_count(usage.total_tokens),
             }),
+            tool_calls,
         })
     }
+
+    /// Sends the smallest completion the provider accepts. Only the HTTP status matters, so
+    /// the single output token is never parsed.
+    async fn warm_up_model(&self, model: &str) -> Result<(), SendAttemptError> {
+        let request_body = json!({
+            "model": model,
+            "messages": [
+                { "role": "user", "content": WARM_UP_PROMPT }
+            ],
+            "max_tokens": WARM_UP_MAX_OUTPUT_TOKENS
+        });
+        let response = self
+            .chat_completions_request()
+            .json(&request_body)
+            .send()
+            .await
+            .map_err(send_error)?;
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+
+        let body = response.text().await.unwrap_or_default();
+        Err(provider_status_error(status, &body))
+    }
+
+    fn chat_completions_request(&self) -> reqwest::RequestBuilder {
+        let mut request_builder = self
+            .client
+            .post(&self.config.chat_completions_url)
+            .bearer_auth(&self.config.api_key);
+        if let Some(http_referer) = self.config.app_http_referer.as_deref() {
+            request_builder = request_builder.header("HTTP-Referer", http_referer);
+        }
+        if let Some(app_title) = self.config.app_title.as_deref() {
+            request_builder = request_builder.header("X-Title", app_title);
+        }
+        request_builder
+    }
 }
 
 impl LlmGateway for OpenRouterGateway {
@@ -309,6 +368,29 @@ impl LlmGateway for OpenRouterGateway {
             ))
         })
     }
+
+    fn warm_up<'a>(&'a self) -> LlmWarmUpFuture<'a> {
+        Box::pin(async move {
+            let candidate_models = self.config.model_route.candidate_models();
+
+            for (index, model) in candidate_models.iter().enumerate() {
+                match self.warm_up_model(model).await {
+                    Ok(()) => return Ok(()),
+                    Err(attempt_err) => {
+                        let has_more_candidates = index + 1 < candidate_models.len();
+                        if has_more_candidates && attempt_err.fallback_allowed {
+                            continue;
+                        }
+                        return Err(attempt_err.error);
+                    }
+                }
+            }
+
+            Err(LlmGatewayError::ProviderFailure(
+                "no_openrouter_model_candidates".to_string(),
+            ))
+        })
+    }
 }
 
 #[derive(Debug)]
@@ -357,7 +439,10 @@ struct OpenRouterChoice {
 
 #[derive(Debug, Deserialize)]
 struct OpenRouterMessage {
+    #[serde(default)]
     content: Value,
+    #[serde(default)]
+    tool_calls: Vec<ChatToolCall>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -367,15 +452,6 @@ struct OpenRouterUsage {
     total_tokens: Option<Number>,
 }
 
-fn parse_model_route() -> OpenRouterModelRoute {
-    OpenRouterModelRoute {
-        primary_model: optional_trimmed_env("OPENROUTER_MODEL_PRIMARY")
-            .unwrap_or_else(|| DEFAULT_PRIMARY_MODEL.to_string()),
-        fallback_model: optional_trimmed_env("OPENROUTER_MODEL_FALLBACK")
-            .or_else(|| Some(DEFAULT_FALLBACK_MODEL.to_string())),
-    }
-}
-
 fn require_non_empty_env(key: &str) -> Result<String, OpenRouterConfigError> {
     let value = env::var(key).map_err(|_| OpenRouterConfigError::MissingVar(key.to_string()))?;
     let trimmed = value.trim();
@@ -433,7 +509,34 @@ fn optional_trimmed_env(key: &str) -> Option<String> {
     })
 }
 
-fn is_retryable_status(status: StatusCode) -> bool {
+fn send_error(err: reqwest::Error) -> SendAttemptError {
+    if err.is_timeout() {
+        SendAttemptError::retryable(
+            LlmGatewayError::Timeout,
+            true, // allow fallback to alternate model on timeout.
+        )
+    } else {
+        SendAttemptError::retryable(
+            LlmGatewayError::ProviderFailure("request_unavailable".to_string()),
+            true,
+        )
+    }
+}
+
+fn provider_status_error(status: StatusCode, body: &str) -> SendAttemptError {
+    let provider_code = parse_provider_error_code(body);
+    let fallback_allowed = status != StatusCode::UNAUTHORIZED && status != StatusCode::FORBIDDEN;
+    SendAttemptError {
+        error: LlmGatewayError::ProviderFailure(format!(
+            "status={} code={provider_code}",
+            status.as_u16()
+        )),
+        retryable: is_retryable_status(status),
+        fallback_allowed,
+    }
+}
+
+pub(super) fn is_retryable_status(status: StatusCode) -> bool {
     let code = status.as_u16();
     matches!(
         status,
@@ -480,11 +583,66 @@ fn parse_provider_error_code(body: &str) -> String {
     }
 }
 
+/// A tool call in the chat completions format OpenRouter, OpenAI, and local servers share.
+#[derive(Debug, Deserialize)]
+pub(super) struct ChatToolCall {
+    id: Option<String>,
+    function: ChatToolCallFunction,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatToolCallFunction {
+    name: String,
+    /// A JSON document encoded as a string; some servers send an empty string for no arguments.
+    #[serde(default)]
+    arguments: String,
+}
+
+pub(super) fn chat_tools_payload(tools: &[LlmToolDefinition]) -> Value {
+    Value::Array(
+        tools
+            .iter()
+            .map(|tool| {
+                json!({
+                    "type": "function",
+                    "function": {
+                        "name": tool.name,
+                        "description": tool.description,
+                        "parameters": tool.parameters
+                    }
+                })
+            })
+            .collect(),
+    )
+}
+
+pub(super) fn parse_chat_tool_calls(
+    tool_calls: Vec<ChatToolCall>,
+) -> Result<Vec<LlmToolCall>, LlmGatewayError> {
+    tool_calls
+        .into_iter()
+        .map(|tool_call| {
+            let arguments = if tool_call.function.arguments.trim().is_empty() {
+                json!({})
+            } else {
+                serde_json::from_str::<Value>(&tool_call.function.arguments).map_err(|_| {
+                    LlmGatewayError::InvalidProviderPayload("tool_arguments_not_json".to_string())
+                })?
+            };
+            Ok(LlmToolCall {
+                id: tool_call.id,
+                name: tool_call.function.name,
+                arguments,
+            })
+        })
+        .collect()
+}
+
 fn clamp_u64_to_u32(value: u64) -> u32 {
     value.min(u32::MAX as u64) as u32
 }
 
-fn parse_token_count(value: Option<Number>) -> u32 {
+pub(super) fn parse_token_count(value: Option<Number>) -> u32 {
     let Some(value) = value else { return 0 };
     if let Some(integer) = value.as_u64() {
         return clamp_u64_to_u32(integer);
@@ -3,6 +3,8 @@ use std::time::Duration;
 
 use thiserror::Error;
 
+use crate::llm::routing::DEFAULT_BUDGET_MODEL;
+
 const DEFAULT_RATE_LIMIT_WINDOW_SECONDS: u64 = 60;
 const DEFAULT_RATE_LIMIT_GLOBAL_MAX_REQUESTS: u32 = 120;
 const DEFAULT_RATE_LIMIT_PER_USER_MAX_REQUESTS: u32 = 30;
@@ -12,7 +14,6 @@ const DEFAULT_CACHE_TTL_SECONDS: u64 = 20;
 const DEFAULT_CACHE_MAX_ENTRIES: usize = 256;
 const DEFAULT_BUDGET_WINDOW_SECONDS: u64 = 3_600;
 const DEFAULT_BUDGET_MAX_ESTIMATED_COST_USD: f64 = 1.0;
-pub(crate) const DEFAULT_BUDGET_MODEL: &str = "openai/gpt-4o-mini";
 
 #[derive(Debug, Clone)]
 pub struct LlmReliabilityConfig {
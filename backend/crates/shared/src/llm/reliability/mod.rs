@@ -1,24 +1,38 @@
 use std::sync::{Arc, Mutex};
 use std::time::Instant;
 
+use chrono::Utc;
 use thiserror::Error;
 use tracing::warn;
 
-use super::gateway::{LlmGateway, LlmGatewayError, LlmGatewayFuture, LlmGatewayRequest};
+use super::anthropic::{AnthropicConfigError, AnthropicGateway};
+use super::bedrock::{BedrockConfigError, BedrockGateway};
+use super::contracts::AssistantCapability;
+use super::gateway::{
+    LlmGateway, LlmGatewayError, LlmGatewayFuture, LlmGatewayRequest, LlmProviderRole,
+    LlmWarmUpFuture,
+};
+use super::local::{LocalLlmConfigError, LocalLlmGateway};
+use super::openai::{OpenAiConfigError, OpenAiGateway};
 use super::openrouter::{
     OpenRouterConfigError, OpenRouterGateway, OpenRouterGatewayConfig, OpenRouterModelRoute,
 };
-use config::DEFAULT_BUDGET_MODEL;
+use super::routing::DEFAULT_BUDGET_MODEL;
+use crate::outbound_rate_limit::OutboundCallLimiter;
 use redis_state::RedisReliabilityState;
 use state::{RateLimitRejection, ReliabilityState};
 use util::{cache_key, duration_to_retry_after_seconds, estimate_cost_usd};
 
 mod config;
 mod redis_state;
+mod spend_budget;
 mod state;
 mod util;
 
 pub use config::{LlmReliabilityConfig, LlmReliabilityConfigError};
+pub use spend_budget::{
+    LlmSpendBudget, LlmSpendBudgetFuture, LlmSpendBudgetStatus, PlanLlmSpendBudget,
+};
 
 #[derive(Debug, Error)]
 pub enum ReliableGatewayBuildError {
@@ -26,11 +40,72 @@ pub enum ReliableGatewayBuildError {
     ReliabilityConfig(#[from] LlmReliabilityConfigError),
     #[error(transparent)]
     OpenRouterConfig(#[from] OpenRouterConfigError),
+    #[error(transparent)]
+    AnthropicConfig(#[from] AnthropicConfigError),
+    #[error(transparent)]
+    OpenAiConfig(#[from] OpenAiConfigError),
+    #[error(transparent)]
+    BedrockConfig(#[from] BedrockConfigError),
+    #[error(transparent)]
+    LocalConfig(#[from] LocalLlmConfigError),
+    #[error("only one direct LLM provider may be primary")]
+    ConflictingPrimaryProviders,
     #[error("failed to initialize redis reliability state: {0}")]
     RedisInitialization(String),
 }
 
-pub type ReliableOpenRouterGateway = ReliableLlmGateway<OpenRouterGateway>;
+pub type ReliableProviderGateway = ReliableLlmGateway<LlmProviderGateway>;
+
+/// One of the concrete provider gateways, so a single reliability layer can mix providers.
+#[derive(Clone)]
+pub enum LlmProviderGateway {
+    OpenRouter(OpenRouterGateway),
+    Anthropic(AnthropicGateway),
+    OpenAi(OpenAiGateway),
+    Bedrock(BedrockGateway),
+    Local(LocalLlmGateway),
+}
+
+impl LlmProviderGateway {
+    fn with_outbound_limiter(self, outbound_limiter: OutboundCallLimiter) -> Self {
+        match self {
+            Self::OpenRouter(gateway) => {
+                Self::OpenRouter(gateway.with_outbound_limiter(outbound_limiter))
+            }
+            Self::Anthropic(gateway) => {
+                Self::Anthropic(gateway.with_outbound_limiter(outbound_limiter))
+            }
+            Self::OpenAi(gateway) => Self::OpenAi(gateway.with_outbound_limiter(outbound_limiter)),
+            Self::Bedrock(gateway) => {
+                Self::Bedrock(gateway.with_outbound_limiter(outbound_limiter))
+            }
+            // A loopback server is not an outbound call.
+            Self::Local(gateway) => Self::Local(gateway),
+        }
+    }
+}
+
+impl LlmGateway for LlmProviderGateway {
+    fn generate<'a>(&'a self, request: LlmGatewayRequest) -> LlmGatewayFuture<'a> {
+        match self {
+            Self::OpenRouter(gateway) => gateway.generate(request),
+            Self::Anthropic(gateway) => gateway.generate(request),
+            Self::OpenAi(gateway) => gateway.generate(request),
+            Self::Bedrock(gateway) => gateway.generate(request),
+            Self::Local(gateway) => gateway.generate(request),
+        }
+    }
+
+    fn warm_up<'a>(&'a self) -> LlmWarmUpFuture<'a> {
+        match self {
+            Self::OpenRouter(gateway) => gateway.warm_up(),
+            Self::Anthropic(gateway) => gateway.warm_up(),
+            Self::OpenAi(gateway) => gateway.warm_up(),
+            Self::Bedrock(gateway) => gateway.warm_up(),
+            Self::Local(gateway) => gateway.warm_up(),
+        }
+    }
+}
 
 #[derive(Clone)]
 enum ReliabilityStateBackend {
@@ -45,10 +120,20 @@ where
 {
     primary_gateway: G,
     budget_gateway: Option<G>,
+    fallback_gateways: Vec<G>,
+    capability_gateway: Option<CapabilityGateway<G>>,
+    spend_budget: Option<Arc<dyn LlmSpendBudget>>,
     config: LlmReliabilityConfig,
     state_backend: ReliabilityStateBackend,
 }
 
+/// A gateway that takes precedence over the primary for a fixed set of capabilities.
+#[derive(Clone)]
+struct CapabilityGateway<G> {
+    gateway: G,
+    capabilities: Vec<AssistantCapability>,
+}
+
 impl<G> ReliableLlmGateway<G>
 where
     G: LlmGateway + Clone + Send + Sync + 'static,
@@ -62,6 +147,9 @@ where
         Ok(Self {
             primary_gateway,
             budget_gateway,
+            fallback_gateways: Vec::new(),
+            capability_gateway: None,
+            spend_budget: None,
             config,
             state_backend: ReliabilityStateBackend::InMemory(Arc::new(Mutex::new(
                 ReliabilityState::default(),
@@ -69,6 +157,39 @@ where
         })
     }
 
+    /// Appends a gateway that retries a request when every gateway before it failed for any
+    /// reason other than the outbound limiter. Only a failure on all of them counts against
+    /// the breaker.
+    pub fn with_fallback_gateway(mut self, fallback_gateway: G) -> Self {
+        self.fallback_gateways.push(fallback_gateway);
+        self
+    }
+
+    /// Sends requests for `capabilities` to `gateway` first. When it fails, the request
+    /// continues down the usual primary (or budget) and fallback chain.
+    pub fn with_capability_gateway(
+        mut self,
+        gateway: G,
+        capabilities: Vec<AssistantCapability>,
+    ) -> Self {
+        self.capability_gateway = Some(CapabilityGateway {
+            gateway,
+            capabilities,
+        });
+        self
+    }
+
+    /// Refuses requests from requesters whose monthly budget is spent and charges each
+    /// provider success to the requester. Cache hits cost nothing and are never refused.
+    ///
+    /// A failed budget lookup lets the request through: the per-user and global rate limits
+    /// still bound request volume, and refusing every LLM call while the budget store is
+    /// unreachable would take the assistant down with it.
+    pub fn with_spend_budget(mut self, spend_budget: Arc<dyn LlmSpendBudget>) -> Self {
+        self.spend_budget = Some(spend_budget);
+        self
+    }
+
     fn lock_state(
         state: &Arc<Mutex<ReliabilityState>>,
     ) -> std::sync::MutexGuard<'_, ReliabilityState> {
@@ -148,6 +269,28 @@ where
         }
     }
 
+    /// Fails open; see [`Self::with_spend_budget`].
+    async fn spend_budget_status(&self, requester_id: &str) -> Option<LlmSpendBudgetStatus> {
+        let spend_budget = self.spend_budget.as_ref()?;
+        match spend_budget.status(requester_id).await {
+            Ok(status) => status,
+            Err(err) => {
+                warn!(error = %err, "llm spend budget lookup failed; allowing request");
+                None
+            }
+        }
+    }
+
+    async fn record_requester_spend(&self, requester_id: &str, estimated_cost_usd: f64) {
+        if let Some(spend_budget) = &self.spend_budget
+            && let Err(err) = spend_budget
+                .record_spend(requester_id, estimated_cost_usd)
+                .await
+        {
+            warn!(error = %err, "llm spend budget update failed");
+        }
+    }
+
     async fn record_provider_success(&self) {
         match &self.state_backend {
             ReliabilityStateBackend::InMemory(state) => {
@@ -220,7 +363,57 @@ where
     }
 }
 
-impl ReliableOpenRouterGateway {
+impl ReliableProviderGateway {
+    /// Shares one outbound limiter across the primary, budget, and fallback gateways.
+    pub fn with_outbound_limiter(mut self, outbound_limiter: OutboundCallLimiter) -> Self {
+        self.budget_gateway = self
+            .budget_gateway
+            .map(|gateway| gateway.with_outbound_limiter(outbound_limiter.clone()));
+        self.fallback_gateways = self
+            .fallback_gateways
+            .into_iter()
+            .map(|gateway| gateway.with_outbound_limiter(outbound_limiter.clone()))
+            .collect();
+        self.primary_gateway = self.primary_gateway.with_outbound_limiter(outbound_limiter);
+        self
+    }
+
+    /// Puts Anthropic in front of OpenRouter, which becomes the first fallback, or behind it.
+    pub fn with_anthropic(self, anthropic_gateway: AnthropicGateway) -> Self {
+        let role = anthropic_gateway.role();
+        self.with_direct_provider(LlmProviderGateway::Anthropic(anthropic_gateway), role)
+    }
+
+    /// Puts OpenAI in front of OpenRouter, which becomes the first fallback, or behind it.
+    pub fn with_openai(self, openai_gateway: OpenAiGateway) -> Self {
+        let role = openai_gateway.role();
+        self.with_direct_provider(LlmProviderGateway::OpenAi(openai_gateway), role)
+    }
+
+    /// Puts Bedrock in front of OpenRouter, which becomes the first fallback, or behind it.
+    pub fn with_bedrock(self, bedrock_gateway: BedrockGateway) -> Self {
+        let role = bedrock_gateway.role();
+        self.with_direct_provider(LlmProviderGateway::Bedrock(bedrock_gateway), role)
+    }
+
+    /// Serves the local gateway's capabilities in front of every remote provider.
+    pub fn with_local(self, local_gateway: LocalLlmGateway) -> Self {
+        let capabilities = local_gateway.capabilities().to_vec();
+        self.with_capability_gateway(LlmProviderGateway::Local(local_gateway), capabilities)
+    }
+
+    /// The budget gateway stays on OpenRouter whatever the direct providers' roles are.
+    fn with_direct_provider(mut self, gateway: LlmProviderGateway, role: LlmProviderRole) -> Self {
+        match role {
+            LlmProviderRole::Primary => {
+                let previous_primary = std::mem::replace(&mut self.primary_gateway, gateway);
+                self.fallback_gateways.insert(0, previous_primary);
+            }
+            LlmProviderRole::Fallback => self.fallback_gateways.push(gateway),
+        }
+        self
+    }
+
     pub fn from_openrouter_config(
         openrouter_config: OpenRouterGatewayConfig,
         reliability_config: LlmReliabilityConfig,
@@ -231,6 +424,9 @@ impl ReliableOpenRouterGateway {
         Ok(Self {
             primary_gateway,
             budget_gateway,
+            fallback_gateways: Vec::new(),
+            capability_gateway: None,
+            spend_budget: None,
             config: reliability_config,
             state_backend: ReliabilityStateBackend::InMemory(Arc::new(Mutex::new(
                 ReliabilityState::default(),
@@ -252,6 +448,9 @@ impl ReliableOpenRouterGateway {
         Ok(Self {
             primary_gateway,
             budget_gateway,
+            fallback_gateways: Vec::new(),
+            capability_gateway: None,
+            spend_budget: None,
             config: reliability_config,
             state_backend: ReliabilityStateBackend::Redis(redis_state),
         })
@@ -282,6 +481,16 @@ where
                 return Ok(cached_response);
             }
 
+            if let Some(status) = self.spend_budget_status(&requester_id).await
+                && status.is_exhausted()
+            {
+                return Err(LlmGatewayError::BudgetExceeded(format!(
+                    "budget_exceeded plan={} retry_after_seconds={}",
+                    status.plan.as_str(),
+                    (status.resets_at - Utc::now()).num_seconds().max(1)
+                )));
+            }
+
             if let Some(retry_after) = self.circuit_breaker_retry_after().await {
                 return Err(LlmGatewayError::ProviderFailure(format!(
                     "circuit_breaker_open retry_after_seconds={}",
@@ -296,16 +505,46 @@ where
             } else {
                 &self.primary_gateway
             };
-            let result = selected_gateway.generate(request).await;
+            let capability_gateway = self
+                .capability_gateway
+                .as_ref()
+                .filter(|routed| routed.capabilities.contains(&request.capability));
+            let mut result = match capability_gateway {
+                Some(routed) => match routed.gateway.generate(request.clone()).await {
+                    Err(err) if !matches!(err, LlmGatewayError::RateLimited(_)) => {
+                        warn!(
+                            error = %err,
+                            capability = request.capability.as_str(),
+                            "capability gateway failed; retrying on primary provider"
+                        );
+                        selected_gateway.generate(request.clone()).await
+                    }
+                    result => result,
+                },
+                None => selected_gateway.generate(request.clone()).await,
+            };
+            for fallback_gateway in &self.fallback_gateways {
+                match &result {
+                    Err(err) if !matches!(err, LlmGatewayError::RateLimited(_)) => {
+                        warn!(error = %err, "llm provider failed; retrying on fallback provider");
+                        result = fallback_gateway.generate(request.clone()).await;
+                    }
+                    _ => break,
+                }
+            }
 
             match &result {
                 Ok(response) => {
+                    let estimated_cost_usd = estimate_cost_usd(response).unwrap_or(0.0);
                     self.record_provider_success().await;
-                    self.record_budget_spend(estimate_cost_usd(response).unwrap_or(0.0))
+                    self.record_budget_spend(estimated_cost_usd).await;
+                    self.record_requester_spend(&requester_id, estimated_cost_usd)
                         .await;
                     self.store_cached_response(&request_cache_key, response)
                         .await;
                 }
+                // The enclave's own limiter says nothing about provider health.
+                Err(LlmGatewayError::RateLimited(_)) => {}
                 Err(_) => {
                     self.record_provider_failure().await;
                 }
@@ -314,14 +553,28 @@ where
             result
         })
     }
+
+    /// Goes straight to the primary provider: a cached warm-up response would not prove the
+    /// provider path, and startup probes must not trip the breaker or spend rate-limit budget.
+    /// A capability gateway is probed as well, since it serves its capabilities first.
+    fn warm_up<'a>(&'a self) -> LlmWarmUpFuture<'a> {
+        Box::pin(async move {
+            self.primary_gateway.warm_up().await?;
+            if let Some(routed) = &self.capability_gateway {
+                routed.gateway.warm_up().await?;
+            }
+            Ok(())
+        })
+    }
 }
 
 fn build_openrouter_gateways(
     openrouter_config: OpenRouterGatewayConfig,
     reliability_config: &LlmReliabilityConfig,
-) -> Result<(OpenRouterGateway, Option<OpenRouterGateway>), ReliableGatewayBuildError> {
+) -> Result<(LlmProviderGateway, Option<LlmProviderGateway>), ReliableGatewayBuildError> {
     reliability_config.validate()?;
-    let primary_gateway = OpenRouterGateway::new(openrouter_config.clone())?;
+    let primary_gateway =
+        LlmProviderGateway::OpenRouter(OpenRouterGateway::new(openrouter_config.clone())?);
 
     let budget_model = reliability_config
         .budget_model
@@ -331,6 +584,7 @@ fn build_openrouter_gateways(
     budget_config.model_route = OpenRouterModelRoute {
         primary_model: budget_model,
         fallback_model: None,
+        temperature: openrouter_config.model_route.temperature,
     };
 
     let budget_gateway = if budget_config.model_route.primary_model
@@ -339,7 +593,9 @@ fn build_openrouter_gateways(
     {
         None
     } else {
-        Some(OpenRouterGateway::new(budget_config)?)
+        Some(LlmProviderGateway::OpenRouter(OpenRouterGateway::new(
+            budget_config,
+        )?))
     };
 
     Ok((primary_gateway, budget_gateway))
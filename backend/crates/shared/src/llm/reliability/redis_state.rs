@@ -7,6 +7,7 @@ use sha2::{Digest, Sha256};
 
 use super::LlmReliabilityConfig;
 use super::state::RateLimitRejection;
+use super::util::usd_to_micros;
 
 const DEFAULT_RELIABILITY_KEY_PREFIX: &str = "alfred:llm:reliability:v1";
 const CACHE_SCOPE: &str = "cache:data";
@@ -276,19 +277,6 @@ fn budget_limit_micros(config: &LlmReliabilityConfig) -> i64 {
     usd_to_micros(config.budget_max_estimated_cost_usd).max(1)
 }
 
-fn usd_to_micros(usd: f64) -> i64 {
-    if !usd.is_finite() || usd <= 0.0 {
-        return 0;
-    }
-
-    let micros = (usd * 1_000_000.0).round();
-    if micros >= i64::MAX as f64 {
-        i64::MAX
-    } else {
-        micros as i64
-    }
-}
-
 fn hashed_label(value: &str) -> String {
     let digest = Sha256::digest(value.as_bytes());
     digest
@@ -0,0 +1,124 @@
+use std::future::Future;
+use std::pin::Pin;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use uuid::Uuid;
+
+use super::util::usd_to_micros;
+use crate::models::UserPlan;
+use crate::quota::{PlanQuotas, llm_spend_month_reset, llm_spend_month_start};
+use crate::repos::{Store, StoreError};
+
+pub type LlmSpendBudgetFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, String>> + Send + 'a>>;
+
+/// A requester's LLM spend for the current budget period against its ceiling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LlmSpendBudgetStatus {
+    pub plan: UserPlan,
+    pub month_start: NaiveDate,
+    pub limit_micros: i64,
+    pub spent_micros: i64,
+    pub resets_at: DateTime<Utc>,
+}
+
+impl LlmSpendBudgetStatus {
+    pub fn remaining_micros(&self) -> i64 {
+        self.limit_micros.saturating_sub(self.spent_micros).max(0)
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.spent_micros >= self.limit_micros
+    }
+}
+
+/// Per-requester spend ceilings checked by the reliability layer before every provider call
+/// and charged with the estimated cost after every success.
+pub trait LlmSpendBudget: Send + Sync {
+    /// `None` when the requester has no budget, such as eval runs or anonymous requests.
+    fn status<'a>(
+        &'a self,
+        requester_id: &'a str,
+    ) -> LlmSpendBudgetFuture<'a, Option<LlmSpendBudgetStatus>>;
+
+    fn record_spend<'a>(
+        &'a self,
+        requester_id: &'a str,
+        estimated_cost_usd: f64,
+    ) -> LlmSpendBudgetFuture<'a, ()>;
+}
+
+/// Monthly budgets set by the user's plan, with spend persisted in Postgres so it survives
+/// Redis flushes and is shared by every enclave instance.
+#[derive(Clone)]
+pub struct PlanLlmSpendBudget {
+    store: Store,
+}
+
+impl PlanLlmSpendBudget {
+    pub fn new(store: Store) -> Self {
+        Self { store }
+    }
+
+    pub async fn status_for_user(
+        &self,
+        user_id: Uuid,
+        now: DateTime<Utc>,
+    ) -> Result<LlmSpendBudgetStatus, StoreError> {
+        let plan = self.store.get_user_plan(user_id).await?;
+        let month_start = llm_spend_month_start(now);
+        let spent_micros = self
+            .store
+            .get_llm_monthly_spend_micros(user_id, month_start)
+            .await?;
+
+        Ok(LlmSpendBudgetStatus {
+            plan,
+            month_start,
+            limit_micros: PlanQuotas::for_plan(plan).llm_spend_micros_per_month,
+            spent_micros,
+            resets_at: llm_spend_month_reset(now),
+        })
+    }
+}
+
+impl LlmSpendBudget for PlanLlmSpendBudget {
+    fn status<'a>(
+        &'a self,
+        requester_id: &'a str,
+    ) -> LlmSpendBudgetFuture<'a, Option<LlmSpendBudgetStatus>> {
+        Box::pin(async move {
+            let Ok(user_id) = Uuid::parse_str(requester_id) else {
+                return Ok(None);
+            };
+            self.status_for_user(user_id, Utc::now())
+                .await
+                .map(Some)
+                .map_err(|err| err.to_string())
+        })
+    }
+
+    fn record_spend<'a>(
+        &'a self,
+        requester_id: &'a str,
+        estimated_cost_usd: f64,
+    ) -> LlmSpendBudgetFuture<'a, ()> {
+        Box::pin(async move {
+            let delta_micros = usd_to_micros(estimated_cost_usd);
+            let Ok(user_id) = Uuid::parse_str(requester_id) else {
+                return Ok(());
+            };
+            if delta_micros <= 0 {
+                return Ok(());
+            }
+            self.store
+                .add_llm_monthly_spend_micros(
+                    user_id,
+                    llm_spend_month_start(Utc::now()),
+                    delta_micros,
+                )
+                .await
+                .map(|_| ())
+                .map_err(|err| err.to_string())
+        })
+    }
+}
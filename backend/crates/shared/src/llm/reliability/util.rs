@@ -3,7 +3,8 @@ use std::time::Duration;
 use serde::Serialize;
 use sha2::{Digest, Sha256};
 
-use crate::llm::{AssistantCapability, LlmGatewayRequest, LlmGatewayResponse};
+use crate::llm::bedrock::foundation_model_id;
+use crate::llm::{LlmGatewayRequest, LlmGatewayResponse, LlmToolDefinition};
 
 pub(crate) fn estimate_cost_usd(response: &LlmGatewayResponse) -> Option<f64> {
     let usage = response.usage.as_ref()?;
@@ -16,6 +17,19 @@ pub(crate) fn estimate_cost_usd(response: &LlmGatewayResponse) -> Option<f64> {
     )
 }
 
+pub(crate) fn usd_to_micros(usd: f64) -> i64 {
+    if !usd.is_finite() || usd <= 0.0 {
+        return 0;
+    }
+
+    let micros = (usd * 1_000_000.0).round();
+    if micros >= i64::MAX as f64 {
+        i64::MAX
+    } else {
+        micros as i64
+    }
+}
+
 pub(crate) fn duration_to_retry_after_seconds(duration: Duration) -> u64 {
     let seconds = duration.as_secs();
     if seconds == 0 {
@@ -31,12 +45,13 @@ pub(crate) fn duration_to_retry_after_seconds(duration: Duration) -> u64 {
 pub(crate) fn cache_key(request: &LlmGatewayRequest) -> String {
     let payload = CacheKeyPayload {
         requester_id: request.requester_id.as_deref(),
-        capability: capability_label(request.capability),
+        capability: request.capability.as_str(),
         contract_version: &request.contract_version,
         system_prompt: &request.system_prompt,
         context_prompt: &request.context_prompt,
         output_schema: &request.output_schema,
         context_payload: &request.context_payload,
+        tools: &request.tools,
     };
     let serialized = serde_json::to_vec(&payload).unwrap_or_default();
     let digest = Sha256::digest(serialized);
@@ -54,18 +69,45 @@ struct ModelPricing {
 
 fn pricing_for_model(model: &str) -> Option<ModelPricing> {
     let normalized = model.trim().to_ascii_lowercase();
-    if normalized.starts_with("openai/gpt-4o-mini") {
+    // OpenRouter ids carry a vendor prefix; direct OpenAI responses name a dated snapshot.
+    let normalized = normalized
+        .strip_prefix("openai/")
+        .unwrap_or(normalized.as_str());
+    // Bedrock ids name the vendor after an optional inference profile region.
+    let normalized = foundation_model_id(normalized);
+    let normalized = normalized.strip_prefix("anthropic.").unwrap_or(normalized);
+    if normalized.starts_with("gpt-4o-mini") {
         return Some(ModelPricing {
             input_per_million: 0.15,
             output_per_million: 0.60,
         });
     }
-    if normalized.starts_with("anthropic/claude-3.5-haiku") {
+    if normalized.starts_with("gpt-4.1-mini") {
+        return Some(ModelPricing {
+            input_per_million: 0.40,
+            output_per_million: 1.60,
+        });
+    }
+    if normalized.starts_with("anthropic/claude-3.5-haiku")
+        || normalized.starts_with("claude-3-5-haiku")
+    {
         return Some(ModelPricing {
             input_per_million: 0.80,
             output_per_million: 4.00,
         });
     }
+    if normalized.starts_with("meta.llama3-1-8b") {
+        return Some(ModelPricing {
+            input_per_million: 0.22,
+            output_per_million: 0.22,
+        });
+    }
+    if normalized.starts_with("meta.llama3-1-70b") {
+        return Some(ModelPricing {
+            input_per_million: 0.72,
+            output_per_million: 0.72,
+        });
+    }
     None
 }
 
@@ -78,14 +120,7 @@ struct CacheKeyPayload<'a> {
     context_prompt: &'a str,
     output_schema: &'a serde_json::Value,
     context_payload: &'a serde_json::Value,
-}
-
-fn capability_label(capability: AssistantCapability) -> &'static str {
-    match capability {
-        AssistantCapability::MeetingsSummary => "meetings_summary",
-        AssistantCapability::GeneralChatSummary => "general_chat_summary",
-        AssistantCapability::MorningBrief => "morning_brief",
-        AssistantCapability::UrgentEmailSummary => "urgent_email_summary",
-        AssistantCapability::AssistantSemanticPlan => "assistant_semantic_plan",
-    }
+    /// Skipped when empty so adding tools left existing cache keys unchanged.
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    tools: &'a [LlmToolDefinition],
 }
@@ -0,0 +1,395 @@
+use std::env;
+
+use thiserror::Error;
+
+use crate::enclave_runtime::AlfredEnvironment;
+
+/// Models the gateways may be routed to. Extend with `LLM_EXTRA_KNOWN_MODELS` (comma separated)
+/// to trial a model without a code change.
+pub const KNOWN_LLM_MODELS: [&str; 8] = [
+    "openai/gpt-4o-mini",
+    "openai/gpt-4o",
+    "openai/gpt-4.1-mini",
+    "openai/gpt-4.1-nano",
+    "openai/gpt-4.1",
+    "anthropic/claude-3.5-haiku",
+    "anthropic/claude-3.5-sonnet",
+    "anthropic/claude-3.7-sonnet",
+];
+
+pub const DEFAULT_PRIMARY_MODEL: &str = "openai/gpt-4o-mini";
+pub const DEFAULT_FALLBACK_MODEL: &str = "anthropic/claude-3.5-haiku";
+pub const DEFAULT_BUDGET_MODEL: &str = "openai/gpt-4o-mini";
+const DEFAULT_TEMPERATURE: f32 = 0.0;
+const MAX_TEMPERATURE: f32 = 2.0;
+
+/// Gateway profiles that get their own model route.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LlmRouteProfile {
+    Worker,
+    AssistantPlanner,
+    AssistantChat,
+    AssistantTool,
+}
+
+impl LlmRouteProfile {
+    fn env_key(self, suffix: &str) -> String {
+        match self {
+            Self::Worker => format!("OPENROUTER_{suffix}"),
+            Self::AssistantPlanner => format!("ASSISTANT_PLANNER_OPENROUTER_{suffix}"),
+            Self::AssistantChat => format!("ASSISTANT_CHAT_OPENROUTER_{suffix}"),
+            Self::AssistantTool => format!("ASSISTANT_TOOL_OPENROUTER_{suffix}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LlmModelRouteConfig {
+    pub primary_model: String,
+    pub fallback_model: Option<String>,
+    pub temperature: f32,
+}
+
+/// Validated model routing for every gateway profile. Each variable may be overridden for one
+/// environment by suffixing it with the upper-cased `ALFRED_ENV` value, e.g.
+/// `ASSISTANT_CHAT_OPENROUTER_MODEL_PRIMARY_STAGING`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LlmRoutingConfig {
+    pub environment: AlfredEnvironment,
+    pub worker: LlmModelRouteConfig,
+    pub assistant_planner: LlmModelRouteConfig,
+    pub assistant_chat: LlmModelRouteConfig,
+    pub assistant_tool: LlmModelRouteConfig,
+    pub budget_model: String,
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum LlmRoutingConfigError {
+    #[error("invalid ALFRED_ENV: {0}")]
+    InvalidEnvironment(String),
+    #[error("{key} names unknown model '{value}'{hint}")]
+    UnknownModel {
+        key: String,
+        value: String,
+        hint: String,
+    },
+    #[error("{key} must be a number between 0 and 2, got '{value}'")]
+    InvalidTemperature { key: String, value: String },
+}
+
+impl LlmRoutingConfig {
+    pub fn from_env() -> Result<Self, LlmRoutingConfigError> {
+        let environment = env::var("ALFRED_ENV")
+            .unwrap_or_else(|_| "production".to_string())
+            .parse::<AlfredEnvironment>()
+            .map_err(LlmRoutingConfigError::InvalidEnvironment)?;
+        Self::from_lookup(environment, |key| env::var(key).ok())
+    }
+
+    pub fn from_lookup(
+        environment: AlfredEnvironment,
+        lookup: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, LlmRoutingConfigError> {
+        let reader = RouteReader {
+            environment,
+            lookup: &lookup,
+            known_models: known_models(lookup("LLM_EXTRA_KNOWN_MODELS")),
+        };
+
+        let worker = reader.route(
+            LlmRouteProfile::Worker,
+            &LlmModelRouteConfig {
+                primary_model: DEFAULT_PRIMARY_MODEL.to_string(),
+                fallback_model: Some(DEFAULT_FALLBACK_MODEL.to_string()),
+                temperature: DEFAULT_TEMPERATURE,
+            },
+        )?;
+        // Assistant profiles are latency bound, so they only fall back when asked to.
+        let assistant_defaults = LlmModelRouteConfig {
+            fallback_model: None,
+            ..worker.clone()
+        };
+
+        Ok(Self {
+            environment,
+            assistant_planner: reader
+                .route(LlmRouteProfile::AssistantPlanner, &assistant_defaults)?,
+            assistant_chat: reader.route(LlmRouteProfile::AssistantChat, &assistant_defaults)?,
+            assistant_tool: reader.route(LlmRouteProfile::AssistantTool, &assistant_defaults)?,
+            budget_model: reader
+                .model("LLM_BUDGET_MODEL")?
+                .unwrap_or_else(|| DEFAULT_BUDGET_MODEL.to_string()),
+            worker,
+        })
+    }
+
+    pub fn route(&self, profile: LlmRouteProfile) -> &LlmModelRouteConfig {
+        match profile {
+            LlmRouteProfile::Worker => &self.worker,
+            LlmRouteProfile::AssistantPlanner => &self.assistant_planner,
+            LlmRouteProfile::AssistantChat => &self.assistant_chat,
+            LlmRouteProfile::AssistantTool => &self.assistant_tool,
+        }
+    }
+}
+
+struct RouteReader<'a, F> {
+    environment: AlfredEnvironment,
+    lookup: &'a F,
+    known_models: Vec<String>,
+}
+
+impl<F> RouteReader<'_, F>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    fn value(&self, key: &str) -> Option<(String, String)> {
+        environment_scoped_value(self.environment, self.lookup, key)
+    }
+
+    fn route(
+        &self,
+        profile: LlmRouteProfile,
+        defaults: &LlmModelRouteConfig,
+    ) -> Result<LlmModelRouteConfig, LlmRoutingConfigError> {
+        let primary_model = self
+            .model(&profile.env_key("MODEL_PRIMARY"))?
+            .unwrap_or_else(|| defaults.primary_model.clone());
+        let fallback_model = match self.value(&profile.env_key("MODEL_FALLBACK")) {
+            // An explicitly empty fallback disables it.
+            Some((_, value)) if value.is_empty() => None,
+            Some((key, value)) => Some(self.validate_model(key, value)?),
+            None => defaults.fallback_model.clone(),
+        }
+        .filter(|fallback_model| fallback_model != &primary_model);
+        let temperature = match self.value(&profile.env_key("TEMPERATURE")) {
+            Some((_, value)) if value.is_empty() => defaults.temperature,
+            Some((key, value)) => parse_temperature(key, value)?,
+            None => defaults.temperature,
+        };
+
+        Ok(LlmModelRouteConfig {
+            primary_model,
+            fallback_model,
+            temperature,
+        })
+    }
+
+    fn model(&self, key: &str) -> Result<Option<String>, LlmRoutingConfigError> {
+        match self.value(key) {
+            Some((_, value)) if value.is_empty() => Ok(None),
+            Some((key, value)) => self.validate_model(key, value).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn validate_model(&self, key: String, value: String) -> Result<String, LlmRoutingConfigError> {
+        if self.known_models.iter().any(|model| model == &value) {
+            return Ok(value);
+        }
+
+        let hint = closest_model(&value, &self.known_models)
+            .map(|model| format!("; did you mean '{model}'?"))
+            .unwrap_or_else(|| {
+                format!(
+                    "; known models: {} (extend with LLM_EXTRA_KNOWN_MODELS)",
+                    self.known_models.join(", ")
+                )
+            });
+        Err(LlmRoutingConfigError::UnknownModel { key, value, hint })
+    }
+}
+
+/// Returns the environment-specific value when set, then the plain key, along with the key
+/// that supplied it.
+pub(super) fn environment_scoped_value(
+    environment: AlfredEnvironment,
+    lookup: &impl Fn(&str) -> Option<String>,
+    key: &str,
+) -> Option<(String, String)> {
+    let scoped_key = format!("{key}_{}", environment.as_str().to_ascii_uppercase());
+    [scoped_key, key.to_string()]
+        .into_iter()
+        .find_map(|key| lookup(&key).map(|value| (key, value.trim().to_string())))
+}
+
+fn known_models(extra: Option<String>) -> Vec<String> {
+    let mut models = KNOWN_LLM_MODELS
+        .iter()
+        .map(|model| (*model).to_string())
+        .collect::<Vec<_>>();
+    models.extend(
+        extra
+            .iter()
+            .flat_map(|extra| extra.split(','))
+            .map(str::trim)
+            .filter(|model| !model.is_empty())
+            .map(ToString::to_string),
+    );
+    models
+}
+
+fn parse_temperature(key: String, value: String) -> Result<f32, LlmRoutingConfigError> {
+    match value.parse::<f32>() {
+        Ok(temperature) if (0.0..=MAX_TEMPERATURE).contains(&temperature) => Ok(temperature),
+        _ => Err(LlmRoutingConfigError::InvalidTemperature { key, value }),
+    }
+}
+
+/// Suggests a known model within a small edit distance of a likely typo.
+fn closest_model<'a>(value: &str, known_models: &'a [String]) -> Option<&'a str> {
+    let value = value.to_ascii_lowercase();
+    known_models
+        .iter()
+        .map(|model| (edit_distance(&value, &model.to_ascii_lowercase()), model))
+        .filter(|(distance, _)| *distance <= 3)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, model)| model.as_str())
+}
+
+fn edit_distance(left: &str, right: &str) -> usize {
+    let right = right.chars().collect::<Vec<_>>();
+    let mut previous = (0..=right.len()).collect::<Vec<_>>();
+    for (i, left_char) in left.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, right_char) in right.iter().enumerate() {
+            let substitution = previous[j] + usize::from(left_char != *right_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[right.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{
+        DEFAULT_BUDGET_MODEL, DEFAULT_FALLBACK_MODEL, DEFAULT_PRIMARY_MODEL, LlmRouteProfile,
+        LlmRoutingConfig, LlmRoutingConfigError,
+    };
+    use crate::enclave_runtime::AlfredEnvironment;
+
+    fn config(
+        environment: AlfredEnvironment,
+        vars: &[(&str, &str)],
+    ) -> Result<LlmRoutingConfig, LlmRoutingConfigError> {
+        let vars = vars
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect::<HashMap<_, _>>();
+        LlmRoutingConfig::from_lookup(environment, |key| vars.get(key).cloned())
+    }
+
+    #[test]
+    fn defaults_route_assistant_profiles_without_fallback() {
+        let config = config(AlfredEnvironment::Production, &[]).expect("defaults are valid");
+
+        assert_eq!(config.worker.primary_model, DEFAULT_PRIMARY_MODEL);
+        assert_eq!(
+            config.worker.fallback_model.as_deref(),
+            Some(DEFAULT_FALLBACK_MODEL)
+        );
+        assert_eq!(config.worker.temperature, 0.0);
+        for profile in [
+            LlmRouteProfile::AssistantPlanner,
+            LlmRouteProfile::AssistantChat,
+            LlmRouteProfile::AssistantTool,
+        ] {
+            assert_eq!(config.route(profile).primary_model, DEFAULT_PRIMARY_MODEL);
+            assert!(config.route(profile).fallback_model.is_none());
+        }
+        assert_eq!(config.budget_model, DEFAULT_BUDGET_MODEL);
+    }
+
+    #[test]
+    fn environment_scoped_values_take_precedence() {
+        let vars = [
+            ("OPENROUTER_MODEL_PRIMARY", "openai/gpt-4.1-mini"),
+            ("ASSISTANT_CHAT_OPENROUTER_MODEL_PRIMARY", "openai/gpt-4o"),
+            (
+                "ASSISTANT_CHAT_OPENROUTER_MODEL_PRIMARY_STAGING",
+                "openai/gpt-4.1-nano",
+            ),
+            ("ASSISTANT_CHAT_OPENROUTER_TEMPERATURE", "0.4"),
+            (
+                "ASSISTANT_TOOL_OPENROUTER_MODEL_FALLBACK",
+                "openai/gpt-4.1-mini",
+            ),
+            ("OPENROUTER_MODEL_FALLBACK_STAGING", ""),
+        ];
+
+        let staging = config(AlfredEnvironment::Staging, &vars).expect("staging is valid");
+        assert_eq!(staging.worker.primary_model, "openai/gpt-4.1-mini");
+        assert!(staging.worker.fallback_model.is_none());
+        assert_eq!(staging.assistant_chat.primary_model, "openai/gpt-4.1-nano");
+        assert_eq!(staging.assistant_chat.temperature, 0.4);
+        assert_eq!(
+            staging.assistant_planner.primary_model,
+            "openai/gpt-4.1-mini"
+        );
+        // A fallback equal to the primary model is dropped.
+        assert!(staging.assistant_tool.fallback_model.is_none());
+
+        let production = config(AlfredEnvironment::Production, &vars).expect("prod is valid");
+        assert_eq!(production.assistant_chat.primary_model, "openai/gpt-4o");
+        assert_eq!(
+            production.worker.fallback_model.as_deref(),
+            Some(DEFAULT_FALLBACK_MODEL)
+        );
+    }
+
+    #[test]
+    fn typos_and_out_of_range_values_are_rejected() {
+        let err = config(
+            AlfredEnvironment::Production,
+            &[(
+                "ASSISTANT_PLANNER_OPENROUTER_MODEL_PRIMARY",
+                "openai/gpt-4o-mni",
+            )],
+        )
+        .expect_err("typo should be rejected");
+        assert_eq!(
+            err.to_string(),
+            "ASSISTANT_PLANNER_OPENROUTER_MODEL_PRIMARY names unknown model 'openai/gpt-4o-mni'; did you mean 'openai/gpt-4o-mini'?"
+        );
+
+        let err = config(
+            AlfredEnvironment::Local,
+            &[("LLM_BUDGET_MODEL_LOCAL", "mistral/unknown-model-xl")],
+        )
+        .expect_err("unknown model should be rejected");
+        assert!(err.to_string().starts_with(
+            "LLM_BUDGET_MODEL_LOCAL names unknown model 'mistral/unknown-model-xl'; known models:"
+        ));
+
+        assert!(matches!(
+            config(
+                AlfredEnvironment::Production,
+                &[("OPENROUTER_TEMPERATURE", "2.5")]
+            ),
+            Err(LlmRoutingConfigError::InvalidTemperature { .. })
+        ));
+        assert!(matches!(
+            config(
+                AlfredEnvironment::Production,
+                &[("OPENROUTER_TEMPERATURE", "warm")]
+            ),
+            Err(LlmRoutingConfigError::InvalidTemperature { .. })
+        ));
+    }
+
+    #[test]
+    fn extra_known_models_extend_the_allow_list() {
+        let config = config(
+            AlfredEnvironment::Production,
+            &[
+                ("LLM_EXTRA_KNOWN_MODELS", " google/gemini-2.0-flash , "),
+                ("OPENROUTER_MODEL_PRIMARY", "google/gemini-2.0-flash"),
+            ],
+        )
+        .expect("extra model should be accepted");
+        assert_eq!(config.worker.primary_model, "google/gemini-2.0-flash");
+    }
+}
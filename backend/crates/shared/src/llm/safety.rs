@@ -6,12 +6,15 @@ use crate::assistant_semantic_plan::{
     ASSISTANT_SEMANTIC_PLAN_VERSION_V1, AssistantSemanticCapability, AssistantSemanticPlanContract,
     AssistantSemanticPlanOutput,
 };
+use crate::automation_draft::{
+    AUTOMATION_DRAFT_VERSION_V1, AutomationDraftContract, AutomationDraftOutput,
+};
 
 use super::contracts::{
     AssistantCapability, AssistantOutputContract, ChatResponseStyle, GeneralChatSummaryContract,
     GeneralChatSummaryOutput, MeetingsSummaryContract, MeetingsSummaryOutput, MorningBriefContract,
     MorningBriefOutput, OUTPUT_CONTRACT_VERSION_V1, UrgencyLevel, UrgentEmailSummaryContract,
-    UrgentEmailSummaryOutput,
+    UrgentEmailSummaryOutput, WeeklyReviewContract, WeeklyReviewOutput,
 };
 use super::validation::validate_output_value;
 
@@ -20,6 +23,9 @@ const MAX_FALLBACK_LIST_ITEMS: usize = 3;
 const MAX_OUTPUT_TEXT_CHARS: usize = 500;
 const MAX_OUTPUT_TITLE_CHARS: usize = 120;
 const MAX_OUTPUT_LIST_ITEMS: usize = 8;
+const MAX_WEEKLY_REVIEW_TEXT_CHARS: usize = 1500;
+const MAX_WEEKLY_REVIEW_LIST_ITEMS: usize = 12;
+const MAX_AUTOMATION_DRAFT_PROMPT_CHARS: usize = 4_000;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SafeOutputSource {
@@ -104,6 +110,12 @@ fn deterministic_fallback_contract(
         AssistantCapability::AssistantSemanticPlan => {
             AssistantOutputContract::AssistantSemanticPlan(fallback_assistant_semantic_plan())
         }
+        AssistantCapability::WeeklyReview => {
+            AssistantOutputContract::WeeklyReview(fallback_weekly_review(context_payload))
+        }
+        AssistantCapability::AutomationDraft => {
+            AssistantOutputContract::AutomationDraft(fallback_automation_draft(context_payload))
+        }
     }
 }
 
@@ -212,6 +224,50 @@ fn fallback_morning_brief(context_payload: &Value) -> MorningBriefContract {
     }
 }
 
+fn fallback_weekly_review(context_payload: &Value) -> WeeklyReviewContract {
+    let context = serde_json::from_value::<FallbackWeeklyReviewContext>(context_payload.clone())
+        .unwrap_or_else(|_| FallbackWeeklyReviewContext {
+            past_meeting_count: 0,
+            email_count: 0,
+            upcoming_meeting_count: 0,
+            upcoming_meetings: Vec::new(),
+        });
+    let past_meeting_count = context.past_meeting_count;
+    let email_count = context.email_count;
+    let upcoming_meeting_count = context
+        .upcoming_meeting_count
+        .max(context.upcoming_meetings.len());
+
+    let upcoming_week = context
+        .upcoming_meetings
+        .iter()
+        .take(MAX_FALLBACK_LIST_ITEMS)
+        .map(|meeting| {
+            format!(
+                "{} - {}",
+                to_display_day_time(&meeting.start_at),
+                sanitize_or_fallback(&meeting.title, "Untitled meeting")
+            )
+        })
+        .collect::<Vec<_>>();
+
+    WeeklyReviewContract {
+        version: OUTPUT_CONTRACT_VERSION_V1.to_string(),
+        output: WeeklyReviewOutput {
+            headline: "Weekly review fallback".to_string(),
+            summary: format!(
+                "Generated deterministic fallback: {past_meeting_count} meeting{} and {email_count} email{} this week, {upcoming_meeting_count} meeting{} next week.",
+                if past_meeting_count == 1 { "" } else { "s" },
+                if email_count == 1 { "" } else { "s" },
+                if upcoming_meeting_count == 1 { "" } else { "s" }
+            ),
+            meetings_recap: vec!["Review last week's calendar manually.".to_string()],
+            email_recap: vec!["Review last week's inbox manually.".to_string()],
+            upcoming_week,
+        },
+    }
+}
+
 fn fallback_general_chat_summary(_context_payload: &Value) -> GeneralChatSummaryContract {
     GeneralChatSummaryContract {
         version: OUTPUT_CONTRACT_VERSION_V1.to_string(),
@@ -272,6 +328,28 @@ fn fallback_assistant_semantic_plan() -> AssistantSemanticPlanContract {
     }
 }
 
+fn fallback_automation_draft(context_payload: &Value) -> AutomationDraftContract {
+    let request = context_payload
+        .get("request_context")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+
+    AutomationDraftContract {
+        version: AUTOMATION_DRAFT_VERSION_V1.to_string(),
+        output: AutomationDraftOutput {
+            title: "New automation".to_string(),
+            prompt: request
+                .chars()
+                .take(MAX_AUTOMATION_DRAFT_PROMPT_CHARS)
+                .collect(),
+            confidence: 0.2,
+            needs_clarification: true,
+            clarifying_question: Some("When should this automation run?".to_string()),
+            schedule: None,
+        },
+    }
+}
+
 fn passes_action_safety_policy(contract: &AssistantOutputContract) -> bool {
     let AssistantOutputContract::UrgentEmailSummary(urgent) = contract else {
         return true;
@@ -355,6 +433,28 @@ fn contract_within_bounds(contract: &AssistantOutputContract) -> bool {
                     .iter()
                     .all(|item| fits_chars(item, MAX_OUTPUT_TEXT_CHARS))
         }
+        AssistantOutputContract::WeeklyReview(review) => {
+            fits_chars(&review.output.headline, MAX_OUTPUT_TITLE_CHARS)
+                && fits_chars(&review.output.summary, MAX_WEEKLY_REVIEW_TEXT_CHARS)
+                && [
+                    &review.output.meetings_recap,
+                    &review.output.email_recap,
+                    &review.output.upcoming_week,
+                ]
+                .into_iter()
+                .all(|items| {
+                    items.len() <= MAX_WEEKLY_REVIEW_LIST_ITEMS
+                        && items
+                            .iter()
+                            .all(|item| fits_chars(item, MAX_OUTPUT_TEXT_CHARS))
+                })
+        }
+        AssistantOutputContract::AutomationDraft(draft) => {
+            (0.0..=1.0).contains(&draft.output.confidence)
+                && fits_chars(&draft.output.title, MAX_OUTPUT_TITLE_CHARS)
+                && fits_chars(&draft.output.prompt, MAX_AUTOMATION_DRAFT_PROMPT_CHARS)
+                && (!draft.output.needs_clarification || draft.output.clarifying_question.is_some())
+        }
         AssistantOutputContract::AssistantSemanticPlan(plan) => {
             (0.0..=1.0).contains(&plan.output.confidence)
                 && plan.output.capabilities.len() <= 2
@@ -414,6 +514,17 @@ fn to_display_time(raw: &str) -> String {
         .unwrap_or_else(|_| "time TBD".to_string())
 }
 
+fn to_display_day_time(raw: &str) -> String {
+    DateTime::parse_from_rfc3339(raw)
+        .map(|timestamp| {
+            timestamp
+                .with_timezone(&Utc)
+                .format("%a %H:%M UTC")
+                .to_string()
+        })
+        .unwrap_or_else(|_| "time TBD".to_string())
+}
+
 fn collapse_whitespace(value: &str) -> String {
     value.split_whitespace().collect::<Vec<_>>().join(" ")
 }
@@ -438,6 +549,18 @@ struct FallbackMorningBriefContext {
     urgent_email_candidates: Vec<FallbackUrgentEmailEntry>,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+struct FallbackWeeklyReviewContext {
+    #[serde(default)]
+    past_meeting_count: usize,
+    #[serde(default)]
+    email_count: usize,
+    #[serde(default)]
+    upcoming_meeting_count: usize,
+    #[serde(default)]
+    upcoming_meetings: Vec<FallbackMeetingEntry>,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 struct FallbackUrgentEmailContext {
     #[serde(default)]
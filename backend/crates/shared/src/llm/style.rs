@@ -0,0 +1,430 @@
+use std::env;
+
+use thiserror::Error;
+
+use super::contracts::AssistantCapability;
+use super::gateway::{LlmGateway, LlmGatewayFuture, LlmGatewayRequest, LlmWarmUpFuture};
+use super::routing::environment_scoped_value;
+use crate::enclave_runtime::AlfredEnvironment;
+
+/// Capabilities that produce user-facing prose and therefore accept a style profile. The
+/// semantic planner and automation drafts emit structured plans and are never styled.
+const STYLED_CAPABILITIES: [AssistantCapability; 5] = [
+    AssistantCapability::MeetingsSummary,
+    AssistantCapability::GeneralChatSummary,
+    AssistantCapability::MorningBrief,
+    AssistantCapability::UrgentEmailSummary,
+    AssistantCapability::WeeklyReview,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LlmResponseTone {
+    Neutral,
+    Terse,
+    Warm,
+    Formal,
+}
+
+impl LlmResponseTone {
+    fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "neutral" => Some(Self::Neutral),
+            "terse" => Some(Self::Terse),
+            "warm" => Some(Self::Warm),
+            "formal" => Some(Self::Formal),
+            _ => None,
+        }
+    }
+
+    fn directive(self) -> Option<&'static str> {
+        match self {
+            Self::Neutral => None,
+            Self::Terse => Some("Be terse: lead with what matters and drop pleasantries."),
+            Self::Warm => Some("Be warm and personable while staying direct and helpful."),
+            Self::Formal => Some("Use a formal, professional tone."),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LlmBulletPreference {
+    Auto,
+    Prefer,
+    Avoid,
+}
+
+impl LlmBulletPreference {
+    fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "auto" => Some(Self::Auto),
+            "prefer" => Some(Self::Prefer),
+            "avoid" => Some(Self::Avoid),
+            _ => None,
+        }
+    }
+
+    fn directive(self) -> Option<&'static str> {
+        match self {
+            Self::Auto => None,
+            Self::Prefer => Some("Prefer short bullet-style key points over paragraphs."),
+            Self::Avoid => Some("Prefer flowing sentences over bullet-style lists."),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LlmStyleProfile {
+    pub tone: LlmResponseTone,
+    pub max_words: Option<u32>,
+    pub bullets: LlmBulletPreference,
+}
+
+impl LlmStyleProfile {
+    pub const NEUTRAL: Self = Self {
+        tone: LlmResponseTone::Neutral,
+        max_words: None,
+        bullets: LlmBulletPreference::Auto,
+    };
+
+    /// The sentence appended to the system prompt, or `None` when the profile asks for nothing
+    /// beyond the capability's own prompt.
+    pub fn prompt_directive(&self) -> Option<String> {
+        let max_words = self
+            .max_words
+            .map(|max_words| format!("Keep each free-text field under {max_words} words."));
+        let parts = [
+            self.tone.directive().map(ToString::to_string),
+            max_words,
+            self.bullets.directive().map(ToString::to_string),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+
+        if parts.is_empty() {
+            None
+        } else {
+            Some(format!("Response style: {}", parts.join(" ")))
+        }
+    }
+}
+
+/// Response style per capability. Each variable may be overridden for one environment by
+/// suffixing it with the upper-cased `ALFRED_ENV` value, e.g.
+/// `LLM_STYLE_MORNING_BRIEF_TONE_STAGING`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LlmStyleConfig {
+    pub environment: AlfredEnvironment,
+    profiles: Vec<(AssistantCapability, LlmStyleProfile)>,
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum LlmStyleConfigError {
+    #[error("invalid ALFRED_ENV: {0}")]
+    InvalidEnvironment(String),
+    #[error("{key} must be {expected}, got '{value}'")]
+    InvalidValue {
+        key: String,
+        value: String,
+        expected: &'static str,
+    },
+}
+
+impl LlmStyleConfig {
+    pub fn from_env() -> Result<Self, LlmStyleConfigError> {
+        let environment = env::var("ALFRED_ENV")
+            .unwrap_or_else(|_| "production".to_string())
+            .parse::<AlfredEnvironment>()
+            .map_err(LlmStyleConfigError::InvalidEnvironment)?;
+        Self::from_lookup(environment, |key| env::var(key).ok())
+    }
+
+    pub fn from_lookup(
+        environment: AlfredEnvironment,
+        lookup: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, LlmStyleConfigError> {
+        let profiles = STYLED_CAPABILITIES
+            .into_iter()
+            .map(|capability| {
+                read_profile(environment, &lookup, capability).map(|profile| (capability, profile))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            environment,
+            profiles,
+        })
+    }
+
+    pub fn profile(&self, capability: AssistantCapability) -> &LlmStyleProfile {
+        self.profiles
+            .iter()
+            .find(|(styled, _)| *styled == capability)
+            .map(|(_, profile)| profile)
+            .unwrap_or(&LlmStyleProfile::NEUTRAL)
+    }
+
+    /// Appends the capability's style directive to the system prompt. This runs on the final
+    /// request, so lanes that replace the template prompt are styled the same way.
+    pub fn apply(&self, mut request: LlmGatewayRequest) -> LlmGatewayRequest {
+        if let Some(directive) = self.profile(request.capability).prompt_directive() {
+            request.system_prompt = format!("{}\n\n{directive}", request.system_prompt);
+        }
+        request
+    }
+}
+
+/// Applies `LlmStyleConfig` to every request before it reaches the wrapped gateway.
+#[derive(Clone)]
+pub struct StyledLlmGateway<G> {
+    inner: G,
+    styles: LlmStyleConfig,
+}
+
+impl<G> StyledLlmGateway<G> {
+    pub fn new(inner: G, styles: LlmStyleConfig) -> Self {
+        Self { inner, styles }
+    }
+}
+
+impl<G> LlmGateway for StyledLlmGateway<G>
+where
+    G: LlmGateway,
+{
+    fn generate<'a>(&'a self, request: LlmGatewayRequest) -> LlmGatewayFuture<'a> {
+        self.inner.generate(self.styles.apply(request))
+    }
+
+    fn warm_up<'a>(&'a self) -> LlmWarmUpFuture<'a> {
+        self.inner.warm_up()
+    }
+}
+
+fn default_profile(capability: AssistantCapability) -> LlmStyleProfile {
+    match capability {
+        AssistantCapability::MorningBrief => LlmStyleProfile {
+            tone: LlmResponseTone::Terse,
+            max_words: None,
+            bullets: LlmBulletPreference::Prefer,
+        },
+        AssistantCapability::GeneralChatSummary => LlmStyleProfile {
+            tone: LlmResponseTone::Warm,
+            max_words: None,
+            bullets: LlmBulletPreference::Auto,
+        },
+        _ => LlmStyleProfile::NEUTRAL,
+    }
+}
+
+fn env_segment(capability: AssistantCapability) -> &'static str {
+    match capability {
+        AssistantCapability::MeetingsSummary => "MEETINGS_SUMMARY",
+        AssistantCapability::GeneralChatSummary => "GENERAL_CHAT",
+        AssistantCapability::MorningBrief => "MORNING_BRIEF",
+        AssistantCapability::UrgentEmailSummary => "URGENT_EMAIL",
+        AssistantCapability::AssistantSemanticPlan => "SEMANTIC_PLAN",
+        AssistantCapability::WeeklyReview => "WEEKLY_REVIEW",
+        AssistantCapability::AutomationDraft => "AUTOMATION_DRAFT",
+    }
+}
+
+fn read_profile(
+    environment: AlfredEnvironment,
+    lookup: &impl Fn(&str) -> Option<String>,
+    capability: AssistantCapability,
+) -> Result<LlmStyleProfile, LlmStyleConfigError> {
+    let defaults = default_profile(capability);
+    let key = |suffix: &str| format!("LLM_STYLE_{}_{suffix}", env_segment(capability));
+    let value = |suffix: &str| {
+        environment_scoped_value(environment, lookup, &key(suffix))
+            .filter(|(_, value)| !value.is_empty())
+    };
+
+    let tone = match value("TONE") {
+        Some((key, value)) => {
+            LlmResponseTone::parse(&value).ok_or(LlmStyleConfigError::InvalidValue {
+                key,
+                value,
+                expected: "one of neutral, terse, warm, formal",
+            })?
+        }
+        None => defaults.tone,
+    };
+    let max_words = match value("MAX_WORDS") {
+        Some((_, value)) if value.eq_ignore_ascii_case("none") => None,
+        Some((key, value)) => match value.parse::<u32>() {
+            Ok(max_words) if max_words > 0 => Some(max_words),
+            _ => {
+                return Err(LlmStyleConfigError::InvalidValue {
+                    key,
+                    value,
+                    expected: "a positive integer or none",
+                });
+            }
+        },
+        None => defaults.max_words,
+    };
+    let bullets = match value("BULLETS") {
+        Some((key, value)) => {
+            LlmBulletPreference::parse(&value).ok_or(LlmStyleConfigError::InvalidValue {
+                key,
+                value,
+                expected: "one of auto, prefer, avoid",
+            })?
+        }
+        None => defaults.bullets,
+    };
+
+    Ok(LlmStyleProfile {
+        tone,
+        max_words,
+        bullets,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde_json::json;
+
+    use super::{
+        LlmBulletPreference, LlmResponseTone, LlmStyleConfig, LlmStyleConfigError, LlmStyleProfile,
+    };
+    use crate::enclave_runtime::AlfredEnvironment;
+    use crate::llm::{AssistantCapability, LlmGatewayRequest, template_for_capability};
+
+    fn config(
+        environment: AlfredEnvironment,
+        vars: &[(&str, &str)],
+    ) -> Result<LlmStyleConfig, LlmStyleConfigError> {
+        let vars = vars
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect::<HashMap<_, _>>();
+        LlmStyleConfig::from_lookup(environment, |key| vars.get(key).cloned())
+    }
+
+    #[test]
+    fn defaults_keep_briefs_terse_and_chat_warm() {
+        let config = config(AlfredEnvironment::Production, &[]).expect("defaults are valid");
+
+        let brief = config.profile(AssistantCapability::MorningBrief);
+        assert_eq!(brief.tone, LlmResponseTone::Terse);
+        assert_eq!(brief.bullets, LlmBulletPreference::Prefer);
+        assert_eq!(
+            config.profile(AssistantCapability::GeneralChatSummary).tone,
+            LlmResponseTone::Warm
+        );
+        assert_eq!(
+            config.profile(AssistantCapability::MeetingsSummary),
+            &LlmStyleProfile::NEUTRAL
+        );
+        assert_eq!(
+            config.profile(AssistantCapability::AssistantSemanticPlan),
+            &LlmStyleProfile::NEUTRAL
+        );
+    }
+
+    #[test]
+    fn environment_scoped_values_take_precedence() {
+        let vars = [
+            ("LLM_STYLE_MORNING_BRIEF_MAX_WORDS", "120"),
+            ("LLM_STYLE_MORNING_BRIEF_MAX_WORDS_STAGING", "none"),
+            ("LLM_STYLE_WEEKLY_REVIEW_TONE_STAGING", "formal"),
+            ("LLM_STYLE_GENERAL_CHAT_BULLETS", "avoid"),
+            // The planner is never styled, even when configured.
+            ("LLM_STYLE_SEMANTIC_PLAN_TONE", "warm"),
+        ];
+
+        let staging = config(AlfredEnvironment::Staging, &vars).expect("staging is valid");
+        assert_eq!(
+            staging.profile(AssistantCapability::MorningBrief).max_words,
+            None
+        );
+        assert_eq!(
+            staging.profile(AssistantCapability::WeeklyReview).tone,
+            LlmResponseTone::Formal
+        );
+        assert_eq!(
+            staging
+                .profile(AssistantCapability::GeneralChatSummary)
+                .bullets,
+            LlmBulletPreference::Avoid
+        );
+        assert_eq!(
+            staging.profile(AssistantCapability::AssistantSemanticPlan),
+            &LlmStyleProfile::NEUTRAL
+        );
+
+        let production = config(AlfredEnvironment::Production, &vars).expect("prod is valid");
+        assert_eq!(
+            production
+                .profile(AssistantCapability::MorningBrief)
+                .max_words,
+            Some(120)
+        );
+        assert_eq!(
+            production.profile(AssistantCapability::WeeklyReview).tone,
+            LlmResponseTone::Neutral
+        );
+    }
+
+    #[test]
+    fn invalid_values_are_rejected() {
+        assert_eq!(
+            config(
+                AlfredEnvironment::Production,
+                &[("LLM_STYLE_URGENT_EMAIL_TONE", "snarky")]
+            ),
+            Err(LlmStyleConfigError::InvalidValue {
+                key: "LLM_STYLE_URGENT_EMAIL_TONE".to_string(),
+                value: "snarky".to_string(),
+                expected: "one of neutral, terse, warm, formal",
+            })
+        );
+        assert!(matches!(
+            config(
+                AlfredEnvironment::Local,
+                &[("LLM_STYLE_MORNING_BRIEF_MAX_WORDS_LOCAL", "0")]
+            ),
+            Err(LlmStyleConfigError::InvalidValue { .. })
+        ));
+        assert!(matches!(
+            config(
+                AlfredEnvironment::Production,
+                &[("LLM_STYLE_MEETINGS_SUMMARY_BULLETS", "always")]
+            ),
+            Err(LlmStyleConfigError::InvalidValue { .. })
+        ));
+    }
+
+    #[test]
+    fn apply_appends_directive_only_for_styled_capabilities() {
+        let config = config(
+            AlfredEnvironment::Production,
+            &[("LLM_STYLE_MORNING_BRIEF_MAX_WORDS", "80")],
+        )
+        .expect("config is valid");
+
+        let template = template_for_capability(AssistantCapability::MorningBrief);
+        let styled = config.apply(LlmGatewayRequest::from_template(
+            template.clone(),
+            json!({}),
+        ));
+        assert_eq!(
+            styled.system_prompt,
+            format!(
+                "{}\n\nResponse style: Be terse: lead with what matters and drop pleasantries. Keep each free-text field under 80 words. Prefer short bullet-style key points over paragraphs.",
+                template.system_prompt
+            )
+        );
+
+        let meetings = LlmGatewayRequest::from_template(
+            template_for_capability(AssistantCapability::MeetingsSummary),
+            json!({}),
+        );
+        let unstyled = config.apply(meetings.clone());
+        assert_eq!(unstyled.system_prompt, meetings.system_prompt);
+    }
+}
@@ -6,6 +6,7 @@ use thiserror::Error;
 use super::contracts::{
     AssistantCapability, AssistantOutputContract, ContractError, output_schema, parse_contract,
 };
+use super::gateway::{LlmToolCall, LlmToolDefinition};
 
 #[derive(Debug, Error)]
 pub enum OutputValidationError {
@@ -25,6 +26,16 @@ pub enum OutputValidationError {
     Contract(#[from] ContractError),
 }
 
+#[derive(Debug, Error)]
+pub enum ToolCallValidationError {
+    #[error("model called undeclared tool {0}")]
+    UnknownTool(String),
+    #[error("parameters schema for tool {tool} failed to compile: {message}")]
+    SchemaCompile { tool: String, message: String },
+    #[error("arguments for tool {tool} failed schema validation: {errors:?}")]
+    SchemaViolation { tool: String, errors: Vec<String> },
+}
+
 pub fn validate_output_json(
     capability: AssistantCapability,
     raw_json: &str,
@@ -49,6 +60,37 @@ pub fn validate_output_value(
     parse_contract(capability, payload.clone()).map_err(OutputValidationError::from)
 }
 
+/// Checks every call names a tool the request offered and that its arguments match that
+/// tool's parameter schema. Tool schemas are caller-supplied, so they compile per call rather
+/// than once like the contract schemas.
+pub fn validate_tool_calls(
+    tools: &[LlmToolDefinition],
+    tool_calls: &[LlmToolCall],
+) -> Result<(), ToolCallValidationError> {
+    for tool_call in tool_calls {
+        let tool = tools
+            .iter()
+            .find(|tool| tool.name == tool_call.name)
+            .ok_or_else(|| ToolCallValidationError::UnknownTool(tool_call.name.clone()))?;
+        let validator = JSONSchema::compile(&tool.parameters).map_err(|err| {
+            ToolCallValidationError::SchemaCompile {
+                tool: tool.name.clone(),
+                message: err.to_string(),
+            }
+        })?;
+        if let Err(validation_errors) = validator.validate(&tool_call.arguments) {
+            let errors = validation_errors
+                .map(|err| err.to_string())
+                .collect::<Vec<_>>();
+            return Err(ToolCallValidationError::SchemaViolation {
+                tool: tool.name.clone(),
+                errors,
+            });
+        }
+    }
+    Ok(())
+}
+
 static MEETINGS_SUMMARY_VALIDATOR: LazyLock<Result<JSONSchema, String>> = LazyLock::new(|| {
     JSONSchema::compile(&output_schema(AssistantCapability::MeetingsSummary))
         .map_err(|err| err.to_string())
@@ -75,6 +117,16 @@ static ASSISTANT_SEMANTIC_PLAN_VALIDATOR: LazyLock<Result<JSONSchema, String>> =
             .map_err(|err| err.to_string())
     });
 
+static WEEKLY_REVIEW_VALIDATOR: LazyLock<Result<JSONSchema, String>> = LazyLock::new(|| {
+    JSONSchema::compile(&output_schema(AssistantCapability::WeeklyReview))
+        .map_err(|err| err.to_string())
+});
+
+static AUTOMATION_DRAFT_VALIDATOR: LazyLock<Result<JSONSchema, String>> = LazyLock::new(|| {
+    JSONSchema::compile(&output_schema(AssistantCapability::AutomationDraft))
+        .map_err(|err| err.to_string())
+});
+
 fn validator_for_capability(
     capability: AssistantCapability,
 ) -> Result<&'static JSONSchema, OutputValidationError> {
@@ -84,6 +136,8 @@ fn validator_for_capability(
         AssistantCapability::MorningBrief => &*MORNING_BRIEF_VALIDATOR,
         AssistantCapability::UrgentEmailSummary => &*URGENT_EMAIL_SUMMARY_VALIDATOR,
         AssistantCapability::AssistantSemanticPlan => &*ASSISTANT_SEMANTIC_PLAN_VALIDATOR,
+        AssistantCapability::WeeklyReview => &*WEEKLY_REVIEW_VALIDATOR,
+        AssistantCapability::AutomationDraft => &*AUTOMATION_DRAFT_VALIDATOR,
     };
 
     validator_result
@@ -98,10 +152,14 @@ fn validator_for_capability(
 mod tests {
     use serde_json::json;
 
-    use super::{OutputValidationError, validate_output_json, validate_output_value};
+    use super::{
+        OutputValidationError, ToolCallValidationError, validate_output_json,
+        validate_output_value, validate_tool_calls,
+    };
     use crate::llm::contracts::{
         AssistantCapability, AssistantOutputContract, ContractError, OUTPUT_CONTRACT_VERSION_V1,
     };
+    use crate::llm::gateway::{LlmToolCall, LlmToolDefinition};
 
     #[test]
     fn validate_output_value_accepts_valid_meetings_summary_contract() {
@@ -196,4 +254,39 @@ mod tests {
             "expected version mismatch, got {err:?}"
         );
     }
+
+    #[test]
+    fn validate_tool_calls_checks_tool_names_and_arguments() {
+        let tools = vec![LlmToolDefinition {
+            name: "search_calendar".to_string(),
+            description: "Find events in a time window.".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "window_days": { "type": "integer", "minimum": 1 }
+                },
+                "required": ["window_days"],
+                "additionalProperties": false
+            }),
+        }];
+        let call = |name: &str, arguments| LlmToolCall {
+            id: Some("call_1".to_string()),
+            name: name.to_string(),
+            arguments,
+        };
+
+        validate_tool_calls(
+            &tools,
+            &[call("search_calendar", json!({ "window_days": 7 }))],
+        )
+        .expect("declared tool with valid arguments should pass");
+        assert!(matches!(
+            validate_tool_calls(&tools, &[call("send_email", json!({}))]),
+            Err(ToolCallValidationError::UnknownTool(name)) if name == "send_email"
+        ));
+        assert!(matches!(
+            validate_tool_calls(&tools, &[call("search_calendar", json!({ "window_days": 0 }))]),
+            Err(ToolCallValidationError::SchemaViolation { tool, .. }) if tool == "search_calendar"
+        ));
+    }
 }
@@ -1,381 +1,179 @@
-use std::collections::HashMap;
-
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use uuid::Uuid;
-
-use crate::automation_schedule::AutomationScheduleType;
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "lowercase")]
-pub enum ApnsEnvironment {
-    Sandbox,
-    Production,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct RegisterDeviceRequest {
-    pub device_id: String,
-    pub apns_token: String,
-    pub environment: ApnsEnvironment,
-    #[serde(default)]
-    pub notification_key_algorithm: Option<String>,
-    #[serde(default)]
-    pub notification_public_key: Option<String>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct SendTestNotificationRequest {
-    #[serde(default)]
-    pub title: Option<String>,
-    #[serde(default)]
-    pub body: Option<String>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct SendTestNotificationResponse {
-    pub queued_job_id: String,
-    pub status: String,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AssistantQueryRequest {
-    pub envelope: AssistantEncryptedRequestEnvelope,
-    #[serde(default)]
-    pub session_id: Option<Uuid>,
-}
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(deny_unknown_fields)]
-pub struct AssistantEncryptedRequestEnvelope {
-    pub version: String,
-    pub algorithm: String,
-    pub key_id: String,
-    pub request_id: String,
-    pub client_ephemeral_public_key: String,
-    pub nonce: String,
-    pub ciphertext: String,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(deny_unknown_fields)]
-pub struct AssistantEncryptedResponseEnvelope {
-    pub version: String,
-    pub algorithm: String,
-    pub key_id: String,
-    pub request_id: String,
-    pub nonce: String,
-    pub ciphertext: String,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(deny_unknown_fields)]
-pub struct AssistantSessionStateEnvelope {
-    pub version: String,
-    pub algorithm: String,
-    pub key_id: String,
-    pub nonce: String,
-    pub ciphertext: String,
-    pub expires_at: DateTime<Utc>,
+mod admin;
+mod assistant;
+mod audit;
+mod automations;
+mod connectors;
+mod devices;
+mod error_catalog;
+mod preferences;
+mod privacy;
+mod status;
+mod webhooks;
+
+pub use admin::{
+    AdminConnectorHealth, AdminDeadLetterJob, AdminFeatureFlag, AdminJobQueueDepth,
+    AdminOrganizationMember, AdminOrganizationPlan, AdminRateLimitState, AdminRateLimitWindow,
+    ConnectorHealthCheckResponse, FeatureFlagEvaluation, ListAdminConnectorHealthResponse,
+    ListAdminDeadLetterJobsResponse, ListAdminFeatureFlagsResponse,
+    ListAdminOrganizationMembersResponse, ListFeatureFlagsResponse, RateLimitBackend,
+    ReplayDeadLetterJobResponse, SetOrganizationPlanRequest,
+};
+pub use assistant::{
+    AssistantAttestedKeyAttestation, AssistantAttestedKeyRequest, AssistantAttestedKeyResponse,
+    AssistantAttestedKeyRotation, AssistantDegradedReason, AssistantEncryptedRequestEnvelope,
+    AssistantEncryptedResponseEnvelope, AssistantFocusBlockProposal,
+    AssistantPlaintextExportedSession, AssistantPlaintextQueryRequest,
+    AssistantPlaintextQueryResponse, AssistantPlaintextSessionExport, AssistantQueryCapability,
+    AssistantQueryRequest, AssistantQueryResponse, AssistantResponsePart,
+    AssistantResponsePartType, AssistantSenderFilterSuggestion, AssistantSessionExportRequest,
+    AssistantSessionExportResponse, AssistantSessionStateEnvelope, AssistantSessionSummary,
+    AssistantSessionTitleEnvelope, AssistantStructuredPayload, ListAssistantSessionsResponse,
+    UpdateAssistantSessionRequest,
+};
+pub use audit::{
+    AuditChainBreak, AuditChainBreakReason, AuditChainVerification, AuditEvent,
+    AuditEventSignatureStatus, AuditEventSignatureVerification, AuditMetadata,
+    ListAuditEventsResponse,
+};
+pub use automations::{
+    AutomationPlaintextDraft, AutomationPromptEnvelope, AutomationReportEnvelope,
+    AutomationReportSummary, AutomationRuleSummary, AutomationRunPreview, AutomationSchedule,
+    AutomationSchedulePreview, AutomationStatus, CreateAutomationRequest, DraftAutomationRequest,
+    DraftAutomationResponse, ListAutomationReportsResponse, ListAutomationsResponse,
+    PreviewAutomationScheduleRequest, RunAutomationNowRequest, RunAutomationNowResponse,
+    SnoozeAutomationRequest, SnoozePreset, TriggerAutomationDebugRunResponse,
+    UpdateAutomationRequest,
+};
+pub use connectors::{
+    CompleteGoogleConnectRequest, CompleteGoogleConnectResponse, ConnectCaldavRequest,
+    ConnectCaldavResponse, ConnectImapRequest, ConnectImapResponse, ConnectorStatus,
+    ConnectorSummary, ListConnectorsResponse, RevokeAllConnectorsResponse, RevokeConnectorResponse,
+    RevokedConnector, StartGoogleConnectRequest, StartGoogleConnectResponse,
+    UpgradeConnectorScopesRequest, UpgradeConnectorScopesResponse,
+};
+pub use devices::{
+    ApnsEnvironment, DeviceSummary, ListDevicesResponse, RegisterDeviceRequest,
+    RegisterRequestSigningKeyRequest, RegisterRequestSigningKeyResponse,
+    RotateDeviceNotificationKeyRequest, RotateDeviceNotificationKeyResponse,
+    SendTestNotificationRequest, SendTestNotificationResponse, SimulatedJobFailure,
+    TestNotificationJobType, TestNotificationSimulatedPayload, UploadSupportDiagnosticsRequest,
+    UploadSupportDiagnosticsResponse,
+};
+pub use error_catalog::{ApiErrorCode, ERROR_CATALOG_DOCS_URL};
+pub use preferences::{
+    DepartureAlertPreferencesResponse, MorningBriefFeedbackRequest, MorningBriefProfileResponse,
+    UpdateDepartureAlertPreferencesRequest, UpdateMorningBriefProfileRequest,
+};
+pub use privacy::{
+    DeleteAllResponse, DeleteAllStatusResponse, DownloadPrivacyExportRequest,
+    PrivacyExportResponse, PrivacyExportStatusResponse,
+};
+pub use status::{
+    AvailabilityComponent, ComponentStatus, ComponentStatusEntry, DailyAvailability,
+    DependencyReadiness, PublicComponentStatus, PublicSloStatus, PublicStatusResponse,
+    ReadinessDependency, ReadinessResponse, ReadinessStatus, StatusComponent, SystemStatusResponse,
+};
+pub use webhooks::{
+    CreateWebhookRequest, CreateWebhookResponse, ListWebhookDeliveriesResponse,
+    ListWebhooksResponse, WebhookDeliverySummary, WebhookSummary,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct OkResponse {
+    pub ok: bool,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "snake_case")]
-pub enum AssistantQueryCapability {
-    MeetingsToday,
-    CalendarLookup,
-    EmailLookup,
-    GeneralChat,
-    Mixed,
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ErrorResponse {
+    pub error: ErrorBody,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// Subscription tier that sets a user's quotas. Tiers are ordered from lowest to highest.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema,
+)]
 #[serde(rename_all = "snake_case")]
-pub enum AssistantResponsePartType {
-    ChatText,
-    ToolSummary,
+pub enum UserPlan {
+    Free,
+    Pro,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
-pub struct AssistantStructuredPayload {
-    pub title: String,
-    pub summary: String,
-    pub key_points: Vec<String>,
-    pub follow_ups: Vec<String>,
-}
-
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
-pub struct AssistantResponsePart {
-    #[serde(rename = "type")]
-    pub part_type: AssistantResponsePartType,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub text: Option<String>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub capability: Option<AssistantQueryCapability>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub payload: Option<AssistantStructuredPayload>,
-}
-
-impl AssistantResponsePart {
-    pub fn chat_text(text: impl Into<String>) -> Self {
-        Self {
-            part_type: AssistantResponsePartType::ChatText,
-            text: Some(text.into()),
-            capability: None,
-            payload: None,
+impl UserPlan {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Free => "free",
+            Self::Pro => "pro",
         }
     }
 
-    pub fn tool_summary(
-        capability: AssistantQueryCapability,
-        payload: AssistantStructuredPayload,
-    ) -> Self {
-        Self {
-            part_type: AssistantResponsePartType::ToolSummary,
-            text: None,
-            capability: Some(capability),
-            payload: Some(payload),
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "free" => Some(Self::Free),
+            "pro" => Some(Self::Pro),
+            _ => None,
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AssistantQueryResponse {
-    pub session_id: Uuid,
-    pub envelope: AssistantEncryptedResponseEnvelope,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AssistantSessionSummary {
-    pub session_id: Uuid,
-    pub created_at: DateTime<Utc>,
-    pub updated_at: DateTime<Utc>,
-    pub expires_at: DateTime<Utc>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ListAssistantSessionsResponse {
-    pub items: Vec<AssistantSessionSummary>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AssistantPlaintextQueryRequest {
-    pub query: String,
-    #[serde(default)]
-    pub session_id: Option<Uuid>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AssistantPlaintextQueryResponse {
-    pub session_id: Uuid,
-    pub capability: AssistantQueryCapability,
-    pub display_text: String,
-    pub payload: AssistantStructuredPayload,
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub response_parts: Vec<AssistantResponsePart>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(deny_unknown_fields)]
-pub struct AssistantAttestedKeyRequest {
-    pub challenge_nonce: String,
-    pub issued_at: i64,
-    pub expires_at: i64,
-    pub request_id: String,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(deny_unknown_fields)]
-pub struct AssistantAttestedKeyResponse {
-    pub key_id: String,
-    pub algorithm: String,
-    pub public_key: String,
-    pub key_expires_at: i64,
-    pub attestation: AssistantAttestedKeyAttestation,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(deny_unknown_fields)]
-pub struct AssistantAttestedKeyAttestation {
-    pub runtime: String,
-    pub measurement: String,
-    pub challenge_nonce: String,
-    pub issued_at: i64,
-    pub expires_at: i64,
-    pub request_id: String,
-    pub evidence_issued_at: i64,
-    pub signature: Option<String>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct StartGoogleConnectRequest {
-    pub redirect_uri: String,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct StartGoogleConnectResponse {
-    pub auth_url: String,
-    pub state: String,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct CompleteGoogleConnectRequest {
-    #[serde(default)]
-    pub code: Option<String>,
-    pub state: String,
-    #[serde(default)]
-    pub error: Option<String>,
-    #[serde(default)]
-    pub error_description: Option<String>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
-pub enum ConnectorStatus {
-    Active,
-    Revoked,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct CompleteGoogleConnectResponse {
-    pub connector_id: String,
-    pub status: ConnectorStatus,
-    pub granted_scopes: Vec<String>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct RevokeConnectorResponse {
-    pub status: ConnectorStatus,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ConnectorSummary {
-    pub connector_id: String,
-    pub provider: String,
-    pub status: ConnectorStatus,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ListConnectorsResponse {
-    pub items: Vec<ConnectorSummary>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(deny_unknown_fields)]
-pub struct AutomationPromptEnvelope {
-    pub version: String,
-    pub algorithm: String,
-    pub key_id: String,
-    pub request_id: String,
-    pub client_ephemeral_public_key: String,
-    pub nonce: String,
-    pub ciphertext: String,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(deny_unknown_fields)]
-pub struct CreateAutomationRequest {
-    pub title: String,
-    pub schedule: AutomationSchedule,
-    pub prompt_envelope: AutomationPromptEnvelope,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(deny_unknown_fields)]
-pub struct AutomationSchedule {
-    pub schedule_type: AutomationScheduleType,
-    pub time_zone: String,
-    pub local_time: String,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
-pub enum AutomationStatus {
-    Active,
-    Paused,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(deny_unknown_fields)]
-pub struct UpdateAutomationRequest {
-    #[serde(default)]
-    pub title: Option<String>,
-    #[serde(default)]
-    pub schedule: Option<AutomationSchedule>,
-    #[serde(default)]
-    pub prompt_envelope: Option<AutomationPromptEnvelope>,
-    #[serde(default)]
-    pub status: Option<AutomationStatus>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AutomationRuleSummary {
-    pub rule_id: String,
-    pub title: String,
-    pub status: AutomationStatus,
-    pub schedule: AutomationSchedule,
-    pub next_run_at: DateTime<Utc>,
-    pub last_run_at: Option<DateTime<Utc>>,
-    pub prompt_sha256: String,
-    pub created_at: DateTime<Utc>,
-    pub updated_at: DateTime<Utc>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ListAutomationsResponse {
-    pub items: Vec<AutomationRuleSummary>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TriggerAutomationDebugRunResponse {
-    pub queued_job_id: String,
-    pub status: String,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AuditEvent {
-    pub id: String,
-    pub timestamp: DateTime<Utc>,
-    pub event_type: String,
-    pub connector: Option<String>,
-    pub result: String,
-    pub metadata: HashMap<String, String>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ListAuditEventsResponse {
-    pub items: Vec<AuditEvent>,
-    pub next_cursor: Option<String>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct DeleteAllResponse {
-    pub request_id: String,
-    pub status: String,
+/// A per-plan limit a request can run into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotaKind {
+    AssistantQueriesPerDay,
+    AutomationRules,
+    /// Enclave LLM calls per UTC day across assistant queries and automation runs.
+    LlmRequestsPerDay,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct QuotaExceededDetail {
+    pub kind: QuotaKind,
+    pub plan: UserPlan,
+    pub limit: i64,
+    pub used: i64,
+    /// When the quota next resets; absent for caps that only free up when the user removes
+    /// something, such as the automation rule count.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resets_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct DeleteAllStatusResponse {
-    pub request_id: String,
-    pub status: String,
-    pub created_at: DateTime<Utc>,
-    pub started_at: Option<DateTime<Utc>>,
-    pub completed_at: Option<DateTime<Utc>>,
-    pub failed_at: Option<DateTime<Utc>>,
+/// 429 body for a plan quota; `quota` says which limit was hit and when it resets.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct QuotaExceededResponse {
+    pub error: ErrorBody,
+    pub quota: QuotaExceededDetail,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct OkResponse {
-    pub ok: bool,
+/// The signed-in user's AI spend for the current UTC month. Amounts are estimated provider
+/// cost in millionths of a US dollar.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct LlmUsageResponse {
+    pub plan: UserPlan,
+    pub month_start: NaiveDate,
+    pub limit_usd_micros: i64,
+    pub spent_usd_micros: i64,
+    pub remaining_usd_micros: i64,
+    pub resets_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ErrorResponse {
+/// 409 body for an update whose expected version is stale; `current` is the stored resource.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct VersionConflictResponse<T> {
     pub error: ErrorBody,
+    pub current: T,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ErrorBody {
-    pub code: String,
+    pub code: ApiErrorCode,
     pub message: String,
+    /// Whether the same request can succeed later without changes.
+    #[serde(default)]
+    pub retryable: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub docs_url: Option<String>,
 }
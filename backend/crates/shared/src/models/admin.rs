@@ -0,0 +1,153 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::{ConnectorStatus, UserPlan};
+use crate::feature_flags::{FeatureFlag, FeatureFlagRule};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct AdminJobQueueDepth {
+    pub user_id: String,
+    pub pending: i64,
+    /// Pending jobs already past their due time.
+    pub due: i64,
+    pub running: i64,
+    /// Dead letters that have not been replayed.
+    pub dead_lettered: i64,
+    pub oldest_due_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AdminDeadLetterJob {
+    pub dead_letter_id: String,
+    pub job_id: String,
+    pub job_type: String,
+    pub attempts: i32,
+    pub reason_code: String,
+    pub reason_message: String,
+    pub failed_at: DateTime<Utc>,
+    pub replayed_at: Option<DateTime<Utc>>,
+    pub replay_job_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ListAdminDeadLetterJobsResponse {
+    pub items: Vec<AdminDeadLetterJob>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ReplayDeadLetterJobResponse {
+    pub dead_letter_id: String,
+    pub job_id: String,
+    pub replayed_at: DateTime<Utc>,
+    /// False when an earlier replay already queued `job_id`.
+    pub newly_queued: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AdminConnectorHealth {
+    pub connector_id: String,
+    pub provider: String,
+    pub status: ConnectorStatus,
+    pub health_score: i16,
+    pub refresh_failure_count: i32,
+    pub provider_unauthorized_count: i32,
+    pub missing_scopes: Vec<String>,
+    pub health_updated_at: Option<DateTime<Utc>>,
+    pub reauth_nudged_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ListAdminConnectorHealthResponse {
+    pub items: Vec<AdminConnectorHealth>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ConnectorHealthCheckResponse {
+    pub probe_succeeded: bool,
+    /// Failure kind of the provider probe, such as `provider_failed`.
+    pub probe_error_code: Option<String>,
+    pub connector: AdminConnectorHealth,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RateLimitBackend {
+    Redis,
+    InProcess,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct AdminRateLimitWindow {
+    pub route_class: String,
+    pub limit: u64,
+    pub window_seconds: u64,
+    pub requests_in_window: u64,
+    pub remaining: u64,
+    /// Seconds until the oldest request leaves the window; absent when the window is empty.
+    pub reset_seconds: Option<u64>,
+    pub backend: RateLimitBackend,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AdminRateLimitState {
+    pub user_id: String,
+    /// Remaining automation mutation block from an abuse escalation on this instance.
+    pub abuse_blocked_for_seconds: Option<u64>,
+    pub items: Vec<AdminRateLimitWindow>,
+}
+
+/// Content-blind view of one organization member.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AdminOrganizationMember {
+    pub user_id: String,
+    pub created_at: DateTime<Utc>,
+    /// Connectors that are not revoked.
+    pub connector_count: i64,
+    /// Automation rules that are not archived or completed.
+    pub automation_count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ListAdminOrganizationMembersResponse {
+    pub org_id: String,
+    /// Plan granted to every member; a member with a higher plan of their own keeps it.
+    pub plan: UserPlan,
+    pub items: Vec<AdminOrganizationMember>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SetOrganizationPlanRequest {
+    pub plan: UserPlan,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AdminOrganizationPlan {
+    pub org_id: String,
+    pub plan: UserPlan,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AdminFeatureFlag {
+    pub flag: FeatureFlag,
+    /// Rule in force: the override when one is set, otherwise the environment default.
+    pub rule: FeatureFlagRule,
+    pub default_rule: FeatureFlagRule,
+    pub overridden: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ListAdminFeatureFlagsResponse {
+    pub items: Vec<AdminFeatureFlag>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FeatureFlagEvaluation {
+    pub flag: FeatureFlag,
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ListFeatureFlagsResponse {
+    pub items: Vec<FeatureFlagEvaluation>,
+}
@@ -0,0 +1,331 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AssistantQueryRequest {
+    pub envelope: AssistantEncryptedRequestEnvelope,
+    #[serde(default)]
+    pub session_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AssistantEncryptedRequestEnvelope {
+    pub version: String,
+    pub algorithm: String,
+    pub key_id: String,
+    pub request_id: String,
+    pub client_ephemeral_public_key: String,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AssistantEncryptedResponseEnvelope {
+    pub version: String,
+    pub algorithm: String,
+    pub key_id: String,
+    pub request_id: String,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AssistantSessionStateEnvelope {
+    pub version: String,
+    pub algorithm: String,
+    pub key_id: String,
+    pub nonce: String,
+    pub ciphertext: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AssistantQueryCapability {
+    MeetingsToday,
+    CalendarLookup,
+    EmailLookup,
+    GeneralChat,
+    Mixed,
+    FocusTime,
+    EmailCleanup,
+}
+
+impl AssistantQueryCapability {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::MeetingsToday => "meetings_today",
+            Self::CalendarLookup => "calendar_lookup",
+            Self::EmailLookup => "email_lookup",
+            Self::GeneralChat => "general_chat",
+            Self::Mixed => "mixed",
+            Self::FocusTime => "focus_time",
+            Self::EmailCleanup => "email_cleanup",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AssistantResponsePartType {
+    ChatText,
+    ToolSummary,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct AssistantStructuredPayload {
+    pub title: String,
+    pub summary: String,
+    pub key_points: Vec<String>,
+    pub follow_ups: Vec<String>,
+}
+
+/// A suggested calendar block; the client turns accepted proposals into calendar writes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct AssistantFocusBlockProposal {
+    pub title: String,
+    pub start_at: DateTime<Utc>,
+    pub end_at: DateTime<Utc>,
+    pub duration_minutes: u32,
+}
+
+/// A bulk sender worth filtering. Carries sender address and counts only, never message content,
+/// so accepted suggestions can seed inbox rules.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct AssistantSenderFilterSuggestion {
+    pub sender: String,
+    pub unread_count: u32,
+    pub categories: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct AssistantResponsePart {
+    #[serde(rename = "type")]
+    pub part_type: AssistantResponsePartType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capability: Option<AssistantQueryCapability>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload: Option<AssistantStructuredPayload>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub focus_blocks: Vec<AssistantFocusBlockProposal>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sender_filters: Vec<AssistantSenderFilterSuggestion>,
+}
+
+impl AssistantResponsePart {
+    pub fn chat_text(text: impl Into<String>) -> Self {
+        Self {
+            part_type: AssistantResponsePartType::ChatText,
+            text: Some(text.into()),
+            capability: None,
+            payload: None,
+            focus_blocks: Vec::new(),
+            sender_filters: Vec::new(),
+        }
+    }
+
+    pub fn tool_summary(
+        capability: AssistantQueryCapability,
+        payload: AssistantStructuredPayload,
+    ) -> Self {
+        Self {
+            part_type: AssistantResponsePartType::ToolSummary,
+            text: None,
+            capability: Some(capability),
+            payload: Some(payload),
+            focus_blocks: Vec::new(),
+            sender_filters: Vec::new(),
+        }
+    }
+
+    pub fn focus_block_proposals(
+        payload: AssistantStructuredPayload,
+        focus_blocks: Vec<AssistantFocusBlockProposal>,
+    ) -> Self {
+        Self {
+            focus_blocks,
+            ..Self::tool_summary(AssistantQueryCapability::FocusTime, payload)
+        }
+    }
+
+    pub fn sender_filter_suggestions(
+        payload: AssistantStructuredPayload,
+        sender_filters: Vec<AssistantSenderFilterSuggestion>,
+    ) -> Self {
+        Self {
+            sender_filters,
+            ..Self::tool_summary(AssistantQueryCapability::EmailCleanup, payload)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AssistantQueryResponse {
+    pub session_id: Uuid,
+    pub envelope: AssistantEncryptedResponseEnvelope,
+    /// Metadata-only signal that the enclave answered in a degraded mode, so the app can
+    /// explain a blander answer. Empty when the answer was produced normally.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub degraded_reasons: Vec<AssistantDegradedReason>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AssistantDegradedReason {
+    /// Query routing fell back to deterministic rules because the planner model was unavailable.
+    PlannerFallback,
+    /// The answer was rendered from a deterministic template because the model was unavailable.
+    ResponseFallback,
+}
+
+/// Thread title sealed on the client with a device-held key. The host stores and returns it
+/// as-is and never sees the plaintext.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AssistantSessionTitleEnvelope {
+    pub version: String,
+    pub algorithm: String,
+    pub key_id: String,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AssistantSessionSummary {
+    pub session_id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub title_envelope: Option<AssistantSessionTitleEnvelope>,
+    pub pinned: bool,
+}
+
+/// Thread drawer metadata update. Omitted fields are left unchanged; `clear_title` removes a
+/// stored title and cannot be combined with `title_envelope`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateAssistantSessionRequest {
+    #[serde(default)]
+    pub title_envelope: Option<AssistantSessionTitleEnvelope>,
+    #[serde(default)]
+    pub clear_title: bool,
+    #[serde(default)]
+    pub pinned: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ListAssistantSessionsResponse {
+    pub items: Vec<AssistantSessionSummary>,
+    pub next_cursor: Option<String>,
+}
+
+/// Requests an archive of the caller's assistant sessions. The enclave encrypts it to
+/// `client_ephemeral_public_key`, so only the requesting client can read the contents.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AssistantSessionExportRequest {
+    pub request_id: String,
+    pub client_ephemeral_public_key: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AssistantSessionExportResponse {
+    pub request_id: String,
+    pub exported_at: DateTime<Utc>,
+    pub sessions: Vec<AssistantSessionSummary>,
+    /// Sessions whose sealed state the enclave could no longer open, e.g. after key expiry.
+    pub skipped_session_ids: Vec<Uuid>,
+    pub envelope: AssistantEncryptedResponseEnvelope,
+}
+
+/// Plaintext inside an [`AssistantSessionExportResponse`] envelope.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssistantPlaintextSessionExport {
+    pub format_version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub sessions: Vec<AssistantPlaintextExportedSession>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssistantPlaintextExportedSession {
+    pub session_id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub last_capability: AssistantQueryCapability,
+    pub memory: crate::assistant_memory::AssistantSessionMemory,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssistantPlaintextQueryRequest {
+    pub query: String,
+    #[serde(default)]
+    pub session_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssistantPlaintextQueryResponse {
+    pub session_id: Uuid,
+    pub capability: AssistantQueryCapability,
+    pub display_text: String,
+    pub payload: AssistantStructuredPayload,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub response_parts: Vec<AssistantResponsePart>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AssistantAttestedKeyRequest {
+    pub challenge_nonce: String,
+    pub issued_at: i64,
+    pub expires_at: i64,
+    pub request_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AssistantAttestedKeyResponse {
+    pub key_id: String,
+    pub algorithm: String,
+    pub public_key: String,
+    pub key_expires_at: i64,
+    pub attestation: AssistantAttestedKeyAttestation,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rotation: Option<AssistantAttestedKeyRotation>,
+}
+
+/// Where the enclave is in its ingress key rotation, so clients know when to refetch the key.
+/// These fields are scheduling hints and are not covered by the attestation signature.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AssistantAttestedKeyRotation {
+    pub rotation_enabled: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub activated_at: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_rotation_at: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_key_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_key_expires_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AssistantAttestedKeyAttestation {
+    pub runtime: String,
+    pub measurement: String,
+    pub challenge_nonce: String,
+    pub issued_at: i64,
+    pub expires_at: i64,
+    pub request_id: String,
+    pub evidence_issued_at: i64,
+    pub signature: Option<String>,
+}
@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Audit metadata keyed by field name, keeping each value's JSON type.
+pub type AuditMetadata = HashMap<String, serde_json::Value>;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AuditEvent {
+    pub id: String,
+    pub timestamp: DateTime<Utc>,
+    pub event_type: String,
+    pub connector: Option<String>,
+    pub result: String,
+    pub metadata: AuditMetadata,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditChainBreakReason {
+    /// The stored entry hash does not match the recomputed hash of the row.
+    HashMismatch,
+    /// The row does not link to the hash of the row before it.
+    PrevHashMismatch,
+    /// A sequence number is missing, so a row was deleted or un-chained.
+    SequenceGap,
+    /// The recorded chain tip is ahead of or differs from the last stored row.
+    HeadMismatch,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct AuditChainBreak {
+    pub chain_seq: i64,
+    pub event_id: Option<String>,
+    pub reason: AuditChainBreakReason,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct AuditChainVerification {
+    pub user_id: String,
+    pub valid: bool,
+    pub verified_events: u64,
+    pub purged_events: u64,
+    pub unchained_events: u64,
+    pub head_seq: i64,
+    pub head_hash: Option<String>,
+    pub first_break: Option<AuditChainBreak>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditEventSignatureStatus {
+    /// The row matches its chain hash and the enclave signature over it verifies.
+    Verified,
+    /// The event was not signed by the enclave.
+    Unsigned,
+    /// The row no longer matches the chain hash the signature covers.
+    HashMismatch,
+    /// The signature does not verify against the enclave attestation key.
+    InvalidSignature,
+    /// The API server has no enclave attestation key to check the signature against.
+    Unverifiable,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct AuditEventSignatureVerification {
+    pub event_id: String,
+    pub event_type: String,
+    pub status: AuditEventSignatureStatus,
+    /// Hex audit chain hash of the event, which the signature covers.
+    pub entry_hash: Option<String>,
+    /// Base64 Ed25519 signature by the enclave attestation key.
+    pub signature: Option<String>,
+    /// The exact string the enclave signed, so auditors can check the signature themselves.
+    pub signed_payload: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ListAuditEventsResponse {
+    pub items: Vec<AuditEvent>,
+    pub next_cursor: Option<String>,
+}
@@ -0,0 +1,232 @@
+use chrono::{DateTime, FixedOffset, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::AssistantEncryptedResponseEnvelope;
+use crate::automation_schedule::{AutomationCondition, AutomationScheduleType, AutomationTemplate};
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AutomationPromptEnvelope {
+    pub version: String,
+    pub algorithm: String,
+    pub key_id: String,
+    pub request_id: String,
+    pub client_ephemeral_public_key: String,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct CreateAutomationRequest {
+    pub title: String,
+    pub schedule: AutomationSchedule,
+    pub prompt_envelope: AutomationPromptEnvelope,
+    #[serde(default)]
+    pub template: Option<AutomationTemplate>,
+    /// Scheduled runs that do not meet the condition finish without a notification.
+    #[serde(default)]
+    pub condition: Option<AutomationCondition>,
+}
+
+/// Asks the enclave to turn a natural-language request such as "brief me every weekday at 7"
+/// into an automation proposal. Nothing is stored: the client confirms the proposal by calling
+/// `createAutomation` with a fresh prompt envelope.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct DraftAutomationRequest {
+    pub prompt_envelope: AutomationPromptEnvelope,
+    /// IANA time zone relative times in the request are resolved in.
+    pub time_zone: String,
+}
+
+/// The proposal is encrypted to the prompt envelope's client key and decrypts to an
+/// [`AutomationPlaintextDraft`].
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DraftAutomationResponse {
+    pub request_id: String,
+    pub envelope: AssistantEncryptedResponseEnvelope,
+}
+
+/// Plaintext inside a [`DraftAutomationResponse`] envelope.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutomationPlaintextDraft {
+    pub title: String,
+    pub prompt: String,
+    /// Ready to submit as `createAutomation`'s schedule; absent when the request named none.
+    pub schedule: Option<AutomationSchedule>,
+    pub next_run_at: Option<DateTime<Utc>>,
+    pub needs_clarification: bool,
+    pub clarifying_question: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AutomationSchedule {
+    pub schedule_type: AutomationScheduleType,
+    pub time_zone: String,
+    /// Required for every schedule type except `CRON` and `ONCE`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub local_time: Option<String>,
+    /// ISO weekdays (1 = Monday through 7 = Sunday) a `WEEKLY` schedule runs on. Defaults to
+    /// the weekday the rule is created or rescheduled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub days_of_week: Option<Vec<u8>>,
+    /// Day a `MONTHLY` schedule runs on, moved to the last day in shorter months. Defaults to
+    /// the day the rule is created or rescheduled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub day_of_month: Option<u8>,
+    /// Required for `CRON` schedules and rejected otherwise.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cron_expression: Option<String>,
+    /// Required for `ONCE` schedules and rejected otherwise. Must be in the future; the rule
+    /// moves to `COMPLETED` once its run is dispatched.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AutomationStatus {
+    Active,
+    Paused,
+    Archived,
+    Completed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateAutomationRequest {
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub schedule: Option<AutomationSchedule>,
+    #[serde(default)]
+    pub prompt_envelope: Option<AutomationPromptEnvelope>,
+    #[serde(default)]
+    pub status: Option<AutomationStatus>,
+    #[serde(default)]
+    pub condition: Option<AutomationCondition>,
+    /// Removes the stored condition; cannot be combined with `condition`.
+    #[serde(default)]
+    pub clear_condition: bool,
+    /// Version the client last read; the update is rejected with 409 if it has moved on.
+    #[serde(default)]
+    pub expected_version: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AutomationRuleSummary {
+    pub rule_id: String,
+    pub title: String,
+    pub status: AutomationStatus,
+    pub template: Option<AutomationTemplate>,
+    pub condition: Option<AutomationCondition>,
+    pub schedule: AutomationSchedule,
+    pub next_run_at: DateTime<Utc>,
+    pub last_run_at: Option<DateTime<Utc>>,
+    /// Set while a snoozed rule is `PAUSED`; the rule resumes on its own at this time.
+    pub paused_until: Option<DateTime<Utc>>,
+    pub prompt_sha256: String,
+    pub version: i64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SnoozePreset {
+    OneDay,
+    OneWeek,
+    Until,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SnoozeAutomationRequest {
+    pub preset: SnoozePreset,
+    /// Required for the `UNTIL` preset and rejected otherwise. Must be in the future and at
+    /// most a year away.
+    #[serde(default)]
+    pub until: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct PreviewAutomationScheduleRequest {
+    pub schedule: AutomationSchedule,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AutomationRunPreview {
+    pub run_at: DateTime<Utc>,
+    /// `run_at` in the schedule's time zone, with that zone's UTC offset at the time.
+    pub local_run_at: DateTime<FixedOffset>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AutomationSchedulePreview {
+    /// Normalized IANA time zone the runs were computed in.
+    pub time_zone: String,
+    /// Upcoming runs in order; fewer than requested only for `ONCE` schedules.
+    pub runs: Vec<AutomationRunPreview>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ListAutomationsResponse {
+    pub items: Vec<AutomationRuleSummary>,
+    pub next_cursor: Option<String>,
+}
+
+/// Automation report payload end-to-end encrypted to a single device's notification key.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AutomationReportEnvelope {
+    pub version: String,
+    pub algorithm: String,
+    pub key_id: String,
+    pub request_id: String,
+    pub sender_public_key: String,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AutomationReportSummary {
+    pub report_id: String,
+    pub rule_id: String,
+    pub run_id: String,
+    pub template: AutomationTemplate,
+    pub envelope: AutomationReportEnvelope,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ListAutomationReportsResponse {
+    pub items: Vec<AutomationReportSummary>,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TriggerAutomationDebugRunResponse {
+    pub queued_job_id: String,
+    pub status: String,
+}
+
+/// `request_id` is chosen by the client and makes retries of the same tap return the
+/// originally queued run instead of queueing another one.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct RunAutomationNowRequest {
+    pub request_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RunAutomationNowResponse {
+    pub queued_job_id: String,
+    pub status: String,
+    /// True when `request_id` had already queued a run and that run is returned instead.
+    pub replayed: bool,
+    pub runs_remaining_today: u32,
+}
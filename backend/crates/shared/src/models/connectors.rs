@@ -0,0 +1,123 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::AutomationPromptEnvelope;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct StartGoogleConnectRequest {
+    pub redirect_uri: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct StartGoogleConnectResponse {
+    pub auth_url: String,
+    pub state: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct UpgradeConnectorScopesRequest {
+    pub redirect_uri: String,
+    pub scopes: Vec<String>,
+}
+
+/// Starts incremental consent; finish it through the regular Google connect callback.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct UpgradeConnectorScopesResponse {
+    pub auth_url: String,
+    pub state: String,
+    pub requested_scopes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct CompleteGoogleConnectRequest {
+    #[serde(default)]
+    pub code: Option<String>,
+    pub state: String,
+    #[serde(default)]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub error_description: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ConnectorStatus {
+    Active,
+    Revoked,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CompleteGoogleConnectResponse {
+    pub connector_id: String,
+    pub status: ConnectorStatus,
+    pub granted_scopes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ConnectCaldavRequest {
+    pub server_url: String,
+    pub username: String,
+    pub app_password_envelope: AutomationPromptEnvelope,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ConnectCaldavResponse {
+    pub connector_id: String,
+    pub status: ConnectorStatus,
+}
+
+/// `port` defaults to 993; connections always use implicit TLS.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ConnectImapRequest {
+    pub host: String,
+    #[serde(default)]
+    pub port: Option<u16>,
+    pub username: String,
+    pub password_envelope: AutomationPromptEnvelope,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ConnectImapResponse {
+    pub connector_id: String,
+    pub status: ConnectorStatus,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RevokeConnectorResponse {
+    pub status: ConnectorStatus,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RevokedConnector {
+    pub connector_id: String,
+    pub provider: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RevokeAllConnectorsResponse {
+    pub revoked: Vec<RevokedConnector>,
+    /// Pending departure alerts cancelled because they read from the revoked calendars.
+    pub cancelled_job_count: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ConnectorSummary {
+    pub connector_id: String,
+    pub provider: String,
+    pub status: ConnectorStatus,
+    pub scopes: Vec<String>,
+    pub token_rotated_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub health_score: i16,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ListConnectorsResponse {
+    pub items: Vec<ConnectorSummary>,
+}
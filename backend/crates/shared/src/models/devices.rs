@@ -0,0 +1,158 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ApnsEnvironment {
+    Sandbox,
+    Production,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct RegisterDeviceRequest {
+    pub device_id: String,
+    pub apns_token: String,
+    pub environment: ApnsEnvironment,
+    #[serde(default)]
+    pub app_bundle_id: Option<String>,
+    #[serde(default)]
+    pub notification_key_algorithm: Option<String>,
+    #[serde(default)]
+    pub notification_public_key: Option<String>,
+    #[serde(default)]
+    pub notification_key_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct RotateDeviceNotificationKeyRequest {
+    pub key_id: String,
+    pub algorithm: String,
+    pub public_key: String,
+    #[serde(default)]
+    pub overlap_seconds: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RotateDeviceNotificationKeyResponse {
+    pub device_id: String,
+    pub key_id: String,
+    pub rotated_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_key_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_key_expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct RegisterRequestSigningKeyRequest {
+    pub public_key: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RegisterRequestSigningKeyResponse {
+    pub device_id: String,
+    pub algorithm: String,
+    pub registered_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DeviceSummary {
+    pub device_id: String,
+    pub environment: ApnsEnvironment,
+    pub app_bundle_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub last_delivery_at: Option<DateTime<Utc>>,
+    pub last_seen_at: DateTime<Utc>,
+    pub disabled_at: Option<DateTime<Utc>>,
+    pub disabled_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ListDevicesResponse {
+    pub items: Vec<DeviceSummary>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SendTestNotificationRequest {
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub body: Option<String>,
+    #[serde(default)]
+    pub job_type: TestNotificationJobType,
+    #[serde(default)]
+    pub simulated_payload: Option<TestNotificationSimulatedPayload>,
+}
+
+/// Job pipeline a test notification is queued on, so delivery can be checked per job type.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TestNotificationJobType {
+    #[default]
+    AutomationRun,
+    DepartureAlert,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct TestNotificationSimulatedPayload {
+    /// Fails the job before delivery: `transient` exercises retries and `permanent` sends it
+    /// straight to the dead-letter queue.
+    #[serde(default)]
+    pub failure: Option<SimulatedJobFailure>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SimulatedJobFailure {
+    Transient,
+    Permanent,
+}
+
+impl SimulatedJobFailure {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Transient => "transient",
+            Self::Permanent => "permanent",
+        }
+    }
+
+    pub fn error_code(self) -> &'static str {
+        match self {
+            Self::Transient => "SIMULATED_TRANSIENT_FAILURE",
+            Self::Permanent => "SIMULATED_PERMANENT_FAILURE",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SendTestNotificationResponse {
+    pub queued_job_id: String,
+    pub job_type: TestNotificationJobType,
+    pub status: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct UploadSupportDiagnosticsRequest {
+    #[serde(default)]
+    pub ticket_reference: Option<String>,
+    pub algorithm: String,
+    pub key_id: String,
+    pub ciphertext: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct UploadSupportDiagnosticsResponse {
+    pub diagnostic_id: String,
+    pub ticket_reference: String,
+    pub size_bytes: i32,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
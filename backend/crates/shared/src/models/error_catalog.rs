@@ -0,0 +1,550 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::ErrorBody;
+
+/// Human-readable reference for every code; `ApiErrorCode::docs_url` links to the code's
+/// section.
+pub const ERROR_CATALOG_DOCS_URL: &str =
+    "https://github.com/niteshbalusu11/alfred/blob/main/docs/api-error-codes.md";
+
+/// Stable `error.code` values. Clients branch on these, so a code is never renamed or reused
+/// for a different failure; add a new variant instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiErrorCode {
+    Unauthorized,
+    DecryptNotAuthorized,
+    ClerkJwksUnavailable,
+    InvalidRequestSignature,
+    RequestSignatureRequired,
+    InvalidRequestBody,
+    PayloadTooLarge,
+    InvalidBody,
+    InvalidCursor,
+    InvalidLimit,
+    InvalidFormat,
+    InvalidRequestId,
+    RequestIdReused,
+    InvalidIfMatch,
+    InvalidExpectedVersion,
+    VersionConflict,
+    NotFound,
+    InternalError,
+    RateLimited,
+    QuotaExceeded,
+    BudgetExceeded,
+    AutomationRunLimitReached,
+    InvalidEnvelopeVersion,
+    InvalidEnvelopeAlgorithm,
+    InvalidKeyId,
+    InvalidClientPublicKey,
+    InvalidNonce,
+    InvalidCiphertext,
+    InvalidPromptEnvelope,
+    InvalidEncryptionMetadata,
+    InvalidChallengeNonce,
+    InvalidChallengeWindow,
+    ChallengeExpired,
+    AttestationChallengeMismatch,
+    InvalidSessionUpdate,
+    InvalidEnclaveRequest,
+    InvalidEnclaveSessionState,
+    EnclaveRpcFailed,
+    InvalidDeviceId,
+    InvalidAppBundleId,
+    InvalidNotificationKey,
+    InvalidNotificationKeyId,
+    InvalidNotificationKeyAlgorithm,
+    InvalidNotificationPublicKey,
+    InvalidOverlapSeconds,
+    InvalidRequestSigningKey,
+    NoRegisteredDevice,
+    InvalidTitle,
+    UnsupportedProvider,
+    InvalidRedirectUri,
+    InvalidState,
+    InvalidOauthCode,
+    InvalidScope,
+    OauthConsentDenied,
+    OauthCallbackError,
+    OauthConfigError,
+    OauthUnavailable,
+    OauthInvalidResponse,
+    OauthTokenExchangeFailed,
+    OauthTokenStoreFailed,
+    OauthRevokeFailed,
+    OauthRevokeUnavailable,
+    ScopesAlreadyGranted,
+    ScopeUpgradeAccountMismatch,
+    ConnectorNotActive,
+    ConnectorNotFound,
+    ConnectorTokenUnavailable,
+    ConnectorTokenDecryptFailed,
+    InvalidCaldavConnect,
+    InvalidCaldavCredentials,
+    InvalidCaldavCalendar,
+    CaldavUnavailable,
+    CaldavCredentialsStoreFailed,
+    InvalidImapConnect,
+    InvalidImapCredentials,
+    ImapUnavailable,
+    ImapCredentialsStoreFailed,
+    InvalidServerUrl,
+    InvalidHost,
+    InvalidPort,
+    InvalidUsername,
+    InvalidAutomationRequest,
+    InvalidAutomationUpdate,
+    InvalidAutomationPayload,
+    InvalidSchedule,
+    InvalidTemplateSchedule,
+    InvalidLocalTime,
+    InvalidCronExpression,
+    InvalidSnooze,
+    InvalidAutomationCondition,
+    InvalidTimeZone,
+    AutomationNotActive,
+    AutomationArchived,
+    InvalidBriefSections,
+    InvalidDepartureAlertRequest,
+    InvalidDepartureMinutes,
+    InvalidCheckTime,
+    InvalidTicketReference,
+    DiagnosticsTooLarge,
+    InvalidWebhookUrl,
+    InvalidWebhookEventTypes,
+    WebhookLimitReached,
+    WebhookNotFound,
+    UserNotFound,
+    AdminScopeForbidden,
+    DeadLetterJobNotFound,
+    FeatureFlagNotFound,
+    InvalidFeatureFlagRule,
+    FeatureFlagStoreUnavailable,
+    LlmProviderNotReady,
+    AttestationDocumentUnavailable,
+    InvalidAttestationChallenge,
+    AttestationChallengeFailed,
+}
+
+impl ApiErrorCode {
+    pub const ALL: [Self; 113] = [
+        Self::Unauthorized,
+        Self::DecryptNotAuthorized,
+        Self::ClerkJwksUnavailable,
+        Self::InvalidRequestSignature,
+        Self::RequestSignatureRequired,
+        Self::InvalidRequestBody,
+        Self::PayloadTooLarge,
+        Self::InvalidBody,
+        Self::InvalidCursor,
+        Self::InvalidLimit,
+        Self::InvalidFormat,
+        Self::InvalidRequestId,
+        Self::RequestIdReused,
+        Self::InvalidIfMatch,
+        Self::InvalidExpectedVersion,
+        Self::VersionConflict,
+        Self::NotFound,
+        Self::InternalError,
+        Self::RateLimited,
+        Self::QuotaExceeded,
+        Self::BudgetExceeded,
+        Self::AutomationRunLimitReached,
+        Self::InvalidEnvelopeVersion,
+        Self::InvalidEnvelopeAlgorithm,
+        Self::InvalidKeyId,
+        Self::InvalidClientPublicKey,
+        Self::InvalidNonce,
+        Self::InvalidCiphertext,
+        Self::InvalidPromptEnvelope,
+        Self::InvalidEncryptionMetadata,
+        Self::InvalidChallengeNonce,
+        Self::InvalidChallengeWindow,
+        Self::ChallengeExpired,
+        Self::AttestationChallengeMismatch,
+        Self::InvalidSessionUpdate,
+        Self::InvalidEnclaveRequest,
+        Self::InvalidEnclaveSessionState,
+        Self::EnclaveRpcFailed,
+        Self::InvalidDeviceId,
+        Self::InvalidAppBundleId,
+        Self::InvalidNotificationKey,
+        Self::InvalidNotificationKeyId,
+        Self::InvalidNotificationKeyAlgorithm,
+        Self::InvalidNotificationPublicKey,
+        Self::InvalidOverlapSeconds,
+        Self::InvalidRequestSigningKey,
+        Self::NoRegisteredDevice,
+        Self::InvalidTitle,
+        Self::UnsupportedProvider,
+        Self::InvalidRedirectUri,
+        Self::InvalidState,
+        Self::InvalidOauthCode,
+        Self::InvalidScope,
+        Self::OauthConsentDenied,
+        Self::OauthCallbackError,
+        Self::OauthConfigError,
+        Self::OauthUnavailable,
+        Self::OauthInvalidResponse,
+        Self::OauthTokenExchangeFailed,
+        Self::OauthTokenStoreFailed,
+        Self::OauthRevokeFailed,
+        Self::OauthRevokeUnavailable,
+        Self::ScopesAlreadyGranted,
+        Self::ScopeUpgradeAccountMismatch,
+        Self::ConnectorNotActive,
+        Self::ConnectorNotFound,
+        Self::ConnectorTokenUnavailable,
+        Self::ConnectorTokenDecryptFailed,
+        Self::InvalidCaldavConnect,
+        Self::InvalidCaldavCredentials,
+        Self::InvalidCaldavCalendar,
+        Self::CaldavUnavailable,
+        Self::CaldavCredentialsStoreFailed,
+        Self::InvalidImapConnect,
+        Self::InvalidImapCredentials,
+        Self::ImapUnavailable,
+        Self::ImapCredentialsStoreFailed,
+        Self::InvalidServerUrl,
+        Self::InvalidHost,
+        Self::InvalidPort,
+        Self::InvalidUsername,
+        Self::InvalidAutomationRequest,
+        Self::InvalidAutomationUpdate,
+        Self::InvalidAutomationPayload,
+        Self::InvalidSchedule,
+        Self::InvalidTemplateSchedule,
+        Self::InvalidLocalTime,
+        Self::InvalidCronExpression,
+        Self::InvalidSnooze,
+        Self::InvalidAutomationCondition,
+        Self::InvalidTimeZone,
+        Self::AutomationNotActive,
+        Self::AutomationArchived,
+        Self::InvalidBriefSections,
+        Self::InvalidDepartureAlertRequest,
+        Self::InvalidDepartureMinutes,
+        Self::InvalidCheckTime,
+        Self::InvalidTicketReference,
+        Self::DiagnosticsTooLarge,
+        Self::InvalidWebhookUrl,
+        Self::InvalidWebhookEventTypes,
+        Self::WebhookLimitReached,
+        Self::WebhookNotFound,
+        Self::UserNotFound,
+        Self::AdminScopeForbidden,
+        Self::DeadLetterJobNotFound,
+        Self::FeatureFlagNotFound,
+        Self::InvalidFeatureFlagRule,
+        Self::FeatureFlagStoreUnavailable,
+        Self::LlmProviderNotReady,
+        Self::AttestationDocumentUnavailable,
+        Self::InvalidAttestationChallenge,
+        Self::AttestationChallengeFailed,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Unauthorized => "unauthorized",
+            Self::DecryptNotAuthorized => "decrypt_not_authorized",
+            Self::ClerkJwksUnavailable => "clerk_jwks_unavailable",
+            Self::InvalidRequestSignature => "invalid_request_signature",
+            Self::RequestSignatureRequired => "request_signature_required",
+            Self::InvalidRequestBody => "invalid_request_body",
+            Self::PayloadTooLarge => "payload_too_large",
+            Self::InvalidBody => "invalid_body",
+            Self::InvalidCursor => "invalid_cursor",
+            Self::InvalidLimit => "invalid_limit",
+            Self::InvalidFormat => "invalid_format",
+            Self::InvalidRequestId => "invalid_request_id",
+            Self::RequestIdReused => "request_id_reused",
+            Self::InvalidIfMatch => "invalid_if_match",
+            Self::InvalidExpectedVersion => "invalid_expected_version",
+            Self::VersionConflict => "version_conflict",
+            Self::NotFound => "not_found",
+            Self::InternalError => "internal_error",
+            Self::RateLimited => "rate_limited",
+            Self::QuotaExceeded => "quota_exceeded",
+            Self::BudgetExceeded => "budget_exceeded",
+            Self::AutomationRunLimitReached => "automation_run_limit_reached",
+            Self::InvalidEnvelopeVersion => "invalid_envelope_version",
+            Self::InvalidEnvelopeAlgorithm => "invalid_envelope_algorithm",
+            Self::InvalidKeyId => "invalid_key_id",
+            Self::InvalidClientPublicKey => "invalid_client_public_key",
+            Self::InvalidNonce => "invalid_nonce",
+            Self::InvalidCiphertext => "invalid_ciphertext",
+            Self::InvalidPromptEnvelope => "invalid_prompt_envelope",
+            Self::InvalidEncryptionMetadata => "invalid_encryption_metadata",
+            Self::InvalidChallengeNonce => "invalid_challenge_nonce",
+            Self::InvalidChallengeWindow => "invalid_challenge_window",
+            Self::ChallengeExpired => "challenge_expired",
+            Self::AttestationChallengeMismatch => "attestation_challenge_mismatch",
+            Self::InvalidSessionUpdate => "invalid_session_update",
+            Self::InvalidEnclaveRequest => "invalid_enclave_request",
+            Self::InvalidEnclaveSessionState => "invalid_enclave_session_state",
+            Self::EnclaveRpcFailed => "enclave_rpc_failed",
+            Self::InvalidDeviceId => "invalid_device_id",
+            Self::InvalidAppBundleId => "invalid_app_bundle_id",
+            Self::InvalidNotificationKey => "invalid_notification_key",
+            Self::InvalidNotificationKeyId => "invalid_notification_key_id",
+            Self::InvalidNotificationKeyAlgorithm => "invalid_notification_key_algorithm",
+            Self::InvalidNotificationPublicKey => "invalid_notification_public_key",
+            Self::InvalidOverlapSeconds => "invalid_overlap_seconds",
+            Self::InvalidRequestSigningKey => "invalid_request_signing_key",
+            Self::NoRegisteredDevice => "no_registered_device",
+            Self::InvalidTitle => "invalid_title",
+            Self::UnsupportedProvider => "unsupported_provider",
+            Self::InvalidRedirectUri => "invalid_redirect_uri",
+            Self::InvalidState => "invalid_state",
+            Self::InvalidOauthCode => "invalid_oauth_code",
+            Self::InvalidScope => "invalid_scope",
+            Self::OauthConsentDenied => "oauth_consent_denied",
+            Self::OauthCallbackError => "oauth_callback_error",
+            Self::OauthConfigError => "oauth_config_error",
+            Self::OauthUnavailable => "oauth_unavailable",
+            Self::OauthInvalidResponse => "oauth_invalid_response",
+            Self::OauthTokenExchangeFailed => "oauth_token_exchange_failed",
+            Self::OauthTokenStoreFailed => "oauth_token_store_failed",
+            Self::OauthRevokeFailed => "oauth_revoke_failed",
+            Self::OauthRevokeUnavailable => "oauth_revoke_unavailable",
+            Self::ScopesAlreadyGranted => "scopes_already_granted",
+            Self::ScopeUpgradeAccountMismatch => "scope_upgrade_account_mismatch",
+            Self::ConnectorNotActive => "connector_not_active",
+            Self::ConnectorNotFound => "connector_not_found",
+            Self::ConnectorTokenUnavailable => "connector_token_unavailable",
+            Self::ConnectorTokenDecryptFailed => "connector_token_decrypt_failed",
+            Self::InvalidCaldavConnect => "invalid_caldav_connect",
+            Self::InvalidCaldavCredentials => "invalid_caldav_credentials",
+            Self::InvalidCaldavCalendar => "invalid_caldav_calendar",
+            Self::CaldavUnavailable => "caldav_unavailable",
+            Self::CaldavCredentialsStoreFailed => "caldav_credentials_store_failed",
+            Self::InvalidImapConnect => "invalid_imap_connect",
+            Self::InvalidImapCredentials => "invalid_imap_credentials",
+            Self::ImapUnavailable => "imap_unavailable",
+            Self::ImapCredentialsStoreFailed => "imap_credentials_store_failed",
+            Self::InvalidServerUrl => "invalid_server_url",
+            Self::InvalidHost => "invalid_host",
+            Self::InvalidPort => "invalid_port",
+            Self::InvalidUsername => "invalid_username",
+            Self::InvalidAutomationRequest => "invalid_automation_request",
+            Self::InvalidAutomationUpdate => "invalid_automation_update",
+            Self::InvalidAutomationPayload => "invalid_automation_payload",
+            Self::InvalidSchedule => "invalid_schedule",
+            Self::InvalidTemplateSchedule => "invalid_template_schedule",
+            Self::InvalidLocalTime => "invalid_local_time",
+            Self::InvalidCronExpression => "invalid_cron_expression",
+            Self::InvalidSnooze => "invalid_snooze",
+            Self::InvalidAutomationCondition => "invalid_automation_condition",
+            Self::InvalidTimeZone => "invalid_time_zone",
+            Self::AutomationNotActive => "automation_not_active",
+            Self::AutomationArchived => "automation_archived",
+            Self::InvalidBriefSections => "invalid_brief_sections",
+            Self::InvalidDepartureAlertRequest => "invalid_departure_alert_request",
+            Self::InvalidDepartureMinutes => "invalid_departure_minutes",
+            Self::InvalidCheckTime => "invalid_check_time",
+            Self::InvalidTicketReference => "invalid_ticket_reference",
+            Self::DiagnosticsTooLarge => "diagnostics_too_large",
+            Self::InvalidWebhookUrl => "invalid_webhook_url",
+            Self::InvalidWebhookEventTypes => "invalid_webhook_event_types",
+            Self::WebhookLimitReached => "webhook_limit_reached",
+            Self::WebhookNotFound => "webhook_not_found",
+            Self::UserNotFound => "user_not_found",
+            Self::AdminScopeForbidden => "admin_scope_forbidden",
+            Self::DeadLetterJobNotFound => "dead_letter_job_not_found",
+            Self::FeatureFlagNotFound => "feature_flag_not_found",
+            Self::InvalidFeatureFlagRule => "invalid_feature_flag_rule",
+            Self::FeatureFlagStoreUnavailable => "feature_flag_store_unavailable",
+            Self::LlmProviderNotReady => "llm_provider_not_ready",
+            Self::AttestationDocumentUnavailable => "attestation_document_unavailable",
+            Self::InvalidAttestationChallenge => "invalid_attestation_challenge",
+            Self::AttestationChallengeFailed => "attestation_challenge_failed",
+        }
+    }
+
+    /// Status the code is returned with. `invalid_request_body` is also sent with `415` when
+    /// the body is not JSON at all.
+    pub fn http_status(self) -> u16 {
+        match self {
+            Self::Unauthorized | Self::InvalidRequestSignature | Self::RequestSignatureRequired => {
+                401
+            }
+            Self::DecryptNotAuthorized | Self::AdminScopeForbidden => 403,
+            Self::NotFound
+            | Self::ConnectorNotFound
+            | Self::UserNotFound
+            | Self::DeadLetterJobNotFound
+            | Self::FeatureFlagNotFound
+            | Self::WebhookNotFound => 404,
+            Self::VersionConflict => 409,
+            Self::PayloadTooLarge | Self::DiagnosticsTooLarge => 413,
+            Self::RateLimited
+            | Self::QuotaExceeded
+            | Self::BudgetExceeded
+            | Self::AutomationRunLimitReached => 429,
+            Self::InternalError
+            | Self::AttestationDocumentUnavailable
+            | Self::AttestationChallengeFailed => 500,
+            Self::ClerkJwksUnavailable
+            | Self::AttestationChallengeMismatch
+            | Self::InvalidEnclaveSessionState
+            | Self::EnclaveRpcFailed
+            | Self::OauthUnavailable
+            | Self::OauthInvalidResponse
+            | Self::OauthTokenExchangeFailed
+            | Self::OauthTokenStoreFailed
+            | Self::OauthRevokeFailed
+            | Self::OauthRevokeUnavailable
+            | Self::ConnectorTokenDecryptFailed
+            | Self::CaldavUnavailable
+            | Self::CaldavCredentialsStoreFailed
+            | Self::ImapUnavailable
+            | Self::ImapCredentialsStoreFailed => 502,
+            Self::LlmProviderNotReady | Self::FeatureFlagStoreUnavailable => 503,
+            Self::InvalidRequestBody
+            | Self::InvalidBody
+            | Self::InvalidCursor
+            | Self::InvalidLimit
+            | Self::InvalidFormat
+            | Self::InvalidRequestId
+            | Self::RequestIdReused
+            | Self::InvalidIfMatch
+            | Self::InvalidExpectedVersion
+            | Self::InvalidEnvelopeVersion
+            | Self::InvalidEnvelopeAlgorithm
+            | Self::InvalidKeyId
+            | Self::InvalidClientPublicKey
+            | Self::InvalidNonce
+            | Self::InvalidCiphertext
+            | Self::InvalidPromptEnvelope
+            | Self::InvalidEncryptionMetadata
+            | Self::InvalidChallengeNonce
+            | Self::InvalidChallengeWindow
+            | Self::ChallengeExpired
+            | Self::InvalidSessionUpdate
+            | Self::InvalidEnclaveRequest
+            | Self::InvalidDeviceId
+            | Self::InvalidAppBundleId
+            | Self::InvalidNotificationKey
+            | Self::InvalidNotificationKeyId
+            | Self::InvalidNotificationKeyAlgorithm
+            | Self::InvalidNotificationPublicKey
+            | Self::InvalidOverlapSeconds
+            | Self::InvalidRequestSigningKey
+            | Self::NoRegisteredDevice
+            | Self::InvalidTitle
+            | Self::UnsupportedProvider
+            | Self::InvalidRedirectUri
+            | Self::InvalidState
+            | Self::InvalidOauthCode
+            | Self::InvalidScope
+            | Self::OauthConsentDenied
+            | Self::OauthCallbackError
+            | Self::OauthConfigError
+            | Self::ScopesAlreadyGranted
+            | Self::ScopeUpgradeAccountMismatch
+            | Self::ConnectorNotActive
+            | Self::ConnectorTokenUnavailable
+            | Self::InvalidCaldavConnect
+            | Self::InvalidCaldavCredentials
+            | Self::InvalidCaldavCalendar
+            | Self::InvalidImapConnect
+            | Self::InvalidImapCredentials
+            | Self::InvalidServerUrl
+            | Self::InvalidHost
+            | Self::InvalidPort
+            | Self::InvalidUsername
+            | Self::InvalidAutomationRequest
+            | Self::InvalidAutomationUpdate
+            | Self::InvalidAutomationPayload
+            | Self::InvalidSchedule
+            | Self::InvalidTemplateSchedule
+            | Self::InvalidLocalTime
+            | Self::InvalidCronExpression
+            | Self::InvalidSnooze
+            | Self::InvalidAutomationCondition
+            | Self::InvalidTimeZone
+            | Self::AutomationNotActive
+            | Self::AutomationArchived
+            | Self::InvalidBriefSections
+            | Self::InvalidDepartureAlertRequest
+            | Self::InvalidDepartureMinutes
+            | Self::InvalidCheckTime
+            | Self::InvalidTicketReference
+            | Self::InvalidWebhookUrl
+            | Self::InvalidWebhookEventTypes
+            | Self::WebhookLimitReached
+            | Self::InvalidFeatureFlagRule
+            | Self::InvalidAttestationChallenge => 400,
+        }
+    }
+
+    /// Whether the same request can succeed later without changes, for example after an
+    /// upstream outage or once `Retry-After` has passed.
+    pub fn retryable(self) -> bool {
+        matches!(
+            self,
+            Self::ClerkJwksUnavailable
+                | Self::InternalError
+                | Self::RateLimited
+                | Self::EnclaveRpcFailed
+                | Self::OauthUnavailable
+                | Self::OauthTokenStoreFailed
+                | Self::OauthRevokeUnavailable
+                | Self::ConnectorTokenUnavailable
+                | Self::CaldavUnavailable
+                | Self::CaldavCredentialsStoreFailed
+                | Self::ImapUnavailable
+                | Self::ImapCredentialsStoreFailed
+                | Self::FeatureFlagStoreUnavailable
+                | Self::LlmProviderNotReady
+                | Self::AttestationDocumentUnavailable
+                | Self::AttestationChallengeFailed
+        )
+    }
+
+    pub fn docs_url(self) -> String {
+        format!("{ERROR_CATALOG_DOCS_URL}#{}", self.as_str())
+    }
+}
+
+impl ErrorBody {
+    pub fn new(code: ApiErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            retryable: code.retryable(),
+            docs_url: Some(code.docs_url()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ApiErrorCode;
+
+    const ERROR_CATALOG_DOCS: &str = include_str!("../../../../../docs/api-error-codes.md");
+
+    #[test]
+    fn codes_serialize_as_their_stable_names() {
+        for code in ApiErrorCode::ALL {
+            assert_eq!(
+                serde_json::to_value(code).expect("code should serialize"),
+                code.as_str(),
+            );
+        }
+    }
+
+    #[test]
+    fn every_code_is_documented_with_its_status() {
+        for code in ApiErrorCode::ALL {
+            let heading = format!("### `{}`\n\n`{}`", code.as_str(), code.http_status());
+            assert!(
+                ERROR_CATALOG_DOCS.contains(&heading),
+                "{} is missing from docs/api-error-codes.md",
+                code.as_str()
+            );
+        }
+    }
+}
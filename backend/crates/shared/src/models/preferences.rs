@@ -0,0 +1,61 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::AutomationPromptEnvelope;
+use crate::brief_profile::{BriefFeedbackRating, MorningBriefSection, MorningBriefVerbosity};
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MorningBriefProfileResponse {
+    pub sections: Vec<MorningBriefSection>,
+    pub verbosity: MorningBriefVerbosity,
+    pub learn_from_feedback: bool,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateMorningBriefProfileRequest {
+    pub sections: Vec<MorningBriefSection>,
+    pub verbosity: MorningBriefVerbosity,
+    #[serde(default)]
+    pub learn_from_feedback: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct MorningBriefFeedbackRequest {
+    pub section: MorningBriefSection,
+    pub rating: BriefFeedbackRating,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DepartureAlertPreferencesResponse {
+    pub enabled: bool,
+    pub time_zone: String,
+    pub check_time: String,
+    pub travel_minutes: u16,
+    pub buffer_minutes: u16,
+    pub has_home_location: bool,
+    pub next_check_at: Option<DateTime<Utc>>,
+    /// Zero until preferences are first saved.
+    pub version: i64,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Replaces departure alert preferences. `home_location_envelope` carries the home address
+/// encrypted to the enclave; omitting it clears any stored location.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateDepartureAlertPreferencesRequest {
+    pub enabled: bool,
+    pub time_zone: String,
+    pub check_time: String,
+    pub travel_minutes: u16,
+    pub buffer_minutes: u16,
+    #[serde(default)]
+    pub home_location_envelope: Option<AutomationPromptEnvelope>,
+    /// Version the client last read; the update is rejected with 409 if it has moved on.
+    #[serde(default)]
+    pub expected_version: Option<i64>,
+}
@@ -0,0 +1,155 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// `degraded` instances keep serving with a dependency impaired; `unready` ones (`503`) cannot
+/// serve until a critical dependency recovers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReadinessStatus {
+    Ready,
+    Degraded,
+    Unready,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReadinessDependency {
+    Database,
+    Redis,
+    Enclave,
+    ClerkJwks,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DependencyReadiness {
+    pub dependency: ReadinessDependency,
+    pub status: ComponentStatus,
+    /// Whether this dependency being unavailable makes the instance `unready`.
+    pub critical: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    pub latency_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ReadinessResponse {
+    pub status: ReadinessStatus,
+    pub dependencies: Vec<DependencyReadiness>,
+    pub checked_at: DateTime<Utc>,
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ComponentStatus {
+    Operational,
+    Degraded,
+    Unavailable,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StatusComponent {
+    Database,
+    Enclave,
+    Assistant,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ComponentStatusEntry {
+    pub component: StatusComponent,
+    pub status: ComponentStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// User-facing components whose rolling availability is published on the public status page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AvailabilityComponent {
+    PushDelivery,
+    Assistant,
+    Connectors,
+    Automations,
+}
+
+impl AvailabilityComponent {
+    pub const ALL: [Self; 4] = [
+        Self::PushDelivery,
+        Self::Assistant,
+        Self::Connectors,
+        Self::Automations,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::PushDelivery => "push_delivery",
+            Self::Assistant => "assistant",
+            Self::Connectors => "connectors",
+            Self::Automations => "automations",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|component| component.as_str() == value)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DailyAvailability {
+    pub date: NaiveDate,
+    pub attempts: i64,
+    /// Share of successful attempts, or `None` when nothing ran that day.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub availability: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PublicComponentStatus {
+    pub component: AvailabilityComponent,
+    pub status: ComponentStatus,
+    /// Short incident copy for the status page and in-app banner; set only when not operational.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub availability_24h: Option<f64>,
+    pub history: Vec<DailyAvailability>,
+}
+
+/// Latest rolling evaluation of a delivery latency SLO.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PublicSloStatus {
+    /// Share of events, in percent, that must meet `target_seconds`.
+    pub objective_percent: i32,
+    pub target_seconds: i64,
+    pub window_minutes: i64,
+    /// Share of events in the window that met the target, or `None` when there were none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compliance: Option<f64>,
+    pub breached: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub breached_since: Option<DateTime<Utc>>,
+    pub evaluated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PublicStatusResponse {
+    pub status: ComponentStatus,
+    pub components: Vec<PublicComponentStatus>,
+    /// Push delivery latency SLO, once the worker has evaluated it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub push_delivery_slo: Option<PublicSloStatus>,
+    pub generated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SystemStatusResponse {
+    pub status: ComponentStatus,
+    pub components: Vec<ComponentStatusEntry>,
+    pub window_minutes: i64,
+    pub checked_at: DateTime<Utc>,
+}
@@ -0,0 +1,57 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::webhooks::{WebhookDeliveryState, WebhookEventType};
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct CreateWebhookRequest {
+    /// Public `https` endpoint the signed events are posted to.
+    pub url: String,
+    pub event_types: Vec<WebhookEventType>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WebhookSummary {
+    pub webhook_id: String,
+    pub url: String,
+    pub event_types: Vec<WebhookEventType>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// The only response that carries `signing_secret`; it cannot be read back later.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CreateWebhookResponse {
+    pub webhook_id: String,
+    pub url: String,
+    pub event_types: Vec<WebhookEventType>,
+    pub created_at: DateTime<Utc>,
+    pub signing_secret: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ListWebhooksResponse {
+    pub items: Vec<WebhookSummary>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WebhookDeliverySummary {
+    pub delivery_id: String,
+    pub event_id: String,
+    pub event_type: WebhookEventType,
+    pub state: WebhookDeliveryState,
+    pub attempts: i32,
+    /// Set while the delivery is `PENDING`.
+    pub next_attempt_at: Option<DateTime<Utc>>,
+    pub last_status_code: Option<i32>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ListWebhookDeliveriesResponse {
+    pub items: Vec<WebhookDeliverySummary>,
+    pub next_cursor: Option<String>,
+}
@@ -0,0 +1,104 @@
+//! Which destinations outbound calls to user-supplied hosts (CalDAV, IMAP, webhooks) may
+//! reach. Names are checked as written and again after resolution, and callers connect to
+//! the address that passed the check, so DNS rebinding cannot slip in a private address.
+
+use std::net::{IpAddr, SocketAddr};
+
+/// Rejects loopback, private, and link-local hosts, both as names and as IP literals.
+pub(crate) fn is_public_host(host: &str) -> bool {
+    let host = host.trim_matches(['[', ']']).to_ascii_lowercase();
+    if host == "localhost" || host.ends_with(".localhost") || host.ends_with(".internal") {
+        return false;
+    }
+    host.parse::<IpAddr>().map_or(true, is_public_ip)
+}
+
+/// Resolves `host` and returns the address to connect to. Fails if any resolved address is
+/// non-public, so a public-looking name cannot point an outbound call at internal services.
+/// Callers connect to the returned address rather than resolving again.
+pub(crate) async fn resolve_public_address(host: &str, port: u16) -> Result<SocketAddr, String> {
+    let addresses = tokio::net::lookup_host((host.trim_matches(['[', ']']), port))
+        .await
+        .map_err(|err| err.to_string())?
+        .collect::<Vec<_>>();
+    if addresses.iter().any(|address| !is_public_ip(address.ip())) {
+        return Err(format!("{host} resolves to a non-public address"));
+    }
+    addresses
+        .first()
+        .copied()
+        .ok_or_else(|| format!("{host} did not resolve"))
+}
+
+fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || ip.is_multicast()
+                || ip.octets()[0] == 0
+                || (ip.octets()[0] == 100 && (64..128).contains(&ip.octets()[1])))
+        }
+        IpAddr::V6(ip) => {
+            let first_segment = ip.segments()[0];
+            !(ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                || (first_segment & 0xfe00) == 0xfc00
+                || (first_segment & 0xffc0) == 0xfe80
+                || ip
+                    .to_ipv4_mapped()
+                    .is_some_and(|ip| !is_public_ip(IpAddr::V4(ip))))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_public_host, resolve_public_address};
+
+    #[test]
+    fn non_public_addresses_are_rejected() {
+        for host in [
+            "localhost",
+            "metadata.internal",
+            "127.0.0.1",
+            "10.0.0.8",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.1.2.3",
+            "224.0.0.251",
+            "239.255.255.250",
+            "[::1]",
+            "[fd00::1]",
+            "[fe80::1]",
+            "[ff02::fb]",
+            "[::ffff:192.168.1.2]",
+        ] {
+            assert!(!is_public_host(host), "{host}");
+        }
+        for host in ["caldav.fastmail.com", "93.184.215.14", "[2606:4700::1111]"] {
+            assert!(is_public_host(host), "{host}");
+        }
+    }
+
+    #[tokio::test]
+    async fn hostnames_resolving_to_private_addresses_are_rejected() {
+        // `localhost` passes through the resolver (it is answered from the hosts file) and
+        // stands in for any public-looking name whose records point at a private address.
+        let err = resolve_public_address("localhost", 443)
+            .await
+            .expect_err("loopback resolution should be rejected");
+        assert!(err.contains("non-public"), "{err}");
+        assert!(resolve_public_address("127.0.0.1", 443).await.is_err());
+        assert!(resolve_public_address("[::1]", 443).await.is_err());
+        assert_eq!(
+            resolve_public_address("93.184.215.14", 443).await,
+            Ok("93.184.215.14:443".parse().expect("valid socket address"))
+        );
+    }
+}
@@ -0,0 +1,288 @@
+//! Provider-wide limits on calls the enclave makes to third-party APIs.
+//!
+//! Per-user limits bound how much one account can spend; these buckets bound what a single
+//! enclave instance sends to each provider regardless of who asked, so traffic spikes queue
+//! (briefly) or fail fast here instead of burning shared Google/LLM provider quota and
+//! erroring at the provider edge.
+
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use thiserror::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutboundProvider {
+    Google,
+    OpenRouter,
+    Anthropic,
+    OpenAi,
+    Bedrock,
+}
+
+impl OutboundProvider {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Google => "google",
+            Self::OpenRouter => "openrouter",
+            Self::Anthropic => "anthropic",
+            Self::OpenAi => "openai",
+            Self::Bedrock => "bedrock",
+        }
+    }
+}
+
+/// Sustained call rate and burst allowance for one provider. A zero rate leaves the provider
+/// unlimited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutboundProviderLimit {
+    pub calls_per_second: u32,
+    pub burst: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutboundRateLimitConfig {
+    pub google: OutboundProviderLimit,
+    pub openrouter: OutboundProviderLimit,
+    pub anthropic: OutboundProviderLimit,
+    pub openai: OutboundProviderLimit,
+    pub bedrock: OutboundProviderLimit,
+    /// Longest a call may queue for its slot before it is rejected instead.
+    pub max_wait: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error(
+    "outbound {} call limit reached; retry after {}ms",
+    .provider.as_str(),
+    .retry_after.as_millis()
+)]
+pub struct OutboundRateLimited {
+    pub provider: OutboundProvider,
+    pub retry_after: Duration,
+}
+
+impl OutboundRateLimited {
+    pub fn retry_after_ms(&self) -> u64 {
+        u64::try_from(self.retry_after.as_millis()).unwrap_or(u64::MAX)
+    }
+}
+
+/// Shared token buckets, one per provider. Clones share state, so one limiter built at
+/// startup covers every client in the process.
+#[derive(Clone)]
+pub struct OutboundCallLimiter {
+    google: Option<Arc<TokenBucket>>,
+    openrouter: Option<Arc<TokenBucket>>,
+    anthropic: Option<Arc<TokenBucket>>,
+    openai: Option<Arc<TokenBucket>>,
+    bedrock: Option<Arc<TokenBucket>>,
+    max_wait: Duration,
+}
+
+impl OutboundCallLimiter {
+    pub fn new(config: &OutboundRateLimitConfig) -> Self {
+        let now = Instant::now();
+        Self {
+            google: TokenBucket::new(config.google, now).map(Arc::new),
+            openrouter: TokenBucket::new(config.openrouter, now).map(Arc::new),
+            anthropic: TokenBucket::new(config.anthropic, now).map(Arc::new),
+            openai: TokenBucket::new(config.openai, now).map(Arc::new),
+            bedrock: TokenBucket::new(config.bedrock, now).map(Arc::new),
+            max_wait: config.max_wait,
+        }
+    }
+
+    /// Limiter that never waits or rejects, for clients built without enclave config.
+    pub fn unlimited() -> Self {
+        Self {
+            google: None,
+            openrouter: None,
+            anthropic: None,
+            openai: None,
+            bedrock: None,
+            max_wait: Duration::ZERO,
+        }
+    }
+
+    /// Waits for the next call slot for `provider`. Slots are reserved in arrival order, so
+    /// a call either gets a fixed place in the queue or is rejected up front when that place
+    /// is further out than the configured maximum wait.
+    pub async fn acquire(&self, provider: OutboundProvider) -> Result<(), OutboundRateLimited> {
+        let bucket = match provider {
+            OutboundProvider::Google => self.google.as_ref(),
+            OutboundProvider::OpenRouter => self.openrouter.as_ref(),
+            OutboundProvider::Anthropic => self.anthropic.as_ref(),
+            OutboundProvider::OpenAi => self.openai.as_ref(),
+            OutboundProvider::Bedrock => self.bedrock.as_ref(),
+        };
+        let Some(bucket) = bucket else {
+            return Ok(());
+        };
+
+        match bucket.reserve(Instant::now(), self.max_wait) {
+            Ok(wait) => {
+                if !wait.is_zero() {
+                    tokio::time::sleep(wait).await;
+                }
+                Ok(())
+            }
+            Err(retry_after) => Err(OutboundRateLimited {
+                provider,
+                retry_after,
+            }),
+        }
+    }
+}
+
+struct TokenBucket {
+    calls_per_second: f64,
+    burst: f64,
+    state: Mutex<BucketState>,
+}
+
+struct BucketState {
+    /// Negative while callers are queued behind an empty bucket.
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    fn new(limit: OutboundProviderLimit, now: Instant) -> Option<Self> {
+        if limit.calls_per_second == 0 {
+            return None;
+        }
+
+        let burst = f64::from(limit.burst.max(1));
+        Some(Self {
+            calls_per_second: f64::from(limit.calls_per_second),
+            burst,
+            state: Mutex::new(BucketState {
+                tokens: burst,
+                refilled_at: now,
+            }),
+        })
+    }
+
+    /// Returns how long the caller must wait for its reserved slot, or the wait it would have
+    /// needed when that exceeds `max_wait` (nothing is reserved then).
+    fn reserve(&self, now: Instant, max_wait: Duration) -> Result<Duration, Duration> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        if now > state.refilled_at {
+            let elapsed = now.duration_since(state.refilled_at).as_secs_f64();
+            state.tokens = (state.tokens + elapsed * self.calls_per_second).min(self.burst);
+            state.refilled_at = now;
+        }
+
+        let wait = if state.tokens >= 1.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64((1.0 - state.tokens) / self.calls_per_second)
+        };
+        if wait > max_wait {
+            return Err(wait);
+        }
+
+        state.tokens -= 1.0;
+        Ok(wait)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{
+        OutboundCallLimiter, OutboundProvider, OutboundProviderLimit, OutboundRateLimitConfig,
+        TokenBucket,
+    };
+
+    fn bucket(calls_per_second: u32, burst: u32, now: Instant) -> TokenBucket {
+        TokenBucket::new(
+            OutboundProviderLimit {
+                calls_per_second,
+                burst,
+            },
+            now,
+        )
+        .expect("non-zero rate should build a bucket")
+    }
+
+    #[test]
+    fn queues_callers_in_order_and_rejects_past_max_wait() {
+        let start = Instant::now();
+        let bucket = bucket(2, 2, start);
+        let max_wait = Duration::from_millis(1_000);
+
+        assert_eq!(bucket.reserve(start, max_wait), Ok(Duration::ZERO));
+        assert_eq!(bucket.reserve(start, max_wait), Ok(Duration::ZERO));
+        assert_eq!(
+            bucket.reserve(start, max_wait),
+            Ok(Duration::from_millis(500))
+        );
+        assert_eq!(
+            bucket.reserve(start, max_wait),
+            Ok(Duration::from_millis(1_000))
+        );
+        assert_eq!(
+            bucket.reserve(start, max_wait),
+            Err(Duration::from_millis(1_500))
+        );
+
+        // Rejections reserve nothing, so the queue drains on schedule.
+        let later = start + Duration::from_millis(1_000);
+        assert_eq!(
+            bucket.reserve(later, max_wait),
+            Ok(Duration::from_millis(500))
+        );
+    }
+
+    #[test]
+    fn refills_up_to_burst_only() {
+        let start = Instant::now();
+        let bucket = bucket(10, 3, start);
+        let later = start + Duration::from_secs(60);
+
+        for _ in 0..3 {
+            assert_eq!(bucket.reserve(later, Duration::ZERO), Ok(Duration::ZERO));
+        }
+        assert!(bucket.reserve(later, Duration::ZERO).is_err());
+    }
+
+    #[tokio::test]
+    async fn zero_rate_leaves_provider_unlimited() {
+        let limiter = OutboundCallLimiter::new(&OutboundRateLimitConfig {
+            google: OutboundProviderLimit {
+                calls_per_second: 0,
+                burst: 0,
+            },
+            openrouter: OutboundProviderLimit {
+                calls_per_second: 1,
+                burst: 1,
+            },
+            anthropic: OutboundProviderLimit {
+                calls_per_second: 0,
+                burst: 0,
+            },
+            openai: OutboundProviderLimit {
+                calls_per_second: 0,
+                burst: 0,
+            },
+            bedrock: OutboundProviderLimit {
+                calls_per_second: 0,
+                burst: 0,
+            },
+            max_wait: Duration::ZERO,
+        });
+
+        for _ in 0..100 {
+            assert!(limiter.acquire(OutboundProvider::Google).await.is_ok());
+        }
+        assert!(limiter.acquire(OutboundProvider::OpenRouter).await.is_ok());
+        let rejected = limiter
+            .acquire(OutboundProvider::OpenRouter)
+            .await
+            .expect_err("second openrouter call should be rejected");
+        assert_eq!(rejected.provider, OutboundProvider::OpenRouter);
+        assert!(rejected.retry_after_ms() > 0);
+    }
+}
@@ -0,0 +1,306 @@
+use base64::Engine as _;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::Sha256;
+use thiserror::Error;
+use uuid::Uuid;
+
+const CURSOR_VERSION: u8 = 1;
+const CURSOR_SIGNATURE_DOMAIN: &[u8] = b"alfred-pagination-cursor-v1";
+
+/// Paginated resource a cursor was issued for. Cursors only decode for the same resource.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CursorResource {
+    AuditEvents,
+    AutomationRules,
+    AutomationReports,
+    AssistantSessions,
+    WebhookDeliveries,
+}
+
+impl CursorResource {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::AuditEvents => "audit_events",
+            Self::AutomationRules => "automation_rules",
+            Self::AutomationReports => "automation_reports",
+            Self::AssistantSessions => "assistant_sessions",
+            Self::WebhookDeliveries => "webhook_deliveries",
+        }
+    }
+}
+
+/// Position after the last row of a page for lists ordered by `(timestamp DESC, id DESC)`.
+/// Repos own which timestamp column `at` refers to; the API only ever sees it signed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageKey {
+    /// Only set by lists that put pinned rows ahead of everything else, i.e. ordered by
+    /// `(pinned DESC, timestamp DESC, id DESC)`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
+    #[serde(alias = "created_at")]
+    pub at: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+/// One page of a keyset-paginated list: start strictly after `after`, return up to `limit`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageRequest {
+    pub after: Option<PageKey>,
+    pub limit: usize,
+}
+
+impl PageRequest {
+    pub fn first(limit: usize) -> Self {
+        Self { after: None, limit }
+    }
+
+    pub fn after_at(&self) -> Option<DateTime<Utc>> {
+        self.after.map(|key| key.at)
+    }
+
+    pub fn after_id(&self) -> Option<Uuid> {
+        self.after.map(|key| key.id)
+    }
+
+    pub fn after_pinned(&self) -> Option<bool> {
+        self.after.map(|key| key.pinned)
+    }
+
+    /// Rows to fetch: the one extra row tells whether another page exists, so a list that
+    /// ends exactly on a page boundary does not hand out a cursor to an empty page.
+    pub fn fetch_limit(&self) -> i64 {
+        i64::try_from(self.limit)
+            .unwrap_or(i64::MAX)
+            .saturating_add(1)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next: Option<PageKey>,
+}
+
+impl<T> Page<T> {
+    /// Builds a page from rows fetched with [`PageRequest::fetch_limit`], each paired with
+    /// its keyset position.
+    pub fn from_keyed_rows(mut rows: Vec<(PageKey, T)>, request: PageRequest) -> Self {
+        let has_more = rows.len() > request.limit;
+        rows.truncate(request.limit);
+        let next = if has_more {
+            rows.last().map(|(key, _)| *key)
+        } else {
+            None
+        };
+
+        Self {
+            items: rows.into_iter().map(|(_, item)| item).collect(),
+            next,
+        }
+    }
+
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            next: self.next,
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum CursorError {
+    #[error("cursor is malformed")]
+    Malformed,
+    #[error("cursor signature is invalid")]
+    InvalidSignature,
+    #[error("cursor was issued for a different resource")]
+    ResourceMismatch,
+}
+
+/// Encodes page keys as opaque `base64url(payload).base64url(hmac)` cursors so clients cannot
+/// read or forge them.
+#[derive(Clone)]
+pub struct PaginationCursorCodec {
+    secret: Vec<u8>,
+}
+
+impl std::fmt::Debug for PaginationCursorCodec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PaginationCursorCodec")
+            .field("secret", &"<redacted>")
+            .finish()
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CursorPayload<T> {
+    v: u8,
+    r: String,
+    k: T,
+}
+
+impl PaginationCursorCodec {
+    pub fn new(secret: &str) -> Self {
+        Self {
+            secret: secret.as_bytes().to_vec(),
+        }
+    }
+
+    pub fn encode<T: Serialize>(&self, resource: CursorResource, key: &T) -> String {
+        let payload = serde_json::to_vec(&CursorPayload {
+            v: CURSOR_VERSION,
+            r: resource.as_str().to_string(),
+            k: key,
+        })
+        .expect("cursor payload should serialize");
+        let signature = self.mac(&payload).finalize().into_bytes();
+
+        format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(&payload),
+            URL_SAFE_NO_PAD.encode(signature)
+        )
+    }
+
+    pub fn decode<T: DeserializeOwned>(
+        &self,
+        resource: CursorResource,
+        cursor: &str,
+    ) -> Result<T, CursorError> {
+        let (payload, signature) = cursor.split_once('.').ok_or(CursorError::Malformed)?;
+        let payload = URL_SAFE_NO_PAD
+            .decode(payload)
+            .map_err(|_| CursorError::Malformed)?;
+        let signature = URL_SAFE_NO_PAD
+            .decode(signature)
+            .map_err(|_| CursorError::Malformed)?;
+        self.mac(&payload)
+            .verify_slice(&signature)
+            .map_err(|_| CursorError::InvalidSignature)?;
+
+        let payload: CursorPayload<Value> =
+            serde_json::from_slice(&payload).map_err(|_| CursorError::Malformed)?;
+        if payload.v != CURSOR_VERSION {
+            return Err(CursorError::Malformed);
+        }
+        if payload.r != resource.as_str() {
+            return Err(CursorError::ResourceMismatch);
+        }
+
+        serde_json::from_value(payload.k).map_err(|_| CursorError::Malformed)
+    }
+
+    fn mac(&self, payload: &[u8]) -> Hmac<Sha256> {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.secret).expect("hmac accepts keys of any length");
+        mac.update(CURSOR_SIGNATURE_DOMAIN);
+        mac.update(payload);
+        mac
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::Engine as _;
+    use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    use serde::{Deserialize, Serialize};
+
+    use chrono::{TimeZone, Utc};
+    use uuid::Uuid;
+
+    use super::{CursorError, CursorResource, Page, PageKey, PageRequest, PaginationCursorCodec};
+
+    #[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+    struct OffsetKey {
+        offset: u32,
+    }
+
+    #[test]
+    fn cursors_round_trip_and_hide_page_keys() {
+        let codec = PaginationCursorCodec::new("test-pagination-secret");
+        let cursor = codec.encode(CursorResource::AuditEvents, &OffsetKey { offset: 42 });
+
+        assert!(!cursor.contains("offset"));
+        assert_eq!(
+            codec.decode::<OffsetKey>(CursorResource::AuditEvents, &cursor),
+            Ok(OffsetKey { offset: 42 })
+        );
+    }
+
+    #[test]
+    fn cursors_reject_tampering_and_other_secrets() {
+        let codec = PaginationCursorCodec::new("test-pagination-secret");
+        let cursor = codec.encode(CursorResource::AuditEvents, &OffsetKey { offset: 42 });
+        let (_, signature) = cursor.split_once('.').expect("cursor has two parts");
+        let forged_payload =
+            URL_SAFE_NO_PAD.encode(br#"{"v":1,"r":"audit_events","k":{"offset":0}}"#);
+
+        assert_eq!(
+            codec.decode::<OffsetKey>(
+                CursorResource::AuditEvents,
+                &format!("{forged_payload}.{signature}")
+            ),
+            Err(CursorError::InvalidSignature)
+        );
+        assert_eq!(
+            PaginationCursorCodec::new("other-pagination-secret")
+                .decode::<OffsetKey>(CursorResource::AuditEvents, &cursor),
+            Err(CursorError::InvalidSignature)
+        );
+        assert_eq!(
+            codec.decode::<OffsetKey>(CursorResource::AuditEvents, "1700000000|not-a-cursor"),
+            Err(CursorError::Malformed)
+        );
+    }
+
+    #[test]
+    fn pages_only_return_a_next_key_when_more_rows_exist() {
+        let keyed_rows = (0..3)
+            .map(|offset| {
+                let key = PageKey {
+                    pinned: false,
+                    at: Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap()
+                        - chrono::Duration::minutes(offset),
+                    id: Uuid::new_v4(),
+                };
+                (key, offset)
+            })
+            .collect::<Vec<_>>();
+        let request = PageRequest::first(2);
+        assert_eq!(request.fetch_limit(), 3);
+
+        let page = Page::from_keyed_rows(keyed_rows.clone(), request);
+        assert_eq!(page.items, vec![0, 1]);
+        assert_eq!(page.next, Some(keyed_rows[1].0));
+
+        let last_page = Page::from_keyed_rows(keyed_rows[..2].to_vec(), request);
+        assert_eq!(last_page.items, vec![0, 1]);
+        assert_eq!(last_page.next, None);
+    }
+
+    #[test]
+    fn page_keys_decode_from_legacy_audit_cursor_fields() {
+        let codec = PaginationCursorCodec::new("test-pagination-secret");
+        let legacy = codec.encode(
+            CursorResource::AuditEvents,
+            &serde_json::json!({
+                "created_at": "2026-01-01T00:00:00Z",
+                "id": "00000000-0000-0000-0000-000000000001"
+            }),
+        );
+
+        let key = codec
+            .decode::<PageKey>(CursorResource::AuditEvents, &legacy)
+            .expect("legacy audit cursors should still decode");
+        assert_eq!(key.at, Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap());
+        assert_eq!(
+            codec.decode::<PageKey>(CursorResource::AutomationRules, &legacy),
+            Err(CursorError::ResourceMismatch)
+        );
+    }
+}
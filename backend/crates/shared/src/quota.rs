@@ -0,0 +1,128 @@
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate, NaiveTime, Utc};
+
+use crate::models::{QuotaKind, UserPlan};
+
+/// Limits granted by a plan. Daily quotas reset at UTC midnight; the LLM spend budget resets
+/// on the first of each UTC month.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlanQuotas {
+    pub assistant_queries_per_day: i64,
+    /// Automation rules that are not archived.
+    pub automation_rules: i64,
+    pub llm_requests_per_day: i64,
+    /// Estimated provider spend allowed per UTC month, in millionths of a US dollar.
+    pub llm_spend_micros_per_month: i64,
+}
+
+impl PlanQuotas {
+    pub fn for_plan(plan: UserPlan) -> Self {
+        match plan {
+            UserPlan::Free => Self {
+                assistant_queries_per_day: 50,
+                automation_rules: 10,
+                llm_requests_per_day: 100,
+                llm_spend_micros_per_month: 1_000_000,
+            },
+            UserPlan::Pro => Self {
+                assistant_queries_per_day: 500,
+                automation_rules: 100,
+                llm_requests_per_day: 1_000,
+                llm_spend_micros_per_month: 20_000_000,
+            },
+        }
+    }
+
+    pub fn limit(&self, kind: QuotaKind) -> i64 {
+        match kind {
+            QuotaKind::AssistantQueriesPerDay => self.assistant_queries_per_day,
+            QuotaKind::AutomationRules => self.automation_rules,
+            QuotaKind::LlmRequestsPerDay => self.llm_requests_per_day,
+        }
+    }
+}
+
+impl QuotaKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::AssistantQueriesPerDay => "assistant_queries_per_day",
+            Self::AutomationRules => "automation_rules",
+            Self::LlmRequestsPerDay => "llm_requests_per_day",
+        }
+    }
+
+    pub fn is_daily(self) -> bool {
+        !matches!(self, Self::AutomationRules)
+    }
+}
+
+/// Start of the UTC day containing `now`; daily usage is counted from here.
+pub fn quota_day_start(now: DateTime<Utc>) -> DateTime<Utc> {
+    now.date_naive().and_time(NaiveTime::MIN).and_utc()
+}
+
+/// When a daily quota counted from `quota_day_start(now)` resets.
+pub fn quota_day_reset(now: DateTime<Utc>) -> DateTime<Utc> {
+    quota_day_start(now) + Duration::days(1)
+}
+
+/// First day of the UTC month containing `now`; LLM spend is counted from here.
+pub fn llm_spend_month_start(now: DateTime<Utc>) -> NaiveDate {
+    now.date_naive().with_day(1).unwrap_or(now.date_naive())
+}
+
+/// When the LLM spend budget counted from `llm_spend_month_start(now)` resets.
+pub fn llm_spend_month_reset(now: DateTime<Utc>) -> DateTime<Utc> {
+    let month_start = llm_spend_month_start(now);
+    month_start
+        .checked_add_months(Months::new(1))
+        .unwrap_or(month_start)
+        .and_time(NaiveTime::MIN)
+        .and_utc()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn pro_plan_raises_every_limit() {
+        let free = PlanQuotas::for_plan(UserPlan::Free);
+        let pro = PlanQuotas::for_plan(UserPlan::Pro);
+        for kind in [
+            QuotaKind::AssistantQueriesPerDay,
+            QuotaKind::AutomationRules,
+            QuotaKind::LlmRequestsPerDay,
+        ] {
+            assert!(pro.limit(kind) > free.limit(kind), "{}", kind.as_str());
+        }
+        assert!(pro.llm_spend_micros_per_month > free.llm_spend_micros_per_month);
+    }
+
+    #[test]
+    fn daily_quotas_reset_at_utc_midnight() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 14, 23, 59, 30).unwrap();
+        assert_eq!(
+            quota_day_start(now),
+            Utc.with_ymd_and_hms(2026, 3, 14, 0, 0, 0).unwrap()
+        );
+        assert_eq!(
+            quota_day_reset(now),
+            Utc.with_ymd_and_hms(2026, 3, 15, 0, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn llm_spend_budget_resets_on_the_first_of_the_utc_month() {
+        let now = Utc.with_ymd_and_hms(2026, 12, 31, 23, 59, 30).unwrap();
+        assert_eq!(
+            llm_spend_month_start(now),
+            NaiveDate::from_ymd_opt(2026, 12, 1).unwrap()
+        );
+        assert_eq!(
+            llm_spend_month_reset(now),
+            Utc.with_ymd_and_hms(2027, 1, 1, 0, 0, 0).unwrap()
+        );
+    }
+}
@@ -0,0 +1,235 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use redis::aio::{ConnectionManager, ConnectionManagerConfig};
+use sha2::{Digest, Sha256};
+use tracing::warn;
+
+const REPLAY_GUARD_KEY_PREFIX: &str = "alfred:replay:v1";
+/// Every guarded request waits on this round trip, so a slow Redis must fail fast and let the
+/// in-memory guard take over.
+const REPLAY_GUARD_RESPONSE_TIMEOUT: Duration = Duration::from_millis(100);
+const REPLAY_GUARD_CONNECTION_TIMEOUT: Duration = Duration::from_secs(2);
+const REPLAY_GUARD_CONNECTION_RETRIES: usize = 2;
+const REPLAY_GUARD_MAX_RETRY_DELAY_MS: u64 = 500;
+pub const DEFAULT_REPLAY_GUARD_MEMORY_CAPACITY: usize = 100_000;
+
+/// Nonces already seen inside their validity window. Nonces are recorded in Redis with a TTL
+/// that ends with the window, so a replay is caught across instances and restarts. Every
+/// nonce is also kept in a bounded in-memory map, which decides on its own while Redis is
+/// unconfigured or failing.
+#[derive(Clone)]
+pub struct NonceReplayGuard {
+    memory: Arc<Mutex<MemoryNonces>>,
+    redis: Option<ConnectionManager>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NonceReplayed;
+
+impl NonceReplayGuard {
+    pub fn in_memory(capacity: usize) -> Self {
+        Self {
+            memory: Arc::new(Mutex::new(MemoryNonces::new(capacity))),
+            redis: None,
+        }
+    }
+
+    /// Shares recorded nonces through Redis. Redis being unreachable at startup is logged and
+    /// the guard stays in-memory.
+    pub async fn with_redis_from_config(mut self, redis_url: &str) -> Self {
+        match connect(redis_url).await {
+            Ok(connection) => self.redis = Some(connection),
+            Err(err) => warn!("shared replay guard disabled, redis unavailable: {err}"),
+        }
+        self
+    }
+
+    /// Records `nonce` under `scope` until `expires_at` and fails if it was already recorded.
+    /// Scopes keep unrelated nonce spaces, such as attestation challenges and RPC requests,
+    /// from colliding.
+    pub async fn check_and_record(
+        &self,
+        scope: &str,
+        nonce: &str,
+        expires_at: i64,
+        now: i64,
+    ) -> Result<(), NonceReplayed> {
+        let key = replay_key(scope, nonce);
+        let expires_at = expires_at.max(now);
+        self.memory_guard()
+            .check_and_record(&key, expires_at, now)?;
+
+        let Some(connection) = self.redis.as_ref() else {
+            return Ok(());
+        };
+        // Keys expire one second after the window closes so a nonce never outlives Redis
+        // rounding down.
+        let ttl_seconds = u64::try_from(expires_at - now).unwrap_or(0) + 1;
+        let mut connection = connection.clone();
+        let recorded = redis::cmd("SET")
+            .arg(&key)
+            .arg(1)
+            .arg("NX")
+            .arg("EX")
+            .arg(ttl_seconds)
+            .query_async::<Option<String>>(&mut connection)
+            .await;
+        match recorded {
+            Ok(Some(_)) => Ok(()),
+            Ok(None) => Err(NonceReplayed),
+            Err(err) => {
+                warn!(error = %err, scope, "shared replay guard check failed, using in-memory guard");
+                Ok(())
+            }
+        }
+    }
+
+    fn memory_guard(&self) -> std::sync::MutexGuard<'_, MemoryNonces> {
+        self.memory
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Default for NonceReplayGuard {
+    fn default() -> Self {
+        Self::in_memory(DEFAULT_REPLAY_GUARD_MEMORY_CAPACITY)
+    }
+}
+
+impl std::fmt::Debug for NonceReplayGuard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NonceReplayGuard")
+            .field("redis", &self.redis.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Expiring nonce set capped at `capacity`. Expired entries are dropped first; when the cap is
+/// still reached the least recently recorded nonce is evicted, which at worst reopens the
+/// oldest nonce of a flood rather than growing without bound.
+#[derive(Debug)]
+struct MemoryNonces {
+    capacity: usize,
+    expiries: HashMap<String, i64>,
+    order: VecDeque<String>,
+}
+
+impl MemoryNonces {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            expiries: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    fn check_and_record(
+        &mut self,
+        key: &str,
+        expires_at: i64,
+        now: i64,
+    ) -> Result<(), NonceReplayed> {
+        if self.expiries.get(key).is_some_and(|expiry| *expiry >= now) {
+            return Err(NonceReplayed);
+        }
+
+        if self.expiries.len() >= self.capacity {
+            self.expiries.retain(|_, expiry| *expiry >= now);
+            let expiries = &self.expiries;
+            self.order.retain(|key| expiries.contains_key(key));
+        }
+        while self.expiries.len() >= self.capacity {
+            let Some(oldest) = self.order.pop_front() else {
+                break;
+            };
+            self.expiries.remove(&oldest);
+        }
+
+        if self.expiries.insert(key.to_string(), expires_at).is_none() {
+            self.order.push_back(key.to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Nonces are caller-chosen, so only their hash reaches Redis.
+fn replay_key(scope: &str, nonce: &str) -> String {
+    let digest = Sha256::digest(nonce.as_bytes());
+    let nonce_hash = digest
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>();
+    format!("{REPLAY_GUARD_KEY_PREFIX}:{scope}:{nonce_hash}")
+}
+
+async fn connect(redis_url: &str) -> Result<ConnectionManager, String> {
+    let client = redis::Client::open(redis_url).map_err(|err| err.to_string())?;
+    let connection = ConnectionManager::new_with_config(
+        client,
+        ConnectionManagerConfig::new()
+            .set_response_timeout(REPLAY_GUARD_RESPONSE_TIMEOUT)
+            .set_connection_timeout(REPLAY_GUARD_CONNECTION_TIMEOUT)
+            .set_number_of_retries(REPLAY_GUARD_CONNECTION_RETRIES)
+            .set_max_delay(REPLAY_GUARD_MAX_RETRY_DELAY_MS),
+    )
+    .await
+    .map_err(|err| err.to_string())?;
+
+    let mut health_connection = connection.clone();
+    redis::cmd("PING")
+        .query_async::<String>(&mut health_connection)
+        .await
+        .map_err(|err| format!("failed to connect to redis: {err}"))?;
+
+    Ok(connection)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn in_memory_guard_rejects_replays_until_the_window_closes() {
+        let guard = NonceReplayGuard::in_memory(16);
+
+        guard
+            .check_and_record("rpc", "nonce-1", 130, 100)
+            .await
+            .expect("first use should pass");
+        assert_eq!(
+            guard.check_and_record("rpc", "nonce-1", 130, 120).await,
+            Err(NonceReplayed)
+        );
+        guard
+            .check_and_record("attestation", "nonce-1", 130, 120)
+            .await
+            .expect("scopes do not share nonces");
+        guard
+            .check_and_record("rpc", "nonce-1", 170, 140)
+            .await
+            .expect("an expired nonce may be reused");
+    }
+
+    #[test]
+    fn memory_nonces_stay_within_capacity() {
+        let mut nonces = MemoryNonces::new(2);
+        nonces.check_and_record("a", 10, 0).expect("a");
+        nonces.check_and_record("b", 20, 0).expect("b");
+        nonces.check_and_record("c", 30, 0).expect("c");
+
+        assert_eq!(nonces.expiries.len(), 2);
+        assert!(
+            !nonces.expiries.contains_key("a"),
+            "oldest nonce is evicted"
+        );
+        assert_eq!(nonces.check_and_record("c", 30, 1), Err(NonceReplayed));
+
+        // Expired entries make room before anything live is evicted.
+        nonces.check_and_record("d", 40, 25).expect("d");
+        assert!(nonces.expiries.contains_key("c"));
+        assert!(!nonces.expiries.contains_key("b"));
+    }
+}
@@ -1,8 +1,10 @@
 use chrono::{DateTime, Duration, Utc};
 use sqlx::Row;
+use sqlx::postgres::PgRow;
 use uuid::Uuid;
 
-use crate::models::AssistantSessionStateEnvelope;
+use crate::models::{AssistantSessionStateEnvelope, AssistantSessionTitleEnvelope};
+use crate::pagination::{Page, PageKey, PageRequest};
 
 use super::{Store, StoreError};
 
@@ -21,18 +23,96 @@ pub struct AssistantEncryptedSessionMetadataRecord {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub expires_at: DateTime<Utc>,
+    pub title_envelope: Option<AssistantSessionTitleEnvelope>,
+    pub pinned: bool,
+}
+
+/// Thread drawer metadata change. `None` leaves a field as stored; `title_envelope:
+/// Some(None)` clears the title.
+#[derive(Debug, Clone, Default)]
+pub struct AssistantEncryptedSessionMetadataUpdate {
+    pub title_envelope: Option<Option<AssistantSessionTitleEnvelope>>,
+    pub pinned: Option<bool>,
+}
+
+/// A live session with its sealed state, as handed to the enclave for a conversation export.
+#[derive(Debug, Clone)]
+pub struct AssistantEncryptedSessionExportRecord {
+    pub session_id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub title_envelope: Option<AssistantSessionTitleEnvelope>,
+    pub pinned: bool,
+    pub state: AssistantSessionStateEnvelope,
 }
 
 impl Store {
+    /// Pinned sessions first, then most recently updated.
     pub async fn list_assistant_encrypted_sessions(
+        &self,
+        user_id: Uuid,
+        now: DateTime<Utc>,
+        page: PageRequest,
+    ) -> Result<Page<AssistantEncryptedSessionMetadataRecord>, StoreError> {
+        if page.limit == 0 {
+            return Err(StoreError::InvalidData(
+                "assistant encrypted session list limit must be > 0".to_string(),
+            ));
+        }
+
+        self.purge_expired_assistant_encrypted_sessions(user_id, now)
+            .await?;
+
+        let rows = sqlx::query(
+            "SELECT session_id, created_at, updated_at, expires_at, title_envelope_json, pinned
+             FROM assistant_encrypted_sessions
+             WHERE user_id = $1
+               AND expires_at > $2
+               AND (
+                 $3::timestamptz IS NULL
+                 OR (pinned, updated_at, session_id) < ($5, $3, $4)
+               )
+             ORDER BY pinned DESC, updated_at DESC, session_id DESC
+             LIMIT $6",
+        )
+        .bind(user_id)
+        .bind(now)
+        .bind(page.after_at())
+        .bind(page.after_id())
+        .bind(page.after_pinned())
+        .bind(page.fetch_limit())
+        .fetch_all(&self.pool)
+        .await?;
+
+        let keyed_rows = rows
+            .into_iter()
+            .map(|row| {
+                let session = metadata_record_from_row(&row)?;
+                Ok((
+                    PageKey {
+                        pinned: session.pinned,
+                        at: session.updated_at,
+                        id: session.session_id,
+                    },
+                    session,
+                ))
+            })
+            .collect::<Result<Vec<_>, StoreError>>()?;
+
+        Ok(Page::from_keyed_rows(keyed_rows, page))
+    }
+
+    /// Newest-first live sessions including their sealed state, capped at `limit`.
+    pub async fn list_assistant_encrypted_sessions_for_export(
         &self,
         user_id: Uuid,
         now: DateTime<Utc>,
         limit: i64,
-    ) -> Result<Vec<AssistantEncryptedSessionMetadataRecord>, StoreError> {
+    ) -> Result<Vec<AssistantEncryptedSessionExportRecord>, StoreError> {
         if limit <= 0 {
             return Err(StoreError::InvalidData(
-                "assistant encrypted session list limit must be > 0".to_string(),
+                "assistant encrypted session export limit must be > 0".to_string(),
             ));
         }
 
@@ -40,7 +120,8 @@ impl Store {
             .await?;
 
         let rows = sqlx::query(
-            "SELECT session_id, created_at, updated_at, expires_at
+            "SELECT session_id, created_at, updated_at, expires_at, title_envelope_json, pinned,
+                    state_json
              FROM assistant_encrypted_sessions
              WHERE user_id = $1
                AND expires_at > $2
@@ -55,11 +136,24 @@ impl Store {
 
         rows.into_iter()
             .map(|row| {
-                Ok(AssistantEncryptedSessionMetadataRecord {
-                    session_id: row.try_get("session_id")?,
-                    created_at: row.try_get("created_at")?,
-                    updated_at: row.try_get("updated_at")?,
-                    expires_at: row.try_get("expires_at")?,
+                let state_json: String = row.try_get("state_json")?;
+                let state = serde_json::from_str::<AssistantSessionStateEnvelope>(&state_json)
+                    .map_err(|err| {
+                        StoreError::InvalidData(format!(
+                            "assistant encrypted session invalid: {err}"
+                        ))
+                    })?;
+
+                let metadata = metadata_record_from_row(&row)?;
+
+                Ok(AssistantEncryptedSessionExportRecord {
+                    session_id: metadata.session_id,
+                    created_at: metadata.created_at,
+                    updated_at: metadata.updated_at,
+                    expires_at: metadata.expires_at,
+                    title_envelope: metadata.title_envelope,
+                    pinned: metadata.pinned,
+                    state,
                 })
             })
             .collect()
@@ -152,6 +246,47 @@ impl Store {
         Ok(())
     }
 
+    /// Applies a thread drawer metadata change to a live session without touching
+    /// `updated_at`, so renaming or pinning a thread does not reorder it by recency. Returns
+    /// `None` when the session does not exist or has expired.
+    pub async fn update_assistant_encrypted_session_metadata(
+        &self,
+        user_id: Uuid,
+        session_id: Uuid,
+        update: &AssistantEncryptedSessionMetadataUpdate,
+        now: DateTime<Utc>,
+    ) -> Result<Option<AssistantEncryptedSessionMetadataRecord>, StoreError> {
+        let title_envelope_json = update
+            .title_envelope
+            .as_ref()
+            .and_then(Option::as_ref)
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|err| {
+                StoreError::InvalidData(format!("assistant session title invalid: {err}"))
+            })?;
+
+        let row = sqlx::query(
+            "UPDATE assistant_encrypted_sessions
+             SET title_envelope_json = CASE WHEN $3 THEN $4 ELSE title_envelope_json END,
+                 pinned = COALESCE($5, pinned)
+             WHERE user_id = $1
+               AND session_id = $2
+               AND expires_at > $6
+             RETURNING session_id, created_at, updated_at, expires_at, title_envelope_json, pinned",
+        )
+        .bind(user_id)
+        .bind(session_id)
+        .bind(update.title_envelope.is_some())
+        .bind(title_envelope_json)
+        .bind(update.pinned)
+        .bind(now)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.as_ref().map(metadata_record_from_row).transpose()
+    }
+
     pub async fn delete_assistant_encrypted_session(
         &self,
         user_id: Uuid,
@@ -245,3 +380,24 @@ impl Store {
         Ok(())
     }
 }
+
+fn metadata_record_from_row(
+    row: &PgRow,
+) -> Result<AssistantEncryptedSessionMetadataRecord, StoreError> {
+    let title_envelope_json: Option<String> = row.try_get("title_envelope_json")?;
+    let title_envelope = title_envelope_json
+        .map(|json| serde_json::from_str::<AssistantSessionTitleEnvelope>(&json))
+        .transpose()
+        .map_err(|err| {
+            StoreError::InvalidData(format!("assistant session title invalid: {err}"))
+        })?;
+
+    Ok(AssistantEncryptedSessionMetadataRecord {
+        session_id: row.try_get("session_id")?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+        expires_at: row.try_get("expires_at")?,
+        title_envelope,
+        pinned: row.try_get("pinned")?,
+    })
+}
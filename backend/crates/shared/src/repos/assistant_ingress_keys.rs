@@ -0,0 +1,155 @@
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+
+use super::{Store, StoreError};
+
+/// Role of a persisted ingress key. Only the active key is handed out to clients; the previous
+/// key keeps decrypting in-flight requests until its expiry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssistantIngressKeyStatus {
+    Active,
+    Previous,
+}
+
+impl AssistantIngressKeyStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Active => "ACTIVE",
+            Self::Previous => "PREVIOUS",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "ACTIVE" => Some(Self::Active),
+            "PREVIOUS" => Some(Self::Previous),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AssistantIngressKeyRecord {
+    pub key_id: String,
+    pub public_key: String,
+    pub wrapped_private_key: Vec<u8>,
+    pub wrap_nonce: Vec<u8>,
+    pub wrapping_key_id: String,
+    pub status: AssistantIngressKeyStatus,
+    pub activated_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewAssistantIngressKey {
+    pub key_id: String,
+    pub public_key: String,
+    pub wrapped_private_key: Vec<u8>,
+    pub wrap_nonce: Vec<u8>,
+    pub wrapping_key_id: String,
+}
+
+impl Store {
+    /// Active key plus any previous key that has not expired yet, newest first.
+    pub async fn list_live_assistant_ingress_keys(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<AssistantIngressKeyRecord>, StoreError> {
+        let rows = sqlx::query(
+            "SELECT key_id, public_key, wrapped_private_key, wrap_nonce, wrapping_key_id, status,
+                    activated_at, expires_at
+             FROM assistant_ingress_keys
+             WHERE status = 'ACTIVE' OR expires_at > $1
+             ORDER BY activated_at DESC",
+        )
+        .bind(now)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let status: String = row.try_get("status")?;
+                Ok(AssistantIngressKeyRecord {
+                    key_id: row.try_get("key_id")?,
+                    public_key: row.try_get("public_key")?,
+                    wrapped_private_key: row.try_get("wrapped_private_key")?,
+                    wrap_nonce: row.try_get("wrap_nonce")?,
+                    wrapping_key_id: row.try_get("wrapping_key_id")?,
+                    status: AssistantIngressKeyStatus::parse(&status).ok_or_else(|| {
+                        StoreError::InvalidData(format!(
+                            "unknown assistant ingress key status persisted: {status}"
+                        ))
+                    })?,
+                    activated_at: row.try_get("activated_at")?,
+                    expires_at: row.try_get("expires_at")?,
+                })
+            })
+            .collect()
+    }
+
+    /// Makes `key` the active ingress key. The current active key is demoted to previous until
+    /// `previous_expires_at`, as are the prompt envelope request ids claimed under it, and the
+    /// key it replaces as previous is deleted. Returns `false`
+    /// without changes when the active key is no longer `expected_active_key_id`, which means
+    /// another enclave instance rotated first.
+    pub async fn rotate_assistant_ingress_key(
+        &self,
+        expected_active_key_id: Option<&str>,
+        key: &NewAssistantIngressKey,
+        previous_expires_at: DateTime<Utc>,
+    ) -> Result<bool, StoreError> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("LOCK TABLE assistant_ingress_keys IN SHARE ROW EXCLUSIVE MODE")
+            .execute(&mut *tx)
+            .await?;
+
+        let active_key_id: Option<String> =
+            sqlx::query_scalar("SELECT key_id FROM assistant_ingress_keys WHERE status = 'ACTIVE'")
+                .fetch_optional(&mut *tx)
+                .await?;
+        if active_key_id.as_deref() != expected_active_key_id {
+            tx.rollback().await?;
+            return Ok(false);
+        }
+
+        sqlx::query("DELETE FROM assistant_ingress_keys WHERE status = 'PREVIOUS'")
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(
+            "UPDATE assistant_ingress_keys
+             SET status = 'PREVIOUS', expires_at = $1
+             WHERE status = 'ACTIVE'",
+        )
+        .bind(previous_expires_at)
+        .execute(&mut *tx)
+        .await?;
+        if let Some(active_key_id) = active_key_id.as_deref() {
+            sqlx::query(
+                "UPDATE prompt_envelope_request_ids
+                 SET expires_at = $2
+                 WHERE key_id = $1
+                   AND expires_at IS NULL",
+            )
+            .bind(active_key_id)
+            .bind(previous_expires_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+        sqlx::query(
+            "INSERT INTO assistant_ingress_keys (
+                key_id, public_key, wrapped_private_key, wrap_nonce, wrapping_key_id, status
+             )
+             VALUES ($1, $2, $3, $4, $5, 'ACTIVE')",
+        )
+        .bind(&key.key_id)
+        .bind(&key.public_key)
+        .bind(&key.wrapped_private_key)
+        .bind(&key.wrap_nonce)
+        .bind(&key.wrapping_key_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+}
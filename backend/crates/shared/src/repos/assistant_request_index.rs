@@ -0,0 +1,207 @@
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+use sqlx::postgres::PgRow;
+use uuid::Uuid;
+
+use super::{Store, StoreError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssistantRequestOutcome {
+    Succeeded,
+    Failed,
+}
+
+impl AssistantRequestOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Succeeded => "SUCCEEDED",
+            Self::Failed => "FAILED",
+        }
+    }
+
+    fn from_db(value: &str) -> Result<Self, StoreError> {
+        match value {
+            "SUCCEEDED" => Ok(Self::Succeeded),
+            "FAILED" => Ok(Self::Failed),
+            _ => Err(StoreError::InvalidData(format!(
+                "unknown assistant request outcome persisted: {value}"
+            ))),
+        }
+    }
+}
+
+/// Content-free metadata of one assistant query. Failed queries carry an error code and no
+/// routing details, since the enclave did not report any.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssistantRequestIndexEntry {
+    pub request_id: String,
+    pub session_id: Option<Uuid>,
+    pub outcome: AssistantRequestOutcome,
+    pub error_code: Option<String>,
+    pub route: Option<String>,
+    pub capability: Option<String>,
+    pub model: Option<String>,
+    pub planner_used_fallback: bool,
+    pub output_used_fallback: bool,
+    pub planner_ms: Option<u64>,
+    pub lane_ms: Option<u64>,
+    pub enclave_rpc_ms: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct AssistantRequestIndexRecord {
+    pub entry: AssistantRequestIndexEntry,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Counts over every assistant query indexed inside a time window, across all users.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AssistantRequestWindowStats {
+    pub total: i64,
+    pub failed: i64,
+    pub enclave_failed: i64,
+    pub fallback: i64,
+}
+
+/// Error codes that mean the enclave itself could not serve the query, as opposed to a
+/// provider or connector failing behind it.
+const ENCLAVE_ERROR_CODES: &[&str] = &[
+    "rpc_unauthorized",
+    "rpc_contract_rejected",
+    "rpc_transport_unavailable",
+    "rpc_response_invalid",
+];
+
+impl Store {
+    /// Records the metadata of one assistant query. A retried request id keeps its first row,
+    /// so feedback always joins against the attempt the client saw first.
+    pub async fn record_assistant_request(
+        &self,
+        user_id: Uuid,
+        entry: &AssistantRequestIndexEntry,
+    ) -> Result<(), StoreError> {
+        sqlx::query(
+            "INSERT INTO assistant_request_index (
+                user_id,
+                request_id,
+                session_id,
+                outcome,
+                error_code,
+                route,
+                capability,
+                model,
+                planner_used_fallback,
+                output_used_fallback,
+                planner_ms,
+                lane_ms,
+                enclave_rpc_ms
+             )
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+             ON CONFLICT (user_id, request_id) DO NOTHING",
+        )
+        .bind(user_id)
+        .bind(&entry.request_id)
+        .bind(entry.session_id)
+        .bind(entry.outcome.as_str())
+        .bind(&entry.error_code)
+        .bind(&entry.route)
+        .bind(&entry.capability)
+        .bind(&entry.model)
+        .bind(entry.planner_used_fallback)
+        .bind(entry.output_used_fallback)
+        .bind(entry.planner_ms.map(clamp_ms))
+        .bind(entry.lane_ms.map(clamp_ms))
+        .bind(clamp_ms(entry.enclave_rpc_ms))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_assistant_request(
+        &self,
+        user_id: Uuid,
+        request_id: &str,
+    ) -> Result<Option<AssistantRequestIndexRecord>, StoreError> {
+        let row = sqlx::query(
+            "SELECT request_id, session_id, outcome, error_code, route, capability, model,
+                    planner_used_fallback, output_used_fallback, planner_ms, lane_ms,
+                    enclave_rpc_ms, created_at
+             FROM assistant_request_index
+             WHERE user_id = $1
+               AND request_id = $2",
+        )
+        .bind(user_id)
+        .bind(request_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.as_ref()
+            .map(assistant_request_record_from_row)
+            .transpose()
+    }
+
+    pub async fn assistant_request_window_stats(
+        &self,
+        since: DateTime<Utc>,
+    ) -> Result<AssistantRequestWindowStats, StoreError> {
+        let row = sqlx::query(
+            "SELECT
+                COUNT(*)::bigint AS total,
+                COUNT(*) FILTER (WHERE outcome = 'FAILED')::bigint AS failed,
+                COUNT(*) FILTER (
+                  WHERE outcome = 'FAILED' AND error_code = ANY($2)
+                )::bigint AS enclave_failed,
+                COUNT(*) FILTER (
+                  WHERE outcome = 'SUCCEEDED'
+                    AND (planner_used_fallback OR output_used_fallback)
+                )::bigint AS fallback
+             FROM assistant_request_index
+             WHERE created_at >= $1",
+        )
+        .bind(since)
+        .bind(ENCLAVE_ERROR_CODES)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(AssistantRequestWindowStats {
+            total: row.try_get("total")?,
+            failed: row.try_get("failed")?,
+            enclave_failed: row.try_get("enclave_failed")?,
+            fallback: row.try_get("fallback")?,
+        })
+    }
+}
+
+fn clamp_ms(value: u64) -> i32 {
+    i32::try_from(value).unwrap_or(i32::MAX)
+}
+
+fn assistant_request_record_from_row(
+    row: &PgRow,
+) -> Result<AssistantRequestIndexRecord, StoreError> {
+    let outcome: String = row.try_get("outcome")?;
+    let ms = |column: &str| -> Result<Option<u64>, StoreError> {
+        Ok(row
+            .try_get::<Option<i32>, _>(column)?
+            .map(|value| value.max(0) as u64))
+    };
+
+    Ok(AssistantRequestIndexRecord {
+        entry: AssistantRequestIndexEntry {
+            request_id: row.try_get("request_id")?,
+            session_id: row.try_get("session_id")?,
+            outcome: AssistantRequestOutcome::from_db(&outcome)?,
+            error_code: row.try_get("error_code")?,
+            route: row.try_get("route")?,
+            capability: row.try_get("capability")?,
+            model: row.try_get("model")?,
+            planner_used_fallback: row.try_get("planner_used_fallback")?,
+            output_used_fallback: row.try_get("output_used_fallback")?,
+            planner_ms: ms("planner_ms")?,
+            lane_ms: ms("lane_ms")?,
+            enclave_rpc_ms: ms("enclave_rpc_ms")?.unwrap_or_default(),
+        },
+        created_at: row.try_get("created_at")?,
+    })
+}
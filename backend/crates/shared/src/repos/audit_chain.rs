@@ -0,0 +1,397 @@
+use std::fmt::Write as _;
+
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use sqlx::{Postgres, Row, Transaction};
+use uuid::Uuid;
+
+use crate::models::{AuditChainBreak, AuditChainBreakReason, AuditChainVerification};
+
+use super::{Store, StoreError};
+
+const AUDIT_CHAIN_DOMAIN: &[u8] = b"alfred.audit_chain.v1";
+const AUDIT_CHAIN_GENESIS_HASH: [u8; 32] = [0; 32];
+const AUDIT_CHAIN_VERIFY_BATCH_SIZE: i64 = 1000;
+
+/// Row fields covered by an audit chain entry hash.
+pub(super) struct AuditChainEntry<'a> {
+    pub(super) chain_seq: i64,
+    pub(super) id: Uuid,
+    pub(super) user_id: Uuid,
+    pub(super) created_at: DateTime<Utc>,
+    pub(super) event_type: &'a str,
+    pub(super) connector: Option<&'a str>,
+    pub(super) result: &'a str,
+    pub(super) metadata: &'a Value,
+    pub(super) typed_metadata: Option<&'a Value>,
+}
+
+pub(super) struct AuditChainLink {
+    pub(super) chain_seq: i64,
+    pub(super) prev_hash: Option<Vec<u8>>,
+}
+
+/// Locks the user's chain tip for the rest of `tx` and returns the position of the next entry.
+pub(super) async fn lock_audit_chain_head(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: Uuid,
+) -> Result<AuditChainLink, StoreError> {
+    sqlx::query(
+        "INSERT INTO audit_chain_heads (user_id)
+         VALUES ($1)
+         ON CONFLICT (user_id) DO NOTHING",
+    )
+    .bind(user_id)
+    .execute(&mut **tx)
+    .await?;
+
+    let row = sqlx::query(
+        "SELECT last_seq, last_hash
+         FROM audit_chain_heads
+         WHERE user_id = $1
+         FOR UPDATE",
+    )
+    .bind(user_id)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    let last_seq: i64 = row.try_get("last_seq")?;
+    Ok(AuditChainLink {
+        chain_seq: last_seq + 1,
+        prev_hash: row.try_get("last_hash")?,
+    })
+}
+
+pub(super) async fn advance_audit_chain_head(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: Uuid,
+    chain_seq: i64,
+    entry_hash: &[u8],
+) -> Result<(), StoreError> {
+    sqlx::query(
+        "UPDATE audit_chain_heads
+         SET last_seq = $2,
+             last_hash = $3,
+             updated_at = NOW()
+         WHERE user_id = $1",
+    )
+    .bind(user_id)
+    .bind(chain_seq)
+    .bind(entry_hash)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Computes `SHA-256(prev_hash || canonical fields)`. Every field is length-prefixed and
+/// metadata is serialized with sorted keys, so the hash survives a JSONB round trip.
+pub(super) fn audit_entry_hash(prev_hash: Option<&[u8]>, entry: &AuditChainEntry<'_>) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(AUDIT_CHAIN_DOMAIN);
+    hasher.update(prev_hash.unwrap_or(&AUDIT_CHAIN_GENESIS_HASH));
+    hasher.update(entry.chain_seq.to_be_bytes());
+    hasher.update(entry.id.as_bytes());
+    hasher.update(entry.user_id.as_bytes());
+    hasher.update(entry.created_at.timestamp_micros().to_be_bytes());
+    update_field(&mut hasher, entry.event_type.as_bytes());
+    match entry.connector {
+        Some(connector) => {
+            hasher.update([1]);
+            update_field(&mut hasher, connector.as_bytes());
+        }
+        None => hasher.update([0]),
+    }
+    update_field(&mut hasher, entry.result.as_bytes());
+
+    let mut metadata = String::new();
+    write_canonical_json(&mut metadata, entry.metadata);
+    update_field(&mut hasher, metadata.as_bytes());
+    // Rows chained before typed metadata existed hash nothing here, so they still verify.
+    if let Some(typed_metadata) = entry.typed_metadata {
+        let mut typed = String::new();
+        write_canonical_json(&mut typed, typed_metadata);
+        hasher.update([1]);
+        update_field(&mut hasher, typed.as_bytes());
+    }
+
+    hasher.finalize().into()
+}
+
+/// Postgres stores microseconds, so chained timestamps are truncated before hashing.
+pub(super) fn audit_chain_timestamp(now: DateTime<Utc>) -> DateTime<Utc> {
+    DateTime::from_timestamp_micros(now.timestamp_micros()).unwrap_or(now)
+}
+
+fn update_field(hasher: &mut Sha256, bytes: &[u8]) {
+    hasher.update((bytes.len() as u64).to_be_bytes());
+    hasher.update(bytes);
+}
+
+fn write_canonical_json(out: &mut String, value: &Value) {
+    match value {
+        Value::Object(map) => {
+            let mut entries = map.iter().collect::<Vec<_>>();
+            entries.sort_by_key(|(key, _)| *key);
+            out.push('{');
+            for (index, (key, value)) in entries.into_iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical_json(out, value);
+            }
+            out.push('}');
+        }
+        Value::Array(items) => {
+            out.push('[');
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                write_canonical_json(out, item);
+            }
+            out.push(']');
+        }
+        scalar => out.push_str(&scalar.to_string()),
+    }
+}
+
+pub(super) fn hex_encode(bytes: &[u8]) -> String {
+    let mut hex = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        let _ = write!(hex, "{byte:02x}");
+    }
+    hex
+}
+
+impl Store {
+    /// Walks the user's chained audit rows in sequence order and reports the first link that
+    /// does not verify. Retention tombstones bridge purged rows and the oldest surviving link
+    /// anchors the walk, so retention is tolerated while edits, deletions, and truncated tails
+    /// are not.
+    pub async fn verify_audit_chain(
+        &self,
+        user_id: Uuid,
+    ) -> Result<AuditChainVerification, StoreError> {
+        let unchained_events: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*)
+             FROM audit_events
+             WHERE user_id = $1
+               AND chain_seq IS NULL",
+        )
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let mut verification = AuditChainVerification {
+            user_id: user_id.to_string(),
+            valid: true,
+            verified_events: 0,
+            purged_events: 0,
+            unchained_events: unchained_events.max(0) as u64,
+            head_seq: 0,
+            head_hash: None,
+            first_break: None,
+        };
+
+        let mut last: Option<(i64, Vec<u8>)> = None;
+        'batches: loop {
+            let after_seq = last.as_ref().map_or(0, |(seq, _)| *seq);
+            let rows = sqlx::query(
+                "SELECT id, chain_seq, prev_hash, entry_hash, created_at, event_type, connector,
+                        result, redacted_metadata, typed_metadata, FALSE AS purged
+                 FROM audit_events
+                 WHERE user_id = $1
+                   AND chain_seq > $2
+                 UNION ALL
+                 SELECT NULL::uuid, chain_seq, prev_hash, entry_hash, NULL::timestamptz,
+                        NULL::text, NULL::text, NULL::text, NULL::jsonb, NULL::jsonb, TRUE
+                 FROM audit_chain_tombstones
+                 WHERE user_id = $1
+                   AND chain_seq > $2
+                 ORDER BY chain_seq ASC
+                 LIMIT $3",
+            )
+            .bind(user_id)
+            .bind(after_seq)
+            .bind(AUDIT_CHAIN_VERIFY_BATCH_SIZE)
+            .fetch_all(&self.pool)
+            .await?;
+            let batch_len = rows.len();
+
+            for row in rows {
+                let chain_seq: i64 = row.try_get("chain_seq")?;
+                let prev_hash: Option<Vec<u8>> = row.try_get("prev_hash")?;
+                let entry_hash: Option<Vec<u8>> = row.try_get("entry_hash")?;
+                let purged: bool = row.try_get("purged")?;
+
+                let link_break = match &last {
+                    Some((last_seq, _)) if chain_seq != last_seq + 1 => {
+                        Some(AuditChainBreakReason::SequenceGap)
+                    }
+                    Some((_, last_hash)) if prev_hash.as_deref() != Some(last_hash.as_slice()) => {
+                        Some(AuditChainBreakReason::PrevHashMismatch)
+                    }
+                    None if chain_seq == 1 && prev_hash.is_some() => {
+                        Some(AuditChainBreakReason::PrevHashMismatch)
+                    }
+                    _ => None,
+                };
+                // A tombstone only carries hashes, so it proves the link but not the content.
+                if purged {
+                    if let Some(reason) = link_break {
+                        verification.valid = false;
+                        verification.first_break = Some(AuditChainBreak {
+                            chain_seq,
+                            event_id: None,
+                            reason,
+                        });
+                        break 'batches;
+                    }
+                    verification.purged_events += 1;
+                    last = entry_hash.map(|entry_hash| (chain_seq, entry_hash));
+                    continue;
+                }
+
+                let id: Uuid = row.try_get("id")?;
+                let created_at: DateTime<Utc> = row.try_get("created_at")?;
+                let event_type: String = row.try_get("event_type")?;
+                let connector: Option<String> = row.try_get("connector")?;
+                let result: String = row.try_get("result")?;
+                let metadata: Value = row.try_get("redacted_metadata")?;
+                let typed_metadata: Option<Value> = row.try_get("typed_metadata")?;
+                let expected_hash = audit_entry_hash(
+                    prev_hash.as_deref(),
+                    &AuditChainEntry {
+                        chain_seq,
+                        id,
+                        user_id,
+                        created_at,
+                        event_type: &event_type,
+                        connector: connector.as_deref(),
+                        result: &result,
+                        metadata: &metadata,
+                        typed_metadata: typed_metadata.as_ref(),
+                    },
+                );
+                let reason = link_break.or_else(|| {
+                    (entry_hash.as_deref() != Some(expected_hash.as_slice()))
+                        .then_some(AuditChainBreakReason::HashMismatch)
+                });
+
+                if let Some(reason) = reason {
+                    verification.valid = false;
+                    verification.first_break = Some(AuditChainBreak {
+                        chain_seq,
+                        event_id: Some(id.to_string()),
+                        reason,
+                    });
+                    break 'batches;
+                }
+
+                verification.verified_events += 1;
+                last = Some((chain_seq, expected_hash.to_vec()));
+            }
+
+            if batch_len < AUDIT_CHAIN_VERIFY_BATCH_SIZE as usize {
+                break;
+            }
+        }
+
+        let head = sqlx::query(
+            "SELECT last_seq, last_hash
+             FROM audit_chain_heads
+             WHERE user_id = $1",
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        let (head_seq, head_hash) = match head {
+            Some(row) => (
+                row.try_get::<i64, _>("last_seq")?,
+                row.try_get::<Option<Vec<u8>>, _>("last_hash")?,
+            ),
+            None => (0, None),
+        };
+        verification.head_seq = head_seq;
+        verification.head_hash = head_hash.as_deref().map(hex_encode);
+
+        if verification.valid {
+            let tail_matches = match &last {
+                Some((last_seq, last_hash)) => {
+                    *last_seq == head_seq && head_hash.as_deref() == Some(last_hash.as_slice())
+                }
+                None => head_seq == 0,
+            };
+            if !tail_matches {
+                verification.valid = false;
+                verification.first_break = Some(AuditChainBreak {
+                    chain_seq: head_seq,
+                    event_id: None,
+                    reason: AuditChainBreakReason::HeadMismatch,
+                });
+            }
+        }
+
+        Ok(verification)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use super::{AuditChainEntry, audit_chain_timestamp, audit_entry_hash};
+
+    fn entry_hash(prev_hash: Option<&[u8]>, metadata: &serde_json::Value) -> [u8; 32] {
+        audit_entry_hash(
+            prev_hash,
+            &AuditChainEntry {
+                chain_seq: 3,
+                id: Uuid::from_u128(1),
+                user_id: Uuid::from_u128(2),
+                created_at: Utc.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap(),
+                event_type: "CONNECTOR_REVOKED",
+                connector: Some("google"),
+                result: "SUCCESS",
+                metadata,
+                typed_metadata: None,
+            },
+        )
+    }
+
+    #[test]
+    fn entry_hash_is_independent_of_metadata_key_order() {
+        let ordered = serde_json::from_str(r#"{"a":"1","b":"2"}"#).unwrap();
+        let reversed = serde_json::from_str(r#"{"b":"2","a":"1"}"#).unwrap();
+
+        assert_eq!(entry_hash(None, &ordered), entry_hash(None, &reversed));
+    }
+
+    #[test]
+    fn entry_hash_binds_previous_hash_and_fields() {
+        let metadata = json!({"reason": "user_requested"});
+        let base = entry_hash(None, &metadata);
+
+        assert_ne!(base, entry_hash(Some(&[7; 32]), &metadata));
+        assert_ne!(
+            base,
+            entry_hash(None, &json!({"reason": "user_requested "}))
+        );
+    }
+
+    #[test]
+    fn chain_timestamp_truncates_to_microseconds() {
+        let now = Utc.timestamp_nanos(1_700_000_000_123_456_789);
+
+        assert_eq!(
+            audit_chain_timestamp(now).timestamp_nanos_opt(),
+            Some(1_700_000_000_123_456_000)
+        );
+    }
+}
@@ -0,0 +1,130 @@
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+use uuid::Uuid;
+
+use crate::audit_retention::AuditRetentionPolicy;
+
+use super::{Store, StoreError};
+
+/// Per-user outcome of one audit retention purge batch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditPurgeSummary {
+    pub user_id: Uuid,
+    pub purged_count: u64,
+    pub oldest_created_at: DateTime<Utc>,
+    pub newest_created_at: DateTime<Utc>,
+}
+
+impl Store {
+    /// Deletes up to `limit` audit events past their retention cutoff, oldest first, and
+    /// returns per-user counts. Purged chained rows are replaced by hash-only tombstones so
+    /// the user's audit chain still verifies across the gap.
+    pub async fn purge_expired_audit_events_batch(
+        &self,
+        policy: &AuditRetentionPolicy,
+        now: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<AuditPurgeSummary>, StoreError> {
+        let (override_event_types, override_cutoffs): (Vec<String>, Vec<DateTime<Utc>>) =
+            policy.override_cutoffs(now).into_iter().unzip();
+        let default_cutoff = policy.default_cutoff(now);
+        let latest_cutoff = override_cutoffs
+            .iter()
+            .copied()
+            .chain(std::iter::once(default_cutoff))
+            .max()
+            .unwrap_or(default_cutoff);
+
+        let mut tx = self.pool.begin().await?;
+        let rows = sqlx::query(
+            "WITH policy AS (
+               SELECT *
+               FROM UNNEST($1::text[], $2::timestamptz[]) AS policy(event_type, cutoff)
+             ),
+             expired AS (
+               SELECT a.id
+               FROM audit_events a
+               LEFT JOIN policy p ON p.event_type = a.event_type
+               WHERE a.created_at < $4
+                 AND a.created_at < COALESCE(p.cutoff, $3)
+               ORDER BY a.created_at ASC
+               LIMIT $5
+               FOR UPDATE OF a SKIP LOCKED
+             ),
+             deleted AS (
+               DELETE FROM audit_events a
+               USING expired e
+               WHERE a.id = e.id
+               RETURNING a.user_id, a.created_at, a.chain_seq, a.prev_hash, a.entry_hash
+             ),
+             tombstoned AS (
+               INSERT INTO audit_chain_tombstones (user_id, chain_seq, prev_hash, entry_hash, purged_at)
+               SELECT user_id, chain_seq, prev_hash, entry_hash, $6
+               FROM deleted
+               WHERE chain_seq IS NOT NULL
+                 AND entry_hash IS NOT NULL
+             )
+             SELECT user_id,
+                    COUNT(*) AS purged_count,
+                    MIN(created_at) AS oldest_created_at,
+                    MAX(created_at) AS newest_created_at
+             FROM deleted
+             GROUP BY user_id
+             ORDER BY user_id",
+        )
+        .bind(&override_event_types)
+        .bind(&override_cutoffs)
+        .bind(default_cutoff)
+        .bind(latest_cutoff)
+        .bind(limit)
+        .bind(now)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let summaries = rows
+            .into_iter()
+            .map(|row| {
+                let purged_count: i64 = row.try_get("purged_count")?;
+                Ok(AuditPurgeSummary {
+                    user_id: row.try_get("user_id")?,
+                    purged_count: purged_count.max(0) as u64,
+                    oldest_created_at: row.try_get("oldest_created_at")?,
+                    newest_created_at: row.try_get("newest_created_at")?,
+                })
+            })
+            .collect::<Result<Vec<_>, StoreError>>()?;
+
+        if !summaries.is_empty() {
+            let user_ids = summaries
+                .iter()
+                .map(|summary| summary.user_id)
+                .collect::<Vec<Uuid>>();
+            // Verification anchors on a user's oldest surviving link, so tombstones before it
+            // carry no information. The newest tombstone is kept when no chained row survives
+            // so the chain head can still be checked.
+            sqlx::query(
+                "DELETE FROM audit_chain_tombstones t
+                 WHERE t.user_id = ANY($1)
+                   AND t.chain_seq < COALESCE(
+                     (
+                       SELECT MIN(a.chain_seq)
+                       FROM audit_events a
+                       WHERE a.user_id = t.user_id
+                         AND a.chain_seq IS NOT NULL
+                     ),
+                     (
+                       SELECT MAX(m.chain_seq)
+                       FROM audit_chain_tombstones m
+                       WHERE m.user_id = t.user_id
+                     )
+                   )",
+            )
+            .bind(&user_ids)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(summaries)
+    }
+}
@@ -1,8 +1,21 @@
 use chrono::{DateTime, Utc};
+use sqlx::Row;
 use uuid::Uuid;
 
 use super::{Store, StoreError};
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OAuthScopeUpgrade {
+    pub connector_id: Uuid,
+    pub scopes: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OAuthStateRecord {
+    pub redirect_uri: String,
+    pub scope_upgrade: Option<OAuthScopeUpgrade>,
+}
+
 impl Store {
     pub async fn store_oauth_state(
         &self,
@@ -10,23 +23,67 @@ impl Store {
         state_hash: &[u8],
         redirect_uri: &str,
         expires_at: DateTime<Utc>,
+    ) -> Result<(), StoreError> {
+        self.insert_oauth_state(user_id, state_hash, redirect_uri, expires_at, None)
+            .await
+    }
+
+    /// Stores the state of an incremental consent flow that adds `scopes` to an existing
+    /// connector. The regular Google callback consumes it.
+    pub async fn store_oauth_scope_upgrade_state(
+        &self,
+        user_id: Uuid,
+        state_hash: &[u8],
+        redirect_uri: &str,
+        expires_at: DateTime<Utc>,
+        connector_id: Uuid,
+        scopes: &[String],
+    ) -> Result<(), StoreError> {
+        self.insert_oauth_state(
+            user_id,
+            state_hash,
+            redirect_uri,
+            expires_at,
+            Some((connector_id, scopes)),
+        )
+        .await
+    }
+
+    async fn insert_oauth_state(
+        &self,
+        user_id: Uuid,
+        state_hash: &[u8],
+        redirect_uri: &str,
+        expires_at: DateTime<Utc>,
+        upgrade: Option<(Uuid, &[String])>,
     ) -> Result<(), StoreError> {
         self.ensure_user(user_id).await?;
 
         sqlx::query(
-            "INSERT INTO oauth_states (user_id, state_hash, redirect_uri, expires_at)
-             VALUES ($1, $2, $3, $4)
+            "INSERT INTO oauth_states (
+                user_id,
+                state_hash,
+                redirect_uri,
+                expires_at,
+                upgrade_connector_id,
+                upgrade_scopes
+             )
+             VALUES ($1, $2, $3, $4, $5, $6)
              ON CONFLICT (state_hash)
              DO UPDATE SET
                user_id = EXCLUDED.user_id,
                redirect_uri = EXCLUDED.redirect_uri,
                expires_at = EXCLUDED.expires_at,
+               upgrade_connector_id = EXCLUDED.upgrade_connector_id,
+               upgrade_scopes = EXCLUDED.upgrade_scopes,
                consumed_at = NULL",
         )
         .bind(user_id)
         .bind(state_hash)
         .bind(redirect_uri)
         .bind(expires_at)
+        .bind(upgrade.map(|(connector_id, _)| connector_id))
+        .bind(upgrade.map(|(_, scopes)| scopes))
         .execute(&self.pool)
         .await?;
 
@@ -39,14 +96,28 @@ impl Store {
         state_hash: &[u8],
         now: DateTime<Utc>,
     ) -> Result<Option<String>, StoreError> {
-        let redirect_uri = sqlx::query_scalar(
+        Ok(self
+            .consume_oauth_state_record(user_id, state_hash, now)
+            .await?
+            .map(|record| record.redirect_uri))
+    }
+
+    /// Consumes a state like `consume_oauth_state`, also returning the scope upgrade it was
+    /// minted for, if any.
+    pub async fn consume_oauth_state_record(
+        &self,
+        user_id: Uuid,
+        state_hash: &[u8],
+        now: DateTime<Utc>,
+    ) -> Result<Option<OAuthStateRecord>, StoreError> {
+        let row = sqlx::query(
             "UPDATE oauth_states
              SET consumed_at = NOW()
              WHERE user_id = $1
                AND state_hash = $2
                AND consumed_at IS NULL
                AND expires_at > $3
-             RETURNING redirect_uri",
+             RETURNING redirect_uri, upgrade_connector_id, upgrade_scopes",
         )
         .bind(user_id)
         .bind(state_hash)
@@ -54,6 +125,53 @@ impl Store {
         .fetch_optional(&self.pool)
         .await?;
 
-        Ok(redirect_uri)
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let upgrade_connector_id: Option<Uuid> = row.try_get("upgrade_connector_id")?;
+        let upgrade_scopes: Option<Vec<String>> = row.try_get("upgrade_scopes")?;
+
+        Ok(Some(OAuthStateRecord {
+            redirect_uri: row.try_get("redirect_uri")?,
+            scope_upgrade: upgrade_connector_id.map(|connector_id| OAuthScopeUpgrade {
+                connector_id,
+                scopes: upgrade_scopes.unwrap_or_default(),
+            }),
+        }))
+    }
+
+    /// Deletes OAuth states that can no longer complete a callback: consumed ones and ones
+    /// past their expiry.
+    pub async fn purge_expired_oauth_states_batch(
+        &self,
+        now: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<u64, StoreError> {
+        if limit <= 0 {
+            return Err(StoreError::InvalidData(
+                "oauth state purge limit must be > 0".to_string(),
+            ));
+        }
+
+        let result = sqlx::query(
+            "WITH expired AS (
+                SELECT id
+                FROM oauth_states
+                WHERE expires_at <= $1
+                   OR consumed_at IS NOT NULL
+                ORDER BY expires_at ASC, id ASC
+                LIMIT $2
+                FOR UPDATE SKIP LOCKED
+             )
+             DELETE FROM oauth_states states
+             USING expired
+             WHERE states.id = expired.id",
+        )
+        .bind(now)
+        .bind(limit)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
     }
 }
@@ -4,15 +4,24 @@ use sqlx::Row;
 use uuid::Uuid;
 
 use crate::automation_schedule::{
-    AutomationScheduleSpec, interval_seconds_hint, validate_schedule_spec,
+    AutomationCondition, AutomationScheduleSpec, AutomationTemplate, interval_seconds_hint,
+    validate_schedule_spec,
 };
+use crate::pagination::{Page, PageKey, PageRequest};
 use crate::timezone::normalize_time_zone;
 
 use super::{
-    AutomationPromptMaterial, AutomationRuleRecord, AutomationRuleStatus, AutomationScheduleType,
-    ClaimedAutomationRule, Store, StoreError,
+    AutomationConditionKind, AutomationPromptMaterial, AutomationRuleRecord, AutomationRuleStatus,
+    AutomationScheduleType, ClaimedAutomationRule, Store, StoreError,
 };
 
+/// Optional generator and notification gate a rule is created with.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AutomationRuleOptions {
+    pub template: Option<AutomationTemplate>,
+    pub condition: Option<AutomationCondition>,
+}
+
 const MAX_AUTOMATION_TITLE_CHARS: usize = 120;
 
 impl Store {
@@ -20,15 +29,20 @@ impl Store {
         &self,
         user_id: Uuid,
         title: &str,
+        options: AutomationRuleOptions,
         schedule: &AutomationScheduleSpec,
         next_run_at: DateTime<Utc>,
-        prompt_ciphertext: &[u8],
-        prompt_sha256: &str,
+        prompt: &AutomationPromptMaterial,
     ) -> Result<AutomationRuleRecord, StoreError> {
         self.ensure_user(user_id).await?;
         let title = normalized_automation_title(title)?;
+        let AutomationRuleOptions {
+            template,
+            condition,
+        } = options;
+        validated_condition(condition)?;
         let schedule = normalized_schedule_spec(schedule)?;
-        let prompt_sha256 = normalized_prompt_sha256(prompt_sha256)?;
+        let prompt_sha256 = normalized_prompt_sha256(&prompt.prompt_sha256)?;
 
         let row = sqlx::query(
             "INSERT INTO automation_rules (
@@ -39,12 +53,18 @@ impl Store {
                 interval_seconds,
                 time_zone,
                 local_time_minutes,
-                anchor_day_of_week,
+                anchor_days_of_week,
                 anchor_day_of_month,
                 anchor_month,
+                cron_expression,
+                run_at,
                 next_run_at,
                 prompt_ciphertext,
-                prompt_sha256
+                prompt_sha256,
+                data_key_id,
+                template,
+                condition_kind,
+                condition_min_count
              ) VALUES (
                 $1,
                 $2,
@@ -56,24 +76,37 @@ impl Store {
                 $7,
                 $8,
                 $9,
+                $16,
+                $17,
                 $10,
                 pgp_sym_encrypt(encode($11, 'base64'), $12),
-                $13
+                $13,
+                $14,
+                $15,
+                $18,
+                $19
              )
              RETURNING
                 id,
                 user_id,
                 title,
                 status,
+                template,
+                condition_kind,
+                condition_min_count,
                 schedule_type,
                 local_time_minutes,
-                anchor_day_of_week,
+                anchor_days_of_week,
                 anchor_day_of_month,
                 anchor_month,
+                cron_expression,
+                run_at,
                 time_zone,
                 next_run_at,
                 last_run_at,
+                paused_until,
                 prompt_sha256,
+                version,
                 created_at,
                 updated_at",
         )
@@ -83,13 +116,19 @@ impl Store {
         .bind(interval_seconds_hint(schedule.schedule_type))
         .bind(schedule.time_zone.as_str())
         .bind(i32::from(schedule.local_time_minutes))
-        .bind(schedule.anchor_day_of_week.map(i16::from))
+        .bind(schedule.anchor_days_of_week.map(i16::from))
         .bind(schedule.anchor_day_of_month.map(i16::from))
         .bind(schedule.anchor_month.map(i16::from))
         .bind(next_run_at)
-        .bind(prompt_ciphertext)
+        .bind(&prompt.prompt_ciphertext)
         .bind(&self.data_encryption_key)
         .bind(prompt_sha256)
+        .bind(&self.data_encryption_key_id)
+        .bind(template.as_ref().map(AutomationTemplate::as_str))
+        .bind(schedule.cron_expression.as_deref())
+        .bind(schedule.run_at)
+        .bind(condition.map(|condition| condition.kind.as_str()))
+        .bind(condition.map(|condition| i16::try_from(condition.min_count).unwrap_or(i16::MAX)))
         .fetch_one(&self.pool)
         .await?;
 
@@ -107,15 +146,22 @@ impl Store {
                 user_id,
                 title,
                 status,
+                template,
+                condition_kind,
+                condition_min_count,
                 schedule_type,
                 local_time_minutes,
-                anchor_day_of_week,
+                anchor_days_of_week,
                 anchor_day_of_month,
                 anchor_month,
+                cron_expression,
+                run_at,
                 time_zone,
                 next_run_at,
                 last_run_at,
+                paused_until,
                 prompt_sha256,
+                version,
                 created_at,
                 updated_at
              FROM automation_rules
@@ -138,14 +184,18 @@ impl Store {
         let row = sqlx::query(
             "SELECT
                 prompt_sha256,
-                pgp_sym_decrypt(prompt_ciphertext, $3) AS prompt_encoded
+                pgp_sym_decrypt(
+                  prompt_ciphertext,
+                  alfred_data_key(data_key_id, $3, $4)
+                ) AS prompt_encoded
              FROM automation_rules
              WHERE user_id = $1
                AND id = $2",
         )
         .bind(user_id)
         .bind(rule_id)
-        .bind(&self.data_encryption_key)
+        .bind(&self.data_encryption_key_ids)
+        .bind(&self.data_encryption_keys)
         .fetch_optional(&self.pool)
         .await?;
 
@@ -153,12 +203,15 @@ impl Store {
             .transpose()
     }
 
+    /// Lists the user's rules newest first. Archived rules are only included when
+    /// `include_archived` is set.
     pub async fn list_automation_rules(
         &self,
         user_id: Uuid,
-        limit: i64,
-    ) -> Result<Vec<AutomationRuleRecord>, StoreError> {
-        if limit <= 0 {
+        include_archived: bool,
+        page: PageRequest,
+    ) -> Result<Page<AutomationRuleRecord>, StoreError> {
+        if page.limit == 0 {
             return Err(StoreError::InvalidData(
                 "automation list limit must be > 0".to_string(),
             ));
@@ -170,30 +223,85 @@ impl Store {
                 user_id,
                 title,
                 status,
+                template,
+                condition_kind,
+                condition_min_count,
                 schedule_type,
                 local_time_minutes,
-                anchor_day_of_week,
+                anchor_days_of_week,
                 anchor_day_of_month,
                 anchor_month,
+                cron_expression,
+                run_at,
                 time_zone,
                 next_run_at,
                 last_run_at,
+                paused_until,
                 prompt_sha256,
+                version,
                 created_at,
                 updated_at
              FROM automation_rules
              WHERE user_id = $1
+               AND ($5 OR status <> 'ARCHIVED')
+               AND (
+                 $2::timestamptz IS NULL
+                 OR created_at < $2
+                 OR (created_at = $2 AND id < $3)
+               )
              ORDER BY created_at DESC, id DESC
-             LIMIT $2",
+             LIMIT $4",
         )
         .bind(user_id)
-        .bind(limit)
+        .bind(page.after_at())
+        .bind(page.after_id())
+        .bind(page.fetch_limit())
+        .bind(include_archived)
         .fetch_all(&self.pool)
         .await?;
 
-        rows.into_iter()
-            .map(|row| automation_rule_from_row(&row))
-            .collect()
+        let keyed_rows = rows
+            .iter()
+            .map(|row| {
+                let rule = automation_rule_from_row(row)?;
+                Ok((
+                    PageKey {
+                        pinned: false,
+                        at: rule.created_at,
+                        id: rule.id,
+                    },
+                    rule,
+                ))
+            })
+            .collect::<Result<Vec<_>, StoreError>>()?;
+
+        Ok(Page::from_keyed_rows(keyed_rows, page))
+    }
+
+    /// Bumps the rule's version ahead of a user edit. With `expected_version` set the bump only
+    /// happens if the stored version still matches. Returns the new version, or `None` when the
+    /// rule is missing or the version has moved on.
+    pub async fn claim_automation_rule_version(
+        &self,
+        user_id: Uuid,
+        rule_id: Uuid,
+        expected_version: Option<i64>,
+    ) -> Result<Option<i64>, StoreError> {
+        let version = sqlx::query_scalar(
+            "UPDATE automation_rules
+             SET version = version + 1
+             WHERE user_id = $1
+               AND id = $2
+               AND ($3::bigint IS NULL OR version = $3)
+             RETURNING version",
+        )
+        .bind(user_id)
+        .bind(rule_id)
+        .bind(expected_version)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(version)
     }
 
     pub async fn update_automation_rule_title(
@@ -215,15 +323,22 @@ impl Store {
                 user_id,
                 title,
                 status,
+                template,
+                condition_kind,
+                condition_min_count,
                 schedule_type,
                 local_time_minutes,
-                anchor_day_of_week,
+                anchor_days_of_week,
                 anchor_day_of_month,
                 anchor_month,
+                cron_expression,
+                run_at,
                 time_zone,
                 next_run_at,
                 last_run_at,
+                paused_until,
                 prompt_sha256,
+                version,
                 created_at,
                 updated_at",
         )
@@ -236,6 +351,56 @@ impl Store {
         row.map(|row| automation_rule_from_row(&row)).transpose()
     }
 
+    /// Sets or clears the condition scheduled runs must meet before they notify.
+    pub async fn update_automation_rule_condition(
+        &self,
+        user_id: Uuid,
+        rule_id: Uuid,
+        condition: Option<AutomationCondition>,
+    ) -> Result<Option<AutomationRuleRecord>, StoreError> {
+        validated_condition(condition)?;
+
+        let row = sqlx::query(
+            "UPDATE automation_rules
+             SET condition_kind = $3,
+                 condition_min_count = $4,
+                 updated_at = NOW()
+             WHERE user_id = $1
+               AND id = $2
+             RETURNING
+                id,
+                user_id,
+                title,
+                status,
+                template,
+                condition_kind,
+                condition_min_count,
+                schedule_type,
+                local_time_minutes,
+                anchor_days_of_week,
+                anchor_day_of_month,
+                anchor_month,
+                cron_expression,
+                run_at,
+                time_zone,
+                next_run_at,
+                last_run_at,
+                paused_until,
+                prompt_sha256,
+                version,
+                created_at,
+                updated_at",
+        )
+        .bind(user_id)
+        .bind(rule_id)
+        .bind(condition.map(|condition| condition.kind.as_str()))
+        .bind(condition.map(|condition| i16::try_from(condition.min_count).unwrap_or(i16::MAX)))
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|row| automation_rule_from_row(&row)).transpose()
+    }
+
     pub async fn update_automation_rule_schedule(
         &self,
         user_id: Uuid,
@@ -251,9 +416,11 @@ impl Store {
                  interval_seconds = $4,
                  time_zone = $5,
                  local_time_minutes = $6,
-                 anchor_day_of_week = $7,
+                 anchor_days_of_week = $7,
                  anchor_day_of_month = $8,
                  anchor_month = $9,
+                 cron_expression = $11,
+                 run_at = $12,
                  next_run_at = $10,
                  updated_at = NOW()
              WHERE user_id = $1
@@ -263,15 +430,22 @@ impl Store {
                 user_id,
                 title,
                 status,
+                template,
+                condition_kind,
+                condition_min_count,
                 schedule_type,
                 local_time_minutes,
-                anchor_day_of_week,
+                anchor_days_of_week,
                 anchor_day_of_month,
                 anchor_month,
+                cron_expression,
+                run_at,
                 time_zone,
                 next_run_at,
                 last_run_at,
+                paused_until,
                 prompt_sha256,
+                version,
                 created_at,
                 updated_at",
         )
@@ -281,10 +455,12 @@ impl Store {
         .bind(interval_seconds_hint(schedule.schedule_type))
         .bind(schedule.time_zone.as_str())
         .bind(i32::from(schedule.local_time_minutes))
-        .bind(schedule.anchor_day_of_week.map(i16::from))
+        .bind(schedule.anchor_days_of_week.map(i16::from))
         .bind(schedule.anchor_day_of_month.map(i16::from))
         .bind(schedule.anchor_month.map(i16::from))
         .bind(next_run_at)
+        .bind(schedule.cron_expression.as_deref())
+        .bind(schedule.run_at)
         .fetch_optional(&self.pool)
         .await?;
 
@@ -304,6 +480,7 @@ impl Store {
             "UPDATE automation_rules
              SET prompt_ciphertext = pgp_sym_encrypt(encode($3, 'base64'), $5),
                  prompt_sha256 = $4,
+                 data_key_id = $6,
                  updated_at = NOW()
              WHERE user_id = $1
                AND id = $2
@@ -312,15 +489,22 @@ impl Store {
                 user_id,
                 title,
                 status,
+                template,
+                condition_kind,
+                condition_min_count,
                 schedule_type,
                 local_time_minutes,
-                anchor_day_of_week,
+                anchor_days_of_week,
                 anchor_day_of_month,
                 anchor_month,
+                cron_expression,
+                run_at,
                 time_zone,
                 next_run_at,
                 last_run_at,
+                paused_until,
                 prompt_sha256,
+                version,
                 created_at,
                 updated_at",
         )
@@ -329,59 +513,13 @@ impl Store {
         .bind(prompt_ciphertext)
         .bind(prompt_sha256)
         .bind(&self.data_encryption_key)
+        .bind(&self.data_encryption_key_id)
         .fetch_optional(&self.pool)
         .await?;
 
         row.map(|row| automation_rule_from_row(&row)).transpose()
     }
 
-    pub async fn pause_automation_rule(
-        &self,
-        user_id: Uuid,
-        rule_id: Uuid,
-    ) -> Result<bool, StoreError> {
-        let result = sqlx::query(
-            "UPDATE automation_rules
-             SET status = 'PAUSED',
-                 lease_owner = NULL,
-                 lease_expires_at = NULL,
-                 updated_at = NOW()
-             WHERE user_id = $1
-               AND id = $2",
-        )
-        .bind(user_id)
-        .bind(rule_id)
-        .execute(&self.pool)
-        .await?;
-
-        Ok(result.rows_affected() > 0)
-    }
-
-    pub async fn resume_automation_rule(
-        &self,
-        user_id: Uuid,
-        rule_id: Uuid,
-        next_run_at: DateTime<Utc>,
-    ) -> Result<bool, StoreError> {
-        let result = sqlx::query(
-            "UPDATE automation_rules
-             SET status = 'ACTIVE',
-                 next_run_at = $3,
-                 lease_owner = NULL,
-                 lease_expires_at = NULL,
-                 updated_at = NOW()
-             WHERE user_id = $1
-               AND id = $2",
-        )
-        .bind(user_id)
-        .bind(rule_id)
-        .bind(next_run_at)
-        .execute(&self.pool)
-        .await?;
-
-        Ok(result.rows_affected() > 0)
-    }
-
     pub async fn delete_automation_rule(
         &self,
         user_id: Uuid,
@@ -452,24 +590,37 @@ impl Store {
                 RETURNING
                     r.id,
                     r.user_id,
+                    r.template,
+                    r.condition_kind,
+                    r.condition_min_count,
                     r.schedule_type,
                     r.local_time_minutes,
-                    r.anchor_day_of_week,
+                    r.anchor_days_of_week,
                     r.anchor_day_of_month,
                     r.anchor_month,
+                    r.cron_expression,
+                    r.run_at,
                     r.time_zone,
                     r.next_run_at,
                     r.prompt_sha256,
-                    pgp_sym_decrypt(r.prompt_ciphertext, $5) AS prompt_encoded
+                    pgp_sym_decrypt(
+                      r.prompt_ciphertext,
+                      alfred_data_key(r.data_key_id, $5, $6)
+                    ) AS prompt_encoded
              )
              SELECT
                 id,
                 user_id,
+                template,
+                condition_kind,
+                condition_min_count,
                 schedule_type,
                 local_time_minutes,
-                anchor_day_of_week,
+                anchor_days_of_week,
                 anchor_day_of_month,
                 anchor_month,
+                cron_expression,
+                run_at,
                 time_zone,
                 next_run_at,
                 prompt_sha256,
@@ -481,7 +632,8 @@ impl Store {
         .bind(max_rules)
         .bind(worker_id)
         .bind(lease_until)
-        .bind(&self.data_encryption_key)
+        .bind(&self.data_encryption_key_ids)
+        .bind(&self.data_encryption_keys)
         .fetch_all(&self.pool)
         .await?;
 
@@ -491,25 +643,35 @@ impl Store {
     }
 }
 
-fn automation_rule_from_row(
+pub(super) fn automation_rule_from_row(
     row: &sqlx::postgres::PgRow,
 ) -> Result<AutomationRuleRecord, StoreError> {
     let status: String = row.try_get("status")?;
+    let template: Option<String> = row.try_get("template")?;
     let schedule_type: String = row.try_get("schedule_type")?;
     Ok(AutomationRuleRecord {
         id: row.try_get("id")?,
         user_id: row.try_get("user_id")?,
         title: row.try_get("title")?,
         status: AutomationRuleStatus::from_db(&status)?,
+        template: template
+            .as_deref()
+            .map(AutomationTemplate::from_db)
+            .transpose()?,
+        condition: automation_condition_from_row(row)?,
         schedule_type: AutomationScheduleType::from_db(&schedule_type)?,
         local_time_minutes: row.try_get("local_time_minutes")?,
-        anchor_day_of_week: row.try_get("anchor_day_of_week")?,
+        anchor_days_of_week: row.try_get("anchor_days_of_week")?,
         anchor_day_of_month: row.try_get("anchor_day_of_month")?,
         anchor_month: row.try_get("anchor_month")?,
+        cron_expression: row.try_get("cron_expression")?,
+        run_at: row.try_get("run_at")?,
         time_zone: row.try_get("time_zone")?,
         next_run_at: row.try_get("next_run_at")?,
         last_run_at: row.try_get("last_run_at")?,
+        paused_until: row.try_get("paused_until")?,
         prompt_sha256: row.try_get("prompt_sha256")?,
+        version: row.try_get("version")?,
         created_at: row.try_get("created_at")?,
         updated_at: row.try_get("updated_at")?,
     })
@@ -520,16 +682,24 @@ fn claimed_automation_rule_from_row(
 ) -> Result<ClaimedAutomationRule, StoreError> {
     let prompt_encoded: String = row.try_get("prompt_encoded")?;
     let prompt_ciphertext = decode_base64_payload(prompt_encoded.as_str())?;
+    let template: Option<String> = row.try_get("template")?;
     let schedule_type: String = row.try_get("schedule_type")?;
 
     Ok(ClaimedAutomationRule {
         id: row.try_get("id")?,
         user_id: row.try_get("user_id")?,
+        template: template
+            .as_deref()
+            .map(AutomationTemplate::from_db)
+            .transpose()?,
+        condition: automation_condition_from_row(&row)?,
         schedule_type: AutomationScheduleType::from_db(&schedule_type)?,
         local_time_minutes: row.try_get("local_time_minutes")?,
-        anchor_day_of_week: row.try_get("anchor_day_of_week")?,
+        anchor_days_of_week: row.try_get("anchor_days_of_week")?,
         anchor_day_of_month: row.try_get("anchor_day_of_month")?,
         anchor_month: row.try_get("anchor_month")?,
+        cron_expression: row.try_get("cron_expression")?,
+        run_at: row.try_get("run_at")?,
         time_zone: row.try_get("time_zone")?,
         next_run_at: row.try_get("next_run_at")?,
         prompt_ciphertext,
@@ -537,6 +707,23 @@ fn claimed_automation_rule_from_row(
     })
 }
 
+fn automation_condition_from_row(
+    row: &sqlx::postgres::PgRow,
+) -> Result<Option<AutomationCondition>, StoreError> {
+    let kind: Option<String> = row.try_get("condition_kind")?;
+    let min_count: Option<i16> = row.try_get("condition_min_count")?;
+    let (Some(kind), Some(min_count)) = (kind, min_count) else {
+        return Ok(None);
+    };
+
+    Ok(Some(AutomationCondition {
+        kind: AutomationConditionKind::from_db(&kind)?,
+        min_count: u16::try_from(min_count).map_err(|_| {
+            StoreError::InvalidData("automation condition min_count is negative".to_string())
+        })?,
+    }))
+}
+
 fn automation_prompt_material_from_row(
     row: &sqlx::postgres::PgRow,
 ) -> Result<AutomationPromptMaterial, StoreError> {
@@ -568,6 +755,13 @@ fn normalized_schedule_spec(
     Ok(normalized)
 }
 
+fn validated_condition(condition: Option<AutomationCondition>) -> Result<(), StoreError> {
+    match condition {
+        Some(condition) => condition.validate().map_err(StoreError::InvalidData),
+        None => Ok(()),
+    }
+}
+
 fn normalized_time_zone(value: &str) -> Result<String, StoreError> {
     normalize_time_zone(value).ok_or_else(|| {
         StoreError::InvalidData("time_zone is not a valid IANA timezone".to_string())
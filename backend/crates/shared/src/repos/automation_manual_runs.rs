@@ -0,0 +1,128 @@
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+use uuid::Uuid;
+
+use super::{Store, StoreError};
+
+/// A user-initiated "run now" request and the job it queued.
+#[derive(Debug, Clone)]
+pub struct AutomationManualRunRecord {
+    pub rule_id: Uuid,
+    pub request_id: String,
+    pub job_id: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Store {
+    pub async fn get_automation_manual_run(
+        &self,
+        user_id: Uuid,
+        request_id: &str,
+    ) -> Result<Option<AutomationManualRunRecord>, StoreError> {
+        let row = sqlx::query(
+            "SELECT rule_id, request_id, job_id, created_at
+             FROM automation_manual_runs
+             WHERE user_id = $1
+               AND request_id = $2",
+        )
+        .bind(user_id)
+        .bind(request_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|row| {
+            Ok(AutomationManualRunRecord {
+                rule_id: row.try_get("rule_id")?,
+                request_id: row.try_get("request_id")?,
+                job_id: row.try_get("job_id")?,
+                created_at: row.try_get("created_at")?,
+            })
+        })
+        .transpose()
+    }
+
+    pub async fn count_automation_manual_runs_since(
+        &self,
+        user_id: Uuid,
+        since: DateTime<Utc>,
+    ) -> Result<i64, StoreError> {
+        let count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*)::bigint
+             FROM automation_manual_runs
+             WHERE user_id = $1
+               AND created_at >= $2",
+        )
+        .bind(user_id)
+        .bind(since)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(count)
+    }
+
+    /// Records a queued manual run. A concurrent retry with the same request id keeps the
+    /// first row; the queued job is shared through its idempotency key either way.
+    pub async fn record_automation_manual_run(
+        &self,
+        user_id: Uuid,
+        rule_id: Uuid,
+        request_id: &str,
+        job_id: Uuid,
+        now: DateTime<Utc>,
+    ) -> Result<(), StoreError> {
+        if request_id.trim().is_empty() {
+            return Err(StoreError::InvalidData(
+                "automation manual run request_id must not be empty".to_string(),
+            ));
+        }
+
+        sqlx::query(
+            "INSERT INTO automation_manual_runs (user_id, rule_id, request_id, job_id, created_at)
+             VALUES ($1, $2, $3, $4, $5)
+             ON CONFLICT (user_id, request_id) DO NOTHING",
+        )
+        .bind(user_id)
+        .bind(rule_id)
+        .bind(request_id)
+        .bind(job_id)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Deletes manual run records created before `created_before`. They only need to outlive
+    /// the daily cap window and client retries.
+    pub async fn purge_expired_automation_manual_runs_batch(
+        &self,
+        created_before: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<u64, StoreError> {
+        if limit <= 0 {
+            return Err(StoreError::InvalidData(
+                "automation manual run purge limit must be > 0".to_string(),
+            ));
+        }
+
+        let result = sqlx::query(
+            "WITH expired AS (
+                SELECT id
+                FROM automation_manual_runs
+                WHERE created_at < $1
+                ORDER BY created_at ASC, id ASC
+                LIMIT $2
+                FOR UPDATE SKIP LOCKED
+             )
+             DELETE FROM automation_manual_runs manual_runs
+             USING expired
+             WHERE manual_runs.id = expired.id",
+        )
+        .bind(created_before)
+        .bind(limit)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
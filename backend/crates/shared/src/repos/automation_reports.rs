@@ -0,0 +1,148 @@
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+use uuid::Uuid;
+
+use crate::automation_schedule::AutomationTemplate;
+use crate::models::AutomationReportEnvelope;
+use crate::pagination::{Page, PageKey, PageRequest};
+
+use super::{Store, StoreError};
+
+/// Long-form automation output (for example a weekly review) encrypted to one device and
+/// kept for in-app retrieval instead of being pushed.
+#[derive(Debug, Clone)]
+pub struct AutomationReportRecord {
+    pub id: Uuid,
+    pub rule_id: Uuid,
+    pub run_id: Uuid,
+    pub device_id: String,
+    pub template: AutomationTemplate,
+    pub envelope: AutomationReportEnvelope,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Store {
+    /// Stores one device-encrypted report for an automation run. Retried runs replace the
+    /// earlier envelope for the same device instead of adding a duplicate.
+    pub async fn store_automation_report(
+        &self,
+        user_id: Uuid,
+        rule_id: Uuid,
+        run_id: Uuid,
+        device_id: &str,
+        template: AutomationTemplate,
+        envelope: &AutomationReportEnvelope,
+    ) -> Result<AutomationReportRecord, StoreError> {
+        let envelope = serde_json::to_value(envelope).map_err(|err| {
+            StoreError::InvalidData(format!(
+                "failed to encode automation report envelope: {err}"
+            ))
+        })?;
+
+        let row = sqlx::query(
+            "INSERT INTO automation_reports (
+                user_id,
+                rule_id,
+                run_id,
+                device_identifier,
+                template,
+                envelope
+             )
+             SELECT $1, r.id, $3, $4, $5, $6
+             FROM automation_rules r
+             WHERE r.user_id = $1
+               AND r.id = $2
+             ON CONFLICT (run_id, device_identifier)
+             DO UPDATE SET
+                envelope = EXCLUDED.envelope,
+                created_at = NOW()
+             RETURNING id, rule_id, run_id, device_identifier, template, envelope, created_at",
+        )
+        .bind(user_id)
+        .bind(rule_id)
+        .bind(run_id)
+        .bind(device_id)
+        .bind(template.as_str())
+        .bind(envelope)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let row = row.ok_or_else(|| {
+            StoreError::InvalidData("automation report rule does not belong to user".to_string())
+        })?;
+        automation_report_from_row(&row)
+    }
+
+    pub async fn list_automation_reports(
+        &self,
+        user_id: Uuid,
+        device_id: &str,
+        page: PageRequest,
+    ) -> Result<Page<AutomationReportRecord>, StoreError> {
+        if page.limit == 0 {
+            return Err(StoreError::InvalidData(
+                "automation report list limit must be > 0".to_string(),
+            ));
+        }
+
+        let rows = sqlx::query(
+            "SELECT id, rule_id, run_id, device_identifier, template, envelope, created_at
+             FROM automation_reports
+             WHERE user_id = $1
+               AND device_identifier = $2
+               AND (
+                 $3::timestamptz IS NULL
+                 OR created_at < $3
+                 OR (created_at = $3 AND id < $4)
+               )
+             ORDER BY created_at DESC, id DESC
+             LIMIT $5",
+        )
+        .bind(user_id)
+        .bind(device_id)
+        .bind(page.after_at())
+        .bind(page.after_id())
+        .bind(page.fetch_limit())
+        .fetch_all(&self.pool)
+        .await?;
+
+        let keyed_rows = rows
+            .iter()
+            .map(|row| {
+                let report = automation_report_from_row(row)?;
+                Ok((
+                    PageKey {
+                        pinned: false,
+                        at: report.created_at,
+                        id: report.id,
+                    },
+                    report,
+                ))
+            })
+            .collect::<Result<Vec<_>, StoreError>>()?;
+
+        Ok(Page::from_keyed_rows(keyed_rows, page))
+    }
+}
+
+fn automation_report_from_row(
+    row: &sqlx::postgres::PgRow,
+) -> Result<AutomationReportRecord, StoreError> {
+    let template: String = row.try_get("template")?;
+    let envelope: serde_json::Value = row.try_get("envelope")?;
+    let envelope = serde_json::from_value(envelope).map_err(|err| {
+        StoreError::InvalidData(format!(
+            "invalid automation report envelope persisted: {err}"
+        ))
+    })?;
+
+    Ok(AutomationReportRecord {
+        id: row.try_get("id")?,
+        rule_id: row.try_get("rule_id")?,
+        run_id: row.try_get("run_id")?,
+        device_id: row.try_get("device_identifier")?,
+        template: AutomationTemplate::from_db(&template)?,
+        envelope,
+        created_at: row.try_get("created_at")?,
+    })
+}
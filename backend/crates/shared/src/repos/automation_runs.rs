@@ -4,13 +4,24 @@ use uuid::Uuid;
 
 use super::{AutomationRunRecord, AutomationRunState, Store, StoreError};
 
+/// Outcome of recording a dead-lettered automation run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AutomationRunFailure {
+    pub rule_id: Uuid,
+    pub consecutive_failures: i64,
+    pub paused_rule: bool,
+}
+
 impl Store {
+    /// Records the run for `scheduled_for` and releases the rule's lease. A `next_run_at` of
+    /// `None` means the rule has no further runs, so it moves to `COMPLETED` and keeps
+    /// `scheduled_for` as its last `next_run_at`.
     pub async fn materialize_automation_run(
         &self,
         rule_id: Uuid,
         worker_id: Uuid,
         scheduled_for: DateTime<Utc>,
-        next_run_at: DateTime<Utc>,
+        next_run_at: Option<DateTime<Utc>>,
         idempotency_key: &str,
     ) -> Result<Option<AutomationRunRecord>, StoreError> {
         if idempotency_key.trim().is_empty() {
@@ -63,6 +74,10 @@ impl Store {
                 job_id,
                 idempotency_key,
                 state,
+                failure_code,
+                paused_rule,
+                condition_met,
+                condition_observed_count,
                 created_at,
                 updated_at",
         )
@@ -80,9 +95,13 @@ impl Store {
                     ELSE last_run_at
                  END,
                  next_run_at = CASE
-                    WHEN next_run_at < $4 THEN $4
+                    WHEN next_run_at < $4::timestamptz THEN $4
                     ELSE next_run_at
                  END,
+                 status = CASE
+                    WHEN $4::timestamptz IS NULL THEN 'COMPLETED'
+                    ELSE status
+                 END,
                  lease_owner = NULL,
                  lease_expires_at = NULL,
                  updated_at = NOW()
@@ -106,6 +125,52 @@ impl Store {
         Ok(Some(automation_run_from_row(&run_row)?))
     }
 
+    /// Whether an earlier scheduled run of the rule is still queued, waiting on a retry, or
+    /// running.
+    pub async fn automation_rule_has_in_flight_run(
+        &self,
+        rule_id: Uuid,
+    ) -> Result<bool, StoreError> {
+        let in_flight = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS (
+                SELECT 1
+                FROM automation_runs r
+                JOIN jobs j ON j.id = r.job_id
+                WHERE r.rule_id = $1
+                  AND r.state = 'ENQUEUED'
+                  AND j.state IN ('PENDING', 'RUNNING')
+             )",
+        )
+        .bind(rule_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(in_flight)
+    }
+
+    /// Hands a claimed rule back without materializing a run, leaving `next_run_at` as is so
+    /// the run is picked up on a later tick.
+    pub async fn release_automation_rule_lease(
+        &self,
+        rule_id: Uuid,
+        worker_id: Uuid,
+    ) -> Result<bool, StoreError> {
+        let result = sqlx::query(
+            "UPDATE automation_rules
+             SET lease_owner = NULL,
+                 lease_expires_at = NULL,
+                 updated_at = NOW()
+             WHERE id = $1
+               AND lease_owner = $2",
+        )
+        .bind(rule_id)
+        .bind(worker_id.to_string())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
     pub async fn mark_automation_run_enqueued(
         &self,
         run_id: Uuid,
@@ -129,6 +194,32 @@ impl Store {
         Ok(result.rows_affected() > 0)
     }
 
+    /// Records what the enclave observed when it evaluated the rule's condition for this run.
+    pub async fn record_automation_run_condition(
+        &self,
+        run_id: Uuid,
+        user_id: Uuid,
+        condition_met: bool,
+        observed_count: i32,
+    ) -> Result<bool, StoreError> {
+        let result = sqlx::query(
+            "UPDATE automation_runs
+             SET condition_met = $3,
+                 condition_observed_count = $4,
+                 updated_at = NOW()
+             WHERE id = $1
+               AND user_id = $2",
+        )
+        .bind(run_id)
+        .bind(user_id)
+        .bind(condition_met)
+        .bind(observed_count)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
     pub async fn mark_automation_run_failed(
         &self,
         run_id: Uuid,
@@ -149,6 +240,93 @@ impl Store {
         Ok(result.rows_affected() > 0)
     }
 
+    /// Marks a dead-lettered run failed with `failure_code` and measures the rule's failure
+    /// streak: failed runs scheduled since its last run that did not fail or that paused it.
+    /// When the streak reaches `pause_threshold` an active rule is paused and this run is
+    /// flagged as the one that paused it, so a streak pauses its rule (and alerts the user)
+    /// once, and a resumed rule starts a fresh streak.
+    pub async fn record_automation_run_failure(
+        &self,
+        run_id: Uuid,
+        user_id: Uuid,
+        failure_code: &str,
+        pause_threshold: i64,
+    ) -> Result<Option<AutomationRunFailure>, StoreError> {
+        let mut tx = self.pool.begin().await?;
+        let Some(run_row) = sqlx::query(
+            "UPDATE automation_runs
+             SET state = 'FAILED',
+                 failure_code = $3,
+                 updated_at = NOW()
+             WHERE id = $1
+               AND user_id = $2
+             RETURNING rule_id, scheduled_for",
+        )
+        .bind(run_id)
+        .bind(user_id)
+        .bind(failure_code)
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            tx.rollback().await?;
+            return Ok(None);
+        };
+        let rule_id: Uuid = run_row.try_get("rule_id")?;
+        let scheduled_for: DateTime<Utc> = run_row.try_get("scheduled_for")?;
+
+        let consecutive_failures: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*)::bigint
+             FROM automation_runs
+             WHERE rule_id = $1
+               AND state = 'FAILED'
+               AND scheduled_for <= $2
+               AND scheduled_for > COALESCE(
+                 (SELECT MAX(scheduled_for)
+                  FROM automation_runs
+                  WHERE rule_id = $1
+                    AND (state <> 'FAILED' OR paused_rule)
+                    AND scheduled_for < $2),
+                 '-infinity'::timestamptz
+               )",
+        )
+        .bind(rule_id)
+        .bind(scheduled_for)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let mut paused_rule = false;
+        if consecutive_failures >= pause_threshold {
+            let paused = sqlx::query(
+                "UPDATE automation_rules
+                 SET status = 'PAUSED',
+                     lease_owner = NULL,
+                     lease_expires_at = NULL,
+                     updated_at = NOW()
+                 WHERE id = $1
+                   AND user_id = $2
+                   AND status = 'ACTIVE'",
+            )
+            .bind(rule_id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+            paused_rule = paused.rows_affected() > 0;
+        }
+        if paused_rule {
+            sqlx::query("UPDATE automation_runs SET paused_rule = TRUE WHERE id = $1")
+                .bind(run_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+        Ok(Some(AutomationRunFailure {
+            rule_id,
+            consecutive_failures,
+            paused_rule,
+        }))
+    }
+
     pub async fn list_automation_runs_for_rule(
         &self,
         user_id: Uuid,
@@ -170,6 +348,10 @@ impl Store {
                 job_id,
                 idempotency_key,
                 state,
+                failure_code,
+                paused_rule,
+                condition_met,
+                condition_observed_count,
                 created_at,
                 updated_at
              FROM automation_runs
@@ -200,6 +382,10 @@ fn automation_run_from_row(row: &sqlx::postgres::PgRow) -> Result<AutomationRunR
         job_id: row.try_get("job_id")?,
         idempotency_key: row.try_get("idempotency_key")?,
         state: AutomationRunState::from_db(&state)?,
+        failure_code: row.try_get("failure_code")?,
+        paused_rule: row.try_get("paused_rule")?,
+        condition_met: row.try_get("condition_met")?,
+        condition_observed_count: row.try_get("condition_observed_count")?,
         created_at: row.try_get("created_at")?,
         updated_at: row.try_get("updated_at")?,
     })
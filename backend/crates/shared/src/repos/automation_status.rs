@@ -0,0 +1,187 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use super::automation::automation_rule_from_row;
+use super::{AutomationRuleRecord, Store, StoreError};
+
+impl Store {
+    pub async fn pause_automation_rule(
+        &self,
+        user_id: Uuid,
+        rule_id: Uuid,
+    ) -> Result<bool, StoreError> {
+        let result = sqlx::query(
+            "UPDATE automation_rules
+             SET status = 'PAUSED',
+                 paused_until = NULL,
+                 lease_owner = NULL,
+                 lease_expires_at = NULL,
+                 updated_at = NOW()
+             WHERE user_id = $1
+               AND id = $2",
+        )
+        .bind(user_id)
+        .bind(rule_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn resume_automation_rule(
+        &self,
+        user_id: Uuid,
+        rule_id: Uuid,
+        next_run_at: DateTime<Utc>,
+    ) -> Result<bool, StoreError> {
+        let result = sqlx::query(
+            "UPDATE automation_rules
+             SET status = 'ACTIVE',
+                 paused_until = NULL,
+                 next_run_at = $3,
+                 lease_owner = NULL,
+                 lease_expires_at = NULL,
+                 updated_at = NOW()
+             WHERE user_id = $1
+               AND id = $2",
+        )
+        .bind(user_id)
+        .bind(rule_id)
+        .bind(next_run_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Retires a rule without deleting its run history. The scheduler only claims active rules,
+    /// so an archived rule stops running until it is restored through pause or resume.
+    pub async fn archive_automation_rule(
+        &self,
+        user_id: Uuid,
+        rule_id: Uuid,
+    ) -> Result<bool, StoreError> {
+        let result = sqlx::query(
+            "UPDATE automation_rules
+             SET status = 'ARCHIVED',
+                 paused_until = NULL,
+                 lease_owner = NULL,
+                 lease_expires_at = NULL,
+                 updated_at = NOW()
+             WHERE user_id = $1
+               AND id = $2",
+        )
+        .bind(user_id)
+        .bind(rule_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Pauses an active or paused rule until `paused_until`. The worker resumes it once the
+    /// snooze window has passed; pause, resume and archive all clear the window.
+    pub async fn snooze_automation_rule(
+        &self,
+        user_id: Uuid,
+        rule_id: Uuid,
+        paused_until: DateTime<Utc>,
+    ) -> Result<bool, StoreError> {
+        let result = sqlx::query(
+            "UPDATE automation_rules
+             SET status = 'PAUSED',
+                 paused_until = $3,
+                 lease_owner = NULL,
+                 lease_expires_at = NULL,
+                 updated_at = NOW()
+             WHERE user_id = $1
+               AND id = $2
+               AND status IN ('ACTIVE', 'PAUSED')",
+        )
+        .bind(user_id)
+        .bind(rule_id)
+        .bind(paused_until)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Lists snoozed rules whose window ended at or before `now`, oldest first.
+    pub async fn list_due_automation_snoozes(
+        &self,
+        now: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<AutomationRuleRecord>, StoreError> {
+        if limit <= 0 {
+            return Ok(Vec::new());
+        }
+
+        let rows = sqlx::query(
+            "SELECT
+                id,
+                user_id,
+                title,
+                status,
+                template,
+                condition_kind,
+                condition_min_count,
+                schedule_type,
+                local_time_minutes,
+                anchor_days_of_week,
+                anchor_day_of_month,
+                anchor_month,
+                cron_expression,
+                run_at,
+                time_zone,
+                next_run_at,
+                last_run_at,
+                paused_until,
+                prompt_sha256,
+                version,
+                created_at,
+                updated_at
+             FROM automation_rules
+             WHERE status = 'PAUSED'
+               AND paused_until IS NOT NULL
+               AND paused_until <= $1
+             ORDER BY paused_until ASC, id ASC
+             LIMIT $2",
+        )
+        .bind(now)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(automation_rule_from_row).collect()
+    }
+
+    /// Reactivates a snoozed rule. The update only applies while the rule is still paused with
+    /// the same `paused_until`, so a user edit made since the listing wins.
+    pub async fn resume_snoozed_automation_rule(
+        &self,
+        rule_id: Uuid,
+        paused_until: DateTime<Utc>,
+        next_run_at: DateTime<Utc>,
+    ) -> Result<bool, StoreError> {
+        let result = sqlx::query(
+            "UPDATE automation_rules
+             SET status = 'ACTIVE',
+                 paused_until = NULL,
+                 next_run_at = $3,
+                 lease_owner = NULL,
+                 lease_expires_at = NULL,
+                 updated_at = NOW()
+             WHERE id = $1
+               AND status = 'PAUSED'
+               AND paused_until = $2",
+        )
+        .bind(rule_id)
+        .bind(paused_until)
+        .bind(next_run_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,189 @@
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+use sqlx::Row;
+use sqlx::postgres::PgRow;
+use uuid::Uuid;
+
+use crate::brief_profile::{
+    BriefFeedbackRating, MorningBriefProfile, MorningBriefSection, MorningBriefVerbosity,
+    apply_brief_feedback,
+};
+
+use super::{Store, StoreError};
+
+#[derive(Debug, Clone)]
+pub struct MorningBriefProfileRecord {
+    pub profile: MorningBriefProfile,
+    pub learn_from_feedback: bool,
+    pub section_scores: BTreeMap<MorningBriefSection, i32>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl Store {
+    /// Returns the stored morning brief profile, or the default profile when the user has
+    /// not customized one yet.
+    pub async fn get_morning_brief_profile(
+        &self,
+        user_id: Uuid,
+    ) -> Result<MorningBriefProfileRecord, StoreError> {
+        let row = sqlx::query(
+            "SELECT sections, verbosity, learn_from_feedback, section_scores, updated_at
+             FROM morning_brief_profiles
+             WHERE user_id = $1",
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match row {
+            Some(row) => brief_profile_record_from_row(&row),
+            None => Ok(default_brief_profile_record()),
+        }
+    }
+
+    /// Replaces the user's profile. Explicit edits reset accumulated feedback scores so the
+    /// chosen ordering is kept until new feedback arrives.
+    pub async fn upsert_morning_brief_profile(
+        &self,
+        user_id: Uuid,
+        profile: &MorningBriefProfile,
+        learn_from_feedback: bool,
+    ) -> Result<MorningBriefProfileRecord, StoreError> {
+        self.ensure_user(user_id).await?;
+
+        let row = sqlx::query(
+            "INSERT INTO morning_brief_profiles (
+                user_id,
+                sections,
+                verbosity,
+                learn_from_feedback,
+                section_scores
+             )
+             VALUES ($1, $2, $3, $4, '{}'::jsonb)
+             ON CONFLICT (user_id)
+             DO UPDATE SET
+               sections = EXCLUDED.sections,
+               verbosity = EXCLUDED.verbosity,
+               learn_from_feedback = EXCLUDED.learn_from_feedback,
+               section_scores = '{}'::jsonb,
+               updated_at = NOW()
+             RETURNING sections, verbosity, learn_from_feedback, section_scores, updated_at",
+        )
+        .bind(user_id)
+        .bind(section_names(&profile.sections))
+        .bind(profile.verbosity.as_str())
+        .bind(learn_from_feedback)
+        .fetch_one(&self.pool)
+        .await?;
+
+        brief_profile_record_from_row(&row)
+    }
+
+    pub async fn record_morning_brief_feedback(
+        &self,
+        user_id: Uuid,
+        section: MorningBriefSection,
+        rating: BriefFeedbackRating,
+    ) -> Result<MorningBriefProfileRecord, StoreError> {
+        self.ensure_user(user_id).await?;
+
+        let default_profile = MorningBriefProfile::default();
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            "INSERT INTO morning_brief_profiles (user_id, sections, verbosity)
+             VALUES ($1, $2, $3)
+             ON CONFLICT (user_id) DO NOTHING",
+        )
+        .bind(user_id)
+        .bind(section_names(&default_profile.sections))
+        .bind(default_profile.verbosity.as_str())
+        .execute(&mut *tx)
+        .await?;
+
+        let row = sqlx::query(
+            "SELECT sections, verbosity, learn_from_feedback, section_scores, updated_at
+             FROM morning_brief_profiles
+             WHERE user_id = $1
+             FOR UPDATE",
+        )
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        let mut record = brief_profile_record_from_row(&row)?;
+
+        apply_brief_feedback(
+            &mut record.profile,
+            &mut record.section_scores,
+            record.learn_from_feedback,
+            section,
+            rating,
+        );
+
+        let section_scores = serde_json::to_value(&record.section_scores).map_err(|err| {
+            StoreError::InvalidData(format!("failed to encode brief section scores: {err}"))
+        })?;
+        let row = sqlx::query(
+            "UPDATE morning_brief_profiles
+             SET sections = $2,
+                 section_scores = $3,
+                 updated_at = NOW()
+             WHERE user_id = $1
+             RETURNING sections, verbosity, learn_from_feedback, section_scores, updated_at",
+        )
+        .bind(user_id)
+        .bind(section_names(&record.profile.sections))
+        .bind(section_scores)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        brief_profile_record_from_row(&row)
+    }
+}
+
+fn default_brief_profile_record() -> MorningBriefProfileRecord {
+    MorningBriefProfileRecord {
+        profile: MorningBriefProfile::default(),
+        learn_from_feedback: true,
+        section_scores: BTreeMap::new(),
+        updated_at: None,
+    }
+}
+
+fn section_names(sections: &[MorningBriefSection]) -> Vec<&'static str> {
+    sections.iter().map(|section| section.as_str()).collect()
+}
+
+fn brief_profile_record_from_row(row: &PgRow) -> Result<MorningBriefProfileRecord, StoreError> {
+    let sections: Vec<String> = row.try_get("sections")?;
+    let verbosity: String = row.try_get("verbosity")?;
+    let section_scores: Value = row.try_get("section_scores")?;
+
+    let sections = sections
+        .iter()
+        .map(|section| {
+            MorningBriefSection::parse(section).ok_or_else(|| {
+                StoreError::InvalidData(format!("unknown brief section persisted: {section}"))
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let verbosity = MorningBriefVerbosity::parse(&verbosity).ok_or_else(|| {
+        StoreError::InvalidData(format!("unknown brief verbosity persisted: {verbosity}"))
+    })?;
+    let section_scores = serde_json::from_value(section_scores).map_err(|err| {
+        StoreError::InvalidData(format!("invalid brief section scores persisted: {err}"))
+    })?;
+
+    Ok(MorningBriefProfileRecord {
+        profile: MorningBriefProfile {
+            sections,
+            verbosity,
+        },
+        learn_from_feedback: row.try_get("learn_from_feedback")?,
+        section_scores,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
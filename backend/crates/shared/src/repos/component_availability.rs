@@ -0,0 +1,84 @@
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+
+use crate::models::AvailabilityComponent;
+
+use super::{Store, StoreError};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComponentAvailabilityBucket {
+    pub component: AvailabilityComponent,
+    pub bucket_start: DateTime<Utc>,
+    pub attempts: i64,
+    pub successes: i64,
+}
+
+impl Store {
+    /// Adds attempt and success counts to the component's current hourly bucket.
+    pub async fn record_component_availability(
+        &self,
+        component: AvailabilityComponent,
+        attempts: i64,
+        successes: i64,
+    ) -> Result<(), StoreError> {
+        sqlx::query(
+            "INSERT INTO component_availability (component, bucket_start, attempts, successes)
+             VALUES ($1, date_trunc('hour', NOW()), $2, $3)
+             ON CONFLICT (component, bucket_start)
+             DO UPDATE SET
+               attempts = component_availability.attempts + EXCLUDED.attempts,
+               successes = component_availability.successes + EXCLUDED.successes,
+               updated_at = NOW()",
+        )
+        .bind(component.as_str())
+        .bind(attempts)
+        .bind(successes)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn list_component_availability(
+        &self,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<ComponentAvailabilityBucket>, StoreError> {
+        let rows = sqlx::query(
+            "SELECT component, bucket_start, attempts, successes
+             FROM component_availability
+             WHERE bucket_start >= date_trunc('hour', $1::timestamptz)
+             ORDER BY bucket_start ASC, component ASC",
+        )
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let component: String = row.try_get("component")?;
+                Ok(ComponentAvailabilityBucket {
+                    component: AvailabilityComponent::parse(&component).ok_or_else(|| {
+                        StoreError::InvalidData(format!(
+                            "unknown availability component persisted: {component}"
+                        ))
+                    })?,
+                    bucket_start: row.try_get("bucket_start")?,
+                    attempts: row.try_get("attempts")?,
+                    successes: row.try_get("successes")?,
+                })
+            })
+            .collect()
+    }
+
+    pub async fn prune_component_availability(
+        &self,
+        before: DateTime<Utc>,
+    ) -> Result<u64, StoreError> {
+        let result = sqlx::query("DELETE FROM component_availability WHERE bucket_start < $1")
+            .bind(before)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+}
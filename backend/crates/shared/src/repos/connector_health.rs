@@ -0,0 +1,184 @@
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+use uuid::Uuid;
+
+use crate::connector_health::{ConnectorHealthSignal, ConnectorHealthState};
+
+use super::{Store, StoreError};
+
+#[derive(Debug, Clone)]
+pub struct ConnectorReauthNudge {
+    pub connector_id: Uuid,
+    pub user_id: Uuid,
+    pub provider: String,
+    pub health_score: i16,
+    pub refresh_failure_count: i32,
+    pub provider_unauthorized_count: i32,
+    pub missing_scopes: Vec<String>,
+}
+
+/// Health bookkeeping for one connector, as seen by operators.
+#[derive(Debug, Clone)]
+pub struct ConnectorHealthRecord {
+    pub connector_id: Uuid,
+    pub provider: String,
+    pub status: String,
+    pub health_score: i16,
+    pub refresh_failure_count: i32,
+    pub provider_unauthorized_count: i32,
+    pub missing_scopes: Vec<String>,
+    pub health_updated_at: Option<DateTime<Utc>>,
+    pub reauth_nudged_at: Option<DateTime<Utc>>,
+}
+
+impl Store {
+    /// Folds one provider call outcome into the connector's persisted health. Returns the new
+    /// score, or `None` when the connector is no longer active. Unchanged state skips the write
+    /// so routine successful calls stay read-only.
+    pub async fn record_connector_health_signal(
+        &self,
+        connector_id: Uuid,
+        signal: ConnectorHealthSignal,
+    ) -> Result<Option<i16>, StoreError> {
+        let mut tx = self.pool.begin().await?;
+
+        let Some(row) = sqlx::query(
+            "SELECT scopes, health_score, refresh_failure_count, provider_unauthorized_count,
+                    missing_scopes
+             FROM connectors
+             WHERE id = $1 AND status = 'ACTIVE'
+             FOR UPDATE",
+        )
+        .bind(connector_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        let scopes: Vec<String> = row.try_get("scopes")?;
+        let current_score: i16 = row.try_get("health_score")?;
+        let current = ConnectorHealthState {
+            refresh_failure_count: row.try_get("refresh_failure_count")?,
+            provider_unauthorized_count: row.try_get("provider_unauthorized_count")?,
+            missing_scopes: row.try_get("missing_scopes")?,
+        };
+        let next = ConnectorHealthState {
+            missing_scopes: ConnectorHealthState::for_scopes(&scopes).missing_scopes,
+            ..current.apply(signal)
+        };
+        let next_score = next.score();
+
+        if next == current && next_score == current_score {
+            return Ok(Some(current_score));
+        }
+
+        sqlx::query(
+            "UPDATE connectors
+             SET health_score = $2,
+                 refresh_failure_count = $3,
+                 provider_unauthorized_count = $4,
+                 missing_scopes = $5,
+                 health_updated_at = NOW()
+             WHERE id = $1",
+        )
+        .bind(connector_id)
+        .bind(next_score)
+        .bind(next.refresh_failure_count)
+        .bind(next.provider_unauthorized_count)
+        .bind(&next.missing_scopes)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(Some(next_score))
+    }
+
+    /// Claims active connectors scoring below `threshold` that have not been nudged since
+    /// `nudged_before`. Claiming stamps `reauth_nudged_at`, so each connector is nudged at most
+    /// once per interval even when several workers race or delivery fails.
+    pub async fn claim_connector_reauth_nudges(
+        &self,
+        threshold: i16,
+        now: DateTime<Utc>,
+        nudged_before: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<ConnectorReauthNudge>, StoreError> {
+        let rows = sqlx::query(
+            "WITH due AS (
+               SELECT id
+               FROM connectors
+               WHERE status = 'ACTIVE'
+                 AND health_score < $1
+                 AND (reauth_nudged_at IS NULL OR reauth_nudged_at <= $3)
+               ORDER BY health_score ASC, id ASC
+               LIMIT $4
+               FOR UPDATE SKIP LOCKED
+             )
+             UPDATE connectors c
+             SET reauth_nudged_at = $2
+             FROM due
+             WHERE c.id = due.id
+             RETURNING c.id, c.user_id, c.provider, c.health_score, c.refresh_failure_count,
+                       c.provider_unauthorized_count, c.missing_scopes",
+        )
+        .bind(threshold)
+        .bind(now)
+        .bind(nudged_before)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut nudges = rows
+            .into_iter()
+            .map(|row| {
+                Ok(ConnectorReauthNudge {
+                    connector_id: row.try_get("id")?,
+                    user_id: row.try_get("user_id")?,
+                    provider: row.try_get("provider")?,
+                    health_score: row.try_get("health_score")?,
+                    refresh_failure_count: row.try_get("refresh_failure_count")?,
+                    provider_unauthorized_count: row.try_get("provider_unauthorized_count")?,
+                    missing_scopes: row.try_get("missing_scopes")?,
+                })
+            })
+            .collect::<Result<Vec<_>, StoreError>>()?;
+        nudges.sort_by_key(|nudge| (nudge.health_score, nudge.connector_id));
+
+        Ok(nudges)
+    }
+
+    /// Health bookkeeping for every connector the user has linked, revoked ones included.
+    pub async fn list_connector_health(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<ConnectorHealthRecord>, StoreError> {
+        let rows = sqlx::query(
+            "SELECT id, provider, status, health_score, refresh_failure_count,
+                    provider_unauthorized_count, missing_scopes, health_updated_at,
+                    reauth_nudged_at
+             FROM connectors
+             WHERE user_id = $1
+             ORDER BY created_at ASC, id ASC",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(ConnectorHealthRecord {
+                    connector_id: row.try_get("id")?,
+                    provider: row.try_get("provider")?,
+                    status: row.try_get("status")?,
+                    health_score: row.try_get("health_score")?,
+                    refresh_failure_count: row.try_get("refresh_failure_count")?,
+                    provider_unauthorized_count: row.try_get("provider_unauthorized_count")?,
+                    missing_scopes: row.try_get("missing_scopes")?,
+                    health_updated_at: row.try_get("health_updated_at")?,
+                    reauth_nudged_at: row.try_get("reauth_nudged_at")?,
+                })
+            })
+            .collect()
+    }
+}
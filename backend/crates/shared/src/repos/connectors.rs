@@ -1,11 +1,43 @@
+use chrono::{DateTime, Utc};
 use sqlx::Row;
 use uuid::Uuid;
 
+use crate::connector_health::{ConnectorHealthState, missing_google_scopes};
+
 use super::{
-    ActiveConnectorMetadata, ConnectorKeyMetadata, ConnectorStateRecord,
-    LEGACY_CONNECTOR_TOKEN_KEY_ID, Store, StoreError,
+    ActiveConnectorMetadata, ConnectorKeyMetadata, LEGACY_CONNECTOR_TOKEN_KEY_ID, Store, StoreError,
 };
 
+#[derive(Debug, Clone)]
+pub struct ConnectorRecord {
+    pub connector_id: Uuid,
+    pub provider: String,
+    pub status: String,
+    pub scopes: Vec<String>,
+    pub token_rotated_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub health_score: i16,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// The connector row an upsert writes: the user's connector for one provider account. A new
+/// row takes `connector_id`; an existing row for the account keeps its own id, which the
+/// upsert returns.
+#[derive(Debug, Clone, Copy)]
+pub struct ConnectorAccount<'a> {
+    pub user_id: Uuid,
+    pub connector_id: Uuid,
+    pub account_key: &'a str,
+}
+
+/// Provider-specific values written by `upsert_connector`.
+struct ConnectorUpsert<'a> {
+    provider: &'a str,
+    secret: &'a str,
+    scopes: &'a [String],
+    health: &'a ConnectorHealthState,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ConnectorKeyRotationOutcome {
     Rotated,
@@ -36,12 +68,12 @@ fn classify_connector_key_rotation_outcome(
 }
 
 impl Store {
-    pub async fn list_connector_states(
-        &self,
-        user_id: Uuid,
-    ) -> Result<Vec<ConnectorStateRecord>, StoreError> {
+    /// Lists every connector the user has linked, including revoked ones. Only metadata is
+    /// read; token ciphertext never leaves the table here.
+    pub async fn list_connectors(&self, user_id: Uuid) -> Result<Vec<ConnectorRecord>, StoreError> {
         let rows = sqlx::query(
-            "SELECT id, provider, status
+            "SELECT id, provider, status, scopes, token_rotated_at, created_at, health_score,
+                    updated_at
              FROM connectors
              WHERE user_id = $1
              ORDER BY created_at ASC, id ASC",
@@ -55,10 +87,15 @@ impl Store {
                 let connector_id: Uuid = row.try_get("id")?;
                 let provider: String = row.try_get("provider")?;
                 let status: String = row.try_get("status")?;
-                Ok(ConnectorStateRecord {
+                Ok(ConnectorRecord {
                     connector_id,
                     provider,
                     status,
+                    scopes: row.try_get("scopes")?,
+                    token_rotated_at: row.try_get("token_rotated_at")?,
+                    created_at: row.try_get("created_at")?,
+                    health_score: row.try_get("health_score")?,
+                    updated_at: row.try_get("updated_at")?,
                 })
             })
             .collect()
@@ -95,18 +132,125 @@ impl Store {
             .collect()
     }
 
-    pub async fn upsert_google_connector(
+    /// The id of the user's `provider` connector for `account_key`, or a fresh id when that
+    /// account is not connected yet. Connector secrets are sealed under their connector id,
+    /// so the id is settled before the secret is sealed and then passed to the upsert.
+    pub async fn connector_id_for_account(
         &self,
         user_id: Uuid,
+        provider: &str,
+        account_key: &str,
+    ) -> Result<Uuid, StoreError> {
+        let connector_id: Option<Uuid> = sqlx::query_scalar(
+            "SELECT id
+             FROM connectors
+             WHERE user_id = $1
+               AND provider = $2
+               AND account_key = $3",
+        )
+        .bind(user_id)
+        .bind(provider)
+        .bind(account_key)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(connector_id.unwrap_or_else(Uuid::new_v4))
+    }
+
+    /// Inserts or refreshes the Google connector for one Google account. Each account a user
+    /// connects gets its own row, keyed by `account_key`.
+    pub async fn upsert_google_connector(
+        &self,
+        account: ConnectorAccount<'_>,
         refresh_token: &str,
         scopes: &[String],
         token_key_id: &str,
         token_version: i32,
     ) -> Result<Uuid, StoreError> {
+        let health = ConnectorHealthState::for_scopes(scopes);
+        self.upsert_connector(
+            account,
+            ConnectorUpsert {
+                provider: "google",
+                secret: refresh_token,
+                scopes,
+                health: &health,
+            },
+            token_key_id,
+            token_version,
+        )
+        .await
+    }
+
+    /// Inserts or refreshes a CalDAV connector. `credentials` is the serialized server URL,
+    /// username, and app password; it is stored encrypted like a Google refresh token.
+    pub async fn upsert_caldav_connector(
+        &self,
+        account: ConnectorAccount<'_>,
+        credentials: &str,
+        token_key_id: &str,
+        token_version: i32,
+    ) -> Result<Uuid, StoreError> {
+        self.upsert_connector(
+            account,
+            ConnectorUpsert {
+                provider: "caldav",
+                secret: credentials,
+                scopes: &[],
+                health: &ConnectorHealthState::default(),
+            },
+            token_key_id,
+            token_version,
+        )
+        .await
+    }
+
+    /// Inserts or refreshes an IMAP connector. `credentials` is the serialized host, port,
+    /// username, and password; it is stored encrypted like a Google refresh token.
+    pub async fn upsert_imap_connector(
+        &self,
+        account: ConnectorAccount<'_>,
+        credentials: &str,
+        token_key_id: &str,
+        token_version: i32,
+    ) -> Result<Uuid, StoreError> {
+        self.upsert_connector(
+            account,
+            ConnectorUpsert {
+                provider: "imap",
+                secret: credentials,
+                scopes: &[],
+                health: &ConnectorHealthState::default(),
+            },
+            token_key_id,
+            token_version,
+        )
+        .await
+    }
+
+    async fn upsert_connector(
+        &self,
+        account: ConnectorAccount<'_>,
+        upsert: ConnectorUpsert<'_>,
+        token_key_id: &str,
+        token_version: i32,
+    ) -> Result<Uuid, StoreError> {
+        let ConnectorAccount {
+            user_id,
+            connector_id,
+            account_key,
+        } = account;
         self.ensure_user(user_id).await?;
+        let ConnectorUpsert {
+            provider,
+            secret,
+            scopes,
+            health,
+        } = upsert;
 
         let connector_id: Uuid = sqlx::query_scalar(
             "INSERT INTO connectors (
+                id,
                 user_id,
                 provider,
                 scopes,
@@ -114,13 +258,22 @@ impl Store {
                 token_key_id,
                 token_version,
                 token_rotated_at,
-                status
+                status,
+                data_key_id,
+                health_score,
+                missing_scopes,
+                health_updated_at,
+                account_key
+             )
+             VALUES (
+                $12, $1, $11, $2, pgp_sym_encrypt($3, $6), $4, $5, NOW(), 'ACTIVE', $7, $8, $9,
+                NOW(), $10
              )
-             VALUES ($1, 'google', $2, pgp_sym_encrypt($3, $6), $4, $5, NOW(), 'ACTIVE')
-             ON CONFLICT (user_id, provider)
+             ON CONFLICT (user_id, provider, account_key)
              DO UPDATE SET
                scopes = EXCLUDED.scopes,
                refresh_token_ciphertext = pgp_sym_encrypt($3, $6),
+               data_key_id = EXCLUDED.data_key_id,
                token_key_id = EXCLUDED.token_key_id,
                token_version = EXCLUDED.token_version,
                token_rotated_at = CASE
@@ -130,21 +283,92 @@ impl Store {
                  ELSE connectors.token_rotated_at
                END,
                status = 'ACTIVE',
-               revoked_at = NULL
+               revoked_at = NULL,
+               health_score = EXCLUDED.health_score,
+               refresh_failure_count = 0,
+               provider_unauthorized_count = 0,
+               missing_scopes = EXCLUDED.missing_scopes,
+               health_updated_at = NOW(),
+               reauth_nudged_at = NULL
              RETURNING id",
         )
         .bind(user_id)
         .bind(scopes)
-        .bind(refresh_token)
+        .bind(secret)
         .bind(token_key_id)
         .bind(token_version)
         .bind(&self.data_encryption_key)
+        .bind(&self.data_encryption_key_id)
+        .bind(health.score())
+        .bind(&health.missing_scopes)
+        .bind(account_key)
+        .bind(provider)
+        .bind(connector_id)
         .fetch_one(&self.pool)
         .await?;
 
         Ok(connector_id)
     }
 
+    /// Adds `scopes` to an active connector's granted scopes without dropping any, and
+    /// refreshes its scope-gap health. Returns the merged scopes, or `None` when the
+    /// connector is missing or inactive.
+    pub async fn merge_connector_scopes(
+        &self,
+        user_id: Uuid,
+        connector_id: Uuid,
+        scopes: &[String],
+    ) -> Result<Option<Vec<String>>, StoreError> {
+        let mut tx = self.pool.begin().await?;
+        let row = sqlx::query(
+            "SELECT scopes, refresh_failure_count, provider_unauthorized_count
+             FROM connectors
+             WHERE id = $1
+               AND user_id = $2
+               AND status = 'ACTIVE'
+             FOR UPDATE",
+        )
+        .bind(connector_id)
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        let mut merged: Vec<String> = row.try_get("scopes")?;
+        for scope in scopes {
+            if !merged.contains(scope) {
+                merged.push(scope.clone());
+            }
+        }
+        let health = ConnectorHealthState {
+            refresh_failure_count: row.try_get("refresh_failure_count")?,
+            provider_unauthorized_count: row.try_get("provider_unauthorized_count")?,
+            missing_scopes: missing_google_scopes(&merged),
+        };
+
+        sqlx::query(
+            "UPDATE connectors
+             SET scopes = $3,
+                 missing_scopes = $4,
+                 health_score = $5,
+                 health_updated_at = NOW()
+             WHERE id = $1
+               AND user_id = $2",
+        )
+        .bind(connector_id)
+        .bind(user_id)
+        .bind(&merged)
+        .bind(&health.missing_scopes)
+        .bind(health.score())
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(Some(merged))
+    }
+
     pub async fn revoke_connector(
         &self,
         user_id: Uuid,
@@ -259,7 +483,10 @@ impl Store {
         metadata: &ConnectorKeyMetadata,
     ) -> Result<Option<String>, StoreError> {
         let refresh_token = sqlx::query_scalar(
-            "SELECT pgp_sym_decrypt(refresh_token_ciphertext, $6) AS refresh_token
+            "SELECT pgp_sym_decrypt(
+                refresh_token_ciphertext,
+                alfred_data_key(data_key_id, $6, $7)
+             ) AS refresh_token
              FROM connectors
              WHERE id = $1
                AND user_id = $2
@@ -273,7 +500,8 @@ impl Store {
         .bind(&metadata.provider)
         .bind(&metadata.token_key_id)
         .bind(metadata.token_version)
-        .bind(&self.data_encryption_key)
+        .bind(&self.data_encryption_key_ids)
+        .bind(&self.data_encryption_keys)
         .fetch_optional(&self.pool)
         .await?;
 
@@ -0,0 +1,286 @@
+use super::{Store, StoreError};
+
+#[derive(Clone)]
+pub struct DataEncryptionKey {
+    pub key_id: String,
+    pub key: String,
+}
+
+impl std::fmt::Debug for DataEncryptionKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DataEncryptionKey")
+            .field("key_id", &self.key_id)
+            .field("key", &"<redacted>")
+            .finish()
+    }
+}
+
+/// New ciphertext is always written with `primary`. While a rotation is in progress the
+/// retiring key is configured as `secondary` so existing rows stay readable until the worker
+/// has re-encrypted them.
+#[derive(Debug, Clone)]
+pub struct DataEncryptionKeyring {
+    pub primary: DataEncryptionKey,
+    pub secondary: Option<DataEncryptionKey>,
+}
+
+impl DataEncryptionKeyring {
+    pub fn single(key_id: &str, key: &str) -> Self {
+        Self {
+            primary: DataEncryptionKey {
+                key_id: key_id.to_string(),
+                key: key.to_string(),
+            },
+            secondary: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DataKeyReencryptionCounts {
+    pub devices: u64,
+    pub connectors: u64,
+    pub jobs: u64,
+    pub dead_letter_jobs: u64,
+    pub automation_rules: u64,
+    pub departure_alert_preferences: u64,
+    pub webhook_subscriptions: u64,
+}
+
+impl DataKeyReencryptionCounts {
+    pub fn total(&self) -> u64 {
+        self.devices
+            + self.connectors
+            + self.jobs
+            + self.dead_letter_jobs
+            + self.automation_rules
+            + self.departure_alert_preferences
+            + self.webhook_subscriptions
+    }
+}
+
+impl Store {
+    /// Rewraps up to `batch_size` rows per table that are still encrypted with the secondary
+    /// (retiring) data encryption key so they use the primary key. Returns zero counts when no
+    /// secondary key is configured.
+    pub async fn reencrypt_secondary_key_rows(
+        &self,
+        batch_size: i64,
+    ) -> Result<DataKeyReencryptionCounts, StoreError> {
+        if batch_size <= 0 {
+            return Err(StoreError::InvalidData(
+                "data key re-encryption batch size must be > 0".to_string(),
+            ));
+        }
+        let Some(secondary) = self.secondary_data_encryption_key.as_ref() else {
+            return Ok(DataKeyReencryptionCounts::default());
+        };
+
+        let devices = sqlx::query(
+            "WITH stale AS (
+                SELECT id
+                FROM devices
+                WHERE data_key_id = $1
+                ORDER BY id
+                LIMIT $2
+                FOR UPDATE SKIP LOCKED
+             )
+             UPDATE devices d
+             SET apns_token_ciphertext = pgp_sym_encrypt(
+                   pgp_sym_decrypt(d.apns_token_ciphertext, $3),
+                   $4
+                 ),
+                 notification_public_key_ciphertext = pgp_sym_encrypt(
+                   pgp_sym_decrypt(d.notification_public_key_ciphertext, $3),
+                   $4
+                 ),
+                 previous_notification_public_key_ciphertext = pgp_sym_encrypt(
+                   pgp_sym_decrypt(d.previous_notification_public_key_ciphertext, $3),
+                   $4
+                 ),
+                 data_key_id = $5
+             FROM stale
+             WHERE d.id = stale.id",
+        )
+        .bind(&secondary.key_id)
+        .bind(batch_size)
+        .bind(&secondary.key)
+        .bind(&self.data_encryption_key)
+        .bind(&self.data_encryption_key_id)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        let connectors = sqlx::query(
+            "WITH stale AS (
+                SELECT id
+                FROM connectors
+                WHERE data_key_id = $1
+                ORDER BY id
+                LIMIT $2
+                FOR UPDATE SKIP LOCKED
+             )
+             UPDATE connectors c
+             SET refresh_token_ciphertext = pgp_sym_encrypt(
+                   pgp_sym_decrypt(c.refresh_token_ciphertext, $3),
+                   $4
+                 ),
+                 data_key_id = $5
+             FROM stale
+             WHERE c.id = stale.id",
+        )
+        .bind(&secondary.key_id)
+        .bind(batch_size)
+        .bind(&secondary.key)
+        .bind(&self.data_encryption_key)
+        .bind(&self.data_encryption_key_id)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        let jobs = sqlx::query(
+            "WITH stale AS (
+                SELECT id
+                FROM jobs
+                WHERE data_key_id = $1
+                ORDER BY id
+                LIMIT $2
+                FOR UPDATE SKIP LOCKED
+             )
+             UPDATE jobs j
+             SET payload_ciphertext = pgp_sym_encrypt(
+                   pgp_sym_decrypt(j.payload_ciphertext, $3),
+                   $4
+                 ),
+                 data_key_id = $5
+             FROM stale
+             WHERE j.id = stale.id",
+        )
+        .bind(&secondary.key_id)
+        .bind(batch_size)
+        .bind(&secondary.key)
+        .bind(&self.data_encryption_key)
+        .bind(&self.data_encryption_key_id)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        let dead_letter_jobs = sqlx::query(
+            "WITH stale AS (
+                SELECT id
+                FROM dead_letter_jobs
+                WHERE data_key_id = $1
+                ORDER BY id
+                LIMIT $2
+                FOR UPDATE SKIP LOCKED
+             )
+             UPDATE dead_letter_jobs j
+             SET payload_ciphertext = pgp_sym_encrypt(
+                   pgp_sym_decrypt(j.payload_ciphertext, $3),
+                   $4
+                 ),
+                 data_key_id = $5
+             FROM stale
+             WHERE j.id = stale.id",
+        )
+        .bind(&secondary.key_id)
+        .bind(batch_size)
+        .bind(&secondary.key)
+        .bind(&self.data_encryption_key)
+        .bind(&self.data_encryption_key_id)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        let automation_rules = sqlx::query(
+            "WITH stale AS (
+                SELECT id
+                FROM automation_rules
+                WHERE data_key_id = $1
+                ORDER BY id
+                LIMIT $2
+                FOR UPDATE SKIP LOCKED
+             )
+             UPDATE automation_rules r
+             SET prompt_ciphertext = pgp_sym_encrypt(
+                   pgp_sym_decrypt(r.prompt_ciphertext, $3),
+                   $4
+                 ),
+                 data_key_id = $5
+             FROM stale
+             WHERE r.id = stale.id",
+        )
+        .bind(&secondary.key_id)
+        .bind(batch_size)
+        .bind(&secondary.key)
+        .bind(&self.data_encryption_key)
+        .bind(&self.data_encryption_key_id)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        let departure_alert_preferences = sqlx::query(
+            "WITH stale AS (
+                SELECT user_id
+                FROM departure_alert_preferences
+                WHERE data_key_id = $1
+                ORDER BY user_id
+                LIMIT $2
+                FOR UPDATE SKIP LOCKED
+             )
+             UPDATE departure_alert_preferences p
+             SET home_location_ciphertext = pgp_sym_encrypt(
+                   pgp_sym_decrypt(p.home_location_ciphertext, $3),
+                   $4
+                 ),
+                 data_key_id = $5
+             FROM stale
+             WHERE p.user_id = stale.user_id",
+        )
+        .bind(&secondary.key_id)
+        .bind(batch_size)
+        .bind(&secondary.key)
+        .bind(&self.data_encryption_key)
+        .bind(&self.data_encryption_key_id)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        let webhook_subscriptions = sqlx::query(
+            "WITH stale AS (
+                SELECT id
+                FROM webhook_subscriptions
+                WHERE data_key_id = $1
+                ORDER BY id
+                LIMIT $2
+                FOR UPDATE SKIP LOCKED
+             )
+             UPDATE webhook_subscriptions w
+             SET signing_secret_ciphertext = pgp_sym_encrypt(
+                   pgp_sym_decrypt(w.signing_secret_ciphertext, $3),
+                   $4
+                 ),
+                 data_key_id = $5
+             FROM stale
+             WHERE w.id = stale.id",
+        )
+        .bind(&secondary.key_id)
+        .bind(batch_size)
+        .bind(&secondary.key)
+        .bind(&self.data_encryption_key)
+        .bind(&self.data_encryption_key_id)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        Ok(DataKeyReencryptionCounts {
+            devices,
+            connectors,
+            jobs,
+            dead_letter_jobs,
+            automation_rules,
+            departure_alert_preferences,
+            webhook_subscriptions,
+        })
+    }
+}
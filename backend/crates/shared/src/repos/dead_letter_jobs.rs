@@ -0,0 +1,176 @@
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+use uuid::Uuid;
+
+use super::{JobType, Store, StoreError};
+
+/// Dead-letter metadata. The payload stays encrypted in the table and is never read here.
+#[derive(Debug, Clone)]
+pub struct DeadLetterJobRecord {
+    pub id: Uuid,
+    pub job_id: Uuid,
+    pub job_type: JobType,
+    pub attempts: i32,
+    pub reason_code: String,
+    pub reason_message: String,
+    pub failed_at: DateTime<Utc>,
+    pub replayed_at: Option<DateTime<Utc>>,
+    pub replay_job_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeadLetterReplay {
+    pub job_id: Uuid,
+    pub replayed_at: DateTime<Utc>,
+    /// False when an earlier replay already queued `job_id`.
+    pub newly_queued: bool,
+}
+
+impl Store {
+    /// Most recent dead letters for one user, newest first.
+    pub async fn list_dead_letter_jobs(
+        &self,
+        user_id: Uuid,
+        limit: i64,
+    ) -> Result<Vec<DeadLetterJobRecord>, StoreError> {
+        if limit <= 0 {
+            return Err(StoreError::InvalidData(
+                "dead letter list limit must be > 0".to_string(),
+            ));
+        }
+
+        let rows = sqlx::query(
+            "SELECT id, job_id, type, attempts, reason_code, reason_message, failed_at,
+                    replayed_at, replay_job_id
+             FROM dead_letter_jobs
+             WHERE user_id = $1
+             ORDER BY failed_at DESC, id DESC
+             LIMIT $2",
+        )
+        .bind(user_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let job_type: String = row.try_get("type")?;
+                Ok(DeadLetterJobRecord {
+                    id: row.try_get("id")?,
+                    job_id: row.try_get("job_id")?,
+                    job_type: JobType::from_db(&job_type)?,
+                    attempts: row.try_get("attempts")?,
+                    reason_code: row.try_get("reason_code")?,
+                    reason_message: row.try_get("reason_message")?,
+                    failed_at: row.try_get("failed_at")?,
+                    replayed_at: row.try_get("replayed_at")?,
+                    replay_job_id: row.try_get("replay_job_id")?,
+                })
+            })
+            .collect()
+    }
+
+    /// Queues a dead-lettered job again as a fresh PENDING job due at `now`, carrying over its
+    /// payload under the active data key. The dead letter is kept and linked to the new job, so
+    /// replaying it twice returns the first replay instead of queueing another run. Returns
+    /// `None` when the dead letter does not belong to the user.
+    pub async fn replay_dead_letter_job(
+        &self,
+        user_id: Uuid,
+        dead_letter_id: Uuid,
+        now: DateTime<Utc>,
+    ) -> Result<Option<DeadLetterReplay>, StoreError> {
+        let mut tx = self.pool.begin().await?;
+
+        let Some(row) = sqlx::query(
+            "SELECT replayed_at, replay_job_id
+             FROM dead_letter_jobs
+             WHERE id = $1
+               AND user_id = $2
+             FOR UPDATE",
+        )
+        .bind(dead_letter_id)
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        let replayed_at: Option<DateTime<Utc>> = row.try_get("replayed_at")?;
+        let replay_job_id: Option<Uuid> = row.try_get("replay_job_id")?;
+        if let (Some(replayed_at), Some(job_id)) = (replayed_at, replay_job_id) {
+            tx.commit().await?;
+            return Ok(Some(DeadLetterReplay {
+                job_id,
+                replayed_at,
+                newly_queued: false,
+            }));
+        }
+
+        // The original job keeps its FAILED row and idempotency key, so the replay gets a key
+        // of its own; otherwise enqueueing would resolve to the finished job.
+        let job_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO jobs_active (
+               user_id,
+               type,
+               due_at,
+               state,
+               payload_ciphertext,
+               idempotency_key,
+               data_key_id
+             )
+             SELECT
+               d.user_id,
+               d.type,
+               $2,
+               'PENDING',
+               CASE
+                 WHEN d.payload_ciphertext IS NULL THEN NULL
+                 ELSE pgp_sym_encrypt(
+                   pgp_sym_decrypt(
+                     d.payload_ciphertext,
+                     alfred_data_key(d.data_key_id, $4, $5)
+                   ),
+                   $6
+                 )
+               END,
+               $3,
+               $7
+             FROM dead_letter_jobs d
+             WHERE d.id = $1
+             ON CONFLICT (user_id, type, idempotency_key)
+             DO UPDATE SET updated_at = NOW()
+             RETURNING id",
+        )
+        .bind(dead_letter_id)
+        .bind(now)
+        .bind(format!("dead_letter_replay:{dead_letter_id}"))
+        .bind(&self.data_encryption_key_ids)
+        .bind(&self.data_encryption_keys)
+        .bind(&self.data_encryption_key)
+        .bind(&self.data_encryption_key_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            "UPDATE dead_letter_jobs
+             SET replayed_at = $2,
+                 replay_job_id = $3
+             WHERE id = $1",
+        )
+        .bind(dead_letter_id)
+        .bind(now)
+        .bind(job_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(Some(DeadLetterReplay {
+            job_id,
+            replayed_at: now,
+            newly_queued: true,
+        }))
+    }
+}
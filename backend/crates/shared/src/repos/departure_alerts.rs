@@ -0,0 +1,362 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::Row;
+use sqlx::postgres::PgRow;
+use uuid::Uuid;
+
+use crate::departure_alert::{DepartureAlertSettings, validate_departure_minutes};
+use crate::models::AutomationPromptEnvelope;
+use crate::timezone::normalize_time_zone;
+
+use super::{Store, StoreError};
+
+#[derive(Debug, Clone)]
+pub struct DepartureAlertPreferencesRecord {
+    pub settings: DepartureAlertSettings,
+    pub has_home_location: bool,
+    pub next_check_at: Option<DateTime<Utc>>,
+    pub version: i64,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DepartureAlertJobMaterial {
+    pub settings: DepartureAlertSettings,
+    pub home_location_envelope: Option<AutomationPromptEnvelope>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DueDepartureCheck {
+    pub user_id: Uuid,
+    pub settings: DepartureAlertSettings,
+    pub next_check_at: DateTime<Utc>,
+}
+
+impl Store {
+    pub async fn get_departure_alert_preferences(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Option<DepartureAlertPreferencesRecord>, StoreError> {
+        let row = sqlx::query(
+            "SELECT
+                enabled,
+                time_zone,
+                check_local_time_minutes,
+                travel_minutes,
+                buffer_minutes,
+                home_location_ciphertext IS NOT NULL AS has_home_location,
+                next_check_at,
+                version,
+                updated_at
+             FROM departure_alert_preferences
+             WHERE user_id = $1",
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|row| departure_alert_preferences_from_row(&row))
+            .transpose()
+    }
+
+    /// Replaces the user's departure alert preferences. A missing `home_location` clears the
+    /// stored location; `next_check_at` is `None` while alerts are disabled.
+    ///
+    /// With `expected_version` set, an existing row is only replaced if its version still
+    /// matches and `None` is returned otherwise. A user without saved preferences is at
+    /// version 0.
+    pub async fn upsert_departure_alert_preferences(
+        &self,
+        user_id: Uuid,
+        settings: &DepartureAlertSettings,
+        home_location: Option<&AutomationPromptEnvelope>,
+        next_check_at: Option<DateTime<Utc>>,
+        expected_version: Option<i64>,
+    ) -> Result<Option<DepartureAlertPreferencesRecord>, StoreError> {
+        self.ensure_user(user_id).await?;
+        let settings = normalized_departure_settings(settings)?;
+        let home_location = home_location
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|err| {
+                StoreError::InvalidData(format!("failed to encode home location envelope: {err}"))
+            })?;
+
+        let row = sqlx::query(
+            "INSERT INTO departure_alert_preferences (
+                user_id,
+                enabled,
+                time_zone,
+                check_local_time_minutes,
+                travel_minutes,
+                buffer_minutes,
+                home_location_ciphertext,
+                data_key_id,
+                next_check_at
+             )
+             SELECT
+                $1,
+                $2,
+                $3,
+                $4,
+                $5,
+                $6,
+                CASE WHEN $7::text IS NULL THEN NULL ELSE pgp_sym_encrypt($7, $8) END,
+                $9,
+                $10
+             WHERE $11::bigint IS NULL
+                OR $11 = 0
+                OR EXISTS (
+                    SELECT 1 FROM departure_alert_preferences WHERE user_id = $1
+                )
+             ON CONFLICT (user_id)
+             DO UPDATE SET
+               enabled = EXCLUDED.enabled,
+               time_zone = EXCLUDED.time_zone,
+               check_local_time_minutes = EXCLUDED.check_local_time_minutes,
+               travel_minutes = EXCLUDED.travel_minutes,
+               buffer_minutes = EXCLUDED.buffer_minutes,
+               home_location_ciphertext = EXCLUDED.home_location_ciphertext,
+               data_key_id = EXCLUDED.data_key_id,
+               next_check_at = EXCLUDED.next_check_at,
+               version = departure_alert_preferences.version + 1,
+               updated_at = NOW()
+             WHERE $11::bigint IS NULL
+                OR departure_alert_preferences.version = $11
+             RETURNING
+                enabled,
+                time_zone,
+                check_local_time_minutes,
+                travel_minutes,
+                buffer_minutes,
+                home_location_ciphertext IS NOT NULL AS has_home_location,
+                next_check_at,
+                version,
+                updated_at",
+        )
+        .bind(user_id)
+        .bind(settings.enabled)
+        .bind(&settings.time_zone)
+        .bind(i32::from(settings.check_local_time_minutes))
+        .bind(i32::from(settings.travel_minutes))
+        .bind(i32::from(settings.buffer_minutes))
+        .bind(home_location)
+        .bind(&self.data_encryption_key)
+        .bind(&self.data_encryption_key_id)
+        .bind(next_check_at.filter(|_| settings.enabled))
+        .bind(expected_version)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|row| departure_alert_preferences_from_row(&row))
+            .transpose()
+    }
+
+    /// Loads the settings and decrypted-at-rest home location envelope a departure check
+    /// needs. The envelope itself stays encrypted to the enclave.
+    pub async fn get_departure_alert_job_material(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Option<DepartureAlertJobMaterial>, StoreError> {
+        let row = sqlx::query(
+            "SELECT
+                enabled,
+                time_zone,
+                check_local_time_minutes,
+                travel_minutes,
+                buffer_minutes,
+                CASE
+                  WHEN home_location_ciphertext IS NULL THEN NULL
+                  ELSE pgp_sym_decrypt(
+                    home_location_ciphertext,
+                    alfred_data_key(data_key_id, $2, $3)
+                  )
+                END AS home_location_json
+             FROM departure_alert_preferences
+             WHERE user_id = $1",
+        )
+        .bind(user_id)
+        .bind(&self.data_encryption_key_ids)
+        .bind(&self.data_encryption_keys)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let home_location_json: Option<String> = row.try_get("home_location_json")?;
+        let home_location_envelope = home_location_json
+            .map(|value| serde_json::from_str(&value))
+            .transpose()
+            .map_err(|err| {
+                StoreError::InvalidData(format!("invalid home location envelope persisted: {err}"))
+            })?;
+
+        Ok(Some(DepartureAlertJobMaterial {
+            settings: departure_settings_from_row(&row)?,
+            home_location_envelope,
+        }))
+    }
+
+    pub async fn list_due_departure_checks(
+        &self,
+        now: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<DueDepartureCheck>, StoreError> {
+        if limit <= 0 {
+            return Err(StoreError::InvalidData(
+                "departure check batch size must be > 0".to_string(),
+            ));
+        }
+
+        let rows = sqlx::query(
+            "SELECT
+                user_id,
+                enabled,
+                time_zone,
+                check_local_time_minutes,
+                travel_minutes,
+                buffer_minutes,
+                next_check_at
+             FROM departure_alert_preferences
+             WHERE enabled = TRUE
+               AND next_check_at <= $1
+             ORDER BY next_check_at ASC
+             LIMIT $2",
+        )
+        .bind(now)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(DueDepartureCheck {
+                    user_id: row.try_get("user_id")?,
+                    settings: departure_settings_from_row(row)?,
+                    next_check_at: row.try_get("next_check_at")?,
+                })
+            })
+            .collect()
+    }
+
+    /// Moves a due check to its next slot. Only the caller that observed `expected` wins,
+    /// which makes this the claim step when several workers see the same due row.
+    pub async fn advance_departure_check(
+        &self,
+        user_id: Uuid,
+        expected: DateTime<Utc>,
+        next_check_at: DateTime<Utc>,
+    ) -> Result<bool, StoreError> {
+        let result = sqlx::query(
+            "UPDATE departure_alert_preferences
+             SET next_check_at = $3
+             WHERE user_id = $1
+               AND enabled = TRUE
+               AND next_check_at = $2",
+        )
+        .bind(user_id)
+        .bind(expected)
+        .bind(next_check_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    pub async fn get_departure_alert_delivery(
+        &self,
+        user_id: Uuid,
+        local_date: NaiveDate,
+    ) -> Result<Option<String>, StoreError> {
+        let fingerprint = sqlx::query_scalar(
+            "SELECT meeting_fingerprint
+             FROM departure_alert_deliveries
+             WHERE user_id = $1
+               AND local_date = $2",
+        )
+        .bind(user_id)
+        .bind(local_date)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(fingerprint)
+    }
+
+    pub async fn record_departure_alert_delivery(
+        &self,
+        user_id: Uuid,
+        local_date: NaiveDate,
+        meeting_fingerprint: &str,
+        leave_by: DateTime<Utc>,
+    ) -> Result<(), StoreError> {
+        sqlx::query(
+            "INSERT INTO departure_alert_deliveries (
+                user_id,
+                local_date,
+                meeting_fingerprint,
+                leave_by
+             )
+             VALUES ($1, $2, $3, $4)
+             ON CONFLICT (user_id, local_date)
+             DO UPDATE SET
+               meeting_fingerprint = EXCLUDED.meeting_fingerprint,
+               leave_by = EXCLUDED.leave_by,
+               delivered_at = NOW()",
+        )
+        .bind(user_id)
+        .bind(local_date)
+        .bind(meeting_fingerprint)
+        .bind(leave_by)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
+
+fn normalized_departure_settings(
+    settings: &DepartureAlertSettings,
+) -> Result<DepartureAlertSettings, StoreError> {
+    let time_zone = normalize_time_zone(&settings.time_zone)
+        .ok_or_else(|| StoreError::InvalidData("departure alert time_zone is invalid".into()))?;
+    if settings.check_local_time_minutes >= 24 * 60 {
+        return Err(StoreError::InvalidData(
+            "departure alert check time is out of range".to_string(),
+        ));
+    }
+    validate_departure_minutes(settings.travel_minutes, settings.buffer_minutes)
+        .map_err(|message| StoreError::InvalidData(message.to_string()))?;
+
+    Ok(DepartureAlertSettings {
+        time_zone,
+        ..settings.clone()
+    })
+}
+
+fn departure_alert_preferences_from_row(
+    row: &PgRow,
+) -> Result<DepartureAlertPreferencesRecord, StoreError> {
+    Ok(DepartureAlertPreferencesRecord {
+        settings: departure_settings_from_row(row)?,
+        has_home_location: row.try_get("has_home_location")?,
+        next_check_at: row.try_get("next_check_at")?,
+        version: row.try_get("version")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
+
+fn departure_settings_from_row(row: &PgRow) -> Result<DepartureAlertSettings, StoreError> {
+    Ok(DepartureAlertSettings {
+        enabled: row.try_get("enabled")?,
+        time_zone: row.try_get("time_zone")?,
+        check_local_time_minutes: minutes_from_row(row, "check_local_time_minutes")?,
+        travel_minutes: minutes_from_row(row, "travel_minutes")?,
+        buffer_minutes: minutes_from_row(row, "buffer_minutes")?,
+    })
+}
+
+fn minutes_from_row(row: &PgRow, column: &str) -> Result<u16, StoreError> {
+    let value: i32 = row.try_get(column)?;
+    u16::try_from(value)
+        .map_err(|_| StoreError::InvalidData(format!("invalid {column} persisted: {value}")))
+}
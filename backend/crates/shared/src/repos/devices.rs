@@ -1,10 +1,56 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use sqlx::Row;
+use sqlx::postgres::PgRow;
 use uuid::Uuid;
 
 use crate::models::ApnsEnvironment;
 
+use super::read_cache::ReadCacheScope;
 use super::{DeviceRegistration, Store, StoreError};
 
+#[derive(Debug, Clone)]
+pub struct DeviceRecord {
+    pub device_id: String,
+    pub environment: ApnsEnvironment,
+    pub app_bundle_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub last_delivery_at: Option<DateTime<Utc>>,
+    pub last_seen_at: DateTime<Utc>,
+    pub disabled_at: Option<DateTime<Utc>>,
+    pub disabled_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct DeviceFailureScore {
+    pub consecutive_failures: i32,
+    /// True only for the failure that crossed the threshold.
+    pub newly_disabled: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct StaleDeviceRecord {
+    pub user_id: Uuid,
+    pub device_id: String,
+    pub last_seen_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceNotificationKey {
+    pub key_id: String,
+    pub algorithm: String,
+    pub public_key: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct DeviceNotificationKeyRotation {
+    pub key_id: String,
+    pub rotated_at: DateTime<Utc>,
+    pub previous_key_id: Option<String>,
+    pub previous_key_expires_at: Option<DateTime<Utc>>,
+}
+
 impl Store {
     pub async fn register_device(
         &self,
@@ -12,8 +58,8 @@ impl Store {
         device_id: &str,
         apns_token: &str,
         environment: &ApnsEnvironment,
-        notification_key_algorithm: Option<&str>,
-        notification_public_key: Option<&str>,
+        app_bundle_id: Option<&str>,
+        notification_key: Option<&DeviceNotificationKey>,
     ) -> Result<(), StoreError> {
         self.ensure_user(user_id).await?;
 
@@ -23,31 +69,141 @@ impl Store {
                 device_identifier,
                 apns_token_ciphertext,
                 environment,
+                notification_key_id,
                 notification_key_algorithm,
-                notification_public_key_ciphertext
+                notification_public_key_ciphertext,
+                data_key_id,
+                app_bundle_id
+             )
+             VALUES (
+               $1, $2, pgp_sym_encrypt($3, $8), $4, $5, $6, pgp_sym_encrypt($7, $8), $9, $12
              )
-             VALUES ($1, $2, pgp_sym_encrypt($3, $7), $4, $5, pgp_sym_encrypt($6, $7))
              ON CONFLICT (user_id, device_identifier)
              DO UPDATE SET
-               apns_token_ciphertext = pgp_sym_encrypt($3, $7),
+               apns_token_ciphertext = pgp_sym_encrypt($3, $8),
                environment = EXCLUDED.environment,
+               app_bundle_id = EXCLUDED.app_bundle_id,
+               notification_key_id = EXCLUDED.notification_key_id,
                notification_key_algorithm = EXCLUDED.notification_key_algorithm,
                notification_public_key_ciphertext = EXCLUDED.notification_public_key_ciphertext,
-               updated_at = NOW()",
+               previous_notification_public_key_ciphertext = pgp_sym_encrypt(
+                 pgp_sym_decrypt(
+                   devices.previous_notification_public_key_ciphertext,
+                   alfred_data_key(devices.data_key_id, $10, $11)
+                 ),
+                 $8
+               ),
+               data_key_id = EXCLUDED.data_key_id,
+               updated_at = NOW(),
+               last_seen_at = NOW(),
+               consecutive_failures = 0,
+               disabled_at = NULL,
+               disabled_reason = NULL",
         )
         .bind(user_id)
         .bind(device_id)
         .bind(apns_token)
         .bind(apns_environment_str(environment))
-        .bind(notification_key_algorithm)
-        .bind(notification_public_key)
+        .bind(notification_key.map(|key| key.key_id.as_str()))
+        .bind(notification_key.map(|key| key.algorithm.as_str()))
+        .bind(notification_key.map(|key| key.public_key.as_str()))
         .bind(&self.data_encryption_key)
+        .bind(&self.data_encryption_key_id)
+        .bind(&self.data_encryption_key_ids)
+        .bind(&self.data_encryption_keys)
+        .bind(app_bundle_id)
         .execute(&self.pool)
         .await?;
+        self.invalidate_user_read(ReadCacheScope::RegisteredDevices, user_id)
+            .await;
 
         Ok(())
     }
 
+    /// Replaces the active notification key for a device. The key being replaced stays
+    /// available as the previous key until `previous_key_expires_at` so notifications that
+    /// are already in flight remain decryptable on the device. Re-submitting the active
+    /// key id updates the key material without disturbing the existing overlap window.
+    pub async fn rotate_device_notification_key(
+        &self,
+        user_id: Uuid,
+        device_id: &str,
+        notification_key: &DeviceNotificationKey,
+        previous_key_expires_at: DateTime<Utc>,
+    ) -> Result<Option<DeviceNotificationKeyRotation>, StoreError> {
+        let row = sqlx::query(
+            "UPDATE devices
+             SET
+               previous_notification_key_id = CASE
+                 WHEN notification_key_id IS DISTINCT FROM $3 THEN notification_key_id
+                 ELSE previous_notification_key_id
+               END,
+               previous_notification_key_algorithm = CASE
+                 WHEN notification_key_id IS DISTINCT FROM $3 THEN notification_key_algorithm
+                 ELSE previous_notification_key_algorithm
+               END,
+               previous_notification_public_key_ciphertext = pgp_sym_encrypt(
+                 pgp_sym_decrypt(
+                   CASE
+                     WHEN notification_key_id IS DISTINCT FROM $3
+                       THEN notification_public_key_ciphertext
+                     ELSE previous_notification_public_key_ciphertext
+                   END,
+                   alfred_data_key(data_key_id, $9, $10)
+                 ),
+                 $7
+               ),
+               previous_notification_key_expires_at = CASE
+                 WHEN notification_key_id IS NULL THEN NULL
+                 WHEN notification_key_id IS DISTINCT FROM $3 THEN $6
+                 ELSE previous_notification_key_expires_at
+               END,
+               notification_key_id = $3,
+               notification_key_algorithm = $4,
+               notification_public_key_ciphertext = pgp_sym_encrypt($5, $7),
+               apns_token_ciphertext = pgp_sym_encrypt(
+                 pgp_sym_decrypt(apns_token_ciphertext, alfred_data_key(data_key_id, $9, $10)),
+                 $7
+               ),
+               data_key_id = $8,
+               notification_key_rotated_at = NOW(),
+               updated_at = NOW()
+             WHERE user_id = $1
+               AND device_identifier = $2
+             RETURNING
+               notification_key_id,
+               notification_key_rotated_at,
+               previous_notification_key_id,
+               previous_notification_key_expires_at",
+        )
+        .bind(user_id)
+        .bind(device_id)
+        .bind(&notification_key.key_id)
+        .bind(&notification_key.algorithm)
+        .bind(&notification_key.public_key)
+        .bind(previous_key_expires_at)
+        .bind(&self.data_encryption_key)
+        .bind(&self.data_encryption_key_id)
+        .bind(&self.data_encryption_key_ids)
+        .bind(&self.data_encryption_keys)
+        .fetch_optional(&self.pool)
+        .await?;
+        if row.is_some() {
+            self.invalidate_user_read(ReadCacheScope::RegisteredDevices, user_id)
+                .await;
+        }
+
+        row.map(|row| {
+            Ok(DeviceNotificationKeyRotation {
+                key_id: row.try_get("notification_key_id")?,
+                rotated_at: row.try_get("notification_key_rotated_at")?,
+                previous_key_id: row.try_get("previous_notification_key_id")?,
+                previous_key_expires_at: row.try_get("previous_notification_key_expires_at")?,
+            })
+        })
+        .transpose()
+    }
+
     pub async fn has_registered_device(&self, user_id: Uuid) -> Result<bool, StoreError> {
         self.ensure_user(user_id).await?;
 
@@ -56,6 +212,7 @@ impl Store {
                 SELECT 1
                 FROM devices
                 WHERE user_id = $1
+                  AND disabled_at IS NULL
             )",
         )
         .bind(user_id)
@@ -65,49 +222,337 @@ impl Store {
         Ok(has_device)
     }
 
-    pub async fn list_registered_devices(
+    /// Lists a user's devices without decrypting push tokens or notification keys.
+    pub async fn list_devices(&self, user_id: Uuid) -> Result<Vec<DeviceRecord>, StoreError> {
+        let rows = self
+            .with_read_pool(|pool| async move {
+                sqlx::query(
+                    "SELECT
+                        device_identifier,
+                        environment,
+                        app_bundle_id,
+                        created_at,
+                        updated_at,
+                        last_delivery_at,
+                        last_seen_at,
+                        disabled_at,
+                        disabled_reason
+                     FROM devices
+                     WHERE user_id = $1
+                     ORDER BY created_at ASC, device_identifier ASC",
+                )
+                .bind(user_id)
+                .fetch_all(&pool)
+                .await
+            })
+            .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let environment: String = row.try_get("environment")?;
+                Ok(DeviceRecord {
+                    device_id: row.try_get("device_identifier")?,
+                    environment: parse_apns_environment(&environment)?,
+                    app_bundle_id: row.try_get("app_bundle_id")?,
+                    created_at: row.try_get("created_at")?,
+                    updated_at: row.try_get("updated_at")?,
+                    last_delivery_at: row.try_get("last_delivery_at")?,
+                    last_seen_at: row.try_get("last_seen_at")?,
+                    disabled_at: row.try_get("disabled_at")?,
+                    disabled_reason: row.try_get("disabled_reason")?,
+                })
+            })
+            .collect()
+    }
+
+    pub async fn delete_device(&self, user_id: Uuid, device_id: &str) -> Result<bool, StoreError> {
+        let result =
+            sqlx::query("DELETE FROM devices WHERE user_id = $1 AND device_identifier = $2")
+                .bind(user_id)
+                .bind(device_id)
+                .execute(&self.pool)
+                .await?;
+        if result.rows_affected() > 0 {
+            self.invalidate_user_read(ReadCacheScope::RegisteredDevices, user_id)
+                .await;
+        }
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn record_device_delivery(
         &self,
         user_id: Uuid,
-    ) -> Result<Vec<DeviceRegistration>, StoreError> {
-        self.ensure_user(user_id).await?;
+        device_id: &str,
+    ) -> Result<(), StoreError> {
+        sqlx::query(
+            "UPDATE devices
+             SET last_delivery_at = NOW(),
+                 last_seen_at = NOW(),
+                 consecutive_failures = 0
+             WHERE user_id = $1
+               AND device_identifier = $2",
+        )
+        .bind(user_id)
+        .bind(device_id)
+        .execute(&self.pool)
+        .await?;
 
-        let rows = sqlx::query(
-            "SELECT
-                device_identifier,
-                pgp_sym_decrypt(apns_token_ciphertext, $2) AS apns_token,
-                environment,
-                notification_key_algorithm,
-                pgp_sym_decrypt(notification_public_key_ciphertext, $2) AS notification_public_key
-             FROM devices
-             WHERE user_id = $1",
+        Ok(())
+    }
+
+    /// Counts a delivery the destination rejected. The device is disabled once the
+    /// consecutive count reaches `disable_threshold`, or immediately with `disable_now`.
+    pub async fn record_device_delivery_failure(
+        &self,
+        user_id: Uuid,
+        device_id: &str,
+        error_code: &str,
+        disable_threshold: i32,
+        disable_now: bool,
+    ) -> Result<Option<DeviceFailureScore>, StoreError> {
+        let row = sqlx::query(
+            "UPDATE devices
+             SET consecutive_failures = consecutive_failures + 1,
+                 disabled_at = CASE
+                   WHEN disabled_at IS NULL
+                     AND ($5 OR consecutive_failures + 1 >= $4)
+                     THEN NOW()
+                   ELSE disabled_at
+                 END,
+                 disabled_reason = CASE
+                   WHEN disabled_at IS NULL
+                     AND ($5 OR consecutive_failures + 1 >= $4)
+                     THEN $3
+                   ELSE disabled_reason
+                 END
+             WHERE user_id = $1
+               AND device_identifier = $2
+             RETURNING consecutive_failures, disabled_at IS NOT NULL AND disabled_at = NOW()
+               AS newly_disabled",
         )
         .bind(user_id)
-        .bind(&self.data_encryption_key)
+        .bind(device_id)
+        .bind(error_code)
+        .bind(disable_threshold)
+        .bind(disable_now)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let score = row
+            .map(|row| {
+                Ok::<_, StoreError>(DeviceFailureScore {
+                    consecutive_failures: row.try_get("consecutive_failures")?,
+                    newly_disabled: row.try_get("newly_disabled")?,
+                })
+            })
+            .transpose()?;
+        if score.as_ref().is_some_and(|score| score.newly_disabled) {
+            self.invalidate_user_read(ReadCacheScope::RegisteredDevices, user_id)
+                .await;
+        }
+
+        Ok(score)
+    }
+
+    /// Marks the device's one-time backlog summary as sent. Returns `false` when it was
+    /// already sent, so concurrent jobs deliver at most one summary per device.
+    pub async fn claim_device_backlog_summary(
+        &self,
+        user_id: Uuid,
+        device_id: &str,
+    ) -> Result<bool, StoreError> {
+        let result = sqlx::query(
+            "UPDATE devices
+             SET backlog_summary_sent_at = NOW()
+             WHERE user_id = $1
+               AND device_identifier = $2
+               AND backlog_summary_sent_at IS NULL",
+        )
+        .bind(user_id)
+        .bind(device_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn enable_device(&self, user_id: Uuid, device_id: &str) -> Result<bool, StoreError> {
+        let result = sqlx::query(
+            "UPDATE devices
+             SET consecutive_failures = 0,
+                 disabled_at = NULL,
+                 disabled_reason = NULL,
+                 updated_at = NOW()
+             WHERE user_id = $1
+               AND device_identifier = $2",
+        )
+        .bind(user_id)
+        .bind(device_id)
+        .execute(&self.pool)
+        .await?;
+        if result.rows_affected() > 0 {
+            self.invalidate_user_read(ReadCacheScope::RegisteredDevices, user_id)
+                .await;
+        }
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Removes devices that have neither re-registered nor accepted a push since
+    /// `seen_before`, returning what was removed so callers can audit per user.
+    pub async fn purge_stale_devices_batch(
+        &self,
+        seen_before: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<StaleDeviceRecord>, StoreError> {
+        if limit <= 0 {
+            return Err(StoreError::InvalidData(
+                "stale device purge limit must be > 0".to_string(),
+            ));
+        }
+
+        let rows = sqlx::query(
+            "WITH stale AS (
+                SELECT id
+                FROM devices
+                WHERE last_seen_at < $1
+                ORDER BY last_seen_at ASC, id ASC
+                LIMIT $2
+                FOR UPDATE SKIP LOCKED
+             )
+             DELETE FROM devices
+             USING stale
+             WHERE devices.id = stale.id
+             RETURNING devices.user_id, devices.device_identifier, devices.last_seen_at",
+        )
+        .bind(seen_before)
+        .bind(limit)
         .fetch_all(&self.pool)
         .await?;
 
+        let purged = rows
+            .into_iter()
+            .map(|row| {
+                Ok(StaleDeviceRecord {
+                    user_id: row.try_get("user_id")?,
+                    device_id: row.try_get("device_identifier")?,
+                    last_seen_at: row.try_get("last_seen_at")?,
+                })
+            })
+            .collect::<Result<Vec<_>, StoreError>>()?;
+        let mut user_ids = purged
+            .iter()
+            .map(|device| device.user_id)
+            .collect::<Vec<_>>();
+        user_ids.sort_unstable();
+        user_ids.dedup();
+        for user_id in user_ids {
+            self.invalidate_user_read(ReadCacheScope::RegisteredDevices, user_id)
+                .await;
+        }
+
+        Ok(purged)
+    }
+
+    pub async fn list_registered_devices(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<DeviceRegistration>, StoreError> {
+        self.cached_user_read(ReadCacheScope::RegisteredDevices, user_id, || {
+            self.load_registered_devices(user_id)
+        })
+        .await
+    }
+
+    async fn load_registered_devices(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<DeviceRegistration>, StoreError> {
+        self.ensure_user(user_id).await?;
+
+        let key_ids = &self.data_encryption_key_ids;
+        let keys = &self.data_encryption_keys;
+        let rows = self
+            .with_read_pool(|pool| async move {
+                sqlx::query(
+                    "SELECT
+                        device_identifier,
+                        pgp_sym_decrypt(
+                          apns_token_ciphertext,
+                          alfred_data_key(data_key_id, $2, $3)
+                        ) AS apns_token,
+                        environment,
+                        app_bundle_id,
+                        created_at,
+                        notification_key_id,
+                        notification_key_algorithm,
+                        pgp_sym_decrypt(
+                          notification_public_key_ciphertext,
+                          alfred_data_key(data_key_id, $2, $3)
+                        ) AS notification_public_key,
+                        CASE
+                          WHEN previous_notification_key_expires_at > NOW()
+                            THEN previous_notification_key_id
+                        END AS previous_notification_key_id,
+                        previous_notification_key_algorithm,
+                        pgp_sym_decrypt(
+                          previous_notification_public_key_ciphertext,
+                          alfred_data_key(data_key_id, $2, $3)
+                        ) AS previous_notification_public_key
+                     FROM devices
+                     WHERE user_id = $1
+                       AND disabled_at IS NULL",
+                )
+                .bind(user_id)
+                .bind(key_ids)
+                .bind(keys)
+                .fetch_all(&pool)
+                .await
+            })
+            .await?;
+
         rows.into_iter()
             .map(|row| {
                 let device_id: String = row.try_get("device_identifier")?;
                 let apns_token: String = row.try_get("apns_token")?;
                 let environment: String = row.try_get("environment")?;
-                let notification_key_algorithm: Option<String> =
-                    row.try_get("notification_key_algorithm")?;
-                let notification_public_key: Option<String> =
-                    row.try_get("notification_public_key")?;
 
                 Ok(DeviceRegistration {
                     device_id,
                     apns_token,
                     environment: parse_apns_environment(&environment)?,
-                    notification_key_algorithm,
-                    notification_public_key,
+                    app_bundle_id: row.try_get("app_bundle_id")?,
+                    registered_at: row.try_get("created_at")?,
+                    notification_key: notification_key_from_row(&row, "")?,
+                    previous_notification_key: notification_key_from_row(&row, "previous_")?,
                 })
             })
             .collect()
     }
 }
 
+fn notification_key_from_row(
+    row: &PgRow,
+    column_prefix: &str,
+) -> Result<Option<DeviceNotificationKey>, StoreError> {
+    let key_id: Option<String> =
+        row.try_get(format!("{column_prefix}notification_key_id").as_str())?;
+    let algorithm: Option<String> =
+        row.try_get(format!("{column_prefix}notification_key_algorithm").as_str())?;
+    let public_key: Option<String> =
+        row.try_get(format!("{column_prefix}notification_public_key").as_str())?;
+
+    Ok(match (key_id, algorithm, public_key) {
+        (Some(key_id), Some(algorithm), Some(public_key)) => Some(DeviceNotificationKey {
+            key_id,
+            algorithm,
+            public_key,
+        }),
+        _ => None,
+    })
+}
+
 fn apns_environment_str(value: &ApnsEnvironment) -> &'static str {
     match value {
         ApnsEnvironment::Sandbox => "sandbox",
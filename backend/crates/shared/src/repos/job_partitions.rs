@@ -0,0 +1,234 @@
+use chrono::{DateTime, Datelike, Months, NaiveDate, Utc};
+
+use super::{Store, StoreError};
+
+#[derive(Debug, Clone, Default)]
+pub struct FinishedJobPruneCounts {
+    pub dropped_partitions: Vec<String>,
+    pub purged_default_rows: u64,
+    pub purged_delivery_rows: u64,
+}
+
+const FINISHED_JOB_PARTITION_PREFIX: &str = "jobs_finished_p";
+
+impl Store {
+    /// Creates monthly `jobs_finished` partitions from the month of `now` through
+    /// `months_ahead` later months. Rows that already landed in the default partition for a
+    /// new month are moved into it. Returns the names of partitions created.
+    pub async fn ensure_finished_job_partitions(
+        &self,
+        now: DateTime<Utc>,
+        months_ahead: u32,
+    ) -> Result<Vec<String>, StoreError> {
+        let current_month = month_start(now.date_naive());
+        let mut created = Vec::new();
+
+        for offset in 0..=months_ahead {
+            let start = current_month + Months::new(offset);
+            let end = start + Months::new(1);
+            let name = finished_partition_name(start);
+
+            let exists: bool = sqlx::query_scalar("SELECT to_regclass($1) IS NOT NULL")
+                .bind(&name)
+                .fetch_one(&self.pool)
+                .await?;
+            if exists {
+                continue;
+            }
+
+            let start_at = start.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+            let end_at = end.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+            let mut tx = self.pool.begin().await?;
+            let stranded: i64 = sqlx::query_scalar(
+                "SELECT COUNT(*)::bigint
+                 FROM jobs_finished_default
+                 WHERE updated_at >= $1
+                   AND updated_at < $2",
+            )
+            .bind(start_at)
+            .bind(end_at)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            // DDL cannot take bind parameters; the name and bounds are generated here.
+            let create_partition = format!(
+                "CREATE TABLE {name} PARTITION OF jobs_finished
+                 FOR VALUES FROM ('{}') TO ('{}')",
+                start_at.to_rfc3339(),
+                end_at.to_rfc3339(),
+            );
+            if stranded == 0 {
+                sqlx::query(&create_partition).execute(&mut *tx).await?;
+            } else {
+                sqlx::query("ALTER TABLE jobs_finished DETACH PARTITION jobs_finished_default")
+                    .execute(&mut *tx)
+                    .await?;
+                sqlx::query(&create_partition).execute(&mut *tx).await?;
+                sqlx::query(
+                    "WITH moved AS (
+                        DELETE FROM jobs_finished_default
+                        WHERE updated_at >= $1
+                          AND updated_at < $2
+                        RETURNING *
+                     )
+                     INSERT INTO jobs_finished
+                     SELECT * FROM moved",
+                )
+                .bind(start_at)
+                .bind(end_at)
+                .execute(&mut *tx)
+                .await?;
+                sqlx::query(
+                    "ALTER TABLE jobs_finished ATTACH PARTITION jobs_finished_default DEFAULT",
+                )
+                .execute(&mut *tx)
+                .await?;
+            }
+            tx.commit().await?;
+            created.push(name);
+        }
+
+        Ok(created)
+    }
+
+    /// Drops finished-job partitions whose whole month ends at or before `cutoff`, and deletes
+    /// older rows that fell into the default partition along with their delivery records. Rows
+    /// that reference a pruned job are removed with it (automation runs keep their history and
+    /// only lose the job link).
+    pub async fn prune_finished_job_partitions(
+        &self,
+        cutoff: DateTime<Utc>,
+    ) -> Result<FinishedJobPruneCounts, StoreError> {
+        let partitions: Vec<String> = sqlx::query_scalar(
+            "SELECT c.relname::text
+             FROM pg_inherits i
+             INNER JOIN pg_class c ON c.oid = i.inhrelid
+             WHERE i.inhparent = 'jobs_finished'::regclass
+             ORDER BY c.relname",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut counts = FinishedJobPruneCounts::default();
+        for name in partitions {
+            let Some(start) = finished_partition_month(&name) else {
+                continue;
+            };
+            let end_at = (start + Months::new(1))
+                .and_hms_opt(0, 0, 0)
+                .unwrap_or_default()
+                .and_utc();
+            if end_at > cutoff {
+                continue;
+            }
+
+            let mut tx = self.pool.begin().await?;
+            purge_finished_job_dependents(&mut tx, &name, cutoff).await?;
+            sqlx::query(&format!(
+                "ALTER TABLE jobs_finished DETACH PARTITION {name}"
+            ))
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query(&format!("DROP TABLE {name}"))
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+            counts.dropped_partitions.push(name);
+        }
+
+        let mut tx = self.pool.begin().await?;
+        purge_finished_job_dependents(&mut tx, "jobs_finished_default", cutoff).await?;
+        counts.purged_default_rows = sqlx::query(
+            "DELETE FROM jobs_finished_default
+             WHERE updated_at < $1",
+        )
+        .bind(cutoff)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        tx.commit().await?;
+
+        counts.purged_delivery_rows = sqlx::query(
+            "DELETE FROM notification_deliveries
+             WHERE created_at < $1",
+        )
+        .bind(cutoff)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        Ok(counts)
+    }
+}
+
+/// Removes rows that point at finished jobs in `partition` last updated before `cutoff`. The
+/// partitioned `jobs` table cannot be the target of a foreign key, so nothing cascades when a
+/// partition is dropped or its rows are deleted.
+async fn purge_finished_job_dependents(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    partition: &str,
+    cutoff: DateTime<Utc>,
+) -> Result<(), StoreError> {
+    // The partition name is either generated here or read back from pg_inherits.
+    let pruned_ids = format!("SELECT id FROM {partition} WHERE updated_at < $1");
+    for table in [
+        "job_idempotency_keys",
+        "dead_letter_jobs",
+        "outbound_action_idempotency",
+    ] {
+        sqlx::query(&format!(
+            "DELETE FROM {table} WHERE job_id IN ({pruned_ids})"
+        ))
+        .bind(cutoff)
+        .execute(&mut **tx)
+        .await?;
+    }
+    sqlx::query(&format!(
+        "UPDATE automation_runs SET job_id = NULL WHERE job_id IN ({pruned_ids})"
+    ))
+    .bind(cutoff)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+fn month_start(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).unwrap_or(date)
+}
+
+fn finished_partition_name(month: NaiveDate) -> String {
+    format!("{FINISHED_JOB_PARTITION_PREFIX}{}", month.format("%Y%m"))
+}
+
+fn finished_partition_month(name: &str) -> Option<NaiveDate> {
+    let suffix = name.strip_prefix(FINISHED_JOB_PARTITION_PREFIX)?;
+    if suffix.len() != 6 || !suffix.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    let year = suffix[..4].parse().ok()?;
+    let month = suffix[4..].parse().ok()?;
+    NaiveDate::from_ymd_opt(year, month, 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::{finished_partition_month, finished_partition_name};
+
+    #[test]
+    fn finished_partition_names_round_trip() {
+        let month = NaiveDate::from_ymd_opt(2026, 3, 1).expect("valid date");
+        let name = finished_partition_name(month);
+        assert_eq!(name, "jobs_finished_p202603");
+        assert_eq!(finished_partition_month(&name), Some(month));
+    }
+
+    #[test]
+    fn finished_partition_month_ignores_other_partitions() {
+        assert_eq!(finished_partition_month("jobs_finished_default"), None);
+        assert_eq!(finished_partition_month("jobs_finished_p202613"), None);
+        assert_eq!(finished_partition_month("jobs_finished_p2026"), None);
+    }
+}
@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+use uuid::Uuid;
+
+use super::{JobState, JobType, Store, StoreError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationDeliveryOutcome {
+    Delivered,
+    Failed,
+    /// Held back because the device registered after the notification was due.
+    SuppressedBacklog,
+}
+
+impl NotificationDeliveryOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Delivered => "DELIVERED",
+            Self::Failed => "FAILED",
+            Self::SuppressedBacklog => "SUPPRESSED_BACKLOG",
+        }
+    }
+
+    fn from_db(value: &str) -> Result<Self, StoreError> {
+        match value {
+            "DELIVERED" => Ok(Self::Delivered),
+            "FAILED" => Ok(Self::Failed),
+            "SUPPRESSED_BACKLOG" => Ok(Self::SuppressedBacklog),
+            _ => Err(StoreError::InvalidData(format!(
+                "unknown notification delivery outcome persisted: {value}"
+            ))),
+        }
+    }
+}
+
+/// One push attempt for a job, as recorded by the worker.
+#[derive(Debug, Clone)]
+pub struct NewNotificationDelivery<'a> {
+    pub user_id: Uuid,
+    pub job_id: Uuid,
+    pub job_attempt: i32,
+    pub enclave_request_id: Option<&'a str>,
+    pub device_id: &'a str,
+    pub outcome: NotificationDeliveryOutcome,
+    pub push_payload_mode: Option<&'a str>,
+    pub error_code: Option<&'a str>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NotificationDeliveryRecord {
+    pub device_id: String,
+    pub job_attempt: i32,
+    pub enclave_request_id: Option<String>,
+    pub outcome: NotificationDeliveryOutcome,
+    pub push_payload_mode: Option<String>,
+    pub error_code: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A job together with the enclave request that last ran it and every push it produced.
+#[derive(Debug, Clone)]
+pub struct JobExecutionTrail {
+    pub job_id: Uuid,
+    pub job_type: JobType,
+    pub state: JobState,
+    pub due_at: DateTime<Utc>,
+    pub attempts: i32,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub last_error_code: Option<String>,
+    pub enclave_request_id: Option<String>,
+    pub deliveries: Vec<NotificationDeliveryRecord>,
+}
+
+impl Store {
+    /// Links the running job to the enclave RPC that served its current attempt; a retry
+    /// overwrites it with the id of the next attempt's RPC.
+    pub async fn record_job_enclave_request(
+        &self,
+        job_id: Uuid,
+        enclave_request_id: &str,
+    ) -> Result<(), StoreError> {
+        sqlx::query(
+            "UPDATE jobs
+             SET enclave_request_id = $2
+             WHERE id = $1
+               AND state = 'RUNNING'",
+        )
+        .bind(job_id)
+        .bind(enclave_request_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn record_notification_delivery(
+        &self,
+        delivery: &NewNotificationDelivery<'_>,
+    ) -> Result<(), StoreError> {
+        sqlx::query(
+            "INSERT INTO notification_deliveries (
+               user_id,
+               job_id,
+               job_attempt,
+               enclave_request_id,
+               device_id,
+               outcome,
+               push_payload_mode,
+               error_code
+             )
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+        )
+        .bind(delivery.user_id)
+        .bind(delivery.job_id)
+        .bind(delivery.job_attempt)
+        .bind(delivery.enclave_request_id)
+        .bind(delivery.device_id)
+        .bind(delivery.outcome.as_str())
+        .bind(delivery.push_payload_mode)
+        .bind(delivery.error_code)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Assembles job -> enclave request -> per-device delivery for the user's jobs due in
+    /// `[due_from, due_until)`, oldest first. Finished jobs are only available within the
+    /// job history retention window.
+    pub async fn list_job_execution_trails(
+        &self,
+        user_id: Uuid,
+        due_from: DateTime<Utc>,
+        due_until: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<JobExecutionTrail>, StoreError> {
+        if limit <= 0 {
+            return Err(StoreError::InvalidData(
+                "job execution trail limit must be > 0".to_string(),
+            ));
+        }
+
+        let job_rows = sqlx::query(
+            "SELECT
+               id,
+               type,
+               state,
+               due_at,
+               attempts,
+               started_at,
+               finished_at,
+               last_error_code,
+               enclave_request_id
+             FROM jobs
+             WHERE user_id = $1
+               AND due_at >= $2
+               AND due_at < $3
+             ORDER BY due_at ASC, id ASC
+             LIMIT $4",
+        )
+        .bind(user_id)
+        .bind(due_from)
+        .bind(due_until)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut trails = Vec::with_capacity(job_rows.len());
+        for row in job_rows {
+            let job_type: String = row.try_get("type")?;
+            let state: String = row.try_get("state")?;
+            trails.push(JobExecutionTrail {
+                job_id: row.try_get("id")?,
+                job_type: JobType::from_db(&job_type)?,
+                state: JobState::from_db(&state)?,
+                due_at: row.try_get("due_at")?,
+                attempts: row.try_get("attempts")?,
+                started_at: row.try_get("started_at")?,
+                finished_at: row.try_get("finished_at")?,
+                last_error_code: row.try_get("last_error_code")?,
+                enclave_request_id: row.try_get("enclave_request_id")?,
+                deliveries: Vec::new(),
+            });
+        }
+        if trails.is_empty() {
+            return Ok(trails);
+        }
+
+        let job_ids = trails.iter().map(|trail| trail.job_id).collect::<Vec<_>>();
+        let delivery_rows = sqlx::query(
+            "SELECT
+               job_id,
+               device_id,
+               job_attempt,
+               enclave_request_id,
+               outcome,
+               push_payload_mode,
+               error_code,
+               created_at
+             FROM notification_deliveries
+             WHERE user_id = $1
+               AND job_id = ANY($2)
+             ORDER BY created_at ASC, id ASC",
+        )
+        .bind(user_id)
+        .bind(&job_ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut deliveries_by_job: HashMap<Uuid, Vec<NotificationDeliveryRecord>> = HashMap::new();
+        for row in delivery_rows {
+            let outcome: String = row.try_get("outcome")?;
+            deliveries_by_job
+                .entry(row.try_get("job_id")?)
+                .or_default()
+                .push(NotificationDeliveryRecord {
+                    device_id: row.try_get("device_id")?,
+                    job_attempt: row.try_get("job_attempt")?,
+                    enclave_request_id: row.try_get("enclave_request_id")?,
+                    outcome: NotificationDeliveryOutcome::from_db(&outcome)?,
+                    push_payload_mode: row.try_get("push_payload_mode")?,
+                    error_code: row.try_get("error_code")?,
+                    created_at: row.try_get("created_at")?,
+                });
+        }
+        for trail in &mut trails {
+            trail.deliveries = deliveries_by_job.remove(&trail.job_id).unwrap_or_default();
+        }
+
+        Ok(trails)
+    }
+}
@@ -2,12 +2,67 @@ use base64::{
     Engine as _, engine::general_purpose::STANDARD, engine::general_purpose::URL_SAFE_NO_PAD,
 };
 use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use sqlx::Row;
 use uuid::Uuid;
 
 use super::{ClaimedJob, JobType, Store, StoreError};
 
+/// One user's share of the job queue. `due` is the subset of `pending` already past `due_at`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JobQueueDepth {
+    pub pending: i64,
+    pub due: i64,
+    pub running: i64,
+    pub dead_lettered: i64,
+    pub oldest_due_at: Option<DateTime<Utc>>,
+}
+
+/// Millisecond durations of one job attempt. Claim time covers the whole batch claim the job
+/// arrived in; stages a job type does not run stay `None`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobStageTimings {
+    pub claim_ms: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fetch_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generate_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deliver_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobState {
+    Pending,
+    Running,
+    Done,
+    Failed,
+}
+
+impl JobState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "PENDING",
+            Self::Running => "RUNNING",
+            Self::Done => "DONE",
+            Self::Failed => "FAILED",
+        }
+    }
+
+    pub(super) fn from_db(value: &str) -> Result<Self, StoreError> {
+        match value {
+            "PENDING" => Ok(Self::Pending),
+            "RUNNING" => Ok(Self::Running),
+            "DONE" => Ok(Self::Done),
+            "FAILED" => Ok(Self::Failed),
+            _ => Err(StoreError::InvalidData(format!(
+                "unknown job state persisted: {value}"
+            ))),
+        }
+    }
+}
+
 impl Store {
     pub async fn enqueue_job(
         &self,
@@ -38,9 +93,52 @@ impl Store {
     ) -> Result<Uuid, StoreError> {
         self.ensure_user(user_id).await?;
 
+        let mut tx = self.pool.begin().await?;
+        // Claiming the key row first serializes enqueues for the same key: a second caller
+        // waits here until the first commits and then reuses its job id.
         let job_id: Uuid = sqlx::query_scalar(
-            "INSERT INTO jobs (user_id, type, due_at, state, payload_ciphertext, idempotency_key)
-             VALUES (
+            "INSERT INTO job_idempotency_keys (user_id, type, idempotency_key, job_id)
+             VALUES ($1, $2, $3, gen_random_uuid())
+             ON CONFLICT (user_id, type, idempotency_key)
+             DO UPDATE SET job_id = job_idempotency_keys.job_id
+             RETURNING job_id",
+        )
+        .bind(user_id)
+        .bind(job_type.as_str())
+        .bind(idempotency_key)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        // A job keeps its id as it moves between partitions, so the id alone says whether it
+        // is still active (merge into it), finished (leave it alone), or pruned (enqueue anew).
+        let merged = sqlx::query(
+            "WITH merged AS (
+                UPDATE jobs_active
+                SET due_at = LEAST(due_at, $3),
+                    payload_ciphertext = CASE
+                      WHEN $4::bytea IS NULL THEN payload_ciphertext
+                      ELSE pgp_sym_encrypt(encode($4, 'base64'), $6)
+                    END,
+                    data_key_id = CASE
+                      WHEN $4::bytea IS NULL THEN data_key_id
+                      ELSE $7
+                    END,
+                    updated_at = NOW()
+                WHERE id = $8
+                RETURNING id
+             )
+             INSERT INTO jobs_active (
+               id,
+               user_id,
+               type,
+               due_at,
+               state,
+               payload_ciphertext,
+               idempotency_key,
+               data_key_id
+             )
+             SELECT
+               $8,
                $1,
                $2,
                $3,
@@ -49,14 +147,10 @@ impl Store {
                  WHEN $4::bytea IS NULL THEN NULL
                  ELSE pgp_sym_encrypt(encode($4, 'base64'), $6)
                END,
-               $5
-             )
-             ON CONFLICT (user_id, type, idempotency_key)
-             DO UPDATE SET
-               due_at = LEAST(jobs.due_at, EXCLUDED.due_at),
-               payload_ciphertext = COALESCE(EXCLUDED.payload_ciphertext, jobs.payload_ciphertext),
-               updated_at = NOW()
-             RETURNING id",
+               $5,
+               $7
+             WHERE NOT EXISTS (SELECT 1 FROM merged)
+               AND NOT EXISTS (SELECT 1 FROM jobs WHERE id = $8)",
         )
         .bind(user_id)
         .bind(job_type.as_str())
@@ -64,8 +158,20 @@ impl Store {
         .bind(payload_ciphertext)
         .bind(idempotency_key)
         .bind(&self.data_encryption_key)
-        .fetch_one(&self.pool)
-        .await?;
+        .bind(&self.data_encryption_key_id)
+        .bind(job_id)
+        .execute(&mut *tx)
+        .await;
+        match merged {
+            Ok(_) => {}
+            // The job finished between the lookup and the merge, so Postgres moved it out of
+            // jobs_active under us. A finished job is not re-run.
+            Err(sqlx::Error::Database(err)) if err.code().as_deref() == Some("40001") => {
+                return Ok(job_id);
+            }
+            Err(err) => return Err(err.into()),
+        }
+        tx.commit().await?;
 
         Ok(job_id)
     }
@@ -123,6 +229,7 @@ impl Store {
                   idempotency_key,
                   attempts,
                   payload_ciphertext,
+                  data_key_id,
                   state
              )
              INSERT INTO dead_letter_jobs (
@@ -133,7 +240,8 @@ impl Store {
                attempts,
                reason_code,
                reason_message,
-               payload_ciphertext
+               payload_ciphertext,
+               data_key_id
              )
              SELECT
                id,
@@ -143,7 +251,8 @@ impl Store {
                attempts,
                'LEASE_EXPIRED_MAX_ATTEMPTS',
                'job lease expired and retry limit was reached',
-               payload_ciphertext
+               payload_ciphertext,
+               data_key_id
              FROM expired
              WHERE state = 'FAILED'
              ON CONFLICT (job_id)
@@ -210,7 +319,10 @@ impl Store {
                   j.due_at,
                   CASE
                     WHEN j.payload_ciphertext IS NULL THEN NULL
-                    ELSE pgp_sym_decrypt(j.payload_ciphertext, $6)
+                    ELSE pgp_sym_decrypt(
+                      j.payload_ciphertext,
+                      alfred_data_key(j.data_key_id, $6, $7)
+                    )
                   END AS payload_encoded,
                   j.attempts,
                   j.max_attempts,
@@ -233,7 +345,8 @@ impl Store {
         .bind(max_jobs)
         .bind(worker_id)
         .bind(lease_until)
-        .bind(&self.data_encryption_key)
+        .bind(&self.data_encryption_key_ids)
+        .bind(&self.data_encryption_keys)
         .fetch_all(&self.pool)
         .await?;
 
@@ -262,6 +375,39 @@ impl Store {
         Ok(result.rows_affected() > 0)
     }
 
+    /// Stores timing for the attempt that just settled. Runs after the state change, so it
+    /// matches the job in whichever partition it landed; a job re-leased by another worker in
+    /// the meantime is left alone.
+    pub async fn record_job_stage_timings(
+        &self,
+        job_id: Uuid,
+        worker_id: Uuid,
+        started_at: DateTime<Utc>,
+        finished_at: DateTime<Utc>,
+        timings: &JobStageTimings,
+    ) -> Result<(), StoreError> {
+        let timings = serde_json::to_value(timings)
+            .map_err(|err| StoreError::InvalidData(format!("invalid job timings: {err}")))?;
+
+        sqlx::query(
+            "UPDATE jobs
+             SET started_at = $2,
+                 finished_at = $3,
+                 stage_timings = $4
+             WHERE id = $1
+               AND (lease_owner IS NULL OR lease_owner = $5)",
+        )
+        .bind(job_id)
+        .bind(started_at)
+        .bind(finished_at)
+        .bind(timings)
+        .bind(worker_id.to_string())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
     pub async fn schedule_job_retry(
         &self,
         job_id: Uuid,
@@ -344,7 +490,8 @@ impl Store {
                 attempts,
                 reason_code,
                 reason_message,
-                payload_ciphertext
+                payload_ciphertext,
+                data_key_id
              ) VALUES (
                 $1,
                 $2,
@@ -356,7 +503,8 @@ impl Store {
                 CASE
                   WHEN $8::bytea IS NULL THEN NULL
                   ELSE pgp_sym_encrypt(encode($8, 'base64'), $9)
-                END
+                END,
+                $10
              )
              ON CONFLICT (job_id)
              DO UPDATE SET
@@ -374,6 +522,7 @@ impl Store {
         .bind(reason_message)
         .bind(job.payload_ciphertext.as_deref())
         .bind(&self.data_encryption_key)
+        .bind(&self.data_encryption_key_id)
         .execute(&mut *tx)
         .await?;
 
@@ -381,6 +530,36 @@ impl Store {
         Ok(true)
     }
 
+    /// Fails a user's pending jobs of one type without dead-lettering them. Used when the data
+    /// the jobs read from is withdrawn, so there is nothing worth replaying.
+    pub async fn cancel_pending_jobs(
+        &self,
+        user_id: Uuid,
+        job_type: JobType,
+        reason_code: &str,
+        reason_message: &str,
+    ) -> Result<u64, StoreError> {
+        let result = sqlx::query(
+            "UPDATE jobs
+             SET state = 'FAILED',
+                 next_run_at = NULL,
+                 last_error_code = $3,
+                 last_error_message = $4,
+                 updated_at = NOW()
+             WHERE user_id = $1
+               AND type = $2
+               AND state = 'PENDING'",
+        )
+        .bind(user_id)
+        .bind(job_type.as_str())
+        .bind(reason_code)
+        .bind(reason_message)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
     pub async fn record_outbound_action_idempotency(
         &self,
         user_id: Uuid,
@@ -423,17 +602,91 @@ impl Store {
         Ok(())
     }
 
+    /// Deletes idempotency keys recorded before `created_before`. Keys only need to outlive
+    /// the retry window of the job that recorded them.
+    pub async fn purge_expired_outbound_action_idempotency_batch(
+        &self,
+        created_before: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<u64, StoreError> {
+        if limit <= 0 {
+            return Err(StoreError::InvalidData(
+                "outbound action idempotency purge limit must be > 0".to_string(),
+            ));
+        }
+
+        let result = sqlx::query(
+            "WITH expired AS (
+                SELECT id
+                FROM outbound_action_idempotency
+                WHERE created_at < $1
+                ORDER BY created_at ASC, id ASC
+                LIMIT $2
+                FOR UPDATE SKIP LOCKED
+             )
+             DELETE FROM outbound_action_idempotency idempotency
+             USING expired
+             WHERE idempotency.id = expired.id",
+        )
+        .bind(created_before)
+        .bind(limit)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
     pub async fn count_due_jobs(&self, now: DateTime<Utc>) -> Result<i64, StoreError> {
-        let count: i64 = sqlx::query_scalar(
-            "SELECT COUNT(*)::bigint
-             FROM jobs
-             WHERE state = 'PENDING' AND due_at <= $1",
+        let count: i64 = self
+            .with_read_pool(|pool| async move {
+                sqlx::query_scalar(
+                    "SELECT COUNT(*)::bigint
+                     FROM jobs
+                     WHERE state = 'PENDING' AND due_at <= $1",
+                )
+                .bind(now)
+                .fetch_one(&pool)
+                .await
+            })
+            .await?;
+
+        Ok(count)
+    }
+
+    /// Active and dead-lettered job counts for one user. Dead letters that were already
+    /// replayed are not counted.
+    pub async fn job_queue_depth(
+        &self,
+        user_id: Uuid,
+        now: DateTime<Utc>,
+    ) -> Result<JobQueueDepth, StoreError> {
+        let row = sqlx::query(
+            "SELECT
+               COUNT(*) FILTER (WHERE state = 'PENDING')::bigint AS pending,
+               COUNT(*) FILTER (WHERE state = 'PENDING' AND due_at <= $2)::bigint AS due,
+               COUNT(*) FILTER (WHERE state = 'RUNNING')::bigint AS running,
+               MIN(due_at) FILTER (WHERE state = 'PENDING') AS oldest_due_at,
+               (
+                 SELECT COUNT(*)::bigint
+                 FROM dead_letter_jobs
+                 WHERE user_id = $1
+                   AND replayed_at IS NULL
+               ) AS dead_lettered
+             FROM jobs_active
+             WHERE user_id = $1",
         )
+        .bind(user_id)
         .bind(now)
         .fetch_one(&self.pool)
         .await?;
 
-        Ok(count)
+        Ok(JobQueueDepth {
+            pending: row.try_get("pending")?,
+            due: row.try_get("due")?,
+            running: row.try_get("running")?,
+            dead_lettered: row.try_get("dead_lettered")?,
+            oldest_due_at: row.try_get("oldest_due_at")?,
+        })
     }
 }
 
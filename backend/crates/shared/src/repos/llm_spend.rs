@@ -0,0 +1,51 @@
+use chrono::NaiveDate;
+use uuid::Uuid;
+
+use super::{Store, StoreError};
+
+impl Store {
+    /// Estimated LLM spend recorded for the user in the month starting `month_start`, in
+    /// millionths of a US dollar.
+    pub async fn get_llm_monthly_spend_micros(
+        &self,
+        user_id: Uuid,
+        month_start: NaiveDate,
+    ) -> Result<i64, StoreError> {
+        let spent_micros: Option<i64> = sqlx::query_scalar(
+            "SELECT spent_micros
+             FROM llm_monthly_spend
+             WHERE user_id = $1
+               AND month_start = $2",
+        )
+        .bind(user_id)
+        .bind(month_start)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(spent_micros.unwrap_or(0))
+    }
+
+    /// Adds `delta_micros` to the user's spend for the month and returns the new total.
+    pub async fn add_llm_monthly_spend_micros(
+        &self,
+        user_id: Uuid,
+        month_start: NaiveDate,
+        delta_micros: i64,
+    ) -> Result<i64, StoreError> {
+        let spent_micros: i64 = sqlx::query_scalar(
+            "INSERT INTO llm_monthly_spend (user_id, month_start, spent_micros)
+             VALUES ($1, $2, $3)
+             ON CONFLICT (user_id, month_start)
+             DO UPDATE SET spent_micros = llm_monthly_spend.spent_micros + EXCLUDED.spent_micros,
+                           updated_at = NOW()
+             RETURNING spent_micros",
+        )
+        .bind(user_id)
+        .bind(month_start)
+        .bind(delta_micros.max(0))
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(spent_micros)
+    }
+}
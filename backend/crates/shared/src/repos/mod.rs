@@ -1,26 +1,110 @@
+use std::sync::Arc;
+
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
 use thiserror::Error;
 use uuid::Uuid;
 
-use crate::automation_schedule::{AutomationScheduleSpec, AutomationScheduleType};
+use crate::audit_redaction::AuditRedactionPolicy;
+use crate::audit_signing::AuditEventSigner;
+use crate::automation_schedule::{
+    AutomationCondition, AutomationConditionKind, AutomationScheduleSpec, AutomationScheduleType,
+    AutomationTemplate,
+};
 use crate::models::ApnsEnvironment;
 
 mod assistant_encrypted_sessions;
+mod assistant_ingress_keys;
+mod assistant_request_index;
 mod audit;
+mod audit_chain;
+mod audit_retention;
 mod auth;
 mod automation;
+mod automation_manual_runs;
+mod automation_reports;
 mod automation_runs;
+mod automation_status;
+mod brief_profiles;
+mod component_availability;
+mod connector_health;
 mod connectors;
+mod data_keys;
+mod dead_letter_jobs;
+mod departure_alerts;
 mod devices;
+mod job_partitions;
+mod job_trail;
 mod jobs;
+mod llm_spend;
+mod organizations;
 mod privacy;
+mod privacy_export;
+mod prompt_envelopes;
+mod read_cache;
+mod read_routing;
+mod request_signing;
+mod slo;
+mod support_diagnostics;
+mod user_plans;
 mod users;
+mod webhooks;
 
+pub use assistant_encrypted_sessions::AssistantEncryptedSessionExportRecord;
 pub use assistant_encrypted_sessions::AssistantEncryptedSessionMetadataRecord;
+pub use assistant_encrypted_sessions::AssistantEncryptedSessionMetadataUpdate;
 pub use assistant_encrypted_sessions::AssistantEncryptedSessionRecord;
+pub use assistant_ingress_keys::{
+    AssistantIngressKeyRecord, AssistantIngressKeyStatus, NewAssistantIngressKey,
+};
+pub use assistant_request_index::{
+    AssistantRequestIndexEntry, AssistantRequestIndexRecord, AssistantRequestOutcome,
+    AssistantRequestWindowStats,
+};
+pub use audit::AuditEventSignatureRecord;
+pub use audit::AuditEventStream;
+pub use audit_retention::AuditPurgeSummary;
+pub use auth::{OAuthScopeUpgrade, OAuthStateRecord};
+pub use automation::AutomationRuleOptions;
+pub use automation_manual_runs::AutomationManualRunRecord;
+pub use automation_reports::AutomationReportRecord;
+pub use automation_runs::AutomationRunFailure;
+pub use brief_profiles::MorningBriefProfileRecord;
+pub use component_availability::ComponentAvailabilityBucket;
+pub use connector_health::{ConnectorHealthRecord, ConnectorReauthNudge};
+pub use connectors::ConnectorAccount;
+pub use connectors::ConnectorRecord;
+pub use data_keys::{DataEncryptionKey, DataEncryptionKeyring, DataKeyReencryptionCounts};
+pub use dead_letter_jobs::{DeadLetterJobRecord, DeadLetterReplay};
+pub use departure_alerts::{
+    DepartureAlertJobMaterial, DepartureAlertPreferencesRecord, DueDepartureCheck,
+};
+pub use devices::{
+    DeviceFailureScore, DeviceNotificationKey, DeviceNotificationKeyRotation, DeviceRecord,
+    StaleDeviceRecord,
+};
+pub use job_partitions::FinishedJobPruneCounts;
+pub use job_trail::{
+    JobExecutionTrail, NewNotificationDelivery, NotificationDeliveryOutcome,
+    NotificationDeliveryRecord,
+};
+pub use jobs::{JobQueueDepth, JobStageTimings, JobState};
+pub use organizations::OrganizationMemberRecord;
+pub use privacy_export::{
+    ClaimedExportRequest, PrivacyExportDownload, PrivacyExportRequestStatus, PrivacyExportStatus,
+};
+pub use prompt_envelopes::PromptEnvelopeUse;
+pub use read_cache::StoreReadCache;
+pub use slo::{SloEvaluation, SloStatusRecord};
+pub use support_diagnostics::SupportDiagnosticRecord;
+pub use webhooks::{
+    ClaimedWebhookDelivery, WebhookDeliveryRecord, WebhookSubscriptionRecord, WebhookTarget,
+};
 
 pub const LEGACY_CONNECTOR_TOKEN_KEY_ID: &str = "__legacy__";
+/// Account key of connectors created before Google account identities were captured.
+pub const LEGACY_CONNECTOR_ACCOUNT_KEY: &str = "primary";
 
 #[derive(Debug, Clone)]
 pub enum AuditResult {
@@ -40,18 +124,21 @@ impl AuditResult {
 #[derive(Debug, Clone)]
 pub enum JobType {
     AutomationRun,
+    DepartureAlert,
 }
 
 impl JobType {
     pub fn as_str(&self) -> &'static str {
         match self {
             Self::AutomationRun => "AUTOMATION_RUN",
+            Self::DepartureAlert => "DEPARTURE_ALERT",
         }
     }
 
     fn from_db(value: &str) -> Result<Self, StoreError> {
         match value {
             "AUTOMATION_RUN" => Ok(Self::AutomationRun),
+            "DEPARTURE_ALERT" => Ok(Self::DepartureAlert),
             _ => Err(StoreError::InvalidData(format!(
                 "unknown job type persisted: {value}"
             ))),
@@ -63,6 +150,8 @@ impl JobType {
 pub enum AutomationRuleStatus {
     Active,
     Paused,
+    Archived,
+    Completed,
 }
 
 impl AutomationRuleStatus {
@@ -70,6 +159,8 @@ impl AutomationRuleStatus {
         match self {
             Self::Active => "ACTIVE",
             Self::Paused => "PAUSED",
+            Self::Archived => "ARCHIVED",
+            Self::Completed => "COMPLETED",
         }
     }
 
@@ -77,6 +168,8 @@ impl AutomationRuleStatus {
         match value {
             "ACTIVE" => Ok(Self::Active),
             "PAUSED" => Ok(Self::Paused),
+            "ARCHIVED" => Ok(Self::Archived),
+            "COMPLETED" => Ok(Self::Completed),
             _ => Err(StoreError::InvalidData(format!(
                 "unknown automation rule status persisted: {value}"
             ))),
@@ -91,6 +184,8 @@ impl AutomationScheduleType {
             Self::Weekly => "WEEKLY",
             Self::Monthly => "MONTHLY",
             Self::Annually => "ANNUALLY",
+            Self::Cron => "CRON",
+            Self::Once => "ONCE",
         }
     }
 
@@ -100,6 +195,8 @@ impl AutomationScheduleType {
             "WEEKLY" => Ok(Self::Weekly),
             "MONTHLY" => Ok(Self::Monthly),
             "ANNUALLY" => Ok(Self::Annually),
+            "CRON" => Ok(Self::Cron),
+            "ONCE" => Ok(Self::Once),
             _ => Err(StoreError::InvalidData(format!(
                 "unknown automation schedule type persisted: {value}"
             ))),
@@ -107,6 +204,44 @@ impl AutomationScheduleType {
     }
 }
 
+impl AutomationTemplate {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::WeeklyReview => "WEEKLY_REVIEW",
+            Self::MorningBrief => "MORNING_BRIEF",
+        }
+    }
+
+    fn from_db(value: &str) -> Result<Self, StoreError> {
+        match value {
+            "WEEKLY_REVIEW" => Ok(Self::WeeklyReview),
+            "MORNING_BRIEF" => Ok(Self::MorningBrief),
+            _ => Err(StoreError::InvalidData(format!(
+                "unknown automation template persisted: {value}"
+            ))),
+        }
+    }
+}
+
+impl AutomationConditionKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::CalendarEventsToday => "CALENDAR_EVENTS_TODAY",
+            Self::UrgentEmails => "URGENT_EMAILS",
+        }
+    }
+
+    fn from_db(value: &str) -> Result<Self, StoreError> {
+        match value {
+            "CALENDAR_EVENTS_TODAY" => Ok(Self::CalendarEventsToday),
+            "URGENT_EMAILS" => Ok(Self::UrgentEmails),
+            _ => Err(StoreError::InvalidData(format!(
+                "unknown automation condition persisted: {value}"
+            ))),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum AutomationRunState {
     Materialized,
@@ -139,8 +274,6 @@ impl AutomationRunState {
 pub enum StoreError {
     #[error("database error: {0}")]
     Database(#[from] sqlx::Error),
-    #[error("invalid cursor")]
-    InvalidCursor,
     #[error("invalid persisted data: {0}")]
     InvalidData(String),
 }
@@ -148,7 +281,15 @@ pub enum StoreError {
 #[derive(Clone)]
 pub struct Store {
     pool: PgPool,
+    read_pool: Option<PgPool>,
     data_encryption_key: String,
+    data_encryption_key_id: String,
+    data_encryption_key_ids: Vec<String>,
+    data_encryption_keys: Vec<String>,
+    secondary_data_encryption_key: Option<DataEncryptionKey>,
+    audit_redaction_policy: Arc<AuditRedactionPolicy>,
+    audit_event_signer: Option<Arc<dyn AuditEventSigner>>,
+    read_cache: Option<StoreReadCache>,
 }
 
 #[derive(Debug, Clone)]
@@ -166,11 +307,14 @@ pub struct ActiveConnectorMetadata {
     pub token_version: i32,
 }
 
-#[derive(Debug, Clone)]
-pub struct ConnectorStateRecord {
-    pub connector_id: Uuid,
-    pub provider: String,
-    pub status: String,
+/// Point-in-time connection counts for one of the store's pools.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StorePoolStats {
+    /// `primary`, or `read_replica` when a replica pool is configured.
+    pub pool: &'static str,
+    pub size: u32,
+    pub idle: u32,
+    pub max_connections: u32,
 }
 
 #[derive(Debug, Clone)]
@@ -191,15 +335,21 @@ pub struct AutomationRuleRecord {
     pub user_id: Uuid,
     pub title: String,
     pub status: AutomationRuleStatus,
+    pub template: Option<AutomationTemplate>,
+    pub condition: Option<AutomationCondition>,
     pub schedule_type: AutomationScheduleType,
     pub local_time_minutes: i32,
-    pub anchor_day_of_week: Option<i16>,
+    pub anchor_days_of_week: Option<i16>,
     pub anchor_day_of_month: Option<i16>,
     pub anchor_month: Option<i16>,
+    pub cron_expression: Option<String>,
+    pub run_at: Option<DateTime<Utc>>,
     pub time_zone: String,
     pub next_run_at: DateTime<Utc>,
     pub last_run_at: Option<DateTime<Utc>>,
+    pub paused_until: Option<DateTime<Utc>>,
     pub prompt_sha256: String,
+    pub version: i64,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -208,11 +358,15 @@ pub struct AutomationRuleRecord {
 pub struct ClaimedAutomationRule {
     pub id: Uuid,
     pub user_id: Uuid,
+    pub template: Option<AutomationTemplate>,
+    pub condition: Option<AutomationCondition>,
     pub schedule_type: AutomationScheduleType,
     pub local_time_minutes: i32,
-    pub anchor_day_of_week: Option<i16>,
+    pub anchor_days_of_week: Option<i16>,
     pub anchor_day_of_month: Option<i16>,
     pub anchor_month: Option<i16>,
+    pub cron_expression: Option<String>,
+    pub run_at: Option<DateTime<Utc>>,
     pub time_zone: String,
     pub next_run_at: DateTime<Utc>,
     pub prompt_ciphertext: Vec<u8>,
@@ -234,6 +388,14 @@ pub struct AutomationRunRecord {
     pub job_id: Option<Uuid>,
     pub idempotency_key: String,
     pub state: AutomationRunState,
+    /// Dead-letter error code of a failed run.
+    pub failure_code: Option<String>,
+    /// Set on the failed run that paused its rule.
+    pub paused_rule: bool,
+    /// Whether the rule's condition held, for runs of rules with a condition.
+    pub condition_met: Option<bool>,
+    /// Matching items the enclave found while evaluating the condition.
+    pub condition_observed_count: Option<i32>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -286,38 +448,49 @@ pub struct PrivacyDeleteRequestStatus {
     pub failed_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DeviceRegistration {
     pub device_id: String,
     pub apns_token: String,
     pub environment: ApnsEnvironment,
-    pub notification_key_algorithm: Option<String>,
-    pub notification_public_key: Option<String>,
+    pub app_bundle_id: Option<String>,
+    /// When the device was first registered; token refreshes do not move it.
+    pub registered_at: DateTime<Utc>,
+    pub notification_key: Option<DeviceNotificationKey>,
+    pub previous_notification_key: Option<DeviceNotificationKey>,
 }
 
 impl AutomationRuleRecord {
     pub fn schedule_spec(&self) -> Result<AutomationScheduleSpec, StoreError> {
-        automation_schedule_spec_from_fields(
-            self.schedule_type,
-            self.time_zone.as_str(),
-            self.local_time_minutes,
-            self.anchor_day_of_week,
-            self.anchor_day_of_month,
-            self.anchor_month,
-        )
+        Ok(AutomationScheduleSpec {
+            run_at: self.run_at,
+            ..automation_schedule_spec_from_fields(
+                self.schedule_type,
+                self.time_zone.as_str(),
+                self.local_time_minutes,
+                self.anchor_days_of_week,
+                self.anchor_day_of_month,
+                self.anchor_month,
+                self.cron_expression.clone(),
+            )?
+        })
     }
 }
 
 impl ClaimedAutomationRule {
     pub fn schedule_spec(&self) -> Result<AutomationScheduleSpec, StoreError> {
-        automation_schedule_spec_from_fields(
-            self.schedule_type,
-            self.time_zone.as_str(),
-            self.local_time_minutes,
-            self.anchor_day_of_week,
-            self.anchor_day_of_month,
-            self.anchor_month,
-        )
+        Ok(AutomationScheduleSpec {
+            run_at: self.run_at,
+            ..automation_schedule_spec_from_fields(
+                self.schedule_type,
+                self.time_zone.as_str(),
+                self.local_time_minutes,
+                self.anchor_days_of_week,
+                self.anchor_day_of_month,
+                self.anchor_month,
+                self.cron_expression.clone(),
+            )?
+        })
     }
 }
 
@@ -325,13 +498,14 @@ fn automation_schedule_spec_from_fields(
     schedule_type: AutomationScheduleType,
     time_zone: &str,
     local_time_minutes: i32,
-    anchor_day_of_week: Option<i16>,
+    anchor_days_of_week: Option<i16>,
     anchor_day_of_month: Option<i16>,
     anchor_month: Option<i16>,
+    cron_expression: Option<String>,
 ) -> Result<AutomationScheduleSpec, StoreError> {
     let local_time_minutes = u16::try_from(local_time_minutes)
         .map_err(|_| StoreError::InvalidData("local_time_minutes must be >= 0".to_string()))?;
-    let anchor_day_of_week = option_i16_to_u8(anchor_day_of_week, "anchor_day_of_week")?;
+    let anchor_days_of_week = option_i16_to_u8(anchor_days_of_week, "anchor_days_of_week")?;
     let anchor_day_of_month = option_i16_to_u8(anchor_day_of_month, "anchor_day_of_month")?;
     let anchor_month = option_i16_to_u8(anchor_month, "anchor_month")?;
 
@@ -339,9 +513,11 @@ fn automation_schedule_spec_from_fields(
         schedule_type,
         time_zone: time_zone.to_string(),
         local_time_minutes,
-        anchor_day_of_week,
+        anchor_days_of_week,
         anchor_day_of_month,
         anchor_month,
+        cron_expression,
+        run_at: None,
     })
 }
 
@@ -0,0 +1,132 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::models::UserPlan;
+
+use super::{Store, StoreError};
+
+/// A member of a Clerk organization, as seen by its admins: what they have set up, never what
+/// it contains.
+#[derive(Debug, Clone)]
+pub struct OrganizationMemberRecord {
+    pub user_id: Uuid,
+    pub created_at: DateTime<Utc>,
+    /// Connectors that are not revoked.
+    pub connector_count: i64,
+    /// Automation rules that are not archived or completed.
+    pub automation_count: i64,
+}
+
+impl Store {
+    /// Like [`Store::ensure_user`], and records `org_id` as the user's organization, clearing it
+    /// when the session has none. Membership follows the verified session on every request, so
+    /// a user removed from an organization loses its plan and its admins' visibility on their
+    /// next request. Only writes when the organization changed.
+    pub async fn ensure_user_organization(
+        &self,
+        user_id: Uuid,
+        org_id: Option<&str>,
+    ) -> Result<(), StoreError> {
+        sqlx::query(
+            "INSERT INTO users (id, org_id) VALUES ($1, $2)
+             ON CONFLICT (id) DO UPDATE SET org_id = EXCLUDED.org_id
+             WHERE users.org_id IS DISTINCT FROM EXCLUDED.org_id",
+        )
+        .bind(user_id)
+        .bind(org_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn user_in_organization(
+        &self,
+        user_id: Uuid,
+        org_id: &str,
+    ) -> Result<bool, StoreError> {
+        let member: bool =
+            sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM users WHERE id = $1 AND org_id = $2)")
+                .bind(user_id)
+                .bind(org_id)
+                .fetch_one(&self.pool)
+                .await?;
+        Ok(member)
+    }
+
+    /// Returns the organization's plan; organizations without a plan row are on the free plan.
+    pub async fn get_organization_plan(&self, org_id: &str) -> Result<UserPlan, StoreError> {
+        let plan: Option<String> =
+            sqlx::query_scalar("SELECT plan FROM organization_plans WHERE org_id = $1")
+                .bind(org_id)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        match plan {
+            Some(plan) => parse_plan(&plan),
+            None => Ok(UserPlan::Free),
+        }
+    }
+
+    pub async fn set_organization_plan(
+        &self,
+        org_id: &str,
+        plan: UserPlan,
+    ) -> Result<(), StoreError> {
+        sqlx::query(
+            "INSERT INTO organization_plans (org_id, plan)
+             VALUES ($1, $2)
+             ON CONFLICT (org_id)
+             DO UPDATE SET plan = EXCLUDED.plan, updated_at = NOW()",
+        )
+        .bind(org_id)
+        .bind(plan.as_str())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Members of `org_id` with counts of what they have set up, oldest member first. Counts
+    /// only; nothing here reads connector secrets or automation content.
+    pub async fn list_organization_members(
+        &self,
+        org_id: &str,
+        limit: i64,
+    ) -> Result<Vec<OrganizationMemberRecord>, StoreError> {
+        let rows: Vec<(Uuid, DateTime<Utc>, i64, i64)> = sqlx::query_as(
+            "SELECT
+                u.id,
+                u.created_at,
+                (SELECT COUNT(*) FROM connectors c
+                 WHERE c.user_id = u.id AND c.status = 'ACTIVE')::bigint,
+                (SELECT COUNT(*) FROM automation_rules a
+                 WHERE a.user_id = u.id AND a.status NOT IN ('ARCHIVED', 'COMPLETED'))::bigint
+             FROM users u
+             WHERE u.org_id = $1
+               AND u.status = 'ACTIVE'
+             ORDER BY u.created_at ASC, u.id ASC
+             LIMIT $2",
+        )
+        .bind(org_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(user_id, created_at, connector_count, automation_count)| {
+                OrganizationMemberRecord {
+                    user_id,
+                    created_at,
+                    connector_count,
+                    automation_count,
+                }
+            })
+            .collect())
+    }
+}
+
+pub(super) fn parse_plan(plan: &str) -> Result<UserPlan, StoreError> {
+    UserPlan::parse(plan)
+        .ok_or_else(|| StoreError::InvalidData(format!("unknown plan persisted: {plan}")))
+}
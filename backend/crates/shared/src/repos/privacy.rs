@@ -2,10 +2,41 @@ use chrono::{DateTime, Duration, Utc};
 use sqlx::Row;
 use uuid::Uuid;
 
+use super::read_cache::ReadCacheScope;
 use super::{
     ClaimedDeleteRequest, PrivacyDeleteRequestStatus, PrivacyDeleteStatus, Store, StoreError,
 };
 
+/// User-owned tables cleared by a delete-all request, in deletion order.
+const USER_PURGE_TABLES: &[&str] = &[
+    "audit_events",
+    "audit_chain_heads",
+    "audit_chain_tombstones",
+    "oauth_states",
+    "assistant_encrypted_sessions",
+    "assistant_request_index",
+    "prompt_envelope_request_ids",
+    "connectors",
+    "request_signature_nonces",
+    "devices",
+    "dead_letter_jobs",
+    "outbound_action_idempotency",
+    "job_idempotency_keys",
+    "jobs",
+    "notification_deliveries",
+    "automation_reports",
+    "automation_manual_runs",
+    "automation_rules",
+    "morning_brief_profiles",
+    "departure_alert_deliveries",
+    "departure_alert_preferences",
+    "webhook_deliveries",
+    "webhook_subscriptions",
+    "support_diagnostics",
+    "llm_monthly_spend",
+    "privacy_export_requests",
+];
+
 impl Store {
     pub async fn queue_delete_all(&self, user_id: Uuid) -> Result<Uuid, StoreError> {
         self.ensure_user(user_id).await?;
@@ -203,13 +234,17 @@ impl Store {
     }
 
     pub async fn count_pending_delete_requests(&self) -> Result<i64, StoreError> {
-        let count: i64 = sqlx::query_scalar(
-            "SELECT COUNT(*)::bigint
-             FROM privacy_delete_requests
-             WHERE status IN ('QUEUED', 'RUNNING')",
-        )
-        .fetch_one(&self.pool)
-        .await?;
+        let count: i64 = self
+            .with_read_pool(|pool| async move {
+                sqlx::query_scalar(
+                    "SELECT COUNT(*)::bigint
+                     FROM privacy_delete_requests
+                     WHERE status IN ('QUEUED', 'RUNNING')",
+                )
+                .fetch_one(&pool)
+                .await
+            })
+            .await?;
 
         Ok(count)
     }
@@ -225,51 +260,41 @@ impl Store {
             ));
         }
 
-        let count: i64 = sqlx::query_scalar(
-            "SELECT COUNT(*)::bigint
-             FROM privacy_delete_requests
-             WHERE status <> 'COMPLETED'
-               AND created_at <= ($1 - ($2 * INTERVAL '1 hour'))",
-        )
-        .bind(now)
-        .bind(sla_hours)
-        .fetch_one(&self.pool)
-        .await?;
+        let count: i64 = self
+            .with_read_pool(|pool| async move {
+                sqlx::query_scalar(
+                    "SELECT COUNT(*)::bigint
+                     FROM privacy_delete_requests
+                     WHERE status <> 'COMPLETED'
+                       AND created_at <= ($1 - ($2 * INTERVAL '1 hour'))",
+                )
+                .bind(now)
+                .bind(sla_hours)
+                .fetch_one(&pool)
+                .await
+            })
+            .await?;
 
         Ok(count)
     }
 
-    pub async fn purge_user_operational_data(&self, user_id: Uuid) -> Result<(), StoreError> {
+    /// Deletes every user-owned row and marks the user deleted, returning how many rows were
+    /// removed from each table in deletion order.
+    pub async fn purge_user_operational_data(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<(&'static str, u64)>, StoreError> {
         let mut tx = self.pool.begin().await?;
 
-        sqlx::query("DELETE FROM audit_events WHERE user_id = $1")
-            .bind(user_id)
-            .execute(&mut *tx)
-            .await?;
-        sqlx::query("DELETE FROM oauth_states WHERE user_id = $1")
-            .bind(user_id)
-            .execute(&mut *tx)
-            .await?;
-        sqlx::query("DELETE FROM assistant_encrypted_sessions WHERE user_id = $1")
-            .bind(user_id)
-            .execute(&mut *tx)
-            .await?;
-        sqlx::query("DELETE FROM connectors WHERE user_id = $1")
-            .bind(user_id)
-            .execute(&mut *tx)
-            .await?;
-        sqlx::query("DELETE FROM devices WHERE user_id = $1")
-            .bind(user_id)
-            .execute(&mut *tx)
-            .await?;
-        sqlx::query("DELETE FROM jobs WHERE user_id = $1")
-            .bind(user_id)
-            .execute(&mut *tx)
-            .await?;
-        sqlx::query("DELETE FROM automation_rules WHERE user_id = $1")
-            .bind(user_id)
-            .execute(&mut *tx)
-            .await?;
+        let mut deleted_rows = Vec::with_capacity(USER_PURGE_TABLES.len());
+        for table in USER_PURGE_TABLES {
+            let result = sqlx::query(&format!("DELETE FROM {table} WHERE user_id = $1"))
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?;
+            deleted_rows.push((*table, result.rows_affected()));
+        }
+
         sqlx::query(
             "UPDATE users
              SET status = 'DELETED'
@@ -280,6 +305,8 @@ impl Store {
         .await?;
 
         tx.commit().await?;
-        Ok(())
+        self.invalidate_user_read(ReadCacheScope::RegisteredDevices, user_id)
+            .await;
+        Ok(deleted_rows)
     }
 }
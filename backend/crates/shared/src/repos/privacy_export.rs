@@ -0,0 +1,570 @@
+use chrono::{DateTime, Duration, Utc};
+use serde_json::Value;
+use sqlx::Row;
+use uuid::Uuid;
+
+use super::{Store, StoreError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrivacyExportStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+    Expired,
+}
+
+impl PrivacyExportStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Queued => "QUEUED",
+            Self::Running => "RUNNING",
+            Self::Completed => "COMPLETED",
+            Self::Failed => "FAILED",
+            Self::Expired => "EXPIRED",
+        }
+    }
+
+    fn from_db(value: &str) -> Result<Self, StoreError> {
+        match value {
+            "QUEUED" => Ok(Self::Queued),
+            "RUNNING" => Ok(Self::Running),
+            "COMPLETED" => Ok(Self::Completed),
+            "FAILED" => Ok(Self::Failed),
+            "EXPIRED" => Ok(Self::Expired),
+            _ => Err(StoreError::InvalidData(format!(
+                "unknown privacy export status persisted: {value}"
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ClaimedExportRequest {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PrivacyExportRequestStatus {
+    pub id: Uuid,
+    pub status: PrivacyExportStatus,
+    pub archive_available: bool,
+    pub archive_size_bytes: Option<i32>,
+    pub created_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub failed_at: Option<DateTime<Utc>>,
+    pub downloaded_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PrivacyExportDownload {
+    pub user_id: Uuid,
+    pub archive: Vec<u8>,
+}
+
+impl Store {
+    pub async fn queue_privacy_export(&self, user_id: Uuid) -> Result<Uuid, StoreError> {
+        self.ensure_user(user_id).await?;
+
+        let existing_request_id = sqlx::query_scalar(
+            "SELECT id
+             FROM privacy_export_requests
+             WHERE user_id = $1
+               AND status IN ('QUEUED', 'RUNNING')
+             ORDER BY created_at ASC, id ASC
+             LIMIT 1",
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        if let Some(existing_request_id) = existing_request_id {
+            return Ok(existing_request_id);
+        }
+
+        let request_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO privacy_export_requests (user_id, status)
+             VALUES ($1, 'QUEUED')
+             RETURNING id",
+        )
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(request_id)
+    }
+
+    pub async fn claim_export_requests(
+        &self,
+        now: DateTime<Utc>,
+        worker_id: Uuid,
+        max_requests: i64,
+        lease_seconds: i64,
+    ) -> Result<Vec<ClaimedExportRequest>, StoreError> {
+        if max_requests <= 0 {
+            return Ok(Vec::new());
+        }
+        if lease_seconds <= 0 {
+            return Err(StoreError::InvalidData(
+                "privacy export lease_seconds must be > 0".to_string(),
+            ));
+        }
+
+        sqlx::query(
+            "UPDATE privacy_export_requests
+             SET status = 'QUEUED',
+                 lease_owner = NULL,
+                 lease_expires_at = NULL,
+                 updated_at = NOW()
+             WHERE status = 'RUNNING'
+               AND lease_expires_at IS NOT NULL
+               AND lease_expires_at <= $1",
+        )
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        let lease_until = now + Duration::seconds(lease_seconds);
+        let worker_id = worker_id.to_string();
+
+        let rows = sqlx::query(
+            "WITH candidate_ids AS (
+                SELECT id
+                FROM privacy_export_requests
+                WHERE status = 'QUEUED'
+                ORDER BY created_at ASC, id ASC
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+             ),
+             claimed AS (
+                UPDATE privacy_export_requests p
+                SET status = 'RUNNING',
+                    started_at = COALESCE(p.started_at, $2),
+                    failed_at = NULL,
+                    failure_reason = NULL,
+                    lease_owner = $3,
+                    lease_expires_at = $4,
+                    updated_at = NOW()
+                FROM candidate_ids c
+                WHERE p.id = c.id
+                RETURNING p.id, p.user_id, p.created_at
+             )
+             SELECT id, user_id, created_at
+             FROM claimed
+             ORDER BY created_at ASC, id ASC",
+        )
+        .bind(max_requests)
+        .bind(now)
+        .bind(worker_id)
+        .bind(lease_until)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(ClaimedExportRequest {
+                    id: row.try_get("id")?,
+                    user_id: row.try_get("user_id")?,
+                    created_at: row.try_get("created_at")?,
+                })
+            })
+            .collect()
+    }
+
+    /// Collects the user's account metadata for a data export. Encrypted payloads (push
+    /// tokens, OAuth tokens, automation prompts, job payloads, webhook signing secrets) are
+    /// never included.
+    pub async fn collect_user_export_data(&self, user_id: Uuid) -> Result<Value, StoreError> {
+        let data: Value = sqlx::query_scalar(
+            "SELECT jsonb_build_object(
+                'account', (
+                  SELECT jsonb_build_object(
+                    'user_id', u.id,
+                    'status', u.status,
+                    'created_at', u.created_at
+                  )
+                  FROM users u
+                  WHERE u.id = $1
+                ),
+                'preferences', jsonb_build_object(
+                  'morning_brief', (
+                    SELECT jsonb_build_object(
+                      'sections', p.sections,
+                      'verbosity', p.verbosity,
+                      'learn_from_feedback', p.learn_from_feedback,
+                      'updated_at', p.updated_at
+                    )
+                    FROM morning_brief_profiles p
+                    WHERE p.user_id = $1
+                  ),
+                  'departure_alerts', (
+                    SELECT jsonb_build_object(
+                      'enabled', d.enabled,
+                      'time_zone', d.time_zone,
+                      'check_local_time_minutes', d.check_local_time_minutes,
+                      'travel_minutes', d.travel_minutes,
+                      'buffer_minutes', d.buffer_minutes,
+                      'updated_at', d.updated_at
+                    )
+                    FROM departure_alert_preferences d
+                    WHERE d.user_id = $1
+                  )
+                ),
+                'connectors', COALESCE((
+                  SELECT jsonb_agg(
+                    jsonb_build_object(
+                      'connector_id', c.id,
+                      'provider', c.provider,
+                      'scopes', c.scopes,
+                      'status', c.status,
+                      'created_at', c.created_at,
+                      'revoked_at', c.revoked_at
+                    )
+                    ORDER BY c.created_at, c.id
+                  )
+                  FROM connectors c
+                  WHERE c.user_id = $1
+                ), '[]'::jsonb),
+                'devices', COALESCE((
+                  SELECT jsonb_agg(
+                    jsonb_build_object(
+                      'device_id', d.device_identifier,
+                      'environment', d.environment,
+                      'app_bundle_id', d.app_bundle_id,
+                      'created_at', d.created_at,
+                      'last_seen_at', d.last_seen_at,
+                      'disabled_at', d.disabled_at
+                    )
+                    ORDER BY d.created_at, d.device_identifier
+                  )
+                  FROM devices d
+                  WHERE d.user_id = $1
+                ), '[]'::jsonb),
+                'webhook_subscriptions', COALESCE((
+                  SELECT jsonb_agg(
+                    jsonb_build_object(
+                      'subscription_id', w.id,
+                      'url', w.url,
+                      'event_types', w.event_types,
+                      'created_at', w.created_at
+                    )
+                    ORDER BY w.created_at, w.id
+                  )
+                  FROM webhook_subscriptions w
+                  WHERE w.user_id = $1
+                ), '[]'::jsonb),
+                'automation_rules', COALESCE((
+                  SELECT jsonb_agg(
+                    jsonb_build_object(
+                      'rule_id', r.id,
+                      'title', r.title,
+                      'status', r.status,
+                      'schedule_type', r.schedule_type,
+                      'interval_seconds', r.interval_seconds,
+                      'time_zone', r.time_zone,
+                      'local_time_minutes', r.local_time_minutes,
+                      'cron_expression', r.cron_expression,
+                      'run_at', r.run_at,
+                      'template', r.template,
+                      'condition_kind', r.condition_kind,
+                      'condition_min_count', r.condition_min_count,
+                      'next_run_at', r.next_run_at,
+                      'last_run_at', r.last_run_at,
+                      'paused_until', r.paused_until,
+                      'created_at', r.created_at,
+                      'updated_at', r.updated_at
+                    )
+                    ORDER BY r.created_at, r.id
+                  )
+                  FROM automation_rules r
+                  WHERE r.user_id = $1
+                ), '[]'::jsonb),
+                'jobs', COALESCE((
+                  SELECT jsonb_agg(
+                    jsonb_build_object(
+                      'job_id', j.id,
+                      'type', j.type,
+                      'state', j.state,
+                      'due_at', j.due_at,
+                      'attempts', j.attempts,
+                      'last_error_code', j.last_error_code,
+                      'started_at', j.started_at,
+                      'finished_at', j.finished_at
+                    )
+                    ORDER BY j.due_at, j.id
+                  )
+                  FROM jobs j
+                  WHERE j.user_id = $1
+                ), '[]'::jsonb),
+                'audit_events', COALESCE((
+                  SELECT jsonb_agg(
+                    jsonb_build_object(
+                      'event_id', a.id,
+                      'event_type', a.event_type,
+                      'connector', a.connector,
+                      'result', a.result,
+                      'metadata', COALESCE(a.typed_metadata, a.redacted_metadata),
+                      'created_at', a.created_at
+                    )
+                    ORDER BY a.created_at, a.id
+                  )
+                  FROM audit_events a
+                  WHERE a.user_id = $1
+                ), '[]'::jsonb)
+             )",
+        )
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(data)
+    }
+
+    /// Stores the finished archive encrypted with the primary data encryption key.
+    pub async fn mark_export_request_completed(
+        &self,
+        request_id: Uuid,
+        worker_id: Uuid,
+        archive: &[u8],
+        completed_at: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+    ) -> Result<bool, StoreError> {
+        let archive_size_bytes = i32::try_from(archive.len()).map_err(|_| {
+            StoreError::InvalidData("privacy export archive is too large".to_string())
+        })?;
+        let result = sqlx::query(
+            "UPDATE privacy_export_requests
+             SET status = 'COMPLETED',
+                 archive_ciphertext = pgp_sym_encrypt_bytea($3, $4),
+                 archive_size_bytes = $5,
+                 data_key_id = $6,
+                 completed_at = $7,
+                 expires_at = $8,
+                 failed_at = NULL,
+                 failure_reason = NULL,
+                 lease_owner = NULL,
+                 lease_expires_at = NULL,
+                 updated_at = NOW()
+             WHERE id = $1
+               AND status = 'RUNNING'
+               AND lease_owner = $2",
+        )
+        .bind(request_id)
+        .bind(worker_id.to_string())
+        .bind(archive)
+        .bind(&self.data_encryption_key)
+        .bind(archive_size_bytes)
+        .bind(&self.data_encryption_key_id)
+        .bind(completed_at)
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn mark_export_request_failed(
+        &self,
+        request_id: Uuid,
+        worker_id: Uuid,
+        failed_at: DateTime<Utc>,
+        failure_reason: &str,
+    ) -> Result<bool, StoreError> {
+        let result = sqlx::query(
+            "UPDATE privacy_export_requests
+             SET status = 'FAILED',
+                 failed_at = $3,
+                 failure_reason = $4,
+                 lease_owner = NULL,
+                 lease_expires_at = NULL,
+                 updated_at = NOW()
+             WHERE id = $1
+               AND status = 'RUNNING'
+               AND lease_owner = $2",
+        )
+        .bind(request_id)
+        .bind(worker_id.to_string())
+        .bind(failed_at)
+        .bind(failure_reason)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn get_export_request_status(
+        &self,
+        user_id: Uuid,
+        request_id: Uuid,
+    ) -> Result<Option<PrivacyExportRequestStatus>, StoreError> {
+        let row = sqlx::query(
+            "SELECT
+                id,
+                status,
+                archive_ciphertext IS NOT NULL AS archive_available,
+                archive_size_bytes,
+                created_at,
+                started_at,
+                completed_at,
+                failed_at,
+                downloaded_at,
+                expires_at
+             FROM privacy_export_requests
+             WHERE user_id = $1
+               AND id = $2",
+        )
+        .bind(user_id)
+        .bind(request_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|row| {
+            let status: String = row.try_get("status")?;
+            Ok(PrivacyExportRequestStatus {
+                id: row.try_get("id")?,
+                status: PrivacyExportStatus::from_db(&status)?,
+                archive_available: row.try_get("archive_available")?,
+                archive_size_bytes: row.try_get("archive_size_bytes")?,
+                created_at: row.try_get("created_at")?,
+                started_at: row.try_get("started_at")?,
+                completed_at: row.try_get("completed_at")?,
+                failed_at: row.try_get("failed_at")?,
+                downloaded_at: row.try_get("downloaded_at")?,
+                expires_at: row.try_get("expires_at")?,
+            })
+        })
+        .transpose()
+    }
+
+    /// Replaces the download token for a completed export. Only the latest token is valid,
+    /// so issuing a new one invalidates any previously handed-out download URL.
+    pub async fn issue_export_download_token(
+        &self,
+        user_id: Uuid,
+        request_id: Uuid,
+        token_hash: &[u8],
+        token_expires_at: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Result<bool, StoreError> {
+        let result = sqlx::query(
+            "UPDATE privacy_export_requests
+             SET download_token_hash = $3,
+                 download_token_expires_at = $4,
+                 updated_at = NOW()
+             WHERE user_id = $1
+               AND id = $2
+               AND status = 'COMPLETED'
+               AND archive_ciphertext IS NOT NULL
+               AND expires_at > $5",
+        )
+        .bind(user_id)
+        .bind(request_id)
+        .bind(token_hash)
+        .bind(token_expires_at)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Redeems a download token and returns the decrypted archive. The archive is deleted in
+    /// the same statement, so each export can be downloaded exactly once.
+    pub async fn consume_export_download(
+        &self,
+        request_id: Uuid,
+        token_hash: &[u8],
+        now: DateTime<Utc>,
+    ) -> Result<Option<PrivacyExportDownload>, StoreError> {
+        let row = sqlx::query(
+            "WITH target AS (
+                SELECT
+                  id,
+                  user_id,
+                  pgp_sym_decrypt_bytea(
+                    archive_ciphertext,
+                    alfred_data_key(data_key_id, $4, $5)
+                  ) AS archive
+                FROM privacy_export_requests
+                WHERE id = $1
+                  AND download_token_hash = $2
+                  AND download_token_expires_at > $3
+                  AND expires_at > $3
+                  AND archive_ciphertext IS NOT NULL
+                FOR UPDATE
+             ),
+             consumed AS (
+                UPDATE privacy_export_requests p
+                SET archive_ciphertext = NULL,
+                    download_token_hash = NULL,
+                    download_token_expires_at = NULL,
+                    downloaded_at = $3,
+                    updated_at = NOW()
+                FROM target
+                WHERE p.id = target.id
+                RETURNING p.id
+             )
+             SELECT target.user_id, target.archive
+             FROM target
+             JOIN consumed ON consumed.id = target.id",
+        )
+        .bind(request_id)
+        .bind(token_hash)
+        .bind(now)
+        .bind(&self.data_encryption_key_ids)
+        .bind(&self.data_encryption_keys)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|row| {
+            Ok(PrivacyExportDownload {
+                user_id: row.try_get("user_id")?,
+                archive: row.try_get("archive")?,
+            })
+        })
+        .transpose()
+    }
+
+    /// Drops archives past their TTL and marks the requests expired.
+    pub async fn purge_expired_export_archives_batch(
+        &self,
+        now: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<u64, StoreError> {
+        if limit <= 0 {
+            return Err(StoreError::InvalidData(
+                "privacy export purge limit must be > 0".to_string(),
+            ));
+        }
+
+        let result = sqlx::query(
+            "WITH expired AS (
+                SELECT id
+                FROM privacy_export_requests
+                WHERE archive_ciphertext IS NOT NULL
+                  AND expires_at <= $1
+                ORDER BY expires_at ASC
+                LIMIT $2
+                FOR UPDATE SKIP LOCKED
+             )
+             UPDATE privacy_export_requests p
+             SET status = 'EXPIRED',
+                 archive_ciphertext = NULL,
+                 download_token_hash = NULL,
+                 download_token_expires_at = NULL,
+                 updated_at = NOW()
+             FROM expired
+             WHERE p.id = expired.id",
+        )
+        .bind(now)
+        .bind(limit)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
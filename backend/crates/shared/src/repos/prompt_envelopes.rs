@@ -0,0 +1,92 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use super::{Store, StoreError};
+
+/// Where an encrypted prompt envelope was submitted. Request ids are unique per user across
+/// all of them, so an envelope cannot be replayed from one surface into another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromptEnvelopeUse {
+    AutomationCreate,
+    AutomationUpdate,
+    AutomationDraft,
+    AssistantQuery,
+}
+
+impl PromptEnvelopeUse {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::AutomationCreate => "AUTOMATION_CREATE",
+            Self::AutomationUpdate => "AUTOMATION_UPDATE",
+            Self::AutomationDraft => "AUTOMATION_DRAFT",
+            Self::AssistantQuery => "ASSISTANT_QUERY",
+        }
+    }
+}
+
+impl Store {
+    /// Claims an encrypted prompt envelope's request id for the user. Returns `false` when the
+    /// id was already accepted, which means the envelope is being replayed. The claim expires
+    /// with the ingress key `key_id` the envelope was sealed to.
+    pub async fn claim_prompt_envelope_request_id(
+        &self,
+        user_id: Uuid,
+        key_id: &str,
+        request_id: &str,
+        used_for: PromptEnvelopeUse,
+    ) -> Result<bool, StoreError> {
+        let result = sqlx::query(
+            "INSERT INTO prompt_envelope_request_ids (
+                user_id, request_id, used_for, key_id, expires_at
+             )
+             VALUES (
+                $1, $2, $3, $4,
+                (SELECT expires_at FROM assistant_ingress_keys WHERE key_id = $4)
+             )
+             ON CONFLICT (user_id, request_id) DO NOTHING",
+        )
+        .bind(user_id)
+        .bind(request_id)
+        .bind(used_for.as_str())
+        .bind(key_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Deletes up to `limit` request ids whose ingress key expired by `now`; their envelopes
+    /// can no longer be decrypted, so there is nothing left to replay.
+    pub async fn purge_expired_prompt_envelope_request_ids_batch(
+        &self,
+        now: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<u64, StoreError> {
+        if limit <= 0 {
+            return Err(StoreError::InvalidData(
+                "prompt envelope request id purge limit must be > 0".to_string(),
+            ));
+        }
+
+        let result = sqlx::query(
+            "WITH expired AS (
+                SELECT user_id, request_id
+                FROM prompt_envelope_request_ids
+                WHERE expires_at <= $1
+                ORDER BY expires_at ASC
+                LIMIT $2
+                FOR UPDATE SKIP LOCKED
+             )
+             DELETE FROM prompt_envelope_request_ids request_ids
+             USING expired
+             WHERE request_ids.user_id = expired.user_id
+               AND request_ids.request_id = expired.request_id",
+        )
+        .bind(now)
+        .bind(limit)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,229 @@
+use std::future::Future;
+use std::time::Duration;
+
+use base64::Engine as _;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Nonce};
+use redis::AsyncCommands;
+use redis::aio::{ConnectionManager, ConnectionManagerConfig};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use sha2::{Digest, Sha256};
+use tracing::warn;
+use uuid::Uuid;
+
+use super::{Store, StoreError};
+
+const READ_CACHE_KEY_PREFIX: &str = "alfred:store:v1";
+/// Redis is an optimization here, so a slow server must not stall the hot path.
+const READ_CACHE_RESPONSE_TIMEOUT: Duration = Duration::from_millis(250);
+const READ_CACHE_CONNECTION_TIMEOUT: Duration = Duration::from_secs(2);
+const READ_CACHE_CONNECTION_RETRIES: usize = 2;
+const READ_CACHE_MAX_RETRY_DELAY_MS: u64 = 500;
+const READ_CACHE_NONCE_LEN: usize = 12;
+
+/// Per-user hot reads that may be served from the cache.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum ReadCacheScope {
+    RegisteredDevices,
+}
+
+impl ReadCacheScope {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::RegisteredDevices => "registered_devices",
+        }
+    }
+}
+
+/// Short-lived Redis cache in front of per-user reads that run on every job and assistant
+/// query. Entries are encrypted with a key derived from the primary data encryption key, so
+/// cached rows stay as protected as the columns they came from.
+#[derive(Clone)]
+pub struct StoreReadCache {
+    connection: ConnectionManager,
+    ttl_seconds: u64,
+}
+
+impl StoreReadCache {
+    pub async fn connect(redis_url: &str, ttl_seconds: u64) -> Result<Self, String> {
+        if ttl_seconds == 0 {
+            return Err("store read cache ttl_seconds must be > 0".to_string());
+        }
+
+        let client = redis::Client::open(redis_url).map_err(|err| err.to_string())?;
+        let connection = ConnectionManager::new_with_config(
+            client,
+            ConnectionManagerConfig::new()
+                .set_response_timeout(READ_CACHE_RESPONSE_TIMEOUT)
+                .set_connection_timeout(READ_CACHE_CONNECTION_TIMEOUT)
+                .set_number_of_retries(READ_CACHE_CONNECTION_RETRIES)
+                .set_max_delay(READ_CACHE_MAX_RETRY_DELAY_MS),
+        )
+        .await
+        .map_err(|err| err.to_string())?;
+
+        let mut health_connection = connection.clone();
+        redis::cmd("PING")
+            .query_async::<String>(&mut health_connection)
+            .await
+            .map_err(|err| format!("failed to connect to redis: {err}"))?;
+
+        Ok(Self {
+            connection,
+            ttl_seconds,
+        })
+    }
+}
+
+impl Store {
+    /// Serves hot per-user reads from Redis when a read cache is configured.
+    pub fn with_read_cache(mut self, read_cache: StoreReadCache) -> Self {
+        self.read_cache = Some(read_cache);
+        self
+    }
+
+    /// Attaches a read cache unless `ttl_seconds` is zero. Redis being unreachable at startup
+    /// is logged and the store keeps reading straight from Postgres.
+    pub async fn with_read_cache_from_config(self, redis_url: &str, ttl_seconds: u64) -> Self {
+        if ttl_seconds == 0 {
+            return self;
+        }
+
+        match StoreReadCache::connect(redis_url, ttl_seconds).await {
+            Ok(read_cache) => self.with_read_cache(read_cache),
+            Err(err) => {
+                warn!("store read cache disabled, redis unavailable: {err}");
+                self
+            }
+        }
+    }
+
+    /// Returns the cached value for `scope` or runs `load` and caches its result. Any cache
+    /// failure falls back to `load`, so Redis outages only cost the extra round trip.
+    pub(super) async fn cached_user_read<T, F, Fut>(
+        &self,
+        scope: ReadCacheScope,
+        user_id: Uuid,
+        load: F,
+    ) -> Result<T, StoreError>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, StoreError>>,
+    {
+        let Some(read_cache) = self.read_cache.as_ref() else {
+            return load().await;
+        };
+
+        let key = read_cache_key(scope, user_id);
+        let cipher_key = self.read_cache_cipher_key();
+        let mut connection = read_cache.connection.clone();
+        match connection.get::<_, Option<String>>(&key).await {
+            Ok(Some(entry)) => match open_entry(&cipher_key, &entry) {
+                Some(value) => return Ok(value),
+                None => warn!(
+                    scope = scope.as_str(),
+                    "discarding unreadable store cache entry"
+                ),
+            },
+            Ok(None) => {}
+            Err(err) => warn!(scope = scope.as_str(), "store cache read failed: {err}"),
+        }
+
+        let value = load().await?;
+        match seal_entry(&cipher_key, &value) {
+            Some(entry) => {
+                if let Err(err) = connection
+                    .set_ex::<_, _, ()>(&key, entry, read_cache.ttl_seconds)
+                    .await
+                {
+                    warn!(scope = scope.as_str(), "store cache write failed: {err}");
+                }
+            }
+            None => warn!(scope = scope.as_str(), "failed to seal store cache entry"),
+        }
+
+        Ok(value)
+    }
+
+    /// Drops the cached value for `scope` after a write. A failed delete is only logged; the
+    /// entry still expires within the cache TTL.
+    pub(super) async fn invalidate_user_read(&self, scope: ReadCacheScope, user_id: Uuid) {
+        let Some(read_cache) = self.read_cache.as_ref() else {
+            return;
+        };
+
+        let mut connection = read_cache.connection.clone();
+        if let Err(err) = connection
+            .del::<_, i64>(read_cache_key(scope, user_id))
+            .await
+        {
+            warn!(
+                scope = scope.as_str(),
+                user_id = %user_id,
+                "store cache invalidation failed: {err}"
+            );
+        }
+    }
+
+    fn read_cache_cipher_key(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(b"alfred-store-read-cache|");
+        hasher.update(self.data_encryption_key.as_bytes());
+        hasher.finalize().into()
+    }
+}
+
+fn read_cache_key(scope: ReadCacheScope, user_id: Uuid) -> String {
+    format!("{READ_CACHE_KEY_PREFIX}:{}:{user_id}", scope.as_str())
+}
+
+fn seal_entry<T: Serialize>(key: &[u8; 32], value: &T) -> Option<String> {
+    let plaintext = serde_json::to_vec(value).ok()?;
+    let nonce_source = Uuid::new_v4();
+    let nonce = &nonce_source.as_bytes()[..READ_CACHE_NONCE_LEN];
+    let ciphertext = ChaCha20Poly1305::new(key.into())
+        .encrypt(Nonce::from_slice(nonce), plaintext.as_slice())
+        .ok()?;
+
+    let mut entry = nonce.to_vec();
+    entry.extend_from_slice(&ciphertext);
+    Some(base64::engine::general_purpose::STANDARD.encode(entry))
+}
+
+/// `None` for entries that fail to decode or authenticate, including ones sealed under a
+/// data key that has since been rotated out.
+fn open_entry<T: DeserializeOwned>(key: &[u8; 32], entry: &str) -> Option<T> {
+    let entry = base64::engine::general_purpose::STANDARD
+        .decode(entry.as_bytes())
+        .ok()?;
+    if entry.len() <= READ_CACHE_NONCE_LEN {
+        return None;
+    }
+
+    let (nonce, ciphertext) = entry.split_at(READ_CACHE_NONCE_LEN);
+    let plaintext = ChaCha20Poly1305::new(key.into())
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .ok()?;
+    serde_json::from_slice(&plaintext).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{open_entry, seal_entry};
+
+    #[test]
+    fn sealed_entries_only_open_with_the_same_key() {
+        let entry =
+            seal_entry(&[1_u8; 32], &vec!["device-1".to_string()]).expect("entry should seal");
+
+        assert!(!entry.contains("device-1"));
+        assert_eq!(
+            open_entry::<Vec<String>>(&[1_u8; 32], &entry),
+            Some(vec!["device-1".to_string()])
+        );
+        assert_eq!(open_entry::<Vec<String>>(&[2_u8; 32], &entry), None);
+        assert_eq!(open_entry::<Vec<String>>(&[1_u8; 32], "not-base64!"), None);
+    }
+}
@@ -0,0 +1,46 @@
+use std::future::Future;
+
+use sqlx::PgPool;
+use tracing::warn;
+
+use super::Store;
+
+/// SQLSTATE returned by a standby that is starting up or shutting down.
+const CANNOT_CONNECT_NOW_SQLSTATE: &str = "57P03";
+
+impl Store {
+    /// Runs a read-only query against the read replica when one is configured, retrying on
+    /// the primary if the replica is unavailable. Query errors that are not connectivity
+    /// failures are returned as-is so bad queries do not silently double their load.
+    pub(super) async fn with_read_pool<T, F, Fut>(&self, query: F) -> Result<T, sqlx::Error>
+    where
+        F: Fn(PgPool) -> Fut,
+        Fut: Future<Output = Result<T, sqlx::Error>>,
+    {
+        let Some(read_pool) = self.read_pool.as_ref() else {
+            return query(self.pool.clone()).await;
+        };
+
+        match query(read_pool.clone()).await {
+            Err(err) if is_replica_unavailable(&err) => {
+                warn!("read replica unavailable, falling back to primary: {err}");
+                query(self.pool.clone()).await
+            }
+            result => result,
+        }
+    }
+}
+
+fn is_replica_unavailable(err: &sqlx::Error) -> bool {
+    match err {
+        sqlx::Error::Io(_)
+        | sqlx::Error::Tls(_)
+        | sqlx::Error::PoolTimedOut
+        | sqlx::Error::PoolClosed
+        | sqlx::Error::WorkerCrashed => true,
+        sqlx::Error::Database(db_err) => {
+            db_err.code().as_deref() == Some(CANNOT_CONNECT_NOW_SQLSTATE)
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,129 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use super::{Store, StoreError};
+
+impl Store {
+    /// Enrolls or replaces the device's request signing key. Returns `false` when the device is
+    /// not registered.
+    pub async fn set_device_request_signing_key(
+        &self,
+        user_id: Uuid,
+        device_id: &str,
+        public_key: &str,
+        registered_at: DateTime<Utc>,
+    ) -> Result<bool, StoreError> {
+        let result = sqlx::query(
+            "UPDATE devices
+             SET request_signing_public_key = $3,
+                 request_signing_key_registered_at = $4,
+                 updated_at = NOW()
+             WHERE user_id = $1
+               AND device_identifier = $2",
+        )
+        .bind(user_id)
+        .bind(device_id)
+        .bind(public_key)
+        .bind(registered_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Base64 Ed25519 public key the device enrolled, if it is registered and enrolled.
+    pub async fn get_device_request_signing_key(
+        &self,
+        user_id: Uuid,
+        device_id: &str,
+    ) -> Result<Option<String>, StoreError> {
+        let public_key = sqlx::query_scalar(
+            "SELECT request_signing_public_key
+             FROM devices
+             WHERE user_id = $1
+               AND device_identifier = $2",
+        )
+        .bind(user_id)
+        .bind(device_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(public_key.flatten())
+    }
+
+    pub async fn has_device_request_signing_keys(&self, user_id: Uuid) -> Result<bool, StoreError> {
+        let enrolled = sqlx::query_scalar(
+            "SELECT EXISTS (
+                SELECT 1
+                FROM devices
+                WHERE user_id = $1
+                  AND request_signing_public_key IS NOT NULL
+            )",
+        )
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(enrolled)
+    }
+
+    /// Records a signed request's nonce. Returns `false` when the device already used it, which
+    /// means the request is a replay.
+    pub async fn record_request_signature_nonce(
+        &self,
+        user_id: Uuid,
+        device_id: &str,
+        nonce: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<bool, StoreError> {
+        let result = sqlx::query(
+            "INSERT INTO request_signature_nonces (user_id, device_identifier, nonce, expires_at)
+             VALUES ($1, $2, $3, $4)
+             ON CONFLICT (user_id, device_identifier, nonce) DO NOTHING",
+        )
+        .bind(user_id)
+        .bind(device_id)
+        .bind(nonce)
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Deletes nonces whose signed timestamp is past the skew window; the timestamp check
+    /// alone rejects those requests from then on.
+    pub async fn purge_expired_request_signature_nonces_batch(
+        &self,
+        now: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<u64, StoreError> {
+        if limit <= 0 {
+            return Err(StoreError::InvalidData(
+                "request signature nonce purge limit must be > 0".to_string(),
+            ));
+        }
+
+        let result = sqlx::query(
+            "WITH expired AS (
+                SELECT user_id, device_identifier, nonce
+                FROM request_signature_nonces
+                WHERE expires_at <= $1
+                ORDER BY expires_at ASC
+                LIMIT $2
+                FOR UPDATE SKIP LOCKED
+             )
+             DELETE FROM request_signature_nonces nonces
+             USING expired
+             WHERE nonces.user_id = expired.user_id
+               AND nonces.device_identifier = expired.device_identifier
+               AND nonces.nonce = expired.nonce",
+        )
+        .bind(now)
+        .bind(limit)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,196 @@
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+
+use super::{Store, StoreError};
+
+/// One rolling evaluation of a latency SLO: `good_events` of `total_events` met the target.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SloEvaluation {
+    pub objective_percent: i32,
+    pub target_seconds: i64,
+    pub window_minutes: i64,
+    pub total_events: i64,
+    pub good_events: i64,
+    pub breached: bool,
+    pub evaluated_at: DateTime<Utc>,
+}
+
+/// The latest persisted SLO evaluation. `breached_since` is kept across evaluations while the
+/// SLO stays breached.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SloStatusRecord {
+    pub objective_percent: i32,
+    pub target_seconds: i64,
+    pub window_minutes: i64,
+    pub total_events: i64,
+    pub good_events: i64,
+    pub breached: bool,
+    pub breached_since: Option<DateTime<Utc>>,
+    pub evaluated_at: DateTime<Utc>,
+}
+
+const PUSH_DELIVERY_LATENCY_SLO: &str = "push_delivery_latency";
+
+impl Store {
+    /// Adds delivery counts to the current one-minute latency bucket.
+    pub async fn record_push_delivery_latency(
+        &self,
+        deliveries: i64,
+        within_target: i64,
+    ) -> Result<(), StoreError> {
+        sqlx::query(
+            "INSERT INTO push_delivery_latency (bucket_start, deliveries, within_target)
+             VALUES (date_trunc('minute', NOW()), $1, $2)
+             ON CONFLICT (bucket_start)
+             DO UPDATE SET
+               deliveries = push_delivery_latency.deliveries + EXCLUDED.deliveries,
+               within_target = push_delivery_latency.within_target + EXCLUDED.within_target,
+               updated_at = NOW()",
+        )
+        .bind(deliveries)
+        .bind(within_target)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Returns `(deliveries, within_target)` summed over buckets starting at or after `since`.
+    pub async fn push_delivery_latency_totals(
+        &self,
+        since: DateTime<Utc>,
+    ) -> Result<(i64, i64), StoreError> {
+        let row = sqlx::query(
+            "SELECT
+                COALESCE(SUM(deliveries), 0)::bigint AS deliveries,
+                COALESCE(SUM(within_target), 0)::bigint AS within_target
+             FROM push_delivery_latency
+             WHERE bucket_start >= date_trunc('minute', $1::timestamptz)",
+        )
+        .bind(since)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok((row.try_get("deliveries")?, row.try_get("within_target")?))
+    }
+
+    pub async fn prune_push_delivery_latency(
+        &self,
+        before: DateTime<Utc>,
+    ) -> Result<u64, StoreError> {
+        let result = sqlx::query("DELETE FROM push_delivery_latency WHERE bucket_start < $1")
+            .bind(before)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Stores the latest push delivery SLO evaluation and returns the stored status together
+    /// with whether the SLO was already breached before this evaluation, so callers can alert
+    /// on transitions only.
+    pub async fn record_push_delivery_slo_evaluation(
+        &self,
+        evaluation: &SloEvaluation,
+    ) -> Result<(SloStatusRecord, bool), StoreError> {
+        let row = sqlx::query(
+            "WITH previous AS (
+                SELECT breached
+                FROM slo_status
+                WHERE slo = $1
+             ),
+             upserted AS (
+                INSERT INTO slo_status (
+                  slo,
+                  objective_percent,
+                  target_seconds,
+                  window_minutes,
+                  total_events,
+                  good_events,
+                  breached,
+                  breached_since,
+                  evaluated_at
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, CASE WHEN $7 THEN $8 END, $8)
+                ON CONFLICT (slo)
+                DO UPDATE SET
+                  objective_percent = EXCLUDED.objective_percent,
+                  target_seconds = EXCLUDED.target_seconds,
+                  window_minutes = EXCLUDED.window_minutes,
+                  total_events = EXCLUDED.total_events,
+                  good_events = EXCLUDED.good_events,
+                  breached = EXCLUDED.breached,
+                  breached_since = CASE
+                    WHEN NOT EXCLUDED.breached THEN NULL
+                    WHEN slo_status.breached THEN slo_status.breached_since
+                    ELSE EXCLUDED.evaluated_at
+                  END,
+                  evaluated_at = EXCLUDED.evaluated_at
+                RETURNING
+                  objective_percent,
+                  target_seconds,
+                  window_minutes,
+                  total_events,
+                  good_events,
+                  breached,
+                  breached_since,
+                  evaluated_at
+             )
+             SELECT
+                upserted.*,
+                COALESCE((SELECT breached FROM previous), FALSE) AS previously_breached
+             FROM upserted",
+        )
+        .bind(PUSH_DELIVERY_LATENCY_SLO)
+        .bind(evaluation.objective_percent)
+        .bind(evaluation.target_seconds)
+        .bind(evaluation.window_minutes)
+        .bind(evaluation.total_events)
+        .bind(evaluation.good_events)
+        .bind(evaluation.breached)
+        .bind(evaluation.evaluated_at)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok((
+            slo_status_from_row(&row)?,
+            row.try_get("previously_breached")?,
+        ))
+    }
+
+    pub async fn get_push_delivery_slo_status(
+        &self,
+    ) -> Result<Option<SloStatusRecord>, StoreError> {
+        let row = sqlx::query(
+            "SELECT
+                objective_percent,
+                target_seconds,
+                window_minutes,
+                total_events,
+                good_events,
+                breached,
+                breached_since,
+                evaluated_at
+             FROM slo_status
+             WHERE slo = $1",
+        )
+        .bind(PUSH_DELIVERY_LATENCY_SLO)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|row| slo_status_from_row(&row)).transpose()
+    }
+}
+
+fn slo_status_from_row(row: &sqlx::postgres::PgRow) -> Result<SloStatusRecord, StoreError> {
+    Ok(SloStatusRecord {
+        objective_percent: row.try_get("objective_percent")?,
+        target_seconds: row.try_get("target_seconds")?,
+        window_minutes: row.try_get("window_minutes")?,
+        total_events: row.try_get("total_events")?,
+        good_events: row.try_get("good_events")?,
+        breached: row.try_get("breached")?,
+        breached_since: row.try_get("breached_since")?,
+        evaluated_at: row.try_get("evaluated_at")?,
+    })
+}
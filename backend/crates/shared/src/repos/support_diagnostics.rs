@@ -0,0 +1,110 @@
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+use uuid::Uuid;
+
+use super::{Store, StoreError};
+
+#[derive(Debug, Clone)]
+pub struct SupportDiagnosticRecord {
+    pub diagnostic_id: Uuid,
+    pub ticket_reference: String,
+    pub size_bytes: i32,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl Store {
+    /// Stores an already client-encrypted diagnostic bundle. The ciphertext is opaque to the
+    /// server and is only ever handed to support tooling holding the matching key.
+    pub async fn create_support_diagnostic(
+        &self,
+        user_id: Uuid,
+        ticket_reference: &str,
+        algorithm: &str,
+        key_id: &str,
+        ciphertext: &[u8],
+        expires_at: DateTime<Utc>,
+    ) -> Result<SupportDiagnosticRecord, StoreError> {
+        self.ensure_user(user_id).await?;
+
+        let size_bytes = i32::try_from(ciphertext.len()).map_err(|_| {
+            StoreError::InvalidData("support diagnostic bundle is too large".to_string())
+        })?;
+        let row = sqlx::query(
+            "INSERT INTO support_diagnostics (
+                user_id,
+                ticket_reference,
+                algorithm,
+                key_id,
+                ciphertext,
+                size_bytes,
+                expires_at
+             )
+             VALUES ($1, $2, $3, $4, $5, $6, $7)
+             RETURNING id, ticket_reference, size_bytes, created_at, expires_at",
+        )
+        .bind(user_id)
+        .bind(ticket_reference)
+        .bind(algorithm)
+        .bind(key_id)
+        .bind(ciphertext)
+        .bind(size_bytes)
+        .bind(expires_at)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(SupportDiagnosticRecord {
+            diagnostic_id: row.try_get("id")?,
+            ticket_reference: row.try_get("ticket_reference")?,
+            size_bytes: row.try_get("size_bytes")?,
+            created_at: row.try_get("created_at")?,
+            expires_at: row.try_get("expires_at")?,
+        })
+    }
+
+    pub async fn delete_support_diagnostic(
+        &self,
+        user_id: Uuid,
+        diagnostic_id: Uuid,
+    ) -> Result<bool, StoreError> {
+        let result = sqlx::query("DELETE FROM support_diagnostics WHERE user_id = $1 AND id = $2")
+            .bind(user_id)
+            .bind(diagnostic_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn purge_expired_support_diagnostics_batch(
+        &self,
+        now: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<u64, StoreError> {
+        if limit <= 0 {
+            return Err(StoreError::InvalidData(
+                "support diagnostic purge limit must be > 0".to_string(),
+            ));
+        }
+
+        let result = sqlx::query(
+            "WITH expired AS (
+                SELECT id
+                FROM support_diagnostics
+                WHERE expires_at <= $1
+                ORDER BY expires_at ASC, id ASC
+                LIMIT $2
+                FOR UPDATE SKIP LOCKED
+             )
+             DELETE FROM support_diagnostics diagnostics
+             USING expired
+             WHERE diagnostics.id = expired.id",
+        )
+        .bind(now)
+        .bind(limit)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,90 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::models::{QuotaKind, UserPlan};
+
+use super::organizations::parse_plan;
+use super::{Store, StoreError};
+
+impl Store {
+    /// Returns the user's plan: the higher of their own plan and their organization's. Users
+    /// with neither are on the free plan.
+    pub async fn get_user_plan(&self, user_id: Uuid) -> Result<UserPlan, StoreError> {
+        let (user_plan, organization_plan): (Option<String>, Option<String>) = sqlx::query_as(
+            "SELECT
+                (SELECT plan FROM user_plans WHERE user_id = $1),
+                (SELECT op.plan
+                 FROM users u
+                 JOIN organization_plans op ON op.org_id = u.org_id
+                 WHERE u.id = $1)",
+        )
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        [user_plan, organization_plan]
+            .into_iter()
+            .flatten()
+            .map(|plan| parse_plan(&plan))
+            .try_fold(UserPlan::Free, |highest, plan| Ok(highest.max(plan?)))
+    }
+
+    pub async fn set_user_plan(&self, user_id: Uuid, plan: UserPlan) -> Result<(), StoreError> {
+        sqlx::query(
+            "INSERT INTO user_plans (user_id, plan)
+             VALUES ($1, $2)
+             ON CONFLICT (user_id)
+             DO UPDATE SET plan = EXCLUDED.plan, updated_at = NOW()",
+        )
+        .bind(user_id)
+        .bind(plan.as_str())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Counts what the user has used of `kind`. Daily quotas count from `day_start`.
+    ///
+    /// LLM requests cover assistant queries plus scheduled and manual automation runs, each of
+    /// which costs one enclave LLM call.
+    pub async fn count_quota_usage(
+        &self,
+        user_id: Uuid,
+        kind: QuotaKind,
+        day_start: DateTime<Utc>,
+    ) -> Result<i64, StoreError> {
+        let query = match kind {
+            QuotaKind::AssistantQueriesPerDay => sqlx::query_scalar(
+                "SELECT COUNT(*)::bigint
+                 FROM assistant_request_index
+                 WHERE user_id = $1
+                   AND created_at >= $2",
+            )
+            .bind(user_id)
+            .bind(day_start),
+            QuotaKind::AutomationRules => sqlx::query_scalar(
+                "SELECT COUNT(*)::bigint
+                 FROM automation_rules
+                 WHERE user_id = $1
+                   AND status NOT IN ('ARCHIVED', 'COMPLETED')",
+            )
+            .bind(user_id),
+            QuotaKind::LlmRequestsPerDay => sqlx::query_scalar(
+                "SELECT (
+                    (SELECT COUNT(*) FROM assistant_request_index
+                     WHERE user_id = $1 AND created_at >= $2)
+                  + (SELECT COUNT(*) FROM automation_runs
+                     WHERE user_id = $1 AND created_at >= $2)
+                  + (SELECT COUNT(*) FROM automation_manual_runs
+                     WHERE user_id = $1 AND created_at >= $2)
+                 )::bigint",
+            )
+            .bind(user_id)
+            .bind(day_start),
+        };
+
+        let used: i64 = query.fetch_one(&self.pool).await?;
+        Ok(used)
+    }
+}
@@ -1,30 +1,84 @@
+use std::sync::Arc;
+use std::time::Duration;
+
 use sqlx::PgPool;
 use sqlx::postgres::PgPoolOptions;
 use uuid::Uuid;
 
-use super::{Store, StoreError};
+use super::{DataEncryptionKeyring, Store, StoreError, StorePoolStats};
+use crate::audit_redaction::AuditRedactionPolicy;
+
+const READ_REPLICA_ACQUIRE_TIMEOUT: Duration = Duration::from_secs(2);
 
 impl Store {
+    /// Connects the primary pool and, when `database_read_url` is set, a lazily connected
+    /// replica pool used by read-only queries. The replica never blocks startup; reads fall
+    /// back to the primary while it is unreachable.
     pub async fn connect(
         database_url: &str,
+        database_read_url: Option<&str>,
         max_connections: u32,
-        data_encryption_key: &str,
+        data_encryption_keys: &DataEncryptionKeyring,
     ) -> Result<Self, sqlx::Error> {
         let pool = PgPoolOptions::new()
             .max_connections(max_connections)
             .connect(database_url)
             .await?;
+        let read_pool = database_read_url
+            .map(|database_read_url| {
+                PgPoolOptions::new()
+                    .max_connections(max_connections)
+                    .acquire_timeout(READ_REPLICA_ACQUIRE_TIMEOUT)
+                    .connect_lazy(database_read_url)
+            })
+            .transpose()?;
+
+        let mut data_encryption_key_ids = vec![data_encryption_keys.primary.key_id.clone()];
+        let mut keys = vec![data_encryption_keys.primary.key.clone()];
+        if let Some(secondary) = data_encryption_keys.secondary.as_ref() {
+            data_encryption_key_ids.push(secondary.key_id.clone());
+            keys.push(secondary.key.clone());
+        }
 
         Ok(Self {
             pool,
-            data_encryption_key: data_encryption_key.to_string(),
+            read_pool,
+            data_encryption_key: data_encryption_keys.primary.key.clone(),
+            data_encryption_key_id: data_encryption_keys.primary.key_id.clone(),
+            data_encryption_key_ids,
+            data_encryption_keys: keys,
+            secondary_data_encryption_key: data_encryption_keys.secondary.clone(),
+            audit_redaction_policy: Arc::new(AuditRedactionPolicy::default()),
+            audit_event_signer: None,
+            read_cache: None,
         })
     }
 
+    /// Replaces the default redaction policy applied to audit metadata on write.
+    pub fn with_audit_redaction_policy(mut self, policy: AuditRedactionPolicy) -> Self {
+        self.audit_redaction_policy = Arc::new(policy);
+        self
+    }
+
     pub fn pool(&self) -> &PgPool {
         &self.pool
     }
 
+    pub fn pool_stats(&self) -> Vec<StorePoolStats> {
+        let stats = |pool: &'static str, pg_pool: &PgPool| StorePoolStats {
+            pool,
+            size: pg_pool.size(),
+            idle: pg_pool.num_idle() as u32,
+            max_connections: pg_pool.options().get_max_connections(),
+        };
+
+        let mut pools = vec![stats("primary", &self.pool)];
+        if let Some(read_pool) = self.read_pool.as_ref() {
+            pools.push(stats("read_replica", read_pool));
+        }
+        pools
+    }
+
     pub async fn ping(&self) -> Result<(), StoreError> {
         let _: i32 = sqlx::query_scalar("SELECT 1").fetch_one(&self.pool).await?;
         Ok(())
@@ -44,4 +98,12 @@ impl Store {
             .await?;
         Ok(())
     }
+
+    pub async fn user_exists(&self, user_id: Uuid) -> Result<bool, StoreError> {
+        let exists: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM users WHERE id = $1)")
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(exists)
+    }
 }
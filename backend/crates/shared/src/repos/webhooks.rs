@@ -0,0 +1,518 @@
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use sqlx::Row;
+use uuid::Uuid;
+
+use crate::pagination::{Page, PageKey, PageRequest};
+use crate::webhooks::{WebhookDeliveryState, WebhookEvent, WebhookEventType};
+
+use super::{Store, StoreError};
+
+#[derive(Debug, Clone)]
+pub struct WebhookSubscriptionRecord {
+    pub id: Uuid,
+    pub url: String,
+    pub event_types: Vec<WebhookEventType>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct WebhookDeliveryRecord {
+    pub id: Uuid,
+    pub subscription_id: Uuid,
+    pub event_id: Uuid,
+    pub event_type: WebhookEventType,
+    pub state: WebhookDeliveryState,
+    pub attempts: i32,
+    pub next_attempt_at: Option<DateTime<Utc>>,
+    pub last_status_code: Option<i32>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+}
+
+/// A due delivery leased to one worker. `attempts` already counts the attempt being made.
+#[derive(Debug, Clone)]
+pub struct ClaimedWebhookDelivery {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub attempts: i32,
+    pub target: WebhookTarget,
+    pub event: WebhookEvent,
+}
+
+/// Where and how to sign one subscription's requests; holds the decrypted signing secret.
+#[derive(Clone)]
+pub struct WebhookTarget {
+    pub subscription_id: Uuid,
+    pub url: String,
+    pub signing_secret: String,
+}
+
+impl std::fmt::Debug for WebhookTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebhookTarget")
+            .field("subscription_id", &self.subscription_id)
+            .field("url", &self.url)
+            .field("signing_secret", &"<redacted>")
+            .finish()
+    }
+}
+
+impl Store {
+    pub async fn create_webhook_subscription(
+        &self,
+        user_id: Uuid,
+        url: &str,
+        event_types: &[WebhookEventType],
+        signing_secret: &str,
+    ) -> Result<WebhookSubscriptionRecord, StoreError> {
+        self.ensure_user(user_id).await?;
+
+        let row = sqlx::query(
+            "INSERT INTO webhook_subscriptions (
+                user_id,
+                url,
+                event_types,
+                signing_secret_ciphertext,
+                data_key_id
+             )
+             VALUES ($1, $2, $3, pgp_sym_encrypt($4, $5), $6)
+             RETURNING id, url, event_types, created_at, updated_at",
+        )
+        .bind(user_id)
+        .bind(url)
+        .bind(event_type_names(event_types))
+        .bind(signing_secret)
+        .bind(&self.data_encryption_key)
+        .bind(&self.data_encryption_key_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        webhook_subscription_from_row(&row)
+    }
+
+    pub async fn count_webhook_subscriptions(&self, user_id: Uuid) -> Result<i64, StoreError> {
+        let count = sqlx::query_scalar(
+            "SELECT COUNT(*)
+             FROM webhook_subscriptions
+             WHERE user_id = $1",
+        )
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(count)
+    }
+
+    pub async fn list_webhook_subscriptions(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<WebhookSubscriptionRecord>, StoreError> {
+        let rows = sqlx::query(
+            "SELECT id, url, event_types, created_at, updated_at
+             FROM webhook_subscriptions
+             WHERE user_id = $1
+             ORDER BY created_at, id",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(webhook_subscription_from_row).collect()
+    }
+
+    pub async fn get_webhook_subscription(
+        &self,
+        user_id: Uuid,
+        subscription_id: Uuid,
+    ) -> Result<Option<WebhookSubscriptionRecord>, StoreError> {
+        let row = sqlx::query(
+            "SELECT id, url, event_types, created_at, updated_at
+             FROM webhook_subscriptions
+             WHERE user_id = $1
+               AND id = $2",
+        )
+        .bind(user_id)
+        .bind(subscription_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.as_ref().map(webhook_subscription_from_row).transpose()
+    }
+
+    /// Deleting a subscription also drops its delivery log and any deliveries still pending.
+    pub async fn delete_webhook_subscription(
+        &self,
+        user_id: Uuid,
+        subscription_id: Uuid,
+    ) -> Result<bool, StoreError> {
+        let result = sqlx::query(
+            "DELETE FROM webhook_subscriptions
+             WHERE user_id = $1
+               AND id = $2",
+        )
+        .bind(user_id)
+        .bind(subscription_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Queues `event` for every subscription of the user that listens to its type. Queueing
+    /// the same event id twice is a no-op, so callers may retry. Returns the deliveries queued.
+    pub async fn enqueue_webhook_event(
+        &self,
+        user_id: Uuid,
+        event: &WebhookEvent,
+    ) -> Result<u64, StoreError> {
+        let result = sqlx::query(
+            "INSERT INTO webhook_deliveries (
+                subscription_id,
+                user_id,
+                event_id,
+                event_type,
+                payload,
+                occurred_at,
+                next_attempt_at
+             )
+             SELECT id, user_id, $2, $3, $4, $5, NOW()
+             FROM webhook_subscriptions
+             WHERE user_id = $1
+               AND $3 = ANY(event_types)
+             ON CONFLICT (subscription_id, event_id) DO NOTHING",
+        )
+        .bind(user_id)
+        .bind(event.id)
+        .bind(event.event_type.as_str())
+        .bind(&event.data)
+        .bind(event.occurred_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    pub async fn list_webhook_deliveries(
+        &self,
+        user_id: Uuid,
+        subscription_id: Uuid,
+        page: PageRequest,
+    ) -> Result<Page<WebhookDeliveryRecord>, StoreError> {
+        if page.limit == 0 {
+            return Err(StoreError::InvalidData(
+                "webhook delivery list limit must be > 0".to_string(),
+            ));
+        }
+
+        let rows = sqlx::query(
+            "SELECT
+                id,
+                subscription_id,
+                event_id,
+                event_type,
+                state,
+                attempts,
+                next_attempt_at,
+                last_status_code,
+                last_error,
+                created_at,
+                delivered_at
+             FROM webhook_deliveries
+             WHERE user_id = $1
+               AND subscription_id = $2
+               AND (
+                 $3::timestamptz IS NULL
+                 OR created_at < $3
+                 OR (created_at = $3 AND id < $4)
+               )
+             ORDER BY created_at DESC, id DESC
+             LIMIT $5",
+        )
+        .bind(user_id)
+        .bind(subscription_id)
+        .bind(page.after_at())
+        .bind(page.after_id())
+        .bind(page.fetch_limit())
+        .fetch_all(&self.pool)
+        .await?;
+
+        let keyed_rows = rows
+            .iter()
+            .map(|row| {
+                let delivery = webhook_delivery_from_row(row)?;
+                Ok((
+                    PageKey {
+                        pinned: false,
+                        at: delivery.created_at,
+                        id: delivery.id,
+                    },
+                    delivery,
+                ))
+            })
+            .collect::<Result<Vec<_>, StoreError>>()?;
+
+        Ok(Page::from_keyed_rows(keyed_rows, page))
+    }
+
+    /// Leases up to `limit` due deliveries to `worker_id` by pushing `next_attempt_at` out by
+    /// `lease_seconds` and counting the attempt up front, so a worker that dies mid-request
+    /// neither blocks the row nor gets unlimited retries. Deliveries whose last attempt was
+    /// leased and never settled are given up as `FAILED` here.
+    pub async fn claim_due_webhook_deliveries(
+        &self,
+        now: DateTime<Utc>,
+        worker_id: Uuid,
+        lease_seconds: i64,
+        max_attempts: i32,
+        limit: i64,
+    ) -> Result<Vec<ClaimedWebhookDelivery>, StoreError> {
+        if limit <= 0 {
+            return Err(StoreError::InvalidData(
+                "webhook delivery claim limit must be > 0".to_string(),
+            ));
+        }
+
+        sqlx::query(
+            "UPDATE webhook_deliveries
+             SET state = 'FAILED',
+                 lease_owner = NULL,
+                 last_error = COALESCE(last_error, 'delivery attempt did not finish')
+             WHERE state = 'PENDING'
+               AND next_attempt_at <= $1
+               AND attempts >= $2",
+        )
+        .bind(now)
+        .bind(max_attempts)
+        .execute(&self.pool)
+        .await?;
+
+        let rows = sqlx::query(
+            "WITH due AS (
+                SELECT id
+                FROM webhook_deliveries
+                WHERE state = 'PENDING'
+                  AND next_attempt_at <= $1
+                  AND attempts < $3
+                ORDER BY next_attempt_at, id
+                LIMIT $4
+                FOR UPDATE SKIP LOCKED
+             ),
+             claimed AS (
+                UPDATE webhook_deliveries d
+                SET attempts = d.attempts + 1,
+                    next_attempt_at = $1 + ($2::bigint * INTERVAL '1 second'),
+                    lease_owner = $7
+                FROM due
+                WHERE d.id = due.id
+                RETURNING
+                  d.id,
+                  d.subscription_id,
+                  d.user_id,
+                  d.event_id,
+                  d.event_type,
+                  d.payload,
+                  d.occurred_at,
+                  d.attempts
+             )
+             SELECT
+                claimed.*,
+                s.url,
+                pgp_sym_decrypt(
+                  s.signing_secret_ciphertext,
+                  alfred_data_key(s.data_key_id, $5, $6)
+                ) AS signing_secret
+             FROM claimed
+             JOIN webhook_subscriptions s ON s.id = claimed.subscription_id
+             ORDER BY claimed.id",
+        )
+        .bind(now)
+        .bind(lease_seconds)
+        .bind(max_attempts)
+        .bind(limit)
+        .bind(&self.data_encryption_key_ids)
+        .bind(&self.data_encryption_keys)
+        .bind(worker_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                let event_type: String = row.try_get("event_type")?;
+                let payload: Value = row.try_get("payload")?;
+                Ok(ClaimedWebhookDelivery {
+                    id: row.try_get("id")?,
+                    user_id: row.try_get("user_id")?,
+                    attempts: row.try_get("attempts")?,
+                    target: WebhookTarget {
+                        subscription_id: row.try_get("subscription_id")?,
+                        url: row.try_get("url")?,
+                        signing_secret: row.try_get("signing_secret")?,
+                    },
+                    event: WebhookEvent {
+                        id: row.try_get("event_id")?,
+                        event_type: webhook_event_type_from_db(&event_type)?,
+                        occurred_at: row.try_get("occurred_at")?,
+                        data: payload,
+                    },
+                })
+            })
+            .collect()
+    }
+
+    /// Settles a delivery leased to `worker_id`. Returns false when the lease was lost.
+    pub async fn mark_webhook_delivery_delivered(
+        &self,
+        delivery_id: Uuid,
+        worker_id: Uuid,
+        status_code: i32,
+        delivered_at: DateTime<Utc>,
+    ) -> Result<bool, StoreError> {
+        let result = sqlx::query(
+            "UPDATE webhook_deliveries
+             SET state = 'DELIVERED',
+                 lease_owner = NULL,
+                 last_status_code = $2,
+                 last_error = NULL,
+                 delivered_at = $3
+             WHERE id = $1
+               AND state = 'PENDING'
+               AND lease_owner = $4",
+        )
+        .bind(delivery_id)
+        .bind(status_code)
+        .bind(delivered_at)
+        .bind(worker_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Records a failed attempt of a delivery leased to `worker_id`. With `retry_at` the
+    /// delivery stays `PENDING` until then; without it the delivery is given up as `FAILED`.
+    pub async fn record_webhook_delivery_failure(
+        &self,
+        delivery_id: Uuid,
+        worker_id: Uuid,
+        status_code: Option<i32>,
+        error: &str,
+        retry_at: Option<DateTime<Utc>>,
+    ) -> Result<bool, StoreError> {
+        let result = sqlx::query(
+            "UPDATE webhook_deliveries
+             SET state = CASE WHEN $4::timestamptz IS NULL THEN 'FAILED' ELSE 'PENDING' END,
+                 next_attempt_at = COALESCE($4, next_attempt_at),
+                 lease_owner = NULL,
+                 last_status_code = $2,
+                 last_error = $3
+             WHERE id = $1
+               AND state = 'PENDING'
+               AND lease_owner = $5",
+        )
+        .bind(delivery_id)
+        .bind(status_code)
+        .bind(error)
+        .bind(retry_at)
+        .bind(worker_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Subscriptions of the user that listen to `event_type`, with decrypted secrets. Used
+    /// when an event has to be sent without a persisted delivery row.
+    pub async fn list_webhook_targets(
+        &self,
+        user_id: Uuid,
+        event_type: WebhookEventType,
+    ) -> Result<Vec<WebhookTarget>, StoreError> {
+        let rows = sqlx::query(
+            "SELECT
+                id,
+                url,
+                pgp_sym_decrypt(
+                  signing_secret_ciphertext,
+                  alfred_data_key(data_key_id, $3, $4)
+                ) AS signing_secret
+             FROM webhook_subscriptions
+             WHERE user_id = $1
+               AND $2 = ANY(event_types)
+             ORDER BY created_at, id",
+        )
+        .bind(user_id)
+        .bind(event_type.as_str())
+        .bind(&self.data_encryption_key_ids)
+        .bind(&self.data_encryption_keys)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(WebhookTarget {
+                    subscription_id: row.try_get("id")?,
+                    url: row.try_get("url")?,
+                    signing_secret: row.try_get("signing_secret")?,
+                })
+            })
+            .collect()
+    }
+}
+
+fn event_type_names(event_types: &[WebhookEventType]) -> Vec<String> {
+    event_types
+        .iter()
+        .map(|event_type| event_type.as_str().to_string())
+        .collect()
+}
+
+fn webhook_event_type_from_db(value: &str) -> Result<WebhookEventType, StoreError> {
+    WebhookEventType::parse(value).ok_or_else(|| {
+        StoreError::InvalidData(format!("unknown webhook event type persisted: {value}"))
+    })
+}
+
+fn webhook_subscription_from_row(
+    row: &sqlx::postgres::PgRow,
+) -> Result<WebhookSubscriptionRecord, StoreError> {
+    let event_types: Vec<String> = row.try_get("event_types")?;
+
+    Ok(WebhookSubscriptionRecord {
+        id: row.try_get("id")?,
+        url: row.try_get("url")?,
+        event_types: event_types
+            .iter()
+            .map(|value| webhook_event_type_from_db(value))
+            .collect::<Result<_, _>>()?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
+
+fn webhook_delivery_from_row(
+    row: &sqlx::postgres::PgRow,
+) -> Result<WebhookDeliveryRecord, StoreError> {
+    let event_type: String = row.try_get("event_type")?;
+    let state: String = row.try_get("state")?;
+    let state = WebhookDeliveryState::parse(&state).ok_or_else(|| {
+        StoreError::InvalidData(format!("unknown webhook delivery state persisted: {state}"))
+    })?;
+    let next_attempt_at: DateTime<Utc> = row.try_get("next_attempt_at")?;
+
+    Ok(WebhookDeliveryRecord {
+        id: row.try_get("id")?,
+        subscription_id: row.try_get("subscription_id")?,
+        event_id: row.try_get("event_id")?,
+        event_type: webhook_event_type_from_db(&event_type)?,
+        state,
+        attempts: row.try_get("attempts")?,
+        next_attempt_at: (state == WebhookDeliveryState::Pending).then_some(next_attempt_at),
+        last_status_code: row.try_get("last_status_code")?,
+        last_error: row.try_get("last_error")?,
+        created_at: row.try_get("created_at")?,
+        delivered_at: row.try_get("delivered_at")?,
+    })
+}
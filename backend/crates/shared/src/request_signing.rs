@@ -0,0 +1,163 @@
+//! Device-bound request signing for mobile clients.
+//!
+//! A device registers an Ed25519 public key and signs every request with the private half,
+//! which never leaves the device. A bearer token lifted from a device is then not enough on
+//! its own: the api-server also needs a fresh signature over the request from the key the
+//! device enrolled.
+
+use base64::Engine as _;
+use ed25519_dalek::{Signature, VerifyingKey};
+use sha2::{Digest, Sha256};
+
+pub const REQUEST_SIGNING_ALGORITHM_ED25519: &str = "ed25519";
+pub const REQUEST_SIGNING_DEVICE_ID_HEADER: &str = "x-alfred-device-id";
+pub const REQUEST_SIGNING_TIMESTAMP_HEADER: &str = "x-alfred-request-timestamp";
+pub const REQUEST_SIGNING_NONCE_HEADER: &str = "x-alfred-request-nonce";
+pub const REQUEST_SIGNING_SIGNATURE_HEADER: &str = "x-alfred-request-signature";
+
+const CANONICAL_REQUEST_PREFIX: &str = "ALFRED-REQUEST-V1";
+const MIN_NONCE_LEN: usize = 16;
+const MAX_NONCE_LEN: usize = 128;
+
+/// The string a device signs: the version tag, uppercase method, path with query exactly as
+/// sent, hex SHA-256 of the raw body, unix timestamp in seconds and the nonce, joined by `\n`.
+pub fn canonical_request(
+    method: &str,
+    path_and_query: &str,
+    body: &[u8],
+    timestamp: i64,
+    nonce: &str,
+) -> String {
+    let body_sha256 = Sha256::digest(body);
+    let mut body_hex = String::with_capacity(64);
+    for byte in body_sha256 {
+        use std::fmt::Write;
+        let _ = write!(body_hex, "{byte:02x}");
+    }
+
+    format!(
+        "{CANONICAL_REQUEST_PREFIX}\n{method}\n{path_and_query}\n{body_hex}\n{timestamp}\n{nonce}"
+    )
+}
+
+/// Decodes a standard-base64 Ed25519 public key, rejecting weak or malformed points.
+pub fn decode_request_signing_public_key(encoded: &str) -> Option<VerifyingKey> {
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(encoded.trim())
+        .ok()?;
+    let bytes: [u8; 32] = bytes.try_into().ok()?;
+    let key = VerifyingKey::from_bytes(&bytes).ok()?;
+    (!key.is_weak()).then_some(key)
+}
+
+/// Verifies a standard-base64 Ed25519 signature over [`canonical_request`].
+pub fn verify_request_signature(
+    encoded_public_key: &str,
+    canonical_request: &str,
+    encoded_signature: &str,
+) -> bool {
+    let Some(public_key) = decode_request_signing_public_key(encoded_public_key) else {
+        return false;
+    };
+    let Ok(signature_bytes) =
+        base64::engine::general_purpose::STANDARD.decode(encoded_signature.trim())
+    else {
+        return false;
+    };
+    let Ok(signature) = Signature::from_slice(&signature_bytes) else {
+        return false;
+    };
+
+    public_key
+        .verify_strict(canonical_request.as_bytes(), &signature)
+        .is_ok()
+}
+
+/// Nonces are opaque to the server but must carry enough entropy to be unique per device.
+pub fn is_valid_request_nonce(nonce: &str) -> bool {
+    (MIN_NONCE_LEN..=MAX_NONCE_LEN).contains(&nonce.len())
+        && nonce
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_'))
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::Engine as _;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    use super::{
+        canonical_request, decode_request_signing_public_key, is_valid_request_nonce,
+        verify_request_signature,
+    };
+
+    fn encode(bytes: &[u8]) -> String {
+        base64::engine::general_purpose::STANDARD.encode(bytes)
+    }
+
+    #[test]
+    fn signatures_bind_every_canonical_field() {
+        let signing_key = SigningKey::from_bytes(&[7_u8; 32]);
+        let public_key = encode(signing_key.verifying_key().as_bytes());
+        let canonical = canonical_request("POST", "/v1/x?a=1", b"{}", 1_700, "nonce-0123456789");
+        let signature = encode(&signing_key.sign(canonical.as_bytes()).to_bytes());
+
+        assert!(verify_request_signature(
+            &public_key,
+            &canonical,
+            &signature
+        ));
+        for (method, path, body, timestamp, nonce) in [
+            ("PUT", "/v1/x?a=1", b"{}", 1_700, "nonce-0123456789"),
+            ("POST", "/v1/x?a=2", b"{}", 1_700, "nonce-0123456789"),
+            ("POST", "/v1/x?a=1", b"[]", 1_700, "nonce-0123456789"),
+            ("POST", "/v1/x?a=1", b"{}", 1_701, "nonce-0123456789"),
+            ("POST", "/v1/x?a=1", b"{}", 1_700, "nonce-0123456780"),
+        ] {
+            let tampered = canonical_request(method, path, body, timestamp, nonce);
+            assert!(!verify_request_signature(
+                &public_key,
+                &tampered,
+                &signature
+            ));
+        }
+
+        let other_key = encode(
+            SigningKey::from_bytes(&[8_u8; 32])
+                .verifying_key()
+                .as_bytes(),
+        );
+        assert!(!verify_request_signature(
+            &other_key, &canonical, &signature
+        ));
+        assert!(!verify_request_signature(
+            &public_key,
+            &canonical,
+            "not-base64"
+        ));
+    }
+
+    #[test]
+    fn public_keys_must_be_32_byte_non_weak_points() {
+        let signing_key = SigningKey::from_bytes(&[7_u8; 32]);
+        assert!(
+            decode_request_signing_public_key(&encode(signing_key.verifying_key().as_bytes()))
+                .is_some()
+        );
+        assert!(decode_request_signing_public_key(&encode(&[1_u8; 31])).is_none());
+        assert!(decode_request_signing_public_key("%%%").is_none());
+
+        let mut identity = [0_u8; 32];
+        identity[0] = 1;
+        assert!(decode_request_signing_public_key(&encode(&identity)).is_none());
+    }
+
+    #[test]
+    fn nonces_are_url_safe_and_bounded() {
+        assert!(is_valid_request_nonce("0123456789abcdef"));
+        assert!(is_valid_request_nonce(&"a".repeat(128)));
+        assert!(!is_valid_request_nonce("short"));
+        assert!(!is_valid_request_nonce(&"a".repeat(129)));
+        assert!(!is_valid_request_nonce("0123456789abcde/"));
+    }
+}
@@ -0,0 +1,701 @@
+use std::collections::HashMap;
+use std::env;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::aws::{AwsCallError, AwsCredentials, AwsJsonClient};
+
+/// Env var values starting with this are resolved through the secrets provider at startup.
+pub const SECRET_REFERENCE_PREFIX: &str = "secret://";
+const DEFAULT_SECRETS_CACHE_TTL_SECONDS: u64 = 300;
+const DEFAULT_VAULT_KV_MOUNT: &str = "secret";
+const SECRETS_MANAGER_SERVICE: &str = "secretsmanager";
+const SECRETS_MANAGER_TARGET_PREFIX: &str = "secretsmanager";
+/// Env vars that configure the provider itself, so they cannot be references.
+const PROVIDER_ENV_VARS: [&str; 9] = [
+    "SECRETS_PROVIDER",
+    "SECRETS_CACHE_TTL_SECONDS",
+    "SECRETS_MANAGER_ENDPOINT",
+    "AWS_REGION",
+    "AWS_ACCESS_KEY_ID",
+    "AWS_SECRET_ACCESS_KEY",
+    "AWS_SESSION_TOKEN",
+    "VAULT_ADDR",
+    "VAULT_TOKEN",
+];
+
+/// `secret://<name>` or `secret://<name>#<field>`. `field` selects a key of a secret stored as
+/// a JSON object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecretReference {
+    pub name: String,
+    pub field: Option<String>,
+}
+
+impl SecretReference {
+    /// `None` when `raw` is a plain value rather than a reference.
+    pub fn parse(raw: &str) -> Option<Result<Self, SecretsError>> {
+        let reference = raw.trim().strip_prefix(SECRET_REFERENCE_PREFIX)?;
+        let (name, field) = match reference.split_once('#') {
+            Some((name, field)) => (name, Some(field.to_string())),
+            None => (reference, None),
+        };
+        if name.is_empty() || field.as_deref().is_some_and(str::is_empty) {
+            return Some(Err(SecretsError::InvalidReference(raw.to_string())));
+        }
+        Some(Ok(Self {
+            name: name.to_string(),
+            field,
+        }))
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum SecretsError {
+    #[error("invalid secret reference '{0}'")]
+    InvalidReference(String),
+    #[error("invalid secrets provider configuration: {0}")]
+    InvalidConfiguration(String),
+    #[error("secret '{name}' was not found")]
+    NotFound { name: String },
+    #[error("secret '{name}' has no field '{field}'")]
+    MissingField { name: String, field: String },
+    #[error("secret '{name}' holds several values; reference one with secret://{name}#<field>")]
+    FieldRequired { name: String },
+    #[error("failed to fetch secret '{name}': {message}")]
+    FetchFailed { name: String, message: String },
+}
+
+/// Where `secret://` references are looked up.
+#[derive(Debug, Clone)]
+pub enum SecretsProvider {
+    /// The reference names another env var. Useful locally and as a no-op default.
+    Env,
+    AwsSecretsManager(AwsSecretsManagerProvider),
+    Vault(VaultProvider),
+}
+
+impl SecretsProvider {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Env => "env",
+            Self::AwsSecretsManager(_) => "aws",
+            Self::Vault(_) => "vault",
+        }
+    }
+
+    async fn fetch(&self, name: &str) -> Result<FetchedSecret, SecretsError> {
+        match self {
+            Self::Env => env::var(name)
+                .map(|value| FetchedSecret {
+                    value,
+                    version: None,
+                })
+                .map_err(|_| SecretsError::NotFound {
+                    name: name.to_string(),
+                }),
+            Self::AwsSecretsManager(provider) => provider.fetch(name).await,
+            Self::Vault(provider) => provider.fetch(name).await,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AwsSecretsManagerProvider {
+    api: AwsJsonClient,
+}
+
+impl AwsSecretsManagerProvider {
+    pub fn new(
+        region: impl Into<String>,
+        endpoint: Option<String>,
+        credentials: AwsCredentials,
+        http_client: reqwest::Client,
+    ) -> Self {
+        Self {
+            api: AwsJsonClient::new(
+                SECRETS_MANAGER_SERVICE,
+                SECRETS_MANAGER_TARGET_PREFIX,
+                region.into(),
+                endpoint,
+                credentials,
+                http_client,
+            ),
+        }
+    }
+
+    async fn fetch(&self, name: &str) -> Result<FetchedSecret, SecretsError> {
+        let response: GetSecretValueResponse = self
+            .api
+            .call("GetSecretValue", &GetSecretValueRequest { secret_id: name })
+            .await
+            .map_err(|err| match err {
+                AwsCallError::Rejected { error_type, .. }
+                    if error_type == "ResourceNotFoundException" =>
+                {
+                    SecretsError::NotFound {
+                        name: name.to_string(),
+                    }
+                }
+                err => SecretsError::FetchFailed {
+                    name: name.to_string(),
+                    message: err.to_string(),
+                },
+            })?;
+        let value = response
+            .secret_string
+            .ok_or_else(|| SecretsError::FetchFailed {
+                name: name.to_string(),
+                message: "only SecretString secrets are supported".to_string(),
+            })?;
+        Ok(FetchedSecret {
+            value,
+            version: response.version_id,
+        })
+    }
+}
+
+/// Reads KV version 2 secrets.
+#[derive(Clone)]
+pub struct VaultProvider {
+    addr: String,
+    token: String,
+    kv_mount: String,
+    http_client: reqwest::Client,
+}
+
+impl VaultProvider {
+    pub fn new(
+        addr: impl Into<String>,
+        token: impl Into<String>,
+        kv_mount: impl Into<String>,
+        http_client: reqwest::Client,
+    ) -> Self {
+        Self {
+            addr: addr.into().trim_end_matches('/').to_string(),
+            token: token.into(),
+            kv_mount: kv_mount.into().trim_matches('/').to_string(),
+            http_client,
+        }
+    }
+
+    async fn fetch(&self, name: &str) -> Result<FetchedSecret, SecretsError> {
+        let fetch_failed = |message: String| SecretsError::FetchFailed {
+            name: name.to_string(),
+            message,
+        };
+        let response = self
+            .http_client
+            .get(format!(
+                "{}/v1/{}/data/{}",
+                self.addr,
+                self.kv_mount,
+                name.trim_start_matches('/')
+            ))
+            .header("x-vault-token", &self.token)
+            .send()
+            .await
+            .map_err(|err| fetch_failed(err.to_string()))?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(SecretsError::NotFound {
+                name: name.to_string(),
+            });
+        }
+        if !response.status().is_success() {
+            return Err(fetch_failed(format!(
+                "vault responded with status={}",
+                response.status().as_u16()
+            )));
+        }
+        let response = response
+            .json::<VaultKvResponse>()
+            .await
+            .map_err(|err| fetch_failed(err.to_string()))?;
+
+        Ok(FetchedSecret {
+            value: Value::Object(response.data.data).to_string(),
+            version: response
+                .data
+                .metadata
+                .and_then(|metadata| metadata.version)
+                .map(|version| version.to_string()),
+        })
+    }
+}
+
+impl std::fmt::Debug for VaultProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VaultProvider")
+            .field("addr", &self.addr)
+            .field("token", &"<redacted>")
+            .field("kv_mount", &self.kv_mount)
+            .finish()
+    }
+}
+
+/// Resolves references through a provider, caching each secret for `cache_ttl`. Clones share
+/// the cache.
+#[derive(Debug, Clone)]
+pub struct SecretsResolver {
+    provider: SecretsProvider,
+    cache_ttl: Duration,
+    cache: Arc<Mutex<HashMap<String, CachedSecret>>>,
+}
+
+#[derive(Debug, Clone)]
+struct FetchedSecret {
+    value: String,
+    /// Provider version id; a change means the secret was rotated.
+    version: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct CachedSecret {
+    secret: FetchedSecret,
+    fetched_at: Instant,
+}
+
+impl SecretsResolver {
+    pub fn new(provider: SecretsProvider, cache_ttl: Duration) -> Self {
+        Self {
+            provider,
+            cache_ttl,
+            cache: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Reads `SECRETS_PROVIDER` (`env`, `aws`, or `vault`; default `env`) and its settings.
+    pub fn from_env(http_client: reqwest::Client) -> Result<Self, SecretsError> {
+        let provider = match optional_env("SECRETS_PROVIDER").as_deref() {
+            None | Some("env") => SecretsProvider::Env,
+            Some("aws") => SecretsProvider::AwsSecretsManager(AwsSecretsManagerProvider::new(
+                require_env("AWS_REGION")?,
+                optional_env("SECRETS_MANAGER_ENDPOINT"),
+                AwsCredentials {
+                    access_key_id: require_env("AWS_ACCESS_KEY_ID")?,
+                    secret_access_key: require_env("AWS_SECRET_ACCESS_KEY")?,
+                    session_token: optional_env("AWS_SESSION_TOKEN"),
+                },
+                http_client,
+            )),
+            Some("vault") => SecretsProvider::Vault(VaultProvider::new(
+                require_env("VAULT_ADDR")?,
+                require_env("VAULT_TOKEN")?,
+                optional_env("VAULT_KV_MOUNT")
+                    .unwrap_or_else(|| DEFAULT_VAULT_KV_MOUNT.to_string()),
+                http_client,
+            )),
+            Some(other) => {
+                return Err(SecretsError::InvalidConfiguration(format!(
+                    "SECRETS_PROVIDER must be one of env, aws, vault (got {other})"
+                )));
+            }
+        };
+        let cache_ttl_seconds = match optional_env("SECRETS_CACHE_TTL_SECONDS") {
+            Some(raw) => raw.parse::<u64>().map_err(|_| {
+                SecretsError::InvalidConfiguration(
+                    "SECRETS_CACHE_TTL_SECONDS must be an integer".to_string(),
+                )
+            })?,
+            None => DEFAULT_SECRETS_CACHE_TTL_SECONDS,
+        };
+
+        Ok(Self::new(provider, Duration::from_secs(cache_ttl_seconds)))
+    }
+
+    pub fn provider(&self) -> &SecretsProvider {
+        &self.provider
+    }
+
+    pub async fn resolve(&self, reference: &SecretReference) -> Result<String, SecretsError> {
+        let cached = self
+            .cache()
+            .get(&reference.name)
+            .filter(|cached| cached.fetched_at.elapsed() < self.cache_ttl)
+            .map(|cached| cached.secret.value.clone());
+        let value = match cached {
+            Some(value) => value,
+            None => self.fetch_and_cache(&reference.name).await?.value,
+        };
+        select_field(&reference.name, &value, reference.field.as_deref())
+    }
+
+    /// Resolves every `secret://` env var and returns `(name, value)` pairs sorted by name.
+    /// The environment itself is left untouched.
+    pub async fn resolve_env_references(&self) -> Result<Vec<(String, String)>, SecretsError> {
+        let references = env::vars()
+            .filter_map(|(key, value)| {
+                SecretReference::parse(&value).map(|reference| (key, reference))
+            })
+            .collect::<Vec<_>>();
+
+        let mut resolved = Vec::with_capacity(references.len());
+        for (key, reference) in references {
+            if PROVIDER_ENV_VARS.contains(&key.as_str()) {
+                return Err(SecretsError::InvalidConfiguration(format!(
+                    "{key} configures the secrets provider and cannot be a secret reference"
+                )));
+            }
+            let value = self.resolve(&reference?).await?;
+            resolved.push((key, value));
+        }
+        resolved.sort();
+        Ok(resolved)
+    }
+
+    /// Re-fetches every cached secret and returns the names whose version changed.
+    pub async fn refresh(&self) -> Vec<String> {
+        let names = self.cache().keys().cloned().collect::<Vec<_>>();
+        let mut rotated = Vec::new();
+        for name in names {
+            let previous = self
+                .cache()
+                .get(&name)
+                .and_then(|cached| cached.secret.version.clone());
+            match self.fetch_and_cache(&name).await {
+                Ok(secret) if secret.version != previous => rotated.push(name),
+                Ok(_) => {}
+                Err(err) => warn!(error = %err, "secret refresh failed; keeping cached value"),
+            }
+        }
+        rotated
+    }
+
+    async fn fetch_and_cache(&self, name: &str) -> Result<FetchedSecret, SecretsError> {
+        let secret = self.provider.fetch(name).await?;
+        self.cache().insert(
+            name.to_string(),
+            CachedSecret {
+                secret: secret.clone(),
+                fetched_at: Instant::now(),
+            },
+        );
+        Ok(secret)
+    }
+
+    fn cache(&self) -> std::sync::MutexGuard<'_, HashMap<String, CachedSecret>> {
+        self.cache
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Re-fetches cached secrets once per cache TTL. Config is read once at startup, so a rotated
+/// secret is not applied in place: it is logged, and the service must be restarted to pick
+/// up the new value.
+pub async fn watch_secret_rotations(resolver: SecretsResolver) {
+    if resolver.cache_ttl.is_zero() || resolver.cache().is_empty() {
+        return;
+    }
+    let mut ticker = tokio::time::interval(resolver.cache_ttl);
+    ticker.tick().await;
+    loop {
+        ticker.tick().await;
+        for secret in resolver.refresh().await {
+            warn!(
+                secret,
+                provider = resolver.provider().name(),
+                "secret rotated; restart to apply the new value"
+            );
+        }
+    }
+}
+
+/// Replaces `secret://` env references with the secrets they name, using the provider
+/// configured in the environment. Call from a synchronous `main` after `load_dotenv` and
+/// before the tokio runtime is built. Lookups run on a throwaway current-thread runtime that
+/// is shut down before any variable is written, so no other thread exists while the
+/// environment changes. The returned resolver keeps the cache for [`watch_secret_rotations`].
+pub fn resolve_env_secret_references() -> Result<SecretsResolver, SecretsError> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|err| SecretsError::InvalidConfiguration(err.to_string()))?;
+    let resolution = runtime.block_on(async {
+        // No idle connections: pooled ones would belong to this runtime, which is dropped
+        // before the rotation watcher reuses the client.
+        let http_client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .pool_max_idle_per_host(0)
+            .build()
+            .map_err(|err| SecretsError::InvalidConfiguration(err.to_string()))?;
+        let resolver = SecretsResolver::from_env(http_client)?;
+        let resolved = resolver.resolve_env_references().await?;
+        Ok::<_, SecretsError>((resolver, resolved))
+    });
+    // Waits for the runtime's blocking DNS threads to exit.
+    drop(runtime);
+    let (resolver, resolved) = resolution?;
+
+    for (key, value) in &resolved {
+        // SAFETY: the process is single-threaded here; the lookup runtime has been shut down
+        // and the main runtime is not built yet.
+        unsafe { env::set_var(key, value) };
+    }
+    if !resolved.is_empty() {
+        info!(
+            provider = resolver.provider().name(),
+            vars = %resolved.iter().map(|(key, _)| key.as_str()).collect::<Vec<_>>().join(","),
+            "resolved secret references"
+        );
+    }
+    Ok(resolver)
+}
+
+/// A secret stored as a JSON object with a single key resolves to that key's value without a
+/// `#field`; any other JSON or plain text is returned whole.
+fn select_field(name: &str, value: &str, field: Option<&str>) -> Result<String, SecretsError> {
+    let object = serde_json::from_str::<serde_json::Map<String, Value>>(value).ok();
+    match (object, field) {
+        (Some(object), Some(field)) => {
+            object
+                .get(field)
+                .map(json_string)
+                .ok_or_else(|| SecretsError::MissingField {
+                    name: name.to_string(),
+                    field: field.to_string(),
+                })
+        }
+        (None, Some(field)) => Err(SecretsError::MissingField {
+            name: name.to_string(),
+            field: field.to_string(),
+        }),
+        (Some(object), None) if object.len() == 1 => {
+            Ok(object.values().next().map(json_string).unwrap_or_default())
+        }
+        (Some(object), None) if object.len() > 1 => Err(SecretsError::FieldRequired {
+            name: name.to_string(),
+        }),
+        _ => Ok(value.to_string()),
+    }
+}
+
+fn json_string(value: &Value) -> String {
+    match value {
+        Value::String(value) => value.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn optional_env(key: &str) -> Option<String> {
+    env::var(key)
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+fn require_env(key: &str) -> Result<String, SecretsError> {
+    optional_env(key)
+        .ok_or_else(|| SecretsError::InvalidConfiguration(format!("{key} is required")))
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct GetSecretValueRequest<'a> {
+    secret_id: &'a str,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct GetSecretValueResponse {
+    secret_string: Option<String>,
+    version_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct VaultKvResponse {
+    data: VaultKvData,
+}
+
+#[derive(Deserialize)]
+struct VaultKvData {
+    data: serde_json::Map<String, Value>,
+    metadata: Option<VaultKvMetadata>,
+}
+
+#[derive(Deserialize)]
+struct VaultKvMetadata {
+    version: Option<u64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    use axum::Router;
+    use axum::extract::{Path, State};
+    use axum::http::{HeaderMap, StatusCode};
+    use axum::response::IntoResponse;
+    use axum::routing::{get, post};
+    use serde_json::json;
+
+    use super::*;
+
+    async fn serve(router: Router) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("mock provider should bind");
+        let addr = listener.local_addr().expect("mock provider address");
+        tokio::spawn(async move {
+            axum::serve(listener, router)
+                .await
+                .expect("mock provider should run");
+        });
+        format!("http://{addr}")
+    }
+
+    fn reference(raw: &str) -> SecretReference {
+        SecretReference::parse(raw)
+            .expect("reference")
+            .expect("valid reference")
+    }
+
+    #[test]
+    fn references_parse_name_and_field() {
+        assert_eq!(
+            reference("secret://alfred/google#client_secret"),
+            SecretReference {
+                name: "alfred/google".to_string(),
+                field: Some("client_secret".to_string()),
+            }
+        );
+        assert!(SecretReference::parse("plain-value").is_none());
+        assert!(matches!(
+            SecretReference::parse("secret://name#"),
+            Some(Err(SecretsError::InvalidReference(_)))
+        ));
+    }
+
+    #[test]
+    fn fields_are_selected_from_json_secrets() {
+        let object = r#"{"client_secret":"abc","client_id":"id"}"#;
+        assert_eq!(
+            select_field("google", object, Some("client_secret")).expect("field"),
+            "abc"
+        );
+        assert!(matches!(
+            select_field("google", object, None),
+            Err(SecretsError::FieldRequired { .. })
+        ));
+        assert_eq!(
+            select_field("clerk", r#"{"value":"sk_test"}"#, None).expect("single key"),
+            "sk_test"
+        );
+        assert_eq!(
+            select_field("plain", "raw-secret", None).expect("plain"),
+            "raw-secret"
+        );
+    }
+
+    #[tokio::test]
+    async fn vault_secrets_are_cached_and_rotations_detected() {
+        let version = Arc::new(AtomicU64::new(1));
+        let fetches = Arc::new(AtomicU64::new(0));
+        let addr = serve(
+            Router::new()
+                .route(
+                    "/v1/secret/data/{*name}",
+                    get(
+                        |State((version, fetches)): State<(Arc<AtomicU64>, Arc<AtomicU64>)>,
+                         Path(name): Path<String>,
+                         headers: HeaderMap| async move {
+                            assert_eq!(headers["x-vault-token"], "root");
+                            if name != "alfred/clerk" {
+                                return StatusCode::NOT_FOUND.into_response();
+                            }
+                            fetches.fetch_add(1, Ordering::SeqCst);
+                            let version = version.load(Ordering::SeqCst);
+                            axum::Json(json!({
+                                "data": {
+                                    "data": {"secret_key": format!("sk_v{version}")},
+                                    "metadata": {"version": version},
+                                }
+                            }))
+                            .into_response()
+                        },
+                    ),
+                )
+                .with_state((version.clone(), fetches.clone())),
+        )
+        .await;
+        let resolver = SecretsResolver::new(
+            SecretsProvider::Vault(VaultProvider::new(
+                addr,
+                "root",
+                "secret",
+                reqwest::Client::new(),
+            )),
+            Duration::from_secs(60),
+        );
+
+        let clerk = reference("secret://alfred/clerk#secret_key");
+        assert_eq!(resolver.resolve(&clerk).await.expect("resolve"), "sk_v1");
+        assert_eq!(resolver.resolve(&clerk).await.expect("cached"), "sk_v1");
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+        assert!(matches!(
+            resolver
+                .resolve(&reference("secret://alfred/missing"))
+                .await,
+            Err(SecretsError::NotFound { .. })
+        ));
+
+        assert!(resolver.refresh().await.is_empty());
+        version.store(2, Ordering::SeqCst);
+        assert_eq!(resolver.refresh().await, vec!["alfred/clerk".to_string()]);
+        assert_eq!(resolver.resolve(&clerk).await.expect("refreshed"), "sk_v2");
+    }
+
+    #[tokio::test]
+    async fn aws_secrets_manager_reads_secret_strings() {
+        let endpoint = serve(Router::new().route(
+            "/",
+            post(|headers: HeaderMap, body: String| async move {
+                assert_eq!(headers["x-amz-target"], "secretsmanager.GetSecretValue");
+                let request: Value = serde_json::from_str(&body).expect("json body");
+                if request["SecretId"] != "alfred/google" {
+                    return (
+                        StatusCode::BAD_REQUEST,
+                        axum::Json(json!({"__type": "ResourceNotFoundException"})),
+                    )
+                        .into_response();
+                }
+                axum::Json(json!({
+                    "SecretString": r#"{"client_secret":"google-secret"}"#,
+                    "VersionId": "v1",
+                }))
+                .into_response()
+            }),
+        ))
+        .await;
+        let resolver = SecretsResolver::new(
+            SecretsProvider::AwsSecretsManager(AwsSecretsManagerProvider::new(
+                "us-east-1",
+                Some(endpoint),
+                AwsCredentials {
+                    access_key_id: "AKIDEXAMPLE".to_string(),
+                    secret_access_key: "secret".to_string(),
+                    session_token: None,
+                },
+                reqwest::Client::new(),
+            )),
+            Duration::from_secs(60),
+        );
+
+        assert_eq!(
+            resolver
+                .resolve(&reference("secret://alfred/google#client_secret"))
+                .await
+                .expect("resolve"),
+            "google-secret"
+        );
+        assert!(matches!(
+            resolver.resolve(&reference("secret://alfred/other")).await,
+            Err(SecretsError::NotFound { .. })
+        ));
+    }
+}
@@ -9,6 +9,19 @@ pub(crate) fn verify_attestation_signature(
     encoded_public_key: &str,
     encoded_signature: &str,
     response: &AttestationChallengeResponse,
+) -> Result<(), SecurityError> {
+    verify_ed25519_signature(
+        encoded_public_key,
+        encoded_signature,
+        attestation_signing_payload(response).as_bytes(),
+    )
+}
+
+/// Verifies a base64 Ed25519 signature over `payload` with a base64 public key.
+pub fn verify_ed25519_signature(
+    encoded_public_key: &str,
+    encoded_signature: &str,
+    payload: &[u8],
 ) -> Result<(), SecurityError> {
     let public_key_bytes = base64::engine::general_purpose::STANDARD
         .decode(encoded_public_key.as_bytes())
@@ -29,7 +42,7 @@ pub(crate) fn verify_attestation_signature(
         .map_err(|_| SecurityError::InvalidAttestationSignature)?;
 
     public_key
-        .verify(attestation_signing_payload(response).as_bytes(), &signature)
+        .verify(payload, &signature)
         .map_err(|_| SecurityError::InvalidAttestationSignature)?;
 
     Ok(())
@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use super::AttestedIdentity;
+
+/// Counters since startup, for operators to judge how many challenge round trips the cache
+/// saves.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AttestationCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Cached identities dropped because the attestation or KMS policy changed.
+    pub policy_revalidations: u64,
+}
+
+/// Recently verified identities, keyed by measurement. An entry serves decrypts for `ttl` and
+/// only while the policy it was verified under is still in force. A zero `ttl` disables the
+/// cache.
+#[derive(Debug, Clone, Default)]
+pub(super) struct AttestationCache {
+    ttl: Duration,
+    entries: Arc<Mutex<HashMap<String, CachedIdentity>>>,
+    counters: Arc<CacheCounters>,
+}
+
+#[derive(Debug, Clone)]
+struct CachedIdentity {
+    identity: AttestedIdentity,
+    policy_fingerprint: String,
+    verified_at: Instant,
+}
+
+#[derive(Debug, Default)]
+struct CacheCounters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    policy_revalidations: AtomicU64,
+}
+
+impl AttestationCache {
+    pub(super) fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            ..Self::default()
+        }
+    }
+
+    pub(super) fn is_enabled(&self) -> bool {
+        !self.ttl.is_zero()
+    }
+
+    /// Any fresh identity verified under `policy_fingerprint`. Entries verified under another
+    /// policy are dropped, so a policy change forces a new challenge.
+    pub(super) fn lookup(&self, policy_fingerprint: &str) -> Option<AttestedIdentity> {
+        if !self.is_enabled() {
+            return None;
+        }
+
+        let mut entries = self.entries();
+        let before = entries.len();
+        entries.retain(|_, cached| cached.policy_fingerprint == policy_fingerprint);
+        let revalidated = before - entries.len();
+        if revalidated > 0 {
+            self.counters
+                .policy_revalidations
+                .fetch_add(revalidated as u64, Ordering::Relaxed);
+        }
+        entries.retain(|_, cached| cached.verified_at.elapsed() < self.ttl);
+
+        let identity = entries
+            .values()
+            .next()
+            .map(|cached| cached.identity.clone());
+        let counter = if identity.is_some() {
+            &self.counters.hits
+        } else {
+            &self.counters.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        identity
+    }
+
+    pub(super) fn store(&self, policy_fingerprint: String, identity: AttestedIdentity) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        self.entries().insert(
+            identity.measurement.clone(),
+            CachedIdentity {
+                identity,
+                policy_fingerprint,
+                verified_at: Instant::now(),
+            },
+        );
+    }
+
+    pub(super) fn stats(&self) -> AttestationCacheStats {
+        AttestationCacheStats {
+            hits: self.counters.hits.load(Ordering::Relaxed),
+            misses: self.counters.misses.load(Ordering::Relaxed),
+            policy_revalidations: self.counters.policy_revalidations.load(Ordering::Relaxed),
+        }
+    }
+
+    fn entries(&self) -> std::sync::MutexGuard<'_, HashMap<String, CachedIdentity>> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn identity() -> AttestedIdentity {
+        AttestedIdentity {
+            runtime: "nitro".to_string(),
+            measurement: "mr_enclave_1".to_string(),
+        }
+    }
+
+    #[test]
+    fn cached_identity_is_served_under_the_same_policy() {
+        let cache = AttestationCache::new(Duration::from_secs(30));
+        assert_eq!(cache.lookup("policy-a"), None);
+
+        cache.store("policy-a".to_string(), identity());
+
+        assert_eq!(cache.lookup("policy-a"), Some(identity()));
+        assert_eq!(
+            cache.stats(),
+            AttestationCacheStats {
+                hits: 1,
+                misses: 1,
+                policy_revalidations: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn policy_change_forces_revalidation() {
+        let cache = AttestationCache::new(Duration::from_secs(30));
+        cache.store("policy-a".to_string(), identity());
+
+        assert_eq!(cache.lookup("policy-b"), None);
+        assert_eq!(
+            cache.lookup("policy-a"),
+            None,
+            "entries from a replaced policy are dropped, not parked"
+        );
+        assert_eq!(cache.stats().policy_revalidations, 1);
+    }
+
+    #[test]
+    fn expired_entries_are_not_served() {
+        let cache = AttestationCache::new(Duration::from_millis(1));
+        cache.store("policy-a".to_string(), identity());
+        std::thread::sleep(Duration::from_millis(5));
+
+        assert_eq!(cache.lookup("policy-a"), None);
+    }
+
+    #[test]
+    fn zero_ttl_disables_the_cache() {
+        let cache = AttestationCache::new(Duration::ZERO);
+        cache.store("policy-a".to_string(), identity());
+
+        assert_eq!(cache.lookup("policy-a"), None);
+        assert_eq!(cache.stats(), AttestationCacheStats::default());
+    }
+}
@@ -0,0 +1,151 @@
+use base64::Engine as _;
+use chacha20poly1305::aead::{Aead, KeyInit, OsRng, Payload, rand_core::RngCore};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use uuid::Uuid;
+
+use super::{KmsEncryptionContext, SecurityError};
+
+/// Marks a connector secret sealed under its own data key; the wrapped data key and the
+/// sealed secret follow, base64-encoded and separated by `.`.
+pub(super) const DATA_KEY_SEALED_SECRET_PREFIX: &str = "kms:v3:";
+/// The same layout, sealed before the context named the connector id.
+pub(super) const LEGACY_DATA_KEY_SEALED_SECRET_PREFIX: &str = "kms:v2:";
+const DATA_KEY_CONTEXT_PURPOSE: &str = "connector_data_key";
+const DATA_KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+
+/// A fresh per-connector data key. Only its KMS-wrapped form is stored.
+pub(super) struct DataKey([u8; DATA_KEY_LEN]);
+
+impl DataKey {
+    pub(super) fn generate() -> Self {
+        let mut key = [0_u8; DATA_KEY_LEN];
+        OsRng.fill_bytes(&mut key);
+        Self(key)
+    }
+
+    pub(super) fn from_bytes(bytes: &[u8]) -> Result<Self, SecurityError> {
+        let key = bytes.try_into().map_err(|_| {
+            SecurityError::KmsCiphertextInvalid("data key has the wrong length".to_string())
+        })?;
+        Ok(Self(key))
+    }
+
+    pub(super) fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Encrypts `secret` with the context bound as associated data, so a sealed secret
+    /// copied to another connector's row does not open.
+    pub(super) fn seal(
+        &self,
+        secret: &[u8],
+        context: &KmsEncryptionContext,
+    ) -> Result<Vec<u8>, SecurityError> {
+        let mut nonce = [0_u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+        let ciphertext = self
+            .cipher()
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: secret,
+                    aad: &context_aad(context),
+                },
+            )
+            .map_err(|_| {
+                SecurityError::KmsCiphertextInvalid("failed to seal connector secret".to_string())
+            })?;
+        Ok([nonce.as_slice(), &ciphertext].concat())
+    }
+
+    pub(super) fn open(
+        &self,
+        sealed: &[u8],
+        context: &KmsEncryptionContext,
+    ) -> Result<Vec<u8>, SecurityError> {
+        if sealed.len() < NONCE_LEN {
+            return Err(SecurityError::KmsCiphertextInvalid(
+                "sealed connector secret is too short".to_string(),
+            ));
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        self.cipher()
+            .decrypt(
+                Nonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: &context_aad(context),
+                },
+            )
+            .map_err(|_| {
+                SecurityError::KmsCiphertextInvalid(
+                    "connector secret does not open under its data key".to_string(),
+                )
+            })
+    }
+
+    fn cipher(&self) -> ChaCha20Poly1305 {
+        ChaCha20Poly1305::new(Key::from_slice(&self.0))
+    }
+}
+
+impl Drop for DataKey {
+    fn drop(&mut self) {
+        self.0.fill(0);
+    }
+}
+
+/// KMS context for a connector's data key. It names the owning connector so KMS refuses to
+/// unwrap a data key for any other connector, including another account of the same user and
+/// provider.
+pub(super) fn data_key_context(
+    user_id: Uuid,
+    provider: &str,
+    connector_id: Uuid,
+) -> KmsEncryptionContext {
+    let mut context = legacy_data_key_context(user_id, provider);
+    context.insert("alfred:connector_id".to_string(), connector_id.to_string());
+    context
+}
+
+/// Context of secrets stored under [`LEGACY_DATA_KEY_SEALED_SECRET_PREFIX`].
+pub(super) fn legacy_data_key_context(user_id: Uuid, provider: &str) -> KmsEncryptionContext {
+    KmsEncryptionContext::from([
+        (
+            "alfred:purpose".to_string(),
+            DATA_KEY_CONTEXT_PURPOSE.to_string(),
+        ),
+        ("alfred:user_id".to_string(), user_id.to_string()),
+        ("alfred:provider".to_string(), provider.to_string()),
+    ])
+}
+
+pub(super) fn encode_sealed_secret(wrapped_data_key: &[u8], sealed: &[u8]) -> String {
+    let engine = base64::engine::general_purpose::STANDARD;
+    format!(
+        "{DATA_KEY_SEALED_SECRET_PREFIX}{}.{}",
+        engine.encode(wrapped_data_key),
+        engine.encode(sealed)
+    )
+}
+
+/// Splits the part after [`DATA_KEY_SEALED_SECRET_PREFIX`] into the wrapped data key and
+/// the sealed secret.
+pub(super) fn decode_sealed_secret(encoded: &str) -> Result<(Vec<u8>, Vec<u8>), SecurityError> {
+    let engine = base64::engine::general_purpose::STANDARD;
+    let invalid = || {
+        SecurityError::KmsCiphertextInvalid(
+            "stored secret is not a wrapped data key and sealed secret".to_string(),
+        )
+    };
+    let (wrapped_data_key, sealed) = encoded.split_once('.').ok_or_else(invalid)?;
+    Ok((
+        engine.decode(wrapped_data_key).map_err(|_| invalid())?,
+        engine.decode(sealed).map_err(|_| invalid())?,
+    ))
+}
+
+fn context_aad(context: &KmsEncryptionContext) -> Vec<u8> {
+    serde_json::to_vec(context).unwrap_or_default()
+}
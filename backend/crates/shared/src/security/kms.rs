@@ -0,0 +1,497 @@
+mod cms;
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use base64::Engine as _;
+use chacha20poly1305::aead::{Aead, KeyInit, OsRng, Payload, rand_core::RngCore};
+use chacha20poly1305::{ChaCha20Poly1305, Nonce};
+use rsa::RsaPrivateKey;
+use rsa::pkcs8::DecodePrivateKey;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::SecurityError;
+use crate::aws::{AwsCallError, AwsCredentials, AwsJsonClient};
+
+const KMS_SERVICE: &str = "kms";
+const KMS_TARGET_PREFIX: &str = "TrentService";
+const RECIPIENT_KEY_ENCRYPTION_ALGORITHM: &str = "RSAES_OAEP_SHA_256";
+const LOCAL_STUB_NONCE_LEN: usize = 12;
+
+/// Key/value pairs bound to a ciphertext. Decrypt fails unless the same context is supplied.
+pub type KmsEncryptionContext = BTreeMap<String, String>;
+
+/// KMS backend that wraps connector secrets.
+#[derive(Debug, Clone)]
+pub enum KmsClient {
+    /// Fixed local key standing in for KMS. Only for local development and tests.
+    LocalStub,
+    Aws(Box<AwsKmsClient>),
+}
+
+impl KmsClient {
+    pub fn provider(&self) -> &'static str {
+        match self {
+            Self::LocalStub => "local",
+            Self::Aws(_) => "aws",
+        }
+    }
+
+    pub async fn encrypt(
+        &self,
+        key_id: &str,
+        plaintext: &[u8],
+        context: &KmsEncryptionContext,
+    ) -> Result<Vec<u8>, SecurityError> {
+        match self {
+            Self::LocalStub => local_stub_encrypt(plaintext, context),
+            Self::Aws(client) => client.encrypt(key_id, plaintext, context).await,
+        }
+    }
+
+    pub async fn decrypt(
+        &self,
+        ciphertext: &[u8],
+        context: &KmsEncryptionContext,
+    ) -> Result<Vec<u8>, SecurityError> {
+        match self {
+            Self::LocalStub => local_stub_decrypt(ciphertext, context),
+            Self::Aws(client) => client.decrypt(ciphertext, context).await,
+        }
+    }
+}
+
+/// Calls the KMS JSON API with SigV4-signed requests.
+#[derive(Debug, Clone)]
+pub struct AwsKmsClient {
+    api: AwsJsonClient,
+    recipient: Option<KmsRecipient>,
+}
+
+impl AwsKmsClient {
+    /// `endpoint` defaults to the regional KMS endpoint; set it for a VPC endpoint or the
+    /// vsock proxy in front of one.
+    pub fn new(
+        region: impl Into<String>,
+        endpoint: Option<String>,
+        credentials: AwsCredentials,
+        http_client: reqwest::Client,
+    ) -> Self {
+        Self {
+            api: AwsJsonClient::new(
+                KMS_SERVICE,
+                KMS_TARGET_PREFIX,
+                region.into(),
+                endpoint,
+                credentials,
+                http_client,
+            ),
+            recipient: None,
+        }
+    }
+
+    /// Sends the enclave's attestation document with every decrypt, so a key policy
+    /// conditioned on `kms:RecipientAttestation:*` only releases plaintext to this enclave.
+    pub fn with_recipient(mut self, recipient: KmsRecipient) -> Self {
+        self.recipient = Some(recipient);
+        self
+    }
+
+    async fn encrypt(
+        &self,
+        key_id: &str,
+        plaintext: &[u8],
+        context: &KmsEncryptionContext,
+    ) -> Result<Vec<u8>, SecurityError> {
+        let response: EncryptResponse = self
+            .api
+            .call(
+                "Encrypt",
+                &EncryptRequest {
+                    key_id,
+                    plaintext: base64::engine::general_purpose::STANDARD.encode(plaintext),
+                    encryption_context: context,
+                },
+            )
+            .await
+            .map_err(|err| kms_call_error("Encrypt", err))?;
+        decode_blob(&response.ciphertext_blob, "CiphertextBlob")
+    }
+
+    async fn decrypt(
+        &self,
+        ciphertext: &[u8],
+        context: &KmsEncryptionContext,
+    ) -> Result<Vec<u8>, SecurityError> {
+        let recipient = match self.recipient.as_ref() {
+            Some(recipient) => Some(RecipientInfo {
+                attestation_document: base64::engine::general_purpose::STANDARD
+                    .encode(recipient.attestation_document()?),
+                key_encryption_algorithm: RECIPIENT_KEY_ENCRYPTION_ALGORITHM,
+            }),
+            None => None,
+        };
+        let response: DecryptResponse = self
+            .api
+            .call(
+                "Decrypt",
+                &DecryptRequest {
+                    ciphertext_blob: base64::engine::general_purpose::STANDARD.encode(ciphertext),
+                    encryption_context: context,
+                    recipient,
+                },
+            )
+            .await
+            .map_err(|err| kms_call_error("Decrypt", err))?;
+
+        match (self.recipient.as_ref(), response) {
+            (
+                Some(recipient),
+                DecryptResponse {
+                    ciphertext_for_recipient: Some(envelope),
+                    ..
+                },
+            ) => {
+                let envelope = decode_blob(&envelope, "CiphertextForRecipient")?;
+                cms::open_enveloped_data(&recipient.private_key, &envelope)
+                    .map_err(SecurityError::KmsCiphertextInvalid)
+            }
+            (Some(_), _) => Err(SecurityError::KmsResponseInvalid(
+                "decrypt response is missing CiphertextForRecipient".to_string(),
+            )),
+            (
+                None,
+                DecryptResponse {
+                    plaintext: Some(plaintext),
+                    ..
+                },
+            ) => decode_blob(&plaintext, "Plaintext"),
+            (None, _) => Err(SecurityError::KmsResponseInvalid(
+                "decrypt response is missing Plaintext".to_string(),
+            )),
+        }
+    }
+}
+
+/// RSA key the enclave receives KMS plaintext under, and the attestation document that
+/// embeds its public key. The document is re-read on every decrypt so the process that
+/// refreshes it from the Nitro Secure Module can replace it in place.
+#[derive(Clone)]
+pub struct KmsRecipient {
+    private_key: RsaPrivateKey,
+    attestation_document_path: PathBuf,
+}
+
+impl KmsRecipient {
+    /// Loads a PKCS#8 PEM RSA private key.
+    pub fn load(
+        private_key_path: &Path,
+        attestation_document_path: impl Into<PathBuf>,
+    ) -> Result<Self, String> {
+        let pem = std::fs::read_to_string(private_key_path).map_err(|err| {
+            format!(
+                "failed to read kms recipient key {}: {err}",
+                private_key_path.display()
+            )
+        })?;
+        let private_key = RsaPrivateKey::from_pkcs8_pem(&pem).map_err(|err| {
+            format!(
+                "kms recipient key {} is not a PKCS#8 RSA key: {err}",
+                private_key_path.display()
+            )
+        })?;
+        Ok(Self {
+            private_key,
+            attestation_document_path: attestation_document_path.into(),
+        })
+    }
+
+    fn attestation_document(&self) -> Result<Vec<u8>, SecurityError> {
+        std::fs::read(&self.attestation_document_path).map_err(|err| {
+            SecurityError::KmsRequestFailed {
+                message: format!(
+                    "failed to read kms recipient attestation document {}: {err}",
+                    self.attestation_document_path.display()
+                ),
+            }
+        })
+    }
+
+    #[cfg(test)]
+    pub(crate) fn fixture() -> Self {
+        let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/kms");
+        Self::load(
+            &fixtures.join("recipient.key"),
+            fixtures.join("attestation.bin"),
+        )
+        .expect("fixture recipient key should load")
+    }
+}
+
+impl fmt::Debug for KmsRecipient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KmsRecipient")
+            .field("private_key", &"<redacted>")
+            .field("attestation_document_path", &self.attestation_document_path)
+            .finish()
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct EncryptRequest<'a> {
+    key_id: &'a str,
+    plaintext: String,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    encryption_context: &'a KmsEncryptionContext,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct EncryptResponse {
+    ciphertext_blob: String,
+}
+
+/// `KeyId` is omitted: the ciphertext names its symmetric key, and a rotated `KMS_KEY_ID`
+/// must still open secrets wrapped under the previous key.
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct DecryptRequest<'a> {
+    ciphertext_blob: String,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    encryption_context: &'a KmsEncryptionContext,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    recipient: Option<RecipientInfo>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct RecipientInfo {
+    attestation_document: String,
+    key_encryption_algorithm: &'static str,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct DecryptResponse {
+    plaintext: Option<String>,
+    ciphertext_for_recipient: Option<String>,
+}
+
+fn kms_call_error(operation: &'static str, err: AwsCallError) -> SecurityError {
+    match err {
+        AwsCallError::Transport(message) => SecurityError::KmsRequestFailed { message },
+        AwsCallError::Rejected { status, error_type } => SecurityError::KmsRequestRejected {
+            operation,
+            status,
+            error_type,
+        },
+        AwsCallError::InvalidResponse(message) => SecurityError::KmsResponseInvalid(message),
+    }
+}
+
+fn decode_blob(encoded: &str, field: &str) -> Result<Vec<u8>, SecurityError> {
+    base64::engine::general_purpose::STANDARD
+        .decode(encoded)
+        .map_err(|_| SecurityError::KmsResponseInvalid(format!("{field} is not valid base64")))
+}
+
+fn local_stub_cipher() -> ChaCha20Poly1305 {
+    let key = Sha256::digest(b"alfred-local-kms-stub");
+    ChaCha20Poly1305::new(&key)
+}
+
+/// The context is bound as associated data, like KMS binds it to its ciphertexts.
+fn local_stub_aad(context: &KmsEncryptionContext) -> Vec<u8> {
+    serde_json::to_vec(context).unwrap_or_default()
+}
+
+fn local_stub_encrypt(
+    plaintext: &[u8],
+    context: &KmsEncryptionContext,
+) -> Result<Vec<u8>, SecurityError> {
+    let mut nonce = [0_u8; LOCAL_STUB_NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce);
+    let ciphertext = local_stub_cipher()
+        .encrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: plaintext,
+                aad: &local_stub_aad(context),
+            },
+        )
+        .map_err(|_| SecurityError::KmsRequestFailed {
+            message: "local kms stub failed to encrypt".to_string(),
+        })?;
+    Ok([nonce.as_slice(), &ciphertext].concat())
+}
+
+fn local_stub_decrypt(
+    ciphertext: &[u8],
+    context: &KmsEncryptionContext,
+) -> Result<Vec<u8>, SecurityError> {
+    if ciphertext.len() < LOCAL_STUB_NONCE_LEN {
+        return Err(SecurityError::KmsCiphertextInvalid(
+            "ciphertext is too short".to_string(),
+        ));
+    }
+    let (nonce, ciphertext) = ciphertext.split_at(LOCAL_STUB_NONCE_LEN);
+    local_stub_cipher()
+        .decrypt(
+            Nonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: &local_stub_aad(context),
+            },
+        )
+        .map_err(|_| {
+            SecurityError::KmsCiphertextInvalid(
+                "ciphertext or encryption context does not match".to_string(),
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::Router;
+    use axum::http::{HeaderMap, StatusCode};
+    use axum::response::IntoResponse;
+    use axum::routing::post;
+    use serde_json::{Value, json};
+
+    use super::*;
+
+    fn context() -> KmsEncryptionContext {
+        KmsEncryptionContext::from([("alfred:purpose".to_string(), "test".to_string())])
+    }
+
+    async fn mock_kms(router: Router) -> AwsKmsClient {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("mock kms should bind");
+        let endpoint = format!(
+            "http://{}",
+            listener.local_addr().expect("mock kms address")
+        );
+        tokio::spawn(async move {
+            axum::serve(listener, router)
+                .await
+                .expect("mock kms should run");
+        });
+        AwsKmsClient::new(
+            "us-east-1",
+            Some(endpoint),
+            AwsCredentials {
+                access_key_id: "AKIDEXAMPLE".to_string(),
+                secret_access_key: "secret".to_string(),
+                session_token: Some("session".to_string()),
+            },
+            reqwest::Client::new(),
+        )
+    }
+
+    #[tokio::test]
+    async fn local_stub_binds_the_encryption_context() {
+        let ciphertext = KmsClient::LocalStub
+            .encrypt("kms/local", b"secret", &context())
+            .await
+            .expect("encrypt should succeed");
+
+        assert_eq!(
+            KmsClient::LocalStub
+                .decrypt(&ciphertext, &context())
+                .await
+                .expect("decrypt should succeed"),
+            b"secret"
+        );
+        let err = KmsClient::LocalStub
+            .decrypt(&ciphertext, &KmsEncryptionContext::new())
+            .await
+            .expect_err("a different context must not decrypt");
+        assert!(matches!(err, SecurityError::KmsCiphertextInvalid(_)));
+    }
+
+    #[tokio::test]
+    async fn aws_decrypt_sends_a_signed_request() {
+        let client = mock_kms(Router::new().route(
+            "/",
+            post(|headers: HeaderMap, body: String| async move {
+                let request: Value = serde_json::from_str(&body).expect("json body");
+                assert_eq!(headers["x-amz-target"], "TrentService.Decrypt");
+                assert_eq!(headers["x-amz-security-token"], "session");
+                let authorization = headers["authorization"].to_str().expect("ascii");
+                assert!(authorization.starts_with("AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/"));
+                assert!(authorization.contains("/us-east-1/kms/aws4_request"));
+                assert_eq!(request["EncryptionContext"]["alfred:purpose"], "test");
+                assert!(request.get("Recipient").is_none());
+                axum::Json(json!({
+                    "KeyId": "arn:aws:kms:us-east-1:111122223333:key/test",
+                    "Plaintext": base64::engine::general_purpose::STANDARD.encode(b"secret"),
+                }))
+            }),
+        ))
+        .await;
+
+        let plaintext = client
+            .decrypt(b"ciphertext", &context())
+            .await
+            .expect("decrypt should succeed");
+
+        assert_eq!(plaintext, b"secret");
+    }
+
+    #[tokio::test]
+    async fn aws_rejections_keep_the_error_type() {
+        let client = mock_kms(Router::new().route(
+            "/",
+            post(|| async {
+                (
+                    StatusCode::BAD_REQUEST,
+                    axum::Json(json!({
+                        "__type": "com.amazonaws.kms#AccessDeniedException",
+                        "message": "attestation does not satisfy the key policy",
+                    })),
+                )
+                    .into_response()
+            }),
+        ))
+        .await;
+
+        let err = client
+            .decrypt(b"ciphertext", &context())
+            .await
+            .expect_err("a rejected decrypt must fail");
+
+        assert!(matches!(
+            err,
+            SecurityError::KmsRequestRejected {
+                operation: "Decrypt",
+                status: 400,
+                ref error_type,
+            } if error_type == "AccessDeniedException"
+        ));
+    }
+
+    #[tokio::test]
+    async fn aws_decrypt_without_a_recipient_envelope_is_rejected() {
+        let client = mock_kms(Router::new().route(
+            "/",
+            post(|| async {
+                axum::Json(json!({
+                    "Plaintext": base64::engine::general_purpose::STANDARD.encode(b"secret"),
+                }))
+            }),
+        ))
+        .await
+        .with_recipient(KmsRecipient::fixture());
+
+        let err = client
+            .decrypt(b"ciphertext", &context())
+            .await
+            .expect_err("plaintext must not be accepted when a recipient was sent");
+
+        assert!(matches!(err, SecurityError::KmsResponseInvalid(_)));
+    }
+}
@@ -0,0 +1,350 @@
+use aes::Aes256;
+use cbc::cipher::block_padding::Pkcs7;
+use cbc::cipher::{BlockDecryptMut, KeyIvInit};
+use rsa::{Oaep, RsaPrivateKey};
+use sha2::Sha256;
+
+const TAG_INTEGER: u8 = 0x02;
+const TAG_OCTET_STRING: u8 = 0x04;
+const TAG_OID: u8 = 0x06;
+const TAG_SEQUENCE: u8 = 0x30;
+const TAG_SET: u8 = 0x31;
+const TAG_CONTEXT_0: u8 = 0xa0;
+const TAG_CONTEXT_0_PRIMITIVE: u8 = 0x80;
+const CONSTRUCTED: u8 = 0x20;
+
+/// 1.2.840.113549.1.7.3
+const OID_ENVELOPED_DATA: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x07, 0x03];
+/// 1.2.840.113549.1.1.7
+const OID_RSAES_OAEP: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x07];
+/// 2.16.840.1.101.3.4.1.42
+const OID_AES_256_CBC: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x01, 0x2a];
+
+/// Opens the CMS `EnvelopedData` KMS returns as `CiphertextForRecipient`: the content key is
+/// RSAES-OAEP-SHA-256 encrypted to the recipient key, and the content is AES-256-CBC.
+pub(super) fn open_enveloped_data(
+    private_key: &RsaPrivateKey,
+    envelope: &[u8],
+) -> Result<Vec<u8>, String> {
+    let (content_info, _) = read_tlv(envelope)?;
+    let mut content_info = expect(content_info, TAG_SEQUENCE, "ContentInfo")?.children();
+    let content_type = expect(
+        next(&mut content_info, "contentType")?,
+        TAG_OID,
+        "contentType",
+    )?;
+    if content_type.content != OID_ENVELOPED_DATA {
+        return Err("content is not CMS enveloped data".to_string());
+    }
+    let explicit = expect(
+        next(&mut content_info, "content")?,
+        TAG_CONTEXT_0,
+        "content",
+    )?;
+    let (enveloped, _) = read_tlv(explicit.content)?;
+    let mut enveloped = expect(enveloped, TAG_SEQUENCE, "EnvelopedData")?.children();
+
+    expect(next(&mut enveloped, "version")?, TAG_INTEGER, "version")?;
+    let mut field = next(&mut enveloped, "recipientInfos")?;
+    if field.tag == TAG_CONTEXT_0 {
+        // originatorInfo
+        field = next(&mut enveloped, "recipientInfos")?;
+    }
+    let mut recipient_infos = expect(field, TAG_SET, "recipientInfos")?.children();
+    let recipient_info = expect(
+        next(&mut recipient_infos, "recipientInfo")?,
+        TAG_SEQUENCE,
+        "KeyTransRecipientInfo",
+    )?;
+    let content_key = decrypt_content_key(private_key, recipient_info)?;
+
+    let mut encrypted_content_info = expect(
+        next(&mut enveloped, "encryptedContentInfo")?,
+        TAG_SEQUENCE,
+        "EncryptedContentInfo",
+    )?
+    .children();
+    expect(
+        next(&mut encrypted_content_info, "contentType")?,
+        TAG_OID,
+        "contentType",
+    )?;
+    let mut algorithm = expect(
+        next(&mut encrypted_content_info, "contentEncryptionAlgorithm")?,
+        TAG_SEQUENCE,
+        "contentEncryptionAlgorithm",
+    )?
+    .children();
+    let algorithm_oid = expect(next(&mut algorithm, "algorithm")?, TAG_OID, "algorithm")?;
+    if algorithm_oid.content != OID_AES_256_CBC {
+        return Err("content encryption algorithm is not AES-256-CBC".to_string());
+    }
+    let iv = expect(next(&mut algorithm, "iv")?, TAG_OCTET_STRING, "iv")?.octets()?;
+    let encrypted_content = next(&mut encrypted_content_info, "encryptedContent")?;
+    if encrypted_content.tag & !CONSTRUCTED != TAG_CONTEXT_0_PRIMITIVE {
+        return Err("encryptedContent is missing".to_string());
+    }
+    let encrypted_content = encrypted_content.octets()?;
+
+    cbc::Decryptor::<Aes256>::new_from_slices(&content_key, &iv)
+        .map_err(|_| "content key or iv has the wrong length".to_string())?
+        .decrypt_padded_vec_mut::<Pkcs7>(&encrypted_content)
+        .map_err(|_| "encrypted content padding is invalid".to_string())
+}
+
+fn decrypt_content_key(
+    private_key: &RsaPrivateKey,
+    recipient_info: Tlv<'_>,
+) -> Result<Vec<u8>, String> {
+    let mut fields = recipient_info.children();
+    expect(next(&mut fields, "version")?, TAG_INTEGER, "version")?;
+    // rid identifies the recipient key; there is only one.
+    next(&mut fields, "rid")?;
+    let mut algorithm = expect(
+        next(&mut fields, "keyEncryptionAlgorithm")?,
+        TAG_SEQUENCE,
+        "keyEncryptionAlgorithm",
+    )?
+    .children();
+    let algorithm_oid = expect(next(&mut algorithm, "algorithm")?, TAG_OID, "algorithm")?;
+    if algorithm_oid.content != OID_RSAES_OAEP {
+        return Err("key encryption algorithm is not RSAES-OAEP".to_string());
+    }
+    let encrypted_key = expect(
+        next(&mut fields, "encryptedKey")?,
+        TAG_OCTET_STRING,
+        "encryptedKey",
+    )?
+    .octets()?;
+
+    private_key
+        .decrypt(Oaep::new::<Sha256>(), &encrypted_key)
+        .map_err(|_| "content key could not be decrypted with the recipient key".to_string())
+}
+
+/// A BER element. KMS emits indefinite lengths, so content is not always DER.
+#[derive(Clone, Copy)]
+struct Tlv<'a> {
+    tag: u8,
+    content: &'a [u8],
+}
+
+impl<'a> Tlv<'a> {
+    fn children(self) -> Children<'a> {
+        Children {
+            remaining: self.content,
+        }
+    }
+
+    /// Content of an OCTET STRING, joining the segments of a constructed one.
+    fn octets(self) -> Result<Vec<u8>, String> {
+        if self.tag & CONSTRUCTED == 0 {
+            return Ok(self.content.to_vec());
+        }
+        let mut octets = Vec::new();
+        for segment in self.children() {
+            octets.extend(segment?.octets()?);
+        }
+        Ok(octets)
+    }
+}
+
+struct Children<'a> {
+    remaining: &'a [u8],
+}
+
+impl<'a> Iterator for Children<'a> {
+    type Item = Result<Tlv<'a>, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining.is_empty() {
+            return None;
+        }
+        Some(read_tlv(self.remaining).map(|(tlv, rest)| {
+            self.remaining = rest;
+            tlv
+        }))
+    }
+}
+
+fn next<'a>(children: &mut Children<'a>, field: &str) -> Result<Tlv<'a>, String> {
+    children
+        .next()
+        .unwrap_or_else(|| Err(format!("{field} is missing")))
+}
+
+fn expect<'a>(tlv: Tlv<'a>, tag: u8, field: &str) -> Result<Tlv<'a>, String> {
+    // Strings may be sent constructed; compare the tag number and class only.
+    if tlv.tag | CONSTRUCTED == tag | CONSTRUCTED {
+        Ok(tlv)
+    } else {
+        Err(format!("{field} has unexpected tag 0x{:02x}", tlv.tag))
+    }
+}
+
+/// Reads one element and returns it with the bytes that follow it.
+fn read_tlv(input: &[u8]) -> Result<(Tlv<'_>, &[u8]), String> {
+    let [tag, length, rest @ ..] = input else {
+        return Err("truncated element".to_string());
+    };
+    if tag & 0x1f == 0x1f {
+        return Err("high tag numbers are not supported".to_string());
+    }
+
+    if *length == 0x80 {
+        if tag & CONSTRUCTED == 0 {
+            return Err("indefinite length on a primitive element".to_string());
+        }
+        let mut cursor = rest;
+        loop {
+            if let [0, 0, after @ ..] = cursor {
+                let content = &rest[..rest.len() - cursor.len()];
+                return Ok((Tlv { tag: *tag, content }, after));
+            }
+            let (_, after) = read_tlv(cursor)?;
+            cursor = after;
+        }
+    }
+
+    let (length, rest) = if length & 0x80 == 0 {
+        (usize::from(*length), rest)
+    } else {
+        let octets = usize::from(length & 0x7f);
+        if octets > 4 || rest.len() < octets {
+            return Err("invalid element length".to_string());
+        }
+        let length = rest[..octets]
+            .iter()
+            .fold(0_usize, |length, byte| (length << 8) | usize::from(*byte));
+        (length, &rest[octets..])
+    };
+    if rest.len() < length {
+        return Err("truncated element".to_string());
+    }
+    Ok((
+        Tlv {
+            tag: *tag,
+            content: &rest[..length],
+        },
+        &rest[length..],
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use aes::Aes256;
+    use cbc::cipher::BlockEncryptMut;
+    use rsa::RsaPublicKey;
+
+    use super::*;
+    use crate::security::kms::KmsRecipient;
+
+    fn der(tag: u8, content: &[u8]) -> Vec<u8> {
+        let mut encoded = vec![tag];
+        let length = content.len();
+        if length < 0x80 {
+            encoded.push(length as u8);
+        } else if length <= 0xff {
+            encoded.extend([0x81, length as u8]);
+        } else {
+            encoded.extend([0x82, (length >> 8) as u8, length as u8]);
+        }
+        encoded.extend_from_slice(content);
+        encoded
+    }
+
+    fn indefinite(tag: u8, children: &[Vec<u8>]) -> Vec<u8> {
+        let mut encoded = vec![tag, 0x80];
+        for child in children {
+            encoded.extend_from_slice(child);
+        }
+        encoded.extend([0, 0]);
+        encoded
+    }
+
+    /// Envelope shaped like the ones KMS returns: indefinite lengths on the outer structures
+    /// and the ciphertext split into constructed OCTET STRING segments.
+    fn envelope(public_key: &RsaPublicKey, plaintext: &[u8]) -> Vec<u8> {
+        let content_key = [9_u8; 32];
+        let iv = [3_u8; 16];
+        let encrypted_key = public_key
+            .encrypt(
+                &mut rsa::rand_core::OsRng,
+                Oaep::new::<Sha256>(),
+                &content_key,
+            )
+            .expect("content key should encrypt");
+        let ciphertext = cbc::Encryptor::<Aes256>::new_from_slices(&content_key, &iv)
+            .expect("key and iv lengths are valid")
+            .encrypt_padded_vec_mut::<Pkcs7>(plaintext);
+        let (first, second) = ciphertext.split_at(ciphertext.len() / 2);
+
+        let recipient_info = der(
+            TAG_SEQUENCE,
+            &[
+                der(TAG_INTEGER, &[2]),
+                der(TAG_CONTEXT_0_PRIMITIVE, b"subject-key-id"),
+                der(
+                    TAG_SEQUENCE,
+                    &[der(TAG_OID, OID_RSAES_OAEP), der(TAG_SEQUENCE, &[])].concat(),
+                ),
+                der(TAG_OCTET_STRING, &encrypted_key),
+            ]
+            .concat(),
+        );
+        let encrypted_content_info = indefinite(
+            TAG_SEQUENCE,
+            &[
+                der(
+                    TAG_OID,
+                    &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x07, 0x01],
+                ),
+                der(
+                    TAG_SEQUENCE,
+                    &[der(TAG_OID, OID_AES_256_CBC), der(TAG_OCTET_STRING, &iv)].concat(),
+                ),
+                indefinite(
+                    TAG_CONTEXT_0,
+                    &[der(TAG_OCTET_STRING, first), der(TAG_OCTET_STRING, second)],
+                ),
+            ],
+        );
+        let enveloped_data = indefinite(
+            TAG_SEQUENCE,
+            &[
+                der(TAG_INTEGER, &[2]),
+                der(TAG_SET, &recipient_info),
+                encrypted_content_info,
+            ],
+        );
+        indefinite(
+            TAG_SEQUENCE,
+            &[
+                der(TAG_OID, OID_ENVELOPED_DATA),
+                indefinite(TAG_CONTEXT_0, &[enveloped_data]),
+            ],
+        )
+    }
+
+    #[test]
+    fn opens_an_envelope_for_the_recipient_key() {
+        let recipient = KmsRecipient::fixture();
+        let envelope = envelope(&recipient.private_key.to_public_key(), b"refresh-token");
+
+        let plaintext =
+            open_enveloped_data(&recipient.private_key, &envelope).expect("envelope should open");
+
+        assert_eq!(plaintext, b"refresh-token");
+    }
+
+    #[test]
+    fn rejects_a_truncated_envelope() {
+        let recipient = KmsRecipient::fixture();
+        let envelope = envelope(&recipient.private_key.to_public_key(), b"refresh-token");
+
+        let err = open_enveloped_data(&recipient.private_key, &envelope[..envelope.len() - 40])
+            .expect_err("truncated envelope must fail");
+
+        assert!(err.contains("truncated"), "{err}");
+    }
+}
@@ -1,18 +1,40 @@
 mod attestation;
-mod replay;
+mod attestation_cache;
+mod data_key;
+mod kms;
 
 #[cfg(test)]
 mod tests;
 
+use base64::Engine as _;
 use chrono::Utc;
-use std::sync::{Arc, Mutex};
+use sha2::{Digest, Sha256};
 use std::time::Duration;
 use thiserror::Error;
 use uuid::Uuid;
 
+use crate::enclave::EnclaveRpcTls;
+use crate::enclave::transport::{
+    EnclaveHttpClient, EnclaveRequest, EnclaveRequestMethod, send_enclave_request,
+};
 use crate::enclave_runtime::{AttestationChallengeRequest, AttestationChallengeResponse};
-
-use replay::ReplayGuard;
+use crate::replay_guard::NonceReplayGuard;
+
+pub use crate::aws::AwsCredentials;
+pub use attestation::verify_ed25519_signature;
+use attestation_cache::AttestationCache;
+pub use attestation_cache::AttestationCacheStats;
+use data_key::{
+    DATA_KEY_SEALED_SECRET_PREFIX, DataKey, LEGACY_DATA_KEY_SEALED_SECRET_PREFIX, data_key_context,
+    decode_sealed_secret, encode_sealed_secret, legacy_data_key_context,
+};
+pub use kms::{AwsKmsClient, KmsClient, KmsEncryptionContext, KmsRecipient};
+
+const ATTESTATION_CHALLENGE_REPLAY_SCOPE: &str = "attestation_challenge";
+/// Marks a connector secret encrypted whole by KMS, before per-connector data keys. Secrets
+/// with neither this nor the data key prefix predate KMS wrapping and are read as-is.
+const KMS_WRAPPED_SECRET_PREFIX: &str = "kms:v1:";
+const CONNECTOR_SECRET_CONTEXT_PURPOSE: &str = "connector_secret";
 
 #[derive(Debug, Clone)]
 pub struct TeeAttestationPolicy {
@@ -37,7 +59,7 @@ pub struct ConnectorKeyMetadata {
     pub key_version: i32,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct AttestedIdentity {
     pub runtime: String,
     pub measurement: String,
@@ -49,8 +71,10 @@ pub struct SecretRuntime {
     kms_policy: KmsDecryptPolicy,
     enclave_runtime_base_url: String,
     attestation_challenge_timeout_ms: u64,
-    http_client: reqwest::Client,
-    replay_guard: Arc<Mutex<ReplayGuard>>,
+    http_client: EnclaveHttpClient,
+    replay_guard: NonceReplayGuard,
+    attestation_cache: AttestationCache,
+    kms_client: Option<KmsClient>,
 }
 
 impl SecretRuntime {
@@ -66,11 +90,124 @@ impl SecretRuntime {
             kms_policy,
             enclave_runtime_base_url,
             attestation_challenge_timeout_ms,
-            http_client,
-            replay_guard: Arc::new(Mutex::new(ReplayGuard::default())),
+            http_client: http_client.into(),
+            replay_guard: NonceReplayGuard::default(),
+            attestation_cache: AttestationCache::default(),
+            kms_client: None,
         }
     }
 
+    /// Sends attestation challenges over the runtime's mTLS listener.
+    pub fn with_enclave_tls(mut self, tls: &EnclaveRpcTls) -> Self {
+        self.http_client = tls.attestation_client();
+        self
+    }
+
+    /// Checks challenge nonces against a guard shared with other instances.
+    pub fn with_replay_guard(mut self, replay_guard: NonceReplayGuard) -> Self {
+        self.replay_guard = replay_guard;
+        self
+    }
+
+    /// Lets connector decrypts reuse an identity verified within `ttl` instead of running a
+    /// challenge each time. Keep `ttl` well under `max_attestation_age_seconds`; zero disables
+    /// the cache.
+    pub fn with_attestation_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.attestation_cache = AttestationCache::new(ttl);
+        self
+    }
+
+    pub fn attestation_cache_stats(&self) -> AttestationCacheStats {
+        self.attestation_cache.stats()
+    }
+
+    /// Wraps connector secrets under the KMS key before they are stored. Without a client,
+    /// secrets are stored as they are and only the database encryption protects them.
+    pub fn with_kms_client(mut self, kms_client: KmsClient) -> Self {
+        self.kms_client = Some(kms_client);
+        self
+    }
+
+    /// Seals `secret` under a fresh data key for one connector and stores that key wrapped by
+    /// the policy's KMS key alongside it, so no single key opens every connector's secret.
+    pub async fn wrap_connector_secret(
+        &self,
+        user_id: Uuid,
+        provider: &str,
+        connector_id: Uuid,
+        secret: &str,
+    ) -> Result<String, SecurityError> {
+        let Some(kms_client) = self.kms_client.as_ref() else {
+            return Ok(secret.to_string());
+        };
+        let context = data_key_context(user_id, provider, connector_id);
+        let data_key = DataKey::generate();
+        let sealed = data_key.seal(secret.as_bytes(), &context)?;
+        let wrapped_data_key = kms_client
+            .encrypt(&self.kms_policy.key_id, data_key.as_bytes(), &context)
+            .await?;
+        Ok(encode_sealed_secret(&wrapped_data_key, &sealed))
+    }
+
+    /// Reverses [`Self::wrap_connector_secret`], and still reads secrets sealed before the
+    /// context named the connector and secrets wrapped whole by KMS before data keys were per
+    /// connector. Callers authorize the decrypt with [`Self::authorize_connector_decrypt`]
+    /// first; with a recipient configured, KMS also checks the enclave's own attestation
+    /// before releasing the data key.
+    pub async fn unwrap_connector_secret(
+        &self,
+        user_id: Uuid,
+        provider: &str,
+        connector_id: Uuid,
+        stored: String,
+    ) -> Result<String, SecurityError> {
+        let data_key_sealed =
+            if let Some(encoded) = stored.strip_prefix(DATA_KEY_SEALED_SECRET_PREFIX) {
+                Some((encoded, data_key_context(user_id, provider, connector_id)))
+            } else {
+                stored
+                    .strip_prefix(LEGACY_DATA_KEY_SEALED_SECRET_PREFIX)
+                    .map(|encoded| (encoded, legacy_data_key_context(user_id, provider)))
+            };
+        let plaintext = if let Some((encoded, context)) = data_key_sealed {
+            let (wrapped_data_key, sealed) = decode_sealed_secret(encoded)?;
+            let data_key = DataKey::from_bytes(
+                &self
+                    .kms_client()?
+                    .decrypt(&wrapped_data_key, &context)
+                    .await?,
+            )?;
+            data_key.open(&sealed, &context)?
+        } else if let Some(encoded) = stored.strip_prefix(KMS_WRAPPED_SECRET_PREFIX) {
+            let ciphertext = base64::engine::general_purpose::STANDARD
+                .decode(encoded)
+                .map_err(|_| {
+                    SecurityError::KmsCiphertextInvalid(
+                        "stored secret is not valid base64".to_string(),
+                    )
+                })?;
+            self.kms_client()?
+                .decrypt(&ciphertext, &connector_secret_context())
+                .await?
+        } else {
+            return Ok(stored);
+        };
+        String::from_utf8(plaintext).map_err(|_| {
+            SecurityError::KmsCiphertextInvalid("connector secret is not utf-8".to_string())
+        })
+    }
+
+    fn kms_client(&self) -> Result<&KmsClient, SecurityError> {
+        self.kms_client
+            .as_ref()
+            .ok_or(SecurityError::KmsNotConfigured)
+    }
+
+    /// Base64 Ed25519 key the enclave signs attestation evidence and audit events with.
+    pub fn attestation_public_key(&self) -> Option<&str> {
+        self.tee_policy.attestation_public_key.as_deref()
+    }
+
     pub fn kms_key_id(&self) -> &str {
         &self.kms_policy.key_id
     }
@@ -92,9 +229,16 @@ impl SecretRuntime {
             });
         }
 
+        let policy_fingerprint = self.policy_fingerprint();
+        if let Some(identity) = self.attestation_cache.lookup(&policy_fingerprint) {
+            return Ok(identity);
+        }
+
         let challenge = self.build_attestation_challenge("decrypt");
         let challenge_response = self.request_attestation_challenge(&challenge).await?;
-        let identity = self.verify_challenge_response(&challenge, &challenge_response)?;
+        let identity = self
+            .verify_challenge_response(&challenge, &challenge_response)
+            .await?;
 
         if !self
             .kms_policy
@@ -107,9 +251,51 @@ impl SecretRuntime {
             });
         }
 
+        self.attestation_cache
+            .store(policy_fingerprint, identity.clone());
         Ok(identity)
     }
 
+    /// Digest of every policy input a cached identity was verified against.
+    fn policy_fingerprint(&self) -> String {
+        let mut hasher = Sha256::new();
+        let tee = &self.tee_policy;
+        let kms = &self.kms_policy;
+        for field in [
+            tee.required.to_string().as_str(),
+            tee.expected_runtime.as_str(),
+            &tee.allowed_measurements.join(","),
+            tee.attestation_public_key.as_deref().unwrap_or_default(),
+            &tee.max_attestation_age_seconds.to_string(),
+            &tee.allow_insecure_dev_attestation.to_string(),
+            kms.key_id.as_str(),
+            &kms.key_version.to_string(),
+            &kms.allowed_measurements.join(","),
+        ] {
+            hasher.update(field.as_bytes());
+            hasher.update([0]);
+        }
+        hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
+    }
+
+    /// Fingerprint of the TLS certificate the runtime attests to serving, for RPC clients to pin.
+    pub async fn attest_enclave_tls_certificate(&self) -> Result<String, SecurityError> {
+        let challenge = self.build_attestation_challenge("rpc_tls_pin");
+        let challenge_response = self.request_attestation_challenge(&challenge).await?;
+        if self.tee_policy.required {
+            self.verify_challenge_response(&challenge, &challenge_response)
+                .await?;
+        }
+
+        challenge_response
+            .tls_certificate_sha256
+            .ok_or(SecurityError::MissingAttestedTlsCertificate)
+    }
+
     fn validate_key_binding(
         &self,
         key_metadata: &ConnectorKeyMetadata,
@@ -148,35 +334,38 @@ impl SecretRuntime {
         &self,
         challenge: &AttestationChallengeRequest,
     ) -> Result<AttestationChallengeResponse, SecurityError> {
-        let url = format!(
-            "{}/v1/attestation/challenge",
-            self.enclave_runtime_base_url.trim_end_matches('/')
-        );
-
-        let response = self
-            .http_client
-            .post(&url)
-            .timeout(Duration::from_millis(self.attestation_challenge_timeout_ms))
-            .json(challenge)
-            .send()
-            .await
-            .map_err(|err| SecurityError::AttestationChallengeRequestFailed {
+        let body = serde_json::to_vec(challenge).map_err(|err| {
+            SecurityError::AttestationChallengeRequestFailed {
                 message: err.to_string(),
-            })?;
-
-        if !response.status().is_success() {
+            }
+        })?;
+        let response = send_enclave_request(
+            &self.enclave_runtime_base_url,
+            &self.http_client,
+            EnclaveRequest {
+                method: EnclaveRequestMethod::Post,
+                path: "/v1/attestation/challenge",
+                headers: vec![("content-type", "application/json".to_string())],
+                body: Some(body),
+                timeout: Some(Duration::from_millis(self.attestation_challenge_timeout_ms)),
+            },
+        )
+        .await
+        .map_err(|err| SecurityError::AttestationChallengeRequestFailed {
+            message: err.to_string(),
+        })?;
+
+        if !(200..300).contains(&response.status) {
             return Err(SecurityError::AttestationChallengeRejected {
-                status: response.status().as_u16(),
+                status: response.status,
             });
         }
 
-        response
-            .json::<AttestationChallengeResponse>()
-            .await
+        serde_json::from_slice::<AttestationChallengeResponse>(&response.body)
             .map_err(|err| SecurityError::InvalidAttestationDocument(err.to_string()))
     }
 
-    fn verify_challenge_response(
+    async fn verify_challenge_response(
         &self,
         challenge: &AttestationChallengeRequest,
         response: &AttestationChallengeResponse,
@@ -280,12 +469,14 @@ impl SecretRuntime {
             attestation::verify_attestation_signature(encoded_public_key, signature, response)?;
         }
 
-        let mut replay_guard = self
-            .replay_guard
-            .lock()
-            .map_err(|_| SecurityError::ReplayGuardUnavailable)?;
-        replay_guard
-            .verify_and_record(response.challenge_nonce.as_str(), response.expires_at, now)
+        self.replay_guard
+            .check_and_record(
+                ATTESTATION_CHALLENGE_REPLAY_SCOPE,
+                response.challenge_nonce.as_str(),
+                response.expires_at,
+                now,
+            )
+            .await
             .map_err(|_| SecurityError::ChallengeReplayDetected {
                 challenge_nonce: response.challenge_nonce.clone(),
             })?;
@@ -311,6 +502,8 @@ pub enum SecurityError {
     MissingAttestationPublicKey,
     #[error("attestation signature is required when insecure mode is disabled")]
     MissingAttestationSignature,
+    #[error("attestation response did not include the runtime tls certificate")]
+    MissingAttestedTlsCertificate,
     #[error("attestation public key is invalid")]
     InvalidAttestationPublicKey,
     #[error("attestation signature is invalid")]
@@ -321,6 +514,20 @@ pub enum SecurityError {
     KmsVersionMismatch { expected: i32, actual: i32 },
     #[error("kms policy denied decrypt for measurement={measurement}")]
     KmsPolicyDenied { measurement: String },
+    #[error("stored secret is kms-wrapped but no kms client is configured")]
+    KmsNotConfigured,
+    #[error("kms request failed: {message}")]
+    KmsRequestFailed { message: String },
+    #[error("kms rejected {operation} with status={status}: {error_type}")]
+    KmsRequestRejected {
+        operation: &'static str,
+        status: u16,
+        error_type: String,
+    },
+    #[error("kms response is invalid: {0}")]
+    KmsResponseInvalid(String),
+    #[error("kms ciphertext could not be decrypted: {0}")]
+    KmsCiphertextInvalid(String),
     #[error("failed to request attestation challenge response: {message}")]
     AttestationChallengeRequestFailed { message: String },
     #[error("attestation challenge request rejected with status={status}")]
@@ -353,8 +560,13 @@ pub enum SecurityError {
     },
     #[error("attestation challenge replay detected for nonce={challenge_nonce}")]
     ChallengeReplayDetected { challenge_nonce: String },
-    #[error("attestation replay guard unavailable")]
-    ReplayGuardUnavailable,
+}
+
+fn connector_secret_context() -> KmsEncryptionContext {
+    KmsEncryptionContext::from([(
+        "alfred:purpose".to_string(),
+        CONNECTOR_SECRET_CONTEXT_PURPOSE.to_string(),
+    )])
 }
 
 #[cfg(test)]
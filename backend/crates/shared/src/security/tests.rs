@@ -1,12 +1,14 @@
 use base64::Engine as _;
 use chrono::Utc;
 use ed25519_dalek::{Signer, SigningKey};
+use uuid::Uuid;
 
 use crate::enclave_runtime::{AttestationChallengeRequest, AttestationChallengeResponse};
 
 use super::{
-    ConnectorKeyMetadata, KmsDecryptPolicy, SecretRuntime, SecurityError, TeeAttestationPolicy,
-    build_attestation_signing_payload_for_tests,
+    ConnectorKeyMetadata, DataKey, KmsClient, KmsDecryptPolicy, KmsEncryptionContext,
+    SecretRuntime, SecurityError, TeeAttestationPolicy,
+    build_attestation_signing_payload_for_tests, encode_sealed_secret,
 };
 
 fn signing_key() -> SigningKey {
@@ -68,6 +70,7 @@ fn signed_response(
         operation_purpose: challenge.operation_purpose.clone(),
         request_id: challenge.request_id.clone(),
         evidence_issued_at,
+        tls_certificate_sha256: None,
         signature: None,
     };
 
@@ -106,8 +109,8 @@ fn validate_key_binding_denies_key_version_mismatch() {
     assert!(matches!(err, SecurityError::KmsVersionMismatch { .. }));
 }
 
-#[test]
-fn verify_challenge_response_allows_valid_signed_attestation() {
+#[tokio::test]
+async fn verify_challenge_response_allows_valid_signed_attestation() {
     let (runtime, signing_key) = runtime();
     let challenge = challenge();
     let response = signed_response(
@@ -120,14 +123,15 @@ fn verify_challenge_response_allows_valid_signed_attestation() {
 
     let identity = runtime
         .verify_challenge_response(&challenge, &response)
+        .await
         .expect("valid challenge response should pass");
 
     assert_eq!(identity.runtime, "nitro");
     assert_eq!(identity.measurement, "mr_enclave_1");
 }
 
-#[test]
-fn verify_challenge_response_denies_runtime_mismatch() {
+#[tokio::test]
+async fn verify_challenge_response_denies_runtime_mismatch() {
     let (runtime, signing_key) = runtime();
     let challenge = challenge();
     let response = signed_response(
@@ -140,13 +144,14 @@ fn verify_challenge_response_denies_runtime_mismatch() {
 
     let err = runtime
         .verify_challenge_response(&challenge, &response)
+        .await
         .expect_err("runtime mismatch must fail");
 
     assert!(matches!(err, SecurityError::RuntimeMismatch { .. }));
 }
 
-#[test]
-fn verify_challenge_response_denies_measurement_mismatch() {
+#[tokio::test]
+async fn verify_challenge_response_denies_measurement_mismatch() {
     let (runtime, signing_key) = runtime();
     let challenge = challenge();
     let response = signed_response(
@@ -159,13 +164,14 @@ fn verify_challenge_response_denies_measurement_mismatch() {
 
     let err = runtime
         .verify_challenge_response(&challenge, &response)
+        .await
         .expect_err("measurement mismatch must fail");
 
     assert!(matches!(err, SecurityError::MeasurementNotAllowed { .. }));
 }
 
-#[test]
-fn verify_challenge_response_denies_stale_evidence_timestamp() {
+#[tokio::test]
+async fn verify_challenge_response_denies_stale_evidence_timestamp() {
     let (mut runtime, signing_key) = runtime();
     runtime.tee_policy.max_attestation_age_seconds = 5;
     let mut challenge = challenge();
@@ -181,13 +187,14 @@ fn verify_challenge_response_denies_stale_evidence_timestamp() {
 
     let err = runtime
         .verify_challenge_response(&challenge, &response)
+        .await
         .expect_err("stale evidence must fail");
 
     assert!(matches!(err, SecurityError::StaleAttestation { .. }));
 }
 
-#[test]
-fn verify_challenge_response_denies_evidence_outside_challenge_window() {
+#[tokio::test]
+async fn verify_challenge_response_denies_evidence_outside_challenge_window() {
     let (runtime, signing_key) = runtime();
     let challenge = challenge();
     let response = signed_response(
@@ -200,6 +207,7 @@ fn verify_challenge_response_denies_evidence_outside_challenge_window() {
 
     let err = runtime
         .verify_challenge_response(&challenge, &response)
+        .await
         .expect_err("evidence outside challenge window must fail");
 
     assert!(matches!(
@@ -208,8 +216,8 @@ fn verify_challenge_response_denies_evidence_outside_challenge_window() {
     ));
 }
 
-#[test]
-fn verify_challenge_response_denies_expired_challenge() {
+#[tokio::test]
+async fn verify_challenge_response_denies_expired_challenge() {
     let (runtime, signing_key) = runtime();
     let now = Utc::now().timestamp();
     let challenge = AttestationChallengeRequest {
@@ -223,13 +231,14 @@ fn verify_challenge_response_denies_expired_challenge() {
 
     let err = runtime
         .verify_challenge_response(&challenge, &response)
+        .await
         .expect_err("expired challenge must fail");
 
     assert!(matches!(err, SecurityError::ChallengeExpired { .. }));
 }
 
-#[test]
-fn verify_challenge_response_denies_nonce_replay() {
+#[tokio::test]
+async fn verify_challenge_response_denies_nonce_replay() {
     let (runtime, signing_key) = runtime();
     let challenge = challenge();
     let response = signed_response(
@@ -242,17 +251,19 @@ fn verify_challenge_response_denies_nonce_replay() {
 
     runtime
         .verify_challenge_response(&challenge, &response)
+        .await
         .expect("first attempt should succeed");
 
     let err = runtime
         .verify_challenge_response(&challenge, &response)
+        .await
         .expect_err("replayed nonce must fail");
 
     assert!(matches!(err, SecurityError::ChallengeReplayDetected { .. }));
 }
 
-#[test]
-fn verify_challenge_response_denies_signature_mismatch() {
+#[tokio::test]
+async fn verify_challenge_response_denies_signature_mismatch() {
     let (runtime, signing_key) = runtime();
     let challenge = challenge();
     let mut response = signed_response(
@@ -266,6 +277,7 @@ fn verify_challenge_response_denies_signature_mismatch() {
 
     let err = runtime
         .verify_challenge_response(&challenge, &response)
+        .await
         .expect_err("tampered payload must fail signature verification");
 
     assert!(matches!(
@@ -273,3 +285,171 @@ fn verify_challenge_response_denies_signature_mismatch() {
         SecurityError::ChallengePurposeMismatch { .. } | SecurityError::InvalidAttestationSignature
     ));
 }
+
+#[test]
+fn policy_fingerprint_changes_with_the_kms_policy() {
+    let (mut runtime, _) = runtime();
+    let before = runtime.policy_fingerprint();
+
+    runtime.kms_policy.key_version = 8;
+
+    assert_ne!(runtime.policy_fingerprint(), before);
+}
+
+#[tokio::test]
+async fn connector_secret_round_trips_through_the_kms_stub() {
+    let (runtime, _) = runtime();
+    let runtime = runtime.with_kms_client(KmsClient::LocalStub);
+    let user_id = Uuid::new_v4();
+    let connector_id = Uuid::new_v4();
+
+    let wrapped = runtime
+        .wrap_connector_secret(user_id, "google", connector_id, "refresh-token")
+        .await
+        .expect("wrap should succeed");
+
+    assert!(wrapped.starts_with("kms:v3:"));
+    assert!(!wrapped.contains("refresh-token"));
+    assert_eq!(
+        runtime
+            .unwrap_connector_secret(user_id, "google", connector_id, wrapped)
+            .await
+            .expect("unwrap should succeed"),
+        "refresh-token"
+    );
+}
+
+#[tokio::test]
+async fn connector_secrets_get_their_own_data_keys() {
+    let (runtime, _) = runtime();
+    let runtime = runtime.with_kms_client(KmsClient::LocalStub);
+    let user_id = Uuid::new_v4();
+    let connector_id = Uuid::new_v4();
+
+    let first = runtime
+        .wrap_connector_secret(user_id, "google", connector_id, "refresh-token")
+        .await
+        .expect("wrap should succeed");
+    let second = runtime
+        .wrap_connector_secret(user_id, "google", connector_id, "refresh-token")
+        .await
+        .expect("wrap should succeed");
+    let wrapped_data_key = |stored: &str| stored.split('.').next().map(ToString::to_string);
+    assert_ne!(wrapped_data_key(&first), wrapped_data_key(&second));
+
+    for (other_user, other_provider, other_connector) in [
+        (Uuid::new_v4(), "google", connector_id),
+        (user_id, "imap", connector_id),
+        (user_id, "google", Uuid::new_v4()),
+    ] {
+        let err = runtime
+            .unwrap_connector_secret(other_user, other_provider, other_connector, first.clone())
+            .await
+            .expect_err("a data key must only open its own connector's secret");
+        assert!(matches!(err, SecurityError::KmsCiphertextInvalid(_)));
+    }
+}
+
+#[tokio::test]
+async fn secrets_wrapped_before_per_connector_data_keys_still_unwrap() {
+    let (runtime, _) = runtime();
+    let runtime = runtime.with_kms_client(KmsClient::LocalStub);
+    let ciphertext = KmsClient::LocalStub
+        .encrypt(
+            "kms/local/alfred-refresh-token",
+            b"refresh-token",
+            &KmsEncryptionContext::from([(
+                "alfred:purpose".to_string(),
+                "connector_secret".to_string(),
+            )]),
+        )
+        .await
+        .expect("stub encrypt should succeed");
+    let stored = format!(
+        "kms:v1:{}",
+        base64::engine::general_purpose::STANDARD.encode(ciphertext)
+    );
+
+    assert_eq!(
+        runtime
+            .unwrap_connector_secret(Uuid::new_v4(), "google", Uuid::new_v4(), stored)
+            .await
+            .expect("legacy kms secret should unwrap"),
+        "refresh-token"
+    );
+}
+
+#[tokio::test]
+async fn secrets_sealed_before_connector_bound_contexts_still_unwrap() {
+    let (runtime, _) = runtime();
+    let runtime = runtime.with_kms_client(KmsClient::LocalStub);
+    let user_id = Uuid::new_v4();
+    let context = KmsEncryptionContext::from([
+        (
+            "alfred:purpose".to_string(),
+            "connector_data_key".to_string(),
+        ),
+        ("alfred:user_id".to_string(), user_id.to_string()),
+        ("alfred:provider".to_string(), "google".to_string()),
+    ]);
+    let data_key = DataKey::generate();
+    let sealed = data_key
+        .seal(b"refresh-token", &context)
+        .expect("seal should succeed");
+    let wrapped_data_key = KmsClient::LocalStub
+        .encrypt(
+            "kms/local/alfred-refresh-token",
+            data_key.as_bytes(),
+            &context,
+        )
+        .await
+        .expect("stub encrypt should succeed");
+    let stored = encode_sealed_secret(&wrapped_data_key, &sealed).replacen("kms:v3:", "kms:v2:", 1);
+
+    assert_eq!(
+        runtime
+            .unwrap_connector_secret(user_id, "google", Uuid::new_v4(), stored)
+            .await
+            .expect("v2 secret should unwrap"),
+        "refresh-token"
+    );
+}
+
+#[tokio::test]
+async fn unwrapped_legacy_secrets_pass_through() {
+    let (runtime, _) = runtime();
+    let runtime = runtime.with_kms_client(KmsClient::LocalStub);
+
+    assert_eq!(
+        runtime
+            .unwrap_connector_secret(
+                Uuid::new_v4(),
+                "google",
+                Uuid::new_v4(),
+                "legacy-refresh-token".to_string()
+            )
+            .await
+            .expect("legacy secret should be returned"),
+        "legacy-refresh-token"
+    );
+}
+
+#[tokio::test]
+async fn wrapped_secret_fails_closed_without_a_kms_client() {
+    let (runtime, _) = runtime();
+    let user_id = Uuid::new_v4();
+    let connector_id = Uuid::new_v4();
+    let wrapped = runtime
+        .clone()
+        .with_kms_client(KmsClient::LocalStub)
+        .wrap_connector_secret(user_id, "google", connector_id, "refresh-token")
+        .await
+        .expect("wrap should succeed");
+
+    let err = runtime
+        .unwrap_connector_secret(user_id, "google", connector_id, wrapped)
+        .await
+        .expect_err("a wrapped secret must not be returned without kms");
+
+    assert!(matches!(err, SecurityError::KmsNotConfigured));
+}
@@ -0,0 +1,231 @@
+//! Outbound developer webhooks shared by the api-server and worker.
+//!
+//! Events are metadata only: identifiers, states and timestamps, never prompts, calendar
+//! content or tokens. Each request body is signed with the subscription's secret so the
+//! receiver can verify it came from Alfred and was not replayed.
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::Url;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::network_policy::{is_public_host, resolve_public_address};
+
+pub const WEBHOOK_URL_MAX_CHARS: usize = 2048;
+pub const WEBHOOK_EVENT_ID_HEADER: &str = "x-alfred-webhook-id";
+pub const WEBHOOK_EVENT_TYPE_HEADER: &str = "x-alfred-webhook-event";
+pub const WEBHOOK_TIMESTAMP_HEADER: &str = "x-alfred-webhook-timestamp";
+pub const WEBHOOK_SIGNATURE_HEADER: &str = "x-alfred-webhook-signature";
+const WEBHOOK_SIGNATURE_VERSION: &str = "v1";
+const WEBHOOK_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Attempts per delivery before it is marked `FAILED` and kept only in the delivery log.
+pub const WEBHOOK_MAX_ATTEMPTS: i32 = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+pub enum WebhookEventType {
+    #[serde(rename = "automation_run.completed")]
+    AutomationRunCompleted,
+    #[serde(rename = "privacy_delete.completed")]
+    PrivacyDeleteCompleted,
+    #[serde(rename = "connector.needs_reauth")]
+    ConnectorNeedsReauth,
+}
+
+impl WebhookEventType {
+    pub const ALL: [Self; 3] = [
+        Self::AutomationRunCompleted,
+        Self::PrivacyDeleteCompleted,
+        Self::ConnectorNeedsReauth,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::AutomationRunCompleted => "automation_run.completed",
+            Self::PrivacyDeleteCompleted => "privacy_delete.completed",
+            Self::ConnectorNeedsReauth => "connector.needs_reauth",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|event_type| event_type.as_str() == value)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum WebhookDeliveryState {
+    Pending,
+    Delivered,
+    Failed,
+}
+
+impl WebhookDeliveryState {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "PENDING",
+            Self::Delivered => "DELIVERED",
+            Self::Failed => "FAILED",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "PENDING" => Some(Self::Pending),
+            "DELIVERED" => Some(Self::Delivered),
+            "FAILED" => Some(Self::Failed),
+            _ => None,
+        }
+    }
+}
+
+/// Request body posted to subscribers. `id` is stable across retries so receivers can
+/// deduplicate.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookEvent {
+    pub id: Uuid,
+    #[serde(rename = "type")]
+    pub event_type: WebhookEventType,
+    pub occurred_at: DateTime<Utc>,
+    pub data: Value,
+}
+
+impl WebhookEvent {
+    pub fn new(event_type: WebhookEventType, data: Value) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            event_type,
+            occurred_at: Utc::now(),
+            data,
+        }
+    }
+}
+
+/// Validates a subscriber endpoint. Only HTTPS URLs pointing at public hosts are accepted,
+/// since the worker posts to them from inside the deployment network.
+pub fn normalize_webhook_url(value: &str) -> Result<String, &'static str> {
+    let trimmed = value.trim();
+    if trimmed.is_empty() || trimmed.chars().count() > WEBHOOK_URL_MAX_CHARS {
+        return Err("url must be between 1 and 2048 characters");
+    }
+
+    let mut url = Url::parse(trimmed).map_err(|_| "url must be a valid URL")?;
+    if url.scheme() != "https" {
+        return Err("url must use https");
+    }
+    if !url.username().is_empty() || url.password().is_some() {
+        return Err("url must not embed credentials");
+    }
+
+    let host = url.host_str().ok_or("url must include a host")?;
+    if !is_public_host(host) {
+        return Err("url must point at a public host");
+    }
+
+    url.set_fragment(None);
+    Ok(url.to_string())
+}
+
+/// Builds the client for one send to a stored endpoint. The name is re-resolved because it
+/// may have been re-pointed at a private address after the subscription was created, and the
+/// client connects to the checked address so a second lookup cannot swap it. Redirects are
+/// not followed.
+pub async fn pinned_webhook_client(url: &str) -> Result<reqwest::Client, String> {
+    let url = Url::parse(url).map_err(|err| format!("invalid webhook url: {err}"))?;
+    let host = url.host_str().ok_or("webhook url has no host")?;
+    let port = url.port_or_known_default().unwrap_or(443);
+    let address = resolve_public_address(host, port)
+        .await
+        .map_err(|err| format!("webhook host rejected: {err}"))?;
+
+    reqwest::Client::builder()
+        .timeout(WEBHOOK_REQUEST_TIMEOUT)
+        .redirect(reqwest::redirect::Policy::none())
+        .resolve(host, address)
+        .build()
+        .map_err(|err| format!("failed to build webhook client: {err}"))
+}
+
+/// Value of [`WEBHOOK_SIGNATURE_HEADER`]: `v1=` followed by the hex HMAC-SHA256 of
+/// `"{timestamp}.{body}"` keyed with the subscription's signing secret.
+pub fn webhook_signature(signing_secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(signing_secret.as_bytes())
+        .expect("hmac accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+
+    let digest = mac.finalize().into_bytes();
+    let mut signature = String::with_capacity(WEBHOOK_SIGNATURE_VERSION.len() + 1 + 64);
+    signature.push_str(WEBHOOK_SIGNATURE_VERSION);
+    signature.push('=');
+    for byte in digest {
+        use std::fmt::Write;
+        let _ = write!(signature, "{byte:02x}");
+    }
+    signature
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{WebhookEventType, normalize_webhook_url, webhook_signature};
+
+    #[test]
+    fn urls_require_https_and_a_public_host() {
+        assert_eq!(
+            normalize_webhook_url(" https://hooks.example.com/alfred#frag "),
+            Ok("https://hooks.example.com/alfred".to_string())
+        );
+        assert!(normalize_webhook_url("http://hooks.example.com/alfred").is_err());
+        assert!(normalize_webhook_url("https://user:pw@hooks.example.com/").is_err());
+        assert!(normalize_webhook_url("https://localhost/hook").is_err());
+        assert!(normalize_webhook_url("https://10.1.2.3/hook").is_err());
+        assert!(normalize_webhook_url("https://169.254.169.254/latest/").is_err());
+        assert!(normalize_webhook_url(&format!("https://a.com/{}", "a".repeat(2048))).is_err());
+    }
+
+    #[test]
+    fn event_types_round_trip_through_their_wire_names() {
+        for event_type in WebhookEventType::ALL {
+            assert_eq!(
+                serde_json::to_value(event_type).expect("event type should serialize"),
+                json!(event_type.as_str())
+            );
+            assert_eq!(
+                WebhookEventType::parse(event_type.as_str()),
+                Some(event_type)
+            );
+        }
+        assert_eq!(WebhookEventType::parse("automation_run.started"), None);
+    }
+
+    #[test]
+    fn signatures_cover_the_timestamp_and_body() {
+        let signature = webhook_signature("whsec_test", 1_700_000_000, b"{\"id\":1}");
+
+        assert!(signature.starts_with("v1="));
+        assert_eq!(signature.len(), 3 + 64);
+        assert_eq!(
+            signature,
+            webhook_signature("whsec_test", 1_700_000_000, b"{\"id\":1}")
+        );
+        assert_ne!(
+            signature,
+            webhook_signature("whsec_test", 1_700_000_001, b"{\"id\":1}")
+        );
+        assert_ne!(
+            signature,
+            webhook_signature("whsec_other", 1_700_000_000, b"{\"id\":1}")
+        );
+    }
+}
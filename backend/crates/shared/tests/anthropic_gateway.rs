@@ -0,0 +1,343 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::post;
+use axum::{Json, Router};
+use serde_json::{Value, json};
+use shared::llm::{
+    AnthropicGateway, AnthropicGatewayConfig, AssistantCapability, LlmGateway, LlmGatewayError,
+    LlmGatewayRequest, LlmProviderRole, LlmToolCall, LlmToolDefinition, template_for_capability,
+};
+use tokio::net::TcpListener;
+use tokio::sync::{Mutex, oneshot};
+
+#[derive(Debug, Clone)]
+struct MockReply {
+    status: StatusCode,
+    body: Value,
+}
+
+#[derive(Debug, Clone)]
+struct TestServerState {
+    replies: Arc<Mutex<VecDeque<MockReply>>>,
+    seen_payloads: Arc<Mutex<Vec<Value>>>,
+    seen_headers: Arc<Mutex<Vec<(String, String)>>>,
+}
+
+impl TestServerState {
+    fn with_replies(replies: Vec<MockReply>) -> Self {
+        Self {
+            replies: Arc::new(Mutex::new(VecDeque::from(replies))),
+            seen_payloads: Arc::new(Mutex::new(Vec::new())),
+            seen_headers: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+}
+
+#[tokio::test]
+async fn maps_system_prompt_and_parses_prefilled_json_with_usage() {
+    let state = TestServerState::with_replies(vec![MockReply {
+        status: StatusCode::OK,
+        body: success_response_body(&prefill_continuation()),
+    }]);
+    let (url, shutdown_tx, server_task) = spawn_test_server(state.clone()).await;
+
+    let gateway = AnthropicGateway::new(config_for(url, 0)).expect("gateway should build");
+    let request = meetings_summary_request();
+    let system_prompt = request.system_prompt.clone();
+    let response = gateway
+        .generate(request)
+        .await
+        .expect("message should succeed");
+
+    shutdown_tx.send(()).expect("shutdown signal should send");
+    server_task.await.expect("server task should join");
+
+    assert_eq!(response.model, "claude-3-5-haiku-20241022");
+    assert_eq!(response.provider_request_id.as_deref(), Some("req_header"));
+    assert_eq!(response.output["output"]["title"], "Daily meetings");
+    let usage = response.usage.expect("usage should be extracted");
+    assert_eq!(usage.prompt_tokens, 12 + 3 + 5);
+    assert_eq!(usage.completion_tokens, 8);
+    assert_eq!(usage.total_tokens, 28);
+
+    let payload = state.seen_payloads.lock().await[0].clone();
+    assert_eq!(payload["model"], "claude-test-model");
+    assert_eq!(payload["system"], system_prompt);
+    assert_eq!(payload["max_tokens"], 600);
+    assert_eq!(payload["messages"][0]["role"], "user");
+    assert_eq!(
+        payload["messages"][1],
+        json!({ "role": "assistant", "content": "{" })
+    );
+    assert!(
+        payload.get("response_format").is_none(),
+        "the Messages API has no response_format"
+    );
+
+    let seen_headers = state.seen_headers.lock().await.clone();
+    assert_eq!(
+        seen_headers,
+        vec![("test-anthropic-key".to_string(), "2023-06-01".to_string())]
+    );
+}
+
+#[tokio::test]
+async fn retries_overloaded_responses_and_reports_error_type() {
+    let state = TestServerState::with_replies(vec![
+        provider_error_reply(
+            StatusCode::from_u16(529).expect("status"),
+            "overloaded_error",
+        ),
+        MockReply {
+            status: StatusCode::OK,
+            body: success_response_body(&prefill_continuation()),
+        },
+        provider_error_reply(StatusCode::UNAUTHORIZED, "authentication_error"),
+    ]);
+    let (url, shutdown_tx, server_task) = spawn_test_server(state.clone()).await;
+
+    let gateway = AnthropicGateway::new(config_for(url, 1)).expect("gateway should build");
+    gateway
+        .generate(meetings_summary_request())
+        .await
+        .expect("overloaded response should be retried");
+    let err = gateway
+        .generate(meetings_summary_request())
+        .await
+        .expect_err("authentication errors should not be retried");
+
+    shutdown_tx.send(()).expect("shutdown signal should send");
+    server_task.await.expect("server task should join");
+
+    assert!(
+        matches!(err, LlmGatewayError::ProviderFailure(ref message) if message == "status=401 code=authentication_error"),
+        "expected provider failure, got {err:?}"
+    );
+    assert_eq!(state.seen_payloads.lock().await.len(), 3);
+}
+
+#[tokio::test]
+async fn rejects_text_that_is_not_a_json_object() {
+    let state = TestServerState::with_replies(vec![MockReply {
+        status: StatusCode::OK,
+        body: success_response_body("I cannot help with that."),
+    }]);
+    let (url, shutdown_tx, server_task) = spawn_test_server(state.clone()).await;
+
+    let gateway = AnthropicGateway::new(config_for(url, 0)).expect("gateway should build");
+    let err = gateway
+        .generate(meetings_summary_request())
+        .await
+        .expect_err("prose should be rejected");
+
+    shutdown_tx.send(()).expect("shutdown signal should send");
+    server_task.await.expect("server task should join");
+
+    assert!(
+        matches!(err, LlmGatewayError::InvalidProviderPayload(ref message) if message == "content_not_json"),
+        "expected invalid payload error, got {err:?}"
+    );
+}
+
+#[tokio::test]
+async fn skips_prefill_for_tools_and_parses_tool_use_blocks() {
+    let state = TestServerState::with_replies(vec![MockReply {
+        status: StatusCode::OK,
+        body: json!({
+            "id": "msg_body",
+            "type": "message",
+            "role": "assistant",
+            "model": "claude-3-5-haiku-20241022",
+            "content": [
+                { "type": "text", "text": "Let me look that meeting up." },
+                {
+                    "type": "tool_use",
+                    "id": "toolu_1",
+                    "name": "lookup_meeting",
+                    "input": { "title": "Team sync" }
+                }
+            ],
+            "stop_reason": "tool_use"
+        }),
+    }]);
+    let (url, shutdown_tx, server_task) = spawn_test_server(state.clone()).await;
+
+    let gateway = AnthropicGateway::new(config_for(url, 0)).expect("gateway should build");
+    let tool = LlmToolDefinition {
+        name: "lookup_meeting".to_string(),
+        description: "Look up a meeting by title.".to_string(),
+        parameters: json!({
+            "type": "object",
+            "properties": { "title": { "type": "string" } },
+            "required": ["title"]
+        }),
+    };
+    let response = gateway
+        .generate(meetings_summary_request().with_tools(vec![tool.clone()]))
+        .await
+        .expect("tool use message should succeed");
+
+    shutdown_tx.send(()).expect("shutdown signal should send");
+    server_task.await.expect("server task should join");
+
+    assert_eq!(response.output, Value::Null);
+    assert_eq!(
+        response.tool_calls,
+        vec![LlmToolCall {
+            id: Some("toolu_1".to_string()),
+            name: "lookup_meeting".to_string(),
+            arguments: json!({ "title": "Team sync" }),
+        }]
+    );
+
+    let payload = state.seen_payloads.lock().await[0].clone();
+    assert_eq!(
+        payload["messages"].as_array().map(Vec::len),
+        Some(1),
+        "tool requests must not prefill the assistant turn"
+    );
+    assert_eq!(payload["tools"][0]["name"], "lookup_meeting");
+    assert_eq!(payload["tools"][0]["input_schema"], tool.parameters);
+}
+
+fn meetings_summary_request() -> LlmGatewayRequest {
+    LlmGatewayRequest::from_template(
+        template_for_capability(AssistantCapability::MeetingsSummary),
+        json!({
+            "calendar_day": "2026-02-15",
+            "meetings": [
+                {
+                    "title": "Team sync",
+                    "start_at": "2026-02-15T09:00:00Z"
+                }
+            ]
+        }),
+    )
+}
+
+fn config_for(messages_url: String, max_retries: u32) -> AnthropicGatewayConfig {
+    AnthropicGatewayConfig {
+        messages_url,
+        api_key: "test-anthropic-key".to_string(),
+        api_version: "2023-06-01".to_string(),
+        role: LlmProviderRole::Fallback,
+        model: "claude-test-model".to_string(),
+        temperature: 0.0,
+        timeout_ms: 5_000,
+        max_retries,
+        retry_base_backoff_ms: 0,
+        max_output_tokens: 600,
+        allow_insecure_http: true,
+    }
+}
+
+/// The model's reply after the prefilled `{`.
+fn prefill_continuation() -> String {
+    let object = json!({
+        "version": "2026-02-15",
+        "output": {
+            "title": "Daily meetings",
+            "summary": "You have one meeting this morning.",
+            "key_points": ["Team sync at 9:00 AM"],
+            "follow_ups": ["Share release blockers before noon"]
+        }
+    })
+    .to_string();
+    object[1..].to_string()
+}
+
+fn success_response_body(text: &str) -> Value {
+    json!({
+        "id": "msg_body",
+        "type": "message",
+        "role": "assistant",
+        "model": "claude-3-5-haiku-20241022",
+        "content": [
+            { "type": "text", "text": text }
+        ],
+        "stop_reason": "end_turn",
+        "usage": {
+            "input_tokens": 12,
+            "cache_creation_input_tokens": 3,
+            "cache_read_input_tokens": 5,
+            "output_tokens": 8
+        }
+    })
+}
+
+fn provider_error_reply(status: StatusCode, error_type: &str) -> MockReply {
+    MockReply {
+        status,
+        body: json!({
+            "type": "error",
+            "error": {
+                "type": error_type,
+                "message": "provider error"
+            }
+        }),
+    }
+}
+
+async fn spawn_test_server(
+    state: TestServerState,
+) -> (String, oneshot::Sender<()>, tokio::task::JoinHandle<()>) {
+    let app = Router::new()
+        .route("/v1/messages", post(test_messages_handler))
+        .with_state(state);
+
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("listener should bind");
+    let local_addr = listener
+        .local_addr()
+        .expect("listener address should resolve");
+    let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+
+    let server_task = tokio::spawn(async move {
+        let server = axum::serve(listener, app).with_graceful_shutdown(async move {
+            let _ = shutdown_rx.await;
+        });
+
+        server.await.expect("test server should run");
+    });
+
+    (
+        format!("http://{local_addr}/v1/messages"),
+        shutdown_tx,
+        server_task,
+    )
+}
+
+async fn test_messages_handler(
+    State(state): State<TestServerState>,
+    headers: HeaderMap,
+    Json(payload): Json<Value>,
+) -> (StatusCode, HeaderMap, Json<Value>) {
+    state.seen_payloads.lock().await.push(payload);
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+            .to_string()
+    };
+    state
+        .seen_headers
+        .lock()
+        .await
+        .push((header("x-api-key"), header("anthropic-version")));
+
+    let reply = state.replies.lock().await.pop_front().unwrap_or(MockReply {
+        status: StatusCode::INTERNAL_SERVER_ERROR,
+        body: json!({ "type": "error", "error": { "type": "api_error" } }),
+    });
+    let mut response_headers = HeaderMap::new();
+    response_headers.insert(
+        "request-id",
+        "req_header".parse().expect("header value should parse"),
+    );
+    (reply.status, response_headers, Json(reply.body))
+}
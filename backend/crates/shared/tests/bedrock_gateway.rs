@@ -0,0 +1,305 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode, Uri};
+use axum::routing::post;
+use axum::{Json, Router};
+use serde_json::{Value, json};
+use shared::aws::AwsCredentials;
+use shared::llm::{
+    AssistantCapability, BedrockGateway, BedrockGatewayConfig, LlmGateway, LlmGatewayError,
+    LlmGatewayRequest, LlmProviderRole, template_for_capability,
+};
+use tokio::net::TcpListener;
+use tokio::sync::{Mutex, oneshot};
+
+const CLAUDE_MODEL: &str = "anthropic.claude-3-5-haiku-20241022-v1:0";
+const LLAMA_MODEL: &str = "meta.llama3-1-70b-instruct-v1:0";
+
+#[derive(Debug, Clone)]
+struct MockReply {
+    status: StatusCode,
+    error_type: Option<&'static str>,
+    body: Value,
+}
+
+#[derive(Debug, Clone)]
+struct SeenRequest {
+    path: String,
+    authorization: String,
+    security_token: String,
+    payload: Value,
+}
+
+#[derive(Debug, Clone)]
+struct TestServerState {
+    replies: Arc<Mutex<VecDeque<MockReply>>>,
+    seen_requests: Arc<Mutex<Vec<SeenRequest>>>,
+}
+
+impl TestServerState {
+    fn with_replies(replies: Vec<MockReply>) -> Self {
+        Self {
+            replies: Arc::new(Mutex::new(VecDeque::from(replies))),
+            seen_requests: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+}
+
+#[tokio::test]
+async fn signs_converse_requests_and_parses_prefilled_claude_output() {
+    let state = TestServerState::with_replies(vec![MockReply {
+        status: StatusCode::OK,
+        error_type: None,
+        body: success_response_body(&valid_output_json_string()[1..]),
+    }]);
+    let (endpoint_url, shutdown_tx, server_task) = spawn_test_server(state.clone()).await;
+
+    let gateway = BedrockGateway::new(config_for(endpoint_url, CLAUDE_MODEL, 0))
+        .expect("gateway should build");
+    let request = meetings_summary_request();
+    let system_prompt = request.system_prompt.clone();
+    let response = gateway
+        .generate(request)
+        .await
+        .expect("conversation should succeed");
+
+    shutdown_tx.send(()).expect("shutdown signal should send");
+    server_task.await.expect("server task should join");
+
+    assert_eq!(response.model, CLAUDE_MODEL);
+    assert_eq!(response.provider_request_id.as_deref(), Some("req_header"));
+    assert_eq!(response.output["output"]["title"], "Daily meetings");
+    let usage = response.usage.expect("usage should be extracted");
+    assert_eq!(usage.prompt_tokens, 12 + 3 + 5);
+    assert_eq!(usage.completion_tokens, 8);
+    assert_eq!(usage.total_tokens, 28);
+
+    let seen = state.seen_requests.lock().await[0].clone();
+    assert_eq!(
+        seen.path,
+        "/model/anthropic.claude-3-5-haiku-20241022-v1%3A0/converse"
+    );
+    assert!(
+        seen.authorization
+            .starts_with("AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/"),
+        "unexpected authorization {}",
+        seen.authorization
+    );
+    assert!(
+        seen.authorization
+            .contains("/us-east-1/bedrock/aws4_request, ")
+    );
+    assert!(
+        seen.authorization.contains(
+            "SignedHeaders=content-type;host;x-amz-date;x-amz-security-token, Signature="
+        )
+    );
+    assert_eq!(seen.security_token, "session-token");
+    assert_eq!(seen.payload["system"], json!([{ "text": system_prompt }]));
+    assert_eq!(seen.payload["inferenceConfig"]["maxTokens"], 600);
+    assert_eq!(seen.payload["messages"][0]["role"], "user");
+    assert_eq!(
+        seen.payload["messages"][1],
+        json!({ "role": "assistant", "content": [{ "text": "{" }] })
+    );
+}
+
+#[tokio::test]
+async fn retries_throttling_and_extracts_llama_json_from_prose() {
+    let state = TestServerState::with_replies(vec![
+        error_reply(StatusCode::TOO_MANY_REQUESTS, "ThrottlingException"),
+        MockReply {
+            status: StatusCode::OK,
+            error_type: None,
+            body: success_response_body(&format!(
+                "Here is the summary:\n{}",
+                valid_output_json_string()
+            )),
+        },
+        error_reply(StatusCode::BAD_REQUEST, "ValidationException"),
+    ]);
+    let (endpoint_url, shutdown_tx, server_task) = spawn_test_server(state.clone()).await;
+
+    let gateway = BedrockGateway::new(config_for(endpoint_url, LLAMA_MODEL, 1))
+        .expect("gateway should build");
+    let response = gateway
+        .generate(meetings_summary_request())
+        .await
+        .expect("throttled conversation should be retried");
+    let err = gateway
+        .generate(meetings_summary_request())
+        .await
+        .expect_err("validation errors should not be retried");
+
+    shutdown_tx.send(()).expect("shutdown signal should send");
+    server_task.await.expect("server task should join");
+
+    assert_eq!(response.output["output"]["title"], "Daily meetings");
+    assert!(
+        matches!(err, LlmGatewayError::ProviderFailure(ref message) if message == "status=400 code=ValidationException"),
+        "expected provider failure, got {err:?}"
+    );
+    let seen_requests = state.seen_requests.lock().await.clone();
+    assert_eq!(seen_requests.len(), 3);
+    assert_eq!(
+        seen_requests[0].payload["messages"]
+            .as_array()
+            .map(Vec::len),
+        Some(1),
+        "Llama requests are not prefilled"
+    );
+}
+
+#[test]
+fn rejects_model_families_without_a_json_prompting_strategy() {
+    let result = BedrockGateway::new(config_for(
+        "https://bedrock-runtime.us-east-1.amazonaws.com".to_string(),
+        "amazon.titan-text-express-v1",
+        0,
+    ));
+
+    assert!(result.is_err());
+}
+
+fn meetings_summary_request() -> LlmGatewayRequest {
+    LlmGatewayRequest::from_template(
+        template_for_capability(AssistantCapability::MeetingsSummary),
+        json!({
+            "calendar_day": "2026-02-15",
+            "meetings": [
+                {
+                    "title": "Team sync",
+                    "start_at": "2026-02-15T09:00:00Z"
+                }
+            ]
+        }),
+    )
+}
+
+fn config_for(endpoint_url: String, model: &str, max_retries: u32) -> BedrockGatewayConfig {
+    BedrockGatewayConfig {
+        endpoint_url,
+        region: "us-east-1".to_string(),
+        credentials: AwsCredentials {
+            access_key_id: "AKIDEXAMPLE".to_string(),
+            secret_access_key: "test-secret".to_string(),
+            session_token: Some("session-token".to_string()),
+        },
+        role: LlmProviderRole::Fallback,
+        model: model.to_string(),
+        temperature: 0.0,
+        timeout_ms: 5_000,
+        max_retries,
+        retry_base_backoff_ms: 0,
+        max_output_tokens: 600,
+        allow_insecure_http: true,
+    }
+}
+
+fn valid_output_json_string() -> String {
+    json!({
+        "version": "2026-02-15",
+        "output": {
+            "title": "Daily meetings",
+            "summary": "You have one meeting this morning.",
+            "key_points": ["Team sync at 9:00 AM"],
+            "follow_ups": ["Share release blockers before noon"]
+        }
+    })
+    .to_string()
+}
+
+fn success_response_body(text: &str) -> Value {
+    json!({
+        "output": {
+            "message": {
+                "role": "assistant",
+                "content": [{ "text": text }]
+            }
+        },
+        "stopReason": "end_turn",
+        "usage": {
+            "inputTokens": 12,
+            "cacheWriteInputTokens": 3,
+            "cacheReadInputTokens": 5,
+            "outputTokens": 8,
+            "totalTokens": 28
+        },
+        "metrics": { "latencyMs": 120 }
+    })
+}
+
+fn error_reply(status: StatusCode, error_type: &'static str) -> MockReply {
+    MockReply {
+        status,
+        error_type: Some(error_type),
+        body: json!({ "message": "provider error" }),
+    }
+}
+
+async fn spawn_test_server(
+    state: TestServerState,
+) -> (String, oneshot::Sender<()>, tokio::task::JoinHandle<()>) {
+    let app = Router::new()
+        .route("/model/{model_id}/converse", post(test_converse_handler))
+        .with_state(state);
+
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("listener should bind");
+    let local_addr = listener
+        .local_addr()
+        .expect("listener address should resolve");
+    let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+
+    let server_task = tokio::spawn(async move {
+        let server = axum::serve(listener, app).with_graceful_shutdown(async move {
+            let _ = shutdown_rx.await;
+        });
+
+        server.await.expect("test server should run");
+    });
+
+    (format!("http://{local_addr}"), shutdown_tx, server_task)
+}
+
+async fn test_converse_handler(
+    State(state): State<TestServerState>,
+    uri: Uri,
+    headers: HeaderMap,
+    Json(payload): Json<Value>,
+) -> (StatusCode, HeaderMap, Json<Value>) {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+            .to_string()
+    };
+    state.seen_requests.lock().await.push(SeenRequest {
+        path: uri.path().to_string(),
+        authorization: header("authorization"),
+        security_token: header("x-amz-security-token"),
+        payload,
+    });
+
+    let reply = state.replies.lock().await.pop_front().unwrap_or_else(|| {
+        error_reply(StatusCode::INTERNAL_SERVER_ERROR, "InternalServerException")
+    });
+    let mut response_headers = HeaderMap::new();
+    response_headers.insert(
+        "x-amzn-requestid",
+        "req_header".parse().expect("header value should parse"),
+    );
+    if let Some(error_type) = reply.error_type {
+        response_headers.insert(
+            "x-amzn-errortype",
+            format!("{error_type}:http://internal.amazon.com/coral/com.amazon.bedrock/")
+                .parse()
+                .expect("header value should parse"),
+        );
+    }
+    (reply.status, response_headers, Json(reply.body))
+}
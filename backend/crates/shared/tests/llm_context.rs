@@ -3,6 +3,7 @@ use serde_json::Value;
 use shared::llm::{
     GoogleCalendarMeetingSource, GoogleEmailCandidateSource, assemble_meetings_today_context,
     assemble_morning_brief_context, assemble_urgent_email_candidates_context,
+    assemble_weekly_review_context,
 };
 
 #[test]
@@ -86,6 +87,43 @@ fn assembly_handles_empty_and_noisy_inputs_gracefully() {
     assert!(!encoded.contains("raw_headers"));
 }
 
+#[test]
+fn weekly_review_context_splits_past_and_upcoming_windows() {
+    let review_end_date = date("2026-02-15");
+    let mut meetings = sample_meetings_unsorted();
+    meetings.push(GoogleCalendarMeetingSource {
+        event_id: Some("evt-too-old".to_string()),
+        title: Some("Outside review window".to_string()),
+        start_at: Some(ts("2026-02-08T09:00:00Z")),
+        end_at: None,
+        attendee_emails: vec![],
+    });
+    let candidates = sample_email_candidates_unsorted();
+
+    let context = assemble_weekly_review_context(
+        review_end_date,
+        &meetings,
+        &candidates,
+        Some("  hiring pipeline  "),
+    );
+
+    assert_eq!(context.review_start_date, "2026-02-09");
+    assert_eq!(context.review_end_date, "2026-02-15");
+    assert_eq!(context.past_meeting_count, 2);
+    assert_eq!(context.past_meetings[0].title, "Team sync");
+    assert_eq!(context.upcoming_meeting_count, 1);
+    assert_eq!(context.upcoming_meetings[0].title, "Wrong day");
+    assert_eq!(
+        context.email_count,
+        assemble_urgent_email_candidates_context(&candidates).candidate_count
+    );
+    assert_eq!(context.focus.as_deref(), Some("hiring pipeline"));
+
+    let without_focus = assemble_weekly_review_context(review_end_date, &[], &[], Some("   "));
+    assert!(without_focus.focus.is_none());
+    assert_eq!(without_focus.past_meeting_count, 0);
+}
+
 fn meetings_fixture() -> Value {
     serde_json::from_str(include_str!("fixtures/meetings_today_context.json"))
         .expect("fixture must be valid JSON")
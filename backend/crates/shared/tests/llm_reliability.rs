@@ -1,19 +1,23 @@
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
 
+use chrono::{TimeZone, Utc};
 use serde_json::json;
-use shared::llm::gateway::{LlmGatewayFuture, LlmTokenUsage};
+use shared::llm::gateway::{LlmGatewayFuture, LlmTokenUsage, LlmWarmUpFuture};
 use shared::llm::reliability::ReliableLlmGateway;
 use shared::llm::{
     AssistantCapability, LlmGateway, LlmGatewayError, LlmGatewayRequest, LlmGatewayResponse,
-    LlmReliabilityConfig, template_for_capability,
+    LlmReliabilityConfig, LlmSpendBudget, LlmSpendBudgetFuture, LlmSpendBudgetStatus,
+    template_for_capability,
 };
+use shared::models::UserPlan;
 use tokio::sync::Mutex;
 
 #[derive(Clone)]
 struct StubGateway {
     responses: Arc<Mutex<VecDeque<Result<LlmGatewayResponse, LlmGatewayError>>>>,
     seen_requesters: Arc<Mutex<Vec<String>>>,
+    warm_up_calls: Arc<Mutex<usize>>,
 }
 
 impl StubGateway {
@@ -21,6 +25,7 @@ impl StubGateway {
         Self {
             responses: Arc::new(Mutex::new(VecDeque::from(responses))),
             seen_requesters: Arc::new(Mutex::new(Vec::new())),
+            warm_up_calls: Arc::new(Mutex::new(0)),
         }
     }
 
@@ -31,6 +36,10 @@ impl StubGateway {
     async fn seen_requesters(&self) -> Vec<String> {
         self.seen_requesters.lock().await.clone()
     }
+
+    async fn warm_up_calls(&self) -> usize {
+        *self.warm_up_calls.lock().await
+    }
 }
 
 impl LlmGateway for StubGateway {
@@ -49,6 +58,94 @@ impl LlmGateway for StubGateway {
             })
         })
     }
+
+    fn warm_up<'a>(&'a self) -> LlmWarmUpFuture<'a> {
+        Box::pin(async move {
+            *self.warm_up_calls.lock().await += 1;
+            Err(LlmGatewayError::ProviderFailure(
+                "provider_down".to_string(),
+            ))
+        })
+    }
+}
+
+struct StubSpendBudget {
+    spent_micros: Mutex<HashMap<String, i64>>,
+    limit_micros: i64,
+}
+
+impl StubSpendBudget {
+    fn with_limit(limit_micros: i64) -> Self {
+        Self {
+            spent_micros: Mutex::new(HashMap::new()),
+            limit_micros,
+        }
+    }
+
+    async fn spent_micros(&self, requester_id: &str) -> i64 {
+        self.spent_micros
+            .lock()
+            .await
+            .get(requester_id)
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+impl LlmSpendBudget for StubSpendBudget {
+    fn status<'a>(
+        &'a self,
+        requester_id: &'a str,
+    ) -> LlmSpendBudgetFuture<'a, Option<LlmSpendBudgetStatus>> {
+        Box::pin(async move {
+            Ok(Some(LlmSpendBudgetStatus {
+                plan: UserPlan::Free,
+                month_start: chrono::NaiveDate::from_ymd_opt(2026, 2, 1).expect("valid date"),
+                limit_micros: self.limit_micros,
+                spent_micros: self.spent_micros(requester_id).await,
+                resets_at: Utc
+                    .with_ymd_and_hms(2026, 3, 1, 0, 0, 0)
+                    .single()
+                    .expect("valid timestamp"),
+            }))
+        })
+    }
+
+    fn record_spend<'a>(
+        &'a self,
+        requester_id: &'a str,
+        estimated_cost_usd: f64,
+    ) -> LlmSpendBudgetFuture<'a, ()> {
+        Box::pin(async move {
+            *self
+                .spent_micros
+                .lock()
+                .await
+                .entry(requester_id.to_string())
+                .or_default() += (estimated_cost_usd * 1_000_000.0).round() as i64;
+            Ok(())
+        })
+    }
+}
+
+/// A budget store that is down, such as Postgres refusing connections.
+struct UnavailableSpendBudget;
+
+impl LlmSpendBudget for UnavailableSpendBudget {
+    fn status<'a>(
+        &'a self,
+        _requester_id: &'a str,
+    ) -> LlmSpendBudgetFuture<'a, Option<LlmSpendBudgetStatus>> {
+        Box::pin(async { Err("connection refused".to_string()) })
+    }
+
+    fn record_spend<'a>(
+        &'a self,
+        _requester_id: &'a str,
+        _estimated_cost_usd: f64,
+    ) -> LlmSpendBudgetFuture<'a, ()> {
+        Box::pin(async { Err("connection refused".to_string()) })
+    }
 }
 
 #[tokio::test]
@@ -202,6 +299,213 @@ async fn switches_to_budget_gateway_when_budget_threshold_exceeded() {
     );
 }
 
+#[tokio::test]
+async fn rejects_requesters_over_their_monthly_spend_budget() {
+    let primary = StubGateway::with_responses(vec![
+        Ok(success_response("openai/gpt-4o-mini", 4_000_000, 0)),
+        Ok(success_response("openai/gpt-4o-mini", 5, 5)),
+    ]);
+    let spend_budget = Arc::new(StubSpendBudget::with_limit(500_000));
+
+    let gateway = ReliableLlmGateway::new(primary.clone(), None, base_config())
+        .expect("gateway should build")
+        .with_spend_budget(spend_budget.clone());
+
+    gateway
+        .generate(request_for("user-a", "first"))
+        .await
+        .expect("first request should fit the budget");
+    assert!(spend_budget.spent_micros("user-a").await >= 500_000);
+
+    let err = gateway
+        .generate(request_for("user-a", "second"))
+        .await
+        .expect_err("exhausted budget should reject");
+    assert!(
+        matches!(err, LlmGatewayError::BudgetExceeded(message) if message.contains("plan=free"))
+    );
+    assert_eq!(
+        primary.calls().await,
+        1,
+        "budget should reject before calling provider"
+    );
+
+    gateway
+        .generate(request_for("user-b", "first"))
+        .await
+        .expect("other requesters keep their own budget");
+}
+
+#[tokio::test]
+async fn unavailable_spend_budget_fails_open_within_the_rate_limits() {
+    let primary = StubGateway::with_responses(vec![
+        Ok(success_response("openai/gpt-4o-mini", 5, 5)),
+        Ok(success_response("openai/gpt-4o-mini", 5, 5)),
+    ]);
+    let mut config = base_config();
+    config.rate_limit_per_user_max_requests = 1;
+
+    let gateway = ReliableLlmGateway::new(primary.clone(), None, config)
+        .expect("gateway should build")
+        .with_spend_budget(Arc::new(UnavailableSpendBudget));
+
+    gateway
+        .generate(request_for("user-a", "first"))
+        .await
+        .expect("a failed budget lookup should not refuse the request");
+    let err = gateway
+        .generate(request_for("user-a", "second"))
+        .await
+        .expect_err("the per-user rate limit still applies");
+
+    assert!(
+        matches!(err, LlmGatewayError::ProviderFailure(message) if message.contains("rate_limited"))
+    );
+    assert_eq!(primary.calls().await, 1);
+}
+
+#[tokio::test]
+async fn fallback_provider_serves_primary_failures_without_opening_breaker() {
+    let primary = StubGateway::with_responses(vec![
+        Err(LlmGatewayError::ProviderFailure("status=529".to_string())),
+        Err(LlmGatewayError::Timeout),
+        Err(LlmGatewayError::RateLimited(
+            "outbound_rate_limited retry_after_ms=10".to_string(),
+        )),
+    ]);
+    let fallback = StubGateway::with_responses(vec![
+        Ok(success_response("claude-3-5-haiku-20241022", 5, 5)),
+        Ok(success_response("claude-3-5-haiku-20241022", 5, 5)),
+    ]);
+    let mut config = base_config();
+    config.circuit_breaker_failure_threshold = 1;
+
+    let gateway = ReliableLlmGateway::new(primary.clone(), None, config)
+        .expect("gateway should build")
+        .with_fallback_gateway(fallback.clone());
+
+    for marker in ["first", "second"] {
+        let response = gateway
+            .generate(request_for("user-a", marker))
+            .await
+            .expect("fallback provider should answer");
+        assert_eq!(response.model, "claude-3-5-haiku-20241022");
+    }
+    let err = gateway
+        .generate(request_for("user-a", "third"))
+        .await
+        .expect_err("the outbound limiter should not fall back");
+
+    assert!(matches!(err, LlmGatewayError::RateLimited(_)));
+    assert_eq!(primary.calls().await, 3);
+    assert_eq!(fallback.calls().await, 2);
+}
+
+#[tokio::test]
+async fn direct_openai_usage_counts_toward_the_budget() {
+    let primary = StubGateway::with_responses(vec![Ok(success_response(
+        "gpt-4o-mini-2024-07-18",
+        4_000_000,
+        0,
+    ))]);
+    let budget =
+        StubGateway::with_responses(vec![Ok(success_response("openai/gpt-4o-mini", 10, 10))]);
+
+    let mut config = base_config();
+    config.budget_max_estimated_cost_usd = 0.5;
+
+    let gateway = ReliableLlmGateway::new(primary.clone(), Some(budget.clone()), config)
+        .expect("gateway should build");
+    for marker in ["first", "second"] {
+        gateway
+            .generate(request_for("user-a", marker))
+            .await
+            .expect("request should succeed");
+    }
+
+    assert_eq!(primary.calls().await, 1);
+    assert_eq!(
+        budget.calls().await,
+        1,
+        "dated OpenAI models should be priced"
+    );
+}
+
+#[tokio::test]
+async fn capability_gateway_serves_its_capabilities_before_the_primary() {
+    let local = StubGateway::with_responses(vec![
+        Ok(success_response("llama3.2:3b", 5, 5)),
+        Err(LlmGatewayError::Timeout),
+    ]);
+    let primary = StubGateway::with_responses(vec![
+        Ok(success_response("openai/gpt-4o-mini", 5, 5)),
+        Ok(success_response("openai/gpt-4o-mini", 5, 5)),
+    ]);
+
+    let gateway = ReliableLlmGateway::new(primary.clone(), None, base_config())
+        .expect("gateway should build")
+        .with_capability_gateway(local.clone(), vec![AssistantCapability::MeetingsSummary]);
+
+    let served_locally = gateway
+        .generate(request_for("user-a", "first"))
+        .await
+        .expect("local model should answer");
+    let fell_back = gateway
+        .generate(request_for("user-a", "second"))
+        .await
+        .expect("primary should answer when the local model fails");
+    gateway
+        .generate(
+            LlmGatewayRequest::from_template(
+                template_for_capability(AssistantCapability::GeneralChatSummary),
+                json!({ "query": "What changed today?" }),
+            )
+            .with_requester_id("user-a"),
+        )
+        .await
+        .expect("other capabilities should go straight to the primary");
+
+    assert_eq!(served_locally.model, "llama3.2:3b");
+    assert_eq!(fell_back.model, "openai/gpt-4o-mini");
+    assert_eq!(local.calls().await, 2);
+    assert_eq!(primary.calls().await, 2);
+
+    gateway
+        .warm_up()
+        .await
+        .expect_err("warm-up should surface the primary failure");
+    assert_eq!(
+        local.warm_up_calls().await,
+        0,
+        "a failed primary probe short-circuits the local one"
+    );
+}
+
+#[tokio::test]
+async fn warm_up_reaches_provider_without_tripping_circuit_breaker() {
+    let primary =
+        StubGateway::with_responses(vec![Ok(success_response("openai/gpt-4o-mini", 5, 5))]);
+    let mut config = base_config();
+    config.circuit_breaker_failure_threshold = 1;
+
+    let gateway =
+        ReliableLlmGateway::new(primary.clone(), None, config).expect("gateway should build");
+
+    for _ in 0..2 {
+        gateway
+            .warm_up()
+            .await
+            .expect_err("warm-up should surface the provider failure");
+    }
+    gateway
+        .generate(request_for("user-a", "after-warm-up"))
+        .await
+        .expect("failed warm-ups should not open the circuit breaker");
+
+    assert_eq!(primary.warm_up_calls().await, 2);
+    assert_eq!(primary.calls().await, 1);
+}
+
 fn request_for(requester_id: &str, marker: &str) -> LlmGatewayRequest {
     LlmGatewayRequest::from_template(
         template_for_capability(AssistantCapability::MeetingsSummary),
@@ -226,6 +530,7 @@ fn success_response(model: &str, prompt_tokens: u32, completion_tokens: u32) ->
                 "follow_ups": []
             }
         }),
+        tool_calls: Vec::new(),
         usage: Some(LlmTokenUsage {
             prompt_tokens,
             completion_tokens,
@@ -0,0 +1,247 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde_json::{Value, json};
+use shared::llm::{
+    AssistantCapability, LlmGateway, LlmGatewayError, LlmGatewayRequest, LocalLlmGateway,
+    LocalLlmGatewayConfig, template_for_capability,
+};
+use tokio::net::TcpListener;
+use tokio::sync::{Mutex, oneshot};
+
+#[derive(Debug, Clone)]
+struct MockReply {
+    status: StatusCode,
+    body: Value,
+}
+
+#[derive(Debug, Clone)]
+struct TestServerState {
+    replies: Arc<Mutex<VecDeque<MockReply>>>,
+    served_models: Vec<&'static str>,
+    seen_payloads: Arc<Mutex<Vec<Value>>>,
+    seen_authorizations: Arc<Mutex<Vec<String>>>,
+}
+
+impl TestServerState {
+    fn new(replies: Vec<MockReply>, served_models: Vec<&'static str>) -> Self {
+        Self {
+            replies: Arc::new(Mutex::new(VecDeque::from(replies))),
+            served_models,
+            seen_payloads: Arc::new(Mutex::new(Vec::new())),
+            seen_authorizations: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+}
+
+#[tokio::test]
+async fn requests_json_object_output_from_the_local_model() {
+    let state = TestServerState::new(
+        vec![MockReply {
+            status: StatusCode::OK,
+            body: success_response_body(&valid_output_json_string()),
+        }],
+        vec!["llama3.2:3b"],
+    );
+    let (base_url, shutdown_tx, server_task) = spawn_test_server(state.clone()).await;
+
+    let mut config = config_for(base_url, "llama3.2:3b");
+    config.api_key = Some("local-key".to_string());
+    let gateway = LocalLlmGateway::new(config).expect("gateway should build");
+    gateway.warm_up().await.expect("served model should pass");
+    let response = gateway
+        .generate(meetings_summary_request())
+        .await
+        .expect("completion should succeed");
+
+    shutdown_tx.send(()).expect("shutdown signal should send");
+    server_task.await.expect("server task should join");
+
+    assert_eq!(response.model, "llama3.2:3b");
+    assert_eq!(response.output["output"]["title"], "Daily meetings");
+    let usage = response.usage.expect("usage should be extracted");
+    assert_eq!(usage.total_tokens, 20);
+
+    let payload = state.seen_payloads.lock().await[0].clone();
+    assert_eq!(payload["model"], "llama3.2:3b");
+    assert_eq!(payload["response_format"], json!({ "type": "json_object" }));
+    assert_eq!(payload["max_tokens"], 180);
+    assert_eq!(payload["stream"], false);
+    assert_eq!(
+        state.seen_authorizations.lock().await.clone(),
+        vec!["Bearer local-key".to_string(); 2]
+    );
+}
+
+#[tokio::test]
+async fn health_probe_fails_until_the_model_is_served() {
+    let state = TestServerState::new(
+        vec![MockReply {
+            status: StatusCode::OK,
+            body: success_response_body("I think the answer is yes."),
+        }],
+        vec!["qwen2.5:1.5b"],
+    );
+    let (base_url, shutdown_tx, server_task) = spawn_test_server(state.clone()).await;
+
+    let gateway =
+        LocalLlmGateway::new(config_for(base_url, "llama3.2:3b")).expect("gateway should build");
+    let probe_err = gateway
+        .warm_up()
+        .await
+        .expect_err("an unserved model should fail the probe");
+    let generate_err = gateway
+        .generate(meetings_summary_request())
+        .await
+        .expect_err("prose should be rejected");
+
+    shutdown_tx.send(()).expect("shutdown signal should send");
+    server_task.await.expect("server task should join");
+
+    assert!(
+        matches!(probe_err, LlmGatewayError::ProviderFailure(ref message) if message == "model_not_served model=llama3.2:3b"),
+        "expected probe failure, got {probe_err:?}"
+    );
+    assert!(
+        matches!(generate_err, LlmGatewayError::InvalidProviderPayload(ref message) if message == "content_not_json"),
+        "expected invalid payload error, got {generate_err:?}"
+    );
+    assert!(
+        state
+            .seen_authorizations
+            .lock()
+            .await
+            .iter()
+            .all(String::is_empty)
+    );
+}
+
+fn meetings_summary_request() -> LlmGatewayRequest {
+    LlmGatewayRequest::from_template(
+        template_for_capability(AssistantCapability::MeetingsSummary),
+        json!({
+            "calendar_day": "2026-02-15",
+            "meetings": [
+                {
+                    "title": "Team sync",
+                    "start_at": "2026-02-15T09:00:00Z"
+                }
+            ]
+        }),
+    )
+}
+
+fn config_for(base_url: String, model: &str) -> LocalLlmGatewayConfig {
+    LocalLlmGatewayConfig {
+        base_url,
+        api_key: None,
+        model: model.to_string(),
+        capabilities: vec![AssistantCapability::MeetingsSummary],
+        temperature: 0.0,
+        timeout_ms: 5_000,
+        max_retries: 0,
+        retry_base_backoff_ms: 0,
+        max_output_tokens: 180,
+    }
+}
+
+fn valid_output_json_string() -> String {
+    json!({
+        "version": "2026-02-15",
+        "output": {
+            "title": "Daily meetings",
+            "summary": "You have one meeting this morning.",
+            "key_points": ["Team sync at 9:00 AM"],
+            "follow_ups": ["Share release blockers before noon"]
+        }
+    })
+    .to_string()
+}
+
+fn success_response_body(content: &str) -> Value {
+    json!({
+        "id": "chatcmpl-local",
+        "object": "chat.completion",
+        "model": "llama3.2:3b",
+        "choices": [
+            {
+                "index": 0,
+                "message": { "role": "assistant", "content": content },
+                "finish_reason": "stop"
+            }
+        ],
+        "usage": {
+            "prompt_tokens": 12,
+            "completion_tokens": 8,
+            "total_tokens": 20
+        }
+    })
+}
+
+async fn spawn_test_server(
+    state: TestServerState,
+) -> (String, oneshot::Sender<()>, tokio::task::JoinHandle<()>) {
+    let app = Router::new()
+        .route("/v1/models", get(test_models_handler))
+        .route("/v1/chat/completions", post(test_chat_completions_handler))
+        .with_state(state);
+
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("listener should bind");
+    let local_addr = listener
+        .local_addr()
+        .expect("listener address should resolve");
+    let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+
+    let server_task = tokio::spawn(async move {
+        let server = axum::serve(listener, app).with_graceful_shutdown(async move {
+            let _ = shutdown_rx.await;
+        });
+
+        server.await.expect("test server should run");
+    });
+
+    (format!("http://{local_addr}/v1"), shutdown_tx, server_task)
+}
+
+async fn record_authorization(state: &TestServerState, headers: &HeaderMap) {
+    let authorization = headers
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    state.seen_authorizations.lock().await.push(authorization);
+}
+
+async fn test_models_handler(
+    State(state): State<TestServerState>,
+    headers: HeaderMap,
+) -> Json<Value> {
+    record_authorization(&state, &headers).await;
+    let data = state
+        .served_models
+        .iter()
+        .map(|id| json!({ "id": id, "object": "model", "owned_by": "library" }))
+        .collect::<Vec<_>>();
+    Json(json!({ "object": "list", "data": data }))
+}
+
+async fn test_chat_completions_handler(
+    State(state): State<TestServerState>,
+    headers: HeaderMap,
+    Json(payload): Json<Value>,
+) -> (StatusCode, Json<Value>) {
+    record_authorization(&state, &headers).await;
+    state.seen_payloads.lock().await.push(payload);
+
+    let reply = state.replies.lock().await.pop_front().unwrap_or(MockReply {
+        status: StatusCode::INTERNAL_SERVER_ERROR,
+        body: json!({ "error": { "type": "internal_error" } }),
+    });
+    (reply.status, Json(reply.body))
+}
@@ -0,0 +1,336 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::post;
+use axum::{Json, Router};
+use serde_json::{Value, json};
+use shared::llm::{
+    AssistantCapability, LlmGateway, LlmGatewayError, LlmGatewayRequest, LlmProviderRole,
+    LlmToolCall, LlmToolDefinition, OpenAiGateway, OpenAiGatewayConfig, template_for_capability,
+    validate_tool_calls,
+};
+use tokio::net::TcpListener;
+use tokio::sync::{Mutex, oneshot};
+
+#[derive(Debug, Clone)]
+struct MockReply {
+    status: StatusCode,
+    body: Value,
+}
+
+#[derive(Debug, Clone)]
+struct TestServerState {
+    replies: Arc<Mutex<VecDeque<MockReply>>>,
+    seen_payloads: Arc<Mutex<Vec<Value>>>,
+    seen_headers: Arc<Mutex<Vec<(String, String)>>>,
+}
+
+impl TestServerState {
+    fn with_replies(replies: Vec<MockReply>) -> Self {
+        Self {
+            replies: Arc::new(Mutex::new(VecDeque::from(replies))),
+            seen_payloads: Arc::new(Mutex::new(Vec::new())),
+            seen_headers: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+}
+
+#[tokio::test]
+async fn requests_strict_json_schema_and_extracts_usage() {
+    let state = TestServerState::with_replies(vec![MockReply {
+        status: StatusCode::OK,
+        body: success_response_body(
+            json!({ "content": valid_output_json_string(), "refusal": null }),
+        ),
+    }]);
+    let (url, shutdown_tx, server_task) = spawn_test_server(state.clone()).await;
+
+    let mut config = config_for(url, 0);
+    config.organization = Some("org-alfred".to_string());
+    let gateway = OpenAiGateway::new(config).expect("gateway should build");
+    let request = meetings_summary_request();
+    let system_prompt = request.system_prompt.clone();
+    let response = gateway
+        .generate(request)
+        .await
+        .expect("completion should succeed");
+
+    shutdown_tx.send(()).expect("shutdown signal should send");
+    server_task.await.expect("server task should join");
+
+    assert_eq!(response.model, "gpt-4o-mini-2024-07-18");
+    assert_eq!(response.provider_request_id.as_deref(), Some("req_header"));
+    assert_eq!(response.output["output"]["title"], "Daily meetings");
+    let usage = response.usage.expect("usage should be extracted");
+    assert_eq!(
+        (
+            usage.prompt_tokens,
+            usage.completion_tokens,
+            usage.total_tokens
+        ),
+        (12, 8, 20)
+    );
+
+    let payload = state.seen_payloads.lock().await[0].clone();
+    assert_eq!(payload["model"], "gpt-test-model");
+    assert_eq!(payload["messages"][0]["content"], system_prompt);
+    assert_eq!(payload["max_completion_tokens"], 600);
+    let format = &payload["response_format"];
+    assert_eq!(format["type"], "json_schema");
+    assert_eq!(format["json_schema"]["name"], "meetings_summary");
+    assert_eq!(format["json_schema"]["strict"], true);
+    let schema = &format["json_schema"]["schema"];
+    assert_eq!(
+        schema["properties"]["output"]["$ref"],
+        "#/$defs/MeetingsSummaryOutput"
+    );
+    assert_eq!(
+        schema["$defs"]["MeetingsSummaryOutput"]["additionalProperties"],
+        false
+    );
+    assert!(schema.get("$schema").is_none());
+
+    let seen_headers = state.seen_headers.lock().await.clone();
+    assert_eq!(
+        seen_headers,
+        vec![(
+            "Bearer test-openai-key".to_string(),
+            "org-alfred".to_string()
+        )]
+    );
+}
+
+#[tokio::test]
+async fn retries_server_errors_and_rejects_refusals() {
+    let state = TestServerState::with_replies(vec![
+        MockReply {
+            status: StatusCode::SERVICE_UNAVAILABLE,
+            body: json!({ "error": { "type": "server_error", "code": null } }),
+        },
+        MockReply {
+            status: StatusCode::OK,
+            body: success_response_body(json!({
+                "content": null,
+                "refusal": "I can't help with that."
+            })),
+        },
+        MockReply {
+            status: StatusCode::TOO_MANY_REQUESTS,
+            body: json!({ "error": { "type": "requests", "code": "rate_limit_exceeded" } }),
+        },
+    ]);
+    let (url, shutdown_tx, server_task) = spawn_test_server(state.clone()).await;
+
+    let gateway = OpenAiGateway::new(config_for(url, 1)).expect("gateway should build");
+    let refused = gateway
+        .generate(meetings_summary_request())
+        .await
+        .expect_err("refusals should not be returned as output");
+    let limited = gateway
+        .generate(meetings_summary_request())
+        .await
+        .expect_err("provider rate limits should surface after retries");
+
+    shutdown_tx.send(()).expect("shutdown signal should send");
+    server_task.await.expect("server task should join");
+
+    assert!(
+        matches!(refused, LlmGatewayError::InvalidProviderPayload(ref message) if message == "model_refused"),
+        "expected refusal error, got {refused:?}"
+    );
+    assert!(
+        matches!(limited, LlmGatewayError::ProviderFailure(ref message) if message.starts_with("status=")),
+        "expected provider failure, got {limited:?}"
+    );
+    assert_eq!(state.seen_payloads.lock().await.len(), 4);
+}
+
+#[tokio::test]
+async fn sends_tool_definitions_and_parses_tool_calls() {
+    let state = TestServerState::with_replies(vec![MockReply {
+        status: StatusCode::OK,
+        body: success_response_body(json!({
+            "content": null,
+            "refusal": null,
+            "tool_calls": [
+                {
+                    "id": "call_1",
+                    "type": "function",
+                    "function": {
+                        "name": "lookup_meeting",
+                        "arguments": "{\"title\":\"Team sync\"}"
+                    }
+                }
+            ]
+        })),
+    }]);
+    let (url, shutdown_tx, server_task) = spawn_test_server(state.clone()).await;
+
+    let gateway = OpenAiGateway::new(config_for(url, 0)).expect("gateway should build");
+    let response = gateway
+        .generate(meetings_summary_request().with_tools(vec![lookup_meeting_tool()]))
+        .await
+        .expect("tool call completion should succeed");
+
+    shutdown_tx.send(()).expect("shutdown signal should send");
+    server_task.await.expect("server task should join");
+
+    assert_eq!(response.output, Value::Null);
+    assert_eq!(
+        response.tool_calls,
+        vec![LlmToolCall {
+            id: Some("call_1".to_string()),
+            name: "lookup_meeting".to_string(),
+            arguments: json!({ "title": "Team sync" }),
+        }]
+    );
+    assert!(validate_tool_calls(&[lookup_meeting_tool()], &response.tool_calls).is_ok());
+
+    let payload = state.seen_payloads.lock().await[0].clone();
+    assert_eq!(payload["tools"][0]["type"], "function");
+    assert_eq!(payload["tools"][0]["function"]["name"], "lookup_meeting");
+    assert_eq!(
+        payload["tools"][0]["function"]["parameters"]["required"],
+        json!(["title"])
+    );
+}
+
+fn lookup_meeting_tool() -> LlmToolDefinition {
+    LlmToolDefinition {
+        name: "lookup_meeting".to_string(),
+        description: "Look up a meeting by title.".to_string(),
+        parameters: json!({
+            "type": "object",
+            "properties": { "title": { "type": "string" } },
+            "required": ["title"],
+            "additionalProperties": false
+        }),
+    }
+}
+
+fn meetings_summary_request() -> LlmGatewayRequest {
+    LlmGatewayRequest::from_template(
+        template_for_capability(AssistantCapability::MeetingsSummary),
+        json!({
+            "calendar_day": "2026-02-15",
+            "meetings": [
+                {
+                    "title": "Team sync",
+                    "start_at": "2026-02-15T09:00:00Z"
+                }
+            ]
+        }),
+    )
+}
+
+fn config_for(chat_completions_url: String, max_retries: u32) -> OpenAiGatewayConfig {
+    OpenAiGatewayConfig {
+        chat_completions_url,
+        api_key: "test-openai-key".to_string(),
+        organization: None,
+        role: LlmProviderRole::Fallback,
+        model: "gpt-test-model".to_string(),
+        temperature: 0.0,
+        timeout_ms: 5_000,
+        max_retries,
+        retry_base_backoff_ms: 0,
+        max_output_tokens: 600,
+        allow_insecure_http: true,
+    }
+}
+
+fn valid_output_json_string() -> String {
+    json!({
+        "version": "2026-02-15",
+        "output": {
+            "title": "Daily meetings",
+            "summary": "You have one meeting this morning.",
+            "key_points": ["Team sync at 9:00 AM"],
+            "follow_ups": ["Share release blockers before noon"]
+        }
+    })
+    .to_string()
+}
+
+fn success_response_body(message: Value) -> Value {
+    json!({
+        "id": "chatcmpl-body",
+        "object": "chat.completion",
+        "model": "gpt-4o-mini-2024-07-18",
+        "choices": [
+            {
+                "index": 0,
+                "message": message,
+                "finish_reason": "stop"
+            }
+        ],
+        "usage": {
+            "prompt_tokens": 12,
+            "completion_tokens": 8,
+            "total_tokens": 20
+        }
+    })
+}
+
+async fn spawn_test_server(
+    state: TestServerState,
+) -> (String, oneshot::Sender<()>, tokio::task::JoinHandle<()>) {
+    let app = Router::new()
+        .route("/v1/chat/completions", post(test_chat_completions_handler))
+        .with_state(state);
+
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("listener should bind");
+    let local_addr = listener
+        .local_addr()
+        .expect("listener address should resolve");
+    let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+
+    let server_task = tokio::spawn(async move {
+        let server = axum::serve(listener, app).with_graceful_shutdown(async move {
+            let _ = shutdown_rx.await;
+        });
+
+        server.await.expect("test server should run");
+    });
+
+    (
+        format!("http://{local_addr}/v1/chat/completions"),
+        shutdown_tx,
+        server_task,
+    )
+}
+
+async fn test_chat_completions_handler(
+    State(state): State<TestServerState>,
+    headers: HeaderMap,
+    Json(payload): Json<Value>,
+) -> (StatusCode, HeaderMap, Json<Value>) {
+    state.seen_payloads.lock().await.push(payload);
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+            .to_string()
+    };
+    state
+        .seen_headers
+        .lock()
+        .await
+        .push((header("authorization"), header("openai-organization")));
+
+    let reply = state.replies.lock().await.pop_front().unwrap_or(MockReply {
+        status: StatusCode::INTERNAL_SERVER_ERROR,
+        body: json!({ "error": { "type": "server_error", "code": null } }),
+    });
+    let mut response_headers = HeaderMap::new();
+    response_headers.insert(
+        "x-request-id",
+        "req_header".parse().expect("header value should parse"),
+    );
+    (reply.status, response_headers, Json(reply.body))
+}
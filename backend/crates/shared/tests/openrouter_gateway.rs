@@ -1,5 +1,6 @@
 use std::collections::VecDeque;
 use std::sync::Arc;
+use std::time::Duration;
 
 use axum::extract::State;
 use axum::http::{HeaderMap, StatusCode, header::AUTHORIZATION};
@@ -10,6 +11,9 @@ use shared::llm::{
     AssistantCapability, LlmGateway, LlmGatewayError, LlmGatewayRequest, OpenRouterGateway,
     OpenRouterGatewayConfig, OpenRouterModelRoute, template_for_capability,
 };
+use shared::outbound_rate_limit::{
+    OutboundCallLimiter, OutboundProviderLimit, OutboundRateLimitConfig,
+};
 use tokio::net::TcpListener;
 use tokio::sync::{Mutex, oneshot};
 
@@ -26,6 +30,7 @@ struct TestServerState {
     seen_auth_headers: Arc<Mutex<Vec<String>>>,
     seen_referer_headers: Arc<Mutex<Vec<String>>>,
     seen_title_headers: Arc<Mutex<Vec<String>>>,
+    seen_max_tokens: Arc<Mutex<Vec<u64>>>,
 }
 
 impl TestServerState {
@@ -36,6 +41,7 @@ impl TestServerState {
             seen_auth_headers: Arc::new(Mutex::new(Vec::new())),
             seen_referer_headers: Arc::new(Mutex::new(Vec::new())),
             seen_title_headers: Arc::new(Mutex::new(Vec::new())),
+            seen_max_tokens: Arc::new(Mutex::new(Vec::new())),
         }
     }
 }
@@ -201,6 +207,90 @@ async fn falls_back_when_primary_returns_invalid_json_payload() {
     );
 }
 
+#[tokio::test]
+async fn warm_up_sends_single_token_request_and_falls_back_to_secondary_model() {
+    let state = TestServerState::with_replies(vec![
+        provider_error_reply(StatusCode::SERVICE_UNAVAILABLE, "capacity"),
+        MockReply {
+            status: StatusCode::OK,
+            body: success_response_body("fallback-model", Value::String("p".to_string())),
+        },
+    ]);
+    let (url, shutdown_tx, server_task) = spawn_test_server(state.clone()).await;
+
+    let gateway = OpenRouterGateway::new(config_for(url, 2, 0)).expect("gateway should build");
+    gateway
+        .warm_up()
+        .await
+        .expect("warm-up should succeed on the fallback model");
+
+    shutdown_tx.send(()).expect("shutdown signal should send");
+    server_task.await.expect("server task should join");
+
+    let seen_models = state.seen_models.lock().await.clone();
+    assert_eq!(
+        seen_models,
+        vec!["primary-model".to_string(), "fallback-model".to_string()],
+        "warm-up should not retry a model before falling back"
+    );
+    let seen_max_tokens = state.seen_max_tokens.lock().await.clone();
+    assert_eq!(seen_max_tokens, vec![1, 1]);
+}
+
+#[tokio::test]
+async fn outbound_limit_rejects_without_calling_provider_or_falling_back() {
+    let state = TestServerState::with_replies(vec![MockReply {
+        status: StatusCode::OK,
+        body: success_response_body("provider-model", valid_output_json_string()),
+    }]);
+    let (url, shutdown_tx, server_task) = spawn_test_server(state.clone()).await;
+
+    let limiter = OutboundCallLimiter::new(&OutboundRateLimitConfig {
+        google: OutboundProviderLimit {
+            calls_per_second: 0,
+            burst: 0,
+        },
+        openrouter: OutboundProviderLimit {
+            calls_per_second: 1,
+            burst: 1,
+        },
+        anthropic: OutboundProviderLimit {
+            calls_per_second: 0,
+            burst: 0,
+        },
+        openai: OutboundProviderLimit {
+            calls_per_second: 0,
+            burst: 0,
+        },
+        bedrock: OutboundProviderLimit {
+            calls_per_second: 0,
+            burst: 0,
+        },
+        max_wait: Duration::ZERO,
+    });
+    let gateway = OpenRouterGateway::new(config_for(url, 2, 0))
+        .expect("gateway should build")
+        .with_outbound_limiter(limiter);
+    gateway
+        .generate(meetings_summary_request())
+        .await
+        .expect("first call should fit the burst");
+    let err = gateway
+        .generate(meetings_summary_request())
+        .await
+        .expect_err("second call should be rate limited");
+
+    shutdown_tx.send(()).expect("shutdown signal should send");
+    server_task.await.expect("server task should join");
+
+    assert!(
+        matches!(err, LlmGatewayError::RateLimited(ref message) if message.contains("retry_after_ms=")),
+        "expected outbound rate limit error, got {err:?}"
+    );
+    let seen_models = state.seen_models.lock().await.clone();
+    assert_eq!(seen_models, vec!["primary-model".to_string()]);
+}
+
 fn meetings_summary_request() -> LlmGatewayRequest {
     LlmGatewayRequest::from_template(
         template_for_capability(AssistantCapability::MeetingsSummary),
@@ -234,6 +324,7 @@ fn config_for(
         model_route: OpenRouterModelRoute {
             primary_model: "primary-model".to_string(),
             fallback_model: Some("fallback-model".to_string()),
+            temperature: 0.0,
         },
     }
 }
@@ -321,6 +412,9 @@ async fn test_chat_completions_handler(
     if let Some(model) = payload.get("model").and_then(Value::as_str) {
         state.seen_models.lock().await.push(model.to_string());
     }
+    if let Some(max_tokens) = payload.get("max_tokens").and_then(Value::as_u64) {
+        state.seen_max_tokens.lock().await.push(max_tokens);
+    }
 
     if let Some(value) = headers
         .get(AUTHORIZATION)
@@ -0,0 +1,95 @@
+use chrono::{SecondsFormat, Utc};
+use shared::config::WorkerConfig;
+use shared::models::AuditMetadata;
+use shared::repos::{AuditPurgeSummary, AuditResult, Store};
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+const AUDIT_RETENTION_PURGED_EVENT: &str = "AUDIT_RETENTION_PURGED";
+
+pub(crate) async fn purge_expired_audit_events(
+    store: &Store,
+    config: &WorkerConfig,
+    worker_id: Uuid,
+) -> u64 {
+    let summaries = match store
+        .purge_expired_audit_events_batch(
+            &config.audit_retention_policy,
+            Utc::now(),
+            i64::from(config.audit_purge_batch_size),
+        )
+        .await
+    {
+        Ok(summaries) => summaries,
+        Err(err) => {
+            error!(worker_id = %worker_id, "failed to purge expired audit events: {err}");
+            return 0;
+        }
+    };
+
+    let purged_rows = summaries
+        .iter()
+        .map(|summary| summary.purged_count)
+        .sum::<u64>();
+    if purged_rows == 0 {
+        debug!(
+            worker_id = %worker_id,
+            batch_size = config.audit_purge_batch_size,
+            "audit retention purge tick found no expired rows"
+        );
+        return 0;
+    }
+
+    for summary in &summaries {
+        record_purge_summary(store, worker_id, summary).await;
+    }
+
+    info!(
+        worker_id = %worker_id,
+        purged_rows,
+        affected_users = summaries.len(),
+        batch_size = config.audit_purge_batch_size,
+        default_retention_days = config.audit_retention_policy.default_days(),
+        "audit retention purge tick"
+    );
+
+    purged_rows
+}
+
+/// Retention is itself audited: each affected user gets an event describing what was removed.
+async fn record_purge_summary(store: &Store, worker_id: Uuid, summary: &AuditPurgeSummary) {
+    let mut metadata = AuditMetadata::new();
+    metadata.insert("purged_count".to_string(), summary.purged_count.into());
+    metadata.insert(
+        "oldest_purged_at".to_string(),
+        summary
+            .oldest_created_at
+            .to_rfc3339_opts(SecondsFormat::Secs, true)
+            .into(),
+    );
+    metadata.insert(
+        "newest_purged_at".to_string(),
+        summary
+            .newest_created_at
+            .to_rfc3339_opts(SecondsFormat::Secs, true)
+            .into(),
+    );
+
+    if let Err(err) = store
+        .add_audit_event(
+            summary.user_id,
+            AUDIT_RETENTION_PURGED_EVENT,
+            None,
+            AuditResult::Success,
+            &metadata,
+        )
+        .await
+    {
+        warn!(
+            worker_id = %worker_id,
+            user_id = %summary.user_id,
+            purged_count = summary.purged_count,
+            "failed to record audit retention summary: {err}"
+        );
+    }
+}
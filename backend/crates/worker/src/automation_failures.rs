@@ -0,0 +1,146 @@
+use shared::models::AuditMetadata;
+use shared::repos::{AuditResult, AutomationRunFailure, ClaimedJob, JobType, Store};
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::automation_runs::AutomationRunJobPayload;
+use crate::{NotificationContent, PushSender, device_health};
+
+/// Job error code for runs that failed because the user's Google connector is unusable.
+pub(crate) const AUTOMATION_CONNECTOR_UNAVAILABLE: &str = "AUTOMATION_CONNECTOR_UNAVAILABLE";
+
+/// Job error code for runs stopped after `WORKER_AUTOMATION_MAX_RUNTIME_SECONDS`. Runs are
+/// only stopped before delivery, so the failure is transient and the job is retried.
+pub(crate) const AUTOMATION_RUN_TIMED_OUT: &str = "AUTOMATION_RUN_TIMED_OUT";
+
+const AUTOMATION_AUTO_PAUSED_EVENT: &str = "AUTOMATION_AUTO_PAUSED";
+
+/// Records a dead-lettered automation run with its error code. When the rule's failure streak
+/// reaches `pause_threshold` the store pauses the rule, and the user gets a single push.
+pub(crate) async fn record_automation_run_failure(
+    store: &Store,
+    push_sender: &PushSender,
+    pause_threshold: u32,
+    job: &ClaimedJob,
+    error_code: &str,
+) {
+    if !matches!(job.job_type, JobType::AutomationRun) {
+        return;
+    }
+
+    let Ok(payload) = AutomationRunJobPayload::parse(job.payload_ciphertext.as_deref()) else {
+        return;
+    };
+
+    let failure = match store
+        .record_automation_run_failure(
+            payload.automation_run_id,
+            job.user_id,
+            error_code,
+            i64::from(pause_threshold),
+        )
+        .await
+    {
+        Ok(Some(failure)) => failure,
+        Ok(None) => {
+            warn!(
+                job_id = %job.id,
+                run_id = %payload.automation_run_id,
+                "automation run state update skipped"
+            );
+            return;
+        }
+        Err(err) => {
+            error!(
+                job_id = %job.id,
+                run_id = %payload.automation_run_id,
+                "failed to mark automation run failed: {err}"
+            );
+            return;
+        }
+    };
+
+    if failure.paused_rule {
+        info!(
+            job_id = %job.id,
+            rule_id = %failure.rule_id,
+            consecutive_failures = failure.consecutive_failures,
+            error_code,
+            "automation paused after consecutive failed runs"
+        );
+        notify_automation_paused(store, push_sender, job.user_id, &failure, error_code).await;
+    }
+}
+
+async fn notify_automation_paused(
+    store: &Store,
+    push_sender: &PushSender,
+    user_id: Uuid,
+    failure: &AutomationRunFailure,
+    error_code: &str,
+) {
+    let devices = match store.list_registered_devices(user_id).await {
+        Ok(devices) => devices,
+        Err(err) => {
+            warn!(
+                rule_id = %failure.rule_id,
+                "failed to load devices for automation pause alert: {err}"
+            );
+            Vec::new()
+        }
+    };
+
+    let content =
+        NotificationContent::automation_paused(error_code == AUTOMATION_CONNECTOR_UNAVAILABLE);
+    let mut delivered_devices = 0_u64;
+    for device in &devices {
+        match push_sender.send(device, &content).await {
+            Ok(_) => {
+                delivered_devices += 1;
+                if let Err(err) = store
+                    .record_device_delivery(user_id, &device.device_id)
+                    .await
+                {
+                    warn!(
+                        device_id = %device.device_id,
+                        "failed to record device delivery: {err}"
+                    );
+                }
+            }
+            Err(err) => {
+                device_health::record_push_failure(store, user_id, &device.device_id, &err).await;
+                let err = err.to_job_error();
+                warn!(
+                    rule_id = %failure.rule_id,
+                    device_id = %device.device_id,
+                    error_code = %err.code,
+                    "automation pause alert delivery failed"
+                );
+            }
+        }
+    }
+
+    let mut metadata = AuditMetadata::new();
+    metadata.insert("rule_id".to_string(), failure.rule_id.to_string().into());
+    metadata.insert("failure_code".to_string(), error_code.into());
+    metadata.insert(
+        "consecutive_failures".to_string(),
+        failure.consecutive_failures.into(),
+    );
+    metadata.insert("delivered_devices".to_string(), delivered_devices.into());
+    if let Err(err) = store
+        .add_audit_event(
+            user_id,
+            AUTOMATION_AUTO_PAUSED_EVENT,
+            None,
+            AuditResult::Failure,
+            &metadata,
+        )
+        .await
+    {
+        warn!(
+            rule_id = %failure.rule_id,
+            "failed to record automation pause audit event: {err}"
+        );
+    }
+}
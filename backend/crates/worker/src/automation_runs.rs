@@ -1,7 +1,9 @@
 use base64::{Engine as _, engine::general_purpose::STANDARD};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use shared::automation_schedule::next_run_after;
+use shared::automation_schedule::{
+    AutomationCondition, AutomationScheduleType, AutomationTemplate, next_run_after,
+};
 use shared::config::WorkerConfig;
 use shared::repos::{JobType, Store};
 use tracing::{error, info, warn};
@@ -14,6 +16,13 @@ pub(crate) struct AutomationRunJobPayload {
     pub(crate) scheduled_for: DateTime<Utc>,
     pub(crate) prompt_sha256: String,
     pub(crate) prompt_envelope_ciphertext_b64: String,
+    #[serde(default)]
+    pub(crate) template: Option<AutomationTemplate>,
+    #[serde(default)]
+    pub(crate) time_zone: Option<String>,
+    /// Only scheduled runs carry the rule's condition; manual runs always notify.
+    #[serde(default)]
+    pub(crate) condition: Option<AutomationCondition>,
 }
 
 impl AutomationRunJobPayload {
@@ -29,6 +38,83 @@ pub(crate) struct AutomationSchedulerMetrics {
     pub(crate) materialized_runs: usize,
     pub(crate) enqueued_runs: usize,
     pub(crate) failed_runs: usize,
+    /// Due rules held back because an earlier run of the same rule was still in flight.
+    pub(crate) deferred_overlapping_runs: usize,
+}
+
+/// Reactivates rules whose snooze window has ended so the scheduler picks them up again.
+pub(crate) async fn resume_snoozed_automation_rules(
+    store: &Store,
+    config: &WorkerConfig,
+    worker_id: Uuid,
+) -> usize {
+    let now = Utc::now();
+    let rules = match store
+        .list_due_automation_snoozes(now, i64::from(config.batch_size))
+        .await
+    {
+        Ok(rules) => rules,
+        Err(err) => {
+            error!(worker_id = %worker_id, "failed to list snoozed automation rules: {err}");
+            return 0;
+        }
+    };
+
+    let mut resumed_rules = 0;
+    for rule in rules {
+        let Some(paused_until) = rule.paused_until else {
+            continue;
+        };
+        let schedule = match rule.schedule_spec() {
+            Ok(schedule) => schedule,
+            Err(err) => {
+                error!(
+                    worker_id = %worker_id,
+                    rule_id = %rule.id,
+                    "failed to reconstruct schedule for snoozed rule: {err}"
+                );
+                continue;
+            }
+        };
+        // A one-shot run that fell inside the snooze window fires as soon as the window ends.
+        let next_run_at = match next_run_after(now, &schedule) {
+            Some(next_run_at) => next_run_at,
+            None if schedule.schedule_type == AutomationScheduleType::Once => now,
+            None => {
+                error!(
+                    worker_id = %worker_id,
+                    rule_id = %rule.id,
+                    "failed to compute next scheduled run for snoozed rule"
+                );
+                continue;
+            }
+        };
+
+        match store
+            .resume_snoozed_automation_rule(rule.id, paused_until, next_run_at)
+            .await
+        {
+            Ok(true) => resumed_rules += 1,
+            Ok(false) => {}
+            Err(err) => {
+                error!(
+                    worker_id = %worker_id,
+                    rule_id = %rule.id,
+                    "failed to resume snoozed automation rule: {err}"
+                );
+            }
+        }
+    }
+
+    if resumed_rules > 0 {
+        info!(
+            worker_id = %worker_id,
+            resumed_automation_rules = resumed_rules,
+            "resumed snoozed automation rules"
+        );
+    }
+
+    resumed_rules
 }
 
 pub(crate) async fn enqueue_due_automation_runs(
@@ -56,6 +142,39 @@ pub(crate) async fn enqueue_due_automation_runs(
     metrics.claimed_rules = claimed_rules.len();
 
     for rule in claimed_rules {
+        // Only one run per rule is in flight at a time; the due run waits for the next tick.
+        match store.automation_rule_has_in_flight_run(rule.id).await {
+            Ok(false) => {}
+            Ok(true) => {
+                metrics.deferred_overlapping_runs += 1;
+                info!(
+                    worker_id = %worker_id,
+                    rule_id = %rule.id,
+                    "automation run deferred because an earlier run is still in flight"
+                );
+                if let Err(err) = store
+                    .release_automation_rule_lease(rule.id, worker_id)
+                    .await
+                {
+                    warn!(
+                        worker_id = %worker_id,
+                        rule_id = %rule.id,
+                        "failed to release deferred automation rule: {err}"
+                    );
+                }
+                continue;
+            }
+            Err(err) => {
+                metrics.failed_runs += 1;
+                error!(
+                    worker_id = %worker_id,
+                    rule_id = %rule.id,
+                    "failed to check for in-flight automation runs: {err}"
+                );
+                continue;
+            }
+        }
+
         let scheduled_for = rule.next_run_at;
         let schedule = match rule.schedule_spec() {
             Ok(schedule) => schedule,
@@ -69,14 +188,20 @@ pub(crate) async fn enqueue_due_automation_runs(
                 continue;
             }
         };
-        let Some(next_run_at) = next_run_after(scheduled_for, &schedule) else {
-            metrics.failed_runs += 1;
-            error!(
-                worker_id = %worker_id,
-                rule_id = %rule.id,
-                "failed to compute next scheduled run for claimed rule"
-            );
-            continue;
+        // A one-shot rule has nothing after this run; materializing it completes the rule.
+        let next_run_at = if schedule.schedule_type == AutomationScheduleType::Once {
+            None
+        } else {
+            let Some(next_run_at) = next_run_after(scheduled_for, &schedule) else {
+                metrics.failed_runs += 1;
+                error!(
+                    worker_id = %worker_id,
+                    rule_id = %rule.id,
+                    "failed to compute next scheduled run for claimed rule"
+                );
+                continue;
+            };
+            Some(next_run_at)
         };
         let idempotency_key = format!("{}:{}", rule.id, scheduled_for.timestamp_micros());
 
@@ -117,6 +242,9 @@ pub(crate) async fn enqueue_due_automation_runs(
             scheduled_for,
             prompt_sha256: rule.prompt_sha256,
             prompt_envelope_ciphertext_b64: STANDARD.encode(rule.prompt_ciphertext),
+            template: rule.template,
+            time_zone: Some(rule.time_zone),
+            condition: rule.condition,
         };
         let payload_json = match serde_json::to_vec(&payload) {
             Ok(payload_json) => payload_json,
@@ -189,6 +317,7 @@ pub(crate) async fn enqueue_due_automation_runs(
         materialized_automation_runs = metrics.materialized_runs,
         enqueued_automation_runs = metrics.enqueued_runs,
         failed_automation_runs = metrics.failed_runs,
+        deferred_overlapping_automation_runs = metrics.deferred_overlapping_runs,
         "automation scheduler metrics"
     );
 
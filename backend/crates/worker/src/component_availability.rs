@@ -0,0 +1,54 @@
+use chrono::{Duration, Utc};
+use shared::models::AvailabilityComponent;
+use shared::repos::Store;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::WorkerTickMetrics;
+
+/// Hourly availability buckets older than this are dropped; the public status page shows a week.
+const AVAILABILITY_RETENTION_DAYS: i64 = 30;
+
+/// Folds one tick's push and automation outcomes into the public availability history.
+pub(crate) async fn record_tick_availability(
+    store: &Store,
+    metrics: &WorkerTickMetrics,
+    worker_id: Uuid,
+) {
+    for (component, attempts, successes) in [
+        (
+            AvailabilityComponent::PushDelivery,
+            metrics.push_attempts,
+            metrics.push_delivered,
+        ),
+        (
+            AvailabilityComponent::Automations,
+            metrics.automation_attempts,
+            metrics.automation_successes,
+        ),
+    ] {
+        if attempts == 0 {
+            continue;
+        }
+
+        if let Err(err) = store
+            .record_component_availability(
+                component,
+                i64::try_from(attempts).unwrap_or(i64::MAX),
+                i64::try_from(successes).unwrap_or(i64::MAX),
+            )
+            .await
+        {
+            warn!(
+                worker_id = %worker_id,
+                component = component.as_str(),
+                "failed to record component availability: {err}"
+            );
+        }
+    }
+
+    let cutoff = Utc::now() - Duration::days(AVAILABILITY_RETENTION_DAYS);
+    if let Err(err) = store.prune_component_availability(cutoff).await {
+        warn!(worker_id = %worker_id, "failed to prune component availability: {err}");
+    }
+}
@@ -0,0 +1,167 @@
+use chrono::{Duration, Utc};
+use serde_json::json;
+use shared::config::WorkerConfig;
+use shared::models::AuditMetadata;
+use shared::repos::{AuditResult, ConnectorReauthNudge, Store};
+use shared::webhooks::WebhookEventType;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::webhook_delivery::enqueue_webhook_event;
+use crate::{NotificationContent, PushSender, device_health};
+
+const CONNECTOR_REAUTH_NUDGE_EVENT: &str = "CONNECTOR_REAUTH_NUDGE_SENT";
+
+pub(crate) async fn send_connector_reauth_nudges(
+    store: &Store,
+    config: &WorkerConfig,
+    push_sender: &PushSender,
+    worker_id: Uuid,
+) -> usize {
+    let now = Utc::now();
+    let interval_hours =
+        i64::try_from(config.connector_reauth_nudge_interval_hours).unwrap_or(i64::MAX);
+    let nudged_before = now - Duration::try_hours(interval_hours).unwrap_or(Duration::MAX);
+    let nudges = match store
+        .claim_connector_reauth_nudges(
+            config.connector_health_nudge_threshold,
+            now,
+            nudged_before,
+            i64::from(config.batch_size),
+        )
+        .await
+    {
+        Ok(nudges) => nudges,
+        Err(err) => {
+            error!(worker_id = %worker_id, "failed to claim connector reauth nudges: {err}");
+            return 0;
+        }
+    };
+
+    if nudges.is_empty() {
+        debug!(worker_id = %worker_id, "no connectors need a reauth nudge");
+        return 0;
+    }
+
+    for nudge in &nudges {
+        send_nudge(store, push_sender, worker_id, nudge).await;
+    }
+
+    info!(
+        worker_id = %worker_id,
+        nudged_connectors = nudges.len(),
+        threshold = config.connector_health_nudge_threshold,
+        interval_hours = config.connector_reauth_nudge_interval_hours,
+        "connector reauth nudge tick"
+    );
+
+    nudges.len()
+}
+
+/// The nudge is already claimed, so a failed delivery waits for the next interval rather than
+/// retrying every tick.
+async fn send_nudge(
+    store: &Store,
+    push_sender: &PushSender,
+    worker_id: Uuid,
+    nudge: &ConnectorReauthNudge,
+) {
+    let devices = match store.list_registered_devices(nudge.user_id).await {
+        Ok(devices) => devices,
+        Err(err) => {
+            warn!(
+                worker_id = %worker_id,
+                connector_id = %nudge.connector_id,
+                "failed to load devices for reauth nudge: {err}"
+            );
+            Vec::new()
+        }
+    };
+
+    let content = NotificationContent::google_reauth_nudge();
+    let mut delivered_devices = 0_u64;
+    for device in &devices {
+        match push_sender.send(device, &content).await {
+            Ok(_) => {
+                delivered_devices += 1;
+                if let Err(err) = store
+                    .record_device_delivery(nudge.user_id, &device.device_id)
+                    .await
+                {
+                    warn!(
+                        worker_id = %worker_id,
+                        device_id = %device.device_id,
+                        "failed to record device delivery: {err}"
+                    );
+                }
+            }
+            Err(err) => {
+                device_health::record_push_failure(store, nudge.user_id, &device.device_id, &err)
+                    .await;
+                let err = err.to_job_error();
+                warn!(
+                    worker_id = %worker_id,
+                    connector_id = %nudge.connector_id,
+                    device_id = %device.device_id,
+                    error_code = %err.code,
+                    "reauth nudge delivery failed"
+                );
+            }
+        }
+    }
+
+    enqueue_webhook_event(
+        store,
+        nudge.user_id,
+        WebhookEventType::ConnectorNeedsReauth,
+        json!({
+            "connector_id": nudge.connector_id,
+            "provider": nudge.provider,
+            "health_score": nudge.health_score,
+            "missing_scopes": nudge.missing_scopes,
+        }),
+    )
+    .await;
+
+    let mut metadata = AuditMetadata::new();
+    metadata.insert(
+        "connector_id".to_string(),
+        nudge.connector_id.to_string().into(),
+    );
+    metadata.insert("health_score".to_string(), nudge.health_score.into());
+    metadata.insert(
+        "refresh_failure_count".to_string(),
+        nudge.refresh_failure_count.into(),
+    );
+    metadata.insert(
+        "provider_unauthorized_count".to_string(),
+        nudge.provider_unauthorized_count.into(),
+    );
+    metadata.insert(
+        "missing_scopes".to_string(),
+        nudge.missing_scopes.clone().into(),
+    );
+    metadata.insert("delivered_devices".to_string(), delivered_devices.into());
+
+    let result = if delivered_devices > 0 {
+        AuditResult::Success
+    } else {
+        AuditResult::Failure
+    };
+    if let Err(err) = store
+        .add_audit_event(
+            nudge.user_id,
+            CONNECTOR_REAUTH_NUDGE_EVENT,
+            Some(nudge.provider.as_str()),
+            result,
+            &metadata,
+        )
+        .await
+    {
+        warn!(
+            worker_id = %worker_id,
+            connector_id = %nudge.connector_id,
+            "failed to record connector reauth nudge: {err}"
+        );
+    }
+}
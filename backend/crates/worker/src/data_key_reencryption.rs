@@ -0,0 +1,43 @@
+use shared::config::WorkerConfig;
+use shared::repos::Store;
+use tracing::{error, info};
+use uuid::Uuid;
+
+pub(crate) async fn reencrypt_secondary_key_rows(
+    store: &Store,
+    config: &WorkerConfig,
+    worker_id: Uuid,
+) -> u64 {
+    let counts = match store
+        .reencrypt_secondary_key_rows(i64::from(config.data_key_reencrypt_batch_size))
+        .await
+    {
+        Ok(counts) => counts,
+        Err(err) => {
+            error!(
+                worker_id = %worker_id,
+                "failed to re-encrypt rows from secondary data encryption key: {err}"
+            );
+            return 0;
+        }
+    };
+
+    let total = counts.total();
+    if total > 0 {
+        info!(
+            worker_id = %worker_id,
+            reencrypted_rows = total,
+            devices = counts.devices,
+            connectors = counts.connectors,
+            jobs = counts.jobs,
+            dead_letter_jobs = counts.dead_letter_jobs,
+            automation_rules = counts.automation_rules,
+            departure_alert_preferences = counts.departure_alert_preferences,
+            webhook_subscriptions = counts.webhook_subscriptions,
+            batch_size = config.data_key_reencrypt_batch_size,
+            "data encryption key re-encryption tick"
+        );
+    }
+
+    total
+}
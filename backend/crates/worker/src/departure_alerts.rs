@@ -0,0 +1,134 @@
+use chrono::{NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use shared::config::WorkerConfig;
+use shared::repos::{JobType, Store};
+use shared::timezone::user_local_date;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct DepartureAlertJobPayload {
+    /// Local day the check belongs to; stale checks from a previous day are skipped.
+    pub(crate) local_date: NaiveDate,
+}
+
+impl DepartureAlertJobPayload {
+    pub(crate) fn parse(payload: Option<&[u8]>) -> Result<Self, &'static str> {
+        let payload = payload.ok_or("departure alert payload is required")?;
+        serde_json::from_slice(payload).map_err(|_| "departure alert payload must be valid JSON")
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct DepartureSchedulerMetrics {
+    pub(crate) due_checks: usize,
+    pub(crate) enqueued_checks: usize,
+    pub(crate) failed_checks: usize,
+}
+
+pub(crate) async fn enqueue_due_departure_checks(
+    store: &Store,
+    config: &WorkerConfig,
+    worker_id: Uuid,
+) -> DepartureSchedulerMetrics {
+    let mut metrics = DepartureSchedulerMetrics::default();
+    let now = Utc::now();
+    let due_checks = match store
+        .list_due_departure_checks(now, i64::from(config.batch_size))
+        .await
+    {
+        Ok(checks) => checks,
+        Err(err) => {
+            error!(worker_id = %worker_id, "failed to list due departure checks: {err}");
+            return metrics;
+        }
+    };
+    metrics.due_checks = due_checks.len();
+
+    for check in due_checks {
+        let Some(next_check_at) = check
+            .settings
+            .next_daily_check_after(check.next_check_at.max(now))
+        else {
+            metrics.failed_checks += 1;
+            error!(
+                worker_id = %worker_id,
+                user_id = %check.user_id,
+                "failed to compute next daily departure check"
+            );
+            continue;
+        };
+
+        match store
+            .advance_departure_check(check.user_id, check.next_check_at, next_check_at)
+            .await
+        {
+            Ok(true) => {}
+            Ok(false) => {
+                warn!(
+                    worker_id = %worker_id,
+                    user_id = %check.user_id,
+                    "departure check skipped because another worker advanced it"
+                );
+                continue;
+            }
+            Err(err) => {
+                metrics.failed_checks += 1;
+                error!(
+                    worker_id = %worker_id,
+                    user_id = %check.user_id,
+                    "failed to advance departure check: {err}"
+                );
+                continue;
+            }
+        }
+
+        let payload = DepartureAlertJobPayload {
+            local_date: user_local_date(now, check.settings.time_zone.as_str()),
+        };
+        let payload_json = match serde_json::to_vec(&payload) {
+            Ok(payload_json) => payload_json,
+            Err(err) => {
+                metrics.failed_checks += 1;
+                error!(
+                    worker_id = %worker_id,
+                    user_id = %check.user_id,
+                    "failed to serialize departure alert payload: {err}"
+                );
+                continue;
+            }
+        };
+        let idempotency_key = format!("daily:{}", check.next_check_at.timestamp_micros());
+
+        match store
+            .enqueue_job_with_idempotency_key(
+                check.user_id,
+                JobType::DepartureAlert,
+                now,
+                Some(&payload_json),
+                &idempotency_key,
+            )
+            .await
+        {
+            Ok(_) => metrics.enqueued_checks += 1,
+            Err(err) => {
+                metrics.failed_checks += 1;
+                error!(
+                    worker_id = %worker_id,
+                    user_id = %check.user_id,
+                    "failed to enqueue departure alert job: {err}"
+                );
+            }
+        }
+    }
+
+    info!(
+        worker_id = %worker_id,
+        due_departure_checks = metrics.due_checks,
+        enqueued_departure_checks = metrics.enqueued_checks,
+        failed_departure_checks = metrics.failed_checks,
+        "departure scheduler metrics"
+    );
+
+    metrics
+}
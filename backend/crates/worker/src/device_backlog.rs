@@ -0,0 +1,107 @@
+use chrono::{DateTime, Utc};
+use shared::repos::{DeviceRegistration, Store};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::{NotificationContent, PushSender};
+
+/// Whether a notification is backlog for this device: it became due before the device was
+/// first registered and is older than `max_age_seconds`. A zero max age disables the policy.
+pub(crate) fn is_backlog_for_device(
+    due_at: DateTime<Utc>,
+    registered_at: DateTime<Utc>,
+    now: DateTime<Utc>,
+    max_age_seconds: u64,
+) -> bool {
+    if max_age_seconds == 0 || due_at >= registered_at {
+        return false;
+    }
+
+    let age_seconds = (now - due_at).num_seconds();
+    age_seconds > i64::try_from(max_age_seconds).unwrap_or(i64::MAX)
+}
+
+/// Sends the single "you're all set" notice that stands in for a new device's backlog.
+/// Returns true when the summary was delivered by this call.
+pub(crate) async fn deliver_backlog_summary(
+    store: &Store,
+    push_sender: &PushSender,
+    user_id: Uuid,
+    device: &DeviceRegistration,
+) -> bool {
+    match store
+        .claim_device_backlog_summary(user_id, &device.device_id)
+        .await
+    {
+        Ok(true) => {}
+        Ok(false) => return false,
+        Err(err) => {
+            warn!(
+                user_id = %user_id,
+                device_id = %device.device_id,
+                "failed to claim device backlog summary: {err}"
+            );
+            return false;
+        }
+    }
+
+    if let Err(err) = push_sender
+        .send(device, &NotificationContent::backlog_summary())
+        .await
+    {
+        warn!(
+            user_id = %user_id,
+            device_id = %device.device_id,
+            error_code = %err.code(),
+            "device backlog summary delivery failed"
+        );
+        return false;
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+
+    use super::is_backlog_for_device;
+
+    #[test]
+    fn old_notifications_queued_before_registration_are_backlog() {
+        let now = Utc::now();
+        let registered_at = now - Duration::minutes(1);
+
+        assert!(is_backlog_for_device(
+            now - Duration::hours(2),
+            registered_at,
+            now,
+            900
+        ));
+        assert!(!is_backlog_for_device(
+            now - Duration::minutes(5),
+            registered_at,
+            now,
+            900
+        ));
+    }
+
+    #[test]
+    fn notifications_due_after_registration_or_with_policy_disabled_are_delivered() {
+        let now = Utc::now();
+        let registered_at = now - Duration::days(3);
+
+        assert!(!is_backlog_for_device(
+            now - Duration::days(1),
+            registered_at,
+            now,
+            900
+        ));
+        assert!(!is_backlog_for_device(
+            now - Duration::days(4),
+            registered_at,
+            now,
+            0
+        ));
+    }
+}
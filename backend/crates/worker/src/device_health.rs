@@ -0,0 +1,92 @@
+use shared::models::AuditMetadata;
+use shared::repos::{AuditResult, DeviceRegistration, Store};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::{DestinationRejection, NotificationContent, PushSendError, PushSender};
+
+/// Consecutive token rejections after which a device is skipped by fan-out.
+const DEVICE_DISABLE_FAILURE_THRESHOLD: i32 = 3;
+
+/// Scores a failed push against the device when APNs rejected the token itself. Returns
+/// true when this failure disabled the device.
+pub(crate) async fn record_push_failure(
+    store: &Store,
+    user_id: Uuid,
+    device_id: &str,
+    err: &PushSendError,
+) -> bool {
+    let Some(rejection) = err.destination_rejection() else {
+        return false;
+    };
+
+    let score = match store
+        .record_device_delivery_failure(
+            user_id,
+            device_id,
+            err.code(),
+            DEVICE_DISABLE_FAILURE_THRESHOLD,
+            rejection == DestinationRejection::Gone,
+        )
+        .await
+    {
+        Ok(Some(score)) => score,
+        Ok(None) => return false,
+        Err(err) => {
+            warn!(
+                user_id = %user_id,
+                device_id = %device_id,
+                "failed to record device delivery failure: {err}"
+            );
+            return false;
+        }
+    };
+    if !score.newly_disabled {
+        return false;
+    }
+
+    let mut metadata = AuditMetadata::new();
+    metadata.insert("device_id".to_string(), device_id.into());
+    metadata.insert("error_code".to_string(), err.code().into());
+    metadata.insert(
+        "consecutive_failures".to_string(),
+        score.consecutive_failures.into(),
+    );
+    if let Err(err) = store
+        .add_audit_event(
+            user_id,
+            "DEVICE_DISABLED",
+            None,
+            AuditResult::Failure,
+            &metadata,
+        )
+        .await
+    {
+        warn!(
+            user_id = %user_id,
+            device_id = %device_id,
+            "failed to record device disablement audit event: {err}"
+        );
+    }
+
+    true
+}
+
+/// Tells the user on devices that still work that another one stopped receiving alerts.
+pub(crate) async fn notify_device_disabled(
+    push_sender: &PushSender,
+    user_id: Uuid,
+    devices: &[&DeviceRegistration],
+) {
+    let content = NotificationContent::device_disabled_notice();
+    for device in devices {
+        if let Err(err) = push_sender.send(device, &content).await {
+            warn!(
+                user_id = %user_id,
+                device_id = %device.device_id,
+                error_code = %err.code(),
+                "device disabled notice delivery failed"
+            );
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use chrono::{Duration, Utc};
+use shared::config::WorkerConfig;
+use shared::repos::Store;
+use tracing::{debug, error, info};
+use uuid::Uuid;
+
+#[derive(Debug, Default)]
+pub(crate) struct EphemeralStatePurgeMetrics {
+    pub oauth_states_reclaimed: u64,
+    pub idempotency_keys_reclaimed: u64,
+    pub manual_runs_reclaimed: u64,
+    pub request_nonces_reclaimed: u64,
+    pub prompt_envelope_request_ids_reclaimed: u64,
+}
+
+/// Manual run records back the API's daily cap and retry replay; neither looks past a day.
+const AUTOMATION_MANUAL_RUN_RETENTION_DAYS: i64 = 2;
+
+/// Reclaims OAuth states that can no longer complete a callback, outbound action
+/// idempotency keys older than the retention window, stale automation manual run
+/// records, request signature nonces past the skew window, and prompt envelope request ids
+/// whose ingress key has expired. These tables otherwise grow forever.
+pub(crate) async fn purge_expired_ephemeral_state(
+    store: &Store,
+    config: &WorkerConfig,
+    worker_id: Uuid,
+) -> EphemeralStatePurgeMetrics {
+    let now = Utc::now();
+    let batch_size = i64::from(config.ephemeral_state_purge_batch_size);
+    let mut metrics = EphemeralStatePurgeMetrics::default();
+
+    match store
+        .purge_expired_oauth_states_batch(now, batch_size)
+        .await
+    {
+        Ok(reclaimed) => metrics.oauth_states_reclaimed = reclaimed,
+        Err(err) => error!(worker_id = %worker_id, "failed to purge expired oauth states: {err}"),
+    }
+
+    let created_before =
+        now - Duration::days(i64::from(config.outbound_idempotency_retention_days));
+    match store
+        .purge_expired_outbound_action_idempotency_batch(created_before, batch_size)
+        .await
+    {
+        Ok(reclaimed) => metrics.idempotency_keys_reclaimed = reclaimed,
+        Err(err) => error!(
+            worker_id = %worker_id,
+            "failed to purge expired outbound action idempotency keys: {err}"
+        ),
+    }
+
+    match store
+        .purge_expired_automation_manual_runs_batch(
+            now - Duration::days(AUTOMATION_MANUAL_RUN_RETENTION_DAYS),
+            batch_size,
+        )
+        .await
+    {
+        Ok(reclaimed) => metrics.manual_runs_reclaimed = reclaimed,
+        Err(err) => error!(
+            worker_id = %worker_id,
+            "failed to purge expired automation manual runs: {err}"
+        ),
+    }
+
+    match store
+        .purge_expired_request_signature_nonces_batch(now, batch_size)
+        .await
+    {
+        Ok(reclaimed) => metrics.request_nonces_reclaimed = reclaimed,
+        Err(err) => error!(
+            worker_id = %worker_id,
+            "failed to purge expired request signature nonces: {err}"
+        ),
+    }
+
+    match store
+        .purge_expired_prompt_envelope_request_ids_batch(now, batch_size)
+        .await
+    {
+        Ok(reclaimed) => metrics.prompt_envelope_request_ids_reclaimed = reclaimed,
+        Err(err) => error!(
+            worker_id = %worker_id,
+            "failed to purge expired prompt envelope request ids: {err}"
+        ),
+    }
+
+    if metrics.oauth_states_reclaimed == 0
+        && metrics.idempotency_keys_reclaimed == 0
+        && metrics.manual_runs_reclaimed == 0
+        && metrics.request_nonces_reclaimed == 0
+        && metrics.prompt_envelope_request_ids_reclaimed == 0
+    {
+        debug!(
+            worker_id = %worker_id,
+            batch_size = config.ephemeral_state_purge_batch_size,
+            "ephemeral state purge tick found no expired rows"
+        );
+        return metrics;
+    }
+
+    info!(
+        worker_id = %worker_id,
+        oauth_states_reclaimed = metrics.oauth_states_reclaimed,
+        idempotency_keys_reclaimed = metrics.idempotency_keys_reclaimed,
+        manual_runs_reclaimed = metrics.manual_runs_reclaimed,
+        request_nonces_reclaimed = metrics.request_nonces_reclaimed,
+        prompt_envelope_request_ids_reclaimed = metrics.prompt_envelope_request_ids_reclaimed,
+        batch_size = config.ephemeral_state_purge_batch_size,
+        idempotency_retention_days = config.outbound_idempotency_retention_days,
+        "ephemeral state purge tick metrics"
+    );
+
+    metrics
+}
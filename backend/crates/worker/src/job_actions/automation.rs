@@ -1,16 +1,28 @@
 use std::collections::HashMap;
+use std::time::Instant;
 
 use base64::Engine as _;
 use shared::assistant_crypto::ASSISTANT_ENCRYPTION_ALGORITHM_X25519_CHACHA20POLY1305;
-use shared::enclave::{AutomationRecipientDevice, EnclaveRpcError};
-use shared::repos::{ClaimedJob, JobType};
+use shared::automation_schedule::AutomationTemplate;
+use shared::enclave::{
+    AutomationConditionRequest, AutomationRecipientDevice, AutomationTemplateRequest,
+    EnclaveRpcError, EncryptedAutomationNotificationEnvelope, ExecuteAutomationRequest,
+};
+use shared::models::{AuditMetadata, AutomationReportEnvelope};
+use shared::repos::{
+    ClaimedJob, DeviceNotificationKey, DeviceRegistration, JobStageTimings, JobType, StoreError,
+};
+
+use tracing::warn;
 
 use super::{JobActionContext, JobActionResult};
+use crate::automation_failures::AUTOMATION_CONNECTOR_UNAVAILABLE;
 use crate::{JobExecutionError, NotificationContent, automation_runs::AutomationRunJobPayload};
 
 pub(super) async fn resolve_job_action(
     context: &JobActionContext<'_>,
     job: &ClaimedJob,
+    timings: &mut JobStageTimings,
 ) -> Result<JobActionResult, JobExecutionError> {
     if !matches!(job.job_type, JobType::AutomationRun) {
         return Err(JobExecutionError::permanent(
@@ -29,6 +41,7 @@ pub(super) async fn resolve_job_action(
         JobExecutionError::permanent("INVALID_AUTOMATION_PROMPT_ENVELOPE", err.to_string())
     })?;
 
+    let fetch_started = Instant::now();
     let devices = context
         .store
         .list_registered_devices(job.user_id)
@@ -40,101 +53,172 @@ pub(super) async fn resolve_job_action(
             )
         })?;
 
-    let mut recipient_devices = Vec::new();
-    let mut missing_key_count = 0_usize;
-    let mut unsupported_algorithm_count = 0_usize;
-    for device in &devices {
-        let Some(key_algorithm) = device.notification_key_algorithm.as_deref() else {
-            missing_key_count += 1;
-            continue;
-        };
-        let Some(public_key) = device.notification_public_key.as_deref() else {
-            missing_key_count += 1;
-            continue;
-        };
-        if key_algorithm != ASSISTANT_ENCRYPTION_ALGORITHM_X25519_CHACHA20POLY1305 {
-            unsupported_algorithm_count += 1;
-            continue;
-        }
+    let recipients = recipient_devices(&devices);
+    timings.fetch_ms = Some(fetch_started.elapsed().as_millis() as u64);
 
-        recipient_devices.push(AutomationRecipientDevice {
-            device_id: device.device_id.clone(),
-            key_id: device.device_id.clone(),
-            algorithm: key_algorithm.to_string(),
-            public_key: public_key.to_string(),
+    let time_zone = payload
+        .time_zone
+        .clone()
+        .unwrap_or_else(|| "UTC".to_string());
+    let brief_profile = match payload.template {
+        Some(AutomationTemplate::MorningBrief) => Some(
+            context
+                .store
+                .get_morning_brief_profile(job.user_id)
+                .await
+                .map_err(|err| {
+                    JobExecutionError::transient(
+                        "BRIEF_PROFILE_LOOKUP_FAILED",
+                        format!("failed to fetch morning brief profile: {err}"),
+                    )
+                })?
+                .profile,
+        ),
+        _ => None,
+    };
+    let template_request = payload.template.map(|template| AutomationTemplateRequest {
+        template,
+        time_zone: time_zone.clone(),
+        brief_profile,
+    });
+    let condition_request = payload
+        .condition
+        .map(|condition| AutomationConditionRequest {
+            condition,
+            time_zone,
         });
-    }
-
+    let generate_started = Instant::now();
     let enclave_response = context
         .enclave_client
-        .execute_automation_run(
-            job.user_id,
-            payload.automation_rule_id,
-            payload.automation_run_id,
-            payload.scheduled_for,
+        .execute_automation_run(ExecuteAutomationRequest {
+            job_id: Some(job.id),
+            user_id: job.user_id,
+            automation_rule_id: payload.automation_rule_id,
+            automation_run_id: payload.automation_run_id,
+            scheduled_for: payload.scheduled_for,
             prompt_envelope,
-            recipient_devices,
-        )
+            recipient_devices: recipients.devices,
+            template: template_request,
+            condition: condition_request,
+        })
         .await
         .map_err(map_automation_enclave_error)?;
+
+    if let Some(result) = enclave_response.condition_result {
+        let observed_count = i32::try_from(result.observed_count).unwrap_or(i32::MAX);
+        match context
+            .store
+            .record_automation_run_condition(
+                payload.automation_run_id,
+                job.user_id,
+                result.met,
+                observed_count,
+            )
+            .await
+        {
+            Ok(true) => {}
+            Ok(false) => warn!(
+                job_id = %job.id,
+                run_id = %payload.automation_run_id,
+                "automation run condition result was not recorded"
+            ),
+            Err(err) => warn!(
+                job_id = %job.id,
+                run_id = %payload.automation_run_id,
+                "failed to record automation run condition result: {err}"
+            ),
+        }
+    }
+
+    let report_count = enclave_response.report_artifacts.len();
+    if let Some(template) = payload.template {
+        for artifact in &enclave_response.report_artifacts {
+            context
+                .store
+                .store_automation_report(
+                    job.user_id,
+                    payload.automation_rule_id,
+                    payload.automation_run_id,
+                    artifact.device_id.as_str(),
+                    template,
+                    &report_envelope(&artifact.envelope),
+                )
+                .await
+                .map_err(map_report_store_error)?;
+        }
+    }
+    timings.generate_ms = Some(generate_started.elapsed().as_millis() as u64);
     let mut encrypted_envelopes_by_device = HashMap::new();
     for artifact in enclave_response.notification_artifacts {
         encrypted_envelopes_by_device.insert(artifact.device_id, artifact.envelope);
     }
 
-    let mut metadata = HashMap::new();
-    metadata.insert("action_source".to_string(), "automation_run".to_string());
+    let mut metadata = AuditMetadata::new();
+    metadata.insert("action_source".to_string(), "automation_run".into());
+    metadata.insert(
+        "enclave_request_id".to_string(),
+        enclave_response.request_id.clone().into(),
+    );
     metadata.insert(
         "automation_run_id".to_string(),
-        payload.automation_run_id.to_string(),
+        payload.automation_run_id.to_string().into(),
     );
     metadata.insert(
         "automation_rule_id".to_string(),
-        payload.automation_rule_id.to_string(),
+        payload.automation_rule_id.to_string().into(),
     );
     metadata.insert(
         "scheduled_for".to_string(),
-        payload.scheduled_for.to_rfc3339(),
-    );
-    metadata.insert("prompt_sha256".to_string(), payload.prompt_sha256);
-    metadata.insert(
-        "registered_device_count".to_string(),
-        devices.len().to_string(),
+        payload.scheduled_for.to_rfc3339().into(),
     );
+    metadata.insert("prompt_sha256".to_string(), payload.prompt_sha256.into());
+    if let Some(template) = payload.template {
+        metadata.insert("automation_template".to_string(), template.as_str().into());
+        metadata.insert("report_artifact_count".to_string(), report_count.into());
+    }
+    metadata.insert("registered_device_count".to_string(), devices.len().into());
     metadata.insert(
         "recipient_device_count".to_string(),
-        encrypted_envelopes_by_device.len().to_string(),
+        encrypted_envelopes_by_device.len().into(),
     );
     metadata.insert(
         "recipient_devices_missing_key".to_string(),
-        missing_key_count.to_string(),
+        recipients.missing_key_count.into(),
     );
     metadata.insert(
         "recipient_devices_unsupported_algorithm".to_string(),
-        unsupported_algorithm_count.to_string(),
+        recipients.unsupported_algorithm_count.into(),
+    );
+    metadata.insert(
+        "recipient_devices_using_previous_key".to_string(),
+        recipients.previous_key_count.into(),
     );
     metadata.insert(
         "automation_should_notify".to_string(),
-        enclave_response.should_notify.to_string(),
+        enclave_response.should_notify.into(),
     );
     metadata.insert(
         "recipient_devices_with_artifact".to_string(),
-        encrypted_envelopes_by_device.len().to_string(),
+        encrypted_envelopes_by_device.len().into(),
     );
     metadata.insert(
         "recipient_devices_without_artifact".to_string(),
         devices
             .len()
             .saturating_sub(encrypted_envelopes_by_device.len())
-            .to_string(),
+            .into(),
     );
     metadata.insert(
         "attested_measurement".to_string(),
-        enclave_response.attested_identity.measurement.clone(),
+        enclave_response
+            .attested_identity
+            .measurement
+            .clone()
+            .into(),
     );
     for (key, value) in enclave_response.metadata {
         if is_allowed_enclave_metadata_key(key.as_str()) {
-            metadata.insert(key, value);
+            metadata.insert(key, value.into());
         }
     }
 
@@ -143,10 +227,80 @@ pub(super) async fn resolve_job_action(
             .should_notify
             .then(NotificationContent::automation_fallback),
         encrypted_envelopes_by_device,
+        departure_alert_delivery: None,
         metadata,
     })
 }
 
+pub(super) struct RecipientDevices {
+    pub(super) devices: Vec<AutomationRecipientDevice>,
+    pub(super) missing_key_count: usize,
+    pub(super) unsupported_algorithm_count: usize,
+    pub(super) previous_key_count: usize,
+}
+
+pub(super) fn recipient_devices(devices: &[DeviceRegistration]) -> RecipientDevices {
+    let mut recipients = RecipientDevices {
+        devices: Vec::new(),
+        missing_key_count: 0,
+        unsupported_algorithm_count: 0,
+        previous_key_count: 0,
+    };
+    for device in devices {
+        let selected_key = match select_recipient_key(device) {
+            RecipientKeySelection::Current(key) => key,
+            RecipientKeySelection::Previous(key) => {
+                recipients.previous_key_count += 1;
+                key
+            }
+            RecipientKeySelection::Missing => {
+                recipients.missing_key_count += 1;
+                continue;
+            }
+            RecipientKeySelection::UnsupportedAlgorithm => {
+                recipients.unsupported_algorithm_count += 1;
+                continue;
+            }
+        };
+
+        recipients.devices.push(AutomationRecipientDevice {
+            device_id: device.device_id.clone(),
+            key_id: selected_key.key_id.clone(),
+            algorithm: selected_key.algorithm.clone(),
+            public_key: selected_key.public_key.clone(),
+        });
+    }
+
+    recipients
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum RecipientKeySelection<'a> {
+    Current(&'a DeviceNotificationKey),
+    Previous(&'a DeviceNotificationKey),
+    Missing,
+    UnsupportedAlgorithm,
+}
+
+/// Picks the key the enclave should encrypt to. The active key always wins; the previous key
+/// is only used while its overlap window is open (the store drops it once expired) and the
+/// active key cannot be used.
+fn select_recipient_key(device: &DeviceRegistration) -> RecipientKeySelection<'_> {
+    let is_supported = |key: &DeviceNotificationKey| {
+        key.algorithm == ASSISTANT_ENCRYPTION_ALGORITHM_X25519_CHACHA20POLY1305
+    };
+
+    match (
+        device.notification_key.as_ref(),
+        device.previous_notification_key.as_ref(),
+    ) {
+        (Some(current), _) if is_supported(current) => RecipientKeySelection::Current(current),
+        (_, Some(previous)) if is_supported(previous) => RecipientKeySelection::Previous(previous),
+        (Some(_), _) | (_, Some(_)) => RecipientKeySelection::UnsupportedAlgorithm,
+        (None, None) => RecipientKeySelection::Missing,
+    }
+}
+
 fn decode_prompt_envelope(
     encoded: &str,
 ) -> Result<shared::models::AutomationPromptEnvelope, &'static str> {
@@ -158,28 +312,58 @@ fn decode_prompt_envelope(
         .map_err(|_| "prompt envelope payload must be valid JSON")
 }
 
+fn report_envelope(envelope: &EncryptedAutomationNotificationEnvelope) -> AutomationReportEnvelope {
+    AutomationReportEnvelope {
+        version: envelope.version.clone(),
+        algorithm: envelope.algorithm.clone(),
+        key_id: envelope.key_id.clone(),
+        request_id: envelope.request_id.clone(),
+        sender_public_key: envelope.sender_public_key.clone(),
+        nonce: envelope.nonce.clone(),
+        ciphertext: envelope.ciphertext.clone(),
+    }
+}
+
+fn map_report_store_error(err: StoreError) -> JobExecutionError {
+    match err {
+        StoreError::InvalidData(message) => {
+            JobExecutionError::permanent("AUTOMATION_REPORT_REJECTED", message)
+        }
+        other => JobExecutionError::transient(
+            "AUTOMATION_REPORT_STORE_FAILED",
+            format!("failed to store automation report: {other}"),
+        ),
+    }
+}
+
 fn map_automation_enclave_error(err: EnclaveRpcError) -> JobExecutionError {
     match err {
         EnclaveRpcError::RpcContractRejected { .. }
         | EnclaveRpcError::DecryptNotAuthorized { .. }
-        | EnclaveRpcError::ConnectorTokenDecryptFailed { .. }
-        | EnclaveRpcError::ConnectorTokenUnavailable => JobExecutionError::permanent(
+        | EnclaveRpcError::LlmBudgetExceeded { .. } => JobExecutionError::permanent(
             "AUTOMATION_ENCLAVE_REJECTED",
             "secure enclave rejected automation execution payload",
         ),
+        // Kept apart from other rejections so the failure alert can ask the user to reconnect.
+        EnclaveRpcError::ConnectorTokenDecryptFailed { .. }
+        | EnclaveRpcError::ConnectorTokenUnavailable => JobExecutionError::permanent(
+            AUTOMATION_CONNECTOR_UNAVAILABLE,
+            "google connector is unavailable for automation execution",
+        ),
         EnclaveRpcError::RpcUnauthorized { .. }
         | EnclaveRpcError::RpcTransportUnavailable { .. }
         | EnclaveRpcError::RpcResponseInvalid { .. }
         | EnclaveRpcError::ProviderRequestUnavailable { .. }
         | EnclaveRpcError::ProviderRequestFailed { .. }
-        | EnclaveRpcError::ProviderResponseInvalid { .. } => JobExecutionError::transient(
+        | EnclaveRpcError::ProviderResponseInvalid { .. }
+        | EnclaveRpcError::ProviderRateLimited { .. } => JobExecutionError::transient(
             "AUTOMATION_ENCLAVE_UNAVAILABLE",
             "secure enclave automation execution unavailable",
         ),
     }
 }
 
-fn is_allowed_enclave_metadata_key(key: &str) -> bool {
+pub(super) fn is_allowed_enclave_metadata_key(key: &str) -> bool {
     matches!(
         key,
         "action_source"
@@ -192,6 +376,7 @@ fn is_allowed_enclave_metadata_key(key: &str) -> bool {
             | "encrypted_artifact_count"
             | "attested_measurement"
     ) || key.starts_with("llm_")
+        || key.starts_with("automation_condition_")
 }
 
 #[cfg(test)]
@@ -216,10 +401,91 @@ mod tests {
         );
     }
 
+    #[test]
+    fn map_automation_enclave_error_flags_connector_failures() {
+        let mapped = map_automation_enclave_error(EnclaveRpcError::ConnectorTokenUnavailable);
+        assert_eq!(mapped.code, AUTOMATION_CONNECTOR_UNAVAILABLE);
+        assert!(matches!(mapped.class, crate::FailureClass::Permanent));
+    }
+
+    #[test]
+    fn select_recipient_key_prefers_active_key_over_previous_key() {
+        let device = sample_device(
+            Some(sample_key(
+                "key-2",
+                ASSISTANT_ENCRYPTION_ALGORITHM_X25519_CHACHA20POLY1305,
+            )),
+            Some(sample_key(
+                "key-1",
+                ASSISTANT_ENCRYPTION_ALGORITHM_X25519_CHACHA20POLY1305,
+            )),
+        );
+
+        match select_recipient_key(&device) {
+            RecipientKeySelection::Current(key) => assert_eq!(key.key_id, "key-2"),
+            other => panic!("expected active key, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn select_recipient_key_falls_back_to_previous_key_in_overlap_window() {
+        let device = sample_device(
+            Some(sample_key("key-2", "unsupported")),
+            Some(sample_key(
+                "key-1",
+                ASSISTANT_ENCRYPTION_ALGORITHM_X25519_CHACHA20POLY1305,
+            )),
+        );
+
+        match select_recipient_key(&device) {
+            RecipientKeySelection::Previous(key) => assert_eq!(key.key_id, "key-1"),
+            other => panic!("expected previous key, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn select_recipient_key_reports_missing_and_unsupported_keys() {
+        assert_eq!(
+            select_recipient_key(&sample_device(None, None)),
+            RecipientKeySelection::Missing
+        );
+        assert_eq!(
+            select_recipient_key(&sample_device(
+                Some(sample_key("key-1", "unsupported")),
+                None
+            )),
+            RecipientKeySelection::UnsupportedAlgorithm
+        );
+    }
+
     #[test]
     fn is_allowed_enclave_metadata_key_only_allows_expected_keys() {
         assert!(is_allowed_enclave_metadata_key("llm_provider"));
         assert!(is_allowed_enclave_metadata_key("attested_measurement"));
+        assert!(is_allowed_enclave_metadata_key("automation_condition_met"));
         assert!(!is_allowed_enclave_metadata_key("notification_title"));
     }
+
+    fn sample_key(key_id: &str, algorithm: &str) -> DeviceNotificationKey {
+        DeviceNotificationKey {
+            key_id: key_id.to_string(),
+            algorithm: algorithm.to_string(),
+            public_key: "cHVibGlj".to_string(),
+        }
+    }
+
+    fn sample_device(
+        notification_key: Option<DeviceNotificationKey>,
+        previous_notification_key: Option<DeviceNotificationKey>,
+    ) -> DeviceRegistration {
+        DeviceRegistration {
+            device_id: "device-1".to_string(),
+            apns_token: "token".to_string(),
+            environment: shared::models::ApnsEnvironment::Sandbox,
+            app_bundle_id: None,
+            registered_at: chrono::Utc::now(),
+            notification_key,
+            previous_notification_key,
+        }
+    }
 }
@@ -1,13 +1,20 @@
 use std::collections::HashMap;
 
+use chrono::{DateTime, NaiveDate, Utc};
+
+use shared::config::WorkerConfig;
 use shared::enclave::EnclaveRpcClient;
 use shared::enclave::EncryptedAutomationNotificationEnvelope;
+use shared::events::EventBus;
+use shared::models::AuditMetadata;
 use shared::repos::Store;
 
 use crate::{NotificationContent, PushSender};
 
 pub(crate) struct JobActionContext<'a> {
     pub(crate) store: &'a Store,
+    pub(crate) events: &'a EventBus,
+    pub(crate) config: &'a WorkerConfig,
     pub(crate) push_sender: &'a PushSender,
     pub(crate) enclave_client: &'a EnclaveRpcClient,
 }
@@ -16,5 +23,13 @@ pub(crate) struct JobActionResult {
     pub(crate) notification: Option<NotificationContent>,
     pub(crate) encrypted_envelopes_by_device:
         HashMap<String, EncryptedAutomationNotificationEnvelope>,
-    pub(crate) metadata: HashMap<String, String>,
+    /// Recorded once the notification is delivered so the same meeting is not alerted twice.
+    pub(crate) departure_alert_delivery: Option<DepartureAlertDelivery>,
+    pub(crate) metadata: AuditMetadata,
+}
+
+pub(crate) struct DepartureAlertDelivery {
+    pub(crate) local_date: NaiveDate,
+    pub(crate) meeting_fingerprint: String,
+    pub(crate) leave_by: DateTime<Utc>,
 }
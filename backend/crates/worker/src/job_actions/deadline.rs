@@ -0,0 +1,121 @@
+use std::future::Future;
+
+use tokio::time::{Duration, Instant, timeout_at};
+
+use crate::JobExecutionError;
+use crate::automation_failures::AUTOMATION_RUN_TIMED_OUT;
+
+/// Runtime budget of one automation run. A run is only cut off before it has pushed
+/// anything, and the cut-off is transient: the retry starts over and cannot deliver twice.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct AutomationDeadline {
+    at: Instant,
+    max_runtime: Duration,
+}
+
+impl AutomationDeadline {
+    pub(super) fn after(max_runtime: Duration) -> Self {
+        Self {
+            at: Instant::now() + max_runtime,
+            max_runtime,
+        }
+    }
+
+    /// Runs the generation phase (device lookup and the enclave's LLM calls), stopping it once
+    /// the deadline passes.
+    pub(super) async fn generate<T>(
+        &self,
+        phase: impl Future<Output = Result<T, JobExecutionError>>,
+    ) -> Result<T, JobExecutionError> {
+        timeout_at(self.at, phase)
+            .await
+            .unwrap_or_else(|_| Err(self.timed_out()))
+    }
+
+    /// Refuses to start delivery after the deadline. Delivery itself is never interrupted, so
+    /// a push is either sent to every device or to none.
+    pub(super) fn check_before_delivery(&self) -> Result<(), JobExecutionError> {
+        if Instant::now() >= self.at {
+            return Err(self.timed_out());
+        }
+
+        Ok(())
+    }
+
+    fn timed_out(&self) -> JobExecutionError {
+        JobExecutionError::transient(
+            AUTOMATION_RUN_TIMED_OUT,
+            format!(
+                "automation run exceeded {} seconds",
+                self.max_runtime.as_secs()
+            ),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use tokio::time::sleep;
+
+    use super::*;
+    use crate::FailureClass;
+
+    async fn run_once(
+        deadline: AutomationDeadline,
+        generation_time: Duration,
+        deliveries: &Cell<usize>,
+    ) -> Result<(), JobExecutionError> {
+        deadline
+            .generate(async {
+                sleep(generation_time).await;
+                Ok(())
+            })
+            .await?;
+        deadline.check_before_delivery()?;
+        deliveries.set(deliveries.get() + 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn timed_out_generation_is_retried_without_double_delivery() {
+        let deliveries = Cell::new(0);
+
+        let err = run_once(
+            AutomationDeadline::after(Duration::from_millis(20)),
+            Duration::from_secs(5),
+            &deliveries,
+        )
+        .await
+        .expect_err("slow generation should time out");
+        assert_eq!(err.code, AUTOMATION_RUN_TIMED_OUT);
+        assert!(matches!(err.class, FailureClass::Transient));
+        assert_eq!(deliveries.get(), 0);
+
+        run_once(
+            AutomationDeadline::after(Duration::from_secs(5)),
+            Duration::ZERO,
+            &deliveries,
+        )
+        .await
+        .expect("retry should deliver");
+        assert_eq!(deliveries.get(), 1);
+    }
+
+    #[tokio::test]
+    async fn delivery_does_not_start_after_the_deadline() {
+        let deadline = AutomationDeadline::after(Duration::from_millis(20));
+        deadline
+            .generate(async { Ok(()) })
+            .await
+            .expect("generation should finish in time");
+        sleep(Duration::from_millis(30)).await;
+
+        let err = deadline
+            .check_before_delivery()
+            .expect_err("delivery should not start late");
+        assert_eq!(err.code, AUTOMATION_RUN_TIMED_OUT);
+        assert!(matches!(err.class, FailureClass::Transient));
+    }
+}
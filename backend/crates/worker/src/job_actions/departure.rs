@@ -0,0 +1,234 @@
+use std::collections::HashMap;
+use std::time::Instant;
+
+use chrono::Utc;
+use shared::departure_alert::next_departure_recheck_at;
+use shared::enclave::{EnclaveRpcError, PlanDepartureAlertRequest};
+use shared::models::AuditMetadata;
+use shared::repos::{ClaimedJob, JobStageTimings, JobType};
+use shared::timezone::user_local_date;
+
+use super::automation::{is_allowed_enclave_metadata_key, recipient_devices};
+use super::{DepartureAlertDelivery, JobActionContext, JobActionResult};
+use crate::departure_alerts::DepartureAlertJobPayload;
+use crate::{JobExecutionError, NotificationContent};
+
+pub(super) async fn resolve_job_action(
+    context: &JobActionContext<'_>,
+    job: &ClaimedJob,
+    timings: &mut JobStageTimings,
+) -> Result<JobActionResult, JobExecutionError> {
+    let payload =
+        DepartureAlertJobPayload::parse(job.payload_ciphertext.as_deref()).map_err(|err| {
+            JobExecutionError::permanent("INVALID_DEPARTURE_ALERT_PAYLOAD", err.to_string())
+        })?;
+
+    let mut metadata = AuditMetadata::new();
+    metadata.insert("action_source".to_string(), "departure_alert".into());
+    metadata.insert(
+        "local_date".to_string(),
+        payload.local_date.to_string().into(),
+    );
+
+    let fetch_started = Instant::now();
+    let material = context
+        .store
+        .get_departure_alert_job_material(job.user_id)
+        .await
+        .map_err(|err| {
+            JobExecutionError::transient(
+                "DEPARTURE_PREFERENCES_LOOKUP_FAILED",
+                format!("failed to load departure alert preferences: {err}"),
+            )
+        })?;
+    let Some(material) = material.filter(|material| material.settings.enabled) else {
+        return Ok(skipped(metadata, "departure_alerts_disabled"));
+    };
+
+    let now = Utc::now();
+    if user_local_date(now, material.settings.time_zone.as_str()) != payload.local_date {
+        return Ok(skipped(metadata, "departure_check_expired"));
+    }
+
+    let devices = context
+        .store
+        .list_registered_devices(job.user_id)
+        .await
+        .map_err(|err| {
+            JobExecutionError::transient(
+                "DEVICE_LOOKUP_FAILED",
+                format!("failed to fetch registered devices: {err}"),
+            )
+        })?;
+    let recipients = recipient_devices(&devices);
+    timings.fetch_ms = Some(fetch_started.elapsed().as_millis() as u64);
+
+    let generate_started = Instant::now();
+    let enclave_response = context
+        .enclave_client
+        .plan_departure_alert(PlanDepartureAlertRequest {
+            job_id: Some(job.id),
+            user_id: job.user_id,
+            time_zone: material.settings.time_zone.clone(),
+            travel_minutes: material.settings.travel_minutes,
+            buffer_minutes: material.settings.buffer_minutes,
+            home_location_envelope: material.home_location_envelope,
+            recipient_devices: recipients.devices,
+        })
+        .await
+        .map_err(map_departure_enclave_error)?;
+    timings.generate_ms = Some(generate_started.elapsed().as_millis() as u64);
+    metadata.insert(
+        "enclave_request_id".to_string(),
+        enclave_response.request_id.clone().into(),
+    );
+
+    for (key, value) in enclave_response.metadata {
+        if is_allowed_enclave_metadata_key(key.as_str()) {
+            metadata.insert(key, value.into());
+        }
+    }
+    metadata.insert(
+        "attested_measurement".to_string(),
+        enclave_response
+            .attested_identity
+            .measurement
+            .clone()
+            .into(),
+    );
+
+    let Some(plan) = enclave_response.plan else {
+        return Ok(skipped(metadata, "no_in_person_meeting"));
+    };
+
+    let delivered_fingerprint = context
+        .store
+        .get_departure_alert_delivery(job.user_id, payload.local_date)
+        .await
+        .map_err(|err| {
+            JobExecutionError::transient(
+                "DEPARTURE_DELIVERY_LOOKUP_FAILED",
+                format!("failed to load departure alert delivery: {err}"),
+            )
+        })?;
+    let already_delivered =
+        delivered_fingerprint.as_deref() == Some(plan.meeting_fingerprint.as_str());
+    let should_notify = plan.due && !already_delivered;
+
+    if let Some(recheck_at) = next_departure_recheck_at(
+        now,
+        plan.leave_by,
+        plan.meeting_start_at,
+        already_delivered || should_notify,
+    ) {
+        let payload_json = serde_json::to_vec(&payload).map_err(|err| {
+            JobExecutionError::permanent(
+                "INVALID_DEPARTURE_ALERT_PAYLOAD",
+                format!("failed to serialize departure alert payload: {err}"),
+            )
+        })?;
+        context
+            .store
+            .enqueue_job_with_idempotency_key(
+                job.user_id,
+                JobType::DepartureAlert,
+                recheck_at,
+                Some(&payload_json),
+                &format!("recheck:{}", job.id),
+            )
+            .await
+            .map_err(|err| {
+                JobExecutionError::transient(
+                    "DEPARTURE_RECHECK_ENQUEUE_FAILED",
+                    format!("failed to enqueue departure recheck: {err}"),
+                )
+            })?;
+        metadata.insert(
+            "departure_recheck_at".to_string(),
+            recheck_at.to_rfc3339().into(),
+        );
+    }
+
+    metadata.insert(
+        "departure_status".to_string(),
+        if already_delivered {
+            "already_delivered"
+        } else if plan.due {
+            "due"
+        } else {
+            "pending"
+        }
+        .into(),
+    );
+    metadata.insert("leave_by".to_string(), plan.leave_by.to_rfc3339().into());
+    metadata.insert(
+        "recipient_devices_missing_key".to_string(),
+        recipients.missing_key_count.into(),
+    );
+
+    let mut encrypted_envelopes_by_device = HashMap::new();
+    for artifact in enclave_response.notification_artifacts {
+        encrypted_envelopes_by_device.insert(artifact.device_id, artifact.envelope);
+    }
+
+    Ok(JobActionResult {
+        notification: should_notify.then(NotificationContent::departure_fallback),
+        encrypted_envelopes_by_device,
+        departure_alert_delivery: should_notify.then_some(DepartureAlertDelivery {
+            local_date: payload.local_date,
+            meeting_fingerprint: plan.meeting_fingerprint,
+            leave_by: plan.leave_by,
+        }),
+        metadata,
+    })
+}
+
+fn skipped(mut metadata: AuditMetadata, status: &str) -> JobActionResult {
+    metadata.insert("departure_status".to_string(), status.into());
+    JobActionResult {
+        notification: None,
+        encrypted_envelopes_by_device: HashMap::new(),
+        departure_alert_delivery: None,
+        metadata,
+    }
+}
+
+fn map_departure_enclave_error(err: EnclaveRpcError) -> JobExecutionError {
+    match err {
+        EnclaveRpcError::RpcContractRejected { .. }
+        | EnclaveRpcError::DecryptNotAuthorized { .. }
+        | EnclaveRpcError::ConnectorTokenDecryptFailed { .. }
+        | EnclaveRpcError::ConnectorTokenUnavailable
+        | EnclaveRpcError::LlmBudgetExceeded { .. } => JobExecutionError::permanent(
+            "DEPARTURE_ENCLAVE_REJECTED",
+            "secure enclave rejected departure alert payload",
+        ),
+        EnclaveRpcError::RpcUnauthorized { .. }
+        | EnclaveRpcError::RpcTransportUnavailable { .. }
+        | EnclaveRpcError::RpcResponseInvalid { .. }
+        | EnclaveRpcError::ProviderRequestUnavailable { .. }
+        | EnclaveRpcError::ProviderRequestFailed { .. }
+        | EnclaveRpcError::ProviderResponseInvalid { .. }
+        | EnclaveRpcError::ProviderRateLimited { .. } => JobExecutionError::transient(
+            "DEPARTURE_ENCLAVE_UNAVAILABLE",
+            "secure enclave departure alert planning unavailable",
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn map_departure_enclave_error_sanitizes_transport_failures() {
+        let mapped = map_departure_enclave_error(EnclaveRpcError::RpcTransportUnavailable {
+            message: "authorization header leaked".to_string(),
+        });
+        assert_eq!(mapped.code, "DEPARTURE_ENCLAVE_UNAVAILABLE");
+        assert_eq!(
+            mapped.message,
+            "secure enclave departure alert planning unavailable"
+        );
+    }
+}
@@ -13,6 +13,18 @@ struct NotificationPayloadBody {
     body: String,
 }
 
+#[derive(Debug, Deserialize)]
+struct SimulatedFailureJobPayload {
+    simulated_failure: Option<SimulatedFailureBody>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SimulatedFailureBody {
+    class: String,
+    code: String,
+    message: String,
+}
+
 #[derive(Debug, Deserialize)]
 struct TraceJobPayload {
     trace: Option<TracePayloadBody>,
@@ -42,10 +54,16 @@ pub(super) fn parse_notification_payload(payload: Option<&[u8]>) -> Option<Notif
     })
 }
 
+/// Accepts both the raw `simulate-failure:class:code:message` text and the JSON
+/// `simulated_failure` object that test notifications attach alongside their content.
 pub(super) fn parse_simulated_failure(payload: Option<&[u8]>) -> Option<JobExecutionError> {
     let payload = payload?;
-    let text = std::str::from_utf8(payload).ok()?;
+    if let Ok(parsed) = serde_json::from_slice::<SimulatedFailureJobPayload>(payload) {
+        let failure = parsed.simulated_failure?;
+        return simulated_failure(&failure.class, failure.code.trim(), failure.message.trim());
+    }
 
+    let text = std::str::from_utf8(payload).ok()?;
     let mut parts = text.splitn(4, ':');
     if parts.next()? != "simulate-failure" {
         return None;
@@ -55,6 +73,10 @@ pub(super) fn parse_simulated_failure(payload: Option<&[u8]>) -> Option<JobExecu
     let code = parts.next()?.trim();
     let message = parts.next()?.trim();
 
+    simulated_failure(class, code, message)
+}
+
+fn simulated_failure(class: &str, code: &str, message: &str) -> Option<JobExecutionError> {
     match class {
         "transient" => Some(JobExecutionError::transient(code, message)),
         "permanent" => Some(JobExecutionError::permanent(code, message)),
@@ -96,6 +118,16 @@ mod tests {
         assert_eq!(permanent.code, "FATAL");
     }
 
+    #[test]
+    fn simulated_failures_are_parsed_from_json_payloads() {
+        let payload = br#"{"notification":{"title":"t","body":"b"},"simulated_failure":{"class":"transient","code":"SIMULATED_FAILURE","message":"retry"}}"#;
+        let transient = parse_simulated_failure(Some(payload)).expect("transient error");
+        assert_eq!(transient.code, "SIMULATED_FAILURE");
+
+        let payload = br#"{"notification":{"title":"t","body":"b"}}"#;
+        assert!(parse_simulated_failure(Some(payload)).is_none());
+    }
+
     #[test]
     fn extracts_request_id_from_trace_payload() {
         let payload = br#"{"trace":{"request_id":"req-123"}} "#;
@@ -1,25 +1,36 @@
 use std::collections::HashMap;
+use std::time::{Duration, Instant};
 
+use chrono::Utc;
+use serde_json::Value;
 use shared::enclave::EncryptedAutomationNotificationEnvelope;
-use shared::repos::{AuditResult, ClaimedJob, Store};
+use shared::events::{DomainEvent, EventBus};
+use shared::models::AuditMetadata;
+use shared::repos::{
+    ClaimedJob, JobStageTimings, JobType, NewNotificationDelivery, NotificationDeliveryOutcome,
+    Store,
+};
 use tracing::warn;
 
 use crate::{
     FailureClass, JobExecutionError, NotificationContent, PushPayloadMode, PushSendError,
-    PushSender, WorkerTickMetrics, apns_environment_label,
+    WorkerTickMetrics, apns_environment_label, device_backlog, device_health,
 };
 
 mod automation;
 mod context;
+mod deadline;
+mod departure;
 mod helpers;
 
 pub(crate) use context::JobActionContext;
-pub(super) use context::JobActionResult;
+pub(super) use context::{DepartureAlertDelivery, JobActionResult};
 
 pub(super) async fn dispatch_job_action(
     context: JobActionContext<'_>,
     job: &ClaimedJob,
     metrics: &mut WorkerTickMetrics,
+    timings: &mut JobStageTimings,
 ) -> Result<(), JobExecutionError> {
     if let Some(simulated_failure) =
         helpers::parse_simulated_failure(job.payload_ciphertext.as_deref())
@@ -27,81 +38,143 @@ pub(super) async fn dispatch_job_action(
         return Err(simulated_failure);
     }
     let request_id = helpers::extract_request_id(job.payload_ciphertext.as_deref());
+    // Automation runs must finish before the job lease can expire, so a slow enclave call
+    // never leaves a second worker running the same job.
+    let deadline = matches!(job.job_type, JobType::AutomationRun).then(|| {
+        deadline::AutomationDeadline::after(Duration::from_secs(
+            context.config.automation_max_runtime_seconds,
+        ))
+    });
 
     let mut action = if let Some(content) =
         helpers::parse_notification_payload(job.payload_ciphertext.as_deref())
     {
-        let mut metadata = HashMap::new();
-        metadata.insert(
-            "action_source".to_string(),
-            "payload_notification".to_string(),
-        );
+        let mut metadata = AuditMetadata::new();
+        metadata.insert("action_source".to_string(), "payload_notification".into());
         JobActionResult {
             notification: Some(content),
             encrypted_envelopes_by_device: HashMap::new(),
+            departure_alert_delivery: None,
             metadata,
         }
     } else {
-        automation::resolve_job_action(&context, job).await?
+        match job.job_type {
+            JobType::AutomationRun => {
+                let generation = automation::resolve_job_action(&context, job, timings);
+                match deadline {
+                    Some(deadline) => deadline.generate(generation).await?,
+                    None => generation.await?,
+                }
+            }
+            JobType::DepartureAlert => {
+                departure::resolve_job_action(&context, job, timings).await?
+            }
+        }
     };
 
     action
         .metadata
-        .insert("job_id".to_string(), job.id.to_string());
+        .insert("job_id".to_string(), job.id.to_string().into());
     action
         .metadata
-        .insert("job_type".to_string(), job.job_type.as_str().to_string());
+        .insert("job_type".to_string(), job.job_type.as_str().into());
     if let Some(request_id) = request_id {
-        action.metadata.insert("request_id".to_string(), request_id);
+        action
+            .metadata
+            .insert("request_id".to_string(), request_id.into());
+    }
+    if let Some(enclave_request_id) = action
+        .metadata
+        .get("enclave_request_id")
+        .and_then(Value::as_str)
+        && let Err(err) = context
+            .store
+            .record_job_enclave_request(job.id, enclave_request_id)
+            .await
+    {
+        warn!(
+            job_id = %job.id,
+            enclave_request_id = %enclave_request_id,
+            "failed to record job enclave request: {err}"
+        );
     }
 
     let Some(content) = action.notification.as_ref() else {
         let mut metadata = action.metadata.clone();
-        metadata.insert("outcome".to_string(), "no_notification".to_string());
+        metadata.insert("outcome".to_string(), "no_notification".into());
 
-        record_notification_audit(
-            context.store,
+        publish_job_event(
+            context.events,
             job.user_id,
-            "JOB_ACTION_SKIPPED",
-            AuditResult::Success,
-            metadata,
+            DomainEvent::JobActionSkipped {
+                job_id: job.id,
+                details: metadata,
+            },
         )
         .await;
 
         return Ok(());
     };
 
-    record_notification_audit(
-        context.store,
+    publish_job_event(
+        context.events,
         job.user_id,
-        "JOB_ACTION_GENERATED",
-        AuditResult::Success,
-        action.metadata.clone(),
+        DomainEvent::JobActionGenerated {
+            job_id: job.id,
+            details: action.metadata.clone(),
+        },
     )
     .await;
 
-    send_notification_to_devices(
-        context.store,
-        context.push_sender,
+    if let Some(deadline) = deadline {
+        deadline.check_before_delivery()?;
+    }
+    let deliver_started = Instant::now();
+    let delivery = send_notification_to_devices(
+        &context,
         job,
         content,
         &action.encrypted_envelopes_by_device,
         &action.metadata,
         metrics,
     )
-    .await
+    .await;
+    timings.deliver_ms = Some(deliver_started.elapsed().as_millis() as u64);
+    delivery?;
+
+    if let Some(delivery) = action.departure_alert_delivery
+        && let Err(err) = context
+            .store
+            .record_departure_alert_delivery(
+                job.user_id,
+                delivery.local_date,
+                delivery.meeting_fingerprint.as_str(),
+                delivery.leave_by,
+            )
+            .await
+    {
+        warn!(
+            job_id = %job.id,
+            user_id = %job.user_id,
+            "failed to record departure alert delivery: {err}"
+        );
+    }
+
+    Ok(())
 }
 
 async fn send_notification_to_devices(
-    store: &Store,
-    push_sender: &PushSender,
+    context: &JobActionContext<'_>,
     job: &ClaimedJob,
     content: &NotificationContent,
     encrypted_envelopes_by_device: &HashMap<String, EncryptedAutomationNotificationEnvelope>,
-    metadata_base: &HashMap<String, String>,
+    metadata_base: &AuditMetadata,
     metrics: &mut WorkerTickMetrics,
 ) -> Result<(), JobExecutionError> {
-    let request_id = metadata_base.get("request_id").map(String::as_str);
+    let store = context.store;
+    let events = context.events;
+    let push_sender = context.push_sender;
+    let request_id = metadata_base.get("request_id").and_then(Value::as_str);
     let devices = store
         .list_registered_devices(job.user_id)
         .await
@@ -119,11 +192,57 @@ async fn send_notification_to_devices(
         ));
     }
 
-    let mut delivered = 0_usize;
+    let now = Utc::now();
+    let mut delivered_devices = Vec::new();
+    let mut suppressed_devices = 0_usize;
+    let mut disabled_devices = 0_usize;
     let mut first_transient_error: Option<JobExecutionError> = None;
     let mut first_permanent_error: Option<JobExecutionError> = None;
 
     for device in &devices {
+        if device_backlog::is_backlog_for_device(
+            job.due_at,
+            device.registered_at,
+            now,
+            context.config.new_device_backlog_max_age_seconds,
+        ) {
+            suppressed_devices += 1;
+            metrics.push_backlog_suppressed += 1;
+            let summary_sent =
+                device_backlog::deliver_backlog_summary(store, push_sender, job.user_id, device)
+                    .await;
+            if summary_sent {
+                record_device_delivery(store, job.user_id, &device.device_id).await;
+            }
+            record_delivery_attempt(
+                store,
+                job,
+                metadata_base,
+                &device.device_id,
+                NotificationDeliveryOutcome::SuppressedBacklog,
+                None,
+                None,
+            )
+            .await;
+
+            let mut metadata = metadata_base.clone();
+            metadata.insert("device_id".to_string(), device.device_id.clone().into());
+            metadata.insert("outcome".to_string(), "suppressed_backlog".into());
+            metadata.insert("backlog_summary_sent".to_string(), summary_sent.into());
+            publish_job_event(
+                events,
+                job.user_id,
+                DomainEvent::NotificationDeliveryAttempted {
+                    job_id: job.id,
+                    device_id: device.device_id.clone(),
+                    outcome: NotificationDeliveryOutcome::SuppressedBacklog,
+                    details: metadata,
+                },
+            )
+            .await;
+            continue;
+        }
+
         metrics.push_attempts += 1;
         let mut content_for_device = content.clone();
         if let Some(envelope) = encrypted_envelopes_by_device.get(&device.device_id) {
@@ -132,27 +251,47 @@ async fn send_notification_to_devices(
 
         match push_sender.send(device, &content_for_device).await {
             Ok(payload_mode) => {
-                delivered += 1;
+                delivered_devices.push(device);
                 metrics.push_delivered += 1;
+                metrics.record_push_delivery_latency(
+                    job.due_at,
+                    Utc::now(),
+                    job.attempts,
+                    context.config.push_delivery_slo_target_seconds,
+                );
+                record_device_delivery(store, job.user_id, &device.device_id).await;
+                record_delivery_attempt(
+                    store,
+                    job,
+                    metadata_base,
+                    &device.device_id,
+                    NotificationDeliveryOutcome::Delivered,
+                    Some(payload_mode.as_str()),
+                    None,
+                )
+                .await;
 
                 let mut metadata = metadata_base.clone();
-                metadata.insert("device_id".to_string(), device.device_id.clone());
+                metadata.insert("device_id".to_string(), device.device_id.clone().into());
                 metadata.insert(
                     "environment".to_string(),
-                    apns_environment_label(&device.environment).to_string(),
+                    apns_environment_label(&device.environment).into(),
                 );
                 metadata.insert(
                     "push_payload_mode".to_string(),
-                    payload_mode.as_str().to_string(),
+                    payload_mode.as_str().into(),
                 );
-                metadata.insert("outcome".to_string(), "delivered".to_string());
+                metadata.insert("outcome".to_string(), "delivered".into());
 
-                record_notification_audit(
-                    store,
+                publish_job_event(
+                    events,
                     job.user_id,
-                    "NOTIFICATION_DELIVERY_ATTEMPT",
-                    AuditResult::Success,
-                    metadata,
+                    DomainEvent::NotificationDeliveryAttempted {
+                        job_id: job.id,
+                        device_id: device.device_id.clone(),
+                        outcome: NotificationDeliveryOutcome::Delivered,
+                        details: metadata,
+                    },
                 )
                 .await;
             }
@@ -168,28 +307,49 @@ async fn send_notification_to_devices(
                     }
                 };
 
+                let push_payload_mode = match content_for_device.encrypted_envelope.as_ref() {
+                    Some(_) => PushPayloadMode::Encrypted,
+                    None => PushPayloadMode::Fallback,
+                };
+                record_delivery_attempt(
+                    store,
+                    job,
+                    metadata_base,
+                    &device.device_id,
+                    NotificationDeliveryOutcome::Failed,
+                    Some(push_payload_mode.as_str()),
+                    Some(error_code.as_str()),
+                )
+                .await;
+
                 let mut metadata = metadata_base.clone();
-                metadata.insert("device_id".to_string(), device.device_id.clone());
+                metadata.insert("device_id".to_string(), device.device_id.clone().into());
                 metadata.insert(
                     "environment".to_string(),
-                    apns_environment_label(&device.environment).to_string(),
+                    apns_environment_label(&device.environment).into(),
                 );
                 metadata.insert(
                     "push_payload_mode".to_string(),
-                    match content_for_device.encrypted_envelope.as_ref() {
-                        Some(_) => PushPayloadMode::Encrypted.as_str().to_string(),
-                        None => PushPayloadMode::Fallback.as_str().to_string(),
-                    },
+                    push_payload_mode.as_str().into(),
                 );
-                metadata.insert("outcome".to_string(), "failed".to_string());
-                metadata.insert("error_code".to_string(), error_code.clone());
+                metadata.insert("outcome".to_string(), "failed".into());
+                metadata.insert("error_code".to_string(), error_code.clone().into());
 
-                record_notification_audit(
-                    store,
+                if device_health::record_push_failure(store, job.user_id, &device.device_id, &err)
+                    .await
+                {
+                    disabled_devices += 1;
+                }
+
+                publish_job_event(
+                    events,
                     job.user_id,
-                    "NOTIFICATION_DELIVERY_ATTEMPT",
-                    AuditResult::Failure,
-                    metadata,
+                    DomainEvent::NotificationDeliveryAttempted {
+                        job_id: job.id,
+                        device_id: device.device_id.clone(),
+                        outcome: NotificationDeliveryOutcome::Failed,
+                        details: metadata,
+                    },
                 )
                 .await;
 
@@ -216,7 +376,11 @@ async fn send_notification_to_devices(
         }
     }
 
-    if delivered > 0 {
+    if disabled_devices > 0 && !delivered_devices.is_empty() {
+        device_health::notify_device_disabled(push_sender, job.user_id, &delivered_devices).await;
+    }
+
+    if !delivered_devices.is_empty() || suppressed_devices > 0 {
         return Ok(());
     }
 
@@ -234,23 +398,56 @@ async fn send_notification_to_devices(
     ))
 }
 
-async fn record_notification_audit(
+async fn publish_job_event(events: &EventBus, user_id: uuid::Uuid, event: DomainEvent) {
+    let event_name = event.name();
+    if let Err(err) = events.publish(user_id, event).await {
+        warn!(
+            user_id = %user_id,
+            event = event_name,
+            "failed to publish job event: {err}"
+        );
+    }
+}
+
+async fn record_device_delivery(store: &Store, user_id: uuid::Uuid, device_id: &str) {
+    if let Err(err) = store.record_device_delivery(user_id, device_id).await {
+        warn!(
+            user_id = %user_id,
+            device_id = %device_id,
+            "failed to record device delivery: {err}"
+        );
+    }
+}
+
+/// Persists the per-device outcome so the job's execution trail can be assembled later.
+/// Failures are logged only; the audit event already records the attempt.
+async fn record_delivery_attempt(
     store: &Store,
-    user_id: uuid::Uuid,
-    event_type: &str,
-    result: AuditResult,
-    metadata: HashMap<String, String>,
+    job: &ClaimedJob,
+    metadata_base: &AuditMetadata,
+    device_id: &str,
+    outcome: NotificationDeliveryOutcome,
+    push_payload_mode: Option<&str>,
+    error_code: Option<&str>,
 ) {
-    let request_id = metadata.get("request_id").map(String::as_str);
-    if let Err(err) = store
-        .add_audit_event(user_id, event_type, None, result, &metadata)
-        .await
-    {
+    let delivery = NewNotificationDelivery {
+        user_id: job.user_id,
+        job_id: job.id,
+        job_attempt: job.attempts,
+        enclave_request_id: metadata_base
+            .get("enclave_request_id")
+            .and_then(Value::as_str),
+        device_id,
+        outcome,
+        push_payload_mode,
+        error_code,
+    };
+    if let Err(err) = store.record_notification_delivery(&delivery).await {
         warn!(
-            user_id = %user_id,
-            event_type = %event_type,
-            request_id = ?request_id,
-            "failed to persist notification audit event: {err}"
+            job_id = %job.id,
+            user_id = %job.user_id,
+            device_id = %device_id,
+            "failed to record notification delivery: {err}"
         );
     }
 }
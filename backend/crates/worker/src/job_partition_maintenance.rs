@@ -0,0 +1,66 @@
+use chrono::{Duration as ChronoDuration, Utc};
+use shared::config::WorkerConfig;
+use shared::repos::Store;
+use tracing::{debug, error, info};
+use uuid::Uuid;
+
+const FINISHED_JOB_PARTITION_MONTHS_AHEAD: u32 = 2;
+
+pub(crate) async fn maintain_finished_job_partitions(
+    store: &Store,
+    config: &WorkerConfig,
+    worker_id: Uuid,
+) {
+    let now = Utc::now();
+    match store
+        .ensure_finished_job_partitions(now, FINISHED_JOB_PARTITION_MONTHS_AHEAD)
+        .await
+    {
+        Ok(created) if !created.is_empty() => {
+            info!(
+                worker_id = %worker_id,
+                created_partitions = ?created,
+                "created finished job partitions"
+            );
+        }
+        Ok(_) => {}
+        Err(err) => {
+            error!(
+                worker_id = %worker_id,
+                "failed to create finished job partitions: {err}"
+            );
+        }
+    }
+
+    let cutoff = now - ChronoDuration::days(i64::from(config.job_history_retention_days));
+    let counts = match store.prune_finished_job_partitions(cutoff).await {
+        Ok(counts) => counts,
+        Err(err) => {
+            error!(
+                worker_id = %worker_id,
+                "failed to prune finished job partitions: {err}"
+            );
+            return;
+        }
+    };
+
+    if !counts.dropped_partitions.is_empty()
+        || counts.purged_default_rows > 0
+        || counts.purged_delivery_rows > 0
+    {
+        info!(
+            worker_id = %worker_id,
+            dropped_partitions = ?counts.dropped_partitions,
+            purged_default_rows = counts.purged_default_rows,
+            purged_delivery_rows = counts.purged_delivery_rows,
+            retention_days = config.job_history_retention_days,
+            "finished job partition prune tick"
+        );
+    } else {
+        debug!(
+            worker_id = %worker_id,
+            retention_days = config.job_history_retention_days,
+            "finished job partition prune tick found nothing to drop"
+        );
+    }
+}
@@ -1,15 +1,25 @@
-use chrono::{Duration as ChronoDuration, Utc};
+use std::time::Instant;
+
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde_json::json;
 use shared::config::WorkerConfig;
 use shared::enclave::EnclaveRpcClient;
-use shared::repos::{ClaimedJob, JobType, Store};
-use tracing::{error, info, warn};
+use shared::events::EventBus;
+use shared::repos::{ClaimedJob, JobStageTimings, JobType, Store};
+use shared::webhooks::WebhookEventType;
+use tracing::{Instrument, error, info, info_span, warn};
 use uuid::Uuid;
 
+use crate::automation_failures::{AUTOMATION_RUN_TIMED_OUT, record_automation_run_failure};
 use crate::automation_runs::AutomationRunJobPayload;
+use crate::component_availability;
+use crate::push_delivery_slo;
+use crate::webhook_delivery::enqueue_webhook_event;
 use crate::{FailureClass, JobExecutionError, PushSender, WorkerTickMetrics, retry_delay_seconds};
 
 struct JobRuntime<'a> {
     store: &'a Store,
+    events: &'a EventBus,
     config: &'a WorkerConfig,
     push_sender: &'a PushSender,
     enclave_client: &'a EnclaveRpcClient,
@@ -17,6 +27,7 @@ struct JobRuntime<'a> {
 
 pub(crate) async fn process_due_jobs(
     store: &Store,
+    events: &EventBus,
     config: &WorkerConfig,
     push_sender: &PushSender,
     enclave_client: &EnclaveRpcClient,
@@ -24,12 +35,14 @@ pub(crate) async fn process_due_jobs(
 ) {
     let runtime = JobRuntime {
         store,
+        events,
         config,
         push_sender,
         enclave_client,
     };
 
     let now = Utc::now();
+    let claim_started = Instant::now();
     let claimed_jobs = match runtime
         .store
         .claim_due_jobs(
@@ -47,6 +60,7 @@ pub(crate) async fn process_due_jobs(
             return;
         }
     };
+    let claim_ms = claim_started.elapsed().as_millis() as u64;
 
     let mut metrics = WorkerTickMetrics {
         claimed_jobs: claimed_jobs.len(),
@@ -55,7 +69,16 @@ pub(crate) async fn process_due_jobs(
 
     for job in claimed_jobs {
         metrics.record_lag(job.due_at, now);
-        process_claimed_job(&runtime, worker_id, job, &mut metrics).await;
+        let span = info_span!(
+            "job",
+            job_id = %job.id,
+            user_id = %job.user_id,
+            job_type = job.job_type.as_str(),
+            attempt = job.attempts.saturating_add(1)
+        );
+        process_claimed_job(&runtime, worker_id, job, &mut metrics, claim_ms)
+            .instrument(span)
+            .await;
     }
 
     let due_count = runtime.store.count_due_jobs(Utc::now()).await.unwrap_or(-1);
@@ -73,11 +96,23 @@ pub(crate) async fn process_due_jobs(
         push_delivered = metrics.push_delivered,
         push_transient_failures = metrics.push_transient_failures,
         push_permanent_failures = metrics.push_permanent_failures,
+        push_backlog_suppressed = metrics.push_backlog_suppressed,
+        push_delivered_within_slo = metrics.push_delivered_within_slo,
         average_lag_seconds = metrics.average_lag_seconds(),
         max_lag_seconds = metrics.max_lag_seconds,
+        automation_timeouts = metrics.automation_timeouts,
         success_rate = metrics.success_rate(),
         "worker tick metrics"
     );
+
+    component_availability::record_tick_availability(runtime.store, &metrics, worker_id).await;
+    push_delivery_slo::evaluate_push_delivery_slo(
+        runtime.store,
+        runtime.config,
+        &metrics,
+        worker_id,
+    )
+    .await;
 }
 
 async fn process_claimed_job(
@@ -85,13 +120,64 @@ async fn process_claimed_job(
     worker_id: Uuid,
     job: ClaimedJob,
     metrics: &mut WorkerTickMetrics,
+    claim_ms: u64,
 ) {
     metrics.processed_jobs += 1;
+    let started_at = Utc::now();
+    let mut timings = JobStageTimings {
+        claim_ms,
+        ..JobStageTimings::default()
+    };
+
+    let outcome = execute_job(runtime, &job, metrics, &mut timings).await;
+    if matches!(job.job_type, JobType::AutomationRun) {
+        metrics.automation_attempts += 1;
+        if outcome.is_ok() {
+            metrics.automation_successes += 1;
+        }
+    }
+    settle_job(runtime, worker_id, &job, outcome, metrics).await;
+    record_job_stage_timings(runtime, worker_id, &job, started_at, &timings).await;
+}
 
-    match execute_job(runtime, &job, metrics).await {
+async fn record_job_stage_timings(
+    runtime: &JobRuntime<'_>,
+    worker_id: Uuid,
+    job: &ClaimedJob,
+    started_at: DateTime<Utc>,
+    timings: &JobStageTimings,
+) {
+    let finished_at = Utc::now();
+    info!(
+        claim_ms = timings.claim_ms,
+        fetch_ms = timings.fetch_ms,
+        generate_ms = timings.generate_ms,
+        deliver_ms = timings.deliver_ms,
+        total_ms = (finished_at - started_at).num_milliseconds(),
+        "job stage timings"
+    );
+
+    if let Err(err) = runtime
+        .store
+        .record_job_stage_timings(job.id, worker_id, started_at, finished_at, timings)
+        .await
+    {
+        warn!(job_id = %job.id, "failed to persist job stage timings: {err}");
+    }
+}
+
+async fn settle_job(
+    runtime: &JobRuntime<'_>,
+    worker_id: Uuid,
+    job: &ClaimedJob,
+    outcome: Result<(), JobExecutionError>,
+    metrics: &mut WorkerTickMetrics,
+) {
+    match outcome {
         Ok(()) => match runtime.store.mark_job_done(job.id, worker_id).await {
             Ok(true) => {
                 metrics.successful_jobs += 1;
+                queue_automation_run_completed_webhook(runtime, job, None).await;
             }
             Ok(false) => {
                 warn!(
@@ -167,13 +253,21 @@ async fn process_claimed_job(
             } else {
                 match runtime
                     .store
-                    .mark_job_failed(&job, worker_id, next_attempt, &err.code, &err.message)
+                    .mark_job_failed(job, worker_id, next_attempt, &err.code, &err.message)
                     .await
                 {
                     Ok(true) => {
                         metrics.permanent_failures += 1;
                         metrics.dead_lettered_jobs += 1;
-                        mark_automation_run_failed_if_needed(runtime, &job).await;
+                        record_automation_run_failure(
+                            runtime.store,
+                            runtime.push_sender,
+                            runtime.config.automation_failure_pause_threshold,
+                            job,
+                            &err.code,
+                        )
+                        .await;
+                        queue_automation_run_completed_webhook(runtime, job, Some(&err.code)).await;
                         warn!(
                             worker_id = %worker_id,
                             job_id = %job.id,
@@ -204,42 +298,41 @@ async fn process_claimed_job(
     }
 }
 
-async fn mark_automation_run_failed_if_needed(runtime: &JobRuntime<'_>, job: &ClaimedJob) {
+/// Fires once per run when it reaches a final state: delivered, or dead-lettered with
+/// `error_code`. Jobs without a run payload (test notifications) are not reported.
+async fn queue_automation_run_completed_webhook(
+    runtime: &JobRuntime<'_>,
+    job: &ClaimedJob,
+    error_code: Option<&str>,
+) {
     if !matches!(job.job_type, JobType::AutomationRun) {
         return;
     }
-
     let Ok(payload) = AutomationRunJobPayload::parse(job.payload_ciphertext.as_deref()) else {
         return;
     };
 
-    match runtime
-        .store
-        .mark_automation_run_failed(payload.automation_run_id, job.user_id)
-        .await
-    {
-        Ok(true) => {}
-        Ok(false) => {
-            warn!(
-                job_id = %job.id,
-                run_id = %payload.automation_run_id,
-                "automation run state update skipped"
-            );
-        }
-        Err(err) => {
-            error!(
-                job_id = %job.id,
-                run_id = %payload.automation_run_id,
-                "failed to mark automation run failed: {err}"
-            );
-        }
-    }
+    enqueue_webhook_event(
+        runtime.store,
+        job.user_id,
+        WebhookEventType::AutomationRunCompleted,
+        json!({
+            "automation_run_id": payload.automation_run_id,
+            "automation_rule_id": payload.automation_rule_id,
+            "job_id": job.id,
+            "scheduled_for": payload.scheduled_for,
+            "outcome": if error_code.is_some() { "failed" } else { "succeeded" },
+            "error_code": error_code,
+        }),
+    )
+    .await;
 }
 
 async fn execute_job(
     runtime: &JobRuntime<'_>,
     job: &ClaimedJob,
     metrics: &mut WorkerTickMetrics,
+    timings: &mut JobStageTimings,
 ) -> Result<(), JobExecutionError> {
     let has_action_lease = runtime
         .store
@@ -262,17 +355,31 @@ async fn execute_job(
         return Ok(());
     }
 
-    if let Err(err) = crate::job_actions::dispatch_job_action(
+    let result = crate::job_actions::dispatch_job_action(
         crate::job_actions::JobActionContext {
             store: runtime.store,
+            events: runtime.events,
+            config: runtime.config,
             push_sender: runtime.push_sender,
             enclave_client: runtime.enclave_client,
         },
         job,
         metrics,
+        timings,
     )
-    .await
+    .await;
+    if let Err(err) = &result
+        && err.code == AUTOMATION_RUN_TIMED_OUT
     {
+        metrics.automation_timeouts += 1;
+        warn!(
+            job_id = %job.id,
+            max_runtime_seconds = runtime.config.automation_max_runtime_seconds,
+            "automation run timed out"
+        );
+    }
+
+    if let Err(err) = result {
         if let Err(release_err) = runtime
             .store
             .release_outbound_action_idempotency(job.user_id, &job.idempotency_key, job.id)
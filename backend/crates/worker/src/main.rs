@@ -1,41 +1,82 @@
-use shared::config::{WorkerConfig, load_dotenv};
-use shared::enclave::EnclaveRpcClient;
+use std::sync::Arc;
+
+use shared::config::{ApnsAppConfig, WorkerConfig, load_dotenv};
+use shared::enclave::{EnclaveRpcClient, EnclaveRpcTls};
 use shared::enclave_runtime::{EnclaveRuntimeEndpointConfig, verify_connectivity};
+use shared::events::{AuditEventSink, EventBus, TracingEventSink};
+use shared::log_redaction::{LogFormat, redacting_log_layer};
+use shared::replay_guard::NonceReplayGuard;
 use shared::repos::Store;
+use shared::secrets::{SecretsResolver, resolve_env_secret_references, watch_secret_rotations};
 use shared::security::{KmsDecryptPolicy, SecretRuntime, TeeAttestationPolicy};
 use tokio::signal;
 use tokio::time::{self, Duration};
 use tracing::{error, info};
+use tracing_subscriber::EnvFilter;
+use tracing_subscriber::layer::SubscriberExt as _;
+use tracing_subscriber::util::SubscriberInitExt as _;
 use uuid::Uuid;
 
 mod assistant_session_purge;
+mod audit_retention;
+mod automation_failures;
 mod automation_runs;
+mod component_availability;
+mod connector_reauth;
+mod data_key_reencryption;
+mod departure_alerts;
+mod device_backlog;
+mod device_health;
+mod ephemeral_state_purge;
 mod job_actions;
+mod job_partition_maintenance;
 mod job_processing;
 mod privacy_delete;
 mod privacy_delete_revoke;
+mod privacy_export;
+mod push_delivery_slo;
 mod push_sender;
 mod retry;
+mod stale_devices;
+mod support_diagnostics_purge;
 mod types;
+mod webhook_delivery;
 
 use job_processing::process_due_jobs;
 pub(crate) use push_sender::{
-    NotificationContent, PushPayloadMode, PushSendError, PushSender, apns_environment_label,
+    DestinationRejection, NotificationContent, PushPayloadMode, PushSendError, PushSender,
+    apns_environment_label,
 };
 pub(crate) use retry::retry_delay_seconds;
 pub(crate) use types::{FailureClass, JobExecutionError, WorkerTickMetrics};
 
-#[tokio::main]
-async fn main() {
+fn main() {
     if let Err(err) = load_dotenv() {
         eprintln!("{err}");
         std::process::exit(1);
     }
 
-    tracing_subscriber::fmt()
-        .with_env_filter(std::env::var("RUST_LOG").unwrap_or_else(|_| "worker=debug".to_string()))
+    tracing_subscriber::registry()
+        .with(EnvFilter::new(
+            std::env::var("RUST_LOG").unwrap_or_else(|_| "worker=debug".to_string()),
+        ))
+        .with(redacting_log_layer(LogFormat::Text))
         .init();
 
+    let secrets = match resolve_env_secret_references() {
+        Ok(secrets) => secrets,
+        Err(err) => {
+            error!("failed to resolve secret references: {err}");
+            std::process::exit(1);
+        }
+    };
+    run(secrets);
+}
+
+#[tokio::main]
+async fn run(secrets: SecretsResolver) {
+    tokio::spawn(watch_secret_rotations(secrets));
+
     let config = match WorkerConfig::from_env() {
         Ok(cfg) => cfg,
         Err(err) => {
@@ -46,24 +87,31 @@ async fn main() {
 
     let store = match Store::connect(
         &config.database_url,
+        config.database_read_url.as_deref(),
         config.database_max_connections,
-        &config.data_encryption_key,
+        &config.data_encryption_keys,
     )
     .await
     {
-        Ok(store) => store,
+        Ok(store) => {
+            store
+                .with_audit_redaction_policy(config.audit_redaction_policy.clone())
+                .with_read_cache_from_config(&config.redis_url, config.store_read_cache_ttl_seconds)
+                .await
+        }
         Err(err) => {
             error!("failed to connect to postgres: {err}");
             std::process::exit(1);
         }
     };
 
-    let push_sender = match PushSender::new(
-        config.apns_key_id.clone(),
-        config.apns_team_id.clone(),
-        config.apns_topic.clone(),
-        config.apns_auth_key_p8.clone(),
-    ) {
+    let default_apns_app = ApnsAppConfig {
+        topic: config.apns_topic.clone(),
+        key_id: config.apns_key_id.clone(),
+        team_id: config.apns_team_id.clone(),
+        auth_key_p8: config.apns_auth_key_p8.clone(),
+    };
+    let push_sender = match PushSender::new(&default_apns_app, &config.apns_additional_apps) {
         Ok(sender) => sender,
         Err(err) => {
             error!("failed to initialize APNs sender: {err}");
@@ -85,7 +133,21 @@ async fn main() {
         base_url: config.enclave_runtime_base_url.clone(),
         probe_timeout_ms: config.enclave_runtime_probe_timeout_ms,
     };
-    if let Err(err) = verify_connectivity(&oauth_client, &enclave_runtime_config).await {
+    let enclave_rpc_tls = match config.enclave_rpc_client_tls.as_ref() {
+        Some(tls) => match EnclaveRpcTls::load(tls, Duration::from_secs(15)) {
+            Ok(tls) => Some(tls),
+            Err(err) => {
+                error!("failed to load enclave rpc client tls identity: {err}");
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
+    let enclave_probe_client = match enclave_rpc_tls.as_ref() {
+        Some(tls) => tls.attestation_client(),
+        None => oauth_client.clone().into(),
+    };
+    if let Err(err) = verify_connectivity(&enclave_probe_client, &enclave_runtime_config).await {
         error!(error = %err, "failed enclave runtime startup connectivity check");
         std::process::exit(1);
     }
@@ -112,14 +174,37 @@ async fn main() {
         config.tee_attestation_challenge_timeout_ms,
         oauth_client.clone(),
     );
+    let secret_runtime = secret_runtime.with_replay_guard(
+        NonceReplayGuard::default()
+            .with_redis_from_config(&config.redis_url)
+            .await,
+    );
+    let secret_runtime = match enclave_rpc_tls.as_ref() {
+        Some(tls) => secret_runtime.with_enclave_tls(tls),
+        None => secret_runtime,
+    };
     let enclave_client = EnclaveRpcClient::new(
         config.enclave_runtime_base_url.clone(),
         shared::enclave::EnclaveRpcAuthConfig {
-            shared_secret: config.enclave_rpc_shared_secret.clone(),
+            signing_key: config.enclave_rpc_signing_key.clone(),
             max_clock_skew_seconds: config.enclave_rpc_auth_max_skew_seconds,
         },
         oauth_client.clone(),
     );
+    let enclave_client = match enclave_rpc_tls.as_ref() {
+        Some(tls) => enclave_client.with_mtls(tls, secret_runtime.clone()),
+        None => enclave_client,
+    };
+    let store = if config.audit_event_signing_enabled {
+        store.with_audit_event_signer(Arc::new(enclave_client.clone()))
+    } else {
+        store
+    };
+
+    let events = EventBus::builder()
+        .inline_sink(AuditEventSink::new(store.clone()))
+        .background_sink(TracingEventSink)
+        .build();
 
     let worker_id = Uuid::new_v4();
     info!(
@@ -148,27 +233,74 @@ async fn main() {
                     worker_id,
                 )
                 .await;
+                data_key_reencryption::reencrypt_secondary_key_rows(
+                    &store,
+                    &config,
+                    worker_id,
+                )
+                .await;
+                job_partition_maintenance::maintain_finished_job_partitions(
+                    &store,
+                    &config,
+                    worker_id,
+                )
+                .await;
+                audit_retention::purge_expired_audit_events(
+                    &store,
+                    &config,
+                    worker_id,
+                )
+                .await;
+                support_diagnostics_purge::purge_expired_support_diagnostics(&store, worker_id)
+                    .await;
+                stale_devices::purge_stale_devices(&store, &config, worker_id).await;
+                ephemeral_state_purge::purge_expired_ephemeral_state(&store, &config, worker_id)
+                    .await;
+                connector_reauth::send_connector_reauth_nudges(
+                    &store,
+                    &config,
+                    &push_sender,
+                    worker_id,
+                )
+                .await;
                 privacy_delete::process_delete_requests(
                     &store,
                     &config,
                     &secret_runtime,
-                    &oauth_client,
+                    &enclave_client,
+                    &push_sender,
                     worker_id,
                 ).await;
+                privacy_export::process_export_requests(&store, worker_id).await;
+                automation_runs::resume_snoozed_automation_rules(&store, &config, worker_id)
+                    .await;
                 automation_runs::enqueue_due_automation_runs(
                     &store,
                     &config,
                     worker_id,
                 )
                 .await;
+                departure_alerts::enqueue_due_departure_checks(
+                    &store,
+                    &config,
+                    worker_id,
+                )
+                .await;
                 process_due_jobs(
                     &store,
+                    &events,
                     &config,
                     &push_sender,
                     &enclave_client,
                     worker_id,
                 )
                 .await;
+                webhook_delivery::deliver_due_webhooks(
+                    &store,
+                    &config,
+                    worker_id,
+                )
+                .await;
             }
         }
     }
@@ -1,13 +1,17 @@
-use std::collections::HashMap;
-
 use chrono::Utc;
+use serde_json::json;
 use shared::config::WorkerConfig;
-use shared::repos::{AuditResult, ClaimedDeleteRequest, Store};
+use shared::enclave::EnclaveRpcClient;
+use shared::models::AuditMetadata;
+use shared::repos::{AuditResult, ClaimedDeleteRequest, DeviceRegistration, Store, WebhookTarget};
 use shared::security::SecretRuntime;
+use shared::webhooks::{WebhookEvent, WebhookEventType};
 use tracing::{error, info, warn};
 use uuid::Uuid;
 
 use crate::privacy_delete_revoke::{DeleteRequestError, revoke_active_connectors};
+use crate::webhook_delivery::send_unlogged_webhooks;
+use crate::{NotificationContent, PushSender};
 
 #[derive(Default)]
 pub(crate) struct PrivacyDeleteTickMetrics {
@@ -19,11 +23,32 @@ pub(crate) struct PrivacyDeleteTickMetrics {
     pub overdue_requests: i64,
 }
 
+struct PrivacyDeleteContext<'a> {
+    store: &'a Store,
+    config: &'a WorkerConfig,
+    secret_runtime: &'a SecretRuntime,
+    enclave_client: &'a EnclaveRpcClient,
+    push_sender: &'a PushSender,
+    worker_id: Uuid,
+}
+
+struct DeleteRequestOutcome {
+    revoked_connectors: usize,
+    deleted_rows: Vec<(&'static str, u64)>,
+    /// Devices registered when the purge ran; kept in memory only so the confirmation push
+    /// can still reach them after their rows are gone.
+    confirmation_devices: Vec<DeviceRegistration>,
+    /// `privacy_delete.completed` subscribers, captured for the same reason. The event has
+    /// no delivery row to retry from, so it is sent best effort.
+    confirmation_webhooks: Vec<WebhookTarget>,
+}
+
 pub(crate) async fn process_delete_requests(
     store: &Store,
     config: &WorkerConfig,
     secret_runtime: &SecretRuntime,
-    oauth_client: &reqwest::Client,
+    enclave_client: &EnclaveRpcClient,
+    push_sender: &PushSender,
     worker_id: Uuid,
 ) -> PrivacyDeleteTickMetrics {
     let now = Utc::now();
@@ -51,17 +76,16 @@ pub(crate) async fn process_delete_requests(
         ..PrivacyDeleteTickMetrics::default()
     };
 
+    let context = PrivacyDeleteContext {
+        store,
+        config,
+        secret_runtime,
+        enclave_client,
+        push_sender,
+        worker_id,
+    };
     for request in claimed_requests {
-        process_claimed_delete_request(
-            store,
-            config,
-            secret_runtime,
-            oauth_client,
-            worker_id,
-            request,
-            &mut metrics,
-        )
-        .await;
+        process_claimed_delete_request(&context, request, &mut metrics).await;
     }
 
     metrics.pending_requests = store.count_pending_delete_requests().await.unwrap_or(-1);
@@ -97,16 +121,14 @@ pub(crate) async fn process_delete_requests(
 }
 
 async fn process_claimed_delete_request(
-    store: &Store,
-    config: &WorkerConfig,
-    secret_runtime: &SecretRuntime,
-    oauth_client: &reqwest::Client,
-    worker_id: Uuid,
+    context: &PrivacyDeleteContext<'_>,
     request: ClaimedDeleteRequest,
     metrics: &mut PrivacyDeleteTickMetrics,
 ) {
-    match execute_delete_request(store, config, secret_runtime, oauth_client, &request).await {
-        Ok(revoked_connectors) => {
+    let store = context.store;
+    let worker_id = context.worker_id;
+    match execute_delete_request(context, &request).await {
+        Ok(outcome) => {
             let completed_at = Utc::now();
             match store
                 .mark_delete_request_completed(request.id, worker_id, completed_at)
@@ -114,14 +136,33 @@ async fn process_claimed_delete_request(
             {
                 Ok(true) => {
                     metrics.completed_requests += 1;
-                    metrics.revoked_connectors += revoked_connectors;
+                    metrics.revoked_connectors += outcome.revoked_connectors;
+                    let notified_devices = send_delete_confirmation(
+                        context.push_sender,
+                        request.user_id,
+                        &outcome.confirmation_devices,
+                    )
+                    .await;
+                    let notified_webhooks = send_unlogged_webhooks(
+                        request.user_id,
+                        &outcome.confirmation_webhooks,
+                        &WebhookEvent::new(
+                            WebhookEventType::PrivacyDeleteCompleted,
+                            json!({
+                                "request_id": request.id,
+                                "completed_at": completed_at,
+                            }),
+                        ),
+                    )
+                    .await;
                     record_delete_completion_audit(
                         store,
-                        request.user_id,
-                        request.id,
+                        &request,
                         completed_at,
-                        revoked_connectors,
-                        config.privacy_delete_sla_hours,
+                        &outcome,
+                        notified_devices,
+                        notified_webhooks,
+                        context.config.privacy_delete_sla_hours,
                     )
                     .await;
                 }
@@ -183,12 +224,10 @@ async fn process_claimed_delete_request(
 }
 
 async fn execute_delete_request(
-    store: &Store,
-    config: &WorkerConfig,
-    secret_runtime: &SecretRuntime,
-    oauth_client: &reqwest::Client,
+    context: &PrivacyDeleteContext<'_>,
     request: &ClaimedDeleteRequest,
-) -> Result<usize, DeleteRequestError> {
+) -> Result<DeleteRequestOutcome, DeleteRequestError> {
+    let store = context.store;
     let active_connectors = store
         .list_active_connector_metadata(request.user_id)
         .await
@@ -198,41 +237,107 @@ async fn execute_delete_request(
 
     let revoked_connectors = revoke_active_connectors(
         store,
-        config,
-        secret_runtime,
-        oauth_client,
+        context.config,
+        context.secret_runtime,
+        context.enclave_client,
         request.user_id,
         active_connectors,
     )
     .await?;
 
-    store
+    // A missing device list only costs the confirmation push, so it must not block the purge.
+    let confirmation_devices = match store.list_registered_devices(request.user_id).await {
+        Ok(devices) => devices,
+        Err(err) => {
+            warn!(
+                worker_id = %context.worker_id,
+                request_id = %request.id,
+                "failed to load devices for delete confirmation: {err}"
+            );
+            Vec::new()
+        }
+    };
+
+    let confirmation_webhooks = match store
+        .list_webhook_targets(request.user_id, WebhookEventType::PrivacyDeleteCompleted)
+        .await
+    {
+        Ok(targets) => targets,
+        Err(err) => {
+            warn!(
+                worker_id = %context.worker_id,
+                request_id = %request.id,
+                "failed to load webhooks for delete confirmation: {err}"
+            );
+            Vec::new()
+        }
+    };
+
+    let deleted_rows = store
         .purge_user_operational_data(request.user_id)
         .await
         .map_err(|_err| {
             DeleteRequestError::new("PURGE_FAILED", "failed to purge user operational data")
         })?;
 
-    Ok(revoked_connectors)
+    Ok(DeleteRequestOutcome {
+        revoked_connectors,
+        deleted_rows,
+        confirmation_devices,
+        confirmation_webhooks,
+    })
+}
+
+/// Sends the final "all data deleted" push to the devices captured before the purge.
+/// Failures are not scored against device health because those rows no longer exist.
+async fn send_delete_confirmation(
+    push_sender: &PushSender,
+    user_id: Uuid,
+    devices: &[DeviceRegistration],
+) -> usize {
+    let content = NotificationContent::privacy_delete_confirmation();
+    let mut delivered_devices = 0_usize;
+    for device in devices {
+        match push_sender.send(device, &content).await {
+            Ok(_) => delivered_devices += 1,
+            Err(err) => warn!(
+                user_id = %user_id,
+                device_id = %device.device_id,
+                error_code = %err.code(),
+                "delete confirmation delivery failed"
+            ),
+        }
+    }
+
+    delivered_devices
 }
 
 async fn record_delete_completion_audit(
     store: &Store,
-    user_id: Uuid,
-    request_id: Uuid,
+    request: &ClaimedDeleteRequest,
     completed_at: chrono::DateTime<Utc>,
-    revoked_connectors: usize,
+    outcome: &DeleteRequestOutcome,
+    notified_devices: usize,
+    notified_webhooks: usize,
     sla_hours: u64,
 ) {
-    let mut metadata = HashMap::new();
-    metadata.insert("request_id".to_string(), request_id.to_string());
-    metadata.insert("status".to_string(), "COMPLETED".to_string());
-    metadata.insert("completed_at".to_string(), completed_at.to_rfc3339());
+    let user_id = request.user_id;
+    let request_id = request.id;
+    let mut metadata = AuditMetadata::new();
+    metadata.insert("request_id".to_string(), request_id.to_string().into());
+    metadata.insert("status".to_string(), "COMPLETED".into());
+    metadata.insert("completed_at".to_string(), completed_at.to_rfc3339().into());
     metadata.insert(
         "revoked_connectors".to_string(),
-        revoked_connectors.to_string(),
+        outcome.revoked_connectors.into(),
+    );
+    metadata.insert(
+        "deleted_rows".to_string(),
+        deleted_rows_metadata(&outcome.deleted_rows),
     );
-    metadata.insert("sla_hours".to_string(), sla_hours.to_string());
+    metadata.insert("notified_devices".to_string(), notified_devices.into());
+    metadata.insert("notified_webhooks".to_string(), notified_webhooks.into());
+    metadata.insert("sla_hours".to_string(), sla_hours.into());
 
     if let Err(err) = store
         .add_audit_event(
@@ -259,11 +364,11 @@ async fn record_delete_failure_audit(
     failed_at: chrono::DateTime<Utc>,
     failure_reason: &str,
 ) {
-    let mut metadata = HashMap::new();
-    metadata.insert("request_id".to_string(), request_id.to_string());
-    metadata.insert("status".to_string(), "FAILED".to_string());
-    metadata.insert("failed_at".to_string(), failed_at.to_rfc3339());
-    metadata.insert("reason".to_string(), failure_reason.to_string());
+    let mut metadata = AuditMetadata::new();
+    metadata.insert("request_id".to_string(), request_id.to_string().into());
+    metadata.insert("status".to_string(), "FAILED".into());
+    metadata.insert("failed_at".to_string(), failed_at.to_rfc3339().into());
+    metadata.insert("reason".to_string(), failure_reason.into());
 
     if let Err(err) = store
         .add_audit_event(
@@ -283,6 +388,14 @@ async fn record_delete_failure_audit(
     }
 }
 
+fn deleted_rows_metadata(deleted_rows: &[(&'static str, u64)]) -> serde_json::Value {
+    deleted_rows
+        .iter()
+        .map(|(table, count)| ((*table).to_string(), serde_json::Value::from(*count)))
+        .collect::<serde_json::Map<_, _>>()
+        .into()
+}
+
 fn format_failure_reason(err: &DeleteRequestError) -> String {
     let mut reason = format!("{}: {}", err.code, err.message);
     const MAX_REASON_LEN: usize = 350;
@@ -291,3 +404,17 @@ fn format_failure_reason(err: &DeleteRequestError) -> String {
     }
     reason
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::deleted_rows_metadata;
+
+    #[test]
+    fn deleted_rows_metadata_maps_tables_to_counts() {
+        let metadata = deleted_rows_metadata(&[("devices", 2), ("jobs", 0)]);
+
+        assert_eq!(metadata, json!({ "devices": 2, "jobs": 0 }));
+    }
+}
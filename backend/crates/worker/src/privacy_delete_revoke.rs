@@ -24,7 +24,7 @@ pub(crate) async fn revoke_active_connectors(
     store: &Store,
     config: &WorkerConfig,
     secret_runtime: &SecretRuntime,
-    oauth_client: &reqwest::Client,
+    enclave_client: &EnclaveRpcClient,
     user_id: Uuid,
     connectors: Vec<ActiveConnectorMetadata>,
 ) -> Result<usize, DeleteRequestError> {
@@ -35,7 +35,7 @@ pub(crate) async fn revoke_active_connectors(
             store,
             config,
             secret_runtime,
-            oauth_client,
+            enclave_client,
             user_id,
             connector,
         )
@@ -50,19 +50,24 @@ async fn revoke_single_connector(
     store: &Store,
     config: &WorkerConfig,
     _secret_runtime: &SecretRuntime,
-    oauth_client: &reqwest::Client,
+    enclave_client: &EnclaveRpcClient,
     user_id: Uuid,
     connector: ActiveConnectorMetadata,
 ) -> Result<(), DeleteRequestError> {
-    if connector.provider != "google" {
-        return Err(DeleteRequestError::new(
-            "UNSUPPORTED_CONNECTOR_PROVIDER",
-            format!("unsupported connector provider: {}", connector.provider),
-        ));
+    match connector.provider.as_str() {
+        "google" => {}
+        // CalDAV and IMAP passwords have no remote revoke; the stored credentials are removed
+        // with the rest of the user's data.
+        "caldav" | "imap" => return Ok(()),
+        _ => {
+            return Err(DeleteRequestError::new(
+                "UNSUPPORTED_CONNECTOR_PROVIDER",
+                format!("unsupported connector provider: {}", connector.provider),
+            ));
+        }
     }
 
     let connector = normalize_connector_metadata(store, config, user_id, connector).await?;
-    let enclave_client = build_enclave_client(config, oauth_client);
     let revoke_response = enclave_client
         .revoke_google_connector_token(ConnectorSecretRequest {
             user_id,
@@ -114,17 +119,6 @@ async fn normalize_connector_metadata(
     }
 }
 
-fn build_enclave_client(config: &WorkerConfig, oauth_client: &reqwest::Client) -> EnclaveRpcClient {
-    EnclaveRpcClient::new(
-        config.enclave_runtime_base_url.clone(),
-        shared::enclave::EnclaveRpcAuthConfig {
-            shared_secret: config.enclave_rpc_shared_secret.clone(),
-            max_clock_skew_seconds: config.enclave_rpc_auth_max_skew_seconds,
-        },
-        oauth_client.clone(),
-    )
-}
-
 fn map_revoke_enclave_error(err: EnclaveRpcError) -> DeleteRequestError {
     match err {
         EnclaveRpcError::DecryptNotAuthorized { .. } => DeleteRequestError::new(
@@ -151,13 +145,18 @@ fn map_revoke_enclave_error(err: EnclaveRpcError) -> DeleteRequestError {
             "GOOGLE_REVOKE_FAILED",
             "Google revoke endpoint returned an invalid response",
         ),
+        EnclaveRpcError::ProviderRateLimited { .. } => DeleteRequestError::new(
+            "GOOGLE_REVOKE_RATE_LIMITED",
+            "Google revoke call was deferred by the enclave outbound rate limit",
+        ),
         EnclaveRpcError::RpcUnauthorized { code }
         | EnclaveRpcError::RpcContractRejected { code } => DeleteRequestError::new(
             "ENCLAVE_RPC_REJECTED",
             format!("secure enclave rpc request rejected: {code}"),
         ),
         EnclaveRpcError::RpcTransportUnavailable { .. }
-        | EnclaveRpcError::RpcResponseInvalid { .. } => {
+        | EnclaveRpcError::RpcResponseInvalid { .. }
+        | EnclaveRpcError::LlmBudgetExceeded { .. } => {
             DeleteRequestError::new("ENCLAVE_RPC_UNAVAILABLE", "secure enclave rpc unavailable")
         }
     }
@@ -0,0 +1,251 @@
+use chrono::{DateTime, Duration, Utc};
+use serde_json::json;
+use shared::models::AuditMetadata;
+use shared::repos::{AuditResult, ClaimedExportRequest, Store};
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+/// Exports are user-initiated and rare; a small batch keeps archive assembly off the hot path.
+const PRIVACY_EXPORT_BATCH_SIZE: i64 = 5;
+const PRIVACY_EXPORT_LEASE_SECONDS: i64 = 300;
+/// Archives hold a full copy of the user's metadata, so they only live long enough to fetch.
+const PRIVACY_EXPORT_ARCHIVE_TTL_HOURS: i64 = 24;
+const PRIVACY_EXPORT_PURGE_BATCH_SIZE: i64 = 100;
+const PRIVACY_EXPORT_FORMAT_VERSION: u32 = 1;
+
+#[derive(Default)]
+pub(crate) struct PrivacyExportTickMetrics {
+    pub claimed_requests: usize,
+    pub completed_requests: usize,
+    pub failed_requests: usize,
+    pub expired_archives: u64,
+}
+
+pub(crate) async fn process_export_requests(
+    store: &Store,
+    worker_id: Uuid,
+) -> PrivacyExportTickMetrics {
+    let mut metrics = PrivacyExportTickMetrics {
+        expired_archives: purge_expired_archives(store, worker_id).await,
+        ..PrivacyExportTickMetrics::default()
+    };
+
+    let claimed_requests = match store
+        .claim_export_requests(
+            Utc::now(),
+            worker_id,
+            PRIVACY_EXPORT_BATCH_SIZE,
+            PRIVACY_EXPORT_LEASE_SECONDS,
+        )
+        .await
+    {
+        Ok(claimed_requests) => claimed_requests,
+        Err(err) => {
+            error!(
+                worker_id = %worker_id,
+                "failed to claim privacy export requests: {err}"
+            );
+            return metrics;
+        }
+    };
+    metrics.claimed_requests = claimed_requests.len();
+
+    for request in claimed_requests {
+        if process_claimed_export_request(store, worker_id, &request).await {
+            metrics.completed_requests += 1;
+        } else {
+            metrics.failed_requests += 1;
+        }
+    }
+
+    if metrics.claimed_requests > 0 || metrics.expired_archives > 0 {
+        info!(
+            worker_id = %worker_id,
+            claimed_requests = metrics.claimed_requests,
+            completed_requests = metrics.completed_requests,
+            failed_requests = metrics.failed_requests,
+            expired_archives = metrics.expired_archives,
+            "privacy export tick metrics"
+        );
+    }
+
+    metrics
+}
+
+async fn process_claimed_export_request(
+    store: &Store,
+    worker_id: Uuid,
+    request: &ClaimedExportRequest,
+) -> bool {
+    let archive = match build_export_archive(store, request).await {
+        Ok(archive) => archive,
+        Err(reason) => {
+            let failed_at = Utc::now();
+            match store
+                .mark_export_request_failed(request.id, worker_id, failed_at, reason)
+                .await
+            {
+                Ok(true) => {
+                    record_export_audit(
+                        store,
+                        request,
+                        "PRIVACY_EXPORT_FAILED",
+                        AuditResult::Failure,
+                        &[("reason", reason.into())],
+                    )
+                    .await;
+                }
+                Ok(false) => warn!(
+                    worker_id = %worker_id,
+                    request_id = %request.id,
+                    "export request failure update skipped because lease ownership was lost"
+                ),
+                Err(err) => error!(
+                    worker_id = %worker_id,
+                    request_id = %request.id,
+                    "failed to mark export request failed: {err}"
+                ),
+            }
+            return false;
+        }
+    };
+
+    let completed_at = Utc::now();
+    let expires_at = completed_at + Duration::hours(PRIVACY_EXPORT_ARCHIVE_TTL_HOURS);
+    match store
+        .mark_export_request_completed(request.id, worker_id, &archive, completed_at, expires_at)
+        .await
+    {
+        Ok(true) => {
+            record_export_audit(
+                store,
+                request,
+                "PRIVACY_EXPORT_COMPLETED",
+                AuditResult::Success,
+                &[
+                    ("archive_size_bytes", archive.len().into()),
+                    ("expires_at", expires_at.to_rfc3339().into()),
+                ],
+            )
+            .await;
+            true
+        }
+        Ok(false) => {
+            warn!(
+                worker_id = %worker_id,
+                request_id = %request.id,
+                "export request completion skipped because lease ownership was lost"
+            );
+            false
+        }
+        Err(err) => {
+            error!(
+                worker_id = %worker_id,
+                request_id = %request.id,
+                "failed to store export archive: {err}"
+            );
+            false
+        }
+    }
+}
+
+async fn build_export_archive(
+    store: &Store,
+    request: &ClaimedExportRequest,
+) -> Result<Vec<u8>, &'static str> {
+    let data = store
+        .collect_user_export_data(request.user_id)
+        .await
+        .map_err(|_| "EXPORT_COLLECT_FAILED: failed to collect user data")?;
+
+    serde_json::to_vec(&export_archive_document(request, Utc::now(), data))
+        .map_err(|_| "EXPORT_SERIALIZE_FAILED: failed to serialize export archive")
+}
+
+fn export_archive_document(
+    request: &ClaimedExportRequest,
+    generated_at: DateTime<Utc>,
+    data: serde_json::Value,
+) -> serde_json::Value {
+    json!({
+        "format_version": PRIVACY_EXPORT_FORMAT_VERSION,
+        "request_id": request.id,
+        "requested_at": request.created_at,
+        "generated_at": generated_at,
+        "data": data,
+    })
+}
+
+async fn purge_expired_archives(store: &Store, worker_id: Uuid) -> u64 {
+    match store
+        .purge_expired_export_archives_batch(Utc::now(), PRIVACY_EXPORT_PURGE_BATCH_SIZE)
+        .await
+    {
+        Ok(purged) => purged,
+        Err(err) => {
+            error!(
+                worker_id = %worker_id,
+                "failed to purge expired privacy export archives: {err}"
+            );
+            0
+        }
+    }
+}
+
+async fn record_export_audit(
+    store: &Store,
+    request: &ClaimedExportRequest,
+    event_type: &str,
+    result: AuditResult,
+    fields: &[(&str, serde_json::Value)],
+) {
+    let mut metadata = AuditMetadata::new();
+    metadata.insert("request_id".to_string(), request.id.to_string().into());
+    for (key, value) in fields {
+        metadata.insert((*key).to_string(), value.clone());
+    }
+
+    if let Err(err) = store
+        .add_audit_event(request.user_id, event_type, None, result, &metadata)
+        .await
+    {
+        warn!(
+            user_id = %request.user_id,
+            request_id = %request.id,
+            "failed to persist {event_type} audit event: {err}"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use serde_json::json;
+    use shared::repos::ClaimedExportRequest;
+    use uuid::Uuid;
+
+    use super::export_archive_document;
+
+    #[test]
+    fn export_archive_document_wraps_collected_data() {
+        let request = ClaimedExportRequest {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            created_at: Utc
+                .with_ymd_and_hms(2026, 3, 1, 12, 0, 0)
+                .single()
+                .expect("valid test timestamp"),
+        };
+        let generated_at = Utc
+            .with_ymd_and_hms(2026, 3, 1, 12, 5, 0)
+            .single()
+            .expect("valid test timestamp");
+
+        let document = export_archive_document(&request, generated_at, json!({ "connectors": [] }));
+
+        assert_eq!(document["format_version"], 1);
+        assert_eq!(document["request_id"], json!(request.id));
+        assert_eq!(document["generated_at"], json!(generated_at));
+        assert_eq!(document["data"]["connectors"], json!([]));
+    }
+}
@@ -0,0 +1,131 @@
+use chrono::{DateTime, Duration, Utc};
+use shared::config::WorkerConfig;
+use shared::repos::{SloEvaluation, Store};
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::WorkerTickMetrics;
+
+/// Below this many deliveries in the window a handful of slow pushes would flap the SLO.
+const MIN_SLO_SAMPLE_SIZE: i64 = 20;
+/// Latency buckets are kept for a week so a breach can be inspected after the fact.
+const LATENCY_RETENTION_DAYS: i64 = 7;
+
+/// Folds one tick's push deliveries into the latency history, re-evaluates the rolling push
+/// delivery SLO, and raises an ops alert when it starts or stops being breached.
+pub(crate) async fn evaluate_push_delivery_slo(
+    store: &Store,
+    config: &WorkerConfig,
+    metrics: &WorkerTickMetrics,
+    worker_id: Uuid,
+) {
+    if metrics.push_delivered > 0
+        && let Err(err) = store
+            .record_push_delivery_latency(
+                i64::try_from(metrics.push_delivered).unwrap_or(i64::MAX),
+                i64::try_from(metrics.push_delivered_within_slo).unwrap_or(i64::MAX),
+            )
+            .await
+    {
+        warn!(worker_id = %worker_id, "failed to record push delivery latency: {err}");
+    }
+
+    let now = Utc::now();
+    let window_minutes = i64::try_from(config.push_delivery_slo_window_minutes).unwrap_or(i64::MAX);
+    let (deliveries, within_target) = match store
+        .push_delivery_latency_totals(now - Duration::minutes(window_minutes))
+        .await
+    {
+        Ok(totals) => totals,
+        Err(err) => {
+            warn!(worker_id = %worker_id, "failed to load push delivery latency: {err}");
+            return;
+        }
+    };
+
+    let evaluation = slo_evaluation(config, deliveries, within_target, now);
+    match store.record_push_delivery_slo_evaluation(&evaluation).await {
+        Ok((status, previously_breached)) => {
+            let compliance = compliance(status.total_events, status.good_events);
+            if status.breached && !previously_breached {
+                error!(
+                    worker_id = %worker_id,
+                    alert = "push_delivery_slo_breach",
+                    objective_percent = status.objective_percent,
+                    target_seconds = status.target_seconds,
+                    window_minutes = status.window_minutes,
+                    deliveries = status.total_events,
+                    within_target = status.good_events,
+                    compliance = compliance.unwrap_or(0.0),
+                    "push delivery SLO breached"
+                );
+            } else if !status.breached && previously_breached {
+                info!(
+                    worker_id = %worker_id,
+                    alert = "push_delivery_slo_recovered",
+                    deliveries = status.total_events,
+                    within_target = status.good_events,
+                    compliance = compliance.unwrap_or(1.0),
+                    "push delivery SLO recovered"
+                );
+            }
+        }
+        Err(err) => {
+            warn!(worker_id = %worker_id, "failed to record push delivery SLO status: {err}");
+        }
+    }
+
+    let cutoff = now - Duration::days(LATENCY_RETENTION_DAYS);
+    if let Err(err) = store.prune_push_delivery_latency(cutoff).await {
+        warn!(worker_id = %worker_id, "failed to prune push delivery latency: {err}");
+    }
+}
+
+fn slo_evaluation(
+    config: &WorkerConfig,
+    deliveries: i64,
+    within_target: i64,
+    now: DateTime<Utc>,
+) -> SloEvaluation {
+    let objective_percent =
+        i32::try_from(config.push_delivery_slo_objective_percent).unwrap_or(100);
+    SloEvaluation {
+        objective_percent,
+        target_seconds: i64::try_from(config.push_delivery_slo_target_seconds).unwrap_or(i64::MAX),
+        window_minutes: i64::try_from(config.push_delivery_slo_window_minutes).unwrap_or(i64::MAX),
+        total_events: deliveries,
+        good_events: within_target,
+        breached: is_breached(deliveries, within_target, objective_percent),
+        evaluated_at: now,
+    }
+}
+
+fn is_breached(deliveries: i64, within_target: i64, objective_percent: i32) -> bool {
+    deliveries >= MIN_SLO_SAMPLE_SIZE
+        && within_target.saturating_mul(100)
+            < deliveries.saturating_mul(i64::from(objective_percent))
+}
+
+fn compliance(deliveries: i64, within_target: i64) -> Option<f64> {
+    (deliveries > 0).then(|| within_target.min(deliveries) as f64 / deliveries as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compliance, is_breached};
+
+    #[test]
+    fn breach_requires_enough_samples_below_the_objective() {
+        assert!(!is_breached(19, 0, 95));
+        assert!(!is_breached(20, 19, 95));
+        assert!(is_breached(20, 18, 95));
+        assert!(!is_breached(1000, 950, 95));
+        assert!(is_breached(1000, 949, 95));
+    }
+
+    #[test]
+    fn compliance_is_undefined_without_deliveries() {
+        assert_eq!(compliance(0, 0), None);
+        assert_eq!(compliance(4, 3), Some(0.75));
+    }
+}
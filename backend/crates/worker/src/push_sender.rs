@@ -1,9 +1,12 @@
+use std::collections::HashMap;
+
 use chrono::Utc;
 use jsonwebtoken::{Algorithm, EncodingKey, Header, encode};
 use reqwest::StatusCode;
 use serde::Serialize;
 use serde_json::{Value, json};
 use shared::assistant_crypto::ASSISTANT_ENCRYPTION_ALGORITHM_X25519_CHACHA20POLY1305;
+use shared::config::ApnsAppConfig;
 use shared::enclave::EncryptedAutomationNotificationEnvelope;
 use shared::models::ApnsEnvironment;
 use shared::repos::DeviceRegistration;
@@ -14,12 +17,19 @@ const APNS_MAX_PAYLOAD_BYTES: usize = 4096;
 const APNS_PRODUCTION_BASE_URL: &str = "https://api.push.apple.com";
 const APNS_SANDBOX_BASE_URL: &str = "https://api.sandbox.push.apple.com";
 
+/// Routes each push to the APNs topic of the app build the device registered from, signing
+/// with that build's credentials. Devices without a bundle id use the default topic.
 #[derive(Clone)]
 pub(crate) struct PushSender {
     client: reqwest::Client,
+    default_topic: String,
+    credentials_by_topic: HashMap<String, ApnsCredentials>,
+}
+
+#[derive(Clone)]
+struct ApnsCredentials {
     key_id: String,
     team_id: String,
-    topic: String,
     signing_key: EncodingKey,
 }
 
@@ -44,7 +54,36 @@ impl PushPayloadMode {
     }
 }
 
+/// Failures caused by the device token itself rather than by Alfred's APNs setup or an
+/// APNs outage. Only these count against a device's delivery score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DestinationRejection {
+    /// APNs reports the token is no longer active for the topic.
+    Gone,
+    /// APNs rejected the token as malformed or issued for another topic.
+    Invalid,
+}
+
 impl PushSendError {
+    pub(crate) fn code(&self) -> &str {
+        match self {
+            Self::Transient { code, .. } | Self::Permanent { code, .. } => code,
+        }
+    }
+
+    pub(crate) fn destination_rejection(&self) -> Option<DestinationRejection> {
+        match self {
+            Self::Transient { .. } => None,
+            Self::Permanent { code, .. } => match code.as_str() {
+                "APNS_UNREGISTERED" | "APNS_HTTP_410" => Some(DestinationRejection::Gone),
+                "APNS_BADDEVICETOKEN" | "APNS_DEVICETOKENNOTFORTOPIC" => {
+                    Some(DestinationRejection::Invalid)
+                }
+                _ => None,
+            },
+        }
+    }
+
     pub(crate) fn to_job_error(&self) -> JobExecutionError {
         match self {
             Self::Transient { code, message } => {
@@ -72,6 +111,61 @@ impl NotificationContent {
             encrypted_envelope: None,
         }
     }
+
+    pub(crate) fn departure_fallback() -> Self {
+        Self {
+            title: "Time to leave".to_string(),
+            body: "Leave soon to make your next meeting on time.".to_string(),
+            encrypted_envelope: None,
+        }
+    }
+
+    pub(crate) fn backlog_summary() -> Self {
+        Self {
+            title: "You're all set".to_string(),
+            body: "Alfred will send new alerts to this device. Open Alfred to catch up on anything you missed.".to_string(),
+            encrypted_envelope: None,
+        }
+    }
+
+    pub(crate) fn device_disabled_notice() -> Self {
+        Self {
+            title: "A device stopped receiving alerts".to_string(),
+            body: "Alfred paused notifications to one of your devices after repeated delivery failures. Open Settings > Devices to re-enable it.".to_string(),
+            encrypted_envelope: None,
+        }
+    }
+
+    pub(crate) fn privacy_delete_confirmation() -> Self {
+        Self {
+            title: "All your data has been deleted".to_string(),
+            body: "Alfred finished deleting your account data. This device will no longer receive alerts.".to_string(),
+            encrypted_envelope: None,
+        }
+    }
+
+    /// Sent once when repeated failures pause an automation. `reconnect_google` is set when
+    /// the runs failed because the Google connector is unusable.
+    pub(crate) fn automation_paused(reconnect_google: bool) -> Self {
+        let body = if reconnect_google {
+            "Alfred lost access to your Google account and paused an automation. Reconnect Google, then resume it in Alfred."
+        } else {
+            "Alfred paused an automation after it failed several times in a row. Open Alfred to review and resume it."
+        };
+        Self {
+            title: "Your automation couldn't run".to_string(),
+            body: body.to_string(),
+            encrypted_envelope: None,
+        }
+    }
+
+    pub(crate) fn google_reauth_nudge() -> Self {
+        Self {
+            title: "Reconnect Google".to_string(),
+            body: "Alfred lost access to your Google account. Open Alfred to reconnect so your briefs stay complete.".to_string(),
+            encrypted_envelope: None,
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -82,23 +176,48 @@ struct ApnsProviderTokenClaims {
 
 impl PushSender {
     pub(crate) fn new(
-        key_id: String,
-        team_id: String,
-        topic: String,
-        auth_key_pem: String,
+        default_app: &ApnsAppConfig,
+        additional_apps: &[ApnsAppConfig],
     ) -> Result<Self, String> {
-        let signing_key = EncodingKey::from_ec_pem(auth_key_pem.as_bytes())
-            .map_err(|err| format!("invalid APNS auth key PEM: {err}"))?;
+        let mut credentials_by_topic = HashMap::new();
+        for app in std::iter::once(default_app).chain(additional_apps) {
+            let signing_key = EncodingKey::from_ec_pem(app.auth_key_p8.as_bytes())
+                .map_err(|err| format!("invalid APNS auth key PEM for {}: {err}", app.topic))?;
+            credentials_by_topic.insert(
+                app.topic.clone(),
+                ApnsCredentials {
+                    key_id: app.key_id.clone(),
+                    team_id: app.team_id.clone(),
+                    signing_key,
+                },
+            );
+        }
 
         Ok(Self {
             client: reqwest::Client::new(),
-            key_id,
-            team_id,
-            topic,
-            signing_key,
+            default_topic: default_app.topic.clone(),
+            credentials_by_topic,
         })
     }
 
+    fn route<'a>(
+        &'a self,
+        device: &'a DeviceRegistration,
+    ) -> Result<(&'a str, &'a ApnsCredentials), PushSendError> {
+        let topic = device
+            .app_bundle_id
+            .as_deref()
+            .unwrap_or(self.default_topic.as_str());
+        let credentials =
+            self.credentials_by_topic
+                .get(topic)
+                .ok_or_else(|| PushSendError::Permanent {
+                    code: "APNS_TOPIC_NOT_CONFIGURED".to_string(),
+                    message: format!("no APNs credentials configured for app bundle {topic}"),
+                })?;
+        Ok((topic, credentials))
+    }
+
     pub(crate) async fn send(
         &self,
         device: &DeviceRegistration,
@@ -114,12 +233,14 @@ impl PushSender {
             PushPayloadMode::Fallback
         };
 
-        let provider_token = self
-            .provider_token()
-            .map_err(|message| PushSendError::Permanent {
-                code: "APNS_PROVIDER_TOKEN_INVALID".to_string(),
-                message,
-            })?;
+        let (topic, credentials) = self.route(device)?;
+        let provider_token =
+            credentials
+                .provider_token()
+                .map_err(|message| PushSendError::Permanent {
+                    code: "APNS_PROVIDER_TOKEN_INVALID".to_string(),
+                    message,
+                })?;
 
         let url = format!(
             "{}/3/device/{}",
@@ -131,7 +252,7 @@ impl PushSender {
             .client
             .post(url)
             .header("authorization", format!("bearer {provider_token}"))
-            .header("apns-topic", self.topic.as_str())
+            .header("apns-topic", topic)
             .header("apns-push-type", "alert")
             .header("apns-priority", "10")
             .json(&payload)
@@ -167,7 +288,9 @@ impl PushSender {
             FailureClass::Permanent => Err(PushSendError::Permanent { code, message }),
         }
     }
+}
 
+impl ApnsCredentials {
     fn provider_token(&self) -> Result<String, String> {
         let claims = ApnsProviderTokenClaims {
             iss: self.team_id.clone(),
@@ -322,11 +445,18 @@ mod tests {
     use shared::assistant_crypto::ASSISTANT_ENCRYPTION_ALGORITHM_X25519_CHACHA20POLY1305;
     use shared::enclave::EncryptedAutomationNotificationEnvelope;
 
-    use super::NotificationContent;
+    use std::collections::HashMap;
+
+    use jsonwebtoken::EncodingKey;
     use reqwest::StatusCode;
+    use shared::models::ApnsEnvironment;
+    use shared::repos::DeviceRegistration;
+
+    use super::NotificationContent;
 
     use super::{
-        apns_payload, classify_http_failure, enforce_apns_payload_size, extract_apns_reason,
+        ApnsCredentials, DestinationRejection, PushSendError, PushSender, apns_payload,
+        classify_http_failure, enforce_apns_payload_size, extract_apns_reason,
         is_valid_encrypted_envelope, normalize_apns_reason,
     };
     use crate::FailureClass;
@@ -457,6 +587,84 @@ mod tests {
         );
     }
 
+    #[test]
+    fn only_token_specific_failures_reject_the_destination() {
+        let permanent = |code: &str| PushSendError::Permanent {
+            code: code.to_string(),
+            message: String::new(),
+        };
+
+        assert_eq!(
+            permanent("APNS_UNREGISTERED").destination_rejection(),
+            Some(DestinationRejection::Gone)
+        );
+        assert_eq!(
+            permanent("APNS_BADDEVICETOKEN").destination_rejection(),
+            Some(DestinationRejection::Invalid)
+        );
+        assert_eq!(
+            permanent("APNS_PROVIDER_TOKEN_INVALID").destination_rejection(),
+            None
+        );
+        let transient = PushSendError::Transient {
+            code: "APNS_HTTP_503".to_string(),
+            message: String::new(),
+        };
+        assert_eq!(transient.destination_rejection(), None);
+    }
+
+    #[test]
+    fn routes_devices_to_their_registered_app_topic() {
+        let credentials = |key_id: &str| ApnsCredentials {
+            key_id: key_id.to_string(),
+            team_id: "team".to_string(),
+            signing_key: EncodingKey::from_secret(b"test"),
+        };
+        let sender = PushSender {
+            client: reqwest::Client::new(),
+            default_topic: "com.example.alfred".to_string(),
+            credentials_by_topic: HashMap::from([
+                ("com.example.alfred".to_string(), credentials("prod-key")),
+                (
+                    "com.example.alfred.beta".to_string(),
+                    credentials("beta-key"),
+                ),
+            ]),
+        };
+
+        let default_device = sample_device(None);
+        let beta_device = sample_device(Some("com.example.alfred.beta"));
+
+        let (topic, credentials) = sender
+            .route(&default_device)
+            .expect("default topic should route");
+        assert_eq!(topic, "com.example.alfred");
+        assert_eq!(credentials.key_id, "prod-key");
+
+        let (topic, credentials) = sender.route(&beta_device).expect("beta topic should route");
+        assert_eq!(topic, "com.example.alfred.beta");
+        assert_eq!(credentials.key_id, "beta-key");
+
+        let err = sender
+            .route(&sample_device(Some("com.example.unknown")))
+            .err()
+            .expect("unknown bundle should be rejected");
+        assert_eq!(err.code(), "APNS_TOPIC_NOT_CONFIGURED");
+        assert_eq!(err.destination_rejection(), None);
+    }
+
+    fn sample_device(app_bundle_id: Option<&str>) -> DeviceRegistration {
+        DeviceRegistration {
+            device_id: "device-1".to_string(),
+            apns_token: "token".to_string(),
+            environment: ApnsEnvironment::Sandbox,
+            app_bundle_id: app_bundle_id.map(ToString::to_string),
+            registered_at: chrono::Utc::now(),
+            notification_key: None,
+            previous_notification_key: None,
+        }
+    }
+
     fn sample_envelope() -> EncryptedAutomationNotificationEnvelope {
         EncryptedAutomationNotificationEnvelope {
             version: "v1".to_string(),
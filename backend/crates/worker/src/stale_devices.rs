@@ -0,0 +1,80 @@
+use chrono::{Duration, SecondsFormat, Utc};
+use shared::config::WorkerConfig;
+use shared::models::AuditMetadata;
+use shared::repos::{AuditResult, StaleDeviceRecord, Store};
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+const DEVICE_REMOVED_STALE_EVENT: &str = "DEVICE_REMOVED_STALE";
+
+/// Drops devices that have not re-registered or accepted a push within the retention window,
+/// so notification fan-out stops spending APNs attempts on wiped or abandoned phones.
+pub(crate) async fn purge_stale_devices(
+    store: &Store,
+    config: &WorkerConfig,
+    worker_id: Uuid,
+) -> usize {
+    let seen_before = Utc::now() - Duration::days(i64::from(config.stale_device_retention_days));
+    let removed = match store
+        .purge_stale_devices_batch(seen_before, i64::from(config.stale_device_purge_batch_size))
+        .await
+    {
+        Ok(removed) => removed,
+        Err(err) => {
+            error!(worker_id = %worker_id, "failed to purge stale devices: {err}");
+            return 0;
+        }
+    };
+
+    if removed.is_empty() {
+        debug!(
+            worker_id = %worker_id,
+            batch_size = config.stale_device_purge_batch_size,
+            "stale device purge tick found no stale devices"
+        );
+        return 0;
+    }
+
+    for device in &removed {
+        record_stale_device_removal(store, worker_id, device).await;
+    }
+
+    info!(
+        worker_id = %worker_id,
+        removed_devices = removed.len(),
+        batch_size = config.stale_device_purge_batch_size,
+        retention_days = config.stale_device_retention_days,
+        "stale device purge tick"
+    );
+
+    removed.len()
+}
+
+async fn record_stale_device_removal(store: &Store, worker_id: Uuid, device: &StaleDeviceRecord) {
+    let mut metadata = AuditMetadata::new();
+    metadata.insert("device_id".to_string(), device.device_id.clone().into());
+    metadata.insert(
+        "last_seen_at".to_string(),
+        device
+            .last_seen_at
+            .to_rfc3339_opts(SecondsFormat::Secs, true)
+            .into(),
+    );
+
+    if let Err(err) = store
+        .add_audit_event(
+            device.user_id,
+            DEVICE_REMOVED_STALE_EVENT,
+            None,
+            AuditResult::Success,
+            &metadata,
+        )
+        .await
+    {
+        warn!(
+            worker_id = %worker_id,
+            user_id = %device.user_id,
+            "failed to record stale device removal audit event: {err}"
+        );
+    }
+}
@@ -0,0 +1,38 @@
+use chrono::Utc;
+use shared::repos::Store;
+use tracing::{debug, error, info};
+use uuid::Uuid;
+
+/// Diagnostic uploads are rare, so a small fixed batch drains expired bundles every tick.
+const SUPPORT_DIAGNOSTICS_PURGE_BATCH_SIZE: i64 = 100;
+
+pub(crate) async fn purge_expired_support_diagnostics(store: &Store, worker_id: Uuid) -> u64 {
+    let purged_rows = match store
+        .purge_expired_support_diagnostics_batch(Utc::now(), SUPPORT_DIAGNOSTICS_PURGE_BATCH_SIZE)
+        .await
+    {
+        Ok(purged_rows) => purged_rows,
+        Err(err) => {
+            error!(
+                worker_id = %worker_id,
+                "failed to purge expired support diagnostics: {err}"
+            );
+            return 0;
+        }
+    };
+
+    if purged_rows > 0 {
+        info!(
+            worker_id = %worker_id,
+            purged_rows,
+            "support diagnostics purge tick"
+        );
+    } else {
+        debug!(
+            worker_id = %worker_id,
+            "support diagnostics purge tick found no expired rows"
+        );
+    }
+
+    purged_rows
+}
@@ -43,6 +43,13 @@ pub(crate) struct WorkerTickMetrics {
     pub(crate) push_delivered: usize,
     pub(crate) push_transient_failures: usize,
     pub(crate) push_permanent_failures: usize,
+    /// Per-device deliveries skipped because the notification predates the device.
+    pub(crate) push_backlog_suppressed: usize,
+    /// Deliveries that reached APNs within the push delivery SLO target of the job's due time.
+    pub(crate) push_delivered_within_slo: usize,
+    pub(crate) automation_attempts: usize,
+    pub(crate) automation_successes: usize,
+    pub(crate) automation_timeouts: usize,
     pub(crate) total_lag_seconds: i64,
     pub(crate) max_lag_seconds: i64,
 }
@@ -54,6 +61,21 @@ impl WorkerTickMetrics {
         self.max_lag_seconds = self.max_lag_seconds.max(lag_seconds);
     }
 
+    /// A retried job already missed its first attempt and its `due_at` has moved to the retry
+    /// time, so its deliveries never count as within target.
+    pub(crate) fn record_push_delivery_latency(
+        &mut self,
+        due_at: DateTime<Utc>,
+        delivered_at: DateTime<Utc>,
+        attempts: i32,
+        target_seconds: u64,
+    ) {
+        let latency_seconds = (delivered_at - due_at).num_seconds().max(0);
+        if attempts == 0 && latency_seconds <= i64::try_from(target_seconds).unwrap_or(i64::MAX) {
+            self.push_delivered_within_slo += 1;
+        }
+    }
+
     pub(crate) fn average_lag_seconds(&self) -> f64 {
         if self.processed_jobs == 0 {
             return 0.0;
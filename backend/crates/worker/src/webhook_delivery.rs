@@ -0,0 +1,273 @@
+use chrono::{Duration as ChronoDuration, Utc};
+use futures_util::{StreamExt, stream};
+use reqwest::header::CONTENT_TYPE;
+use serde_json::Value;
+use shared::config::WorkerConfig;
+use shared::repos::{ClaimedWebhookDelivery, Store, WebhookTarget};
+use shared::webhooks::{
+    WEBHOOK_EVENT_ID_HEADER, WEBHOOK_EVENT_TYPE_HEADER, WEBHOOK_MAX_ATTEMPTS,
+    WEBHOOK_SIGNATURE_HEADER, WEBHOOK_TIMESTAMP_HEADER, WebhookEvent, WebhookEventType,
+    pinned_webhook_client, webhook_signature,
+};
+use tokio::time::{Duration, sleep, timeout};
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::retry_delay_seconds;
+
+/// Long enough to cover the request timeout; a lapsed lease makes the delivery due again.
+const WEBHOOK_DELIVERY_LEASE_SECONDS: i64 = 60;
+/// A tick stops sending well before its leases lapse, so no attempt is still running when
+/// another worker may reclaim the row.
+const WEBHOOK_DELIVERY_BATCH_DEADLINE: Duration = Duration::from_secs(45);
+const WEBHOOK_DELIVERY_CONCURRENCY: usize = 8;
+/// Sends that have no delivery row to fall back on (the account is already purged) get a
+/// short in-process retry instead of the persisted backoff.
+const UNLOGGED_WEBHOOK_ATTEMPTS: u32 = 2;
+const UNLOGGED_WEBHOOK_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+#[derive(Debug)]
+pub(crate) struct WebhookSendError {
+    pub(crate) status_code: Option<u16>,
+    pub(crate) message: String,
+}
+
+/// Queues a metadata-only event for the user's matching subscriptions. Webhooks are a side
+/// channel, so a failure here is logged and never fails the caller's work.
+pub(crate) async fn enqueue_webhook_event(
+    store: &Store,
+    user_id: Uuid,
+    event_type: WebhookEventType,
+    data: Value,
+) {
+    let event = WebhookEvent::new(event_type, data);
+    if let Err(err) = store.enqueue_webhook_event(user_id, &event).await {
+        warn!(
+            user_id = %user_id,
+            event_type = event_type.as_str(),
+            event_id = %event.id,
+            "failed to queue webhook event: {err}"
+        );
+    }
+}
+
+pub(crate) async fn deliver_due_webhooks(
+    store: &Store,
+    config: &WorkerConfig,
+    worker_id: Uuid,
+) -> usize {
+    let deliveries = match store
+        .claim_due_webhook_deliveries(
+            Utc::now(),
+            worker_id,
+            WEBHOOK_DELIVERY_LEASE_SECONDS,
+            WEBHOOK_MAX_ATTEMPTS,
+            i64::from(config.batch_size),
+        )
+        .await
+    {
+        Ok(deliveries) => deliveries,
+        Err(err) => {
+            error!(worker_id = %worker_id, "failed to claim webhook deliveries: {err}");
+            return 0;
+        }
+    };
+
+    if deliveries.is_empty() {
+        debug!(worker_id = %worker_id, "no webhook deliveries due");
+        return 0;
+    }
+
+    let mut settled = 0_usize;
+    let mut delivered = 0_usize;
+    let sends = stream::iter(&deliveries)
+        .map(|delivery| deliver(store, config, worker_id, delivery))
+        .buffer_unordered(WEBHOOK_DELIVERY_CONCURRENCY)
+        .for_each(|ok| {
+            settled += 1;
+            delivered += usize::from(ok);
+            async {}
+        });
+    if timeout(WEBHOOK_DELIVERY_BATCH_DEADLINE, sends)
+        .await
+        .is_err()
+    {
+        // Unsettled deliveries keep their counted attempt and become due when the lease lapses.
+        warn!(
+            worker_id = %worker_id,
+            claimed_deliveries = deliveries.len(),
+            settled,
+            "webhook delivery tick hit its deadline"
+        );
+    }
+
+    info!(
+        worker_id = %worker_id,
+        claimed_deliveries = deliveries.len(),
+        delivered,
+        "webhook delivery tick"
+    );
+
+    deliveries.len()
+}
+
+async fn deliver(
+    store: &Store,
+    config: &WorkerConfig,
+    worker_id: Uuid,
+    delivery: &ClaimedWebhookDelivery,
+) -> bool {
+    let outcome = send_webhook(&delivery.target, &delivery.event).await;
+    let now = Utc::now();
+    let recorded = match &outcome {
+        Ok(status_code) => {
+            store
+                .mark_webhook_delivery_delivered(
+                    delivery.id,
+                    worker_id,
+                    i32::from(*status_code),
+                    now,
+                )
+                .await
+        }
+        Err(err) => {
+            let retry_at = (delivery.attempts < WEBHOOK_MAX_ATTEMPTS).then(|| {
+                let delay_seconds = retry_delay_seconds(
+                    config.retry_base_delay_seconds,
+                    config.retry_max_delay_seconds,
+                    delivery.attempts,
+                );
+                now + ChronoDuration::seconds(i64::try_from(delay_seconds).unwrap_or(i64::MAX))
+            });
+            warn!(
+                worker_id = %worker_id,
+                delivery_id = %delivery.id,
+                subscription_id = %delivery.target.subscription_id,
+                attempts = delivery.attempts,
+                status_code = ?err.status_code,
+                will_retry = retry_at.is_some(),
+                "webhook delivery attempt failed: {}",
+                err.message
+            );
+            store
+                .record_webhook_delivery_failure(
+                    delivery.id,
+                    worker_id,
+                    err.status_code.map(i32::from),
+                    &err.message,
+                    retry_at,
+                )
+                .await
+        }
+    };
+
+    match recorded {
+        Ok(true) => {}
+        Ok(false) => warn!(
+            worker_id = %worker_id,
+            delivery_id = %delivery.id,
+            "webhook delivery lease was lost before its outcome was recorded"
+        ),
+        Err(err) => error!(
+            worker_id = %worker_id,
+            delivery_id = %delivery.id,
+            user_id = %delivery.user_id,
+            "failed to record webhook delivery outcome: {err}"
+        ),
+    }
+
+    outcome.is_ok()
+}
+
+/// Sends `event` straight to `targets` without a delivery row, for events raised after the
+/// account's rows are gone. Returns how many targets accepted it.
+pub(crate) async fn send_unlogged_webhooks(
+    user_id: Uuid,
+    targets: &[WebhookTarget],
+    event: &WebhookEvent,
+) -> usize {
+    let mut delivered = 0_usize;
+    for target in targets {
+        for attempt in 1..=UNLOGGED_WEBHOOK_ATTEMPTS {
+            match send_webhook(target, event).await {
+                Ok(_) => {
+                    delivered += 1;
+                    break;
+                }
+                Err(err) => {
+                    warn!(
+                        user_id = %user_id,
+                        subscription_id = %target.subscription_id,
+                        event_type = event.event_type.as_str(),
+                        attempt,
+                        status_code = ?err.status_code,
+                        "unlogged webhook delivery failed: {}",
+                        err.message
+                    );
+                    if attempt < UNLOGGED_WEBHOOK_ATTEMPTS {
+                        sleep(UNLOGGED_WEBHOOK_RETRY_DELAY).await;
+                    }
+                }
+            }
+        }
+    }
+
+    delivered
+}
+
+/// Posts one signed event. Any non-2xx answer counts as a failure; redirects are not followed.
+pub(crate) async fn send_webhook(
+    target: &WebhookTarget,
+    event: &WebhookEvent,
+) -> Result<u16, WebhookSendError> {
+    let client = pinned_webhook_client(&target.url)
+        .await
+        .map_err(|message| WebhookSendError {
+            status_code: None,
+            message,
+        })?;
+
+    let body = serde_json::to_vec(event).map_err(|err| WebhookSendError {
+        status_code: None,
+        message: format!("failed to encode webhook event: {err}"),
+    })?;
+    let timestamp = Utc::now().timestamp();
+    let signature = webhook_signature(&target.signing_secret, timestamp, &body);
+
+    let response = client
+        .post(&target.url)
+        .header(CONTENT_TYPE, "application/json")
+        .header(WEBHOOK_EVENT_ID_HEADER, event.id.to_string())
+        .header(WEBHOOK_EVENT_TYPE_HEADER, event.event_type.as_str())
+        .header(WEBHOOK_TIMESTAMP_HEADER, timestamp.to_string())
+        .header(WEBHOOK_SIGNATURE_HEADER, signature)
+        .body(body)
+        .send()
+        .await
+        .map_err(|err| WebhookSendError {
+            status_code: None,
+            message: request_error_message(&err).to_string(),
+        })?;
+
+    let status = response.status();
+    if status.is_success() {
+        Ok(status.as_u16())
+    } else {
+        Err(WebhookSendError {
+            status_code: Some(status.as_u16()),
+            message: format!("endpoint answered HTTP {}", status.as_u16()),
+        })
+    }
+}
+
+/// Stored in the user-visible delivery log, so it names the failure without echoing
+/// transport internals.
+fn request_error_message(err: &reqwest::Error) -> &'static str {
+    if err.is_timeout() {
+        "request timed out"
+    } else if err.is_connect() {
+        "connection failed"
+    } else {
+        "request failed"
+    }
+}
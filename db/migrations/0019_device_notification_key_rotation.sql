@@ -0,0 +1,51 @@
+ALTER TABLE devices
+  ADD COLUMN IF NOT EXISTS notification_key_id TEXT NULL,
+  ADD COLUMN IF NOT EXISTS notification_key_rotated_at TIMESTAMPTZ NULL,
+  ADD COLUMN IF NOT EXISTS previous_notification_key_id TEXT NULL,
+  ADD COLUMN IF NOT EXISTS previous_notification_key_algorithm TEXT NULL,
+  ADD COLUMN IF NOT EXISTS previous_notification_public_key_ciphertext BYTEA NULL,
+  ADD COLUMN IF NOT EXISTS previous_notification_key_expires_at TIMESTAMPTZ NULL;
+
+-- Keys registered before key ids existed were addressed by device identifier.
+UPDATE devices
+SET notification_key_id = device_identifier
+WHERE notification_key_algorithm IS NOT NULL
+  AND notification_key_id IS NULL;
+
+ALTER TABLE devices
+  DROP CONSTRAINT IF EXISTS devices_notification_key_fields_check;
+
+ALTER TABLE devices
+  ADD CONSTRAINT devices_notification_key_fields_check
+  CHECK (
+    (
+      notification_key_id IS NULL
+      AND notification_key_algorithm IS NULL
+      AND notification_public_key_ciphertext IS NULL
+    )
+    OR (
+      notification_key_id IS NOT NULL
+      AND notification_key_algorithm = 'x25519-chacha20poly1305'
+      AND notification_public_key_ciphertext IS NOT NULL
+    )
+  );
+
+ALTER TABLE devices
+  DROP CONSTRAINT IF EXISTS devices_previous_notification_key_fields_check;
+
+ALTER TABLE devices
+  ADD CONSTRAINT devices_previous_notification_key_fields_check
+  CHECK (
+    (
+      previous_notification_key_id IS NULL
+      AND previous_notification_key_algorithm IS NULL
+      AND previous_notification_public_key_ciphertext IS NULL
+      AND previous_notification_key_expires_at IS NULL
+    )
+    OR (
+      previous_notification_key_id IS NOT NULL
+      AND previous_notification_key_algorithm = 'x25519-chacha20poly1305'
+      AND previous_notification_public_key_ciphertext IS NOT NULL
+      AND previous_notification_key_expires_at IS NOT NULL
+    )
+  );
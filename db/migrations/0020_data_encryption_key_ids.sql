@@ -0,0 +1,49 @@
+-- Every row holding pgp_sym_encrypt ciphertext records which data encryption key
+-- (DEK) wrapped it. Rows written before key ids existed used the initial key, which
+-- is addressed as 'v1' (the default DATA_ENCRYPTION_KEY_ID).
+ALTER TABLE devices
+  ADD COLUMN IF NOT EXISTS data_key_id TEXT NOT NULL DEFAULT 'v1';
+ALTER TABLE devices
+  ALTER COLUMN data_key_id DROP DEFAULT;
+
+ALTER TABLE connectors
+  ADD COLUMN IF NOT EXISTS data_key_id TEXT NOT NULL DEFAULT 'v1';
+ALTER TABLE connectors
+  ALTER COLUMN data_key_id DROP DEFAULT;
+
+ALTER TABLE jobs
+  ADD COLUMN IF NOT EXISTS data_key_id TEXT NOT NULL DEFAULT 'v1';
+ALTER TABLE jobs
+  ALTER COLUMN data_key_id DROP DEFAULT;
+
+ALTER TABLE dead_letter_jobs
+  ADD COLUMN IF NOT EXISTS data_key_id TEXT NOT NULL DEFAULT 'v1';
+ALTER TABLE dead_letter_jobs
+  ALTER COLUMN data_key_id DROP DEFAULT;
+
+ALTER TABLE automation_rules
+  ADD COLUMN IF NOT EXISTS data_key_id TEXT NOT NULL DEFAULT 'v1';
+ALTER TABLE automation_rules
+  ALTER COLUMN data_key_id DROP DEFAULT;
+
+CREATE INDEX IF NOT EXISTS devices_data_key_id_idx ON devices (data_key_id);
+CREATE INDEX IF NOT EXISTS connectors_data_key_id_idx ON connectors (data_key_id);
+CREATE INDEX IF NOT EXISTS jobs_data_key_id_idx ON jobs (data_key_id);
+CREATE INDEX IF NOT EXISTS dead_letter_jobs_data_key_id_idx ON dead_letter_jobs (data_key_id);
+CREATE INDEX IF NOT EXISTS automation_rules_data_key_id_idx ON automation_rules (data_key_id);
+
+-- Resolves the key material for a row's data_key_id from the keyring bound by the
+-- caller. Keys never live in the database; unknown ids fail closed.
+CREATE OR REPLACE FUNCTION alfred_data_key(row_key_id TEXT, key_ids TEXT[], keys TEXT[])
+RETURNS TEXT
+LANGUAGE plpgsql
+IMMUTABLE
+AS $$
+DECLARE
+  key_index INTEGER := array_position(key_ids, row_key_id);
+BEGIN
+  IF key_index IS NULL THEN
+    RAISE EXCEPTION 'unknown data encryption key id: %', row_key_id;
+  END IF;
+  RETURN keys[key_index];
+END $$;
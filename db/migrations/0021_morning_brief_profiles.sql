@@ -0,0 +1,26 @@
+CREATE TABLE IF NOT EXISTS morning_brief_profiles (
+  user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+  sections TEXT[] NOT NULL,
+  verbosity TEXT NOT NULL,
+  learn_from_feedback BOOLEAN NOT NULL DEFAULT TRUE,
+  section_scores JSONB NOT NULL DEFAULT '{}'::jsonb,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+ALTER TABLE morning_brief_profiles
+  DROP CONSTRAINT IF EXISTS morning_brief_profiles_sections_check;
+
+ALTER TABLE morning_brief_profiles
+  ADD CONSTRAINT morning_brief_profiles_sections_check
+  CHECK (
+    cardinality(sections) BETWEEN 1 AND 3
+    AND sections <@ ARRAY['priorities', 'schedule', 'alerts']::TEXT[]
+  );
+
+ALTER TABLE morning_brief_profiles
+  DROP CONSTRAINT IF EXISTS morning_brief_profiles_verbosity_check;
+
+ALTER TABLE morning_brief_profiles
+  ADD CONSTRAINT morning_brief_profiles_verbosity_check
+  CHECK (verbosity IN ('concise', 'standard', 'detailed'));
@@ -0,0 +1,24 @@
+ALTER TABLE automation_rules
+  ADD COLUMN IF NOT EXISTS template TEXT NULL;
+
+ALTER TABLE automation_rules
+  DROP CONSTRAINT IF EXISTS automation_rules_template_check;
+
+ALTER TABLE automation_rules
+  ADD CONSTRAINT automation_rules_template_check
+  CHECK (template IS NULL OR template IN ('WEEKLY_REVIEW'));
+
+CREATE TABLE IF NOT EXISTS automation_reports (
+  id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+  user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+  rule_id UUID NOT NULL REFERENCES automation_rules(id) ON DELETE CASCADE,
+  run_id UUID NOT NULL,
+  device_identifier TEXT NOT NULL CHECK (char_length(trim(device_identifier)) BETWEEN 1 AND 128),
+  template TEXT NOT NULL CHECK (template IN ('WEEKLY_REVIEW')),
+  envelope JSONB NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  UNIQUE (run_id, device_identifier)
+);
+
+CREATE INDEX IF NOT EXISTS idx_automation_reports_user_device_created
+  ON automation_reports (user_id, device_identifier, created_at DESC, id DESC);
@@ -0,0 +1,61 @@
+ALTER TABLE jobs
+  DROP CONSTRAINT IF EXISTS jobs_type_check;
+
+ALTER TABLE dead_letter_jobs
+  DROP CONSTRAINT IF EXISTS dead_letter_jobs_type_check;
+
+ALTER TABLE jobs
+  ADD CONSTRAINT jobs_type_check
+  CHECK (type IN ('AUTOMATION_RUN', 'DEPARTURE_ALERT'));
+
+ALTER TABLE dead_letter_jobs
+  ADD CONSTRAINT dead_letter_jobs_type_check
+  CHECK (type IN ('AUTOMATION_RUN', 'DEPARTURE_ALERT'));
+
+-- home_location_ciphertext holds the client envelope (encrypted to the enclave ingress key),
+-- wrapped again at rest with the data encryption key named by data_key_id.
+CREATE TABLE IF NOT EXISTS departure_alert_preferences (
+  user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+  enabled BOOLEAN NOT NULL DEFAULT TRUE,
+  time_zone TEXT NOT NULL,
+  check_local_time_minutes INTEGER NOT NULL,
+  travel_minutes INTEGER NOT NULL,
+  buffer_minutes INTEGER NOT NULL,
+  home_location_ciphertext BYTEA NULL,
+  data_key_id TEXT NOT NULL,
+  next_check_at TIMESTAMPTZ NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+ALTER TABLE departure_alert_preferences
+  DROP CONSTRAINT IF EXISTS departure_alert_preferences_check_time_check;
+
+ALTER TABLE departure_alert_preferences
+  ADD CONSTRAINT departure_alert_preferences_check_time_check
+  CHECK (check_local_time_minutes BETWEEN 0 AND 1439);
+
+ALTER TABLE departure_alert_preferences
+  DROP CONSTRAINT IF EXISTS departure_alert_preferences_minutes_check;
+
+ALTER TABLE departure_alert_preferences
+  ADD CONSTRAINT departure_alert_preferences_minutes_check
+  CHECK (travel_minutes BETWEEN 1 AND 240 AND buffer_minutes BETWEEN 0 AND 120);
+
+CREATE INDEX IF NOT EXISTS departure_alert_preferences_due_idx
+  ON departure_alert_preferences (next_check_at)
+  WHERE enabled = TRUE;
+
+CREATE INDEX IF NOT EXISTS departure_alert_preferences_data_key_id_idx
+  ON departure_alert_preferences (data_key_id);
+
+-- One row per user and local day: the meeting the last delivered alert was for. A changed
+-- fingerprint (moved time or location) means the alert is re-sent.
+CREATE TABLE IF NOT EXISTS departure_alert_deliveries (
+  user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+  local_date DATE NOT NULL,
+  meeting_fingerprint TEXT NOT NULL,
+  leave_by TIMESTAMPTZ NOT NULL,
+  delivered_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (user_id, local_date)
+);
@@ -0,0 +1,137 @@
+-- Split jobs by state so claiming only touches the small active partition. Finished jobs are
+-- further partitioned by month of updated_at; the worker pre-creates upcoming months and
+-- drops months older than the history retention window.
+--
+-- Partitioned tables cannot be the target of a foreign key on id alone, so the references
+-- from dead_letter_jobs, outbound_action_idempotency, and automation_runs become plain ids.
+-- Their rows are still removed with the user (user_id cascades) and by the privacy purge.
+
+ALTER TABLE dead_letter_jobs
+  DROP CONSTRAINT IF EXISTS dead_letter_jobs_job_id_fkey;
+
+ALTER TABLE outbound_action_idempotency
+  DROP CONSTRAINT IF EXISTS outbound_action_idempotency_job_id_fkey;
+
+ALTER TABLE automation_runs
+  DROP CONSTRAINT IF EXISTS automation_runs_job_id_fkey;
+
+ALTER TABLE jobs RENAME TO jobs_unpartitioned;
+
+CREATE TABLE jobs (
+  id UUID NOT NULL DEFAULT gen_random_uuid(),
+  user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+  type TEXT NOT NULL,
+  due_at TIMESTAMPTZ NOT NULL,
+  state TEXT NOT NULL,
+  payload_ciphertext BYTEA NULL,
+  last_run_at TIMESTAMPTZ NULL,
+  next_run_at TIMESTAMPTZ NULL,
+  lease_owner TEXT NULL,
+  lease_expires_at TIMESTAMPTZ NULL,
+  attempts INT NOT NULL DEFAULT 0,
+  max_attempts INT NOT NULL DEFAULT 5,
+  idempotency_key TEXT NOT NULL,
+  last_error_code TEXT NULL,
+  last_error_message TEXT NULL,
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  data_key_id TEXT NOT NULL,
+  CONSTRAINT jobs_state_check CHECK (state IN ('PENDING', 'RUNNING', 'DONE', 'FAILED')),
+  CONSTRAINT jobs_type_check CHECK (type IN ('AUTOMATION_RUN', 'DEPARTURE_ALERT')),
+  CONSTRAINT jobs_attempts_check CHECK (attempts >= 0),
+  CONSTRAINT jobs_max_attempts_check CHECK (max_attempts > 0)
+) PARTITION BY LIST (state);
+
+CREATE TABLE jobs_active
+  PARTITION OF jobs
+  FOR VALUES IN ('PENDING', 'RUNNING');
+
+CREATE TABLE jobs_finished
+  PARTITION OF jobs
+  FOR VALUES IN ('DONE', 'FAILED')
+  PARTITION BY RANGE (updated_at);
+
+-- Catches rows whose month has no partition yet; the worker purges aged rows from it too.
+CREATE TABLE jobs_finished_default
+  PARTITION OF jobs_finished
+  DEFAULT;
+
+DO $$
+DECLARE
+  month_start DATE;
+BEGIN
+  FOR offset_months IN 0..1 LOOP
+    month_start := (date_trunc('month', NOW() AT TIME ZONE 'UTC') + make_interval(months => offset_months))::date;
+    EXECUTE format(
+      'CREATE TABLE IF NOT EXISTS %I PARTITION OF jobs_finished FOR VALUES FROM (%L) TO (%L)',
+      'jobs_finished_p' || to_char(month_start, 'YYYYMM'),
+      month_start::timestamp AT TIME ZONE 'UTC',
+      (month_start + INTERVAL '1 month')::timestamp AT TIME ZONE 'UTC'
+    );
+  END LOOP;
+END
+$$;
+
+-- Uniqueness is enforced on the active partition only. Enqueue checks finished jobs for the
+-- same idempotency key before inserting.
+ALTER TABLE jobs_active
+  ADD CONSTRAINT jobs_active_pkey PRIMARY KEY (id);
+
+CREATE UNIQUE INDEX IF NOT EXISTS jobs_active_user_type_idempotency_idx
+  ON jobs_active (user_id, type, idempotency_key);
+
+CREATE INDEX IF NOT EXISTS jobs_active_claimable_idx
+  ON jobs_active (state, due_at, lease_expires_at);
+
+CREATE INDEX IF NOT EXISTS jobs_active_running_user_lease_idx
+  ON jobs_active (user_id, state, lease_expires_at);
+
+CREATE INDEX IF NOT EXISTS jobs_finished_id_idx
+  ON jobs_finished (id);
+
+CREATE INDEX IF NOT EXISTS jobs_finished_user_type_idempotency_idx
+  ON jobs_finished (user_id, type, idempotency_key);
+
+INSERT INTO jobs (
+  id,
+  user_id,
+  type,
+  due_at,
+  state,
+  payload_ciphertext,
+  last_run_at,
+  next_run_at,
+  lease_owner,
+  lease_expires_at,
+  attempts,
+  max_attempts,
+  idempotency_key,
+  last_error_code,
+  last_error_message,
+  updated_at,
+  data_key_id
+)
+SELECT
+  id,
+  user_id,
+  type,
+  due_at,
+  state,
+  payload_ciphertext,
+  last_run_at,
+  next_run_at,
+  lease_owner,
+  lease_expires_at,
+  attempts,
+  max_attempts,
+  idempotency_key,
+  last_error_code,
+  last_error_message,
+  updated_at,
+  data_key_id
+FROM jobs_unpartitioned;
+
+DROP TABLE jobs_unpartitioned;
+
+CREATE INDEX IF NOT EXISTS idx_jobs_user_id ON jobs (user_id);
+
+CREATE INDEX IF NOT EXISTS jobs_data_key_id_idx ON jobs (data_key_id);
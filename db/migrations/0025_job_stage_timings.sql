@@ -0,0 +1,7 @@
+-- Per-attempt timing for the last run of each job so slow stages can be attributed to a
+-- single job instead of only showing up in per-tick aggregates. stage_timings holds
+-- millisecond durations keyed by stage (claim_ms, fetch_ms, generate_ms, deliver_ms).
+ALTER TABLE jobs
+  ADD COLUMN IF NOT EXISTS started_at TIMESTAMPTZ NULL,
+  ADD COLUMN IF NOT EXISTS finished_at TIMESTAMPTZ NULL,
+  ADD COLUMN IF NOT EXISTS stage_timings JSONB NULL;
@@ -0,0 +1,20 @@
+-- Per-user hash chain over audit_events. Each chained row stores the previous row's hash and
+-- entry_hash = SHA-256(prev_hash || canonical fields), so editing, deleting, or reordering
+-- history breaks verification. Rows written before this migration keep NULL chain columns.
+ALTER TABLE audit_events
+  ADD COLUMN IF NOT EXISTS chain_seq BIGINT NULL,
+  ADD COLUMN IF NOT EXISTS prev_hash BYTEA NULL,
+  ADD COLUMN IF NOT EXISTS entry_hash BYTEA NULL;
+
+CREATE UNIQUE INDEX IF NOT EXISTS audit_events_user_chain_seq_idx
+  ON audit_events (user_id, chain_seq)
+  WHERE chain_seq IS NOT NULL;
+
+-- Chain tip per user. Appends lock this row so concurrent writers serialize per user, and
+-- verification compares against it to detect truncated tails.
+CREATE TABLE IF NOT EXISTS audit_chain_heads (
+  user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+  last_seq BIGINT NOT NULL DEFAULT 0,
+  last_hash BYTEA NULL,
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
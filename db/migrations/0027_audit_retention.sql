@@ -0,0 +1,15 @@
+-- Retention purges delete audit rows by per-event-type TTL, which can remove rows from the
+-- middle of a user's hash chain. Each purged chained row leaves a tombstone with only its
+-- chain position and hashes so verification can still link the surviving rows. Tombstones
+-- older than a user's oldest surviving row are compacted away.
+CREATE TABLE IF NOT EXISTS audit_chain_tombstones (
+  user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+  chain_seq BIGINT NOT NULL,
+  prev_hash BYTEA NULL,
+  entry_hash BYTEA NOT NULL,
+  purged_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (user_id, chain_seq)
+);
+
+CREATE INDEX IF NOT EXISTS audit_events_created_at_idx
+  ON audit_events (created_at);
@@ -0,0 +1,6 @@
+-- Typed audit metadata. New writes keep filling the legacy string-map redacted_metadata
+-- column so instances still on the old reader keep working during a rolling deploy, and add
+-- the same redacted values with their JSON types in typed_metadata. Readers prefer
+-- typed_metadata and fall back to redacted_metadata for rows written before this migration.
+ALTER TABLE audit_events
+  ADD COLUMN IF NOT EXISTS typed_metadata JSONB NULL;
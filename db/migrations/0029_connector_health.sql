@@ -0,0 +1,11 @@
+ALTER TABLE connectors
+ADD COLUMN IF NOT EXISTS health_score SMALLINT NOT NULL DEFAULT 100,
+ADD COLUMN IF NOT EXISTS refresh_failure_count INTEGER NOT NULL DEFAULT 0,
+ADD COLUMN IF NOT EXISTS provider_unauthorized_count INTEGER NOT NULL DEFAULT 0,
+ADD COLUMN IF NOT EXISTS missing_scopes TEXT[] NOT NULL DEFAULT '{}',
+ADD COLUMN IF NOT EXISTS health_updated_at TIMESTAMPTZ,
+ADD COLUMN IF NOT EXISTS reauth_nudged_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS connectors_reauth_candidates_idx
+ON connectors (health_score, reauth_nudged_at)
+WHERE status = 'ACTIVE';
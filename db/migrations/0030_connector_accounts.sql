@@ -0,0 +1,10 @@
+-- Connectors created before account identities were captured keep the 'primary' key, so a
+-- reconnect without an id_token still updates the legacy row instead of duplicating it.
+ALTER TABLE connectors
+ADD COLUMN IF NOT EXISTS account_key TEXT NOT NULL DEFAULT 'primary';
+
+ALTER TABLE connectors
+DROP CONSTRAINT IF EXISTS connectors_user_id_provider_key;
+
+ALTER TABLE connectors
+ADD CONSTRAINT connectors_user_provider_account_key UNIQUE (user_id, provider, account_key);
@@ -0,0 +1,8 @@
+-- CalDAV connectors keep their credentials (server URL, username, app password) as an
+-- encrypted JSON document in refresh_token_ciphertext; account_key hashes server and username.
+ALTER TABLE connectors
+  DROP CONSTRAINT IF EXISTS connectors_provider_check;
+
+ALTER TABLE connectors
+  ADD CONSTRAINT connectors_provider_check
+  CHECK (provider IN ('google', 'caldav'));
@@ -0,0 +1,8 @@
+-- IMAP connectors keep their credentials (host, port, username, password) as an encrypted
+-- JSON document in refresh_token_ciphertext; account_key hashes host and username.
+ALTER TABLE connectors
+  DROP CONSTRAINT IF EXISTS connectors_provider_check;
+
+ALTER TABLE connectors
+  ADD CONSTRAINT connectors_provider_check
+  CHECK (provider IN ('google', 'caldav', 'imap'));
@@ -0,0 +1,30 @@
+-- Content-free index of assistant queries. Rows hold no query or response text, only routing,
+-- model, fallback, and latency metadata keyed by the client request_id so feedback and quality
+-- dashboards can join on it.
+CREATE TABLE IF NOT EXISTS assistant_request_index (
+  user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+  request_id TEXT NOT NULL,
+  session_id UUID NULL,
+  outcome TEXT NOT NULL,
+  error_code TEXT NULL,
+  route TEXT NULL,
+  capability TEXT NULL,
+  model TEXT NULL,
+  planner_used_fallback BOOLEAN NOT NULL DEFAULT FALSE,
+  output_used_fallback BOOLEAN NOT NULL DEFAULT FALSE,
+  planner_ms INTEGER NULL,
+  lane_ms INTEGER NULL,
+  enclave_rpc_ms INTEGER NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (user_id, request_id)
+);
+
+ALTER TABLE assistant_request_index
+  DROP CONSTRAINT IF EXISTS assistant_request_index_outcome_check;
+
+ALTER TABLE assistant_request_index
+  ADD CONSTRAINT assistant_request_index_outcome_check
+  CHECK (outcome IN ('SUCCEEDED', 'FAILED'));
+
+CREATE INDEX IF NOT EXISTS assistant_request_index_route_created_idx
+  ON assistant_request_index (route, created_at DESC);
@@ -0,0 +1,3 @@
+-- Supports the rolling, cross-user health window behind GET /v1/status.
+CREATE INDEX IF NOT EXISTS assistant_request_index_created_idx
+  ON assistant_request_index (created_at DESC);
@@ -0,0 +1,7 @@
+-- An OAuth state minted by POST /v1/connectors/{id}/scopes/upgrade remembers which connector
+-- it upgrades and which scopes were asked for, so the shared callback can merge and audit them.
+ALTER TABLE oauth_states
+  ADD COLUMN IF NOT EXISTS upgrade_connector_id UUID NULL REFERENCES connectors(id) ON DELETE CASCADE;
+
+ALTER TABLE oauth_states
+  ADD COLUMN IF NOT EXISTS upgrade_scopes TEXT[] NULL;
@@ -0,0 +1,20 @@
+-- Hourly success counters per user-facing component, fed by the worker, API server, and
+-- enclave. Rows are aggregate-only and carry no user identifiers.
+CREATE TABLE IF NOT EXISTS component_availability (
+  component TEXT NOT NULL,
+  bucket_start TIMESTAMPTZ NOT NULL,
+  attempts BIGINT NOT NULL DEFAULT 0,
+  successes BIGINT NOT NULL DEFAULT 0,
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (component, bucket_start)
+);
+
+ALTER TABLE component_availability
+  DROP CONSTRAINT IF EXISTS component_availability_component_check;
+
+ALTER TABLE component_availability
+  ADD CONSTRAINT component_availability_component_check
+  CHECK (component IN ('push_delivery', 'assistant', 'connectors', 'automations'));
+
+CREATE INDEX IF NOT EXISTS component_availability_bucket_idx
+  ON component_availability (bucket_start);
@@ -0,0 +1,2 @@
+ALTER TABLE devices
+  ADD COLUMN IF NOT EXISTS last_delivery_at TIMESTAMPTZ NULL;
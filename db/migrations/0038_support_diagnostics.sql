@@ -0,0 +1,19 @@
+-- Client-encrypted diagnostic bundles uploaded for support tickets. The server never holds
+-- the key; rows expire and are purged by the worker.
+CREATE TABLE IF NOT EXISTS support_diagnostics (
+  id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+  user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+  ticket_reference TEXT NOT NULL,
+  algorithm TEXT NOT NULL,
+  key_id TEXT NOT NULL,
+  ciphertext BYTEA NOT NULL,
+  size_bytes INTEGER NOT NULL CHECK (size_bytes > 0),
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  expires_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_support_diagnostics_user_created
+  ON support_diagnostics (user_id, created_at DESC);
+
+CREATE INDEX IF NOT EXISTS idx_support_diagnostics_expires_at_id
+  ON support_diagnostics (expires_at, id);
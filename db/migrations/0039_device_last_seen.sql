@@ -0,0 +1,14 @@
+-- Last time the device proved it is still installed: a registration or a successful push.
+ALTER TABLE devices
+  ADD COLUMN IF NOT EXISTS last_seen_at TIMESTAMPTZ NULL;
+
+UPDATE devices
+SET last_seen_at = GREATEST(updated_at, COALESCE(last_delivery_at, updated_at))
+WHERE last_seen_at IS NULL;
+
+ALTER TABLE devices
+  ALTER COLUMN last_seen_at SET DEFAULT NOW(),
+  ALTER COLUMN last_seen_at SET NOT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_devices_last_seen_at
+  ON devices (last_seen_at, id);
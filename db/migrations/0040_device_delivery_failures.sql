@@ -0,0 +1,6 @@
+-- Per-device delivery failure scoring. Devices that APNs keeps rejecting are disabled and
+-- skipped by notification fan-out until the user re-enables or re-registers them.
+ALTER TABLE devices
+  ADD COLUMN IF NOT EXISTS consecutive_failures INTEGER NOT NULL DEFAULT 0,
+  ADD COLUMN IF NOT EXISTS disabled_at TIMESTAMPTZ NULL,
+  ADD COLUMN IF NOT EXISTS disabled_reason TEXT NULL;
@@ -0,0 +1,5 @@
+-- App bundle identifier the device registered from. Beta and production builds ship under
+-- different bundle ids and need pushes routed to the matching APNs topic; NULL uses the
+-- worker's default topic.
+ALTER TABLE devices
+  ADD COLUMN IF NOT EXISTS app_bundle_id TEXT NULL;
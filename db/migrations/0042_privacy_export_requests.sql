@@ -0,0 +1,33 @@
+-- GDPR data export ("takeout") requests. The worker assembles the user's metadata into an
+-- archive encrypted with the data encryption key; the user downloads it once through a
+-- short-lived token before the archive expires.
+CREATE TABLE IF NOT EXISTS privacy_export_requests (
+  id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+  user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+  status TEXT NOT NULL CHECK (status IN ('QUEUED', 'RUNNING', 'COMPLETED', 'FAILED', 'EXPIRED')),
+  archive_ciphertext BYTEA NULL,
+  archive_size_bytes INTEGER NULL,
+  data_key_id TEXT NULL,
+  download_token_hash BYTEA NULL,
+  download_token_expires_at TIMESTAMPTZ NULL,
+  downloaded_at TIMESTAMPTZ NULL,
+  expires_at TIMESTAMPTZ NULL,
+  lease_owner TEXT NULL,
+  lease_expires_at TIMESTAMPTZ NULL,
+  failure_reason TEXT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  started_at TIMESTAMPTZ NULL,
+  completed_at TIMESTAMPTZ NULL,
+  failed_at TIMESTAMPTZ NULL,
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_privacy_export_requests_user_created
+  ON privacy_export_requests (user_id, created_at DESC);
+
+CREATE INDEX IF NOT EXISTS idx_privacy_export_requests_status_created
+  ON privacy_export_requests (status, created_at ASC);
+
+CREATE INDEX IF NOT EXISTS idx_privacy_export_requests_archive_expiry
+  ON privacy_export_requests (expires_at ASC)
+  WHERE archive_ciphertext IS NOT NULL;
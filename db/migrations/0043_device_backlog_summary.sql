@@ -0,0 +1,4 @@
+-- Set when a newly registered device receives its one-time "you're all set" summary in place
+-- of notifications that were queued before the device existed.
+ALTER TABLE devices
+  ADD COLUMN IF NOT EXISTS backlog_summary_sent_at TIMESTAMPTZ NULL;
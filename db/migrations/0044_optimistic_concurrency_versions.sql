@@ -0,0 +1,8 @@
+-- Versions for optimistic concurrency on user-edited settings. Each user update bumps the
+-- version; clients send the version they last read and get 409 Conflict if it moved on.
+-- Worker bookkeeping (next run/check times, leases) leaves the version untouched.
+ALTER TABLE automation_rules
+  ADD COLUMN IF NOT EXISTS version BIGINT NOT NULL DEFAULT 1;
+
+ALTER TABLE departure_alert_preferences
+  ADD COLUMN IF NOT EXISTS version BIGINT NOT NULL DEFAULT 1;
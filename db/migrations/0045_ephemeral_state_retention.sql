@@ -0,0 +1,8 @@
+-- The worker reclaims consumed or expired OAuth states and idempotency keys older than the
+-- configured retention window. These indexes keep its batched deletes off sequential scans.
+CREATE INDEX IF NOT EXISTS idx_oauth_states_consumed_at
+    ON oauth_states (consumed_at)
+    WHERE consumed_at IS NOT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_outbound_action_idempotency_created_at
+    ON outbound_action_idempotency (created_at);
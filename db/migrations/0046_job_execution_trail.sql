@@ -0,0 +1,27 @@
+-- Correlates a job with the enclave RPC that ran it and the per-device pushes it produced,
+-- so one lookup can explain what happened to a given run.
+ALTER TABLE jobs
+  ADD COLUMN IF NOT EXISTS enclave_request_id TEXT NULL;
+
+-- job_id is a plain id because partitioned jobs cannot be a foreign key target. Rows are
+-- pruned with finished job history and removed with the user.
+CREATE TABLE IF NOT EXISTS notification_deliveries (
+  id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+  user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+  job_id UUID NOT NULL,
+  job_attempt INT NOT NULL,
+  enclave_request_id TEXT NULL,
+  device_id TEXT NOT NULL,
+  outcome TEXT NOT NULL,
+  push_payload_mode TEXT NULL,
+  error_code TEXT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  CONSTRAINT notification_deliveries_outcome_check
+    CHECK (outcome IN ('DELIVERED', 'FAILED', 'SUPPRESSED_BACKLOG'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_notification_deliveries_job_id
+  ON notification_deliveries (job_id, created_at);
+
+CREATE INDEX IF NOT EXISTS idx_notification_deliveries_created_at
+  ON notification_deliveries (created_at);
@@ -0,0 +1,8 @@
+-- Thread drawer metadata. The title is sealed on the client with a key the host never holds,
+-- so only the opaque envelope is stored here.
+ALTER TABLE assistant_encrypted_sessions
+  ADD COLUMN IF NOT EXISTS title_envelope_json TEXT NULL,
+  ADD COLUMN IF NOT EXISTS pinned BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE INDEX IF NOT EXISTS idx_assistant_encrypted_sessions_user_pinned_updated
+  ON assistant_encrypted_sessions (user_id, pinned DESC, updated_at DESC, session_id DESC);
@@ -0,0 +1,18 @@
+-- User-initiated "run now" requests. One row per client request id makes retries idempotent
+-- and lets the API enforce a per-user daily cap. rule_id is a plain id so deleting and
+-- recreating an automation does not reset the day's count.
+CREATE TABLE IF NOT EXISTS automation_manual_runs (
+  id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+  user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+  rule_id UUID NOT NULL,
+  request_id TEXT NOT NULL,
+  job_id UUID NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  UNIQUE (user_id, request_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_automation_manual_runs_user_created_at
+  ON automation_manual_runs (user_id, created_at DESC);
+
+CREATE INDEX IF NOT EXISTS idx_automation_manual_runs_created_at
+  ON automation_manual_runs (created_at);
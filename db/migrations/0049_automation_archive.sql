@@ -0,0 +1,11 @@
+-- Archived rules are retired by the user but keep their run and report history. They are
+-- never claimed by the scheduler because claiming only considers ACTIVE rules.
+ALTER TABLE automation_rules
+  DROP CONSTRAINT IF EXISTS automation_rules_status_check;
+
+ALTER TABLE automation_rules
+  ADD CONSTRAINT automation_rules_status_check
+  CHECK (status IN ('ACTIVE', 'PAUSED', 'ARCHIVED'));
+
+CREATE INDEX IF NOT EXISTS idx_automation_rules_user_status_created
+  ON automation_rules (user_id, status, created_at DESC, id DESC);
@@ -0,0 +1,21 @@
+-- Per-minute push delivery latency counters, fed by the worker. A delivery is within target
+-- when it reached APNs within the SLO target of the job's due_at. Rows are aggregate-only.
+CREATE TABLE IF NOT EXISTS push_delivery_latency (
+  bucket_start TIMESTAMPTZ PRIMARY KEY,
+  deliveries BIGINT NOT NULL DEFAULT 0,
+  within_target BIGINT NOT NULL DEFAULT 0,
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Latest rolling SLO evaluation, one row per SLO. Read by the public status endpoint.
+CREATE TABLE IF NOT EXISTS slo_status (
+  slo TEXT PRIMARY KEY CHECK (slo IN ('push_delivery_latency')),
+  objective_percent INT NOT NULL CHECK (objective_percent BETWEEN 1 AND 100),
+  target_seconds BIGINT NOT NULL CHECK (target_seconds > 0),
+  window_minutes BIGINT NOT NULL CHECK (window_minutes > 0),
+  total_events BIGINT NOT NULL,
+  good_events BIGINT NOT NULL,
+  breached BOOLEAN NOT NULL,
+  breached_since TIMESTAMPTZ NULL,
+  evaluated_at TIMESTAMPTZ NOT NULL
+);
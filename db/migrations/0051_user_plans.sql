@@ -0,0 +1,11 @@
+-- Subscription tier per user. Users without a row are on the free plan.
+CREATE TABLE IF NOT EXISTS user_plans (
+  user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+  plan TEXT NOT NULL CHECK (plan IN ('free', 'pro')),
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Per-user daily counts for quota checks.
+CREATE INDEX IF NOT EXISTS assistant_request_index_user_created_idx
+  ON assistant_request_index (user_id, created_at DESC);
@@ -0,0 +1,5 @@
+-- Operators can replay a dead-lettered job from the admin API. The replay is a fresh PENDING
+-- job; the dead-letter row is kept and points at it so a second replay is a no-op.
+ALTER TABLE dead_letter_jobs
+  ADD COLUMN IF NOT EXISTS replayed_at TIMESTAMPTZ NULL,
+  ADD COLUMN IF NOT EXISTS replay_job_id UUID NULL;
@@ -0,0 +1,28 @@
+-- Last change to a connector row, used to build the weak ETag on the connector list. Rows are
+-- updated from many places (health checks, key rotation, revoke, reconnect), so a trigger
+-- keeps the timestamp honest instead of every statement having to remember it.
+ALTER TABLE connectors
+  ADD COLUMN IF NOT EXISTS updated_at TIMESTAMPTZ NULL;
+
+UPDATE connectors
+SET updated_at = GREATEST(created_at, token_rotated_at, revoked_at, health_updated_at)
+WHERE updated_at IS NULL;
+
+ALTER TABLE connectors
+  ALTER COLUMN updated_at SET DEFAULT NOW(),
+  ALTER COLUMN updated_at SET NOT NULL;
+
+CREATE OR REPLACE FUNCTION alfred_touch_updated_at()
+RETURNS TRIGGER
+LANGUAGE plpgsql
+AS $$
+BEGIN
+  NEW.updated_at := NOW();
+  RETURN NEW;
+END $$;
+
+DROP TRIGGER IF EXISTS connectors_touch_updated_at ON connectors;
+CREATE TRIGGER connectors_touch_updated_at
+  BEFORE UPDATE ON connectors
+  FOR EACH ROW
+  EXECUTE FUNCTION alfred_touch_updated_at();
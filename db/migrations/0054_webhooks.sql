@@ -0,0 +1,50 @@
+-- Developer webhook endpoints. The signing secret is encrypted at rest like other
+-- credentials and is only returned to the client once, when the subscription is created.
+CREATE TABLE IF NOT EXISTS webhook_subscriptions (
+  id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+  user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+  url TEXT NOT NULL,
+  event_types TEXT[] NOT NULL CHECK (cardinality(event_types) > 0),
+  signing_secret_ciphertext BYTEA NOT NULL,
+  data_key_id TEXT NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_subscriptions_user_created
+  ON webhook_subscriptions (user_id, created_at, id);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_subscriptions_data_key_id
+  ON webhook_subscriptions (data_key_id);
+
+-- One row per (subscription, event). `payload` is the metadata-only event body; it never
+-- carries user content. A claimed row's `next_attempt_at` is pushed out by the worker lease,
+-- so a crashed worker's delivery is picked up again once the lease lapses.
+CREATE TABLE IF NOT EXISTS webhook_deliveries (
+  id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+  subscription_id UUID NOT NULL REFERENCES webhook_subscriptions(id) ON DELETE CASCADE,
+  user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+  event_id UUID NOT NULL,
+  event_type TEXT NOT NULL,
+  payload JSONB NOT NULL,
+  occurred_at TIMESTAMPTZ NOT NULL,
+  state TEXT NOT NULL DEFAULT 'PENDING'
+    CHECK (state IN ('PENDING', 'DELIVERED', 'FAILED')),
+  attempts INTEGER NOT NULL DEFAULT 0,
+  next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  last_status_code INTEGER NULL,
+  last_error TEXT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  delivered_at TIMESTAMPTZ NULL,
+  UNIQUE (subscription_id, event_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_pending_due
+  ON webhook_deliveries (next_attempt_at, id)
+  WHERE state = 'PENDING';
+
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_subscription_created
+  ON webhook_deliveries (subscription_id, created_at DESC, id DESC);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_user_id
+  ON webhook_deliveries (user_id);
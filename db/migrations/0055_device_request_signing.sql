@@ -0,0 +1,18 @@
+-- Ed25519 public key a device signs its API requests with. It is a public key, so it is
+-- stored in the clear; NULL means the device has not enrolled in request signing.
+ALTER TABLE devices
+  ADD COLUMN IF NOT EXISTS request_signing_public_key TEXT NULL,
+  ADD COLUMN IF NOT EXISTS request_signing_key_registered_at TIMESTAMPTZ NULL;
+
+-- Nonces of accepted signed requests, kept until the signed timestamp falls outside the
+-- allowed clock skew so a captured request cannot be replayed inside that window.
+CREATE TABLE IF NOT EXISTS request_signature_nonces (
+  user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+  device_identifier TEXT NOT NULL,
+  nonce TEXT NOT NULL,
+  expires_at TIMESTAMPTZ NOT NULL,
+  PRIMARY KEY (user_id, device_identifier, nonce)
+);
+
+CREATE INDEX IF NOT EXISTS idx_request_signature_nonces_expires_at
+  ON request_signature_nonces (expires_at);
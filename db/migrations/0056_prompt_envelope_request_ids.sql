@@ -0,0 +1,18 @@
+-- Request ids of every encrypted prompt envelope the API accepted. Envelopes carry no expiry,
+-- so a captured envelope stays decryptable; one row per (user, request_id) lets the API reject
+-- it when it is submitted again, whether as a new automation prompt or an assistant query.
+-- Rows hold no envelope content and go away with the user.
+CREATE TABLE IF NOT EXISTS prompt_envelope_request_ids (
+  user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+  request_id TEXT NOT NULL,
+  used_for TEXT NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (user_id, request_id)
+);
+
+ALTER TABLE prompt_envelope_request_ids
+  DROP CONSTRAINT IF EXISTS prompt_envelope_request_ids_used_for_check;
+
+ALTER TABLE prompt_envelope_request_ids
+  ADD CONSTRAINT prompt_envelope_request_ids_used_for_check
+  CHECK (used_for IN ('AUTOMATION_CREATE', 'AUTOMATION_UPDATE', 'ASSISTANT_QUERY'));
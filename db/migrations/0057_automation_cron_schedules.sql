@@ -0,0 +1,45 @@
+-- Cron schedules keep their 5-field expression and are evaluated in the rule's time zone.
+-- They carry no local time or anchors, so local_time_minutes is stored as 0.
+ALTER TABLE automation_rules
+  ADD COLUMN IF NOT EXISTS cron_expression TEXT NULL;
+
+ALTER TABLE automation_rules
+  DROP CONSTRAINT IF EXISTS automation_rules_schedule_type_check;
+
+ALTER TABLE automation_rules
+  ADD CONSTRAINT automation_rules_schedule_type_check
+  CHECK (schedule_type IN ('DAILY', 'WEEKLY', 'MONTHLY', 'ANNUALLY', 'CRON'));
+
+ALTER TABLE automation_rules
+  DROP CONSTRAINT IF EXISTS automation_rules_schedule_anchor_check;
+
+ALTER TABLE automation_rules
+  ADD CONSTRAINT automation_rules_schedule_anchor_check
+  CHECK (
+    (schedule_type = 'DAILY'
+      AND anchor_day_of_week IS NULL
+      AND anchor_day_of_month IS NULL
+      AND anchor_month IS NULL
+      AND cron_expression IS NULL)
+    OR (schedule_type = 'WEEKLY'
+      AND anchor_day_of_week BETWEEN 1 AND 7
+      AND anchor_day_of_month IS NULL
+      AND anchor_month IS NULL
+      AND cron_expression IS NULL)
+    OR (schedule_type = 'MONTHLY'
+      AND anchor_day_of_week IS NULL
+      AND anchor_day_of_month BETWEEN 1 AND 31
+      AND anchor_month IS NULL
+      AND cron_expression IS NULL)
+    OR (schedule_type = 'ANNUALLY'
+      AND anchor_day_of_week IS NULL
+      AND anchor_day_of_month BETWEEN 1 AND 31
+      AND anchor_month BETWEEN 1 AND 12
+      AND cron_expression IS NULL)
+    OR (schedule_type = 'CRON'
+      AND anchor_day_of_week IS NULL
+      AND anchor_day_of_month IS NULL
+      AND anchor_month IS NULL
+      AND local_time_minutes = 0
+      AND cron_expression IS NOT NULL)
+  );
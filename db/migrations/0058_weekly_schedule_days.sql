@@ -0,0 +1,46 @@
+-- Weekly schedules run on any set of weekdays. The single anchor weekday becomes a bitmap
+-- (bit 0 = Monday through bit 6 = Sunday) so existing rules keep their day.
+ALTER TABLE automation_rules
+  ADD COLUMN IF NOT EXISTS anchor_days_of_week SMALLINT;
+
+UPDATE automation_rules
+SET anchor_days_of_week = (1 << (anchor_day_of_week - 1))::SMALLINT
+WHERE anchor_day_of_week IS NOT NULL
+  AND anchor_days_of_week IS NULL;
+
+ALTER TABLE automation_rules
+  DROP CONSTRAINT IF EXISTS automation_rules_schedule_anchor_check;
+
+ALTER TABLE automation_rules
+  DROP COLUMN IF EXISTS anchor_day_of_week;
+
+ALTER TABLE automation_rules
+  ADD CONSTRAINT automation_rules_schedule_anchor_check
+  CHECK (
+    (schedule_type = 'DAILY'
+      AND anchor_days_of_week IS NULL
+      AND anchor_day_of_month IS NULL
+      AND anchor_month IS NULL
+      AND cron_expression IS NULL)
+    OR (schedule_type = 'WEEKLY'
+      AND anchor_days_of_week BETWEEN 1 AND 127
+      AND anchor_day_of_month IS NULL
+      AND anchor_month IS NULL
+      AND cron_expression IS NULL)
+    OR (schedule_type = 'MONTHLY'
+      AND anchor_days_of_week IS NULL
+      AND anchor_day_of_month BETWEEN 1 AND 31
+      AND anchor_month IS NULL
+      AND cron_expression IS NULL)
+    OR (schedule_type = 'ANNUALLY'
+      AND anchor_days_of_week IS NULL
+      AND anchor_day_of_month BETWEEN 1 AND 31
+      AND anchor_month BETWEEN 1 AND 12
+      AND cron_expression IS NULL)
+    OR (schedule_type = 'CRON'
+      AND anchor_days_of_week IS NULL
+      AND anchor_day_of_month IS NULL
+      AND anchor_month IS NULL
+      AND local_time_minutes = 0
+      AND cron_expression IS NOT NULL)
+  );
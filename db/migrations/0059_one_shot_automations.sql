@@ -0,0 +1,65 @@
+-- One-shot (ONCE) schedules carry the single instant they run at. They have no local time,
+-- anchors, or cron expression, so local_time_minutes is stored as 0. Once its run is
+-- materialized the scheduler moves the rule to COMPLETED, which it never claims again.
+ALTER TABLE automation_rules
+  ADD COLUMN IF NOT EXISTS run_at TIMESTAMPTZ NULL;
+
+ALTER TABLE automation_rules
+  DROP CONSTRAINT IF EXISTS automation_rules_status_check;
+
+ALTER TABLE automation_rules
+  ADD CONSTRAINT automation_rules_status_check
+  CHECK (status IN ('ACTIVE', 'PAUSED', 'ARCHIVED', 'COMPLETED'));
+
+ALTER TABLE automation_rules
+  DROP CONSTRAINT IF EXISTS automation_rules_schedule_type_check;
+
+ALTER TABLE automation_rules
+  ADD CONSTRAINT automation_rules_schedule_type_check
+  CHECK (schedule_type IN ('DAILY', 'WEEKLY', 'MONTHLY', 'ANNUALLY', 'CRON', 'ONCE'));
+
+ALTER TABLE automation_rules
+  DROP CONSTRAINT IF EXISTS automation_rules_schedule_anchor_check;
+
+ALTER TABLE automation_rules
+  ADD CONSTRAINT automation_rules_schedule_anchor_check
+  CHECK (
+    (schedule_type = 'DAILY'
+      AND anchor_days_of_week IS NULL
+      AND anchor_day_of_month IS NULL
+      AND anchor_month IS NULL
+      AND cron_expression IS NULL
+      AND run_at IS NULL)
+    OR (schedule_type = 'WEEKLY'
+      AND anchor_days_of_week BETWEEN 1 AND 127
+      AND anchor_day_of_month IS NULL
+      AND anchor_month IS NULL
+      AND cron_expression IS NULL
+      AND run_at IS NULL)
+    OR (schedule_type = 'MONTHLY'
+      AND anchor_days_of_week IS NULL
+      AND anchor_day_of_month BETWEEN 1 AND 31
+      AND anchor_month IS NULL
+      AND cron_expression IS NULL
+      AND run_at IS NULL)
+    OR (schedule_type = 'ANNUALLY'
+      AND anchor_days_of_week IS NULL
+      AND anchor_day_of_month BETWEEN 1 AND 31
+      AND anchor_month BETWEEN 1 AND 12
+      AND cron_expression IS NULL
+      AND run_at IS NULL)
+    OR (schedule_type = 'CRON'
+      AND anchor_days_of_week IS NULL
+      AND anchor_day_of_month IS NULL
+      AND anchor_month IS NULL
+      AND local_time_minutes = 0
+      AND cron_expression IS NOT NULL
+      AND run_at IS NULL)
+    OR (schedule_type = 'ONCE'
+      AND anchor_days_of_week IS NULL
+      AND anchor_day_of_month IS NULL
+      AND anchor_month IS NULL
+      AND local_time_minutes = 0
+      AND cron_expression IS NULL
+      AND run_at IS NOT NULL)
+  );
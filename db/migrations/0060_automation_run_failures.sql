@@ -0,0 +1,5 @@
+-- Failed runs keep the dead-letter error code so the run history says why they failed, and
+-- the run whose failure streak paused its rule is flagged.
+ALTER TABLE automation_runs
+  ADD COLUMN IF NOT EXISTS failure_code TEXT NULL,
+  ADD COLUMN IF NOT EXISTS paused_rule BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,7 @@
+-- A snoozed rule is paused until `paused_until`, after which the worker resumes it.
+ALTER TABLE automation_rules
+  ADD COLUMN IF NOT EXISTS paused_until TIMESTAMPTZ NULL;
+
+CREATE INDEX IF NOT EXISTS idx_automation_rules_snooze_due
+  ON automation_rules (paused_until)
+  WHERE status = 'PAUSED' AND paused_until IS NOT NULL;
@@ -0,0 +1,21 @@
+-- Optional condition a scheduled run must meet before it notifies, evaluated in the enclave.
+ALTER TABLE automation_rules
+  ADD COLUMN IF NOT EXISTS condition_kind TEXT NULL,
+  ADD COLUMN IF NOT EXISTS condition_min_count SMALLINT NULL;
+
+ALTER TABLE automation_rules
+  DROP CONSTRAINT IF EXISTS automation_rules_condition_check;
+
+ALTER TABLE automation_rules
+  ADD CONSTRAINT automation_rules_condition_check CHECK (
+    (condition_kind IS NULL AND condition_min_count IS NULL)
+    OR (
+      condition_kind IN ('CALENDAR_EVENTS_TODAY', 'URGENT_EMAILS')
+      AND condition_min_count BETWEEN 1 AND 10
+    )
+  );
+
+-- What the enclave observed when it evaluated the rule's condition for a run.
+ALTER TABLE automation_runs
+  ADD COLUMN IF NOT EXISTS condition_met BOOLEAN NULL,
+  ADD COLUMN IF NOT EXISTS condition_observed_count INTEGER NULL;
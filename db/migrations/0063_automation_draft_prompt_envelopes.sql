@@ -0,0 +1,8 @@
+-- Natural-language automation drafts send an encrypted prompt to the enclave as well, so
+-- their envelope request ids are claimed the same way to block replays.
+ALTER TABLE prompt_envelope_request_ids
+  DROP CONSTRAINT IF EXISTS prompt_envelope_request_ids_used_for_check;
+
+ALTER TABLE prompt_envelope_request_ids
+  ADD CONSTRAINT prompt_envelope_request_ids_used_for_check
+  CHECK (used_for IN ('AUTOMATION_CREATE', 'AUTOMATION_UPDATE', 'AUTOMATION_DRAFT', 'ASSISTANT_QUERY'));
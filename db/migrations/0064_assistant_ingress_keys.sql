@@ -0,0 +1,19 @@
+-- Assistant ingress keys generated by the enclave rotation job. Private keys are sealed inside
+-- the enclave before they are written, so rows only ever hold wrapped key material.
+CREATE TABLE IF NOT EXISTS assistant_ingress_keys (
+  key_id TEXT PRIMARY KEY,
+  public_key TEXT NOT NULL,
+  wrapped_private_key BYTEA NOT NULL,
+  wrap_nonce BYTEA NOT NULL,
+  wrapping_key_id TEXT NOT NULL,
+  status TEXT NOT NULL CHECK (status IN ('ACTIVE', 'PREVIOUS')),
+  activated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  expires_at TIMESTAMPTZ,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  CHECK (status = 'ACTIVE' OR expires_at IS NOT NULL)
+);
+
+-- At most one key is handed out to clients at a time.
+CREATE UNIQUE INDEX IF NOT EXISTS assistant_ingress_keys_single_active_idx
+  ON assistant_ingress_keys (status)
+  WHERE status = 'ACTIVE';
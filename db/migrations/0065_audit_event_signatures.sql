@@ -0,0 +1,7 @@
+-- Enclave signature over high-value audit events (connector changes, privacy deletes,
+-- automation runs). The signature is a base64 Ed25519 signature by the enclave attestation key
+-- over the row's entry_hash and identity. It is filled in after the row is chained, so it is
+-- NULL for unsigned event types, for rows written while signing was disabled, and for rows
+-- whose signing call failed.
+ALTER TABLE audit_events
+  ADD COLUMN IF NOT EXISTS signature TEXT NULL;
@@ -0,0 +1,16 @@
+-- Clerk organization a user last used the API from. NULL for users who never had an active
+-- organization in their session.
+ALTER TABLE users ADD COLUMN IF NOT EXISTS org_id TEXT;
+
+CREATE INDEX IF NOT EXISTS users_org_id_idx
+  ON users (org_id)
+  WHERE org_id IS NOT NULL;
+
+-- Subscription tier granted to every member of an organization. A member gets the higher of
+-- this and their own user_plans row.
+CREATE TABLE IF NOT EXISTS organization_plans (
+  org_id TEXT PRIMARY KEY,
+  plan TEXT NOT NULL CHECK (plan IN ('free', 'pro')),
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
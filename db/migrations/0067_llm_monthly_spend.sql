@@ -0,0 +1,10 @@
+-- Estimated LLM provider spend per user per UTC month, checked against the plan's monthly
+-- budget before every enclave LLM call. Amounts are millionths of a US dollar.
+CREATE TABLE IF NOT EXISTS llm_monthly_spend (
+  user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+  month_start DATE NOT NULL,
+  spent_micros BIGINT NOT NULL DEFAULT 0 CHECK (spent_micros >= 0),
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (user_id, month_start)
+);
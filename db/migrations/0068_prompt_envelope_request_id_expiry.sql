@@ -0,0 +1,16 @@
+-- An envelope only decrypts while the ingress key it was sealed to is live, so a request id
+-- needs to be remembered only that long. `expires_at` copies the key's expiry: it stays NULL
+-- while the key is active and is set when rotation demotes the key. The ephemeral-state purge
+-- deletes rows past it. Rows claimed before this migration, and envelopes sealed to keys that
+-- are not rotated through `assistant_ingress_keys`, keep a NULL expiry and are never purged.
+ALTER TABLE prompt_envelope_request_ids
+  ADD COLUMN IF NOT EXISTS key_id TEXT NULL,
+  ADD COLUMN IF NOT EXISTS expires_at TIMESTAMPTZ NULL;
+
+CREATE INDEX IF NOT EXISTS prompt_envelope_request_ids_expires_at_idx
+  ON prompt_envelope_request_ids (expires_at)
+  WHERE expires_at IS NOT NULL;
+
+CREATE INDEX IF NOT EXISTS prompt_envelope_request_ids_unexpired_key_idx
+  ON prompt_envelope_request_ids (key_id)
+  WHERE expires_at IS NULL;
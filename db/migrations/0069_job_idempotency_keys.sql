@@ -0,0 +1,31 @@
+-- The unique idempotency index on jobs covers only the active partition, so an enqueue racing a
+-- job's move into jobs_finished could insert a duplicate. Keys now live in an unpartitioned
+-- table with its own primary key; enqueue claims the key row first, which serializes every
+-- enqueue for the same key, and a job keeps the id recorded here across partitions.
+--
+-- Rows are removed with the user and with their job when finished-job partitions are pruned.
+
+CREATE TABLE IF NOT EXISTS job_idempotency_keys (
+  user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+  type TEXT NOT NULL,
+  idempotency_key TEXT NOT NULL,
+  job_id UUID NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (user_id, type, idempotency_key)
+);
+
+CREATE INDEX IF NOT EXISTS job_idempotency_keys_job_id_idx
+  ON job_idempotency_keys (job_id);
+
+-- Active keys are unique already; among finished duplicates the newest job wins, matching the
+-- lookup enqueue used before this table existed.
+INSERT INTO job_idempotency_keys (user_id, type, idempotency_key, job_id, created_at)
+SELECT DISTINCT ON (user_id, type, idempotency_key)
+  user_id,
+  type,
+  idempotency_key,
+  id,
+  updated_at
+FROM jobs
+ORDER BY user_id, type, idempotency_key, (state IN ('PENDING', 'RUNNING')) DESC, updated_at DESC
+ON CONFLICT (user_id, type, idempotency_key) DO NOTHING;
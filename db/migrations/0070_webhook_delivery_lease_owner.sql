@@ -0,0 +1,4 @@
+-- Deliveries record the worker holding their lease, so an attempt that outlives its lease
+-- cannot settle a row another worker has since reclaimed.
+ALTER TABLE webhook_deliveries
+  ADD COLUMN IF NOT EXISTS lease_owner UUID NULL;
@@ -0,0 +1,6 @@
+ALTER TABLE automation_rules
+  DROP CONSTRAINT IF EXISTS automation_rules_template_check;
+
+ALTER TABLE automation_rules
+  ADD CONSTRAINT automation_rules_template_check
+  CHECK (template IS NULL OR template IN ('WEEKLY_REVIEW', 'MORNING_BRIEF'));